@@ -0,0 +1,167 @@
+use std::{env, fmt, str::FromStr, time::Duration};
+
+use crate::errors::AppError;
+
+/// Contra qué se mide: el protocolo de línea del master o el gateway HTTP del cliente.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Native,
+    Http,
+}
+
+impl Target {
+    fn default_addr(self) -> &'static str {
+        match self {
+            Target::Native => "127.0.0.1:5555",
+            Target::Http => "127.0.0.1:3000",
+        }
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Target::Native => "native",
+            Target::Http => "http",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchConfig {
+    pub target: Target,
+    pub addr: String,
+    /// Claves distintas (`bench:0` .. `bench:<keys-1>`).
+    pub keys: usize,
+    pub value_size: usize,
+    /// Exponente de la distribución zipf de las claves; 0 es uniforme.
+    pub zipf: f64,
+    /// Fracción (0..=1) de las operaciones que son `GET`.
+    pub read_ratio: f64,
+    /// Requests por segundo entre todos los workers; `None` manda lo más rápido posible.
+    pub qps: Option<u64>,
+    /// Workers, cada uno con un request en vuelo a la vez.
+    pub concurrency: usize,
+    /// Conexiones al master que comparten los workers (solo `native`; en `http` cada
+    /// worker usa la suya).
+    pub connections: usize,
+    pub duration: Duration,
+    pub timeout: Duration,
+    pub ttl: Option<Duration>,
+    /// Escribir cada clave una vez antes de medir, para que los `GET` encuentren algo.
+    pub preload: bool,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self {
+            target: Target::Native,
+            addr: Target::Native.default_addr().to_string(),
+            keys: 10_000,
+            value_size: 100,
+            zipf: 0.0,
+            read_ratio: 0.9,
+            qps: None,
+            concurrency: 32,
+            connections: 4,
+            duration: Duration::from_secs(30),
+            timeout: Duration::from_secs(5),
+            ttl: None,
+            preload: true,
+        }
+    }
+}
+
+impl BenchConfig {
+    /// `BENCH_TARGET` (`native` o `http`), `BENCH_ADDR` (`127.0.0.1:5555`, o
+    /// `127.0.0.1:3000` con `http`), `BENCH_KEYS` (10000), `BENCH_VALUE_SIZE` (100),
+    /// `BENCH_ZIPF` (0), `BENCH_READ_RATIO` (0.9), `BENCH_QPS` (0, sin límite),
+    /// `BENCH_CONCURRENCY` (32), `BENCH_CONNECTIONS` (4), `BENCH_DURATION_SECS` (30),
+    /// `BENCH_TIMEOUT_MS` (5000), `BENCH_TTL_MS` (sin TTL) y `BENCH_PRELOAD` (true).
+    pub fn from_env() -> Result<Self, AppError> {
+        let mut config = Self::default();
+        if let Some(target) = var("BENCH_TARGET") {
+            config.target = match target.as_str() {
+                "native" => Target::Native,
+                "http" => Target::Http,
+                other => return Err(invalid("BENCH_TARGET", other)),
+            };
+        }
+        config.addr = var("BENCH_ADDR").unwrap_or_else(|| config.target.default_addr().into());
+
+        if let Some(keys) = parse::<usize>("BENCH_KEYS")? {
+            config.keys = keys;
+        }
+        if let Some(size) = parse::<usize>("BENCH_VALUE_SIZE")? {
+            config.value_size = size;
+        }
+        if let Some(zipf) = parse::<f64>("BENCH_ZIPF")? {
+            config.zipf = zipf;
+        }
+        if let Some(ratio) = parse::<f64>("BENCH_READ_RATIO")? {
+            config.read_ratio = ratio;
+        }
+        if let Some(qps) = parse::<u64>("BENCH_QPS")? {
+            config.qps = (qps > 0).then_some(qps);
+        }
+        if let Some(concurrency) = parse::<usize>("BENCH_CONCURRENCY")? {
+            config.concurrency = concurrency;
+        }
+        if let Some(connections) = parse::<usize>("BENCH_CONNECTIONS")? {
+            config.connections = connections;
+        }
+        if let Some(secs) = parse::<u64>("BENCH_DURATION_SECS")? {
+            config.duration = Duration::from_secs(secs);
+        }
+        if let Some(ms) = parse::<u64>("BENCH_TIMEOUT_MS")? {
+            config.timeout = Duration::from_millis(ms);
+        }
+        if let Some(ms) = parse::<u64>("BENCH_TTL_MS")? {
+            config.ttl = (ms > 0).then(|| Duration::from_millis(ms));
+        }
+        if let Some(preload) = parse::<bool>("BENCH_PRELOAD")? {
+            config.preload = preload;
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.keys == 0 {
+            return Err(invalid("BENCH_KEYS", "0"));
+        }
+        if !(self.zipf.is_finite() && self.zipf >= 0.0) {
+            return Err(invalid("BENCH_ZIPF", &self.zipf.to_string()));
+        }
+        if !(0.0..=1.0).contains(&self.read_ratio) {
+            return Err(invalid("BENCH_READ_RATIO", &self.read_ratio.to_string()));
+        }
+        if self.concurrency == 0 {
+            return Err(invalid("BENCH_CONCURRENCY", "0"));
+        }
+        if self.connections == 0 {
+            return Err(invalid("BENCH_CONNECTIONS", "0"));
+        }
+        if self.duration.is_zero() {
+            return Err(invalid("BENCH_DURATION_SECS", "0"));
+        }
+        Ok(())
+    }
+}
+
+fn var(name: &str) -> Option<String> {
+    env::var(name)
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn parse<T: FromStr>(name: &str) -> Result<Option<T>, AppError> {
+    var(name)
+        .map(|v| v.parse::<T>().map_err(|_| invalid(name, &v)))
+        .transpose()
+}
+
+fn invalid(name: &str, value: &str) -> AppError {
+    AppError::Config(format!("{name}={value}"))
+}
@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum AppError {
+    #[error("Configuración inválida: {0}")]
+    Config(String),
+
+    #[error("Error de conexión: {0}")]
+    ConnectionError(String),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
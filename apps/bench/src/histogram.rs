@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+/// Debajo de esto cada microsegundo tiene su bucket.
+const LINEAR: u64 = 128;
+/// Buckets por cada potencia de dos por encima de `LINEAR`: ~1.5% de error.
+const SUB_BUCKETS: u64 = 64;
+const SUB_BITS: u32 = SUB_BUCKETS.trailing_zeros();
+
+/// Histograma de latencias en microsegundos, log-lineal como un HDR: los buckets se
+/// ensanchan con el valor, así que un cuantil sale con error relativo acotado en vez de
+/// caer en los buckets fijos (en ms) de `LatencyHistogram`. Cada worker tiene el suyo y
+/// se juntan al final.
+#[derive(Debug, Clone, Default)]
+pub struct Histogram {
+    counts: Vec<u64>,
+    total: u64,
+    sum_micros: u64,
+    max_micros: u64,
+}
+
+impl Histogram {
+    pub fn record(&mut self, elapsed: Duration) {
+        let micros = elapsed.as_micros().min(u64::MAX as u128) as u64;
+        let idx = bucket(micros);
+        if idx >= self.counts.len() {
+            self.counts.resize(idx + 1, 0);
+        }
+        self.counts[idx] += 1;
+        self.total += 1;
+        self.sum_micros = self.sum_micros.saturating_add(micros);
+        self.max_micros = self.max_micros.max(micros);
+    }
+
+    pub fn merge(&mut self, other: &Histogram) {
+        if other.counts.len() > self.counts.len() {
+            self.counts.resize(other.counts.len(), 0);
+        }
+        for (mine, theirs) in self.counts.iter_mut().zip(&other.counts) {
+            *mine += theirs;
+        }
+        self.total += other.total;
+        self.sum_micros = self.sum_micros.saturating_add(other.sum_micros);
+        self.max_micros = self.max_micros.max(other.max_micros);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.total
+    }
+
+    pub fn max_micros(&self) -> u64 {
+        self.max_micros
+    }
+
+    pub fn mean_micros(&self) -> Option<u64> {
+        (self.total > 0).then(|| self.sum_micros / self.total)
+    }
+
+    /// Límite superior (µs) del bucket del cuantil `q`, sin pasarse del máximo visto.
+    pub fn quantile_micros(&self, q: f64) -> Option<u64> {
+        if self.total == 0 {
+            return None;
+        }
+        let target = ((self.total as f64) * q.clamp(0.0, 1.0)).ceil().max(1.0) as u64;
+        let mut acc = 0;
+        for (idx, count) in self.counts.iter().enumerate() {
+            acc += count;
+            if acc >= target {
+                return Some(upper_bound(idx).min(self.max_micros));
+            }
+        }
+        Some(self.max_micros)
+    }
+}
+
+fn bucket(micros: u64) -> usize {
+    if micros < LINEAR {
+        return micros as usize;
+    }
+    // `micros` tiene `log2 + 1` bits; se quedan los `SUB_BITS + 1` más altos
+    let log2 = 63 - micros.leading_zeros();
+    let shift = log2 - SUB_BITS;
+    let mantissa = micros >> shift;
+    let first = LINEAR.trailing_zeros();
+    (LINEAR + (log2 - first) as u64 * SUB_BUCKETS + (mantissa - SUB_BUCKETS)) as usize
+}
+
+fn upper_bound(idx: usize) -> u64 {
+    let idx = idx as u64;
+    if idx < LINEAR {
+        return idx;
+    }
+    let first = LINEAR.trailing_zeros() as u64;
+    let log2 = (idx - LINEAR) / SUB_BUCKETS + first;
+    let mantissa = (idx - LINEAR) % SUB_BUCKETS + SUB_BUCKETS;
+    let shift = log2 - SUB_BITS as u64;
+    let upper = ((mantissa as u128 + 1) << shift) - 1;
+    upper.min(u64::MAX as u128) as u64
+}
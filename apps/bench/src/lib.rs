@@ -0,0 +1,7 @@
+pub mod config;
+pub mod errors;
+pub mod histogram;
+pub mod runner;
+pub mod target;
+pub mod tests;
+pub mod workload;
@@ -0,0 +1,25 @@
+use cache_bench::{config::BenchConfig, errors::AppError, runner};
+use dotenvy::{dotenv, from_filename};
+use tracing::info;
+
+fn load_env_for_workspace() {
+    let _ = from_filename(concat!(env!("CARGO_MANIFEST_DIR"), "/.env"));
+    let _ = from_filename(".env");
+}
+
+#[tokio::main]
+async fn main() -> Result<(), AppError> {
+    dotenv().ok();
+
+    load_env_for_workspace();
+
+    app_logging::init_logging();
+
+    let config = BenchConfig::from_env()?;
+    info!(target = %config.target, addr = %config.addr, "arrancando la carga");
+
+    let report = runner::run(config).await?;
+    println!("{report}");
+
+    Ok(())
+}
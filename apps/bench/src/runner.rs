@@ -0,0 +1,264 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use fastrand::Rng;
+use tokio::{task::JoinSet, time};
+use tracing::info;
+
+use crate::{
+    config::{BenchConfig, Target},
+    errors::AppError,
+    histogram::Histogram,
+    target::{Connection, HttpConnection, NativeConnection, Outcome},
+    workload::{Op, Workload},
+};
+
+/// Resultados de una operación (`GET` o `PUT`).
+#[derive(Debug, Clone, Default)]
+pub struct OpStats {
+    /// Latencias de las respuestas (`ok` y `miss`); los errores solo se cuentan.
+    pub latency: Histogram,
+    pub ok: u64,
+    pub miss: u64,
+    /// Por etiqueta del error (código, `timeout` o `connection`).
+    pub errors: BTreeMap<String, u64>,
+}
+
+impl OpStats {
+    pub fn record(&mut self, outcome: Outcome, elapsed: Duration) {
+        match outcome {
+            Outcome::Ok => self.ok += 1,
+            Outcome::Miss => self.miss += 1,
+            Outcome::Error(label) => {
+                *self.errors.entry(label).or_default() += 1;
+                return;
+            }
+        }
+        self.latency.record(elapsed);
+    }
+
+    pub fn merge(&mut self, other: &OpStats) {
+        self.latency.merge(&other.latency);
+        self.ok += other.ok;
+        self.miss += other.miss;
+        for (label, count) in &other.errors {
+            *self.errors.entry(label.clone()).or_default() += count;
+        }
+    }
+
+    pub fn ops(&self) -> u64 {
+        self.ok + self.miss + self.error_count()
+    }
+
+    pub fn error_count(&self) -> u64 {
+        self.errors.values().sum()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct WorkerStats {
+    get: OpStats,
+    put: OpStats,
+}
+
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub config: BenchConfig,
+    pub elapsed: Duration,
+    pub get: OpStats,
+    pub put: OpStats,
+}
+
+impl Report {
+    pub fn throughput(&self) -> f64 {
+        (self.get.ops() + self.put.ops()) as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+/// Precarga (si corresponde) y corre la carga de `config` durante `config.duration`.
+pub async fn run(config: BenchConfig) -> Result<Report, AppError> {
+    let workload = Arc::new(Workload::new(&config));
+    let mut connections = open_connections(&config).await?;
+
+    if config.preload {
+        let started = Instant::now();
+        let stats = preload(&config, &workload, &mut connections).await;
+        info!(
+            keys = config.keys,
+            errors = stats.error_count(),
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            "precarga terminada"
+        );
+    }
+
+    let started = Instant::now();
+    let deadline = started + config.duration;
+    let mut set = JoinSet::new();
+    for (worker, mut connection) in connections.into_iter().enumerate() {
+        let (config, workload) = (config.clone(), workload.clone());
+        set.spawn(async move {
+            let mut rng = Rng::with_seed(worker as u64);
+            let mut stats = WorkerStats::default();
+            match pacing(&config, worker) {
+                Some((first, interval)) => {
+                    // lazo abierto: cada request tiene su hora y la latencia se mide desde
+                    // ahí, así un servidor lento no baja la carga ni esconde la espera
+                    let mut scheduled = started + first;
+                    while scheduled < deadline {
+                        time::sleep_until(scheduled.into()).await;
+                        let op = workload.next_op(&mut rng);
+                        let outcome = connection.run(op, &workload, config.ttl).await;
+                        stats.record(op, outcome, scheduled.elapsed());
+                        scheduled += interval;
+                    }
+                }
+                None => {
+                    while Instant::now() < deadline {
+                        let op = workload.next_op(&mut rng);
+                        let sent = Instant::now();
+                        let outcome = connection.run(op, &workload, config.ttl).await;
+                        stats.record(op, outcome, sent.elapsed());
+                    }
+                }
+            }
+            stats
+        });
+    }
+
+    let mut total = WorkerStats::default();
+    while let Some(stats) = set.join_next().await {
+        let stats = stats.map_err(|e| AppError::ConnectionError(e.to_string()))?;
+        total.get.merge(&stats.get);
+        total.put.merge(&stats.put);
+    }
+
+    Ok(Report {
+        elapsed: started.elapsed(),
+        config,
+        get: total.get,
+        put: total.put,
+    })
+}
+
+impl WorkerStats {
+    fn record(&mut self, op: Op, outcome: Outcome, elapsed: Duration) {
+        match op {
+            Op::Get(_) => self.get.record(outcome, elapsed),
+            Op::Put(_) => self.put.record(outcome, elapsed),
+        }
+    }
+}
+
+/// Una conexión por worker; en `native` los workers se reparten `connections`.
+async fn open_connections(config: &BenchConfig) -> Result<Vec<Connection>, AppError> {
+    match config.target {
+        Target::Native => {
+            let mut shared = Vec::with_capacity(config.connections);
+            for _ in 0..config.connections {
+                let conn = NativeConnection::connect(&config.addr, config.timeout).await?;
+                shared.push(Arc::new(conn));
+            }
+            Ok((0..config.concurrency)
+                .map(|worker| Connection::Native(shared[worker % shared.len()].clone()))
+                .collect())
+        }
+        Target::Http => {
+            let mut connections = Vec::with_capacity(config.concurrency);
+            for _ in 0..config.concurrency {
+                let mut conn = HttpConnection::new(&config.addr, config.timeout);
+                conn.connect().await?;
+                connections.push(Connection::Http(conn));
+            }
+            Ok(connections)
+        }
+    }
+}
+
+/// Escribe cada clave una vez, repartidas entre los workers, sin medir.
+async fn preload(
+    config: &BenchConfig,
+    workload: &Arc<Workload>,
+    connections: &mut Vec<Connection>,
+) -> OpStats {
+    let workers = connections.len();
+    let mut set = JoinSet::new();
+    for (worker, mut connection) in std::mem::take(connections).into_iter().enumerate() {
+        let (workload, keys, ttl) = (workload.clone(), config.keys, config.ttl);
+        set.spawn(async move {
+            let mut stats = OpStats::default();
+            for idx in (worker..keys).step_by(workers) {
+                let outcome = connection.run(Op::Put(idx), &workload, ttl).await;
+                stats.record(outcome, Duration::ZERO);
+            }
+            (worker, connection, stats)
+        });
+    }
+
+    let mut returned = Vec::with_capacity(workers);
+    let mut total = OpStats::default();
+    while let Some(Ok((worker, connection, stats))) = set.join_next().await {
+        total.merge(&stats);
+        returned.push((worker, connection));
+    }
+    returned.sort_by_key(|(worker, _)| *worker);
+    connections.extend(returned.into_iter().map(|(_, connection)| connection));
+    total
+}
+
+/// Con `qps`, cuándo manda su primer request `worker` y cada cuánto los siguientes. Los
+/// workers arrancan escalonados para no mandar todos a la vez.
+pub fn pacing(config: &BenchConfig, worker: usize) -> Option<(Duration, Duration)> {
+    let qps = config.qps?;
+    let interval = Duration::from_secs_f64(config.concurrency as f64 / qps as f64);
+    let first = interval.mul_f64(worker as f64 / config.concurrency as f64);
+    Some((first, interval))
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let config = &self.config;
+        let qps = config
+            .qps
+            .map_or_else(|| "max".to_string(), |qps| qps.to_string());
+        writeln!(
+            f,
+            "target={} addr={} keys={} value_size={} zipf={} read_ratio={} qps={qps} concurrency={} duration={:.1}s",
+            config.target,
+            config.addr,
+            config.keys,
+            config.value_size,
+            config.zipf,
+            config.read_ratio,
+            config.concurrency,
+            self.elapsed.as_secs_f64(),
+        )?;
+        for (name, stats) in [("GET", &self.get), ("PUT", &self.put)] {
+            write!(
+                f,
+                "{name} ops={} ok={} miss={} errors={}",
+                stats.ops(),
+                stats.ok,
+                stats.miss,
+                stats.error_count()
+            )?;
+            let latency = &stats.latency;
+            for (label, q) in [("p50", 0.5), ("p90", 0.9), ("p99", 0.99), ("p999", 0.999)] {
+                if let Some(micros) = latency.quantile_micros(q) {
+                    write!(f, " {label}={micros}us")?;
+                }
+            }
+            if let Some(mean) = latency.mean_micros() {
+                write!(f, " max={}us mean={mean}us", latency.max_micros())?;
+            }
+            for (label, count) in &stats.errors {
+                write!(f, " {label}={count}")?;
+            }
+            writeln!(f)?;
+        }
+        write!(f, "throughput={:.0}/s", self.throughput())
+    }
+}
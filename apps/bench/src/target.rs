@@ -0,0 +1,316 @@
+use std::{io, net::SocketAddr, sync::Arc, time::Duration};
+
+use app_core::{error::ErrorKind, id::new_sortable_id};
+use app_net::{
+    ParsedMsg, RequestDataInput, ResponseData, Socket, SocketError, encode_args, encode_token,
+    format_millis, parse_line,
+};
+use bytes::Bytes;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::TcpStream,
+    sync::mpsc,
+    task::JoinHandle,
+    time,
+};
+
+use crate::{
+    errors::AppError,
+    workload::{Op, Workload, key},
+};
+
+/// Cómo terminó un request. Los errores llevan una etiqueta para agruparlos en el reporte:
+/// el código de respuesta, `timeout` o `connection`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Ok,
+    /// `GET` de una clave que no está: un `200` vacío.
+    Miss,
+    Error(String),
+}
+
+/// Una conexión de un worker, a cualquiera de los dos targets.
+pub enum Connection {
+    /// Compartida: el protocolo multiplexa los requests por id.
+    Native(Arc<NativeConnection>),
+    Http(HttpConnection),
+}
+
+impl Connection {
+    pub async fn run(&mut self, op: Op, workload: &Workload, ttl: Option<Duration>) -> Outcome {
+        match (self, op) {
+            (Connection::Native(conn), Op::Get(idx)) => conn.get(&key(idx)).await,
+            (Connection::Native(conn), Op::Put(idx)) => {
+                conn.put(&key(idx), workload.value(), ttl).await
+            }
+            (Connection::Http(conn), Op::Get(idx)) => conn.get(&key(idx)).await,
+            (Connection::Http(conn), Op::Put(idx)) => {
+                conn.put(&key(idx), workload.value(), ttl).await
+            }
+        }
+    }
+}
+
+/// Conexión al master por el protocolo de línea, como un cliente más. No se reconecta:
+/// si el master la corta, lo que sigue cuenta como `connection`.
+pub struct NativeConnection {
+    socket: Arc<Socket>,
+    tasks: [JoinHandle<()>; 2],
+}
+
+impl NativeConnection {
+    pub async fn connect(addr: &str, timeout: Duration) -> Result<Self, AppError> {
+        let stream = TcpStream::connect(addr)
+            .await
+            .map_err(|e| AppError::ConnectionError(format!("{addr}: {e}")))?;
+        stream.set_nodelay(true)?;
+        let (reader, mut writer) = stream.into_split();
+
+        let id = new_sortable_id();
+        let (tx, mut rx) = mpsc::unbounded_channel::<Bytes>();
+        let socket = Arc::new(Socket::new(id.clone(), tx, timeout));
+
+        let writer_task = tokio::spawn(async move {
+            while let Some(bytes) = rx.recv().await {
+                if writer.write_all(&bytes).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let reader_socket = socket.clone();
+        let reader_task = tokio::spawn(async move {
+            let mut br = BufReader::new(reader);
+            let mut line = String::new();
+            loop {
+                line.clear();
+                match br.read_line(&mut line).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(_) => {}
+                }
+                if let Ok(ParsedMsg::Res { id, raw_response }) = parse_line(&line) {
+                    reader_socket.handle_response(id, raw_response.to_string());
+                }
+            }
+        });
+
+        // la primera línea es el id con el que el master registra al cliente
+        socket
+            .send_raw(Bytes::from(format!("{id}\n")))
+            .map_err(|e| AppError::ConnectionError(e.to_string()))?;
+
+        Ok(Self {
+            socket,
+            tasks: [writer_task, reader_task],
+        })
+    }
+
+    pub async fn get(&self, key: &str) -> Outcome {
+        let response = self
+            .socket
+            .request(RequestDataInput::new("GET", &encode_token(key)))
+            .await;
+        native_outcome(response, true)
+    }
+
+    pub async fn put(&self, key: &str, value: &str, ttl: Option<Duration>) -> Outcome {
+        let ttl = ttl.map(|ttl| format_millis(ttl.as_millis() as u64));
+        let payload = encode_args([key, value].into_iter().chain(ttl.as_deref()));
+        let response = self
+            .socket
+            .request(RequestDataInput::new("PUT", &payload))
+            .await;
+        native_outcome(response, false)
+    }
+}
+
+impl Drop for NativeConnection {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+fn native_outcome(response: Result<ResponseData, SocketError>, read: bool) -> Outcome {
+    match response {
+        Ok(response) if response.is_success() => {
+            if read && (response.is_empty_value() || response.payload.is_empty()) {
+                Outcome::Miss
+            } else {
+                Outcome::Ok
+            }
+        }
+        Ok(response) if response.error_kind() == Some(ErrorKind::NotFound) => Outcome::Miss,
+        Ok(response) => Outcome::Error(response.code.to_string()),
+        Err(SocketError::Timeout { .. }) => Outcome::Error("timeout".into()),
+        Err(_) => Outcome::Error("connection".into()),
+    }
+}
+
+/// Conexión keep-alive al gateway HTTP, un request a la vez. Habla lo justo de HTTP/1.1
+/// para `GET`/`PUT /kv/<clave>`; si la conexión falla se abre otra en el request
+/// siguiente.
+pub struct HttpConnection {
+    addr: String,
+    timeout: Duration,
+    stream: Option<BufReader<TcpStream>>,
+    request: Vec<u8>,
+    body: Vec<u8>,
+}
+
+impl HttpConnection {
+    pub fn new(addr: &str, timeout: Duration) -> Self {
+        Self {
+            addr: addr.to_string(),
+            timeout,
+            stream: None,
+            request: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    /// Abre la conexión ya, para fallar antes de empezar a medir.
+    pub async fn connect(&mut self) -> Result<(), AppError> {
+        self.open()
+            .await
+            .map_err(|e| AppError::ConnectionError(format!("{}: {e}", self.addr)))
+    }
+
+    pub async fn get(&mut self, key: &str) -> Outcome {
+        match self.send("GET", key, None).await {
+            Ok(200) if self.body_has(br#""value":"""#) => Outcome::Miss,
+            Ok(200) => Outcome::Ok,
+            Ok(404) => Outcome::Miss,
+            Ok(status) => Outcome::Error(status.to_string()),
+            Err(e) => io_outcome(&e),
+        }
+    }
+
+    pub async fn put(&mut self, key: &str, value: &str, ttl: Option<Duration>) -> Outcome {
+        // la clave y el valor son alfanuméricos: no hace falta escapar nada
+        let body = match ttl {
+            Some(ttl) => format!(r#"{{"value":"{value}","ttl_ms":{}}}"#, ttl.as_millis()),
+            None => format!(r#"{{"value":"{value}"}}"#),
+        };
+        match self.send("PUT", key, Some(&body)).await {
+            Ok(status) if (200..300).contains(&status) => Outcome::Ok,
+            Ok(status) => Outcome::Error(status.to_string()),
+            Err(e) => io_outcome(&e),
+        }
+    }
+
+    fn body_has(&self, needle: &[u8]) -> bool {
+        self.body.windows(needle.len()).any(|w| w == needle)
+    }
+
+    async fn send(&mut self, method: &str, key: &str, body: Option<&str>) -> io::Result<u16> {
+        let result = time::timeout(self.timeout, self.exchange(method, key, body))
+            .await
+            .unwrap_or_else(|_| Err(io::ErrorKind::TimedOut.into()));
+        if result.is_err() {
+            // a medio leer o sin contestar: la conexión ya no sirve
+            self.stream = None;
+        }
+        result
+    }
+
+    async fn exchange(&mut self, method: &str, key: &str, body: Option<&str>) -> io::Result<u16> {
+        if self.stream.is_none() {
+            self.open().await?;
+        }
+        self.request.clear();
+        self.request.extend_from_slice(
+            format!("{method} /kv/{key} HTTP/1.1\r\nHost: {}\r\n", self.addr).as_bytes(),
+        );
+        if let Some(body) = body {
+            self.request.extend_from_slice(
+                format!(
+                    "Content-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+                    body.len()
+                )
+                .as_bytes(),
+            );
+        } else {
+            self.request.extend_from_slice(b"\r\n");
+        }
+
+        let Some(stream) = self.stream.as_mut() else {
+            return Err(io::ErrorKind::NotConnected.into());
+        };
+        stream.get_mut().write_all(&self.request).await?;
+
+        let mut line = String::new();
+        read_line(stream, &mut line).await?;
+        let status = line
+            .split_whitespace()
+            .nth(1)
+            .and_then(|code| code.parse::<u16>().ok())
+            .ok_or_else(|| invalid_data(format!("línea de estado inválida: {line:?}")))?;
+
+        let mut content_length = 0;
+        let mut close = false;
+        loop {
+            read_line(stream, &mut line).await?;
+            let header = line.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            let Some((name, value)) = header.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value
+                    .parse::<usize>()
+                    .map_err(|_| invalid_data(format!("Content-Length inválido: {value}")))?;
+            } else if name.eq_ignore_ascii_case("transfer-encoding") {
+                return Err(invalid_data(format!(
+                    "Transfer-Encoding no soportado: {value}"
+                )));
+            } else if name.eq_ignore_ascii_case("connection") {
+                close = value.eq_ignore_ascii_case("close");
+            }
+        }
+
+        self.body.resize(content_length, 0);
+        stream.read_exact(&mut self.body).await?;
+        if close {
+            self.stream = None;
+        }
+        Ok(status)
+    }
+
+    async fn open(&mut self) -> io::Result<()> {
+        let addr: SocketAddr = match self.addr.parse() {
+            Ok(addr) => addr,
+            Err(_) => tokio::net::lookup_host(&self.addr)
+                .await?
+                .next()
+                .ok_or_else(|| io::Error::from(io::ErrorKind::AddrNotAvailable))?,
+        };
+        let stream = TcpStream::connect(addr).await?;
+        stream.set_nodelay(true)?;
+        self.stream = Some(BufReader::new(stream));
+        Ok(())
+    }
+}
+
+async fn read_line(stream: &mut BufReader<TcpStream>, line: &mut String) -> io::Result<()> {
+    line.clear();
+    if stream.read_line(line).await? == 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(())
+}
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn io_outcome(e: &io::Error) -> Outcome {
+    match e.kind() {
+        io::ErrorKind::TimedOut => Outcome::Error("timeout".into()),
+        _ => Outcome::Error("connection".into()),
+    }
+}
@@ -0,0 +1,43 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::histogram::Histogram;
+
+    fn micros(values: impl IntoIterator<Item = u64>) -> Histogram {
+        let mut histogram = Histogram::default();
+        for v in values {
+            histogram.record(Duration::from_micros(v));
+        }
+        histogram
+    }
+
+    #[test]
+    fn small_latencies_are_exact() {
+        let histogram = micros(1..=100);
+        assert_eq!(histogram.quantile_micros(0.5), Some(50));
+        assert_eq!(histogram.quantile_micros(0.99), Some(99));
+        assert_eq!(histogram.quantile_micros(1.0), Some(100));
+        assert_eq!(histogram.mean_micros(), Some(50));
+    }
+
+    #[test]
+    fn large_latencies_stay_within_two_percent() {
+        for v in [150, 1_000, 12_345, 1_000_000, 7_654_321] {
+            let mut histogram = micros([v]);
+            histogram.record(Duration::from_secs(60));
+            let p50 = histogram.quantile_micros(0.5).unwrap();
+            assert!(p50 >= v && p50 <= v + v / 50, "{v} -> {p50}");
+        }
+    }
+
+    #[test]
+    fn merging_adds_the_samples_of_every_worker() {
+        let mut total = micros([10, 20]);
+        total.merge(&micros([5_000, 30]));
+        assert_eq!(total.count(), 4);
+        assert_eq!(total.max_micros(), 5_000);
+        assert_eq!(total.quantile_micros(0.5), Some(20));
+        assert!(Histogram::default().quantile_micros(0.5).is_none());
+    }
+}
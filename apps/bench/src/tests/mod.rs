@@ -0,0 +1,3 @@
+mod histogram_test;
+mod runner_test;
+mod workload_test;
@@ -0,0 +1,44 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use cluster_harness::TestCluster;
+
+    use crate::{
+        config::{BenchConfig, Target},
+        runner,
+    };
+
+    #[tokio::test]
+    async fn a_short_run_against_the_cluster_reports_every_operation() {
+        let cluster = TestCluster::start(2).await;
+        let config = BenchConfig {
+            target: Target::Native,
+            addr: cluster.master_addr().to_string(),
+            keys: 200,
+            zipf: 0.99,
+            read_ratio: 0.5,
+            qps: Some(400),
+            concurrency: 4,
+            connections: 2,
+            duration: Duration::from_millis(500),
+            ..BenchConfig::default()
+        };
+
+        let report = runner::run(config).await.unwrap();
+
+        assert!(report.get.ops() > 0 && report.put.ops() > 0);
+        assert_eq!(report.get.error_count() + report.put.error_count(), 0);
+        assert_eq!(report.get.miss, 0, "la precarga escribió todas las claves");
+        // 400/s durante medio segundo, con margen para el arranque escalonado
+        let ops = report.get.ops() + report.put.ops();
+        assert!((150..=210).contains(&ops), "{ops}");
+        let text = report.to_string();
+        assert!(
+            text.contains("GET ops=") && text.contains(" p99="),
+            "{text}"
+        );
+
+        cluster.shutdown().await;
+    }
+}
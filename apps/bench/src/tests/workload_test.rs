@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use fastrand::Rng;
+
+    use crate::{
+        config::BenchConfig,
+        runner::pacing,
+        workload::{KeySampler, Op, Workload},
+    };
+
+    fn hits(sampler: &KeySampler, n: usize, samples: usize) -> Vec<usize> {
+        let mut rng = Rng::with_seed(7);
+        let mut hits = vec![0; n];
+        for _ in 0..samples {
+            hits[sampler.sample(&mut rng)] += 1;
+        }
+        hits
+    }
+
+    #[test]
+    fn zipf_favours_the_first_keys() {
+        let hits = hits(&KeySampler::new(1_000, 1.0), 1_000, 100_000);
+        // con s = 1 la clave 0 se lleva ~1/H(1000) ≈ 13% y la 1 la mitad de eso
+        assert!((12_000..15_000).contains(&hits[0]), "{}", hits[0]);
+        assert!((5_500..7_500).contains(&hits[1]), "{}", hits[1]);
+        assert!(hits[999] < 100);
+    }
+
+    #[test]
+    fn zero_skew_spreads_the_keys_evenly() {
+        let hits = hits(&KeySampler::new(10, 0.0), 10, 100_000);
+        assert!(hits.iter().all(|h| (9_000..11_000).contains(h)), "{hits:?}");
+    }
+
+    #[test]
+    fn the_read_ratio_splits_gets_and_puts() {
+        let workload = Workload::new(&BenchConfig {
+            read_ratio: 0.75,
+            value_size: 16,
+            ..BenchConfig::default()
+        });
+        assert_eq!(workload.value().len(), 16);
+
+        let mut rng = Rng::with_seed(1);
+        let gets = (0..10_000)
+            .filter(|_| matches!(workload.next_op(&mut rng), Op::Get(_)))
+            .count();
+        assert!((7_200..7_800).contains(&gets), "{gets}");
+    }
+
+    #[test]
+    fn a_target_qps_is_spread_across_the_workers() {
+        let config = BenchConfig {
+            qps: Some(1_000),
+            concurrency: 4,
+            ..BenchConfig::default()
+        };
+        assert_eq!(
+            pacing(&config, 0),
+            Some((Duration::ZERO, Duration::from_millis(4)))
+        );
+        assert_eq!(
+            pacing(&config, 2),
+            Some((Duration::from_millis(2), Duration::from_millis(4)))
+        );
+        assert_eq!(pacing(&BenchConfig::default(), 0), None);
+    }
+}
@@ -0,0 +1,83 @@
+use std::sync::Arc;
+
+use fastrand::Rng;
+
+use crate::config::BenchConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Get(usize),
+    Put(usize),
+}
+
+/// Elige claves entre `0..n` con probabilidad proporcional a `1 / (rank + 1)^s`: la 0 es
+/// la más pedida y con `s = 0` todas por igual.
+pub struct KeySampler {
+    /// Acumulada normalizada; vacía si es uniforme.
+    cdf: Vec<f64>,
+    n: usize,
+}
+
+impl KeySampler {
+    pub fn new(n: usize, s: f64) -> Self {
+        if s == 0.0 {
+            return Self { cdf: Vec::new(), n };
+        }
+        let mut acc = 0.0;
+        let mut cdf: Vec<f64> = (0..n)
+            .map(|rank| {
+                acc += 1.0 / ((rank + 1) as f64).powf(s);
+                acc
+            })
+            .collect();
+        for p in &mut cdf {
+            *p /= acc;
+        }
+        Self { cdf, n }
+    }
+
+    pub fn sample(&self, rng: &mut Rng) -> usize {
+        if self.cdf.is_empty() {
+            return rng.usize(..self.n);
+        }
+        let u = rng.f64();
+        self.cdf.partition_point(|p| *p < u).min(self.n - 1)
+    }
+}
+
+/// Lo que hace cada worker: qué operación, sobre qué clave y con qué valor.
+pub struct Workload {
+    sampler: KeySampler,
+    read_ratio: f64,
+    value: Arc<str>,
+}
+
+impl Workload {
+    pub fn new(config: &BenchConfig) -> Self {
+        let mut rng = Rng::with_seed(config.keys as u64);
+        let value: String = (0..config.value_size).map(|_| rng.alphanumeric()).collect();
+        Self {
+            sampler: KeySampler::new(config.keys, config.zipf),
+            read_ratio: config.read_ratio,
+            value: value.into(),
+        }
+    }
+
+    pub fn next_op(&self, rng: &mut Rng) -> Op {
+        let key = self.sampler.sample(rng);
+        if rng.f64() < self.read_ratio {
+            Op::Get(key)
+        } else {
+            Op::Put(key)
+        }
+    }
+
+    /// Alfanumérico, así viaja igual en el protocolo y en el JSON del gateway.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+pub fn key(idx: usize) -> String {
+    format!("bench:{idx}")
+}
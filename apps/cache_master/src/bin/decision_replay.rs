@@ -0,0 +1,58 @@
+//! Rearma el anillo del master a partir de su `DECISION_LOG`:
+//!
+//! ```text
+//! decision_replay <archivo> <ms> [<clave>]
+//! ```
+//!
+//! Muestra los shards del anillo en `<ms>` (hora del master) y, con una clave, a quién le
+//! tocaba entonces y cada ruta anotada para su hash, marcando las que no coinciden con el
+//! anillo de ese momento.
+
+use std::{env, fs, process::ExitCode};
+
+use cache_master::{
+    core::domain::services::ConsistentHasherService, infrastructure::decision_log::DecisionReplay,
+};
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (Some(path), Some(at_ms)) = (args.first(), args.get(1).and_then(|ms| ms.parse().ok()))
+    else {
+        eprintln!("uso: decision_replay <archivo> <ms> [<clave>]");
+        return ExitCode::from(2);
+    };
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("{path}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let replay = DecisionReplay::parse(&text);
+    let ring = replay.ring_at(at_ms);
+    for line in replay.skipped() {
+        eprintln!("{path}:{line}: línea ilegible, se saltea");
+    }
+    println!("anillo en {at_ms}: {}", replay.shards_at(at_ms).join(" "));
+
+    if let Some(key) = args.get(2) {
+        let hash = ring.create_hash(key);
+        let owner = ring.get_node_id_from_hash(&hash);
+        println!("{key} -> {hash} -> {}", owner.as_deref().unwrap_or("-"));
+        for check in replay.routes_of(&hash) {
+            let verdict = if check.matches() {
+                "ok".to_string()
+            } else {
+                format!("DISTINTO, el anillo daba {}", check.expected.join(" "))
+            };
+            println!(
+                "  {} epoch={} -> {} ({verdict})",
+                check.at_ms,
+                check.epoch,
+                check.nodes.join(" ")
+            );
+        }
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,152 @@
+use std::{fmt, sync::Arc};
+
+use app_net::tokenize;
+
+use crate::core::domain::models::AppError;
+
+/// Un shard dentro de un backup: quién lo contestó y hasta dónde llegaba su log de
+/// replicación cuando armó la foto (ver `app_net::SnapshotHeader`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShardBackup {
+    pub shard: Arc<str>,
+    pub node: Arc<str>,
+    pub epoch: String,
+    pub seq: u64,
+    pub entries: u64,
+    /// Tamaño del archivo del shard.
+    pub bytes: u64,
+}
+
+/// Lo que describe un backup; se guarda como `manifest` junto a un `<shard>.snap` por shard.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BackupManifest {
+    /// Hora UTC del master al empezar (`20240131T235959Z`); ordena los backups.
+    pub id: String,
+    pub created_at_ms: u64,
+    /// Ordenados por shard.
+    pub shards: Vec<ShardBackup>,
+    /// Shards que no contestaron el `SNAPSHOT`: el backup no los tiene.
+    pub unreachable: Vec<Arc<str>>,
+}
+
+impl BackupManifest {
+    pub fn is_complete(&self) -> bool {
+        self.unreachable.is_empty()
+    }
+
+    pub fn entries(&self) -> u64 {
+        self.shards.iter().map(|s| s.entries).sum()
+    }
+
+    /// Archivo de `shard` dentro del backup.
+    pub fn shard_file(shard: &str) -> String {
+        format!("{shard}.snap")
+    }
+
+    /// `id=.. created_at=.. entries=.. shards=.. complete=..` y después `<shard> node=..
+    /// epoch=.. seq=.. entries=.. bytes=..` por shard (`<shard> unreachable` si no contestó).
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "id={} created_at={} entries={} shards={} complete={}",
+            self.id,
+            self.created_at_ms,
+            self.entries(),
+            self.shards.len() + self.unreachable.len(),
+            self.is_complete()
+        )];
+        lines.extend(self.shards.iter().map(|s| {
+            format!(
+                "{} node={} epoch={} seq={} entries={} bytes={}",
+                s.shard, s.node, s.epoch, s.seq, s.entries, s.bytes
+            )
+        }));
+        lines.extend(
+            self.unreachable
+                .iter()
+                .map(|shard| format!("{shard} unreachable")),
+        );
+        lines
+    }
+
+    /// Lee lo que escribió `lines`, una por renglón.
+    pub fn parse(text: &str) -> Result<Self, AppError> {
+        let bad = |line: &str| AppError::Storage(format!("manifest inválido: '{line}'"));
+        let mut lines = text.lines().filter(|l| !l.trim().is_empty());
+        let first = lines.next().ok_or_else(|| bad(""))?;
+
+        let mut manifest = BackupManifest::default();
+        for (field, value) in fields(first) {
+            match field.as_str() {
+                "id" => manifest.id = value,
+                "created_at" => manifest.created_at_ms = value.parse().map_err(|_| bad(first))?,
+                _ => {}
+            }
+        }
+        if manifest.id.is_empty() {
+            return Err(bad(first));
+        }
+
+        for line in lines {
+            let mut tokens = tokenize(line);
+            let shard: Arc<str> = Arc::from(tokens.next().ok_or_else(|| bad(line))?.as_ref());
+            let rest: Vec<_> = tokens.map(|t| t.into_owned()).collect();
+            if rest == ["unreachable"] {
+                manifest.unreachable.push(shard);
+                continue;
+            }
+
+            let mut backup = ShardBackup {
+                shard,
+                node: Arc::from(""),
+                epoch: String::new(),
+                seq: 0,
+                entries: 0,
+                bytes: 0,
+            };
+            for token in &rest {
+                let (field, value) = token.split_once('=').ok_or_else(|| bad(line))?;
+                let number = || value.parse::<u64>().map_err(|_| bad(line));
+                match field {
+                    "node" => backup.node = Arc::from(value),
+                    "epoch" => backup.epoch = value.to_string(),
+                    "seq" => backup.seq = number()?,
+                    "entries" => backup.entries = number()?,
+                    "bytes" => backup.bytes = number()?,
+                    _ => {}
+                }
+            }
+            manifest.shards.push(backup);
+        }
+
+        Ok(manifest)
+    }
+}
+
+fn fields(line: &str) -> impl Iterator<Item = (String, String)> + '_ {
+    tokenize(line).filter_map(|token| {
+        let (field, value) = token.split_once('=')?;
+        Some((field.to_string(), value.to_string()))
+    })
+}
+
+/// Resultado de restaurar un backup.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RestoreReport {
+    pub id: String,
+    pub restored: u64,
+    /// Entradas que vencieron desde el backup; no se escriben.
+    pub expired: u64,
+    /// `PUT` rechazados (solo lectura, memoria, sin nodo...) o líneas ilegibles.
+    pub failed: u64,
+    pub first_error: Option<String>,
+}
+
+impl fmt::Display for RestoreReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "id={} restored={} expired={} failed={}",
+            self.id, self.restored, self.expired, self.failed
+        )
+    }
+}
@@ -0,0 +1,67 @@
+use std::fmt;
+
+use app_net::key_hash;
+
+/// Con qué empieza el valor de una clave partida. Con los valores partidos habilitados,
+/// un `PUT` con un valor que empiece así se rechaza.
+pub const CHUNKED_PREFIX: &str = "#chunked:";
+
+/// Lo que queda en la clave cuando su valor se partió en pedazos (ver
+/// `PutKeyUseCase::with_chunk_size`): cada pedazo es una clave más,
+/// `<clave>#chunk:<id>:<i>`, que va a su shard como cualquier otra. `id` sale del valor,
+/// así los pedazos de una escritura no se mezclan con los de otra de la misma clave.
+///
+/// Se guarda como `#chunked:<id>:<pedazos>:<bytes>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkManifest {
+    pub id: String,
+    pub count: usize,
+    pub len: usize,
+}
+
+impl ChunkManifest {
+    /// El manifiesto de `value` partido en pedazos de hasta `chunk_size` bytes.
+    pub fn for_value(value: &str, chunk_size: usize) -> Self {
+        Self {
+            id: format!("{:016x}", key_hash(value)),
+            count: split_chunks(value, chunk_size).count(),
+            len: value.len(),
+        }
+    }
+
+    /// `None` si `value` no es un manifiesto.
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut fields = value.strip_prefix(CHUNKED_PREFIX)?.split(':');
+        let id = fields.next().filter(|id| !id.is_empty())?.to_string();
+        let count = fields.next()?.parse().ok()?;
+        let len = fields.next()?.parse().ok()?;
+        fields.next().is_none().then_some(Self { id, count, len })
+    }
+
+    pub fn chunk_key(&self, key: &str, index: usize) -> String {
+        format!("{key}#chunk:{}:{index}", self.id)
+    }
+}
+
+impl fmt::Display for ChunkManifest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{CHUNKED_PREFIX}{}:{}:{}", self.id, self.count, self.len)
+    }
+}
+
+/// Los pedazos de `value`, de hasta `chunk_size` bytes sin cortar un carácter.
+pub fn split_chunks(value: &str, chunk_size: usize) -> impl Iterator<Item = &str> {
+    let mut rest = value;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let mut end = chunk_size.max(4).min(rest.len());
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (chunk, tail) = rest.split_at(end);
+        rest = tail;
+        Some(chunk)
+    })
+}
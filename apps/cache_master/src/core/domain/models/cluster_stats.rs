@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use app_net::NodeStats;
+
+/// Un shard en la última pasada de `StatsAggregationService`, según su primario.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShardStats {
+    pub shard: Arc<str>,
+    /// Nodo que contestó; el primario salvo que no esté registrado.
+    pub node: Arc<str>,
+    /// Contadores acumulados del nodo.
+    pub total: NodeStats,
+    /// Lo que sumaron los contadores desde la pasada anterior (`interval_ms` atrás); en la
+    /// primera, todo lo acumulado.
+    pub interval: NodeStats,
+    /// 0 si no hubo pasada anterior.
+    pub interval_ms: u64,
+}
+
+impl ShardStats {
+    /// Lecturas y escrituras por segundo en el intervalo.
+    pub fn ops_per_sec(&self) -> f64 {
+        if self.interval_ms == 0 {
+            return 0.0;
+        }
+        self.interval.ops() as f64 * 1000.0 / self.interval_ms as f64
+    }
+
+    pub fn hit_ratio(&self) -> f64 {
+        self.interval.hit_ratio()
+    }
+}
+
+/// Vista del cluster armada con el `STATS` de cada shard.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClusterStats {
+    /// Hora del master (ms) al terminar la pasada.
+    pub collected_at_ms: u64,
+    /// Ordenados por shard.
+    pub shards: Vec<ShardStats>,
+    /// Shards que no contestaron en esta pasada.
+    pub unreachable: Vec<Arc<str>>,
+}
+
+impl ClusterStats {
+    /// Claves en los primarios; las réplicas no se suman.
+    pub fn keys(&self) -> u64 {
+        self.shards.iter().map(|s| s.total.entries).sum()
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.shards.iter().map(|s| s.total.bytes).sum()
+    }
+
+    pub fn ops_per_sec(&self) -> f64 {
+        self.shards.iter().map(ShardStats::ops_per_sec).sum()
+    }
+
+    /// Aciertos sobre lecturas del intervalo sumando todos los shards.
+    pub fn hit_ratio(&self) -> f64 {
+        let (hits, reads) = self.shards.iter().fold((0, 0), |(hits, reads), s| {
+            (
+                hits + s.interval.hits,
+                reads + s.interval.hits + s.interval.misses,
+            )
+        });
+        if reads == 0 {
+            return 0.0;
+        }
+        hits as f64 / reads as f64
+    }
+
+    /// `keys=.. bytes=.. ops_per_sec=.. hit_ratio=.. shards=.. collected_at=..` y después
+    /// `<shard> node=.. keys=.. bytes=.. capacity=.. ops_per_sec=.. hit_ratio=..` por shard
+    /// (`<shard> unreachable` si no contestó).
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "keys={} bytes={} ops_per_sec={:.1} hit_ratio={:.3} shards={} collected_at={}",
+            self.keys(),
+            self.bytes(),
+            self.ops_per_sec(),
+            self.hit_ratio(),
+            self.shards.len() + self.unreachable.len(),
+            self.collected_at_ms
+        )];
+        lines.extend(self.shards.iter().map(|s| {
+            format!(
+                "{} node={} keys={} bytes={} capacity={} ops_per_sec={:.1} hit_ratio={:.3}",
+                s.shard,
+                s.node,
+                s.total.entries,
+                s.total.bytes,
+                s.total.capacity,
+                s.ops_per_sec(),
+                s.hit_ratio()
+            )
+        }));
+        lines.extend(
+            self.unreachable
+                .iter()
+                .map(|shard| format!("{shard} unreachable")),
+        );
+        lines
+    }
+}
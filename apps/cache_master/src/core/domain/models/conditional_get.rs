@@ -0,0 +1,8 @@
+/// Resultado de un `GET ... IF-NOT-VERSION` (ver `app_net::conditional`). La versión es
+/// la del primario del shard.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConditionalGet {
+    Missing,
+    NotModified { version: u64 },
+    Modified { value: String, version: u64 },
+}
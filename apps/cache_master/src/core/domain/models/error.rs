@@ -1,3 +1,6 @@
+use app_core::error::{ErrorKind, HasErrorKind};
+use app_logging::LogFilterError;
+use app_net::SocketError;
 use thiserror::Error;
 
 #[derive(Debug, Error, Clone)]
@@ -16,4 +19,74 @@ pub enum AppError {
 
     #[error("Bad request: {0}")]
     BadRequest(String),
+
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("Node busy: {0}")]
+    NodeBusy(String),
+
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    #[error("Precondition failed: {0}")]
+    PreconditionFailed(String),
+
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+
+    #[error("Quota exceeded: {0}")]
+    QuotaExceeded(String),
+
+    #[error("Read only: {0}")]
+    ReadOnly(String),
+
+    /// La mayoría de los masters no aceptó un cambio de topología.
+    #[error("No quorum: {0}")]
+    NoQuorum(String),
+
+    /// No se pudo leer o escribir en el destino de los backups.
+    #[error("Storage error: {0}")]
+    Storage(String),
+
+    /// Configuración con la que el master no arranca.
+    #[error("Invalid config: {0}")]
+    Config(String),
+
+    #[error("Network error: {0}")]
+    Net(#[from] SocketError),
+
+    #[error("Log filter: {0}")]
+    LogFilter(#[from] LogFilterError),
+}
+
+impl HasErrorKind for AppError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            AppError::SocketError(_) | AppError::ConnectionError(_) => ErrorKind::Connection,
+            AppError::FirstConnectionEmpty | AppError::BadRequest(_) | AppError::Config(_) => {
+                ErrorKind::BadRequest
+            }
+            AppError::NodeNotFound(_) | AppError::NodeBusy(_) | AppError::NoQuorum(_) => {
+                ErrorKind::Unavailable
+            }
+            AppError::NotFound(_) => ErrorKind::NotFound,
+            AppError::Unauthorized(_) => ErrorKind::Unauthorized,
+            AppError::Conflict(_) => ErrorKind::Conflict,
+            AppError::PreconditionFailed(_) => ErrorKind::PreconditionFailed,
+            AppError::PayloadTooLarge(_) => ErrorKind::PayloadTooLarge,
+            AppError::RateLimited(_) => ErrorKind::RateLimited,
+            AppError::QuotaExceeded(_) => ErrorKind::QuotaExceeded,
+            AppError::ReadOnly(_) => ErrorKind::ReadOnly,
+            AppError::Storage(_) => ErrorKind::Internal,
+            AppError::Net(e) => e.kind(),
+            AppError::LogFilter(e) => e.kind(),
+        }
+    }
 }
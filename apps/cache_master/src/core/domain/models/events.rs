@@ -0,0 +1,37 @@
+use app_core::events::EventBus;
+
+/// Eventos de dominio publicados por los casos de uso del master.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DomainEvent {
+    /// Un nodo se unió al cluster; `shard_id` es el master de su shard (él mismo si es master).
+    NodeJoined {
+        node_id: String,
+        shard_id: String,
+    },
+    NodeRemoved {
+        node_id: String,
+    },
+    /// Un `RENAME` movió la entrada de `key` del shard `from_node` al shard `to_node` (con
+    /// otro nombre). Uno en el mismo shard no la mueve y no se publica.
+    KeyMigrated {
+        key: String,
+        from_node: String,
+        to_node: String,
+    },
+    /// `key` venció en `node_id` y el nodo la sacó; lo publica el master cuando el nodo se
+    /// lo avisa (ver `app_net::event::KEYS_EXPIRED`). Cada nodo del shard avisa por su lado.
+    KeyExpired {
+        key: String,
+        node_id: String,
+    },
+    /// El cache de un nodo está lleno y desaloja claves por capacidad: su shard necesita
+    /// más memoria o menos claves. Se publica al entrar en ese estado, no en cada reporte.
+    ShardUndersized {
+        node_id: String,
+        shard_id: String,
+        evictions_per_sec: u64,
+        fill_percent: u8,
+    },
+}
+
+pub type DomainEventBus = EventBus<DomainEvent>;
@@ -0,0 +1,28 @@
+use std::fmt;
+
+/// Resultado de `IMPORT`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    pub file: String,
+    /// `rdb` o `ndjson`.
+    pub format: &'static str,
+    pub imported: u64,
+    /// Entradas que ya vencieron; no se escriben.
+    pub expired: u64,
+    /// Lo que el formato trae pero el cluster no guarda (tipos que no son string, otras
+    /// bases de Redis, valores binarios).
+    pub skipped: u64,
+    /// `PUT` rechazados o líneas ilegibles.
+    pub failed: u64,
+    pub first_error: Option<String>,
+}
+
+impl fmt::Display for ImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "file={} format={} imported={} expired={} skipped={} failed={}",
+            self.file, self.format, self.imported, self.expired, self.skipped, self.failed
+        )
+    }
+}
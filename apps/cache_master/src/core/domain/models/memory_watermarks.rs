@@ -0,0 +1,38 @@
+/// Marcas de memoria para admitir escrituras en un nodo, como fracción de su
+/// `MAX_MEMORY_BYTES`: al pasar `high` el master deja de mandarle `PUT` y vuelve a hacerlo
+/// cuando baja de `low`. La distancia entre las dos evita que entre y salga en cada reporte.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MemoryWatermarks {
+    pub high: f64,
+    pub low: f64,
+}
+
+impl Default for MemoryWatermarks {
+    fn default() -> Self {
+        Self {
+            high: 0.9,
+            low: 0.8,
+        }
+    }
+}
+
+impl MemoryWatermarks {
+    /// En porcentajes; `high` se acota a 100 y `low` a `high`.
+    pub fn from_percent(high: u8, low: u8) -> Self {
+        let high = high.min(100);
+        Self {
+            high: f64::from(high) / 100.0,
+            low: f64::from(low.min(high)) / 100.0,
+        }
+    }
+
+    /// Estado que sigue a `over` (si el nodo estaba sobre la marca alta) con la ocupación
+    /// `ratio`.
+    pub fn next(&self, over: bool, ratio: f64) -> bool {
+        if over {
+            ratio >= self.low
+        } else {
+            ratio >= self.high
+        }
+    }
+}
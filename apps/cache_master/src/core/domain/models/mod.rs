@@ -1,7 +1,25 @@
+pub mod backup;
+pub mod chunks;
+pub mod cluster_stats;
+pub mod conditional_get;
 pub mod error;
+pub mod events;
+pub mod import;
+pub mod memory_watermarks;
 pub mod node;
+pub mod put_key;
+pub mod ttl_jitter;
 pub mod usecases;
 
+pub use backup::{BackupManifest, RestoreReport, ShardBackup};
+pub use chunks::{CHUNKED_PREFIX, ChunkManifest, split_chunks};
+pub use cluster_stats::{ClusterStats, ShardStats};
+pub use conditional_get::ConditionalGet;
 pub use error::AppError;
+pub use events::{DomainEvent, DomainEventBus};
+pub use import::ImportReport;
+pub use memory_watermarks::MemoryWatermarks;
 pub use node::EntryNode;
 pub use node::NodeType;
+pub use put_key::PutKey;
+pub use ttl_jitter::TtlJitter;
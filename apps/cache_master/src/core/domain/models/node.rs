@@ -9,16 +9,32 @@ pub enum NodeType {
     Client,
 }
 
+impl NodeType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NodeType::Master => "master",
+            NodeType::Replica => "replica",
+            NodeType::Client => "client",
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct EntryNode {
     pub node_type: NodeType,
     pub id: String,
+    /// Dirección del listener de replicación del nodo, si lo tiene.
+    pub repl_addr: Option<String>,
 }
 
 impl EntryNode {
     #[inline]
     pub fn new(node_type: NodeType, id: String) -> Self {
-        Self { node_type, id }
+        Self {
+            node_type,
+            id,
+            repl_addr: None,
+        }
     }
 }
 
@@ -28,11 +44,15 @@ impl FromStr for EntryNode {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut parts = s.split_whitespace();
 
-        match (parts.next(), parts.next()) {
-            (Some("MASTER"), Some(id)) => Ok(EntryNode::new(NodeType::Master, id.to_string())),
-            (Some("REPLICA"), Some(id)) => Ok(EntryNode::new(NodeType::Replica, id.to_string())),
-            (Some(id), None) => Ok(EntryNode::new(NodeType::Client, id.to_string())),
-            _ => Err(AppError::ConnectionError("Node type not found".to_string())),
-        }
+        let mut entry = match (parts.next(), parts.next()) {
+            (Some("MASTER"), Some(id)) => EntryNode::new(NodeType::Master, id.to_string()),
+            (Some("REPLICA"), Some(id)) => EntryNode::new(NodeType::Replica, id.to_string()),
+            (Some(id), None) => return Ok(EntryNode::new(NodeType::Client, id.to_string())),
+            _ => return Err(AppError::ConnectionError("Node type not found".to_string())),
+        };
+
+        // `<ROL> <id> <dirección de replicación>`
+        entry.repl_addr = parts.next().map(str::to_string);
+        Ok(entry)
     }
 }
@@ -0,0 +1,12 @@
+/// Un `PUT` hacia los nodos de un shard (ver `NetworkService::request_put_key`).
+#[derive(Debug, Clone, Copy)]
+pub struct PutKey<'a> {
+    pub key: &'a str,
+    pub value: &'a str,
+    /// Instante absoluto, en ms del reloj del master.
+    pub expires_at: Option<u64>,
+    pub tags: &'a [String],
+    /// El `idem=` que mandó el cliente; viaja igual en los reintentos, así el nodo no
+    /// aplica dos veces el mismo `PUT` (ver `app_net::idempotency`).
+    pub idempotency: Option<&'a str>,
+}
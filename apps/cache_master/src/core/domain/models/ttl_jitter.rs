@@ -0,0 +1,28 @@
+/// Porcentaje al azar que se suma a cada TTL antes de convertirlo en `expires_at`, para
+/// que las claves escritas juntas no venzan todas en el mismo tick de la rueda de los
+/// nodos. Solo alarga: una clave nunca vence antes de lo que pidió el cliente.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TtlJitter {
+    pct: u8,
+}
+
+impl TtlJitter {
+    /// `pct` se acota a 100 (el TTL como mucho se duplica).
+    pub fn new(pct: u8) -> Self {
+        Self { pct: pct.min(100) }
+    }
+
+    pub fn pct(&self) -> u8 {
+        self.pct
+    }
+
+    /// `ttl_ms` más entre 0 y `pct`% de sí mismo.
+    pub fn apply(&self, ttl_ms: u64) -> u64 {
+        if self.pct == 0 {
+            return ttl_ms;
+        }
+        let max_extra = u128::from(ttl_ms) * u128::from(self.pct) / 100;
+        let max_extra = u64::try_from(max_extra).unwrap_or(u64::MAX);
+        ttl_ms.saturating_add(fastrand::u64(0..=max_extra))
+    }
+}
@@ -1,10 +1,27 @@
 #[derive(Debug)]
 pub struct GetKeyUseCaseInput {
     pub key: String,
+    /// Ventana de refresco anticipado (ver `app_net::refresh`), si se pidió.
+    pub refresh_ms: Option<u64>,
+    /// `IF-NOT-VERSION` (ver `app_net::conditional`), si se pidió.
+    pub if_not_version: Option<u64>,
+    /// `stale=<máximo>` (ver `app_net::refresh`), si se pidió.
+    pub stale_ms: Option<u64>,
+    /// `node=<id>` (ver `app_net::sticky`): leer del nodo que confirmó una escritura de
+    /// quien pregunta.
+    pub read_from: Option<String>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct GetKeyUseCaseOutput {
     pub success: bool,
     pub result: String,
+    /// Con `refresh_ms`: si quien pregunta tiene que refrescar la clave.
+    pub refresh: Option<bool>,
+    /// Con `if_not_version`: la versión de la clave, si existe.
+    pub version: Option<u64>,
+    /// Con `if_not_version`: la clave sigue en esa versión y `result` va vacío.
+    pub not_modified: bool,
+    /// Con `stale_ms`: hace cuántos ms venció el valor, 0 si está vigente.
+    pub stale_for: Option<u64>,
 }
@@ -1,9 +1,13 @@
 pub mod assign_node_use_case;
 pub mod get_key_use_case;
+pub mod multi_use_case;
 pub mod put_key_use_case;
 pub mod remove_node_use_case;
+pub mod rename_use_case;
 
 pub use assign_node_use_case::{AssignNodeUseCaseInput, AssignNodeUseCaseOutput};
 pub use get_key_use_case::{GetKeyUseCaseInput, GetKeyUseCaseOutput};
+pub use multi_use_case::{MultiUseCaseInput, MultiUseCaseOutput};
 pub use put_key_use_case::{PutKeyUseCaseInput, PutKeyUseCaseOutput};
 pub use remove_node_use_case::{RemoveNodeUseCaseInput, RemoveNodeUseCaseOutput};
+pub use rename_use_case::{RenameUseCaseInput, RenameUseCaseOutput};
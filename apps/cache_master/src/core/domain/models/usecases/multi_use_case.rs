@@ -0,0 +1,13 @@
+use app_net::TxCommand;
+
+#[derive(Debug)]
+pub struct MultiUseCaseInput {
+    /// Con los TTL de `PUT` relativos a ahora, como los manda el cliente.
+    pub commands: Vec<TxCommand>,
+}
+
+#[derive(Debug)]
+pub struct MultiUseCaseOutput {
+    /// Un resultado por comando que no sea `WATCH`, en orden.
+    pub results: Vec<String>,
+}
@@ -1,11 +1,22 @@
+use app_net::PutCondition;
+
 #[derive(Debug)]
 pub struct PutKeyUseCaseInput {
     pub key: String,
     pub value: String,
-    pub ttl: Option<u64>,
+    /// Relativo a ahora, en milisegundos.
+    pub ttl_ms: Option<u64>,
+    /// Reemplazan los que tuviera la clave; ver `app_net::tags`.
+    pub tags: Vec<String>,
+    /// `IF <condición>`: se evalúa en el primario del shard.
+    pub condition: Option<PutCondition>,
+    /// `idem=<token>` del cliente, para que los reintentos no se apliquen dos veces.
+    pub idempotency: Option<String>,
 }
 
 #[derive(Debug)]
 pub struct PutKeyUseCaseOutput {
     pub success: bool,
+    /// El nodo del shard dueño que confirmó la escritura.
+    pub acked_by: Option<String>,
 }
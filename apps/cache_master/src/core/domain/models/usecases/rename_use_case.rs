@@ -0,0 +1,11 @@
+#[derive(Debug)]
+pub struct RenameUseCaseInput {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug)]
+pub struct RenameUseCaseOutput {
+    /// `to` cae en otro shard que `from`: se copió y se borró el origen.
+    pub across_shards: bool,
+}
@@ -8,4 +8,8 @@ pub trait ConsistentHasherService: Send + Sync {
     fn node_exists(&self, node_id: &str) -> bool;
 
     fn get_node_id_from_hash(&self, hash: &str) -> Option<String>;
+
+    /// El dueño de `hash` y los nodos distintos que le siguen en el anillo, hasta `count`
+    /// en total (menos si no hay tantos).
+    fn get_node_ids_from_hash(&self, hash: &str, count: usize) -> Vec<String>;
 }
@@ -1,5 +1,7 @@
 pub mod consistent_hasher_service;
 pub mod network_service;
+pub mod topology_coordinator;
 
 pub use consistent_hasher_service::ConsistentHasherService;
 pub use network_service::NetworkService;
+pub use topology_coordinator::TopologyCoordinator;
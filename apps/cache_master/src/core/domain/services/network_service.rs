@@ -1,10 +1,13 @@
+use app_net::{PutCondition, TxCommand};
 use async_trait::async_trait;
 
-use crate::core::domain::models::AppError;
+use crate::core::domain::models::{AppError, ConditionalGet, PutKey};
 
 #[async_trait]
 pub trait NetworkService: Send + Sync {
-    fn get_node_id_with_less_replicas(&self) -> Option<String>;
+    /// Shard para una réplica nueva `node_id`: el de menos réplicas entre los que no tienen
+    /// ya un miembro de su mismo proceso, o el de menos réplicas si todos lo tienen.
+    fn get_node_id_with_less_replicas(&self, node_id: &str) -> Option<String>;
 
     fn get_all_nodes_by_id(&self, node_id: &str) -> Vec<String>;
 
@@ -17,13 +20,81 @@ pub trait NetworkService: Send + Sync {
 
     fn count_replica_nodes(&self, node_id: &str) -> usize;
 
+    /// El nodo que confirmó la escritura (el primero, si la política de escritura pide
+    /// más de uno), o `None` si el shard no la aplicó.
     async fn request_put_key(
         &self,
         node_id: &str,
-        key: &str,
-        value: &str,
-        ttl: Option<u64>,
-    ) -> Result<bool, AppError>;
+        put: &PutKey<'_>,
+    ) -> Result<Option<String>, AppError>;
+
+    /// `PUT` que el primario del shard aplica solo si se cumple `condition`; si no, falla
+    /// con `AppError::PreconditionFailed`. Lo confirma el primario.
+    async fn request_put_key_if(
+        &self,
+        node_id: &str,
+        put: &PutKey<'_>,
+        condition: &PutCondition,
+    ) -> Result<Option<String>, AppError>;
 
     async fn request_get_key(&self, node_id: &str, key: &str) -> Result<Option<String>, AppError>;
+
+    /// `GET` al nodo `read_from` del shard, el que confirmó una escritura de quien
+    /// pregunta (ver `app_net::sticky`). Si ya no está en el shard o no contesta, como
+    /// `request_get_key`.
+    async fn request_get_key_from(
+        &self,
+        node_id: &str,
+        key: &str,
+        read_from: &str,
+    ) -> Result<Option<String>, AppError>;
+
+    /// `GET` al primario del shard que se saltea el valor si la clave sigue en
+    /// `if_not_version`.
+    async fn request_get_key_if_not_version(
+        &self,
+        node_id: &str,
+        key: &str,
+        if_not_version: u64,
+    ) -> Result<ConditionalGet, AppError>;
+
+    /// `GET` con refresco anticipado: el valor y si quien pregunta quedó elegido para
+    /// refrescarlo (ver `app_net::refresh`).
+    async fn request_get_key_refresh(
+        &self,
+        node_id: &str,
+        key: &str,
+        window_ms: u64,
+    ) -> Result<Option<(String, bool)>, AppError>;
+
+    /// `GET` que acepta un valor vencido hace menos de `max_stale_ms`: el valor, si quien
+    /// pregunta quedó elegido para refrescarlo y hace cuántos ms venció (ver
+    /// `app_net::refresh`).
+    async fn request_get_key_stale(
+        &self,
+        node_id: &str,
+        key: &str,
+        max_stale_ms: u64,
+    ) -> Result<Option<(String, bool, u64)>, AppError>;
+
+    /// Aplica el lote en el primario del shard `node_id`; los `PUT` ya llevan el
+    /// `expires_at` absoluto. Devuelve el resultado de cada comando que no sea `WATCH`.
+    async fn request_multi(
+        &self,
+        node_id: &str,
+        commands: &[TxCommand],
+    ) -> Result<Vec<String>, AppError>;
+
+    /// `RENAME` en el primario del shard: mueve la entrada a otra clave del mismo shard. Falla
+    /// con `AppError::NotFound` si `from` no está y con `AppError::Conflict` si `to` ya
+    /// existe.
+    async fn request_rename(&self, node_id: &str, from: &str, to: &str) -> Result<(), AppError>;
+
+    /// Versión y `expires_at` de `key` según el `META` del primario del shard, o `None` si no
+    /// está.
+    async fn request_key_expiry(
+        &self,
+        node_id: &str,
+        key: &str,
+    ) -> Result<Option<(u64, Option<u64>)>, AppError>;
 }
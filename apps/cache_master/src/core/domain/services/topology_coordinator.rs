@@ -0,0 +1,17 @@
+use async_trait::async_trait;
+
+use crate::core::domain::models::AppError;
+
+/// Acuerdo con los otros masters sobre las altas de nodos, para que todos armen el mismo
+/// anillo y pongan cada réplica en el mismo shard.
+#[async_trait]
+pub trait TopologyCoordinator: Send + Sync {
+    /// Shard que los masters ya acordaron para `node_id`, si hay.
+    fn agreed_shard(&self, node_id: &str) -> Option<String>;
+
+    /// Propone que `node_id` entre a `shard_id`. Falla con `AppError::NoQuorum` si no lo
+    /// confirmó la mayoría de los masters y con `AppError::Conflict` si el nodo ya tiene
+    /// acordado otro shard vivo: en ese caso el estado ya se puso al día y conviene elegir
+    /// shard de nuevo.
+    async fn commit_join(&self, node_id: &str, shard_id: &str) -> Result<(), AppError>;
+}
@@ -6,32 +6,83 @@ use tracing::info;
 
 use crate::core::domain::{
     models::{
-        AppError, NodeType,
+        AppError, DomainEvent, DomainEventBus, NodeType,
         usecases::assign_node_use_case::{AssignNodeUseCaseInput, AssignNodeUseCaseOutput},
     },
-    services::{ConsistentHasherService, NetworkService},
+    services::{ConsistentHasherService, NetworkService, TopologyCoordinator},
 };
 
+/// Propuestas de un alta antes de rendirse cuando otros masters proponen a la vez.
+const JOIN_ATTEMPTS: usize = 3;
+
 pub struct AssignNodeUseCase {
     hasher_service: Arc<dyn ConsistentHasherService>,
     network_service: Arc<dyn NetworkService>,
+    event_bus: Arc<DomainEventBus>,
+    /// Sin coordinador (un master solo) las altas no se consultan con nadie.
+    coordinator: Option<Arc<dyn TopologyCoordinator>>,
 }
 
 impl AssignNodeUseCase {
     pub fn new(
         hasher_service: Arc<dyn ConsistentHasherService>,
         network_service: Arc<dyn NetworkService>,
+        event_bus: Arc<DomainEventBus>,
     ) -> Self {
         Self {
             hasher_service,
             network_service,
+            event_bus,
+            coordinator: None,
+        }
+    }
+
+    /// Cada alta necesita antes el acuerdo de la mayoría de los masters.
+    pub fn with_coordinator(mut self, coordinator: Arc<dyn TopologyCoordinator>) -> Self {
+        self.coordinator = Some(coordinator);
+        self
+    }
+
+    /// Acuerda con los otros masters el shard de `node_id`: el que devuelve `choose`, que
+    /// se vuelve a pedir si otro master propuso a la vez (ya con el estado al día).
+    async fn agree_shard(
+        &self,
+        node_id: &str,
+        choose: impl Fn(Option<String>) -> Result<Option<String>, AppError>,
+    ) -> Result<Option<String>, AppError> {
+        let Some(coordinator) = &self.coordinator else {
+            return choose(None);
+        };
+
+        let mut last_error = None;
+        for _ in 0..JOIN_ATTEMPTS {
+            let Some(shard_id) = choose(coordinator.agreed_shard(node_id))? else {
+                return Ok(None);
+            };
+            match coordinator.commit_join(node_id, &shard_id).await {
+                Ok(()) => return Ok(Some(shard_id)),
+                Err(e @ AppError::Conflict(_)) => last_error = Some(e),
+                Err(e) => return Err(e),
+            }
         }
+        Err(last_error.unwrap_or_else(|| AppError::NoQuorum(node_id.to_string())))
+    }
+
+    fn publish_joined(&self, node_id: &str, shard_id: &str) {
+        self.event_bus.publish(DomainEvent::NodeJoined {
+            node_id: node_id.to_string(),
+            shard_id: shard_id.to_string(),
+        });
     }
 
     async fn handle_master_insert(
         &self,
         input: AssignNodeUseCaseInput,
     ) -> Result<AssignNodeUseCaseOutput, AppError> {
+        // el shard de un master es él mismo: lo que se acuerda es que entre al anillo
+        self.agree_shard(&input.node_id, |_| Ok(Some(input.node_id.clone())))
+            .await?;
+
         self.hasher_service.add_node(&input.node_id);
 
         if !self.hasher_service.node_exists(&input.node_id) {
@@ -42,6 +93,10 @@ impl AssignNodeUseCase {
 
         let success = self.network_service.add_master_node(&input.node_id).await?;
 
+        if success {
+            self.publish_joined(&input.node_id, &input.node_id);
+        }
+
         Ok(AssignNodeUseCaseOutput { success })
     }
 
@@ -49,7 +104,20 @@ impl AssignNodeUseCase {
         &self,
         input: AssignNodeUseCaseInput,
     ) -> Result<AssignNodeUseCaseOutput, AppError> {
-        let possible_master_node_id = self.network_service.get_node_id_with_less_replicas();
+        // el shard acordado, o el de menos réplicas si todavía no tiene. Si el primario del
+        // acordado no volvió a este master (tras un reinicio), la réplica lo espera: pasarla
+        // a otro shard la movería también en los demás masters
+        let possible_master_node_id = self
+            .agree_shard(&input.node_id, |agreed| match agreed {
+                Some(shard_id) if self.hasher_service.node_exists(&shard_id) => Ok(Some(shard_id)),
+                Some(shard_id) => Err(AppError::ConnectionError(format!(
+                    "El primario de {shard_id} todavía no se conectó"
+                ))),
+                None => Ok(self
+                    .network_service
+                    .get_node_id_with_less_replicas(&input.node_id)),
+            })
+            .await?;
 
         match possible_master_node_id {
             Some(master_node_id) => {
@@ -58,6 +126,10 @@ impl AssignNodeUseCase {
                     .add_replica_node(master_node_id.as_str(), &input.node_id)
                     .await?;
 
+                if success {
+                    self.publish_joined(&input.node_id, &master_node_id);
+                }
+
                 Ok(AssignNodeUseCaseOutput { success })
             }
             None => Err(AppError::ConnectionError(
@@ -0,0 +1,56 @@
+//! Limpieza de los pedazos de un valor partido (ver `PutKeyUseCase::with_chunk_size`)
+//! cuando su clave se sobrescribe o se borra. Es a mejor esfuerzo: lo que no se puede
+//! leer o borrar solo se anota, y la escritura que lo pidió ya quedó hecha.
+
+use std::collections::BTreeMap;
+
+use app_net::TxCommand;
+use tracing::{debug, warn};
+
+use crate::core::domain::{
+    models::ChunkManifest,
+    services::{ConsistentHasherService, NetworkService},
+};
+
+/// El manifiesto que guarda hoy `key` en su dueño, o `None` si no guarda uno.
+pub(crate) async fn current_manifest(
+    hasher_service: &dyn ConsistentHasherService,
+    network_service: &dyn NetworkService,
+    key: &str,
+) -> Option<ChunkManifest> {
+    let hash = hasher_service.create_hash(key);
+    let owner = hasher_service.get_node_id_from_hash(&hash)?;
+    match network_service.request_get_key(&owner, key).await {
+        Ok(value) => value.as_deref().and_then(ChunkManifest::parse),
+        Err(e) => {
+            debug!(key, "no se pudo leer el valor anterior: {e}");
+            None
+        }
+    }
+}
+
+/// Borra los pedazos de `manifest` en su dueño y en los shards con copias, un `MULTI` por
+/// shard.
+pub(crate) async fn drop_chunks(
+    hasher_service: &dyn ConsistentHasherService,
+    network_service: &dyn NetworkService,
+    replication_factor: usize,
+    key: &str,
+    manifest: &ChunkManifest,
+) {
+    let mut by_shard: BTreeMap<String, Vec<TxCommand>> = BTreeMap::new();
+    for i in 0..manifest.count {
+        let chunk_key = manifest.chunk_key(key, i);
+        let hash = hasher_service.create_hash(&chunk_key);
+        for shard in hasher_service.get_node_ids_from_hash(&hash, replication_factor) {
+            by_shard.entry(shard).or_default().push(TxCommand::Del {
+                key: chunk_key.clone(),
+            });
+        }
+    }
+    for (shard, deletes) in by_shard {
+        if let Err(e) = network_service.request_multi(&shard, &deletes).await {
+            warn!(key, %shard, "pedazos del valor anterior no borrados: {e}");
+        }
+    }
+}
@@ -1,20 +1,29 @@
-use std::sync::Arc;
+use std::{future::Future, sync::Arc};
 
-use app_core::{UseCase, UseCaseValidatable};
+use app_core::{
+    UseCase, UseCaseValidatable,
+    memoize::{MemoKey, Memoized},
+};
+use app_net::IF_NOT_VERSION;
 use async_trait::async_trait;
-use tracing::trace;
+use tracing::{debug, trace};
 
 use crate::core::domain::{
     models::{
-        AppError,
+        AppError, ChunkManifest, ConditionalGet,
         usecases::{GetKeyUseCaseInput, GetKeyUseCaseOutput},
     },
     services::{ConsistentHasherService, NetworkService},
 };
 
+/// `GetKeyUseCase` con las lecturas simples memoizadas (ver `MemoKey`).
+pub type MemoizedGetKeyUseCase = Memoized<GetKeyUseCase, GetKeyUseCaseInput, GetKeyUseCaseOutput>;
+
 pub struct GetKeyUseCase {
     hasher_service: Arc<dyn ConsistentHasherService>,
     network_service: Arc<dyn NetworkService>,
+    replication_factor: usize,
+    chunks: bool,
 }
 
 impl GetKeyUseCase {
@@ -25,8 +34,74 @@ impl GetKeyUseCase {
         Self {
             hasher_service,
             network_service,
+            replication_factor: 1,
+            chunks: false,
+        }
+    }
+
+    /// Si el shard dueño no tiene la clave, se busca en los `replication_factor - 1` que
+    /// le siguen en el anillo (ver `PutKeyUseCase::with_replication_factor`).
+    pub fn with_replication_factor(mut self, replication_factor: usize) -> Self {
+        self.replication_factor = replication_factor.max(1);
+        self
+    }
+
+    /// Un valor que es un `ChunkManifest` se arma con sus pedazos (ver
+    /// `PutKeyUseCase::with_chunk_size`).
+    pub fn with_chunks(mut self, chunks: bool) -> Self {
+        self.chunks = chunks;
+        self
+    }
+
+    /// `value` tal cual o, si es un manifiesto, armado con sus pedazos; `None` si falta
+    /// alguno (la clave se está sobrescribiendo o se le vencieron).
+    async fn assemble(&self, key: &str, value: String) -> Result<Option<String>, AppError> {
+        let Some(manifest) = self.chunks.then(|| ChunkManifest::parse(&value)).flatten() else {
+            return Ok(Some(value));
+        };
+        let mut assembled = String::with_capacity(manifest.len);
+        for i in 0..manifest.count {
+            let chunk_key = manifest.chunk_key(key, i);
+            let hash = self.hasher_service.create_hash(&chunk_key);
+            let node_ids = self
+                .hasher_service
+                .get_node_ids_from_hash(&hash, self.replication_factor);
+            let chunk = first_hit(&node_ids, |node_id| {
+                let chunk_key = chunk_key.as_str();
+                async move {
+                    self.network_service
+                        .request_get_key(&node_id, chunk_key)
+                        .await
+                }
+            })
+            .await?;
+            let Some(chunk) = chunk else {
+                debug!(key, chunk = i, "falta un pedazo del valor");
+                return Ok(None);
+            };
+            assembled.push_str(&chunk);
+        }
+        Ok((assembled.len() == manifest.len).then_some(assembled))
+    }
+}
+
+/// La primera lectura de `node_ids`, en orden, que encuentre la clave. Un error del dueño
+/// (el primero) se devuelve si nadie más la tiene; los de los demás solo se anotan.
+async fn first_hit<T, F, Fut>(node_ids: &[String], read: F) -> Result<Option<T>, AppError>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<Option<T>, AppError>>,
+{
+    let mut owner_error = None;
+    for (i, node_id) in node_ids.iter().enumerate() {
+        match read(node_id.clone()).await {
+            Ok(Some(found)) => return Ok(Some(found)),
+            Ok(None) => {}
+            Err(e) if i == 0 => owner_error = Some(e),
+            Err(e) => debug!(shard = %node_id, "copia de la clave no leída: {e}"),
         }
     }
+    owner_error.map_or(Ok(None), Err)
 }
 
 #[async_trait]
@@ -35,27 +110,110 @@ impl UseCase<GetKeyUseCaseInput, GetKeyUseCaseOutput, AppError> for GetKeyUseCas
         let hash = self.hasher_service.create_hash(&input.key);
         trace!("Hash for key {}: {}", input.key, hash);
 
-        let node_id_option = self.hasher_service.get_node_id_from_hash(&hash);
+        let node_ids = self
+            .hasher_service
+            .get_node_ids_from_hash(&hash, self.replication_factor);
 
-        if node_id_option.is_none() {
+        let Some(node_id) = node_ids.first().cloned() else {
             return Err(AppError::NodeNotFound(format!(
                 "No node found for key {} with hash {}",
                 input.key, hash
             )));
-        }
-
-        let node_id = node_id_option.unwrap();
+        };
+        let key = input.key.as_str();
 
         trace!("Node ID for key {}: {}", input.key, node_id);
 
-        let get_result = self
-            .network_service
-            .request_get_key(&node_id, &input.key)
+        // las versiones son del dueño: el GET condicional no busca copias
+        if let Some(if_not_version) = input.if_not_version {
+            let (result, version, not_modified) = match self
+                .network_service
+                .request_get_key_if_not_version(&node_id, &input.key, if_not_version)
+                .await?
+            {
+                ConditionalGet::Missing => (String::new(), None, false),
+                ConditionalGet::NotModified { version } => (String::new(), Some(version), true),
+                ConditionalGet::Modified { value, version } => (
+                    self.assemble(key, value).await?.unwrap_or_default(),
+                    Some(version),
+                    false,
+                ),
+            };
+            return Ok(GetKeyUseCaseOutput {
+                success: true,
+                result,
+                refresh: None,
+                version,
+                not_modified,
+                stale_for: None,
+            });
+        }
+
+        if let Some(max_stale_ms) = input.stale_ms {
+            let get_result = first_hit(&node_ids, |node_id| async move {
+                self.network_service
+                    .request_get_key_stale(&node_id, key, max_stale_ms)
+                    .await
+            })
+            .await?;
+            let (result, refresh, stale_for) = get_result.unwrap_or_default();
+            let result = self.assemble(key, result).await?.unwrap_or_default();
+            return Ok(GetKeyUseCaseOutput {
+                success: true,
+                result,
+                refresh: Some(refresh),
+                version: None,
+                not_modified: false,
+                stale_for: Some(stale_for),
+            });
+        }
+
+        if let Some(window_ms) = input.refresh_ms {
+            let get_result = first_hit(&node_ids, |node_id| async move {
+                self.network_service
+                    .request_get_key_refresh(&node_id, key, window_ms)
+                    .await
+            })
             .await?;
+            let (result, refresh) = get_result.unwrap_or_default();
+            let result = self.assemble(key, result).await?.unwrap_or_default();
+            return Ok(GetKeyUseCaseOutput {
+                success: true,
+                result,
+                refresh: Some(refresh),
+                version: None,
+                not_modified: false,
+                stale_for: None,
+            });
+        }
+
+        // el nodo fijado es del dueño; en las copias se lee como siempre
+        let owner = node_id.as_str();
+        let read_from = input.read_from.as_deref();
+        let get_result = first_hit(&node_ids, |node_id| async move {
+            match read_from.filter(|_| node_id == owner) {
+                Some(read_from) => {
+                    self.network_service
+                        .request_get_key_from(&node_id, key, read_from)
+                        .await
+                }
+                None => self.network_service.request_get_key(&node_id, key).await,
+            }
+        })
+        .await?;
+
+        let result = match get_result {
+            Some(value) => self.assemble(key, value).await?,
+            None => None,
+        };
 
         Ok(GetKeyUseCaseOutput {
             success: true,
-            result: get_result.unwrap_or_default(),
+            result: result.unwrap_or_default(),
+            refresh: None,
+            version: None,
+            not_modified: false,
+            stale_for: None,
         })
     }
 }
@@ -66,7 +224,34 @@ impl UseCaseValidatable<GetKeyUseCaseInput, GetKeyUseCaseOutput, AppError> for G
         if input.key.is_empty() {
             return Err(AppError::BadRequest("Key is empty".to_string()));
         }
+        if input.if_not_version.is_some()
+            && (input.refresh_ms.is_some() || input.stale_ms.is_some())
+        {
+            return Err(AppError::BadRequest(format!(
+                "{IF_NOT_VERSION} no va con refresh= ni stale="
+            )));
+        }
+        if input.refresh_ms.is_some() && input.stale_ms.is_some() {
+            return Err(AppError::BadRequest(
+                "refresh= no va con stale=".to_string(),
+            ));
+        }
 
         Ok(())
     }
 }
+
+/// Solo las lecturas simples. Con `refresh=` o `stale=` la salida dice si quien pregunta
+/// refresca la clave, y eso se le dice a uno solo; con `IF-NOT-VERSION` depende de la
+/// versión que se mandó, y con `node=` quien pregunta quiere ver su propia escritura.
+impl MemoKey for GetKeyUseCaseInput {
+    type Key = String;
+
+    fn memo_key(&self) -> Option<String> {
+        let plain = self.refresh_ms.is_none()
+            && self.if_not_version.is_none()
+            && self.stale_ms.is_none()
+            && self.read_from.is_none();
+        plain.then(|| self.key.clone())
+    }
+}
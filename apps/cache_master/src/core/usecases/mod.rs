@@ -1,9 +1,14 @@
 pub mod assign_node_use_case;
+mod chunk_cleanup;
 pub mod get_key_use_case;
+pub mod multi_use_case;
 pub mod put_key_use_case;
 pub mod remove_node_use_case;
+pub mod rename_use_case;
 
 pub use assign_node_use_case::AssignNodeUseCase;
-pub use get_key_use_case::GetKeyUseCase;
+pub use get_key_use_case::{GetKeyUseCase, MemoizedGetKeyUseCase};
+pub use multi_use_case::MultiUseCase;
 pub use put_key_use_case::PutKeyUseCase;
 pub use remove_node_use_case::RemoveNodeUseCase;
+pub use rename_use_case::RenameUseCase;
@@ -0,0 +1,220 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use app_core::{UseCase, UseCaseValidatable, clock::Clock};
+use app_net::TxCommand;
+use async_trait::async_trait;
+use tracing::{trace, warn};
+
+use crate::core::{
+    domain::{
+        models::{
+            AppError, ChunkManifest, TtlJitter,
+            usecases::{MultiUseCaseInput, MultiUseCaseOutput},
+        },
+        services::{ConsistentHasherService, NetworkService},
+    },
+    usecases::chunk_cleanup::{current_manifest, drop_chunks},
+};
+
+/// Un `MULTI` va entero al shard dueño de sus claves, así que todas tienen que caer en el
+/// mismo.
+pub struct MultiUseCase {
+    hasher_service: Arc<dyn ConsistentHasherService>,
+    network_service: Arc<dyn NetworkService>,
+    clock: Arc<dyn Clock>,
+    ttl_jitter: TtlJitter,
+    replication_factor: usize,
+    chunks: bool,
+}
+
+impl MultiUseCase {
+    pub fn new(
+        hasher_service: Arc<dyn ConsistentHasherService>,
+        network_service: Arc<dyn NetworkService>,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        Self {
+            hasher_service,
+            network_service,
+            clock,
+            ttl_jitter: TtlJitter::default(),
+            replication_factor: 1,
+            chunks: false,
+        }
+    }
+
+    pub fn with_ttl_jitter(mut self, ttl_jitter: TtlJitter) -> Self {
+        self.ttl_jitter = ttl_jitter;
+        self
+    }
+
+    /// Las escrituras que aplicó el dueño se repiten en los shards que guardan copias de
+    /// cada clave (ver `PutKeyUseCase::with_replication_factor`).
+    pub fn with_replication_factor(mut self, replication_factor: usize) -> Self {
+        self.replication_factor = replication_factor.max(1);
+        self
+    }
+
+    /// Un `DEL` o `PUT` que pisa un `ChunkManifest` borra después sus pedazos (ver
+    /// `PutKeyUseCase::with_chunk_size`).
+    pub fn with_chunks(mut self, chunks: bool) -> Self {
+        self.chunks = chunks;
+        self
+    }
+
+    /// Los manifiestos que guardan hoy las claves que escribe `commands`.
+    async fn overwritten_manifests(&self, commands: &[TxCommand]) -> Vec<(String, ChunkManifest)> {
+        let mut manifests = Vec::new();
+        for command in commands.iter().filter(|c| c.is_write()) {
+            let key = command.key();
+            if manifests.iter().any(|(seen, _)| seen == key) {
+                continue;
+            }
+            let manifest = current_manifest(
+                self.hasher_service.as_ref(),
+                self.network_service.as_ref(),
+                key,
+            )
+            .await;
+            if let Some(manifest) = manifest {
+                manifests.push((key.to_string(), manifest));
+            }
+        }
+        manifests
+    }
+
+    /// Los shards con copias de `key`, sin el dueño. Dependen de dónde cae la clave en el
+    /// anillo, así que dos claves del mismo dueño pueden tener copias en shards distintos.
+    fn copy_shards(&self, key: &str) -> Vec<String> {
+        let hash = self.hasher_service.create_hash(key);
+        self.hasher_service
+            .get_node_ids_from_hash(&hash, self.replication_factor)
+            .into_iter()
+            .skip(1)
+            .collect()
+    }
+
+    /// Sin `WATCH`: el dueño ya los comprobó y las copias no llevan sus versiones.
+    async fn copy_writes(&self, commands: &[TxCommand]) {
+        let mut by_shard: BTreeMap<String, Vec<TxCommand>> = BTreeMap::new();
+        for command in commands.iter().filter(|c| c.is_write()) {
+            for shard in self.copy_shards(command.key()) {
+                by_shard.entry(shard).or_default().push(command.clone());
+            }
+        }
+        for (shard, writes) in by_shard {
+            if let Err(e) = self.network_service.request_multi(&shard, &writes).await {
+                warn!(%shard, "copia del MULTI no aplicada: {e}");
+            }
+        }
+    }
+
+    fn node_for(&self, key: &str) -> Result<String, AppError> {
+        let hash = self.hasher_service.create_hash(key);
+        self.hasher_service
+            .get_node_id_from_hash(&hash)
+            .ok_or_else(|| {
+                AppError::NodeNotFound(format!(
+                    "No node found for key {key} with hash {hash} on MULTI"
+                ))
+            })
+    }
+}
+
+#[async_trait]
+impl UseCase<MultiUseCaseInput, MultiUseCaseOutput, AppError> for MultiUseCase {
+    async fn execute(&self, input: MultiUseCaseInput) -> Result<MultiUseCaseOutput, AppError> {
+        let mut node_id: Option<String> = None;
+        for command in &input.commands {
+            let owner = self.node_for(command.key())?;
+            match &node_id {
+                Some(first) if *first != owner => {
+                    return Err(AppError::BadRequest(format!(
+                        "MULTI: {} está en otro shard que las demás claves",
+                        command.key()
+                    )));
+                }
+                Some(_) => {}
+                None => node_id = Some(owner),
+            }
+        }
+        let Some(node_id) = node_id else {
+            return Err(AppError::BadRequest("MULTI sin comandos".to_string()));
+        };
+
+        // el nodo recibe el instante absoluto, igual que en PUT
+        let now = self.clock.now_millis().as_millis_u64();
+        let commands = input
+            .commands
+            .into_iter()
+            .map(|command| match command {
+                TxCommand::Put {
+                    key,
+                    value,
+                    ttl: Some(ttl_ms),
+                } => {
+                    let expires_at =
+                        now.checked_add(self.ttl_jitter.apply(ttl_ms))
+                            .ok_or_else(|| {
+                                AppError::BadRequest(format!("TTL too large: {ttl_ms}ms"))
+                            })?;
+                    Ok(TxCommand::Put {
+                        key,
+                        value,
+                        ttl: Some(expires_at),
+                    })
+                }
+                other => Ok(other),
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        let overwritten = match self.chunks {
+            true => self.overwritten_manifests(&commands).await,
+            false => Vec::new(),
+        };
+
+        trace!("MULTI de {} comandos al nodo {}", commands.len(), node_id);
+        let results = self
+            .network_service
+            .request_multi(&node_id, &commands)
+            .await?;
+        if self.replication_factor > 1 {
+            self.copy_writes(&commands).await;
+        }
+
+        for (key, manifest) in overwritten {
+            // un `PUT` que vuelve a dejar el mismo manifiesto sigue usando sus pedazos
+            let kept = commands
+                .iter()
+                .rev()
+                .find(|c| c.is_write() && c.key() == key);
+            let manifest_kept = matches!(
+                kept,
+                Some(TxCommand::Put { value, .. }) if ChunkManifest::parse(value).as_ref() == Some(&manifest)
+            );
+            if !manifest_kept {
+                drop_chunks(
+                    self.hasher_service.as_ref(),
+                    self.network_service.as_ref(),
+                    self.replication_factor,
+                    &key,
+                    &manifest,
+                )
+                .await;
+            }
+        }
+
+        Ok(MultiUseCaseOutput { results })
+    }
+}
+
+#[async_trait]
+impl UseCaseValidatable<MultiUseCaseInput, MultiUseCaseOutput, AppError> for MultiUseCase {
+    async fn validate(&self, input: &MultiUseCaseInput) -> Result<(), AppError> {
+        if input.commands.is_empty() {
+            return Err(AppError::BadRequest("MULTI sin comandos".to_string()));
+        }
+
+        Ok(())
+    }
+}
@@ -2,20 +2,28 @@ use std::sync::Arc;
 
 use app_core::{UseCase, UseCaseValidatable, clock::Clock};
 use async_trait::async_trait;
-use tracing::trace;
+use tracing::{trace, warn};
 
-use crate::core::domain::{
-    models::{
-        AppError,
-        usecases::{PutKeyUseCaseInput, PutKeyUseCaseOutput},
+use app_net::PutCondition;
+
+use crate::core::{
+    domain::{
+        models::{
+            AppError, CHUNKED_PREFIX, ChunkManifest, PutKey, TtlJitter, split_chunks,
+            usecases::{PutKeyUseCaseInput, PutKeyUseCaseOutput},
+        },
+        services::{ConsistentHasherService, NetworkService},
     },
-    services::{ConsistentHasherService, NetworkService},
+    usecases::chunk_cleanup::{current_manifest, drop_chunks},
 };
 
 pub struct PutKeyUseCase {
     hasher_service: Arc<dyn ConsistentHasherService>,
     network_service: Arc<dyn NetworkService>,
     clock: Arc<dyn Clock>,
+    ttl_jitter: TtlJitter,
+    replication_factor: usize,
+    chunk_size: Option<usize>,
 }
 
 impl PutKeyUseCase {
@@ -28,39 +36,164 @@ impl PutKeyUseCase {
             hasher_service,
             network_service,
             clock,
+            ttl_jitter: TtlJitter::default(),
+            replication_factor: 1,
+            chunk_size: None,
         }
     }
-}
 
-#[async_trait]
-impl UseCase<PutKeyUseCaseInput, PutKeyUseCaseOutput, AppError> for PutKeyUseCase {
-    async fn execute(&self, input: PutKeyUseCaseInput) -> Result<PutKeyUseCaseOutput, AppError> {
-        let hash = self.hasher_service.create_hash(&input.key);
+    pub fn with_ttl_jitter(mut self, ttl_jitter: TtlJitter) -> Self {
+        self.ttl_jitter = ttl_jitter;
+        self
+    }
+
+    /// Además del shard dueño, la clave se escribe en los `replication_factor - 1` shards
+    /// que le siguen en el anillo.
+    pub fn with_replication_factor(mut self, replication_factor: usize) -> Self {
+        self.replication_factor = replication_factor.max(1);
+        self
+    }
 
-        let node_id_option = self.hasher_service.get_node_id_from_hash(&hash);
+    /// Un valor de más de `chunk_size` bytes se parte en pedazos de ese tamaño, cada uno
+    /// en su clave y su shard, y en la clave queda un `ChunkManifest` (ver
+    /// `GetKeyUseCase::with_chunks`). Así un valor no está atado al tope de payload de los
+    /// nodos. Los pedazos del valor que la escritura pisa se borran después de escribirla.
+    pub fn with_chunk_size(mut self, chunk_size: Option<usize>) -> Self {
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// Escribe `put` en el shard dueño de su clave y, si lo confirmó, en los que le siguen.
+    async fn write(
+        &self,
+        put: &PutKey<'_>,
+        condition: Option<&PutCondition>,
+    ) -> Result<Option<String>, AppError> {
+        let hash = self.hasher_service.create_hash(put.key);
 
-        if node_id_option.is_none() {
+        let mut node_ids = self
+            .hasher_service
+            .get_node_ids_from_hash(&hash, self.replication_factor)
+            .into_iter();
+
+        let Some(node_id) = node_ids.next() else {
             return Err(AppError::NodeNotFound(format!(
                 "No node found for key {} with hash {} on PUT",
-                input.key, hash
+                put.key, hash
             )));
+        };
+        trace!(
+            "Node ID {} for key: {} {:?}",
+            node_id, put.key, put.expires_at
+        );
+
+        let acked_by = match condition {
+            Some(condition) => {
+                self.network_service
+                    .request_put_key_if(&node_id, put, condition)
+                    .await?
+            }
+            None => self.network_service.request_put_key(&node_id, put).await?,
+        };
+
+        // la escritura es la del dueño (y su condición): los demás shards reciben una copia
+        // y si alguno falla la clave queda con menos copias, no sin escribir
+        if acked_by.is_some() {
+            for extra in node_ids {
+                if let Err(e) = self.network_service.request_put_key(&extra, put).await {
+                    warn!(key = %put.key, shard = %extra, "copia de la clave no escrita: {e}");
+                }
+            }
         }
+        Ok(acked_by)
+    }
 
-        let node_id = node_id_option.unwrap();
-        trace!("Node ID {} for key: {} {:?}", node_id, input.key, input.ttl);
+    /// Instante absoluto (ms del reloj del master) que viaja a los nodos, con el jitter ya
+    /// sumado: primario y réplicas reciben el mismo.
+    fn expires_at(&self, ttl_ms: u64) -> Result<u64, AppError> {
+        self.clock
+            .now_millis()
+            .as_millis_u64()
+            .checked_add(self.ttl_jitter.apply(ttl_ms))
+            .ok_or_else(|| AppError::BadRequest(format!("TTL too large: {ttl_ms}ms")))
+    }
+}
+
+#[async_trait]
+impl UseCase<PutKeyUseCaseInput, PutKeyUseCaseOutput, AppError> for PutKeyUseCase {
+    async fn execute(&self, input: PutKeyUseCaseInput) -> Result<PutKeyUseCaseOutput, AppError> {
+        let expires_at = input
+            .ttl_ms
+            .map(|ttl_ms| self.expires_at(ttl_ms))
+            .transpose()?;
+
+        let mut put = PutKey {
+            key: &input.key,
+            value: &input.value,
+            expires_at,
+            tags: &input.tags,
+            idempotency: input.idempotency.as_deref(),
+        };
 
-        let expires_at = match input.ttl {
-            Some(ttl_ms) => Some(self.clock.now_millis().as_millis_u64() + ttl_ms),
+        // si la clave guarda un valor partido, sus pedazos se borran cuando deja de apuntarles
+        let previous = match self.chunk_size {
+            Some(_) => {
+                current_manifest(
+                    self.hasher_service.as_ref(),
+                    self.network_service.as_ref(),
+                    &input.key,
+                )
+                .await
+            }
             None => None,
         };
 
-        let put_result = self
-            .network_service
-            .request_put_key(&node_id, &input.key, &input.value, expires_at)
-            .await?;
+        // los pedazos primero: la clave no apunta a ellos hasta que están todos
+        let manifest;
+        let mut chunked = None;
+        if let Some(chunk_size) = self.chunk_size.filter(|size| input.value.len() > *size) {
+            let chunks = ChunkManifest::for_value(&input.value, chunk_size);
+            for (i, chunk) in split_chunks(&input.value, chunk_size).enumerate() {
+                let chunk_key = chunks.chunk_key(&input.key, i);
+                let chunk = PutKey {
+                    key: &chunk_key,
+                    value: chunk,
+                    expires_at,
+                    tags: &[],
+                    idempotency: None,
+                };
+                if self.write(&chunk, None).await?.is_none() {
+                    return Ok(PutKeyUseCaseOutput {
+                        success: false,
+                        acked_by: None,
+                    });
+                }
+            }
+            manifest = chunks.to_string();
+            put.value = &manifest;
+            chunked = Some(chunks);
+        }
+
+        let acked_by = self.write(&put, input.condition.as_ref()).await?;
+
+        // el mismo valor vuelve a usar los mismos pedazos
+        if acked_by.is_some()
+            && let Some(previous) =
+                previous.filter(|previous| chunked.as_ref().is_none_or(|new| new.id != previous.id))
+        {
+            drop_chunks(
+                self.hasher_service.as_ref(),
+                self.network_service.as_ref(),
+                self.replication_factor,
+                &input.key,
+                &previous,
+            )
+            .await;
+        }
 
         Ok(PutKeyUseCaseOutput {
-            success: put_result,
+            success: acked_by.is_some(),
+            acked_by,
         })
     }
 }
@@ -76,6 +209,26 @@ impl UseCaseValidatable<PutKeyUseCaseInput, PutKeyUseCaseOutput, AppError> for P
             return Err(AppError::BadRequest("Value is empty".to_string()));
         }
 
+        if input.ttl_ms == Some(0) {
+            return Err(AppError::BadRequest(
+                "TTL must be greater than 0".to_string(),
+            ));
+        }
+
+        if let Some(chunk_size) = self.chunk_size {
+            if input.value.starts_with(CHUNKED_PREFIX) {
+                return Err(AppError::BadRequest(format!(
+                    "Value cannot start with {CHUNKED_PREFIX}"
+                )));
+            }
+            // los pedazos se escribirían aunque la condición no se cumpla
+            if input.condition.is_some() && input.value.len() > chunk_size {
+                return Err(AppError::BadRequest(format!(
+                    "IF needs a value of at most {chunk_size} bytes"
+                )));
+            }
+        }
+
         Ok(())
     }
 }
@@ -2,11 +2,11 @@ use std::sync::Arc;
 
 use app_core::{UseCase, UseCaseValidatable};
 use async_trait::async_trait;
-use tracing::info;
+use tracing::debug;
 
 use crate::core::domain::{
     models::{
-        AppError,
+        AppError, DomainEvent, DomainEventBus,
         usecases::remove_node_use_case::{RemoveNodeUseCaseInput, RemoveNodeUseCaseOutput},
     },
     services::{ConsistentHasherService, NetworkService},
@@ -15,16 +15,19 @@ use crate::core::domain::{
 pub struct RemoveNodeUseCase {
     hasher_service: Arc<dyn ConsistentHasherService>,
     network_service: Arc<dyn NetworkService>,
+    event_bus: Arc<DomainEventBus>,
 }
 
 impl RemoveNodeUseCase {
     pub fn new(
         hasher_service: Arc<dyn ConsistentHasherService>,
         network_service: Arc<dyn NetworkService>,
+        event_bus: Arc<DomainEventBus>,
     ) -> Self {
         Self {
             hasher_service,
             network_service,
+            event_bus,
         }
     }
 }
@@ -41,16 +44,21 @@ impl UseCase<RemoveNodeUseCaseInput, RemoveNodeUseCaseOutput, AppError> for Remo
         let mut hasher_service_remove_result: bool = false;
 
         if replica_count <= 1 {
-            info!(
-                "Remove node result from hasher service: {node_id} {hasher_service_remove_result}"
-            );
-
             hasher_service_remove_result = self.hasher_service.remove_node(node_id);
+            debug!(
+                node_id,
+                removed = hasher_service_remove_result,
+                "hasher remove_node"
+            );
         }
 
         let network_service_remove_result = self.network_service.remove_node(node_id).await?;
 
-        info!("Remove node result from network service: {node_id} {network_service_remove_result}");
+        debug!(
+            node_id,
+            removed = network_service_remove_result,
+            "network remove_node"
+        );
 
         if !network_service_remove_result {
             return Err(AppError::NodeNotFound(format!(
@@ -58,6 +66,10 @@ impl UseCase<RemoveNodeUseCaseInput, RemoveNodeUseCaseOutput, AppError> for Remo
             )));
         }
 
+        self.event_bus.publish(DomainEvent::NodeRemoved {
+            node_id: node_id.to_string(),
+        });
+
         Ok(RemoveNodeUseCaseOutput {
             success: hasher_service_remove_result && network_service_remove_result,
         })
@@ -0,0 +1,284 @@
+use std::sync::Arc;
+
+use app_core::{UseCase, UseCaseValidatable};
+use app_net::{PutCondition, TxCommand};
+use async_trait::async_trait;
+use tracing::{trace, warn};
+
+use crate::core::domain::{
+    models::{
+        AppError, CHUNKED_PREFIX, ConditionalGet, DomainEvent, DomainEventBus, PutKey,
+        usecases::{RenameUseCaseInput, RenameUseCaseOutput},
+    },
+    services::{ConsistentHasherService, NetworkService},
+};
+
+/// `RENAME`: si las dos claves caen en el mismo shard, el primario mueve la entrada de una
+/// vez (ver `NetworkService::request_rename`). Si no, el master copia el valor al shard de
+/// `to` con `IF absent` y borra `from` con un `MULTI` que vigila la versión que leyó; si
+/// `from` cambió entre medio, deshace la copia y responde 409. Entre shards los tags no
+/// viajan. Un movimiento entre shards publica `DomainEvent::KeyMigrated`.
+pub struct RenameUseCase {
+    hasher_service: Arc<dyn ConsistentHasherService>,
+    network_service: Arc<dyn NetworkService>,
+    event_bus: Arc<DomainEventBus>,
+    replication_factor: usize,
+    chunks: bool,
+}
+
+impl RenameUseCase {
+    pub fn new(
+        hasher_service: Arc<dyn ConsistentHasherService>,
+        network_service: Arc<dyn NetworkService>,
+        event_bus: Arc<DomainEventBus>,
+    ) -> Self {
+        Self {
+            hasher_service,
+            network_service,
+            event_bus,
+            replication_factor: 1,
+            chunks: false,
+        }
+    }
+
+    /// Las copias de las dos claves en los shards que siguen al dueño se actualizan después
+    /// del movimiento (ver `PutKeyUseCase::with_replication_factor`).
+    pub fn with_replication_factor(mut self, replication_factor: usize) -> Self {
+        self.replication_factor = replication_factor.max(1);
+        self
+    }
+
+    /// Con los valores partidos habilitados, un `ChunkManifest` no se mueve: sus pedazos
+    /// cuelgan del nombre de la clave (ver `PutKeyUseCase::with_chunk_size`).
+    pub fn with_chunks(mut self, chunks: bool) -> Self {
+        self.chunks = chunks;
+        self
+    }
+
+    fn node_for(&self, key: &str) -> Result<String, AppError> {
+        let hash = self.hasher_service.create_hash(key);
+        self.hasher_service
+            .get_node_id_from_hash(&hash)
+            .ok_or_else(|| {
+                AppError::NodeNotFound(format!(
+                    "No node found for key {key} with hash {hash} on RENAME"
+                ))
+            })
+    }
+
+    fn copy_shards(&self, key: &str) -> Vec<String> {
+        let hash = self.hasher_service.create_hash(key);
+        self.hasher_service
+            .get_node_ids_from_hash(&hash, self.replication_factor)
+            .into_iter()
+            .skip(1)
+            .collect()
+    }
+
+    /// Valor y versión vigentes de `from` en el primario de `shard`.
+    async fn read_source(&self, shard: &str, from: &str) -> Result<(String, u64), AppError> {
+        let (value, version) = match self
+            .network_service
+            .request_get_key_if_not_version(shard, from, 0)
+            .await?
+        {
+            ConditionalGet::Modified { value, version } => (value, version),
+            ConditionalGet::Missing | ConditionalGet::NotModified { .. } => {
+                return Err(AppError::NotFound(format!("RENAME: {from} no existe")));
+            }
+        };
+        if self.chunks && value.starts_with(CHUNKED_PREFIX) {
+            return Err(AppError::BadRequest(format!(
+                "RENAME: {from} tiene un valor partido"
+            )));
+        }
+        Ok((value, version))
+    }
+
+    /// Copia y borrado entre shards; devuelve lo que quedó en `to`.
+    async fn move_across(
+        &self,
+        src: &str,
+        dst: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<(String, Option<u64>), AppError> {
+        let (value, version) = self.read_source(src, from).await?;
+        let expires_at = match self.network_service.request_key_expiry(src, from).await? {
+            Some((current, expires_at)) if current == version => expires_at,
+            Some(_) => return Err(changed(from)),
+            None => return Err(AppError::NotFound(format!("RENAME: {from} no existe"))),
+        };
+
+        let put = PutKey {
+            key: to,
+            value: &value,
+            expires_at,
+            tags: &[],
+            idempotency: None,
+        };
+        let written = self
+            .network_service
+            .request_put_key_if(dst, &put, &PutCondition::Absent)
+            .await
+            .map_err(|e| match e {
+                AppError::PreconditionFailed(_) => {
+                    AppError::Conflict(format!("RENAME: {to} ya existe"))
+                }
+                e => e,
+            })?;
+        if written.is_none() {
+            return Err(AppError::ConnectionError(format!(
+                "RENAME: el shard {dst} no escribió {to}"
+            )));
+        }
+
+        let delete = [
+            TxCommand::Watch {
+                key: from.to_string(),
+                version,
+            },
+            TxCommand::Del {
+                key: from.to_string(),
+            },
+        ];
+        if let Err(e) = self.network_service.request_multi(src, &delete).await {
+            // la copia es nueva (versión 1): si alguien ya la pisó, queda lo suyo
+            let undo = [
+                TxCommand::Watch {
+                    key: to.to_string(),
+                    version: 1,
+                },
+                TxCommand::Del {
+                    key: to.to_string(),
+                },
+            ];
+            if let Err(undo_err) = self.network_service.request_multi(dst, &undo).await {
+                warn!(%to, shard = %dst, "copia del RENAME no deshecha: {undo_err}");
+            }
+            return Err(match e {
+                AppError::Conflict(_) => changed(from),
+                e => e,
+            });
+        }
+        Ok((value, expires_at))
+    }
+
+    /// Borra `from` de sus copias y escribe `to` en las suyas, que pueden ser otros shards.
+    async fn update_copies(
+        &self,
+        from: &str,
+        to: &str,
+        dst: &str,
+        moved: Option<(String, Option<u64>)>,
+    ) {
+        let delete = [TxCommand::Del {
+            key: from.to_string(),
+        }];
+        for shard in self.copy_shards(from) {
+            if let Err(e) = self.network_service.request_multi(&shard, &delete).await {
+                warn!(%from, %shard, "copia del origen del RENAME no borrada: {e}");
+            }
+        }
+
+        let shards = self.copy_shards(to);
+        if shards.is_empty() {
+            return;
+        }
+        let moved = match moved {
+            Some(moved) => moved,
+            None => match self.read_moved(dst, to).await {
+                Ok(Some(moved)) => moved,
+                Ok(None) => return,
+                Err(e) => {
+                    warn!(%to, "RENAME sin copias del destino: {e}");
+                    return;
+                }
+            },
+        };
+        let put = PutKey {
+            key: to,
+            value: &moved.0,
+            expires_at: moved.1,
+            tags: &[],
+            idempotency: None,
+        };
+        for shard in shards {
+            if let Err(e) = self.network_service.request_put_key(&shard, &put).await {
+                warn!(%to, %shard, "copia del destino del RENAME no escrita: {e}");
+            }
+        }
+    }
+
+    /// Lo que movió el primario en un `RENAME` del mismo shard.
+    async fn read_moved(
+        &self,
+        shard: &str,
+        key: &str,
+    ) -> Result<Option<(String, Option<u64>)>, AppError> {
+        let value = match self
+            .network_service
+            .request_get_key_if_not_version(shard, key, 0)
+            .await?
+        {
+            ConditionalGet::Modified { value, .. } => value,
+            _ => return Ok(None),
+        };
+        let expires_at = self
+            .network_service
+            .request_key_expiry(shard, key)
+            .await?
+            .and_then(|(_, expires_at)| expires_at);
+        Ok(Some((value, expires_at)))
+    }
+}
+
+fn changed(key: &str) -> AppError {
+    AppError::Conflict(format!("RENAME: {key} cambió mientras se movía"))
+}
+
+#[async_trait]
+impl UseCase<RenameUseCaseInput, RenameUseCaseOutput, AppError> for RenameUseCase {
+    async fn execute(&self, input: RenameUseCaseInput) -> Result<RenameUseCaseOutput, AppError> {
+        let src = self.node_for(&input.from)?;
+        let dst = self.node_for(&input.to)?;
+        trace!(from = %input.from, to = %input.to, %src, %dst, "RENAME");
+
+        let moved = if src == dst {
+            if self.chunks {
+                self.read_source(&src, &input.from).await?;
+            }
+            self.network_service
+                .request_rename(&src, &input.from, &input.to)
+                .await?;
+            None
+        } else {
+            let moved = self.move_across(&src, &dst, &input.from, &input.to).await?;
+            self.event_bus.publish(DomainEvent::KeyMigrated {
+                key: input.from.clone(),
+                from_node: src.clone(),
+                to_node: dst.clone(),
+            });
+            Some(moved)
+        };
+
+        if self.replication_factor > 1 {
+            self.update_copies(&input.from, &input.to, &dst, moved)
+                .await;
+        }
+        Ok(RenameUseCaseOutput {
+            across_shards: src != dst,
+        })
+    }
+}
+
+#[async_trait]
+impl UseCaseValidatable<RenameUseCaseInput, RenameUseCaseOutput, AppError> for RenameUseCase {
+    async fn validate(&self, input: &RenameUseCaseInput) -> Result<(), AppError> {
+        if input.from.is_empty() || input.to.is_empty() {
+            return Err(AppError::BadRequest("Key is empty".to_string()));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,35 @@
+use async_trait::async_trait;
+
+use app_net::tokenize;
+
+use crate::{
+    core::domain::models::AppError,
+    infrastructure::adapters::controllers::router::{ActionHandler, RequestContext},
+};
+
+/// `AUTH "<token>"`: habilita las acciones de administración en la conexión.
+pub struct AuthAction {
+    admin_token: Option<String>,
+}
+
+impl AuthAction {
+    pub fn new(admin_token: Option<String>) -> Self {
+        Self { admin_token }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for AuthAction {
+    async fn handle(&self, ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        let token = tokenize(payload).next().unwrap_or_default();
+
+        match &self.admin_token {
+            None => Ok("OK".to_string()),
+            Some(expected) if *expected == token => {
+                ctx.grant_admin();
+                Ok("OK".to_string())
+            }
+            Some(_) => Err(AppError::Unauthorized("token inválido".to_string())),
+        }
+    }
+}
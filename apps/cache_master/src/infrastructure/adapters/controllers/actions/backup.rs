@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use app_net::{encode_args, tokenize};
+use async_trait::async_trait;
+
+use crate::{
+    core::domain::models::AppError,
+    infrastructure::adapters::{
+        controllers::router::{ActionHandler, RequestContext},
+        services::BackupService,
+    },
+};
+
+fn service(backups: &Option<Arc<BackupService>>) -> Result<&BackupService, AppError> {
+    backups.as_deref().ok_or_else(|| {
+        AppError::BadRequest("no hay destino de backups (BACKUP_DIR o BACKUP_S3_BUCKET)".into())
+    })
+}
+
+/// `BACKUP ["list" | "show" "<id>"]`: sin argumentos hace un backup y devuelve su
+/// manifest (ver `BackupManifest::lines`); `list` devuelve los ids guardados y `show`, el
+/// manifest de uno.
+pub struct BackupAction {
+    backups: Option<Arc<BackupService>>,
+}
+
+impl BackupAction {
+    pub fn new(backups: Option<Arc<BackupService>>) -> Self {
+        Self { backups }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for BackupAction {
+    async fn handle(&self, _ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        let backups = service(&self.backups)?;
+        let mut args = tokenize(payload);
+
+        let lines = match args.next().as_deref() {
+            None | Some("") => backups.backup().await?.lines(),
+            Some("list") => backups.list().await?,
+            Some("show") => {
+                let id = args
+                    .next()
+                    .ok_or_else(|| AppError::BadRequest("BACKUP show necesita un id".into()))?;
+                backups.manifest(&id).await?.lines()
+            }
+            Some(other) => {
+                return Err(AppError::BadRequest(format!(
+                    "BACKUP: argumento desconocido '{other}'"
+                )));
+            }
+        };
+        Ok(encode_args(lines.iter().map(String::as_str)))
+    }
+}
+
+/// `RESTORE ["<id>"]`: vuelve a escribir el backup `id` (el último sin id) y devuelve
+/// `id=.. restored=.. expired=.. failed=..`, más `error=..` con el primer rechazo si hubo.
+pub struct RestoreAction {
+    backups: Option<Arc<BackupService>>,
+}
+
+impl RestoreAction {
+    pub fn new(backups: Option<Arc<BackupService>>) -> Self {
+        Self { backups }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for RestoreAction {
+    async fn handle(&self, _ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        let backups = service(&self.backups)?;
+        let id = tokenize(payload).next().filter(|id| !id.is_empty());
+
+        let report = backups.restore(id.as_deref()).await?;
+        let mut lines = vec![report.to_string()];
+        lines.extend(report.first_error.map(|e| format!("error={e}")));
+        Ok(encode_args(lines.iter().map(String::as_str)))
+    }
+}
@@ -0,0 +1,83 @@
+use std::sync::Arc;
+
+use app_net::tokenize;
+use async_trait::async_trait;
+
+use crate::{
+    core::domain::models::AppError,
+    infrastructure::adapters::{
+        controllers::router::{ActionHandler, RequestContext},
+        services::{NodeRule, tcp_network_service::TcpNetworkService},
+    },
+};
+
+fn rule(payload: &str) -> Result<Option<NodeRule>, AppError> {
+    tokenize(payload)
+        .next()
+        .filter(|rule| !rule.is_empty())
+        .map(|rule| rule.parse())
+        .transpose()
+}
+
+fn bans(network: &TcpNetworkService) -> String {
+    let bans: Vec<String> = network
+        .node_access()
+        .bans()
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    format!("bans={}", bans.join(","))
+}
+
+/// `BAN ["<regla>"]`: no deja registrarse más a los nodos que coinciden con la regla (un id,
+/// con `*` como comodín, o una IP o red) y corta a los que ya están conectados. Devuelve
+/// `bans=<regla>,.. disconnected=<id>,..`; sin regla, solo la lista.
+pub struct BanAction {
+    network: Arc<TcpNetworkService>,
+}
+
+impl BanAction {
+    pub fn new(network: Arc<TcpNetworkService>) -> Self {
+        Self { network }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for BanAction {
+    async fn handle(&self, _ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        let kicked = match rule(payload)? {
+            Some(rule) => self.network.ban(rule),
+            None => Vec::new(),
+        };
+        let kicked: Vec<&str> = kicked.iter().map(|id| &**id).collect();
+        Ok(format!(
+            "{} disconnected={}",
+            bans(&self.network),
+            kicked.join(",")
+        ))
+    }
+}
+
+/// `UNBAN "<regla>"`: saca una regla agregada con `BAN` (las de `NODE_DENY` quedan) y
+/// devuelve `bans=<regla>,..`.
+pub struct UnbanAction {
+    network: Arc<TcpNetworkService>,
+}
+
+impl UnbanAction {
+    pub fn new(network: Arc<TcpNetworkService>) -> Self {
+        Self { network }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for UnbanAction {
+    async fn handle(&self, _ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        let rule = rule(payload)?
+            .ok_or_else(|| AppError::BadRequest("UNBAN necesita una regla".into()))?;
+        if !self.network.node_access().unban(&rule) {
+            return Err(AppError::NotFound(format!("no hay un BAN para {rule}")));
+        }
+        Ok(bans(&self.network))
+    }
+}
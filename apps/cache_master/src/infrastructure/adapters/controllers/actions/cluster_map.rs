@@ -0,0 +1,45 @@
+use std::sync::Arc;
+
+use app_net::ClusterMap;
+use async_trait::async_trait;
+
+use crate::{
+    core::domain::models::AppError,
+    infrastructure::{
+        adapters::{
+            controllers::router::{ActionHandler, RequestContext},
+            services::tcp_network_service::TcpNetworkService,
+        },
+        topology_feed::TopologyFeed,
+    },
+};
+
+/// `CLUSTER-MAP`: los shards con sus nodos y el `epoch` del último aviso de `TOPOLOGY`
+/// (ver `app_net::ClusterMap`), para que un cliente arme su vista y después la siga con
+/// `SUBSCRIBE "TOPOLOGY"`.
+pub struct ClusterMapAction {
+    feed: Arc<TopologyFeed>,
+    network: Arc<TcpNetworkService>,
+}
+
+impl ClusterMapAction {
+    pub fn new(feed: Arc<TopologyFeed>, network: Arc<TcpNetworkService>) -> Self {
+        Self { feed, network }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for ClusterMapAction {
+    async fn handle(&self, _ctx: &RequestContext, _payload: &str) -> Result<String, AppError> {
+        // el epoch antes que los shards: el mapa puede quedar adelantado a su epoch, nunca
+        // atrasado, y aplicarle de nuevo los avisos que ya refleja no cambia nada
+        let epoch = self.feed.epoch();
+        let map = self.network.shard_tree().into_iter().fold(
+            ClusterMap::new(epoch),
+            |map, (shard_id, nodes)| {
+                map.with_shard(&*shard_id, nodes.iter().map(|n| n.to_string()))
+            },
+        );
+        Ok(map.to_string())
+    }
+}
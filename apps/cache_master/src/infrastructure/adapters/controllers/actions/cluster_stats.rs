@@ -0,0 +1,47 @@
+use std::sync::Arc;
+
+use app_net::{encode_args, tokenize};
+use async_trait::async_trait;
+
+use crate::{
+    core::domain::models::AppError,
+    infrastructure::adapters::{
+        controllers::router::{ActionHandler, RequestContext},
+        services::stats_aggregation_service::StatsAggregationService,
+    },
+};
+
+/// `CLUSTER-STATS ["refresh"]`: la última vista del cluster que juntó
+/// `StatsAggregationService` (ver `ClusterStats::lines`). Con `refresh`, o si todavía no
+/// hubo ninguna pasada, la junta en el momento.
+pub struct ClusterStatsAction {
+    stats: Arc<StatsAggregationService>,
+}
+
+impl ClusterStatsAction {
+    pub fn new(stats: Arc<StatsAggregationService>) -> Self {
+        Self { stats }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for ClusterStatsAction {
+    async fn handle(&self, _ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        let refresh = match tokenize(payload).next().as_deref() {
+            None | Some("") => false,
+            Some(arg) if arg.eq_ignore_ascii_case("refresh") => true,
+            Some(other) => {
+                return Err(AppError::BadRequest(format!(
+                    "CLUSTER-STATS: argumento desconocido '{other}'"
+                )));
+            }
+        };
+
+        let cluster = match self.stats.latest() {
+            Some(cluster) if !refresh => cluster,
+            _ => self.stats.collect().await,
+        };
+        let lines = cluster.lines();
+        Ok(encode_args(lines.iter().map(String::as_str)))
+    }
+}
@@ -0,0 +1,57 @@
+use std::sync::Arc;
+
+use app_net::tokenize;
+use async_trait::async_trait;
+
+use crate::{
+    core::domain::models::AppError,
+    infrastructure::{
+        adapters::controllers::router::{ActionHandler, RequestContext},
+        live_config::{ConfigKey, LiveConfig},
+    },
+};
+
+/// `CONFIG GET ["<ajuste>"]` | `CONFIG SET "<ajuste>" "<valor>"` (ver `ConfigKey`). `GET`
+/// sin ajuste devuelve todos como `ajuste=valor` separados por espacio; con ajuste, su
+/// valor. `SET` devuelve `ajuste=valor` con cómo quedó.
+pub struct ConfigAction {
+    config: Arc<LiveConfig>,
+}
+
+impl ConfigAction {
+    pub fn new(config: Arc<LiveConfig>) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for ConfigAction {
+    async fn handle(&self, _ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        let mut parts = tokenize(payload);
+        let op = parts.next().unwrap_or_default();
+        let key = parts
+            .next()
+            .map(|key| key.parse::<ConfigKey>())
+            .transpose()?;
+
+        match (&*op, key) {
+            ("GET", None) => Ok(self
+                .config
+                .entries()
+                .into_iter()
+                .map(|(key, value)| format!("{key}={value}"))
+                .collect::<Vec<_>>()
+                .join(" ")),
+            ("GET", Some(key)) => Ok(self.config.get(key)),
+            ("SET", Some(key)) => {
+                let value = parts
+                    .next()
+                    .ok_or_else(|| AppError::BadRequest(format!("CONFIG SET {key} sin valor")))?;
+                Ok(format!("{key}={}", self.config.set(key, &value)?))
+            }
+            _ => Err(AppError::BadRequest(
+                "CONFIG espera GET [ajuste] o SET <ajuste> <valor>".to_string(),
+            )),
+        }
+    }
+}
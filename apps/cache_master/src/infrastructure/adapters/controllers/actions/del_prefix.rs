@@ -0,0 +1,38 @@
+use std::sync::Arc;
+
+use app_net::DelPrefix;
+use async_trait::async_trait;
+use tracing::info;
+
+use crate::{
+    core::domain::models::AppError,
+    infrastructure::adapters::{
+        controllers::router::{ActionHandler, RequestContext},
+        services::tcp_network_service::TcpNetworkService,
+    },
+};
+
+/// `DEL-PREFIX "<prefijo>" ["dry-run"]` (ver `app_net::prefix`): borra en todos los shards
+/// las claves que empiezan con el prefijo y devuelve cuántas eran; con `dry-run` solo las
+/// cuenta.
+pub struct DelPrefixAction {
+    network: Arc<TcpNetworkService>,
+}
+
+impl DelPrefixAction {
+    pub fn new(network: Arc<TcpNetworkService>) -> Self {
+        Self { network }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for DelPrefixAction {
+    async fn handle(&self, ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        let request = DelPrefix::parse(payload)?;
+        let count = self.network.request_del_prefix(&request).await?;
+        if !request.dry_run {
+            info!(prefix = %request.prefix, removed = count, peer = %ctx.peer_id, "DEL-PREFIX");
+        }
+        Ok(count.to_string())
+    }
+}
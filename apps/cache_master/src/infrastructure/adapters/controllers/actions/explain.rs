@@ -0,0 +1,121 @@
+use std::sync::Arc;
+
+use app_net::tokenize;
+use async_trait::async_trait;
+
+use crate::{
+    core::domain::{models::AppError, services::ConsistentHasherService},
+    infrastructure::adapters::{
+        controllers::router::{ActionHandler, RequestContext},
+        services::{
+            FanoutPolicy, dashmap_consistent_hasher_service::DashmapConsistentHasherService,
+            tcp_network_service::TcpNetworkService,
+        },
+    },
+};
+
+/// `EXPLAIN "<clave>"`: por dónde pasa la clave, sin tocar los nodos. Contesta
+///
+/// ```text
+/// hash=<hash> vnode=<nodo>#<i>@<posición> owner=<shard> members=<nodos> shards=<shards>
+/// hot=<shards> read_policy=<política> get=<ruta> write_policy=<política> put=<ruta>
+/// ```
+///
+/// en una línea. `members` son los nodos del shard dueño (el primario primero), `shards`
+/// el dueño y los `REPLICATION_FACTOR - 1` que le siguen en el anillo, y `hot` los shards
+/// entre los que se reparten las lecturas si la clave está copiada por caliente. En las
+/// rutas `/` separa los shards en el orden en que se prueban (`GET`) o se escriben
+/// (`PUT`), `,` los nodos que reciben el request a la vez y `>` los que se suman de a uno.
+/// `-` es una lista vacía.
+pub struct ExplainAction {
+    hasher: Arc<DashmapConsistentHasherService>,
+    network: Arc<TcpNetworkService>,
+    replication_factor: usize,
+}
+
+impl ExplainAction {
+    pub fn new(
+        hasher: Arc<DashmapConsistentHasherService>,
+        network: Arc<TcpNetworkService>,
+        replication_factor: usize,
+    ) -> Self {
+        Self {
+            hasher,
+            network,
+            replication_factor: replication_factor.max(1),
+        }
+    }
+
+    fn route(&self, shards: &[String], policy: FanoutPolicy) -> String {
+        let sep = if policy.is_sequential() { ">" } else { "," };
+        let route: Vec<String> = shards
+            .iter()
+            .map(|shard| {
+                let nodes: Vec<String> = self
+                    .network
+                    .get_all_nodes(shard)
+                    .iter()
+                    .map(|node| node.node_id.to_string())
+                    .collect();
+                list(&nodes, sep)
+            })
+            .collect();
+        list(&route, "/")
+    }
+}
+
+fn list(items: &[impl AsRef<str>], sep: &str) -> String {
+    if items.is_empty() {
+        return "-".to_string();
+    }
+    items
+        .iter()
+        .map(AsRef::as_ref)
+        .collect::<Vec<_>>()
+        .join(sep)
+}
+
+#[async_trait]
+impl ActionHandler for ExplainAction {
+    async fn handle(&self, _ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        let key = tokenize(payload).next().unwrap_or_default();
+        if key.is_empty() {
+            return Err(AppError::BadRequest("Key is empty".to_string()));
+        }
+
+        let hash = self.hasher.create_hash(&key);
+        let shards = self
+            .hasher
+            .get_node_ids_from_hash(&hash, self.replication_factor);
+        let (Some(owner), Some((position, vnode))) = (shards.first(), self.hasher.vnode_of(&hash))
+        else {
+            return Err(AppError::NodeNotFound(format!(
+                "No node found for key {key}"
+            )));
+        };
+
+        let members: Vec<String> = self
+            .network
+            .get_all_nodes(owner)
+            .iter()
+            .map(|node| node.node_id.to_string())
+            .collect();
+        let hot = self
+            .network
+            .hot_key_copies()
+            .shards(&key)
+            .unwrap_or_default();
+        let read_policy = self.network.read_policy();
+        let write_policy = self.network.write_policy();
+
+        Ok(format!(
+            "hash={hash} vnode={vnode}@{position:016x} owner={owner} members={} shards={} \
+             hot={} read_policy={read_policy} get={} write_policy={write_policy} put={}",
+            list(&members, ","),
+            list(&shards, ","),
+            list(&hot, ","),
+            self.route(&shards, read_policy),
+            self.route(&shards, write_policy),
+        ))
+    }
+}
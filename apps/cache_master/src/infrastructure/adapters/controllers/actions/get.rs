@@ -0,0 +1,102 @@
+use std::sync::Arc;
+
+use app_core::UseCaseValidatable;
+use app_net::{
+    ResponseBody, encode_args, take_if_not_version, take_read_node, take_refresh, take_stale,
+    tokenize,
+};
+use async_trait::async_trait;
+
+use crate::{
+    core::{
+        domain::models::{AppError, usecases::GetKeyUseCaseInput},
+        usecases::MemoizedGetKeyUseCase,
+    },
+    infrastructure::{
+        adapters::controllers::router::{ActionHandler, RequestContext},
+        metrics::MasterMetrics,
+    },
+};
+
+/// `GET "<clave>" ["refresh=<ventana>" | "stale=<máximo>" | IF-NOT-VERSION <versión>]`; con
+/// ventana responde `"<valor>" 1|0` y con máximo `"<valor>" 1|0 <ms desde que venció>`
+/// (ver `app_net::refresh`), o vacío si la clave no existe. Con versión
+/// la respuesta la trae junto al código, y es un `304` sin valor si la clave no cambió
+/// (ver `app_net::conditional`). Con `node=<id>` la lectura simple va a ese nodo del shard
+/// (ver `app_net::sticky`).
+pub struct GetAction {
+    get_key_use_case: Arc<MemoizedGetKeyUseCase>,
+    metrics: Arc<MasterMetrics>,
+}
+
+impl GetAction {
+    pub fn new(get_key_use_case: Arc<MemoizedGetKeyUseCase>, metrics: Arc<MasterMetrics>) -> Self {
+        Self {
+            get_key_use_case,
+            metrics,
+        }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for GetAction {
+    async fn handle(&self, ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        self.respond(ctx, payload).await.map(|body| body.payload())
+    }
+
+    async fn respond(
+        &self,
+        _ctx: &RequestContext,
+        payload: &str,
+    ) -> Result<ResponseBody, AppError> {
+        let mut args: Vec<_> = tokenize(payload).collect();
+        let if_not_version = take_if_not_version(&mut args)?;
+        let refresh_ms = take_refresh(&mut args)?;
+        let stale_ms = take_stale(&mut args)?;
+        let read_from = take_read_node(&mut args)?;
+        let key = args.into_iter().next().unwrap_or_default().to_string();
+        self.metrics.observe_read(&key);
+
+        let response = self
+            .get_key_use_case
+            .validate_and_execute(GetKeyUseCaseInput {
+                key,
+                refresh_ms,
+                if_not_version,
+                stale_ms,
+                read_from,
+            })
+            .await?;
+
+        if !response.success {
+            return Err(AppError::NotFound("Key not found".to_string()));
+        }
+
+        if let (Some(stale_for), Some(refresh)) = (response.stale_for, response.refresh)
+            && !response.result.is_empty()
+        {
+            return Ok(ResponseBody::Value(encode_args([
+                response.result.as_str(),
+                if refresh { "1" } else { "0" },
+                &stale_for.to_string(),
+            ])));
+        }
+
+        match (response.version, response.refresh) {
+            (Some(version), _) if response.not_modified => {
+                Ok(ResponseBody::NotModified { version })
+            }
+            (Some(version), _) => Ok(ResponseBody::Versioned {
+                value: response.result,
+                version,
+            }),
+            (None, Some(refresh)) if !response.result.is_empty() => {
+                Ok(ResponseBody::Value(encode_args([
+                    response.result.as_str(),
+                    if refresh { "1" } else { "0" },
+                ])))
+            }
+            _ => Ok(ResponseBody::Value(response.result)),
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use std::sync::Arc;
+
+use app_net::{encode_args, tokenize};
+use async_trait::async_trait;
+
+use crate::{
+    core::domain::models::AppError,
+    infrastructure::adapters::{
+        controllers::router::{ActionHandler, RequestContext},
+        services::ImportService,
+    },
+};
+
+/// `IMPORT "<archivo>"`: carga un dump RDB o un export NDJSON de `IMPORT_DIR` (ver
+/// `ImportService`) y devuelve `file=.. format=.. imported=.. expired=.. skipped=..
+/// failed=..`, más `error=..` con el primer rechazo si hubo.
+pub struct ImportAction {
+    imports: Option<Arc<ImportService>>,
+}
+
+impl ImportAction {
+    pub fn new(imports: Option<Arc<ImportService>>) -> Self {
+        Self { imports }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for ImportAction {
+    async fn handle(&self, _ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        let imports = self
+            .imports
+            .as_deref()
+            .ok_or_else(|| AppError::BadRequest("no hay IMPORT_DIR configurado".into()))?;
+        let file = tokenize(payload)
+            .next()
+            .filter(|file| !file.is_empty())
+            .ok_or_else(|| AppError::BadRequest("IMPORT necesita un archivo".into()))?;
+
+        let report = imports.import(&file).await?;
+        let mut lines = vec![report.to_string()];
+        lines.extend(report.first_error.map(|e| format!("error={e}")));
+        Ok(encode_args(lines.iter().map(String::as_str)))
+    }
+}
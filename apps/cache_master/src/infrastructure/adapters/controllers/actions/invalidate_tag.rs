@@ -0,0 +1,37 @@
+use std::sync::Arc;
+
+use app_net::tokenize;
+use async_trait::async_trait;
+
+use crate::{
+    core::domain::models::AppError,
+    infrastructure::adapters::{
+        controllers::router::{ActionHandler, RequestContext},
+        services::tcp_network_service::TcpNetworkService,
+    },
+};
+
+/// `INVALIDATE-TAG "<tag>"`: borra en todos los shards las claves escritas con ese tag
+/// (`PUT ... "tags=<tag>"`) y devuelve cuántas eran.
+pub struct InvalidateTagAction {
+    network: Arc<TcpNetworkService>,
+}
+
+impl InvalidateTagAction {
+    pub fn new(network: Arc<TcpNetworkService>) -> Self {
+        Self { network }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for InvalidateTagAction {
+    async fn handle(&self, _ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        let tag = tokenize(payload).next().unwrap_or_default();
+        if tag.is_empty() {
+            return Err(AppError::BadRequest("INVALIDATE-TAG sin tag".to_string()));
+        }
+
+        let removed = self.network.request_invalidate_tag(&tag).await?;
+        Ok(removed.to_string())
+    }
+}
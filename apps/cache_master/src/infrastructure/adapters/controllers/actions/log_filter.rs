@@ -0,0 +1,58 @@
+use std::sync::Arc;
+
+use app_net::tokenize;
+use async_trait::async_trait;
+
+use crate::{
+    core::domain::models::AppError,
+    infrastructure::adapters::{
+        controllers::router::{ActionHandler, RequestContext},
+        services::tcp_network_service::TcpNetworkService,
+    },
+};
+
+/// `LOG-FILTER "<filtro>" ["master" | "*" | "<node_id>"]`. Con filtro vacío solo lee.
+/// `*` aplica en el master y en todos los nodos y devuelve `id=filtro` de cada uno.
+pub struct LogFilterAction {
+    network: Arc<TcpNetworkService>,
+}
+
+impl LogFilterAction {
+    pub fn new(network: Arc<TcpNetworkService>) -> Self {
+        Self { network }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for LogFilterAction {
+    async fn handle(&self, _ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        let mut parts = tokenize(payload);
+        let filter = parts.next().unwrap_or_default();
+        let target = parts.next().unwrap_or("master".into());
+
+        match &*target {
+            "master" => Ok(app_logging::global()?.apply(&filter)?),
+            "*" => {
+                let mut out = vec![format!("master={}", app_logging::global()?.apply(&filter)?)];
+
+                for (node_id, res) in self.network.request_log_filter(None, &filter).await? {
+                    match res {
+                        Ok(current) => out.push(format!("{node_id}={current}")),
+                        Err(e) => out.push(format!("{node_id}=ERROR {e}")),
+                    }
+                }
+
+                Ok(out.join("; "))
+            }
+            node_id => {
+                let (_, res) = self
+                    .network
+                    .request_log_filter(Some(node_id), &filter)
+                    .await?
+                    .pop()
+                    .ok_or_else(|| AppError::NodeNotFound(node_id.to_string()))?;
+                res
+            }
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use app_net::tokenize;
+use async_trait::async_trait;
+
+use crate::{
+    core::domain::{models::AppError, services::ConsistentHasherService},
+    infrastructure::adapters::{
+        controllers::router::{ActionHandler, RequestContext},
+        services::{
+            dashmap_consistent_hasher_service::DashmapConsistentHasherService,
+            tcp_network_service::TcpNetworkService,
+        },
+    },
+};
+
+/// `META "<clave>"`: `id=metadatos` de cada nodo del shard de la clave, separados por `; `.
+pub struct MetaAction {
+    hasher: Arc<DashmapConsistentHasherService>,
+    network: Arc<TcpNetworkService>,
+}
+
+impl MetaAction {
+    pub fn new(
+        hasher: Arc<DashmapConsistentHasherService>,
+        network: Arc<TcpNetworkService>,
+    ) -> Self {
+        Self { hasher, network }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for MetaAction {
+    async fn handle(&self, _ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        let key = tokenize(payload).next().unwrap_or_default();
+        if key.is_empty() {
+            return Err(AppError::BadRequest("Key is empty".to_string()));
+        }
+
+        let hash = self.hasher.create_hash(&key);
+        let shard_id = self
+            .hasher
+            .get_node_id_from_hash(&hash)
+            .ok_or_else(|| AppError::NodeNotFound(format!("No node found for key {key}")))?;
+
+        let out: Vec<String> = self
+            .network
+            .request_key_meta(&shard_id, &key)
+            .await
+            .into_iter()
+            .map(|(node_id, res)| match res {
+                Ok(meta) => format!("{node_id}={meta}"),
+                Err(e) => format!("{node_id}=ERROR {e}"),
+            })
+            .collect();
+
+        Ok(out.join("; "))
+    }
+}
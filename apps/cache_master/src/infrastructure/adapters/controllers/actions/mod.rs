@@ -0,0 +1,290 @@
+//! Acciones del protocolo de clientes, una por módulo. Se registran con su política en
+//! `register_actions`, que llama `CacheMasterModule` al armar el router.
+
+pub mod auth;
+pub mod backup;
+pub mod ban;
+pub mod cluster_map;
+pub mod cluster_stats;
+pub mod config;
+pub mod del_prefix;
+pub mod explain;
+pub mod get;
+pub mod import;
+pub mod invalidate_tag;
+pub mod log_filter;
+pub mod meta;
+pub mod monitor;
+pub mod multi;
+pub mod node_config;
+pub mod peek;
+pub mod peer;
+pub mod ping;
+pub mod put;
+pub mod read_only;
+pub mod rename;
+pub mod set_role;
+pub mod slow_log;
+pub mod stats;
+pub mod subscribe;
+pub mod top_keys;
+pub mod write_retries;
+
+use std::sync::Arc;
+
+pub use self::auth::AuthAction;
+pub use self::backup::{BackupAction, RestoreAction};
+pub use self::ban::{BanAction, UnbanAction};
+pub use self::cluster_map::ClusterMapAction;
+pub use self::cluster_stats::ClusterStatsAction;
+pub use self::config::ConfigAction;
+pub use self::del_prefix::DelPrefixAction;
+pub use self::explain::ExplainAction;
+pub use self::get::GetAction;
+pub use self::import::ImportAction;
+pub use self::invalidate_tag::InvalidateTagAction;
+pub use self::log_filter::LogFilterAction;
+pub use self::meta::MetaAction;
+pub use self::monitor::MonitorAction;
+pub use self::multi::MultiAction;
+pub use self::node_config::NodeConfigAction;
+pub use self::peek::PeekAction;
+pub use self::peer::{PeerForwardAction, PeerHelloAction, PeerStateAction, RaftAction};
+pub use self::ping::PingAction;
+pub use self::put::PutAction;
+pub use self::read_only::ReadOnlyAction;
+pub use self::rename::RenameAction;
+pub use self::set_role::SetRoleAction;
+pub use self::slow_log::SlowLogAction;
+pub use self::stats::StatsAction;
+pub use self::subscribe::{SubscribeAction, UnsubscribeAction};
+pub use self::top_keys::TopKeysAction;
+pub use self::write_retries::WriteRetriesAction;
+
+use crate::{
+    core::usecases::{MemoizedGetKeyUseCase, MultiUseCase, PutKeyUseCase, RenameUseCase},
+    infrastructure::{
+        adapters::{
+            controllers::router::{ActionPolicy, ActionRouter},
+            services::{
+                BackupService, ImportService,
+                dashmap_consistent_hasher_service::DashmapConsistentHasherService,
+                stats_aggregation_service::StatsAggregationService,
+                tcp_network_service::TcpNetworkService,
+            },
+        },
+        clock_skew::ClockSkews,
+        live_config::LiveConfig,
+        metrics::MasterMetrics,
+        monitor::MasterMonitor,
+        peers::{PEER_FORWARD, PEER_HELLO, PEER_STATE, PeerCoordinator},
+        raft::{RAFT_APPEND, RAFT_SNAPSHOT, RAFT_VOTE},
+        topology_feed::TopologyFeed,
+        write_retries::WriteRetries,
+    },
+};
+
+/// Lo que necesitan las acciones de base.
+pub struct ActionDeps {
+    pub metrics: Arc<MasterMetrics>,
+    pub live_config: Arc<LiveConfig>,
+    pub monitor: Arc<MasterMonitor>,
+    pub topology_feed: Arc<TopologyFeed>,
+    pub peers: Arc<PeerCoordinator>,
+    pub hasher: Arc<DashmapConsistentHasherService>,
+    pub network: Arc<TcpNetworkService>,
+    pub stats_aggregation: Arc<StatsAggregationService>,
+    pub clock_skews: Arc<ClockSkews>,
+    pub write_retries: Arc<WriteRetries>,
+    /// En cuántos shards se escribe cada clave (ver `EXPLAIN`).
+    pub replication_factor: usize,
+    /// `None` sin destino de backups: `BACKUP` y `RESTORE` responden error.
+    pub backups: Option<Arc<BackupService>>,
+    /// `None` sin `IMPORT_DIR`: `IMPORT` responde error.
+    pub imports: Option<Arc<ImportService>>,
+    pub get_key_use_case: Arc<MemoizedGetKeyUseCase>,
+    pub put_key_use_case: Arc<PutKeyUseCase>,
+    pub multi_use_case: Arc<MultiUseCase>,
+    pub rename_use_case: Arc<RenameUseCase>,
+    pub admin_token: Option<String>,
+}
+
+pub fn register_actions(router: &mut ActionRouter, deps: ActionDeps) {
+    router
+        .route("PING", ActionPolicy::open("PING"), PingAction)
+        .route(
+            "AUTH",
+            ActionPolicy::open("AUTH"),
+            AuthAction::new(deps.admin_token),
+        )
+        .route(
+            "PUT",
+            ActionPolicy::data("PUT"),
+            PutAction::new(deps.put_key_use_case, deps.metrics.clone()),
+        )
+        .route(
+            "GET",
+            ActionPolicy::data("GET"),
+            GetAction::new(deps.get_key_use_case, deps.metrics.clone()),
+        )
+        .route(
+            "MULTI",
+            ActionPolicy::data("MULTI"),
+            MultiAction::new(deps.multi_use_case, deps.metrics.clone()),
+        )
+        .route(
+            "RENAME",
+            ActionPolicy::data("RENAME"),
+            RenameAction::new(deps.rename_use_case, deps.metrics),
+        )
+        .route(
+            "PEEK",
+            ActionPolicy::data("PEEK"),
+            PeekAction::new(deps.hasher.clone(), deps.network.clone()),
+        )
+        .route(
+            "SUBSCRIBE",
+            ActionPolicy::data("SUBSCRIBE"),
+            SubscribeAction::new(deps.topology_feed.clone()),
+        )
+        .route(
+            "UNSUBSCRIBE",
+            ActionPolicy::data("UNSUBSCRIBE"),
+            UnsubscribeAction::new(deps.topology_feed.clone()),
+        )
+        .route(
+            "CLUSTER-MAP",
+            ActionPolicy::data("CLUSTER-MAP"),
+            ClusterMapAction::new(deps.topology_feed, deps.network.clone()),
+        )
+        .route(
+            "INVALIDATE-TAG",
+            ActionPolicy::data("INVALIDATE-TAG"),
+            InvalidateTagAction::new(deps.network.clone()),
+        )
+        .route(
+            "DEL-PREFIX",
+            ActionPolicy::admin("DEL-PREFIX"),
+            DelPrefixAction::new(deps.network.clone()),
+        )
+        .route(
+            "META",
+            ActionPolicy::admin("META"),
+            MetaAction::new(deps.hasher.clone(), deps.network.clone()),
+        )
+        .route(
+            "EXPLAIN",
+            ActionPolicy::admin("EXPLAIN"),
+            ExplainAction::new(deps.hasher, deps.network.clone(), deps.replication_factor),
+        )
+        .route(
+            "LOG-FILTER",
+            ActionPolicy::admin("LOG-FILTER"),
+            LogFilterAction::new(deps.network.clone()),
+        )
+        .route(
+            "SET-ROLE",
+            ActionPolicy::admin("SET-ROLE"),
+            SetRoleAction::new(deps.network.clone()),
+        )
+        .route(
+            "READ-ONLY",
+            ActionPolicy::admin("READ-ONLY"),
+            ReadOnlyAction::new(deps.network.clone()),
+        )
+        .route(
+            "CONFIG",
+            ActionPolicy::admin("CONFIG"),
+            ConfigAction::new(deps.live_config),
+        )
+        .route(
+            "NODE-CONFIG",
+            ActionPolicy::admin("NODE-CONFIG"),
+            NodeConfigAction::new(deps.network.clone()),
+        )
+        .route(
+            "BAN",
+            ActionPolicy::admin("BAN"),
+            BanAction::new(deps.network.clone()),
+        )
+        .route(
+            "UNBAN",
+            ActionPolicy::admin("UNBAN"),
+            UnbanAction::new(deps.network.clone()),
+        )
+        .route(
+            "WRITE-RETRIES",
+            ActionPolicy::admin("WRITE-RETRIES"),
+            WriteRetriesAction::new(deps.write_retries),
+        )
+        .route(
+            "SLOWLOG",
+            ActionPolicy::admin("SLOWLOG"),
+            SlowLogAction::new(deps.network.clone()),
+        )
+        .route(
+            "TOPKEYS",
+            ActionPolicy::admin("TOPKEYS"),
+            TopKeysAction::new(deps.network.clone()),
+        )
+        .route(
+            "STATS",
+            ActionPolicy::admin("STATS"),
+            StatsAction::new(deps.network.clone(), deps.clock_skews),
+        )
+        .route(
+            "CLUSTER-STATS",
+            ActionPolicy::admin("CLUSTER-STATS"),
+            ClusterStatsAction::new(deps.stats_aggregation),
+        )
+        .route(
+            "BACKUP",
+            ActionPolicy::admin("BACKUP"),
+            BackupAction::new(deps.backups.clone()),
+        )
+        .route(
+            "RESTORE",
+            ActionPolicy::admin("RESTORE"),
+            RestoreAction::new(deps.backups),
+        )
+        .route(
+            "IMPORT",
+            ActionPolicy::admin("IMPORT"),
+            ImportAction::new(deps.imports),
+        )
+        .route(
+            "MONITOR",
+            ActionPolicy::admin("MONITOR"),
+            MonitorAction::new(deps.monitor, deps.network),
+        )
+        .route(
+            PEER_HELLO,
+            ActionPolicy::peer(PEER_HELLO),
+            PeerHelloAction::new(deps.peers.clone()),
+        )
+        .route(
+            PEER_FORWARD,
+            ActionPolicy::peer(PEER_FORWARD),
+            PeerForwardAction::new(deps.peers.clone()),
+        )
+        .route(
+            PEER_STATE,
+            ActionPolicy::peer(PEER_STATE),
+            PeerStateAction::new(deps.peers.clone()),
+        )
+        .route(
+            RAFT_VOTE,
+            ActionPolicy::peer(RAFT_VOTE),
+            RaftAction::new(RAFT_VOTE, deps.peers.clone()),
+        )
+        .route(
+            RAFT_APPEND,
+            ActionPolicy::peer(RAFT_APPEND),
+            RaftAction::new(RAFT_APPEND, deps.peers.clone()),
+        )
+        .route(
+            RAFT_SNAPSHOT,
+            ActionPolicy::peer(RAFT_SNAPSHOT),
+            RaftAction::new(RAFT_SNAPSHOT, deps.peers),
+        );
+}
@@ -0,0 +1,68 @@
+use std::{sync::Arc, time::Duration};
+
+use app_net::{MonitorOptions, tokenize};
+use async_trait::async_trait;
+use tokio::time::Instant;
+
+use crate::{
+    core::domain::models::AppError,
+    infrastructure::{
+        adapters::{
+            controllers::router::{ActionHandler, RequestContext},
+            services::tcp_network_service::TcpNetworkService,
+        },
+        monitor::MasterMonitor,
+    },
+};
+
+/// `MONITOR ["master" | "<node_id>"] [secs=<n>] [sample=<r>] [redact]`: desde ahí la
+/// conexión recibe un `EVT MONITOR` por cada comando que procese el master o ese nodo,
+/// hasta que venzan los segundos (60 por defecto). Ver `app_net::MonitorOptions`.
+pub struct MonitorAction {
+    monitor: Arc<MasterMonitor>,
+    network: Arc<TcpNetworkService>,
+}
+
+impl MonitorAction {
+    pub fn new(monitor: Arc<MasterMonitor>, network: Arc<TcpNetworkService>) -> Self {
+        Self { monitor, network }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for MonitorAction {
+    async fn handle(&self, ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        let socket = ctx
+            .socket()
+            .ok_or_else(|| AppError::BadRequest("MONITOR sin conexión".to_string()))?;
+
+        let mut args: Vec<_> = tokenize(payload).collect();
+        let target = match args.first() {
+            Some(first) if !first.contains('=') && first != "redact" => args.remove(0),
+            _ => "master".into(),
+        };
+        let options = MonitorOptions::parse(&args)?;
+
+        if target == "master" {
+            self.monitor.local().subscribe(socket.clone(), options);
+            return Ok("OK".to_string());
+        }
+
+        // el nodo manda su stream hasta que termine la suscripción más larga sobre él
+        let hub = self.monitor.node(&target);
+        let remaining = hub
+            .watched_until()
+            .map(|until| until.saturating_duration_since(Instant::now()))
+            .unwrap_or_default();
+        let node_options = MonitorOptions {
+            duration: options
+                .duration
+                .max(Duration::from_secs(remaining.as_secs_f64().ceil() as u64)),
+            ..options
+        };
+        self.network.request_monitor(&target, node_options).await?;
+
+        hub.subscribe(socket.clone(), options);
+        Ok("OK".to_string())
+    }
+}
@@ -0,0 +1,49 @@
+use std::sync::Arc;
+
+use app_core::UseCaseValidatable;
+use app_net::{encode_args, parse_multi};
+use async_trait::async_trait;
+
+use crate::{
+    core::{
+        domain::models::{AppError, usecases::MultiUseCaseInput},
+        usecases::MultiUseCase,
+    },
+    infrastructure::{
+        adapters::controllers::router::{ActionHandler, RequestContext},
+        metrics::MasterMetrics,
+    },
+};
+
+/// `MULTI "<comando>"...` (ver `app_net::tx`). Responde un argumento por comando que no
+/// sea `WATCH`; si un `WATCH` falla no se aplica nada y se responde 409.
+pub struct MultiAction {
+    multi_use_case: Arc<MultiUseCase>,
+    metrics: Arc<MasterMetrics>,
+}
+
+impl MultiAction {
+    pub fn new(multi_use_case: Arc<MultiUseCase>, metrics: Arc<MasterMetrics>) -> Self {
+        Self {
+            multi_use_case,
+            metrics,
+        }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for MultiAction {
+    async fn handle(&self, _ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        let commands = parse_multi(payload)?;
+        for command in &commands {
+            self.metrics.observe_key(command.key());
+        }
+
+        let response = self
+            .multi_use_case
+            .validate_and_execute(MultiUseCaseInput { commands })
+            .await?;
+
+        Ok(encode_args(response.results.iter().map(String::as_str)))
+    }
+}
@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use app_net::{encode_args, tokenize};
+use async_trait::async_trait;
+
+use crate::{
+    core::domain::models::AppError,
+    infrastructure::adapters::{
+        controllers::router::{ActionHandler, RequestContext},
+        services::tcp_network_service::TcpNetworkService,
+    },
+};
+
+/// `NODE-CONFIG "<node_id>" | "*" ["GET" ["<ajuste>"] | "SET" "<ajuste>" "<valor>"]`: el
+/// `CONFIG` de un nodo o de todos (`capacity` y `max-memory`). Con `*` devuelve
+/// `id=respuesta` de cada uno separados por `; `. Después de un `SET` el master vuelve a
+/// leer la memoria de esos nodos, así un tope nuevo frena o libera sus `PUT` enseguida.
+pub struct NodeConfigAction {
+    network: Arc<TcpNetworkService>,
+}
+
+impl NodeConfigAction {
+    pub fn new(network: Arc<TcpNetworkService>) -> Self {
+        Self { network }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for NodeConfigAction {
+    async fn handle(&self, _ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        let mut parts = tokenize(payload);
+        let target = parts.next().unwrap_or_default();
+        if target.is_empty() {
+            return Err(AppError::BadRequest("NODE-CONFIG sin node_id".to_string()));
+        }
+        let rest: Vec<_> = parts.collect();
+        let refresh = rest.first().is_some_and(|op| op == "SET");
+        let args = if rest.is_empty() {
+            "GET".to_string()
+        } else {
+            encode_args(rest.iter().map(|a| a.as_ref()))
+        };
+
+        let node_id = (target != "*").then_some(&*target);
+        let mut results = self
+            .network
+            .request_node_config(node_id, &args, refresh)
+            .await?;
+        if node_id.is_some() {
+            let (_, res) = results
+                .pop()
+                .ok_or_else(|| AppError::NodeNotFound(target.to_string()))?;
+            return res;
+        }
+
+        Ok(results
+            .into_iter()
+            .map(|(node_id, res)| match res {
+                Ok(current) => format!("{node_id}={current}"),
+                Err(e) => format!("{node_id}=ERROR {e}"),
+            })
+            .collect::<Vec<_>>()
+            .join("; "))
+    }
+}
@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use app_net::tokenize;
+use async_trait::async_trait;
+
+use crate::{
+    core::domain::{models::AppError, services::ConsistentHasherService},
+    infrastructure::adapters::{
+        controllers::router::{ActionHandler, RequestContext},
+        services::{
+            dashmap_consistent_hasher_service::DashmapConsistentHasherService,
+            tcp_network_service::TcpNetworkService,
+        },
+    },
+};
+
+/// `PEEK "<clave>"`: el valor como en `GET`, pero sin contar la lectura en las métricas de
+/// claves calientes ni en el orden de desalojo de los nodos.
+pub struct PeekAction {
+    hasher: Arc<DashmapConsistentHasherService>,
+    network: Arc<TcpNetworkService>,
+}
+
+impl PeekAction {
+    pub fn new(
+        hasher: Arc<DashmapConsistentHasherService>,
+        network: Arc<TcpNetworkService>,
+    ) -> Self {
+        Self { hasher, network }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for PeekAction {
+    async fn handle(&self, _ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        let key = tokenize(payload).next().unwrap_or_default();
+        if key.is_empty() {
+            return Err(AppError::BadRequest("Key is empty".to_string()));
+        }
+
+        let hash = self.hasher.create_hash(&key);
+        let shard_id = self
+            .hasher
+            .get_node_id_from_hash(&hash)
+            .ok_or_else(|| AppError::NodeNotFound(format!("No node found for key {key}")))?;
+
+        self.network
+            .request_peek_key(&shard_id, &key)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Key not found".to_string()))
+    }
+}
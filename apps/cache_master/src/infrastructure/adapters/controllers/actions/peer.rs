@@ -0,0 +1,97 @@
+use std::sync::Arc;
+
+use app_net::tokenize;
+use async_trait::async_trait;
+
+use crate::{
+    core::domain::models::AppError,
+    infrastructure::{
+        adapters::controllers::router::{ActionHandler, RequestContext},
+        peers::PeerCoordinator,
+    },
+};
+
+/// `PEER-HELLO`: el `MASTER_ID` de este master, para que el otro sepa a quién le habla.
+pub struct PeerHelloAction {
+    peers: Arc<PeerCoordinator>,
+}
+
+impl PeerHelloAction {
+    pub fn new(peers: Arc<PeerCoordinator>) -> Self {
+        Self { peers }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for PeerHelloAction {
+    async fn handle(&self, _ctx: &RequestContext, _payload: &str) -> Result<String, AppError> {
+        Ok(self.peers.config().master_id.clone())
+    }
+}
+
+/// `PEER-FORWARD "<nodo>" "<shard>"`: un alta que otro master le pasa al líder. Contesta
+/// `OK <índice>` o `CONFLICT <índice>` (el nodo ya tiene otro shard vivo), con el índice
+/// desde el que se ve la decisión; `NoQuorum` si este master no es el líder o no juntó
+/// la mayoría.
+pub struct PeerForwardAction {
+    peers: Arc<PeerCoordinator>,
+}
+
+impl PeerForwardAction {
+    pub fn new(peers: Arc<PeerCoordinator>) -> Self {
+        Self { peers }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for PeerForwardAction {
+    async fn handle(&self, _ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        let mut args = tokenize(payload);
+        let (Some(node_id), Some(shard_id)) = (args.next(), args.next()) else {
+            return Err(AppError::BadRequest(
+                "uso: PEER-FORWARD \"<nodo>\" \"<shard>\"".to_string(),
+            ));
+        };
+        let (accepted, index) = self.peers.lead_join(&node_id, &shard_id).await?;
+        let outcome = if accepted { "OK" } else { "CONFLICT" };
+        Ok(format!("{outcome} {index}"))
+    }
+}
+
+/// `PEER-STATE`: las altas acordadas que ya aplicó este master (ver
+/// `raft::TopologyLedger`). La contesta cualquiera, no solo el líder.
+pub struct PeerStateAction {
+    peers: Arc<PeerCoordinator>,
+}
+
+impl PeerStateAction {
+    pub fn new(peers: Arc<PeerCoordinator>) -> Self {
+        Self { peers }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for PeerStateAction {
+    async fn handle(&self, _ctx: &RequestContext, _payload: &str) -> Result<String, AppError> {
+        Ok(self.peers.ledger().to_string())
+    }
+}
+
+/// `RAFT-VOTE`, `RAFT-APPEND` y `RAFT-SNAPSHOT` (ver `raft::messages`).
+pub struct RaftAction {
+    action: &'static str,
+    peers: Arc<PeerCoordinator>,
+}
+
+impl RaftAction {
+    pub fn new(action: &'static str, peers: Arc<PeerCoordinator>) -> Self {
+        Self { action, peers }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for RaftAction {
+    async fn handle(&self, _ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        self.peers.handle_rpc(self.action, payload).await
+    }
+}
@@ -0,0 +1,15 @@
+use async_trait::async_trait;
+
+use crate::{
+    core::domain::models::AppError,
+    infrastructure::adapters::controllers::router::{ActionHandler, RequestContext},
+};
+
+pub struct PingAction;
+
+#[async_trait]
+impl ActionHandler for PingAction {
+    async fn handle(&self, _ctx: &RequestContext, _payload: &str) -> Result<String, AppError> {
+        Ok(String::from("PONG"))
+    }
+}
@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use app_core::UseCaseValidatable;
+use app_net::{
+    PutCondition, encode_args, parse_millis, take_idempotency, take_sticky, take_tags, tokenize,
+};
+use async_trait::async_trait;
+
+use crate::{
+    core::{
+        domain::models::{AppError, usecases::PutKeyUseCaseInput},
+        usecases::PutKeyUseCase,
+    },
+    infrastructure::{
+        adapters::controllers::router::{ActionHandler, RequestContext},
+        metrics::MasterMetrics,
+    },
+};
+
+/// `PUT "<clave>" "<valor>" ["<ttl>"] ["tags=<a,b>"] ["idem=<token>"] ["IF" "<condición>"]`,
+/// con el TTL relativo (ver `app_net::ttl`), los tags de `app_net::tags`, el token de
+/// `app_net::idempotency` y la condición de `app_net::PutCondition`. Con `sticky=1` responde
+/// `"OK" "<nodo>"`, el nodo que confirmó la escritura (ver `app_net::sticky`).
+pub struct PutAction {
+    put_key_use_case: Arc<PutKeyUseCase>,
+    metrics: Arc<MasterMetrics>,
+}
+
+impl PutAction {
+    pub fn new(put_key_use_case: Arc<PutKeyUseCase>, metrics: Arc<MasterMetrics>) -> Self {
+        Self {
+            put_key_use_case,
+            metrics,
+        }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for PutAction {
+    async fn handle(&self, _ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        let mut parts: Vec<_> = tokenize(payload).collect();
+        let condition = PutCondition::take_from(&mut parts)?;
+        let idempotency = take_idempotency(&mut parts, 2)?;
+        let sticky = take_sticky(&mut parts, 2)?;
+        let tags = take_tags(&mut parts)?;
+        let mut parts = parts.into_iter();
+        let key = parts.next().unwrap_or_default().to_string();
+        let value = parts.next().unwrap_or_default().to_string();
+        let ttl_ms = parts.next().map(|s| parse_millis(&s)).transpose()?;
+        self.metrics.observe_key(&key);
+
+        let response = self
+            .put_key_use_case
+            .validate_and_execute(PutKeyUseCaseInput {
+                key,
+                value,
+                ttl_ms,
+                tags,
+                condition,
+                idempotency,
+            })
+            .await?;
+
+        if !response.success {
+            return Err(AppError::BadRequest("Failed to put key".to_string()));
+        }
+
+        match response.acked_by.filter(|_| sticky) {
+            Some(node_id) => Ok(encode_args(["OK", node_id.as_str()])),
+            None => Ok("OK".to_string()),
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use app_net::tokenize;
+use async_trait::async_trait;
+
+use crate::{
+    core::domain::models::AppError,
+    infrastructure::adapters::{
+        controllers::router::{ActionHandler, RequestContext},
+        services::tcp_network_service::TcpNetworkService,
+    },
+};
+
+/// `READ-ONLY ["on" | "off"] ["<node_id>"]`: sin nodo cambia el del cluster; sin nada solo
+/// lee. Devuelve `cluster=on|off nodes=<id>,..`.
+pub struct ReadOnlyAction {
+    network: Arc<TcpNetworkService>,
+}
+
+impl ReadOnlyAction {
+    pub fn new(network: Arc<TcpNetworkService>) -> Self {
+        Self { network }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for ReadOnlyAction {
+    async fn handle(&self, _ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        let mut parts = tokenize(payload);
+        if let Some(switch) = parts.next() {
+            let on = match &*switch {
+                "on" => true,
+                "off" => false,
+                other => {
+                    return Err(AppError::BadRequest(format!(
+                        "READ-ONLY espera on u off: {other}"
+                    )));
+                }
+            };
+            let node_id = parts.next();
+            self.network.set_read_only(node_id.as_deref(), on)?;
+        }
+
+        let switches = self.network.read_only();
+        let nodes = switches.nodes();
+        let nodes: Vec<&str> = nodes.iter().map(|n| &**n).collect();
+        Ok(format!(
+            "cluster={} nodes={}",
+            if switches.cluster() { "on" } else { "off" },
+            nodes.join(",")
+        ))
+    }
+}
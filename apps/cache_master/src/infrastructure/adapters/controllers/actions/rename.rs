@@ -0,0 +1,48 @@
+use std::sync::Arc;
+
+use app_core::UseCaseValidatable;
+use app_net::tokenize;
+use async_trait::async_trait;
+
+use crate::{
+    core::{
+        domain::models::{AppError, usecases::RenameUseCaseInput},
+        usecases::RenameUseCase,
+    },
+    infrastructure::{
+        adapters::controllers::router::{ActionHandler, RequestContext},
+        metrics::MasterMetrics,
+    },
+};
+
+/// `RENAME "<desde>" "<hasta>"`: responde `OK`, 404 si `desde` no existe y 409 si `hasta`
+/// ya existe (o `desde` cambió mientras se movía a otro shard).
+pub struct RenameAction {
+    rename_use_case: Arc<RenameUseCase>,
+    metrics: Arc<MasterMetrics>,
+}
+
+impl RenameAction {
+    pub fn new(rename_use_case: Arc<RenameUseCase>, metrics: Arc<MasterMetrics>) -> Self {
+        Self {
+            rename_use_case,
+            metrics,
+        }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for RenameAction {
+    async fn handle(&self, _ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        let mut parts = tokenize(payload);
+        let from = parts.next().unwrap_or_default().into_owned();
+        let to = parts.next().unwrap_or_default().into_owned();
+        self.metrics.observe_key(&from);
+        self.metrics.observe_key(&to);
+
+        self.rename_use_case
+            .validate_and_execute(RenameUseCaseInput { from, to })
+            .await?;
+        Ok("OK".to_string())
+    }
+}
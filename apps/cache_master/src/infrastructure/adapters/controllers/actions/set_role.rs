@@ -0,0 +1,37 @@
+use std::sync::Arc;
+
+use app_net::tokenize;
+use async_trait::async_trait;
+
+use crate::{
+    core::domain::models::AppError,
+    infrastructure::adapters::{
+        controllers::router::{ActionHandler, RequestContext},
+        services::tcp_network_service::TcpNetworkService,
+    },
+};
+
+/// `SET-ROLE "<node_id>" ["MASTER" | "REPLICA"]`; sin rol devuelve el actual.
+pub struct SetRoleAction {
+    network: Arc<TcpNetworkService>,
+}
+
+impl SetRoleAction {
+    pub fn new(network: Arc<TcpNetworkService>) -> Self {
+        Self { network }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for SetRoleAction {
+    async fn handle(&self, _ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        let mut parts = tokenize(payload);
+        let node_id = parts.next().unwrap_or_default();
+        let role = parts.next().unwrap_or_default();
+        if node_id.is_empty() {
+            return Err(AppError::BadRequest("SET-ROLE sin node_id".to_string()));
+        }
+
+        self.network.request_set_role(&node_id, &role).await
+    }
+}
@@ -0,0 +1,39 @@
+use std::sync::Arc;
+
+use app_net::{encode_args, tokenize};
+use async_trait::async_trait;
+
+use crate::{
+    core::domain::models::AppError,
+    infrastructure::adapters::{
+        controllers::router::{ActionHandler, RequestContext},
+        services::tcp_network_service::TcpNetworkService,
+    },
+};
+
+/// `SLOWLOG "<node_id>" ["GET" [n] | "LEN" | "RESET"]`: el slow log de un nodo, con los
+/// comandos que tardaron más que su `SLOWLOG_THRESHOLD_MS` en el nodo mismo.
+pub struct SlowLogAction {
+    network: Arc<TcpNetworkService>,
+}
+
+impl SlowLogAction {
+    pub fn new(network: Arc<TcpNetworkService>) -> Self {
+        Self { network }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for SlowLogAction {
+    async fn handle(&self, _ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        let mut parts = tokenize(payload);
+        let node_id = parts.next().unwrap_or_default();
+        if node_id.is_empty() {
+            return Err(AppError::BadRequest("SLOWLOG sin node_id".to_string()));
+        }
+        let rest: Vec<_> = parts.collect();
+        let args = encode_args(rest.iter().map(|a| a.as_ref()));
+
+        self.network.request_slow_log(&node_id, &args).await
+    }
+}
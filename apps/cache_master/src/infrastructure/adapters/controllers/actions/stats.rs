@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use app_net::{encode_args, tokenize};
+use async_trait::async_trait;
+
+use crate::{
+    core::domain::models::AppError,
+    infrastructure::{
+        adapters::{
+            controllers::router::{ActionHandler, RequestContext},
+            services::tcp_network_service::TcpNetworkService,
+        },
+        clock_skew::ClockSkews,
+    },
+};
+
+/// `STATS "<node_id>" ["<namespace>"]`: ocupación del cache de un nodo y, por namespace,
+/// entradas, bytes, cuota, desalojos y escrituras rechazadas. Una línea por cada uno. Sin
+/// namespace, el master le suma a la del cache los contadores de la conexión del nodo (ver
+/// `ConnectionStats`) y `clock_skew_ms=.. clock_rtt_ms=..`: cuánto va adelantado el reloj
+/// del nodo (ver `ClockSkews`), si ya se midió.
+///
+/// `STATS` sin nodo lista las conexiones del master, clientes incluidos, una por línea
+/// (ver `AppNetworkNode::connection_line`) y de la que más bytes mandó a la que menos.
+pub struct StatsAction {
+    network: Arc<TcpNetworkService>,
+    clock_skews: Arc<ClockSkews>,
+}
+
+impl StatsAction {
+    pub fn new(network: Arc<TcpNetworkService>, clock_skews: Arc<ClockSkews>) -> Self {
+        Self {
+            network,
+            clock_skews,
+        }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for StatsAction {
+    async fn handle(&self, _ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        let mut parts = tokenize(payload);
+        let node_id = parts.next().unwrap_or_default();
+        if node_id.is_empty() {
+            let lines: Vec<String> = self
+                .network
+                .connections()
+                .iter()
+                .map(|node| node.connection_line())
+                .collect();
+            return Ok(encode_args(lines.iter().map(String::as_str)));
+        }
+        let namespace = parts.next().filter(|n| !n.is_empty());
+
+        let stats = self
+            .network
+            .request_stats(&node_id, namespace.as_deref())
+            .await?;
+        if namespace.is_some() {
+            return Ok(stats);
+        }
+        let mut values: Vec<String> = tokenize(&stats).map(|v| v.into_owned()).collect();
+        if let Some(totals) = values.first_mut() {
+            if let Some(connection) = self.network.connection_stats(&node_id) {
+                totals.push(' ');
+                totals.push_str(&connection);
+            }
+            if let Some(skew) = self.clock_skews.estimate(&node_id) {
+                totals.push_str(&format!(
+                    " clock_skew_ms={} clock_rtt_ms={}",
+                    skew.offset_ms, skew.rtt_ms
+                ));
+            }
+        }
+        Ok(encode_args(values.iter().map(String::as_str)))
+    }
+}
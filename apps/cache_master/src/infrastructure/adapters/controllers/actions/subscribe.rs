@@ -0,0 +1,69 @@
+use std::sync::Arc;
+
+use app_net::{event::TOPOLOGY, tokenize};
+use async_trait::async_trait;
+
+use crate::{
+    core::domain::models::AppError,
+    infrastructure::{
+        adapters::controllers::router::{ActionHandler, RequestContext},
+        topology_feed::TopologyFeed,
+    },
+};
+
+fn topic(payload: &str, action: &str) -> Result<(), AppError> {
+    match tokenize(payload).next().as_deref() {
+        Some(TOPOLOGY) => Ok(()),
+        Some(other) => Err(AppError::BadRequest(format!(
+            "{action}: tema desconocido: {other}"
+        ))),
+        None => Err(AppError::BadRequest(format!("{action} sin tema"))),
+    }
+}
+
+/// `SUBSCRIBE "TOPOLOGY"`: desde ahí la conexión recibe un `EVT TOPOLOGY` por cada nodo o
+/// shard que entra o sale, hasta `UNSUBSCRIBE` o hasta cortarse. Responde el `epoch`
+/// actual, para saber si en el medio se perdió algún aviso.
+pub struct SubscribeAction {
+    feed: Arc<TopologyFeed>,
+}
+
+impl SubscribeAction {
+    pub fn new(feed: Arc<TopologyFeed>) -> Self {
+        Self { feed }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for SubscribeAction {
+    async fn handle(&self, ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        topic(payload, "SUBSCRIBE")?;
+        let socket = ctx
+            .socket()
+            .ok_or_else(|| AppError::BadRequest("SUBSCRIBE sin conexión".to_string()))?;
+
+        let epoch = self.feed.subscribe(ctx.peer_id.clone(), socket.clone());
+        Ok(epoch.to_string())
+    }
+}
+
+/// `UNSUBSCRIBE "TOPOLOGY"`: corta los avisos; `OK` aunque la conexión no estuviera
+/// suscrita.
+pub struct UnsubscribeAction {
+    feed: Arc<TopologyFeed>,
+}
+
+impl UnsubscribeAction {
+    pub fn new(feed: Arc<TopologyFeed>) -> Self {
+        Self { feed }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for UnsubscribeAction {
+    async fn handle(&self, ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        topic(payload, "UNSUBSCRIBE")?;
+        self.feed.unsubscribe(&ctx.peer_id);
+        Ok("OK".to_string())
+    }
+}
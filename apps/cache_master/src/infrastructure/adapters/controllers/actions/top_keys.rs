@@ -0,0 +1,40 @@
+use std::sync::Arc;
+
+use app_net::{encode_args, tokenize};
+use async_trait::async_trait;
+
+use crate::{
+    core::domain::models::AppError,
+    infrastructure::adapters::{
+        controllers::router::{ActionHandler, RequestContext},
+        services::tcp_network_service::TcpNetworkService,
+    },
+};
+
+/// `TOPKEYS "<node_id>" ["GET" [n] | "LEN" | "RESET"]`: las claves que más le piden a un
+/// nodo, contadas por el nodo mismo. Contrasta con las hot keys que ve el master, que solo
+/// cuenta las lecturas que reparte él.
+pub struct TopKeysAction {
+    network: Arc<TcpNetworkService>,
+}
+
+impl TopKeysAction {
+    pub fn new(network: Arc<TcpNetworkService>) -> Self {
+        Self { network }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for TopKeysAction {
+    async fn handle(&self, _ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        let mut parts = tokenize(payload);
+        let node_id = parts.next().unwrap_or_default();
+        if node_id.is_empty() {
+            return Err(AppError::BadRequest("TOPKEYS sin node_id".to_string()));
+        }
+        let rest: Vec<_> = parts.collect();
+        let args = encode_args(rest.iter().map(|a| a.as_ref()));
+
+        self.network.request_top_keys(&node_id, &args).await
+    }
+}
@@ -0,0 +1,61 @@
+use std::sync::Arc;
+
+use app_net::{encode_args, encode_token, tokenize};
+use async_trait::async_trait;
+
+use crate::{
+    core::domain::models::AppError,
+    infrastructure::{
+        adapters::controllers::router::{ActionHandler, RequestContext},
+        write_retries::WriteRetries,
+    },
+};
+
+/// `WRITE-RETRIES ["retry" | "clear"]`: sin argumentos devuelve `pending=.. dead=..` y una
+/// línea por dead letter (`node=.. key=.. version=.. attempts=.. error=..`, de la más
+/// vieja a la más nueva); `retry` las vuelve a encolar con los intentos en cero y `clear`
+/// las descarta, y los dos devuelven cuántas eran.
+pub struct WriteRetriesAction {
+    retries: Arc<WriteRetries>,
+}
+
+impl WriteRetriesAction {
+    pub fn new(retries: Arc<WriteRetries>) -> Self {
+        Self { retries }
+    }
+}
+
+#[async_trait]
+impl ActionHandler for WriteRetriesAction {
+    async fn handle(&self, _ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+        let lines = match tokenize(payload).next().as_deref() {
+            None | Some("") => {
+                let dead = self.retries.dead_letters();
+                let mut lines = vec![format!(
+                    "pending={} dead={}",
+                    self.retries.pending_len(),
+                    dead.len()
+                )];
+                lines.extend(dead.iter().map(|write| {
+                    format!(
+                        "node={} key={} version={} attempts={} error={}",
+                        write.node_id,
+                        encode_token(&write.key),
+                        write.version,
+                        write.attempts,
+                        write.last_error
+                    )
+                }));
+                lines
+            }
+            Some("retry") => vec![format!("requeued={}", self.retries.requeue_dead())],
+            Some("clear") => vec![format!("cleared={}", self.retries.clear_dead())],
+            Some(other) => {
+                return Err(AppError::BadRequest(format!(
+                    "WRITE-RETRIES: argumento desconocido '{other}'"
+                )));
+            }
+        };
+        Ok(encode_args(lines.iter().map(String::as_str)))
+    }
+}
@@ -1 +1,2 @@
-pub mod request_controller;
+pub mod actions;
+pub mod router;
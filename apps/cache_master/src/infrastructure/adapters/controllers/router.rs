@@ -0,0 +1,343 @@
+//! Router de acciones del master: cada acción del protocolo de clientes se mapea a un
+//! `ActionHandler` con su política (autenticación, clase de rate-limit y etiqueta de
+//! métricas), que el router aplica antes de llamar al handler.
+
+use std::{
+    collections::HashMap,
+    env,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use app_net::{DEFAULT_MAX_PAYLOAD, ResponseBody, Socket};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use tokio::{sync::watch, time::Instant};
+
+use crate::{core::domain::models::AppError, infrastructure::metrics::MasterMetrics};
+
+/// Etiqueta de métricas de las acciones sin ruta.
+pub const UNROUTED_LABEL: &str = "OTHER";
+
+/// Quién puede usar una acción.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthRequirement {
+    Public,
+    /// Solo conexiones que mandaron `AUTH` con el token de admin (si hay uno configurado).
+    Admin,
+}
+
+/// Las acciones de una misma clase comparten cupo de requests por segundo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RateClass {
+    Unlimited,
+    Data,
+    Admin,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActionPolicy {
+    pub auth: AuthRequirement,
+    pub rate_class: RateClass,
+    pub metrics_label: &'static str,
+}
+
+impl ActionPolicy {
+    /// Sin autenticación ni límite (p. ej. `PING`, `AUTH`).
+    pub const fn open(metrics_label: &'static str) -> Self {
+        Self {
+            auth: AuthRequirement::Public,
+            rate_class: RateClass::Unlimited,
+            metrics_label,
+        }
+    }
+
+    /// Lecturas y escrituras de claves.
+    pub const fn data(metrics_label: &'static str) -> Self {
+        Self {
+            auth: AuthRequirement::Public,
+            rate_class: RateClass::Data,
+            metrics_label,
+        }
+    }
+
+    /// Administración del cluster.
+    pub const fn admin(metrics_label: &'static str) -> Self {
+        Self {
+            auth: AuthRequirement::Admin,
+            rate_class: RateClass::Admin,
+            metrics_label,
+        }
+    }
+
+    /// Coordinación entre masters: exige el token de admin pero no gasta el cupo de
+    /// administración, que es para personas.
+    pub const fn peer(metrics_label: &'static str) -> Self {
+        Self {
+            auth: AuthRequirement::Admin,
+            rate_class: RateClass::Unlimited,
+            metrics_label,
+        }
+    }
+}
+
+/// Estado de la conexión que hace el request.
+#[derive(Debug)]
+pub struct RequestContext {
+    pub peer_id: Arc<str>,
+    admin: AtomicBool,
+    /// Para las acciones que después le escriben a la conexión por su cuenta (`MONITOR`).
+    socket: Option<Arc<Socket>>,
+}
+
+impl RequestContext {
+    pub fn new(peer_id: Arc<str>) -> Self {
+        Self {
+            peer_id,
+            admin: AtomicBool::new(false),
+            socket: None,
+        }
+    }
+
+    pub fn with_socket(mut self, socket: Arc<Socket>) -> Self {
+        self.socket = Some(socket);
+        self
+    }
+
+    pub fn socket(&self) -> Option<&Arc<Socket>> {
+        self.socket.as_ref()
+    }
+
+    pub fn is_admin(&self) -> bool {
+        self.admin.load(Ordering::Relaxed)
+    }
+
+    pub fn grant_admin(&self) {
+        self.admin.store(true, Ordering::Relaxed);
+    }
+}
+
+#[async_trait]
+pub trait ActionHandler: Send + Sync {
+    async fn handle(&self, ctx: &RequestContext, payload: &str) -> Result<String, AppError>;
+
+    /// La respuesta completa; la redefinen las acciones que contestan algo más que un
+    /// payload con `200` (un `GET` condicional, ver `app_net::conditional`).
+    async fn respond(&self, ctx: &RequestContext, payload: &str) -> Result<ResponseBody, AppError> {
+        self.handle(ctx, payload).await.map(ResponseBody::Value)
+    }
+}
+
+/// Lo que decide el router antes de llamar a una acción: autenticación, cupos y tamaño
+/// del payload. El resto de la configuración del master está en `MasterConfig`.
+#[derive(Debug, Clone)]
+pub struct RouterConfig {
+    /// Con token, las acciones `Admin` exigen un `AUTH "<token>"` previo en la conexión;
+    /// sin token quedan abiertas.
+    pub admin_token: Option<String>,
+    /// Requests por segundo de cada clase; las que no figuran no tienen límite. Se pueden
+    /// cambiar con `CONFIG SET` (ver `LiveConfig`).
+    pub rate_limits: HashMap<RateClass, u32>,
+    /// Bytes que puede tener el payload de un request; uno más grande se contesta con
+    /// `413` sin llegar a su acción.
+    pub max_payload: usize,
+}
+
+impl Default for RouterConfig {
+    fn default() -> Self {
+        Self {
+            admin_token: None,
+            rate_limits: HashMap::new(),
+            max_payload: DEFAULT_MAX_PAYLOAD,
+        }
+    }
+}
+
+impl RouterConfig {
+    /// `ADMIN_TOKEN`, `RATE_LIMIT_DATA` y `RATE_LIMIT_ADMIN` (requests por segundo) y
+    /// `MAX_PAYLOAD_BYTES` (1 MiB por defecto).
+    pub fn from_env() -> Self {
+        let rate = |var: &str| env::var(var).ok().and_then(|v| v.parse::<u32>().ok());
+
+        let mut rate_limits = HashMap::new();
+        for (class, var) in [
+            (RateClass::Data, "RATE_LIMIT_DATA"),
+            (RateClass::Admin, "RATE_LIMIT_ADMIN"),
+        ] {
+            if let Some(per_sec) = rate(var).filter(|r| *r > 0) {
+                rate_limits.insert(class, per_sec);
+            }
+        }
+
+        Self {
+            admin_token: env::var("ADMIN_TOKEN").ok().filter(|t| !t.is_empty()),
+            rate_limits,
+            max_payload: env::var("MAX_PAYLOAD_BYTES")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .filter(|bytes| *bytes > 0)
+                .unwrap_or(DEFAULT_MAX_PAYLOAD),
+        }
+    }
+}
+
+/// Token bucket con ráfaga igual a la tasa por segundo. La tasa llega en cada request
+/// porque se puede cambiar en caliente; si baja, lo acumulado se recorta a la nueva.
+struct RateLimiter {
+    state: Mutex<(f64, Instant)>,
+}
+
+impl RateLimiter {
+    fn new() -> Self {
+        Self {
+            // arranca lleno, sea cual sea la tasa
+            state: Mutex::new((f64::INFINITY, Instant::now())),
+        }
+    }
+
+    fn try_acquire(&self, per_sec: u32) -> bool {
+        let per_sec = f64::from(per_sec);
+        let mut state = self.state.lock();
+        let (tokens, last) = &mut *state;
+        let now = Instant::now();
+        *tokens = (*tokens + now.duration_since(*last).as_secs_f64() * per_sec).min(per_sec);
+        *last = now;
+
+        if *tokens >= 1.0 {
+            *tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+struct Route {
+    policy: ActionPolicy,
+    handler: Arc<dyn ActionHandler>,
+}
+
+pub struct ActionRouter {
+    routes: HashMap<&'static str, Route>,
+    limiters: HashMap<RateClass, RateLimiter>,
+    /// Requests por segundo de cada clase con límite.
+    rate_limits: watch::Receiver<HashMap<RateClass, u32>>,
+    auth_enabled: bool,
+    max_payload: usize,
+    metrics: Arc<MasterMetrics>,
+}
+
+impl ActionRouter {
+    pub fn new(config: &RouterConfig, metrics: Arc<MasterMetrics>) -> Self {
+        Self {
+            routes: HashMap::new(),
+            limiters: [RateClass::Data, RateClass::Admin]
+                .into_iter()
+                .map(|class| (class, RateLimiter::new()))
+                .collect(),
+            rate_limits: watch::channel(config.rate_limits.clone()).1,
+            auth_enabled: config.admin_token.is_some(),
+            max_payload: config.max_payload,
+            metrics,
+        }
+    }
+
+    /// Toma los límites de `rate_limits` en vez de los fijos de `RouterConfig` (ver
+    /// `LiveConfig`).
+    pub fn with_rate_limits(
+        mut self,
+        rate_limits: watch::Receiver<HashMap<RateClass, u32>>,
+    ) -> Self {
+        self.rate_limits = rate_limits;
+        self
+    }
+
+    /// Registra `handler` para `action`. Una acción duplicada es un error de armado del
+    /// módulo, así que entra en pánico.
+    pub fn route<H: ActionHandler + 'static>(
+        &mut self,
+        action: &'static str,
+        policy: ActionPolicy,
+        handler: H,
+    ) -> &mut Self {
+        self.metrics.register_action(policy.metrics_label);
+        let previous = self.routes.insert(
+            action,
+            Route {
+                policy,
+                handler: Arc::new(handler),
+            },
+        );
+        assert!(previous.is_none(), "acción {action} registrada dos veces");
+        self
+    }
+
+    /// El payload más grande que acepta `dispatch`.
+    pub fn max_payload(&self) -> usize {
+        self.max_payload
+    }
+
+    pub fn policy(&self, action: &str) -> Option<ActionPolicy> {
+        self.routes.get(action).map(|route| route.policy)
+    }
+
+    /// Acciones registradas, ordenadas.
+    pub fn actions(&self) -> Vec<&'static str> {
+        let mut actions: Vec<_> = self.routes.keys().copied().collect();
+        actions.sort_unstable();
+        actions
+    }
+
+    /// Aplica la política de `action` y llama a su handler. Devuelve también la etiqueta
+    /// con la que se cuenta el request en las métricas.
+    pub async fn dispatch(
+        &self,
+        ctx: &RequestContext,
+        action: &str,
+        payload: &str,
+    ) -> (&'static str, Result<ResponseBody, AppError>) {
+        let Some(route) = self.routes.get(action) else {
+            return (
+                UNROUTED_LABEL,
+                Err(AppError::BadRequest(format!("Unknown action: {}", action))),
+            );
+        };
+        let policy = route.policy;
+
+        if policy.auth == AuthRequirement::Admin && self.auth_enabled && !ctx.is_admin() {
+            return (
+                policy.metrics_label,
+                Err(AppError::Unauthorized(format!("{action} requiere AUTH"))),
+            );
+        }
+
+        if payload.len() > self.max_payload {
+            return (
+                policy.metrics_label,
+                Err(AppError::PayloadTooLarge(format!(
+                    "{action}: payload de {} bytes; el máximo es {}",
+                    payload.len(),
+                    self.max_payload
+                ))),
+            );
+        }
+
+        let per_sec = self.rate_limits.borrow().get(&policy.rate_class).copied();
+        if let Some(per_sec) = per_sec
+            && let Some(limiter) = self.limiters.get(&policy.rate_class)
+            && !limiter.try_acquire(per_sec)
+        {
+            return (
+                policy.metrics_label,
+                Err(AppError::RateLimited(format!("{action}: cupo agotado"))),
+            );
+        }
+
+        (
+            policy.metrics_label,
+            route.handler.respond(ctx, payload).await,
+        )
+    }
+}
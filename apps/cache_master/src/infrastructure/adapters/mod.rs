@@ -1,2 +1,3 @@
 pub mod controllers;
 pub mod services;
+pub mod subscribers;
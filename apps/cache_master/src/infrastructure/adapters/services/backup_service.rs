@@ -0,0 +1,251 @@
+use std::{sync::Arc, time::Duration};
+
+use app_core::clock::Clock;
+use app_net::{SnapshotHeader, parse_millis, take_tags, tokenize};
+use tokio::{
+    sync::Mutex,
+    time::{self, MissedTickBehavior},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use crate::{
+    core::domain::{
+        models::{AppError, BackupManifest, RestoreReport, ShardBackup},
+        services::ConsistentHasherService,
+    },
+    infrastructure::adapters::services::{
+        backup_store::{BackupStore, MANIFEST},
+        bulk_put::BulkPut,
+        tcp_network_service::TcpNetworkService,
+    },
+};
+
+/// Backups del cluster: el `SNAPSHOT` del primario de cada shard, guardado en `store` como
+/// un `<shard>.snap` por shard y un `manifest` (ver `BackupManifest`).
+///
+/// Cada shard es una foto consistente de su nodo; entre shards no hay un instante común,
+/// porque se piden todos a la vez pero cada nodo arma la suya cuando le llega.
+pub struct BackupService {
+    network: Arc<TcpNetworkService>,
+    hasher: Arc<dyn ConsistentHasherService>,
+    store: Arc<dyn BackupStore>,
+    clock: Arc<dyn Clock>,
+    /// Copias de cada clave que se escribe, ver `BulkPut`.
+    replication_factor: usize,
+    /// Un backup o un restore a la vez.
+    running: Mutex<()>,
+}
+
+impl BackupService {
+    pub fn new(
+        network: Arc<TcpNetworkService>,
+        hasher: Arc<dyn ConsistentHasherService>,
+        store: Arc<dyn BackupStore>,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        Self {
+            network,
+            hasher,
+            store,
+            clock,
+            replication_factor: 1,
+            running: Mutex::new(()),
+        }
+    }
+
+    pub fn with_replication_factor(mut self, replication_factor: usize) -> Self {
+        self.replication_factor = replication_factor;
+        self
+    }
+
+    /// Ids de los backups guardados, del más viejo al más nuevo.
+    pub async fn list(&self) -> Result<Vec<String>, AppError> {
+        self.store.sets().await
+    }
+
+    pub async fn manifest(&self, id: &str) -> Result<BackupManifest, AppError> {
+        let raw = self.store.read(id, MANIFEST).await?;
+        BackupManifest::parse(&String::from_utf8_lossy(&raw))
+    }
+
+    /// Un backup nuevo. Los shards que no contestan quedan en `unreachable`; si no contesta
+    /// ninguno, no se guarda nada.
+    pub async fn backup(&self) -> Result<BackupManifest, AppError> {
+        let _running = self.running.try_lock().map_err(|_| busy())?;
+
+        let created_at = self.clock.now_millis();
+        let id = created_at.to_utc_basic();
+        if self.store.sets().await?.contains(&id) {
+            return Err(AppError::Conflict(format!("ya existe el backup {id}")));
+        }
+
+        let mut manifest = BackupManifest {
+            id: id.clone(),
+            created_at_ms: created_at.as_millis_u64(),
+            ..BackupManifest::default()
+        };
+        for (shard, node, res) in self.network.request_shard_snapshots().await {
+            let snapshot = res.and_then(|payload| {
+                let lines: Vec<String> = tokenize(&payload).map(|l| l.into_owned()).collect();
+                let header = lines.first().map(|l| l.parse::<SnapshotHeader>());
+                match header {
+                    Some(Ok(header)) => Ok((header, lines)),
+                    Some(Err(e)) => Err(e.into()),
+                    None => Err(AppError::BadRequest("SNAPSHOT vacío".into())),
+                }
+            });
+            let (header, lines) = match snapshot {
+                Ok(snapshot) => snapshot,
+                Err(e) => {
+                    warn!(%shard, %node, "SNAPSHOT sin respuesta: {e}");
+                    manifest.unreachable.push(shard);
+                    continue;
+                }
+            };
+
+            let mut data = lines.join("\n");
+            data.push('\n');
+            let bytes = data.len() as u64;
+            self.store
+                .write(&id, &BackupManifest::shard_file(&shard), data.into_bytes())
+                .await?;
+            manifest.shards.push(ShardBackup {
+                shard,
+                node,
+                epoch: header.epoch,
+                seq: header.seq,
+                entries: header.entries,
+                bytes,
+            });
+        }
+
+        if manifest.shards.is_empty() {
+            return Err(AppError::NodeNotFound(format!(
+                "backup {id}: ningún shard contestó el SNAPSHOT"
+            )));
+        }
+        let text = manifest.lines().join("\n");
+        self.store.write(&id, MANIFEST, text.into_bytes()).await?;
+
+        info!(
+            backup = %id,
+            entries = manifest.entries(),
+            shards = manifest.shards.len(),
+            unreachable = manifest.unreachable.len(),
+            "Backup guardado"
+        );
+        Ok(manifest)
+    }
+
+    /// Vuelve a escribir las entradas del backup `id` (el último si es `None`) con `PUT`
+    /// normales: cada clave va al shard que le toca hoy, aunque la topología haya cambiado,
+    /// con su vencimiento original. Las que ya vencieron no se escriben.
+    pub async fn restore(&self, id: Option<&str>) -> Result<RestoreReport, AppError> {
+        let _running = self.running.try_lock().map_err(|_| busy())?;
+
+        let id = match id {
+            Some(id) => id.to_string(),
+            None => self
+                .store
+                .sets()
+                .await?
+                .pop()
+                .ok_or_else(|| AppError::NotFound("no hay backups".into()))?,
+        };
+        let manifest = self.manifest(&id).await?;
+        let now_ms = self.clock.now_millis().as_millis_u64();
+
+        let mut report = RestoreReport {
+            id,
+            ..RestoreReport::default()
+        };
+        let mut puts = BulkPut::new(self.network.clone(), self.hasher.clone())
+            .with_replication_factor(self.replication_factor);
+        for shard in &manifest.shards {
+            let raw = self
+                .store
+                .read(&manifest.id, &BackupManifest::shard_file(&shard.shard))
+                .await?;
+            let raw = String::from_utf8_lossy(&raw);
+
+            // la primera línea es el `SnapshotHeader`
+            for line in raw.lines().skip(1).filter(|l| !l.is_empty()) {
+                let Some(entry) = Entry::parse(line) else {
+                    debug!(shard = %shard.shard, "entrada ilegible en el backup: {line}");
+                    report.failed += 1;
+                    continue;
+                };
+                if entry.expires_at.is_some_and(|at| at <= now_ms) {
+                    report.expired += 1;
+                    continue;
+                }
+                puts.put(entry.key, entry.value, entry.expires_at, entry.tags)
+                    .await;
+            }
+        }
+        let tally = puts.finish().await;
+        report.restored = tally.written;
+        report.failed += tally.failed;
+        report.first_error = tally.first_error;
+
+        info!(
+            backup = %report.id,
+            restored = report.restored,
+            expired = report.expired,
+            failed = report.failed,
+            "Backup restaurado"
+        );
+        Ok(report)
+    }
+}
+
+fn busy() -> AppError {
+    AppError::Conflict("ya hay un backup o un restore en curso".into())
+}
+
+/// Una línea de un `<shard>.snap`: el payload del `PUT` que la vuelve a escribir.
+struct Entry {
+    key: String,
+    value: String,
+    expires_at: Option<u64>,
+    tags: Vec<String>,
+}
+
+impl Entry {
+    fn parse(line: &str) -> Option<Self> {
+        let mut args: Vec<_> = tokenize(line).collect();
+        let tags = take_tags(&mut args).ok()?;
+        let mut args = args.into_iter();
+        let key = args.next()?.into_owned();
+        let value = args.next()?.into_owned();
+        let expires_at = args.next().map(|s| parse_millis(&s)).transpose().ok()?;
+        Some(Self {
+            key,
+            value,
+            expires_at,
+            tags,
+        })
+    }
+}
+
+/// `backup` cada `interval` hasta que se cancele `token`. El primero sale pasado un
+/// intervalo, no al arrancar.
+pub async fn run_backups(
+    service: Arc<BackupService>,
+    interval: Duration,
+    token: CancellationToken,
+) {
+    let mut ticks = time::interval_at(time::Instant::now() + interval, interval);
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            _ = token.cancelled() => break,
+            _ = ticks.tick() => {}
+        }
+        if let Err(e) = service.backup().await {
+            warn!("Backup programado falló: {e}");
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use std::{
+    env,
+    io::ErrorKind as IoErrorKind,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use app_core::clock::Clock;
+use async_trait::async_trait;
+
+use crate::{
+    core::domain::models::AppError,
+    infrastructure::adapters::services::{S3BackupStore, S3Config},
+};
+
+/// Archivo que describe un backup (ver `BackupManifest`). Se escribe al final: un backup
+/// sin él quedó a medias y no aparece en `sets`.
+pub const MANIFEST: &str = "manifest";
+
+/// Dónde quedan los backups: cada uno es un conjunto de archivos con nombre bajo un id.
+#[async_trait]
+pub trait BackupStore: Send + Sync {
+    /// Reemplaza lo que hubiera en `<set>/<name>`.
+    async fn write(&self, set: &str, name: &str, data: Vec<u8>) -> Result<(), AppError>;
+
+    /// `AppError::NotFound` si no existe.
+    async fn read(&self, set: &str, name: &str) -> Result<Vec<u8>, AppError>;
+
+    /// Ids de los backups con manifest, ordenados.
+    async fn sets(&self) -> Result<Vec<String>, AppError>;
+}
+
+/// Un directorio por backup bajo `dir`.
+pub struct LocalBackupStore {
+    dir: PathBuf,
+}
+
+impl LocalBackupStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> AppError {
+    if e.kind() == IoErrorKind::NotFound {
+        return AppError::NotFound(path.display().to_string());
+    }
+    AppError::Storage(format!("{}: {e}", path.display()))
+}
+
+#[async_trait]
+impl BackupStore for LocalBackupStore {
+    async fn write(&self, set: &str, name: &str, data: Vec<u8>) -> Result<(), AppError> {
+        let dir = self.dir.join(set);
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .map_err(|e| io_error(&dir, e))?;
+
+        // con rename, un archivo a medio escribir nunca queda con su nombre final
+        let path = dir.join(name);
+        let tmp = dir.join(format!(".{name}.tmp"));
+        tokio::fs::write(&tmp, data)
+            .await
+            .map_err(|e| io_error(&tmp, e))?;
+        tokio::fs::rename(&tmp, &path)
+            .await
+            .map_err(|e| io_error(&path, e))
+    }
+
+    async fn read(&self, set: &str, name: &str) -> Result<Vec<u8>, AppError> {
+        let path = self.dir.join(set).join(name);
+        tokio::fs::read(&path).await.map_err(|e| io_error(&path, e))
+    }
+
+    async fn sets(&self) -> Result<Vec<String>, AppError> {
+        let mut entries = match tokio::fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == IoErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(io_error(&self.dir, e)),
+        };
+
+        let mut sets = Vec::new();
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| io_error(&self.dir, e))?
+        {
+            let has_manifest = tokio::fs::try_exists(entry.path().join(MANIFEST))
+                .await
+                .unwrap_or(false);
+            if let (true, Some(name)) = (has_manifest, entry.file_name().to_str()) {
+                sets.push(name.to_string());
+            }
+        }
+        sets.sort();
+        Ok(sets)
+    }
+}
+
+/// Destino de los backups elegido por entorno.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BackupTarget {
+    Dir(PathBuf),
+    S3(S3Config),
+}
+
+impl BackupTarget {
+    /// `BACKUP_S3_BUCKET` (con `BACKUP_S3_ENDPOINT` y el resto de `S3Config::from_env`) o, si
+    /// no, `BACKUP_DIR`. `None` sin ninguno de los dos.
+    pub fn from_env() -> Option<Self> {
+        if let Some(config) = S3Config::from_env() {
+            return Some(BackupTarget::S3(config));
+        }
+        env::var("BACKUP_DIR")
+            .ok()
+            .filter(|dir| !dir.trim().is_empty())
+            .map(|dir| BackupTarget::Dir(PathBuf::from(dir)))
+    }
+
+    /// El reloj firma los requests a S3.
+    pub fn into_store(self, clock: Arc<dyn Clock>) -> Arc<dyn BackupStore> {
+        match self {
+            BackupTarget::Dir(dir) => Arc::new(LocalBackupStore::new(dir)),
+            BackupTarget::S3(config) => Arc::new(S3BackupStore::new(config, clock)),
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use std::sync::Arc;
+
+use tokio::task::{JoinError, JoinSet};
+use tracing::{debug, warn};
+
+use crate::{
+    core::domain::{
+        models::{AppError, PutKey},
+        services::{ConsistentHasherService, NetworkService},
+    },
+    infrastructure::adapters::services::tcp_network_service::TcpNetworkService,
+};
+
+/// `PUT` en vuelo a la vez.
+const CONCURRENCY: usize = 64;
+
+/// Cómo terminaron los `PUT` de un `BulkPut`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BulkTally {
+    pub written: u64,
+    /// Rechazados (solo lectura, memoria, sin nodo...) o que no terminaron.
+    pub failed: u64,
+    pub first_error: Option<String>,
+}
+
+/// Muchas claves con `PUT` normales, de a `CONCURRENCY` en vuelo: cada una va al shard
+/// que le toca hoy según el hasher, como cualquier escritura, y a sus copias si el factor
+/// de replicación es mayor a 1. Lo usan `RESTORE` e `IMPORT`.
+pub struct BulkPut {
+    network: Arc<TcpNetworkService>,
+    hasher: Arc<dyn ConsistentHasherService>,
+    replication_factor: usize,
+    puts: JoinSet<Result<bool, AppError>>,
+    tally: BulkTally,
+}
+
+impl BulkPut {
+    pub fn new(network: Arc<TcpNetworkService>, hasher: Arc<dyn ConsistentHasherService>) -> Self {
+        Self {
+            network,
+            hasher,
+            replication_factor: 1,
+            puts: JoinSet::new(),
+            tally: BulkTally::default(),
+        }
+    }
+
+    pub fn with_replication_factor(mut self, replication_factor: usize) -> Self {
+        self.replication_factor = replication_factor.max(1);
+        self
+    }
+
+    /// Lanza el `PUT`; si ya hay `CONCURRENCY` en vuelo, antes espera que termine uno.
+    pub async fn put(
+        &mut self,
+        key: String,
+        value: String,
+        expires_at: Option<u64>,
+        tags: Vec<String>,
+    ) {
+        if self.puts.len() >= CONCURRENCY
+            && let Some(done) = self.puts.join_next().await
+        {
+            self.count(done);
+        }
+
+        let hash = self.hasher.create_hash(&key);
+        let nodes = self
+            .hasher
+            .get_node_ids_from_hash(&hash, self.replication_factor);
+        let network = self.network.clone();
+        self.puts.spawn(async move {
+            let (owner, copies) = nodes
+                .split_first()
+                .ok_or_else(|| AppError::NodeNotFound(key.clone()))?;
+            let put = PutKey {
+                key: &key,
+                value: &value,
+                expires_at,
+                tags: &tags,
+                idempotency: None,
+            };
+            let written = network.request_put_key(owner, &put).await?;
+            // cuenta lo que pasó en el dueño; una copia que falla no hace fallar la clave
+            for copy in copies {
+                if let Err(e) = network.request_put_key(copy, &put).await {
+                    warn!(key = %key, shard = %copy, "copia de la clave no escrita: {e}");
+                }
+            }
+            Ok(written.is_some())
+        });
+    }
+
+    /// Espera los que siguen en vuelo.
+    pub async fn finish(mut self) -> BulkTally {
+        while let Some(done) = self.puts.join_next().await {
+            self.count(done);
+        }
+        self.tally
+    }
+
+    fn count(&mut self, done: Result<Result<bool, AppError>, JoinError>) {
+        match done {
+            Ok(Ok(_)) => self.tally.written += 1,
+            Ok(Err(e)) => {
+                debug!("PUT masivo rechazado: {e}");
+                self.tally.failed += 1;
+                self.tally.first_error.get_or_insert_with(|| e.to_string());
+            }
+            Err(e) => {
+                debug!("PUT masivo no terminó: {e}");
+                self.tally.failed += 1;
+            }
+        }
+    }
+}
@@ -1,13 +1,12 @@
-use std::{
-    collections::BTreeMap,
-    hash::{DefaultHasher, Hash, Hasher},
-    sync::Arc,
-};
+use std::{collections::BTreeMap, sync::Arc};
 
+use app_net::key_hash;
 use dashmap::{DashMap, Entry};
 use parking_lot::RwLock;
 
-use crate::core::domain::services::ConsistentHasherService;
+use crate::{
+    core::domain::services::ConsistentHasherService, infrastructure::decision_log::DecisionLog,
+};
 
 const VNODE_REPLICAS: usize = 128;
 
@@ -15,6 +14,7 @@ pub struct DashmapConsistentHasherService {
     ring: RwLock<BTreeMap<u64, Arc<str>>>,
     real_nodes: DashMap<Arc<str>, ()>,
     vnodes: usize,
+    decision_log: Option<Arc<DecisionLog>>,
 }
 
 impl DashmapConsistentHasherService {
@@ -23,19 +23,34 @@ impl DashmapConsistentHasherService {
             ring: RwLock::new(BTreeMap::new()),
             real_nodes: DashMap::new(),
             vnodes: VNODE_REPLICAS,
+            decision_log: None,
         }
     }
 
+    /// Anota en `log` cada cambio del anillo y cada ruta que da (ver `DecisionLog`).
+    pub fn with_decision_log(mut self, log: Arc<DecisionLog>) -> Self {
+        self.decision_log = Some(log);
+        self
+    }
+
     pub fn new_shared() -> Arc<Self> {
         Arc::new(Self::new())
     }
 
-    //TODO change to twox-hash for better performance
+    /// Nodos reales en el anillo.
+    pub fn node_count(&self) -> usize {
+        self.real_nodes.len()
+    }
+
+    /// Entradas del anillo (vnodes), descontando colisiones de hash.
+    pub fn ring_size(&self) -> usize {
+        self.ring.read().len()
+    }
+
+    /// La de `app_net::key_hash`: los nodos la usan para filtrar `EXPORT-RANGE`.
     #[inline]
     fn hash_u64(&self, key: &str) -> u64 {
-        let mut h = DefaultHasher::new();
-        key.hash(&mut h);
-        h.finish()
+        key_hash(key)
     }
 
     fn insert_vnodes(&self, node_id: &Arc<str>) {
@@ -46,19 +61,74 @@ impl DashmapConsistentHasherService {
 
             ring.insert(hv, node_id.clone());
         }
+        if let Some(log) = &self.decision_log {
+            log.ring_added(node_id);
+        }
+    }
+
+    /// Se llama con el anillo tomado, para que la ruta quede anotada después de los
+    /// cambios que vio y antes de los que no.
+    fn log_route(&self, target: u64, nodes: &[Arc<str>]) {
+        if let Some(log) = &self.decision_log {
+            log.route(&format!("{target:016x}"), nodes);
+        }
     }
 
     fn locate_node(&self, target: u64) -> Option<Arc<str>> {
         let ring = self.ring.read();
-        if ring.is_empty() {
-            return None;
-        }
+        let node = ring
+            .range(target..)
+            .next()
+            .or_else(|| ring.iter().next())
+            .map(|(_, node)| node.clone());
+        self.log_route(target, node.as_slice());
+        node
+    }
+
+    /// Recorre el anillo desde `target`, dando la vuelta, y junta los primeros `count`
+    /// nodos reales distintos.
+    fn locate_nodes(&self, target: u64, count: usize) -> Vec<Arc<str>> {
+        let ring = self.ring.read();
+        let count = count.min(self.real_nodes.len());
+        let mut nodes: Vec<Arc<str>> = Vec::with_capacity(count);
 
-        if let Some((_, node)) = ring.range(target..).next() {
-            return Some(node.clone());
+        for (_, node) in ring.range(target..).chain(ring.range(..target)) {
+            if nodes.len() == count {
+                break;
+            }
+            if !nodes.contains(node) {
+                nodes.push(node.clone());
+            }
         }
+        self.log_route(target, &nodes);
+        nodes
+    }
 
-        ring.iter().next().map(|(_, node)| node.clone())
+    /// El vnode del anillo en el que cae `hash`: su posición y su nombre (`<nodo>#<i>`).
+    pub fn vnode_of(&self, hash: &str) -> Option<(u64, String)> {
+        let target = Self::parse_hash(hash)?;
+        let (position, node) = {
+            let ring = self.ring.read();
+            let (position, node) = ring.range(target..).next().or_else(|| ring.iter().next())?;
+            (*position, node.clone())
+        };
+        // el anillo no guarda el número de réplica: se busca cuál da esa posición
+        let vnode = (0..self.vnodes)
+            .map(|i| format!("{node}#{i}"))
+            .find(|vnode| self.hash_u64(vnode) == position)?;
+        Some((position, vnode))
+    }
+
+    fn parse_hash(hash: &str) -> Option<u64> {
+        u64::from_str_radix(hash.trim_start_matches("0x"), 16)
+            .ok()
+            .or_else(|| hash.parse::<u64>().ok())
+    }
+}
+
+impl Default for DashmapConsistentHasherService {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -89,18 +159,18 @@ impl ConsistentHasherService for DashmapConsistentHasherService {
     }
 
     fn get_node_id_from_hash(&self, hash: &str) -> Option<String> {
-        let parsed = if let Ok(v) = u64::from_str_radix(hash.trim_start_matches("0x"), 16) {
-            v
-        } else if let Ok(v) = hash.parse::<u64>() {
-            v
-        } else {
-            return None;
-        };
+        let parsed = Self::parse_hash(hash)?;
+        self.locate_node(parsed).map(|node| node.to_string())
+    }
 
-        match self.locate_node(parsed) {
-            Some(node) => Some(node.to_string()),
-            None => None,
-        }
+    fn get_node_ids_from_hash(&self, hash: &str, count: usize) -> Vec<String> {
+        let Some(parsed) = Self::parse_hash(hash) else {
+            return Vec::new();
+        };
+        self.locate_nodes(parsed, count)
+            .iter()
+            .map(|node| node.to_string())
+            .collect()
     }
 
     fn remove_node(&self, node_id: &str) -> bool {
@@ -123,6 +193,9 @@ impl ConsistentHasherService for DashmapConsistentHasherService {
             }
 
             ring.retain(|_, v| v.as_ref() != node_id);
+            if let Some(log) = &self.decision_log {
+                log.ring_removed(node_id);
+            }
         }
 
         self.real_nodes.remove(node_id).is_some()
@@ -0,0 +1,480 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use app_core::metrics::LATENCY_BUCKETS_MS;
+use app_net::{RequestDataInput, ResponseData, SocketError, types::SocketResult};
+use tokio::{
+    sync::oneshot,
+    task::{Id, JoinSet},
+    time,
+};
+
+use crate::infrastructure::{app_state::AppNetworkNode, metrics::MasterMetrics};
+
+/// Cuántos nodos de un shard tienen que contestar un request y en qué orden se les manda.
+/// "Contestar" es que llegue una respuesta, con el código que sea: qué hacer con un 503 o
+/// un error del nodo lo decide quien llama mirando `FanoutOutcome::outcomes`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FanoutPolicy {
+    /// A todos a la vez; alcanza con la primera respuesta.
+    #[default]
+    FirstSuccess,
+    /// A todos a la vez; hacen falta `n` respuestas (entre 1 y la cantidad de nodos).
+    Quorum(usize),
+    /// A todos a la vez; hacen falta todas.
+    All,
+    /// Al primer nodo de la lista (el primario, ver `TcpNetworkService::get_all_nodes`) y,
+    /// solo si no contesta, a las réplicas de a una y en orden.
+    PrimaryThenReplicas,
+    /// Al primer nodo de la lista; si no contestó en el `HedgeDelay` (o falló) se suma el
+    /// siguiente, sin cancelar a los que ya tienen el request. Gana la primera respuesta.
+    Hedged(HedgeDelay),
+}
+
+/// Cuánto se espera al nodo preferido antes de preguntarle también al siguiente.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HedgeDelay {
+    Fixed(Duration),
+    /// El p95 de la latencia del nodo para esa acción según `MasterMetrics`
+    /// (`HEDGE_FALLBACK_DELAY` mientras no tenga muestras).
+    P95,
+}
+
+/// Espera de `HedgeDelay::P95` para un nodo sin latencias registradas.
+pub const HEDGE_FALLBACK_DELAY: Duration = Duration::from_millis(10);
+
+impl HedgeDelay {
+    fn resolve(&self, metrics: &MasterMetrics, node_id: &str, action: &str) -> Duration {
+        match *self {
+            Self::Fixed(delay) => delay,
+            Self::P95 => match metrics.node_histogram(node_id, action) {
+                Some(h) if h.count() > 0 => {
+                    // sin bucket es que el p95 cae en +Inf: el más alto que hay
+                    let ms = h
+                        .quantile_ms(0.95)
+                        .unwrap_or(LATENCY_BUCKETS_MS[LATENCY_BUCKETS_MS.len() - 1]);
+                    Duration::from_millis(ms)
+                }
+                _ => HEDGE_FALLBACK_DELAY,
+            },
+        }
+    }
+}
+
+impl FanoutPolicy {
+    /// Respuestas que hacen falta con `nodes` nodos.
+    pub fn required(&self, nodes: usize) -> usize {
+        match *self {
+            Self::FirstSuccess | Self::PrimaryThenReplicas | Self::Hedged(_) => 1,
+            Self::Quorum(n) => n.clamp(1, nodes.max(1)),
+            Self::All => nodes,
+        }
+    }
+
+    /// Si suma los nodos de a uno (empezando por el primario) en vez de mandarles a todos
+    /// a la vez.
+    pub fn is_sequential(&self) -> bool {
+        matches!(self, Self::PrimaryThenReplicas | Self::Hedged(_))
+    }
+}
+
+/// `first`, `quorum:<n>`, `all`, `primary` o `hedged:<ms>|p95`, como en
+/// `READ_POLICY`/`WRITE_POLICY`.
+impl FromStr for FanoutPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "first" => Ok(Self::FirstSuccess),
+            "all" => Ok(Self::All),
+            "primary" => Ok(Self::PrimaryThenReplicas),
+            "hedged:p95" => Ok(Self::Hedged(HedgeDelay::P95)),
+            other if other.starts_with("hedged:") => other["hedged:".len()..]
+                .parse::<u64>()
+                .map(|ms| Self::Hedged(HedgeDelay::Fixed(Duration::from_millis(ms))))
+                .map_err(|_| format!("política de fan-out inválida: {other}")),
+            other => other
+                .strip_prefix("quorum:")
+                .and_then(|n| n.parse::<usize>().ok())
+                .filter(|n| *n > 0)
+                .map(Self::Quorum)
+                .ok_or_else(|| format!("política de fan-out inválida: {other}")),
+        }
+    }
+}
+
+impl fmt::Display for FanoutPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FirstSuccess => f.write_str("first"),
+            Self::Quorum(n) => write!(f, "quorum:{n}"),
+            Self::All => f.write_str("all"),
+            Self::PrimaryThenReplicas => f.write_str("primary"),
+            Self::Hedged(HedgeDelay::P95) => f.write_str("hedged:p95"),
+            Self::Hedged(HedgeDelay::Fixed(delay)) => write!(f, "hedged:{}", delay.as_millis()),
+        }
+    }
+}
+
+/// Qué pasa con los nodos que faltan cuando la política ya se cumplió.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stragglers {
+    /// Se abortan sus requests (y con `PrimaryThenReplicas` o `Hedged` a los que no se les
+    /// mandó nada no se les manda).
+    Abort,
+    /// Reciben el request igual y se espera su respuesta en segundo plano para medir
+    /// cuánto después que el primero confirmaron (lag de réplica). Lo que contestan llega
+    /// por `FanoutOutcome::late`.
+    TrackLag,
+}
+
+/// La respuesta (o el error) de un nodo.
+#[derive(Debug)]
+pub struct NodeOutcome {
+    pub node_id: Arc<str>,
+    /// Desde que salió el primer request del fan-out.
+    pub elapsed: Duration,
+    pub result: SocketResult<ResponseData>,
+}
+
+impl NodeOutcome {
+    pub fn answered(&self) -> bool {
+        self.result.is_ok()
+    }
+}
+
+/// Lo que pasó con cada nodo hasta que se cumplió la política (o ya no quedaban nodos),
+/// en orden de llegada. Los que se abortaron o quedaron en segundo plano no figuran.
+#[derive(Debug)]
+pub struct FanoutOutcome {
+    pub policy: FanoutPolicy,
+    /// Respuestas que pedía la política.
+    pub required: usize,
+    pub outcomes: Vec<NodeOutcome>,
+    /// Con `Stragglers::TrackLag`, lo que pasó con los nodos que quedaron en segundo plano,
+    /// cuando terminan todos; `None` si no quedó ninguno.
+    pub late: Option<oneshot::Receiver<Vec<NodeOutcome>>>,
+}
+
+impl FanoutOutcome {
+    pub fn answered(&self) -> usize {
+        self.outcomes.iter().filter(|o| o.answered()).count()
+    }
+
+    /// Respuestas exitosas (2xx).
+    pub fn confirmed(&self) -> usize {
+        self.outcomes
+            .iter()
+            .filter(|o| matches!(&o.result, Ok(r) if r.is_success()))
+            .count()
+    }
+
+    pub fn is_satisfied(&self) -> bool {
+        !self.outcomes.is_empty() && self.answered() >= self.required
+    }
+
+    /// Si confirmaron todas las respuestas que pedía la política (para escrituras).
+    pub fn is_confirmed(&self) -> bool {
+        !self.outcomes.is_empty() && self.confirmed() >= self.required
+    }
+
+    /// El primero que confirmó.
+    pub fn first_confirmed(&self) -> Option<&Arc<str>> {
+        self.outcomes
+            .iter()
+            .find(|o| matches!(&o.result, Ok(r) if r.is_success()))
+            .map(|o| &o.node_id)
+    }
+
+    /// La primera respuesta que no fue exitosa; si no hay, el error de no haber juntado
+    /// las respuestas necesarias.
+    pub fn into_rejection(mut self) -> SocketResult<ResponseData> {
+        match self
+            .outcomes
+            .iter()
+            .position(|o| matches!(&o.result, Ok(r) if !r.is_success()))
+        {
+            Some(i) => self.outcomes.swap_remove(i).result,
+            None => Err(self.shortfall()),
+        }
+    }
+
+    /// La primera respuesta si se cumplió la política; si no, un error.
+    pub fn into_response(self) -> SocketResult<ResponseData> {
+        if !self.is_satisfied() {
+            return Err(self.shortfall());
+        }
+        self.outcomes
+            .into_iter()
+            .find_map(|o| o.result.ok())
+            .ok_or_else(|| SocketError::ConnectionError("no hay sockets".into()))
+    }
+
+    fn shortfall(self) -> SocketError {
+        let answered = self.answered();
+        let last_err = self.outcomes.into_iter().rev().find_map(|o| o.result.err());
+        match (answered, last_err) {
+            (0, Some(e)) => e,
+            (0, None) => SocketError::ConnectionError("no hay sockets".into()),
+            (_, e) => SocketError::ConnectionError(format!(
+                "{answered} de {} respuestas ({:?}){}",
+                self.required,
+                self.policy,
+                e.map(|e| format!(": {e}")).unwrap_or_default()
+            )),
+        }
+    }
+}
+
+/// Manda `input` a `nodes` según `policy`. Sin nodos devuelve un resultado vacío, que no
+/// cumple ninguna política.
+pub async fn fanout(
+    nodes: &[Arc<AppNetworkNode>],
+    input: RequestDataInput<'_>,
+    policy: FanoutPolicy,
+    stragglers: Stragglers,
+    metrics: &Arc<MasterMetrics>,
+) -> FanoutOutcome {
+    let request = Request {
+        action: Arc::from(input.action),
+        payload: Arc::from(input.payload),
+        started: Instant::now(),
+        metrics: Arc::clone(metrics),
+    };
+    let required = policy.required(nodes.len());
+    let (outcomes, late) = match policy {
+        _ if nodes.is_empty() => (Vec::new(), None),
+        FanoutPolicy::PrimaryThenReplicas => in_order(nodes, request, stragglers).await,
+        FanoutPolicy::Hedged(delay) => {
+            let delay = delay.resolve(metrics, &nodes[0].node_id, input.action);
+            hedged(nodes, request, delay, stragglers).await
+        }
+        _ => concurrent(nodes, request, required, stragglers).await,
+    };
+
+    FanoutOutcome {
+        policy,
+        required,
+        outcomes,
+        late,
+    }
+}
+
+#[derive(Clone)]
+struct Request {
+    action: Arc<str>,
+    payload: Arc<str>,
+    started: Instant,
+    metrics: Arc<MasterMetrics>,
+}
+
+impl Request {
+    async fn send(self, node: Arc<AppNetworkNode>) -> NodeOutcome {
+        let input = RequestDataInput {
+            action: &self.action,
+            payload: &self.payload,
+        };
+        let result = node.socket.request(input).await;
+        let elapsed = self.started.elapsed();
+        let success = matches!(&result, Ok(r) if r.is_success());
+        self.metrics
+            .observe_node_request(&node.node_id, &self.action, elapsed, success);
+
+        NodeOutcome {
+            node_id: node.node_id.clone(),
+            elapsed,
+            result,
+        }
+    }
+}
+
+type Late = oneshot::Receiver<Vec<NodeOutcome>>;
+
+/// Requests en vuelo, con el nodo de cada tarea por si alguna entra en pánico.
+struct InFlight {
+    request: Request,
+    set: JoinSet<NodeOutcome>,
+    ids: HashMap<Id, Arc<str>>,
+}
+
+impl InFlight {
+    fn new(request: Request) -> Self {
+        Self {
+            request,
+            set: JoinSet::new(),
+            ids: HashMap::new(),
+        }
+    }
+
+    fn send(&mut self, node: Arc<AppNetworkNode>) {
+        let node_id = node.node_id.clone();
+        let handle = self.set.spawn(self.request.clone().send(node));
+        self.ids.insert(handle.id(), node_id);
+    }
+
+    async fn next(&mut self) -> Option<NodeOutcome> {
+        let outcome = match self.set.join_next_with_id().await? {
+            Ok((_, outcome)) => outcome,
+            // la tarea entró en pánico: cuenta como un nodo que no contestó
+            Err(e) => NodeOutcome {
+                node_id: self.ids.remove(&e.id()).unwrap_or_else(|| Arc::from("?")),
+                elapsed: self.request.started.elapsed(),
+                result: Err(SocketError::Internal(e.to_string())),
+            },
+        };
+        Some(outcome)
+    }
+
+    fn finish(self, outcomes: &[NodeOutcome], stragglers: Stragglers) -> Option<Late> {
+        finish(outcomes, self.set, stragglers, &self.request.metrics)
+    }
+}
+
+async fn concurrent(
+    nodes: &[Arc<AppNetworkNode>],
+    request: Request,
+    required: usize,
+    stragglers: Stragglers,
+) -> (Vec<NodeOutcome>, Option<Late>) {
+    let mut in_flight = InFlight::new(request);
+    for node in nodes.iter().cloned() {
+        in_flight.send(node);
+    }
+
+    let mut outcomes = Vec::with_capacity(nodes.len());
+    let mut answered = 0;
+    while let Some(outcome) = in_flight.next().await {
+        answered += usize::from(outcome.answered());
+        outcomes.push(outcome);
+        if answered >= required {
+            break;
+        }
+    }
+
+    let late = in_flight.finish(&outcomes, stragglers);
+    (outcomes, late)
+}
+
+async fn hedged(
+    nodes: &[Arc<AppNetworkNode>],
+    request: Request,
+    delay: Duration,
+    stragglers: Stragglers,
+) -> (Vec<NodeOutcome>, Option<Late>) {
+    let mut in_flight = InFlight::new(request);
+    let mut rest = nodes.iter().cloned().peekable();
+    if let Some(first) = rest.next() {
+        in_flight.send(first);
+    }
+
+    let mut outcomes = Vec::with_capacity(1);
+    loop {
+        let can_hedge = rest.peek().is_some();
+        tokio::select! {
+            outcome = in_flight.next() => {
+                let Some(outcome) = outcome else { break };
+                let answered = outcome.answered();
+                outcomes.push(outcome);
+                if answered {
+                    break;
+                }
+                // falló: no tiene sentido esperar el delay para el siguiente
+                if let Some(node) = rest.next() {
+                    in_flight.send(node);
+                }
+            }
+            _ = time::sleep(delay), if can_hedge => {
+                if let Some(node) = rest.next() {
+                    in_flight.request.metrics.observe_hedge();
+                    in_flight.send(node);
+                }
+            }
+        }
+    }
+
+    if stragglers == Stragglers::TrackLag {
+        for node in rest {
+            in_flight.send(node);
+        }
+    }
+    let late = in_flight.finish(&outcomes, stragglers);
+    (outcomes, late)
+}
+
+async fn in_order(
+    nodes: &[Arc<AppNetworkNode>],
+    request: Request,
+    stragglers: Stragglers,
+) -> (Vec<NodeOutcome>, Option<Late>) {
+    let mut outcomes = Vec::with_capacity(1);
+    let mut rest = nodes.iter();
+    for node in rest.by_ref() {
+        let outcome = request.clone().send(node.clone()).await;
+        let answered = outcome.answered();
+        outcomes.push(outcome);
+        if answered {
+            break;
+        }
+    }
+
+    let mut set = JoinSet::new();
+    if stragglers == Stragglers::TrackLag {
+        for node in rest.cloned() {
+            set.spawn(request.clone().send(node));
+        }
+    }
+    let late = finish(&outcomes, set, stragglers, &request.metrics);
+    (outcomes, late)
+}
+
+/// Registra el lag de los que ya contestaron y se ocupa de los que faltan.
+fn finish(
+    outcomes: &[NodeOutcome],
+    mut set: JoinSet<NodeOutcome>,
+    stragglers: Stragglers,
+    metrics: &Arc<MasterMetrics>,
+) -> Option<Late> {
+    match stragglers {
+        Stragglers::Abort => {
+            set.abort_all();
+            None
+        }
+        Stragglers::TrackLag => {
+            let first = outcomes.iter().find(|o| o.answered()).map(|o| o.elapsed)?;
+            for outcome in outcomes.iter().filter(|o| o.answered()) {
+                metrics
+                    .observe_replica_lag(&outcome.node_id, outcome.elapsed.saturating_sub(first));
+            }
+            if set.is_empty() {
+                return None;
+            }
+            let (tx, rx) = oneshot::channel();
+            tokio::spawn(track_stragglers(set, first, Arc::clone(metrics), tx));
+            Some(rx)
+        }
+    }
+}
+
+/// Espera al resto de los nodos (acotado por el timeout del socket), registra su lag y
+/// manda por `done` lo que contestaron.
+async fn track_stragglers(
+    mut set: JoinSet<NodeOutcome>,
+    first: Duration,
+    metrics: Arc<MasterMetrics>,
+    done: oneshot::Sender<Vec<NodeOutcome>>,
+) {
+    let mut outcomes = Vec::with_capacity(set.len());
+    while let Some(joined) = set.join_next().await {
+        let Ok(outcome) = joined else { continue };
+        if outcome.answered() {
+            metrics.observe_replica_lag(&outcome.node_id, outcome.elapsed.saturating_sub(first));
+        }
+        outcomes.push(outcome);
+    }
+    let _ = done.send(outcomes);
+}
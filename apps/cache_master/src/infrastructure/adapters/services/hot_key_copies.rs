@@ -0,0 +1,145 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Instant,
+};
+
+use dashmap::{DashMap, Entry};
+
+/// Claves calientes copiadas a otros shards, para repartir sus lecturas. Las copias
+/// vencen solas en los nodos; acá se dejan de usar un poco antes (`until`).
+#[derive(Default)]
+pub struct HotKeyCopies {
+    copies: DashMap<String, HotCopy>,
+}
+
+enum HotCopy {
+    /// Se están escribiendo las copias; las lecturas siguen yendo al dueño.
+    Copying,
+    Ready {
+        /// El shard dueño primero y después los que tienen copia.
+        shards: Vec<Arc<str>>,
+        next: AtomicUsize,
+        until: Instant,
+    },
+}
+
+impl HotKeyCopies {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Shard al que mandar la lectura de `key`, rotando entre el dueño y las copias;
+    /// `None` si la clave no tiene copias vigentes.
+    pub fn route(&self, key: &str) -> Option<Arc<str>> {
+        let copy = self.copies.get(key)?;
+        let HotCopy::Ready {
+            shards,
+            next,
+            until,
+        } = &*copy
+        else {
+            return None;
+        };
+        if *until <= Instant::now() {
+            return None;
+        }
+        let i = next.fetch_add(1, Ordering::Relaxed) % shards.len();
+        Some(shards[i].clone())
+    }
+
+    /// Los shards entre los que se reparten las lecturas de `key`, sin avanzar la rotación
+    /// de `route`; `None` si la clave no tiene copias vigentes.
+    pub fn shards(&self, key: &str) -> Option<Vec<Arc<str>>> {
+        match &*self.copies.get(key)? {
+            HotCopy::Ready { shards, until, .. } if *until > Instant::now() => Some(shards.clone()),
+            _ => None,
+        }
+    }
+
+    /// Reserva `key` para copiarla; `false` si ya tiene copias vigentes o en curso.
+    pub fn begin(&self, key: &str) -> bool {
+        match self.copies.entry(key.to_string()) {
+            Entry::Occupied(mut occ) => {
+                let stale =
+                    matches!(occ.get(), HotCopy::Ready { until, .. } if *until <= Instant::now());
+                if stale {
+                    occ.insert(HotCopy::Copying);
+                }
+                stale
+            }
+            Entry::Vacant(vac) => {
+                vac.insert(HotCopy::Copying);
+                true
+            }
+        }
+    }
+
+    /// Habilita las copias de `key` reservada con `begin`. Devuelve `false` si en el medio
+    /// hubo una escritura (`invalidate`): las copias ya escritas quedaron viejas.
+    pub fn finish(&self, key: &str, shards: Vec<Arc<str>>, until: Instant) -> bool {
+        match self.copies.get_mut(key) {
+            Some(mut copy) if matches!(*copy, HotCopy::Copying) => {
+                *copy = HotCopy::Ready {
+                    shards,
+                    next: AtomicUsize::new(0),
+                    until,
+                };
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Una reserva que no llegó a `finish` (falló la copia).
+    pub fn abandon(&self, key: &str) {
+        self.copies
+            .remove_if(key, |_, copy| matches!(copy, HotCopy::Copying));
+    }
+
+    /// Deja de usar las copias de `key` antes de escribirla; devuelve los shards que
+    /// tenían copia, para borrarlas.
+    pub fn invalidate(&self, key: &str) -> Vec<Arc<str>> {
+        match self.copies.remove(key) {
+            Some((_, HotCopy::Ready { shards, .. })) => shards.into_iter().skip(1).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// `invalidate` de todas las claves.
+    pub fn invalidate_all(&self) -> Vec<(String, Vec<Arc<str>>)> {
+        self.invalidate_prefix("")
+    }
+
+    /// `invalidate` de las claves que empiezan con `prefix`.
+    pub fn invalidate_prefix(&self, prefix: &str) -> Vec<(String, Vec<Arc<str>>)> {
+        let keys: Vec<String> = self
+            .copies
+            .iter()
+            .map(|c| c.key().clone())
+            .filter(|key| key.starts_with(prefix))
+            .collect();
+        keys.into_iter()
+            .map(|key| {
+                let shards = self.invalidate(&key);
+                (key, shards)
+            })
+            .filter(|(_, shards)| !shards.is_empty())
+            .collect()
+    }
+
+    /// Claves con copias vigentes.
+    pub fn len(&self) -> usize {
+        let now = Instant::now();
+        self.copies
+            .iter()
+            .filter(|c| matches!(c.value(), HotCopy::Ready { until, .. } if *until > now))
+            .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -0,0 +1,199 @@
+use std::{
+    io::ErrorKind as IoErrorKind,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use app_core::clock::Clock;
+use serde::Deserialize;
+use tokio::sync::Mutex;
+use tracing::info;
+
+use crate::{
+    core::domain::{
+        models::{AppError, ImportReport},
+        services::ConsistentHasherService,
+    },
+    infrastructure::adapters::services::{
+        bulk_put::BulkPut,
+        redis_rdb::{is_rdb, parse_rdb},
+        tcp_network_service::TcpNetworkService,
+    },
+};
+
+/// Carga masiva desde archivos de `dir`, para migrar desde Redis: un dump RDB o un export
+/// NDJSON, una entrada por línea:
+///
+/// `{"key": "..", "value": "..", "expires_at": <unix ms>, "ttl_ms": <ms>, "tags": [..]}`
+///
+/// con `expires_at`, `ttl_ms` y `tags` opcionales. Las entradas se escriben con `PUT`
+/// normales (ver `BulkPut`); el archivo se lee entero a memoria.
+pub struct ImportService {
+    network: Arc<TcpNetworkService>,
+    hasher: Arc<dyn ConsistentHasherService>,
+    dir: PathBuf,
+    clock: Arc<dyn Clock>,
+    /// Copias de cada clave que se escribe, ver `BulkPut`.
+    replication_factor: usize,
+    /// Un import a la vez.
+    running: Mutex<()>,
+}
+
+/// Una línea del NDJSON.
+#[derive(Debug, Deserialize)]
+struct JsonEntry {
+    key: String,
+    value: String,
+    expires_at: Option<u64>,
+    ttl_ms: Option<u64>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// Entrada lista para escribir, venga del formato que venga.
+struct Entry {
+    key: String,
+    value: String,
+    expires_at: Option<u64>,
+    tags: Vec<String>,
+}
+
+impl ImportService {
+    pub fn new(
+        network: Arc<TcpNetworkService>,
+        hasher: Arc<dyn ConsistentHasherService>,
+        dir: impl Into<PathBuf>,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        Self {
+            network,
+            hasher,
+            dir: dir.into(),
+            clock,
+            replication_factor: 1,
+            running: Mutex::new(()),
+        }
+    }
+
+    pub fn with_replication_factor(mut self, replication_factor: usize) -> Self {
+        self.replication_factor = replication_factor;
+        self
+    }
+
+    /// Importa `file`, un nombre dentro de `dir` (sin directorios). El formato sale del
+    /// contenido: RDB si empieza con `REDIS`, NDJSON si no.
+    pub async fn import(&self, file: &str) -> Result<ImportReport, AppError> {
+        let _running = self
+            .running
+            .try_lock()
+            .map_err(|_| AppError::Conflict("ya hay un import en curso".into()))?;
+
+        if Path::new(file).file_name().and_then(|name| name.to_str()) != Some(file)
+            || file.starts_with('.')
+        {
+            return Err(AppError::BadRequest(format!(
+                "IMPORT: '{file}' no es un nombre de archivo"
+            )));
+        }
+        let path = self.dir.join(file);
+        let data = tokio::fs::read(&path).await.map_err(|e| match e.kind() {
+            IoErrorKind::NotFound => AppError::NotFound(path.display().to_string()),
+            _ => AppError::Storage(format!("{}: {e}", path.display())),
+        })?;
+
+        let mut report = ImportReport {
+            file: file.to_string(),
+            ..ImportReport::default()
+        };
+        let entries = if is_rdb(&data) {
+            report.format = "rdb";
+            let dump = tokio::task::spawn_blocking(move || parse_rdb(&data))
+                .await
+                .map_err(|e| AppError::Storage(e.to_string()))??;
+            report.skipped = dump.skipped;
+            dump.entries
+                .into_iter()
+                .map(|e| Entry {
+                    key: e.key,
+                    value: e.value,
+                    expires_at: e.expires_at,
+                    tags: Vec::new(),
+                })
+                .collect()
+        } else {
+            report.format = "ndjson";
+            self.parse_ndjson(&data, &mut report)
+        };
+
+        let now_ms = self.clock.now_millis().as_millis_u64();
+        let mut puts = BulkPut::new(self.network.clone(), self.hasher.clone())
+            .with_replication_factor(self.replication_factor);
+        for entry in entries {
+            if entry.expires_at.is_some_and(|at| at <= now_ms) {
+                report.expired += 1;
+                continue;
+            }
+            // el cluster no guarda valores vacíos
+            if entry.key.is_empty() || entry.value.is_empty() {
+                report.skipped += 1;
+                continue;
+            }
+            puts.put(entry.key, entry.value, entry.expires_at, entry.tags)
+                .await;
+        }
+        let tally = puts.finish().await;
+        report.imported = tally.written;
+        report.failed += tally.failed;
+        if report.first_error.is_none() {
+            report.first_error = tally.first_error;
+        }
+
+        info!(
+            file = %report.file,
+            format = report.format,
+            imported = report.imported,
+            expired = report.expired,
+            skipped = report.skipped,
+            failed = report.failed,
+            "Import terminado"
+        );
+        Ok(report)
+    }
+
+    /// Las líneas que no se pueden leer cuentan como `failed`.
+    fn parse_ndjson(&self, data: &[u8], report: &mut ImportReport) -> Vec<Entry> {
+        let now_ms = self.clock.now_millis().as_millis_u64();
+        let text = String::from_utf8_lossy(data);
+
+        let mut entries = Vec::new();
+        for (n, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry = match serde_json::from_str::<JsonEntry>(line) {
+                Ok(entry) if entry.expires_at.is_some() && entry.ttl_ms.is_some() => {
+                    Err("expires_at y ttl_ms a la vez".to_string())
+                }
+                Ok(entry) => Ok(entry),
+                Err(e) => Err(e.to_string()),
+            };
+            match entry {
+                Ok(entry) => entries.push(Entry {
+                    expires_at: entry
+                        .expires_at
+                        .or(entry.ttl_ms.map(|ttl| now_ms.saturating_add(ttl))),
+                    key: entry.key,
+                    value: entry.value,
+                    tags: entry.tags,
+                }),
+                Err(e) => {
+                    report.failed += 1;
+                    report
+                        .first_error
+                        .get_or_insert_with(|| format!("línea {}: {e}", n + 1));
+                }
+            }
+        }
+        entries
+    }
+}
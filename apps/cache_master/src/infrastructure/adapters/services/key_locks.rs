@@ -0,0 +1,70 @@
+use std::{future::Future, sync::Arc, time::Duration};
+
+use dashmap::DashMap;
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+/// Un candado por clave para que las escrituras de una misma clave salgan hacia su shard de
+/// a una: la siguiente espera a que todos los nodos contestaran la anterior, así todos las
+/// aplican en el mismo orden. Un candado queda en el mapa solo mientras alguien lo tiene o
+/// lo espera.
+pub struct KeyLocks {
+    locks: DashMap<String, Arc<Mutex<()>>>,
+    /// Lo más que una escritura retiene su clave esperando al resto del shard.
+    max_hold: Duration,
+}
+
+impl KeyLocks {
+    pub fn new(max_hold: Duration) -> Self {
+        Self {
+            locks: DashMap::new(),
+            max_hold,
+        }
+    }
+
+    /// Espera a que `key` quede libre y la toma hasta que se suelte el guard.
+    pub async fn lock(self: &Arc<Self>, key: &str) -> KeyGuard {
+        let lock = self.locks.entry(key.to_string()).or_default().clone();
+        KeyGuard {
+            locks: self.clone(),
+            key: key.to_string(),
+            guard: Some(lock.lock_owned().await),
+        }
+    }
+
+    /// Claves tomadas o esperadas ahora.
+    pub fn len(&self) -> usize {
+        self.locks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.locks.is_empty()
+    }
+}
+
+/// La clave tomada; se suelta al descartarlo.
+pub struct KeyGuard {
+    locks: Arc<KeyLocks>,
+    key: String,
+    guard: Option<OwnedMutexGuard<()>>,
+}
+
+impl KeyGuard {
+    /// Retiene la clave mientras corre `work`, hasta `max_hold`; `None` si se venció antes.
+    pub async fn hold<F: Future>(self, work: F) -> Option<F::Output> {
+        tokio::time::timeout(self.locks.max_hold, work).await.ok()
+    }
+}
+
+impl Drop for KeyGuard {
+    fn drop(&mut self) {
+        let Some(guard) = self.guard.take() else {
+            return;
+        };
+        let lock = OwnedMutexGuard::mutex(&guard).clone();
+        drop(guard);
+        // el del mapa y este: nadie más lo espera
+        self.locks.locks.remove_if(&self.key, |_, current| {
+            Arc::ptr_eq(current, &lock) && Arc::strong_count(&lock) == 2
+        });
+    }
+}
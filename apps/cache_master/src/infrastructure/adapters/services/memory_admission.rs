@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use dashmap::DashSet;
+
+use crate::core::domain::models::MemoryWatermarks;
+
+/// Nodos sobre la marca alta de memoria, según lo último que reportaron (`CACHE-PRESSURE`
+/// o `STATS`). Un nodo sin `MAX_MEMORY_BYTES` nunca entra.
+#[derive(Default)]
+pub struct MemoryAdmission {
+    watermarks: MemoryWatermarks,
+    over: DashSet<Arc<str>>,
+}
+
+impl MemoryAdmission {
+    pub fn new(watermarks: MemoryWatermarks) -> Self {
+        Self {
+            watermarks,
+            over: DashSet::new(),
+        }
+    }
+
+    /// Anota la ocupación de `node_id` (`None` si no tiene tope). Devuelve el estado nuevo
+    /// si cambió: `Some(true)` al pasar la marca alta, `Some(false)` al bajar de la baja.
+    pub fn observe(&self, node_id: &Arc<str>, ratio: Option<f64>) -> Option<bool> {
+        let was_over = self.over.contains(node_id);
+        let over = ratio.is_some_and(|ratio| self.watermarks.next(was_over, ratio));
+        if over == was_over {
+            return None;
+        }
+        if over {
+            self.over.insert(node_id.clone());
+        } else {
+            self.over.remove(node_id);
+        }
+        Some(over)
+    }
+
+    pub fn is_over(&self, node_id: &str) -> bool {
+        self.over.contains(node_id)
+    }
+
+    pub fn forget(&self, node_id: &str) {
+        self.over.remove(node_id);
+    }
+
+    /// Ordenados por id.
+    pub fn over_nodes(&self) -> Vec<Arc<str>> {
+        let mut nodes: Vec<_> = self.over.iter().map(|n| n.key().clone()).collect();
+        nodes.sort();
+        nodes
+    }
+}
@@ -1,5 +1,31 @@
+pub mod backup_service;
+pub mod backup_store;
+pub mod bulk_put;
 pub mod dashmap_consistent_hasher_service;
+pub mod fanout;
+pub mod hot_key_copies;
+pub mod import_service;
+pub mod key_locks;
+pub mod memory_admission;
+pub mod node_access;
+pub mod read_only;
+pub mod redis_rdb;
+pub mod registration_queue;
+pub mod s3_backup_store;
+pub mod single_flight;
+pub mod stats_aggregation_service;
 pub mod tcp_network_service;
-pub mod utils;
 
-pub use utils::request_all_race_first_abort_rest;
+pub use backup_service::{BackupService, run_backups};
+pub use backup_store::{BackupStore, BackupTarget, LocalBackupStore};
+pub use bulk_put::{BulkPut, BulkTally};
+pub use fanout::{FanoutOutcome, FanoutPolicy, HedgeDelay, NodeOutcome, Stragglers, fanout};
+pub use hot_key_copies::HotKeyCopies;
+pub use import_service::ImportService;
+pub use key_locks::{KeyGuard, KeyLocks};
+pub use memory_admission::MemoryAdmission;
+pub use node_access::{NodeAccess, NodeRule};
+pub use read_only::ReadOnlySwitches;
+pub use registration_queue::{RegistrationLimits, RegistrationQueue};
+pub use s3_backup_store::{S3BackupStore, S3Config};
+pub use single_flight::SingleFlight;
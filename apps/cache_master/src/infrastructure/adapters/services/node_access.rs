@@ -0,0 +1,186 @@
+use std::{fmt, net::IpAddr, str::FromStr};
+
+use app_net::event::group;
+use parking_lot::RwLock;
+
+use crate::core::domain::models::AppError;
+
+/// Regla de `NODE_ALLOW`, `NODE_DENY` y `BAN`: una IP o red (`10.0.0.0/8`, `::1`) o, si no,
+/// un patrón de id de nodo donde `*` es cualquier cosa (`node-*`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeRule {
+    Id(String),
+    Net { addr: IpAddr, prefix: u8 },
+}
+
+impl NodeRule {
+    /// `ip` es la del otro extremo de la conexión; `None` si no es TCP.
+    pub fn matches(&self, node_id: &str, ip: Option<IpAddr>) -> bool {
+        match self {
+            Self::Id(pattern) => glob_matches(pattern, node_id),
+            Self::Net { addr, prefix } => ip.is_some_and(|ip| in_network(ip, *addr, *prefix)),
+        }
+    }
+}
+
+impl FromStr for NodeRule {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(AppError::BadRequest("regla de nodo vacía".into()));
+        }
+        let (host, prefix) = match s.split_once('/') {
+            Some((host, prefix)) => (host, Some(prefix)),
+            None => (s, None),
+        };
+        let Ok(addr) = host.parse::<IpAddr>() else {
+            return Ok(Self::Id(s.to_string()));
+        };
+
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            None => max,
+            Some(prefix) => prefix
+                .parse::<u8>()
+                .ok()
+                .filter(|p| *p <= max)
+                .ok_or_else(|| AppError::BadRequest(format!("red inválida: {s}")))?,
+        };
+        Ok(Self::Net { addr, prefix })
+    }
+}
+
+impl fmt::Display for NodeRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Id(pattern) => f.write_str(pattern),
+            Self::Net { addr, prefix } => write!(f, "{addr}/{prefix}"),
+        }
+    }
+}
+
+/// Qué nodos pueden registrarse. Un nodo que coincide con una regla de `deny` o con un
+/// `BAN` se rechaza; si hay reglas de `allow`, además tiene que coincidir con alguna. Si
+/// presentó un certificado (TLS mutuo), el id que anuncia tiene que ser el del
+/// certificado. Los clientes no pasan por acá.
+#[derive(Default)]
+pub struct NodeAccess {
+    allow: Vec<NodeRule>,
+    deny: Vec<NodeRule>,
+    /// Agregadas en caliente con `BAN`; no sobreviven a un reinicio.
+    bans: RwLock<Vec<NodeRule>>,
+    /// Rechazar a los nodos que no presentaron certificado.
+    certs_required: bool,
+}
+
+impl NodeAccess {
+    pub fn new(allow: Vec<NodeRule>, deny: Vec<NodeRule>) -> Self {
+        Self {
+            allow,
+            deny,
+            bans: RwLock::new(Vec::new()),
+            certs_required: false,
+        }
+    }
+
+    pub fn with_certs_required(mut self, required: bool) -> Self {
+        self.certs_required = required;
+        self
+    }
+
+    /// `Err` con el motivo si el nodo no puede registrarse. `identity` es la del
+    /// certificado que presentó, si lo hizo; vale para todos los grupos de su proceso.
+    pub fn check(
+        &self,
+        node_id: &str,
+        ip: Option<IpAddr>,
+        identity: Option<&str>,
+    ) -> Result<(), String> {
+        match identity {
+            Some(identity) if identity != group::process_id(node_id) => {
+                return Err(format!("el certificado es de {identity}"));
+            }
+            None if self.certs_required => {
+                return Err("hace falta un certificado de cliente".into());
+            }
+            _ => {}
+        }
+        let bans = self.bans.read();
+        if let Some(rule) = self
+            .deny
+            .iter()
+            .chain(bans.iter())
+            .find(|rule| rule.matches(node_id, ip))
+        {
+            return Err(format!("denegado por {rule}"));
+        }
+        if !self.allow.is_empty() && !self.allow.iter().any(|rule| rule.matches(node_id, ip)) {
+            return Err("no figura en NODE_ALLOW".into());
+        }
+        Ok(())
+    }
+
+    /// `false` si ya estaba.
+    pub fn ban(&self, rule: NodeRule) -> bool {
+        let mut bans = self.bans.write();
+        if bans.contains(&rule) {
+            return false;
+        }
+        bans.push(rule);
+        true
+    }
+
+    /// `false` si no estaba.
+    pub fn unban(&self, rule: &NodeRule) -> bool {
+        let mut bans = self.bans.write();
+        let before = bans.len();
+        bans.retain(|r| r != rule);
+        bans.len() != before
+    }
+
+    /// En el orden en que se agregaron.
+    pub fn bans(&self) -> Vec<NodeRule> {
+        self.bans.read().clone()
+    }
+}
+
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // sin `*` hay una sola parte y tiene que ser todo el texto
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = parts.collect();
+    let Some(last) = parts.pop() else {
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+fn in_network(ip: IpAddr, network: IpAddr, prefix: u8) -> bool {
+    // una IPv4 que llega como IPv6 mapeada (`::ffff:a.b.c.d`) se compara como IPv4
+    let ip = match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(ip, IpAddr::V4),
+        v4 => v4,
+    };
+    match (ip, network) {
+        (IpAddr::V4(ip), IpAddr::V4(net)) => {
+            let mask = u32::MAX.checked_shl(32 - u32::from(prefix)).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(net) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(net)) => {
+            let mask = u128::MAX.checked_shl(128 - u32::from(prefix)).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(net) & mask
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,54 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+use dashmap::DashSet;
+
+/// Interruptores de solo lectura del master: uno para todo el cluster y uno por nodo. Un
+/// nodo en solo lectura frena las escrituras de todo su shard, porque van a todos sus
+/// nodos; las lecturas siguen igual.
+#[derive(Default)]
+pub struct ReadOnlySwitches {
+    cluster: AtomicBool,
+    /// Ids de nodo; pueden no estar conectados (p. ej. prendidos desde el entorno).
+    nodes: DashSet<Arc<str>>,
+}
+
+impl ReadOnlySwitches {
+    pub fn new(cluster: bool, nodes: impl IntoIterator<Item = Arc<str>>) -> Self {
+        Self {
+            cluster: AtomicBool::new(cluster),
+            nodes: nodes.into_iter().collect(),
+        }
+    }
+
+    pub fn cluster(&self) -> bool {
+        self.cluster.load(Ordering::Relaxed)
+    }
+
+    /// Devuelve el valor anterior.
+    pub fn set_cluster(&self, on: bool) -> bool {
+        self.cluster.swap(on, Ordering::Relaxed)
+    }
+
+    pub fn node(&self, node_id: &str) -> bool {
+        self.nodes.contains(node_id)
+    }
+
+    /// Devuelve el valor anterior.
+    pub fn set_node(&self, node_id: &str, on: bool) -> bool {
+        if on {
+            !self.nodes.insert(Arc::from(node_id))
+        } else {
+            self.nodes.remove(node_id).is_some()
+        }
+    }
+
+    /// Ordenados por id.
+    pub fn nodes(&self) -> Vec<Arc<str>> {
+        let mut nodes: Vec<_> = self.nodes.iter().map(|n| n.key().clone()).collect();
+        nodes.sort();
+        nodes
+    }
+}
@@ -0,0 +1,291 @@
+//! Lectura de un dump RDB de Redis (versiones 1 a 12) para `IMPORT`. Solo se importan las
+//! claves string de la base 0, con su vencimiento; el resto de los tipos se recorre para
+//! seguir leyendo y se cuenta como salteado. Los streams, los módulos y los hashes con TTL
+//! por campo cortan la lectura, porque no se pueden saltear sin interpretarlos.
+
+use crate::core::domain::models::AppError;
+
+const MAGIC: &[u8] = b"REDIS";
+const MAX_VERSION: u32 = 12;
+
+// opcodes
+const SLOT_INFO: u8 = 0xF4;
+const FUNCTION2: u8 = 0xF5;
+const MODULE_AUX: u8 = 0xF7;
+const IDLE: u8 = 0xF8;
+const FREQ: u8 = 0xF9;
+const AUX: u8 = 0xFA;
+const RESIZE_DB: u8 = 0xFB;
+const EXPIRE_TIME_MS: u8 = 0xFC;
+const EXPIRE_TIME: u8 = 0xFD;
+const SELECT_DB: u8 = 0xFE;
+const EOF: u8 = 0xFF;
+
+/// Una clave string del dump.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RdbEntry {
+    pub key: String,
+    pub value: String,
+    /// Instante unix en ms, como lo guarda Redis.
+    pub expires_at: Option<u64>,
+}
+
+/// Lo que se puede importar del dump.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RdbDump {
+    pub entries: Vec<RdbEntry>,
+    /// Claves de otros tipos o de otras bases, o con clave o valor que no son UTF-8.
+    pub skipped: u64,
+}
+
+/// `true` si `data` empieza como un dump RDB.
+pub fn is_rdb(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+pub fn parse_rdb(data: &[u8]) -> Result<RdbDump, AppError> {
+    let mut reader = Reader { data, pos: 0 };
+    if reader.take(MAGIC.len())? != MAGIC {
+        return Err(invalid("no empieza con REDIS"));
+    }
+    let version = std::str::from_utf8(reader.take(4)?)
+        .ok()
+        .and_then(|v| v.parse::<u32>().ok())
+        .ok_or_else(|| invalid("versión ilegible"))?;
+    if version > MAX_VERSION {
+        return Err(invalid(&format!("versión {version} no soportada")));
+    }
+
+    let mut dump = RdbDump::default();
+    let mut db = 0;
+    let mut expires_at = None;
+    loop {
+        match reader.byte()? {
+            EOF => break,
+            SELECT_DB => db = reader.length()?,
+            EXPIRE_TIME => {
+                let secs = u32::from_le_bytes(reader.array()?);
+                expires_at = Some(u64::from(secs) * 1000);
+            }
+            EXPIRE_TIME_MS => expires_at = Some(u64::from_le_bytes(reader.array()?)),
+            RESIZE_DB => {
+                reader.length()?;
+                reader.length()?;
+            }
+            AUX => {
+                reader.string()?;
+                reader.string()?;
+            }
+            FREQ => {
+                reader.byte()?;
+            }
+            IDLE => {
+                reader.length()?;
+            }
+            FUNCTION2 => {
+                reader.string()?;
+            }
+            SLOT_INFO => {
+                for _ in 0..3 {
+                    reader.length()?;
+                }
+            }
+            MODULE_AUX => return Err(invalid("datos de módulos no soportados")),
+            kind => {
+                let key = reader.string()?;
+                let value = reader.value(kind)?;
+                let expires_at = expires_at.take();
+
+                let entry = match (db, value) {
+                    (0, Some(value)) => String::from_utf8(key)
+                        .ok()
+                        .zip(String::from_utf8(value).ok()),
+                    _ => None,
+                };
+                match entry {
+                    Some((key, value)) => dump.entries.push(RdbEntry {
+                        key,
+                        value,
+                        expires_at,
+                    }),
+                    None => dump.skipped += 1,
+                }
+            }
+        }
+    }
+    Ok(dump)
+}
+
+fn invalid(reason: &str) -> AppError {
+    AppError::BadRequest(format!("RDB inválido: {reason}"))
+}
+
+/// Largo de un string o un entero codificado en su lugar (los dos bits altos en `11`).
+enum Length {
+    Len(u64),
+    Encoded(u8),
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], AppError> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| invalid("termina antes de tiempo"))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], AppError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn byte(&mut self) -> Result<u8, AppError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn raw_length(&mut self) -> Result<Length, AppError> {
+        let first = self.byte()?;
+        let len = match first >> 6 {
+            0 => u64::from(first & 0x3F),
+            1 => (u64::from(first & 0x3F) << 8) | u64::from(self.byte()?),
+            2 => match first {
+                0x80 => u64::from(u32::from_be_bytes(self.array()?)),
+                0x81 => u64::from_be_bytes(self.array()?),
+                _ => return Err(invalid("largo ilegible")),
+            },
+            _ => return Ok(Length::Encoded(first & 0x3F)),
+        };
+        Ok(Length::Len(len))
+    }
+
+    fn length(&mut self) -> Result<u64, AppError> {
+        match self.raw_length()? {
+            Length::Len(len) => Ok(len),
+            Length::Encoded(_) => Err(invalid("se esperaba un largo")),
+        }
+    }
+
+    fn usize_length(&mut self) -> Result<usize, AppError> {
+        usize::try_from(self.length()?).map_err(|_| invalid("largo fuera de rango"))
+    }
+
+    /// String de Redis: crudo, entero o comprimido con LZF.
+    fn string(&mut self) -> Result<Vec<u8>, AppError> {
+        match self.raw_length()? {
+            Length::Len(len) => {
+                let len = usize::try_from(len).map_err(|_| invalid("largo fuera de rango"))?;
+                Ok(self.take(len)?.to_vec())
+            }
+            Length::Encoded(0) => Ok((self.byte()? as i8).to_string().into_bytes()),
+            Length::Encoded(1) => Ok(i16::from_le_bytes(self.array()?).to_string().into_bytes()),
+            Length::Encoded(2) => Ok(i32::from_le_bytes(self.array()?).to_string().into_bytes()),
+            Length::Encoded(3) => {
+                let compressed = self.usize_length()?;
+                let len = self.usize_length()?;
+                let data = self.take(compressed)?;
+                lzf_decompress(data, len).ok_or_else(|| invalid("string LZF corrupto"))
+            }
+            Length::Encoded(other) => Err(invalid(&format!("codificación {other} desconocida"))),
+        }
+    }
+
+    /// Valor de tipo `kind`: `Some` si es un string, `None` si es de otro tipo y se salteó.
+    fn value(&mut self, kind: u8) -> Result<Option<Vec<u8>>, AppError> {
+        match kind {
+            // string
+            0 => return self.string().map(Some),
+            // list, set
+            1 | 2 => {
+                for _ in 0..self.length()? {
+                    self.string()?;
+                }
+            }
+            // zset con el score como texto
+            3 => {
+                for _ in 0..self.length()? {
+                    self.string()?;
+                    let len = self.byte()?;
+                    if len < 253 {
+                        self.take(usize::from(len))?;
+                    }
+                }
+            }
+            // hash
+            4 => {
+                for _ in 0..self.length()?.saturating_mul(2) {
+                    self.string()?;
+                }
+            }
+            // zset con el score binario
+            5 => {
+                for _ in 0..self.length()? {
+                    self.string()?;
+                    self.take(8)?;
+                }
+            }
+            // zipmap, ziplist, intset, listpack...: un solo blob
+            9..=13 | 16 | 17 | 20 => {
+                self.string()?;
+            }
+            // quicklist
+            14 => {
+                for _ in 0..self.length()? {
+                    self.string()?;
+                }
+            }
+            // quicklist con el tipo de cada nodo
+            18 => {
+                for _ in 0..self.length()? {
+                    self.length()?;
+                    self.string()?;
+                }
+            }
+            other => return Err(invalid(&format!("tipo de valor {other} no soportado"))),
+        }
+        Ok(None)
+    }
+}
+
+/// Descomprime un bloque LZF (el formato de `lzf_compress` que usa Redis); `None` si no
+/// da exactamente `len` bytes.
+fn lzf_decompress(data: &[u8], len: usize) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(len);
+    let mut i = 0;
+    while i < data.len() {
+        let ctrl = usize::from(data[i]);
+        i += 1;
+
+        if ctrl < 32 {
+            // literal de ctrl + 1 bytes
+            let literal = data.get(i..i + ctrl + 1)?;
+            out.extend_from_slice(literal);
+            i += ctrl + 1;
+            continue;
+        }
+
+        // referencia hacia atrás en lo ya descomprimido
+        let mut run = ctrl >> 5;
+        if run == 7 {
+            run += usize::from(*data.get(i)?);
+            i += 1;
+        }
+        let back = ((ctrl & 0x1F) << 8) + usize::from(*data.get(i)?) + 1;
+        i += 1;
+        let start = out.len().checked_sub(back)?;
+        // se copia de a un byte: la referencia puede pisarse con lo que se escribe
+        for offset in 0..run + 2 {
+            out.push(out[start + offset]);
+        }
+    }
+    (out.len() == len).then_some(out)
+}
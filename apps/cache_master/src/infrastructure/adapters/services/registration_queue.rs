@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// Cuántas altas de nodos corren a la vez y cuánto se demora, al azar, cada una que llega
+/// con la cola llena.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegistrationLimits {
+    pub concurrency: usize,
+    pub jitter: Duration,
+}
+
+impl Default for RegistrationLimits {
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            jitter: Duration::from_millis(250),
+        }
+    }
+}
+
+/// Turnos para registrar nodos. Cuando el master reinicia se le reconectan todos a la vez;
+/// con la cola las altas (y los cambios del anillo que traen) se escalonan en vez de
+/// pisarse. Los clientes no pasan por acá.
+pub struct RegistrationQueue {
+    permits: Semaphore,
+    jitter: Duration,
+}
+
+impl RegistrationQueue {
+    pub fn new(limits: RegistrationLimits) -> Self {
+        Self {
+            permits: Semaphore::new(limits.concurrency.max(1)),
+            jitter: limits.jitter,
+        }
+    }
+
+    /// Espera el turno. Si no hay uno libre, antes de ponerse en la fila espera un rato al
+    /// azar menor a `jitter`, para que una ráfaga de conexiones no quede en el orden en que
+    /// llegó ni se despierte toda junta.
+    pub async fn acquire(&self) -> SemaphorePermit<'_> {
+        if self.permits.available_permits() == 0 && !self.jitter.is_zero() {
+            tokio::time::sleep(self.jitter.mul_f64(fastrand::f64())).await;
+        }
+        self.permits
+            .acquire()
+            .await
+            .expect("el semáforo de altas no se cierra")
+    }
+
+    /// Turnos libres en este momento.
+    pub fn available(&self) -> usize {
+        self.permits.available_permits()
+    }
+}
+
+impl Default for RegistrationQueue {
+    fn default() -> Self {
+        Self::new(RegistrationLimits::default())
+    }
+}
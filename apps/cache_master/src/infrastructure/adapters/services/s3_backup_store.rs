@@ -0,0 +1,372 @@
+use std::{
+    env,
+    fmt::{self, Write as _},
+    sync::Arc,
+    time::Duration,
+};
+
+use app_core::clock::Clock;
+use async_trait::async_trait;
+use hmac_sha256::{HMAC, Hash};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+use tracing::warn;
+
+use crate::{
+    core::domain::models::AppError,
+    infrastructure::adapters::services::backup_store::{BackupStore, MANIFEST},
+};
+
+/// Tope de cada request a S3, conexión incluida.
+const S3_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Bucket de un servicio compatible con S3 (MinIO, Ceph, ...) por HTTP plano, con URLs
+/// de estilo path (`http://host/bucket/key`) y firma SigV4.
+#[derive(Clone, PartialEq, Eq)]
+pub struct S3Config {
+    /// `host:puerto`, sin esquema.
+    pub host: String,
+    pub bucket: String,
+    /// Se antepone a cada backup (`<prefix>/<id>/...`); vacío, van en la raíz del bucket.
+    pub prefix: String,
+    pub region: String,
+    pub access_key: String,
+    pub secret_key: String,
+}
+
+/// Sin la clave secreta, que termina en logs con el resto de la configuración.
+impl fmt::Debug for S3Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("S3Config")
+            .field("host", &self.host)
+            .field("bucket", &self.bucket)
+            .field("prefix", &self.prefix)
+            .field("region", &self.region)
+            .field("access_key", &self.access_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl S3Config {
+    /// `BACKUP_S3_BUCKET`, `BACKUP_S3_ENDPOINT` (`http://host:puerto`),
+    /// `BACKUP_S3_PREFIX`, `BACKUP_S3_REGION` (`us-east-1` por defecto),
+    /// `BACKUP_S3_ACCESS_KEY` y `BACKUP_S3_SECRET_KEY`. `None` sin bucket o sin un endpoint
+    /// `http://` válido.
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| env::var(name).ok().filter(|v| !v.trim().is_empty());
+        let bucket = var("BACKUP_S3_BUCKET")?;
+        let endpoint = var("BACKUP_S3_ENDPOINT").unwrap_or_default();
+        let Some(host) = endpoint
+            .strip_prefix("http://")
+            .map(|h| h.trim_end_matches('/'))
+            .filter(|h| !h.is_empty())
+        else {
+            warn!("BACKUP_S3_ENDPOINT tiene que ser http://host:puerto (no hay TLS): '{endpoint}'");
+            return None;
+        };
+
+        Some(Self {
+            host: host.to_string(),
+            bucket,
+            prefix: var("BACKUP_S3_PREFIX")
+                .map(|p| p.trim_matches('/').to_string())
+                .unwrap_or_default(),
+            region: var("BACKUP_S3_REGION").unwrap_or_else(|| "us-east-1".to_string()),
+            access_key: var("BACKUP_S3_ACCESS_KEY").unwrap_or_default(),
+            secret_key: var("BACKUP_S3_SECRET_KEY").unwrap_or_default(),
+        })
+    }
+}
+
+pub struct S3BackupStore {
+    config: S3Config,
+    clock: Arc<dyn Clock>,
+}
+
+/// Status y cuerpo de una respuesta.
+struct S3Response {
+    status: u16,
+    body: Vec<u8>,
+}
+
+impl S3BackupStore {
+    pub fn new(config: S3Config, clock: Arc<dyn Clock>) -> Self {
+        Self { config, clock }
+    }
+
+    fn key(&self, set: &str, name: &str) -> String {
+        match self.config.prefix.as_str() {
+            "" => format!("{set}/{name}"),
+            prefix => format!("{prefix}/{set}/{name}"),
+        }
+    }
+
+    /// `Authorization` de SigV4 para el request; `headers` en minúsculas y ordenados, con
+    /// `host`, `x-amz-content-sha256` y `x-amz-date`.
+    pub(crate) fn authorization(
+        &self,
+        method: &str,
+        uri: &str,
+        query: &str,
+        headers: &[(&str, &str)],
+        payload_hash: &str,
+        amz_date: &str,
+    ) -> String {
+        let date = &amz_date[..8];
+        let scope = format!("{date}/{}/s3/aws4_request", self.config.region);
+
+        let canonical_headers: String = headers
+            .iter()
+            .map(|(name, value)| format!("{name}:{}\n", value.trim()))
+            .collect();
+        let signed_headers = headers
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(";");
+        let canonical_request = format!(
+            "{method}\n{uri}\n{query}\n{canonical_headers}\n{signed_headers}\n{payload_hash}"
+        );
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+            hex(&Hash::hash(canonical_request.as_bytes()))
+        );
+
+        let key = [date, self.config.region.as_str(), "s3", "aws4_request"]
+            .iter()
+            .fold(
+                format!("AWS4{}", self.config.secret_key).into_bytes(),
+                |key, part| HMAC::mac(part.as_bytes(), &key).to_vec(),
+            );
+        let signature = hex(&HMAC::mac(string_to_sign.as_bytes(), &key));
+
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, \
+             Signature={signature}",
+            self.config.access_key
+        )
+    }
+
+    /// `key` vacía es el bucket; `query` ya codificada y ordenada.
+    async fn send(
+        &self,
+        method: &str,
+        key: &str,
+        query: &str,
+        body: Vec<u8>,
+    ) -> Result<S3Response, AppError> {
+        let bucket = uri_encode(&self.config.bucket, false);
+        let uri = match key {
+            "" => format!("/{bucket}"),
+            key => format!("/{bucket}/{}", uri_encode(key, true)),
+        };
+        let amz_date = self.clock.now_millis().to_utc_basic();
+        let payload_hash = hex(&Hash::hash(&body));
+        let host = self.config.host.as_str();
+        let authorization = self.authorization(
+            method,
+            &uri,
+            query,
+            &[
+                ("host", host),
+                ("x-amz-content-sha256", &payload_hash),
+                ("x-amz-date", &amz_date),
+            ],
+            &payload_hash,
+            &amz_date,
+        );
+
+        let target = match query {
+            "" => uri,
+            query => format!("{uri}?{query}"),
+        };
+        let head = format!(
+            "{method} {target} HTTP/1.1\r\nHost: {host}\r\nx-amz-content-sha256: {payload_hash}\r\n\
+             x-amz-date: {amz_date}\r\nAuthorization: {authorization}\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        );
+
+        let exchange = async {
+            let mut stream = TcpStream::connect(host).await?;
+            stream.write_all(head.as_bytes()).await?;
+            stream.write_all(&body).await?;
+            let mut raw = Vec::new();
+            stream.read_to_end(&mut raw).await?;
+            Ok::<_, std::io::Error>(raw)
+        };
+        let raw = tokio::time::timeout(S3_TIMEOUT, exchange)
+            .await
+            .map_err(|_| AppError::Storage(format!("S3 {method} {target}: timeout")))?
+            .map_err(|e| AppError::Storage(format!("S3 {method} {target}: {e}")))?;
+
+        parse_response(&raw)
+            .ok_or_else(|| AppError::Storage(format!("S3 {method} {target}: respuesta ilegible")))
+    }
+
+    fn failure(&self, what: &str, res: &S3Response) -> AppError {
+        let body = String::from_utf8_lossy(&res.body);
+        let code = between(&body, "<Code>", "</Code>").unwrap_or_default();
+        AppError::Storage(format!("S3 {what}: {} {code}", res.status))
+    }
+}
+
+#[async_trait]
+impl BackupStore for S3BackupStore {
+    async fn write(&self, set: &str, name: &str, data: Vec<u8>) -> Result<(), AppError> {
+        let key = self.key(set, name);
+        let res = self.send("PUT", &key, "", data).await?;
+        if !(200..300).contains(&res.status) {
+            return Err(self.failure(&format!("PUT {key}"), &res));
+        }
+        Ok(())
+    }
+
+    async fn read(&self, set: &str, name: &str) -> Result<Vec<u8>, AppError> {
+        let key = self.key(set, name);
+        let res = self.send("GET", &key, "", Vec::new()).await?;
+        match res.status {
+            200..=299 => Ok(res.body),
+            404 => Err(AppError::NotFound(key)),
+            _ => Err(self.failure(&format!("GET {key}"), &res)),
+        }
+    }
+
+    /// `ListObjectsV2` de todo el prefijo, de a páginas.
+    async fn sets(&self) -> Result<Vec<String>, AppError> {
+        let prefix = match self.config.prefix.as_str() {
+            "" => String::new(),
+            prefix => format!("{prefix}/"),
+        };
+        let mut sets = Vec::new();
+        let mut token: Option<String> = None;
+
+        loop {
+            let mut query = String::new();
+            if let Some(token) = &token {
+                let _ = write!(query, "continuation-token={}&", uri_encode(token, false));
+            }
+            let _ = write!(query, "list-type=2&prefix={}", uri_encode(&prefix, false));
+
+            let res = self.send("GET", "", &query, Vec::new()).await?;
+            if res.status != 200 {
+                return Err(self.failure("LIST", &res));
+            }
+            let body = String::from_utf8_lossy(&res.body);
+            for key in all_between(&body, "<Key>", "</Key>") {
+                let key = xml_unescape(key);
+                let set = key
+                    .strip_prefix(prefix.as_str())
+                    .and_then(|rest| rest.strip_suffix(&format!("/{MANIFEST}")))
+                    .filter(|set| !set.contains('/'));
+                if let Some(set) = set {
+                    sets.push(set.to_string());
+                }
+            }
+
+            token = match between(&body, "<IsTruncated>", "</IsTruncated>") {
+                Some("true") => {
+                    between(&body, "<NextContinuationToken>", "</NextContinuationToken>")
+                        .map(xml_unescape)
+                }
+                _ => None,
+            };
+            if token.is_none() {
+                break;
+            }
+        }
+
+        sets.sort();
+        Ok(sets)
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .fold(String::with_capacity(bytes.len() * 2), |mut out, b| {
+            let _ = write!(out, "{b:02x}");
+            out
+        })
+}
+
+/// Codificación de URI de SigV4: todo salvo `A-Z a-z 0-9 - _ . ~` (y `/` en las claves).
+fn uri_encode(s: &str, keep_slash: bool) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(b as char)
+            }
+            b'/' if keep_slash => out.push('/'),
+            _ => {
+                let _ = write!(out, "%{b:02X}");
+            }
+        }
+    }
+    out
+}
+
+fn between<'a>(s: &'a str, open: &str, close: &str) -> Option<&'a str> {
+    let start = s.find(open)? + open.len();
+    let len = s[start..].find(close)?;
+    Some(&s[start..start + len])
+}
+
+fn all_between<'a>(mut s: &'a str, open: &'a str, close: &'a str) -> impl Iterator<Item = &'a str> {
+    std::iter::from_fn(move || {
+        let found = between(s, open, close)?;
+        let end = s.find(open)? + open.len() + found.len() + close.len();
+        s = &s[end..];
+        Some(found)
+    })
+}
+
+fn xml_unescape(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Status y cuerpo de una respuesta HTTP/1.1 completa (la conexión se cierra al final),
+/// con o sin `Transfer-Encoding: chunked`.
+fn parse_response(raw: &[u8]) -> Option<S3Response> {
+    let split = raw.windows(4).position(|w| w == b"\r\n\r\n")?;
+    let head = std::str::from_utf8(&raw[..split]).ok()?;
+    let body = &raw[split + 4..];
+
+    let mut lines = head.split("\r\n");
+    let status = lines.next()?.split_whitespace().nth(1)?.parse().ok()?;
+    let chunked = lines.any(|line| {
+        line.split_once(':').is_some_and(|(name, value)| {
+            name.eq_ignore_ascii_case("transfer-encoding")
+                && value.trim().eq_ignore_ascii_case("chunked")
+        })
+    });
+
+    let body = if chunked {
+        dechunk(body)?
+    } else {
+        body.to_vec()
+    };
+    Some(S3Response { status, body })
+}
+
+fn dechunk(mut raw: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    loop {
+        let eol = raw.windows(2).position(|w| w == b"\r\n")?;
+        let size = std::str::from_utf8(&raw[..eol]).ok()?;
+        let size = usize::from_str_radix(size.split(';').next()?.trim(), 16).ok()?;
+        raw = &raw[eol + 2..];
+        if size == 0 {
+            return Some(out);
+        }
+        out.extend_from_slice(raw.get(..size)?);
+        raw = raw.get(size + 2..)?;
+    }
+}
@@ -0,0 +1,44 @@
+use std::{future::Future, hash::Hash, sync::Arc};
+
+use dashmap::DashMap;
+use tokio::sync::OnceCell;
+
+/// Junta las llamadas concurrentes con la misma clave: la primera hace el trabajo y las
+/// que llegan mientras tanto esperan su resultado en vez de repetirlo. Una llamada que
+/// llega cuando la anterior ya terminó arranca otra.
+pub struct SingleFlight<K, V> {
+    in_flight: DashMap<K, Arc<OnceCell<V>>>,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> SingleFlight<K, V> {
+    pub fn new() -> Self {
+        Self {
+            in_flight: DashMap::new(),
+        }
+    }
+
+    /// Si la llamada que está haciendo el trabajo se cancela, lo retoma una de las que
+    /// esperaban.
+    pub async fn run<F, Fut>(&self, key: K, work: F) -> V
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = V>,
+    {
+        let cell = self.in_flight.entry(key.clone()).or_default().clone();
+        let value = cell.get_or_init(work).await.clone();
+        // solo la entrada de este vuelo; puede haber arrancado otro
+        self.in_flight
+            .remove_if(&key, |_, current| Arc::ptr_eq(current, &cell));
+        value
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.len()
+    }
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> Default for SingleFlight<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
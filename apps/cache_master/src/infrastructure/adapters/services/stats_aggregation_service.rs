@@ -0,0 +1,121 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use app_core::clock::Clock;
+use app_net::NodeStats;
+use parking_lot::Mutex;
+use tokio::time::{self, MissedTickBehavior};
+use tokio_util::sync::CancellationToken;
+use tracing::debug;
+
+use crate::{
+    core::domain::models::{ClusterStats, ShardStats},
+    infrastructure::{
+        adapters::services::tcp_network_service::TcpNetworkService, metrics::MasterMetrics,
+    },
+};
+
+/// Junta el `STATS` del primario de cada shard y deja la vista del cluster en
+/// `MasterMetrics` (de ahí la leen `/metrics` y `CLUSTER-STATS`).
+pub struct StatsAggregationService {
+    network: Arc<TcpNetworkService>,
+    metrics: Arc<MasterMetrics>,
+    clock: Arc<dyn Clock>,
+    /// Último `STATS` de cada shard, para las tasas del intervalo.
+    previous: Mutex<HashMap<Arc<str>, Sample>>,
+}
+
+struct Sample {
+    at_ms: u64,
+    node: Arc<str>,
+    stats: NodeStats,
+}
+
+impl StatsAggregationService {
+    pub fn new(
+        network: Arc<TcpNetworkService>,
+        metrics: Arc<MasterMetrics>,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        Self {
+            network,
+            metrics,
+            clock,
+            previous: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// La vista de la última pasada, si hubo alguna.
+    pub fn latest(&self) -> Option<Arc<ClusterStats>> {
+        self.metrics.cluster_stats()
+    }
+
+    /// Una pasada por todos los shards; la vista nueva reemplaza a la anterior.
+    pub async fn collect(&self) -> Arc<ClusterStats> {
+        let results = self.network.request_shard_stats().await;
+        let now_ms = self.clock.now_millis().as_millis_u64();
+
+        let mut cluster = ClusterStats {
+            collected_at_ms: now_ms,
+            ..ClusterStats::default()
+        };
+        let mut previous = self.previous.lock();
+        for (shard, node, res) in results {
+            let total = match res {
+                Ok(total) => total,
+                Err(e) => {
+                    debug!(%shard, %node, "STATS sin respuesta: {e}");
+                    cluster.unreachable.push(shard);
+                    continue;
+                }
+            };
+            self.network.observe_memory(&node, total.memory_ratio());
+            // si cambió el nodo que contesta, sus contadores no siguen a los anteriores
+            let (interval, interval_ms) = match previous.get(&shard) {
+                Some(last) if last.node == node => {
+                    (total.since(&last.stats), now_ms.saturating_sub(last.at_ms))
+                }
+                _ => (total, 0),
+            };
+            let sample = Sample {
+                at_ms: now_ms,
+                node: node.clone(),
+                stats: total,
+            };
+            previous.insert(shard.clone(), sample);
+            cluster.shards.push(ShardStats {
+                shard,
+                node,
+                total,
+                interval,
+                interval_ms,
+            });
+        }
+        // shards que ya no existen
+        previous.retain(|shard, _| {
+            cluster.shards.iter().any(|s| s.shard == *shard) || cluster.unreachable.contains(shard)
+        });
+        drop(previous);
+
+        let cluster = Arc::new(cluster);
+        self.metrics.observe_cluster_stats(cluster.clone());
+        cluster
+    }
+}
+
+/// `collect` cada `interval` hasta que se cancele `token`.
+pub async fn aggregate_stats(
+    service: Arc<StatsAggregationService>,
+    interval: Duration,
+    token: CancellationToken,
+) {
+    let mut interval = time::interval(interval);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            _ = token.cancelled() => break,
+            _ = interval.tick() => {}
+        }
+        service.collect().await;
+    }
+}
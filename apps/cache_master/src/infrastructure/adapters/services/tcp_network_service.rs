@@ -1,36 +1,259 @@
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use app_net::RequestDataInput;
+use app_core::{clock::Clock, error::ErrorKind};
+use app_net::{
+    DEL_PREFIX, DelPrefix, IF_NOT_VERSION, MonitorOptions, NodeStats, PutCondition,
+    RequestDataInput, ResponseData, TxCommand, encode_args, encode_idempotency, encode_multi,
+    encode_refresh, encode_stale, encode_tags, encode_token,
+    event::group,
+    format_millis, format_relative,
+    monitor::MONITOR,
+    snapshot::SNAPSHOT,
+    stats::STATS,
+    tags::INVALIDATE_TAG,
+    tokenize,
+    tx::{IF, MULTI},
+};
 use async_trait::async_trait;
 use dashmap::{DashMap, Entry};
+use tokio::{sync::watch, task::JoinSet};
+use tracing::{debug, info, warn};
 
 use crate::{
-    core::domain::{models::AppError, services::NetworkService},
+    core::domain::{
+        models::{AppError, ConditionalGet, MemoryWatermarks, PutKey},
+        services::NetworkService,
+    },
     infrastructure::{
-        adapters::services::request_all_race_first_abort_rest,
+        adapters::services::{
+            FanoutPolicy, HedgeDelay, HotKeyCopies, KeyGuard, KeyLocks, MemoryAdmission,
+            NodeAccess, NodeOutcome, NodeRule, ReadOnlySwitches, SingleFlight, Stragglers, fanout,
+        },
         app_state::{AppNetworkNode, AppNetworkState},
+        metrics::MasterMetrics,
+        write_retries::WriteRetries,
     },
 };
 
+type Shard = DashMap<Arc<str>, Arc<AppNetworkNode>>;
+
 pub struct TcpNetworkService {
     network_state: Arc<AppNetworkState>,
-    nodes: DashMap<Arc<str>, DashMap<Arc<str>, Arc<AppNetworkNode>>>,
+    nodes: DashMap<Arc<str>, Shard>,
+    metrics: Arc<MasterMetrics>,
+    /// `GET` en vuelo por (shard, clave): ante una estampida sale uno solo hacia el shard.
+    gets: SingleFlight<(String, String), Result<Option<String>, AppError>>,
+    /// Claves calientes con copias en otros shards (ver `replicate_hot_key`).
+    hot: HotKeyCopies,
+    /// Nodos que no reciben escrituras por estar cerca de su tope de memoria.
+    memory: MemoryAdmission,
+    /// Solo lectura por mantenimiento, del cluster o de nodos.
+    read_only: ReadOnlySwitches,
+    /// Qué nodos pueden registrarse (ver `handle_conn`).
+    access: NodeAccess,
+    /// Cuántos nodos del shard tienen que contestar un `GET` y confirmar un `PUT`; se leen
+    /// en cada request (ver `LiveConfig`).
+    read_policy: watch::Receiver<FanoutPolicy>,
+    write_policy: watch::Receiver<FanoutPolicy>,
+    /// Con reloj, los `PUT` llevan lo que le falta a la clave (`ttl=`) en lugar del
+    /// `expires_at` (ver `MasterConfig::relative_ttl`).
+    relative_ttl: Option<Arc<dyn Clock>>,
+    /// Dónde quedan los `PUT` que el shard confirmó y alguno de sus nodos no.
+    write_retries: Option<Arc<WriteRetries>>,
+    /// Con candados, los `PUT` de una misma clave salen hacia su shard de a uno (ver
+    /// `MasterConfig::serialize_writes`).
+    key_locks: Option<Arc<KeyLocks>>,
 }
 
 impl TcpNetworkService {
     #[inline]
-    pub fn from_state(network_state: Arc<AppNetworkState>) -> Self {
+    pub fn from_state(network_state: Arc<AppNetworkState>, metrics: Arc<MasterMetrics>) -> Self {
         Self {
             network_state,
             nodes: DashMap::new(),
+            metrics,
+            gets: SingleFlight::new(),
+            hot: HotKeyCopies::new(),
+            memory: MemoryAdmission::default(),
+            read_only: ReadOnlySwitches::default(),
+            access: NodeAccess::default(),
+            read_policy: watch::channel(FanoutPolicy::Hedged(HedgeDelay::P95)).1,
+            write_policy: watch::channel(FanoutPolicy::FirstSuccess).1,
+            relative_ttl: None,
+            write_retries: None,
+            key_locks: None,
+        }
+    }
+
+    pub fn with_fanout_policies(
+        mut self,
+        reads: watch::Receiver<FanoutPolicy>,
+        writes: watch::Receiver<FanoutPolicy>,
+    ) -> Self {
+        self.read_policy = reads;
+        self.write_policy = writes;
+        self
+    }
+
+    pub fn with_relative_ttl(mut self, clock: Option<Arc<dyn Clock>>) -> Self {
+        self.relative_ttl = clock;
+        self
+    }
+
+    pub fn with_write_retries(mut self, retries: Arc<WriteRetries>) -> Self {
+        self.write_retries = Some(retries);
+        self
+    }
+
+    /// `max_hold` es lo más que un `PUT` retiene su clave esperando al resto del shard;
+    /// con `None` las escrituras de una clave salen sin esperarse.
+    pub fn with_write_serialization(mut self, max_hold: Option<Duration>) -> Self {
+        self.key_locks = max_hold.map(|hold| Arc::new(KeyLocks::new(hold)));
+        self
+    }
+
+    pub fn with_memory_watermarks(mut self, watermarks: MemoryWatermarks) -> Self {
+        self.memory = MemoryAdmission::new(watermarks);
+        self
+    }
+
+    pub fn with_read_only(mut self, switches: ReadOnlySwitches) -> Self {
+        self.read_only = switches;
+        self
+    }
+
+    pub fn read_only(&self) -> &ReadOnlySwitches {
+        &self.read_only
+    }
+
+    pub fn with_node_access(mut self, access: NodeAccess) -> Self {
+        self.access = access;
+        self
+    }
+
+    pub fn node_access(&self) -> &NodeAccess {
+        &self.access
+    }
+
+    /// Agrega `rule` a los `BAN` y corta la conexión de los nodos registrados que coinciden;
+    /// devuelve sus ids, ordenados.
+    pub fn ban(&self, rule: NodeRule) -> Vec<Arc<str>> {
+        let mut kicked: Vec<Arc<str>> = self
+            .network_state
+            .nodes_registry
+            .iter()
+            .filter(|node| rule.matches(&node.node_id, node.peer_ip))
+            .map(|node| {
+                node.disconnect();
+                node.key().clone()
+            })
+            .collect();
+        kicked.sort();
+        warn!(target: "topology", %rule, kicked = kicked.len(), "BAN de nodos");
+        self.access.ban(rule);
+        kicked
+    }
+
+    /// Corta la conexión de `node_id`; al cerrarse se lo saca de la topología como a
+    /// cualquier nodo que se desconecta. `false` si no está registrado.
+    pub fn disconnect(&self, node_id: &str) -> bool {
+        let Some(node) = self.network_state.nodes_registry.get(node_id) else {
+            return false;
+        };
+        node.disconnect();
+        true
+    }
+
+    /// Prende o apaga el solo lectura del cluster (`node_id` en `None`) o de un nodo. Para
+    /// prenderlo en un nodo tiene que estar registrado; apagarlo se puede siempre.
+    pub fn set_read_only(&self, node_id: Option<&str>, on: bool) -> Result<(), AppError> {
+        let previous = match node_id {
+            None => self.read_only.set_cluster(on),
+            Some(node_id) => {
+                if on {
+                    self.resolve_node(node_id)?;
+                }
+                self.read_only.set_node(node_id, on)
+            }
+        };
+        if previous != on {
+            let scope = node_id.unwrap_or("cluster");
+            if on {
+                warn!(target: "topology", scope, "solo lectura: se rechazan PUT y DEL");
+            } else {
+                info!(target: "topology", scope, "fin del solo lectura");
+            }
+        }
+        Ok(())
+    }
+
+    /// Error si el cluster o algún nodo del shard está en solo lectura.
+    fn check_writable(&self, shard: &str) -> Result<(), AppError> {
+        if self.read_only.cluster() {
+            return Err(AppError::ReadOnly(
+                "el cluster está en solo lectura".to_string(),
+            ));
+        }
+        match self
+            .get_all_nodes(shard)
+            .into_iter()
+            .find(|node| self.read_only.node(&node.node_id))
+        {
+            Some(node) => Err(AppError::ReadOnly(format!(
+                "{} está en solo lectura",
+                node.node_id
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Ocupación de memoria que reportó `node_id` (`None` si no tiene tope).
+    pub fn observe_memory(&self, node_id: &Arc<str>, ratio: Option<f64>) {
+        let percent = ratio.map(|r| (r * 100.0).round() as u64);
+        match self.memory.observe(node_id, ratio) {
+            Some(true) => warn!(
+                target: "topology",
+                %node_id,
+                percent,
+                "nodo sobre la marca alta de memoria: no recibe PUT"
+            ),
+            Some(false) => info!(
+                target: "topology",
+                %node_id,
+                percent,
+                "nodo bajo la marca baja de memoria: vuelve a recibir PUT"
+            ),
+            None => {}
+        }
+    }
+
+    pub fn memory_admission(&self) -> &MemoryAdmission {
+        &self.memory
+    }
+
+    /// Si el shard acepta un `PUT`. Las escrituras van a todos los nodos del shard: con
+    /// uno sobre la marca alta, el shard entero deja de aceptarlas (503) para no forzarlo
+    /// a desalojar.
+    fn admit_writes(&self, shard: &str) -> Result<(), AppError> {
+        self.check_writable(shard)?;
+        let full = self
+            .get_all_nodes(shard)
+            .into_iter()
+            .find(|node| self.memory.is_over(&node.node_id));
+        match full {
+            Some(node) => Err(AppError::NodeBusy(format!(
+                "{} sobre la marca alta de memoria",
+                node.node_id
+            ))),
+            None => Ok(()),
         }
     }
 
     #[inline]
-    fn ensure_shard(
-        &self,
-        master_id: &str,
-    ) -> dashmap::mapref::one::RefMut<'_, Arc<str>, DashMap<Arc<str>, Arc<AppNetworkNode>>> {
+    fn ensure_shard(&self, master_id: &str) -> dashmap::mapref::one::RefMut<'_, Arc<str>, Shard> {
         self.nodes.entry(Arc::<str>::from(master_id)).or_default()
     }
 
@@ -46,14 +269,11 @@ impl TcpNetworkService {
     }
 
     #[inline]
-    fn get_shard(
-        &self,
-        master_id: &str,
-    ) -> Option<dashmap::mapref::one::Ref<'_, Arc<str>, DashMap<Arc<str>, Arc<AppNetworkNode>>>>
-    {
+    fn get_shard(&self, master_id: &str) -> Option<dashmap::mapref::one::Ref<'_, Arc<str>, Shard>> {
         self.nodes.get(master_id)
     }
 
+    /// Los nodos del shard `node_id`: primero el primario y después las réplicas por id.
     pub fn get_all_nodes(&self, node_id: &str) -> Vec<Arc<AppNetworkNode>> {
         let mut result = Vec::new();
 
@@ -63,38 +283,784 @@ impl TcpNetworkService {
             }
         }
 
+        result.sort_by(|a, b| {
+            (*a.node_id != *node_id, &a.node_id).cmp(&(*b.node_id != *node_id, &b.node_id))
+        });
         result
     }
 
-    pub fn pretty_print(&self) {
-        println!(
-            "🚀 TcpNetworkService: {}",
-            self.network_state.nodes_registry.len()
-        );
-        for master_entry in self.nodes.iter() {
-            let master_id = master_entry.key();
-            let replicas = master_entry.value();
+    /// Las conexiones vivas, clientes incluidos, de la que más bytes mandó a la que menos.
+    pub fn connections(&self) -> Vec<Arc<AppNetworkNode>> {
+        let mut connections: Vec<Arc<AppNetworkNode>> = self
+            .network_state
+            .connections
+            .iter()
+            .map(|entry| entry.value().clone())
+            .collect();
+        connections.sort_by(|a, b| {
+            (b.stats.bytes_in(), &a.node_id).cmp(&(a.stats.bytes_in(), &b.node_id))
+        });
+        connections
+    }
 
-            println!("  🌐 Master Node: {master_id}");
+    /// Los contadores de la conexión del nodo registrado como `node_id`.
+    pub fn connection_stats(&self, node_id: &str) -> Option<String> {
+        let node = self.network_state.nodes_registry.get(node_id)?;
+        Some(node.stats.to_wire())
+    }
 
-            if replicas.is_empty() {
-                println!("    └─ (no replicas)");
-            } else {
-                for replica_entry in replicas.iter() {
-                    let replica_id = replica_entry.key();
-                    println!("    └─ 📦 Replica: {replica_id}");
+    pub fn registered_nodes(&self) -> usize {
+        self.network_state.nodes_registry.len()
+    }
+
+    pub fn shard_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Manda `LOG-FILTER` a `node_id`, o a todos los nodos registrados si es `None`, y
+    /// devuelve el filtro que quedó activo en cada uno.
+    pub async fn request_log_filter(
+        &self,
+        node_id: Option<&str>,
+        filter: &str,
+    ) -> Result<Vec<(Arc<str>, Result<String, AppError>)>, AppError> {
+        let payload = encode_token(filter);
+        let mut results = Vec::new();
+
+        for node in self.target_nodes(node_id)? {
+            let res = Self::admin_request(&node, "LOG-FILTER", &payload).await;
+            results.push((node.node_id.clone(), res));
+        }
+
+        Ok(results)
+    }
+
+    /// Reenvía `CONFIG <args>` a `node_id` (a todos con `None`, por id). Con `refresh`, a
+    /// cada nodo que contestó se le vuelve a leer el `STATS` para que la admisión por
+    /// memoria use enseguida su tope nuevo, sin esperar su próximo reporte.
+    pub async fn request_node_config(
+        &self,
+        node_id: Option<&str>,
+        args: &str,
+        refresh: bool,
+    ) -> Result<Vec<(Arc<str>, Result<String, AppError>)>, AppError> {
+        let mut results = Vec::new();
+
+        for node in self.target_nodes(node_id)? {
+            let res = Self::admin_request(&node, "CONFIG", args).await;
+            if refresh && res.is_ok() {
+                self.refresh_memory(&node).await;
+            }
+            results.push((node.node_id.clone(), res));
+        }
+
+        Ok(results)
+    }
+
+    async fn refresh_memory(&self, node: &AppNetworkNode) {
+        let stats = Self::admin_request(node, STATS, "")
+            .await
+            .and_then(|payload| {
+                let total = tokenize(&payload).next().unwrap_or_default();
+                total.parse::<NodeStats>().map_err(AppError::from)
+            });
+        match stats {
+            Ok(stats) => self.observe_memory(&node.node_id, stats.memory_ratio()),
+            Err(e) => debug!(node_id = %node.node_id, "STATS sin respuesta: {e}"),
+        }
+    }
+
+    /// `node_id`, o todos los registrados ordenados por id.
+    fn target_nodes(&self, node_id: Option<&str>) -> Result<Vec<Arc<AppNetworkNode>>, AppError> {
+        if let Some(id) = node_id {
+            return Ok(vec![self.resolve_node(id)?]);
+        }
+        let mut all: Vec<_> = self
+            .network_state
+            .nodes_registry
+            .iter()
+            .map(|n| n.value().clone())
+            .collect();
+        all.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+        Ok(all)
+    }
+
+    /// Manda `SET-ROLE` a `node_id` y devuelve el rol que el nodo confirma. Solo cambia el
+    /// rol del nodo; el shard en el que está registrado sigue igual hasta que reconecte.
+    pub async fn request_set_role(&self, node_id: &str, role: &str) -> Result<String, AppError> {
+        let node = self.resolve_node(node_id)?;
+        Self::admin_request(&node, "SET-ROLE", &encode_token(role)).await
+    }
+
+    /// Reenvía `SLOWLOG <args>` a `node_id` y devuelve su respuesta tal cual.
+    pub async fn request_slow_log(&self, node_id: &str, args: &str) -> Result<String, AppError> {
+        let node = self.resolve_node(node_id)?;
+        Self::admin_request(&node, "SLOWLOG", args).await
+    }
+
+    /// Reenvía `TOPKEYS <args>` a `node_id` y devuelve su respuesta tal cual.
+    pub async fn request_top_keys(&self, node_id: &str, args: &str) -> Result<String, AppError> {
+        let node = self.resolve_node(node_id)?;
+        Self::admin_request(&node, "TOPKEYS", args).await
+    }
+
+    /// `STATS ["<namespace>"]` de `node_id`: ocupación de su cache y uso por namespace.
+    pub async fn request_stats(
+        &self,
+        node_id: &str,
+        namespace: Option<&str>,
+    ) -> Result<String, AppError> {
+        let node = self.resolve_node(node_id)?;
+        let args = encode_args(namespace);
+        Self::admin_request(&node, STATS, &args).await
+    }
+
+    /// `STATS` del primario de cada shard (de otro nodo del shard si el primario no está),
+    /// todos a la vez: `(shard, nodo, totales del nodo)`.
+    pub async fn request_shard_stats(
+        &self,
+    ) -> Vec<(Arc<str>, Arc<str>, Result<NodeStats, AppError>)> {
+        self.request_each_shard(STATS)
+            .await
+            .into_iter()
+            .map(|(shard, node, res)| {
+                let stats = res.and_then(|payload| {
+                    let total = tokenize(&payload).next().unwrap_or_default();
+                    total.parse::<NodeStats>().map_err(AppError::from)
+                });
+                (shard, node, stats)
+            })
+            .collect()
+    }
+
+    /// `SNAPSHOT` de cada shard, elegido y pedido como en `request_shard_stats`; el payload
+    /// va sin interpretar.
+    pub async fn request_shard_snapshots(
+        &self,
+    ) -> Vec<(Arc<str>, Arc<str>, Result<String, AppError>)> {
+        self.request_each_shard(SNAPSHOT).await
+    }
+
+    /// `action` sin argumentos al primario de cada shard, ordenado por shard.
+    async fn request_each_shard(
+        &self,
+        action: &'static str,
+    ) -> Vec<(Arc<str>, Arc<str>, Result<String, AppError>)> {
+        let targets: Vec<(Arc<str>, Arc<AppNetworkNode>)> = self
+            .nodes
+            .iter()
+            .filter_map(|shard| {
+                let node = shard
+                    .value()
+                    .get(shard.key())
+                    .map(|n| n.value().clone())
+                    .or_else(|| {
+                        shard
+                            .value()
+                            .iter()
+                            .map(|n| n.value().clone())
+                            .min_by(|a, b| a.node_id.cmp(&b.node_id))
+                    })?;
+                Some((shard.key().clone(), node))
+            })
+            .collect();
+
+        let mut set = JoinSet::new();
+        for (shard, node) in targets {
+            set.spawn(async move {
+                let res = Self::admin_request(&node, action, "").await;
+                (shard, node.node_id.clone(), res)
+            });
+        }
+
+        let mut results = Vec::new();
+        while let Some(joined) = set.join_next().await {
+            match joined {
+                Ok(result) => results.push(result),
+                Err(e) => debug!("{action} a un shard no terminó: {e}"),
+            }
+        }
+        results.sort_by(|a, b| a.0.cmp(&b.0));
+        results
+    }
+
+    /// Pide a `node_id` que mande al master cada comando que procese durante `options`;
+    /// el nodo manda todo y el master aplica el muestreo y la redacción de cada cliente.
+    pub async fn request_monitor(
+        &self,
+        node_id: &str,
+        options: MonitorOptions,
+    ) -> Result<(), AppError> {
+        let node = self.resolve_node(node_id)?;
+        let full = MonitorOptions {
+            duration: options.duration,
+            ..MonitorOptions::default()
+        };
+        Self::admin_request(&node, MONITOR, &full.to_args()).await?;
+        Ok(())
+    }
+
+    /// Manda `META` a todos los nodos del shard `shard_id` (primero el primario) y devuelve
+    /// lo que contestó cada uno, para comparar la entrada entre réplicas.
+    pub async fn request_key_meta(
+        &self,
+        shard_id: &str,
+        key: &str,
+    ) -> Vec<(Arc<str>, Result<String, AppError>)> {
+        let mut nodes = self.get_all_nodes(shard_id);
+        nodes.sort_by(|a, b| {
+            (&*a.node_id != shard_id, &a.node_id).cmp(&(&*b.node_id != shard_id, &b.node_id))
+        });
+
+        let payload = encode_token(key);
+        let mut results = Vec::with_capacity(nodes.len());
+        for node in nodes {
+            let res = Self::admin_request(&node, "META", &payload).await;
+            results.push((node.node_id.clone(), res));
+        }
+        results
+    }
+
+    /// `PEEK` de `key` en el shard `shard_id`: no pasa por las copias de claves calientes ni
+    /// cambia el orden de desalojo en los nodos.
+    pub async fn request_peek_key(
+        &self,
+        shard_id: &str,
+        key: &str,
+    ) -> Result<Option<String>, AppError> {
+        self.read_key(shard_id, "PEEK", key).await
+    }
+
+    /// Indica a `replica_id` que replique desde el primario de `shard_id`. Devuelve la
+    /// dirección confirmada por la réplica, o `None` si el primario no anunció listener.
+    pub async fn assign_replication_source(
+        &self,
+        replica_id: &str,
+        shard_id: &str,
+    ) -> Result<Option<String>, AppError> {
+        let primary = self.resolve_node(shard_id)?;
+        let Some(addr) = primary.repl_addr.as_deref() else {
+            return Ok(None);
+        };
+
+        let replica = self.resolve_node(replica_id)?;
+        Self::admin_request(&replica, "REPLICATE-FROM", &encode_token(addr))
+            .await
+            .map(Some)
+    }
+
+    /// Request de administración a un nodo; si el nodo responde con error se devuelve
+    /// como 400 con su mensaje.
+    async fn admin_request(
+        node: &AppNetworkNode,
+        action: &str,
+        payload: &str,
+    ) -> Result<String, AppError> {
+        let res = node
+            .socket
+            .request(RequestDataInput { action, payload })
+            .await?;
+
+        match res.error_message() {
+            Some(e) => Err(AppError::BadRequest(e.to_string())),
+            None => Ok(res.payload),
+        }
+    }
+
+    /// Request al primario del shard `node_id` (el nodo con el mismo id) para las
+    /// escrituras que se deciden en un solo nodo. Los errores del nodo conservan su
+    /// categoría.
+    async fn request_primary(
+        &self,
+        node_id: &str,
+        action: &str,
+        payload: &str,
+    ) -> Result<ResponseData, AppError> {
+        let primary = self.resolve_node(node_id)?;
+        let response = primary
+            .socket
+            .request(RequestDataInput { action, payload })
+            .await?;
+        if response.is_success() {
+            return Ok(response);
+        }
+        node_busy(&response)?;
+
+        let message = response.error_message().unwrap_or_default().to_string();
+        Err(match response.error_kind() {
+            Some(ErrorKind::Conflict) => AppError::Conflict(message),
+            Some(ErrorKind::NotFound) => AppError::NotFound(message),
+            Some(ErrorKind::PreconditionFailed) => AppError::PreconditionFailed(message),
+            Some(ErrorKind::QuotaExceeded) => AppError::QuotaExceeded(message),
+            Some(ErrorKind::PayloadTooLarge) => AppError::PayloadTooLarge(message),
+            Some(ErrorKind::BadRequest) => AppError::BadRequest(message),
+            _ => AppError::ConnectionError(format!(
+                "Error en {action}: {} {}",
+                response.code, response.payload
+            )),
+        })
+    }
+
+    /// Manda al resto del shard, en segundo plano, lo que ya aplicó el primario: igual que
+    /// los `PUT` comunes, que van a todos los nodos.
+    /// Con `serial`, la clave sigue tomada hasta que contestan todas.
+    fn replay_to_replicas(
+        &self,
+        node_id: &str,
+        action: &'static str,
+        payload: String,
+        serial: Option<KeyGuard>,
+    ) {
+        let replicas: Vec<_> = self
+            .get_all_nodes(node_id)
+            .into_iter()
+            .filter(|n| *n.node_id != *node_id)
+            .collect();
+        if replicas.is_empty() {
+            return;
+        }
+
+        tokio::spawn(async move {
+            let replay = async {
+                for replica in replicas {
+                    let request = RequestDataInput {
+                        action,
+                        payload: &payload,
+                    };
+                    if let Err(e) = replica.socket.request(request).await {
+                        debug!("{action} a la réplica {} falló: {e}", replica.node_id);
+                    }
                 }
+            };
+            match serial {
+                Some(serial) => {
+                    if serial.hold(replay).await.is_none() {
+                        debug!("{action} a las réplicas sigue después de soltar la clave");
+                    }
+                }
+                None => replay.await,
+            }
+        });
+    }
+
+    /// `INVALIDATE-TAG` en todos los shards; devuelve cuántas claves se borraron en total.
+    pub async fn request_invalidate_tag(&self, tag: &str) -> Result<i64, AppError> {
+        for (shard, _) in self.shard_tree() {
+            self.check_writable(&shard)?;
+        }
+        // las copias no llevan tags: se borran todas
+        for (key, shards) in self.hot.invalidate_all() {
+            self.delete_copies(&key, shards);
+        }
+        self.broadcast_count(INVALIDATE_TAG, encode_token(tag).into_owned())
+            .await
+    }
+
+    /// `DEL-PREFIX` en todos los shards; devuelve cuántas claves se borraron (o, con
+    /// `dry-run`, cuántas se borrarían) en total. Con `REPLICATION_FACTOR` una clave cuenta
+    /// una vez por shard que la guarda.
+    pub async fn request_del_prefix(&self, request: &DelPrefix) -> Result<i64, AppError> {
+        if !request.dry_run {
+            for (shard, _) in self.shard_tree() {
+                self.check_writable(&shard)?;
+            }
+            for (key, shards) in self.hot.invalidate_prefix(&request.prefix) {
+                self.delete_copies(&key, shards);
+            }
+        }
+        self.broadcast_count(DEL_PREFIX, request.to_string()).await
+    }
+
+    /// Como un `PUT`, manda `action` a todos los nodos de cada shard y cuenta la primera
+    /// respuesta de cada uno; devuelve la suma de los enteros que contestaron.
+    async fn broadcast_count(
+        &self,
+        action: &'static str,
+        payload: String,
+    ) -> Result<i64, AppError> {
+        let shards: Vec<Vec<Arc<AppNetworkNode>>> = self
+            .nodes
+            .iter()
+            .map(|shard| shard.iter().map(|n| n.value().clone()).collect())
+            .collect();
+
+        let mut set = JoinSet::new();
+        for nodes in shards {
+            let payload = payload.clone();
+            let metrics = self.metrics.clone();
+            set.spawn(async move {
+                let request = RequestDataInput {
+                    action,
+                    payload: &payload,
+                };
+                let policy = FanoutPolicy::FirstSuccess;
+                fanout(&nodes, request, policy, Stragglers::TrackLag, &metrics)
+                    .await
+                    .into_response()
+            });
+        }
+
+        let mut removed = 0;
+        while let Some(joined) = set.join_next().await {
+            let response = joined.map_err(|e| AppError::SocketError(e.to_string()))??;
+            node_busy(&response)?;
+            if let Some(e) = response.error_message() {
+                return Err(AppError::BadRequest(e.to_string()));
             }
+            removed += response.integer()?;
         }
+        Ok(removed)
+    }
+
+    /// `PUT` ya armado de `key` a todos los nodos de `node_id`. Si el shard lo confirma, los
+    /// nodos que no lo hicieron quedan en `WriteRetries`. Devuelve el primero que confirmó.
+    async fn put_to_shard(
+        &self,
+        node_id: &str,
+        key: &str,
+        payload: &str,
+    ) -> Result<Option<String>, AppError> {
+        let request = RequestDataInput {
+            action: "PUT",
+            payload,
+        };
+
+        let nodes = self.get_all_nodes(node_id);
+
+        let serial = match &self.key_locks {
+            Some(locks) => Some(locks.lock(key).await),
+            None => None,
+        };
+        let policy = *self.write_policy.borrow();
+        let version = self.write_retries.as_ref().map(|r| r.next_version(key));
+        let mut outcome =
+            fanout(&nodes, request, policy, Stragglers::TrackLag, &self.metrics).await;
+        if outcome.is_confirmed() {
+            let acked_by = outcome.first_confirmed().map(|id| id.to_string());
+            let tracked = self.write_retries.clone().zip(version);
+            let payload: Arc<str> = Arc::from(payload);
+            if let Some((retries, version)) = &tracked {
+                track_write(retries, key, *version, &payload, &outcome.outcomes);
+            }
+            let late = outcome.late.take();
+            if let Some(late) = late.filter(|_| tracked.is_some() || serial.is_some()) {
+                let key = key.to_string();
+                tokio::spawn(async move {
+                    // la clave sigue tomada hasta que contesta el resto del shard
+                    let outcomes = match serial {
+                        Some(serial) => serial.hold(late).await.and_then(Result::ok),
+                        None => late.await.ok(),
+                    };
+                    if let (Some((retries, version)), Some(outcomes)) = (tracked, outcomes) {
+                        track_write(&retries, &key, version, &payload, &outcomes);
+                    }
+                });
+            }
+            return Ok(acked_by);
+        }
+
+        let response = outcome.into_rejection()?;
+        node_busy(&response)?;
+        let message = response.error_message().unwrap_or_default().to_string();
+        match response.error_kind() {
+            Some(ErrorKind::QuotaExceeded) => return Err(AppError::QuotaExceeded(message)),
+            // un nodo con un tope más bajo que el del master
+            Some(ErrorKind::PayloadTooLarge) => return Err(AppError::PayloadTooLarge(message)),
+            _ => {}
+        }
+
+        Err(AppError::ConnectionError(format!(
+            "Error en PUT: {} {}",
+            response.code, response.payload
+        )))
+    }
+
+    /// Un `PUT` ya armado a un solo nodo, sin pasar por el resto de su shard (los reintentos
+    /// de `WriteRetries`).
+    pub async fn put_to_node(&self, node_id: &str, payload: &str) -> Result<(), AppError> {
+        let node = self.resolve_node(node_id)?;
+        let response = node
+            .socket
+            .request(RequestDataInput {
+                action: "PUT",
+                payload,
+            })
+            .await?;
+        if response.is_success() {
+            return Ok(());
+        }
+        Err(AppError::ConnectionError(format!(
+            "Error en PUT: {} {}",
+            response.code, response.payload
+        )))
+    }
+
+    /// La política con la que sale ahora un `GET` a los nodos de un shard.
+    pub fn read_policy(&self) -> FanoutPolicy {
+        *self.read_policy.borrow()
+    }
+
+    /// La política con la que sale ahora un `PUT` a los nodos de un shard.
+    pub fn write_policy(&self) -> FanoutPolicy {
+        *self.write_policy.borrow()
+    }
+
+    /// Payload de `PUT` hacia los nodos: el `expires_at` ya es absoluto, o lo que le falta
+    /// desde ahora con `relative_ttl`.
+    fn put_payload(&self, put: &PutKey<'_>) -> String {
+        let expires_at = put.expires_at.map(|at| match &self.relative_ttl {
+            Some(clock) => format_relative(at.saturating_sub(clock.now_millis().as_millis_u64())),
+            None => format_millis(at),
+        });
+        let tags = encode_tags(put.tags);
+        let idempotency = put.idempotency.map(encode_idempotency);
+        let optional = expires_at
+            .as_deref()
+            .into_iter()
+            .chain(tags.as_deref())
+            .chain(idempotency.as_deref());
+        encode_args([put.key, put.value].into_iter().chain(optional))
+    }
+
+    /// Claves copiadas a otros shards por calientes.
+    pub fn hot_key_copies(&self) -> &HotKeyCopies {
+        &self.hot
+    }
+
+    /// Copia `key` del primario de `owner` a los `extra` shards siguientes y reparte desde
+    /// ahí sus lecturas. Las copias vencen con la clave o a los `copy_ttl_ms`, lo que pase
+    /// antes; una escritura de la clave las borra. Devuelve si quedaron habilitadas.
+    pub async fn replicate_hot_key(
+        &self,
+        owner: &str,
+        key: &str,
+        extra: usize,
+        now_ms: u64,
+        copy_ttl_ms: u64,
+    ) -> Result<bool, AppError> {
+        let extras = self.next_shards(owner, extra);
+        if extras.is_empty() || !self.hot.begin(key) {
+            return Ok(false);
+        }
+
+        let copied = self
+            .copy_key(owner, key, &extras, now_ms, copy_ttl_ms)
+            .await;
+        let expires_at = match copied {
+            Ok(Some(expires_at)) => expires_at,
+            Ok(None) => {
+                self.hot.abandon(key);
+                return Ok(false);
+            }
+            Err(e) => {
+                self.hot.abandon(key);
+                return Err(e);
+            }
+        };
+
+        // se dejan de leer antes de que venzan en los nodos
+        let usable = expires_at
+            .saturating_sub(now_ms)
+            .saturating_sub(HOT_COPY_MARGIN_MS);
+        let until = Instant::now() + Duration::from_millis(usable);
+        let mut shards = vec![Arc::<str>::from(owner)];
+        shards.extend(extras.iter().cloned());
+
+        if self.hot.finish(key, shards, until) {
+            return Ok(true);
+        }
+        // la clave se escribió mientras se copiaba
+        self.delete_copies(key, extras);
+        Ok(false)
+    }
+
+    /// Escribe el valor vigente de `key` en `extras`; el `expires_at` de las copias, o
+    /// `None` si la clave no existe o vence demasiado pronto para copiarla.
+    async fn copy_key(
+        &self,
+        owner: &str,
+        key: &str,
+        extras: &[Arc<str>],
+        now_ms: u64,
+        copy_ttl_ms: u64,
+    ) -> Result<Option<u64>, AppError> {
+        let token = encode_token(key);
+        let meta = self.request_primary(owner, "META", &token).await?;
+        if meta.is_empty_value() {
+            return Ok(None);
+        }
+        let owner_expiry = meta
+            .payload
+            .split(' ')
+            .find_map(|field| field.strip_prefix("expires_at="))
+            .and_then(|exp| exp.parse::<u64>().ok());
+
+        // la copia no es una lectura del cliente: no debe proteger la clave del desalojo
+        let value = self.request_primary(owner, "PEEK", &token).await?.payload;
+        if value.is_empty() {
+            return Ok(None);
+        }
+
+        let copy_expiry = now_ms.saturating_add(copy_ttl_ms);
+        let expires_at = owner_expiry.map_or(copy_expiry, |exp| exp.min(copy_expiry));
+        if expires_at <= now_ms + HOT_COPY_MARGIN_MS {
+            return Ok(None);
+        }
+
+        let payload = self.put_payload(&PutKey {
+            key,
+            value: &value,
+            expires_at: Some(expires_at),
+            tags: &[],
+            idempotency: None,
+        });
+        for shard in extras {
+            self.put_to_shard(shard, key, &payload).await?;
+        }
+        Ok(Some(expires_at))
+    }
+
+    /// Hasta `n` shards que siguen a `owner` en orden de id, dando la vuelta.
+    fn next_shards(&self, owner: &str, n: usize) -> Vec<Arc<str>> {
+        let ids: Vec<Arc<str>> = self.shard_tree().into_iter().map(|(id, _)| id).collect();
+        let start = ids
+            .iter()
+            .position(|id| &**id == owner)
+            .map_or(0, |i| i + 1);
+        ids.iter()
+            .cycle()
+            .skip(start)
+            .take(ids.len())
+            .filter(|id| &***id != owner && self.admit_writes(id).is_ok())
+            .take(n)
+            .cloned()
+            .collect()
+    }
+
+    /// Al escribir `key`: las lecturas vuelven al dueño y se borran sus copias.
+    fn drop_hot_copies(&self, key: &str) {
+        let shards = self.hot.invalidate(key);
+        if !shards.is_empty() {
+            self.delete_copies(key, shards);
+        }
+    }
+
+    /// `DEL` de `key` en todos los nodos de `shards`, en segundo plano.
+    fn delete_copies(&self, key: &str, shards: Vec<Arc<str>>) {
+        let targets: Vec<Vec<Arc<AppNetworkNode>>> = shards
+            .iter()
+            .map(|shard| self.get_all_nodes(shard))
+            .collect();
+        let payload = encode_token(key).into_owned();
+        let metrics = self.metrics.clone();
+
+        tokio::spawn(async move {
+            for nodes in targets {
+                let request = RequestDataInput {
+                    action: "DEL",
+                    payload: &payload,
+                };
+                let policy = FanoutPolicy::FirstSuccess;
+                let outcome = fanout(&nodes, request, policy, Stragglers::TrackLag, &metrics).await;
+                if let Err(e) = outcome.into_response() {
+                    debug!("no se pudo borrar la copia de {payload}: {e}");
+                }
+            }
+        });
+    }
+
+    /// El `GET` hacia el shard, sin juntar llamadas; lo usa `request_get_key`.
+    async fn fetch_key(&self, node_id: &str, key: &str) -> Result<Option<String>, AppError> {
+        // el nodo contesta una clave que no tiene con un 200 vacío, y un valor nunca es
+        // vacío: así quien lee sabe que tiene que buscar en otro shard
+        let value = self.read_key(node_id, "GET", key).await?;
+        Ok(value.filter(|value| !value.is_empty()))
+    }
+
+    /// Lectura de `key` en el primero de los nodos del shard que conteste.
+    async fn read_key(
+        &self,
+        node_id: &str,
+        action: &str,
+        key: &str,
+    ) -> Result<Option<String>, AppError> {
+        let payload = encode_token(key);
+        let request = RequestDataInput {
+            action,
+            payload: &payload,
+        };
+
+        let nodes = self.get_all_nodes(node_id);
+
+        let policy = *self.read_policy.borrow();
+        let response = fanout(&nodes, request, policy, Stragglers::Abort, &self.metrics)
+            .await
+            .into_response()?;
+
+        if response.is_success() {
+            return Ok(Some(response.payload));
+        }
+        node_busy(&response)?;
+
+        Ok(None)
+    }
+
+    /// `GET` con opciones (`refresh=`, `stale=`) en el primero de los nodos del shard que
+    /// conteste; los valores de la respuesta, o `None` si la clave no existe.
+    async fn read_key_values(
+        &self,
+        node_id: &str,
+        payload: &str,
+    ) -> Result<Option<Vec<String>>, AppError> {
+        let request = RequestDataInput {
+            action: "GET",
+            payload,
+        };
+
+        let nodes = self.get_all_nodes(node_id);
+        let policy = *self.read_policy.borrow();
+        let response = fanout(&nodes, request, policy, Stragglers::Abort, &self.metrics)
+            .await
+            .into_response()?;
+        node_busy(&response)?;
+        if let Some(e) = response.error_message() {
+            return Err(AppError::BadRequest(e.to_string()));
+        }
+        if !response.is_success() || response.payload.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(response.values()))
+    }
+
+    /// Shards con sus nodos, ordenados por id. El master del shard tiene el mismo id que el shard.
+    pub fn shard_tree(&self) -> Vec<(Arc<str>, Vec<Arc<str>>)> {
+        let mut tree: Vec<_> = self
+            .nodes
+            .iter()
+            .map(|shard| {
+                let mut members: Vec<Arc<str>> = shard.iter().map(|n| n.key().clone()).collect();
+                members.sort();
+                (shard.key().clone(), members)
+            })
+            .collect();
+        tree.sort();
+        tree
     }
 }
 
 #[async_trait]
 impl NetworkService for TcpNetworkService {
-    fn get_node_id_with_less_replicas(&self) -> Option<String> {
+    fn get_node_id_with_less_replicas(&self, node_id: &str) -> Option<String> {
+        let process = group::process_id(node_id);
         self.nodes
             .iter()
-            .min_by_key(|entry| entry.value().len())
+            .min_by_key(|entry| {
+                let same_process = entry
+                    .value()
+                    .iter()
+                    .any(|member| group::process_id(member.key()) == process);
+                (same_process, entry.value().len())
+            })
             .map(|entry| entry.key().to_string())
     }
 
@@ -136,7 +1102,11 @@ impl NetworkService for TcpNetworkService {
 
         match shard.entry(Arc::<str>::from(node_id)) {
             Entry::Occupied(_) => {
-                println!("Node {node_id} already exists in {master_node_id}");
+                debug!(
+                    node_id,
+                    shard_id = master_node_id,
+                    "replica ya registrada en el shard"
+                );
                 Ok(false)
             }
             Entry::Vacant(v) => {
@@ -195,6 +1165,8 @@ impl NetworkService for TcpNetworkService {
             }
         }
 
+        self.memory.forget(node_id);
+
         // Remover SIEMPRE del registry (si existe)
         let removed_registry = self.network_state.nodes_registry.remove(node_id).is_some();
 
@@ -202,57 +1174,238 @@ impl NetworkService for TcpNetworkService {
     }
 
     async fn request_put_key(
+        &self,
+        node_id: &str,
+        put: &PutKey<'_>,
+    ) -> Result<Option<String>, AppError> {
+        self.admit_writes(node_id)?;
+        self.drop_hot_copies(put.key);
+        let payload = self.put_payload(put);
+        let stored = self.put_to_shard(node_id, put.key, &payload).await;
+        // una copia que empezó durante la escritura pudo leer el valor anterior
+        self.drop_hot_copies(put.key);
+        stored
+    }
+
+    async fn request_get_key(&self, node_id: &str, key: &str) -> Result<Option<String>, AppError> {
+        let shard = self.hot.route(key);
+        let node_id = shard.as_deref().unwrap_or(node_id);
+        let flight = (node_id.to_string(), key.to_string());
+        self.gets.run(flight, || self.fetch_key(node_id, key)).await
+    }
+
+    async fn request_get_key_from(
         &self,
         node_id: &str,
         key: &str,
-        value: &str,
-        ttl: Option<u64>,
-    ) -> Result<bool, AppError> {
-        let ttl_str = ttl.map(|t| t.to_string()).unwrap_or_default();
+        read_from: &str,
+    ) -> Result<Option<String>, AppError> {
+        // ni copias calientes ni lecturas juntadas: tiene que ser ese nodo
+        let pinned = self
+            .get_all_nodes(node_id)
+            .into_iter()
+            .find(|node| *node.node_id == *read_from);
+        let Some(pinned) = pinned else {
+            return self.request_get_key(node_id, key).await;
+        };
+        let payload = encode_token(key);
+        let response = pinned
+            .socket
+            .request(RequestDataInput {
+                action: "GET",
+                payload: &payload,
+            })
+            .await;
+        match response {
+            Ok(response) if response.is_success() => {
+                Ok(Some(response.payload).filter(|value| !value.is_empty()))
+            }
+            Ok(response) => {
+                debug!(node = %read_from, "GET fijado respondió {}", response.code);
+                self.request_get_key(node_id, key).await
+            }
+            Err(e) => {
+                debug!(node = %read_from, "GET fijado falló: {e}");
+                self.request_get_key(node_id, key).await
+            }
+        }
+    }
 
-        //TODO Find a better way to format this
-        let payload = format!(r#"{} "{}" {}"#, key, value, ttl_str)
-            .trim()
-            .to_string();
+    async fn request_get_key_if_not_version(
+        &self,
+        node_id: &str,
+        key: &str,
+        if_not_version: u64,
+    ) -> Result<ConditionalGet, AppError> {
+        // cada nodo lleva sus versiones: se pregunta al primario, como en los `WATCH`
+        let payload = encode_args([key, IF_NOT_VERSION, &if_not_version.to_string()]);
+        let response = self
+            .resolve_node(node_id)?
+            .socket
+            .request(RequestDataInput {
+                action: "GET",
+                payload: &payload,
+            })
+            .await?;
 
-        let request = RequestDataInput {
-            action: "PUT",
-            payload: &payload,
+        match response.version {
+            Some(version) if response.is_not_modified() => {
+                return Ok(ConditionalGet::NotModified { version });
+            }
+            Some(version) if response.is_success() => {
+                return Ok(ConditionalGet::Modified {
+                    value: response.payload,
+                    version,
+                });
+            }
+            _ => {}
+        }
+        node_busy(&response)?;
+        if let Some(e) = response.error_message() {
+            return Err(AppError::BadRequest(e.to_string()));
+        }
+        Ok(ConditionalGet::Missing)
+    }
+
+    async fn request_get_key_refresh(
+        &self,
+        node_id: &str,
+        key: &str,
+        window_ms: u64,
+    ) -> Result<Option<(String, bool)>, AppError> {
+        // no se junta con otros: el nodo ya elige a uno solo para refrescar
+        let payload = encode_args([key, &encode_refresh(window_ms)]);
+        let Some(values) = self.read_key_values(node_id, &payload).await? else {
+            return Ok(None);
         };
+        match values.as_slice() {
+            [value, refresh] => Ok(Some((value.clone(), refresh == "1"))),
+            _ => Err(AppError::SocketError(format!(
+                "respuesta inválida a GET refresh: {values:?}"
+            ))),
+        }
+    }
 
-        let nodes = self.get_all_nodes(node_id);
+    async fn request_get_key_stale(
+        &self,
+        node_id: &str,
+        key: &str,
+        max_stale_ms: u64,
+    ) -> Result<Option<(String, bool, u64)>, AppError> {
+        let payload = encode_args([key, &encode_stale(max_stale_ms)]);
+        let Some(values) = self.read_key_values(node_id, &payload).await? else {
+            return Ok(None);
+        };
+        match values.as_slice() {
+            [value, refresh, stale_for] => match stale_for.parse() {
+                Ok(stale_for) => Ok(Some((value.clone(), refresh == "1", stale_for))),
+                Err(_) => Err(AppError::SocketError(format!(
+                    "respuesta inválida a GET stale: {values:?}"
+                ))),
+            },
+            _ => Err(AppError::SocketError(format!(
+                "respuesta inválida a GET stale: {values:?}"
+            ))),
+        }
+    }
 
-        let response = request_all_race_first_abort_rest(&nodes, request)
-            .await
-            .map_err(|e| AppError::ConnectionError(e.to_string()))?;
+    async fn request_multi(
+        &self,
+        node_id: &str,
+        commands: &[TxCommand],
+    ) -> Result<Vec<String>, AppError> {
+        let writes: Vec<TxCommand> = commands.iter().filter(|c| c.is_write()).cloned().collect();
+        // un DEL libera memoria: solo los PUT esperan a que el shard baje
+        if writes.iter().any(|w| matches!(w, TxCommand::Put { .. })) {
+            self.admit_writes(node_id)?;
+        } else if !writes.is_empty() {
+            self.check_writable(node_id)?;
+        }
+        for write in &writes {
+            self.drop_hot_copies(write.key());
+        }
 
-        if response.is_success() {
-            return Ok(true);
+        // los WATCH se comparan con las versiones de un solo nodo: el primario
+        let response = self
+            .request_primary(node_id, MULTI, &encode_multi(commands))
+            .await;
+
+        for write in &writes {
+            self.drop_hot_copies(write.key());
+        }
+        let response = response?;
+        if !writes.is_empty() {
+            self.replay_to_replicas(node_id, MULTI, encode_multi(&writes), None);
         }
 
-        Err(AppError::ConnectionError(format!(
-            "Error en PUT: {} {}",
-            response.code, response.payload
-        )))
+        Ok(response.values())
     }
 
-    async fn request_get_key(&self, node_id: &str, key: &str) -> Result<Option<String>, AppError> {
-        let request = RequestDataInput {
-            action: "GET",
-            payload: key,
-        };
+    async fn request_rename(&self, node_id: &str, from: &str, to: &str) -> Result<(), AppError> {
+        // el valor cambia de clave sin ocupar más memoria en el shard
+        self.check_writable(node_id)?;
+        self.drop_hot_copies(from);
+        self.drop_hot_copies(to);
 
-        let nodes = self.get_all_nodes(node_id);
+        let payload = encode_args([from, to]);
+        let response = self.request_primary(node_id, "RENAME", &payload).await;
 
-        let response = request_all_race_first_abort_rest(&nodes, request)
-            .await
-            .map_err(|e| AppError::ConnectionError(e.to_string()))?;
+        self.drop_hot_copies(from);
+        self.drop_hot_copies(to);
+        response?;
+        self.replay_to_replicas(node_id, "RENAME", payload, None);
+        Ok(())
+    }
 
-        if response.is_success() {
-            return Ok(Some(response.payload));
+    async fn request_key_expiry(
+        &self,
+        node_id: &str,
+        key: &str,
+    ) -> Result<Option<(u64, Option<u64>)>, AppError> {
+        let meta = self
+            .request_primary(node_id, "META", &encode_token(key))
+            .await?;
+        if meta.is_empty_value() {
+            return Ok(None);
         }
+        let field = |name: &str| {
+            meta.payload
+                .split(' ')
+                .find_map(|field| field.strip_prefix(name))
+                .map(str::to_string)
+        };
+        let version = field("version=")
+            .and_then(|version| version.parse::<u64>().ok())
+            .ok_or_else(|| {
+                AppError::ConnectionError(format!("META sin versión: {}", meta.payload))
+            })?;
+        let expires_at = field("expires_at=").and_then(|exp| exp.parse::<u64>().ok());
+        Ok(Some((version, expires_at)))
+    }
 
-        Ok(None)
+    async fn request_put_key_if(
+        &self,
+        node_id: &str,
+        put: &PutKey<'_>,
+        condition: &PutCondition,
+    ) -> Result<Option<String>, AppError> {
+        let key = put.key;
+        self.admit_writes(node_id)?;
+        self.drop_hot_copies(key);
+        let put = self.put_payload(put);
+        let serial = match &self.key_locks {
+            Some(locks) => Some(locks.lock(key).await),
+            None => None,
+        };
+
+        // la condición se evalúa en el primario; las réplicas copian el resultado
+        let payload = format!("{put} {}", encode_args([IF, &condition.to_string()]));
+        let applied = self.request_primary(node_id, "PUT", &payload).await;
+        self.drop_hot_copies(key);
+        applied?;
+        self.replay_to_replicas(node_id, "PUT", put, serial);
+
+        Ok(Some(node_id.to_string()))
     }
 
     fn count_replica_nodes(&self, node_id: &str) -> usize {
@@ -279,3 +1432,51 @@ impl NetworkService for TcpNetworkService {
             .unwrap_or_default()
     }
 }
+
+/// Las copias de una clave caliente se dejan de leer este tiempo antes de que venzan.
+const HOT_COPY_MARGIN_MS: u64 = 500;
+
+/// Anota en `retries` qué nodos confirmaron la escritura `version` de `key` y cuáles se la
+/// perdieron por no contestar o por un error propio (no por rechazarla: eso no se arregla
+/// reintentando).
+fn track_write(
+    retries: &WriteRetries,
+    key: &str,
+    version: u64,
+    payload: &Arc<str>,
+    outcomes: &[NodeOutcome],
+) {
+    for outcome in outcomes {
+        match &outcome.result {
+            Ok(response) if response.is_success() => {
+                retries.confirmed(&outcome.node_id, key, version)
+            }
+            Ok(response)
+                if matches!(
+                    response.error_kind(),
+                    Some(
+                        ErrorKind::Unavailable
+                            | ErrorKind::Timeout
+                            | ErrorKind::Connection
+                            | ErrorKind::Internal
+                    )
+                ) =>
+            {
+                let error = format!("{} {}", response.code, response.payload);
+                retries.missed(&outcome.node_id, key, version, payload, error);
+            }
+            Ok(_) => {}
+            Err(e) => retries.missed(&outcome.node_id, key, version, payload, e.to_string()),
+        }
+    }
+}
+
+/// El nodo contestó que está al tope de requests en curso (ver `RequestLimits` del nodo).
+fn node_busy(response: &ResponseData) -> Result<(), AppError> {
+    match response.error_kind() {
+        Some(ErrorKind::Unavailable) => Err(AppError::NodeBusy(
+            response.error_message().unwrap_or_default().to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
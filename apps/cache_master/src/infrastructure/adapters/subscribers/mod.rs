@@ -0,0 +1,7 @@
+pub mod replication_subscriber;
+pub mod topology_feed_subscriber;
+pub mod topology_log_subscriber;
+
+pub use replication_subscriber::ReplicationSubscriber;
+pub use topology_feed_subscriber::TopologyFeedSubscriber;
+pub use topology_log_subscriber::TopologyLogSubscriber;
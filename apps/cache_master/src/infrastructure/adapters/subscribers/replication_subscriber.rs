@@ -0,0 +1,48 @@
+use std::sync::Arc;
+
+use app_core::events::{Envelope, EventSubscriber};
+use async_trait::async_trait;
+use tracing::{info, warn};
+
+use crate::{
+    core::domain::models::DomainEvent,
+    infrastructure::adapters::services::tcp_network_service::TcpNetworkService,
+};
+
+/// Cuando una réplica entra a un shard le indica de qué primario replicar, para que
+/// converja aunque el fan-out del master no le llegue.
+pub struct ReplicationSubscriber {
+    network: Arc<TcpNetworkService>,
+}
+
+impl ReplicationSubscriber {
+    pub fn new(network: Arc<TcpNetworkService>) -> Self {
+        Self { network }
+    }
+}
+
+#[async_trait]
+impl EventSubscriber<DomainEvent> for ReplicationSubscriber {
+    async fn handle(&self, envelope: &Envelope<DomainEvent>) {
+        let DomainEvent::NodeJoined { node_id, shard_id } = &envelope.event else {
+            return;
+        };
+        if node_id == shard_id {
+            return;
+        }
+
+        match self
+            .network
+            .assign_replication_source(node_id, shard_id)
+            .await
+        {
+            Ok(Some(primary)) => {
+                info!(target: "topology", node_id, shard_id, primary, "replicación asignada")
+            }
+            Ok(None) => {}
+            Err(e) => {
+                warn!(target: "topology", node_id, shard_id, "no se pudo asignar la replicación: {e}")
+            }
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use std::{collections::HashSet, sync::Arc};
+
+use app_core::events::{Envelope, EventSubscriber};
+use app_net::TopologyChange;
+use async_trait::async_trait;
+use parking_lot::Mutex;
+
+use crate::{
+    core::domain::{models::DomainEvent, services::ConsistentHasherService},
+    infrastructure::topology_feed::TopologyFeed,
+};
+
+/// Traduce altas y bajas de nodos a los avisos de `TopologyFeed`. Un shard sale del anillo
+/// recién cuando se va su último nodo, así que en cada baja se revisa qué shards
+/// conocidos ya no están en el hasher.
+pub struct TopologyFeedSubscriber {
+    feed: Arc<TopologyFeed>,
+    hasher: Arc<dyn ConsistentHasherService>,
+    /// Shards avisados con `ShardAdded` que siguen en el anillo.
+    shards: Mutex<HashSet<String>>,
+}
+
+impl TopologyFeedSubscriber {
+    pub fn new(feed: Arc<TopologyFeed>, hasher: Arc<dyn ConsistentHasherService>) -> Self {
+        Self {
+            feed,
+            hasher,
+            shards: Mutex::new(HashSet::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl EventSubscriber<DomainEvent> for TopologyFeedSubscriber {
+    async fn handle(&self, envelope: &Envelope<DomainEvent>) {
+        match &envelope.event {
+            DomainEvent::NodeJoined { node_id, shard_id } if node_id == shard_id => {
+                self.shards.lock().insert(shard_id.clone());
+                self.feed.publish(TopologyChange::ShardAdded {
+                    shard_id: shard_id.clone(),
+                });
+            }
+            DomainEvent::NodeJoined { node_id, shard_id } => {
+                self.feed.publish(TopologyChange::NodeJoined {
+                    node_id: node_id.clone(),
+                    shard_id: shard_id.clone(),
+                });
+            }
+            DomainEvent::NodeRemoved { node_id } => {
+                self.feed.publish(TopologyChange::NodeLeft {
+                    node_id: node_id.clone(),
+                });
+
+                let mut gone: Vec<String> = Vec::new();
+                self.shards.lock().retain(|shard_id| {
+                    let alive = self.hasher.node_exists(shard_id);
+                    if !alive {
+                        gone.push(shard_id.clone());
+                    }
+                    alive
+                });
+                gone.sort();
+                for shard_id in gone {
+                    self.feed.publish(TopologyChange::ShardRemoved { shard_id });
+                }
+            }
+            _ => {}
+        }
+    }
+}
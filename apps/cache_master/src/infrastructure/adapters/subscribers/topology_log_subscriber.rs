@@ -0,0 +1,45 @@
+use app_core::events::{Envelope, EventSubscriber};
+use async_trait::async_trait;
+use tracing::{debug, info, warn};
+
+use crate::core::domain::models::DomainEvent;
+
+/// Deja traza de los cambios de topología publicados en el bus.
+pub struct TopologyLogSubscriber;
+
+#[async_trait]
+impl EventSubscriber<DomainEvent> for TopologyLogSubscriber {
+    async fn handle(&self, envelope: &Envelope<DomainEvent>) {
+        let event_id = envelope.id.as_str();
+        match &envelope.event {
+            DomainEvent::NodeJoined { node_id, shard_id } => {
+                info!(target: "topology", event_id, node_id, shard_id, "node joined")
+            }
+            DomainEvent::NodeRemoved { node_id } => {
+                info!(target: "topology", event_id, node_id, "node removed")
+            }
+            DomainEvent::KeyMigrated {
+                key,
+                from_node,
+                to_node,
+            } => debug!(target: "topology", event_id, key, from_node, to_node, "key migrated"),
+            DomainEvent::KeyExpired { key, node_id } => {
+                debug!(target: "topology", event_id, key, node_id, "key expired")
+            }
+            DomainEvent::ShardUndersized {
+                node_id,
+                shard_id,
+                evictions_per_sec,
+                fill_percent,
+            } => warn!(
+                target: "topology",
+                event_id,
+                node_id,
+                shard_id,
+                evictions_per_sec,
+                fill_percent,
+                "shard undersized: evicting under memory pressure"
+            ),
+        }
+    }
+}
@@ -1,13 +1,79 @@
-use std::sync::Arc;
+use std::{
+    net::IpAddr,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
 
-use app_net::Socket;
+use app_net::{EventData, Socket, event::GROUP_ASSIGNED};
 use dashmap::DashMap;
 use parking_lot::RwLock;
+use tokio_util::sync::CancellationToken;
+
+/// Contadores de una conexión, para saber quién genera la carga sin capturar tráfico. Los
+/// bytes son de frames enteros (con el `\n`); `requests` cuenta los `REQ` que mandó el
+/// otro extremo y `errors` las respuestas de error que recibió, más los frames rechazados
+/// por largos. `last_activity_ms` es la hora del master (ms desde epoch) del último frame
+/// que llegó.
+#[derive(Debug, Default)]
+pub struct ConnectionStats {
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+    requests: AtomicU64,
+    errors: AtomicU64,
+    last_activity_ms: AtomicU64,
+}
+
+impl ConnectionStats {
+    pub fn observe_frame(&self, len: usize, now_ms: u64) {
+        self.bytes_in.fetch_add(len as u64 + 1, Ordering::Relaxed);
+        self.last_activity_ms.fetch_max(now_ms, Ordering::Relaxed);
+    }
+
+    pub fn observe_request(&self) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn observe_error(&self) {
+        self.errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn observe_sent(&self, len: usize) {
+        self.bytes_out.fetch_add(len as u64, Ordering::Relaxed);
+    }
+
+    pub fn bytes_in(&self) -> u64 {
+        self.bytes_in.load(Ordering::Relaxed)
+    }
+
+    /// `bytes_in=.. bytes_out=.. requests=.. errors=.. last_activity_ms=..`
+    pub fn to_wire(&self) -> String {
+        format!(
+            "bytes_in={} bytes_out={} requests={} errors={} last_activity_ms={}",
+            self.bytes_in(),
+            self.bytes_out.load(Ordering::Relaxed),
+            self.requests.load(Ordering::Relaxed),
+            self.errors.load(Ordering::Relaxed),
+            self.last_activity_ms.load(Ordering::Relaxed),
+        )
+    }
+}
 
 pub struct AppNetworkNode {
     pub master_id: RwLock<Option<Arc<str>>>,
     pub node_id: Arc<str>,
     pub socket: Arc<Socket>,
+    /// Dónde escuchan el nodo a sus réplicas (replicación nodo a nodo), si lo anunció.
+    pub repl_addr: Option<Arc<str>>,
+    /// IP del otro extremo de la conexión; `None` si no es TCP.
+    pub peer_ip: Option<IpAddr>,
+    /// `master`, `replica` o `client`, según cómo se presentó.
+    pub kind: &'static str,
+    /// Lo comparte con la tarea que escribe en la conexión, que suma `bytes_out`.
+    pub stats: Arc<ConnectionStats>,
+    /// Cancelarlo cierra la conexión del nodo (ver `disconnect`).
+    closed: CancellationToken,
 }
 
 impl AppNetworkNode {
@@ -17,17 +83,62 @@ impl AppNetworkNode {
             socket,
             master_id: RwLock::new(None),
             node_id,
+            repl_addr: None,
+            peer_ip: None,
+            kind: "master",
+            stats: Arc::default(),
+            closed: CancellationToken::new(),
         }
     }
 
+    pub fn with_peer_ip(mut self, peer_ip: Option<IpAddr>) -> Self {
+        self.peer_ip = peer_ip;
+        self
+    }
+
+    pub fn with_kind(mut self, kind: &'static str) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// `<id> type=<kind> peer=<ip> bytes_in=..`: una línea del listado de `STATS`.
+    pub fn connection_line(&self) -> String {
+        let peer = self.peer_ip.map(|ip| ip.to_string()).unwrap_or_default();
+        format!(
+            "{} type={} peer={peer} {}",
+            self.node_id,
+            self.kind,
+            self.stats.to_wire()
+        )
+    }
+
+    /// Corta la conexión del nodo; el master lo saca como a cualquiera que se desconecta.
+    pub fn disconnect(&self) {
+        self.closed.cancel();
+    }
+
+    /// Se completa al llamarse `disconnect`.
+    pub async fn disconnected(&self) {
+        self.closed.cancelled().await
+    }
+
+    pub fn with_repl_addr(mut self, repl_addr: Option<&str>) -> Self {
+        self.repl_addr = repl_addr.map(Arc::from);
+        self
+    }
+
     #[inline]
     pub fn new_shared(socket: Arc<Socket>, node_id: Arc<str>) -> Arc<Self> {
         Arc::new(Self::new(socket, node_id))
     }
 
+    /// Lo deja en el shard de `id` y se lo avisa con `GROUP-ASSIGNED`, que es como un
+    /// proceso con varios grupos sabe de cuál es cada una de sus conexiones.
     pub fn set_master_id(&self, id: &str) {
         let mut g = self.master_id.write();
         *g = Some(Arc::<str>::from(id));
+        drop(g);
+        let _ = self.socket.send_evt(&EventData::new(GROUP_ASSIGNED, id));
     }
 
     pub fn get_master_id(&self) -> Option<Arc<str>> {
@@ -37,6 +148,10 @@ impl AppNetworkNode {
 
 pub struct AppNetworkState {
     pub nodes_registry: DashMap<Arc<str>, Arc<AppNetworkNode>>,
+    /// Todas las conexiones vivas, clientes incluidos, por un número de conexión: varios
+    /// clientes pueden presentarse con el mismo id.
+    pub connections: DashMap<u64, Arc<AppNetworkNode>>,
+    next_connection: AtomicU64,
 }
 
 impl AppNetworkState {
@@ -44,15 +159,30 @@ impl AppNetworkState {
     pub fn new() -> Self {
         Self {
             nodes_registry: DashMap::new(),
+            connections: DashMap::new(),
+            next_connection: AtomicU64::new(0),
         }
     }
 
+    /// La anota entre las conexiones vivas; devuelve el número con que sacarla.
+    pub fn add_connection(&self, node: Arc<AppNetworkNode>) -> u64 {
+        let id = self.next_connection.fetch_add(1, Ordering::Relaxed);
+        self.connections.insert(id, node);
+        id
+    }
+
     #[inline]
     pub fn new_shared() -> Arc<Self> {
         Arc::new(Self::new())
     }
 }
 
+impl Default for AppNetworkState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub struct AppState {
     pub network_state: Arc<AppNetworkState>,
 }
@@ -68,3 +198,9 @@ impl AppState {
         Arc::new(Self::new())
     }
 }
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,77 @@
+//! Diferencia de reloj de cada nodo con el master, medida con el eco de los `HEARTBEAT`
+//! (ver `app_net::event::Heartbeat`). Importa porque los `expires_at` de los `PUT` los
+//! calcula el master y los evalúa el nodo con su reloj: un nodo adelantado vence las claves
+//! antes de tiempo y uno atrasado las sirve de más.
+
+use std::{collections::VecDeque, sync::Arc};
+
+use app_net::event::ClockSample;
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use tracing::{info, warn};
+
+/// Muestras que se guardan por nodo; la estimación es la de menor ida y vuelta entre ellas,
+/// la que menos error puede tener (como hace NTP).
+const SAMPLES: usize = 8;
+
+/// A partir de esta diferencia se avisa en el log.
+pub const SKEW_WARN_MS: u64 = 500;
+
+#[derive(Default)]
+struct NodeClock {
+    samples: VecDeque<ClockSample>,
+    warned: bool,
+}
+
+impl NodeClock {
+    fn best(&self) -> Option<ClockSample> {
+        self.samples.iter().min_by_key(|s| s.rtt_ms).copied()
+    }
+}
+
+/// Lo que se sabe del reloj de cada nodo que manda latidos con eco.
+#[derive(Default)]
+pub struct ClockSkews {
+    nodes: DashMap<Arc<str>, Mutex<NodeClock>>,
+}
+
+impl ClockSkews {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Suma una muestra de `node_id` y devuelve la estimación que queda. Avisa una vez
+    /// cuando la diferencia pasa `SKEW_WARN_MS` y otra cuando vuelve por debajo.
+    pub fn observe(&self, node_id: &Arc<str>, sample: ClockSample) -> ClockSample {
+        let entry = self.nodes.entry(node_id.clone()).or_default();
+        let mut clock = entry.lock();
+        if clock.samples.len() == SAMPLES {
+            clock.samples.pop_front();
+        }
+        clock.samples.push_back(sample);
+
+        let best = clock.best().unwrap_or(sample);
+        let skewed = best.offset_ms.unsigned_abs() >= SKEW_WARN_MS;
+        if skewed && !clock.warned {
+            warn!(
+                node_id = %node_id,
+                offset_ms = best.offset_ms,
+                rtt_ms = best.rtt_ms,
+                "el reloj del nodo difiere del master: los TTL vencen corridos"
+            );
+        } else if !skewed && clock.warned {
+            info!(node_id = %node_id, offset_ms = best.offset_ms, "el reloj del nodo volvió a estar en hora");
+        }
+        clock.warned = skewed;
+        best
+    }
+
+    /// La mejor estimación de `node_id`; `None` si todavía no mandó un latido con eco.
+    pub fn estimate(&self, node_id: &str) -> Option<ClockSample> {
+        self.nodes.get(node_id)?.lock().best()
+    }
+
+    pub fn forget(&self, node_id: &str) {
+        self.nodes.remove(node_id);
+    }
+}
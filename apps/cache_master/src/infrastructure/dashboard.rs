@@ -0,0 +1,265 @@
+//! Vista del cluster para `/dashboard`: topología, salud por nodo y actividad del último
+//! minuto. Se arma en cada request a partir de los servicios y de `MasterMetrics`.
+
+use std::fmt::Write;
+
+use serde::Serialize;
+
+use crate::infrastructure::{
+    di::CacheMasterModule,
+    metrics::{ACTIVITY_WINDOW_SECS, TopologyGauges},
+};
+
+const HOT_KEYS_SHOWN: usize = 10;
+/// Por debajo de estos requests no se opina sobre la salud de un nodo.
+const HEALTH_MIN_SAMPLES: u64 = 10;
+/// Proporción de requests fallidos a partir de la cual un nodo se considera degradado.
+const DEGRADED_ERROR_RATE: f64 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeHealth {
+    /// Todavía sin suficientes requests para juzgar.
+    Idle,
+    Healthy,
+    Degraded,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeRole {
+    Master,
+    Replica,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeView {
+    pub id: String,
+    pub role: NodeRole,
+    pub health: NodeHealth,
+    pub requests: u64,
+    pub errors: u64,
+    pub p50_ms: Option<u64>,
+    pub p99_ms: Option<u64>,
+    /// Cuánto después que el primer nodo del shard confirma las escrituras.
+    pub replica_lag_p50_ms: Option<u64>,
+    pub replica_lag_p99_ms: Option<u64>,
+    /// Del último `CACHE-PRESSURE` del nodo; `None` si no reporta.
+    pub cache_fill_ratio: Option<f64>,
+    pub evictions_per_sec: Option<f64>,
+    /// `phi` del detector de fallas; `None` si el nodo no manda latidos.
+    pub suspicion: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ShardView {
+    pub id: String,
+    pub nodes: Vec<NodeView>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HotKeyView {
+    pub key: String,
+    pub hits: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ThroughputView {
+    pub window_secs: u64,
+    pub requests: u64,
+    pub failures: u64,
+    /// Requests por segundo, del más viejo al actual.
+    pub per_second: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Dashboard {
+    pub uptime_secs: u64,
+    pub registered_nodes: usize,
+    pub ring_nodes: usize,
+    pub ring_vnodes: usize,
+    pub shed_requests: u64,
+    pub shards: Vec<ShardView>,
+    pub hot_keys: Vec<HotKeyView>,
+    pub throughput: ThroughputView,
+}
+
+impl Dashboard {
+    pub fn collect(module: &CacheMasterModule) -> Self {
+        let metrics = &module.metrics;
+        let TopologyGauges {
+            registered_nodes,
+            ring_nodes,
+            ring_vnodes,
+            ..
+        } = module.topology_gauges();
+
+        let shards = module
+            .tcp_network_service
+            .shard_tree()
+            .into_iter()
+            .map(|(shard_id, members)| {
+                let nodes = members
+                    .into_iter()
+                    .map(|node_id| {
+                        let latency = metrics.node_latency_total(&node_id);
+                        let lag = metrics.replica_lag_histogram(&node_id);
+                        let pressure = metrics.cache_pressure(&node_id);
+
+                        NodeView {
+                            role: if node_id == shard_id {
+                                NodeRole::Master
+                            } else {
+                                NodeRole::Replica
+                            },
+                            health: health(latency.count(), latency.errors()),
+                            requests: latency.count(),
+                            errors: latency.errors(),
+                            p50_ms: latency.quantile_ms(0.50),
+                            p99_ms: latency.quantile_ms(0.99),
+                            replica_lag_p50_ms: lag.as_ref().and_then(|h| h.quantile_ms(0.50)),
+                            replica_lag_p99_ms: lag.as_ref().and_then(|h| h.quantile_ms(0.99)),
+                            cache_fill_ratio: pressure.map(|p| p.fill_ratio()),
+                            evictions_per_sec: pressure.map(|p| p.evictions_per_sec()),
+                            suspicion: module.failure_detector.phi(&node_id),
+                            id: node_id.to_string(),
+                        }
+                    })
+                    .collect();
+
+                ShardView {
+                    id: shard_id.to_string(),
+                    nodes,
+                }
+            })
+            .collect();
+
+        let hot_keys = metrics
+            .hot_keys(HOT_KEYS_SHOWN)
+            .into_iter()
+            .map(|(key, hits)| HotKeyView {
+                key: key.to_string(),
+                hits,
+            })
+            .collect();
+
+        let per_second = metrics.throughput_per_second();
+        let throughput = ThroughputView {
+            window_secs: ACTIVITY_WINDOW_SECS,
+            requests: per_second.iter().sum(),
+            failures: metrics.failures_per_second().iter().sum(),
+            per_second,
+        };
+
+        Self {
+            uptime_secs: metrics.uptime().as_secs(),
+            registered_nodes,
+            ring_nodes,
+            ring_vnodes,
+            shed_requests: metrics.shed_total(),
+            shards,
+            hot_keys,
+            throughput,
+        }
+    }
+
+    /// Versión HTML mínima (sin JS) para abrir desde el navegador.
+    pub fn to_html(&self) -> String {
+        let mut out = String::from(
+            "<!doctype html><html><head><meta charset=\"utf-8\"><title>cache_master</title>\
+             <style>body{font-family:monospace}td,th{padding:2px 8px;text-align:left}</style>\
+             </head><body><h1>cache_master</h1>",
+        );
+
+        let _ = write!(
+            out,
+            "<p>uptime {}s · {} nodos · anillo {} nodos / {} vnodes · {} requests y {} fallas \
+             en {}s · {} descartados</p>",
+            self.uptime_secs,
+            self.registered_nodes,
+            self.ring_nodes,
+            self.ring_vnodes,
+            self.throughput.requests,
+            self.throughput.failures,
+            self.throughput.window_secs,
+            self.shed_requests,
+        );
+
+        out.push_str("<h2>Shards</h2>");
+        for shard in &self.shards {
+            let _ = write!(
+                out,
+                "<h3>{}</h3><table><tr><th>nodo</th><th>rol</th><th>salud</th>\
+                 <th>requests</th><th>errores</th><th>p50</th><th>p99</th>\
+                 <th>lag p50</th><th>lag p99</th><th>cache</th><th>evicciones/s</th></tr>",
+                escape(&shard.id)
+            );
+            for node in &shard.nodes {
+                let _ = write!(
+                    out,
+                    "<tr><td>{}</td><td>{:?}</td><td>{:?}</td><td>{}</td><td>{}</td>\
+                     <td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                    escape(&node.id),
+                    node.role,
+                    node.health,
+                    node.requests,
+                    node.errors,
+                    ms(node.p50_ms),
+                    ms(node.p99_ms),
+                    ms(node.replica_lag_p50_ms),
+                    ms(node.replica_lag_p99_ms),
+                    node.cache_fill_ratio
+                        .map(|r| format!("{:.0}%", r * 100.0))
+                        .unwrap_or_else(|| "-".into()),
+                    node.evictions_per_sec
+                        .map(|r| format!("{r:.1}"))
+                        .unwrap_or_else(|| "-".into()),
+                );
+            }
+            out.push_str("</table>");
+        }
+
+        out.push_str("<h2>Hot keys</h2><table><tr><th>clave</th><th>accesos</th></tr>");
+        for hot in &self.hot_keys {
+            let _ = write!(
+                out,
+                "<tr><td>{}</td><td>{}</td></tr>",
+                escape(&hot.key),
+                hot.hits
+            );
+        }
+        out.push_str("</table></body></html>");
+        out
+    }
+}
+
+fn health(requests: u64, errors: u64) -> NodeHealth {
+    if requests < HEALTH_MIN_SAMPLES {
+        NodeHealth::Idle
+    } else if errors as f64 / requests as f64 > DEGRADED_ERROR_RATE {
+        NodeHealth::Degraded
+    } else {
+        NodeHealth::Healthy
+    }
+}
+
+fn ms(value: Option<u64>) -> String {
+    value
+        .map(|v| format!("≤{v}ms"))
+        .unwrap_or_else(|| "-".into())
+}
+
+fn escape(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    for c in raw.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
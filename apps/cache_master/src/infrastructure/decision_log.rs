@@ -0,0 +1,325 @@
+//! Registro de decisiones del master, para reconstruir después por qué una clave fue a
+//! parar a un nodo. Con `DECISION_LOG=<archivo>` se anota, una por línea y con la hora del
+//! master y el `epoch` de topología del momento:
+//!
+//! - `<ms> <epoch> start`: el master arrancó con el anillo vacío.
+//! - `<ms> <epoch> ring-add "<shard>"` / `ring-remove "<shard>"`: el anillo cambió.
+//! - `<ms> <epoch> route <hash> "<nodo>"..`: a qué shards mandó el hasher ese hash.
+//! - `<ms> <epoch> <tipo> "<id>" ["<shard>"]`: un aviso de `TopologyFeed` (ver
+//!   `app_net::TopologyEvent`), que es el que sube el `epoch`.
+//!
+//! Los cambios del anillo y las rutas se anotan con el anillo tomado, así que en el
+//! archivo quedan en el orden en que pasaron. `DecisionReplay` relee el archivo y arma el
+//! anillo de cualquier momento (ver el binario `decision_replay`).
+
+use std::{
+    collections::BTreeSet,
+    fmt,
+    fs::OpenOptions,
+    io::{self, BufWriter, Write},
+    path::Path,
+    str::FromStr,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+        mpsc,
+    },
+    thread::JoinHandle,
+};
+
+use app_core::clock::Clock;
+use app_net::{TopologyChange, TopologyEvent, encode_args, encode_token, tokenize};
+use tracing::warn;
+
+use crate::{
+    core::domain::{models::AppError, services::ConsistentHasherService},
+    infrastructure::adapters::services::dashmap_consistent_hasher_service::DashmapConsistentHasherService,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decision {
+    Start,
+    RingAdd { shard_id: String },
+    RingRemove { shard_id: String },
+    Route { hash: String, nodes: Vec<String> },
+    Topology(TopologyChange),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecisionEntry {
+    pub at_ms: u64,
+    pub epoch: u64,
+    pub decision: Decision,
+}
+
+impl fmt::Display for DecisionEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ", self.at_ms)?;
+        match &self.decision {
+            Decision::Start => write!(f, "{} start", self.epoch),
+            Decision::RingAdd { shard_id } => {
+                write!(f, "{} ring-add {}", self.epoch, encode_token(shard_id))
+            }
+            Decision::RingRemove { shard_id } => {
+                write!(f, "{} ring-remove {}", self.epoch, encode_token(shard_id))
+            }
+            Decision::Route { hash, nodes } => write!(
+                f,
+                "{} route {hash} {}",
+                self.epoch,
+                encode_args(nodes.iter().map(String::as_str))
+            ),
+            Decision::Topology(change) => write!(
+                f,
+                "{}",
+                TopologyEvent {
+                    epoch: self.epoch,
+                    change: change.clone(),
+                }
+            ),
+        }
+    }
+}
+
+impl FromStr for DecisionEntry {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = || AppError::BadRequest(format!("línea de decisión inválida: {s}"));
+        let (at_ms, rest) = s.trim().split_once(' ').ok_or_else(bad)?;
+        let at_ms = at_ms.parse::<u64>().map_err(|_| bad())?;
+
+        let mut parts = tokenize(rest);
+        let epoch = parts
+            .next()
+            .and_then(|e| e.parse::<u64>().ok())
+            .ok_or_else(bad)?;
+        let kind = parts.next().ok_or_else(bad)?;
+        let mut id = || parts.next().map(|id| id.into_owned()).ok_or_else(bad);
+        let decision = match &*kind {
+            "start" => Decision::Start,
+            "ring-add" => Decision::RingAdd { shard_id: id()? },
+            "ring-remove" => Decision::RingRemove { shard_id: id()? },
+            "route" => {
+                let hash = id()?;
+                let nodes = parts.map(|node| node.into_owned()).collect();
+                Decision::Route { hash, nodes }
+            }
+            _ => {
+                let event = rest.parse::<TopologyEvent>().map_err(|_| bad())?;
+                Decision::Topology(event.change)
+            }
+        };
+        Ok(Self {
+            at_ms,
+            epoch,
+            decision,
+        })
+    }
+}
+
+/// Escribe las decisiones desde un hilo propio: quien decide solo las encola.
+pub struct DecisionLog {
+    clock: Arc<dyn Clock>,
+    /// El del último aviso de `TopologyFeed`.
+    epoch: AtomicU64,
+    tx: Option<mpsc::Sender<String>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl DecisionLog {
+    /// Agrega al final de `path`, empezando con un `start`.
+    pub fn open(path: &Path, clock: Arc<dyn Clock>) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self::to_writer(file, clock))
+    }
+
+    pub fn to_writer(sink: impl Write + Send + 'static, clock: Arc<dyn Clock>) -> Self {
+        let (tx, rx) = mpsc::channel::<String>();
+        let writer = std::thread::spawn(move || {
+            let mut sink = BufWriter::new(sink);
+            while let Ok(line) = rx.recv() {
+                let mut written = sink.write_all(line.as_bytes());
+                // se baja a disco cada vez que se vacía la cola
+                while let Ok(line) = rx.try_recv() {
+                    written = written.and_then(|_| sink.write_all(line.as_bytes()));
+                }
+                if let Err(e) = written.and_then(|_| sink.flush()) {
+                    warn!("decision log: {e}");
+                }
+            }
+        });
+        let log = Self {
+            clock,
+            epoch: AtomicU64::new(0),
+            tx: Some(tx),
+            writer: Some(writer),
+        };
+        log.record(Decision::Start);
+        log
+    }
+
+    pub fn ring_added(&self, shard_id: &str) {
+        self.record(Decision::RingAdd {
+            shard_id: shard_id.to_string(),
+        });
+    }
+
+    pub fn ring_removed(&self, shard_id: &str) {
+        self.record(Decision::RingRemove {
+            shard_id: shard_id.to_string(),
+        });
+    }
+
+    pub fn route(&self, hash: &str, nodes: &[Arc<str>]) {
+        self.record(Decision::Route {
+            hash: hash.to_string(),
+            nodes: nodes.iter().map(|node| node.to_string()).collect(),
+        });
+    }
+
+    /// Deja el `epoch` del aviso para las decisiones que siguen.
+    pub fn topology(&self, event: &TopologyEvent) {
+        self.epoch.fetch_max(event.epoch, Ordering::AcqRel);
+        self.write(DecisionEntry {
+            at_ms: self.clock.now_millis().as_millis_u64(),
+            epoch: event.epoch,
+            decision: Decision::Topology(event.change.clone()),
+        });
+    }
+
+    fn record(&self, decision: Decision) {
+        self.write(DecisionEntry {
+            at_ms: self.clock.now_millis().as_millis_u64(),
+            epoch: self.epoch.load(Ordering::Acquire),
+            decision,
+        });
+    }
+
+    fn write(&self, entry: DecisionEntry) {
+        if let Some(tx) = &self.tx {
+            let _ = tx.send(format!("{entry}\n"));
+        }
+    }
+}
+
+impl Drop for DecisionLog {
+    /// Espera a que se escriba lo encolado.
+    fn drop(&mut self) {
+        drop(self.tx.take());
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+/// Una ruta anotada junto con la que da el anillo rearmado en ese momento.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteCheck {
+    pub at_ms: u64,
+    pub epoch: u64,
+    pub nodes: Vec<String>,
+    pub expected: Vec<String>,
+}
+
+impl RouteCheck {
+    pub fn matches(&self) -> bool {
+        self.nodes == self.expected
+    }
+}
+
+/// Un registro de decisiones leído para rearmar el anillo.
+pub struct DecisionReplay {
+    entries: Vec<DecisionEntry>,
+    /// Líneas (desde 1) que no se entendieron, p. ej. la última si el master se cayó
+    /// escribiéndola; se saltean.
+    skipped: Vec<usize>,
+}
+
+impl DecisionReplay {
+    pub fn parse(text: &str) -> Self {
+        let mut entries = Vec::new();
+        let mut skipped = Vec::new();
+        for (n, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match line.parse::<DecisionEntry>() {
+                Ok(entry) => entries.push(entry),
+                Err(_) => skipped.push(n + 1),
+            }
+        }
+        Self { entries, skipped }
+    }
+
+    pub fn skipped(&self) -> &[usize] {
+        &self.skipped
+    }
+
+    pub fn entries(&self) -> &[DecisionEntry] {
+        &self.entries
+    }
+
+    /// El anillo como estaba en `at_ms` (incluido); un `start` lo vacía.
+    pub fn ring_at(&self, at_ms: u64) -> DashmapConsistentHasherService {
+        let mut ring = DashmapConsistentHasherService::new();
+        for entry in self.entries.iter().take_while(|e| e.at_ms <= at_ms) {
+            Self::apply(&mut ring, entry);
+        }
+        ring
+    }
+
+    /// Los shards que había en el anillo en `at_ms`, ordenados.
+    pub fn shards_at(&self, at_ms: u64) -> Vec<String> {
+        let mut shards = BTreeSet::new();
+        for entry in self.entries.iter().take_while(|e| e.at_ms <= at_ms) {
+            match &entry.decision {
+                Decision::Start => shards.clear(),
+                Decision::RingAdd { shard_id } => {
+                    shards.insert(shard_id.clone());
+                }
+                Decision::RingRemove { shard_id } => {
+                    shards.remove(shard_id);
+                }
+                Decision::Route { .. } | Decision::Topology(_) => {}
+            }
+        }
+        shards.into_iter().collect()
+    }
+
+    /// Cada `route` de `hash` con lo que daba el anillo rearmado hasta esa línea.
+    pub fn routes_of(&self, hash: &str) -> Vec<RouteCheck> {
+        let mut ring = DashmapConsistentHasherService::new();
+        let mut checks = Vec::new();
+        for entry in &self.entries {
+            Self::apply(&mut ring, entry);
+            if let Decision::Route {
+                hash: routed,
+                nodes,
+            } = &entry.decision
+                && routed == hash
+            {
+                checks.push(RouteCheck {
+                    at_ms: entry.at_ms,
+                    epoch: entry.epoch,
+                    nodes: nodes.clone(),
+                    expected: ring.get_node_ids_from_hash(hash, nodes.len()),
+                });
+            }
+        }
+        checks
+    }
+
+    fn apply(ring: &mut DashmapConsistentHasherService, entry: &DecisionEntry) {
+        match &entry.decision {
+            Decision::Start => *ring = DashmapConsistentHasherService::new(),
+            Decision::RingAdd { shard_id } => {
+                ring.add_node(shard_id);
+            }
+            Decision::RingRemove { shard_id } => {
+                ring.remove_node(shard_id);
+            }
+            Decision::Route { .. } | Decision::Topology(_) => {}
+        }
+    }
+}
@@ -1,61 +1,316 @@
+pub mod config;
+
 use std::sync::Arc;
 
-use app_core::clock::AppClock;
+use app_core::{
+    UseCaseExt,
+    clock::{AppClock, Clock},
+    events::EventBus,
+};
+
+use app_net::ActionTimeouts;
+use tracing::warn;
 
 use crate::{
-    core::usecases::{AssignNodeUseCase, GetKeyUseCase, PutKeyUseCase, RemoveNodeUseCase},
+    core::{
+        domain::models::DomainEventBus,
+        usecases::{
+            AssignNodeUseCase, GetKeyUseCase, MemoizedGetKeyUseCase, MultiUseCase, PutKeyUseCase,
+            RemoveNodeUseCase, RenameUseCase,
+        },
+    },
     infrastructure::{
-        adapters::services::{
-            dashmap_consistent_hasher_service::DashmapConsistentHasherService,
-            tcp_network_service::TcpNetworkService,
+        adapters::{
+            controllers::{
+                actions::{ActionDeps, register_actions},
+                router::ActionRouter,
+            },
+            services::{
+                BackupService, ImportService, NodeAccess, ReadOnlySwitches, RegistrationQueue,
+                dashmap_consistent_hasher_service::DashmapConsistentHasherService,
+                stats_aggregation_service::StatsAggregationService,
+                tcp_network_service::TcpNetworkService,
+            },
         },
         app_state::AppState,
+        clock_skew::ClockSkews,
+        decision_log::DecisionLog,
+        di::config::MasterConfig,
+        failure_detector::FailureDetector,
+        live_config::LiveConfig,
+        metrics::{MasterMetrics, TopologyGauges},
+        monitor::MasterMonitor,
+        peers::PeerCoordinator,
+        topology_feed::TopologyFeed,
+        write_retries::WriteRetries,
     },
 };
 
 pub struct CacheMasterModule {
+    /// El mismo reloj con el que se calculan los `expires_at` de los `PUT`.
+    pub clock: Arc<dyn Clock>,
+    pub event_bus: Arc<DomainEventBus>,
+    pub metrics: Arc<MasterMetrics>,
+    /// Lo que `CONFIG SET` puede cambiar en caliente.
+    pub live_config: Arc<LiveConfig>,
+    /// Las clases de timeout de los sockets de los nodos (ver `MasterConfig::action_timeouts`).
+    pub action_timeouts: Arc<ActionTimeouts>,
+    /// Ver `MasterConfig::compress_min_bytes`.
+    pub compress_min_bytes: Option<usize>,
+    pub monitor: Arc<MasterMonitor>,
+    /// Suscripciones de clientes a `SUBSCRIBE "TOPOLOGY"`.
+    pub topology_feed: Arc<TopologyFeed>,
+    /// Sospecha sobre los nodos a partir de sus `EVT HEARTBEAT`.
+    pub failure_detector: Arc<FailureDetector>,
+    /// Diferencia de reloj de cada nodo, con el eco de los mismos latidos.
+    pub clock_skews: Arc<ClockSkews>,
+    /// Los `PUT` que algún nodo de su shard no confirmó (ver `write_retries`).
+    pub write_retries: Arc<WriteRetries>,
+    /// Acuerdo de las altas con los otros masters (ver `peers`).
+    pub peers: Arc<PeerCoordinator>,
+    pub consistent_hasher_service: Arc<DashmapConsistentHasherService>,
     pub tcp_network_service: Arc<TcpNetworkService>,
+    pub stats_aggregation_service: Arc<StatsAggregationService>,
+    /// `None` sin destino de backups (ver `MasterConfig::backup_target`).
+    pub backup_service: Option<Arc<BackupService>>,
+    /// `None` sin `MasterConfig::import_dir`.
+    pub import_service: Option<Arc<ImportService>>,
+    /// Turnos para las altas de nodos (ver `MasterConfig::registration`).
+    pub registrations: Arc<RegistrationQueue>,
     pub assign_node_use_case: Arc<AssignNodeUseCase>,
     pub delete_node_use_case: Arc<RemoveNodeUseCase>,
-    pub get_key_use_case: Arc<GetKeyUseCase>,
+    /// Sin `MasterConfig::get_memo` no memoiza nada.
+    pub get_key_use_case: Arc<MemoizedGetKeyUseCase>,
     pub put_key_use_case: Arc<PutKeyUseCase>,
+    pub multi_use_case: Arc<MultiUseCase>,
+    pub rename_use_case: Arc<RenameUseCase>,
+    pub router: Arc<ActionRouter>,
 }
 
 impl CacheMasterModule {
     pub fn build_from_state(app_state: Arc<AppState>) -> Self {
-        let consistent_hasher_service = Arc::new(DashmapConsistentHasherService::new());
-        let tcp_network_service = Arc::new(TcpNetworkService::from_state(
-            app_state.network_state.clone(),
-        ));
-        let clock = Arc::new(AppClock::new());
+        Self::build_with_clock(app_state, Arc::new(AppClock::new()))
+    }
 
-        let assign_node_use_case = Arc::new(AssignNodeUseCase::new(
+    /// Igual que `build_from_state` con un reloj inyectado (simulaciones).
+    pub fn build_with_clock(app_state: Arc<AppState>, clock: Arc<dyn Clock>) -> Self {
+        Self::build_with(app_state, clock, &MasterConfig::default())
+    }
+
+    /// Arma el módulo completo; `config` define el token de admin, los límites por
+    /// clase de las acciones de clientes (ver `actions::register_actions`), el jitter de
+    /// los TTL, los umbrales de memoria para aceptar `PUT`, cuántos nodos de cada shard
+    /// contestan lecturas y escrituras, el solo lectura con el que arranca, dónde se
+    /// guardan los backups, de dónde lee `IMPORT`, cuántos nodos se registran a la vez y
+    /// en cuántos shards se guarda cada clave.
+    pub fn build_with(
+        app_state: Arc<AppState>,
+        clock: Arc<dyn Clock>,
+        config: &MasterConfig,
+    ) -> Self {
+        let decision_log = config.decision_log.as_ref().and_then(|path| {
+            DecisionLog::open(path, clock.clone())
+                .inspect_err(|e| warn!(path = %path.display(), "sin decision log: {e}"))
+                .ok()
+                .map(Arc::new)
+        });
+        let mut consistent_hasher_service = DashmapConsistentHasherService::new();
+        let mut topology_feed = TopologyFeed::default();
+        if let Some(log) = decision_log {
+            consistent_hasher_service = consistent_hasher_service.with_decision_log(log.clone());
+            topology_feed = topology_feed.with_decision_log(log);
+        }
+        let consistent_hasher_service = Arc::new(consistent_hasher_service);
+        let topology_feed = Arc::new(topology_feed);
+        let metrics = MasterMetrics::new_shared();
+        let live_config = Arc::new(LiveConfig::new(config));
+        let write_retries = Arc::new(WriteRetries::new(config.write_retry, clock.clone()));
+        let tcp_network_service = Arc::new(
+            TcpNetworkService::from_state(app_state.network_state.clone(), metrics.clone())
+                .with_memory_watermarks(config.memory_watermarks)
+                .with_relative_ttl(config.relative_ttl.then(|| clock.clone()))
+                .with_fanout_policies(live_config.read_policy(), live_config.write_policy())
+                .with_write_retries(write_retries.clone())
+                .with_write_serialization(config.serialize_writes)
+                .with_read_only(ReadOnlySwitches::new(
+                    config.read_only,
+                    config.read_only_nodes.iter().cloned(),
+                ))
+                .with_node_access(
+                    NodeAccess::new(config.node_allow.clone(), config.node_deny.clone())
+                        .with_certs_required(config.node_certs),
+                ),
+        );
+        let event_bus = EventBus::new_shared(1024);
+        let monitor = MasterMonitor::new_shared();
+        let failure_detector =
+            Arc::new(FailureDetector::new(config.failure_detector, clock.clone()));
+        let clock_skews = Arc::new(ClockSkews::new());
+        let peers = Arc::new(PeerCoordinator::new(
+            config.peers.clone(),
+            config.router.admin_token.clone(),
             consistent_hasher_service.clone(),
-            tcp_network_service.clone(),
         ));
 
+        let assign_node_use_case = Arc::new(
+            AssignNodeUseCase::new(
+                consistent_hasher_service.clone(),
+                tcp_network_service.clone(),
+                event_bus.clone(),
+            )
+            .with_coordinator(peers.clone()),
+        );
+
         let delete_node_use_case = Arc::new(crate::core::usecases::RemoveNodeUseCase::new(
             consistent_hasher_service.clone(),
             tcp_network_service.clone(),
+            event_bus.clone(),
         ));
 
-        let get_key_use_case = Arc::new(GetKeyUseCase::new(
-            consistent_hasher_service.clone(),
-            tcp_network_service.clone(),
-        ));
+        let get_key_use_case = Arc::new(
+            GetKeyUseCase::new(
+                consistent_hasher_service.clone(),
+                tcp_network_service.clone(),
+            )
+            .with_replication_factor(config.replication_factor)
+            .with_chunks(config.chunk_size.is_some())
+            .memoize(config.get_memo)
+            .with_clock(clock.clone()),
+        );
 
-        let put_key_use_case = Arc::new(PutKeyUseCase::new(
-            consistent_hasher_service,
+        let put_key_use_case = Arc::new(
+            PutKeyUseCase::new(
+                consistent_hasher_service.clone(),
+                tcp_network_service.clone(),
+                clock.clone(),
+            )
+            .with_ttl_jitter(config.ttl_jitter)
+            .with_replication_factor(config.replication_factor)
+            .with_chunk_size(config.chunk_size),
+        );
+
+        let multi_use_case = Arc::new(
+            MultiUseCase::new(
+                consistent_hasher_service.clone(),
+                tcp_network_service.clone(),
+                clock.clone(),
+            )
+            .with_ttl_jitter(config.ttl_jitter)
+            .with_replication_factor(config.replication_factor)
+            .with_chunks(config.chunk_size.is_some()),
+        );
+
+        let rename_use_case = Arc::new(
+            RenameUseCase::new(
+                consistent_hasher_service.clone(),
+                tcp_network_service.clone(),
+                event_bus.clone(),
+            )
+            .with_replication_factor(config.replication_factor)
+            .with_chunks(config.chunk_size.is_some()),
+        );
+
+        let stats_aggregation_service = Arc::new(StatsAggregationService::new(
             tcp_network_service.clone(),
+            metrics.clone(),
             clock.clone(),
         ));
 
+        let backup_service = config.backup_target.clone().map(|target| {
+            Arc::new(
+                BackupService::new(
+                    tcp_network_service.clone(),
+                    consistent_hasher_service.clone(),
+                    target.into_store(clock.clone()),
+                    clock.clone(),
+                )
+                .with_replication_factor(config.replication_factor),
+            )
+        });
+
+        let import_service = config.import_dir.clone().map(|dir| {
+            Arc::new(
+                ImportService::new(
+                    tcp_network_service.clone(),
+                    consistent_hasher_service.clone(),
+                    dir,
+                    clock.clone(),
+                )
+                .with_replication_factor(config.replication_factor),
+            )
+        });
+
+        let mut router = ActionRouter::new(&config.router, metrics.clone())
+            .with_rate_limits(live_config.rate_limits());
+        register_actions(
+            &mut router,
+            ActionDeps {
+                metrics: metrics.clone(),
+                live_config: live_config.clone(),
+                monitor: monitor.clone(),
+                topology_feed: topology_feed.clone(),
+                peers: peers.clone(),
+                hasher: consistent_hasher_service.clone(),
+                network: tcp_network_service.clone(),
+                stats_aggregation: stats_aggregation_service.clone(),
+                clock_skews: clock_skews.clone(),
+                write_retries: write_retries.clone(),
+                replication_factor: config.replication_factor,
+                backups: backup_service.clone(),
+                imports: import_service.clone(),
+                get_key_use_case: get_key_use_case.clone(),
+                put_key_use_case: put_key_use_case.clone(),
+                multi_use_case: multi_use_case.clone(),
+                rename_use_case: rename_use_case.clone(),
+                admin_token: config.router.admin_token.clone(),
+            },
+        );
+
         Self {
+            clock,
+            event_bus,
+            metrics,
+            live_config,
+            action_timeouts: Arc::new(config.action_timeouts.clone()),
+            compress_min_bytes: config.compress_min_bytes,
+            monitor,
+            topology_feed,
+            failure_detector,
+            clock_skews,
+            write_retries,
+            peers,
+            consistent_hasher_service,
+            registrations: Arc::new(RegistrationQueue::new(config.registration)),
             assign_node_use_case,
             tcp_network_service,
+            stats_aggregation_service,
+            backup_service,
+            import_service,
             delete_node_use_case,
             get_key_use_case,
             put_key_use_case,
+            multi_use_case,
+            rename_use_case,
+            router: Arc::new(router),
         }
     }
+
+    pub fn topology_gauges(&self) -> TopologyGauges {
+        TopologyGauges {
+            registered_nodes: self.tcp_network_service.registered_nodes(),
+            shards: self.tcp_network_service.shard_count(),
+            ring_nodes: self.consistent_hasher_service.node_count(),
+            ring_vnodes: self.consistent_hasher_service.ring_size(),
+            peer_masters: self.peers.masters() - 1,
+            reachable_peer_masters: self.peers.reachable_peers(),
+            raft_term: self.peers.term() as usize,
+            raft_leader: usize::from(self.peers.is_leader()),
+            raft_commit_index: self.peers.commit_index() as usize,
+        }
+    }
+
+    /// Texto Prometheus con las métricas del master y la topología actual.
+    pub fn render_metrics(&self) -> String {
+        self.metrics.render(&self.topology_gauges())
+    }
 }
@@ -0,0 +1,282 @@
+//! Configuración del master: la de `RouterConfig` más la de los servicios que arma `di`.
+
+use std::{env, path::PathBuf, sync::Arc, time::Duration};
+
+use app_net::{ActionTimeouts, TimeoutClass};
+use tracing::warn;
+
+use crate::{
+    core::domain::models::{AppError, MemoryWatermarks, TtlJitter},
+    infrastructure::{
+        adapters::{
+            controllers::router::RouterConfig,
+            services::{BackupTarget, FanoutPolicy, HedgeDelay, NodeRule, RegistrationLimits},
+        },
+        failure_detector::FailureDetectorConfig,
+        live_config::DEFAULT_NODE_TIMEOUT,
+        peers::PeerConfig,
+        write_retries::WriteRetryConfig,
+    },
+};
+
+/// Todo lo que se configura del master, para armar su módulo (ver
+/// `CacheMasterModule::build_with`) y sus tareas (ver `server::start_with_config`).
+#[derive(Debug, Clone)]
+pub struct MasterConfig {
+    /// Autenticación, cupos y tamaño de los requests, lo que aplica el router.
+    pub router: RouterConfig,
+    /// Jitter de los TTL de `PUT` y `MULTI`.
+    pub ttl_jitter: TtlJitter,
+    /// Los `PUT` viajan a los nodos con lo que le falta a la clave en vez del `expires_at`,
+    /// así el vencimiento no depende de que sus relojes estén en hora con el del master.
+    pub relative_ttl: bool,
+    /// Cuándo deja un shard de recibir `PUT` por la memoria de sus nodos.
+    pub memory_watermarks: MemoryWatermarks,
+    /// Cuántos nodos del shard contestan un `GET` y confirman un `PUT`. Se pueden cambiar
+    /// con `CONFIG SET`, igual que `node_timeout`.
+    pub read_policy: FanoutPolicy,
+    pub write_policy: FanoutPolicy,
+    /// Con un tiempo, los `PUT` de una misma clave salen hacia su shard de a uno y cada uno
+    /// retiene la clave hasta que contestan todos los nodos, o como mucho ese tiempo: las
+    /// réplicas los aplican en el mismo orden. `None` los deja salir a la vez.
+    pub serialize_writes: Option<Duration>,
+    /// Arranque en solo lectura, del cluster y de nodos sueltos (ver `READ-ONLY`).
+    pub read_only: bool,
+    pub read_only_nodes: Vec<Arc<str>>,
+    /// Dónde guardan `BACKUP` y el backup programado; sin destino no hay backups.
+    pub backup_target: Option<BackupTarget>,
+    /// De dónde lee `IMPORT` sus archivos; sin directorio no hay imports.
+    pub import_dir: Option<PathBuf>,
+    /// Altas de nodos a la vez (ver `RegistrationQueue`).
+    pub registration: RegistrationLimits,
+    /// Qué nodos pueden registrarse (ver `NodeAccess`); sin reglas, cualquiera.
+    pub node_allow: Vec<NodeRule>,
+    pub node_deny: Vec<NodeRule>,
+    /// Los nodos solo se registran con un certificado de cliente, o sea por el puerto de
+    /// cluster (ver `server::start_cluster`).
+    pub node_certs: bool,
+    /// En cuántos shards distintos del anillo se guarda cada clave: el dueño y los que le
+    /// siguen. 1 es solo el dueño.
+    pub replication_factor: usize,
+    /// Cuánto reusa el master la respuesta de un `GET` simple para la misma clave sin
+    /// preguntarle al nodo (ver `MemoizedGetKeyUseCase`); cero no la reusa.
+    pub get_memo: Duration,
+    /// Cuánto espera el master la respuesta de un nodo.
+    pub node_timeout: Duration,
+    /// Lo que esperan las acciones lentas (`STATS`, `SNAPSHOT`..) en vez de `node_timeout`.
+    pub action_timeouts: ActionTimeouts,
+    /// Los valores de `PUT` más largos que esto se guardan partidos en pedazos de este
+    /// tamaño, repartidos por el anillo (ver `ChunkManifest`); sirve para aceptar con
+    /// `RouterConfig::max_payload` valores más grandes que el tope de los nodos. `None` no
+    /// parte nada.
+    pub chunk_size: Option<usize>,
+    /// Las respuestas a los clientes de al menos estos bytes de payload van comprimidas
+    /// a los que avisaron que las entienden (ver `app_net::compression`). `None` no comprime.
+    pub compress_min_bytes: Option<usize>,
+    /// Archivo donde se anotan los cambios del anillo y las rutas de cada clave (ver
+    /// `DecisionLog`); sin archivo no se anota nada.
+    pub decision_log: Option<PathBuf>,
+    /// Cuándo se corta a un nodo cuyos latidos dejaron de llegar.
+    pub failure_detector: FailureDetectorConfig,
+    /// Cuánto se reintentan los `PUT` que un nodo del shard no confirmó.
+    pub write_retry: WriteRetryConfig,
+    /// Los otros masters con los que se acuerdan las altas (ver `peers`).
+    pub peers: PeerConfig,
+}
+
+/// Las lecturas van al primario y se cubren con una réplica pasado su p95.
+pub const DEFAULT_READ_POLICY: FanoutPolicy = FanoutPolicy::Hedged(HedgeDelay::P95);
+
+impl Default for MasterConfig {
+    fn default() -> Self {
+        Self {
+            router: RouterConfig::default(),
+            ttl_jitter: TtlJitter::default(),
+            relative_ttl: false,
+            memory_watermarks: MemoryWatermarks::default(),
+            read_policy: DEFAULT_READ_POLICY,
+            write_policy: FanoutPolicy::default(),
+            serialize_writes: None,
+            read_only: false,
+            read_only_nodes: Vec::new(),
+            backup_target: None,
+            import_dir: None,
+            registration: RegistrationLimits::default(),
+            node_allow: Vec::new(),
+            node_deny: Vec::new(),
+            node_certs: false,
+            replication_factor: 1,
+            get_memo: Duration::ZERO,
+            node_timeout: DEFAULT_NODE_TIMEOUT,
+            action_timeouts: ActionTimeouts::default(),
+            chunk_size: None,
+            compress_min_bytes: None,
+            decision_log: None,
+            failure_detector: FailureDetectorConfig::default(),
+            write_retry: WriteRetryConfig::default(),
+            peers: PeerConfig::default(),
+        }
+    }
+}
+
+impl MasterConfig {
+    /// Lo del router (ver `RouterConfig::from_env`), `TTL_JITTER_PCT` (0 a 100),
+    /// `RELATIVE_TTL=true`, `MEMORY_HIGH_WATERMARK_PCT`/`MEMORY_LOW_WATERMARK_PCT` (90 y 80
+    /// por defecto), `READ_POLICY`/`WRITE_POLICY` (`hedged:p95` y `first` por defecto, ver
+    /// `FanoutPolicy`), `SERIALIZE_WRITES_MS` (0 por defecto, sin serializar),
+    /// `READ_ONLY=true`, `READ_ONLY_NODES` (ids separados por coma), el destino de los
+    /// backups (ver `BackupTarget::from_env`), `IMPORT_DIR`,
+    /// `REGISTRATION_CONCURRENCY`/`REGISTRATION_JITTER_MS` (4 y 250 por defecto) y
+    /// `NODE_ALLOW`/`NODE_DENY` (reglas de `NodeRule` separadas por coma),
+    /// `REPLICATION_FACTOR` (1 por defecto), `GET_MEMO_MS` (0 por defecto),
+    /// `NODE_TIMEOUT_MS` (2000 por defecto), las clases de timeout (ver
+    /// `action_timeouts_from_env`), `CHUNK_SIZE_BYTES` (0 por defecto, sin partir),
+    /// `COMPRESS_MIN_BYTES` (0 por defecto, sin comprimir), los reintentos de escrituras
+    /// (ver `WriteRetryConfig::from_env`); con `CLUSTER_PORT` o `CLUSTER_QUIC_PORT` los
+    /// nodos tienen que presentar certificado. Falla con `PEER_MASTERS` sin `RAFT_DIR` (ver
+    /// `PeerConfig::from_env`).
+    pub fn from_env() -> Result<Self, AppError> {
+        let policy = |var: &str, default: FanoutPolicy| {
+            env::var(var)
+                .ok()
+                .and_then(|v| v.parse::<FanoutPolicy>().ok())
+                .unwrap_or(default)
+        };
+
+        Ok(Self {
+            router: RouterConfig::from_env(),
+            ttl_jitter: env::var("TTL_JITTER_PCT")
+                .ok()
+                .and_then(|v| v.parse::<u8>().ok())
+                .map(TtlJitter::new)
+                .unwrap_or_default(),
+            relative_ttl: env::var("RELATIVE_TTL").is_ok_and(|v| v.trim() == "true"),
+            memory_watermarks: memory_watermarks_from_env(),
+            read_policy: policy("READ_POLICY", DEFAULT_READ_POLICY),
+            write_policy: policy("WRITE_POLICY", FanoutPolicy::default()),
+            serialize_writes: env::var("SERIALIZE_WRITES_MS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .filter(|ms| *ms > 0)
+                .map(Duration::from_millis),
+            read_only: env::var("READ_ONLY").is_ok_and(|v| v.trim() == "true"),
+            read_only_nodes: env::var("READ_ONLY_NODES")
+                .map(|v| {
+                    v.split(',')
+                        .map(str::trim)
+                        .filter(|id| !id.is_empty())
+                        .map(Arc::from)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            backup_target: BackupTarget::from_env(),
+            import_dir: env::var("IMPORT_DIR")
+                .ok()
+                .filter(|dir| !dir.trim().is_empty())
+                .map(PathBuf::from),
+            registration: registration_limits_from_env(),
+            node_allow: node_rules_from_env("NODE_ALLOW"),
+            node_deny: node_rules_from_env("NODE_DENY"),
+            node_certs: ["CLUSTER_PORT", "CLUSTER_QUIC_PORT"]
+                .iter()
+                .any(|var| env::var(var).is_ok_and(|p| p.parse::<u16>().is_ok())),
+            replication_factor: env::var("REPLICATION_FACTOR")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(1)
+                .max(1),
+            get_memo: env::var("GET_MEMO_MS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .map(Duration::from_millis)
+                .unwrap_or_default(),
+            node_timeout: env::var("NODE_TIMEOUT_MS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .filter(|ms| *ms > 0)
+                .map(Duration::from_millis)
+                .unwrap_or(DEFAULT_NODE_TIMEOUT),
+            action_timeouts: action_timeouts_from_env(),
+            chunk_size: env::var("CHUNK_SIZE_BYTES")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .filter(|bytes| *bytes > 0),
+            compress_min_bytes: env::var("COMPRESS_MIN_BYTES")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .filter(|bytes| *bytes > 0),
+            decision_log: env::var("DECISION_LOG")
+                .ok()
+                .filter(|path| !path.trim().is_empty())
+                .map(PathBuf::from),
+            failure_detector: FailureDetectorConfig::from_env(),
+            write_retry: WriteRetryConfig::from_env(),
+            peers: PeerConfig::from_env()?,
+        })
+    }
+}
+
+/// Una regla que no se entiende se descarta con un aviso.
+fn node_rules_from_env(var: &str) -> Vec<NodeRule> {
+    env::var(var)
+        .unwrap_or_default()
+        .split(',')
+        .filter(|rule| !rule.trim().is_empty())
+        .filter_map(|rule| {
+            rule.parse::<NodeRule>()
+                .inspect_err(|e| warn!("{var}: {e}; se ignora"))
+                .ok()
+        })
+        .collect()
+}
+
+/// `ADMIN_TIMEOUT_MS` y `BULK_TIMEOUT_MS` (10000 y 60000 por defecto) y
+/// `ACTION_TIMEOUT_CLASSES` (`<ACCIÓN>=data|admin|bulk,..`) para cambiar la clase de una
+/// acción. Una lista que no se entiende se ignora entera con un aviso.
+fn action_timeouts_from_env() -> ActionTimeouts {
+    let mut timeouts = ActionTimeouts::default();
+    for (class, var) in [
+        (TimeoutClass::Admin, "ADMIN_TIMEOUT_MS"),
+        (TimeoutClass::Bulk, "BULK_TIMEOUT_MS"),
+    ] {
+        if let Some(ms) = env::var(var)
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|ms| *ms > 0)
+        {
+            timeouts = timeouts.with_class(class, Duration::from_millis(ms));
+        }
+    }
+    let classes = env::var("ACTION_TIMEOUT_CLASSES").unwrap_or_default();
+    match timeouts.clone().with_actions(&classes) {
+        Ok(with_actions) => with_actions,
+        Err(e) => {
+            warn!("ACTION_TIMEOUT_CLASSES: {e}; se ignora");
+            timeouts
+        }
+    }
+}
+
+fn registration_limits_from_env() -> RegistrationLimits {
+    let defaults = RegistrationLimits::default();
+    RegistrationLimits {
+        concurrency: env::var("REGISTRATION_CONCURRENCY")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(defaults.concurrency),
+        jitter: env::var("REGISTRATION_JITTER_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .map(Duration::from_millis)
+            .unwrap_or(defaults.jitter),
+    }
+}
+
+fn memory_watermarks_from_env() -> MemoryWatermarks {
+    let pct = |var: &str| env::var(var).ok().and_then(|v| v.parse::<u8>().ok());
+    let defaults = MemoryWatermarks::default();
+    let high = pct("MEMORY_HIGH_WATERMARK_PCT").unwrap_or((defaults.high * 100.0) as u8);
+    let low = pct("MEMORY_LOW_WATERMARK_PCT").unwrap_or((defaults.low * 100.0) as u8);
+    MemoryWatermarks::from_percent(high, low)
+}
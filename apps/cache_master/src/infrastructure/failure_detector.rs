@@ -0,0 +1,234 @@
+//! Detector de fallas phi-accrual (Hayashibara et al.) a partir de los `EVT HEARTBEAT` de
+//! cada nodo. En vez de dar por muerto a un nodo que no se oye hace X tiempo, estima la
+//! distribución de los intervalos entre sus latidos y da un nivel de sospecha `phi`: con
+//! `phi = 8` la chance de que el nodo siga vivo y el latido solo venga demorado es de
+//! 10^-8. Un nodo que late irregular tiene más margen que uno que late como un reloj, así
+//! que una pausa de GC o un hipo de red de un par de intervalos no alcanza para sacarlo.
+
+use std::{collections::VecDeque, env, sync::Arc, time::Duration};
+
+use app_core::clock::Clock;
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use tokio::time::{self, MissedTickBehavior};
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+use crate::infrastructure::di::CacheMasterModule;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FailureDetectorConfig {
+    /// `phi` a partir del cual se corta al nodo; 0 no corta a nadie.
+    pub threshold: f64,
+    /// Intervalos que se recuerdan por nodo.
+    pub max_samples: usize,
+    /// Piso de la desviación estándar, para que un nodo muy regular no quede a un
+    /// milisegundo de la sospecha.
+    pub min_std_deviation: Duration,
+    /// Demora que se le tolera a cada latido además de lo esperable.
+    pub acceptable_pause: Duration,
+    /// Intervalo que se supone hasta tener el primer par de latidos.
+    pub first_heartbeat_estimate: Duration,
+    /// Cada cuánto se revisa a los nodos.
+    pub check_interval: Duration,
+}
+
+impl Default for FailureDetectorConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 8.0,
+            max_samples: 200,
+            min_std_deviation: Duration::from_millis(100),
+            acceptable_pause: Duration::from_secs(1),
+            first_heartbeat_estimate: Duration::from_secs(1),
+            check_interval: Duration::from_millis(500),
+        }
+    }
+}
+
+impl FailureDetectorConfig {
+    /// `PHI_THRESHOLD` (8 por defecto, 0 desactiva) y `HEARTBEAT_ACCEPTABLE_PAUSE_MS`.
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Some(threshold) = env::var("PHI_THRESHOLD")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|t| *t >= 0.0)
+        {
+            config.threshold = threshold;
+        }
+        if let Some(ms) = env::var("HEARTBEAT_ACCEPTABLE_PAUSE_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+        {
+            config.acceptable_pause = Duration::from_millis(ms);
+        }
+        config
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.threshold > 0.0
+    }
+}
+
+/// Intervalos entre los últimos latidos de un nodo, con sus sumas para sacar media y
+/// varianza sin recorrerlos.
+struct HeartbeatHistory {
+    last_ms: u64,
+    intervals: VecDeque<u64>,
+    sum: u64,
+    sum_sq: u64,
+}
+
+impl HeartbeatHistory {
+    fn push(&mut self, interval: u64, max_samples: usize) {
+        if self.intervals.len() >= max_samples
+            && let Some(oldest) = self.intervals.pop_front()
+        {
+            self.sum -= oldest;
+            self.sum_sq -= oldest * oldest;
+        }
+        self.intervals.push_back(interval);
+        self.sum += interval;
+        self.sum_sq += interval * interval;
+    }
+
+    fn mean(&self) -> f64 {
+        self.sum as f64 / self.intervals.len() as f64
+    }
+
+    fn std_deviation(&self) -> f64 {
+        let mean = self.mean();
+        (self.sum_sq as f64 / self.intervals.len() as f64 - mean * mean)
+            .max(0.0)
+            .sqrt()
+    }
+}
+
+/// Sospecha sobre cada nodo que manda latidos. Los que nunca mandaron uno no se siguen:
+/// para ellos la única señal de falla es que se corte la conexión.
+pub struct FailureDetector {
+    config: FailureDetectorConfig,
+    clock: Arc<dyn Clock>,
+    nodes: DashMap<Arc<str>, Mutex<HeartbeatHistory>>,
+}
+
+impl FailureDetector {
+    pub fn new(config: FailureDetectorConfig, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            config,
+            clock,
+            nodes: DashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> &FailureDetectorConfig {
+        &self.config
+    }
+
+    pub fn heartbeat(&self, node_id: &Arc<str>) {
+        let now = self.now();
+        if let Some(history) = self.nodes.get(node_id) {
+            let mut history = history.lock();
+            let interval = now.saturating_sub(history.last_ms);
+            history.last_ms = now;
+            history.push(interval, self.config.max_samples);
+            return;
+        }
+
+        // hasta tener intervalos reales, dos alrededor del estimado
+        let estimate = self.config.first_heartbeat_estimate.as_millis() as u64;
+        let mut history = HeartbeatHistory {
+            last_ms: now,
+            intervals: VecDeque::new(),
+            sum: 0,
+            sum_sq: 0,
+        };
+        history.push(estimate - estimate / 4, self.config.max_samples);
+        history.push(estimate + estimate / 4, self.config.max_samples);
+        self.nodes.insert(node_id.clone(), Mutex::new(history));
+    }
+
+    /// Nivel de sospecha de `node_id` ahora; `None` si nunca mandó un latido.
+    pub fn phi(&self, node_id: &str) -> Option<f64> {
+        let now = self.now();
+        let history = self.nodes.get(node_id)?;
+        let history = history.lock();
+        let elapsed = now.saturating_sub(history.last_ms) as f64;
+
+        let mean = history.mean() + self.config.acceptable_pause.as_millis() as f64;
+        let std = history
+            .std_deviation()
+            .max(self.config.min_std_deviation.as_millis() as f64);
+        Some(phi(elapsed, mean, std))
+    }
+
+    /// Cada nodo seguido con su `phi`, ordenados por id.
+    pub fn levels(&self) -> Vec<(Arc<str>, f64)> {
+        let ids: Vec<Arc<str>> = self.nodes.iter().map(|e| e.key().clone()).collect();
+        let mut levels: Vec<_> = ids
+            .into_iter()
+            .filter_map(|id| self.phi(&id).map(|phi| (id, phi)))
+            .collect();
+        levels.sort_by(|a, b| a.0.cmp(&b.0));
+        levels
+    }
+
+    /// Los que pasaron el umbral.
+    pub fn suspects(&self) -> Vec<(Arc<str>, f64)> {
+        if !self.config.enabled() {
+            return Vec::new();
+        }
+        self.levels()
+            .into_iter()
+            .filter(|(_, phi)| *phi >= self.config.threshold)
+            .collect()
+    }
+
+    pub fn forget(&self, node_id: &str) {
+        self.nodes.remove(node_id);
+    }
+
+    fn now(&self) -> u64 {
+        self.clock.now_millis().as_millis_u64()
+    }
+}
+
+/// `-log10` de la probabilidad de que un latido llegue más de `elapsed` después del
+/// anterior, con los intervalos ~ N(`mean`, `std`). Usa la aproximación logística de la
+/// normal acumulada, igual que Akka y Cassandra.
+fn phi(elapsed: f64, mean: f64, std: f64) -> f64 {
+    let y = (elapsed - mean) / std;
+    let e = (-y * (1.5976 + 0.070566 * y * y)).exp();
+    if elapsed > mean {
+        -(e / (1.0 + e)).log10()
+    } else {
+        -(1.0 - 1.0 / (1.0 + e)).log10()
+    }
+}
+
+/// Cada `check_interval` corta la conexión de los nodos sospechados, hasta que se cancele
+/// `token`. El corte sigue el camino de cualquier desconexión (ver `server::forget_node`).
+pub async fn watch_heartbeats(module: Arc<CacheMasterModule>, token: CancellationToken) {
+    let detector = module.failure_detector.clone();
+    let mut interval = time::interval(detector.config().check_interval);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            _ = token.cancelled() => break,
+            _ = interval.tick() => {}
+        }
+
+        for (node_id, phi) in detector.levels() {
+            module.metrics.observe_suspicion(&node_id, phi);
+        }
+        for (node_id, phi) in detector.suspects() {
+            // sin volver a sospechar de él mientras se desconecta
+            detector.forget(&node_id);
+            if module.tcp_network_service.disconnect(&node_id) {
+                warn!(%node_id, phi = format!("{phi:.1}"), "nodo sospechado de caído: se corta");
+            }
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use std::{env, sync::Arc, time::Duration};
+
+use tokio::time::{self, MissedTickBehavior};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info};
+
+use crate::{
+    core::domain::services::ConsistentHasherService, infrastructure::di::CacheMasterModule,
+};
+
+/// Cuándo una clave se considera caliente y cuántas copias se le hacen (ver
+/// `TcpNetworkService::replicate_hot_key`).
+#[derive(Debug, Clone)]
+pub struct HotKeyConfig {
+    /// Shards, además del dueño, que reciben copia de cada clave caliente.
+    pub extra_shards: usize,
+    /// Lecturas en el último minuto a partir de las cuales una clave puede ser caliente.
+    pub min_reads: u64,
+    /// Veces el promedio de lecturas de las demás claves.
+    pub factor: f64,
+    /// Cada cuánto se buscan claves calientes.
+    pub interval: Duration,
+    /// Vida máxima de cada copia; mientras la clave siga caliente se vuelve a copiar.
+    pub copy_ttl: Duration,
+    /// Claves con copias a la vez.
+    pub max_keys: usize,
+}
+
+impl Default for HotKeyConfig {
+    fn default() -> Self {
+        Self {
+            extra_shards: 2,
+            min_reads: 1_000,
+            factor: 10.0,
+            interval: Duration::from_secs(1),
+            copy_ttl: Duration::from_secs(10),
+            max_keys: 16,
+        }
+    }
+}
+
+impl HotKeyConfig {
+    /// `HOT_KEY_REPLICAS` (shards extra; sin él o en 0 no se copia nada),
+    /// `HOT_KEY_MIN_READS` y `HOT_KEY_FACTOR`.
+    pub fn from_env() -> Option<Self> {
+        let extra_shards = env::var("HOT_KEY_REPLICAS")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|n| *n > 0)?;
+
+        let mut config = Self {
+            extra_shards,
+            ..Self::default()
+        };
+        if let Some(min_reads) = env::var("HOT_KEY_MIN_READS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+        {
+            config.min_reads = min_reads;
+        }
+        if let Some(factor) = env::var("HOT_KEY_FACTOR")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|f| *f >= 1.0)
+        {
+            config.factor = factor;
+        }
+        Some(config)
+    }
+}
+
+/// Cada `config.interval` copia a otros shards las claves leídas muy por encima del
+/// promedio, hasta que se cancele `token`.
+pub async fn replicate_hot_keys(
+    module: Arc<CacheMasterModule>,
+    config: HotKeyConfig,
+    token: CancellationToken,
+) {
+    let mut interval = time::interval(config.interval);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let copy_ttl_ms = u64::try_from(config.copy_ttl.as_millis()).unwrap_or(u64::MAX);
+
+    loop {
+        tokio::select! {
+            _ = token.cancelled() => break,
+            _ = interval.tick() => {}
+        }
+
+        let network = &module.tcp_network_service;
+        let hot = module.metrics.hot_reads(config.factor, config.min_reads);
+        for key in hot {
+            if network.hot_key_copies().len() >= config.max_keys {
+                break;
+            }
+            let hasher = &module.consistent_hasher_service;
+            let Some(owner) = hasher.get_node_id_from_hash(&hasher.create_hash(&key)) else {
+                continue;
+            };
+
+            let now_ms = module.clock.now_millis().as_millis_u64();
+            match network
+                .replicate_hot_key(&owner, &key, config.extra_shards, now_ms, copy_ttl_ms)
+                .await
+            {
+                Ok(true) => info!(target: "topology", %key, %owner, "clave caliente copiada"),
+                Ok(false) => {}
+                Err(e) => debug!(target: "topology", %key, "no se pudo copiar: {e}"),
+            }
+        }
+    }
+}
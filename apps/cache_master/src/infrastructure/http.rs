@@ -0,0 +1,78 @@
+//! Listener HTTP de administración del master (opcional, ver `server::start_http`).
+
+use std::sync::Arc;
+
+use app_core::{error::HasErrorKind, metrics::PrometheusEncoder};
+use axum::{
+    Json, Router,
+    extract::State,
+    http::{HeaderMap, StatusCode, header},
+    response::{Html, IntoResponse, Response},
+    routing::get,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::infrastructure::{dashboard::Dashboard, di::CacheMasterModule};
+
+pub fn router(module: Arc<CacheMasterModule>) -> Router {
+    Router::new()
+        .route("/metrics", get(metrics))
+        .with_state(module)
+}
+
+/// Endpoints de operador; van en su propio listener, solo en loopback (ver
+/// `server::start_admin_http`). El dashboard muestra hot keys y la topología interna.
+pub fn admin_router(module: Arc<CacheMasterModule>) -> Router {
+    Router::new()
+        .route("/dashboard", get(dashboard))
+        .route("/log-filter", get(get_log_filter).put(put_log_filter))
+        .with_state(module)
+}
+
+async fn metrics(State(module): State<Arc<CacheMasterModule>>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, PrometheusEncoder::CONTENT_TYPE)],
+        module.render_metrics(),
+    )
+}
+
+/// JSON por defecto; HTML si el cliente lo pide (un navegador).
+async fn dashboard(State(module): State<Arc<CacheMasterModule>>, headers: HeaderMap) -> Response {
+    let view = Dashboard::collect(&module);
+    let wants_html = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/html"));
+
+    if wants_html {
+        Html(view.to_html()).into_response()
+    } else {
+        Json(view).into_response()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct LogFilterBody {
+    /// Directivas con la sintaxis de `RUST_LOG`.
+    pub filter: String,
+}
+
+async fn get_log_filter() -> Response {
+    log_filter_response(app_logging::global().map(|log| log.current()))
+}
+
+async fn put_log_filter(Json(body): Json<LogFilterBody>) -> Response {
+    log_filter_response(app_logging::global().and_then(|log| log.apply(&body.filter)))
+}
+
+fn log_filter_response(res: Result<String, app_logging::LogFilterError>) -> Response {
+    match res {
+        Ok(filter) => Json(LogFilterBody { filter }).into_response(),
+        Err(e) => {
+            let status =
+                StatusCode::from_u16(e.wire_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+            (status, e.to_string()).into_response()
+        }
+    }
+}
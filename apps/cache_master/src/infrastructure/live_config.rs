@@ -0,0 +1,188 @@
+//! Ajustes del master que se pueden cambiar en caliente con `CONFIG SET`. Cada uno tiene
+//! su canal `watch`: arranca con el valor de `MasterConfig`, y quien lo usa se queda con
+//! un receptor y lo lee en cada request, así que un cambio aplica desde el request
+//! siguiente sin reiniciar ni reconectar nada.
+
+use std::{collections::HashMap, fmt, str::FromStr, time::Duration};
+
+use tokio::sync::watch;
+use tracing::info;
+
+use crate::{
+    core::domain::models::AppError,
+    infrastructure::{
+        adapters::{
+            controllers::router::RateClass,
+            services::{FanoutPolicy, HedgeDelay},
+        },
+        di::config::MasterConfig,
+    },
+};
+
+/// Cuánto espera el master la respuesta de un nodo si no se configura otra cosa.
+pub const DEFAULT_NODE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Lo que se puede leer y cambiar con `CONFIG`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigKey {
+    /// `node-timeout-ms`: cuánto se espera la respuesta de un nodo.
+    NodeTimeout,
+    /// `read-policy`: a qué nodos del shard va un `GET` (ver `FanoutPolicy`), o sea
+    /// si se lee del primario, de cualquiera o con cobertura.
+    ReadPolicy,
+    /// `write-policy`: cuántos nodos del shard confirman un `PUT`.
+    WritePolicy,
+    /// `hedge-delay`: `<ms>` o `p95`; cambiarlo deja las lecturas en `hedged:<delay>`.
+    /// Se lee `off` si `read-policy` no cubre.
+    HedgeDelay,
+    /// `rate-limit-data` / `rate-limit-admin`: requests por segundo; 0 es sin límite.
+    RateLimit(RateClass),
+}
+
+impl ConfigKey {
+    pub const ALL: [ConfigKey; 6] = [
+        Self::NodeTimeout,
+        Self::ReadPolicy,
+        Self::WritePolicy,
+        Self::HedgeDelay,
+        Self::RateLimit(RateClass::Data),
+        Self::RateLimit(RateClass::Admin),
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NodeTimeout => "node-timeout-ms",
+            Self::ReadPolicy => "read-policy",
+            Self::WritePolicy => "write-policy",
+            Self::HedgeDelay => "hedge-delay",
+            Self::RateLimit(RateClass::Data) => "rate-limit-data",
+            Self::RateLimit(RateClass::Admin) => "rate-limit-admin",
+            Self::RateLimit(RateClass::Unlimited) => "rate-limit-unlimited",
+        }
+    }
+}
+
+impl FromStr for ConfigKey {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|key| key.as_str() == s)
+            .ok_or_else(|| AppError::BadRequest(format!("ajuste desconocido: {s}")))
+    }
+}
+
+impl fmt::Display for ConfigKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+pub struct LiveConfig {
+    node_timeout: watch::Sender<Duration>,
+    read_policy: watch::Sender<FanoutPolicy>,
+    write_policy: watch::Sender<FanoutPolicy>,
+    /// Solo las clases con límite.
+    rate_limits: watch::Sender<HashMap<RateClass, u32>>,
+}
+
+impl LiveConfig {
+    pub fn new(config: &MasterConfig) -> Self {
+        Self {
+            node_timeout: watch::Sender::new(config.node_timeout),
+            read_policy: watch::Sender::new(config.read_policy),
+            write_policy: watch::Sender::new(config.write_policy),
+            rate_limits: watch::Sender::new(config.router.rate_limits.clone()),
+        }
+    }
+
+    pub fn node_timeout(&self) -> watch::Receiver<Duration> {
+        self.node_timeout.subscribe()
+    }
+
+    pub fn read_policy(&self) -> watch::Receiver<FanoutPolicy> {
+        self.read_policy.subscribe()
+    }
+
+    pub fn write_policy(&self) -> watch::Receiver<FanoutPolicy> {
+        self.write_policy.subscribe()
+    }
+
+    pub fn rate_limits(&self) -> watch::Receiver<HashMap<RateClass, u32>> {
+        self.rate_limits.subscribe()
+    }
+
+    pub fn get(&self, key: ConfigKey) -> String {
+        match key {
+            ConfigKey::NodeTimeout => self.node_timeout.borrow().as_millis().to_string(),
+            ConfigKey::ReadPolicy => self.read_policy.borrow().to_string(),
+            ConfigKey::WritePolicy => self.write_policy.borrow().to_string(),
+            ConfigKey::HedgeDelay => match *self.read_policy.borrow() {
+                FanoutPolicy::Hedged(HedgeDelay::P95) => "p95".to_string(),
+                FanoutPolicy::Hedged(HedgeDelay::Fixed(delay)) => delay.as_millis().to_string(),
+                _ => "off".to_string(),
+            },
+            ConfigKey::RateLimit(class) => self
+                .rate_limits
+                .borrow()
+                .get(&class)
+                .copied()
+                .unwrap_or(0)
+                .to_string(),
+        }
+    }
+
+    /// Todos los ajustes, en el orden de `ConfigKey::ALL`.
+    pub fn entries(&self) -> Vec<(ConfigKey, String)> {
+        ConfigKey::ALL
+            .into_iter()
+            .map(|key| (key, self.get(key)))
+            .collect()
+    }
+
+    /// Valida `value` y lo aplica; devuelve cómo quedó el ajuste.
+    pub fn set(&self, key: ConfigKey, value: &str) -> Result<String, AppError> {
+        let invalid = || AppError::BadRequest(format!("{key}: valor inválido: {value}"));
+        match key {
+            ConfigKey::NodeTimeout => {
+                let ms = value
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|ms| *ms > 0)
+                    .ok_or_else(invalid)?;
+                self.node_timeout.send_replace(Duration::from_millis(ms));
+            }
+            ConfigKey::ReadPolicy | ConfigKey::WritePolicy => {
+                let policy = value
+                    .parse::<FanoutPolicy>()
+                    .map_err(AppError::BadRequest)?;
+                let sender = match key {
+                    ConfigKey::ReadPolicy => &self.read_policy,
+                    _ => &self.write_policy,
+                };
+                sender.send_replace(policy);
+            }
+            ConfigKey::HedgeDelay => {
+                let policy = format!("hedged:{value}")
+                    .parse::<FanoutPolicy>()
+                    .map_err(|_| invalid())?;
+                self.read_policy.send_replace(policy);
+            }
+            ConfigKey::RateLimit(class) => {
+                let per_sec = value.parse::<u32>().map_err(|_| invalid())?;
+                self.rate_limits.send_modify(|limits| {
+                    if per_sec == 0 {
+                        limits.remove(&class);
+                    } else {
+                        limits.insert(class, per_sec);
+                    }
+                });
+            }
+        }
+
+        let current = self.get(key);
+        info!(%key, value = %current, "ajuste cambiado en caliente");
+        Ok(current)
+    }
+}
@@ -0,0 +1,690 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use app_core::{
+    error::ErrorKind,
+    metrics::{LatencyHistogram, MetricKind, PrometheusEncoder},
+};
+use app_net::CachePressure;
+use dashmap::DashMap;
+use parking_lot::{Mutex, RwLock};
+
+use crate::core::domain::models::{ClusterStats, ShardStats};
+
+/// Acciones con serie propia además de las que registra el router (`register_action`);
+/// cualquier otra se agrupa en `OTHER` para no abrir una serie por cada acción
+/// desconocida que mande un cliente.
+const KNOWN_ACTIONS: [&str; 3] = ["PING", "PUT", "GET"];
+
+/// Estado de la topología al momento del scrape. Se lee de los servicios, no se acumula.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TopologyGauges {
+    pub registered_nodes: usize,
+    pub shards: usize,
+    pub ring_nodes: usize,
+    pub ring_vnodes: usize,
+    /// Otros masters de `PEER_MASTERS`, y cuántos están conectados.
+    pub peer_masters: usize,
+    pub reachable_peer_masters: usize,
+    /// Término de Raft, si este master es el líder (1 o 0) y hasta dónde confirmó el log.
+    pub raft_term: usize,
+    pub raft_leader: usize,
+    pub raft_commit_index: usize,
+}
+
+type NodeSeries = (Arc<str>, &'static str);
+
+/// Segundos que cubren las ventanas de throughput y hot keys.
+pub const ACTIVITY_WINDOW_SECS: u64 = 60;
+
+/// Contador por segundo de los últimos `ACTIVITY_WINDOW_SECS`. Un slot por segundo, que se
+/// reinicia al reusarse; es aproximado (un incremento puede perderse justo en el cambio de
+/// segundo), suficiente para el dashboard.
+pub(crate) struct RateWindow {
+    /// `(segundo + 1, cuenta)`; `0` marca un slot que nunca se usó.
+    slots: [(AtomicU64, AtomicU64); ACTIVITY_WINDOW_SECS as usize],
+}
+
+impl Default for RateWindow {
+    fn default() -> Self {
+        Self {
+            slots: std::array::from_fn(|_| (AtomicU64::new(0), AtomicU64::new(0))),
+        }
+    }
+}
+
+impl RateWindow {
+    pub(crate) fn record_at(&self, sec: u64) {
+        let (tag, count) = &self.slots[(sec % ACTIVITY_WINDOW_SECS) as usize];
+        let current = tag.load(Ordering::Acquire);
+
+        if current != sec + 1
+            && tag
+                .compare_exchange(current, sec + 1, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+        {
+            count.store(0, Ordering::Release);
+        }
+        count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Cuentas por segundo, de la más vieja a la actual (que puede estar incompleta).
+    pub(crate) fn per_second_at(&self, now_sec: u64) -> Vec<u64> {
+        (0..ACTIVITY_WINDOW_SECS)
+            .rev()
+            .map(|ago| {
+                let Some(sec) = now_sec.checked_sub(ago) else {
+                    return 0;
+                };
+                let (tag, count) = &self.slots[(sec % ACTIVITY_WINDOW_SECS) as usize];
+                if tag.load(Ordering::Acquire) == sec + 1 {
+                    count.load(Ordering::Relaxed)
+                } else {
+                    0
+                }
+            })
+            .collect()
+    }
+}
+
+const HOT_KEY_BUCKET_SECS: u64 = 10;
+const HOT_KEY_BUCKETS: u64 = ACTIVITY_WINDOW_SECS / HOT_KEY_BUCKET_SECS;
+/// Claves distintas por bucket; las que llegan con el bucket lleno no se cuentan. Una
+/// clave caliente aparece temprano en el bucket, así que el corte casi no la afecta.
+const HOT_KEY_BUCKET_CAPACITY: usize = 4096;
+
+/// `(índice de bucket, accesos por clave)`.
+type HotKeyBucket = (u64, HashMap<Arc<str>, u64>);
+
+/// Accesos por clave en buckets de `HOT_KEY_BUCKET_SECS`, sobre el último minuto.
+#[derive(Default)]
+pub(crate) struct HotKeys {
+    buckets: Mutex<VecDeque<HotKeyBucket>>,
+}
+
+impl HotKeys {
+    pub(crate) fn observe_at(&self, key: &str, sec: u64) {
+        let idx = sec / HOT_KEY_BUCKET_SECS;
+        let mut buckets = self.buckets.lock();
+
+        while buckets
+            .front()
+            .is_some_and(|(front, _)| front + HOT_KEY_BUCKETS <= idx)
+        {
+            buckets.pop_front();
+        }
+        if buckets.back().is_none_or(|(back, _)| *back != idx) {
+            buckets.push_back((idx, HashMap::new()));
+        }
+
+        let (_, counts) = buckets.back_mut().expect("bucket recién insertado");
+        if let Some(hits) = counts.get_mut(key) {
+            *hits += 1;
+        } else if counts.len() < HOT_KEY_BUCKET_CAPACITY {
+            counts.insert(Arc::from(key), 1);
+        }
+    }
+
+    /// Las `n` claves con más accesos en la ventana, de mayor a menor.
+    pub(crate) fn top_at(&self, n: usize, now_sec: u64) -> Vec<(Arc<str>, u64)> {
+        let mut top: Vec<_> = self.merged_at(now_sec).into_iter().collect();
+        top.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        top.truncate(n);
+        top
+    }
+
+    /// Claves con al menos `min_hits` accesos en la ventana y `factor` veces el promedio
+    /// de las demás, de mayor a menor.
+    pub(crate) fn outliers_at(&self, factor: f64, min_hits: u64, now_sec: u64) -> Vec<Arc<str>> {
+        let merged = self.merged_at(now_sec);
+        let total: u64 = merged.values().sum();
+        let others = merged.len().saturating_sub(1).max(1) as f64;
+
+        let mut hot: Vec<_> = merged
+            .into_iter()
+            .filter(|(_, hits)| {
+                *hits >= min_hits && *hits as f64 >= factor * ((total - hits) as f64 / others)
+            })
+            .collect();
+        hot.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        hot.into_iter().map(|(key, _)| key).collect()
+    }
+
+    fn merged_at(&self, now_sec: u64) -> HashMap<Arc<str>, u64> {
+        let idx = now_sec / HOT_KEY_BUCKET_SECS;
+        let mut merged: HashMap<Arc<str>, u64> = HashMap::new();
+
+        for (bucket, counts) in self.buckets.lock().iter() {
+            if bucket + HOT_KEY_BUCKETS <= idx {
+                continue;
+            }
+            for (key, hits) in counts {
+                *merged.entry(key.clone()).or_default() += hits;
+            }
+        }
+        merged
+    }
+}
+
+/// Contadores e histogramas del master, expuestos en formato Prometheus y en el dashboard.
+pub struct MasterMetrics {
+    started: Instant,
+    /// Etiquetas registradas por el router, además de `KNOWN_ACTIONS`.
+    actions: RwLock<Vec<&'static str>>,
+    requests: DashMap<(&'static str, u16), AtomicU64>,
+    request_latency: DashMap<&'static str, Arc<LatencyHistogram>>,
+    node_latency: DashMap<NodeSeries, Arc<LatencyHistogram>>,
+    replica_lag: DashMap<Arc<str>, Arc<LatencyHistogram>>,
+    /// Último `CACHE-PRESSURE` de cada nodo.
+    cache_pressure: DashMap<Arc<str>, CachePressure>,
+    /// `phi` de cada nodo que manda latidos, de la última revisión del `FailureDetector`.
+    suspicion: DashMap<Arc<str>, f64>,
+    /// Última pasada de `StatsAggregationService`.
+    cluster_stats: RwLock<Option<Arc<ClusterStats>>>,
+    shed: AtomicU64,
+    /// Requests extra de `FanoutPolicy::Hedged` por un nodo que tardó.
+    hedges: AtomicU64,
+    throughput: RateWindow,
+    failures: RateWindow,
+    hot_keys: HotKeys,
+    /// Como `hot_keys` pero solo lecturas: decide qué claves copiar a otros shards.
+    hot_reads: HotKeys,
+}
+
+impl Default for MasterMetrics {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            actions: RwLock::default(),
+            requests: DashMap::new(),
+            request_latency: DashMap::new(),
+            node_latency: DashMap::new(),
+            replica_lag: DashMap::new(),
+            cache_pressure: DashMap::new(),
+            suspicion: DashMap::new(),
+            cluster_stats: RwLock::default(),
+            shed: AtomicU64::new(0),
+            hedges: AtomicU64::new(0),
+            throughput: RateWindow::default(),
+            failures: RateWindow::default(),
+            hot_keys: HotKeys::default(),
+            hot_reads: HotKeys::default(),
+        }
+    }
+}
+
+impl MasterMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn new_shared() -> Arc<Self> {
+        Arc::new(Self::new())
+    }
+
+    /// Da serie propia a `label` (una etiqueta de métricas de una ruta del router).
+    pub fn register_action(&self, label: &'static str) {
+        let mut actions = self.actions.write();
+        if !actions.contains(&label) {
+            actions.push(label);
+        }
+    }
+
+    fn action_label(&self, action: &str) -> &'static str {
+        KNOWN_ACTIONS
+            .iter()
+            .chain(self.actions.read().iter())
+            .find(|known| **known == action)
+            .copied()
+            .unwrap_or("OTHER")
+    }
+
+    /// Request de un cliente ya respondido con `code`. Los 503 (sin nodo disponible para
+    /// atenderlo) cuentan además como descartados.
+    pub fn observe_request(&self, action: &str, code: u16, elapsed: Duration) {
+        let action = self.action_label(action);
+
+        self.requests
+            .entry((action, code))
+            .or_default()
+            .fetch_add(1, Ordering::Relaxed);
+
+        self.request_latency
+            .entry(action)
+            .or_default()
+            .record(elapsed, code < 500);
+
+        if code == ErrorKind::Unavailable.wire_code() {
+            self.shed.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let sec = self.uptime().as_secs();
+        self.throughput.record_at(sec);
+        if code >= 500 {
+            self.failures.record_at(sec);
+        }
+    }
+
+    /// Clave leída o escrita por un cliente, para el ranking de hot keys.
+    pub fn observe_key(&self, key: &str) {
+        if key.is_empty() {
+            return;
+        }
+        self.hot_keys.observe_at(key, self.uptime().as_secs());
+    }
+
+    /// `observe_key` de un `GET`; además cuenta para `hot_reads`.
+    pub fn observe_read(&self, key: &str) {
+        if key.is_empty() {
+            return;
+        }
+        let sec = self.uptime().as_secs();
+        self.hot_keys.observe_at(key, sec);
+        self.hot_reads.observe_at(key, sec);
+    }
+
+    /// Cuánto después que el primer nodo del shard confirmó `node_id` una escritura.
+    pub fn observe_replica_lag(&self, node_id: &Arc<str>, lag: Duration) {
+        self.replica_lag
+            .entry(node_id.clone())
+            .or_default()
+            .record(lag, true);
+    }
+
+    /// Request que el master hizo a un nodo. Las carreras perdidas (abortadas) no llegan acá.
+    pub fn observe_node_request(
+        &self,
+        node_id: &Arc<str>,
+        action: &str,
+        elapsed: Duration,
+        success: bool,
+    ) {
+        self.node_latency
+            .entry((node_id.clone(), self.action_label(action)))
+            .or_default()
+            .record(elapsed, success);
+    }
+
+    /// Guarda el reporte de presión de `node_id` y devuelve el anterior.
+    pub fn observe_cache_pressure(
+        &self,
+        node_id: &Arc<str>,
+        report: CachePressure,
+    ) -> Option<CachePressure> {
+        self.cache_pressure.insert(node_id.clone(), report)
+    }
+
+    pub fn cache_pressure(&self, node_id: &str) -> Option<CachePressure> {
+        self.cache_pressure.get(node_id).map(|r| *r)
+    }
+
+    pub fn observe_suspicion(&self, node_id: &Arc<str>, phi: f64) {
+        self.suspicion.insert(node_id.clone(), phi);
+    }
+
+    pub fn suspicion(&self, node_id: &str) -> Option<f64> {
+        self.suspicion.get(node_id).map(|phi| *phi)
+    }
+
+    pub fn observe_cluster_stats(&self, stats: Arc<ClusterStats>) {
+        *self.cluster_stats.write() = Some(stats);
+    }
+
+    pub fn cluster_stats(&self) -> Option<Arc<ClusterStats>> {
+        self.cluster_stats.read().clone()
+    }
+
+    /// Borra las series de un nodo que dejó el cluster.
+    pub fn forget_node(&self, node_id: &str) {
+        self.node_latency
+            .retain(|(node, _), _| node.as_ref() != node_id);
+        self.replica_lag.remove(node_id);
+        self.cache_pressure.remove(node_id);
+        self.suspicion.remove(node_id);
+    }
+
+    pub fn uptime(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    pub fn requests_total(&self, action: &str, code: u16) -> u64 {
+        self.requests
+            .get(&(self.action_label(action), code))
+            .map(|c| c.load(Ordering::Relaxed))
+            .unwrap_or_default()
+    }
+
+    pub fn shed_total(&self) -> u64 {
+        self.shed.load(Ordering::Relaxed)
+    }
+
+    pub fn observe_hedge(&self) {
+        self.hedges.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn hedges_total(&self) -> u64 {
+        self.hedges.load(Ordering::Relaxed)
+    }
+
+    pub fn node_histogram(&self, node_id: &str, action: &str) -> Option<Arc<LatencyHistogram>> {
+        self.node_latency
+            .get(&(Arc::from(node_id), self.action_label(action)))
+            .map(|h| h.clone())
+    }
+
+    /// Latencia de un nodo sumando todas sus acciones.
+    pub fn node_latency_total(&self, node_id: &str) -> LatencyHistogram {
+        let total = LatencyHistogram::default();
+        for e in self.node_latency.iter() {
+            if e.key().0.as_ref() == node_id {
+                total.merge_from(e.value());
+            }
+        }
+        total
+    }
+
+    pub fn replica_lag_histogram(&self, node_id: &str) -> Option<Arc<LatencyHistogram>> {
+        self.replica_lag.get(node_id).map(|h| h.clone())
+    }
+
+    /// Requests por segundo del último minuto, del más viejo al actual.
+    pub fn throughput_per_second(&self) -> Vec<u64> {
+        self.throughput.per_second_at(self.uptime().as_secs())
+    }
+
+    /// Requests respondidos con 5xx por segundo del último minuto.
+    pub fn failures_per_second(&self) -> Vec<u64> {
+        self.failures.per_second_at(self.uptime().as_secs())
+    }
+
+    pub fn hot_keys(&self, n: usize) -> Vec<(Arc<str>, u64)> {
+        self.hot_keys.top_at(n, self.uptime().as_secs())
+    }
+
+    /// Claves leídas muy por encima del promedio en el último minuto (ver
+    /// `HotKeys::outliers_at`).
+    pub fn hot_reads(&self, factor: f64, min_hits: u64) -> Vec<Arc<str>> {
+        self.hot_reads
+            .outliers_at(factor, min_hits, self.uptime().as_secs())
+    }
+
+    pub fn render(&self, topology: &TopologyGauges) -> String {
+        let mut enc = PrometheusEncoder::new();
+
+        let mut requests: Vec<_> = self
+            .requests
+            .iter()
+            .map(|e| (*e.key(), e.value().load(Ordering::Relaxed)))
+            .collect();
+        requests.sort();
+
+        enc.family(
+            "cache_master_requests_total",
+            MetricKind::Counter,
+            "Requests de clientes por acción y código de respuesta.",
+        );
+        for ((action, code), value) in requests {
+            let code = code.to_string();
+            enc.sample(
+                "cache_master_requests_total",
+                &[("action", action), ("code", &code)],
+                value as f64,
+            );
+        }
+
+        enc.family(
+            "cache_master_shed_requests_total",
+            MetricKind::Counter,
+            "Requests rechazados por no haber nodo disponible.",
+        )
+        .sample(
+            "cache_master_shed_requests_total",
+            &[],
+            self.shed_total() as f64,
+        );
+
+        enc.family(
+            "cache_master_hedged_requests_total",
+            MetricKind::Counter,
+            "Requests a una réplica más porque el nodo preferido no contestó a tiempo.",
+        )
+        .sample(
+            "cache_master_hedged_requests_total",
+            &[],
+            self.hedges_total() as f64,
+        );
+
+        let mut latency: Vec<_> = self
+            .request_latency
+            .iter()
+            .map(|e| (*e.key(), e.value().clone()))
+            .collect();
+        latency.sort_by_key(|(action, _)| *action);
+
+        enc.family(
+            "cache_master_request_duration_seconds",
+            MetricKind::Histogram,
+            "Latencia de los requests de clientes.",
+        );
+        for (action, h) in &latency {
+            enc.histogram(
+                "cache_master_request_duration_seconds",
+                &[("action", action)],
+                h,
+            );
+        }
+
+        let mut nodes: Vec<_> = self
+            .node_latency
+            .iter()
+            .map(|e| (e.key().clone(), e.value().clone()))
+            .collect();
+        nodes.sort_by(|a, b| a.0.cmp(&b.0));
+
+        enc.family(
+            "cache_master_node_request_duration_seconds",
+            MetricKind::Histogram,
+            "Latencia de los requests del master a cada nodo.",
+        );
+        for ((node, action), h) in &nodes {
+            enc.histogram(
+                "cache_master_node_request_duration_seconds",
+                &[("node", node), ("action", action)],
+                h,
+            );
+        }
+
+        enc.family(
+            "cache_master_node_request_errors_total",
+            MetricKind::Counter,
+            "Requests a nodos que fallaron (timeout, conexión o error del nodo).",
+        );
+        for ((node, action), h) in &nodes {
+            enc.sample(
+                "cache_master_node_request_errors_total",
+                &[("node", node), ("action", action)],
+                h.errors() as f64,
+            );
+        }
+
+        let mut pressure: Vec<_> = self
+            .cache_pressure
+            .iter()
+            .map(|e| (e.key().clone(), *e.value()))
+            .collect();
+        pressure.sort_by(|a, b| a.0.cmp(&b.0));
+
+        enc.family(
+            "cache_master_node_cache_fill_ratio",
+            MetricKind::Gauge,
+            "Ocupación del cache de cada nodo según su último reporte (0 a 1).",
+        );
+        for (node, report) in &pressure {
+            enc.sample(
+                "cache_master_node_cache_fill_ratio",
+                &[("node", node)],
+                report.fill_ratio(),
+            );
+        }
+
+        enc.family(
+            "cache_master_node_evictions_per_second",
+            MetricKind::Gauge,
+            "Evicciones por capacidad de cada nodo en su último intervalo reportado.",
+        );
+        for (node, report) in &pressure {
+            enc.sample(
+                "cache_master_node_evictions_per_second",
+                &[("node", node)],
+                report.evictions_per_sec(),
+            );
+        }
+
+        enc.family(
+            "cache_master_node_memory_ratio",
+            MetricKind::Gauge,
+            "Memoria usada sobre MAX_MEMORY_BYTES de cada nodo con límite (0 a 1).",
+        );
+        for (node, report) in &pressure {
+            if let Some(ratio) = report.memory_ratio() {
+                enc.sample("cache_master_node_memory_ratio", &[("node", node)], ratio);
+            }
+        }
+
+        let mut suspicion: Vec<_> = self
+            .suspicion
+            .iter()
+            .map(|e| (e.key().clone(), *e.value()))
+            .collect();
+        suspicion.sort_by(|a, b| a.0.cmp(&b.0));
+
+        enc.family(
+            "cache_master_node_suspicion_phi",
+            MetricKind::Gauge,
+            "Sospecha de caída de cada nodo que manda latidos (phi-accrual).",
+        );
+        for (node, phi) in &suspicion {
+            enc.sample("cache_master_node_suspicion_phi", &[("node", node)], *phi);
+        }
+
+        if let Some(cluster) = self.cluster_stats() {
+            render_cluster_stats(&mut enc, &cluster);
+        }
+
+        let gauges = [
+            (
+                "cache_master_registered_nodes",
+                "Nodos conectados al master.",
+                topology.registered_nodes,
+            ),
+            (
+                "cache_master_shards",
+                "Shards (masters de datos) con al menos un nodo.",
+                topology.shards,
+            ),
+            (
+                "cache_master_ring_nodes",
+                "Nodos reales en el anillo de hashing.",
+                topology.ring_nodes,
+            ),
+            (
+                "cache_master_ring_vnodes",
+                "Nodos virtuales en el anillo de hashing.",
+                topology.ring_vnodes,
+            ),
+            (
+                "cache_master_peer_masters",
+                "Otros masters con los que se acuerdan las altas de nodos.",
+                topology.peer_masters,
+            ),
+            (
+                "cache_master_peer_masters_reachable",
+                "Otros masters conectados; sin mayoría no se aceptan altas.",
+                topology.reachable_peer_masters,
+            ),
+            (
+                "cache_master_raft_term",
+                "Término de Raft entre los masters; sube con cada elección.",
+                topology.raft_term,
+            ),
+            (
+                "cache_master_raft_leader",
+                "1 si este master es el líder de Raft.",
+                topology.raft_leader,
+            ),
+            (
+                "cache_master_raft_commit_index",
+                "Último índice del log de topología confirmado por la mayoría.",
+                topology.raft_commit_index,
+            ),
+        ];
+        for (name, help, value) in gauges {
+            enc.family(name, MetricKind::Gauge, help)
+                .sample(name, &[], value as f64);
+        }
+
+        enc.finish()
+    }
+}
+
+/// Totales del cluster y una serie por shard, con lo último que juntó
+/// `StatsAggregationService`.
+fn render_cluster_stats(enc: &mut PrometheusEncoder, cluster: &ClusterStats) {
+    let totals = [
+        (
+            "cache_master_cluster_keys",
+            "Claves en los primarios de todos los shards.",
+            cluster.keys() as f64,
+        ),
+        (
+            "cache_master_cluster_bytes",
+            "Bytes de clave y valor en los primarios de todos los shards.",
+            cluster.bytes() as f64,
+        ),
+        (
+            "cache_master_cluster_ops_per_second",
+            "Lecturas y escrituras por segundo en todos los shards.",
+            cluster.ops_per_sec(),
+        ),
+    ];
+    for (name, help, value) in totals {
+        enc.family(name, MetricKind::Gauge, help)
+            .sample(name, &[], value);
+    }
+
+    type ShardGauge = fn(&ShardStats) -> f64;
+    let per_shard: [(&str, &str, ShardGauge); 4] = [
+        (
+            "cache_master_shard_keys",
+            "Claves en el primario de cada shard.",
+            |s| s.total.entries as f64,
+        ),
+        (
+            "cache_master_shard_bytes",
+            "Bytes de clave y valor en el primario de cada shard.",
+            |s| s.total.bytes as f64,
+        ),
+        (
+            "cache_master_shard_hit_ratio",
+            "Aciertos sobre lecturas de cada shard en el último intervalo (0 a 1).",
+            ShardStats::hit_ratio,
+        ),
+        (
+            "cache_master_shard_ops_per_second",
+            "Lecturas y escrituras por segundo de cada shard en el último intervalo.",
+            ShardStats::ops_per_sec,
+        ),
+    ];
+    for (name, help, value) in per_shard {
+        enc.family(name, MetricKind::Gauge, help);
+        for shard in &cluster.shards {
+            enc.sample(name, &[("shard", &shard.shard)], value(shard));
+        }
+    }
+}
@@ -1,4 +1,17 @@
 pub mod adapters;
 pub mod app_state;
+pub mod clock_skew;
+pub mod dashboard;
+pub mod decision_log;
 pub mod di;
+pub mod failure_detector;
+pub mod hot_keys;
+pub mod http;
+pub mod live_config;
+pub mod metrics;
+pub mod monitor;
+pub mod peers;
+pub mod raft;
+pub mod topology_feed;
 pub mod utils;
+pub mod write_retries;
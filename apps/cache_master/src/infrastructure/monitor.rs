@@ -0,0 +1,47 @@
+use std::sync::Arc;
+
+use app_net::{MonitorEntry, MonitorHub};
+use dashmap::DashMap;
+
+/// Suscripciones a `MONITOR` de los clientes: a los comandos del propio master o a los de
+/// un nodo. Cada nodo monitoreado manda su stream completo una sola vez al master, que lo
+/// reparte aplicando el muestreo y la redacción de cada cliente.
+#[derive(Default)]
+pub struct MasterMonitor {
+    local: MonitorHub,
+    nodes: DashMap<Arc<str>, Arc<MonitorHub>>,
+}
+
+impl MasterMonitor {
+    pub fn new_shared() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    pub fn local(&self) -> &MonitorHub {
+        &self.local
+    }
+
+    /// Hub de `node_id`; se crea al primer `MONITOR` que lo pide.
+    pub fn node(&self, node_id: &str) -> Arc<MonitorHub> {
+        if let Some(hub) = self.nodes.get(node_id) {
+            return hub.clone();
+        }
+        self.nodes.entry(Arc::from(node_id)).or_default().clone()
+    }
+
+    /// Reparte un comando que reportó `node_id` (`EVT MONITOR`).
+    pub fn relay(&self, node_id: &str, entry: &MonitorEntry<'_>) {
+        if let Some(hub) = self.nodes.get(node_id) {
+            hub.record(entry);
+        }
+    }
+
+    /// Al desconectarse `peer_id`: deja de monitorear y, si era un nodo, se descarta su hub.
+    pub fn forget(&self, peer_id: &str) {
+        self.nodes.remove(peer_id);
+        self.local.unsubscribe(peer_id);
+        for hub in self.nodes.iter() {
+            hub.unsubscribe(peer_id);
+        }
+    }
+}
@@ -0,0 +1,669 @@
+//! Coordinación entre masters. Los nodos se conectan a todos los de `MASTER_IPS` y cada
+//! master arma su propio anillo, así que sin acuerdo dos masters pueden poner una réplica
+//! en shards distintos, o uno aislado del resto seguir sumando nodos por su cuenta.
+//!
+//! Cada master se conecta a los de `PEER_MASTERS` como un cliente más y entre todos
+//! replican con Raft (ver `raft`) en qué shard está cada nodo. Las altas las decide el
+//! líder: un master que no lo es se la reenvía con `PEER-FORWARD "<nodo>" "<shard>"`, y
+//! el líder rechaza con `Conflict` la que cambia de shard a un nodo cuyo shard acordado
+//! sigue vivo en su anillo. Un alta cuenta cuando la guardó la mayoría, así que la
+//! topología acordada sobrevive a la caída de cualquier master en minoría, y un master
+//! aislado no suma nodos. Las lecturas (`agreed_shard`, `PEER-STATE`) las contesta cada
+//! master con lo que ya aplicó, sin pasar por el líder.
+//!
+//! Las bajas no se consultan: un master no puede rutear a un nodo con el que perdió la
+//! conexión, lo vean o no los demás.
+
+use std::{
+    env,
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use app_core::{
+    id::new_sortable_id,
+    retry::{RetryError, RetryPolicy, retry_with_backoff_until},
+};
+use app_net::{
+    Connector, FrameReader, ParsedMsg, RequestDataInput, Socket, encode_args, encode_token,
+    parse_frame, tokenize,
+};
+use async_trait::async_trait;
+use bytes::Bytes;
+use parking_lot::{Mutex, RwLock};
+use tokio::{
+    io::AsyncWriteExt,
+    sync::{Notify, mpsc, watch},
+    time::{self, Instant},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    core::domain::{
+        models::AppError,
+        services::{ConsistentHasherService, TopologyCoordinator},
+    },
+    infrastructure::raft::{
+        Command, Message, Outcome, PersistentState, RAFT_APPEND, RAFT_SNAPSHOT, RAFT_VOTE,
+        RaftCore, RaftStorage, Role, TopologyLedger,
+    },
+};
+
+pub const PEER_HELLO: &str = "PEER-HELLO";
+pub const PEER_FORWARD: &str = "PEER-FORWARD";
+pub const PEER_STATE: &str = "PEER-STATE";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerConfig {
+    /// Id con el que este master se presenta a los otros.
+    pub master_id: String,
+    /// Direcciones de los otros masters; sin ninguna, el master no coordina con nadie.
+    pub peers: Vec<String>,
+    /// Cada cuánto el líder manda latidos (y entradas nuevas) a los demás.
+    pub heartbeat_interval: Duration,
+    /// Sin oír al líder entre esto y el doble, un master llama a elecciones.
+    pub election_timeout: Duration,
+    /// Cuánto se espera la respuesta de otro master, o que se confirme un alta.
+    pub rpc_timeout: Duration,
+    /// Dónde guardar el estado de Raft. Sin directorio un master que reinicia vuelve vacío
+    /// y podría votar dos veces en el mismo término: `from_env` no arranca con
+    /// `PEER_MASTERS` y sin `RAFT_DIR` (sin disco solo lo usan los tests).
+    pub raft_dir: Option<PathBuf>,
+}
+
+impl Default for PeerConfig {
+    fn default() -> Self {
+        Self {
+            master_id: format!("master-{}", new_sortable_id()),
+            peers: Vec::new(),
+            heartbeat_interval: Duration::from_millis(100),
+            election_timeout: Duration::from_secs(1),
+            rpc_timeout: Duration::from_millis(500),
+            raft_dir: None,
+        }
+    }
+}
+
+impl PeerConfig {
+    /// `PEER_MASTERS` (direcciones separadas por coma), `MASTER_ID` (uno al azar por
+    /// defecto), `PEER_HEARTBEAT_MS` (100), `PEER_ELECTION_TIMEOUT_MS` (1000),
+    /// `PEER_RPC_TIMEOUT_MS` (500) y `RAFT_DIR`, obligatorio si hay `PEER_MASTERS`.
+    pub fn from_env() -> Result<Self, AppError> {
+        let mut config = Self::default();
+        if let Ok(id) = env::var("MASTER_ID")
+            && !id.trim().is_empty()
+        {
+            config.master_id = id.trim().to_string();
+        }
+        config.peers = env::var("PEER_MASTERS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|addr| !addr.is_empty())
+            .map(str::to_string)
+            .collect();
+        let millis = |name: &str| {
+            env::var(name)
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .filter(|ms| *ms > 0)
+                .map(Duration::from_millis)
+        };
+        if let Some(every) = millis("PEER_HEARTBEAT_MS") {
+            config.heartbeat_interval = every;
+        }
+        if let Some(timeout) = millis("PEER_ELECTION_TIMEOUT_MS") {
+            config.election_timeout = timeout;
+        }
+        if let Some(timeout) = millis("PEER_RPC_TIMEOUT_MS") {
+            config.rpc_timeout = timeout;
+        }
+        config.raft_dir = env::var("RAFT_DIR")
+            .ok()
+            .filter(|dir| !dir.trim().is_empty())
+            .map(PathBuf::from);
+        if config.enabled() && config.raft_dir.is_none() {
+            return Err(AppError::Config(
+                "PEER_MASTERS necesita RAFT_DIR: sin el estado en disco un master que reinicia \
+                 puede votar dos veces en el mismo término"
+                    .into(),
+            ));
+        }
+        Ok(config)
+    }
+
+    pub fn enabled(&self) -> bool {
+        !self.peers.is_empty()
+    }
+}
+
+/// Conexión a otro master; `None` mientras no está conectado.
+struct PeerLink {
+    addr: Arc<str>,
+    socket: RwLock<Option<Arc<Socket>>>,
+    /// El `MASTER_ID` del otro lado, para reenviarle las altas si es el líder.
+    master_id: RwLock<Option<String>>,
+    /// Hay un mensaje de Raft en vuelo: a un master lento no se le apilan latidos.
+    busy: AtomicBool,
+}
+
+impl PeerLink {
+    fn socket(&self) -> Option<Arc<Socket>> {
+        self.socket.read().clone()
+    }
+}
+
+/// Cambios al estado de Raft que hay que guardar antes de seguir, numerados para que
+/// dos guardados que se cruzan no dejen el más viejo.
+type Pending = Option<(u64, PersistentState)>;
+
+pub struct PeerCoordinator {
+    config: PeerConfig,
+    /// Para habilitar `PEER-*` y `RAFT-*` en los otros masters (son de admin).
+    admin_token: Option<String>,
+    /// El anillo de este master: un shard acordado que sigue en él no se cambia por otro.
+    ring: Arc<dyn ConsistentHasherService>,
+    raft: Mutex<RaftCore>,
+    storage: Option<RaftStorage>,
+    /// El estado de `RAFT_DIR` no se pudo leer: antes que arrancar de cero y olvidar lo
+    /// que votó, el master no participa hasta que se arregle.
+    broken: Option<String>,
+    versions: AtomicU64,
+    /// Último índice aplicado, para los que esperan que se confirme un alta.
+    applied: watch::Sender<u64>,
+    /// Despierta a `run_raft` para mandar ya lo nuevo en vez de esperar el latido.
+    kick: Notify,
+    started: Instant,
+    links: Vec<PeerLink>,
+}
+
+impl PeerCoordinator {
+    pub fn new(
+        config: PeerConfig,
+        admin_token: Option<String>,
+        ring: Arc<dyn ConsistentHasherService>,
+    ) -> Self {
+        let storage = config.raft_dir.as_ref().map(RaftStorage::new);
+        let (state, broken) = match storage.as_ref().map(RaftStorage::load) {
+            None => (PersistentState::default(), None),
+            Some(Ok(state)) => (state, None),
+            Some(Err(e)) => {
+                error!(target: "topology", "no se pudo leer el estado de Raft: {e}");
+                (PersistentState::default(), Some(e.to_string()))
+            }
+        };
+        let applied = state.log.snapshot().last_index();
+        let raft = RaftCore::new(
+            config.master_id.clone(),
+            config.peers.len(),
+            config.heartbeat_interval,
+            config.election_timeout,
+            state,
+            0,
+        );
+        let links = config
+            .peers
+            .iter()
+            .map(|addr| PeerLink {
+                addr: Arc::from(addr.as_str()),
+                socket: RwLock::new(None),
+                master_id: RwLock::new(None),
+                busy: AtomicBool::new(false),
+            })
+            .collect();
+        Self {
+            config,
+            admin_token,
+            ring,
+            raft: Mutex::new(raft),
+            storage,
+            broken,
+            versions: AtomicU64::new(0),
+            applied: watch::channel(applied).0,
+            kick: Notify::new(),
+            started: Instant::now(),
+            links,
+        }
+    }
+
+    pub fn config(&self) -> &PeerConfig {
+        &self.config
+    }
+
+    /// Lo acordado que ya aplicó este master.
+    pub fn ledger(&self) -> TopologyLedger {
+        self.raft.lock().ledger().clone()
+    }
+
+    pub fn applied_index(&self) -> u64 {
+        *self.applied.borrow()
+    }
+
+    pub fn term(&self) -> u64 {
+        self.raft.lock().term()
+    }
+
+    pub fn role(&self) -> Role {
+        self.raft.lock().role()
+    }
+
+    pub fn leader(&self) -> Option<String> {
+        self.raft.lock().leader().map(str::to_string)
+    }
+
+    pub fn commit_index(&self) -> u64 {
+        self.raft.lock().commit_index()
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.role() == Role::Leader
+    }
+
+    /// Masters contando a este.
+    pub fn masters(&self) -> usize {
+        self.links.len() + 1
+    }
+
+    /// Otros masters conectados ahora.
+    pub fn reachable_peers(&self) -> usize {
+        self.links
+            .iter()
+            .filter(|link| link.socket.read().is_some())
+            .count()
+    }
+
+    pub fn quorum(&self) -> usize {
+        self.masters() / 2 + 1
+    }
+
+    pub fn has_quorum(&self) -> bool {
+        self.reachable_peers() + 1 >= self.quorum()
+    }
+
+    fn now(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64
+    }
+
+    fn ensure_usable(&self) -> Result<(), AppError> {
+        match &self.broken {
+            Some(e) => Err(AppError::NoQuorum(format!("estado de Raft ilegible: {e}"))),
+            None => Ok(()),
+        }
+    }
+
+    /// Corre `f` sobre Raft y guarda lo que haya cambiado antes de devolver.
+    async fn step<T>(&self, f: impl FnOnce(&mut RaftCore, u64) -> T) -> Result<T, AppError> {
+        self.ensure_usable()?;
+        let (out, pending) = self.with_raft(f);
+        if let (Some(storage), Some((version, state))) = (&self.storage, pending) {
+            storage.save(version, &state).await?;
+        }
+        Ok(out)
+    }
+
+    fn with_raft<T>(&self, f: impl FnOnce(&mut RaftCore, u64) -> T) -> (T, Pending) {
+        let mut raft = self.raft.lock();
+        let out = f(&mut raft, self.now());
+        let pending = raft
+            .take_dirty()
+            .map(|state| (self.versions.fetch_add(1, Ordering::Relaxed) + 1, state));
+        let applied = raft.ledger().applied();
+        self.applied.send_if_modified(|known| {
+            let changed = *known != applied;
+            *known = applied;
+            changed
+        });
+        (out, pending)
+    }
+
+    /// `RAFT-VOTE`, `RAFT-APPEND` o `RAFT-SNAPSHOT` de otro master.
+    pub async fn handle_rpc(&self, action: &str, payload: &str) -> Result<String, AppError> {
+        match action {
+            RAFT_VOTE => {
+                let req = payload.parse()?;
+                let res = self.step(|raft, now| raft.handle_vote(&req, now)).await?;
+                Ok(res.to_string())
+            }
+            RAFT_APPEND => {
+                let req = payload.parse()?;
+                let res = self.step(|raft, now| raft.handle_append(req, now)).await?;
+                Ok(res.to_string())
+            }
+            RAFT_SNAPSHOT => {
+                let req = payload.parse()?;
+                let res = self
+                    .step(|raft, now| raft.handle_snapshot(req, now))
+                    .await?;
+                Ok(res.to_string())
+            }
+            other => Err(AppError::BadRequest(format!(
+                "acción de Raft desconocida: {other}"
+            ))),
+        }
+    }
+
+    /// Manda lo que toque a los otros masters (ver `RaftCore::tick`).
+    pub async fn tick(self: &Arc<Self>) -> Result<(), AppError> {
+        let messages = self.step(|raft, now| raft.tick(now)).await?;
+        for (peer, message) in messages {
+            self.send(peer, message);
+        }
+        Ok(())
+    }
+
+    fn send(self: &Arc<Self>, peer: usize, message: Message) {
+        let link = &self.links[peer];
+        let Some(socket) = link.socket() else {
+            return;
+        };
+        if link.busy.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let coordinator = self.clone();
+        tokio::spawn(async move {
+            let res = socket
+                .request(RequestDataInput::new(message.action(), &message.payload()))
+                .await;
+            let handled = match res {
+                Ok(res) if res.is_success() => {
+                    coordinator.handle_reply(peer, &message, &res.payload).await
+                }
+                Ok(res) => Err(AppError::Conflict(res.payload)),
+                Err(e) => Err(e.into()),
+            };
+            coordinator.links[peer].busy.store(false, Ordering::Release);
+            if let Err(e) = handled {
+                debug!(target: "topology", peer = &*coordinator.links[peer].addr, action = message.action(), "{e}");
+            }
+        });
+    }
+
+    async fn handle_reply(&self, peer: usize, sent: &Message, reply: &str) -> Result<(), AppError> {
+        match sent {
+            Message::Vote(_) => {
+                let res = reply.parse()?;
+                let elected = self
+                    .step(|raft, now| {
+                        let was_leader = raft.role() == Role::Leader;
+                        raft.handle_vote_response(peer, res, now);
+                        !was_leader && raft.role() == Role::Leader
+                    })
+                    .await?;
+                if elected {
+                    info!(target: "topology", term = self.term(), "este master es el líder");
+                }
+                // un sondeo ganado llama a elecciones y un líder nuevo avisa, sin esperar
+                self.kick.notify_one();
+            }
+            Message::Append(_) | Message::Snapshot(_) => {
+                let res = reply.parse()?;
+                self.step(|raft, now| raft.handle_append_response(peer, res, now))
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// `PEER-FORWARD` de otro master, o un alta propia si este es el líder: si `node_id`
+    /// puede ir a `shard_id`, y el índice a partir del cual se ve la decisión.
+    pub async fn lead_join(&self, node_id: &str, shard_id: &str) -> Result<(bool, u64), AppError> {
+        let proposed = self
+            .step(|raft, now| {
+                let expected = match raft.ledger().shard_of(node_id) {
+                    Some(agreed) if agreed == shard_id => return Ok(Err(true)),
+                    Some(agreed) if self.ring.node_exists(agreed) => return Ok(Err(false)),
+                    agreed => agreed.map(str::to_string),
+                };
+                let command = Command::Assign {
+                    node_id: node_id.to_string(),
+                    shard_id: shard_id.to_string(),
+                    expected,
+                };
+                raft.propose(command, now)
+                    .map(Ok)
+                    .map_err(|_| AppError::NoQuorum("este master no es el líder".to_string()))
+            })
+            .await??;
+        let (index, term) = match proposed {
+            Ok(entry) => entry,
+            Err(accepted) => return Ok((accepted, self.applied_index())),
+        };
+        self.kick.notify_one();
+
+        let deadline = Instant::now() + self.config.rpc_timeout;
+        let mut applied = self.applied.subscribe();
+        loop {
+            let outcome = self.raft.lock().outcome(index, term);
+            match outcome {
+                Outcome::Applied => {
+                    let ledger = self.ledger();
+                    debug!(target: "topology", index, node_id, shard_id, "alta acordada");
+                    return Ok((ledger.shard_of(node_id) == Some(shard_id), ledger.applied()));
+                }
+                Outcome::Lost => {
+                    return Err(AppError::NoQuorum(format!(
+                        "cambió el líder antes de confirmar el alta de {node_id}"
+                    )));
+                }
+                Outcome::Pending => {}
+            }
+            if time::timeout_at(deadline, applied.changed()).await.is_err() {
+                return Err(AppError::NoQuorum(format!(
+                    "la mayoría de los masters no confirmó el alta de {node_id}"
+                )));
+            }
+        }
+    }
+
+    /// Le pasa el alta al líder `leader_id`.
+    async fn forward_join(
+        &self,
+        leader_id: &str,
+        node_id: &str,
+        shard_id: &str,
+    ) -> Result<(bool, u64), AppError> {
+        let socket = self
+            .links
+            .iter()
+            .find(|link| link.master_id.read().as_deref() == Some(leader_id))
+            .and_then(PeerLink::socket)
+            .ok_or_else(|| AppError::NoQuorum(format!("sin conexión con el líder {leader_id}")))?;
+
+        let res = socket
+            .request(RequestDataInput::new(
+                PEER_FORWARD,
+                &encode_args([node_id, shard_id]),
+            ))
+            .await?;
+        if !res.is_success() {
+            return Err(AppError::NoQuorum(res.payload));
+        }
+        let mut parts = tokenize(&res.payload);
+        let accepted = parts.next().as_deref() == Some("OK");
+        let index = parts
+            .next()
+            .and_then(|i| i.parse::<u64>().ok())
+            .ok_or_else(|| AppError::BadRequest(format!("{PEER_FORWARD}: {}", res.payload)))?;
+
+        // hasta ver acá lo que decidió el líder, para que la próxima lectura ya lo tenga
+        let mut applied = self.applied.subscribe();
+        let _ = time::timeout(
+            self.config.rpc_timeout,
+            applied.wait_for(|applied| *applied >= index),
+        )
+        .await;
+        Ok((accepted, index))
+    }
+}
+
+#[async_trait]
+impl TopologyCoordinator for PeerCoordinator {
+    fn agreed_shard(&self, node_id: &str) -> Option<String> {
+        self.raft
+            .lock()
+            .ledger()
+            .shard_of(node_id)
+            .map(str::to_string)
+    }
+
+    async fn commit_join(&self, node_id: &str, shard_id: &str) -> Result<(), AppError> {
+        if self.links.is_empty() {
+            return Ok(());
+        }
+        self.ensure_usable()?;
+        if !self.has_quorum() {
+            return Err(AppError::NoQuorum(format!(
+                "{} de {} masters conectados, hacen falta {}",
+                self.reachable_peers() + 1,
+                self.masters(),
+                self.quorum()
+            )));
+        }
+
+        // recién arrancado (o tras perder al líder) se da tiempo a que haya uno
+        let deadline = Instant::now() + self.config.election_timeout * 2;
+        let (accepted, _) = loop {
+            let (role, leader) = {
+                let raft = self.raft.lock();
+                (raft.role(), raft.leader().map(str::to_string))
+            };
+            match (role, leader) {
+                (Role::Leader, _) => break self.lead_join(node_id, shard_id).await?,
+                (_, Some(leader)) => break self.forward_join(&leader, node_id, shard_id).await?,
+                _ if Instant::now() >= deadline => {
+                    return Err(AppError::NoQuorum(
+                        "los masters no tienen líder".to_string(),
+                    ));
+                }
+                _ => time::sleep(self.config.heartbeat_interval).await,
+            }
+        };
+        if accepted {
+            return Ok(());
+        }
+        Err(AppError::Conflict(format!(
+            "{node_id} ya tiene acordado el shard {}",
+            self.agreed_shard(node_id).unwrap_or_default()
+        )))
+    }
+}
+
+/// Mantiene la conexión al master `idx` de `PEER_MASTERS` hasta que se cancele `token`.
+pub async fn link_peer(
+    coordinator: Arc<PeerCoordinator>,
+    idx: usize,
+    connector: Arc<dyn Connector>,
+    token: CancellationToken,
+) {
+    let link = &coordinator.links[idx];
+    let addr = link.addr.clone();
+    let policy = RetryPolicy::default().with_jitter(0.5);
+
+    loop {
+        let stream =
+            match retry_with_backoff_until(&policy, &token, |_| connector.connect(&addr)).await {
+                Ok(stream) => stream,
+                Err(RetryError::Cancelled { .. }) => return,
+                Err(e) => {
+                    warn!(target: "topology", peer = &*addr, "no se pudo conectar: {e}");
+                    continue;
+                }
+            };
+
+        let (reader, mut writer) = tokio::io::split(stream);
+        let (tx, mut rx) = mpsc::unbounded_channel::<Bytes>();
+        let socket = Arc::new(Socket::new(
+            coordinator.config.master_id.clone(),
+            tx,
+            coordinator.config.rpc_timeout * 2,
+        ));
+        let writer_task = tokio::spawn(async move {
+            while let Some(bytes) = rx.recv().await {
+                if writer.write_all(&bytes).await.is_err() {
+                    break;
+                }
+            }
+        });
+        let reader_socket = socket.clone();
+        let mut reader_task = tokio::spawn(async move {
+            let mut frames = FrameReader::new(reader);
+            while let Ok(Some(frame)) = frames.next_frame().await {
+                if let Ok(ParsedMsg::Res { id, raw_response }) = parse_frame(&frame) {
+                    reader_socket.handle_response(id, raw_response.to_string());
+                }
+            }
+        });
+
+        let _ = socket.send_raw(Bytes::from(format!("{}\n", coordinator.config.master_id)));
+        let authorized = match &coordinator.admin_token {
+            None => true,
+            Some(admin_token) => socket
+                .request(RequestDataInput::new("AUTH", &encode_token(admin_token)))
+                .await
+                .is_ok_and(|res| res.is_success()),
+        };
+        let master_id = match authorized {
+            true => socket
+                .request(RequestDataInput::new(PEER_HELLO, ""))
+                .await
+                .ok()
+                .filter(|res| res.is_success())
+                .map(|res| res.payload),
+            false => None,
+        };
+        if let Some(master_id) = master_id {
+            *link.master_id.write() = Some(master_id);
+            *link.socket.write() = Some(socket);
+            info!(target: "topology", peer = &*addr, "conectado al master par");
+        } else {
+            warn!(target: "topology", peer = &*addr, "el master par no aceptó el AUTH");
+            reader_task.abort();
+        }
+
+        tokio::select! {
+            _ = token.cancelled() => {}
+            _ = &mut reader_task => {}
+        }
+        *link.socket.write() = None;
+        link.busy.store(false, Ordering::Release);
+        reader_task.abort();
+        writer_task.abort();
+        if token.is_cancelled() {
+            return;
+        }
+        warn!(target: "topology", peer = &*addr, "se perdió la conexión con el master par");
+
+        tokio::select! {
+            _ = token.cancelled() => return,
+            _ = time::sleep(policy.initial_delay) => {}
+        }
+    }
+}
+
+/// Maneja Raft hasta que se cancele `token`: latidos, elecciones y lo que haya para
+/// replicar apenas aparece.
+pub async fn run_raft(coordinator: Arc<PeerCoordinator>, token: CancellationToken) {
+    if let Err(e) = coordinator.ensure_usable() {
+        error!(target: "topology", "{e}: este master no participa de Raft");
+        return;
+    }
+    let every = (coordinator.config.heartbeat_interval / 2).max(Duration::from_millis(5));
+    let mut interval = time::interval(every);
+    interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            _ = token.cancelled() => break,
+            _ = interval.tick() => {}
+            _ = coordinator.kick.notified() => {}
+        }
+        if let Err(e) = coordinator.tick().await {
+            error!(target: "topology", "no se pudo guardar el estado de Raft: {e}");
+        }
+    }
+}
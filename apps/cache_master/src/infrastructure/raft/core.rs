@@ -0,0 +1,517 @@
+use std::{collections::HashSet, time::Duration};
+
+use crate::infrastructure::raft::{
+    log::{Command, Entry, RaftLog, Snapshot, TopologyLedger},
+    messages::{
+        AppendRequest, AppendResponse, Message, SnapshotRequest, VoteRequest, VoteResponse,
+    },
+    storage::PersistentState,
+};
+
+/// Entradas por `RAFT-APPEND`.
+const MAX_BATCH: usize = 64;
+
+/// Entradas aplicadas que se juntan antes de compactarlas en una instantánea.
+const SNAPSHOT_EVERY: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Follower,
+    Candidate,
+    Leader,
+}
+
+impl Role {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Role::Follower => "follower",
+            Role::Candidate => "candidate",
+            Role::Leader => "leader",
+        }
+    }
+}
+
+/// Cómo terminó una entrada propuesta (ver `RaftCore::outcome`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Pending,
+    Applied,
+    /// Un líder posterior la reemplazó antes de que se confirmara.
+    Lost,
+}
+
+/// Un miembro de Raft sin red ni reloj: recibe mensajes y el tiempo (en ms) y devuelve los
+/// mensajes para los otros, que se identifican por su posición (`0..peers`). Quien lo
+/// maneja (ver `peers::PeerCoordinator`) los manda y le pasa las respuestas.
+pub struct RaftCore {
+    id: String,
+    peers: usize,
+    heartbeat_ms: u64,
+    election_ms: u64,
+    snapshot_every: usize,
+
+    term: u64,
+    voted_for: Option<String>,
+    log: RaftLog,
+    /// Estado con las entradas confirmadas ya aplicadas.
+    ledger: TopologyLedger,
+    commit: u64,
+
+    role: Role,
+    leader: Option<String>,
+    /// Última vez que se oyó al líder: mientras sea reciente no se vota a otro, para que
+    /// un master que vuelve de una partición no tumbe a un líder sano.
+    last_heard: Option<u64>,
+    election_deadline: u64,
+    next_heartbeat: u64,
+    /// Sondeo previo a una elección (ver `VoteRequest::pre`): quién dijo que votaría. Un
+    /// master aislado nunca junta la mayoría, así que no sube el término y al volver no
+    /// obliga al líder a dejar el puesto.
+    pre_votes: Option<HashSet<usize>>,
+    /// El sondeo salió bien: en el próximo `tick` se llama a elecciones.
+    pre_vote_won: bool,
+    votes: HashSet<usize>,
+    next_index: Vec<u64>,
+    match_index: Vec<u64>,
+    /// Cambió algo que hay que guardar antes de contestar.
+    dirty: bool,
+}
+
+impl RaftCore {
+    pub fn new(
+        id: String,
+        peers: usize,
+        heartbeat: Duration,
+        election_timeout: Duration,
+        state: PersistentState,
+        now: u64,
+    ) -> Self {
+        let ledger = state.log.snapshot().ledger.clone();
+        let mut core = Self {
+            id,
+            peers,
+            heartbeat_ms: heartbeat.as_millis() as u64,
+            election_ms: election_timeout.as_millis() as u64,
+            snapshot_every: SNAPSHOT_EVERY,
+            term: state.term,
+            voted_for: state.voted_for,
+            commit: ledger.applied(),
+            log: state.log,
+            ledger,
+            role: Role::Follower,
+            leader: None,
+            last_heard: None,
+            election_deadline: 0,
+            next_heartbeat: 0,
+            pre_votes: None,
+            pre_vote_won: false,
+            votes: HashSet::new(),
+            next_index: Vec::new(),
+            match_index: Vec::new(),
+            dirty: false,
+        };
+        core.reset_election(now);
+        core
+    }
+
+    /// Compacta cada `entries` entradas aplicadas en vez de cada `SNAPSHOT_EVERY`.
+    pub fn with_snapshot_every(mut self, entries: usize) -> Self {
+        self.snapshot_every = entries.max(1);
+        self
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn term(&self) -> u64 {
+        self.term
+    }
+
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    pub fn leader(&self) -> Option<&str> {
+        self.leader.as_deref()
+    }
+
+    pub fn commit_index(&self) -> u64 {
+        self.commit
+    }
+
+    pub fn log(&self) -> &RaftLog {
+        &self.log
+    }
+
+    pub fn ledger(&self) -> &TopologyLedger {
+        &self.ledger
+    }
+
+    pub fn quorum(&self) -> usize {
+        let members = self.peers + 1;
+        members / 2 + 1
+    }
+
+    /// Lo que hay que guardar, si cambió desde la última vez.
+    pub fn take_dirty(&mut self) -> Option<PersistentState> {
+        if !std::mem::take(&mut self.dirty) {
+            return None;
+        }
+        Some(PersistentState {
+            term: self.term,
+            voted_for: self.voted_for.clone(),
+            log: self.log.clone(),
+        })
+    }
+
+    /// Lo que toca hacer a `now`: latidos (o entradas) a cada seguidor si es líder, o
+    /// llamar a elecciones si hace mucho que no oye a ninguno.
+    pub fn tick(&mut self, now: u64) -> Vec<(usize, Message)> {
+        match self.role {
+            Role::Leader if now >= self.next_heartbeat => {
+                self.next_heartbeat = now + self.heartbeat_ms;
+                (0..self.peers).map(|p| (p, self.replication(p))).collect()
+            }
+            Role::Leader => Vec::new(),
+            _ if std::mem::take(&mut self.pre_vote_won) => self.start_election(now),
+            _ if now >= self.election_deadline => self.start_pre_vote(now),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Lo que necesita el seguidor `peer` para alcanzar al líder.
+    pub fn replication(&self, peer: usize) -> Message {
+        let next = self.next_index[peer];
+        if next <= self.log.snapshot_index() {
+            return Message::Snapshot(SnapshotRequest {
+                term: self.term,
+                leader_id: self.id.clone(),
+                snapshot: self.log.snapshot().clone(),
+            });
+        }
+        let prev_index = next - 1;
+        Message::Append(AppendRequest {
+            term: self.term,
+            leader_id: self.id.clone(),
+            prev_index,
+            prev_term: self.log.term_at(prev_index).unwrap_or_default(),
+            leader_commit: self.commit,
+            entries: self.log.entries_from(next, MAX_BATCH),
+        })
+    }
+
+    /// Agrega `command` al log si este master es el líder y devuelve su índice y término;
+    /// si no, el líder conocido.
+    pub fn propose(&mut self, command: Command, now: u64) -> Result<(u64, u64), Option<String>> {
+        if self.role != Role::Leader {
+            return Err(self.leader.clone());
+        }
+        let index = self.log.append(Entry {
+            term: self.term,
+            command,
+        });
+        self.dirty = true;
+        // sin esperar al próximo latido
+        self.next_heartbeat = now;
+        self.advance_commit();
+        Ok((index, self.term))
+    }
+
+    pub fn outcome(&self, index: u64, term: u64) -> Outcome {
+        match self.log.term_at(index) {
+            Some(t) if t != term => Outcome::Lost,
+            None if index > self.log.last_index() => Outcome::Lost,
+            _ if self.ledger.applied() >= index => Outcome::Applied,
+            _ => Outcome::Pending,
+        }
+    }
+
+    pub fn handle_vote(&mut self, req: &VoteRequest, now: u64) -> VoteResponse {
+        let leader_alive = self.role == Role::Leader
+            || self
+                .last_heard
+                .is_some_and(|heard| now < heard + self.election_ms);
+        let up_to_date =
+            (req.last_term, req.last_index) >= (self.log.last_term(), self.log.last_index());
+
+        if req.pre {
+            return VoteResponse {
+                term: self.term,
+                granted: req.term > self.term && !leader_alive && up_to_date,
+                pre: true,
+            };
+        }
+
+        if req.term > self.term && !leader_alive {
+            self.step_down(req.term, now);
+        }
+        let free = self
+            .voted_for
+            .as_ref()
+            .is_none_or(|voted| *voted == req.candidate_id);
+        let granted = req.term == self.term && self.role == Role::Follower && up_to_date && free;
+        if granted {
+            self.voted_for = Some(req.candidate_id.clone());
+            self.dirty = true;
+            self.reset_election(now);
+        }
+        VoteResponse {
+            term: self.term,
+            granted,
+            pre: false,
+        }
+    }
+
+    pub fn handle_vote_response(&mut self, peer: usize, res: VoteResponse, now: u64) {
+        if res.term > self.term {
+            self.step_down(res.term, now);
+            return;
+        }
+        if !res.granted {
+            return;
+        }
+        if res.pre {
+            let quorum = self.quorum();
+            if let Some(pre_votes) = &mut self.pre_votes {
+                pre_votes.insert(peer);
+                if pre_votes.len() + 1 >= quorum {
+                    self.pre_votes = None;
+                    self.pre_vote_won = true;
+                }
+            }
+            return;
+        }
+        if self.role != Role::Candidate || res.term != self.term {
+            return;
+        }
+        self.votes.insert(peer);
+        if self.votes.len() + 1 >= self.quorum() {
+            self.become_leader(now);
+        }
+    }
+
+    pub fn handle_append(&mut self, req: AppendRequest, now: u64) -> AppendResponse {
+        if !self.follow(req.term, &req.leader_id, now) {
+            return self.append_response(false, self.log.last_index());
+        }
+
+        let AppendRequest {
+            mut prev_index,
+            mut prev_term,
+            mut entries,
+            leader_commit,
+            ..
+        } = req;
+        // lo que ya está en la instantánea está confirmado: coincide seguro
+        let snapshot_index = self.log.snapshot_index();
+        if prev_index < snapshot_index {
+            let covered = (snapshot_index - prev_index) as usize;
+            if covered >= entries.len() {
+                return self.append_response(true, prev_index + entries.len() as u64);
+            }
+            entries.drain(..covered);
+            prev_index = snapshot_index;
+            prev_term = self.log.snapshot().last_term;
+        }
+
+        if prev_index > self.log.last_index() {
+            return self.append_response(false, self.log.last_index());
+        }
+        if self.log.term_at(prev_index) != Some(prev_term) {
+            return self.append_response(false, prev_index - 1);
+        }
+
+        let last_new = prev_index + entries.len() as u64;
+        for (index, entry) in (prev_index + 1..).zip(entries) {
+            match self.log.term_at(index) {
+                Some(term) if term == entry.term => continue,
+                Some(_) => self.log.truncate_from(index),
+                None => {}
+            }
+            self.log.append(entry);
+            self.dirty = true;
+        }
+
+        let commit = leader_commit.min(last_new);
+        if commit > self.commit {
+            self.commit = commit;
+            self.apply();
+        }
+        self.append_response(true, last_new)
+    }
+
+    pub fn handle_snapshot(&mut self, req: SnapshotRequest, now: u64) -> AppendResponse {
+        if !self.follow(req.term, &req.leader_id, now) {
+            return self.append_response(false, self.log.last_index());
+        }
+        let last_index = req.snapshot.last_index();
+        if last_index > self.commit {
+            self.install(req.snapshot);
+        }
+        self.append_response(true, last_index)
+    }
+
+    pub fn handle_append_response(&mut self, peer: usize, res: AppendResponse, now: u64) {
+        if res.term > self.term {
+            self.step_down(res.term, now);
+            return;
+        }
+        if self.role != Role::Leader || res.term != self.term {
+            return;
+        }
+        if res.success {
+            self.match_index[peer] = self.match_index[peer].max(res.last_index);
+            self.next_index[peer] = self.match_index[peer] + 1;
+            self.advance_commit();
+        } else {
+            let next = self.next_index[peer]
+                .saturating_sub(1)
+                .min(res.last_index + 1);
+            self.next_index[peer] = next.max(self.match_index[peer] + 1).max(1);
+        }
+    }
+
+    /// Reconoce a `leader_id` como líder de `term`; `false` si el término es viejo.
+    fn follow(&mut self, term: u64, leader_id: &str, now: u64) -> bool {
+        if term < self.term {
+            return false;
+        }
+        if term > self.term || self.role != Role::Follower {
+            self.step_down(term, now);
+        }
+        self.leader = Some(leader_id.to_string());
+        self.last_heard = Some(now);
+        self.pre_votes = None;
+        self.pre_vote_won = false;
+        self.reset_election(now);
+        true
+    }
+
+    fn append_response(&self, success: bool, last_index: u64) -> AppendResponse {
+        AppendResponse {
+            term: self.term,
+            success,
+            last_index,
+        }
+    }
+
+    fn start_pre_vote(&mut self, now: u64) -> Vec<(usize, Message)> {
+        self.reset_election(now);
+        if self.quorum() == 1 {
+            return self.start_election(now);
+        }
+        self.pre_votes = Some(HashSet::new());
+        self.vote_requests(self.term + 1, true)
+    }
+
+    fn start_election(&mut self, now: u64) -> Vec<(usize, Message)> {
+        self.pre_votes = None;
+        self.term += 1;
+        self.role = Role::Candidate;
+        self.voted_for = Some(self.id.clone());
+        self.leader = None;
+        self.votes.clear();
+        self.dirty = true;
+        self.reset_election(now);
+        if self.quorum() == 1 {
+            self.become_leader(now);
+            return Vec::new();
+        }
+
+        self.vote_requests(self.term, false)
+    }
+
+    fn vote_requests(&self, term: u64, pre: bool) -> Vec<(usize, Message)> {
+        let req = VoteRequest {
+            term,
+            candidate_id: self.id.clone(),
+            last_index: self.log.last_index(),
+            last_term: self.log.last_term(),
+            pre,
+        };
+        (0..self.peers)
+            .map(|p| (p, Message::Vote(req.clone())))
+            .collect()
+    }
+
+    fn become_leader(&mut self, now: u64) {
+        self.role = Role::Leader;
+        self.leader = Some(self.id.clone());
+        self.next_index = vec![self.log.last_index() + 1; self.peers];
+        self.match_index = vec![0; self.peers];
+        self.log.append(Entry {
+            term: self.term,
+            command: Command::Noop,
+        });
+        self.dirty = true;
+        self.next_heartbeat = now;
+        self.advance_commit();
+    }
+
+    fn step_down(&mut self, term: u64, now: u64) {
+        if term > self.term {
+            self.term = term;
+            self.voted_for = None;
+            self.pre_votes = None;
+            self.pre_vote_won = false;
+            self.dirty = true;
+        }
+        if self.role != Role::Follower {
+            self.role = Role::Follower;
+            self.leader = None;
+            self.reset_election(now);
+        }
+    }
+
+    fn reset_election(&mut self, now: u64) {
+        self.election_deadline = now + self.election_ms + fastrand::u64(0..=self.election_ms);
+    }
+
+    /// Confirma la entrada más alta de este término que ya tiene la mayoría; las de
+    /// términos anteriores quedan confirmadas con ella.
+    fn advance_commit(&mut self) {
+        let quorum = self.quorum();
+        let committed = (self.commit + 1..=self.log.last_index()).rev().find(|&n| {
+            self.log.term_at(n) == Some(self.term)
+                && 1 + self.match_index.iter().filter(|m| **m >= n).count() >= quorum
+        });
+        if let Some(commit) = committed {
+            self.commit = commit;
+            self.apply();
+        }
+    }
+
+    fn apply(&mut self) {
+        while self.ledger.applied() < self.commit {
+            let index = self.ledger.applied() + 1;
+            let Some(entry) = self.log.get(index) else {
+                break;
+            };
+            self.ledger.apply(index, &entry.command);
+        }
+        if self.log.entries().len() > self.snapshot_every
+            && self.ledger.applied() > self.log.snapshot_index()
+        {
+            self.log.compact(self.ledger.clone());
+            self.dirty = true;
+        }
+    }
+
+    /// Si el log ya tiene la última entrada de la instantánea (mismo término), lo que le
+    /// sigue se conserva: una instantánea que llega tarde no borra entradas que este master
+    /// ya confirmó al líder (§7). Si no coincide, el log empieza de nuevo desde ella.
+    fn install(&mut self, snapshot: Snapshot) {
+        let last_index = snapshot.last_index();
+        self.commit = last_index;
+        self.ledger = snapshot.ledger.clone();
+        if last_index >= self.log.snapshot_index()
+            && self.log.term_at(last_index) == Some(snapshot.last_term)
+        {
+            self.log.compact(snapshot.ledger);
+        } else {
+            self.log = RaftLog::new(snapshot, Vec::new());
+        }
+        self.dirty = true;
+    }
+}
@@ -0,0 +1,275 @@
+use std::{borrow::Cow, collections::HashMap, fmt, str::FromStr};
+
+use app_net::{codec::Tokenizer, encode_token, tokenize};
+
+use crate::core::domain::models::AppError;
+
+/// Lo que se replica: cada entrada del log le cambia algo a la topología acordada.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Primera entrada de cada líder: al confirmarla quedan confirmadas también las que
+    /// dejaron líderes anteriores.
+    Noop,
+    /// `node_id` pasa a `shard_id` si su shard acordado sigue siendo `expected`; si otra
+    /// entrada lo cambió antes, esta no hace nada. Así dos altas del mismo nodo que el
+    /// líder acepta a la vez no se pisan.
+    Assign {
+        node_id: String,
+        shard_id: String,
+        expected: Option<String>,
+    },
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Command::Noop => f.write_str("noop"),
+            Command::Assign {
+                node_id,
+                shard_id,
+                expected,
+            } => write!(
+                f,
+                "assign {} {} {}",
+                encode_token(node_id),
+                encode_token(shard_id),
+                encode_token(expected.as_deref().unwrap_or_default())
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub term: u64,
+    pub command: Command,
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.term, self.command)
+    }
+}
+
+impl Entry {
+    /// Lee la próxima entrada de `parts`; `Ok(None)` si no quedan más.
+    pub fn read(parts: &mut Tokenizer<'_>) -> Result<Option<Entry>, AppError> {
+        let Some(term) = parts.next() else {
+            return Ok(None);
+        };
+        let term = parse_num(&term)?;
+        let command = match parts.next().as_deref() {
+            Some("noop") => Command::Noop,
+            Some("assign") => {
+                let mut arg = || next_token(parts).map(Cow::into_owned);
+                let (node_id, shard_id, expected) = (arg()?, arg()?, arg()?);
+                Command::Assign {
+                    node_id,
+                    shard_id,
+                    expected: Some(expected).filter(|e| !e.is_empty()),
+                }
+            }
+            other => {
+                return Err(AppError::BadRequest(format!(
+                    "entrada de Raft desconocida: {other:?}"
+                )));
+            }
+        };
+        Ok(Some(Entry { term, command }))
+    }
+}
+
+pub(super) fn next_token<'a>(parts: &mut Tokenizer<'a>) -> Result<Cow<'a, str>, AppError> {
+    parts
+        .next()
+        .ok_or_else(|| AppError::BadRequest("faltan argumentos de Raft".to_string()))
+}
+
+pub(super) fn parse_num(token: &str) -> Result<u64, AppError> {
+    token
+        .parse()
+        .map_err(|_| AppError::BadRequest(format!("número inválido: {token}")))
+}
+
+/// La máquina de estados que replica Raft: por nodo, el shard acordado y el índice del
+/// log en que se acordó, más el último índice aplicado. Viaja (en `PEER-STATE` y en las
+/// instantáneas) como `<aplicado> "<nodo>" "<shard>" <índice>...`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TopologyLedger {
+    applied: u64,
+    assignments: HashMap<String, (String, u64)>,
+}
+
+impl TopologyLedger {
+    pub fn applied(&self) -> u64 {
+        self.applied
+    }
+
+    pub fn len(&self) -> usize {
+        self.assignments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.assignments.is_empty()
+    }
+
+    pub fn shard_of(&self, node_id: &str) -> Option<&str> {
+        self.assignments
+            .get(node_id)
+            .map(|(shard_id, _)| shard_id.as_str())
+    }
+
+    /// Cada nodo con su shard acordado.
+    pub fn assignments(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.assignments
+            .iter()
+            .map(|(node_id, (shard_id, _))| (node_id.as_str(), shard_id.as_str()))
+    }
+
+    /// Aplica la entrada `index`. Es determinístico: todos los masters que aplican el
+    /// mismo log llegan al mismo estado.
+    pub fn apply(&mut self, index: u64, command: &Command) {
+        self.applied = index;
+        if let Command::Assign {
+            node_id,
+            shard_id,
+            expected,
+        } = command
+            && self.shard_of(node_id) == expected.as_deref()
+        {
+            self.assignments
+                .insert(node_id.clone(), (shard_id.clone(), index));
+        }
+    }
+}
+
+impl fmt::Display for TopologyLedger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.applied)?;
+        let mut assignments: Vec<_> = self.assignments.iter().collect();
+        assignments.sort();
+        for (node_id, (shard_id, index)) in assignments {
+            write!(
+                f,
+                " {} {} {index}",
+                encode_token(node_id),
+                encode_token(shard_id)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for TopologyLedger {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = tokenize(s);
+        let mut ledger = TopologyLedger {
+            applied: parse_num(&next_token(&mut parts)?)?,
+            assignments: HashMap::new(),
+        };
+        while let Some(node_id) = parts.next() {
+            let shard_id = next_token(&mut parts)?;
+            let index = parse_num(&next_token(&mut parts)?)?;
+            ledger
+                .assignments
+                .insert(node_id.into_owned(), (shard_id.into_owned(), index));
+        }
+        Ok(ledger)
+    }
+}
+
+/// Estado aplicado hasta un índice, que reemplaza a las entradas que cubre.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    pub last_term: u64,
+    pub ledger: TopologyLedger,
+}
+
+impl Snapshot {
+    pub fn last_index(&self) -> u64 {
+        self.ledger.applied()
+    }
+}
+
+/// El log de Raft desde la última instantánea: `entries[i]` es el índice
+/// `snapshot.last_index() + 1 + i`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RaftLog {
+    snapshot: Snapshot,
+    entries: Vec<Entry>,
+}
+
+impl RaftLog {
+    pub fn new(snapshot: Snapshot, entries: Vec<Entry>) -> Self {
+        Self { snapshot, entries }
+    }
+
+    pub fn snapshot(&self) -> &Snapshot {
+        &self.snapshot
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub fn snapshot_index(&self) -> u64 {
+        self.snapshot.last_index()
+    }
+
+    pub fn last_index(&self) -> u64 {
+        self.snapshot_index() + self.entries.len() as u64
+    }
+
+    pub fn last_term(&self) -> u64 {
+        self.entries
+            .last()
+            .map_or(self.snapshot.last_term, |entry| entry.term)
+    }
+
+    /// Término de la entrada `index`; `None` si no está o quedó dentro de la instantánea.
+    pub fn term_at(&self, index: u64) -> Option<u64> {
+        if index == self.snapshot_index() {
+            return Some(self.snapshot.last_term);
+        }
+        self.get(index).map(|entry| entry.term)
+    }
+
+    pub fn get(&self, index: u64) -> Option<&Entry> {
+        let offset = index.checked_sub(self.snapshot_index() + 1)?;
+        self.entries.get(offset as usize)
+    }
+
+    /// Hasta `max` entradas desde `index`.
+    pub fn entries_from(&self, index: u64, max: usize) -> Vec<Entry> {
+        let offset = index.saturating_sub(self.snapshot_index() + 1) as usize;
+        self.entries
+            .iter()
+            .skip(offset)
+            .take(max)
+            .cloned()
+            .collect()
+    }
+
+    pub fn append(&mut self, entry: Entry) -> u64 {
+        self.entries.push(entry);
+        self.last_index()
+    }
+
+    /// Descarta `index` y todo lo que le sigue.
+    pub fn truncate_from(&mut self, index: u64) {
+        let offset = index.saturating_sub(self.snapshot_index() + 1) as usize;
+        self.entries.truncate(offset);
+    }
+
+    /// Reemplaza por `ledger` las entradas hasta `ledger.applied()`, que ya se aplicaron.
+    pub fn compact(&mut self, ledger: TopologyLedger) {
+        let Some(last_term) = self.term_at(ledger.applied()) else {
+            return;
+        };
+        let covered = (ledger.applied() - self.snapshot_index()) as usize;
+        self.entries.drain(..covered.min(self.entries.len()));
+        self.snapshot = Snapshot { last_term, ledger };
+    }
+}
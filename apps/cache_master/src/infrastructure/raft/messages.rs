@@ -0,0 +1,242 @@
+use std::{fmt, str::FromStr};
+
+use app_net::{encode_token, tokenize};
+
+use crate::{
+    core::domain::models::AppError,
+    infrastructure::raft::log::{Entry, Snapshot, TopologyLedger, next_token, parse_num},
+};
+
+pub const RAFT_VOTE: &str = "RAFT-VOTE";
+pub const RAFT_APPEND: &str = "RAFT-APPEND";
+pub const RAFT_SNAPSHOT: &str = "RAFT-SNAPSHOT";
+
+/// `RAFT-VOTE <término> "<candidato>" <último índice> <último término> [pre]`. Con `pre`
+/// es un sondeo: el que contesta no cambia nada, solo dice si votaría.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VoteRequest {
+    pub term: u64,
+    pub candidate_id: String,
+    pub last_index: u64,
+    pub last_term: u64,
+    pub pre: bool,
+}
+
+/// `<término> <1|0> [pre]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VoteResponse {
+    pub term: u64,
+    pub granted: bool,
+    pub pre: bool,
+}
+
+/// `RAFT-APPEND <término> "<líder>" <índice previo> <término previo> <confirmado>
+/// [<término> <entrada>]...`; sin entradas es un latido.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppendRequest {
+    pub term: u64,
+    pub leader_id: String,
+    pub prev_index: u64,
+    pub prev_term: u64,
+    pub leader_commit: u64,
+    pub entries: Vec<Entry>,
+}
+
+/// `RAFT-SNAPSHOT <término> "<líder>" <término de la instantánea> <ledger>`: para el que
+/// quedó tan atrás que el líder ya compactó las entradas que le faltan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotRequest {
+    pub term: u64,
+    pub leader_id: String,
+    pub snapshot: Snapshot,
+}
+
+/// Respuesta a `RAFT-APPEND` y `RAFT-SNAPSHOT`: `<término> <1|0> <índice>`. Si salió
+/// bien, `last_index` es hasta dónde el log coincide con el del líder; si no, desde
+/// dónde conviene reintentar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AppendResponse {
+    pub term: u64,
+    pub success: bool,
+    pub last_index: u64,
+}
+
+/// Lo que un master le manda a otro (ver `RaftCore::tick`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    Vote(VoteRequest),
+    Append(AppendRequest),
+    Snapshot(SnapshotRequest),
+}
+
+impl Message {
+    /// Acción y payload con los que viaja.
+    pub fn action(&self) -> &'static str {
+        match self {
+            Message::Vote(_) => RAFT_VOTE,
+            Message::Append(_) => RAFT_APPEND,
+            Message::Snapshot(_) => RAFT_SNAPSHOT,
+        }
+    }
+
+    pub fn payload(&self) -> String {
+        match self {
+            Message::Vote(req) => req.to_string(),
+            Message::Append(req) => req.to_string(),
+            Message::Snapshot(req) => req.to_string(),
+        }
+    }
+}
+
+fn flag(token: &str) -> Result<bool, AppError> {
+    match token {
+        "1" => Ok(true),
+        "0" => Ok(false),
+        other => Err(AppError::BadRequest(format!("se esperaba 1 o 0: {other}"))),
+    }
+}
+
+impl fmt::Display for VoteRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}{}",
+            self.term,
+            encode_token(&self.candidate_id),
+            self.last_index,
+            self.last_term,
+            if self.pre { " pre" } else { "" }
+        )
+    }
+}
+
+impl FromStr for VoteRequest {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = tokenize(s);
+        Ok(VoteRequest {
+            term: parse_num(&next_token(&mut parts)?)?,
+            candidate_id: next_token(&mut parts)?.into_owned(),
+            last_index: parse_num(&next_token(&mut parts)?)?,
+            last_term: parse_num(&next_token(&mut parts)?)?,
+            pre: parts.next().as_deref() == Some("pre"),
+        })
+    }
+}
+
+impl fmt::Display for VoteResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.term, u8::from(self.granted))?;
+        if self.pre {
+            f.write_str(" pre")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for VoteResponse {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = tokenize(s);
+        Ok(VoteResponse {
+            term: parse_num(&next_token(&mut parts)?)?,
+            granted: flag(&next_token(&mut parts)?)?,
+            pre: parts.next().as_deref() == Some("pre"),
+        })
+    }
+}
+
+impl fmt::Display for AppendRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {}",
+            self.term,
+            encode_token(&self.leader_id),
+            self.prev_index,
+            self.prev_term,
+            self.leader_commit
+        )?;
+        for entry in &self.entries {
+            write!(f, " {entry}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for AppendRequest {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = tokenize(s);
+        let mut req = AppendRequest {
+            term: parse_num(&next_token(&mut parts)?)?,
+            leader_id: next_token(&mut parts)?.into_owned(),
+            prev_index: parse_num(&next_token(&mut parts)?)?,
+            prev_term: parse_num(&next_token(&mut parts)?)?,
+            leader_commit: parse_num(&next_token(&mut parts)?)?,
+            entries: Vec::new(),
+        };
+        while let Some(entry) = Entry::read(&mut parts)? {
+            req.entries.push(entry);
+        }
+        Ok(req)
+    }
+}
+
+impl fmt::Display for SnapshotRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.term,
+            encode_token(&self.leader_id),
+            self.snapshot.last_term,
+            self.snapshot.ledger
+        )
+    }
+}
+
+impl FromStr for SnapshotRequest {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = tokenize(s);
+        let term = parse_num(&next_token(&mut parts)?)?;
+        let leader_id = next_token(&mut parts)?.into_owned();
+        let last_term = parse_num(&next_token(&mut parts)?)?;
+        let ledger: TopologyLedger = parts.rest().unwrap_or_default().parse()?;
+        Ok(SnapshotRequest {
+            term,
+            leader_id,
+            snapshot: Snapshot { last_term, ledger },
+        })
+    }
+}
+
+impl fmt::Display for AppendResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.term,
+            u8::from(self.success),
+            self.last_index
+        )
+    }
+}
+
+impl FromStr for AppendResponse {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = tokenize(s);
+        Ok(AppendResponse {
+            term: parse_num(&next_token(&mut parts)?)?,
+            success: flag(&next_token(&mut parts)?)?,
+            last_index: parse_num(&next_token(&mut parts)?)?,
+        })
+    }
+}
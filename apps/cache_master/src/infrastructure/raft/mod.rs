@@ -0,0 +1,21 @@
+//! Raft entre los masters de `PEER_MASTERS`, para que la topología acordada (en qué shard
+//! está cada nodo) sobreviva a la caída de cualquiera de ellos. Es lo justo de Raft para
+//! un estado chico: elección de líder, replicación del log, instantáneas para el que quedó
+//! muy atrás y el estado en disco con `RAFT_DIR`. Los miembros son fijos (los de
+//! `PEER_MASTERS` más este): no hay cambios de configuración en caliente.
+//!
+//! `RaftCore` es la máquina de Raft sin red ni reloj; la maneja `peers::PeerCoordinator`,
+//! que manda sus mensajes por las conexiones a los otros masters.
+
+mod core;
+mod log;
+mod messages;
+mod storage;
+
+pub use core::{Outcome, RaftCore, Role};
+pub use log::{Command, Entry, RaftLog, Snapshot, TopologyLedger};
+pub use messages::{
+    AppendRequest, AppendResponse, Message, RAFT_APPEND, RAFT_SNAPSHOT, RAFT_VOTE, SnapshotRequest,
+    VoteRequest, VoteResponse,
+};
+pub use storage::{PersistentState, RaftStorage};
@@ -0,0 +1,157 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use app_net::{encode_token, tokenize};
+use tokio::{fs::File, io::AsyncWriteExt, sync::Mutex};
+
+use crate::{
+    core::domain::models::AppError,
+    infrastructure::raft::log::{Entry, RaftLog, Snapshot, next_token, parse_num},
+};
+
+const STATE_FILE: &str = "raft.state";
+
+/// Lo que Raft tiene que recordar al reiniciar: sin esto un master podría votar dos veces
+/// en el mismo término u olvidar entradas que ayudó a confirmar. Se guarda como
+///
+/// ```text
+/// term <término> "<votado>"
+/// snapshot <término> <ledger>
+/// <término> <entrada>
+/// ...
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PersistentState {
+    pub term: u64,
+    pub voted_for: Option<String>,
+    pub log: RaftLog,
+}
+
+impl fmt::Display for PersistentState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let snapshot = self.log.snapshot();
+        writeln!(
+            f,
+            "term {} {}",
+            self.term,
+            encode_token(self.voted_for.as_deref().unwrap_or_default())
+        )?;
+        writeln!(f, "snapshot {} {}", snapshot.last_term, snapshot.ledger)?;
+        for entry in self.log.entries() {
+            writeln!(f, "{entry}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for PersistentState {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = |what: &str| AppError::Storage(format!("estado de Raft inválido: {what}"));
+        let mut lines = s.lines().filter(|line| !line.trim().is_empty());
+
+        let mut head = tokenize(lines.next().ok_or_else(|| bad("vacío"))?);
+        if head.next().as_deref() != Some("term") {
+            return Err(bad("falta term"));
+        }
+        let term = parse_num(&next_token(&mut head)?)?;
+        let voted_for = Some(next_token(&mut head)?.into_owned()).filter(|v| !v.is_empty());
+
+        let mut snapshot = tokenize(lines.next().ok_or_else(|| bad("falta snapshot"))?);
+        if snapshot.next().as_deref() != Some("snapshot") {
+            return Err(bad("falta snapshot"));
+        }
+        let last_term = parse_num(&next_token(&mut snapshot)?)?;
+        let ledger = snapshot.rest().unwrap_or_default().parse()?;
+
+        let mut entries = Vec::new();
+        for line in lines {
+            let entry = Entry::read(&mut tokenize(line))?.ok_or_else(|| bad(line))?;
+            entries.push(entry);
+        }
+        Ok(PersistentState {
+            term,
+            voted_for,
+            log: RaftLog::new(Snapshot { last_term, ledger }, entries),
+        })
+    }
+}
+
+/// El estado de Raft en `RAFT_DIR`. Cada versión se escribe entera en un archivo temporal
+/// y se renombra, así que un corte a mitad de camino deja la anterior. `save` no vuelve
+/// hasta que el archivo y el rename están en disco (fsync del archivo y del directorio):
+/// recién entonces se contesta el voto o el append que lo cambió.
+pub struct RaftStorage {
+    path: PathBuf,
+    /// Última versión escrita: dos guardados que se cruzan no dejan la más vieja.
+    written: Mutex<u64>,
+}
+
+impl RaftStorage {
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            path: dir.as_ref().join(STATE_FILE),
+            written: Mutex::new(0),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// El estado guardado; el inicial si todavía no hay nada.
+    pub fn load(&self) -> Result<PersistentState, AppError> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(text) => text.parse(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(PersistentState::default()),
+            Err(e) => Err(io_error(&self.path, e)),
+        }
+    }
+
+    /// Guarda `state` si `version` es más nueva que la última escrita.
+    pub async fn save(&self, version: u64, state: &PersistentState) -> Result<(), AppError> {
+        let mut written = self.written.lock().await;
+        if version <= *written {
+            return Ok(());
+        }
+        if let Some(dir) = self.path.parent() {
+            tokio::fs::create_dir_all(dir)
+                .await
+                .map_err(|e| io_error(dir, e))?;
+        }
+        let tmp = self.path.with_extension("tmp");
+        let mut file = File::create(&tmp).await.map_err(|e| io_error(&tmp, e))?;
+        file.write_all(state.to_string().as_bytes())
+            .await
+            .map_err(|e| io_error(&tmp, e))?;
+        file.sync_all().await.map_err(|e| io_error(&tmp, e))?;
+        drop(file);
+        tokio::fs::rename(&tmp, &self.path)
+            .await
+            .map_err(|e| io_error(&self.path, e))?;
+        // sin esto el rename puede perderse en un corte y volver la versión anterior
+        if let Some(dir) = self.path.parent() {
+            let dir_path = if dir.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                dir
+            };
+            File::open(dir_path)
+                .await
+                .map_err(|e| io_error(dir_path, e))?
+                .sync_all()
+                .await
+                .map_err(|e| io_error(dir_path, e))?;
+        }
+        *written = version;
+        Ok(())
+    }
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> AppError {
+    AppError::Storage(format!("{}: {e}", path.display()))
+}
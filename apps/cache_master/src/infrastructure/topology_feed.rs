@@ -0,0 +1,77 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicU64, Ordering},
+};
+
+use app_net::{EventData, Socket, TopologyChange, TopologyEvent, event::TOPOLOGY};
+use dashmap::DashMap;
+use tracing::debug;
+
+use crate::infrastructure::decision_log::DecisionLog;
+
+/// Conexiones suscritas con `SUBSCRIBE "TOPOLOGY"`: cada cambio de topología les llega
+/// como `EVT TOPOLOGY` (ver `app_net::TopologyEvent`), para que clientes y gateways
+/// rearmen su ruteo sin esperar a que un request falle.
+#[derive(Default)]
+pub struct TopologyFeed {
+    subscribers: DashMap<Arc<str>, Arc<Socket>>,
+    /// Último `epoch` publicado.
+    epoch: AtomicU64,
+    decision_log: Option<Arc<DecisionLog>>,
+}
+
+impl TopologyFeed {
+    pub fn new_shared() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Anota cada aviso en `log`, que desde ahí usa su `epoch` (ver `DecisionLog`).
+    pub fn with_decision_log(mut self, log: Arc<DecisionLog>) -> Self {
+        self.decision_log = Some(log);
+        self
+    }
+
+    /// Suscribe la conexión `peer_id` (otra vez no cambia nada) y devuelve el `epoch`
+    /// actual: el próximo aviso trae el siguiente.
+    pub fn subscribe(&self, peer_id: Arc<str>, socket: Arc<Socket>) -> u64 {
+        self.subscribers.insert(peer_id, socket);
+        self.epoch()
+    }
+
+    pub fn unsubscribe(&self, peer_id: &str) -> bool {
+        self.subscribers.remove(peer_id).is_some()
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch.load(Ordering::Acquire)
+    }
+
+    pub fn subscribers(&self) -> usize {
+        self.subscribers.len()
+    }
+
+    /// Numera `change` y se lo manda a cada suscrito; el que ya no tiene conexión se
+    /// descarta. El `epoch` sube aunque no haya nadie escuchando.
+    pub fn publish(&self, change: TopologyChange) {
+        let event = TopologyEvent {
+            epoch: self.epoch.fetch_add(1, Ordering::AcqRel) + 1,
+            change,
+        };
+        if let Some(log) = &self.decision_log {
+            log.topology(&event);
+        }
+        if self.subscribers.is_empty() {
+            return;
+        }
+
+        let payload = event.to_string();
+        let evt = EventData::new(TOPOLOGY, payload.as_str());
+        self.subscribers.retain(|peer_id, socket| {
+            let sent = socket.send_evt(&evt).is_ok();
+            if !sent {
+                debug!(%peer_id, "suscripción a TOPOLOGY sin conexión");
+            }
+            sent
+        });
+    }
+}
@@ -0,0 +1,427 @@
+//! Reintentos de las escrituras que un shard confirmó pero alguno de sus nodos no (caído,
+//! sin contestar a tiempo u ocupado). Sin esto la réplica que se perdió el `PUT` sirve el
+//! valor anterior hasta que la clave se vuelva a escribir o venza. Las que agotan los
+//! intentos quedan como dead letters, a la vista con `WRITE-RETRIES`, para repararlas a
+//! mano.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    env,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use app_core::clock::Clock;
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use tokio::{
+    task::JoinSet,
+    time::{self, MissedTickBehavior},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
+
+use crate::infrastructure::di::CacheMasterModule;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteRetryConfig {
+    /// Escrituras pendientes a la vez; con la cola llena, la que falla va directo a dead
+    /// letter. 0 no reintenta nada.
+    pub capacity: usize,
+    /// Intentos antes de pasar a dead letter.
+    pub max_attempts: u32,
+    /// Espera antes del primer reintento; se duplica en cada uno hasta `max_delay`.
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Dead letters que se guardan; pasado el tope se descartan las más viejas.
+    pub dead_letters: usize,
+    /// Cada cuánto se buscan reintentos vencidos.
+    pub check_interval: Duration,
+    /// Cuánto se recuerda la última escritura que salió de cada clave. Tiene que cubrir lo
+    /// que tarda en llegar el resultado de un nodo lento, para que su fallo no se encole
+    /// después de una escritura más nueva de la misma clave.
+    pub horizon: Duration,
+}
+
+impl Default for WriteRetryConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 10_000,
+            max_attempts: 5,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(10),
+            dead_letters: 1_000,
+            check_interval: Duration::from_millis(100),
+            horizon: Duration::from_secs(30),
+        }
+    }
+}
+
+impl WriteRetryConfig {
+    /// `WRITE_RETRY_CAPACITY` (10000 por defecto, 0 desactiva), `WRITE_RETRY_ATTEMPTS` (5)
+    /// y `WRITE_RETRY_BASE_MS` (200).
+    pub fn from_env() -> Self {
+        let number = |var: &str| env::var(var).ok().and_then(|v| v.parse::<u64>().ok());
+        let mut config = Self::default();
+        if let Some(capacity) = number("WRITE_RETRY_CAPACITY") {
+            config.capacity = capacity as usize;
+        }
+        if let Some(attempts) = number("WRITE_RETRY_ATTEMPTS").filter(|n| *n > 0) {
+            config.max_attempts = attempts as u32;
+        }
+        if let Some(ms) = number("WRITE_RETRY_BASE_MS").filter(|ms| *ms > 0) {
+            config.base_delay = Duration::from_millis(ms);
+        }
+        config
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    /// Espera antes del intento `attempt` (contando desde 1).
+    fn delay(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+            .min(self.max_delay)
+    }
+}
+
+/// Un `PUT` que `node_id` no confirmó. `version` numera las escrituras del master: un
+/// reintento nunca pisa una escritura posterior de la misma clave en el mismo nodo.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissedWrite {
+    pub node_id: Arc<str>,
+    pub key: String,
+    pub version: u64,
+    /// El `PUT` tal como salió hacia el shard.
+    pub payload: Arc<str>,
+    pub attempts: u32,
+    pub last_error: String,
+}
+
+struct Pending {
+    write: MissedWrite,
+    due_ms: u64,
+    in_flight: bool,
+}
+
+#[derive(Default)]
+struct State {
+    pending: HashMap<(Arc<str>, String), Pending>,
+    dead: VecDeque<MissedWrite>,
+}
+
+impl State {
+    fn bury(&mut self, write: MissedWrite, max: usize) {
+        warn!(
+            node_id = %write.node_id,
+            key = %write.key,
+            attempts = write.attempts,
+            error = %write.last_error,
+            "escritura sin confirmar pasa a dead letter"
+        );
+        self.dead
+            .retain(|dead| !(dead.node_id == write.node_id && dead.key == write.key));
+        if self.dead.len() >= max {
+            self.dead.pop_front();
+        }
+        self.dead.push_back(write);
+    }
+}
+
+/// Cola acotada de escrituras a reintentar y sus dead letters.
+pub struct WriteRetries {
+    config: WriteRetryConfig,
+    clock: Arc<dyn Clock>,
+    next_version: AtomicU64,
+    /// Clave -> (versión, ms) de la última escritura que salió, durante `horizon`.
+    started: DashMap<String, (u64, u64)>,
+    last_prune_ms: AtomicU64,
+    /// Pendientes más dead letters; en cero, `confirmed` no toma el lock.
+    tracked: AtomicUsize,
+    state: Mutex<State>,
+}
+
+impl WriteRetries {
+    pub fn new(config: WriteRetryConfig, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            config,
+            clock,
+            next_version: AtomicU64::new(1),
+            started: DashMap::new(),
+            last_prune_ms: AtomicU64::new(0),
+            tracked: AtomicUsize::new(0),
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    pub fn config(&self) -> &WriteRetryConfig {
+        &self.config
+    }
+
+    /// La versión de la escritura de `key` que está por salir.
+    pub fn next_version(&self, key: &str) -> u64 {
+        let version = self.next_version.fetch_add(1, Ordering::Relaxed);
+        if self.config.enabled() {
+            let now = self.now();
+            self.started
+                .entry(key.to_string())
+                .and_modify(|started| *started = (version.max(started.0), now))
+                .or_insert((version, now));
+        }
+        version
+    }
+
+    /// Ya salió una escritura de `key` posterior a `version`: esa manda, con su propio
+    /// reintento si le hace falta.
+    fn superseded(&self, key: &str, version: u64) -> bool {
+        self.started
+            .get(key)
+            .is_some_and(|started| started.0 > version)
+    }
+
+    /// `node_id` no confirmó la escritura `version` de `key`. Se descarta si ya salió una
+    /// escritura más nueva de la clave.
+    pub fn missed(
+        &self,
+        node_id: &Arc<str>,
+        key: &str,
+        version: u64,
+        payload: &Arc<str>,
+        error: String,
+    ) {
+        if !self.config.enabled() {
+            return;
+        }
+        if self.superseded(key, version) {
+            return;
+        }
+        let mut state = self.state.lock();
+        let slot = (node_id.clone(), key.to_string());
+        if let Some(pending) = state.pending.get(&slot)
+            && pending.write.version >= version
+        {
+            return;
+        }
+
+        let write = MissedWrite {
+            node_id: node_id.clone(),
+            key: key.to_string(),
+            version,
+            payload: payload.clone(),
+            attempts: 0,
+            last_error: error,
+        };
+        if !state.pending.contains_key(&slot) && state.pending.len() >= self.config.capacity {
+            let write = MissedWrite {
+                last_error: format!("cola de reintentos llena: {}", write.last_error),
+                ..write
+            };
+            state.bury(write, self.config.dead_letters);
+            self.sync(&state);
+            return;
+        }
+        debug!(%node_id, key, version, "escritura sin confirmar: se reintenta");
+        let due_ms = self.now() + self.config.delay(1).as_millis() as u64;
+        state.pending.insert(
+            slot,
+            Pending {
+                write,
+                due_ms,
+                in_flight: false,
+            },
+        );
+        self.sync(&state);
+    }
+
+    /// `node_id` confirmó la escritura `version` de `key`: lo anterior pendiente de esa
+    /// clave en ese nodo ya no hace falta.
+    pub fn confirmed(&self, node_id: &Arc<str>, key: &str, version: u64) {
+        if self.tracked.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        let mut state = self.state.lock();
+        let slot = (node_id.clone(), key.to_string());
+        if state
+            .pending
+            .get(&slot)
+            .is_some_and(|pending| pending.write.version <= version)
+        {
+            state.pending.remove(&slot);
+        }
+        state.dead.retain(|dead| {
+            !(dead.node_id == *node_id && dead.key == key && dead.version <= version)
+        });
+        self.sync(&state);
+    }
+
+    /// Los reintentos que ya tocan; quedan en vuelo hasta su `retried`. Los que quedaron
+    /// viejos por una escritura posterior de la clave se descartan.
+    pub fn due(&self) -> Vec<MissedWrite> {
+        let now = self.now();
+        self.prune_started(now);
+        let mut state = self.state.lock();
+        state
+            .pending
+            .retain(|(_, key), pending| !self.superseded(key, pending.write.version));
+        let due = state
+            .pending
+            .values_mut()
+            .filter(|pending| !pending.in_flight && pending.due_ms <= now)
+            .map(|pending| {
+                pending.in_flight = true;
+                pending.write.clone()
+            })
+            .collect();
+        self.sync(&state);
+        due
+    }
+
+    /// Resultado del reintento de `write`. Si mientras tanto llegó una escritura más nueva
+    /// de la clave, el resultado no cambia nada.
+    pub fn retried(&self, write: &MissedWrite, result: Result<(), String>) {
+        let mut state = self.state.lock();
+        let slot = (write.node_id.clone(), write.key.clone());
+        let Some(pending) = state
+            .pending
+            .get_mut(&slot)
+            .filter(|pending| pending.write.version == write.version)
+        else {
+            return;
+        };
+
+        let error = match result {
+            Ok(()) => {
+                state.pending.remove(&slot);
+                self.sync(&state);
+                return;
+            }
+            Err(error) => error,
+        };
+        pending.write.attempts += 1;
+        pending.write.last_error = error;
+        pending.in_flight = false;
+        if pending.write.attempts >= self.config.max_attempts {
+            if let Some(pending) = state.pending.remove(&slot) {
+                state.bury(pending.write, self.config.dead_letters);
+            }
+            self.sync(&state);
+            return;
+        }
+        let delay = self.config.delay(pending.write.attempts + 1);
+        pending.due_ms = self.now() + delay.as_millis() as u64;
+    }
+
+    pub fn pending_len(&self) -> usize {
+        self.state.lock().pending.len()
+    }
+
+    /// Las dead letters, de la más vieja a la más nueva.
+    pub fn dead_letters(&self) -> Vec<MissedWrite> {
+        self.state.lock().dead.iter().cloned().collect()
+    }
+
+    /// Vuelve a encolar las dead letters con los intentos en cero; devuelve cuántas.
+    pub fn requeue_dead(&self) -> usize {
+        let now = self.now();
+        let mut state = self.state.lock();
+        let dead = std::mem::take(&mut state.dead);
+        let mut requeued = 0;
+        for write in dead {
+            if self.superseded(&write.key, write.version) {
+                continue;
+            }
+            let slot = (write.node_id.clone(), write.key.clone());
+            if state
+                .pending
+                .get(&slot)
+                .is_some_and(|pending| pending.write.version >= write.version)
+            {
+                continue;
+            }
+            requeued += 1;
+            state.pending.insert(
+                slot,
+                Pending {
+                    write: MissedWrite {
+                        attempts: 0,
+                        ..write
+                    },
+                    due_ms: now,
+                    in_flight: false,
+                },
+            );
+        }
+        self.sync(&state);
+        requeued
+    }
+
+    /// Descarta las dead letters; devuelve cuántas había.
+    pub fn clear_dead(&self) -> usize {
+        let mut state = self.state.lock();
+        let cleared = state.dead.len();
+        state.dead.clear();
+        self.sync(&state);
+        cleared
+    }
+
+    fn sync(&self, state: &State) {
+        self.tracked
+            .store(state.pending.len() + state.dead.len(), Ordering::Relaxed);
+    }
+
+    /// Olvida, a lo sumo una vez por `horizon`, las claves sin escrituras en ese lapso.
+    fn prune_started(&self, now: u64) {
+        let horizon = self.config.horizon.as_millis() as u64;
+        let last = self.last_prune_ms.load(Ordering::Relaxed);
+        if now < last + horizon
+            || self
+                .last_prune_ms
+                .compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed)
+                .is_err()
+        {
+            return;
+        }
+        self.started.retain(|_, started| started.1 + horizon > now);
+    }
+
+    fn now(&self) -> u64 {
+        self.clock.now_millis().as_millis_u64()
+    }
+}
+
+/// Cada `check_interval` vuelve a mandar a su nodo los `PUT` pendientes que ya tocan, hasta
+/// que se cancele `token`.
+pub async fn retry_writes(module: Arc<CacheMasterModule>, token: CancellationToken) {
+    let retries = module.write_retries.clone();
+    let mut interval = time::interval(retries.config().check_interval);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            _ = token.cancelled() => break,
+            _ = interval.tick() => {}
+        }
+
+        let mut set = JoinSet::new();
+        for write in retries.due() {
+            let network = module.tcp_network_service.clone();
+            set.spawn(async move {
+                let result = network
+                    .put_to_node(&write.node_id, &write.payload)
+                    .await
+                    .map_err(|e| e.to_string());
+                (write, result)
+            });
+        }
+        while let Some(joined) = set.join_next().await {
+            if let Ok((write, result)) = joined {
+                retries.retried(&write, result);
+            }
+        }
+    }
+}
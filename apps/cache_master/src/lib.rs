@@ -0,0 +1,4 @@
+pub mod core;
+pub mod infrastructure;
+pub mod server;
+pub mod tests;
@@ -1,66 +1,27 @@
-use std::{env, net::SocketAddr, str::FromStr, sync::Arc, time::Duration};
-
-use app_core::UseCaseValidatable;
-use bytes::Bytes;
-use tokio::{
-    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
-    net::{TcpListener, TcpStream},
-    sync::mpsc,
-};
-use tracing::{error, info};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
-use uuid::Uuid;
-
-use app_net::{
-    ParsedMsg, ResponseData, Socket, SocketError, parse_line,
-    request::{RequestData, data::RequestDataOwned},
-    types::SocketResult,
+use std::{
+    env,
+    net::{Ipv4Addr, SocketAddr},
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
 };
 
-use crate::{
-    core::domain::models::{
-        AppError, EntryNode, NodeType,
-        usecases::{RemoveNodeUseCaseInput, assign_node_use_case::AssignNodeUseCaseInput},
-    },
-    infrastructure::{
-        adapters::controllers::request_controller::RequestController,
-        app_state::{AppNetworkNode, AppState},
-        di::CacheMasterModule,
-    },
+use app_core::supervisor::{ShutdownStage, Supervisor};
+use app_net::ClusterTls;
+use tokio::net::TcpListener;
+use tracing::info;
+
+use cache_master::{
+    core::domain::models::AppError,
+    infrastructure::{di::config::MasterConfig, hot_keys::HotKeyConfig},
+    server::{self, MasterHandle},
 };
 
-pub mod core;
-pub mod infrastructure;
-pub mod tests;
-
-async fn handle_request_async(
-    request_controller: Arc<RequestController>,
-    socket: Arc<Socket>,
-    data: RequestData<'_>,
-) {
-    let data = RequestDataOwned::from(data);
-
-    let request_controller = request_controller.clone();
-    tokio::spawn(async move {
-        let reply = request_controller
-            .handle_request(&data.action, &data.payload)
-            .await;
-
-        let response = if let Ok(reply) = reply {
-            ResponseData::new(data.id, 200, reply)
-        } else {
-            ResponseData::new(data.id, 500, format!("ERROR {}", reply.err().unwrap()))
-        };
-
-        let _ = socket.send_res(response);
-    });
-}
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
 
 #[tokio::main]
 async fn main() -> Result<(), AppError> {
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    let log = app_logging::init_logging();
 
     let port = env::var("PORT")
         .ok()
@@ -75,160 +36,146 @@ async fn main() -> Result<(), AppError> {
 
     info!("App listen in: {:?}", listener.local_addr().unwrap());
 
-    let app_state = AppState::new_shared();
-    let module_dependencies = Arc::new(CacheMasterModule::build_from_state(app_state.clone()));
-    let request_controller = Arc::new(RequestController::new(module_dependencies.clone()));
-
-    /*
-    let service = module_dependencies.tcp_network_service.clone();
-    //let app_state_clone = app_state.clone();
+    let supervisor = Supervisor::new_shared();
+    let config = MasterConfig::from_env()?;
+    let handle = server::start_with_config(listener, &supervisor, &config);
+
+    // CLUSTER_PORT: puerto con TLS mutuo para los nodos (certificados en TLS_CERT, TLS_KEY y
+    // TLS_CA); con él abierto, los nodos ya no pueden registrarse por PORT. CLUSTER_QUIC_PORT
+    // es lo mismo sobre QUIC (UDP), para los nodos de otro datacenter; puede ir con o sin
+    // CLUSTER_PORT, y solo con la feature `quic`
+    let port_var = |name: &str| env::var(name).ok().and_then(|p| p.parse::<u16>().ok());
+    let (cluster_port, quic_port) = (port_var("CLUSTER_PORT"), port_var("CLUSTER_QUIC_PORT"));
+    if cluster_port.is_some() || quic_port.is_some() {
+        let tls_error = |e| AppError::SocketError(format!("TLS: {e}"));
+        let tls = ClusterTls::from_env().map_err(tls_error)?.ok_or_else(|| {
+            AppError::SocketError(
+                "CLUSTER_PORT y CLUSTER_QUIC_PORT necesitan TLS_CERT, TLS_KEY y TLS_CA".into(),
+            )
+        })?;
 
-    //let modules_dependencies_clone = module_dependencies.clone();
-    tokio::spawn(async move {
-        let mut interval = time::interval(Duration::from_secs(10));
+        if let Some(cluster_port) = cluster_port {
+            let cluster_listener = TcpListener::bind(format!("0.0.0.0:{cluster_port}"))
+                .await
+                .map_err(|e| AppError::SocketError(format!("bind error: {e}")))?;
+
+            info!(
+                "Cluster (TLS mutuo) in: {:?}",
+                cluster_listener.local_addr().unwrap()
+            );
+            let acceptor = tls.acceptor(cluster_listener).map_err(tls_error)?;
+            server::start_cluster(acceptor, &handle, &supervisor);
+        }
 
-        loop {
-            interval.tick().await;
-            println!("--- Network State {:?} ---", time::Instant::now());
-            service.pretty_print();
-            println!("---------------------------");
+        if let Some(quic_port) = quic_port {
+            start_quic_cluster(&tls, quic_port, &handle, &supervisor)?;
         }
-    });*/
+    }
 
-    loop {
-        let (socket, addr) = listener
-            .accept()
-            .await
-            .map_err(|e| AppError::SocketError(format!("accept error: {e}")))?;
-
-        let app_state = app_state.clone();
-        let module_dependencies = module_dependencies.clone();
-        let request_controller = request_controller.clone();
-
-        tokio::spawn(async move {
-            if let Err(e) = handle_conn(
-                socket,
-                addr,
-                app_state,
-                module_dependencies,
-                request_controller,
-            )
-            .await
-            {
-                error!("conn error: {e}");
-            }
-        });
+    if let Some(config) = HotKeyConfig::from_env() {
+        info!(
+            "Copia de claves calientes a {} shards extra",
+            config.extra_shards
+        );
+        server::start_hot_keys(&handle, &supervisor, config);
     }
-}
 
-async fn handle_conn(
-    socket: TcpStream,
-    addr: SocketAddr,
-    app_state: Arc<AppState>,
-    module_dependencies: Arc<CacheMasterModule>,
-    request_controller: Arc<RequestController>,
-) -> SocketResult<()> {
-    let (reader, mut writer) = socket.into_split();
-
-    let mut first_line = String::new();
-
-    let mut reader = BufReader::new(reader);
-
-    let node_id =
-        match tokio::time::timeout(Duration::from_secs(5), reader.read_line(&mut first_line)).await
-        {
-            Ok(Ok(n)) if n > 0 => first_line.trim().to_string(),
-            _ => Uuid::new_v4().to_string(),
-        };
-
-    let (tx, mut rx) = mpsc::unbounded_channel::<Bytes>();
-
-    //TODO Remap error
-    let entry_node = EntryNode::from_str(node_id.as_str()).unwrap();
-    let id: Arc<str> = Arc::from(entry_node.id.as_str());
-
-    let connection_socket = Arc::new(Socket::new(
-        entry_node.id.clone(),
-        tx,
-        Duration::from_secs(2),
-    ));
-    let network_node = AppNetworkNode::new_shared(connection_socket.clone(), id.clone());
-
-    match entry_node.node_type {
-        NodeType::Master | NodeType::Replica => {
-            app_state
-                .network_state
-                .nodes_registry
-                .insert(id.clone(), network_node.clone());
-
-            let _ = module_dependencies
-                .assign_node_use_case
-                .validate_and_execute(AssignNodeUseCaseInput {
-                    node_id: entry_node.id,
-                    node_type: entry_node.node_type,
-                })
-                .await
-                .ok();
-        }
-        NodeType::Client => {}
-    };
-
-    info!("Conectado {} desde {addr}", id);
-
-    let writer_task = {
-        let node_id = id.clone();
-
-        tokio::spawn(async move {
-            while let Some(bytes) = rx.recv().await {
-                if let Err(e) = writer.write_all(&bytes).await {
-                    error!("[{node_id}] write error: {e}");
-                    break;
-                }
-            }
-            info!("[{node_id}] writer task ended");
-        })
-    };
-
-    let mut line = String::new();
-    loop {
-        line.clear();
-
-        let n = reader
-            .read_line(&mut line)
+    // STATS_INTERVAL_SECS: cada cuánto juntar el STATS de los shards (10 por defecto, 0 no
+    // junta y CLUSTER-STATS lo pide en el momento)
+    let stats_interval = env::var("STATS_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(10);
+    if stats_interval > 0 {
+        server::start_stats_aggregation(&handle, &supervisor, Duration::from_secs(stats_interval));
+    }
+
+    // BACKUP_INTERVAL_SECS: cada cuánto hacer un backup del cluster (0 por defecto: solo
+    // con BACKUP)
+    let backup_interval = env::var("BACKUP_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(0);
+    if backup_interval > 0 {
+        server::start_backups(&handle, &supervisor, Duration::from_secs(backup_interval));
+    }
+
+    // SIGHUP vuelve a leer el .env y aplica su RUST_LOG
+    supervisor.spawn("log-reload", ShutdownStage::Background, |token| {
+        log.reload_on_sighup(vec![PathBuf::from(".env")], token)
+    });
+
+    // Listener HTTP de métricas, solo si se pide
+    if let Some(metrics_port) = env::var("METRICS_PORT")
+        .ok()
+        .and_then(|p| p.parse::<u16>().ok())
+    {
+        let http_listener = TcpListener::bind(format!("0.0.0.0:{metrics_port}"))
             .await
-            .map_err(|e| SocketError::BadMessage(format!("read_line error: {e}")))?;
+            .map_err(|e| AppError::SocketError(format!("bind error: {e}")))?;
 
-        if n == 0 {
-            break; // EOF
-        }
+        info!(
+            "Metrics in: http://{}/metrics",
+            http_listener.local_addr().unwrap()
+        );
+        server::start_http(http_listener, &handle, &supervisor);
+    }
 
-        match parse_line(&line)? {
-            ParsedMsg::Res { id, raw_response } => {
-                // Relacionamos respuesta pendiente
-                connection_socket.handle_response(id, raw_response.to_string());
-            }
-            ParsedMsg::Req { data } => {
-                handle_request_async(request_controller.clone(), connection_socket.clone(), data)
-                    .await;
-            }
-            ParsedMsg::Other(msg) => {
-                info!("Other Req: [] -> {msg}");
-            }
-        }
+    // ADMIN_PORT: endpoints de operador en su propio listener, solo en loopback; sin la
+    // variable no se exponen
+    if let Some(admin_port) = env::var("ADMIN_PORT")
+        .ok()
+        .and_then(|p| p.parse::<u16>().ok())
+    {
+        let admin_listener = TcpListener::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, admin_port)))
+            .await
+            .map_err(|e| AppError::SocketError(format!("bind error: {e}")))?;
+
+        info!(
+            "Admin in: http://{}/dashboard",
+            admin_listener.local_addr().unwrap()
+        );
+        server::start_admin_http(admin_listener, &handle, &supervisor);
     }
 
-    module_dependencies
-        .delete_node_use_case
-        .validate_and_execute(RemoveNodeUseCaseInput {
-            node_id: id.to_string(),
-        })
-        .await
-        .ok();
+    let _ = tokio::signal::ctrl_c().await;
+    info!("Apagando...");
+
+    let report = supervisor.shutdown(SHUTDOWN_GRACE).await;
+    info!(
+        completed = report.completed.len(),
+        aborted = report.aborted.len(),
+        panics = report.panics.len(),
+        "Apagado completo"
+    );
+
+    Ok(())
+}
 
-    //writer_task.abort();
-    drop(connection_socket);
+#[cfg(feature = "quic")]
+fn start_quic_cluster(
+    tls: &ClusterTls,
+    port: u16,
+    handle: &MasterHandle,
+    supervisor: &Arc<Supervisor>,
+) -> Result<(), AppError> {
+    let acceptor = tls
+        .quic_acceptor(SocketAddr::from((Ipv4Addr::UNSPECIFIED, port)))
+        .map_err(|e| AppError::SocketError(format!("bind error: {e}")))?;
 
-    let _ = writer_task.await;
-    println!("Desconectado {} desde {addr}", id);
+    info!("Cluster (QUIC) in: {:?}", acceptor.local_addr().unwrap());
+    server::start_cluster(acceptor, handle, supervisor);
     Ok(())
 }
+
+#[cfg(not(feature = "quic"))]
+fn start_quic_cluster(
+    _tls: &ClusterTls,
+    _port: u16,
+    _handle: &MasterHandle,
+    _supervisor: &Arc<Supervisor>,
+) -> Result<(), AppError> {
+    Err(AppError::Config(
+        "CLUSTER_QUIC_PORT necesita el master compilado con la feature quic".into(),
+    ))
+}
@@ -0,0 +1,677 @@
+use std::{
+    net::SocketAddr,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use app_core::{
+    UseCaseValidatable,
+    clock::{AppClock, Clock},
+    error::HasErrorKind,
+    id::new_sortable_id,
+    supervisor::{ShutdownStage, Supervisor},
+};
+use bytes::Bytes;
+use tokio::{io::AsyncWriteExt, net::TcpListener, sync::mpsc};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+
+use app_net::{
+    Acceptor, BoxedStream, COMPRESS, CachePressure, Compression, EventData, FRAME_OVERHEAD,
+    FrameReader, FrameTooLarge, MonitorEntry, ParsedMsg, Peer, RequestDataInput, ResponseData,
+    Socket, SocketError, TcpConnector,
+    event::{CACHE_PRESSURE, HEARTBEAT, Heartbeat, KEYS_EXPIRED, NODE_ID_CONFLICT, NODE_REFUSED},
+    monitor::MONITOR,
+    parse_frame,
+    request::{RequestData, data::RequestDataOwned},
+    tokenize,
+    types::SocketResult,
+};
+
+use crate::{
+    core::domain::models::{
+        DomainEvent, EntryNode, NodeType,
+        usecases::{RemoveNodeUseCaseInput, assign_node_use_case::AssignNodeUseCaseInput},
+    },
+    infrastructure::{
+        adapters::{
+            controllers::router::{ActionRouter, RequestContext, UNROUTED_LABEL},
+            services::{run_backups, stats_aggregation_service::aggregate_stats},
+            subscribers::{ReplicationSubscriber, TopologyFeedSubscriber, TopologyLogSubscriber},
+        },
+        app_state::{AppNetworkNode, AppNetworkState, AppState},
+        di::{CacheMasterModule, config::MasterConfig},
+        failure_detector::watch_heartbeats,
+        hot_keys::{HotKeyConfig, replicate_hot_keys},
+        http,
+        live_config::DEFAULT_NODE_TIMEOUT,
+        metrics::MasterMetrics,
+        monitor::MasterMonitor,
+        peers::{link_peer, run_raft},
+        write_retries::retry_writes,
+    },
+};
+
+/// Ocupación a partir de la cual un nodo que además desaloja se considera chico.
+const UNDERSIZED_FILL_RATIO: f64 = 0.9;
+
+/// Estado del master levantado con `start`, para quien necesite inspeccionarlo (tests, admin).
+#[derive(Clone)]
+pub struct MasterHandle {
+    pub app_state: Arc<AppState>,
+    pub module: Arc<CacheMasterModule>,
+}
+
+/// Arma las dependencias y lanza el loop de accept sobre `listener`. Todas las tareas
+/// quedan bajo `supervisor`; el apagado lo decide quien llama.
+pub fn start<A: Acceptor>(listener: A, supervisor: &Arc<Supervisor>) -> MasterHandle {
+    start_with_clock(listener, supervisor, Arc::new(AppClock::new()))
+}
+
+/// Igual que `start`, con el reloj inyectado (p. ej. `RuntimeClock` en simulaciones).
+pub fn start_with_clock<A: Acceptor>(
+    listener: A,
+    supervisor: &Arc<Supervisor>,
+    clock: Arc<dyn Clock>,
+) -> MasterHandle {
+    start_with(listener, supervisor, clock, &MasterConfig::default())
+}
+
+/// Igual que `start`, con la configuración del master (auth y rate-limit de las acciones,
+/// políticas de fan-out, otros masters..).
+pub fn start_with_config<A: Acceptor>(
+    listener: A,
+    supervisor: &Arc<Supervisor>,
+    config: &MasterConfig,
+) -> MasterHandle {
+    start_with(listener, supervisor, Arc::new(AppClock::new()), config)
+}
+
+fn start_with<A: Acceptor>(
+    listener: A,
+    supervisor: &Arc<Supervisor>,
+    clock: Arc<dyn Clock>,
+    config: &MasterConfig,
+) -> MasterHandle {
+    let app_state = AppState::new_shared();
+    let module_dependencies = Arc::new(CacheMasterModule::build_with(
+        app_state.clone(),
+        clock,
+        config,
+    ));
+    let handle = MasterHandle {
+        app_state,
+        module: module_dependencies.clone(),
+    };
+
+    let event_bus = module_dependencies.event_bus.clone();
+    supervisor.spawn("topology-log", ShutdownStage::Background, |token| {
+        event_bus.subscriber_loop(Arc::new(TopologyLogSubscriber), token)
+    });
+
+    let event_bus = module_dependencies.event_bus.clone();
+    let replication = Arc::new(ReplicationSubscriber::new(
+        module_dependencies.tcp_network_service.clone(),
+    ));
+    supervisor.spawn("replication", ShutdownStage::Background, |token| {
+        event_bus.subscriber_loop(replication, token)
+    });
+
+    let event_bus = module_dependencies.event_bus.clone();
+    let topology_feed = Arc::new(TopologyFeedSubscriber::new(
+        module_dependencies.topology_feed.clone(),
+        module_dependencies.consistent_hasher_service.clone(),
+    ));
+    supervisor.spawn("topology-feed", ShutdownStage::Background, |token| {
+        event_bus.subscriber_loop(topology_feed, token)
+    });
+
+    if config.failure_detector.enabled() {
+        let module = module_dependencies.clone();
+        supervisor.spawn("failure-detector", ShutdownStage::Background, |token| {
+            watch_heartbeats(module, token)
+        });
+    }
+
+    if config.write_retry.enabled() {
+        let module = module_dependencies.clone();
+        supervisor.spawn("write-retries", ShutdownStage::Background, |token| {
+            retry_writes(module, token)
+        });
+    }
+
+    if config.peers.enabled() {
+        for (idx, addr) in config.peers.peers.iter().enumerate() {
+            let peers = module_dependencies.peers.clone();
+            supervisor.spawn(
+                format!("peer {addr}"),
+                ShutdownStage::Connections,
+                |token| link_peer(peers, idx, Arc::new(TcpConnector), token),
+            );
+        }
+        let peers = module_dependencies.peers.clone();
+        supervisor.spawn("raft", ShutdownStage::Background, |token| {
+            run_raft(peers, token)
+        });
+    }
+
+    spawn_accept_loop("accept", listener, &handle, supervisor);
+
+    handle
+}
+
+/// Puerto de cluster: `listener` (un `TlsAcceptor`) entrega la identidad del certificado de
+/// cada nodo. Con `MasterConfig::node_certs` es la única entrada por la que un nodo puede
+/// registrarse; los clientes siguen usando la de `start`.
+pub fn start_cluster<A: Acceptor>(
+    listener: A,
+    handle: &MasterHandle,
+    supervisor: &Arc<Supervisor>,
+) {
+    spawn_accept_loop("accept-cluster", listener, handle, supervisor);
+}
+
+fn spawn_accept_loop<A: Acceptor>(
+    name: &'static str,
+    mut listener: A,
+    handle: &MasterHandle,
+    supervisor: &Arc<Supervisor>,
+) {
+    let app_state = handle.app_state.clone();
+    let module_dependencies = handle.module.clone();
+    let sup = supervisor.clone();
+    supervisor.spawn(name, ShutdownStage::Ingress, |token| async move {
+        loop {
+            let (socket, peer) = tokio::select! {
+                _ = token.cancelled() => break,
+                accepted = listener.accept() => match accepted {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        error!("accept error: {e}");
+                        continue;
+                    }
+                },
+            };
+
+            let app_state = app_state.clone();
+            let module_dependencies = module_dependencies.clone();
+
+            sup.spawn(
+                format!("conn {peer}"),
+                ShutdownStage::Connections,
+                |token| async move {
+                    if let Err(e) =
+                        handle_conn(socket, peer, app_state, module_dependencies, token).await
+                    {
+                        error!("conn error: {e}");
+                    }
+                },
+            );
+        }
+    });
+}
+
+/// Copia a otros shards las claves leídas muy por encima del promedio (ver
+/// `hot_keys::replicate_hot_keys`) hasta el apagado del supervisor.
+pub fn start_hot_keys(handle: &MasterHandle, supervisor: &Arc<Supervisor>, config: HotKeyConfig) {
+    let module = handle.module.clone();
+    supervisor.spawn("hot-keys", ShutdownStage::Background, |token| {
+        replicate_hot_keys(module, config, token)
+    });
+}
+
+/// Junta cada `interval` el `STATS` de todos los shards (ver
+/// `stats_aggregation_service::aggregate_stats`) hasta el apagado del supervisor.
+pub fn start_stats_aggregation(
+    handle: &MasterHandle,
+    supervisor: &Arc<Supervisor>,
+    interval: Duration,
+) {
+    let service = handle.module.stats_aggregation_service.clone();
+    supervisor.spawn("stats-aggregation", ShutdownStage::Background, |token| {
+        aggregate_stats(service, interval, token)
+    });
+}
+
+/// Un backup cada `interval` (ver `BackupService::backup`) hasta el apagado del supervisor.
+/// No hace nada si el master no tiene destino de backups.
+pub fn start_backups(handle: &MasterHandle, supervisor: &Arc<Supervisor>, interval: Duration) {
+    let Some(service) = handle.module.backup_service.clone() else {
+        warn!(
+            "BACKUP_INTERVAL_SECS sin BACKUP_DIR ni BACKUP_S3_BUCKET: no hay backups programados"
+        );
+        return;
+    };
+    supervisor.spawn("backups", ShutdownStage::Background, |token| {
+        run_backups(service, interval, token)
+    });
+}
+
+/// Sirve `/metrics` (ver `http::router`) sobre `listener` hasta el apagado del
+/// supervisor.
+pub fn start_http(listener: TcpListener, handle: &MasterHandle, supervisor: &Arc<Supervisor>) {
+    let app = http::router(handle.module.clone());
+
+    supervisor.spawn("http", ShutdownStage::Ingress, |token| async move {
+        if let Err(e) = axum::serve(listener, app)
+            .with_graceful_shutdown(token.cancelled_owned())
+            .await
+        {
+            error!("http error: {e}");
+        }
+    });
+}
+
+/// Sirve `/dashboard` y `/log-filter` (ver `http::admin_router`) sobre `listener` hasta
+/// el apagado del supervisor.
+pub fn start_admin_http(
+    listener: TcpListener,
+    handle: &MasterHandle,
+    supervisor: &Arc<Supervisor>,
+) {
+    let app = http::admin_router(handle.module.clone());
+
+    supervisor.spawn("admin-http", ShutdownStage::Ingress, |token| async move {
+        if let Err(e) = axum::serve(listener, app)
+            .with_graceful_shutdown(token.cancelled_owned())
+            .await
+        {
+            error!("admin http error: {e}");
+        }
+    });
+}
+
+async fn handle_request_async(
+    router: Arc<ActionRouter>,
+    metrics: Arc<MasterMetrics>,
+    monitor: &MasterMonitor,
+    ctx: Arc<RequestContext>,
+    node: Arc<AppNetworkNode>,
+    frame: &Bytes,
+    data: RequestData<'_>,
+) {
+    monitor.local().record(&MonitorEntry::new(
+        "master",
+        &ctx.peer_id,
+        data.action,
+        &data.payload,
+    ));
+    let data = RequestDataOwned::from_frame(frame, data);
+
+    tokio::spawn(async move {
+        let started = Instant::now();
+        let (label, reply) = router.dispatch(&ctx, data.action(), data.payload()).await;
+
+        let response = match reply {
+            Ok(body) => body.into_response(data.id),
+            Err(e) => ResponseData::new(data.id, e.wire_code(), format!("ERROR {e}")),
+        };
+
+        metrics.observe_request(label, response.code, started.elapsed());
+        if response.code >= 400 {
+            node.stats.observe_error();
+        }
+        let _ = node.socket.send_res(response);
+    });
+}
+
+/// Contesta `413` a un request que no entró en el máximo de línea, ya descartado por
+/// `FrameReader`. Sin un id al que contestar, se corta la conexión.
+fn reject_too_large(
+    node: &AppNetworkNode,
+    metrics: &MasterMetrics,
+    e: &std::io::Error,
+) -> SocketResult<()> {
+    let Some(too_large) = FrameTooLarge::from_io(e) else {
+        return Ok(());
+    };
+    node.stats.observe_error();
+    let socket = &node.socket;
+    let error = SocketError::from(too_large.clone());
+    let Some(req_id) = too_large.req_id() else {
+        return Err(error);
+    };
+    warn!(socket_id = %socket.id, "{error}");
+    metrics.observe_request(UNROUTED_LABEL, error.wire_code(), Duration::ZERO);
+    socket.send_res(ResponseData::new(
+        req_id,
+        error.wire_code(),
+        format!("ERROR {error}"),
+    ))
+}
+
+/// `EVT` de un nodo: `MONITOR` se reparte entre los clientes que lo monitorean,
+/// `CACHE-PRESSURE` se guarda para métricas y dashboard, `KEYS-EXPIRED` se publica como
+/// `KeyExpired` y `HEARTBEAT` alimenta al `FailureDetector` y a `ClockSkews`. `COMPRESS`
+/// puede venir de cualquier conexión: desde ahí se le contesta comprimido.
+fn handle_event(module: &CacheMasterModule, node: &AppNetworkNode, data: EventData<'_>) {
+    match data.name {
+        MONITOR => match MonitorEntry::parse(&data.payload) {
+            Ok(entry) => module.monitor.relay(&node.node_id, &entry),
+            Err(e) => warn!(node_id = %node.node_id, "{e}"),
+        },
+        CACHE_PRESSURE => handle_cache_pressure(module, node, &data.payload),
+        KEYS_EXPIRED => {
+            for key in tokenize(&data.payload) {
+                module.event_bus.publish(DomainEvent::KeyExpired {
+                    key: key.into_owned(),
+                    node_id: node.node_id.to_string(),
+                });
+            }
+        }
+        HEARTBEAT => handle_heartbeat(module, node, &data.payload),
+        COMPRESS => match data.payload.parse::<Compression>() {
+            Ok(compression) => node.socket.accept_compression(compression),
+            Err(e) => warn!(node_id = %node.node_id, "{e}"),
+        },
+        name => debug!(node_id = %node.node_id, name, "EVT desconocido"),
+    }
+}
+
+/// Un latido que trae el reloj del nodo se contesta con el del master, que el nodo devuelve
+/// en el siguiente para medir la diferencia (ver `app_net::event::Heartbeat`).
+fn handle_heartbeat(module: &CacheMasterModule, node: &AppNetworkNode, payload: &str) {
+    module.failure_detector.heartbeat(&node.node_id);
+
+    let beat = match payload.parse::<Heartbeat>() {
+        Ok(beat) => beat,
+        Err(e) => {
+            warn!(node_id = %node.node_id, "{e}");
+            return;
+        }
+    };
+    let now_ms = module.clock.now_millis().as_millis_u64();
+    if let Some(sample) = beat.clock_sample(now_ms) {
+        module.clock_skews.observe(&node.node_id, sample);
+    }
+    if beat.sent_ms.is_some() {
+        let _ = node
+            .socket
+            .send_evt(&EventData::new(HEARTBEAT, now_ms.to_string()));
+    }
+}
+
+/// Si el nodo pasa a desalojar con el cache lleno se publica `ShardUndersized`.
+fn handle_cache_pressure(module: &CacheMasterModule, node: &AppNetworkNode, payload: &str) {
+    let report = match payload.parse::<CachePressure>() {
+        Ok(report) => report,
+        Err(e) => {
+            warn!(node_id = %node.node_id, "{e}");
+            return;
+        }
+    };
+
+    module
+        .tcp_network_service
+        .observe_memory(&node.node_id, report.memory_ratio());
+
+    let undersized = |r: &CachePressure| r.evictions > 0 && r.fill_ratio() >= UNDERSIZED_FILL_RATIO;
+    let previous = module.metrics.observe_cache_pressure(&node.node_id, report);
+
+    if undersized(&report) && !previous.as_ref().is_some_and(undersized) {
+        module.event_bus.publish(DomainEvent::ShardUndersized {
+            node_id: node.node_id.to_string(),
+            shard_id: node
+                .get_master_id()
+                .map(|id| id.to_string())
+                .unwrap_or_default(),
+            evictions_per_sec: report.evictions_per_sec().round() as u64,
+            fill_percent: (report.fill_ratio() * 100.0).min(100.0) as u8,
+        });
+    }
+}
+
+/// Si ya hay un nodo registrado como `id`, le manda un `PING`: si contesta, el id está en
+/// uso y el que llega se rechaza. Si no, es una conexión muerta que el master todavía no
+/// cerró (el nodo se reconecta tras un corte): se la saca como si se hubiera desconectado y
+/// el id queda libre.
+async fn id_in_use(app_state: &AppState, module: &CacheMasterModule, id: &Arc<str>) -> bool {
+    let Some(existing) = app_state
+        .network_state
+        .nodes_registry
+        .get(id)
+        .map(|node| node.clone())
+    else {
+        return false;
+    };
+    if existing
+        .socket
+        .request(RequestDataInput::new("PING", ""))
+        .await
+        .is_ok()
+    {
+        return true;
+    }
+
+    info!(node_id = %id, "El nodo se reconectó sin cerrar su conexión anterior");
+    forget_node(module, id).await;
+    false
+}
+
+/// Saca al nodo de la topología y olvida sus métricas y suscripciones.
+async fn forget_node(module: &CacheMasterModule, id: &str) {
+    module
+        .delete_node_use_case
+        .validate_and_execute(RemoveNodeUseCaseInput {
+            node_id: id.to_string(),
+        })
+        .await
+        .ok();
+    module.metrics.forget_node(id);
+    module.monitor.forget(id);
+    module.topology_feed.unsubscribe(id);
+    module.failure_detector.forget(id);
+    module.clock_skews.forget(id);
+}
+
+/// La conexión en `AppNetworkState::connections` mientras vive, también si el loop sale
+/// por un error. Hay que soltarla antes de esperar al writer: el nodo guarda su `tx`.
+struct ConnectionEntry<'a> {
+    state: &'a AppNetworkState,
+    id: u64,
+}
+
+impl<'a> ConnectionEntry<'a> {
+    fn add(state: &'a AppNetworkState, node: Arc<AppNetworkNode>) -> Self {
+        let id = state.add_connection(node);
+        Self { state, id }
+    }
+}
+
+impl Drop for ConnectionEntry<'_> {
+    fn drop(&mut self) {
+        self.state.connections.remove(&self.id);
+    }
+}
+
+async fn handle_conn(
+    socket: BoxedStream,
+    peer: Peer,
+    app_state: Arc<AppState>,
+    module_dependencies: Arc<CacheMasterModule>,
+    cancel: CancellationToken,
+) -> SocketResult<()> {
+    let (reader, mut writer) = tokio::io::split(socket);
+
+    let mut frames = FrameReader::new(reader);
+
+    let node_id = match tokio::time::timeout(Duration::from_secs(5), frames.next_frame()).await {
+        Ok(Ok(Some(first_line))) => String::from_utf8_lossy(&first_line).trim().to_string(),
+        _ => new_sortable_id(),
+    };
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<Bytes>();
+
+    //TODO Remap error
+    let entry_node = EntryNode::from_str(node_id.as_str()).unwrap();
+    let id: Arc<str> = Arc::from(entry_node.id.as_str());
+
+    let connection_socket = Arc::new(
+        Socket::new(entry_node.id.clone(), tx, DEFAULT_NODE_TIMEOUT)
+            .with_max_duration(module_dependencies.live_config.node_timeout())
+            .with_action_timeouts(module_dependencies.action_timeouts.clone())
+            .with_compression(module_dependencies.compress_min_bytes),
+    );
+    let peer_ip = peer.addr.parse::<SocketAddr>().ok().map(|addr| addr.ip());
+    let network_node = Arc::new(
+        AppNetworkNode::new(connection_socket.clone(), id.clone())
+            .with_repl_addr(entry_node.repl_addr.as_deref())
+            .with_peer_ip(peer_ip)
+            .with_kind(entry_node.node_type.as_str()),
+    );
+
+    let registered = matches!(entry_node.node_type, NodeType::Master | NodeType::Replica);
+    // las respuestas de los nodos (p. ej. un `SNAPSHOT`) no tienen tope; los clientes sí
+    if !registered {
+        frames.set_max_frame(Some(
+            module_dependencies.router.max_payload() + FRAME_OVERHEAD,
+        ));
+    }
+    if registered
+        && let Err(reason) = module_dependencies.tcp_network_service.node_access().check(
+            &id,
+            peer_ip,
+            peer.identity.as_deref(),
+        )
+    {
+        warn!(node_id = %id, %peer, %reason, "Nodo rechazado");
+        let refused = EventData::new(NODE_REFUSED, &reason).to_string();
+        let _ = writer.write_all(refused.as_bytes()).await;
+        return Ok(());
+    }
+    // de a pocos: tras un reinicio del master se reconectan todos los nodos juntos
+    let registration = if registered {
+        Some(module_dependencies.registrations.acquire().await)
+    } else {
+        None
+    };
+    if registered && id_in_use(&app_state, &module_dependencies, &id).await {
+        warn!(node_id = %id, %peer, "Otra conexión viva ya usa este id: se rechaza");
+        let conflict = EventData::new(NODE_ID_CONFLICT, &*id).to_string();
+        let _ = writer.write_all(conflict.as_bytes()).await;
+        return Ok(());
+    }
+
+    match entry_node.node_type {
+        NodeType::Master | NodeType::Replica => {
+            app_state
+                .network_state
+                .nodes_registry
+                .insert(id.clone(), network_node.clone());
+
+            let assigned = module_dependencies
+                .assign_node_use_case
+                .validate_and_execute(AssignNodeUseCaseInput {
+                    node_id: entry_node.id,
+                    node_type: entry_node.node_type,
+                })
+                .await;
+            // sin acuerdo de los masters, o sin shard todavía (una réplica que vuelve antes
+            // que su primario), el nodo no entra: reintenta como tras un corte
+            if let Err(e) = assigned {
+                warn!(node_id = %id, %peer, "Alta rechazada: {e}");
+                app_state.network_state.nodes_registry.remove(&id);
+                let refused = EventData::new(NODE_REFUSED, e.to_string()).to_string();
+                let _ = writer.write_all(refused.as_bytes()).await;
+                return Ok(());
+            }
+        }
+        NodeType::Client => {}
+    };
+    drop(registration);
+    // el master siempre entiende las respuestas comprimidas; el nodo decide si comprime
+    if registered {
+        let accepts = EventData::new(COMPRESS, Compression::Deflate.as_str());
+        let _ = connection_socket.send_evt(&accepts);
+    }
+
+    info!("Conectado {} desde {peer}", id);
+    // el `AUTH` vale para toda la conexión
+    let request_ctx =
+        Arc::new(RequestContext::new(id.clone()).with_socket(connection_socket.clone()));
+
+    let connection = ConnectionEntry::add(&app_state.network_state, network_node.clone());
+
+    let writer_task = {
+        let node_id = id.clone();
+        let stats = network_node.stats.clone();
+
+        tokio::spawn(async move {
+            while let Some(bytes) = rx.recv().await {
+                if let Err(e) = writer.write_all(&bytes).await {
+                    error!("[{node_id}] write error: {e}");
+                    break;
+                }
+                stats.observe_sent(bytes.len());
+            }
+            info!("[{node_id}] writer task ended");
+        })
+    };
+
+    loop {
+        let frame = tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = network_node.disconnected() => break,
+            frame = frames.next_frame() => match frame {
+                Err(e) if FrameTooLarge::from_io(&e).is_some() => {
+                    reject_too_large(&network_node, &module_dependencies.metrics, &e)?;
+                    continue;
+                }
+                frame => frame.map_err(|e| SocketError::BadMessage(format!("read error: {e}")))?,
+            }
+        };
+
+        let Some(frame) = frame else {
+            break; // EOF
+        };
+        let now_ms = module_dependencies.clock.now_millis().as_millis_u64();
+        network_node.stats.observe_frame(frame.len(), now_ms);
+
+        match parse_frame(&frame)? {
+            ParsedMsg::Res { id, raw_response } => {
+                // Relacionamos respuesta pendiente
+                connection_socket.handle_response(id, raw_response.to_string());
+            }
+            ParsedMsg::Req { data } => {
+                network_node.stats.observe_request();
+                handle_request_async(
+                    module_dependencies.router.clone(),
+                    module_dependencies.metrics.clone(),
+                    &module_dependencies.monitor,
+                    request_ctx.clone(),
+                    network_node.clone(),
+                    &frame,
+                    data,
+                )
+                .await;
+            }
+            ParsedMsg::Evt { data } => handle_event(&module_dependencies, &network_node, data),
+            ParsedMsg::Other(msg) => {
+                debug!(node_id = %id, %msg, "línea fuera de protocolo");
+            }
+        }
+    }
+
+    // si otra conexión se quedó con el id (ver `id_in_use`), el nodo es de ella
+    let replaced = registered
+        && !app_state
+            .network_state
+            .nodes_registry
+            .get(&id)
+            .is_some_and(|node| Arc::ptr_eq(&node, &network_node));
+    if !replaced {
+        forget_node(&module_dependencies, &id).await;
+    }
+
+    //writer_task.abort();
+    // el writer termina cuando se sueltan todos los `tx`: socket y nodo locales
+    drop(connection);
+    drop(network_node);
+    drop(request_ctx);
+    drop(connection_socket);
+
+    let _ = writer_task.await;
+    info!(node_id = %id, %peer, "Desconectado");
+    Ok(())
+}
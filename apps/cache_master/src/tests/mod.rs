@@ -1,2 +1,3 @@
+mod services;
 pub mod test_mocks;
 mod usecases;
@@ -0,0 +1,276 @@
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Arc};
+
+    use app_core::{clock::AppClock, error::HasErrorKind};
+    use async_trait::async_trait;
+
+    use crate::{
+        core::domain::{models::AppError, services::ConsistentHasherService},
+        infrastructure::{
+            adapters::controllers::router::{
+                ActionHandler, ActionPolicy, ActionRouter, RateClass, RequestContext, RouterConfig,
+            },
+            app_state::AppState,
+            di::{CacheMasterModule, config::MasterConfig},
+            metrics::MasterMetrics,
+        },
+    };
+
+    struct Echo;
+
+    #[async_trait]
+    impl ActionHandler for Echo {
+        async fn handle(&self, _ctx: &RequestContext, payload: &str) -> Result<String, AppError> {
+            Ok(payload.to_string())
+        }
+    }
+
+    fn ctx() -> RequestContext {
+        RequestContext::new(Arc::from("client-1"))
+    }
+
+    fn module(config: &MasterConfig) -> CacheMasterModule {
+        CacheMasterModule::build_with(AppState::new_shared(), Arc::new(AppClock::new()), config)
+    }
+
+    #[test]
+    fn the_module_routes_every_builtin_action() {
+        let module = module(&MasterConfig::default());
+
+        assert_eq!(
+            module.router.actions(),
+            vec![
+                "AUTH",
+                "BACKUP",
+                "BAN",
+                "CLUSTER-MAP",
+                "CLUSTER-STATS",
+                "CONFIG",
+                "DEL-PREFIX",
+                "EXPLAIN",
+                "GET",
+                "IMPORT",
+                "INVALIDATE-TAG",
+                "LOG-FILTER",
+                "META",
+                "MONITOR",
+                "MULTI",
+                "NODE-CONFIG",
+                "PEEK",
+                "PEER-FORWARD",
+                "PEER-HELLO",
+                "PEER-STATE",
+                "PING",
+                "PUT",
+                "RAFT-APPEND",
+                "RAFT-SNAPSHOT",
+                "RAFT-VOTE",
+                "READ-ONLY",
+                "RENAME",
+                "RESTORE",
+                "SET-ROLE",
+                "SLOWLOG",
+                "STATS",
+                "SUBSCRIBE",
+                "TOPKEYS",
+                "UNBAN",
+                "UNSUBSCRIBE",
+                "WRITE-RETRIES"
+            ]
+        );
+        assert_eq!(
+            module.router.policy("SET-ROLE"),
+            Some(ActionPolicy::admin("SET-ROLE"))
+        );
+        assert_eq!(
+            module.router.policy("MONITOR"),
+            Some(ActionPolicy::admin("MONITOR"))
+        );
+        assert_eq!(
+            module.router.policy("RAFT-APPEND"),
+            Some(ActionPolicy::peer("RAFT-APPEND"))
+        );
+        assert_eq!(module.router.policy("PUT"), Some(ActionPolicy::data("PUT")));
+        assert_eq!(
+            module.router.policy("MULTI"),
+            Some(ActionPolicy::data("MULTI"))
+        );
+    }
+
+    #[tokio::test]
+    async fn admin_actions_need_auth_only_when_a_token_is_configured() {
+        let open = module(&MasterConfig::default());
+        let (_, res) = open.router.dispatch(&ctx(), "META", "").await;
+        // pasa el control de auth y falla en el handler
+        assert!(matches!(res, Err(AppError::BadRequest(_))));
+
+        let locked = module(&MasterConfig {
+            router: RouterConfig {
+                admin_token: Some("s3cret".into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let ctx = ctx();
+
+        let (label, res) = locked.router.dispatch(&ctx, "META", "").await;
+        assert_eq!(label, "META");
+        assert_eq!(res.unwrap_err().wire_code(), 401);
+
+        let (_, res) = locked.router.dispatch(&ctx, "AUTH", "otro").await;
+        assert!(matches!(res, Err(AppError::Unauthorized(_))));
+        assert!(!ctx.is_admin());
+
+        let (_, res) = locked.router.dispatch(&ctx, "AUTH", "s3cret").await;
+        assert_eq!(res.unwrap().payload(), "OK");
+        let (_, res) = locked.router.dispatch(&ctx, "META", "").await;
+        assert!(matches!(res, Err(AppError::BadRequest(_))));
+
+        // las acciones públicas no miran el token
+        let (_, res) = locked.router.dispatch(&self::ctx(), "PING", "").await;
+        assert_eq!(res.unwrap().payload(), "PONG");
+    }
+
+    #[tokio::test]
+    async fn a_rate_class_shares_its_budget_across_its_actions() {
+        let metrics = MasterMetrics::new_shared();
+        let mut router = ActionRouter::new(
+            &RouterConfig {
+                admin_token: None,
+                rate_limits: HashMap::from([(RateClass::Data, 2)]),
+                ..Default::default()
+            },
+            metrics,
+        );
+        router
+            .route("A", ActionPolicy::data("A"), Echo)
+            .route("B", ActionPolicy::data("B"), Echo)
+            .route("C", ActionPolicy::open("C"), Echo);
+        let ctx = ctx();
+
+        assert!(router.dispatch(&ctx, "A", "x").await.1.is_ok());
+        assert!(router.dispatch(&ctx, "B", "x").await.1.is_ok());
+
+        let (label, res) = router.dispatch(&ctx, "A", "x").await;
+        assert_eq!(label, "A");
+        assert_eq!(res.unwrap_err().wire_code(), 429);
+        assert!(router.dispatch(&ctx, "C", "x").await.1.is_ok());
+    }
+
+    #[tokio::test]
+    async fn a_payload_over_the_limit_is_rejected_before_its_action() {
+        let mut router = ActionRouter::new(
+            &RouterConfig {
+                max_payload: 4,
+                ..Default::default()
+            },
+            MasterMetrics::new_shared(),
+        );
+        router.route("ECHO", ActionPolicy::open("ECHO"), Echo);
+
+        let (_, res) = router.dispatch(&ctx(), "ECHO", "1234").await;
+        assert_eq!(res.unwrap().payload(), "1234");
+        let (label, res) = router.dispatch(&ctx(), "ECHO", "12345").await;
+        assert_eq!(label, "ECHO");
+        assert_eq!(res.unwrap_err().wire_code(), 413);
+    }
+
+    #[tokio::test]
+    async fn config_set_changes_rate_limits_without_rebuilding_the_router() {
+        let module = module(&MasterConfig::default());
+        let ctx = ctx();
+        let config = |payload: &'static str| module.router.dispatch(&ctx, "CONFIG", payload);
+
+        let (_, res) = config("SET rate-limit-data 1").await;
+        assert_eq!(res.unwrap().payload(), "rate-limit-data=1");
+        assert!(module.router.dispatch(&ctx, "PING", "").await.1.is_ok());
+        assert!(module.router.dispatch(&ctx, "GET", "k").await.1.is_err());
+        let (_, res) = module.router.dispatch(&ctx, "GET", "k").await;
+        assert_eq!(res.unwrap_err().wire_code(), 429);
+
+        let (_, res) = config("SET rate-limit-data 0").await;
+        assert_eq!(res.unwrap().payload(), "rate-limit-data=0");
+        let (_, res) = module.router.dispatch(&ctx, "GET", "k").await;
+        assert_ne!(res.unwrap_err().wire_code(), 429);
+
+        let (_, res) = config("GET").await;
+        assert_eq!(
+            res.unwrap().payload(),
+            "node-timeout-ms=2000 read-policy=hedged:p95 write-policy=first hedge-delay=p95 \
+             rate-limit-data=0 rate-limit-admin=0"
+        );
+        for bad in [
+            "SET read-policy nada",
+            "SET otra 1",
+            "SET node-timeout-ms",
+            "BORRAR",
+        ] {
+            let (_, res) = config(bad).await;
+            assert_eq!(res.unwrap_err().wire_code(), 400, "{bad}");
+        }
+    }
+
+    #[tokio::test]
+    async fn custom_routes_get_their_own_metrics_series() {
+        let metrics = MasterMetrics::new_shared();
+        let mut router = ActionRouter::new(&RouterConfig::default(), metrics.clone());
+        router.route("ECHO", ActionPolicy::open("ECHO"), Echo);
+
+        let (label, res) = router.dispatch(&ctx(), "ECHO", "hola").await;
+        assert_eq!((label, res.unwrap().payload().as_str()), ("ECHO", "hola"));
+        metrics.observe_request(label, 200, Default::default());
+        assert_eq!(metrics.requests_total("ECHO", 200), 1);
+
+        let (label, res) = router.dispatch(&ctx(), "NOPE", "").await;
+        assert_eq!(label, "OTHER");
+        assert!(matches!(res, Err(AppError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn explain_shows_the_ring_position_and_the_route_of_a_key() {
+        let module = module(&MasterConfig {
+            replication_factor: 2,
+            ..Default::default()
+        });
+        let hasher = &module.consistent_hasher_service;
+        for shard in ["s1", "s2", "s3"] {
+            hasher.add_node(shard);
+        }
+
+        let (_, res) = module
+            .router
+            .dispatch(&ctx(), "EXPLAIN", "\"user:1\"")
+            .await;
+        let res = res.unwrap().payload();
+        let fields: HashMap<&str, &str> = res
+            .split(' ')
+            .filter_map(|field| field.split_once('='))
+            .collect();
+
+        let hash = hasher.create_hash("user:1");
+        let shards = hasher.get_node_ids_from_hash(&hash, 2);
+        assert_eq!(fields["hash"], hash);
+        assert_eq!(fields["owner"], shards[0]);
+        assert_eq!(fields["shards"], shards.join(","));
+        assert!(fields["vnode"].starts_with(&format!("{}#", shards[0])));
+        // ningún nodo registrado en esos shards todavía
+        assert_eq!(fields["members"], "-");
+        assert_eq!(fields["hot"], "-");
+        assert_eq!(fields["read_policy"], "hedged:p95");
+        assert_eq!(fields["get"], "-/-");
+        assert_eq!(fields["write_policy"], "first");
+
+        let (_, res) = module.router.dispatch(&ctx(), "EXPLAIN", "").await;
+        assert!(matches!(res, Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    #[should_panic(expected = "acción ECHO registrada dos veces")]
+    fn routing_an_action_twice_panics() {
+        let mut router = ActionRouter::new(&RouterConfig::default(), MasterMetrics::new_shared());
+        router
+            .route("ECHO", ActionPolicy::open("ECHO"), Echo)
+            .route("ECHO", ActionPolicy::data("ECHO"), Echo);
+    }
+}
@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use app_net::event::{ClockEcho, ClockSample, Heartbeat};
+
+    use crate::infrastructure::clock_skew::ClockSkews;
+
+    fn sample(offset_ms: i64, rtt_ms: u64) -> ClockSample {
+        ClockSample { offset_ms, rtt_ms }
+    }
+
+    #[test]
+    fn the_estimate_is_the_sample_with_the_shortest_round_trip() {
+        let skews = ClockSkews::new();
+        let node: Arc<str> = Arc::from("n1");
+        assert_eq!(skews.estimate("n1"), None);
+
+        skews.observe(&node, sample(900, 400));
+        skews.observe(&node, sample(210, 4));
+        assert_eq!(skews.observe(&node, sample(-50, 120)), sample(210, 4));
+
+        // la buena sale de la ventana y queda la mejor de las que siguen
+        for _ in 0..7 {
+            skews.observe(&node, sample(300, 50));
+        }
+        assert_eq!(skews.estimate("n1"), Some(sample(300, 50)));
+
+        skews.forget("n1");
+        assert_eq!(skews.estimate("n1"), None);
+    }
+
+    #[test]
+    fn a_heartbeat_echo_measures_a_node_that_runs_late() {
+        let skews = ClockSkews::new();
+        let node: Arc<str> = Arc::from("n1");
+        // el nodo va 3 s atrasado, cada tramo tarda 2 ms y el eco espera medio segundo
+        let master_ms = 1_700_000_000_000;
+        let beat = Heartbeat {
+            seq: 2,
+            sent_ms: Some(master_ms + 2 - 3_000 + 500),
+            echo: Some(ClockEcho {
+                master_ms,
+                received_ms: master_ms + 2 - 3_000,
+            }),
+        };
+
+        let measured = beat.clock_sample(master_ms + 504).unwrap();
+        assert_eq!(skews.observe(&node, measured), sample(-3_000, 4));
+    }
+}
@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod tests {
+    use crate::infrastructure::{
+        dashboard::{Dashboard, HotKeyView, ThroughputView},
+        metrics::{HotKeys, RateWindow},
+    };
+
+    #[test]
+    fn rate_window_keeps_only_the_last_minute() {
+        let w = RateWindow::default();
+        w.record_at(10);
+        w.record_at(10);
+        w.record_at(11);
+
+        let at_11 = w.per_second_at(11);
+        assert_eq!(at_11.len(), 60);
+        assert_eq!(&at_11[58..], &[2, 1]);
+
+        // 60s después el slot del segundo 10 se reusa y arranca de cero
+        w.record_at(70);
+        let at_70 = w.per_second_at(70);
+        assert_eq!(at_70.iter().sum::<u64>(), 2);
+        assert_eq!(at_70[59], 1);
+        assert_eq!(w.per_second_at(200).iter().sum::<u64>(), 0);
+    }
+
+    #[test]
+    fn hot_keys_rank_by_hits_and_age_out() {
+        let hot = HotKeys::default();
+        for _ in 0..3 {
+            hot.observe_at("a", 1);
+        }
+        hot.observe_at("b", 15);
+        hot.observe_at("a", 15);
+        hot.observe_at("c", 30);
+        hot.observe_at("c", 30);
+
+        let top = hot.top_at(2, 30);
+        assert_eq!(top.len(), 2);
+        assert_eq!((top[0].0.as_ref(), top[0].1), ("a", 4));
+        assert_eq!((top[1].0.as_ref(), top[1].1), ("c", 2));
+
+        // el bucket de los segundos 0..10 ya salió de la ventana
+        let later = hot.top_at(3, 65);
+        assert_eq!((later[0].0.as_ref(), later[0].1), ("c", 2));
+        assert_eq!(later.len(), 3);
+    }
+
+    #[test]
+    fn outliers_are_far_above_the_other_keys() {
+        let hot = HotKeys::default();
+        for _ in 0..100 {
+            hot.observe_at("viral", 5);
+        }
+        for key in ["a", "b", "c"] {
+            for _ in 0..5 {
+                hot.observe_at(key, 5);
+            }
+        }
+
+        let outliers = hot.outliers_at(10.0, 50, 5);
+        assert_eq!(outliers.len(), 1);
+        assert_eq!(outliers[0].as_ref(), "viral");
+
+        // por debajo del mínimo no cuenta, por más que destaque
+        assert!(hot.outliers_at(10.0, 101, 5).is_empty());
+        assert!(hot.outliers_at(30.0, 50, 5).is_empty());
+    }
+
+    #[test]
+    fn html_view_escapes_user_keys() {
+        let view = Dashboard {
+            uptime_secs: 1,
+            registered_nodes: 0,
+            ring_nodes: 0,
+            ring_vnodes: 0,
+            shed_requests: 0,
+            shards: Vec::new(),
+            hot_keys: vec![HotKeyView {
+                key: "<script>".into(),
+                hits: 1,
+            }],
+            throughput: ThroughputView {
+                window_secs: 60,
+                requests: 1,
+                failures: 0,
+                per_second: vec![1],
+            },
+        };
+
+        let html = view.to_html();
+        assert!(html.contains("&lt;script&gt;"));
+        assert!(!html.contains("<script>"));
+    }
+}
@@ -0,0 +1,124 @@
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{self, Write},
+        sync::Arc,
+        time::Duration,
+    };
+
+    use app_core::clock::SimulatedClock;
+    use app_net::{TopologyChange, TopologyEvent};
+    use parking_lot::Mutex;
+
+    use crate::{
+        core::domain::services::ConsistentHasherService,
+        infrastructure::{
+            adapters::services::dashmap_consistent_hasher_service::DashmapConsistentHasherService,
+            decision_log::{Decision, DecisionEntry, DecisionLog, DecisionReplay},
+        },
+    };
+
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn entries_round_trip_through_their_line() {
+        let entries = [
+            DecisionEntry {
+                at_ms: 10,
+                epoch: 0,
+                decision: Decision::Start,
+            },
+            DecisionEntry {
+                at_ms: 11,
+                epoch: 2,
+                decision: Decision::RingAdd {
+                    shard_id: "shard con espacio".into(),
+                },
+            },
+            DecisionEntry {
+                at_ms: 12,
+                epoch: 2,
+                decision: Decision::Route {
+                    hash: "00000000000000ff".into(),
+                    nodes: vec!["n1".into(), "n2".into()],
+                },
+            },
+            DecisionEntry {
+                at_ms: 13,
+                epoch: 3,
+                decision: Decision::Topology(TopologyChange::NodeJoined {
+                    node_id: "r1".into(),
+                    shard_id: "n1".into(),
+                }),
+            },
+        ];
+        for entry in entries {
+            assert_eq!(entry.to_string().parse::<DecisionEntry>().unwrap(), entry);
+        }
+        assert!("12 x ring-add".parse::<DecisionEntry>().is_err());
+        assert!(
+            "12 3 tele-transport \"n1\""
+                .parse::<DecisionEntry>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn the_replay_rebuilds_the_ring_and_flags_routes_it_would_not_have_taken() {
+        let clock = Arc::new(SimulatedClock::new(1_000));
+        let buf = SharedBuf::default();
+        let log = Arc::new(DecisionLog::to_writer(buf.clone(), clock.clone()));
+        let ring = DashmapConsistentHasherService::new().with_decision_log(log.clone());
+
+        ring.add_node("n1");
+        ring.add_node("n2");
+        log.topology(&TopologyEvent {
+            epoch: 2,
+            change: TopologyChange::ShardAdded {
+                shard_id: "n2".into(),
+            },
+        });
+        let hash = ring.create_hash("user:42");
+        let owner = ring.get_node_id_from_hash(&hash).unwrap();
+
+        clock.advance(Duration::from_millis(500));
+        ring.remove_node(&owner);
+        let survivor = ring.get_node_id_from_hash(&hash).unwrap();
+        assert_ne!(survivor, owner);
+        drop(ring);
+        drop(log);
+
+        let mut text = String::from_utf8(buf.0.lock().clone()).unwrap();
+        // una ruta que el anillo no daba y una línea cortada por una caída
+        text.push_str(&format!("1500 2 route {hash} \"{owner}\"\n1501 2 ring-a"));
+        let replay = DecisionReplay::parse(&text);
+
+        assert_eq!(replay.skipped(), &[text.lines().count()]);
+        assert_eq!(replay.shards_at(999), Vec::<String>::new());
+        assert_eq!(replay.shards_at(1_000), vec!["n1", "n2"]);
+        assert_eq!(replay.shards_at(1_500), vec![survivor.clone()]);
+        assert_eq!(
+            replay.ring_at(1_000).get_node_id_from_hash(&hash),
+            Some(owner.clone())
+        );
+
+        let checks = replay.routes_of(&hash);
+        assert_eq!(checks.len(), 3);
+        assert!(checks[0].matches() && checks[0].epoch == 2 && checks[0].at_ms == 1_000);
+        assert!(checks[1].matches() && checks[1].nodes == vec![survivor.clone()]);
+        assert!(!checks[2].matches());
+        assert_eq!(checks[2].expected, vec![survivor]);
+    }
+}
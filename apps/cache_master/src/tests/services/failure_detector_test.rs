@@ -0,0 +1,88 @@
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use app_core::clock::SimulatedClock;
+
+    use crate::infrastructure::failure_detector::{FailureDetector, FailureDetectorConfig};
+
+    fn config() -> FailureDetectorConfig {
+        FailureDetectorConfig {
+            threshold: 8.0,
+            min_std_deviation: Duration::from_millis(10),
+            acceptable_pause: Duration::ZERO,
+            first_heartbeat_estimate: Duration::from_millis(100),
+            ..FailureDetectorConfig::default()
+        }
+    }
+
+    /// 50 latidos separados por `intervals` (en ms), dando la vuelta.
+    fn beat(
+        detector: &FailureDetector,
+        clock: &SimulatedClock,
+        node: &Arc<str>,
+        intervals: &[u64],
+    ) {
+        for (i, ms) in intervals.iter().cycle().take(50).enumerate() {
+            if i > 0 {
+                clock.advance(Duration::from_millis(*ms));
+            }
+            detector.heartbeat(node);
+        }
+    }
+
+    #[test]
+    fn silence_raises_suspicion_until_the_node_is_a_suspect() {
+        let clock = Arc::new(SimulatedClock::new(0));
+        let detector = FailureDetector::new(config(), clock.clone());
+        let node: Arc<str> = Arc::from("n1");
+        assert_eq!(detector.phi("n1"), None);
+
+        beat(&detector, &clock, &node, &[100]);
+        clock.advance(Duration::from_millis(100));
+        assert!(detector.phi("n1").unwrap() < 1.0);
+        assert!(detector.suspects().is_empty());
+
+        clock.advance(Duration::from_millis(400));
+        let suspects = detector.suspects();
+        assert_eq!(suspects.len(), 1);
+        assert!(suspects[0].1 >= 8.0);
+
+        detector.forget("n1");
+        assert_eq!(detector.phi("n1"), None);
+    }
+
+    #[test]
+    fn an_irregular_node_gets_more_slack_than_a_regular_one() {
+        // phi después de latir con `intervals` y callarse 250 ms
+        let phi_after_silence = |intervals: &[u64]| {
+            let clock = Arc::new(SimulatedClock::new(0));
+            let detector = FailureDetector::new(config(), clock.clone());
+            let node: Arc<str> = Arc::from("n1");
+            beat(&detector, &clock, &node, intervals);
+            clock.advance(Duration::from_millis(250));
+            detector.phi("n1").unwrap()
+        };
+
+        // el mismo promedio de 100 ms
+        let regular = phi_after_silence(&[100]);
+        let jittery = phi_after_silence(&[20, 180]);
+        assert!(regular >= 8.0, "phi {regular}");
+        assert!(jittery < 8.0, "phi {jittery}");
+    }
+
+    #[test]
+    fn a_zero_threshold_suspects_nobody() {
+        let clock = Arc::new(SimulatedClock::new(0));
+        let config = FailureDetectorConfig {
+            threshold: 0.0,
+            ..config()
+        };
+        let detector = FailureDetector::new(config, clock.clone());
+        detector.heartbeat(&Arc::from("n1"));
+        clock.advance(Duration::from_secs(60));
+
+        assert!(detector.phi("n1").unwrap() > 8.0);
+        assert!(detector.suspects().is_empty());
+    }
+}
@@ -0,0 +1,297 @@
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        time::Duration,
+    };
+
+    use app_net::{ParsedMsg, RequestDataInput, Socket, parse_line};
+    use bytes::Bytes;
+    use tokio::sync::mpsc;
+
+    use crate::infrastructure::{
+        adapters::services::{FanoutPolicy, HedgeDelay, Stragglers, fanout},
+        app_state::AppNetworkNode,
+        metrics::MasterMetrics,
+    };
+
+    /// Nodo falso que contesta `code` después de `delay` y cuenta los requests que recibe;
+    /// sin `code`, está caído.
+    fn node(
+        id: &str,
+        code: Option<u16>,
+        delay: Duration,
+    ) -> (Arc<AppNetworkNode>, Arc<AtomicUsize>) {
+        let (tx, mut rx) = mpsc::unbounded_channel::<Bytes>();
+        let socket = Arc::new(Socket::new(id.into(), tx, Duration::from_secs(1)));
+        let received = Arc::new(AtomicUsize::new(0));
+
+        match code {
+            Some(code) => {
+                let responder = socket.clone();
+                let count = received.clone();
+                let id = id.to_string();
+                tokio::spawn(async move {
+                    while let Some(bytes) = rx.recv().await {
+                        let line = String::from_utf8_lossy(&bytes).into_owned();
+                        let Ok(ParsedMsg::Req { data }) = parse_line(&line) else {
+                            continue;
+                        };
+                        count.fetch_add(1, Ordering::SeqCst);
+                        let req_id = data.id.to_string();
+                        let responder = responder.clone();
+                        let id = id.clone();
+                        tokio::spawn(async move {
+                            tokio::time::sleep(delay).await;
+                            let res = format!("RES {req_id} {code} \"{id}\"");
+                            responder.handle_response(req_id, res);
+                        });
+                    }
+                });
+            }
+            None => drop(rx),
+        }
+
+        (AppNetworkNode::new_shared(socket, Arc::from(id)), received)
+    }
+
+    fn request() -> RequestDataInput<'static> {
+        RequestDataInput {
+            action: "GET",
+            payload: "k",
+        }
+    }
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[tokio::test]
+    async fn quorum_waits_for_n_answers() {
+        let metrics = MasterMetrics::new_shared();
+        let nodes = vec![
+            node("a", Some(200), ms(0)).0,
+            node("b", Some(200), ms(30)).0,
+            node("c", Some(200), ms(500)).0,
+        ];
+
+        let outcome = fanout(
+            &nodes,
+            request(),
+            FanoutPolicy::Quorum(2),
+            Stragglers::Abort,
+            &metrics,
+        )
+        .await;
+
+        let ids: Vec<_> = outcome.outcomes.iter().map(|o| &*o.node_id).collect();
+        assert_eq!(ids, ["a", "b"]);
+        assert!(outcome.is_satisfied() && outcome.is_confirmed());
+        assert_eq!(outcome.into_response().unwrap().payload, "a");
+    }
+
+    #[tokio::test]
+    async fn primary_then_replicas_falls_back_in_order() {
+        let metrics = MasterMetrics::new_shared();
+        let (primary, _) = node("p", None, ms(0));
+        let (first, first_count) = node("r1", Some(200), ms(0));
+        let (second, second_count) = node("r2", Some(200), ms(0));
+
+        let outcome = fanout(
+            &[primary, first, second],
+            request(),
+            FanoutPolicy::PrimaryThenReplicas,
+            Stragglers::Abort,
+            &metrics,
+        )
+        .await;
+
+        let ids: Vec<_> = outcome.outcomes.iter().map(|o| &*o.node_id).collect();
+        assert_eq!(ids, ["p", "r1"]);
+        assert!(!outcome.outcomes[0].answered());
+        assert_eq!(outcome.into_response().unwrap().payload, "r1");
+        assert_eq!(first_count.load(Ordering::SeqCst), 1);
+        assert_eq!(second_count.load(Ordering::SeqCst), 0, "no hizo falta");
+    }
+
+    #[tokio::test]
+    async fn track_lag_still_sends_to_the_replicas_left() {
+        let metrics = MasterMetrics::new_shared();
+        let (primary, _) = node("p", Some(200), ms(0));
+        let (replica, replica_count) = node("r1", Some(200), ms(0));
+
+        let outcome = fanout(
+            &[primary, replica],
+            request(),
+            FanoutPolicy::PrimaryThenReplicas,
+            Stragglers::TrackLag,
+            &metrics,
+        )
+        .await;
+        assert_eq!(outcome.outcomes.len(), 1);
+
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while replica_count.load(Ordering::SeqCst) == 0 {
+                tokio::time::sleep(ms(5)).await;
+            }
+        })
+        .await
+        .expect("la réplica no recibió el request");
+    }
+
+    fn hedged(ms_delay: u64) -> FanoutPolicy {
+        FanoutPolicy::Hedged(HedgeDelay::Fixed(ms(ms_delay)))
+    }
+
+    #[tokio::test]
+    async fn hedging_leaves_the_replicas_alone_while_the_preferred_node_is_fast() {
+        let metrics = MasterMetrics::new_shared();
+        let (primary, _) = node("p", Some(200), ms(0));
+        let (replica, replica_count) = node("r1", Some(200), ms(0));
+
+        let outcome = fanout(
+            &[primary, replica],
+            request(),
+            hedged(200),
+            Stragglers::Abort,
+            &metrics,
+        )
+        .await;
+
+        assert_eq!(outcome.into_response().unwrap().payload, "p");
+        assert_eq!(replica_count.load(Ordering::SeqCst), 0);
+        assert_eq!(metrics.hedges_total(), 0);
+    }
+
+    #[tokio::test]
+    async fn a_slow_preferred_node_is_hedged_after_the_delay() {
+        let metrics = MasterMetrics::new_shared();
+        let (primary, _) = node("p", Some(200), ms(500));
+        let (replica, _) = node("r1", Some(200), ms(0));
+        let (spare, spare_count) = node("r2", Some(200), ms(0));
+
+        let outcome = fanout(
+            &[primary, replica, spare],
+            request(),
+            hedged(20),
+            Stragglers::Abort,
+            &metrics,
+        )
+        .await;
+
+        let ids: Vec<_> = outcome.outcomes.iter().map(|o| &*o.node_id).collect();
+        assert_eq!(ids, ["r1"]);
+        assert_eq!(metrics.hedges_total(), 1);
+        assert_eq!(spare_count.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn a_failed_preferred_node_is_hedged_without_waiting() {
+        let metrics = MasterMetrics::new_shared();
+        let (primary, _) = node("p", None, ms(0));
+        let (replica, _) = node("r1", Some(200), ms(0));
+
+        let outcome = tokio::time::timeout(
+            Duration::from_secs(1),
+            fanout(
+                &[primary, replica],
+                request(),
+                hedged(60_000),
+                Stragglers::Abort,
+                &metrics,
+            ),
+        )
+        .await
+        .expect("esperó el delay");
+
+        assert_eq!(outcome.into_response().unwrap().payload, "r1");
+        assert_eq!(metrics.hedges_total(), 0, "no fue por demora");
+    }
+
+    #[tokio::test]
+    async fn all_is_not_satisfied_with_a_node_down() {
+        let metrics = MasterMetrics::new_shared();
+        let nodes = vec![node("a", Some(200), ms(0)).0, node("b", None, ms(0)).0];
+
+        let outcome = fanout(
+            &nodes,
+            request(),
+            FanoutPolicy::All,
+            Stragglers::Abort,
+            &metrics,
+        )
+        .await;
+
+        assert_eq!((outcome.required, outcome.answered()), (2, 1));
+        assert!(!outcome.is_satisfied());
+        let err = outcome.into_response().unwrap_err();
+        assert!(err.to_string().contains("1 de 2"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn a_rejected_write_is_not_confirmed() {
+        let metrics = MasterMetrics::new_shared();
+        let nodes = vec![
+            node("a", Some(200), ms(0)).0,
+            node("b", Some(503), ms(20)).0,
+        ];
+
+        let outcome = fanout(
+            &nodes,
+            request(),
+            FanoutPolicy::All,
+            Stragglers::Abort,
+            &metrics,
+        )
+        .await;
+
+        assert!(outcome.is_satisfied());
+        assert!(!outcome.is_confirmed());
+        assert_eq!(outcome.into_rejection().unwrap().code, 503);
+    }
+
+    #[tokio::test]
+    async fn no_nodes_satisfy_no_policy() {
+        let metrics = MasterMetrics::new_shared();
+        let outcome = fanout(
+            &[],
+            request(),
+            FanoutPolicy::All,
+            Stragglers::Abort,
+            &metrics,
+        )
+        .await;
+
+        assert!(!outcome.is_satisfied() && !outcome.is_confirmed());
+        assert!(outcome.into_response().is_err());
+    }
+
+    #[test]
+    fn policies_parse_and_round_trip() {
+        for policy in [
+            FanoutPolicy::FirstSuccess,
+            FanoutPolicy::Quorum(2),
+            FanoutPolicy::All,
+            FanoutPolicy::PrimaryThenReplicas,
+            FanoutPolicy::Hedged(HedgeDelay::P95),
+            hedged(25),
+        ] {
+            assert_eq!(policy.to_string().parse::<FanoutPolicy>(), Ok(policy));
+        }
+        for bad in [
+            "",
+            "quorum",
+            "quorum:0",
+            "quorum:x",
+            "hedged:",
+            "hedged:p99",
+            "some",
+        ] {
+            assert!(bad.parse::<FanoutPolicy>().is_err(), "{bad}");
+        }
+        assert_eq!(FanoutPolicy::Quorum(5).required(3), 3);
+    }
+}
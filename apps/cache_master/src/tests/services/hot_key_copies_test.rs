@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::Arc,
+        time::{Duration, Instant},
+    };
+
+    use crate::infrastructure::adapters::services::HotKeyCopies;
+
+    fn shards(ids: &[&str]) -> Vec<Arc<str>> {
+        ids.iter().map(|id| Arc::from(*id)).collect()
+    }
+
+    #[test]
+    fn reads_rotate_between_the_owner_and_the_copies() {
+        let copies = HotKeyCopies::new();
+        assert!(copies.route("k").is_none());
+
+        assert!(copies.begin("k"));
+        assert!(
+            copies.route("k").is_none(),
+            "mientras se copia lee el dueño"
+        );
+        let until = Instant::now() + Duration::from_secs(60);
+        assert!(copies.finish("k", shards(&["a", "b", "c"]), until));
+        assert_eq!(copies.len(), 1);
+
+        let routed: Vec<_> = (0..6).map(|_| copies.route("k").unwrap()).collect();
+        assert_eq!(routed, shards(&["a", "b", "c", "a", "b", "c"]));
+        assert!(!copies.begin("k"), "ya tiene copias vigentes");
+    }
+
+    #[test]
+    fn a_write_drops_the_copies_even_while_copying() {
+        let copies = HotKeyCopies::new();
+        let until = Instant::now() + Duration::from_secs(60);
+        copies.begin("k");
+        copies.finish("k", shards(&["a", "b", "c"]), until);
+
+        assert_eq!(copies.invalidate("k"), shards(&["b", "c"]));
+        assert!(copies.route("k").is_none());
+        assert!(copies.is_empty());
+
+        // una escritura en medio de la copia la deja sin efecto
+        assert!(copies.begin("k"));
+        assert!(copies.invalidate("k").is_empty());
+        assert!(!copies.finish("k", shards(&["a", "b"]), until));
+        assert!(copies.route("k").is_none());
+    }
+
+    #[test]
+    fn expired_copies_stop_being_read() {
+        let copies = HotKeyCopies::new();
+        copies.begin("k");
+        copies.finish("k", shards(&["a", "b"]), Instant::now());
+
+        assert!(copies.route("k").is_none());
+        assert!(copies.is_empty());
+        assert!(copies.begin("k"), "se puede volver a copiar");
+    }
+}
@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        core::domain::models::AppError,
+        infrastructure::adapters::services::redis_rdb::{RdbEntry, is_rdb, parse_rdb},
+    };
+
+    fn string(out: &mut Vec<u8>, s: &str) {
+        out.push(s.len() as u8);
+        out.extend_from_slice(s.as_bytes());
+    }
+
+    /// Dump armado a mano con lo que escribe Redis 7: metadatos, vencimientos, enteros,
+    /// un string LZF, una lista y una clave en otra base.
+    fn dump() -> Vec<u8> {
+        let mut rdb = b"REDIS0011".to_vec();
+        rdb.push(0xFA);
+        string(&mut rdb, "redis-ver");
+        string(&mut rdb, "7.2.0");
+        rdb.extend_from_slice(&[0xFE, 0x00, 0xFB, 0x05, 0x02]);
+
+        rdb.push(0xFC);
+        rdb.extend_from_slice(&4_102_444_800_000u64.to_le_bytes());
+        rdb.push(0);
+        string(&mut rdb, "a");
+        string(&mut rdb, "uno");
+
+        rdb.push(0);
+        string(&mut rdb, "n");
+        rdb.extend_from_slice(&[0xC0, 42]);
+        rdb.push(0);
+        string(&mut rdb, "m");
+        rdb.push(0xC1);
+        rdb.extend_from_slice(&(-300i16).to_le_bytes());
+
+        // "ab" literal y 6 bytes copiados de 2 atrás: "abababab"
+        rdb.push(0);
+        string(&mut rdb, "z");
+        rdb.extend_from_slice(&[0xC3, 5, 8, 0x01, b'a', b'b', 0x80, 0x01]);
+
+        rdb.push(1);
+        string(&mut rdb, "lista");
+        rdb.push(2);
+        string(&mut rdb, "x");
+        string(&mut rdb, "y");
+
+        rdb.push(0xFD);
+        rdb.extend_from_slice(&1u32.to_le_bytes());
+        rdb.push(0);
+        string(&mut rdb, "vieja");
+        string(&mut rdb, "v");
+
+        rdb.extend_from_slice(&[0xFE, 0x01, 0x00]);
+        string(&mut rdb, "otra-base");
+        string(&mut rdb, "v");
+
+        rdb.push(0xFF);
+        rdb.extend_from_slice(&[0; 8]);
+        rdb
+    }
+
+    fn entry(key: &str, value: &str, expires_at: Option<u64>) -> RdbEntry {
+        RdbEntry {
+            key: key.into(),
+            value: value.into(),
+            expires_at,
+        }
+    }
+
+    #[test]
+    fn rdb_strings_of_db_zero_are_read_with_their_expiry() {
+        let rdb = dump();
+        assert!(is_rdb(&rdb));
+
+        let dump = parse_rdb(&rdb).unwrap();
+        assert_eq!(
+            dump.entries,
+            [
+                entry("a", "uno", Some(4_102_444_800_000)),
+                entry("n", "42", None),
+                entry("m", "-300", None),
+                entry("z", "abababab", None),
+                entry("vieja", "v", Some(1000)),
+            ]
+        );
+        // la lista y la clave de la base 1
+        assert_eq!(dump.skipped, 2);
+    }
+
+    #[test]
+    fn unsupported_or_truncated_rdb_is_rejected() {
+        let mut stream = b"REDIS0011".to_vec();
+        stream.push(15);
+        string(&mut stream, "s");
+        assert!(matches!(parse_rdb(&stream), Err(AppError::BadRequest(_))));
+
+        let rdb = dump();
+        assert!(parse_rdb(&rdb[..rdb.len() - 20]).is_err());
+        assert!(parse_rdb(b"REDIS0099\xFF").is_err());
+        assert!(!is_rdb(b"{\"key\": \"a\"}"));
+    }
+}
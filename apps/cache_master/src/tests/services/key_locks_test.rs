@@ -0,0 +1,54 @@
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use parking_lot::Mutex;
+
+    use crate::infrastructure::adapters::services::KeyLocks;
+
+    #[tokio::test]
+    async fn writes_of_the_same_key_run_one_after_the_other() {
+        let locks = Arc::new(KeyLocks::new(Duration::from_secs(5)));
+        let applied = Arc::new(Mutex::new(Vec::new()));
+
+        let first = locks.lock("k").await;
+        let mut waiters = Vec::new();
+        for i in 1..=3 {
+            let (locks, applied) = (locks.clone(), applied.clone());
+            waiters.push(tokio::spawn(async move {
+                let _serial = locks.lock("k").await;
+                applied.lock().push(i);
+            }));
+            // cada uno llega después del anterior
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        // otra clave no espera
+        drop(locks.lock("other").await);
+        assert!(applied.lock().is_empty());
+
+        drop(first);
+        for w in waiters {
+            w.await.unwrap();
+        }
+        assert_eq!(*applied.lock(), [1, 2, 3]);
+        assert!(locks.is_empty(), "sin nadie esperando no queda el candado");
+    }
+
+    #[tokio::test]
+    async fn a_held_key_is_released_after_max_hold() {
+        let locks = Arc::new(KeyLocks::new(Duration::from_millis(20)));
+
+        let serial = locks.lock("k").await;
+        let held = tokio::spawn(serial.hold(std::future::pending::<()>()));
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        let next = tokio::time::timeout(Duration::from_secs(1), locks.lock("k")).await;
+        assert!(next.is_ok(), "la clave se soltó al vencerse");
+        assert_eq!(held.await.unwrap(), None);
+        drop(next);
+
+        let done = locks.lock("k").await.hold(async { 3 }).await;
+        assert_eq!(done, Some(3));
+        assert!(locks.is_empty());
+    }
+}
@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use app_net::{RequestDataInput, Socket, SocketError};
+    use tokio::sync::mpsc;
+
+    use crate::infrastructure::{
+        adapters::{
+            controllers::router::RateClass,
+            services::{FanoutPolicy, HedgeDelay},
+        },
+        di::config::MasterConfig,
+        live_config::{ConfigKey, LiveConfig},
+    };
+
+    #[test]
+    fn receivers_see_every_change_and_bad_values_leave_the_setting_alone() {
+        let config = LiveConfig::new(&MasterConfig::default());
+        let mut reads = config.read_policy();
+        let limits = config.rate_limits();
+
+        assert_eq!(
+            config.set(ConfigKey::ReadPolicy, "primary").unwrap(),
+            "primary"
+        );
+        assert!(reads.has_changed().unwrap());
+        assert_eq!(
+            *reads.borrow_and_update(),
+            FanoutPolicy::PrimaryThenReplicas
+        );
+        assert_eq!(config.get(ConfigKey::HedgeDelay), "off");
+
+        // cambiar la espera vuelve a cubrir las lecturas
+        assert_eq!(config.set(ConfigKey::HedgeDelay, "25").unwrap(), "25");
+        assert_eq!(
+            *reads.borrow(),
+            FanoutPolicy::Hedged(HedgeDelay::Fixed(Duration::from_millis(25)))
+        );
+        assert_eq!(config.get(ConfigKey::ReadPolicy), "hedged:25");
+
+        config
+            .set(ConfigKey::RateLimit(RateClass::Admin), "5")
+            .unwrap();
+        assert_eq!(limits.borrow().get(&RateClass::Admin), Some(&5));
+
+        for (key, bad) in [
+            (ConfigKey::NodeTimeout, "0"),
+            (ConfigKey::WritePolicy, "quorum:0"),
+            (ConfigKey::HedgeDelay, "ya"),
+            (ConfigKey::RateLimit(RateClass::Data), "-1"),
+        ] {
+            let before = config.get(key);
+            assert!(config.set(key, bad).is_err(), "{key}={bad}");
+            assert_eq!(config.get(key), before);
+        }
+        assert_eq!(
+            "hedge-delay".parse::<ConfigKey>().unwrap(),
+            ConfigKey::HedgeDelay
+        );
+    }
+
+    #[tokio::test]
+    async fn open_sockets_take_the_new_node_timeout() {
+        let config = LiveConfig::new(&MasterConfig::default());
+        // nadie contesta: el request termina por timeout
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let socket = Socket::new("node-1".into(), tx, Duration::from_secs(5))
+            .with_max_duration(config.node_timeout());
+
+        config.set(ConfigKey::NodeTimeout, "20").unwrap();
+        let started = Instant::now();
+        let res = socket
+            .request(RequestDataInput {
+                action: "GET",
+                payload: "k",
+            })
+            .await;
+        assert!(matches!(res, Err(SocketError::Timeout { .. })));
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,45 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        core::domain::models::MemoryWatermarks, infrastructure::adapters::services::MemoryAdmission,
+    };
+
+    #[test]
+    fn a_node_stays_over_until_it_drops_below_the_low_watermark() {
+        let admission = MemoryAdmission::new(MemoryWatermarks::from_percent(90, 80));
+        let node: Arc<str> = Arc::from("n1");
+
+        assert_eq!(admission.observe(&node, Some(0.85)), None);
+        assert_eq!(admission.observe(&node, Some(0.9)), Some(true));
+        assert!(admission.is_over("n1"));
+
+        assert_eq!(
+            admission.observe(&node, Some(0.85)),
+            None,
+            "entre las marcas"
+        );
+        assert!(admission.is_over("n1"));
+        assert_eq!(admission.observe(&node, Some(0.79)), Some(false));
+        assert!(!admission.is_over("n1"));
+    }
+
+    #[test]
+    fn a_node_without_a_memory_limit_is_always_admitted() {
+        let admission = MemoryAdmission::new(MemoryWatermarks::default());
+        let node: Arc<str> = Arc::from("n1");
+        admission.observe(&node, Some(0.95));
+
+        assert_eq!(admission.observe(&node, None), Some(false));
+        assert!(admission.over_nodes().is_empty());
+    }
+
+    #[test]
+    fn percents_are_clamped() {
+        let w = MemoryWatermarks::from_percent(150, 120);
+        assert_eq!((w.high, w.low), (1.0, 1.0));
+        let w = MemoryWatermarks::from_percent(70, 90);
+        assert_eq!((w.high, w.low), (0.7, 0.7));
+    }
+}
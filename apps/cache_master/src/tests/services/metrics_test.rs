@@ -0,0 +1,198 @@
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use app_core::supervisor::Supervisor;
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+        net::{TcpListener, TcpStream},
+    };
+
+    use app_net::NodeStats;
+
+    use crate::{
+        core::domain::models::{ClusterStats, ShardStats},
+        infrastructure::metrics::{MasterMetrics, TopologyGauges},
+        server,
+    };
+
+    #[test]
+    fn requests_are_counted_by_action_and_code() {
+        let m = MasterMetrics::new();
+        m.observe_request("GET", 200, Duration::from_millis(2));
+        m.observe_request("GET", 200, Duration::from_millis(3));
+        m.observe_request("GET", 503, Duration::from_millis(1));
+        m.observe_request("FOO", 400, Duration::from_millis(1));
+
+        assert_eq!(m.requests_total("GET", 200), 2);
+        assert_eq!(m.requests_total("GET", 503), 1);
+        // acciones desconocidas comparten la serie OTHER
+        assert_eq!(m.requests_total("BAR", 400), 1);
+        assert_eq!(m.shed_total(), 1);
+    }
+
+    #[test]
+    fn node_series_are_dropped_when_the_node_leaves() {
+        let m = MasterMetrics::new();
+        let node: Arc<str> = Arc::from("n1");
+        m.observe_node_request(&node, "PUT", Duration::from_millis(4), true);
+        m.observe_node_request(&node, "PUT", Duration::from_secs(2), false);
+
+        let h = m.node_histogram("n1", "PUT").unwrap();
+        assert_eq!((h.count(), h.errors()), (2, 1));
+
+        let text = m.render(&TopologyGauges::default());
+        assert!(
+            text.contains("cache_master_node_request_errors_total{node=\"n1\",action=\"PUT\"} 1\n")
+        );
+
+        m.forget_node("n1");
+        assert!(m.node_histogram("n1", "PUT").is_none());
+        assert!(!m.render(&TopologyGauges::default()).contains("node=\"n1\""));
+    }
+
+    #[test]
+    fn cluster_stats_are_rendered_per_shard() {
+        let m = MasterMetrics::new();
+        assert!(
+            !m.render(&TopologyGauges::default())
+                .contains("cache_master_shard_")
+        );
+
+        let total: NodeStats = "entries=40 bytes=800 hits=30 misses=10 writes=40"
+            .parse()
+            .unwrap();
+        let interval: NodeStats = "entries=40 bytes=800 hits=3 misses=1 writes=4"
+            .parse()
+            .unwrap();
+        m.observe_cluster_stats(Arc::new(ClusterStats {
+            collected_at_ms: 1_000,
+            shards: vec![ShardStats {
+                shard: Arc::from("s1"),
+                node: Arc::from("s1"),
+                total,
+                interval,
+                interval_ms: 1_000,
+            }],
+            unreachable: vec![Arc::from("s2")],
+        }));
+
+        let text = m.render(&TopologyGauges::default());
+        assert!(text.contains("cache_master_cluster_keys 40\n"));
+        assert!(text.contains("cache_master_shard_bytes{shard=\"s1\"} 800\n"));
+        assert!(text.contains("cache_master_shard_hit_ratio{shard=\"s1\"} 0.75\n"));
+        assert!(text.contains("cache_master_shard_ops_per_second{shard=\"s1\"} 8\n"));
+        assert_eq!(
+            m.cluster_stats().unwrap().lines()[2..],
+            ["s2 unreachable".to_string()]
+        );
+    }
+
+    #[test]
+    fn render_includes_topology_gauges() {
+        let text = MasterMetrics::new().render(&TopologyGauges {
+            registered_nodes: 3,
+            shards: 2,
+            ring_nodes: 2,
+            ring_vnodes: 256,
+            peer_masters: 2,
+            reachable_peer_masters: 1,
+            raft_term: 4,
+            raft_leader: 1,
+            raft_commit_index: 17,
+        });
+
+        assert!(text.contains("# TYPE cache_master_registered_nodes gauge\n"));
+        assert!(text.contains("cache_master_registered_nodes 3\n"));
+        assert!(text.contains("cache_master_shards 2\n"));
+        assert!(text.contains("cache_master_ring_vnodes 256\n"));
+        assert!(text.contains("cache_master_peer_masters_reachable 1\n"));
+        assert!(text.contains("cache_master_raft_leader 1\n"));
+        assert!(text.contains("cache_master_raft_commit_index 17\n"));
+        assert!(text.contains("cache_master_shed_requests_total 0\n"));
+    }
+
+    #[tokio::test]
+    async fn metrics_endpoint_reports_served_requests() {
+        let supervisor = Supervisor::new_shared();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let master_addr = listener.local_addr().unwrap();
+        let handle = server::start(listener, &supervisor);
+
+        let http_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let http_addr = http_listener.local_addr().unwrap();
+        server::start_http(http_listener, &handle, &supervisor);
+
+        // cliente: PING responde, GET sin nodos se descarta con 503
+        let mut client = BufReader::new(TcpStream::connect(master_addr).await.unwrap());
+        client
+            .get_mut()
+            .write_all(b"client-1\nREQ 1 PING \"\"\nREQ 2 GET \"k\"\n")
+            .await
+            .unwrap();
+        let mut line = String::new();
+        for _ in 0..2 {
+            line.clear();
+            client.read_line(&mut line).await.unwrap();
+        }
+
+        let mut http = TcpStream::connect(http_addr).await.unwrap();
+        http.write_all(b"GET /metrics HTTP/1.1\r\nHost: x\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut body = String::new();
+        http.read_to_string(&mut body).await.unwrap();
+
+        assert!(body.starts_with("HTTP/1.1 200"), "{body}");
+        assert!(body.contains("text/plain; version=0.0.4"));
+        assert!(body.contains("cache_master_requests_total{action=\"PING\",code=\"200\"} 1\n"));
+        assert!(body.contains("cache_master_requests_total{action=\"GET\",code=\"503\"} 1\n"));
+        assert!(body.contains("cache_master_shed_requests_total 1\n"));
+        assert!(body.contains("cache_master_registered_nodes 0\n"));
+
+        drop(client);
+        assert!(
+            supervisor
+                .shutdown(Duration::from_secs(2))
+                .await
+                .aborted
+                .is_empty()
+        );
+    }
+
+    async fn http_get(addr: std::net::SocketAddr, path: &str) -> String {
+        let mut http = TcpStream::connect(addr).await.unwrap();
+        http.write_all(
+            format!("GET {path} HTTP/1.1\r\nHost: x\r\nConnection: close\r\n\r\n").as_bytes(),
+        )
+        .await
+        .unwrap();
+        let mut body = String::new();
+        http.read_to_string(&mut body).await.unwrap();
+        body
+    }
+
+    #[tokio::test]
+    async fn the_dashboard_is_served_only_on_the_admin_listener() {
+        let supervisor = Supervisor::new_shared();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let handle = server::start(listener, &supervisor);
+
+        let http_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let http_addr = http_listener.local_addr().unwrap();
+        server::start_http(http_listener, &handle, &supervisor);
+        let admin_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let admin_addr = admin_listener.local_addr().unwrap();
+        server::start_admin_http(admin_listener, &handle, &supervisor);
+
+        for path in ["/dashboard", "/log-filter"] {
+            let body = http_get(http_addr, path).await;
+            assert!(body.starts_with("HTTP/1.1 404"), "{path}: {body}");
+        }
+        let body = http_get(admin_addr, "/dashboard").await;
+        assert!(body.starts_with("HTTP/1.1 200"), "{body}");
+        assert!(body.contains("application/json"), "{body}");
+
+        supervisor.shutdown(Duration::from_secs(2)).await;
+    }
+}
@@ -0,0 +1,19 @@
+mod action_router_test;
+mod backup_test;
+mod clock_skew_test;
+mod dashboard_test;
+mod decision_log_test;
+mod failure_detector_test;
+mod fanout_test;
+mod hot_key_copies_test;
+mod import_test;
+mod key_locks_test;
+mod live_config_test;
+mod memory_admission_test;
+mod metrics_test;
+mod node_access_test;
+mod peers_test;
+mod raft_test;
+mod registration_queue_test;
+mod single_flight_test;
+mod write_retries_test;
@@ -0,0 +1,106 @@
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+
+    use crate::infrastructure::adapters::services::{NodeAccess, NodeRule};
+
+    fn rules(rules: &[&str]) -> Vec<NodeRule> {
+        rules.iter().map(|r| r.parse().unwrap()).collect()
+    }
+
+    fn ip(ip: &str) -> Option<IpAddr> {
+        Some(ip.parse().unwrap())
+    }
+
+    #[test]
+    fn rules_are_networks_when_they_parse_as_ips_and_id_patterns_otherwise() {
+        assert_eq!(
+            "10.0.0.0/8".parse::<NodeRule>().unwrap(),
+            NodeRule::Net {
+                addr: "10.0.0.0".parse().unwrap(),
+                prefix: 8
+            }
+        );
+        assert_eq!("::1".parse::<NodeRule>().unwrap().to_string(), "::1/128");
+        assert_eq!(
+            "node-*".parse::<NodeRule>().unwrap(),
+            NodeRule::Id("node-*".into())
+        );
+        assert!("10.0.0.0/33".parse::<NodeRule>().is_err());
+        assert!(" ".parse::<NodeRule>().is_err());
+    }
+
+    #[test]
+    fn id_patterns_take_star_as_any_run_of_characters() {
+        let rule: NodeRule = "node-*-b".parse().unwrap();
+        assert!(rule.matches("node-1-b", None));
+        assert!(rule.matches("node--b", None));
+        assert!(!rule.matches("node-1-a", None));
+        assert!(
+            !"node-1"
+                .parse::<NodeRule>()
+                .unwrap()
+                .matches("node-10", None)
+        );
+        assert!("*".parse::<NodeRule>().unwrap().matches("anything", None));
+    }
+
+    #[test]
+    fn networks_match_v4_v6_and_v4_mapped_peers() {
+        let v4: NodeRule = "192.168.1.0/24".parse().unwrap();
+        assert!(v4.matches("n", ip("192.168.1.77")));
+        assert!(v4.matches("n", ip("::ffff:192.168.1.77")));
+        assert!(!v4.matches("n", ip("192.168.2.1")));
+        assert!(!v4.matches("n", None));
+
+        let v6: NodeRule = "fd00::/8".parse().unwrap();
+        assert!(v6.matches("n", ip("fd12::1")));
+        assert!(!v6.matches("n", ip("fe80::1")));
+        assert!(!v6.matches("n", ip("10.0.0.1")));
+    }
+
+    #[test]
+    fn deny_wins_over_allow_and_a_non_empty_allow_list_is_required() {
+        let access = NodeAccess::new(rules(&["10.0.0.0/8"]), rules(&["node-bad"]));
+
+        assert!(access.check("node-1", ip("10.1.2.3"), None).is_ok());
+        assert!(access.check("node-1", ip("172.16.0.1"), None).is_err());
+        assert_eq!(
+            access.check("node-bad", ip("10.1.2.3"), None),
+            Err("denegado por node-bad".into())
+        );
+        assert!(NodeAccess::default().check("any", None, None).is_ok());
+    }
+
+    #[test]
+    fn bans_refuse_until_they_are_lifted() {
+        let access = NodeAccess::default();
+        let rule: NodeRule = "node-1".parse().unwrap();
+
+        assert!(access.ban(rule.clone()));
+        assert!(!access.ban(rule.clone()));
+        assert!(access.check("node-1", None, None).is_err());
+        assert_eq!(access.bans(), vec![rule.clone()]);
+
+        assert!(access.unban(&rule));
+        assert!(!access.unban(&rule));
+        assert!(access.check("node-1", None, None).is_ok());
+    }
+
+    #[test]
+    fn a_certificate_pins_the_node_id() {
+        let access = NodeAccess::default().with_certs_required(true);
+
+        assert!(access.check("node-a", None, Some("node-a")).is_ok());
+        assert_eq!(
+            access.check("node-b", None, Some("node-a")),
+            Err("el certificado es de node-a".into())
+        );
+        assert!(access.check("node-a", None, None).is_err());
+        assert!(NodeAccess::default().check("node-a", None, None).is_ok());
+
+        // los demás grupos del mismo proceso entran con el mismo certificado
+        assert!(access.check("node-a+1", None, Some("node-a")).is_ok());
+        assert!(access.check("node-b+1", None, Some("node-a")).is_err());
+    }
+}
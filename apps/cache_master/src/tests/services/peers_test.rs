@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        core::domain::{models::AppError, services::TopologyCoordinator},
+        infrastructure::{
+            adapters::services::dashmap_consistent_hasher_service::DashmapConsistentHasherService,
+            peers::{PeerConfig, PeerCoordinator},
+            raft::{Command, TopologyLedger},
+        },
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn an_assignment_only_applies_over_the_shard_it_expected() {
+        let mut ledger = TopologyLedger::default();
+        let assign = |node_id: &str, shard_id: &str, expected: Option<&str>| Command::Assign {
+            node_id: node_id.into(),
+            shard_id: shard_id.into(),
+            expected: expected.map(str::to_string),
+        };
+
+        ledger.apply(1, &assign("r1", "m1", None));
+        ledger.apply(2, &assign("r1", "m2", None));
+        ledger.apply(3, &Command::Noop);
+        assert_eq!(ledger.shard_of("r1"), Some("m1"));
+
+        // su shard se cayó y el líder lo mueve
+        ledger.apply(4, &assign("r1", "m2", Some("m1")));
+        assert_eq!(ledger.shard_of("r1"), Some("m2"));
+        assert_eq!((ledger.applied(), ledger.len()), (4, 1));
+
+        let wire: TopologyLedger = ledger.to_string().parse().unwrap();
+        assert_eq!(wire, ledger);
+    }
+
+    #[tokio::test]
+    async fn a_master_cut_off_from_its_peers_accepts_no_joins() {
+        let ring = Arc::new(DashmapConsistentHasherService::new());
+        let alone = PeerCoordinator::new(PeerConfig::default(), None, ring.clone());
+        assert!(alone.commit_join("m1", "m1").await.is_ok());
+
+        let config = PeerConfig {
+            peers: vec!["127.0.0.1:1".into(), "127.0.0.1:2".into()],
+            ..PeerConfig::default()
+        };
+        let cut_off = PeerCoordinator::new(config, None, ring);
+        assert_eq!((cut_off.masters(), cut_off.quorum()), (3, 2));
+
+        let err = cut_off.commit_join("m1", "m1").await.unwrap_err();
+        assert!(matches!(err, AppError::NoQuorum(_)));
+        // sin proponer nada
+        assert_eq!(cut_off.commit_index(), 0);
+    }
+
+    #[tokio::test]
+    async fn an_unreadable_raft_state_keeps_the_master_out() {
+        let dir = std::env::temp_dir().join(format!("raft-{}", fastrand::u64(..)));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        tokio::fs::write(dir.join("raft.state"), "cualquier cosa")
+            .await
+            .unwrap();
+
+        let config = PeerConfig {
+            peers: vec!["127.0.0.1:1".into()],
+            raft_dir: Some(dir.clone()),
+            ..PeerConfig::default()
+        };
+        let peers = PeerCoordinator::new(
+            config,
+            None,
+            Arc::new(DashmapConsistentHasherService::new()),
+        );
+        let err = peers
+            .handle_rpc("RAFT-VOTE", "1 \"m2\" 0 0")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::NoQuorum(_)));
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+}
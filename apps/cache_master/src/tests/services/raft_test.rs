@@ -0,0 +1,365 @@
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{HashSet, VecDeque},
+        time::Duration,
+    };
+
+    use crate::infrastructure::raft::{
+        AppendRequest, Command, Entry, Message, Outcome, PersistentState, RaftCore, RaftStorage,
+        Role, Snapshot, SnapshotRequest, TopologyLedger, VoteRequest,
+    };
+
+    const STEP_MS: u64 = 5;
+
+    /// Masters de Raft con una red sincrónica en la que se puede aislar a cualquiera.
+    struct Sim {
+        cores: Vec<RaftCore>,
+        cut: HashSet<usize>,
+        now: u64,
+    }
+
+    impl Sim {
+        fn new(n: usize, snapshot_every: usize) -> Self {
+            let cores = (0..n)
+                .map(|i| {
+                    RaftCore::new(
+                        format!("m{i}"),
+                        n - 1,
+                        Duration::from_millis(10),
+                        Duration::from_millis(100),
+                        PersistentState::default(),
+                        0,
+                    )
+                    .with_snapshot_every(snapshot_every)
+                })
+                .collect();
+            Self {
+                cores,
+                cut: HashSet::new(),
+                now: 0,
+            }
+        }
+
+        /// El master al que apunta el par `peer` de `from`.
+        fn target(&self, from: usize, peer: usize) -> usize {
+            (0..self.cores.len())
+                .filter(|i| *i != from)
+                .nth(peer)
+                .unwrap()
+        }
+
+        fn peer_of(&self, of: usize, other: usize) -> usize {
+            (0..self.cores.len())
+                .filter(|i| *i != of)
+                .position(|i| i == other)
+                .unwrap()
+        }
+
+        fn run(&mut self, ms: u64) {
+            for _ in 0..ms / STEP_MS {
+                self.now += STEP_MS;
+                for from in 0..self.cores.len() {
+                    let messages = self.cores[from].tick(self.now);
+                    self.deliver(from, messages);
+                }
+            }
+        }
+
+        fn deliver(&mut self, from: usize, messages: Vec<(usize, Message)>) {
+            let mut queue: VecDeque<_> = messages.into_iter().collect();
+            while let Some((peer, message)) = queue.pop_front() {
+                let to = self.target(from, peer);
+                if self.cut.contains(&from) || self.cut.contains(&to) {
+                    continue;
+                }
+                let now = self.now;
+                let back = self.peer_of(from, to);
+                match message {
+                    Message::Vote(req) => {
+                        let res = self.cores[to].handle_vote(&req, now);
+                        self.cores[from].handle_vote_response(back, res, now);
+                    }
+                    Message::Append(req) => {
+                        let res = self.cores[to].handle_append(req, now);
+                        self.cores[from].handle_append_response(back, res, now);
+                    }
+                    Message::Snapshot(req) => {
+                        let res = self.cores[to].handle_snapshot(req, now);
+                        self.cores[from].handle_append_response(back, res, now);
+                    }
+                }
+            }
+        }
+
+        fn leader(&self) -> usize {
+            (0..self.cores.len())
+                .filter(|i| !self.cut.contains(i))
+                .find(|i| self.cores[*i].role() == Role::Leader)
+                .expect("sin líder")
+        }
+
+        fn assign(&mut self, node_id: &str, shard_id: &str) -> (usize, u64, u64) {
+            let leader = self.leader();
+            let (index, term) = self.assign_on(leader, node_id, shard_id);
+            (leader, index, term)
+        }
+
+        fn assign_on(&mut self, leader: usize, node_id: &str, shard_id: &str) -> (u64, u64) {
+            let expected = self.cores[leader]
+                .ledger()
+                .shard_of(node_id)
+                .map(str::to_string);
+            self.cores[leader]
+                .propose(
+                    Command::Assign {
+                        node_id: node_id.into(),
+                        shard_id: shard_id.into(),
+                        expected,
+                    },
+                    self.now,
+                )
+                .unwrap()
+        }
+    }
+
+    #[test]
+    fn a_leader_is_elected_and_every_master_applies_what_it_commits() {
+        let mut sim = Sim::new(3, 1024);
+        sim.run(500);
+        let leader = sim.leader();
+        let term = sim.cores[leader].term();
+        assert!(
+            sim.cores
+                .iter()
+                .all(|core| core.leader() == Some(&*format!("m{leader}")))
+        );
+
+        let (_, index, term_of_entry) = sim.assign("r1", "m1");
+        assert_eq!(term_of_entry, term);
+        sim.run(50);
+
+        for core in &sim.cores {
+            assert_eq!(core.ledger().shard_of("r1"), Some("m1"));
+            assert_eq!(core.commit_index(), index);
+        }
+        assert_eq!(sim.cores[leader].outcome(index, term), Outcome::Applied);
+        // un latido de más no cambia de líder
+        sim.run(500);
+        assert_eq!((sim.leader(), sim.cores[leader].term()), (leader, term));
+    }
+
+    #[test]
+    fn the_majority_survives_its_leader_and_the_old_leader_drops_what_it_did_not_commit() {
+        let mut sim = Sim::new(3, 1024);
+        sim.run(500);
+        sim.assign("r1", "m1");
+        sim.run(50);
+
+        let old = sim.leader();
+        sim.cut.insert(old);
+        let (lost_index, lost_term) = sim.assign_on(old, "r2", "m-viejo");
+        sim.run(500);
+
+        let new = sim.leader();
+        assert_ne!(new, old);
+        sim.assign("r2", "m2");
+        sim.run(50);
+        assert_eq!(sim.cores[new].ledger().shard_of("r1"), Some("m1"));
+        assert_eq!(sim.cores[new].ledger().shard_of("r2"), Some("m2"));
+
+        sim.cut.clear();
+        sim.run(100);
+        assert_eq!(sim.cores[old].role(), Role::Follower);
+        assert_eq!(sim.cores[old].outcome(lost_index, lost_term), Outcome::Lost);
+        assert_eq!(sim.cores[old].ledger(), sim.cores[new].ledger());
+    }
+
+    #[test]
+    fn a_master_that_fell_behind_catches_up_from_a_snapshot() {
+        let mut sim = Sim::new(3, 2);
+        sim.run(500);
+        let leader = sim.leader();
+        let behind = (leader + 1) % 3;
+        sim.cut.insert(behind);
+        for i in 0..6 {
+            sim.assign(&format!("r{i}"), "m1");
+            sim.run(20);
+        }
+        let snapshot_index = sim.cores[leader].log().snapshot_index();
+        assert!(snapshot_index > 0, "el líder no compactó");
+
+        sim.cut.clear();
+        sim.run(100);
+        assert_eq!(sim.cores[behind].ledger(), sim.cores[leader].ledger());
+        assert!(sim.cores[behind].log().snapshot_index() >= snapshot_index);
+    }
+
+    #[test]
+    fn a_late_snapshot_keeps_the_entries_that_follow_it_unless_they_conflict() {
+        let assign = |node_id: &str| Command::Assign {
+            node_id: node_id.into(),
+            shard_id: "m1".into(),
+            expected: None,
+        };
+        let follower = || {
+            let mut core = RaftCore::new(
+                "m1".into(),
+                2,
+                Duration::from_millis(10),
+                Duration::from_millis(100),
+                PersistentState::default(),
+                0,
+            );
+            // tres entradas del líder, confirmada solo la primera
+            let res = core.handle_append(
+                AppendRequest {
+                    term: 1,
+                    leader_id: "m0".into(),
+                    prev_index: 0,
+                    prev_term: 0,
+                    leader_commit: 1,
+                    entries: ["r1", "r2", "r3"]
+                        .map(|id| Entry {
+                            term: 1,
+                            command: assign(id),
+                        })
+                        .to_vec(),
+                },
+                0,
+            );
+            assert!(res.success && res.last_index == 3);
+            core
+        };
+        let snapshot = |last_term| {
+            let mut ledger = TopologyLedger::default();
+            ledger.apply(1, &assign("r1"));
+            ledger.apply(2, &assign("r2"));
+            SnapshotRequest {
+                term: 1,
+                leader_id: "m0".into(),
+                snapshot: Snapshot { last_term, ledger },
+            }
+        };
+
+        // la instantánea del índice 2 llega después del append que ya trajo el 3
+        let mut core = follower();
+        let res = core.handle_snapshot(snapshot(1), 0);
+        assert!(res.success);
+        assert_eq!(core.commit_index(), 2);
+        assert_eq!(core.log().snapshot_index(), 2);
+        assert_eq!(core.log().last_index(), 3);
+        assert_eq!(core.log().get(3).map(|e| &e.command), Some(&assign("r3")));
+
+        // si el término del índice 2 no coincide, el resto no es del líder
+        let mut core = follower();
+        core.handle_snapshot(snapshot(0), 0);
+        assert_eq!(core.log().last_index(), 2);
+        assert_eq!(core.ledger().shard_of("r2"), Some("m1"));
+    }
+
+    #[test]
+    fn two_assignments_of_the_same_node_in_flight_keep_the_first() {
+        let mut sim = Sim::new(3, 1024);
+        sim.run(500);
+        // las dos parten de que r1 no tiene shard
+        let (leader, first, _) = sim.assign("r1", "m1");
+        let (_, second, _) = sim.assign("r1", "m2");
+        sim.run(50);
+
+        let ledger = sim.cores[leader].ledger();
+        assert!(ledger.applied() >= second && second > first);
+        assert_eq!(ledger.shard_of("r1"), Some("m1"));
+    }
+
+    #[test]
+    fn a_master_that_was_cut_off_does_not_unseat_the_leader_when_it_returns() {
+        let mut sim = Sim::new(3, 1024);
+        sim.run(500);
+        let leader = sim.leader();
+        let term = sim.cores[leader].term();
+        let lonely = (leader + 1) % 3;
+
+        sim.cut.insert(lonely);
+        sim.run(1000);
+        // sondea pero nunca junta la mayoría: no sube el término
+        assert_eq!(sim.cores[lonely].term(), term);
+
+        sim.cut.clear();
+        sim.run(100);
+        assert_eq!((sim.leader(), sim.cores[leader].term()), (leader, term));
+        assert_eq!(sim.cores[lonely].leader(), Some(&*format!("m{leader}")));
+
+        // ni un voto de verdad con el líder a la vista
+        let req = VoteRequest {
+            term: term + 1,
+            candidate_id: format!("m{lonely}"),
+            last_index: sim.cores[lonely].log().last_index(),
+            last_term: sim.cores[lonely].log().last_term(),
+            pre: false,
+        };
+        let follower = 3 - leader - lonely;
+        assert!(!sim.cores[follower].handle_vote(&req, sim.now).granted);
+        assert_eq!(sim.cores[follower].term(), term);
+    }
+
+    #[tokio::test]
+    async fn the_state_is_saved_and_read_back() {
+        let mut sim = Sim::new(3, 1024);
+        sim.run(500);
+        sim.assign("r \"1\"", "m1");
+        sim.run(50);
+        let leader = sim.leader();
+        let state = sim.cores[leader].take_dirty().unwrap();
+        assert_eq!(state.to_string().parse::<PersistentState>().unwrap(), state);
+
+        let dir = std::env::temp_dir().join(format!("raft-{}", fastrand::u64(..)));
+        let storage = RaftStorage::new(&dir);
+        assert_eq!(storage.load().unwrap(), PersistentState::default());
+        storage.save(2, &state).await.unwrap();
+        // una versión vieja que llega tarde no pisa a la nueva
+        storage.save(1, &PersistentState::default()).await.unwrap();
+        assert_eq!(storage.load().unwrap(), state);
+
+        let restarted = RaftCore::new(
+            format!("m{leader}"),
+            2,
+            Duration::from_millis(10),
+            Duration::from_millis(100),
+            storage.load().unwrap(),
+            0,
+        );
+        assert_eq!(restarted.term(), sim.cores[leader].term());
+        assert_eq!(
+            restarted.log().last_index(),
+            sim.cores[leader].log().last_index()
+        );
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[test]
+    fn an_append_round_trips_over_the_wire() {
+        let req = AppendRequest {
+            term: 3,
+            leader_id: "m 1".into(),
+            prev_index: 4,
+            prev_term: 2,
+            leader_commit: 4,
+            entries: vec![
+                Entry {
+                    term: 3,
+                    command: Command::Noop,
+                },
+                Entry {
+                    term: 3,
+                    command: Command::Assign {
+                        node_id: "r1".into(),
+                        shard_id: "m1".into(),
+                        expected: None,
+                    },
+                },
+            ],
+        };
+        assert_eq!(req.to_string().parse::<AppendRequest>().unwrap(), req);
+    }
+}
@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        time::Duration,
+    };
+
+    use crate::infrastructure::adapters::services::{RegistrationLimits, RegistrationQueue};
+
+    #[tokio::test]
+    async fn registrations_run_at_most_concurrency_at_a_time() {
+        let queue = Arc::new(RegistrationQueue::new(RegistrationLimits {
+            concurrency: 2,
+            jitter: Duration::ZERO,
+        }));
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let mut nodes = Vec::new();
+        for _ in 0..10 {
+            let (queue, running, peak) = (queue.clone(), running.clone(), peak.clone());
+            nodes.push(tokio::spawn(async move {
+                let _turn = queue.acquire().await;
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+            }));
+        }
+        for node in nodes {
+            node.await.unwrap();
+        }
+
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+        assert_eq!(queue.available(), 2);
+    }
+
+    #[tokio::test]
+    async fn a_newcomer_waits_in_line_until_a_turn_is_released() {
+        let queue = RegistrationQueue::new(RegistrationLimits {
+            concurrency: 1,
+            jitter: Duration::from_millis(20),
+        });
+
+        let first = queue.acquire().await;
+        let waiting = queue.acquire();
+        tokio::pin!(waiting);
+        // más que el jitter: ya está en la fila y sigue esperando
+        assert!(
+            tokio::time::timeout(Duration::from_millis(100), &mut waiting)
+                .await
+                .is_err()
+        );
+
+        drop(first);
+        let _second = tokio::time::timeout(Duration::from_millis(100), waiting)
+            .await
+            .expect("el turno liberado no pasó al que esperaba");
+    }
+}
@@ -0,0 +1,67 @@
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        time::Duration,
+    };
+
+    use tokio::sync::Notify;
+
+    use crate::infrastructure::adapters::services::SingleFlight;
+
+    #[tokio::test]
+    async fn concurrent_calls_with_the_same_key_share_one_execution() {
+        let flights = Arc::new(SingleFlight::<&str, u32>::new());
+        let runs = Arc::new(AtomicUsize::new(0));
+        let release = Arc::new(Notify::new());
+
+        let mut waiters = Vec::new();
+        for _ in 0..50 {
+            let (flights, runs, release) = (flights.clone(), runs.clone(), release.clone());
+            waiters.push(tokio::spawn(async move {
+                flights
+                    .run("k", || async move {
+                        runs.fetch_add(1, Ordering::SeqCst);
+                        release.notified().await;
+                        7
+                    })
+                    .await
+            }));
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        release.notify_one();
+
+        for w in waiters {
+            assert_eq!(w.await.unwrap(), 7);
+        }
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_eq!(flights.in_flight(), 0);
+
+        // terminado el vuelo, la siguiente llamada vuelve a ejecutar
+        assert_eq!(flights.run("k", || async { 8 }).await, 8);
+    }
+
+    #[tokio::test]
+    async fn a_waiter_takes_over_when_the_first_call_is_cancelled() {
+        let flights = Arc::new(SingleFlight::<&str, u32>::new());
+
+        let first = {
+            let flights = flights.clone();
+            tokio::spawn(async move { flights.run("k", std::future::pending).await })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let second = {
+            let flights = flights.clone();
+            tokio::spawn(async move { flights.run("k", || async { 3 }).await })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        first.abort();
+
+        assert_eq!(second.await.unwrap(), 3);
+        assert_eq!(flights.in_flight(), 0);
+    }
+}
@@ -0,0 +1,148 @@
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use app_core::clock::SimulatedClock;
+
+    use crate::infrastructure::write_retries::{WriteRetries, WriteRetryConfig};
+
+    fn retries(config: WriteRetryConfig) -> (WriteRetries, Arc<SimulatedClock>) {
+        let clock = Arc::new(SimulatedClock::new(0));
+        (WriteRetries::new(config, clock.clone()), clock)
+    }
+
+    fn config() -> WriteRetryConfig {
+        WriteRetryConfig {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(100),
+            ..WriteRetryConfig::default()
+        }
+    }
+
+    #[test]
+    fn a_missed_write_is_retried_with_backoff_until_it_lands_in_the_dead_letters() {
+        let (retries, clock) = retries(config());
+        let (node, payload): (Arc<str>, Arc<str>) = (Arc::from("n2"), Arc::from("k v"));
+        let version = retries.next_version("k");
+        retries.missed(&node, "k", version, &payload, "timeout".into());
+
+        assert!(retries.due().is_empty(), "todavía no pasó el primer delay");
+        clock.advance(Duration::from_millis(100));
+        let due = retries.due();
+        assert_eq!(due.len(), 1);
+        assert!(retries.due().is_empty(), "en vuelo no se repite");
+
+        retries.retried(&due[0], Err("caído".into()));
+        clock.advance(Duration::from_millis(100));
+        assert!(retries.due().is_empty(), "el segundo espera el doble");
+        clock.advance(Duration::from_millis(100));
+        let due = retries.due();
+        retries.retried(&due[0], Err("caído".into()));
+
+        clock.advance(Duration::from_millis(400));
+        let due = retries.due();
+        retries.retried(&due[0], Err("caído".into()));
+        assert_eq!(retries.pending_len(), 0);
+        let dead = retries.dead_letters();
+        assert_eq!(dead.len(), 1);
+        assert_eq!((dead[0].key.as_str(), dead[0].attempts), ("k", 3));
+        assert_eq!(dead[0].last_error, "caído");
+    }
+
+    #[test]
+    fn a_successful_retry_drops_the_write() {
+        let (retries, clock) = retries(config());
+        let (node, payload): (Arc<str>, Arc<str>) = (Arc::from("n2"), Arc::from("k v"));
+        let version = retries.next_version("k");
+        retries.missed(&node, "k", version, &payload, "timeout".into());
+
+        clock.advance(Duration::from_millis(100));
+        let due = retries.due();
+        retries.retried(&due[0], Ok(()));
+        assert_eq!(retries.pending_len(), 0);
+        assert!(retries.dead_letters().is_empty());
+    }
+
+    #[test]
+    fn a_newer_write_of_the_key_supersedes_the_missed_one() {
+        let (retries, clock) = retries(config());
+        let (node, payload): (Arc<str>, Arc<str>) = (Arc::from("n2"), Arc::from("k v1"));
+        let old = retries.next_version("k");
+        retries.missed(&node, "k", old, &payload, "timeout".into());
+        let new = retries.next_version("k");
+
+        clock.advance(Duration::from_millis(100));
+        assert!(retries.due().is_empty(), "la v1 ya no se manda");
+        assert_eq!(retries.pending_len(), 0);
+
+        // el fallo de un nodo lento que llega después de la escritura nueva
+        retries.missed(&node, "k", old, &payload, "timeout".into());
+        assert_eq!(retries.pending_len(), 0);
+
+        retries.missed(&node, "k", new, &Arc::from("k v2"), "timeout".into());
+        assert_eq!(retries.pending_len(), 1);
+        retries.confirmed(&node, "k", new);
+        assert_eq!(retries.pending_len(), 0);
+    }
+
+    #[test]
+    fn a_full_queue_sends_the_write_straight_to_the_dead_letters() {
+        let (retries, _clock) = retries(WriteRetryConfig {
+            capacity: 1,
+            ..config()
+        });
+        let (node, payload): (Arc<str>, Arc<str>) = (Arc::from("n2"), Arc::from("x"));
+        for key in ["a", "b"] {
+            let version = retries.next_version(key);
+            retries.missed(&node, key, version, &payload, "timeout".into());
+        }
+
+        assert_eq!(retries.pending_len(), 1);
+        let dead = retries.dead_letters();
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].key, "b");
+        assert!(dead[0].last_error.starts_with("cola de reintentos llena"));
+    }
+
+    #[test]
+    fn dead_letters_can_be_requeued_or_cleared() {
+        let (retries, clock) = retries(WriteRetryConfig {
+            max_attempts: 1,
+            ..config()
+        });
+        let (node, payload): (Arc<str>, Arc<str>) = (Arc::from("n2"), Arc::from("x"));
+        for key in ["a", "b"] {
+            let version = retries.next_version(key);
+            retries.missed(&node, key, version, &payload, "timeout".into());
+        }
+        clock.advance(Duration::from_millis(100));
+        for write in retries.due() {
+            retries.retried(&write, Err("caído".into()));
+        }
+        assert_eq!(retries.dead_letters().len(), 2);
+
+        // una de las claves se volvió a escribir: su dead letter quedó vieja
+        retries.next_version("b");
+        assert_eq!(retries.requeue_dead(), 1);
+        assert_eq!(retries.pending_len(), 1);
+        let due = retries.due();
+        assert_eq!((due[0].key.as_str(), due[0].attempts), ("a", 0));
+
+        retries.retried(&due[0], Err("caído".into()));
+        assert_eq!(retries.clear_dead(), 1);
+        assert!(retries.dead_letters().is_empty());
+    }
+
+    #[test]
+    fn a_disabled_queue_tracks_nothing() {
+        let (retries, _clock) = retries(WriteRetryConfig {
+            capacity: 0,
+            ..config()
+        });
+        let (node, payload): (Arc<str>, Arc<str>) = (Arc::from("n2"), Arc::from("x"));
+        let version = retries.next_version("k");
+        retries.missed(&node, "k", version, &payload, "timeout".into());
+        assert_eq!(retries.pending_len(), 0);
+        assert!(retries.dead_letters().is_empty());
+    }
+}
@@ -1,12 +1,22 @@
 use async_trait::async_trait;
 use parking_lot::Mutex;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
 
 use crate::core::domain::{
-    models::AppError,
-    services::{ConsistentHasherService, NetworkService},
+    models::{AppError, ConditionalGet, DomainEventBus, PutKey},
+    services::{ConsistentHasherService, NetworkService, TopologyCoordinator},
+};
+use app_core::{
+    clock::{AppTime, Clock},
+    events::EventBus,
 };
-use app_core::clock::{AppTime, Clock};
+use app_net::{PutCondition, TxCommand};
 
 // ----------------- MockHasher -----------------
 
@@ -17,6 +27,10 @@ pub struct MockHasher {
 
     // nodo que devolverá get_node_id_from_hash
     pub node_for_hash: Mutex<Option<String>>,
+    // los que le siguen en get_node_ids_from_hash
+    pub next_nodes_for_hash: Mutex<Vec<String>>,
+    /// Dueño de cada clave que figura; las demás van a `node_for_hash`.
+    pub node_for_key: Mutex<HashMap<String, String>>,
 
     // tracking
     pub last_add_node: Mutex<Option<String>>,
@@ -30,6 +44,8 @@ impl MockHasher {
             add_node_result: true,
             node_exists_result: true,
             node_for_hash: Mutex::new(None),
+            next_nodes_for_hash: Mutex::new(Vec::new()),
+            node_for_key: Mutex::new(HashMap::new()),
             last_add_node: Mutex::new(None),
             last_node_exists: Mutex::new(None),
             last_remove_node: Mutex::new(None),
@@ -46,10 +62,23 @@ impl MockHasher {
     pub fn set_node_for_hash(&self, node_id: Option<&str>) {
         *self.node_for_hash.lock() = node_id.map(|s| s.to_string());
     }
+
+    pub fn set_node_for_key(&self, key: &str, node_id: &str) {
+        self.node_for_key
+            .lock()
+            .insert(key.to_string(), node_id.to_string());
+    }
+
+    pub fn set_next_nodes_for_hash(&self, node_ids: &[&str]) {
+        *self.next_nodes_for_hash.lock() = node_ids.iter().map(|s| s.to_string()).collect();
+    }
 }
 
 impl ConsistentHasherService for MockHasher {
-    fn create_hash(&self, _key: &str) -> String {
+    fn create_hash(&self, key: &str) -> String {
+        if self.node_for_key.lock().contains_key(key) {
+            return format!("hash:{key}");
+        }
         "hash".into()
     }
     fn add_node(&self, node_id: &str) -> bool {
@@ -64,13 +93,33 @@ impl ConsistentHasherService for MockHasher {
         *self.last_node_exists.lock() = Some(node_id.to_string());
         self.node_exists_result
     }
-    fn get_node_id_from_hash(&self, _hash: &str) -> Option<String> {
+    fn get_node_id_from_hash(&self, hash: &str) -> Option<String> {
+        if let Some(key) = hash.strip_prefix("hash:") {
+            return self.node_for_key.lock().get(key).cloned();
+        }
         self.node_for_hash.lock().clone()
     }
+    fn get_node_ids_from_hash(&self, hash: &str, count: usize) -> Vec<String> {
+        let owner = self.get_node_id_from_hash(hash);
+        owner
+            .into_iter()
+            .chain(self.next_nodes_for_hash.lock().iter().cloned())
+            .take(count)
+            .collect()
+    }
+}
+
+impl Default for MockHasher {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 // ----------------- MockNetwork -----------------
 
+type PutCall = (String, String, String, Option<u64>);
+type PutIfCall = (String, String, PutCondition);
+
 pub struct MockNetwork {
     // configurables
     pub next_master_for_replica: Mutex<Option<String>>,
@@ -81,16 +130,43 @@ pub struct MockNetwork {
 
     // GET
     pub request_get_key_result: Mutex<Result<Option<String>, AppError>>,
+    /// Si el nodo figura, `request_get_key` devuelve esto en vez de `request_get_key_result`.
+    pub request_get_key_by_node: Mutex<HashMap<String, Option<String>>>,
+    /// Lo mismo por clave, si el nodo no figura.
+    pub request_get_key_by_key: Mutex<HashMap<String, Option<String>>>,
 
     // PUT
     pub request_put_key_result: Mutex<Result<bool, AppError>>,
 
+    // MULTI
+    pub request_multi_result: Mutex<Result<Vec<String>, AppError>>,
+
     // tracking
     pub last_add_master: Mutex<Option<String>>,
     pub last_add_replica: Mutex<Option<(String, String)>>,
     pub last_remove_node: Mutex<Option<String>>,
     pub last_request_get: Mutex<Option<(String, String)>>,
-    pub last_request_put: Mutex<Option<(String, String, String, Option<u64>)>>,
+    /// Ventana del último `request_get_key_refresh`.
+    pub last_request_get_refresh: Mutex<Option<u64>>,
+    /// `read_from` del último `request_get_key_from`.
+    pub last_request_get_from: Mutex<Option<String>>,
+    pub last_request_put: Mutex<Option<PutCall>>,
+    /// Todos los `request_put_key`, en orden.
+    pub request_puts: Mutex<Vec<PutCall>>,
+    pub last_request_put_if: Mutex<Option<PutIfCall>>,
+    /// Tags del último `request_put_key` o `request_put_key_if`.
+    pub last_request_put_tags: Mutex<Vec<String>>,
+    /// `idem=` del último `request_put_key` o `request_put_key_if`.
+    pub last_request_put_idempotency: Mutex<Option<String>>,
+    pub last_request_multi: Mutex<Option<(String, Vec<TxCommand>)>>,
+    /// Todos los `request_multi`, en orden.
+    pub request_multis: Mutex<Vec<(String, Vec<TxCommand>)>>,
+
+    // RENAME
+    pub request_rename_result: Mutex<Result<(), AppError>>,
+    pub last_request_rename: Mutex<Option<(String, String, String)>>,
+    /// Lo que devuelve `request_key_expiry`.
+    pub key_expiry: Mutex<Option<(u64, Option<u64>)>>,
 }
 
 impl MockNetwork {
@@ -102,12 +178,26 @@ impl MockNetwork {
             replica_count: Mutex::new(0),
             remove_result: Mutex::new(Ok(true)),
             request_get_key_result: Mutex::new(Ok(None)),
+            request_get_key_by_node: Mutex::new(HashMap::new()),
+            request_get_key_by_key: Mutex::new(HashMap::new()),
             request_put_key_result: Mutex::new(Ok(true)),
+            request_multi_result: Mutex::new(Ok(vec![])),
             last_add_master: Mutex::new(None),
             last_add_replica: Mutex::new(None),
             last_remove_node: Mutex::new(None),
             last_request_get: Mutex::new(None),
+            request_puts: Mutex::new(Vec::new()),
+            last_request_get_refresh: Mutex::new(None),
+            last_request_get_from: Mutex::new(None),
             last_request_put: Mutex::new(None),
+            last_request_put_if: Mutex::new(None),
+            last_request_put_tags: Mutex::new(Vec::new()),
+            last_request_put_idempotency: Mutex::new(None),
+            last_request_multi: Mutex::new(None),
+            request_multis: Mutex::new(Vec::new()),
+            request_rename_result: Mutex::new(Ok(())),
+            last_request_rename: Mutex::new(None),
+            key_expiry: Mutex::new(None),
         }
     }
 
@@ -133,11 +223,26 @@ impl MockNetwork {
     pub fn set_request_put_key_result(&self, r: Result<bool, AppError>) {
         *self.request_put_key_result.lock() = r;
     }
+    fn put_ack(&self, node_id: &str) -> Result<Option<String>, AppError> {
+        self.request_put_key_result
+            .lock()
+            .clone()
+            .map(|written| written.then(|| node_id.to_string()))
+    }
+    pub fn set_request_multi_result(&self, r: Result<Vec<String>, AppError>) {
+        *self.request_multi_result.lock() = r;
+    }
+}
+
+impl Default for MockNetwork {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[async_trait]
 impl NetworkService for MockNetwork {
-    fn get_node_id_with_less_replicas(&self) -> Option<String> {
+    fn get_node_id_with_less_replicas(&self, _node_id: &str) -> Option<String> {
         self.next_master_for_replica.lock().clone()
     }
 
@@ -168,22 +273,126 @@ impl NetworkService for MockNetwork {
         *self.replica_count.lock()
     }
 
+    /// Con `Ok(true)` confirma `node_id`.
     async fn request_put_key(
         &self,
         node_id: &str,
-        key: &str,
-        value: &str,
-        ttl: Option<u64>,
-    ) -> Result<bool, AppError> {
-        *self.last_request_put.lock() =
-            Some((node_id.to_string(), key.to_string(), value.to_string(), ttl));
-        self.request_put_key_result.lock().clone()
+        put: &PutKey<'_>,
+    ) -> Result<Option<String>, AppError> {
+        *self.last_request_put_tags.lock() = put.tags.to_vec();
+        *self.last_request_put_idempotency.lock() = put.idempotency.map(str::to_string);
+        let call = (
+            node_id.to_string(),
+            put.key.to_string(),
+            put.value.to_string(),
+            put.expires_at,
+        );
+        self.request_puts.lock().push(call.clone());
+        *self.last_request_put.lock() = Some(call);
+        self.put_ack(node_id)
+    }
+
+    /// Comparte el resultado configurado con `request_put_key`.
+    async fn request_put_key_if(
+        &self,
+        node_id: &str,
+        put: &PutKey<'_>,
+        condition: &PutCondition,
+    ) -> Result<Option<String>, AppError> {
+        *self.last_request_put_tags.lock() = put.tags.to_vec();
+        *self.last_request_put_idempotency.lock() = put.idempotency.map(str::to_string);
+        *self.last_request_put_if.lock() =
+            Some((node_id.to_string(), put.key.to_string(), condition.clone()));
+        self.put_ack(node_id)
     }
 
     async fn request_get_key(&self, node_id: &str, key: &str) -> Result<Option<String>, AppError> {
         *self.last_request_get.lock() = Some((node_id.to_string(), key.to_string()));
+        if let Some(value) = self.request_get_key_by_node.lock().get(node_id) {
+            return Ok(value.clone());
+        }
+        if let Some(value) = self.request_get_key_by_key.lock().get(key) {
+            return Ok(value.clone());
+        }
         self.request_get_key_result.lock().clone()
     }
+
+    async fn request_get_key_from(
+        &self,
+        node_id: &str,
+        key: &str,
+        read_from: &str,
+    ) -> Result<Option<String>, AppError> {
+        *self.last_request_get_from.lock() = Some(read_from.to_string());
+        self.request_get_key(node_id, key).await
+    }
+
+    /// Comparte `request_get_key_result`; toda clave existente tiene versión 1.
+    async fn request_get_key_if_not_version(
+        &self,
+        node_id: &str,
+        key: &str,
+        if_not_version: u64,
+    ) -> Result<ConditionalGet, AppError> {
+        *self.last_request_get.lock() = Some((node_id.to_string(), key.to_string()));
+        Ok(match self.request_get_key_result.lock().clone()? {
+            None => ConditionalGet::Missing,
+            Some(_) if if_not_version == 1 => ConditionalGet::NotModified { version: 1 },
+            Some(value) => ConditionalGet::Modified { value, version: 1 },
+        })
+    }
+
+    /// Comparte `request_get_key_result` y siempre elige a quien pregunta.
+    async fn request_get_key_refresh(
+        &self,
+        node_id: &str,
+        key: &str,
+        window_ms: u64,
+    ) -> Result<Option<(String, bool)>, AppError> {
+        *self.last_request_get.lock() = Some((node_id.to_string(), key.to_string()));
+        *self.last_request_get_refresh.lock() = Some(window_ms);
+        let result = self.request_get_key_result.lock().clone();
+        result.map(|value| value.map(|v| (v, true)))
+    }
+
+    /// Comparte `request_get_key_result`: nada está vencido ni se elige para refrescar.
+    async fn request_get_key_stale(
+        &self,
+        node_id: &str,
+        key: &str,
+        _max_stale_ms: u64,
+    ) -> Result<Option<(String, bool, u64)>, AppError> {
+        *self.last_request_get.lock() = Some((node_id.to_string(), key.to_string()));
+        let result = self.request_get_key_result.lock().clone();
+        result.map(|value| value.map(|v| (v, false, 0)))
+    }
+
+    async fn request_multi(
+        &self,
+        node_id: &str,
+        commands: &[TxCommand],
+    ) -> Result<Vec<String>, AppError> {
+        *self.last_request_multi.lock() = Some((node_id.to_string(), commands.to_vec()));
+        self.request_multis
+            .lock()
+            .push((node_id.to_string(), commands.to_vec()));
+        self.request_multi_result.lock().clone()
+    }
+
+    async fn request_rename(&self, node_id: &str, from: &str, to: &str) -> Result<(), AppError> {
+        *self.last_request_rename.lock() =
+            Some((node_id.to_string(), from.to_string(), to.to_string()));
+        self.request_rename_result.lock().clone()
+    }
+
+    async fn request_key_expiry(
+        &self,
+        node_id: &str,
+        key: &str,
+    ) -> Result<Option<(u64, Option<u64>)>, AppError> {
+        *self.last_request_get.lock() = Some((node_id.to_string(), key.to_string()));
+        Ok(*self.key_expiry.lock())
+    }
 }
 
 // ----------------- MockClock -----------------
@@ -208,3 +417,42 @@ impl Clock for MockClock {
         AppTime::new(self.now_ms.load(Ordering::SeqCst))
     }
 }
+
+// ----------------- MockCoordinator -----------------
+
+/// Acepta cada propuesta salvo que haya una respuesta encolada en `replies`.
+#[derive(Default)]
+pub struct MockCoordinator {
+    pub agreed: Mutex<Option<String>>,
+    pub replies: Mutex<VecDeque<Result<(), AppError>>>,
+    pub proposals: Mutex<Vec<(String, String)>>,
+}
+
+impl MockCoordinator {
+    pub fn with_replies(replies: impl IntoIterator<Item = Result<(), AppError>>) -> Self {
+        Self {
+            replies: Mutex::new(replies.into_iter().collect()),
+            ..Self::default()
+        }
+    }
+}
+
+#[async_trait]
+impl TopologyCoordinator for MockCoordinator {
+    fn agreed_shard(&self, _node_id: &str) -> Option<String> {
+        self.agreed.lock().clone()
+    }
+
+    async fn commit_join(&self, node_id: &str, shard_id: &str) -> Result<(), AppError> {
+        self.proposals
+            .lock()
+            .push((node_id.to_string(), shard_id.to_string()));
+        self.replies.lock().pop_front().unwrap_or(Ok(()))
+    }
+}
+
+// ----------------- EventBus -----------------
+
+pub fn bus() -> Arc<DomainEventBus> {
+    EventBus::new_shared(16)
+}
@@ -5,11 +5,12 @@ mod tests {
     use crate::{
         core::{
             domain::models::{
-                AppError, NodeType, usecases::assign_node_use_case::AssignNodeUseCaseInput,
+                AppError, DomainEvent, NodeType,
+                usecases::assign_node_use_case::AssignNodeUseCaseInput,
             },
             usecases::AssignNodeUseCase,
         },
-        tests::test_mocks::{MockHasher, MockNetwork},
+        tests::test_mocks::{MockCoordinator, MockHasher, MockNetwork, bus},
     };
     use std::sync::Arc;
 
@@ -19,7 +20,7 @@ mod tests {
     async fn validate_fails_on_empty_node_id() {
         let hasher = Arc::new(MockHasher::new());
         let net = Arc::new(MockNetwork::new());
-        let uc = AssignNodeUseCase::new(hasher, net);
+        let uc = AssignNodeUseCase::new(hasher, net, bus());
 
         let input = AssignNodeUseCaseInput {
             node_id: "".into(),
@@ -33,7 +34,7 @@ mod tests {
     async fn master_insert_happy_path() {
         let hasher = Arc::new(MockHasher::with_exists(true));
         let net = Arc::new(MockNetwork::new());
-        let uc = AssignNodeUseCase::new(hasher.clone(), net.clone());
+        let uc = AssignNodeUseCase::new(hasher.clone(), net.clone(), bus());
 
         let input = AssignNodeUseCaseInput {
             node_id: "m1".into(),
@@ -51,7 +52,7 @@ mod tests {
     async fn master_insert_fails_if_hasher_reports_not_exists() {
         let hasher = Arc::new(MockHasher::with_exists(false));
         let net = Arc::new(MockNetwork::new());
-        let uc = AssignNodeUseCase::new(hasher, net);
+        let uc = AssignNodeUseCase::new(hasher, net, bus());
 
         let input = AssignNodeUseCaseInput {
             node_id: "m2".into(),
@@ -70,7 +71,7 @@ mod tests {
         let net = Arc::new(MockNetwork::new());
         net.set_next_master(Some("m1")); // el servicio dirá que m1 es el master con menos réplicas
 
-        let uc = AssignNodeUseCase::new(hasher, net.clone());
+        let uc = AssignNodeUseCase::new(hasher, net.clone(), bus());
         let input = AssignNodeUseCaseInput {
             node_id: "r1".into(),
             node_type: NodeType::Replica,
@@ -94,7 +95,7 @@ mod tests {
         let net = Arc::new(MockNetwork::new());
         net.set_next_master(None); // sin masters
 
-        let uc = AssignNodeUseCase::new(hasher, net);
+        let uc = AssignNodeUseCase::new(hasher, net, bus());
         let input = AssignNodeUseCaseInput {
             node_id: "rX".into(),
             node_type: NodeType::Replica,
@@ -106,4 +107,161 @@ mod tests {
             _ => panic!("Esperaba ConnectionError(\"No hay nodos en la red\")"),
         }
     }
+
+    #[tokio::test]
+    async fn publishes_node_joined_with_its_shard() {
+        let hasher = Arc::new(MockHasher::new());
+        let net = Arc::new(MockNetwork::new());
+        net.set_next_master(Some("m1"));
+        let events = bus();
+        let mut rx = events.subscribe();
+
+        let uc = AssignNodeUseCase::new(hasher, net, events);
+
+        uc.execute(AssignNodeUseCaseInput {
+            node_id: "m1".into(),
+            node_type: NodeType::Master,
+        })
+        .await
+        .unwrap();
+        uc.execute(AssignNodeUseCaseInput {
+            node_id: "r1".into(),
+            node_type: NodeType::Replica,
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(
+            rx.recv().await.unwrap().event,
+            DomainEvent::NodeJoined {
+                node_id: "m1".into(),
+                shard_id: "m1".into()
+            }
+        );
+        assert_eq!(
+            rx.recv().await.unwrap().event,
+            DomainEvent::NodeJoined {
+                node_id: "r1".into(),
+                shard_id: "m1".into()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn does_not_publish_when_network_rejects_node() {
+        let hasher = Arc::new(MockHasher::new());
+        let net = Arc::new(MockNetwork::new());
+        net.set_add_master_result(Ok(false));
+        let events = bus();
+        let mut rx = events.subscribe();
+
+        let uc = AssignNodeUseCase::new(hasher, net, events);
+        let out = uc
+            .execute(AssignNodeUseCaseInput {
+                node_id: "m1".into(),
+                node_type: NodeType::Master,
+            })
+            .await
+            .unwrap();
+
+        assert!(!out.success);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn a_replica_goes_to_the_shard_the_masters_agreed_on() {
+        let hasher = Arc::new(MockHasher::new());
+        let net = Arc::new(MockNetwork::new());
+        net.set_next_master(Some("m1"));
+        let coordinator = Arc::new(MockCoordinator::default());
+        *coordinator.agreed.lock() = Some("m2".into());
+
+        let uc = AssignNodeUseCase::new(hasher, net.clone(), bus())
+            .with_coordinator(coordinator.clone());
+        uc.execute(AssignNodeUseCaseInput {
+            node_id: "r1".into(),
+            node_type: NodeType::Replica,
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(
+            net.last_add_replica.lock().clone(),
+            Some(("m2".to_string(), "r1".to_string()))
+        );
+        assert_eq!(
+            *coordinator.proposals.lock(),
+            [("r1".to_string(), "m2".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn a_replica_whose_primary_has_not_come_back_waits_for_it() {
+        // el master reinició: m2 está en el acuerdo pero todavía no se conectó
+        let hasher = Arc::new(MockHasher::with_exists(false));
+        let net = Arc::new(MockNetwork::new());
+        net.set_next_master(Some("m1"));
+        let coordinator = Arc::new(MockCoordinator::default());
+        *coordinator.agreed.lock() = Some("m2".into());
+
+        let uc = AssignNodeUseCase::new(hasher, net.clone(), bus())
+            .with_coordinator(coordinator.clone());
+        let err = uc
+            .execute(AssignNodeUseCaseInput {
+                node_id: "r1".into(),
+                node_type: NodeType::Replica,
+            })
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, AppError::ConnectionError(_)));
+        assert!(net.last_add_replica.lock().is_none());
+        assert!(coordinator.proposals.lock().is_empty());
+    }
+
+    #[tokio::test]
+    async fn a_join_without_quorum_leaves_the_ring_alone() {
+        let hasher = Arc::new(MockHasher::new());
+        let net = Arc::new(MockNetwork::new());
+        let coordinator = Arc::new(MockCoordinator::with_replies([Err(AppError::NoQuorum(
+            "1 de 3".into(),
+        ))]));
+
+        let uc = AssignNodeUseCase::new(hasher.clone(), net.clone(), bus())
+            .with_coordinator(coordinator);
+        let err = uc
+            .execute(AssignNodeUseCaseInput {
+                node_id: "m1".into(),
+                node_type: NodeType::Master,
+            })
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, AppError::NoQuorum(_)));
+        assert!(hasher.last_add_node.lock().is_none());
+        assert!(net.last_add_master.lock().is_none());
+    }
+
+    #[tokio::test]
+    async fn a_join_that_lost_a_race_is_proposed_again() {
+        let hasher = Arc::new(MockHasher::new());
+        let net = Arc::new(MockNetwork::new());
+        net.set_next_master(Some("m1"));
+        let coordinator = Arc::new(MockCoordinator::with_replies([Err(AppError::Conflict(
+            "epoch 3".into(),
+        ))]));
+
+        let uc = AssignNodeUseCase::new(hasher, net.clone(), bus())
+            .with_coordinator(coordinator.clone());
+        let out = uc
+            .execute(AssignNodeUseCaseInput {
+                node_id: "r1".into(),
+                node_type: NodeType::Replica,
+            })
+            .await
+            .unwrap();
+
+        assert!(out.success);
+        assert_eq!(coordinator.proposals.lock().len(), 2);
+    }
 }
@@ -1,9 +1,9 @@
 #[cfg(test)]
 mod tests {
-    use app_core::{UseCase, UseCaseValidatable};
-    use std::sync::Arc;
+    use app_core::{UseCase, UseCaseExt, UseCaseValidatable, clock::SimulatedClock};
+    use std::{sync::Arc, time::Duration};
 
-    use crate::core::domain::models::{AppError, usecases::GetKeyUseCaseInput};
+    use crate::core::domain::models::{AppError, ChunkManifest, usecases::GetKeyUseCaseInput};
     use crate::core::usecases::GetKeyUseCase;
     use crate::tests::test_mocks::{MockHasher, MockNetwork}; // ajusta el path a tus mocks
 
@@ -13,7 +13,13 @@ mod tests {
         let net = Arc::new(MockNetwork::new());
         let uc = GetKeyUseCase::new(hasher, net);
 
-        let input = GetKeyUseCaseInput { key: "".into() };
+        let input = GetKeyUseCaseInput {
+            key: "".into(),
+            refresh_ms: None,
+            if_not_version: None,
+            stale_ms: None,
+            read_from: None,
+        };
         let err = uc.validate(&input).await.unwrap_err();
 
         match err {
@@ -32,6 +38,10 @@ mod tests {
 
         let input = GetKeyUseCaseInput {
             key: "mykey".into(),
+            refresh_ms: None,
+            if_not_version: None,
+            stale_ms: None,
+            read_from: None,
         };
         let err = uc.execute(input).await.unwrap_err();
 
@@ -54,7 +64,13 @@ mod tests {
 
         let uc = GetKeyUseCase::new(hasher.clone(), net.clone());
 
-        let input = GetKeyUseCaseInput { key: "k1".into() };
+        let input = GetKeyUseCaseInput {
+            key: "k1".into(),
+            refresh_ms: None,
+            if_not_version: None,
+            stale_ms: None,
+            read_from: None,
+        };
         let out = uc.execute(input).await.expect("no debería fallar");
 
         assert!(out.success);
@@ -77,7 +93,13 @@ mod tests {
 
         let uc = GetKeyUseCase::new(hasher.clone(), net.clone());
 
-        let input = GetKeyUseCaseInput { key: "k2".into() };
+        let input = GetKeyUseCaseInput {
+            key: "k2".into(),
+            refresh_ms: None,
+            if_not_version: None,
+            stale_ms: None,
+            read_from: None,
+        };
         let out = uc.execute(input).await.expect("no debería fallar");
 
         assert!(out.success);
@@ -99,7 +121,13 @@ mod tests {
 
         let uc = GetKeyUseCase::new(hasher.clone(), net.clone());
 
-        let input = GetKeyUseCaseInput { key: "k3".into() };
+        let input = GetKeyUseCaseInput {
+            key: "k3".into(),
+            refresh_ms: None,
+            if_not_version: None,
+            stale_ms: None,
+            read_from: None,
+        };
         let err = uc.execute(input).await.unwrap_err();
 
         match err {
@@ -112,4 +140,235 @@ mod tests {
         assert_eq!(node_id, "node-3");
         assert_eq!(key, "k3");
     }
+
+    #[tokio::test]
+    async fn execute_with_a_refresh_window_reports_whether_to_refresh() {
+        let hasher = Arc::new(MockHasher::new());
+        hasher.set_node_for_hash(Some("node-1"));
+        let net = Arc::new(MockNetwork::new());
+        net.set_request_get_key_result(Ok(Some("v".to_string())));
+
+        let uc = GetKeyUseCase::new(hasher, net.clone());
+        let input = GetKeyUseCaseInput {
+            key: "k1".into(),
+            refresh_ms: Some(5_000),
+            if_not_version: None,
+            stale_ms: None,
+            read_from: None,
+        };
+        let out = uc.execute(input).await.expect("no debería fallar");
+
+        assert_eq!((out.result.as_str(), out.refresh), ("v", Some(true)));
+        assert_eq!(*net.last_request_get_refresh.lock(), Some(5_000));
+    }
+
+    #[tokio::test]
+    async fn execute_with_a_version_skips_the_value_when_it_did_not_change() {
+        let hasher = Arc::new(MockHasher::new());
+        hasher.set_node_for_hash(Some("node-1"));
+        let net = Arc::new(MockNetwork::new());
+        net.set_request_get_key_result(Ok(Some("v".to_string())));
+        let uc = GetKeyUseCase::new(hasher, net);
+
+        let input = |if_not_version| GetKeyUseCaseInput {
+            key: "k1".into(),
+            refresh_ms: None,
+            if_not_version: Some(if_not_version),
+            stale_ms: None,
+            read_from: None,
+        };
+        let out = uc.execute(input(1)).await.expect("no debería fallar");
+        assert!(out.not_modified);
+        assert_eq!((out.result.as_str(), out.version), ("", Some(1)));
+
+        let out = uc.execute(input(0)).await.expect("no debería fallar");
+        assert!(!out.not_modified);
+        assert_eq!((out.result.as_str(), out.version), ("v", Some(1)));
+
+        let both = GetKeyUseCaseInput {
+            refresh_ms: Some(5_000),
+            ..input(1)
+        };
+        assert!(matches!(
+            uc.validate(&both).await,
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn execute_with_a_stale_limit_reports_how_stale_the_value_is() {
+        let hasher = Arc::new(MockHasher::new());
+        hasher.set_node_for_hash(Some("node-1"));
+        let net = Arc::new(MockNetwork::new());
+        net.set_request_get_key_result(Ok(Some("v".to_string())));
+        let uc = GetKeyUseCase::new(hasher, net);
+
+        let input = || GetKeyUseCaseInput {
+            key: "k1".into(),
+            refresh_ms: None,
+            if_not_version: None,
+            stale_ms: Some(5_000),
+            read_from: None,
+        };
+        let out = uc.execute(input()).await.expect("no debería fallar");
+        assert_eq!(out.result, "v");
+        assert_eq!((out.refresh, out.stale_for), (Some(false), Some(0)));
+
+        for bad in [
+            GetKeyUseCaseInput {
+                refresh_ms: Some(5_000),
+                ..input()
+            },
+            GetKeyUseCaseInput {
+                if_not_version: Some(1),
+                ..input()
+            },
+        ] {
+            assert!(matches!(
+                uc.validate(&bad).await,
+                Err(AppError::BadRequest(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn execute_reads_a_copy_when_the_owner_misses() {
+        let hasher = Arc::new(MockHasher::new());
+        hasher.set_node_for_hash(Some("node-1"));
+        hasher.set_next_nodes_for_hash(&["node-2"]);
+        let net = Arc::new(MockNetwork::new());
+        net.request_get_key_by_node
+            .lock()
+            .insert("node-1".into(), None);
+        net.request_get_key_by_node
+            .lock()
+            .insert("node-2".into(), Some("copia".into()));
+
+        let input = || GetKeyUseCaseInput {
+            key: "k1".into(),
+            refresh_ms: None,
+            if_not_version: None,
+            stale_ms: None,
+            read_from: None,
+        };
+
+        // con factor 1 solo se pregunta al dueño
+        let uc = GetKeyUseCase::new(hasher.clone(), net.clone());
+        let out = uc.execute(input()).await.unwrap();
+        assert_eq!(out.result, "");
+
+        let uc = GetKeyUseCase::new(hasher, net.clone()).with_replication_factor(2);
+        let out = uc.execute(input()).await.unwrap();
+        assert!(out.success);
+        assert_eq!(out.result, "copia");
+        let (node_id, _) = net.last_request_get.lock().clone().unwrap();
+        assert_eq!(node_id, "node-2");
+    }
+
+    #[tokio::test]
+    async fn a_pinned_read_goes_to_the_node_of_the_owner_and_is_not_memoized() {
+        let hasher = Arc::new(MockHasher::new());
+        hasher.set_node_for_hash(Some("node-1"));
+        hasher.set_next_nodes_for_hash(&["node-2"]);
+        let net = Arc::new(MockNetwork::new());
+        net.request_get_key_by_node
+            .lock()
+            .insert("node-1".into(), None);
+        net.request_get_key_by_node
+            .lock()
+            .insert("node-2".into(), Some("copia".into()));
+        let uc = GetKeyUseCase::new(hasher, net.clone())
+            .with_replication_factor(2)
+            .memoize(Duration::from_millis(50))
+            .with_clock(Arc::new(SimulatedClock::new(0)));
+
+        let input = || GetKeyUseCaseInput {
+            key: "k1".into(),
+            refresh_ms: None,
+            if_not_version: None,
+            stale_ms: None,
+            read_from: Some("node-1-replica".into()),
+        };
+        let out = uc.validate_and_execute(input()).await.unwrap();
+        assert_eq!(out.result, "copia");
+        assert_eq!(
+            net.last_request_get_from.lock().take().as_deref(),
+            Some("node-1-replica")
+        );
+
+        uc.validate_and_execute(input()).await.unwrap();
+        assert!(net.last_request_get_from.lock().is_some());
+    }
+
+    #[tokio::test]
+    async fn memoized_reads_reuse_plain_gets_but_not_refreshes() {
+        let hasher = Arc::new(MockHasher::new());
+        hasher.set_node_for_hash(Some("node-1"));
+        let net = Arc::new(MockNetwork::new());
+        net.set_request_get_key_result(Ok(Some("v1".to_string())));
+        let clock = Arc::new(SimulatedClock::new(0));
+        let uc = GetKeyUseCase::new(hasher, net.clone())
+            .memoize(Duration::from_millis(50))
+            .with_clock(clock.clone());
+
+        let input = || GetKeyUseCaseInput {
+            key: "k1".into(),
+            refresh_ms: None,
+            if_not_version: None,
+            stale_ms: None,
+            read_from: None,
+        };
+        assert_eq!(uc.validate_and_execute(input()).await.unwrap().result, "v1");
+
+        net.set_request_get_key_result(Ok(Some("v2".to_string())));
+        assert_eq!(uc.validate_and_execute(input()).await.unwrap().result, "v1");
+        // la validación corre aunque la salida esté guardada
+        let empty = GetKeyUseCaseInput {
+            key: "".into(),
+            ..input()
+        };
+        assert!(uc.validate_and_execute(empty).await.is_err());
+
+        let refresh = GetKeyUseCaseInput {
+            refresh_ms: Some(1_000),
+            ..input()
+        };
+        assert_eq!(uc.execute(refresh).await.unwrap().result, "v2");
+        assert_eq!(uc.len(), 1);
+
+        clock.advance(Duration::from_millis(50));
+        assert_eq!(uc.execute(input()).await.unwrap().result, "v2");
+    }
+
+    #[tokio::test]
+    async fn a_chunked_value_is_assembled_and_a_missing_chunk_reads_as_missing() {
+        let hasher = Arc::new(MockHasher::new());
+        hasher.set_node_for_hash(Some("node-1"));
+        let net = Arc::new(MockNetwork::new());
+        let manifest = ChunkManifest::for_value("0123456789", 4);
+        let stored = [
+            ("big".to_string(), manifest.to_string()),
+            (manifest.chunk_key("big", 0), "0123".to_string()),
+            (manifest.chunk_key("big", 1), "4567".to_string()),
+            (manifest.chunk_key("big", 2), "89".to_string()),
+        ];
+        for (key, value) in stored {
+            net.request_get_key_by_key.lock().insert(key, Some(value));
+        }
+        let uc = GetKeyUseCase::new(hasher, net.clone()).with_chunks(true);
+        let input = || GetKeyUseCaseInput {
+            key: "big".into(),
+            refresh_ms: None,
+            if_not_version: None,
+            stale_ms: None,
+            read_from: None,
+        };
+
+        assert_eq!(uc.execute(input()).await.unwrap().result, "0123456789");
+
+        net.request_get_key_by_key
+            .lock()
+            .insert(manifest.chunk_key("big", 1), None);
+        assert_eq!(uc.execute(input()).await.unwrap().result, "");
+    }
 }
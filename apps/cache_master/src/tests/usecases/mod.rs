@@ -1,4 +1,6 @@
 mod assign_node_use_case_test;
 mod get_key_use_case_test;
+mod multi_use_case_test;
+mod put_key_use_case_test;
 mod remove_node_use_case_test;
-mod put_key_use_case_test;
\ No newline at end of file
+mod rename_use_case_test;
@@ -0,0 +1,154 @@
+#[cfg(test)]
+mod tests {
+    use app_core::UseCaseValidatable;
+    use app_net::TxCommand;
+    use std::sync::Arc;
+
+    use crate::core::domain::models::{AppError, ChunkManifest, usecases::MultiUseCaseInput};
+    use crate::core::usecases::MultiUseCase;
+    use crate::tests::test_mocks::{MockClock, MockHasher, MockNetwork};
+
+    fn put(key: &str, ttl: Option<u64>) -> TxCommand {
+        TxCommand::Put {
+            key: key.into(),
+            value: "v".into(),
+            ttl,
+        }
+    }
+
+    #[tokio::test]
+    async fn execute_sends_the_batch_to_the_owner_with_absolute_expirations() {
+        let hasher = Arc::new(MockHasher::new());
+        hasher.set_node_for_hash(Some("node-1"));
+        let net = Arc::new(MockNetwork::new());
+        net.set_request_multi_result(Ok(vec!["OK".into(), "OK".into()]));
+
+        let uc = MultiUseCase::new(hasher, net.clone(), Arc::new(MockClock::new(1_000)));
+        let watch = TxCommand::Watch {
+            key: "a".into(),
+            version: 2,
+        };
+        let out = uc
+            .validate_and_execute(MultiUseCaseInput {
+                commands: vec![watch.clone(), put("a", Some(500)), put("b", None)],
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(out.results, vec!["OK", "OK"]);
+        let (node_id, sent) = net.last_request_multi.lock().clone().unwrap();
+        assert_eq!(node_id, "node-1");
+        assert_eq!(sent, vec![watch, put("a", Some(1_500)), put("b", None)]);
+    }
+
+    #[tokio::test]
+    async fn execute_propagates_a_watch_conflict() {
+        let hasher = Arc::new(MockHasher::new());
+        hasher.set_node_for_hash(Some("node-1"));
+        let net = Arc::new(MockNetwork::new());
+        net.set_request_multi_result(Err(AppError::Conflict("WATCH a".into())));
+
+        let uc = MultiUseCase::new(hasher, net, Arc::new(MockClock::new(0)));
+        let err = uc
+            .validate_and_execute(MultiUseCaseInput {
+                commands: vec![put("a", None)],
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)));
+    }
+
+    #[tokio::test]
+    async fn execute_fails_without_an_owner_or_without_commands() {
+        let net = Arc::new(MockNetwork::new());
+        let uc = MultiUseCase::new(
+            Arc::new(MockHasher::new()),
+            net.clone(),
+            Arc::new(MockClock::new(0)),
+        );
+
+        let err = uc
+            .validate_and_execute(MultiUseCaseInput {
+                commands: vec![put("a", None)],
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::NodeNotFound(_)));
+
+        let err = uc
+            .validate_and_execute(MultiUseCaseInput { commands: vec![] })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::BadRequest(_)));
+        assert!(net.last_request_multi.lock().is_none());
+    }
+
+    #[tokio::test]
+    async fn execute_replays_the_writes_on_the_copies_after_the_owner() {
+        let hasher = Arc::new(MockHasher::new());
+        hasher.set_node_for_hash(Some("node-1"));
+        hasher.set_next_nodes_for_hash(&["node-2"]);
+        let net = Arc::new(MockNetwork::new());
+        net.set_request_multi_result(Ok(vec!["OK".into()]));
+
+        let uc = MultiUseCase::new(hasher, net.clone(), Arc::new(MockClock::new(1_000)))
+            .with_replication_factor(2);
+        let watch = TxCommand::Watch {
+            key: "a".into(),
+            version: 2,
+        };
+        uc.validate_and_execute(MultiUseCaseInput {
+            commands: vec![watch, put("a", Some(500))],
+        })
+        .await
+        .unwrap();
+
+        // la copia recibe las escrituras, sin el WATCH (las versiones son de cada nodo)
+        let (node_id, sent) = net.last_request_multi.lock().clone().unwrap();
+        assert_eq!(node_id, "node-2");
+        assert_eq!(sent, vec![put("a", Some(1_500))]);
+    }
+
+    #[tokio::test]
+    async fn a_del_of_a_chunked_value_deletes_its_chunks_after_the_key() {
+        let hasher = Arc::new(MockHasher::new());
+        hasher.set_node_for_hash(Some("node-1"));
+        let net = Arc::new(MockNetwork::new());
+        let manifest = ChunkManifest::for_value("0123456789", 4);
+        net.request_get_key_by_key
+            .lock()
+            .insert("big".into(), Some(manifest.to_string()));
+        net.set_request_multi_result(Ok(vec!["1".into(), "1".into()]));
+
+        let uc =
+            MultiUseCase::new(hasher, net.clone(), Arc::new(MockClock::new(0))).with_chunks(true);
+        let del = |key: &str| TxCommand::Del { key: key.into() };
+        uc.validate_and_execute(MultiUseCaseInput {
+            commands: vec![del("big"), del("otra")],
+        })
+        .await
+        .unwrap();
+
+        let multis = net.request_multis.lock().clone();
+        assert_eq!(multis.len(), 2);
+        assert_eq!(multis[0].1, vec![del("big"), del("otra")]);
+        let chunks: Vec<_> = (0..manifest.count)
+            .map(|i| del(&manifest.chunk_key("big", i)))
+            .collect();
+        assert_eq!(multis[1], ("node-1".to_string(), chunks));
+
+        // si el MULTI no se aplicó, los pedazos siguen siendo de la clave
+        net.request_multis.lock().clear();
+        net.set_request_multi_result(Err(AppError::Conflict("WATCH big".into())));
+        let watch = TxCommand::Watch {
+            key: "big".into(),
+            version: 2,
+        };
+        uc.validate_and_execute(MultiUseCaseInput {
+            commands: vec![watch, del("big")],
+        })
+        .await
+        .unwrap_err();
+        assert_eq!(net.request_multis.lock().len(), 1);
+    }
+}
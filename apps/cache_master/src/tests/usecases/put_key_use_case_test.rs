@@ -1,9 +1,12 @@
 #[cfg(test)]
 mod tests {
     use app_core::{UseCase, UseCaseValidatable};
-    use std::sync::Arc;
+    use app_net::{PutCondition, TxCommand};
+    use std::{collections::HashSet, sync::Arc};
 
-    use crate::core::domain::models::{AppError, usecases::PutKeyUseCaseInput};
+    use crate::core::domain::models::{
+        AppError, ChunkManifest, TtlJitter, usecases::PutKeyUseCaseInput,
+    };
 
     use crate::core::usecases::PutKeyUseCase;
 
@@ -23,7 +26,10 @@ mod tests {
         let input = PutKeyUseCaseInput {
             key: "".into(),
             value: "v".into(),
-            ttl: None,
+            ttl_ms: None,
+            tags: vec![],
+            condition: None,
+            idempotency: None,
         };
         let err = uc.validate(&input).await.unwrap_err();
         match err {
@@ -43,7 +49,10 @@ mod tests {
         let input = PutKeyUseCaseInput {
             key: "k".into(),
             value: "".into(),
-            ttl: None,
+            ttl_ms: None,
+            tags: vec![],
+            condition: None,
+            idempotency: None,
         };
         let err = uc.validate(&input).await.unwrap_err();
         match err {
@@ -52,6 +61,26 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn validate_fails_when_ttl_is_zero() {
+        let uc = PutKeyUseCase::new(
+            Arc::new(MockHasher::new()),
+            Arc::new(MockNetwork::new()),
+            Arc::new(MockClock::new(0)),
+        );
+
+        let input = PutKeyUseCaseInput {
+            key: "k".into(),
+            value: "v".into(),
+            ttl_ms: Some(0),
+            tags: vec![],
+            condition: None,
+            idempotency: None,
+        };
+        let err = uc.validate(&input).await.unwrap_err();
+        assert!(matches!(err, AppError::BadRequest(msg) if msg == "TTL must be greater than 0"));
+    }
+
     // ---------- Ejecución ----------
 
     #[tokio::test]
@@ -67,7 +96,10 @@ mod tests {
         let input = PutKeyUseCaseInput {
             key: "mykey".into(),
             value: "v".into(),
-            ttl: None,
+            ttl_ms: None,
+            tags: vec![],
+            condition: None,
+            idempotency: None,
         };
         let err = uc.execute(input).await.unwrap_err();
 
@@ -95,11 +127,15 @@ mod tests {
         let input = PutKeyUseCaseInput {
             key: "k1".into(),
             value: "v1".into(),
-            ttl: None,
+            ttl_ms: None,
+            tags: vec![],
+            condition: None,
+            idempotency: None,
         };
         let out = uc.execute(input).await.expect("no debería fallar");
 
         assert!(out.success);
+        assert_eq!(out.acked_by.as_deref(), Some("node-1"));
 
         // verificar parámetros enviados
         let called = net
@@ -130,7 +166,10 @@ mod tests {
         let input = PutKeyUseCaseInput {
             key: "k2".into(),
             value: "v2".into(),
-            ttl: Some(500),
+            ttl_ms: Some(500),
+            tags: vec![],
+            condition: None,
+            idempotency: None,
         };
         let out = uc.execute(input).await.expect("no debería fallar");
 
@@ -148,6 +187,39 @@ mod tests {
         assert_eq!(expires_at, Some(1_000_500)); // now + ttl
     }
 
+    #[tokio::test]
+    async fn ttl_jitter_spreads_expires_at_without_shortening_the_ttl() {
+        let hasher = Arc::new(MockHasher::new());
+        hasher.set_node_for_hash(Some("node-2"));
+        let net = Arc::new(MockNetwork::new());
+        net.set_request_put_key_result(Ok(true));
+        let clock = Arc::new(MockClock::new(1_000_000));
+
+        let uc = PutKeyUseCase::new(hasher, net.clone(), clock).with_ttl_jitter(TtlJitter::new(20));
+
+        let mut seen = HashSet::new();
+        for i in 0..50 {
+            let input = PutKeyUseCaseInput {
+                key: format!("k{i}"),
+                value: "v".into(),
+                ttl_ms: Some(10_000),
+                tags: vec![],
+                condition: None,
+                idempotency: None,
+            };
+            uc.execute(input).await.unwrap();
+
+            let (_, _, _, expires_at) = net.last_request_put.lock().clone().unwrap();
+            let expires_at = expires_at.unwrap();
+            assert!(
+                (1_010_000..=1_012_000).contains(&expires_at),
+                "{expires_at}"
+            );
+            seen.insert(expires_at);
+        }
+        assert!(seen.len() > 1);
+    }
+
     #[tokio::test]
     async fn execute_returns_false_when_network_returns_false() {
         let hasher = Arc::new(MockHasher::new());
@@ -162,7 +234,10 @@ mod tests {
         let input = PutKeyUseCaseInput {
             key: "kx".into(),
             value: "vx".into(),
-            ttl: None,
+            ttl_ms: None,
+            tags: vec![],
+            condition: None,
+            idempotency: None,
         };
         let out = uc.execute(input).await.expect("no debería fallar");
 
@@ -194,7 +269,10 @@ mod tests {
         let input = PutKeyUseCaseInput {
             key: "ke".into(),
             value: "ve".into(),
-            ttl: Some(1),
+            ttl_ms: Some(1),
+            tags: vec![],
+            condition: None,
+            idempotency: None,
         };
         let err = uc.execute(input).await.unwrap_err();
 
@@ -214,4 +292,232 @@ mod tests {
         assert_eq!(value, "ve");
         assert_eq!(expires_at, Some(124)); // 123 + 1
     }
+
+    #[tokio::test]
+    async fn execute_rejects_ttl_that_overflows_the_clock() {
+        let hasher = Arc::new(MockHasher::new());
+        hasher.set_node_for_hash(Some("node-o"));
+        let net = Arc::new(MockNetwork::new());
+        net.set_request_put_key_result(Ok(true));
+
+        let uc = PutKeyUseCase::new(hasher, net.clone(), Arc::new(MockClock::new(10)));
+
+        let input = PutKeyUseCaseInput {
+            key: "k".into(),
+            value: "v".into(),
+            ttl_ms: Some(u64::MAX),
+            tags: vec![],
+            condition: None,
+            idempotency: None,
+        };
+        let err = uc.execute(input).await.unwrap_err();
+
+        assert!(matches!(err, AppError::BadRequest(_)));
+        assert!(net.last_request_put.lock().is_none());
+    }
+
+    #[tokio::test]
+    async fn execute_with_a_condition_goes_through_the_conditional_put() {
+        let hasher = Arc::new(MockHasher::new());
+        hasher.set_node_for_hash(Some("node-1"));
+        let net = Arc::new(MockNetwork::new());
+        net.set_request_put_key_result(Err(AppError::PreconditionFailed("no".into())));
+
+        let uc = PutKeyUseCase::new(hasher, net.clone(), Arc::new(MockClock::new(0)));
+        let input = PutKeyUseCaseInput {
+            key: "k1".into(),
+            value: "v1".into(),
+            ttl_ms: None,
+            tags: vec![],
+            condition: Some(PutCondition::Version(3)),
+            idempotency: None,
+        };
+        let err = uc.execute(input).await.unwrap_err();
+
+        assert!(matches!(err, AppError::PreconditionFailed(_)));
+        assert!(net.last_request_put.lock().is_none());
+        assert_eq!(
+            net.last_request_put_if.lock().clone(),
+            Some(("node-1".into(), "k1".into(), PutCondition::Version(3)))
+        );
+    }
+
+    #[tokio::test]
+    async fn execute_forwards_the_tags_to_the_node() {
+        let hasher = Arc::new(MockHasher::new());
+        hasher.set_node_for_hash(Some("node-1"));
+        let net = Arc::new(MockNetwork::new());
+        net.set_request_put_key_result(Ok(true));
+
+        let uc = PutKeyUseCase::new(hasher, net.clone(), Arc::new(MockClock::new(0)));
+        let input = PutKeyUseCaseInput {
+            key: "k1".into(),
+            value: "v1".into(),
+            ttl_ms: None,
+            tags: vec!["user:42".into(), "session".into()],
+            condition: None,
+            idempotency: None,
+        };
+        uc.execute(input).await.expect("no debería fallar");
+
+        assert_eq!(*net.last_request_put_tags.lock(), ["user:42", "session"]);
+    }
+
+    #[tokio::test]
+    async fn execute_forwards_the_idempotency_token_to_the_node() {
+        let hasher = Arc::new(MockHasher::new());
+        hasher.set_node_for_hash(Some("node-1"));
+        let net = Arc::new(MockNetwork::new());
+
+        let uc = PutKeyUseCase::new(hasher, net.clone(), Arc::new(MockClock::new(0)));
+        let input = PutKeyUseCaseInput {
+            key: "k1".into(),
+            value: "v1".into(),
+            ttl_ms: None,
+            tags: vec![],
+            condition: Some(PutCondition::Version(3)),
+            idempotency: Some("01J0".into()),
+        };
+        uc.execute(input).await.expect("no debería fallar");
+
+        assert_eq!(
+            net.last_request_put_idempotency.lock().as_deref(),
+            Some("01J0")
+        );
+    }
+
+    #[tokio::test]
+    async fn execute_copies_the_key_to_the_next_shards_of_the_ring() {
+        let hasher = Arc::new(MockHasher::new());
+        hasher.set_node_for_hash(Some("node-1"));
+        hasher.set_next_nodes_for_hash(&["node-2", "node-3"]);
+        let net = Arc::new(MockNetwork::new());
+
+        let uc = PutKeyUseCase::new(hasher, net.clone(), Arc::new(MockClock::new(0)))
+            .with_replication_factor(2);
+        let input = PutKeyUseCaseInput {
+            key: "k1".into(),
+            value: "v1".into(),
+            ttl_ms: None,
+            tags: vec![],
+            condition: None,
+            idempotency: None,
+        };
+        let out = uc.execute(input).await.expect("no debería fallar");
+
+        assert!(out.success);
+        let shards: Vec<String> = net
+            .request_puts
+            .lock()
+            .iter()
+            .map(|(node_id, ..)| node_id.clone())
+            .collect();
+        assert_eq!(shards, ["node-1", "node-2"]);
+    }
+
+    #[tokio::test]
+    async fn a_value_past_the_chunk_size_is_written_in_chunks_before_its_manifest() {
+        let hasher = Arc::new(MockHasher::new());
+        hasher.set_node_for_hash(Some("node-1"));
+        let net = Arc::new(MockNetwork::new());
+
+        let uc = PutKeyUseCase::new(hasher, net.clone(), Arc::new(MockClock::new(1_000)))
+            .with_chunk_size(Some(4));
+        let input = PutKeyUseCaseInput {
+            key: "big".into(),
+            value: "0123456789".into(),
+            ttl_ms: Some(500),
+            tags: vec!["t".into()],
+            condition: None,
+            idempotency: None,
+        };
+        assert!(uc.execute(input).await.unwrap().success);
+
+        let puts = net.request_puts.lock().clone();
+        let manifest = ChunkManifest::parse(&puts[3].2).expect("la clave guarda el manifiesto");
+        assert_eq!((manifest.count, manifest.len), (3, 10));
+        assert_eq!(puts[3].1, "big");
+        let chunks: Vec<_> = puts[..3]
+            .iter()
+            .map(|(_, key, value, expires_at)| (key.clone(), value.as_str(), *expires_at))
+            .collect();
+        assert_eq!(
+            chunks,
+            [
+                (manifest.chunk_key("big", 0), "0123", Some(1_500)),
+                (manifest.chunk_key("big", 1), "4567", Some(1_500)),
+                (manifest.chunk_key("big", 2), "89", Some(1_500)),
+            ]
+        );
+        // los tags son de la clave, no de sus pedazos
+        assert_eq!(*net.last_request_put_tags.lock(), ["t"]);
+    }
+
+    #[tokio::test]
+    async fn chunking_refuses_conditions_on_big_values_and_values_that_look_like_a_manifest() {
+        let uc = PutKeyUseCase::new(
+            Arc::new(MockHasher::new()),
+            Arc::new(MockNetwork::new()),
+            Arc::new(MockClock::new(0)),
+        )
+        .with_chunk_size(Some(4));
+        let input = |value: &str, condition| PutKeyUseCaseInput {
+            key: "k".into(),
+            value: value.into(),
+            ttl_ms: None,
+            tags: vec![],
+            condition,
+            idempotency: None,
+        };
+
+        assert!(uc.validate(&input("#chunked:x:1:1", None)).await.is_err());
+        let big_if = input("0123456789", Some(PutCondition::Absent));
+        assert!(matches!(
+            uc.validate(&big_if).await,
+            Err(AppError::BadRequest(_))
+        ));
+        let small_if = input("012", Some(PutCondition::Absent));
+        assert!(uc.validate(&small_if).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn overwriting_a_chunked_value_deletes_its_chunks_unless_they_are_reused() {
+        let hasher = Arc::new(MockHasher::new());
+        hasher.set_node_for_hash(Some("node-1"));
+        let net = Arc::new(MockNetwork::new());
+        let old = ChunkManifest::for_value("0123456789", 4);
+        *net.request_get_key_result.lock() = Ok(Some(old.to_string()));
+
+        let uc = PutKeyUseCase::new(hasher, net.clone(), Arc::new(MockClock::new(0)))
+            .with_chunk_size(Some(4));
+        let input = |value: &str| PutKeyUseCaseInput {
+            key: "big".into(),
+            value: value.into(),
+            ttl_ms: None,
+            tags: vec![],
+            condition: None,
+            idempotency: None,
+        };
+
+        // el mismo valor escribe los mismos pedazos: no hay nada que borrar
+        assert!(uc.execute(input("0123456789")).await.unwrap().success);
+        assert!(net.request_multis.lock().is_empty());
+
+        assert!(uc.execute(input("chico")).await.unwrap().success);
+        let deleted: Vec<_> = net
+            .request_multis
+            .lock()
+            .iter()
+            .flat_map(|(node_id, commands)| {
+                commands.iter().map(move |c| (node_id.clone(), c.clone()))
+            })
+            .collect();
+        let expected: Vec<_> = (0..old.count)
+            .map(|i| {
+                let key = old.chunk_key("big", i);
+                ("node-1".to_string(), TxCommand::Del { key })
+            })
+            .collect();
+        assert_eq!(deleted, expected);
+    }
 }
@@ -4,10 +4,12 @@ mod tests {
 
     use crate::{
         core::{
-            domain::models::{AppError, usecases::remove_node_use_case::RemoveNodeUseCaseInput},
+            domain::models::{
+                AppError, DomainEvent, usecases::remove_node_use_case::RemoveNodeUseCaseInput,
+            },
             usecases::RemoveNodeUseCase,
         },
-        tests::test_mocks::{MockHasher, MockNetwork},
+        tests::test_mocks::{MockHasher, MockNetwork, bus},
     };
     use std::sync::Arc;
 
@@ -17,7 +19,7 @@ mod tests {
     async fn validate_fails_when_node_id_is_empty() {
         let hasher = Arc::new(MockHasher::new());
         let net = Arc::new(MockNetwork::new());
-        let uc = RemoveNodeUseCase::new(hasher, net);
+        let uc = RemoveNodeUseCase::new(hasher, net, bus());
 
         let input = RemoveNodeUseCaseInput { node_id: "".into() };
         let err = uc.validate(&input).await.unwrap_err();
@@ -31,7 +33,7 @@ mod tests {
         net.set_replica_count(1); // <= 1 → removerá también del hasher
         net.set_remove_result(Ok(true)); // network OK
 
-        let uc = RemoveNodeUseCase::new(hasher.clone(), net.clone());
+        let uc = RemoveNodeUseCase::new(hasher.clone(), net.clone(), bus());
 
         let input = RemoveNodeUseCaseInput {
             node_id: "n1".into(),
@@ -50,7 +52,7 @@ mod tests {
         net.set_replica_count(2); // > 1 → NO removerá del hasher
         net.set_remove_result(Ok(true)); // network OK
 
-        let uc = RemoveNodeUseCase::new(hasher.clone(), net.clone());
+        let uc = RemoveNodeUseCase::new(hasher.clone(), net.clone(), bus());
 
         let input = RemoveNodeUseCaseInput {
             node_id: "n2".into(),
@@ -70,7 +72,7 @@ mod tests {
         net.set_replica_count(0);
         net.set_remove_result(Ok(false)); // <— network dice “no encontrado”
 
-        let uc = RemoveNodeUseCase::new(hasher, net);
+        let uc = RemoveNodeUseCase::new(hasher, net, bus());
 
         let input = RemoveNodeUseCaseInput {
             node_id: "n3".into(),
@@ -90,7 +92,7 @@ mod tests {
         net.set_replica_count(0);
         net.set_remove_result(Err(AppError::ConnectionError("fail".into())));
 
-        let uc = RemoveNodeUseCase::new(hasher, net);
+        let uc = RemoveNodeUseCase::new(hasher, net, bus());
 
         let input = RemoveNodeUseCaseInput {
             node_id: "n4".into(),
@@ -102,4 +104,34 @@ mod tests {
             _ => panic!("Esperaba ConnectionError"),
         }
     }
+
+    #[tokio::test]
+    async fn publishes_node_removed_only_on_success() {
+        let hasher = Arc::new(MockHasher::new());
+        let net = Arc::new(MockNetwork::new());
+        let events = bus();
+        let mut rx = events.subscribe();
+
+        let uc = RemoveNodeUseCase::new(hasher, net.clone(), events);
+
+        uc.execute(RemoveNodeUseCaseInput {
+            node_id: "n5".into(),
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            rx.recv().await.unwrap().event,
+            DomainEvent::NodeRemoved {
+                node_id: "n5".into()
+            }
+        );
+
+        net.set_remove_result(Ok(false));
+        let _ = uc
+            .execute(RemoveNodeUseCaseInput {
+                node_id: "n6".into(),
+            })
+            .await;
+        assert!(rx.try_recv().is_err());
+    }
 }
@@ -0,0 +1,141 @@
+#[cfg(test)]
+mod tests {
+    use app_core::UseCaseValidatable;
+    use app_net::{PutCondition, TxCommand};
+    use std::sync::Arc;
+
+    use crate::core::domain::models::{AppError, DomainEvent, usecases::RenameUseCaseInput};
+    use crate::core::usecases::RenameUseCase;
+    use crate::tests::test_mocks::{MockHasher, MockNetwork, bus};
+
+    fn input(from: &str, to: &str) -> RenameUseCaseInput {
+        RenameUseCaseInput {
+            from: from.into(),
+            to: to.into(),
+        }
+    }
+
+    /// `a` en `node-1` y `b` en `node-2`; `a` existe con versión 1.
+    fn across() -> (Arc<MockNetwork>, RenameUseCase) {
+        let hasher = Arc::new(MockHasher::new());
+        hasher.set_node_for_key("a", "node-1");
+        hasher.set_node_for_key("b", "node-2");
+        let net = Arc::new(MockNetwork::new());
+        net.set_request_get_key_result(Ok(Some("v".into())));
+        *net.key_expiry.lock() = Some((1, Some(5_000)));
+        (net.clone(), RenameUseCase::new(hasher, net, bus()))
+    }
+
+    #[tokio::test]
+    async fn keys_of_the_same_shard_are_moved_by_its_primary() {
+        let hasher = Arc::new(MockHasher::new());
+        hasher.set_node_for_hash(Some("node-1"));
+        let net = Arc::new(MockNetwork::new());
+        let uc = RenameUseCase::new(hasher, net.clone(), bus());
+
+        let out = uc.validate_and_execute(input("a", "b")).await.unwrap();
+        assert!(!out.across_shards);
+        assert_eq!(
+            net.last_request_rename.lock().clone(),
+            Some(("node-1".into(), "a".into(), "b".into()))
+        );
+
+        *net.request_rename_result.lock() = Err(AppError::NotFound("a".into()));
+        let err = uc.validate_and_execute(input("a", "b")).await.unwrap_err();
+        assert!(matches!(err, AppError::NotFound(_)));
+        let err = uc.validate_and_execute(input("", "b")).await.unwrap_err();
+        assert!(matches!(err, AppError::BadRequest(_)));
+    }
+
+    #[tokio::test]
+    async fn across_shards_the_value_is_copied_if_absent_and_the_source_deleted() {
+        let (net, uc) = across();
+
+        let out = uc.validate_and_execute(input("a", "b")).await.unwrap();
+        assert!(out.across_shards);
+        let (node, key, condition) = net.last_request_put_if.lock().clone().unwrap();
+        assert_eq!((node.as_str(), key.as_str()), ("node-2", "b"));
+        assert_eq!(condition, PutCondition::Absent);
+        assert_eq!(
+            net.request_multis.lock().clone(),
+            vec![(
+                "node-1".to_string(),
+                vec![
+                    TxCommand::Watch {
+                        key: "a".into(),
+                        version: 1
+                    },
+                    TxCommand::Del { key: "a".into() },
+                ]
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn an_existing_destination_is_a_conflict_and_the_source_stays() {
+        let (net, uc) = across();
+        net.set_request_put_key_result(Err(AppError::PreconditionFailed("absent".into())));
+
+        let err = uc.validate_and_execute(input("a", "b")).await.unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)));
+        assert!(net.request_multis.lock().is_empty());
+    }
+
+    #[tokio::test]
+    async fn a_source_written_meanwhile_undoes_the_copy() {
+        let (net, uc) = across();
+        net.set_request_multi_result(Err(AppError::Conflict("WATCH a".into())));
+
+        let err = uc.validate_and_execute(input("a", "b")).await.unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)));
+        let multis = net.request_multis.lock().clone();
+        assert_eq!(multis.len(), 2);
+        assert_eq!(
+            multis[1],
+            (
+                "node-2".to_string(),
+                vec![
+                    TxCommand::Watch {
+                        key: "b".into(),
+                        version: 1
+                    },
+                    TxCommand::Del { key: "b".into() },
+                ]
+            )
+        );
+
+        // la versión que da META tiene que ser la del valor leído
+        let (net, uc) = across();
+        *net.key_expiry.lock() = Some((2, None));
+        let err = uc.validate_and_execute(input("a", "b")).await.unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)));
+        assert!(net.last_request_put_if.lock().is_none());
+    }
+
+    #[tokio::test]
+    async fn only_a_move_across_shards_publishes_key_migrated() {
+        let hasher = Arc::new(MockHasher::new());
+        hasher.set_node_for_key("a", "node-1");
+        hasher.set_node_for_key("b", "node-2");
+        hasher.set_node_for_key("c", "node-1");
+        let net = Arc::new(MockNetwork::new());
+        net.set_request_get_key_result(Ok(Some("v".into())));
+        *net.key_expiry.lock() = Some((1, None));
+        let events = bus();
+        let mut rx = events.subscribe();
+        let uc = RenameUseCase::new(hasher, net, events);
+
+        uc.validate_and_execute(input("a", "c")).await.unwrap();
+        assert!(rx.try_recv().is_err());
+
+        uc.validate_and_execute(input("a", "b")).await.unwrap();
+        assert_eq!(
+            rx.recv().await.unwrap().event,
+            DomainEvent::KeyMigrated {
+                key: "a".into(),
+                from_node: "node-1".into(),
+                to_node: "node-2".into(),
+            }
+        );
+    }
+}
@@ -0,0 +1,126 @@
+//! `PUT` directo contra `PUT` por `WriteBatcher`. De a uno se ve lo que cuesta la ventana
+//! en la latencia; con muchos en curso a la vez, si tomar el lock del orden de desalojo
+//! una vez por lote compensa la espera. Las claves son el cuádruple de las que entran, así
+//! que casi todos los `PUT` desalojan.
+//! `cargo bench -p cache_node --bench batching`
+
+use std::{sync::Arc, time::Duration};
+
+use app_core::{clock::AppClock, supervisor::Supervisor};
+use cache_node::{
+    core::{
+        domain::services::CacheService,
+        services::{WriteBatcher, WriteBatching},
+    },
+    infrastructure::adapters::services::cache_service::{CacheConfig, InMemCache},
+};
+use tokio::runtime::Runtime;
+
+const KEYS: u32 = 4 * 1024;
+const WINDOWS_US: [u64; 3] = [0, 50, 200];
+const IN_FLIGHT: [usize; 2] = [64, 512];
+
+fn main() {
+    divan::main();
+}
+
+struct Setup {
+    runtime: Runtime,
+    cache: Arc<InMemCache>,
+    batcher: Arc<WriteBatcher<InMemCache>>,
+    _supervisor: Supervisor,
+}
+
+fn setup(window_us: u64) -> Setup {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(4)
+        .enable_all()
+        .build()
+        .unwrap();
+    let (supervisor, cache, batcher) = runtime.block_on(async {
+        let supervisor = Supervisor::new();
+        let cache = Arc::new(InMemCache::with_config(
+            &supervisor,
+            Arc::new(AppClock::new()),
+            CacheConfig::default(),
+        ));
+        let batcher = Arc::new(WriteBatcher::new(
+            cache.clone(),
+            WriteBatching {
+                window: Duration::from_micros(window_us),
+                ..WriteBatching::default()
+            },
+        ));
+        tokio::spawn(batcher.clone().run(supervisor.token()));
+        (supervisor, cache, batcher)
+    });
+    Setup {
+        runtime,
+        cache,
+        batcher,
+        _supervisor: supervisor,
+    }
+}
+
+fn key() -> String {
+    format!("k{}", fastrand::u32(..KEYS))
+}
+
+/// Un `PUT` por vez: la latencia de uno solo, que con lotes paga la ventana entera.
+#[divan::bench]
+fn latency_direct(bencher: divan::Bencher) {
+    let setup = setup(0);
+    bencher.bench_local(|| {
+        setup
+            .runtime
+            .block_on(setup.cache.put(key(), "v".into(), None, &[]))
+    });
+}
+
+#[divan::bench(args = WINDOWS_US)]
+fn latency_batched(bencher: divan::Bencher, window_us: u64) {
+    let setup = setup(window_us);
+    bencher.bench_local(|| {
+        setup
+            .runtime
+            .block_on(setup.batcher.put(key(), "v".into(), None, &[]))
+    });
+}
+
+/// `IN_FLIGHT` `PUT` a la vez, como los requests en curso de varias conexiones.
+#[divan::bench(args = IN_FLIGHT)]
+fn throughput_direct(bencher: divan::Bencher, in_flight: usize) {
+    let setup = setup(0);
+    bencher
+        .counter(divan::counter::ItemsCount::new(in_flight))
+        .bench_local(|| {
+            setup.runtime.block_on(concurrently(in_flight, |key| {
+                let cache = setup.cache.clone();
+                async move { cache.put(key, "v".into(), None, &[]).await }
+            }))
+        });
+}
+
+#[divan::bench(args = IN_FLIGHT)]
+fn throughput_batched(bencher: divan::Bencher, in_flight: usize) {
+    let setup = setup(WriteBatching::default().window.as_micros() as u64);
+    bencher
+        .counter(divan::counter::ItemsCount::new(in_flight))
+        .bench_local(|| {
+            setup.runtime.block_on(concurrently(in_flight, |key| {
+                let batcher = setup.batcher.clone();
+                async move { batcher.put(key, "v".into(), None, &[]).await }
+            }))
+        });
+}
+
+async fn concurrently<F, Fut>(n: usize, put: F)
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = bool> + Send + 'static,
+{
+    let tasks: Vec<_> = (0..n).map(|_| tokio::spawn(put(key()))).collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+}
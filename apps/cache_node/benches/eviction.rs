@@ -0,0 +1,134 @@
+//! Las políticas de desalojo sobre una carga Zipf (pocas claves muy leídas y una cola
+//! larga de claves que se leen una vez), sola y mezclada con recorridos de claves nuevas.
+//! Antes de medir imprime la tasa de aciertos de cada política; después, cuánto tarda
+//! cada una en la misma secuencia de accesos y, desde varios hilos, cuánto se estorban
+//! las lecturas (las políticas estrictas anotan el acceso sin esperar y lo aplican
+//! después; la aproximada por muestreo ni lo anota) y las lecturas con escrituras.
+//! `cargo bench -p cache_node --bench eviction`
+
+use std::sync::Arc;
+
+use app_core::clock::AppClock;
+use cache_node::core::services::{Cache, EvictionPolicy};
+
+const KEYS: usize = 100_000;
+const CAPACITY: usize = 1_000;
+const ACCESSES: usize = 200_000;
+const SKEWS: [f64; 3] = [0.7, 0.9, 1.1];
+const POLICIES: [EvictionPolicy; 4] = [
+    EvictionPolicy::Lru,
+    EvictionPolicy::Slru,
+    EvictionPolicy::TinyLfu,
+    EvictionPolicy::Sampled {
+        samples: EvictionPolicy::DEFAULT_SAMPLES,
+    },
+];
+
+fn main() {
+    for skew in SKEWS {
+        print_hit_ratios(&format!("zipf {skew}"), &zipf_accesses(skew));
+    }
+    print_hit_ratios("zipf 0.9 + recorridos", &with_scans(zipf_accesses(0.9)));
+    divan::main();
+}
+
+fn print_hit_ratios(workload: &str, accesses: &[u32]) {
+    let ratios: Vec<String> = POLICIES
+        .iter()
+        .map(|policy| format!("{policy:?} {:.3}", hit_ratio(*policy, accesses)))
+        .collect();
+    println!("{workload}: aciertos {}", ratios.join(" "));
+}
+
+/// `ACCESSES` claves en `0..KEYS` con probabilidad proporcional a `1 / (rango + 1)^skew`.
+fn zipf_accesses(skew: f64) -> Vec<u32> {
+    let mut cumulative = Vec::with_capacity(KEYS);
+    let mut total = 0.0;
+    for rank in 0..KEYS {
+        total += 1.0 / ((rank + 1) as f64).powf(skew);
+        cumulative.push(total);
+    }
+
+    let mut rng = fastrand::Rng::with_seed(7);
+    (0..ACCESSES)
+        .map(|_| {
+            let draw = rng.f64() * total;
+            cumulative.partition_point(|c| *c < draw) as u32
+        })
+        .collect()
+}
+
+/// Cada 1000 accesos, un recorrido de `CAPACITY` claves que no se repiten.
+fn with_scans(accesses: Vec<u32>) -> Vec<u32> {
+    let mut fresh = KEYS as u32..;
+    accesses
+        .chunks(1_000)
+        .flat_map(|chunk| {
+            let scan: Vec<u32> = fresh.by_ref().take(CAPACITY).collect();
+            chunk.iter().copied().chain(scan)
+        })
+        .collect()
+}
+
+/// Lee cada clave y la escribe si no estaba, como un cache delante de una base.
+fn run(cache: &Cache<u32, u32>, accesses: &[u32]) -> usize {
+    let mut hits = 0;
+    for key in accesses {
+        if cache.get(key).is_some() {
+            hits += 1;
+        } else {
+            cache.put(*key, *key, None);
+        }
+    }
+    hits
+}
+
+fn new_cache(policy: EvictionPolicy) -> Arc<Cache<u32, u32>> {
+    Cache::new_with_policy(CAPACITY, 1024, 1000, Arc::new(AppClock::new()), policy)
+}
+
+fn hit_ratio(policy: EvictionPolicy, accesses: &[u32]) -> f64 {
+    run(&new_cache(policy), accesses) as f64 / accesses.len() as f64
+}
+
+#[divan::bench(args = SKEWS)]
+fn lru(bencher: divan::Bencher, skew: f64) {
+    let accesses = zipf_accesses(skew);
+    bencher.bench_local(|| run(&new_cache(EvictionPolicy::Lru), &accesses));
+}
+
+#[divan::bench(args = SKEWS)]
+fn slru(bencher: divan::Bencher, skew: f64) {
+    let accesses = zipf_accesses(skew);
+    bencher.bench_local(|| run(&new_cache(EvictionPolicy::Slru), &accesses));
+}
+
+#[divan::bench(args = SKEWS)]
+fn tinylfu(bencher: divan::Bencher, skew: f64) {
+    let accesses = zipf_accesses(skew);
+    bencher.bench_local(|| run(&new_cache(EvictionPolicy::TinyLfu), &accesses));
+}
+
+/// Lectura de una clave al azar entre el doble de las que entran, escribiéndola si no
+/// estaba: la mitad de las operaciones desaloja.
+#[divan::bench(args = ["lru", "sampled"], threads = [1, 4, 8])]
+fn concurrent(bencher: divan::Bencher, policy: &str) {
+    let cache = new_cache(policy.parse().unwrap());
+    let keys = 2 * CAPACITY as u32;
+    bencher.bench(|| {
+        let key = fastrand::u32(..keys);
+        if cache.get(&key).is_none() {
+            cache.put(key, key, None);
+        }
+    });
+}
+
+/// Solo lecturas que aciertan, desde varios hilos.
+#[divan::bench(args = ["lru", "slru", "tinylfu", "sampled"], threads = [1, 4, 8])]
+fn concurrent_gets(bencher: divan::Bencher, policy: &str) {
+    let cache = new_cache(policy.parse().unwrap());
+    for key in 0..CAPACITY as u32 {
+        cache.put(key, key, None);
+    }
+    bencher.bench(|| cache.get(&fastrand::u32(..CAPACITY as u32)));
+}
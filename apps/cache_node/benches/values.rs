@@ -0,0 +1,116 @@
+//! Memoria y tiempo del cache con los valores como `String` y como `CompactValue`, para
+//! varios largos de valor. Antes de medir imprime cuánta memoria queda reservada por
+//! entrada (la entrada del map y el orden de desalojo incluidos; las claves son `u32`) y
+//! en cuántas reservas; después, cuánto tarda escribir y leer con cada uno.
+//! `cargo bench -p cache_node --bench values`
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fmt::Display,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use app_core::clock::AppClock;
+use cache_node::core::services::{Cache, CompactValue, EvictionPolicy};
+
+const ENTRIES: usize = 100_000;
+const LENGTHS: [usize; 4] = [8, 22, 64, 256];
+
+/// Cuenta los bytes y las reservas vivas para `footprint`.
+struct Counting;
+
+static LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+static LIVE_ALLOCS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        LIVE_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        LIVE_ALLOCS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        LIVE_ALLOCS.fetch_sub(1, Ordering::Relaxed);
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        LIVE_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOC: Counting = Counting;
+
+fn main() {
+    for len in LENGTHS {
+        let (string_bytes, string_allocs) = footprint::<String>(len);
+        let (compact_bytes, compact_allocs) = footprint::<CompactValue>(len);
+        println!(
+            "valores de {len} bytes, por entrada: String {string_bytes:.1} bytes en \
+             {string_allocs:.1} reservas, CompactValue {compact_bytes:.1} bytes en \
+             {compact_allocs:.1} reservas"
+        );
+    }
+    divan::main();
+}
+
+fn new_cache<V: Send + Sync + 'static>() -> Arc<Cache<u32, V>> {
+    Cache::new_with_policy(
+        ENTRIES,
+        1024,
+        1000,
+        Arc::new(AppClock::new()),
+        EvictionPolicy::Lru,
+    )
+}
+
+/// Como llega del protocolo: un `String` del largo justo.
+fn value(len: usize) -> String {
+    "v".repeat(len)
+}
+
+/// Bytes y reservas que suma cada entrada con valores de `len` bytes.
+fn footprint<V: From<String> + Send + Sync + 'static>(len: usize) -> (f64, f64) {
+    let cache = new_cache::<V>();
+    let bytes = LIVE_BYTES.load(Ordering::Relaxed);
+    let allocs = LIVE_ALLOCS.load(Ordering::Relaxed);
+    for key in 0..ENTRIES as u32 {
+        cache.put(key, value(len).into(), None);
+    }
+    let per_entry = |now: usize, before: usize| (now - before) as f64 / ENTRIES as f64;
+    (
+        per_entry(LIVE_BYTES.load(Ordering::Relaxed), bytes),
+        per_entry(LIVE_ALLOCS.load(Ordering::Relaxed), allocs),
+    )
+}
+
+#[divan::bench(types = [String, CompactValue], args = LENGTHS)]
+fn put<V: From<String> + Send + Sync + 'static>(bencher: divan::Bencher, len: usize) {
+    let cache = new_cache::<V>();
+    let mut key = 0;
+    bencher.bench_local(|| {
+        key = (key + 1) % ENTRIES as u32;
+        cache.put(key, value(len).into(), None)
+    });
+}
+
+/// Lectura que acierta y arma la respuesta, que es un `String`.
+#[divan::bench(types = [String, CompactValue], args = LENGTHS)]
+fn get<V: From<String> + Display + Send + Sync + 'static>(bencher: divan::Bencher, len: usize) {
+    let cache = new_cache::<V>();
+    for key in 0..ENTRIES as u32 {
+        cache.put(key, value(len).into(), None);
+    }
+    bencher.bench_local(|| {
+        cache
+            .get(&fastrand::u32(..ENTRIES as u32))
+            .map(|value| value.to_string())
+    });
+}
@@ -0,0 +1,43 @@
+use std::sync::Arc;
+
+use app_net::tokenize;
+use async_trait::async_trait;
+
+use crate::core::{
+    domain::{
+        models::Response,
+        services::{CacheService, CommandHandler},
+    },
+    usecases::exec_config,
+};
+
+/// `CONFIG GET ["<ajuste>"]` | `CONFIG SET "<ajuste>" "<valor>"`: lee o cambia en caliente
+/// la capacidad del cache y el tope de memoria (ver `exec_config`).
+pub struct ConfigCommand<C> {
+    cache: Arc<C>,
+}
+
+impl<C: CacheService> ConfigCommand<C> {
+    pub fn new(cache: Arc<C>) -> Self {
+        Self { cache }
+    }
+}
+
+#[async_trait]
+impl<C: CacheService + 'static> CommandHandler for ConfigCommand<C> {
+    fn action(&self) -> &'static str {
+        "CONFIG"
+    }
+
+    async fn handle(&self, payload: &str) -> Response {
+        let args: Vec<_> = tokenize(payload).collect();
+        let arg = |i: usize| args.get(i).map(|a| &**a);
+        exec_config(
+            self.cache.as_ref(),
+            arg(0).unwrap_or_default(),
+            arg(1),
+            arg(2),
+        )
+        .await
+    }
+}
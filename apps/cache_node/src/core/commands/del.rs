@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use app_net::{take_idempotency, tokenize};
+use async_trait::async_trait;
+
+use crate::core::{
+    domain::{
+        models::Response,
+        services::{CacheService, CommandHandler},
+    },
+    services::{IdempotencyCache, Op, OpLog},
+    usecases::exec_del,
+};
+
+/// `DEL "<clave>" ["idem=<token>"]`; con `idem=` un reintento no se vuelve a aplicar (ver
+/// `IdempotencyCache`).
+pub struct DelCommand<C> {
+    cache: Arc<C>,
+    op_log: Arc<OpLog>,
+    idempotency: Option<Arc<IdempotencyCache>>,
+}
+
+impl<C: CacheService> DelCommand<C> {
+    pub fn new(cache: Arc<C>, op_log: Arc<OpLog>) -> Self {
+        Self {
+            cache,
+            op_log,
+            idempotency: None,
+        }
+    }
+
+    pub fn with_idempotency(mut self, idempotency: Option<Arc<IdempotencyCache>>) -> Self {
+        self.idempotency = idempotency;
+        self
+    }
+
+    async fn apply(&self, key: String) -> Response {
+        let _order = self.op_log.lock_keys(&[&key]).await;
+        let res = exec_del(self.cache.as_ref(), key.clone()).await;
+        // solo se replican los DEL que borraron algo
+        if res == Response::Integer(1) {
+            self.op_log.append(Op::Del { key });
+        }
+        res
+    }
+}
+
+#[async_trait]
+impl<C: CacheService + 'static> CommandHandler for DelCommand<C> {
+    fn action(&self) -> &'static str {
+        "DEL"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn accesses_key(&self) -> bool {
+        true
+    }
+
+    async fn handle(&self, payload: &str) -> Response {
+        let mut args: Vec<_> = tokenize(payload).collect();
+        let token = match take_idempotency(&mut args, 1) {
+            Ok(token) => token,
+            Err(e) => return Response::from_error(&e),
+        };
+        let key = args.into_iter().next().unwrap_or_default().into_owned();
+        match &self.idempotency {
+            Some(idempotency) => idempotency.run(token.as_deref(), self.apply(key)).await,
+            None => self.apply(key).await,
+        }
+    }
+}
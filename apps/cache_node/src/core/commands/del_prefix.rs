@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use app_net::{DEL_PREFIX, DelPrefix};
+use async_trait::async_trait;
+
+use crate::core::{
+    domain::{
+        models::Response,
+        services::{CacheService, CommandHandler},
+    },
+    services::{Op, OpLog},
+    usecases::exec_del_prefix,
+};
+
+/// `DEL-PREFIX "<prefijo>" ["dry-run"]` (ver `app_net::prefix`): recorre las claves del
+/// nodo, así que tarda según cuántas guarda.
+pub struct DelPrefixCommand<C> {
+    cache: Arc<C>,
+    op_log: Arc<OpLog>,
+}
+
+impl<C: CacheService> DelPrefixCommand<C> {
+    pub fn new(cache: Arc<C>, op_log: Arc<OpLog>) -> Self {
+        Self { cache, op_log }
+    }
+}
+
+#[async_trait]
+impl<C: CacheService + 'static> CommandHandler for DelPrefixCommand<C> {
+    fn action(&self) -> &'static str {
+        DEL_PREFIX
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    async fn handle(&self, payload: &str) -> Response {
+        let request = match DelPrefix::parse(payload) {
+            Ok(request) => request,
+            Err(e) => return Response::from_error(&e),
+        };
+        let _order = self.op_log.lock_all().await;
+        let res = exec_del_prefix(self.cache.as_ref(), &request).await;
+        if !request.dry_run && matches!(res, Response::Integer(removed) if removed > 0) {
+            self.op_log.append(Op::DelPrefix {
+                prefix: request.prefix,
+            });
+        }
+        res
+    }
+}
@@ -0,0 +1,40 @@
+use std::sync::Arc;
+
+use app_net::{EXPORT_RANGE, HashRange};
+use async_trait::async_trait;
+
+use crate::core::{
+    domain::{
+        models::Response,
+        services::{CacheService, CommandHandler},
+    },
+    services::OpLog,
+    usecases::exec_export_range,
+};
+
+/// `EXPORT-RANGE "<start>" "<end>"`: las entradas vigentes de un tramo del anillo (ver
+/// `app_net::HashRange`), para mover ese tramo a otro shard.
+pub struct ExportRangeCommand<C> {
+    cache: Arc<C>,
+    op_log: Arc<OpLog>,
+}
+
+impl<C: CacheService> ExportRangeCommand<C> {
+    pub fn new(cache: Arc<C>, op_log: Arc<OpLog>) -> Self {
+        Self { cache, op_log }
+    }
+}
+
+#[async_trait]
+impl<C: CacheService + 'static> CommandHandler for ExportRangeCommand<C> {
+    fn action(&self) -> &'static str {
+        EXPORT_RANGE
+    }
+
+    async fn handle(&self, payload: &str) -> Response {
+        match payload.parse::<HashRange>() {
+            Ok(range) => exec_export_range(self.cache.as_ref(), &self.op_log, range).await,
+            Err(e) => Response::from_error(&e),
+        }
+    }
+}
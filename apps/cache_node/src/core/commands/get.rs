@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use app_net::{IF_NOT_VERSION, take_if_not_version, take_refresh, take_stale, tokenize};
+use async_trait::async_trait;
+
+use crate::core::{
+    domain::{
+        models::Response,
+        services::{CacheService, CommandHandler},
+    },
+    usecases::{exec_get, exec_get_if_not_version, exec_get_refresh, exec_get_stale},
+};
+
+/// `GET "<clave>" ["refresh=<ventana>" | "stale=<máximo>" | IF-NOT-VERSION <versión>]`; con
+/// ventana responde también si hay que refrescar la entrada, con máximo además acepta una
+/// vencida hace poco (ver `app_net::refresh`) y con versión, `304` si la clave no cambió
+/// (ver `app_net::conditional`).
+pub struct GetCommand<C> {
+    cache: Arc<C>,
+}
+
+impl<C: CacheService> GetCommand<C> {
+    pub fn new(cache: Arc<C>) -> Self {
+        Self { cache }
+    }
+}
+
+#[async_trait]
+impl<C: CacheService + 'static> CommandHandler for GetCommand<C> {
+    fn action(&self) -> &'static str {
+        "GET"
+    }
+
+    fn accesses_key(&self) -> bool {
+        true
+    }
+
+    async fn handle(&self, payload: &str) -> Response {
+        let mut args: Vec<_> = tokenize(payload).collect();
+        let (version, window, max_stale) = match take_if_not_version(&mut args)
+            .and_then(|version| Ok((version, take_refresh(&mut args)?, take_stale(&mut args)?)))
+        {
+            Ok(parsed) => parsed,
+            Err(e) => return Response::from_error(&e),
+        };
+        let key = args.into_iter().next().unwrap_or_default().into_owned();
+
+        match (version, window, max_stale) {
+            (Some(_), Some(_), _) | (Some(_), _, Some(_)) => {
+                Response::bad_request(format!("{IF_NOT_VERSION} no va con refresh= ni stale="))
+            }
+            (None, Some(_), Some(_)) => Response::bad_request("refresh= no va con stale="),
+            (Some(version), None, None) => {
+                exec_get_if_not_version(self.cache.as_ref(), key, version).await
+            }
+            (None, Some(window_ms), None) => {
+                exec_get_refresh(self.cache.as_ref(), key, window_ms).await
+            }
+            (None, None, Some(max_stale_ms)) => {
+                exec_get_stale(self.cache.as_ref(), key, max_stale_ms).await
+            }
+            (None, None, None) => exec_get(self.cache.as_ref(), key).await,
+        }
+    }
+}
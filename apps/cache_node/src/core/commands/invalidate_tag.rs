@@ -0,0 +1,46 @@
+use std::sync::Arc;
+
+use app_net::{tags::INVALIDATE_TAG, tokenize};
+use async_trait::async_trait;
+
+use crate::core::{
+    domain::{
+        models::Response,
+        services::{CacheService, CommandHandler},
+    },
+    services::{Op, OpLog},
+    usecases::exec_invalidate_tag,
+};
+
+/// `INVALIDATE-TAG "<tag>"`: borra las claves de este nodo escritas con `tags=<tag>`.
+pub struct InvalidateTagCommand<C> {
+    cache: Arc<C>,
+    op_log: Arc<OpLog>,
+}
+
+impl<C: CacheService> InvalidateTagCommand<C> {
+    pub fn new(cache: Arc<C>, op_log: Arc<OpLog>) -> Self {
+        Self { cache, op_log }
+    }
+}
+
+#[async_trait]
+impl<C: CacheService + 'static> CommandHandler for InvalidateTagCommand<C> {
+    fn action(&self) -> &'static str {
+        INVALIDATE_TAG
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    async fn handle(&self, payload: &str) -> Response {
+        let tag = tokenize(payload).next().unwrap_or_default().into_owned();
+        let _order = self.op_log.lock_all().await;
+        let res = exec_invalidate_tag(self.cache.as_ref(), &tag).await;
+        if matches!(res, Response::Integer(removed) if removed > 0) {
+            self.op_log.append(Op::InvalidateTag { tag });
+        }
+        res
+    }
+}
@@ -0,0 +1,22 @@
+use app_net::tokenize;
+use async_trait::async_trait;
+
+use crate::core::{
+    domain::{models::Response, services::CommandHandler},
+    usecases::exec_log_filter,
+};
+
+/// Lee (sin filtro) o reemplaza el filtro de logs del nodo.
+pub struct LogFilterCommand;
+
+#[async_trait]
+impl CommandHandler for LogFilterCommand {
+    fn action(&self) -> &'static str {
+        "LOG-FILTER"
+    }
+
+    async fn handle(&self, payload: &str) -> Response {
+        let filter = tokenize(payload).next().unwrap_or_default().into_owned();
+        exec_log_filter(filter).await
+    }
+}
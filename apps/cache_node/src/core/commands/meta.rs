@@ -0,0 +1,34 @@
+use std::sync::Arc;
+
+use app_net::tokenize;
+use async_trait::async_trait;
+
+use crate::core::{
+    domain::{
+        models::Response,
+        services::{CacheService, CommandHandler},
+    },
+    usecases::exec_meta,
+};
+
+pub struct MetaCommand<C> {
+    cache: Arc<C>,
+}
+
+impl<C: CacheService> MetaCommand<C> {
+    pub fn new(cache: Arc<C>) -> Self {
+        Self { cache }
+    }
+}
+
+#[async_trait]
+impl<C: CacheService + 'static> CommandHandler for MetaCommand<C> {
+    fn action(&self) -> &'static str {
+        "META"
+    }
+
+    async fn handle(&self, payload: &str) -> Response {
+        let key = tokenize(payload).next().unwrap_or_default().into_owned();
+        exec_meta(self.cache.as_ref(), key).await
+    }
+}
@@ -0,0 +1,111 @@
+//! Comandos del protocolo del nodo, uno por módulo. Para agregar uno: implementar
+//! `CommandHandler` y darlo de alta en `register_builtins` (o en el `CommandRegistry` que
+//! arma `CacheNodeModule`).
+
+pub mod config;
+pub mod del;
+pub mod del_prefix;
+pub mod export_range;
+pub mod get;
+pub mod invalidate_tag;
+pub mod log_filter;
+pub mod meta;
+pub mod multi;
+pub mod peek;
+pub mod ping;
+pub mod put;
+pub mod rename;
+pub mod replicate_from;
+pub mod set_role;
+pub mod slow_log;
+pub mod snapshot;
+pub mod stats;
+pub mod top_keys;
+
+use std::sync::Arc;
+
+use app_core::clock::Clock;
+
+pub use self::config::ConfigCommand;
+pub use self::del::DelCommand;
+pub use self::del_prefix::DelPrefixCommand;
+pub use self::export_range::ExportRangeCommand;
+pub use self::get::GetCommand;
+pub use self::invalidate_tag::InvalidateTagCommand;
+pub use self::log_filter::LogFilterCommand;
+pub use self::meta::MetaCommand;
+pub use self::multi::MultiCommand;
+pub use self::peek::PeekCommand;
+pub use self::ping::PingCommand;
+pub use self::put::PutCommand;
+pub use self::rename::RenameCommand;
+pub use self::replicate_from::ReplicateFromCommand;
+pub use self::set_role::SetRoleCommand;
+pub use self::slow_log::SlowLogCommand;
+pub use self::snapshot::SnapshotCommand;
+pub use self::stats::StatsCommand;
+pub use self::top_keys::TopKeysCommand;
+
+use crate::core::{
+    domain::{
+        models::RoleState,
+        services::{CacheService, ReplicationService},
+    },
+    services::{CommandRegistry, IdempotencyCache, OpLog, WriteBatcher},
+};
+
+/// Lo que necesitan los comandos de base.
+pub struct CommandDeps<C> {
+    pub cache: Arc<C>,
+    pub role: Arc<RoleState>,
+    /// Escrituras aplicadas, para el stream de replicación hacia las réplicas.
+    pub op_log: Arc<OpLog>,
+    pub replication: Option<Arc<dyn ReplicationService>>,
+    /// El del cache: un `PUT` con `ttl=` vence contando desde él.
+    pub clock: Arc<dyn Clock>,
+    /// Si está, los `PUT` sin condición pasan por él.
+    pub write_batcher: Option<Arc<WriteBatcher<C>>>,
+    /// Las respuestas de los `PUT` y `DEL` con `idem=`; sin él se aplican siempre.
+    pub idempotency: Option<Arc<IdempotencyCache>>,
+}
+
+pub fn register_builtins<C: CacheService + 'static>(
+    registry: &mut CommandRegistry,
+    deps: CommandDeps<C>,
+) {
+    registry
+        .register(PingCommand)
+        .register(
+            PutCommand::new(deps.cache.clone(), deps.op_log.clone())
+                .with_clock(deps.clock)
+                .with_batcher(deps.write_batcher)
+                .with_idempotency(deps.idempotency.clone()),
+        )
+        .register(GetCommand::new(deps.cache.clone()))
+        .register(PeekCommand::new(deps.cache.clone()))
+        .register(
+            DelCommand::new(deps.cache.clone(), deps.op_log.clone())
+                .with_idempotency(deps.idempotency),
+        )
+        .register(DelPrefixCommand::new(
+            deps.cache.clone(),
+            deps.op_log.clone(),
+        ))
+        .register(MultiCommand::new(deps.cache.clone(), deps.op_log.clone()))
+        .register(RenameCommand::new(deps.cache.clone(), deps.op_log.clone()))
+        .register(InvalidateTagCommand::new(
+            deps.cache.clone(),
+            deps.op_log.clone(),
+        ))
+        .register(MetaCommand::new(deps.cache.clone()))
+        .register(SnapshotCommand::new(
+            deps.cache.clone(),
+            deps.op_log.clone(),
+        ))
+        .register(ExportRangeCommand::new(deps.cache.clone(), deps.op_log))
+        .register(ConfigCommand::new(deps.cache.clone()))
+        .register(StatsCommand::new(deps.cache))
+        .register(LogFilterCommand)
+        .register(SetRoleCommand::new(deps.role))
+        .register(ReplicateFromCommand::new(deps.replication));
+}
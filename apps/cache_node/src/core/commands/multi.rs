@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use app_net::{TxCommand, parse_multi};
+use async_trait::async_trait;
+
+use crate::core::{
+    domain::{
+        models::Response,
+        services::{CacheService, CommandHandler},
+    },
+    services::{Op, OpLog},
+    usecases::exec_multi,
+};
+
+/// `MULTI "<comando>"...` (ver `app_net::tx`): `WATCH`, `GET`, `VERSION`, `PUT` y `DEL`
+/// sobre este nodo, aplicados como una unidad.
+pub struct MultiCommand<C> {
+    cache: Arc<C>,
+    op_log: Arc<OpLog>,
+}
+
+impl<C: CacheService> MultiCommand<C> {
+    pub fn new(cache: Arc<C>, op_log: Arc<OpLog>) -> Self {
+        Self { cache, op_log }
+    }
+}
+
+#[async_trait]
+impl<C: CacheService + 'static> CommandHandler for MultiCommand<C> {
+    fn action(&self) -> &'static str {
+        "MULTI"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    async fn handle(&self, payload: &str) -> Response {
+        let commands = match parse_multi(payload) {
+            Ok(commands) => commands,
+            Err(e) => return Response::from_error(&e),
+        };
+
+        let written: Vec<&str> = commands
+            .iter()
+            .filter_map(|command| match command {
+                TxCommand::Put { key, .. } | TxCommand::Del { key } => Some(key.as_str()),
+                _ => None,
+            })
+            .collect();
+        let _order = self.op_log.lock_keys(&written).await;
+        let res = exec_multi(self.cache.as_ref(), &commands).await;
+        let Response::Values(results) = &res else {
+            return res;
+        };
+
+        // a las réplicas les llegan como escrituras sueltas, en el mismo orden
+        let applied = commands.iter().filter(|c| !c.is_watch()).zip(results);
+        for (command, result) in applied {
+            match command {
+                TxCommand::Put { key, value, ttl } => {
+                    self.op_log.append(Op::Put {
+                        key: key.clone(),
+                        value: value.clone(),
+                        expires_at: *ttl,
+                        tags: Vec::new(),
+                    });
+                }
+                TxCommand::Del { key } if result == "1" => {
+                    self.op_log.append(Op::Del { key: key.clone() });
+                }
+                _ => {}
+            }
+        }
+        res
+    }
+}
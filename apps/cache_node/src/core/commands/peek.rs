@@ -0,0 +1,35 @@
+use std::sync::Arc;
+
+use app_net::tokenize;
+use async_trait::async_trait;
+
+use crate::core::{
+    domain::{
+        models::Response,
+        services::{CacheService, CommandHandler},
+    },
+    usecases::exec_peek,
+};
+
+/// `PEEK "<clave>"`: el valor, como `GET`, sin moverla en el LRU.
+pub struct PeekCommand<C> {
+    cache: Arc<C>,
+}
+
+impl<C: CacheService> PeekCommand<C> {
+    pub fn new(cache: Arc<C>) -> Self {
+        Self { cache }
+    }
+}
+
+#[async_trait]
+impl<C: CacheService + 'static> CommandHandler for PeekCommand<C> {
+    fn action(&self) -> &'static str {
+        "PEEK"
+    }
+
+    async fn handle(&self, payload: &str) -> Response {
+        let key = tokenize(payload).next().unwrap_or_default().into_owned();
+        exec_peek(self.cache.as_ref(), key).await
+    }
+}
@@ -0,0 +1,19 @@
+use async_trait::async_trait;
+
+use crate::core::{
+    domain::{models::Response, services::CommandHandler},
+    usecases::exec_ping,
+};
+
+pub struct PingCommand;
+
+#[async_trait]
+impl CommandHandler for PingCommand {
+    fn action(&self) -> &'static str {
+        "PING"
+    }
+
+    async fn handle(&self, _payload: &str) -> Response {
+        exec_ping().await
+    }
+}
@@ -0,0 +1,129 @@
+use std::sync::Arc;
+
+use app_core::clock::{AppClock, Clock};
+use app_net::{PutCondition, parse_expiry, take_idempotency, take_tags, tokenize};
+use async_trait::async_trait;
+
+use crate::core::{
+    domain::{
+        models::Response,
+        services::{CacheService, CommandHandler},
+    },
+    services::{IdempotencyCache, Op, OpLog, WriteBatcher},
+    usecases::{exec_put, exec_put_batched, exec_put_if},
+};
+
+/// `PUT "<clave>" "<valor>" ["<expires_at>"] ["tags=<a,b>"] ["idem=<token>"] ["IF" "<condición>"]`:
+/// `expires_at` es el instante absoluto en ms (o con unidad, ver `app_net::ttl`) que ya
+/// calculó el master, o `ttl=<ms>` si el master manda lo que le falta a la clave y el
+/// vencimiento se cuenta con el reloj de este nodo; los tags, los de `app_net::tags`, y la condición, la de
+/// `app_net::PutCondition`. Con `idem=` un reintento del mismo `PUT` no se vuelve a aplicar
+/// (ver `IdempotencyCache`).
+pub struct PutCommand<C> {
+    cache: Arc<C>,
+    op_log: Arc<OpLog>,
+    clock: Arc<dyn Clock>,
+    batcher: Option<Arc<WriteBatcher<C>>>,
+    idempotency: Option<Arc<IdempotencyCache>>,
+}
+
+impl<C: CacheService> PutCommand<C> {
+    pub fn new(cache: Arc<C>, op_log: Arc<OpLog>) -> Self {
+        Self {
+            cache,
+            op_log,
+            clock: Arc::new(AppClock::new()),
+            batcher: None,
+            idempotency: None,
+        }
+    }
+
+    /// El reloj contra el que vencen las claves del cache, para los `ttl=` relativos.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Los `PUT` sin condición se aplican en lotes (ver `WriteBatcher`).
+    pub fn with_batcher(mut self, batcher: Option<Arc<WriteBatcher<C>>>) -> Self {
+        self.batcher = batcher;
+        self
+    }
+
+    /// Dónde se recuerdan los `idem=`; sin él el token se acepta y se ignora.
+    pub fn with_idempotency(mut self, idempotency: Option<Arc<IdempotencyCache>>) -> Self {
+        self.idempotency = idempotency;
+        self
+    }
+
+    async fn apply(
+        &self,
+        key: String,
+        value: String,
+        expires_at: Option<u64>,
+        tags: Vec<String>,
+        condition: Option<PutCondition>,
+    ) -> Response {
+        let op = Op::Put {
+            key: key.clone(),
+            value: value.clone(),
+            expires_at,
+            tags: tags.clone(),
+        };
+        let cache = self.cache.as_ref();
+        let _order = self.op_log.lock_keys(&[&key]).await;
+        let res = match &condition {
+            Some(condition) => exec_put_if(cache, key, value, expires_at, &tags, condition).await,
+            None => match &self.batcher {
+                Some(batcher) => exec_put_batched(batcher, key, value, expires_at, &tags).await,
+                None => exec_put(cache, key, value, expires_at, &tags).await,
+            },
+        };
+        if matches!(res, Response::OkEmpty) {
+            self.op_log.append(op);
+        }
+        res
+    }
+}
+
+#[async_trait]
+impl<C: CacheService + 'static> CommandHandler for PutCommand<C> {
+    fn action(&self) -> &'static str {
+        "PUT"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn accesses_key(&self) -> bool {
+        true
+    }
+
+    async fn handle(&self, payload: &str) -> Response {
+        let mut args: Vec<_> = tokenize(payload).collect();
+        let parsed = PutCondition::take_from(&mut args).and_then(|condition| {
+            let token = take_idempotency(&mut args, 2)?;
+            Ok((condition, take_tags(&mut args)?, token))
+        });
+        let (condition, tags, token) = match parsed {
+            Ok(parsed) => parsed,
+            Err(e) => return Response::from_error(&e),
+        };
+        let mut args = args.into_iter();
+        let key = args.next().unwrap_or_default().into_owned();
+        let value = args.next().unwrap_or_default().into_owned();
+        // antes un valor ilegible se ignoraba y la clave quedaba sin expiración
+        let now_ms = self.clock.now_millis().as_millis_u64();
+        let expires_at = match args.next().map(|s| parse_expiry(&s, now_ms)).transpose() {
+            Ok(expires_at) => expires_at,
+            Err(e) => return Response::from_error(&e),
+        };
+
+        let write = self.apply(key, value, expires_at, tags, condition);
+        match &self.idempotency {
+            Some(idempotency) => idempotency.run(token.as_deref(), write).await,
+            None => write.await,
+        }
+    }
+}
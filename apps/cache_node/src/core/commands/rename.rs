@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use app_net::tokenize;
+use async_trait::async_trait;
+
+use crate::core::{
+    domain::{
+        models::Response,
+        services::{CacheService, CommandHandler},
+    },
+    services::{Op, OpLog},
+    usecases::exec_rename,
+};
+
+/// `RENAME "<desde>" "<hasta>"`: mueve la entrada (valor, vencimiento y tags) a otra clave
+/// del mismo nodo sin pisar una que ya exista.
+pub struct RenameCommand<C> {
+    cache: Arc<C>,
+    op_log: Arc<OpLog>,
+}
+
+impl<C: CacheService> RenameCommand<C> {
+    pub fn new(cache: Arc<C>, op_log: Arc<OpLog>) -> Self {
+        Self { cache, op_log }
+    }
+}
+
+#[async_trait]
+impl<C: CacheService + 'static> CommandHandler for RenameCommand<C> {
+    fn action(&self) -> &'static str {
+        "RENAME"
+    }
+
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn accesses_key(&self) -> bool {
+        true
+    }
+
+    async fn handle(&self, payload: &str) -> Response {
+        let mut args = tokenize(payload);
+        let from = args.next().unwrap_or_default().into_owned();
+        let to = args.next().unwrap_or_default().into_owned();
+        let _order = self.op_log.lock_keys(&[&from, &to]).await;
+        let res = exec_rename(self.cache.as_ref(), &from, &to).await;
+        if res == Response::OkEmpty {
+            self.op_log.append(Op::Rename { from, to });
+        }
+        res
+    }
+}
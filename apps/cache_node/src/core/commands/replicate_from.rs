@@ -0,0 +1,35 @@
+use std::sync::Arc;
+
+use app_net::tokenize;
+use async_trait::async_trait;
+
+use crate::core::{
+    domain::{
+        models::Response,
+        services::{CommandHandler, ReplicationService},
+    },
+    usecases::exec_replicate_from,
+};
+
+/// Empieza a replicar desde el primario en la dirección dada (vacía: deja de replicar).
+pub struct ReplicateFromCommand {
+    replication: Option<Arc<dyn ReplicationService>>,
+}
+
+impl ReplicateFromCommand {
+    pub fn new(replication: Option<Arc<dyn ReplicationService>>) -> Self {
+        Self { replication }
+    }
+}
+
+#[async_trait]
+impl CommandHandler for ReplicateFromCommand {
+    fn action(&self) -> &'static str {
+        "REPLICATE-FROM"
+    }
+
+    async fn handle(&self, payload: &str) -> Response {
+        let addr = tokenize(payload).next().unwrap_or_default().into_owned();
+        exec_replicate_from(self.replication.as_deref(), addr).await
+    }
+}
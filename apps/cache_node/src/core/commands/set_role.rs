@@ -0,0 +1,35 @@
+use std::sync::Arc;
+
+use app_net::tokenize;
+use async_trait::async_trait;
+
+use crate::core::{
+    domain::{
+        models::{Response, RoleState},
+        services::CommandHandler,
+    },
+    usecases::exec_set_role,
+};
+
+/// Lee (sin rol) o cambia el rol del nodo.
+pub struct SetRoleCommand {
+    role: Arc<RoleState>,
+}
+
+impl SetRoleCommand {
+    pub fn new(role: Arc<RoleState>) -> Self {
+        Self { role }
+    }
+}
+
+#[async_trait]
+impl CommandHandler for SetRoleCommand {
+    fn action(&self) -> &'static str {
+        "SET-ROLE"
+    }
+
+    async fn handle(&self, payload: &str) -> Response {
+        let role = tokenize(payload).next().unwrap_or_default().into_owned();
+        exec_set_role(&self.role, role).await
+    }
+}
@@ -0,0 +1,35 @@
+use std::sync::Arc;
+
+use app_net::tokenize;
+use async_trait::async_trait;
+
+use crate::core::{
+    domain::{models::Response, services::CommandHandler},
+    services::SlowLog,
+    usecases::exec_slow_log,
+};
+
+/// `SLOWLOG ["GET" [n] | "LEN" | "RESET"]` sobre los comandos lentos del nodo.
+pub struct SlowLogCommand {
+    slow_log: Arc<SlowLog>,
+}
+
+impl SlowLogCommand {
+    pub fn new(slow_log: Arc<SlowLog>) -> Self {
+        Self { slow_log }
+    }
+}
+
+#[async_trait]
+impl CommandHandler for SlowLogCommand {
+    fn action(&self) -> &'static str {
+        "SLOWLOG"
+    }
+
+    async fn handle(&self, payload: &str) -> Response {
+        let mut parts = tokenize(payload);
+        let sub = parts.next().unwrap_or_default().into_owned();
+        let arg = parts.next().map(|a| a.into_owned());
+        exec_slow_log(&self.slow_log, sub, arg).await
+    }
+}
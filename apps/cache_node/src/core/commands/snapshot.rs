@@ -0,0 +1,36 @@
+use std::sync::Arc;
+
+use app_net::snapshot::SNAPSHOT;
+use async_trait::async_trait;
+
+use crate::core::{
+    domain::{
+        models::Response,
+        services::{CacheService, CommandHandler},
+    },
+    services::OpLog,
+    usecases::exec_snapshot,
+};
+
+/// `SNAPSHOT`: todas las entradas vigentes, para los backups del master (ver `exec_snapshot`).
+pub struct SnapshotCommand<C> {
+    cache: Arc<C>,
+    op_log: Arc<OpLog>,
+}
+
+impl<C: CacheService> SnapshotCommand<C> {
+    pub fn new(cache: Arc<C>, op_log: Arc<OpLog>) -> Self {
+        Self { cache, op_log }
+    }
+}
+
+#[async_trait]
+impl<C: CacheService + 'static> CommandHandler for SnapshotCommand<C> {
+    fn action(&self) -> &'static str {
+        SNAPSHOT
+    }
+
+    async fn handle(&self, _payload: &str) -> Response {
+        exec_snapshot(self.cache.as_ref(), &self.op_log).await
+    }
+}
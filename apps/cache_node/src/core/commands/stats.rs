@@ -0,0 +1,38 @@
+use std::sync::Arc;
+
+use app_net::{stats::STATS, tokenize};
+use async_trait::async_trait;
+
+use crate::core::{
+    domain::{
+        models::Response,
+        services::{CacheService, CommandHandler},
+    },
+    usecases::exec_stats,
+};
+
+/// `STATS ["<namespace>"]`: ocupación del cache y uso de cada namespace.
+pub struct StatsCommand<C> {
+    cache: Arc<C>,
+}
+
+impl<C: CacheService> StatsCommand<C> {
+    pub fn new(cache: Arc<C>) -> Self {
+        Self { cache }
+    }
+}
+
+#[async_trait]
+impl<C: CacheService + 'static> CommandHandler for StatsCommand<C> {
+    fn action(&self) -> &'static str {
+        STATS
+    }
+
+    async fn handle(&self, payload: &str) -> Response {
+        let namespace = tokenize(payload)
+            .next()
+            .map(|n| n.into_owned())
+            .filter(|n| !n.is_empty());
+        exec_stats(self.cache.as_ref(), namespace).await
+    }
+}
@@ -0,0 +1,35 @@
+use std::sync::Arc;
+
+use app_net::tokenize;
+use async_trait::async_trait;
+
+use crate::core::{
+    domain::{models::Response, services::CommandHandler},
+    services::TopKeys,
+    usecases::exec_top_keys,
+};
+
+/// `TOPKEYS ["GET" [n] | "LEN" | "RESET"]` sobre las claves más pedidas al nodo.
+pub struct TopKeysCommand {
+    top_keys: Arc<TopKeys>,
+}
+
+impl TopKeysCommand {
+    pub fn new(top_keys: Arc<TopKeys>) -> Self {
+        Self { top_keys }
+    }
+}
+
+#[async_trait]
+impl CommandHandler for TopKeysCommand {
+    fn action(&self) -> &'static str {
+        "TOPKEYS"
+    }
+
+    async fn handle(&self, payload: &str) -> Response {
+        let mut parts = tokenize(payload);
+        let sub = parts.next().unwrap_or_default().into_owned();
+        let arg = parts.next().map(|a| a.into_owned());
+        exec_top_keys(&self.top_keys, sub, arg).await
+    }
+}
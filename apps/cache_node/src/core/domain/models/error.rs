@@ -1,3 +1,5 @@
+use app_core::error::{ErrorKind, HasErrorKind};
+use app_net::SocketError;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -7,4 +9,20 @@ pub enum AppError {
 
     #[error("Error on socket reading: {0}")]
     SocketReadingError(String),
+
+    #[error("Network error: {0}")]
+    Net(#[from] SocketError),
+
+    #[error("Invalid config: {0}")]
+    Config(String),
+}
+
+impl HasErrorKind for AppError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            AppError::SocketError(_) | AppError::SocketReadingError(_) => ErrorKind::Connection,
+            AppError::Net(e) => e.kind(),
+            AppError::Config(_) => ErrorKind::BadRequest,
+        }
+    }
 }
@@ -0,0 +1,32 @@
+use std::fmt;
+
+/// Metadatos de una clave tal como los ve el nodo (`META`), para comparar réplicas o
+/// revisar que el TTL llegó bien. Los tiempos son ms del reloj del nodo.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyMeta {
+    pub version: u64,
+    pub expires_at: Option<u64>,
+    /// Aproximado: bytes de la clave más los del valor.
+    pub size_bytes: usize,
+    /// Último `PUT`/`GET` de la clave.
+    pub last_access: u64,
+    /// Ya venció pero el reaper todavía no la sacó.
+    pub expired: bool,
+    /// Vencida hace menos que `STALE_GRACE_MS`: un `GET ... stale=` todavía puede servirla.
+    pub stale: bool,
+}
+
+impl fmt::Display for KeyMeta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "version={} expires_at=", self.version)?;
+        match self.expires_at {
+            Some(exp) => write!(f, "{exp}")?,
+            None => f.write_str("-")?,
+        }
+        write!(
+            f,
+            " size={} last_access={} expired={} stale={}",
+            self.size_bytes, self.last_access, self.expired, self.stale
+        )
+    }
+}
@@ -1,7 +1,9 @@
-pub mod command;
 pub mod error;
+pub mod key_meta;
 pub mod response;
+pub mod role;
 
-pub use self::command::Command;
 pub use self::error::AppError;
+pub use self::key_meta::KeyMeta;
 pub use self::response::Response;
+pub use self::role::{NodeRole, RoleState};
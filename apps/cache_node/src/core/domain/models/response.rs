@@ -1,36 +1,103 @@
+use app_core::error::{ErrorKind, HasErrorKind};
+use app_net::{ResponseBody, ResponseData, types::ReqId};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Response {
     OkEmpty,
-    OkValue(String),
+    Value(String),
+    Values(Vec<String>),
+    /// Respuesta de un `GET` condicional (ver `app_net::conditional`).
+    Versioned {
+        value: String,
+        version: u64,
+    },
+    NotModified {
+        version: u64,
+    },
+    Integer(i64),
     Pong,
     Echo(String),
     Empty,
-    Error(String),
+    Error {
+        code: ErrorKind,
+        msg: String,
+    },
 }
 
 impl Response {
-    pub fn to_wire(&self) -> String {
+    pub fn error(code: ErrorKind, msg: impl Into<String>) -> Self {
+        Response::Error {
+            code,
+            msg: msg.into(),
+        }
+    }
+
+    pub fn bad_request(msg: impl Into<String>) -> Self {
+        Self::error(ErrorKind::BadRequest, msg)
+    }
+
+    /// Error con la categoría de `e`.
+    pub fn from_error<E: HasErrorKind + ToString>(e: &E) -> Self {
+        Self::error(e.kind(), e.to_string())
+    }
+
+    /// Codificación en el protocolo; la define `ResponseBody` (app_net).
+    pub fn to_body(&self) -> ResponseBody {
         match self {
-            Response::Pong => "pong".to_string(),
-            Response::OkEmpty => "".to_string(),
-            Response::OkValue(v) => format!("{}", v),
-            Response::Echo(s) => format!("echo:{s}"),
-            Response::Empty => "EMPTY".to_string(),
-            Response::Error(e) => format!("ERROR: {e}"),
+            Response::Pong => ResponseBody::Value("pong".to_string()),
+            Response::OkEmpty => ResponseBody::Ok,
+            Response::Value(v) => ResponseBody::Value(v.clone()),
+            Response::Values(values) => ResponseBody::Values(values.clone()),
+            Response::Versioned { value, version } => ResponseBody::Versioned {
+                value: value.clone(),
+                version: *version,
+            },
+            Response::NotModified { version } => ResponseBody::NotModified { version: *version },
+            Response::Integer(n) => ResponseBody::Integer(*n),
+            Response::Echo(s) => ResponseBody::Value(format!("echo:{s}")),
+            Response::Empty => ResponseBody::Empty,
+            Response::Error { code, msg } => ResponseBody::error(*code, msg.clone()),
         }
     }
+
+    /// Payload en el protocolo.
+    pub fn to_wire(&self) -> String {
+        self.to_body().payload()
+    }
+
+    pub fn into_response(self, req_id: ReqId) -> ResponseData {
+        self.to_body().into_response(req_id)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use app_core::error::ErrorKind;
+
     use crate::core::domain::models::Response;
 
     #[test]
     fn response_to_wire_variants() {
         assert_eq!(Response::Pong.to_wire(), "pong");
         assert_eq!(Response::OkEmpty.to_wire(), "");
-        assert_eq!(Response::OkValue("abc".into()).to_wire(), "abc");
+        assert_eq!(Response::Value("abc".into()).to_wire(), "abc");
+        assert_eq!(
+            Response::Values(vec!["a".into(), "b c".into()]).to_wire(),
+            "a \"b c\""
+        );
+        assert_eq!(Response::Integer(42).to_wire(), "42");
         assert_eq!(Response::Echo("x".into()).to_wire(), "echo:x");
         assert_eq!(Response::Empty.to_wire(), "EMPTY");
-        assert_eq!(Response::Error("boom".into()).to_wire(), "ERROR: boom");
+        assert_eq!(Response::bad_request("boom").to_wire(), "ERROR: boom");
+    }
+
+    #[test]
+    fn errors_carry_their_code() {
+        let res = Response::error(ErrorKind::Conflict, "boom").into_response("1".into());
+        assert_eq!((res.code, res.error_message()), (409, Some("boom")));
+        assert_eq!(
+            Response::Value("v".into()).into_response("1".into()).code,
+            200
+        );
     }
 }
@@ -0,0 +1,76 @@
+use std::{fmt, str::FromStr};
+
+use parking_lot::RwLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeRole {
+    Master,
+    Replica,
+}
+
+impl NodeRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NodeRole::Master => "MASTER",
+            NodeRole::Replica => "REPLICA",
+        }
+    }
+}
+
+impl fmt::Display for NodeRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for NodeRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "MASTER" => Ok(NodeRole::Master),
+            "REPLICA" => Ok(NodeRole::Replica),
+            other => Err(format!("rol inválido: '{other}' (MASTER | REPLICA)")),
+        }
+    }
+}
+
+/// Rol actual del nodo, compartido entre las conexiones y el controller. Lo cambia el
+/// master con `SET-ROLE` (promoción de una réplica o failover manual).
+pub struct RoleState {
+    role: RwLock<NodeRole>,
+    /// Si está activo, una réplica rechaza los `PUT`.
+    strict_writes: bool,
+}
+
+impl RoleState {
+    pub fn new(role: NodeRole, strict_writes: bool) -> Self {
+        Self {
+            role: RwLock::new(role),
+            strict_writes,
+        }
+    }
+
+    pub fn get(&self) -> NodeRole {
+        *self.role.read()
+    }
+
+    /// Cambia el rol y devuelve el anterior.
+    pub fn set(&self, role: NodeRole) -> NodeRole {
+        std::mem::replace(&mut *self.role.write(), role)
+    }
+
+    pub fn strict_writes(&self) -> bool {
+        self.strict_writes
+    }
+
+    pub fn accepts_writes(&self) -> bool {
+        !self.strict_writes || self.get() == NodeRole::Master
+    }
+}
+
+impl Default for RoleState {
+    fn default() -> Self {
+        Self::new(NodeRole::Master, false)
+    }
+}
@@ -1,7 +1,83 @@
+use app_net::{PutCondition, TxCommand};
 use async_trait::async_trait;
 
+use crate::core::{
+    domain::models::KeyMeta,
+    services::{
+        BatchPut, CacheStats, NamespaceStats, Op, QuotaExceeded, RenameOutcome, TxConflict,
+        TxOutcome,
+    },
+};
+
 #[async_trait]
 pub trait CacheService: Send + Sync {
-    async fn put(&self, key: String, value: String, ttl: Option<u64>);
-    async fn get(&self, key: &String) -> Option<String>;
+    /// Los `tags` reemplazan los que tuviera la clave (ver `Cache::put_tagged`). `false` si
+    /// no entró en la cuota de su namespace.
+    async fn put(
+        &self,
+        key: String,
+        value: String,
+        expires_at: Option<u64>,
+        tags: &[String],
+    ) -> bool;
+    /// Varios `put` en orden (ver `Cache::put_batch`); uno por uno si el cache no sabe
+    /// agruparlos. Devuelve lo que habría devuelto cada `put`.
+    async fn put_batch(&self, puts: Vec<BatchPut<String, String>>) -> Vec<bool> {
+        let mut stored = Vec::with_capacity(puts.len());
+        for put in puts {
+            stored.push(
+                self.put(put.key, put.value, put.expires_at, &put.tags)
+                    .await,
+            );
+        }
+        stored
+    }
+    /// `put` si se cumple `condition` (ver `Cache::put_if`); devuelve si escribió.
+    async fn put_if(
+        &self,
+        key: String,
+        value: String,
+        expires_at: Option<u64>,
+        tags: &[String],
+        condition: &PutCondition,
+    ) -> Result<bool, QuotaExceeded>;
+    async fn get(&self, key: &str) -> Option<String>;
+    /// `get` con la versión de la clave (ver `Cache::get_versioned`).
+    async fn get_versioned(&self, key: &str) -> Option<(String, u64)>;
+    /// `get` que no cuenta como acceso (ver `Cache::peek`).
+    async fn peek(&self, key: &str) -> Option<String>;
+    /// `get` con refresco anticipado (ver `Cache::get_for_refresh`): el valor y si quien
+    /// lee quedó elegido para volver a escribirlo.
+    async fn get_for_refresh(&self, key: &str, window_ms: u64) -> Option<(String, bool)>;
+    /// `get` que acepta una entrada vencida hace menos de `max_stale_ms` (ver
+    /// `Cache::get_stale`): el valor, si hay que refrescarlo y hace cuánto venció.
+    async fn get_stale(&self, key: &str, max_stale_ms: u64) -> Option<(String, bool, Option<u64>)>;
+    /// `true` si la clave existía.
+    async fn remove(&self, key: &str) -> bool;
+    /// Borra las claves con `tag`; devuelve cuáles.
+    async fn invalidate_tag(&self, tag: &str) -> Vec<String>;
+    /// Borra las claves que empiezan con `prefix` (ver `Cache::invalidate_where`); devuelve
+    /// cuántas eran.
+    async fn remove_prefix(&self, prefix: &str) -> usize;
+    /// Cuántas claves borraría `remove_prefix`.
+    async fn count_prefix(&self, prefix: &str) -> usize;
+    /// Mueve la entrada de `from` a `to` sin pisar una que ya exista (ver `Cache::rename`).
+    async fn rename(&self, from: &str, to: &str) -> Result<RenameOutcome, QuotaExceeded>;
+    /// Metadatos de la clave; no cuenta como acceso (no la mueve en el LRU).
+    async fn meta(&self, key: &str) -> Option<KeyMeta>;
+    /// Los comandos de un `MULTI` como una unidad (ver `Cache::transact`), con el
+    /// `expires_at` absoluto en los `PUT`. Un resultado por comando que no sea `WATCH`.
+    async fn transact(
+        &self,
+        commands: &[TxCommand],
+    ) -> Result<Vec<TxOutcome<String>>, TxConflict<String>>;
+    fn stats(&self) -> CacheStats;
+    /// Cambia la capacidad en entradas (ver `Cache::resize`); devuelve cuántas desalojó.
+    fn resize(&self, capacity: usize) -> usize;
+    /// Cambia el tope de memoria que se anuncia (`CacheStats::max_bytes`); `None` sin tope.
+    fn set_max_bytes(&self, max_bytes: Option<u64>);
+    /// Entradas vigentes como `Op::Put`, con el `expires_at` absoluto y sus tags.
+    fn snapshot(&self) -> Vec<Op>;
+    /// Uso y cuota de cada namespace, ordenados por nombre.
+    fn namespace_stats(&self) -> Vec<(String, NamespaceStats)>;
 }
@@ -0,0 +1,22 @@
+use async_trait::async_trait;
+
+use crate::core::domain::models::Response;
+
+/// Un comando del protocolo del nodo (`REQ <id> <ACCIÓN> "<payload>"`). Cada uno
+/// interpreta su propio payload; se dan de alta en un `CommandRegistry`.
+#[async_trait]
+pub trait CommandHandler: Send + Sync {
+    fn action(&self) -> &'static str;
+
+    /// Modifica datos: una réplica en modo estricto lo rechaza sin llegar a `handle`.
+    fn is_write(&self) -> bool {
+        false
+    }
+
+    /// Su primer argumento es la clave que lee o escribe; cuenta para `TOPKEYS`.
+    fn accesses_key(&self) -> bool {
+        false
+    }
+
+    async fn handle(&self, payload: &str) -> Response;
+}
@@ -1,3 +1,7 @@
 pub mod cache_service;
+pub mod command_handler;
+pub mod replication_service;
 
 pub use cache_service::CacheService;
+pub use command_handler::CommandHandler;
+pub use replication_service::ReplicationService;
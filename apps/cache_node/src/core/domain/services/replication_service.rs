@@ -0,0 +1,8 @@
+/// Lado réplica de la replicación entre nodos: sigue el stream de op-log de un primario.
+pub trait ReplicationService: Send + Sync {
+    /// Cambia el primario del que se replica; `None` deja de replicar.
+    fn follow(&self, primary_addr: Option<&str>);
+
+    /// Dirección del primario que se está siguiendo.
+    fn source(&self) -> Option<String>;
+}
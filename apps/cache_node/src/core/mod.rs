@@ -1,3 +1,4 @@
+pub mod commands;
 pub mod domain;
 pub mod services;
 pub mod usecases;
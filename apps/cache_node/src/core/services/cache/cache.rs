@@ -1,179 +1,1327 @@
-use std::{hash::Hash, sync::Arc};
+use std::{
+    cell::Cell,
+    hash::Hash,
+    sync::{
+        Arc, OnceLock,
+        atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
 
 use app_core::clock::{AppClock, AppTime, Clock};
+use app_net::refresh::should_refresh;
 use dashmap::{DashMap, Entry};
-use parking_lot::Mutex;
-use tokio::time;
+use parking_lot::{Mutex, RwLock, RwLockWriteGuard};
+use tokio::{sync::broadcast, time};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
 
-use crate::core::services::cache::{lru::LruState, timing_wheel::TimingWheel};
+use crate::core::services::cache::{
+    compaction::shrink_dashmap,
+    expiry::ExpiryStrategy,
+    namespaces::{NamespaceAccounting, NamespaceStats, QuotaExceeded},
+    policy::{Eviction, EvictionPolicy},
+    read_buffer::ReadBuffer,
+    tags::TagIndex,
+    timing_wheel::TimingWheel,
+};
+
+/// Desalojos seguidos sin víctima (ver `SampledKeys::victim`) tras los que `resize` deja
+/// de intentar aunque sobren entradas.
+const RESIZE_MISSES: usize = 256;
+
+/// Ticks seguidos con más claves pendientes que el anterior tras los que el reaper avisa
+/// que no da abasto.
+const BACKLOG_WARN_TICKS: u32 = 10;
+
+/// Ticks salteados de una vez (el reaper no corrió a tiempo) a partir de los que se avisa.
+const MISSED_TICKS_WARN: u64 = 10;
+
+/// Claves que `invalidate_where` borra con el LRU tomado antes de soltarlo.
+const SCAN_BATCH: usize = 1024;
 
-#[derive(Clone)]
 pub struct CacheEntry<V> {
     pub value: Arc<V>,
     pub version: u64,
     pub expires_at: Option<AppTime>,
+    /// Último `put`/`get` (ms del reloj del cache). Atómico para que `get` lo actualice
+    /// con el shard tomado en lectura.
+    pub last_access: AtomicU64,
+    /// Ya se eligió a alguien para refrescarla (ver `Cache::get_for_refresh` y
+    /// `Cache::get_stale`); se limpia con la próxima escritura, que crea otra entrada.
+    pub refresh_claimed: AtomicBool,
 }
 
 impl<V> CacheEntry<V> {
     #[inline]
-    pub fn new(value: V, version: u64, expires_at: Option<AppTime>) -> Self {
+    pub fn new(value: V, version: u64, expires_at: Option<AppTime>, now_ms: u64) -> Self {
         Self {
             value: Arc::new(value),
             version,
             expires_at,
+            last_access: AtomicU64::new(now_ms),
+            refresh_claimed: AtomicBool::new(false),
+        }
+    }
+}
+
+/// Metadatos de una entrada, leídos sin contar como acceso (ver `Cache::meta`).
+#[derive(Debug, Clone)]
+pub struct EntryMeta<V> {
+    pub value: Arc<V>,
+    pub version: u64,
+    pub expires_at: Option<AppTime>,
+    pub last_access: AppTime,
+}
+
+/// Una entrada vigente de `Cache::snapshot_iter`.
+#[derive(Debug, Clone)]
+pub struct SnapshotEntry<K, V> {
+    pub key: K,
+    pub value: Arc<V>,
+    pub version: u64,
+    pub expires_at: Option<AppTime>,
+}
+
+/// Recorre el cache sin tomar sus shards durante todo el recorrido: las claves se copian
+/// de a un shard por vez al crearlo y cada entrada se lee recién al pedirla, con el shard
+/// de su clave tomado solo para esa lectura. Las claves que salen mientras tanto se
+/// saltean y las que entran después no aparecen; una clave presente de punta a punta sale
+/// una vez, con el valor que tenga al leerla.
+pub struct SnapshotIter<'a, K: Eq + Hash + Clone + Send + Sync + 'static, V: Send + Sync + 'static>
+{
+    cache: &'a Cache<K, V>,
+    keys: std::vec::IntoIter<K>,
+}
+
+impl<K: Eq + Hash + Clone + Send + Sync + 'static, V: Send + Sync + 'static> Iterator
+    for SnapshotIter<'_, K, V>
+{
+    type Item = SnapshotEntry<K, V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for key in self.keys.by_ref() {
+            let now = self.cache.clock.now_millis();
+            let Some(entry) = self.cache.map.get(&key) else {
+                continue;
+            };
+            if entry
+                .expires_at
+                .as_ref()
+                .is_some_and(|exp| exp.is_before_or_eq(&now))
+            {
+                continue;
+            }
+            let (value, version, expires_at) =
+                (entry.value.clone(), entry.version, entry.expires_at.clone());
+            drop(entry);
+            return Some(SnapshotEntry {
+                key,
+                value,
+                version,
+                expires_at,
+            });
         }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.keys.len()))
     }
 }
 
+/// Un `put_tagged` de `Cache::put_batch`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchPut<K, V> {
+    pub key: K,
+    pub value: V,
+    pub expires_at: Option<u64>,
+    pub tags: Vec<String>,
+}
+
+/// Un paso de `Cache::transact`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxStep<K, V> {
+    Get(K),
+    Version(K),
+    /// Como `put`: la clave queda sin tags.
+    Put {
+        key: K,
+        value: V,
+        expires_at: Option<u64>,
+    },
+    Del(K),
+}
+
+/// Resultado de cada `TxStep`, en el mismo orden.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxOutcome<V> {
+    Value(Option<Arc<V>>),
+    /// 0 si la clave no existe.
+    Version(u64),
+    Stored,
+    /// Si la clave existía.
+    Removed(bool),
+    /// El `Put` no entró en la cuota de su namespace y no se aplicó.
+    OverQuota,
+}
+
+impl<V> TxOutcome<V> {
+    /// El mismo resultado, con el valor leído (si hay) pasado por `f`.
+    pub fn map_value<W>(self, f: impl FnOnce(&V) -> W) -> TxOutcome<W> {
+        match self {
+            Self::Value(value) => TxOutcome::Value(value.map(|value| Arc::new(f(&value)))),
+            Self::Version(version) => TxOutcome::Version(version),
+            Self::Stored => TxOutcome::Stored,
+            Self::Removed(removed) => TxOutcome::Removed(removed),
+            Self::OverQuota => TxOutcome::OverQuota,
+        }
+    }
+}
+
+/// Un `WATCH` no coincidió: `version` es la actual de `key`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxConflict<K> {
+    pub key: K,
+    pub version: u64,
+}
+
+/// Qué hizo `Cache::rename`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenameOutcome {
+    Moved,
+    /// `from` no existe o venció.
+    Missing,
+    /// `to` ya existe; no se tocó nada.
+    TargetExists,
+}
+
+/// Contadores acumulados desde que se creó el cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub entries: usize,
+    pub capacity: usize,
+    /// Entradas sacadas por el LRU al pasarse de capacidad.
+    pub evictions: u64,
+    /// Entradas borradas por vencer su TTL (reaper o `get`).
+    pub expirations: u64,
+    /// Entradas borradas a pedido: `invalidate`, `invalidate_tag` o un `Del` de `transact`.
+    pub invalidations: u64,
+    /// Clave más valor de todas las entradas; 0 si el cache no tiene `NamespaceAccounting`,
+    /// que es quien sabe pesarlas.
+    pub bytes: u64,
+    /// Tope de `bytes` que anuncia el nodo (0 es sin tope); el cache no lo aplica, solo lo
+    /// completa quien lo envuelve.
+    pub max_bytes: u64,
+    /// Lecturas (`get`) que encontraron una entrada vigente.
+    pub hits: u64,
+    pub misses: u64,
+    /// Altas y reemplazos, incluidos los de `transact`.
+    pub writes: u64,
+    /// Claves vencidas que el reaper dejó para el próximo tick por los topes de
+    /// `ExpiryStrategy::Hybrid`.
+    pub expiry_backlog: u64,
+    /// Claves que revisó el último tick del reaper.
+    pub expiry_tick_keys: u64,
+    /// Ticks que el reaper salteó por correr tarde, acumulados.
+    pub expiry_missed_ticks: u64,
+    /// Entradas que sacó el reaper (las demás de `expirations` las sacó una lectura).
+    pub expiry_reaped: u64,
+    /// Suma, sobre esas entradas, de cuánto después de su vencimiento (más la gracia de
+    /// `get_stale`) las sacó; incluye hasta un tick por la resolución de la rueda.
+    pub expiry_drift_ms: u64,
+    /// El mayor de esos retrasos en el último tick.
+    pub expiry_drift_max_ms: u64,
+    /// Pasadas de `Cache::compact` que achicaron alguna tabla.
+    pub compactions: u64,
+    /// Bytes (aproximados) que soltaron esas pasadas.
+    pub compacted_bytes: u64,
+}
+
 pub struct Cache<K: Eq + Hash + Clone + Send + Sync + 'static, V: Send + Sync + 'static> {
-    pub map: DashMap<K, CacheEntry<V>>,
-    pub clock: Arc<AppClock>,
-    lru: Mutex<LruState<K>>,
+    map: DashMap<K, CacheEntry<V>>,
+    pub clock: Arc<dyn Clock>,
+    /// Cambia con `resize`, con el LRU tomado exclusivo.
+    capacity: AtomicUsize,
+    /// Orden de desalojo (ver `EvictionPolicy`). Se toma exclusivo para escribir, salvo
+    /// los `put` de una política con `shared_writes`, que lo comparten.
+    lru: RwLock<Eviction<K>>,
+    /// `shared_writes` de la política: los `put` no excluyen a otros `put` y los `get` no
+    /// tocan `lru`.
+    shared_writes: bool,
+    /// Lecturas que todavía no movieron a su clave en `lru`; se aplican antes de cada
+    /// escritura, cuando se llena una franja o en cada tick del reaper.
+    reads: ReadBuffer<K>,
+    /// Se toma después del LRU y del shard de la clave (orden: lru -> shard -> tags).
+    tags: Mutex<TagIndex<K>>,
     wheel: TimingWheel<K>,
+    /// Quién saca las entradas vencidas: las lecturas, el reaper o los dos.
+    expiry: ExpiryStrategy,
+    /// Claves que el reaper dejó para el próximo tick por los topes de `expiry`.
+    expiry_backlog: AtomicU64,
+    /// Ticks seguidos en que `expiry_backlog` creció (ver `BACKLOG_WARN_TICKS`).
+    expiry_backlog_growth: AtomicU32,
+    expiry_tick_keys: AtomicU64,
+    expiry_missed_ticks: AtomicU64,
+    expiry_reaped: AtomicU64,
+    expiry_drift_ms: AtomicU64,
+    expiry_drift_max_ms: AtomicU64,
+    /// Cuánto más se guarda una entrada vencida para las lecturas de `get_stale`; las demás
+    /// ya no la ven. 0 es sacarla al vencer.
+    stale_grace_ms: AtomicU64,
+    /// Uso y cuotas por namespace; se toma después de los tags.
+    namespaces: Option<NamespaceAccounting<K, V>>,
+    evictions: AtomicU64,
+    expirations: AtomicU64,
+    /// Las claves que vencen, para quien se suscribió (ver `subscribe_expired`).
+    expired_keys: OnceLock<broadcast::Sender<K>>,
+    invalidations: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    writes: AtomicU64,
+    compactions: AtomicU64,
+    compacted_bytes: AtomicU64,
+}
+
+/// Resultado de `Cache::store`.
+struct Stored {
+    new: bool,
+    /// El namespace de la clave quedó pasado de su cuota (`QuotaMode::Evict`).
+    over_quota: bool,
 }
 
 impl<K: Eq + Hash + Clone + Send + Sync + 'static, V: Send + Sync + 'static> Cache<K, V> {
     pub fn new_with_capacity(capacity: usize, wheel_size: usize, tick_ms: u64) -> Arc<Self> {
+        Self::new_with_clock(capacity, wheel_size, tick_ms, Arc::new(AppClock::new()))
+    }
+
+    /// Igual que `new_with_capacity` pero con un reloj inyectado (p. ej. `SimulatedClock`).
+    pub fn new_with_clock(
+        capacity: usize,
+        wheel_size: usize,
+        tick_ms: u64,
+        clock: Arc<dyn Clock>,
+    ) -> Arc<Self> {
+        Self::new_with_policy(capacity, wheel_size, tick_ms, clock, EvictionPolicy::Lru)
+    }
+
+    pub fn new_with_policy(
+        capacity: usize,
+        wheel_size: usize,
+        tick_ms: u64,
+        clock: Arc<dyn Clock>,
+        policy: EvictionPolicy,
+    ) -> Arc<Self> {
+        Self::new_with_namespaces(
+            capacity,
+            wheel_size,
+            tick_ms,
+            clock,
+            policy,
+            None,
+            ExpiryStrategy::default(),
+        )
+    }
+
+    /// `new_with_policy` que además cuenta el uso de cada namespace y aplica sus cuotas, y
+    /// saca las vencidas según `expiry`.
+    pub fn new_with_namespaces(
+        capacity: usize,
+        wheel_size: usize,
+        tick_ms: u64,
+        clock: Arc<dyn Clock>,
+        policy: EvictionPolicy,
+        namespaces: Option<NamespaceAccounting<K, V>>,
+        expiry: ExpiryStrategy,
+    ) -> Arc<Self> {
         assert!(capacity > 0, "capacity must be > 0");
 
-        let clock = Arc::new(AppClock::new());
         let now = clock.now_millis().as_millis_u64();
 
-        let this = Arc::new(Self {
+        Arc::new(Self {
             map: DashMap::new(),
             clock,
-            lru: Mutex::new(LruState::new(capacity)),
+            capacity: AtomicUsize::new(capacity),
+            lru: RwLock::new(Eviction::new(policy, capacity)),
+            shared_writes: policy.shared_writes(),
+            reads: ReadBuffer::new(),
+            tags: Mutex::new(TagIndex::new()),
             wheel: TimingWheel::new(wheel_size, tick_ms, now),
-        });
-
-        this
+            expiry,
+            expiry_backlog: AtomicU64::new(0),
+            expiry_backlog_growth: AtomicU32::new(0),
+            expiry_tick_keys: AtomicU64::new(0),
+            expiry_missed_ticks: AtomicU64::new(0),
+            expiry_reaped: AtomicU64::new(0),
+            expiry_drift_ms: AtomicU64::new(0),
+            expiry_drift_max_ms: AtomicU64::new(0),
+            stale_grace_ms: AtomicU64::new(0),
+            namespaces,
+            evictions: AtomicU64::new(0),
+            expirations: AtomicU64::new(0),
+            expired_keys: OnceLock::new(),
+            invalidations: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            writes: AtomicU64::new(0),
+            compactions: AtomicU64::new(0),
+            compacted_bytes: AtomicU64::new(0),
+        })
     }
 
     pub fn new() -> Arc<Self> {
         Self::new_with_capacity(1024, 1024, 1000)
     }
 
+    /// Cuánto se guardan las entradas vencidas para `get_stale`. Vale para lo que se agende
+    /// desde ahora; las ya agendadas salen en el primer tick en que estén pasadas de gracia.
+    pub fn set_stale_grace(&self, grace: Duration) {
+        let grace_ms = u64::try_from(grace.as_millis()).unwrap_or(u64::MAX);
+        self.stale_grace_ms.store(grace_ms, Ordering::Relaxed);
+    }
+
+    /// La de `set_stale_grace`, en ms.
+    pub fn stale_grace(&self) -> u64 {
+        self.stale_grace_ms.load(Ordering::Relaxed)
+    }
+
+    /// Milisegundos desde que venció la entrada, `None` si sigue vigente.
+    fn overdue(entry: &CacheEntry<V>, now: &AppTime) -> Option<u64> {
+        let exp = entry.expires_at.as_ref()?;
+        exp.is_before_or_eq(now)
+            .then(|| now.as_millis_u64().saturating_sub(exp.as_millis_u64()))
+    }
+
     pub fn put(&self, key: K, value: V, expires_at: Option<u64>) -> bool {
+        self.put_tagged(key, value, expires_at, &[])
+    }
+
+    /// `put` que además deja a la clave con `tags` (reemplaza los que tuviera), para
+    /// borrarla junto con las demás del tag con `invalidate_tag`. Devuelve `false` si no
+    /// entró en la cuota de su namespace.
+    pub fn put_tagged(&self, key: K, value: V, expires_at: Option<u64>, tags: &[String]) -> bool {
         let expires_at = expires_at.map(AppTime::new);
+        let now_ms = self.clock.now_millis().as_millis_u64();
+
+        // Altas y bajas del map se hacen con el lock del LRU tomado (orden: lru -> shard):
+        // si no, un put/invalidate concurrente con una evicción deja al map y al LRU
+        // desincronizados (ver `tests/services/cache_loom.rs`).
+        let inserted = if self.shared_writes {
+            let lru = self.lru.read();
+            self.insert_shared(&lru, key, value, expires_at, tags, now_ms)
+        } else {
+            let mut lru = self.write_lru();
+            self.insert_locked(&mut lru, key, value, expires_at, tags, now_ms)
+        };
+
+        match inserted {
+            Ok(evicted) => {
+                if let Some(evict_key) = evicted {
+                    self.wheel.deschedule(&evict_key);
+                }
+                true
+            }
+            Err(_) => false,
+        }
+    }
 
-        if let Some(exp) = &expires_at {
-            self.wheel.schedule(key.clone(), exp.as_millis_u64());
+    /// Varios `put_tagged` seguidos, en orden, tomando el LRU una sola vez para todos; las
+    /// víctimas salen de la rueda después de soltarlo. Devuelve, para cada uno, si entró
+    /// en la cuota de su namespace.
+    pub fn put_batch(&self, puts: Vec<BatchPut<K, V>>) -> Vec<bool> {
+        let now_ms = self.clock.now_millis().as_millis_u64();
+        let mut evicted = Vec::new();
+        let mut apply = |inserted: Result<Option<K>, QuotaExceeded>| match inserted {
+            Ok(victim) => {
+                evicted.extend(victim);
+                true
+            }
+            Err(_) => false,
+        };
+
+        let stored = if self.shared_writes {
+            let lru = self.lru.read();
+            puts.into_iter()
+                .map(|put| {
+                    let expires_at = put.expires_at.map(AppTime::new);
+                    apply(
+                        self.insert_shared(&lru, put.key, put.value, expires_at, &put.tags, now_ms),
+                    )
+                })
+                .collect()
         } else {
-            // Sin expiración -> por si estaba previamente agendado
-            self.wheel.deschedule(&key);
+            let mut lru = self.write_lru();
+            puts.into_iter()
+                .map(|put| {
+                    let expires_at = put.expires_at.map(AppTime::new);
+                    apply(
+                        self.insert_locked(
+                            &mut lru, put.key, put.value, expires_at, &put.tags, now_ms,
+                        ),
+                    )
+                })
+                .collect()
+        };
+
+        for key in &evicted {
+            self.wheel.deschedule(key);
+        }
+        stored
+    }
+
+    /// `put` solo si `condition` acepta la entrada vigente (valor y versión, `None` si no
+    /// existe o venció), evaluada con el LRU tomado para que nada se escriba en el medio.
+    /// Devuelve si escribió, o el error si la condición se cumplía pero la entrada no
+    /// entró en la cuota de su namespace.
+    pub fn put_if<F>(
+        &self,
+        key: K,
+        value: V,
+        expires_at: Option<u64>,
+        tags: &[String],
+        condition: F,
+    ) -> Result<bool, QuotaExceeded>
+    where
+        F: FnOnce(Option<(&V, u64)>) -> bool,
+    {
+        let now = self.clock.now_millis();
+        let mut lru = self.write_lru();
+
+        let current = self.live_locked(&mut lru, &key, &now);
+        if !condition(
+            current
+                .as_ref()
+                .map(|(value, version)| (&**value, *version)),
+        ) {
+            return Ok(false);
+        }
+
+        let evicted = self.insert_locked(
+            &mut lru,
+            key,
+            value,
+            expires_at.map(AppTime::new),
+            tags,
+            now.as_millis_u64(),
+        )?;
+        drop(lru);
+
+        if let Some(evict_key) = evicted {
+            self.wheel.deschedule(&evict_key);
+        }
+        Ok(true)
+    }
+
+    /// Con el LRU tomado: alta o reemplazo en el map (la versión crece), sus tags, su
+    /// vencimiento y su lugar en el LRU. Devuelve la clave desalojada por capacidad, si
+    /// hubo; las que salen por la cuota del namespace ya se desagendaron.
+    fn insert_locked(
+        &self,
+        lru: &mut Eviction<K>,
+        key: K,
+        value: V,
+        expires_at: Option<AppTime>,
+        tags: &[String],
+        now_ms: u64,
+    ) -> Result<Option<K>, QuotaExceeded> {
+        let schedule = expires_at.as_ref().map(AppTime::as_millis_u64);
+        let stored = self.store(lru, key.clone(), value, expires_at, tags, now_ms)?;
+        self.schedule(&key, schedule);
+        lru.touch(key.clone());
+        if stored.over_quota {
+            self.evict_namespace(&key, |victim| self.remove_locked(lru, victim));
+        }
+        Ok(self.evict_over_capacity(lru, Some(&key)))
+    }
+
+    /// `insert_locked` con el LRU compartido: solo para políticas sin orden que mantener.
+    /// Si hace falta desalojar, la víctima se saca con su shard tomado, por si otro `put`
+    /// la está escribiendo.
+    fn insert_shared(
+        &self,
+        lru: &Eviction<K>,
+        key: K,
+        value: V,
+        expires_at: Option<AppTime>,
+        tags: &[String],
+        now_ms: u64,
+    ) -> Result<Option<K>, QuotaExceeded> {
+        let schedule = expires_at.as_ref().map(AppTime::as_millis_u64);
+        let stored = self.store(lru, key.clone(), value, expires_at, tags, now_ms)?;
+        self.schedule(&key, schedule);
+        if stored.over_quota {
+            self.evict_namespace(&key, |victim| self.remove_shared(lru, victim));
+        }
+        if !stored.new {
+            return Ok(None);
+        }
+        // si otro `put` desalojó la misma víctima se elige otra, mientras siga sobrando
+        loop {
+            let Some(victim) = lru.sampled_victim(|k| self.last_access_unless(k, Some(&key)))
+            else {
+                return Ok(None);
+            };
+            if self.remove_shared(lru, &victim) {
+                self.count_eviction(&victim);
+                return Ok(Some(victim));
+            }
         }
+    }
 
-        match self.map.entry(key.clone()) {
+    /// Baja de `key` con su shard tomado y el LRU compartido; devuelve si estaba.
+    fn remove_shared(&self, lru: &Eviction<K>, key: &K) -> bool {
+        let Entry::Occupied(occ) = self.map.entry(key.clone()) else {
+            return false;
+        };
+        self.tags.lock().unlink(occ.key());
+        lru.untrack(occ.key());
+        let (key, entry) = occ.remove_entry();
+        self.release(&key, &entry);
+        true
+    }
+
+    /// Desaloja claves del namespace de `keep`, de acceso más viejo, hasta que vuelva a
+    /// entrar en su cuota; `remove` saca a cada una del map y del orden de desalojo.
+    fn evict_namespace<F>(&self, keep: &K, mut remove: F)
+    where
+        F: FnMut(&K) -> bool,
+    {
+        let Some(namespaces) = &self.namespaces else {
+            return;
+        };
+        while let Some(victim) = namespaces.victim(keep, |k| self.last_access_unless(k, Some(keep)))
+        {
+            if remove(&victim) {
+                self.wheel.deschedule(&victim);
+                self.count_eviction(&victim);
+            }
+        }
+    }
+
+    fn schedule(&self, key: &K, expires_at: Option<u64>) {
+        // sin reaper la rueda no se recorre
+        if !self.expiry.active() {
+            return;
+        }
+        match expires_at {
+            Some(exp) => self
+                .wheel
+                .schedule(key.clone(), exp.saturating_add(self.stale_grace())),
+            // por si estaba agendada de antes
+            None => self.wheel.deschedule(key),
+        }
+    }
+
+    fn count_eviction(&self, key: &K) {
+        self.evictions.fetch_add(1, Ordering::Relaxed);
+        if let Some(namespaces) = &self.namespaces {
+            namespaces.count_eviction(key);
+        }
+    }
+
+    /// Descuenta del namespace una entrada que ya salió del map.
+    fn release(&self, key: &K, entry: &CacheEntry<V>) {
+        if let Some(namespaces) = &self.namespaces {
+            namespaces.release(key, &entry.value);
+        }
+    }
+
+    /// Alta o reemplazo en el map (la versión crece) y sus tags, con el shard de la clave
+    /// tomado. Si no entra en la cuota de su namespace no toca nada.
+    fn store(
+        &self,
+        lru: &Eviction<K>,
+        key: K,
+        value: V,
+        expires_at: Option<AppTime>,
+        tags: &[String],
+        now_ms: u64,
+    ) -> Result<Stored, QuotaExceeded> {
+        let stored = self.store_entry(lru, key, value, expires_at, tags, now_ms)?;
+        self.writes.fetch_add(1, Ordering::Relaxed);
+        Ok(stored)
+    }
+
+    fn store_entry(
+        &self,
+        lru: &Eviction<K>,
+        key: K,
+        value: V,
+        expires_at: Option<AppTime>,
+        tags: &[String],
+        now_ms: u64,
+    ) -> Result<Stored, QuotaExceeded> {
+        match self.map.entry(key) {
             Entry::Occupied(mut occ) => {
+                let over_quota = self.admit(occ.key(), &value, Some(&occ.get().value))?;
+                self.tags.lock().set(occ.key(), tags);
                 let next = occ.get().version.saturating_add(1);
-                *occ.get_mut() = CacheEntry::new(value, next, expires_at);
+                *occ.get_mut() = CacheEntry::new(value, next, expires_at, now_ms);
+                Ok(Stored {
+                    new: false,
+                    over_quota,
+                })
             }
             Entry::Vacant(vac) => {
-                vac.insert(CacheEntry::new(value, 1, expires_at));
+                let over_quota = self.admit(vac.key(), &value, None)?;
+                self.tags.lock().set(vac.key(), tags);
+                lru.track(vac.key());
+                vac.insert(CacheEntry::new(value, 1, expires_at, now_ms));
+                Ok(Stored {
+                    new: true,
+                    over_quota,
+                })
             }
         }
+    }
 
-        let to_evict = {
-            let mut lru = self.lru.lock();
-            lru.touch(key.clone());
-            if lru.over_capacity() {
-                lru.pop_back()
-            } else {
-                None
-            }
-        };
+    fn admit(&self, key: &K, value: &V, old: Option<&V>) -> Result<bool, QuotaExceeded> {
+        self.namespaces
+            .as_ref()
+            .map_or(Ok(false), |namespaces| namespaces.admit(key, value, old))
+    }
 
-        if let Some(evict_key) = to_evict
-            && evict_key != key
-        {
-            self.wheel.deschedule(&evict_key);
-            let _ = self.map.remove(&evict_key);
+    /// `last_access` de `key` para elegir víctima; `None` si es `keep` o ya no está.
+    fn last_access_unless(&self, key: &K, keep: Option<&K>) -> Option<u64> {
+        if keep == Some(key) {
+            return None;
         }
+        self.map
+            .get(key)
+            .map(|e| e.last_access.load(Ordering::Relaxed))
+    }
 
-        true
+    fn remove_locked(&self, lru: &mut Eviction<K>, key: &K) -> bool {
+        self.tags.lock().unlink(key);
+        let removed_map = self
+            .map
+            .remove(key)
+            .map(|(key, entry)| self.release(&key, &entry))
+            .is_some();
+        let removed_lru = lru.remove(key);
+        removed_map || removed_lru
     }
 
-    pub fn get(&self, key: &K) -> Option<Arc<V>> {
+    /// Aplica `steps` en orden como una unidad, con el LRU tomado de punta a punta: ningún
+    /// otro `put`/`invalidate` (ni otra transacción) se intercala. Antes compara cada
+    /// `watch` (clave y versión esperada, 0 = no existe) con la versión vigente; si alguno
+    /// no coincide no se aplica nada. Un `get` de afuera puede ver parte de las escrituras
+    /// antes de que termine.
+    pub fn transact(
+        &self,
+        watches: &[(K, u64)],
+        steps: Vec<TxStep<K, V>>,
+    ) -> Result<Vec<TxOutcome<V>>, TxConflict<K>> {
         let now = self.clock.now_millis();
+        let now_ms = now.as_millis_u64();
+        let mut lru = self.write_lru();
 
-        if let Some(entry) = self.map.get(key) {
-            if entry
-                .expires_at
-                .as_ref()
-                .is_some_and(|exp| exp.is_before_or_eq(&now))
-            {
-                drop(entry);
-                let _ = self.invalidate(key);
-                return None;
+        for (key, expected) in watches {
+            let version = self.live_locked(&mut lru, key, &now).map_or(0, |e| e.1);
+            if version != *expected {
+                return Err(TxConflict {
+                    key: key.clone(),
+                    version,
+                });
             }
+        }
 
-            let to_evict = {
-                let mut lru = self.lru.lock();
-                if lru.contains(key) {
-                    lru.touch(key.clone());
-                } else {
-                    lru.push_front(key.clone());
+        let mut outcomes = Vec::with_capacity(steps.len());
+        for step in steps {
+            let outcome = match step {
+                TxStep::Get(key) => {
+                    let live = self.live_locked(&mut lru, &key, &now);
+                    if live.is_some() {
+                        if let Some(entry) = self.map.get(&key) {
+                            entry.last_access.store(now_ms, Ordering::Relaxed);
+                        }
+                        lru.touch(key);
+                    }
+                    TxOutcome::Value(live.map(|e| e.0))
+                }
+                TxStep::Version(key) => {
+                    TxOutcome::Version(self.live_locked(&mut lru, &key, &now).map_or(0, |e| e.1))
+                }
+                TxStep::Put {
+                    key,
+                    value,
+                    expires_at,
+                } => {
+                    let expires_at = expires_at.map(AppTime::new);
+                    match self.insert_locked(&mut lru, key, value, expires_at, &[], now_ms) {
+                        Ok(evicted) => {
+                            if let Some(evicted) = evicted {
+                                self.wheel.deschedule(&evicted);
+                            }
+                            TxOutcome::Stored
+                        }
+                        Err(_) => TxOutcome::OverQuota,
+                    }
                 }
-                if lru.over_capacity() {
-                    lru.pop_back()
-                } else {
-                    None
+                TxStep::Del(key) => {
+                    self.wheel.deschedule(&key);
+                    let removed = self.remove_locked(&mut lru, &key);
+                    if removed {
+                        self.invalidations.fetch_add(1, Ordering::Relaxed);
+                    }
+                    TxOutcome::Removed(removed)
                 }
             };
+            outcomes.push(outcome);
+        }
 
-            if let Some(evict_key) = to_evict
-                && &evict_key != key
-            {
-                self.wheel.deschedule(&evict_key);
-                let _ = self.map.remove(&evict_key);
-            }
+        Ok(outcomes)
+    }
+
+    /// Mueve la entrada vigente de `from` a `to`, con su valor, su vencimiento y sus tags,
+    /// con el LRU tomado: ninguna escritura de las dos claves se intercala. `to` queda como
+    /// una entrada nueva (versión 1) y, si ya existe, no se pisa. Igual que en
+    /// `transact`, un `get` de afuera puede ver las dos claves por un instante.
+    pub fn rename(&self, from: &K, to: K) -> Result<RenameOutcome, QuotaExceeded>
+    where
+        V: Clone,
+    {
+        let now = self.clock.now_millis();
+        let mut lru = self.write_lru();
+
+        let Some((value, _)) = self.live_locked(&mut lru, from, &now) else {
+            return Ok(RenameOutcome::Missing);
+        };
+        if *from == to || self.live_locked(&mut lru, &to, &now).is_some() {
+            return Ok(RenameOutcome::TargetExists);
+        }
+        let expires_at = self
+            .map
+            .get(from)
+            .and_then(|entry| entry.expires_at.clone());
+        let tags = self.tags(from);
 
-            return Some(entry.value.clone());
+        let evicted = self.insert_locked(
+            &mut lru,
+            to,
+            V::clone(&value),
+            expires_at,
+            &tags,
+            now.as_millis_u64(),
+        )?;
+        self.wheel.deschedule(from);
+        self.remove_locked(&mut lru, from);
+        drop(lru);
+
+        if let Some(evicted) = evicted {
+            self.wheel.deschedule(&evicted);
+        }
+        Ok(RenameOutcome::Moved)
+    }
+
+    /// Con el LRU tomado: valor y versión de la entrada si no venció; si venció devuelve
+    /// `None` y, pasada la gracia de `get_stale`, la saca (cuenta como expiración).
+    /// Con `ExpiryStrategy::Active` no mira el vencimiento.
+    fn live_locked(&self, lru: &mut Eviction<K>, key: &K, now: &AppTime) -> Option<(Arc<V>, u64)> {
+        let (value, version, overdue) = {
+            let entry = self.map.get(key)?;
+            let overdue = self
+                .expiry
+                .lazy()
+                .then(|| Self::overdue(&entry, now))
+                .flatten();
+            (entry.value.clone(), entry.version, overdue)
+        };
+
+        let Some(overdue) = overdue else {
+            return Some((value, version));
+        };
+        if overdue >= self.stale_grace() {
+            self.wheel.deschedule(key);
+            if self.remove_locked(lru, key) {
+                self.note_expired(key);
+            }
         }
         None
     }
 
+    pub fn get(&self, key: &K) -> Option<Arc<V>> {
+        self.read(key, 0, |_, _| false).map(|(value, _, _)| value)
+    }
+
+    /// `get` con la versión de la entrada (la de `WATCH`), para un `GET` condicional.
+    pub fn get_versioned(&self, key: &K) -> Option<(Arc<V>, u64)> {
+        self.read(key, 0, |_, _| false)
+            .map(|(value, version, _)| (value, version))
+    }
+
+    /// `get` que también devuelve una entrada vencida hace menos de `max_stale_ms` (y de
+    /// la gracia de `set_stale_grace`), con los milisegundos desde que venció (`None` si
+    /// está vigente). Al primero que la lee vencida después de cada escritura le toca
+    /// refrescarla (`true`).
+    pub fn get_stale(&self, key: &K, max_stale_ms: u64) -> Option<(Arc<V>, bool, Option<u64>)> {
+        let mut stale_for = None;
+        self.read(key, max_stale_ms, |entry, now| {
+            stale_for = Self::overdue(entry, now);
+            stale_for.is_some() && !entry.refresh_claimed.swap(true, Ordering::Relaxed)
+        })
+        .map(|(value, _, claimed)| (value, claimed, stale_for))
+    }
+
+    /// `get` que además dice si quien lee quedó elegido para refrescar la entrada antes de
+    /// que venza: solo si vence dentro de `window_ms`, con la chance de
+    /// `app_net::refresh::should_refresh` (`draw` al azar en `[0, 1)`) y a uno solo por
+    /// escritura.
+    pub fn get_for_refresh(&self, key: &K, window_ms: u64, draw: f64) -> Option<(Arc<V>, bool)> {
+        self.read(key, 0, |entry, now| {
+            entry.expires_at.as_ref().is_some_and(|exp| {
+                let remaining = exp.as_millis_u64().saturating_sub(now.as_millis_u64());
+                should_refresh(remaining, window_ms, draw)
+            }) && !entry.refresh_claimed.swap(true, Ordering::Relaxed)
+        })
+        .map(|(value, _, claimed)| (value, claimed))
+    }
+
+    /// Lectura de una entrada vigente, o vencida hace menos de `max_stale_ms` (valor,
+    /// versión y lo que dio `claim`); `claim` se evalúa con el shard tomado. Cuenta como
+    /// acierto o fallo.
+    fn read<F>(&self, key: &K, max_stale_ms: u64, claim: F) -> Option<(Arc<V>, u64, bool)>
+    where
+        F: FnOnce(&CacheEntry<V>, &AppTime) -> bool,
+    {
+        let read = self.read_entry(key, max_stale_ms, claim);
+        let counter = if read.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        read
+    }
+
+    fn read_entry<F>(&self, key: &K, max_stale_ms: u64, claim: F) -> Option<(Arc<V>, u64, bool)>
+    where
+        F: FnOnce(&CacheEntry<V>, &AppTime) -> bool,
+    {
+        let now = self.clock.now_millis();
+        let grace = self.stale_grace();
+
+        // se suelta el shard antes de tomar el LRU para respetar el orden lru -> shard
+        let (value, version, claimed, overdue) = {
+            let entry = self.map.get(key)?;
+            // con `Active` las lecturas no miran el vencimiento, salvo para la gracia
+            let overdue = (self.expiry.lazy() || grace > 0)
+                .then(|| Self::overdue(&entry, &now))
+                .flatten()
+                .filter(|overdue| *overdue >= max_stale_ms.min(grace));
+            let claimed = overdue.is_none() && claim(&entry, &now);
+            if overdue.is_none() {
+                entry
+                    .last_access
+                    .store(now.as_millis_u64(), Ordering::Relaxed);
+            }
+            (entry.value.clone(), entry.version, claimed, overdue)
+        };
+
+        if let Some(overdue) = overdue {
+            if self.expiry.lazy() && overdue >= grace {
+                self.expire(key);
+            }
+            return None;
+        }
+
+        if self.shared_writes {
+            // no hay orden que actualizar: alcanza con `last_access`
+            return Some((value, version, claimed));
+        }
+        // el acceso llega al LRU más tarde; si otro hilo lo tiene, lo aplica el próximo
+        if self.reads.record(key.clone())
+            && let Some(mut lru) = self.lru.try_write()
+        {
+            self.apply_reads(&mut lru);
+        }
+
+        Some((value, version, claimed))
+    }
+
+    /// Toma el LRU para escribir, con las lecturas anotadas ya aplicadas.
+    fn write_lru(&self) -> RwLockWriteGuard<'_, Eviction<K>> {
+        let mut lru = self.lru.write();
+        self.apply_reads(&mut lru);
+        lru
+    }
+
+    fn apply_reads(&self, lru: &mut Eviction<K>) {
+        self.reads.drain(|key| {
+            // borrada después de leerla: no se resucita en el LRU
+            if self.map.contains_key(&key) {
+                lru.touch(key);
+            }
+        });
+    }
+
+    /// Con el LRU tomado: si se pasó de capacidad, saca la clave que elige la política
+    /// también del map y la devuelve (nunca `keep`).
+    fn evict_over_capacity(&self, lru: &mut Eviction<K>, keep: Option<&K>) -> Option<K> {
+        let evict_key = lru.evict(|k| self.last_access_unless(k, keep))?;
+        if keep == Some(&evict_key) {
+            return None;
+        }
+        self.tags.lock().unlink(&evict_key);
+        if let Some((key, entry)) = self.map.remove(&evict_key) {
+            self.release(&key, &entry);
+        }
+        self.count_eviction(&evict_key);
+        Some(evict_key)
+    }
+
     pub fn invalidate(&self, key: &K) -> bool {
+        let removed = self.remove(key);
+        if removed {
+            self.invalidations.fetch_add(1, Ordering::Relaxed);
+        }
+        removed
+    }
+
+    /// Borra todas las claves con `tag` y las devuelve.
+    pub fn invalidate_tag(&self, tag: &str) -> Vec<K> {
+        let mut lru = self.write_lru();
+        let keys = self.tags.lock().keys(tag);
+        let removed: Vec<K> = keys
+            .into_iter()
+            .filter(|key| {
+                self.wheel.deschedule(key);
+                self.remove_locked(&mut lru, key)
+            })
+            .collect();
+        self.invalidations
+            .fetch_add(removed.len() as u64, Ordering::Relaxed);
+        removed
+    }
+
+    /// Borra las claves que cumplen `matches` y las devuelve, vencidas o no. Las claves se
+    /// copian sin frenar a nadie y se borran de a `SCAN_BATCH`, soltando el LRU entre lote y
+    /// lote: una escritura que llega en medio no espera a todo el recorrido (y si crea una
+    /// clave que cumple, puede quedar).
+    pub fn invalidate_where(&self, matches: impl Fn(&K) -> bool) -> Vec<K> {
+        let keys: Vec<K> = self
+            .copy_keys()
+            .into_iter()
+            .filter(|k| matches(k))
+            .collect();
+        let mut removed = Vec::with_capacity(keys.len());
+        for batch in keys.chunks(SCAN_BATCH) {
+            let mut lru = self.write_lru();
+            for key in batch {
+                self.wheel.deschedule(key);
+                if self.remove_locked(&mut lru, key) {
+                    removed.push(key.clone());
+                }
+            }
+        }
+        self.invalidations
+            .fetch_add(removed.len() as u64, Ordering::Relaxed);
+        removed
+    }
+
+    /// Cuántas claves borraría ahora `invalidate_where`.
+    pub fn count_where(&self, matches: impl Fn(&K) -> bool) -> usize {
+        self.map.iter().filter(|e| matches(e.key())).count()
+    }
+
+    fn remove(&self, key: &K) -> bool {
         self.wheel.deschedule(key);
-        let removed_map = self.map.remove(key).is_some();
-        let mut lru = self.lru.lock();
-        let removed_lru = lru.remove(key);
-        removed_map || removed_lru
+        let mut lru = self.write_lru();
+        self.remove_locked(&mut lru, key)
+    }
+
+    /// Tags con los que se escribió la clave por última vez.
+    pub fn tags(&self, key: &K) -> Vec<String> {
+        self.tags.lock().tags(key).to_vec()
     }
 
+    fn expire(&self, key: &K) {
+        if self.remove(key) {
+            self.note_expired(key);
+        }
+    }
+
+    fn note_expired(&self, key: &K) {
+        self.expirations.fetch_add(1, Ordering::Relaxed);
+        if let Some(expired_keys) = self.expired_keys.get()
+            && expired_keys.receiver_count() > 0
+        {
+            let _ = expired_keys.send(key.clone());
+        }
+    }
+
+    /// Las claves que vencen desde ahora, las saque una lectura o el reaper. El canal es
+    /// de `capacity` claves (la del primero que se suscribe): quien se atrasa pierde las
+    /// más viejas.
+    pub fn subscribe_expired(&self, capacity: usize) -> broadcast::Receiver<K> {
+        self.expired_keys
+            .get_or_init(|| broadcast::channel(capacity.max(1)).0)
+            .subscribe()
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.map.len(),
+            capacity: self.capacity(),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            expirations: self.expirations.load(Ordering::Relaxed),
+            invalidations: self.invalidations.load(Ordering::Relaxed),
+            bytes: self.memory_used(),
+            max_bytes: 0,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            writes: self.writes.load(Ordering::Relaxed),
+            expiry_backlog: self.expiry_backlog.load(Ordering::Relaxed),
+            expiry_tick_keys: self.expiry_tick_keys.load(Ordering::Relaxed),
+            expiry_missed_ticks: self.expiry_missed_ticks.load(Ordering::Relaxed),
+            expiry_reaped: self.expiry_reaped.load(Ordering::Relaxed),
+            expiry_drift_ms: self.expiry_drift_ms.load(Ordering::Relaxed),
+            expiry_drift_max_ms: self.expiry_drift_max_ms.load(Ordering::Relaxed),
+            compactions: self.compactions.load(Ordering::Relaxed),
+            compacted_bytes: self.compacted_bytes.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Uso de cada namespace (ver `NamespaceAccounting::stats`); vacío si no se cuentan.
+    pub fn namespace_stats(&self) -> Vec<(String, NamespaceStats)> {
+        self.namespaces
+            .as_ref()
+            .map(NamespaceAccounting::stats)
+            .unwrap_or_default()
+    }
+
+    /// Entradas presentes, vencidas incluidas hasta que salgan.
     pub fn len(&self) -> usize {
         self.map.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Entradas a partir de las cuales se desaloja.
+    pub fn capacity(&self) -> usize {
+        self.capacity.load(Ordering::Relaxed)
+    }
+
+    /// Cambia la capacidad en caliente. Si baja, desaloja con la política (y contando
+    /// `evictions`) hasta entrar, con el LRU tomado todo ese tiempo; devuelve cuántas
+    /// entradas sacó.
+    pub fn resize(&self, capacity: usize) -> usize {
+        assert!(capacity > 0, "capacity must be > 0");
+
+        let mut lru = self.write_lru();
+        lru.resize(capacity);
+        self.capacity.store(capacity, Ordering::Relaxed);
+        let mut evicted = Vec::new();
+        // `Sampled` elige entre claves al azar y puede no encontrar ninguna aunque sobren
+        let mut misses = 0;
+        while misses < RESIZE_MISSES {
+            match self.evict_over_capacity(&mut lru, None) {
+                Some(key) => {
+                    evicted.push(key);
+                    misses = 0;
+                }
+                None if self.map.len() > capacity => misses += 1,
+                None => break,
+            }
+        }
+        drop(lru);
+
+        for key in &evicted {
+            self.wheel.deschedule(key);
+        }
+        evicted.len()
+    }
+
+    /// Bytes de las entradas según el `weight_of` de `NamespaceAccounting`; 0 si el cache
+    /// no las pesa.
+    pub fn memory_used(&self) -> u64 {
+        self.namespaces
+            .as_ref()
+            .map_or(0, NamespaceAccounting::bytes)
+    }
+
+    /// Las claves presentes en este momento, vencidas incluidas. Se copian antes de
+    /// devolverlas: el cache se puede escribir mientras se recorren.
+    pub fn iter_keys(&self) -> impl Iterator<Item = K> + use<K, V> {
+        self.copy_keys().into_iter()
+    }
+
+    /// Las entradas vigentes, sin frenar las escrituras mientras se recorren (ver
+    /// `SnapshotIter`). Para persistir o transferir el cache entero.
+    pub fn snapshot_iter(&self) -> SnapshotIter<'_, K, V> {
+        SnapshotIter {
+            cache: self,
+            keys: self.copy_keys().into_iter(),
+        }
+    }
+
+    /// `DashMap::iter` toma los shards de a uno y los suelta al pasar al siguiente.
+    fn copy_keys(&self) -> Vec<K> {
+        self.map.iter().map(|e| e.key().clone()).collect()
+    }
+
     // Limpieza de expirados
 
     pub fn start_reaper(self: &Arc<Self>) {
-        let this = Arc::clone(self);
-        let tick_ms = this.wheel.tick_ms;
-        tokio::spawn(async move {
-            let mut interval = time::interval(time::Duration::from_millis(tick_ms));
-            loop {
-                interval.tick().await;
-                this.advance_wheel_to_now();
+        tokio::spawn(Arc::clone(self).reaper(CancellationToken::new()));
+    }
+
+    /// Loop del reaper; termina al cancelarse `cancel`.
+    pub async fn reaper(self: Arc<Self>, cancel: CancellationToken) {
+        let mut interval = time::interval(time::Duration::from_millis(self.wheel.tick_ms));
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = interval.tick() => {
+                    if self.expiry.active() {
+                        self.advance_wheel_to_now();
+                    }
+                    // sin escrituras las lecturas también llegan al LRU
+                    drop(self.write_lru());
+                }
             }
-        });
+        }
+    }
+
+    /// Loop de la compactación: cada `every` llama a `compact`; termina al cancelarse
+    /// `cancel`.
+    pub async fn compactor(self: Arc<Self>, every: Duration, cancel: CancellationToken) {
+        let mut interval = time::interval(every);
+        // el primer tick es inmediato y recién arrancado no hay nada que soltar
+        interval.tick().await;
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = interval.tick() => {
+                    let released = self.compact();
+                    if released > 0 {
+                        debug!(released, "tablas del cache compactadas");
+                    }
+                }
+            }
+        }
+    }
+
+    /// Rearma a la medida las tablas que quedaron grandes para lo que tienen (el map, el
+    /// orden de desalojo, los tags, la rueda y las claves por namespace; ver
+    /// `compaction`) y devuelve los bytes que soltó. Cada shard del map se toma solo
+    /// mientras se rearma; el resto, con el LRU tomado, frena las escrituras un momento.
+    pub fn compact(&self) -> u64 {
+        let mut released = shrink_dashmap(&self.map) + self.wheel.compact();
+        {
+            let mut lru = self.write_lru();
+            released += lru.compact();
+            released += self.tags.lock().compact();
+        }
+        if let Some(namespaces) = &self.namespaces {
+            released += namespaces.compact();
+        }
+
+        if released > 0 {
+            self.compactions.fetch_add(1, Ordering::Relaxed);
+            self.compacted_bytes.fetch_add(released, Ordering::Relaxed);
+        }
+        released
     }
 
+    /// Un tick del reaper, con los topes de `ExpiryStrategy::Hybrid`. Anota cuánto tarde
+    /// salió cada entrada y avisa si el reaper se atrasa (ver `BACKLOG_WARN_TICKS`).
     pub fn advance_wheel_to_now(&self) {
         let now = self.clock.now_millis().as_millis_u64();
-        self.wheel.advance_to(now, self, |cache, key, now_ms| {
-            if let Some(e) = cache.map.get(key) {
-                if e.expires_at
-                    .as_ref()
-                    .is_some_and(|exp| exp.is_before_or_eq(&AppTime::new(now_ms)))
-                {
-                    drop(e);
-                    let _ = cache.invalidate(key);
-                } else if let Some(exp) = &e.expires_at {
-                    cache.wheel.schedule(key.clone(), exp.as_millis_u64());
+        let (max_keys, budget) = self.expiry.tick_limits();
+        let (reaped, drift, drift_max) = (Cell::new(0), Cell::new(0), Cell::new(0));
+        let report = self
+            .wheel
+            .advance_to(now, self, max_keys, budget, |cache, key, now_ms| {
+                if let Some(e) = cache.map.get(key) {
+                    // la entrada queda la gracia de `get_stale` después de vencer
+                    let grace = cache.stale_grace();
+                    let due = e
+                        .expires_at
+                        .as_ref()
+                        .map(|exp| exp.as_millis_u64().saturating_add(grace));
+                    match due {
+                        Some(due) if due <= now_ms => {
+                            drop(e);
+                            if cache.remove(key) {
+                                cache.note_expired(key);
+                                let late = now_ms - due;
+                                reaped.set(reaped.get() + 1);
+                                drift.set(drift.get() + late);
+                                drift_max.set(drift_max.get().max(late));
+                            }
+                        }
+                        Some(due) => cache.wheel.schedule(key.clone(), due),
+                        None => {}
+                    }
                 }
-            }
-        });
+            });
+
+        self.expiry_reaped
+            .fetch_add(reaped.get(), Ordering::Relaxed);
+        self.expiry_drift_ms
+            .fetch_add(drift.get(), Ordering::Relaxed);
+        self.expiry_drift_max_ms
+            .store(drift_max.get(), Ordering::Relaxed);
+        self.expiry_tick_keys
+            .store(report.checked as u64, Ordering::Relaxed);
+        self.expiry_missed_ticks
+            .fetch_add(report.missed_ticks, Ordering::Relaxed);
+        if report.missed_ticks >= MISSED_TICKS_WARN {
+            warn!(
+                missed_ticks = report.missed_ticks,
+                tick_ms = self.wheel.tick_ms,
+                "el reaper corrió tarde: las entradas de esos ticks salen juntas"
+            );
+        }
+
+        let pending = report.pending as u64;
+        let previous = self.expiry_backlog.swap(pending, Ordering::Relaxed);
+        if pending == 0 || pending <= previous {
+            self.expiry_backlog_growth.store(0, Ordering::Relaxed);
+        } else if self.expiry_backlog_growth.fetch_add(1, Ordering::Relaxed) + 1
+            >= BACKLOG_WARN_TICKS
+        {
+            self.expiry_backlog_growth.store(0, Ordering::Relaxed);
+            warn!(
+                pending,
+                checked = report.checked,
+                "el reaper no da abasto: las vencidas pendientes crecieron \
+                 {BACKLOG_WARN_TICKS} ticks seguidos (ver EXPIRY_MAX_KEYS_PER_TICK y \
+                 EXPIRY_BUDGET_MS)"
+            );
+        }
+    }
+
+    /// Valor vigente sin contar como acceso: no toca el LRU ni `last_access`, y una entrada
+    /// vencida no se borra (queda para el reaper). Con `ExpiryStrategy::Active` la vencida
+    /// se devuelve igual, como en `get`.
+    pub fn peek(&self, key: &K) -> Option<Arc<V>> {
+        let now = self.clock.now_millis();
+        let entry = self.map.get(key)?;
+        if self.expiry.lazy()
+            && entry
+                .expires_at
+                .as_ref()
+                .is_some_and(|exp| exp.is_before_or_eq(&now))
+        {
+            return None;
+        }
+        Some(entry.value.clone())
+    }
+
+    /// Estado de la entrada sin tocar el LRU ni `last_access`, aunque ya haya expirado
+    /// (el reaper todavía no la sacó).
+    pub fn meta(&self, key: &K) -> Option<EntryMeta<V>> {
+        let entry = self.map.get(key)?;
+        Some(EntryMeta {
+            value: entry.value.clone(),
+            version: entry.version,
+            expires_at: entry.expires_at.clone(),
+            last_access: AppTime::new(entry.last_access.load(Ordering::Relaxed)),
+        })
     }
 
     pub fn contains_key(&self, key: &K) -> bool {
@@ -0,0 +1,63 @@
+//! Ni `HashMap` ni `DashMap` achican su capacidad al sacar claves: después de un pico, un
+//! nodo que corre mucho tiempo sigue reservando la memoria de ese pico. `Cache::compact`
+//! rearma a la medida las tablas que quedaron subutilizadas con estas funciones.
+
+use std::{collections::HashMap, hash::Hash, mem::size_of};
+
+use dashmap::{DashMap, DashSet};
+
+/// Por debajo de esta capacidad no vale la pena rearmar la tabla.
+const MIN_CAPACITY: usize = 64;
+/// Una tabla se rearma cuando usa menos de `1 / LOW_LOAD` de su capacidad.
+const LOW_LOAD: usize = 4;
+
+fn underused(len: usize, capacity: usize) -> bool {
+    capacity > MIN_CAPACITY && len.saturating_mul(LOW_LOAD) < capacity
+}
+
+/// Bytes que suelta una tabla de `T` al pasar de `before` a `after` lugares: el de cada
+/// `T` más su byte de control. Es aproximado; lo que cada `T` tenga en el heap se soltó al
+/// sacarlo.
+fn released<T>(before: usize, after: usize) -> u64 {
+    (before.saturating_sub(after) * (size_of::<T>() + 1)) as u64
+}
+
+/// Rearma `map` si está subutilizado; devuelve los bytes que soltó.
+pub(crate) fn shrink_map<K: Eq + Hash, V>(map: &mut HashMap<K, V>) -> u64 {
+    let before = map.capacity();
+    if !underused(map.len(), before) {
+        return 0;
+    }
+    map.shrink_to_fit();
+    released::<(K, V)>(before, map.capacity())
+}
+
+/// `shrink_map` para un `DashMap`. Se mira el total, pero cada shard se rearma con su
+/// propio lock y solo si tiene lugar de más: los que están a la medida no se tocan.
+pub(crate) fn shrink_dashmap<K: Eq + Hash + Clone, V>(map: &DashMap<K, V>) -> u64 {
+    let before = map.capacity();
+    if !underused(map.len(), before) {
+        return 0;
+    }
+    map.shrink_to_fit();
+    released::<(K, V)>(before, map.capacity())
+}
+
+pub(crate) fn shrink_dashset<K: Eq + Hash + Clone>(set: &DashSet<K>) -> u64 {
+    let before = set.capacity();
+    if !underused(set.len(), before) {
+        return 0;
+    }
+    set.shrink_to_fit();
+    released::<K>(before, set.capacity())
+}
+
+pub(crate) fn shrink_vec<T>(vec: &mut Vec<T>) -> u64 {
+    let before = vec.capacity();
+    if !underused(vec.len(), before) {
+        return 0;
+    }
+    vec.shrink_to_fit();
+    // sin bytes de control
+    (before.saturating_sub(vec.capacity()) * size_of::<T>()) as u64
+}
@@ -0,0 +1,68 @@
+use std::{str::FromStr, time::Duration};
+
+/// Cómo salen las entradas vencidas (`EXPIRY_STRATEGY`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpiryStrategy {
+    /// Solo al tocarlas: un `get` (o una escritura condicional) de una entrada vencida la
+    /// saca. No hay rueda ni reaper que las busque, así que las que nadie vuelve a leer
+    /// ocupan lugar hasta que las desaloje la política.
+    Lazy,
+    /// Solo el reaper: las lecturas no miran el vencimiento y devuelven la entrada hasta
+    /// que el reaper la saca, como mucho un tick después de vencer.
+    Active,
+    /// Las dos: el reaper saca las vencidas de cada tick y las lecturas no ven ninguna.
+    /// En cada tick revisa a lo sumo `max_keys_per_tick` claves y corta pasado `budget`;
+    /// lo que queda sigue en el tick siguiente (mientras, lo cubren las lecturas).
+    Hybrid {
+        max_keys_per_tick: Option<usize>,
+        budget: Option<Duration>,
+    },
+}
+
+impl ExpiryStrategy {
+    /// Si el reaper recorre la rueda (y hace falta agendar los vencimientos).
+    pub fn active(&self) -> bool {
+        !matches!(self, Self::Lazy)
+    }
+
+    /// Si las lecturas descartan las entradas vencidas.
+    pub fn lazy(&self) -> bool {
+        !matches!(self, Self::Active)
+    }
+
+    /// Tope de claves y de tiempo de cada tick del reaper.
+    pub(crate) fn tick_limits(&self) -> (usize, Option<Duration>) {
+        match self {
+            Self::Hybrid {
+                max_keys_per_tick,
+                budget,
+            } => (max_keys_per_tick.unwrap_or(usize::MAX), *budget),
+            _ => (usize::MAX, None),
+        }
+    }
+}
+
+impl Default for ExpiryStrategy {
+    /// Híbrida y sin topes: todo lo vencido de cada tick sale en ese tick.
+    fn default() -> Self {
+        Self::Hybrid {
+            max_keys_per_tick: None,
+            budget: None,
+        }
+    }
+}
+
+impl FromStr for ExpiryStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "lazy" => Ok(Self::Lazy),
+            "active" => Ok(Self::Active),
+            "hybrid" => Ok(Self::default()),
+            other => Err(format!(
+                "estrategia de vencimiento inválida: '{other}' (lazy | active | hybrid)"
+            )),
+        }
+    }
+}
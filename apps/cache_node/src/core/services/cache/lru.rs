@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 use std::hash::Hash;
 
+use crate::core::services::cache::compaction::shrink_map;
+
 pub struct LruState<K> {
     capacity: usize,
     head: Option<K>,                           // MRU
@@ -63,10 +65,10 @@ impl<K: Eq + Hash + Clone> LruState<K> {
         self.links.insert(key.clone(), (None, old_head.clone()));
 
         // Arregla el prev del viejo head (si existía)
-        if let Some(h) = old_head {
-            if let Some(e) = self.links.get_mut(&h) {
-                e.0 = Some(key.clone());
-            }
+        if let Some(h) = old_head
+            && let Some(e) = self.links.get_mut(&h)
+        {
+            e.0 = Some(key.clone());
         }
 
         // Si no había tail, también es tail
@@ -107,7 +109,26 @@ impl<K: Eq + Hash + Clone> LruState<K> {
         true
     }
 
+    pub fn len(&self) -> usize {
+        self.links.len()
+    }
+
+    /// La clave LRU, sin sacarla.
+    pub fn back(&self) -> Option<&K> {
+        self.tail.as_ref()
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+    }
+
     pub fn over_capacity(&self) -> bool {
         self.links.len() > self.capacity
     }
+
+    /// Rearma `links` a la medida si quedó grande para las claves que tiene; devuelve
+    /// los bytes que soltó.
+    pub fn compact(&mut self) -> u64 {
+        shrink_map(&mut self.links)
+    }
 }
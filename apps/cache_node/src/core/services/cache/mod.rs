@@ -1,5 +1,26 @@
+// `cache.rs` tiene el `Cache` que da nombre al módulo y se reexporta abajo: de afuera
+// se usa `cache::Cache`, nunca `cache::cache`
+#[allow(clippy::module_inception)]
 pub mod cache;
-mod lru;
+mod compaction;
+mod expiry;
+pub(crate) mod lru;
+mod namespaces;
+mod policy;
+mod read_buffer;
+mod sampled;
+mod sketch;
+mod tags;
 mod timing_wheel;
+mod value;
 
-pub use cache::Cache;
+pub use cache::{
+    BatchPut, Cache, CacheStats, RenameOutcome, SnapshotEntry, SnapshotIter, TxConflict, TxOutcome,
+    TxStep,
+};
+pub use expiry::ExpiryStrategy;
+pub use namespaces::{
+    NamespaceAccounting, NamespaceQuota, NamespaceQuotas, NamespaceStats, QuotaExceeded, QuotaMode,
+};
+pub use policy::EvictionPolicy;
+pub use value::{CompactValue, INLINE_CAPACITY};
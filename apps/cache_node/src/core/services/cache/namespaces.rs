@@ -0,0 +1,326 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    hash::Hash,
+    str::FromStr,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use parking_lot::Mutex;
+
+use crate::core::services::cache::sampled::SampledKeys;
+
+/// Claves que se comparan al elegir a quién desalojar dentro de un namespace.
+const VICTIM_SAMPLES: usize = 5;
+
+/// Tope de un namespace; `None` es sin tope en esa medida.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NamespaceQuota {
+    pub max_entries: Option<u64>,
+    /// Suma de clave y valor de sus entradas.
+    pub max_bytes: Option<u64>,
+}
+
+impl NamespaceQuota {
+    fn allows(&self, entries: u64, bytes: u64) -> bool {
+        self.max_entries.is_none_or(|max| entries <= max)
+            && self.max_bytes.is_none_or(|max| bytes <= max)
+    }
+}
+
+/// `<entradas>[/<bytes>]`; cualquiera de los dos puede quedar vacío (`/65536`).
+impl FromStr for NamespaceQuota {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (entries, bytes) = s.split_once('/').unwrap_or((s, ""));
+        let limit = |part: &str| {
+            let part = part.trim();
+            if part.is_empty() {
+                return Ok(None);
+            }
+            part.parse::<u64>()
+                .map(Some)
+                .map_err(|_| format!("cuota inválida: '{s}' (<entradas>[/<bytes>])"))
+        };
+        Ok(Self {
+            max_entries: limit(entries)?,
+            max_bytes: limit(bytes)?,
+        })
+    }
+}
+
+impl fmt::Display for NamespaceQuota {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let limit = |f: &mut fmt::Formatter<'_>, max: Option<u64>| match max {
+            Some(max) => write!(f, "{max}"),
+            None => f.write_str("-"),
+        };
+        f.write_str("max_entries=")?;
+        limit(f, self.max_entries)?;
+        f.write_str(" max_bytes=")?;
+        limit(f, self.max_bytes)
+    }
+}
+
+/// Qué pasa con una escritura que deja a su namespace pasado de cuota
+/// (`NAMESPACE_QUOTA_MODE`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum QuotaMode {
+    /// Se rechaza y la entrada anterior, si había, queda como estaba.
+    #[default]
+    Reject,
+    /// Se escribe y salen las claves del mismo namespace de acceso más viejo hasta que
+    /// entre. Solo se rechaza una entrada que no entra ni sola.
+    Evict,
+}
+
+impl FromStr for QuotaMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "reject" => Ok(Self::Reject),
+            "evict" => Ok(Self::Evict),
+            other => Err(format!(
+                "modo de cuota inválido: '{other}' (reject | evict)"
+            )),
+        }
+    }
+}
+
+/// Cuotas por namespace (`NAMESPACE_QUOTAS`, p. ej. `tenant-a=1000/1048576,tenant-b=500`).
+/// Los namespaces sin cuota se cuentan igual.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NamespaceQuotas {
+    pub quotas: HashMap<String, NamespaceQuota>,
+    pub mode: QuotaMode,
+}
+
+/// `<namespace>=<cuota>` separados por coma, con el modo por defecto.
+impl FromStr for NamespaceQuotas {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut quotas = HashMap::new();
+        for item in s.split(',').map(str::trim).filter(|item| !item.is_empty()) {
+            let (namespace, quota) = item
+                .split_once('=')
+                .ok_or_else(|| format!("cuota sin namespace: '{item}' (<namespace>=<cuota>)"))?;
+            quotas.insert(namespace.trim().to_string(), quota.parse()?);
+        }
+        Ok(Self {
+            quotas,
+            mode: QuotaMode::default(),
+        })
+    }
+}
+
+/// Uso de un namespace desde que arrancó el cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NamespaceStats {
+    pub entries: u64,
+    pub bytes: u64,
+    /// Claves del namespace desalojadas, por su cuota o por la capacidad del cache.
+    pub evictions: u64,
+    /// Escrituras rechazadas por la cuota.
+    pub rejected: u64,
+    pub quota: Option<NamespaceQuota>,
+}
+
+impl fmt::Display for NamespaceStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "entries={} bytes={} {} evictions={} rejected={}",
+            self.entries,
+            self.bytes,
+            self.quota.unwrap_or_default(),
+            self.evictions,
+            self.rejected
+        )
+    }
+}
+
+/// La escritura no entra en la cuota de `namespace`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuotaExceeded {
+    pub namespace: String,
+}
+
+impl fmt::Display for QuotaExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cuota del namespace {} llena", self.namespace)
+    }
+}
+
+/// Cuenta entradas y bytes por namespace (y los bytes de todo el cache) y aplica
+/// `NamespaceQuotas`. El cache la llama
+/// con el shard de la clave tomado al escribir y después de sacarla del map al borrar.
+pub struct NamespaceAccounting<K, V> {
+    namespace_of: fn(&K) -> Option<&str>,
+    weight_of: fn(&K, &V) -> u64,
+    quotas: NamespaceQuotas,
+    usage: Mutex<HashMap<String, NamespaceStats>>,
+    /// Claves de cada namespace con cuota, para elegir víctima con `QuotaMode::Evict`.
+    keys: HashMap<String, SampledKeys<K>>,
+    /// Todas las entradas, tengan namespace o no.
+    bytes: AtomicU64,
+}
+
+impl<K: Eq + Hash + Clone, V> NamespaceAccounting<K, V> {
+    pub fn new(
+        quotas: NamespaceQuotas,
+        namespace_of: fn(&K) -> Option<&str>,
+        weight_of: fn(&K, &V) -> u64,
+    ) -> Self {
+        let keys = match quotas.mode {
+            QuotaMode::Reject => HashMap::new(),
+            QuotaMode::Evict => quotas
+                .quotas
+                .iter()
+                .map(|(namespace, quota)| {
+                    // solo con tope de bytes: franjas como para muchas claves, que `untrack`
+                    // recorre la suya
+                    let capacity = quota.max_entries.unwrap_or(u64::from(u16::MAX));
+                    let capacity = usize::try_from(capacity).unwrap_or(usize::MAX);
+                    (
+                        namespace.clone(),
+                        SampledKeys::new(capacity, VICTIM_SAMPLES),
+                    )
+                })
+                .collect(),
+        };
+        // los que tienen cuota se listan aunque todavía no tengan claves
+        let usage = quotas
+            .quotas
+            .keys()
+            .map(|namespace| (namespace.clone(), NamespaceStats::default()))
+            .collect();
+        Self {
+            namespace_of,
+            weight_of,
+            quotas,
+            usage: Mutex::new(usage),
+            keys,
+            bytes: AtomicU64::new(0),
+        }
+    }
+
+    /// Suma `key` con `value` en lugar de `old` (la entrada que reemplaza), si la cuota lo
+    /// permite. `Ok(true)` si el namespace quedó pasado y hay que desalojar (ver
+    /// `victim`).
+    pub(crate) fn admit(&self, key: &K, value: &V, old: Option<&V>) -> Result<bool, QuotaExceeded> {
+        let weight = (self.weight_of)(key, value);
+        let old_weight = old.map_or(0, |old| (self.weight_of)(key, old));
+        let Some(namespace) = (self.namespace_of)(key) else {
+            self.replace_bytes(old_weight, weight);
+            return Ok(false);
+        };
+        let quota = self.quotas.quotas.get(namespace);
+
+        let mut usage = self.usage.lock();
+        if !usage.contains_key(namespace) {
+            usage.insert(namespace.to_string(), NamespaceStats::default());
+        }
+        let stats = usage.get_mut(namespace).expect("se acaba de insertar");
+
+        let entries = stats.entries + u64::from(old.is_none());
+        let bytes = (stats.bytes - old_weight.min(stats.bytes)).saturating_add(weight);
+        let over = quota.is_some_and(|quota| !quota.allows(entries, bytes));
+        let fits_alone = quota.is_none_or(|quota| quota.allows(1, weight));
+        if over && (self.quotas.mode == QuotaMode::Reject || !fits_alone) {
+            stats.rejected += 1;
+            return Err(QuotaExceeded {
+                namespace: namespace.to_string(),
+            });
+        }
+        stats.entries = entries;
+        stats.bytes = bytes;
+        drop(usage);
+        self.replace_bytes(old_weight, weight);
+
+        if old.is_none()
+            && let Some(keys) = self.keys.get(namespace)
+        {
+            keys.track(key);
+        }
+        Ok(over)
+    }
+
+    /// Resta una entrada que salió del map.
+    pub(crate) fn release(&self, key: &K, value: &V) {
+        let weight = (self.weight_of)(key, value);
+        self.replace_bytes(weight, 0);
+        let Some(namespace) = (self.namespace_of)(key) else {
+            return;
+        };
+        if let Some(stats) = self.usage.lock().get_mut(namespace) {
+            stats.entries = stats.entries.saturating_sub(1);
+            stats.bytes = stats.bytes.saturating_sub(weight);
+        }
+        if let Some(keys) = self.keys.get(namespace) {
+            keys.untrack(key);
+        }
+    }
+
+    fn replace_bytes(&self, old: u64, new: u64) {
+        // sumar antes de restar: el total no da la vuelta por debajo de cero
+        self.bytes.fetch_add(new, Ordering::Relaxed);
+        self.bytes.fetch_sub(old, Ordering::Relaxed);
+    }
+
+    /// Bytes de todas las entradas del cache.
+    pub fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn count_eviction(&self, key: &K) {
+        let Some(namespace) = (self.namespace_of)(key) else {
+            return;
+        };
+        if let Some(stats) = self.usage.lock().get_mut(namespace) {
+            stats.evictions += 1;
+        }
+    }
+
+    /// Mientras el namespace de `key` siga pasado de cuota, la clave del namespace con
+    /// menor `last_access` entre unas pocas al azar (nunca una para la que dé `None`).
+    pub(crate) fn victim<F>(&self, key: &K, last_access: F) -> Option<K>
+    where
+        F: Fn(&K) -> Option<u64>,
+    {
+        let namespace = (self.namespace_of)(key)?;
+        let quota = self.quotas.quotas.get(namespace)?;
+        let over = self
+            .usage
+            .lock()
+            .get(namespace)
+            .is_some_and(|stats| !quota.allows(stats.entries, stats.bytes));
+        if !over {
+            return None;
+        }
+        self.keys.get(namespace)?.oldest(last_access)
+    }
+
+    /// Achica las claves de cada namespace que quedaron grandes (ver
+    /// `SampledKeys::compact`); devuelve los bytes que soltó.
+    pub(crate) fn compact(&self) -> u64 {
+        self.keys.values().map(SampledKeys::compact).sum()
+    }
+
+    /// Uso de cada namespace visto, ordenado por nombre.
+    pub fn stats(&self) -> Vec<(String, NamespaceStats)> {
+        let mut out: Vec<_> = self
+            .usage
+            .lock()
+            .iter()
+            .map(|(namespace, stats)| {
+                let quota = self.quotas.quotas.get(namespace).copied();
+                (namespace.clone(), NamespaceStats { quota, ..*stats })
+            })
+            .collect();
+        out.sort_by(|a, b| a.0.cmp(&b.0));
+        out
+    }
+}
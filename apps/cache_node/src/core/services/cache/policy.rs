@@ -0,0 +1,303 @@
+use std::{hash::Hash, str::FromStr};
+
+use crate::core::services::cache::{lru::LruState, sampled::SampledKeys, sketch::FrequencySketch};
+
+/// Qué entrada saca el cache cuando se pasa de capacidad (`EVICTION_POLICY`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    /// La usada hace más tiempo.
+    #[default]
+    Lru,
+    /// LRU segmentado: las claves nuevas entran a un segmento de prueba y pasan al
+    /// protegido (80% de la capacidad) cuando se vuelven a acceder. Se desaloja primero
+    /// del de prueba, así un recorrido de claves leídas una vez no toca a las protegidas.
+    Slru,
+    /// Window-TinyLFU: las claves nuevas pasan por una ventana LRU chica y, al salir de
+    /// ella, solo desplazan a la víctima del LRU principal si se accedieron más veces.
+    /// Una clave leída una sola vez no saca a las que se leen seguido.
+    TinyLfu,
+    /// Aproximado, como Redis: no hay orden que mantener y al pasarse de capacidad sale
+    /// la de acceso más viejo entre `samples` claves al azar. Los `get` no toman ningún
+    /// lock y los `put` no se esperan entre sí.
+    Sampled { samples: usize },
+}
+
+impl EvictionPolicy {
+    /// Claves que compara `Sampled` por defecto (`EVICTION_SAMPLES`).
+    pub const DEFAULT_SAMPLES: usize = 5;
+
+    /// Si los `put` pueden correr a la vez (ver `Sampled`).
+    pub fn shared_writes(&self) -> bool {
+        matches!(self, Self::Sampled { .. })
+    }
+}
+
+impl FromStr for EvictionPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "lru" => Ok(Self::Lru),
+            "slru" => Ok(Self::Slru),
+            "tinylfu" | "w-tinylfu" => Ok(Self::TinyLfu),
+            "sampled" => Ok(Self::Sampled {
+                samples: Self::DEFAULT_SAMPLES,
+            }),
+            other => Err(format!(
+                "política de desalojo inválida: '{other}' (lru | slru | tinylfu | sampled)"
+            )),
+        }
+    }
+}
+
+/// Orden de desalojo de las claves del cache según su `EvictionPolicy`.
+pub(crate) enum Eviction<K> {
+    Lru(LruState<K>),
+    Slru(SegmentedLru<K>),
+    TinyLfu(WindowTinyLfu<K>),
+    Sampled(SampledKeys<K>),
+}
+
+impl<K: Eq + Hash + Clone> Eviction<K> {
+    pub fn new(policy: EvictionPolicy, capacity: usize) -> Self {
+        match policy {
+            EvictionPolicy::Lru => Self::Lru(LruState::new(capacity)),
+            EvictionPolicy::Slru => Self::Slru(SegmentedLru::new(capacity)),
+            EvictionPolicy::TinyLfu => Self::TinyLfu(WindowTinyLfu::new(capacity)),
+            EvictionPolicy::Sampled { samples } => {
+                Self::Sampled(SampledKeys::new(capacity, samples))
+            }
+        }
+    }
+
+    /// Alta o acceso de `key`.
+    pub fn touch(&mut self, key: K) {
+        match self {
+            Self::Lru(lru) => lru.touch(key),
+            Self::Slru(slru) => slru.touch(key),
+            Self::TinyLfu(lfu) => lfu.touch(key),
+            Self::Sampled(_) => {}
+        }
+    }
+
+    /// Alta de `key` en el map, con su shard tomado. Solo la necesita `Sampled`; las demás
+    /// se enteran con `touch`.
+    pub fn track(&self, key: &K) {
+        if let Self::Sampled(keys) = self {
+            keys.track(key);
+        }
+    }
+
+    /// Baja de `key` del map con su shard tomado, sin el lock exclusivo (ver `track`).
+    pub fn untrack(&self, key: &K) {
+        if let Self::Sampled(keys) = self {
+            keys.untrack(key);
+        }
+    }
+
+    pub fn remove(&mut self, key: &K) -> bool {
+        match self {
+            Self::Lru(lru) => lru.remove(key),
+            Self::Slru(slru) => slru.remove(key),
+            Self::TinyLfu(lfu) => lfu.remove(key),
+            Self::Sampled(keys) => keys.untrack(key),
+        }
+    }
+
+    /// Si hay más claves que capacidad, saca una y la devuelve. `Sampled` elige por
+    /// `last_access` y nunca una para la que dé `None`.
+    pub fn evict<F>(&mut self, last_access: F) -> Option<K>
+    where
+        F: Fn(&K) -> Option<u64>,
+    {
+        match self {
+            Self::Lru(lru) if lru.over_capacity() => lru.pop_back(),
+            Self::Lru(_) => None,
+            Self::Slru(slru) => slru.evict(),
+            Self::TinyLfu(lfu) => lfu.evict(),
+            Self::Sampled(keys) => {
+                let key = keys.victim(last_access)?;
+                keys.untrack(&key);
+                Some(key)
+            }
+        }
+    }
+
+    /// Cambia la capacidad. No desaloja: si quedó pasada, cada `evict` saca una clave hasta
+    /// que entre.
+    pub fn resize(&mut self, capacity: usize) {
+        match self {
+            Self::Lru(lru) => lru.set_capacity(capacity),
+            Self::Slru(slru) => slru.resize(capacity),
+            Self::TinyLfu(lfu) => lfu.resize(capacity),
+            Self::Sampled(keys) => keys.set_capacity(capacity),
+        }
+    }
+
+    /// Achica lo que quedó grande para las claves que hay; devuelve los bytes que soltó.
+    pub fn compact(&mut self) -> u64 {
+        match self {
+            Self::Lru(lru) => lru.compact(),
+            Self::Slru(slru) => slru.probation.compact() + slru.protected.compact(),
+            Self::TinyLfu(lfu) => lfu.window.compact() + lfu.main.compact(),
+            Self::Sampled(keys) => keys.compact(),
+        }
+    }
+
+    /// Con `Sampled` pasada de capacidad, la clave a desalojar (sigue en el map y acá).
+    pub fn sampled_victim<F>(&self, last_access: F) -> Option<K>
+    where
+        F: Fn(&K) -> Option<u64>,
+    {
+        match self {
+            Self::Sampled(keys) => keys.victim(last_access),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) struct SegmentedLru<K> {
+    capacity: usize,
+    protected_capacity: usize,
+    probation: LruState<K>,
+    protected: LruState<K>,
+}
+
+impl<K: Eq + Hash + Clone> SegmentedLru<K> {
+    fn new(capacity: usize) -> Self {
+        let protected_capacity = capacity * 4 / 5;
+        Self {
+            capacity,
+            protected_capacity,
+            probation: LruState::new(capacity - protected_capacity),
+            protected: LruState::new(protected_capacity),
+        }
+    }
+
+    /// Lo que sobra del protegido vuelve a prueba, más viejo primero, así se desaloja de
+    /// ahí como siempre.
+    fn resize(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.protected_capacity = capacity * 4 / 5;
+        self.probation
+            .set_capacity(capacity - self.protected_capacity);
+        self.protected.set_capacity(self.protected_capacity);
+        while self.protected.len() > self.protected_capacity
+            && let Some(demoted) = self.protected.pop_back()
+        {
+            self.probation.touch(demoted);
+        }
+    }
+
+    /// Un segundo acceso promueve la clave; si el protegido se pasa, su clave más vieja
+    /// vuelve a prueba como la más nueva.
+    fn touch(&mut self, key: K) {
+        if self.protected.contains(&key) {
+            self.protected.touch(key);
+            return;
+        }
+        if !self.probation.remove(&key) {
+            self.probation.touch(key);
+            return;
+        }
+
+        self.protected.touch(key);
+        if self.protected.len() > self.protected_capacity
+            && let Some(demoted) = self.protected.pop_back()
+        {
+            self.probation.touch(demoted);
+        }
+    }
+
+    fn remove(&mut self, key: &K) -> bool {
+        self.probation.remove(key) || self.protected.remove(key)
+    }
+
+    fn evict(&mut self) -> Option<K> {
+        if self.probation.len() + self.protected.len() <= self.capacity {
+            return None;
+        }
+        self.probation
+            .pop_back()
+            .or_else(|| self.protected.pop_back())
+    }
+}
+
+/// Ventana LRU con el 1% de la capacidad (al menos una clave) delante del LRU principal,
+/// que se queda con el resto.
+pub(crate) struct WindowTinyLfu<K> {
+    capacity: usize,
+    window_capacity: usize,
+    window: LruState<K>,
+    main: LruState<K>,
+    sketch: FrequencySketch,
+}
+
+impl<K: Eq + Hash + Clone> WindowTinyLfu<K> {
+    fn new(capacity: usize) -> Self {
+        let window_capacity = (capacity / 100).max(1);
+        Self {
+            capacity,
+            window_capacity,
+            window: LruState::new(window_capacity),
+            main: LruState::new(capacity.saturating_sub(window_capacity)),
+            sketch: FrequencySketch::new(capacity),
+        }
+    }
+
+    /// El sketch se queda con el tamaño con el que se creó: al crecer mucho estima peor,
+    /// pero no pierde lo que ya contó.
+    fn resize(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.window_capacity = (capacity / 100).max(1);
+        self.window.set_capacity(self.window_capacity);
+        self.main
+            .set_capacity(capacity.saturating_sub(self.window_capacity));
+    }
+
+    fn touch(&mut self, key: K) {
+        self.sketch.record(&key);
+        if self.main.contains(&key) {
+            self.main.touch(key);
+        } else {
+            self.window.touch(key);
+        }
+    }
+
+    fn remove(&mut self, key: &K) -> bool {
+        self.window.remove(key) || self.main.remove(key)
+    }
+
+    fn len(&self) -> usize {
+        self.window.len() + self.main.len()
+    }
+
+    /// Solo entran claves por la ventana, así que pasarse de capacidad es que la ventana
+    /// se pasó de la suya: su clave más vieja pasa al LRU principal si hay lugar, o
+    /// compite con la víctima de ese LRU y sale la que se accedió menos (en empate, la
+    /// que venía de la ventana).
+    fn evict(&mut self) -> Option<K> {
+        if self.window.len() <= self.window_capacity {
+            // con la ventana en su lugar solo se pasa después de achicar (ver `resize`)
+            return (self.len() > self.capacity)
+                .then(|| self.main.pop_back().or_else(|| self.window.pop_back()))
+                .flatten();
+        }
+        let candidate = self.window.pop_back()?;
+        if self.len() < self.capacity {
+            self.main.touch(candidate);
+            return None;
+        }
+
+        let Some(victim) = self.main.back() else {
+            return Some(candidate);
+        };
+        if self.sketch.estimate(&candidate) > self.sketch.estimate(victim) {
+            let victim = self.main.pop_back();
+            self.main.touch(candidate);
+            victim
+        } else {
+            Some(candidate)
+        }
+    }
+}
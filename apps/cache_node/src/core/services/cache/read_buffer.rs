@@ -0,0 +1,54 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use parking_lot::Mutex;
+
+/// Accesos por franja antes de pedir que se apliquen.
+const STRIPE_LEN: usize = 16;
+
+static NEXT_STRIPE: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static STRIPE: usize = NEXT_STRIPE.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Lecturas que todavía no se aplicaron al orden de desalojo, en franjas por hilo. Anotar
+/// nunca espera: si la franja está tomada o llena, el acceso se pierde (el orden queda un
+/// poco menos exacto, pero un `get` no se frena por otro).
+pub(crate) struct ReadBuffer<K> {
+    stripes: Box<[Mutex<Vec<K>>]>,
+}
+
+impl<K> ReadBuffer<K> {
+    pub fn new() -> Self {
+        let stripes = std::thread::available_parallelism()
+            .map_or(4, |n| n.get())
+            .max(4)
+            .next_power_of_two();
+        Self {
+            stripes: (0..stripes)
+                .map(|_| Mutex::new(Vec::with_capacity(STRIPE_LEN)))
+                .collect(),
+        }
+    }
+
+    /// Devuelve si la franja del hilo se llenó y conviene aplicar los accesos.
+    pub fn record(&self, key: K) -> bool {
+        let i = STRIPE.with(|stripe| *stripe) & (self.stripes.len() - 1);
+        let Some(mut stripe) = self.stripes[i].try_lock() else {
+            return false;
+        };
+        if stripe.len() < STRIPE_LEN {
+            stripe.push(key);
+        }
+        stripe.len() >= STRIPE_LEN
+    }
+
+    /// Pasa a `apply` los accesos anotados, franja por franja y en el orden de cada una.
+    pub fn drain(&self, mut apply: impl FnMut(K)) {
+        for stripe in &self.stripes {
+            for key in stripe.lock().drain(..) {
+                apply(key);
+            }
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use std::{
+    hash::{BuildHasher, Hash, RandomState},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use parking_lot::Mutex;
+
+use crate::core::services::cache::compaction::shrink_vec;
+
+/// Las claves del cache repartidas por hash en franjas de unas 16, para sacar algunas al
+/// azar sin recorrer el map. Cada alta y baja se hace con el shard de la clave tomado, así
+/// lo que hay acá coincide con el map aunque varios `put` corran a la vez.
+pub(crate) struct SampledKeys<K> {
+    capacity: usize,
+    samples: usize,
+    stripes: Box<[Mutex<Vec<K>>]>,
+    hasher: RandomState,
+    len: AtomicUsize,
+}
+
+impl<K: Eq + Hash + Clone> SampledKeys<K> {
+    pub fn new(capacity: usize, samples: usize) -> Self {
+        let stripes = (capacity / 16).clamp(1, 4096).next_power_of_two();
+        Self {
+            capacity,
+            samples: samples.max(1),
+            stripes: (0..stripes).map(|_| Mutex::new(Vec::new())).collect(),
+            hasher: RandomState::new(),
+            len: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn track(&self, key: &K) {
+        self.stripe(key).lock().push(key.clone());
+        self.len.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn untrack(&self, key: &K) -> bool {
+        let mut stripe = self.stripe(key).lock();
+        let Some(i) = stripe.iter().position(|k| k == key) else {
+            return false;
+        };
+        stripe.swap_remove(i);
+        self.len.fetch_sub(1, Ordering::Relaxed);
+        true
+    }
+
+    /// Si hay más claves que capacidad, la de menor `last_access` entre `samples` claves
+    /// al azar; las que dan `None` (ya no están o no se pueden sacar) no cuentan. No la da
+    /// de baja.
+    pub fn victim<F>(&self, last_access: F) -> Option<K>
+    where
+        F: Fn(&K) -> Option<u64>,
+    {
+        if self.len.load(Ordering::Relaxed) <= self.capacity {
+            return None;
+        }
+        self.oldest(last_access)
+    }
+
+    /// Como `victim`, sin mirar la capacidad.
+    pub fn oldest<F>(&self, last_access: F) -> Option<K>
+    where
+        F: Fn(&K) -> Option<u64>,
+    {
+        let mut oldest: Option<(K, u64)> = None;
+        let mut found = 0;
+        // franjas vacías o claves descartadas: se intenta algunas veces más
+        for _ in 0..self.samples * 4 {
+            if found == self.samples {
+                break;
+            }
+            let stripe = &self.stripes[fastrand::usize(..self.stripes.len())];
+            let key = {
+                let stripe = stripe.lock();
+                if stripe.is_empty() {
+                    continue;
+                }
+                stripe[fastrand::usize(..stripe.len())].clone()
+            };
+            let Some(at) = last_access(&key) else {
+                continue;
+            };
+            found += 1;
+            if oldest.as_ref().is_none_or(|(_, oldest_at)| at < *oldest_at) {
+                oldest = Some((key, at));
+            }
+        }
+        oldest.map(|(key, _)| key)
+    }
+
+    /// Las franjas siguen siendo las que se armaron para la capacidad inicial.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+    }
+
+    /// Achica las franjas que quedaron grandes; devuelve los bytes que soltó.
+    pub fn compact(&self) -> u64 {
+        self.stripes
+            .iter()
+            .map(|stripe| shrink_vec(&mut stripe.lock()))
+            .sum()
+    }
+
+    fn stripe(&self, key: &K) -> &Mutex<Vec<K>> {
+        let i = self.hasher.hash_one(key) as usize & (self.stripes.len() - 1);
+        &self.stripes[i]
+    }
+}
@@ -0,0 +1,69 @@
+use std::hash::{BuildHasher, Hash, RandomState};
+
+const DEPTH: usize = 4;
+const MAX_COUNT: u8 = 15;
+const SEEDS: [u64; DEPTH] = [
+    0xc3a5_c85c_97cb_3127,
+    0xb492_b66f_be98_f273,
+    0x9ae1_6a3b_2f90_404f,
+    0xcbf2_9ce4_8422_2325,
+];
+
+/// Frecuencia aproximada de acceso por clave (count-min de 4 filas de `4 * capacidad`
+/// contadores de hasta 15). Cada `10 * capacidad` accesos todos los contadores se dividen
+/// a la mitad, así una clave que fue popular y dejó de serlo pierde peso.
+pub(crate) struct FrequencySketch {
+    table: Vec<u8>,
+    mask: usize,
+    hasher: RandomState,
+    additions: usize,
+    sample: usize,
+}
+
+impl FrequencySketch {
+    pub fn new(capacity: usize) -> Self {
+        let width = capacity.max(16).saturating_mul(4).next_power_of_two();
+        Self {
+            table: vec![0; width * DEPTH],
+            mask: width - 1,
+            hasher: RandomState::new(),
+            additions: 0,
+            sample: capacity.max(16).saturating_mul(10),
+        }
+    }
+
+    pub fn record<K: Hash>(&mut self, key: &K) {
+        for slot in self.slots(key) {
+            let count = &mut self.table[slot];
+            *count = (*count + 1).min(MAX_COUNT);
+        }
+        self.additions += 1;
+        if self.additions >= self.sample {
+            self.age();
+        }
+    }
+
+    pub fn estimate<K: Hash>(&self, key: &K) -> u8 {
+        self.slots(key)
+            .map(|slot| self.table[slot])
+            .min()
+            .unwrap_or(0)
+    }
+
+    fn age(&mut self) {
+        for count in &mut self.table {
+            *count /= 2;
+        }
+        self.additions /= 2;
+    }
+
+    /// Un contador por fila.
+    fn slots<K: Hash>(&self, key: &K) -> impl Iterator<Item = usize> + use<K> {
+        let hash = self.hasher.hash_one(key);
+        let (mask, width) = (self.mask, self.mask + 1);
+        SEEDS.into_iter().enumerate().map(move |(row, seed)| {
+            let h = (hash ^ seed).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+            row * width + ((h ^ (h >> 32)) as usize & mask)
+        })
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use crate::core::services::cache::compaction::shrink_map;
+
+/// Índice secundario tag -> claves. Se modifica solo con el lock del LRU tomado, así que
+/// queda en sintonía con el map: cada alta reemplaza los tags de la clave y cada baja
+/// (borrado, evicción o expiración) los suelta.
+pub struct TagIndex<K> {
+    by_tag: HashMap<String, HashSet<K>>,
+    by_key: HashMap<K, Box<[String]>>,
+}
+
+impl<K: Eq + Hash + Clone> TagIndex<K> {
+    pub fn new() -> Self {
+        Self {
+            by_tag: HashMap::new(),
+            by_key: HashMap::new(),
+        }
+    }
+
+    /// Deja a `key` con exactamente `tags` (sin tags si está vacío).
+    pub fn set(&mut self, key: &K, tags: &[String]) {
+        self.unlink(key);
+        if tags.is_empty() {
+            return;
+        }
+        for tag in tags {
+            self.by_tag
+                .entry(tag.clone())
+                .or_default()
+                .insert(key.clone());
+        }
+        self.by_key.insert(key.clone(), tags.into());
+    }
+
+    pub fn unlink(&mut self, key: &K) {
+        let Some(tags) = self.by_key.remove(key) else {
+            return;
+        };
+        for tag in tags.iter() {
+            if let Some(keys) = self.by_tag.get_mut(tag) {
+                keys.remove(key);
+                if keys.is_empty() {
+                    self.by_tag.remove(tag);
+                }
+            }
+        }
+    }
+
+    pub fn keys(&self, tag: &str) -> Vec<K> {
+        self.by_tag
+            .get(tag)
+            .map(|keys| keys.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn tags(&self, key: &K) -> &[String] {
+        self.by_key.get(key).map_or(&[], |tags| tags)
+    }
+
+    /// Achica los dos índices si quedaron grandes; devuelve los bytes que soltó.
+    pub fn compact(&mut self) -> u64 {
+        shrink_map(&mut self.by_tag) + shrink_map(&mut self.by_key)
+    }
+}
+
+impl<K: Eq + Hash + Clone> Default for TagIndex<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,11 +1,28 @@
 use std::{
+    collections::VecDeque,
     hash::Hash,
     sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
 };
 
 use dashmap::{DashMap, DashSet};
+use parking_lot::Mutex;
 
-use crate::core::services::cache::Cache;
+use crate::core::services::cache::{
+    Cache,
+    compaction::{shrink_dashmap, shrink_dashset},
+};
+
+/// Lo que hizo un `advance_to`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TickReport {
+    /// Ticks que el cursor avanzó de más: los que pasaron sin que se llamara a tiempo.
+    pub missed_ticks: u64,
+    /// Claves revisadas.
+    pub checked: usize,
+    /// Claves que quedaron pendientes para el próximo llamado.
+    pub pending: usize,
+}
 
 pub struct TimingWheel<K>
 where
@@ -21,6 +38,8 @@ where
     size: usize,
     /// Número absoluto de tick (crece sin tope; usamos % size para el slot).
     pub cursor: AtomicU64,
+    /// Claves de slots ya drenados que falta revisar porque un tick llegó a su tope.
+    backlog: Mutex<VecDeque<K>>,
 }
 
 //Nota Hay muchos comentarios porque igual es un algoritmo que no domino del todo
@@ -48,6 +67,7 @@ where
             tick_ms,
             size,
             cursor: AtomicU64::new(start_tick),
+            backlog: Mutex::new(VecDeque::new()),
         }
     }
 
@@ -94,16 +114,26 @@ where
         }
     }
 
-    /// Avanza el cursor hasta `target_ms`, drenando los slots intermedios.
-    /// Llama a `invalidate_if_expired` para cada clave en el slot.
+    /// Achica el índice y los slots que quedaron grandes después de un pico de claves
+    /// agendadas; devuelve los bytes que soltó.
+    pub fn compact(&self) -> u64 {
+        shrink_dashmap(&self.index) + self.slots.iter().map(shrink_dashset).sum::<u64>()
+    }
+
+    /// Avanza el cursor hasta `target_ms`, drenando los slots intermedios, y llama a
+    /// `invalidate_if_expired` para cada clave drenada. Revisa a lo sumo `max_keys` y corta
+    /// pasado `budget`; las que quedan esperan al próximo llamado, antes que las nuevas.
     pub fn advance_to<V: Send + Sync + 'static>(
         &self,
         target_ms: u64,
         cache: &Cache<K, V>,
+        max_keys: usize,
+        budget: Option<Duration>,
         invalidate_if_expired: impl Fn(&Cache<K, V>, &K, u64),
-    ) {
+    ) -> TickReport {
         let target_tick = target_ms / self.tick_ms;
         let mut cur = self.cursor.load(Ordering::Relaxed);
+        let missed_ticks = target_tick.saturating_sub(cur).saturating_sub(1);
 
         while cur < target_tick {
             let slot_idx = (cur as usize) & (self.size - 1);
@@ -114,18 +144,38 @@ where
                 // copiamos las claves a un Vec y luego removemos del índice.
                 let keys: Vec<K> = set.iter().map(|r| r.clone()).collect();
 
+                let mut backlog = self.backlog.lock();
                 for k in keys {
                     // Sacar del slot + índice inverso
                     set.remove(&k);
                     let _ = self.index.remove(&k);
-
-                    // Validar expiración real y, si aplica, invalidar
-                    invalidate_if_expired(cache, &k, target_ms);
+                    backlog.push_back(k);
                 }
             }
 
             cur += 1;
             self.cursor.store(cur, Ordering::Relaxed);
         }
+
+        // Validar expiración real y, si aplica, invalidar. El lock se suelta antes de cada
+        // llamado: la clave puede volver a agendarse para otra vuelta.
+        let started = Instant::now();
+        let mut checked = 0;
+        while checked < max_keys {
+            // mirar la hora cada tanto alcanza y cuesta menos
+            if checked % 64 == 0 && budget.is_some_and(|budget| started.elapsed() >= budget) {
+                break;
+            }
+            let Some(k) = self.backlog.lock().pop_front() else {
+                break;
+            };
+            invalidate_if_expired(cache, &k, target_ms);
+            checked += 1;
+        }
+        TickReport {
+            missed_ticks,
+            checked,
+            pending: self.backlog.lock().len(),
+        }
     }
 }
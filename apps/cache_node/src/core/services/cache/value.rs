@@ -0,0 +1,78 @@
+use std::{fmt, ops::Deref};
+
+/// Bytes que entran en el propio `CompactValue`, sin reservar memoria aparte.
+pub const INLINE_CAPACITY: usize = 22;
+
+/// Texto inmutable para los valores del cache, que ya va dentro de un `Arc`. Hasta
+/// `INLINE_CAPACITY` bytes se guarda adentro (la entrada queda en una sola reserva, la del
+/// `Arc`); más largo, en una reserva del tamaño justo, sin la capacidad de más que puede
+/// traer un `String`. Ocupa lo mismo que un `String`.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum CompactValue {
+    Inline {
+        len: u8,
+        bytes: [u8; INLINE_CAPACITY],
+    },
+    Heap(Box<str>),
+}
+
+impl CompactValue {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Inline { len, bytes } => std::str::from_utf8(&bytes[..*len as usize])
+                .expect("un valor inline se arma siempre desde un &str"),
+            Self::Heap(value) => value,
+        }
+    }
+
+    /// Si se guarda adentro, sin reserva propia.
+    pub fn is_inline(&self) -> bool {
+        matches!(self, Self::Inline { .. })
+    }
+}
+
+impl Deref for CompactValue {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl From<&str> for CompactValue {
+    fn from(value: &str) -> Self {
+        if value.len() > INLINE_CAPACITY {
+            return Self::Heap(value.into());
+        }
+        let mut bytes = [0; INLINE_CAPACITY];
+        bytes[..value.len()].copy_from_slice(value.as_bytes());
+        Self::Inline {
+            len: value.len() as u8,
+            bytes,
+        }
+    }
+}
+
+impl From<String> for CompactValue {
+    /// Uno corto se copia adentro y suelta su reserva; uno largo se queda con ella,
+    /// recortada a su largo.
+    fn from(value: String) -> Self {
+        if value.len() > INLINE_CAPACITY {
+            Self::Heap(value.into_boxed_str())
+        } else {
+            Self::from(value.as_str())
+        }
+    }
+}
+
+impl fmt::Display for CompactValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for CompactValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
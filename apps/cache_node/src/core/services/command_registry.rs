@@ -0,0 +1,35 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::core::domain::services::CommandHandler;
+
+/// Comandos del nodo por acción.
+#[derive(Default)]
+pub struct CommandRegistry {
+    handlers: HashMap<&'static str, Arc<dyn CommandHandler>>,
+}
+
+impl CommandRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Da de alta `handler` bajo su acción. Registrar dos veces la misma acción es un
+    /// error de armado del módulo, así que entra en pánico.
+    pub fn register<H: CommandHandler + 'static>(&mut self, handler: H) -> &mut Self {
+        let action = handler.action();
+        let previous = self.handlers.insert(action, Arc::new(handler));
+        assert!(previous.is_none(), "comando {action} registrado dos veces");
+        self
+    }
+
+    pub fn get(&self, action: &str) -> Option<&Arc<dyn CommandHandler>> {
+        self.handlers.get(action)
+    }
+
+    /// Acciones registradas, ordenadas.
+    pub fn actions(&self) -> Vec<&'static str> {
+        let mut actions: Vec<_> = self.handlers.keys().copied().collect();
+        actions.sort_unstable();
+        actions
+    }
+}
@@ -0,0 +1,104 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    future::Future,
+    sync::Arc,
+    time::Duration,
+};
+
+use app_core::clock::Clock;
+use parking_lot::Mutex;
+use tokio::sync::OnceCell;
+
+use crate::core::domain::models::Response;
+
+/// Cuánto recuerda `IdempotencyCache` cada token y cuántos a la vez.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdempotencyConfig {
+    /// Con cero no se recuerda nada y cada escritura se aplica.
+    pub window: Duration,
+    pub max_len: usize,
+}
+
+impl Default for IdempotencyConfig {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(30),
+            max_len: 65_536,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Entries {
+    by_token: HashMap<String, Arc<OnceCell<Response>>>,
+    /// Los tokens en el orden en que llegaron, con el instante (ms) de cada uno.
+    order: VecDeque<(u64, String)>,
+}
+
+/// La respuesta de cada escritura que trajo `idem=<token>` (ver `app_net::idempotency`),
+/// para que el reintento del master o la conmutación del cliente no apliquen dos veces la
+/// misma operación: el mismo token dentro de `window` recibe la respuesta de la primera
+/// vez. Dos iguales al mismo tiempo esperan a una sola ejecución.
+///
+/// Los tokens no viajan por la replicación: después de un failover el nuevo primario no
+/// conoce los del anterior.
+pub struct IdempotencyCache {
+    config: IdempotencyConfig,
+    clock: Arc<dyn Clock>,
+    entries: Mutex<Entries>,
+}
+
+impl IdempotencyCache {
+    pub fn new(config: IdempotencyConfig, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            config,
+            clock,
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+
+    /// Ejecuta `write` si `token` no se vio en la ventana; si se vio, devuelve lo que
+    /// respondió aquella vez. Sin token, o con la ventana en cero, siempre ejecuta.
+    pub async fn run<F>(&self, token: Option<&str>, write: F) -> Response
+    where
+        F: Future<Output = Response>,
+    {
+        let Some(token) = token.filter(|_| !self.config.window.is_zero()) else {
+            return write.await;
+        };
+        let cell = self.cell(token);
+        // si la primera se cancela, la celda queda vacía y la ejecuta el que siga
+        cell.get_or_init(|| write).await.clone()
+    }
+
+    /// Tokens recordados ahora.
+    pub fn len(&self) -> usize {
+        self.entries.lock().by_token.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn cell(&self, token: &str) -> Arc<OnceCell<Response>> {
+        let now = self.clock.now_millis().as_millis_u64();
+        let window = self.config.window.as_millis() as u64;
+        let mut entries = self.entries.lock();
+        let Entries { by_token, order } = &mut *entries;
+        while let Some((at, _)) = order.front() {
+            if now.saturating_sub(*at) < window && order.len() < self.config.max_len.max(1) {
+                break;
+            }
+            if let Some((_, old)) = order.pop_front() {
+                by_token.remove(&old);
+            }
+        }
+        by_token
+            .entry(token.to_string())
+            .or_insert_with(|| {
+                order.push_back((now, token.to_string()));
+                Arc::new(OnceCell::new())
+            })
+            .clone()
+    }
+}
@@ -1,6 +1,22 @@
-pub mod action_parser_service;
 pub mod cache;
+pub mod command_registry;
+pub mod idempotency;
+pub mod op_log;
 pub mod request_controller_service;
+pub mod request_queue;
+pub mod slow_log;
+pub mod top_keys;
+pub mod write_batcher;
 
-pub use action_parser_service::ActionParserService;
-pub use cache::Cache;
+pub use cache::{
+    BatchPut, Cache, CacheStats, CompactValue, EvictionPolicy, ExpiryStrategy, INLINE_CAPACITY,
+    NamespaceAccounting, NamespaceQuota, NamespaceQuotas, NamespaceStats, QuotaExceeded, QuotaMode,
+    RenameOutcome, SnapshotEntry, SnapshotIter, TxConflict, TxOutcome, TxStep,
+};
+pub use command_registry::CommandRegistry;
+pub use idempotency::{IdempotencyCache, IdempotencyConfig};
+pub use op_log::{Op, OpLog, WriteGuard};
+pub use request_queue::{RequestPriority, RequestQueue, RequestWorkers};
+pub use slow_log::{SlowEntry, SlowLog, SlowLogConfig};
+pub use top_keys::{KeyCount, TopKeys, TopKeysConfig};
+pub use write_batcher::{WriteBatcher, WriteBatching};
@@ -0,0 +1,266 @@
+use std::{
+    collections::VecDeque,
+    hash::{BuildHasher, RandomState},
+    sync::Arc,
+};
+
+use app_core::id::new_sortable_id;
+use app_net::{
+    DEL_PREFIX, RequestDataInput, encode_args, encode_tags, format_millis, parse_instant,
+    tags::INVALIDATE_TAG, take_tags, tokenize,
+};
+use parking_lot::Mutex;
+use tokio::sync::{
+    Mutex as AsyncMutex, MutexGuard as AsyncMutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard,
+    watch,
+};
+
+/// Operaciones que guarda el op-log por defecto antes de descartar las más viejas.
+pub const DEFAULT_OP_LOG_CAPACITY: usize = 10_000;
+
+/// Franjas de `OpLog::lock_keys`: dos claves distintas solo se esperan si caen en la misma.
+const KEY_STRIPES: usize = 256;
+
+/// Escritura aplicada en el nodo, tal como se reenvía a las réplicas.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Op {
+    Put {
+        key: String,
+        value: String,
+        expires_at: Option<u64>,
+        tags: Vec<String>,
+    },
+    Del {
+        key: String,
+    },
+    /// Se replica como una sola operación: la réplica tiene el mismo índice de tags.
+    InvalidateTag {
+        tag: String,
+    },
+    /// Como `InvalidateTag`: la réplica recorre sus claves con el mismo prefijo.
+    DelPrefix {
+        prefix: String,
+    },
+    /// Como `InvalidateTag`: la réplica tiene la misma entrada y la mueve igual (ver
+    /// `Cache::rename`).
+    Rename {
+        from: String,
+        to: String,
+    },
+}
+
+impl Op {
+    /// Línea `REQ <seq> PUT|DEL|INVALIDATE-TAG|DEL-PREFIX|RENAME ...` del stream de replicación; la réplica la vuelve a
+    /// leer con `Op::parse`.
+    pub fn to_line(&self, seq: u64) -> String {
+        let (action, payload) = self.to_request();
+
+        RequestDataInput::new(action, &payload)
+            .from_id(seq.to_string())
+            .to_string()
+    }
+
+    /// Acción y payload con los que se vuelve a aplicar la operación.
+    pub fn to_request(&self) -> (&'static str, String) {
+        match self {
+            Op::Put {
+                key,
+                value,
+                expires_at,
+                tags,
+            } => {
+                let expires_at = expires_at.map(format_millis);
+                let tags = encode_tags(tags);
+                let args = [key.as_str(), value.as_str()];
+                let optional = expires_at.as_deref().into_iter().chain(tags.as_deref());
+                ("PUT", encode_args(args.into_iter().chain(optional)))
+            }
+            Op::Del { key } => ("DEL", encode_args([key.as_str()])),
+            Op::InvalidateTag { tag } => (INVALIDATE_TAG, encode_args([tag.as_str()])),
+            Op::DelPrefix { prefix } => (DEL_PREFIX, encode_args([prefix.as_str()])),
+            Op::Rename { from, to } => ("RENAME", encode_args([from.as_str(), to.as_str()])),
+        }
+    }
+
+    /// Acción y payload de un REQ del stream. `None` si no es una escritura o el payload
+    /// no se puede interpretar.
+    pub fn parse(action: &str, payload: &str) -> Option<Self> {
+        let mut args: Vec<_> = tokenize(payload).collect();
+        let tags = take_tags(&mut args).ok()?;
+        let mut args = args.into_iter();
+        let key = args.next()?.into_owned();
+
+        match action {
+            "PUT" => {
+                let value = args.next()?.into_owned();
+                let expires_at = args.next().map(|s| parse_instant(&s)).transpose().ok()?;
+                Some(Op::Put {
+                    key,
+                    value,
+                    expires_at,
+                    tags,
+                })
+            }
+            "DEL" => Some(Op::Del { key }),
+            INVALIDATE_TAG => Some(Op::InvalidateTag { tag: key }),
+            DEL_PREFIX => Some(Op::DelPrefix { prefix: key }),
+            "RENAME" => Some(Op::Rename {
+                from: key,
+                to: args.next()?.into_owned(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// `read_from` pidió operaciones que ya se descartaron: la réplica necesita un SYNC completo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Truncated {
+    /// Primera secuencia que todavía está en el log.
+    pub oldest: u64,
+}
+
+struct OpLogInner {
+    ops: VecDeque<(u64, Arc<Op>)>,
+    /// Secuencia de la última operación agregada (0 = ninguna).
+    head: u64,
+}
+
+/// Log acotado de escrituras con secuencia creciente (desde 1). Avisa de cada operación
+/// nueva a los streams de replicación por un `watch` con la última secuencia.
+///
+/// Las secuencias solo valen dentro de una `epoch` (se genera al crear el log): si el
+/// primario se reinicia, una réplica no puede retomar con un offset del log anterior.
+///
+/// Una escritura se aplica al cache y se agrega al log bajo el mismo `WriteGuard` (ver
+/// `lock_keys`): si no, dos `PUT`/`DEL` concurrentes sobre la misma clave podían quedar en
+/// el log en el orden inverso al que se aplicaron y la réplica terminaba con otro valor.
+pub struct OpLog {
+    epoch: Arc<str>,
+    inner: Mutex<OpLogInner>,
+    capacity: usize,
+    changes: watch::Sender<u64>,
+    /// Compartido por las escrituras de claves sueltas, exclusivo para las que no saben de
+    /// antemano qué claves tocan (y para los snapshots).
+    order: RwLock<()>,
+    stripes: Box<[AsyncMutex<()>]>,
+    hasher: RandomState,
+}
+
+/// Mientras vive, ninguna otra escritura sobre las mismas claves se aplica ni entra al log.
+pub struct WriteGuard<'a> {
+    _shared: Option<RwLockReadGuard<'a, ()>>,
+    _exclusive: Option<RwLockWriteGuard<'a, ()>>,
+    _keys: Vec<AsyncMutexGuard<'a, ()>>,
+}
+
+impl OpLog {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be > 0");
+
+        Self {
+            epoch: Arc::from(new_sortable_id()),
+            inner: Mutex::new(OpLogInner {
+                ops: VecDeque::with_capacity(capacity.min(1024)),
+                head: 0,
+            }),
+            capacity,
+            changes: watch::Sender::new(0),
+            order: RwLock::new(()),
+            stripes: (0..KEY_STRIPES).map(|_| AsyncMutex::new(())).collect(),
+            hasher: RandomState::new(),
+        }
+    }
+
+    /// Ordena las escrituras sobre `keys`: se toma antes de tocar el cache y se suelta
+    /// después del `append`. Las franjas se toman en orden, así dos escrituras de varias
+    /// claves no se bloquean entre sí.
+    pub async fn lock_keys<K: AsRef<str>>(&self, keys: &[K]) -> WriteGuard<'_> {
+        let shared = self.order.read().await;
+        let mut stripes: Vec<usize> = keys
+            .iter()
+            .map(|key| self.hasher.hash_one(key.as_ref()) as usize % self.stripes.len())
+            .collect();
+        stripes.sort_unstable();
+        stripes.dedup();
+
+        let mut guards = Vec::with_capacity(stripes.len());
+        for stripe in stripes {
+            guards.push(self.stripes[stripe].lock().await);
+        }
+        WriteGuard {
+            _shared: Some(shared),
+            _exclusive: None,
+            _keys: guards,
+        }
+    }
+
+    /// Como `lock_keys`, pero frente a todas las escrituras: para las que recorren el cache
+    /// (tags, prefijos) y para leer el cache junto con `head` sin que se cuele nada.
+    pub async fn lock_all(&self) -> WriteGuard<'_> {
+        WriteGuard {
+            _shared: None,
+            _exclusive: Some(self.order.write().await),
+            _keys: Vec::new(),
+        }
+    }
+
+    pub fn append(&self, op: Op) -> u64 {
+        let seq = {
+            let mut inner = self.inner.lock();
+            inner.head += 1;
+            let seq = inner.head;
+            if inner.ops.len() == self.capacity {
+                inner.ops.pop_front();
+            }
+            inner.ops.push_back((seq, Arc::new(op)));
+            seq
+        };
+
+        self.changes.send_replace(seq);
+        seq
+    }
+
+    pub fn epoch(&self) -> &str {
+        &self.epoch
+    }
+
+    pub fn head(&self) -> u64 {
+        self.inner.lock().head
+    }
+
+    /// Hasta `max` operaciones con secuencia `>= from`, en orden.
+    pub fn read_from(&self, from: u64, max: usize) -> Result<Vec<(u64, Arc<Op>)>, Truncated> {
+        let inner = self.inner.lock();
+        let oldest = inner.ops.front().map_or(inner.head + 1, |(seq, _)| *seq);
+
+        // `oldest > 1`: ya se descartó algo
+        if from < oldest && oldest > 1 {
+            return Err(Truncated { oldest });
+        }
+
+        let skip = from.saturating_sub(oldest) as usize;
+        Ok(inner.ops.iter().skip(skip).take(max).cloned().collect())
+    }
+
+    /// Primera secuencia a mandarle a una réplica que ya aplicó hasta `seq` del log
+    /// `epoch`. `None` si hace falta un SYNC completo: otra epoch, un offset que este log
+    /// no emitió o operaciones ya descartadas.
+    pub fn resume_point(&self, epoch: &str, seq: u64) -> Option<u64> {
+        if epoch != &*self.epoch || seq > self.head() {
+            return None;
+        }
+        self.read_from(seq + 1, 0).ok().map(|_| seq + 1)
+    }
+
+    /// Receptor que cambia con cada `append` (valor: última secuencia).
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.changes.subscribe()
+    }
+}
+
+impl Default for OpLog {
+    fn default() -> Self {
+        Self::new(DEFAULT_OP_LOG_CAPACITY)
+    }
+}
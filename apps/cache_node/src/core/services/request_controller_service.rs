@@ -1,31 +1,68 @@
-// src/app/controller.rs
-use std::sync::Arc;
+use std::{sync::Arc, time::Instant};
+
+use app_core::error::ErrorKind;
 
 use crate::core::{
-    domain::{
-        models::{Command, Response},
-        services::CacheService,
-    },
-    usecases::{exec_get, exec_ping, exec_put},
+    domain::models::{Response, RoleState},
+    services::{CommandRegistry, SlowLog, TopKeys},
 };
 
-pub struct RequestControllerService<C: CacheService> {
-    cache: Arc<C>,
+pub struct RequestControllerService {
+    commands: CommandRegistry,
+    role: Arc<RoleState>,
+    slow_log: Option<Arc<SlowLog>>,
+    top_keys: Option<Arc<TopKeys>>,
 }
 
-impl<C: CacheService> RequestControllerService<C> {
-    pub fn new(cache: Arc<C>) -> Self {
-        Self { cache }
+impl RequestControllerService {
+    pub fn new(commands: CommandRegistry, role: Arc<RoleState>) -> Self {
+        Self {
+            commands,
+            role,
+            slow_log: None,
+            top_keys: None,
+        }
+    }
+
+    /// Mide cada comando y guarda en `slow_log` los que superan su umbral.
+    pub fn with_slow_log(mut self, slow_log: Arc<SlowLog>) -> Self {
+        self.slow_log = Some(slow_log);
+        self
     }
 
-    pub async fn handle(&self, cmd: Command) -> Response {
-        match cmd {
-            Command::Ping => exec_ping().await,
-            Command::Put { key, value, ttl } => {
-                exec_put(self.cache.as_ref(), key, value, ttl).await
-            }
-            Command::Get { key } => exec_get(self.cache.as_ref(), key).await,
-            Command::Unknown(other) => Response::Echo(other),
+    /// Cuenta en `top_keys` las claves de los comandos que leen o escriben una.
+    pub fn with_top_keys(mut self, top_keys: Arc<TopKeys>) -> Self {
+        self.top_keys = Some(top_keys);
+        self
+    }
+
+    pub fn commands(&self) -> &CommandRegistry {
+        &self.commands
+    }
+
+    pub async fn handle(&self, action: &str, payload: &str) -> Response {
+        let Some(command) = self.commands.get(action) else {
+            return Response::Echo(action.to_string());
+        };
+
+        if command.is_write() && !self.role.accepts_writes() {
+            return Response::error(
+                ErrorKind::Conflict,
+                "réplica en modo estricto: no acepta escrituras",
+            );
+        }
+
+        if command.accesses_key()
+            && let Some(top_keys) = &self.top_keys
+        {
+            top_keys.observe(command.is_write(), payload);
+        }
+
+        let started = Instant::now();
+        let response = command.handle(payload).await;
+        if let Some(slow_log) = &self.slow_log {
+            slow_log.observe(action, payload, started.elapsed());
         }
+        response
     }
 }
@@ -0,0 +1,184 @@
+use std::{
+    collections::VecDeque,
+    panic::{AssertUnwindSafe, catch_unwind},
+    pin::Pin,
+    sync::atomic::{AtomicU64, Ordering},
+    task::{Context, Poll},
+};
+
+use app_net::TimeoutClass;
+use parking_lot::Mutex;
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+use tracing::error;
+
+/// Cuándo se atiende un request frente a los demás en cola: primero los de control, al
+/// final los que recorren todo el cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RequestPriority {
+    /// `PING` y las consultas y ajustes del nodo (`STATS`, `CONFIG`..).
+    Control,
+    /// `GET`, `PEEK` y `META`.
+    Read,
+    /// Las escrituras y cualquier acción que no esté en otra clase.
+    Write,
+    /// `SNAPSHOT`, `EXPORT-RANGE`, `DEL-PREFIX` e `INVALIDATE-TAG`.
+    Bulk,
+}
+
+impl RequestPriority {
+    pub const ALL: [Self; 4] = [Self::Control, Self::Read, Self::Write, Self::Bulk];
+
+    /// Sale de las clases de timeout (ver `app_net::TimeoutClass`), partiendo las de datos.
+    pub fn of(action: &str) -> Self {
+        match TimeoutClass::of(action) {
+            TimeoutClass::Admin => Self::Control,
+            TimeoutClass::Bulk => Self::Bulk,
+            TimeoutClass::Data => match action {
+                "PING" => Self::Control,
+                "GET" | "PEEK" | "META" => Self::Read,
+                _ => Self::Write,
+            },
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Control => "control",
+            Self::Read => "read",
+            Self::Write => "write",
+            Self::Bulk => "bulk",
+        }
+    }
+
+    fn idx(self) -> usize {
+        self as usize
+    }
+}
+
+/// Cuántos requests atiende el nodo a la vez.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestWorkers {
+    pub workers: usize,
+    /// De esos, cuántos pueden estar a la vez con un `Bulk`: los demás quedan libres para
+    /// los `GET` aunque haya un `EXPORT-RANGE` tras otro.
+    pub bulk: usize,
+}
+
+impl Default for RequestWorkers {
+    fn default() -> Self {
+        Self {
+            workers: 64,
+            bulk: 4,
+        }
+    }
+}
+
+type Job = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+#[derive(Default)]
+struct Queues {
+    pending: [VecDeque<Job>; 4],
+    bulk_running: usize,
+}
+
+/// Cola de requests del nodo, una por prioridad, que atienden `workers` tareas fijas en
+/// vez de una tarea por request. Cada worker toma el primero de la cola más prioritaria
+/// que tenga algo; los `Bulk` solo si hay menos de `bulk` en curso. Lo que entra a la cola
+/// ya pasó por los topes de requests en curso, así que no crece sin límite.
+pub struct RequestQueue {
+    config: RequestWorkers,
+    queues: Mutex<Queues>,
+    notify: Notify,
+    /// Requests atendidos por prioridad.
+    served: [AtomicU64; 4],
+}
+
+impl RequestQueue {
+    pub fn new(config: RequestWorkers) -> Self {
+        let workers = config.workers.max(1);
+        Self {
+            config: RequestWorkers {
+                workers,
+                bulk: config.bulk.clamp(1, workers),
+            },
+            queues: Mutex::new(Queues::default()),
+            notify: Notify::new(),
+            served: Default::default(),
+        }
+    }
+
+    pub fn config(&self) -> RequestWorkers {
+        self.config
+    }
+
+    pub fn push(&self, priority: RequestPriority, job: impl Future<Output = ()> + Send + 'static) {
+        self.queues.lock().pending[priority.idx()].push_back(Box::pin(job));
+        self.notify.notify_one();
+    }
+
+    /// Requests esperando un worker, por prioridad.
+    pub fn queued(&self, priority: RequestPriority) -> usize {
+        self.queues.lock().pending[priority.idx()].len()
+    }
+
+    pub fn served(&self, priority: RequestPriority) -> u64 {
+        self.served[priority.idx()].load(Ordering::Relaxed)
+    }
+
+    /// Un worker: atiende la cola hasta que se cancela `token`. Se lanzan `config().workers`.
+    pub async fn run_worker(&self, token: CancellationToken) {
+        loop {
+            // se pide antes de mirar la cola, para no perder un `push` entre medio
+            let notified = self.notify.notified();
+            let Some((priority, job)) = self.pop() else {
+                tokio::select! {
+                    _ = notified => continue,
+                    _ = token.cancelled() => return,
+                }
+            };
+
+            // un request que entra en pánico no se lleva al worker
+            if CatchUnwind(job).await.is_err() {
+                error!(target: "srv", priority = priority.as_str(), "request en pánico");
+            }
+            self.served[priority.idx()].fetch_add(1, Ordering::Relaxed);
+            if priority == RequestPriority::Bulk {
+                let mut queues = self.queues.lock();
+                queues.bulk_running -= 1;
+                if !queues.pending[priority.idx()].is_empty() {
+                    self.notify.notify_one();
+                }
+            }
+        }
+    }
+
+    fn pop(&self) -> Option<(RequestPriority, Job)> {
+        let mut queues = self.queues.lock();
+        let bulk_free = queues.bulk_running < self.config.bulk;
+        let priority = RequestPriority::ALL.into_iter().find(|priority| {
+            (*priority != RequestPriority::Bulk || bulk_free)
+                && !queues.pending[priority.idx()].is_empty()
+        })?;
+        let job = queues.pending[priority.idx()].pop_front()?;
+        if priority == RequestPriority::Bulk {
+            queues.bulk_running += 1;
+        }
+        Some((priority, job))
+    }
+}
+
+/// `Err` si `job` entró en pánico.
+struct CatchUnwind(Job);
+
+impl Future for CatchUnwind {
+    type Output = Result<(), ()>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match catch_unwind(AssertUnwindSafe(|| self.0.as_mut().poll(cx))) {
+            Ok(Poll::Ready(())) => Poll::Ready(Ok(())),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(_) => Poll::Ready(Err(())),
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use app_core::clock::Clock;
+use app_net::redact_args;
+use parking_lot::Mutex;
+
+/// Cuándo un comando cuenta como lento y cuántos se guardan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlowLogConfig {
+    pub threshold: Duration,
+    /// Con 0 no se guarda nada.
+    pub max_len: usize,
+}
+
+impl Default for SlowLogConfig {
+    fn default() -> Self {
+        Self {
+            threshold: Duration::from_millis(10),
+            max_len: 128,
+        }
+    }
+}
+
+/// Un comando que tardó `threshold` o más.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlowEntry {
+    /// Creciente; sigue contando después de un `SLOWLOG RESET`.
+    pub id: u64,
+    /// Cuándo terminó, según el `Clock` del nodo (ms).
+    pub at: u64,
+    pub duration: Duration,
+    pub action: String,
+    /// La clave y el largo de los demás argumentos (ver `redact_args`); los valores no se
+    /// guardan.
+    pub args: String,
+}
+
+impl SlowEntry {
+    /// `id=.. at=.. duration_us=.. action=.. args=..`; `args` va al final porque puede
+    /// tener espacios.
+    pub fn to_wire(&self) -> String {
+        format!(
+            "id={} at={} duration_us={} action={} args={}",
+            self.id,
+            self.at,
+            self.duration.as_micros(),
+            self.action,
+            self.args
+        )
+    }
+}
+
+/// Los últimos comandos lentos del nodo, del más nuevo al más viejo. Complementa el
+/// tiempo por request que mide el master: acá se ve cuánto tardó la operación en el
+/// nodo mismo, sin la red.
+pub struct SlowLog {
+    config: SlowLogConfig,
+    clock: Arc<dyn Clock>,
+    next_id: AtomicU64,
+    entries: Mutex<VecDeque<SlowEntry>>,
+}
+
+impl SlowLog {
+    pub fn new(config: SlowLogConfig, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            config,
+            clock,
+            next_id: AtomicU64::new(0),
+            entries: Mutex::new(VecDeque::with_capacity(config.max_len)),
+        }
+    }
+
+    pub fn threshold(&self) -> Duration {
+        self.config.threshold
+    }
+
+    /// Guarda el comando si tardó lo suficiente; devuelve si lo guardó.
+    pub fn observe(&self, action: &str, payload: &str, elapsed: Duration) -> bool {
+        if elapsed < self.config.threshold || self.config.max_len == 0 {
+            return false;
+        }
+
+        let entry = SlowEntry {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            at: self.clock.now_millis().as_millis_u64(),
+            duration: elapsed,
+            action: action.to_string(),
+            args: redact_args(payload),
+        };
+
+        let mut entries = self.entries.lock();
+        if entries.len() == self.config.max_len {
+            entries.pop_back();
+        }
+        entries.push_front(entry);
+        true
+    }
+
+    /// Hasta `limit` entradas, la más nueva primero.
+    pub fn latest(&self, limit: usize) -> Vec<SlowEntry> {
+        self.entries.lock().iter().take(limit).cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn reset(&self) {
+        self.entries.lock().clear();
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use app_net::{encode_token, tokenize};
+use parking_lot::Mutex;
+
+/// Cuántas claves sigue `TopKeys` y cada cuántos accesos cuenta uno.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TopKeysConfig {
+    /// Con 0 no se cuenta nada.
+    pub capacity: usize,
+    /// Se cuenta, al azar, uno de cada `sample` accesos; 1 los cuenta todos.
+    pub sample: u32,
+}
+
+impl Default for TopKeysConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 0,
+            sample: 16,
+        }
+    }
+}
+
+/// Lo que se sabe de una clave, ya multiplicado por el muestreo.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyCount {
+    pub key: String,
+    /// Accesos estimados; puede pasarse del real hasta en `error`.
+    pub count: u64,
+    /// Lo que tenía contado la clave a la que ésta reemplazó al llegar con la tabla llena.
+    pub error: u64,
+    /// Los `GET` y las escrituras contados desde que la clave entró a la tabla.
+    pub reads: u64,
+    pub writes: u64,
+}
+
+impl KeyCount {
+    /// `count=.. reads=.. writes=.. error=.. key=..`; `key` va al final y entre comillas
+    /// si hace falta.
+    pub fn to_wire(&self) -> String {
+        format!(
+            "count={} reads={} writes={} error={} key={}",
+            self.count,
+            self.reads,
+            self.writes,
+            self.error,
+            encode_token(&self.key)
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Counter {
+    count: u64,
+    error: u64,
+    reads: u64,
+    writes: u64,
+}
+
+/// Las claves más pedidas al nodo, contadas sobre una muestra de sus accesos con
+/// Space-Saving: la tabla no pasa de `capacity` claves y una nueva con la tabla llena
+/// reemplaza a la de menos accesos heredando su cuenta. Las claves que de verdad son
+/// frecuentes quedan arriba; el master solo ve las lecturas que reparte, acá se ve lo que
+/// el shard atiende.
+pub struct TopKeys {
+    config: TopKeysConfig,
+    counters: Mutex<HashMap<String, Counter>>,
+}
+
+impl TopKeys {
+    pub fn new(config: TopKeysConfig) -> Self {
+        Self {
+            config,
+            counters: Mutex::new(HashMap::with_capacity(config.capacity)),
+        }
+    }
+
+    /// Cuenta un acceso a la clave de `payload` (su primer argumento) si cae en la
+    /// muestra; devuelve si lo contó.
+    pub fn observe(&self, write: bool, payload: &str) -> bool {
+        if self.config.capacity == 0
+            || (self.config.sample > 1 && fastrand::u32(..self.config.sample) != 0)
+        {
+            return false;
+        }
+        let Some(key) = tokenize(payload).next().filter(|key| !key.is_empty()) else {
+            return false;
+        };
+
+        let mut counters = self.counters.lock();
+        if !counters.contains_key(key.as_ref()) && counters.len() >= self.config.capacity {
+            // la tabla es chica: buscar la menor recorriéndola alcanza
+            let (evicted, least) = counters
+                .iter()
+                .min_by_key(|(_, counter)| counter.count)
+                .map(|(key, counter)| (key.clone(), counter.count))
+                .expect("capacity > 0");
+            counters.remove(&evicted);
+            counters.insert(
+                key.to_string(),
+                Counter {
+                    count: least,
+                    error: least,
+                    ..Counter::default()
+                },
+            );
+        }
+        let counter = counters.entry(key.into_owned()).or_default();
+        counter.count += 1;
+        if write {
+            counter.writes += 1;
+        } else {
+            counter.reads += 1;
+        }
+        true
+    }
+
+    /// Hasta `limit` claves, la de más accesos primero.
+    pub fn top(&self, limit: usize) -> Vec<KeyCount> {
+        let scale = u64::from(self.config.sample.max(1));
+        let mut keys: Vec<_> = self
+            .counters
+            .lock()
+            .iter()
+            .map(|(key, counter)| KeyCount {
+                key: key.clone(),
+                count: counter.count * scale,
+                error: counter.error * scale,
+                reads: counter.reads * scale,
+                writes: counter.writes * scale,
+            })
+            .collect();
+        keys.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+        keys.truncate(limit);
+        keys
+    }
+
+    pub fn len(&self) -> usize {
+        self.counters.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn reset(&self) {
+        self.counters.lock().clear();
+    }
+}
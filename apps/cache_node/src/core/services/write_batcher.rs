@@ -0,0 +1,162 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+use tokio::sync::{mpsc, oneshot};
+use tokio_util::sync::CancellationToken;
+
+use crate::core::{domain::services::CacheService, services::BatchPut};
+
+/// Cuántos `PUT` junta `WriteBatcher` y cuánto los espera.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteBatching {
+    /// Desde que llega el primero del lote; con cero se agrupan solo los que ya estaban
+    /// esperando.
+    pub window: Duration,
+    pub max_ops: usize,
+}
+
+impl Default for WriteBatching {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_micros(200),
+            max_ops: 64,
+        }
+    }
+}
+
+struct PendingPut {
+    put: BatchPut<String, String>,
+    done: oneshot::Sender<bool>,
+}
+
+/// Junta los `PUT` sin condición que llegan casi juntos y los aplica con un solo
+/// `CacheService::put_batch`, para que el lock del orden de desalojo se tome una vez por
+/// lote y no una por `PUT`. Cada `PUT` espera a lo sumo `window` a que se llene su lote.
+pub struct WriteBatcher<C> {
+    cache: Arc<C>,
+    config: WriteBatching,
+    tx: mpsc::UnboundedSender<PendingPut>,
+    /// Lo toma `run`.
+    rx: Mutex<Option<mpsc::UnboundedReceiver<PendingPut>>>,
+    batches: AtomicU64,
+    batched: AtomicU64,
+}
+
+impl<C: CacheService> WriteBatcher<C> {
+    pub fn new(cache: Arc<C>, config: WriteBatching) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            cache,
+            config: WriteBatching {
+                max_ops: config.max_ops.max(1),
+                ..config
+            },
+            tx,
+            rx: Mutex::new(Some(rx)),
+            batches: AtomicU64::new(0),
+            batched: AtomicU64::new(0),
+        }
+    }
+
+    /// Como `CacheService::put`, pero aplicado con los demás de su lote. Si `run` ya no
+    /// corre (p. ej. en pleno apagado) se escribe solo.
+    pub async fn put(
+        &self,
+        key: String,
+        value: String,
+        expires_at: Option<u64>,
+        tags: &[String],
+    ) -> bool {
+        let (done, stored) = oneshot::channel();
+        let pending = PendingPut {
+            put: BatchPut {
+                key,
+                value,
+                expires_at,
+                tags: tags.to_vec(),
+            },
+            done,
+        };
+        if let Err(mpsc::error::SendError(pending)) = self.tx.send(pending) {
+            let put = pending.put;
+            return self
+                .cache
+                .put(put.key, put.value, put.expires_at, &put.tags)
+                .await;
+        }
+        // si `run` se corta sin aplicar el lote, cuenta como no escrito
+        stored.await.unwrap_or(false)
+    }
+
+    /// Lotes aplicados y `PUT` que entraron en ellos.
+    pub fn counts(&self) -> (u64, u64) {
+        (
+            self.batches.load(Ordering::Relaxed),
+            self.batched.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Arma y aplica lotes hasta que se cancele `cancel`. Solo corre uno.
+    pub async fn run(self: Arc<Self>, cancel: CancellationToken) {
+        let Some(mut rx) = self.rx.lock().take() else {
+            return;
+        };
+        let mut batch = Vec::with_capacity(self.config.max_ops);
+        loop {
+            let first = tokio::select! {
+                _ = cancel.cancelled() => break,
+                first = rx.recv() => first,
+            };
+            let Some(first) = first else { break };
+            batch.push(first);
+            self.fill(&mut rx, &mut batch).await;
+            self.apply(&mut batch).await;
+        }
+        // los que quedaron en la cola se escriben igual antes de salir
+        rx.close();
+        while let Ok(pending) = rx.try_recv() {
+            batch.push(pending);
+        }
+        if !batch.is_empty() {
+            self.apply(&mut batch).await;
+        }
+    }
+
+    /// Suma al lote lo que llegue dentro de la ventana. El timer de tokio no baja del
+    /// milisegundo, así que en vez de dormir se cede el hilo hasta que vence.
+    async fn fill(
+        &self,
+        rx: &mut mpsc::UnboundedReceiver<PendingPut>,
+        batch: &mut Vec<PendingPut>,
+    ) {
+        let deadline = Instant::now() + self.config.window;
+        while batch.len() < self.config.max_ops {
+            match rx.try_recv() {
+                Ok(pending) => batch.push(pending),
+                Err(mpsc::error::TryRecvError::Empty) if Instant::now() < deadline => {
+                    tokio::task::yield_now().await
+                }
+                Err(_) => break,
+            }
+        }
+    }
+
+    async fn apply(&self, batch: &mut Vec<PendingPut>) {
+        let (puts, waiting): (Vec<_>, Vec<_>) = batch
+            .drain(..)
+            .map(|pending| (pending.put, pending.done))
+            .unzip();
+        self.batches.fetch_add(1, Ordering::Relaxed);
+        self.batched.fetch_add(puts.len() as u64, Ordering::Relaxed);
+        let stored = self.cache.put_batch(puts).await;
+        for (done, stored) in waiting.into_iter().zip(stored) {
+            let _ = done.send(stored);
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use tracing::info;
+
+use crate::core::domain::{models::Response, services::CacheService};
+
+/// Lo que acepta `CONFIG` en el nodo.
+const KEYS: [&str; 2] = ["capacity", "max-memory"];
+
+/// `GET` sin ajuste devuelve `capacity=<entradas> max-memory=<bytes>` (0 es sin tope), y con
+/// ajuste su valor. `SET capacity <n>` desaloja si hace falta y devuelve `capacity=<n>
+/// evicted=<cuántas>`; `SET max-memory <bytes>` cambia el tope que se anuncia al master.
+pub async fn exec_config<C: CacheService>(
+    cache: &C,
+    op: &str,
+    key: Option<&str>,
+    value: Option<&str>,
+) -> Response {
+    if let Some(key) = key
+        && !KEYS.contains(&key)
+    {
+        return Response::bad_request(format!("ajuste desconocido: {key}"));
+    }
+
+    match (op, key, value) {
+        ("GET", None, _) => {
+            let stats = cache.stats();
+            Response::Value(format!(
+                "capacity={} max-memory={}",
+                stats.capacity, stats.max_bytes
+            ))
+        }
+        ("GET", Some(key), _) => {
+            let stats = cache.stats();
+            let value = match key {
+                "capacity" => stats.capacity as u64,
+                _ => stats.max_bytes,
+            };
+            Response::Value(value.to_string())
+        }
+        ("SET", Some("capacity"), Some(value)) => {
+            let Some(capacity) = value.parse::<usize>().ok().filter(|n| *n > 0) else {
+                return Response::bad_request(format!("capacity: valor inválido: {value}"));
+            };
+            let evicted = cache.resize(capacity);
+            info!(capacity, evicted, "capacidad del cache cambiada");
+            Response::Value(format!("capacity={capacity} evicted={evicted}"))
+        }
+        ("SET", Some(_), Some(value)) => {
+            let Ok(max_bytes) = value.parse::<u64>() else {
+                return Response::bad_request(format!("max-memory: valor inválido: {value}"));
+            };
+            cache.set_max_bytes(Some(max_bytes).filter(|n| *n > 0));
+            info!(max_bytes, "tope de memoria cambiado");
+            Response::Value(format!("max-memory={max_bytes}"))
+        }
+        _ => Response::bad_request("CONFIG espera GET [ajuste] o SET <ajuste> <valor>"),
+    }
+}
@@ -0,0 +1,15 @@
+use app_net::DelPrefix;
+use tracing::trace;
+
+use crate::core::domain::{models::Response, services::CacheService};
+
+/// Cuántas claves con el prefijo había en el nodo: borradas, o solo contadas con `dry-run`.
+pub async fn exec_del_prefix<C: CacheService>(cache: &C, request: &DelPrefix) -> Response {
+    let count = if request.dry_run {
+        cache.count_prefix(&request.prefix).await
+    } else {
+        cache.remove_prefix(&request.prefix).await
+    };
+    trace!(prefix = %request.prefix, dry_run = request.dry_run, count, "del prefix");
+    Response::Integer(count as i64)
+}
@@ -0,0 +1,11 @@
+use crate::core::domain::{models::Response, services::CacheService};
+
+/// Devuelve `1` si la clave existía y `0` si no.
+pub async fn exec_del<C: CacheService>(cache: &C, key: String) -> Response {
+    if key.is_empty() {
+        return Response::Empty;
+    }
+
+    let removed = cache.remove(&key).await;
+    Response::Integer(i64::from(removed))
+}
@@ -0,0 +1,33 @@
+use app_net::{HashRange, SnapshotHeader, key_hash};
+
+use crate::core::{
+    domain::{models::Response, services::CacheService},
+    services::{Op, OpLog},
+};
+
+/// Como `exec_snapshot`, pero solo con las entradas cuya clave cae en `range` del anillo:
+/// lo que se lleva el rebalanceo cuando ese tramo cambia de dueño.
+pub async fn exec_export_range<C: CacheService>(
+    cache: &C,
+    op_log: &OpLog,
+    range: HashRange,
+) -> Response {
+    let order = op_log.lock_all().await;
+    let seq = op_log.head();
+    let entries: Vec<Op> = cache
+        .snapshot()
+        .into_iter()
+        .filter(|op| matches!(op, Op::Put { key, .. } if range.contains(key_hash(key))))
+        .collect();
+    drop(order);
+
+    let header = SnapshotHeader {
+        epoch: op_log.epoch().to_string(),
+        seq,
+        entries: entries.len() as u64,
+    };
+    let mut lines = Vec::with_capacity(entries.len() + 1);
+    lines.push(header.to_string());
+    lines.extend(entries.iter().map(|op| op.to_request().1));
+    Response::Values(lines)
+}
@@ -5,7 +5,57 @@ pub async fn exec_get<C: CacheService>(cache: &C, key: String) -> Response {
         return Response::Empty;
     }
     match cache.get(&key).await {
-        Some(v) => Response::OkValue(v),
+        Some(v) => Response::Value(v),
+        None => Response::OkEmpty,
+    }
+}
+
+/// `GET` con `IF-NOT-VERSION <versión>`: `NotModified` si la clave sigue en esa versión y
+/// si no el valor con la suya (ver `app_net::conditional`).
+pub async fn exec_get_if_not_version<C: CacheService>(
+    cache: &C,
+    key: String,
+    if_not_version: u64,
+) -> Response {
+    if key.is_empty() {
+        return Response::Empty;
+    }
+    match cache.get_versioned(&key).await {
+        Some((_, version)) if version == if_not_version => Response::NotModified { version },
+        Some((value, version)) => Response::Versioned { value, version },
+        None => Response::OkEmpty,
+    }
+}
+
+/// `GET` con `refresh=<ventana>`: el valor y `1`/`0` según si quien lee tiene que
+/// refrescarlo (ver `app_net::refresh`).
+pub async fn exec_get_refresh<C: CacheService>(cache: &C, key: String, window_ms: u64) -> Response {
+    if key.is_empty() {
+        return Response::Empty;
+    }
+    match cache.get_for_refresh(&key, window_ms).await {
+        Some((v, claimed)) => Response::Values(vec![v, u8::from(claimed).to_string()]),
+        None => Response::OkEmpty,
+    }
+}
+
+/// `GET` con `stale=<máximo>`: el valor aunque haya vencido hace menos de `máximo`, `1`/`0`
+/// según si quien lee tiene que refrescarlo y hace cuántos ms venció (ver
+/// `app_net::refresh`).
+pub async fn exec_get_stale<C: CacheService>(
+    cache: &C,
+    key: String,
+    max_stale_ms: u64,
+) -> Response {
+    if key.is_empty() {
+        return Response::Empty;
+    }
+    match cache.get_stale(&key, max_stale_ms).await {
+        Some((v, claimed, stale_for)) => {
+            // vencida en el mismo ms cuenta como 1, así 0 queda para las vigentes
+            let stale_ms = stale_for.map_or(0, |ms| ms.max(1));
+            Response::Values(vec![v, u8::from(claimed).to_string(), stale_ms.to_string()])
+        }
         None => Response::OkEmpty,
     }
 }
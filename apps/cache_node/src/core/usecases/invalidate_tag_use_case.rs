@@ -0,0 +1,14 @@
+use tracing::trace;
+
+use crate::core::domain::{models::Response, services::CacheService};
+
+/// Cuántas claves con `tag` había en el nodo (y se borraron).
+pub async fn exec_invalidate_tag<C: CacheService>(cache: &C, tag: &str) -> Response {
+    if tag.is_empty() {
+        return Response::bad_request("falta el tag");
+    }
+
+    let removed = cache.invalidate_tag(tag).await;
+    trace!(tag, removed = removed.len(), "invalidate tag");
+    Response::Integer(removed.len() as i64)
+}
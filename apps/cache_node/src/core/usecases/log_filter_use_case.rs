@@ -0,0 +1,18 @@
+use app_logging::LogControl;
+
+use crate::core::domain::models::Response;
+
+/// Sin filtro devuelve el activo; con filtro lo reemplaza y devuelve el nuevo.
+pub async fn exec_log_filter(filter: String) -> Response {
+    match app_logging::global() {
+        Ok(control) => apply_log_filter(control, &filter),
+        Err(e) => Response::from_error(&e),
+    }
+}
+
+pub fn apply_log_filter(control: &LogControl, filter: &str) -> Response {
+    match control.apply(filter) {
+        Ok(current) => Response::Value(current),
+        Err(e) => Response::from_error(&e),
+    }
+}
@@ -0,0 +1,14 @@
+use crate::core::domain::{models::Response, services::CacheService};
+
+/// `version=.. expires_at=.. size=.. last_access=.. expired=.. stale=..`, o `EMPTY` si la
+/// clave no está. No cuenta como acceso a la clave.
+pub async fn exec_meta<C: CacheService>(cache: &C, key: String) -> Response {
+    if key.is_empty() {
+        return Response::Empty;
+    }
+
+    match cache.meta(&key).await {
+        Some(meta) => Response::Value(meta.to_string()),
+        None => Response::Empty,
+    }
+}
@@ -1,7 +1,39 @@
+pub mod config_use_case;
+pub mod del_prefix_use_case;
+pub mod del_use_case;
+pub mod export_range_use_case;
 pub mod get_use_case;
+pub mod invalidate_tag_use_case;
+pub mod log_filter_use_case;
+pub mod meta_use_case;
+pub mod multi_use_case;
+pub mod peek_use_case;
 pub mod ping_use_case;
 pub mod put_use_case;
+pub mod rename_use_case;
+pub mod replicate_from_use_case;
+pub mod set_role_use_case;
+pub mod slow_log_use_case;
+pub mod snapshot_use_case;
+pub mod stats_use_case;
+pub mod top_keys_use_case;
 
-pub use self::get_use_case::exec_get;
+pub use self::config_use_case::exec_config;
+pub use self::del_prefix_use_case::exec_del_prefix;
+pub use self::del_use_case::exec_del;
+pub use self::export_range_use_case::exec_export_range;
+pub use self::get_use_case::{exec_get, exec_get_if_not_version, exec_get_refresh, exec_get_stale};
+pub use self::invalidate_tag_use_case::exec_invalidate_tag;
+pub use self::log_filter_use_case::exec_log_filter;
+pub use self::meta_use_case::exec_meta;
+pub use self::multi_use_case::exec_multi;
+pub use self::peek_use_case::exec_peek;
 pub use self::ping_use_case::exec_ping;
-pub use self::put_use_case::exec_put;
+pub use self::put_use_case::{exec_put, exec_put_batched, exec_put_if};
+pub use self::rename_use_case::exec_rename;
+pub use self::replicate_from_use_case::exec_replicate_from;
+pub use self::set_role_use_case::exec_set_role;
+pub use self::slow_log_use_case::exec_slow_log;
+pub use self::snapshot_use_case::exec_snapshot;
+pub use self::stats_use_case::exec_stats;
+pub use self::top_keys_use_case::exec_top_keys;
@@ -0,0 +1,38 @@
+use app_core::error::ErrorKind;
+use app_net::{TxCommand, response::body::EMPTY_PAYLOAD};
+use tracing::trace;
+
+use crate::core::{
+    domain::{models::Response, services::CacheService},
+    services::TxOutcome,
+};
+
+/// Aplica el lote y devuelve un valor por comando que no sea `WATCH`: el valor (o
+/// `EMPTY`) de un `GET`, la versión de un `VERSION`, `OK` de un `PUT` (`QUOTA` si no entró
+/// en la cuota de su namespace) y `1`/`0` de un `DEL`. Si un `WATCH` no coincide no se aplica nada y se responde 409.
+pub async fn exec_multi<C: CacheService>(cache: &C, commands: &[TxCommand]) -> Response {
+    trace!(commands = commands.len(), "multi");
+
+    match cache.transact(commands).await {
+        Ok(outcomes) => Response::Values(
+            outcomes
+                .into_iter()
+                .map(|outcome| match outcome {
+                    TxOutcome::Value(Some(value)) => (*value).clone(),
+                    TxOutcome::Value(None) => EMPTY_PAYLOAD.to_string(),
+                    TxOutcome::Version(version) => version.to_string(),
+                    TxOutcome::Stored => "OK".to_string(),
+                    TxOutcome::OverQuota => "QUOTA".to_string(),
+                    TxOutcome::Removed(removed) => u8::from(removed).to_string(),
+                })
+                .collect(),
+        ),
+        Err(conflict) => Response::error(
+            ErrorKind::Conflict,
+            format!(
+                "WATCH {}: la versión actual es {}",
+                conflict.key, conflict.version
+            ),
+        ),
+    }
+}
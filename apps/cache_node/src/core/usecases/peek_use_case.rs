@@ -0,0 +1,13 @@
+use crate::core::domain::{models::Response, services::CacheService};
+
+/// Como `exec_get`, pero la lectura no cuenta como acceso a la clave: para herramientas de
+/// monitoreo o reparación que no deberían cambiar qué se desaloja.
+pub async fn exec_peek<C: CacheService>(cache: &C, key: String) -> Response {
+    if key.is_empty() {
+        return Response::Empty;
+    }
+    match cache.peek(&key).await {
+        Some(v) => Response::Value(v),
+        None => Response::OkEmpty,
+    }
+}
@@ -1,20 +1,84 @@
+use app_core::error::ErrorKind;
+use app_net::PutCondition;
 use tracing::trace;
 
-use crate::core::domain::{models::Response, services::CacheService};
+use crate::core::{
+    domain::{models::Response, services::CacheService},
+    services::WriteBatcher,
+};
+
+/// Lo que se contesta sin escribir: clave o valor vacíos, o un `expires_at` inválido.
+fn rejected(key: &str, value: &str, expires_at: Option<u64>) -> Option<Response> {
+    if key.is_empty() || value.is_empty() {
+        return Some(Response::Empty);
+    }
+    if expires_at == Some(0) {
+        return Some(Response::bad_request("expires_at inválido: 0"));
+    }
+    None
+}
+
+fn stored(stored: bool) -> Response {
+    if !stored {
+        return Response::error(ErrorKind::QuotaExceeded, "cuota del namespace llena");
+    }
+    Response::OkEmpty
+}
 
 pub async fn exec_put<C: CacheService>(
     cache: &C,
     key: String,
     value: String,
-    ttl: Option<u64>,
+    expires_at: Option<u64>,
+    tags: &[String],
 ) -> Response {
-    if key.is_empty() || value.is_empty() {
-        return Response::Empty;
+    if let Some(res) = rejected(&key, &value, expires_at) {
+        return res;
     }
 
-    trace!("Putting key: {}, value: {}, ttl: {:?}", key, value, ttl);
+    trace!(key, value_len = value.len(), expires_at, "put");
 
-    cache.put(key, value, ttl).await;
+    stored(cache.put(key, value, expires_at, tags).await)
+}
 
-    Response::OkEmpty
+/// `exec_put` a través de `batcher`, junto con los demás `PUT` de su lote.
+pub async fn exec_put_batched<C: CacheService>(
+    batcher: &WriteBatcher<C>,
+    key: String,
+    value: String,
+    expires_at: Option<u64>,
+    tags: &[String],
+) -> Response {
+    if let Some(res) = rejected(&key, &value, expires_at) {
+        return res;
+    }
+
+    trace!(key, value_len = value.len(), expires_at, "put batched");
+
+    stored(batcher.put(key, value, expires_at, tags).await)
+}
+
+/// `PUT ... IF <condición>`: 412 si la condición no se cumple contra la entrada vigente.
+pub async fn exec_put_if<C: CacheService>(
+    cache: &C,
+    key: String,
+    value: String,
+    expires_at: Option<u64>,
+    tags: &[String],
+    condition: &PutCondition,
+) -> Response {
+    if let Some(res) = rejected(&key, &value, expires_at) {
+        return res;
+    }
+
+    trace!(key, value_len = value.len(), expires_at, %condition, "put if");
+
+    match cache.put_if(key, value, expires_at, tags, condition).await {
+        Ok(true) => Response::OkEmpty,
+        Ok(false) => Response::error(
+            ErrorKind::PreconditionFailed,
+            format!("la condición {condition} no se cumple"),
+        ),
+        Err(e) => Response::error(ErrorKind::QuotaExceeded, e.to_string()),
+    }
 }
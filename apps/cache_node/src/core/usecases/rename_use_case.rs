@@ -0,0 +1,25 @@
+use app_core::error::ErrorKind;
+use tracing::trace;
+
+use crate::core::{
+    domain::{models::Response, services::CacheService},
+    services::RenameOutcome,
+};
+
+/// `OK` si movió la entrada; 404 si `from` no está y 409 si `to` ya existe.
+pub async fn exec_rename<C: CacheService>(cache: &C, from: &str, to: &str) -> Response {
+    if from.is_empty() || to.is_empty() {
+        return Response::bad_request("faltan las claves");
+    }
+
+    let outcome = cache.rename(from, to).await;
+    trace!(from, to, ?outcome, "rename");
+    match outcome {
+        Ok(RenameOutcome::Moved) => Response::OkEmpty,
+        Ok(RenameOutcome::Missing) => Response::error(ErrorKind::NotFound, "la clave no existe"),
+        Ok(RenameOutcome::TargetExists) => {
+            Response::error(ErrorKind::Conflict, "la clave destino ya existe")
+        }
+        Err(e) => Response::error(ErrorKind::QuotaExceeded, e.to_string()),
+    }
+}
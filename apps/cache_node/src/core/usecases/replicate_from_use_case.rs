@@ -0,0 +1,24 @@
+use app_core::error::ErrorKind;
+
+use crate::core::domain::{models::Response, services::ReplicationService};
+
+/// `addr` vacío deja de replicar. Confirma con la dirección que queda activa.
+pub async fn exec_replicate_from(
+    replication: Option<&dyn ReplicationService>,
+    addr: String,
+) -> Response {
+    let Some(replication) = replication else {
+        return Response::error(
+            ErrorKind::Unavailable,
+            "replicación no disponible en este nodo",
+        );
+    };
+
+    let addr = addr.trim();
+    replication.follow((!addr.is_empty()).then_some(addr));
+
+    match replication.source() {
+        Some(source) => Response::Value(source),
+        None => Response::OkEmpty,
+    }
+}
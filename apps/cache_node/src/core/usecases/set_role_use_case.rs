@@ -0,0 +1,22 @@
+use tracing::info;
+
+use crate::core::domain::models::{NodeRole, Response, RoleState};
+
+/// Sin rol devuelve el actual; con rol lo cambia y confirma con el nuevo.
+pub async fn exec_set_role(state: &RoleState, role: String) -> Response {
+    if role.trim().is_empty() {
+        return Response::Value(state.get().to_string());
+    }
+
+    let role = match role.parse::<NodeRole>() {
+        Ok(role) => role,
+        Err(e) => return Response::bad_request(e),
+    };
+
+    let previous = state.set(role);
+    if previous != role {
+        info!(from = %previous, to = %role, "rol del nodo cambiado");
+    }
+
+    Response::Value(role.to_string())
+}
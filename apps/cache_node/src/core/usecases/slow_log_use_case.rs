@@ -0,0 +1,30 @@
+use crate::core::{domain::models::Response, services::SlowLog};
+
+/// Entradas que devuelve `SLOWLOG GET` sin cantidad.
+const DEFAULT_GET: usize = 10;
+
+/// `GET [n]` (las `n` más nuevas, una por valor), `LEN` o `RESET`. Sin subcomando, `GET`.
+pub async fn exec_slow_log(slow_log: &SlowLog, sub: String, arg: Option<String>) -> Response {
+    match sub.to_ascii_uppercase().as_str() {
+        "" | "GET" => {
+            let limit = match arg.as_deref().map(str::parse::<usize>) {
+                None => DEFAULT_GET,
+                Some(Ok(n)) => n,
+                Some(Err(_)) => return Response::bad_request("SLOWLOG GET: cantidad inválida"),
+            };
+            Response::Values(
+                slow_log
+                    .latest(limit)
+                    .iter()
+                    .map(|entry| entry.to_wire())
+                    .collect(),
+            )
+        }
+        "LEN" => Response::Integer(slow_log.len() as i64),
+        "RESET" => {
+            slow_log.reset();
+            Response::OkEmpty
+        }
+        other => Response::bad_request(format!("SLOWLOG: subcomando desconocido {other}")),
+    }
+}
@@ -0,0 +1,26 @@
+use app_net::SnapshotHeader;
+
+use crate::core::{
+    domain::{models::Response, services::CacheService},
+    services::OpLog,
+};
+
+/// `SnapshotHeader` y después el payload de un `PUT` por entrada vigente. La posición del log
+/// y el recorrido del cache se toman bajo `OpLog::lock_all`: ninguna escritura queda
+/// aplicada sin estar en el log, ni al revés.
+pub async fn exec_snapshot<C: CacheService>(cache: &C, op_log: &OpLog) -> Response {
+    let order = op_log.lock_all().await;
+    let seq = op_log.head();
+    let entries = cache.snapshot();
+    drop(order);
+
+    let header = SnapshotHeader {
+        epoch: op_log.epoch().to_string(),
+        seq,
+        entries: entries.len() as u64,
+    };
+    let mut lines = Vec::with_capacity(entries.len() + 1);
+    lines.push(header.to_string());
+    lines.extend(entries.iter().map(|op| op.to_request().1));
+    Response::Values(lines)
+}
@@ -0,0 +1,38 @@
+use app_net::NodeStats;
+
+use crate::core::domain::{models::Response, services::CacheService};
+
+/// Una línea con el total del cache (`app_net::NodeStats`) y después `<namespace> entries=.. bytes=.. max_entries=.. max_bytes=.. evictions=..
+/// rejected=..` por namespace (`-` es sin tope). Con `namespace` solo va ese, si se vio.
+pub async fn exec_stats<C: CacheService>(cache: &C, namespace: Option<String>) -> Response {
+    let total = cache.stats();
+    let total = NodeStats {
+        entries: total.entries as u64,
+        capacity: total.capacity as u64,
+        bytes: total.bytes,
+        max_bytes: total.max_bytes,
+        hits: total.hits,
+        misses: total.misses,
+        writes: total.writes,
+        evictions: total.evictions,
+        expirations: total.expirations,
+        invalidations: total.invalidations,
+        compactions: total.compactions,
+        compacted_bytes: total.compacted_bytes,
+        expiry_backlog: total.expiry_backlog,
+        expiry_tick_keys: total.expiry_tick_keys,
+        expiry_missed_ticks: total.expiry_missed_ticks,
+        expiry_reaped: total.expiry_reaped,
+        expiry_drift_ms: total.expiry_drift_ms,
+        expiry_drift_max_ms: total.expiry_drift_max_ms,
+    };
+    let mut lines = vec![total.to_string()];
+    lines.extend(
+        cache
+            .namespace_stats()
+            .into_iter()
+            .filter(|(name, _)| namespace.as_ref().is_none_or(|wanted| name == wanted))
+            .map(|(name, stats)| format!("{name} {stats}")),
+    );
+    Response::Values(lines)
+}
@@ -0,0 +1,31 @@
+use crate::core::{domain::models::Response, services::TopKeys};
+
+/// Claves que devuelve `TOPKEYS GET` sin cantidad.
+const DEFAULT_GET: usize = 10;
+
+/// `GET [n]` (las `n` claves más pedidas, una por valor), `LEN` o `RESET`. Sin subcomando,
+/// `GET`.
+pub async fn exec_top_keys(top_keys: &TopKeys, sub: String, arg: Option<String>) -> Response {
+    match sub.to_ascii_uppercase().as_str() {
+        "" | "GET" => {
+            let limit = match arg.as_deref().map(str::parse::<usize>) {
+                None => DEFAULT_GET,
+                Some(Ok(n)) => n,
+                Some(Err(_)) => return Response::bad_request("TOPKEYS GET: cantidad inválida"),
+            };
+            Response::Values(
+                top_keys
+                    .top(limit)
+                    .iter()
+                    .map(|key| key.to_wire())
+                    .collect(),
+            )
+        }
+        "LEN" => Response::Integer(top_keys.len() as i64),
+        "RESET" => {
+            top_keys.reset();
+            Response::OkEmpty
+        }
+        other => Response::bad_request(format!("TOPKEYS: subcomando desconocido {other}")),
+    }
+}
@@ -1,29 +1,336 @@
-use std::sync::Arc;
+use std::{
+    collections::HashSet,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
 
+use app_core::{
+    clock::{AppClock, Clock},
+    namespace::split_namespace,
+    supervisor::{ShutdownStage, Supervisor},
+};
+use app_net::{PutCondition, TxCommand};
 use async_trait::async_trait;
+use tokio::sync::broadcast;
 
-use crate::core::{domain::services::CacheService, services::Cache};
+use crate::core::{
+    domain::{models::KeyMeta, services::CacheService},
+    services::{
+        BatchPut, Cache, CacheStats, CompactValue, EvictionPolicy, ExpiryStrategy,
+        IdempotencyConfig, NamespaceAccounting, NamespaceQuotas, NamespaceStats, Op, QuotaExceeded,
+        RenameOutcome, TopKeysConfig, TxConflict, TxOutcome, TxStep, WriteBatching,
+    },
+};
 
+/// Cómo arma `InMemCache` su `Cache`.
+#[derive(Debug, Clone, Default)]
+pub struct CacheConfig {
+    pub eviction: EvictionPolicy,
+    /// Las claves `namespace:clave` se cuentan por namespace (ver `app_core::namespace`).
+    pub namespaces: NamespaceQuotas,
+    /// Memoria (clave más valor) que el nodo anuncia como tope en `STATS` y
+    /// `CACHE-PRESSURE`. El master deja de mandarle `PUT` al acercarse (ver
+    /// `MemoryWatermarks`); el nodo no rechaza nada por esto.
+    pub max_bytes: Option<u64>,
+    pub expiry: ExpiryStrategy,
+    /// Cuánto se guardan las entradas vencidas para los `GET ... stale=` (ver
+    /// `Cache::set_stale_grace`).
+    pub stale_grace: Duration,
+    /// Cada cuánto achicar las tablas que quedaron grandes (ver `Cache::compact`). Sin
+    /// intervalo no se compacta.
+    pub compaction: Option<Duration>,
+    /// Los `PUT` sin condición que llegan casi juntos se aplican en un solo lote (ver
+    /// `WriteBatcher`). Sin configurar, cada uno por su lado.
+    pub write_batching: Option<WriteBatching>,
+    /// Cuánto se recuerdan los tokens `idem=` de los `PUT` y `DEL` (ver
+    /// `IdempotencyCache`).
+    pub idempotency: IdempotencyConfig,
+    /// Cuántas claves cuenta el nodo para `TOPKEYS` y con qué muestreo (ver `TopKeys`).
+    pub top_keys: TopKeysConfig,
+}
+
+/// El `Cache` del nodo, con los valores como `CompactValue`.
 pub struct InMemCache {
-    cache: Arc<Cache<String, String>>,
+    cache: Arc<Cache<String, CompactValue>>,
+    /// 0 es sin tope; se cambia con `CONFIG SET max-memory`.
+    max_bytes: AtomicU64,
 }
 
 impl InMemCache {
     pub fn new() -> Self {
-        let cache: Arc<Cache<String, String>> = Cache::new();
+        let cache: Arc<Cache<String, CompactValue>> = Cache::new();
 
         cache.start_reaper();
 
-        Self { cache }
+        Self {
+            cache,
+            max_bytes: AtomicU64::new(0),
+        }
+    }
+
+    /// Igual que `new`, pero el reaper corre bajo `supervisor` y se detiene al apagar.
+    pub fn with_supervisor(supervisor: &Supervisor) -> Self {
+        Self::with_supervisor_and_clock(supervisor, Arc::new(AppClock::new()))
+    }
+
+    pub fn with_supervisor_and_clock(supervisor: &Supervisor, clock: Arc<dyn Clock>) -> Self {
+        Self::with_config(supervisor, clock, CacheConfig::default())
+    }
+
+    pub fn with_config(
+        supervisor: &Supervisor,
+        clock: Arc<dyn Clock>,
+        config: CacheConfig,
+    ) -> Self {
+        let namespaces = NamespaceAccounting::new(
+            config.namespaces,
+            |key: &String| split_namespace(key).map(|(namespace, _)| namespace),
+            |key: &String, value: &CompactValue| (key.len() + value.len()) as u64,
+        );
+        let cache: Arc<Cache<String, CompactValue>> = Cache::new_with_namespaces(
+            1024,
+            1024,
+            1000,
+            clock,
+            config.eviction,
+            Some(namespaces),
+            config.expiry,
+        );
+        cache.set_stale_grace(config.stale_grace);
+
+        let reaper = cache.clone();
+        supervisor.spawn("cache-reaper", ShutdownStage::Background, |token| {
+            reaper.reaper(token)
+        });
+        if let Some(every) = config.compaction {
+            let compactor = cache.clone();
+            supervisor.spawn(
+                "cache-compaction",
+                ShutdownStage::Background,
+                move |token| compactor.compactor(every, token),
+            );
+        }
+
+        Self {
+            cache,
+            max_bytes: AtomicU64::new(config.max_bytes.unwrap_or(0)),
+        }
+    }
+
+    /// Entradas vigentes como `Op::Put` (el TTL es el `expires_at` absoluto, con sus tags), para el SYNC
+    /// completo de una réplica.
+    pub fn snapshot(&self) -> Vec<Op> {
+        self.snapshot_iter().collect()
+    }
+
+    /// Como `snapshot`, de a una entrada y sin frenar las escrituras (ver
+    /// `Cache::snapshot_iter`).
+    pub fn snapshot_iter(&self) -> impl Iterator<Item = Op> + '_ {
+        self.cache.snapshot_iter().map(|entry| Op::Put {
+            value: entry.value.to_string(),
+            expires_at: entry.expires_at.as_ref().map(|t| t.as_millis_u64()),
+            tags: self.cache.tags(&entry.key),
+            key: entry.key,
+        })
+    }
+
+    /// Borra las claves que no están en `keep`: lo que le sobra a una réplica después de un
+    /// SYNC completo (borrados que se perdió mientras estaba desconectada). Devuelve cuántas
+    /// borró.
+    pub fn retain_only(&self, keep: &HashSet<String>) -> usize {
+        self.cache.invalidate_where(|key| !keep.contains(key)).len()
+    }
+
+    /// Cambia el vencimiento de una entrada vigente sin tocar su valor ni sus tags. Devuelve
+    /// la escritura para el op-log, o `None` si la clave no está.
+    pub fn touch(&self, key: &str, expires_at: Option<u64>) -> Option<Op> {
+        let key = key.to_string();
+        loop {
+            let meta = self.cache.meta(&key)?;
+            let value = (*meta.value).clone();
+            let tags = self.cache.tags(&key);
+
+            // se reescribe solo si nadie la cambió desde que se leyó
+            let mut gone = false;
+            let written =
+                self.cache
+                    .put_if(key.clone(), value.clone(), expires_at, &tags, |current| {
+                        gone = current.is_none();
+                        current.is_some_and(|(_, version)| version == meta.version)
+                    });
+            match written {
+                Ok(true) => {
+                    return Some(Op::Put {
+                        key,
+                        value: value.to_string(),
+                        expires_at,
+                        tags,
+                    });
+                }
+                Ok(false) if !gone => continue,
+                _ => return None,
+            }
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            max_bytes: self.max_bytes.load(Ordering::Relaxed),
+            ..self.cache.stats()
+        }
+    }
+
+    /// Ver `Cache::subscribe_expired`.
+    pub fn subscribe_expired(&self, capacity: usize) -> broadcast::Receiver<String> {
+        self.cache.subscribe_expired(capacity)
+    }
+}
+
+impl Default for InMemCache {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 #[async_trait]
 impl CacheService for InMemCache {
-    async fn put(&self, key: String, value: String, ttl: Option<u64>) {
-        self.cache.put(key, value, ttl);
+    async fn put(
+        &self,
+        key: String,
+        value: String,
+        expires_at: Option<u64>,
+        tags: &[String],
+    ) -> bool {
+        self.cache.put_tagged(key, value.into(), expires_at, tags)
+    }
+    async fn put_batch(&self, puts: Vec<BatchPut<String, String>>) -> Vec<bool> {
+        self.cache.put_batch(
+            puts.into_iter()
+                .map(|put| BatchPut {
+                    key: put.key,
+                    value: put.value.into(),
+                    expires_at: put.expires_at,
+                    tags: put.tags,
+                })
+                .collect(),
+        )
+    }
+    async fn put_if(
+        &self,
+        key: String,
+        value: String,
+        expires_at: Option<u64>,
+        tags: &[String],
+        condition: &PutCondition,
+    ) -> Result<bool, QuotaExceeded> {
+        self.cache
+            .put_if(key, value.into(), expires_at, tags, |current| {
+                condition.holds(current.map(|(value, version)| (value.as_str(), version)))
+            })
+    }
+    async fn get(&self, key: &str) -> Option<String> {
+        self.cache
+            .get(&key.to_string())
+            .map(|entry| entry.to_string())
+    }
+
+    async fn get_versioned(&self, key: &str) -> Option<(String, u64)> {
+        self.cache
+            .get_versioned(&key.to_string())
+            .map(|(value, version)| (value.to_string(), version))
+    }
+
+    async fn peek(&self, key: &str) -> Option<String> {
+        self.cache
+            .peek(&key.to_string())
+            .map(|entry| entry.to_string())
+    }
+    async fn get_for_refresh(&self, key: &str, window_ms: u64) -> Option<(String, bool)> {
+        self.cache
+            .get_for_refresh(&key.to_string(), window_ms, fastrand::f64())
+            .map(|(entry, claimed)| (entry.to_string(), claimed))
+    }
+    async fn get_stale(&self, key: &str, max_stale_ms: u64) -> Option<(String, bool, Option<u64>)> {
+        self.cache
+            .get_stale(&key.to_string(), max_stale_ms)
+            .map(|(entry, claimed, stale_for)| (entry.to_string(), claimed, stale_for))
+    }
+    async fn remove(&self, key: &str) -> bool {
+        self.cache.invalidate(&key.to_string())
+    }
+    async fn invalidate_tag(&self, tag: &str) -> Vec<String> {
+        self.cache.invalidate_tag(tag)
+    }
+    async fn remove_prefix(&self, prefix: &str) -> usize {
+        self.cache
+            .invalidate_where(|key| key.starts_with(prefix))
+            .len()
+    }
+    async fn count_prefix(&self, prefix: &str) -> usize {
+        self.cache.count_where(|key| key.starts_with(prefix))
+    }
+    async fn rename(&self, from: &str, to: &str) -> Result<RenameOutcome, QuotaExceeded> {
+        self.cache.rename(&from.to_string(), to.to_string())
+    }
+    async fn meta(&self, key: &str) -> Option<KeyMeta> {
+        let meta = self.cache.meta(&key.to_string())?;
+        let now = self.cache.clock.now_millis();
+        let overdue = meta
+            .expires_at
+            .as_ref()
+            .filter(|exp| exp.is_before_or_eq(&now))
+            .map(|exp| now.as_millis_u64().saturating_sub(exp.as_millis_u64()));
+        Some(KeyMeta {
+            version: meta.version,
+            expired: overdue.is_some(),
+            stale: overdue.is_some_and(|ms| ms < self.cache.stale_grace()),
+            expires_at: meta.expires_at.map(|t| t.as_millis_u64()),
+            size_bytes: key.len() + meta.value.len(),
+            last_access: meta.last_access.as_millis_u64(),
+        })
+    }
+    async fn transact(
+        &self,
+        commands: &[TxCommand],
+    ) -> Result<Vec<TxOutcome<String>>, TxConflict<String>> {
+        let mut watches = Vec::new();
+        let mut steps = Vec::with_capacity(commands.len());
+        for command in commands.iter().cloned() {
+            match command {
+                TxCommand::Watch { key, version } => watches.push((key, version)),
+                TxCommand::Get { key } => steps.push(TxStep::Get(key)),
+                TxCommand::Version { key } => steps.push(TxStep::Version(key)),
+                TxCommand::Put { key, value, ttl } => steps.push(TxStep::Put {
+                    key,
+                    value: value.into(),
+                    expires_at: ttl,
+                }),
+                TxCommand::Del { key } => steps.push(TxStep::Del(key)),
+            }
+        }
+        let outcomes = self.cache.transact(&watches, steps)?;
+        Ok(outcomes
+            .into_iter()
+            .map(|outcome| outcome.map_value(CompactValue::to_string))
+            .collect())
+    }
+    fn stats(&self) -> CacheStats {
+        InMemCache::stats(self)
+    }
+    fn resize(&self, capacity: usize) -> usize {
+        self.cache.resize(capacity)
+    }
+    fn set_max_bytes(&self, max_bytes: Option<u64>) {
+        self.max_bytes
+            .store(max_bytes.unwrap_or(0), Ordering::Relaxed);
+    }
+    fn snapshot(&self) -> Vec<Op> {
+        InMemCache::snapshot(self)
     }
-    async fn get(&self, key: &String) -> Option<String> {
-        self.cache.get(&key).map(|entry| (*entry).clone())
+    fn namespace_stats(&self) -> Vec<(String, NamespaceStats)> {
+        self.cache.namespace_stats()
     }
 }
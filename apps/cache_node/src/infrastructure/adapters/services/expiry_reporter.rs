@@ -0,0 +1,37 @@
+use std::sync::Arc;
+
+use app_net::{EventData, Socket, encode_args, event::KEYS_EXPIRED};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::debug;
+
+use crate::infrastructure::adapters::services::cache_service::InMemCache;
+
+/// Claves vencidas que se juntan en un `EVT KEYS-EXPIRED`, y las que se esperan sin
+/// mandar: si el master no da abasto, las que sobran no se avisan.
+pub const MAX_EXPIRED_PER_EVENT: usize = 1024;
+
+/// Avisa al master por `socket` cada clave que vence (`EVT KEYS-EXPIRED`, sin respuesta)
+/// hasta que la conexión se cierre. Las que vencen juntas van en el mismo evento.
+pub async fn report_expired_keys(cache: Arc<InMemCache>, socket: Arc<Socket>) {
+    let mut expired = cache.subscribe_expired(MAX_EXPIRED_PER_EVENT);
+    loop {
+        let mut keys = match expired.recv().await {
+            Ok(key) => vec![key],
+            Err(RecvError::Lagged(skipped)) => {
+                debug!(target: "conn", skipped, "claves vencidas sin avisar al master");
+                continue;
+            }
+            Err(RecvError::Closed) => return,
+        };
+        while keys.len() < MAX_EXPIRED_PER_EVENT
+            && let Ok(key) = expired.try_recv()
+        {
+            keys.push(key);
+        }
+
+        let event = EventData::new(KEYS_EXPIRED, encode_args(keys.iter().map(String::as_str)));
+        if socket.send_evt(&event).is_err() {
+            break;
+        }
+    }
+}
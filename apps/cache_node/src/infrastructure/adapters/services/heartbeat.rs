@@ -0,0 +1,69 @@
+use std::{sync::Arc, time::Duration};
+
+use app_core::clock::Clock;
+use app_net::{
+    EventData, Socket,
+    event::{ClockEcho, HEARTBEAT, Heartbeat},
+};
+use parking_lot::Mutex;
+use tokio::time::{self, MissedTickBehavior};
+
+/// El último `HEARTBEAT` que contestó el master de una conexión, para devolvérselo en el
+/// próximo latido (ver `app_net::event::Heartbeat`).
+#[derive(Default)]
+pub struct MasterEcho {
+    last: Mutex<Option<ClockEcho>>,
+}
+
+impl MasterEcho {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Anota el `HEARTBEAT` del master con `payload` (su reloj), recibido en `now_ms`.
+    /// Un payload ilegible se ignora.
+    pub fn record(&self, payload: &str, now_ms: u64) {
+        if let Ok(master_ms) = payload.trim().parse() {
+            *self.last.lock() = Some(ClockEcho {
+                master_ms,
+                received_ms: now_ms,
+            });
+        }
+    }
+
+    fn last(&self) -> Option<ClockEcho> {
+        *self.last.lock()
+    }
+}
+
+/// Manda un `EVT HEARTBEAT` por `socket` cada `every` hasta que la conexión se cierre,
+/// el primero apenas arranca, con el reloj de `clock` y el eco de `echo`. Va por el mismo
+/// writer que las respuestas, así que un nodo trabado deja de latir aunque la conexión
+/// siga abierta.
+pub async fn send_heartbeats(
+    socket: Arc<Socket>,
+    every: Duration,
+    clock: Arc<dyn Clock>,
+    echo: Arc<MasterEcho>,
+) {
+    let mut interval = time::interval(every);
+    // tras una pausa no se mandan de golpe los atrasados: el master tiene que verla
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    let mut seq: u64 = 0;
+    loop {
+        interval.tick().await;
+        seq += 1;
+        let beat = Heartbeat {
+            seq,
+            sent_ms: Some(clock.now_millis().as_millis_u64()),
+            echo: echo.last(),
+        };
+        if socket
+            .send_evt(&EventData::new(HEARTBEAT, beat.to_string()))
+            .is_err()
+        {
+            break;
+        }
+    }
+}
@@ -0,0 +1,404 @@
+//! Protocolo de texto de memcached sobre el cache del nodo, para aplicaciones que ya hablan
+//! memcached. Se entienden `get`, `set`, `delete`, `touch`, `stats`, `version` y `quit`; el
+//! resto contesta `ERROR`.
+//!
+//! Estas escrituras no pasan por el master: el cliente reparte las claves entre nodos a su
+//! manera, y solo las ven las réplicas que siguen a este nodo por el op-log. Los flags no
+//! se guardan, así que solo se aceptan en `0`, y los valores tienen que ser UTF-8.
+
+use std::sync::Arc;
+
+use app_core::clock::Clock;
+use app_net::{Acceptor, BoxedStream};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
+
+use crate::{
+    core::{
+        domain::models::{Response, RoleState},
+        services::{Op, OpLog},
+        usecases::{exec_del, exec_get, exec_put},
+    },
+    infrastructure::adapters::services::cache_service::InMemCache,
+};
+
+/// Largo máximo de una clave, como en memcached.
+const MAX_KEY_LEN: usize = 250;
+/// Una línea de comando más larga que esto corta la conexión.
+const MAX_LINE_LEN: u64 = 8 * 1024;
+/// Tamaño máximo de un valor (el `-I` por defecto de memcached).
+const MAX_VALUE_LEN: usize = 1024 * 1024;
+/// `exptime` hasta este valor son segundos desde ahora; más arriba, un timestamp unix.
+const MAX_RELATIVE_EXPTIME: i64 = 30 * 24 * 60 * 60;
+
+/// Lo que comparten las conexiones memcached.
+#[derive(Clone)]
+pub struct Memcached {
+    pub cache: Arc<InMemCache>,
+    pub op_log: Arc<OpLog>,
+    pub role: Arc<RoleState>,
+    /// Resuelve los `exptime` relativos; el mismo reloj que el del cache.
+    pub clock: Arc<dyn Clock>,
+}
+
+/// Acepta clientes memcached en `acceptor` hasta que se cancele `cancel`.
+pub async fn serve_memcached(
+    mut acceptor: Box<dyn Acceptor>,
+    memcached: Memcached,
+    cancel: CancellationToken,
+) {
+    loop {
+        let (stream, peer) = tokio::select! {
+            _ = cancel.cancelled() => break,
+            accepted = acceptor.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!(target: "memcached", "accept error: {e}");
+                    continue;
+                }
+            },
+        };
+
+        let memcached = memcached.clone();
+        let cancel = cancel.child_token();
+        tokio::spawn(async move {
+            if let Err(e) = memcached.serve(stream, cancel).await {
+                debug!(target: "memcached", %peer, "conexión cortada: {e}");
+            }
+        });
+    }
+}
+
+/// Vencimiento pedido con un `exptime` de memcached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Expiry {
+    Never,
+    At(u64),
+    /// Negativo o un timestamp que ya pasó: la clave vence en el acto.
+    Past,
+}
+
+impl Expiry {
+    fn from_exptime(exptime: i64, now_ms: u64) -> Self {
+        let at_ms = match exptime {
+            0 => return Expiry::Never,
+            ..0 => return Expiry::Past,
+            1..=MAX_RELATIVE_EXPTIME => now_ms.saturating_add(exptime as u64 * 1000),
+            _ => (exptime as u64).saturating_mul(1000),
+        };
+        if at_ms <= now_ms {
+            return Expiry::Past;
+        }
+        Expiry::At(at_ms)
+    }
+}
+
+/// Qué hacer con la conexión después de un comando.
+enum Flow {
+    Continue,
+    Quit,
+}
+
+impl Memcached {
+    async fn serve(&self, stream: BoxedStream, cancel: CancellationToken) -> std::io::Result<()> {
+        let (reader, mut writer) = tokio::io::split(stream);
+        let mut reader = BufReader::new(reader);
+        let mut line = Vec::new();
+
+        loop {
+            line.clear();
+            let mut limited = (&mut reader).take(MAX_LINE_LEN);
+            let n = tokio::select! {
+                _ = cancel.cancelled() => break,
+                n = limited.read_until(b'\n', &mut line) => n?,
+            };
+            if n == 0 {
+                break;
+            }
+            if line.last() != Some(&b'\n') {
+                // sin salto de línea dentro del tope: la conexión no se puede resincronizar
+                writer
+                    .write_all("CLIENT_ERROR línea demasiado larga\r\n".as_bytes())
+                    .await?;
+                break;
+            }
+
+            let mut out = Vec::new();
+            let flow = match std::str::from_utf8(&line) {
+                Ok(command) => {
+                    self.execute(command.trim_end(), &mut reader, &mut out)
+                        .await?
+                }
+                Err(_) => {
+                    out.extend_from_slice(b"CLIENT_ERROR comando no es UTF-8\r\n");
+                    Flow::Continue
+                }
+            };
+            if !out.is_empty() {
+                writer.write_all(&out).await?;
+            }
+            if matches!(flow, Flow::Quit) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Ejecuta una línea de comando y deja la respuesta en `out`. `reader` entrega el bloque
+    /// de datos de un `set`.
+    async fn execute<R: AsyncBufRead + Unpin>(
+        &self,
+        command: &str,
+        reader: &mut R,
+        out: &mut Vec<u8>,
+    ) -> std::io::Result<Flow> {
+        let args: Vec<&str> = command.split_ascii_whitespace().collect();
+        let Some((name, mut args)) = args.split_first() else {
+            out.extend_from_slice(ERROR.as_bytes());
+            return Ok(Flow::Continue);
+        };
+        let writes = matches!(*name, "set" | "delete" | "touch");
+        let noreply = writes && args.last() == Some(&"noreply");
+        if noreply {
+            args = &args[..args.len() - 1];
+        }
+
+        let reply = match *name {
+            "get" => self.get(args).await,
+            "set" => self.set(args, reader).await?,
+            "delete" => self.delete(args).await,
+            "touch" => self.touch(args).await,
+            "stats" if args.is_empty() => self.stats(),
+            "version" => format!("VERSION {}\r\n", env!("CARGO_PKG_VERSION")),
+            "quit" => return Ok(Flow::Quit),
+            _ => ERROR.to_string(),
+        };
+
+        // con noreply solo se contestan los errores
+        if !noreply || reply.starts_with("CLIENT_ERROR") || reply == ERROR {
+            out.extend_from_slice(reply.as_bytes());
+        }
+        Ok(Flow::Continue)
+    }
+
+    /// `get <clave>*`: las que no están no aparecen en la respuesta.
+    async fn get(&self, keys: &[&str]) -> String {
+        if keys.is_empty() {
+            return ERROR.to_string();
+        }
+        if let Some(invalid) = keys.iter().find_map(|key| check_key(key).err()) {
+            return invalid;
+        }
+
+        let mut reply = String::new();
+        for key in keys {
+            if let Response::Value(value) = exec_get(self.cache.as_ref(), key.to_string()).await {
+                reply.push_str(&format!("VALUE {key} 0 {}\r\n{value}\r\n", value.len()));
+            }
+        }
+        reply.push_str("END\r\n");
+        reply
+    }
+
+    /// `set <clave> <flags> <exptime> <bytes> [noreply]` seguido del bloque de datos.
+    async fn set<R: AsyncBufRead + Unpin>(
+        &self,
+        args: &[&str],
+        reader: &mut R,
+    ) -> std::io::Result<String> {
+        let [key, flags, exptime, len] = args else {
+            return Ok(bad_format());
+        };
+        let (Ok(flags), Ok(exptime), Ok(len)) = (
+            flags.parse::<u32>(),
+            exptime.parse::<i64>(),
+            len.parse::<usize>(),
+        ) else {
+            return Ok(bad_format());
+        };
+
+        // el bloque se consume siempre, aunque el set no siga, para no perder el hilo
+        if len > MAX_VALUE_LEN {
+            let mut block = (&mut *reader).take(len as u64 + 2);
+            tokio::io::copy(&mut block, &mut tokio::io::sink()).await?;
+            return Ok("SERVER_ERROR valor demasiado grande\r\n".to_string());
+        }
+        let mut data = vec![0; len + 2];
+        reader.read_exact(&mut data).await?;
+        if !data.ends_with(b"\r\n") {
+            // como memcached, se descarta hasta el fin de la línea
+            if data.last() != Some(&b'\n') {
+                let mut rest = Vec::new();
+                (&mut *reader)
+                    .take(MAX_LINE_LEN)
+                    .read_until(b'\n', &mut rest)
+                    .await?;
+            }
+            return Ok("CLIENT_ERROR bloque de datos mal terminado\r\n".to_string());
+        }
+        data.truncate(len);
+
+        if let Err(invalid) = check_key(key) {
+            return Ok(invalid);
+        }
+        if flags != 0 {
+            return Ok("CLIENT_ERROR solo se aceptan flags 0\r\n".to_string());
+        }
+        let Ok(value) = String::from_utf8(data) else {
+            return Ok("CLIENT_ERROR el valor no es UTF-8\r\n".to_string());
+        };
+        if let Some(refused) = self.refuse_writes() {
+            return Ok(refused);
+        }
+
+        let expires_at = match self.expiry(exptime) {
+            Expiry::Past => {
+                self.remove(key).await;
+                return Ok(STORED.to_string());
+            }
+            Expiry::Never => None,
+            Expiry::At(at_ms) => Some(at_ms),
+        };
+        // el cambio y su anotación en el op-log van bajo la misma guarda, como en `PUT`
+        let _order = self.op_log.lock_keys(&[key]).await;
+        let res = exec_put(
+            self.cache.as_ref(),
+            key.to_string(),
+            value.clone(),
+            expires_at,
+            &[],
+        )
+        .await;
+        Ok(match res {
+            Response::OkEmpty => {
+                self.op_log.append(Op::Put {
+                    key: key.to_string(),
+                    value,
+                    expires_at,
+                    tags: Vec::new(),
+                });
+                STORED.to_string()
+            }
+            // el cache no guarda valores vacíos
+            Response::Empty => "NOT_STORED\r\n".to_string(),
+            Response::Error { msg, .. } => format!("SERVER_ERROR {msg}\r\n"),
+            other => format!("SERVER_ERROR respuesta inesperada {other:?}\r\n"),
+        })
+    }
+
+    /// `delete <clave> [0] [noreply]`; el `0` lo mandan clientes viejos.
+    async fn delete(&self, args: &[&str]) -> String {
+        let ([key] | [key, "0"]) = args else {
+            return bad_format();
+        };
+        if let Err(invalid) = check_key(key) {
+            return invalid;
+        }
+        if let Some(refused) = self.refuse_writes() {
+            return refused;
+        }
+
+        match self.remove(key).await {
+            true => "DELETED\r\n".to_string(),
+            false => NOT_FOUND.to_string(),
+        }
+    }
+
+    /// `touch <clave> <exptime> [noreply]`: nuevo vencimiento sin reescribir el valor.
+    async fn touch(&self, args: &[&str]) -> String {
+        let [key, exptime] = args else {
+            return bad_format();
+        };
+        let Ok(exptime) = exptime.parse::<i64>() else {
+            return bad_format();
+        };
+        if let Err(invalid) = check_key(key) {
+            return invalid;
+        }
+        if let Some(refused) = self.refuse_writes() {
+            return refused;
+        }
+
+        let touched = match self.expiry(exptime) {
+            Expiry::Past => self.remove(key).await,
+            Expiry::Never => self.touch_until(key, None).await,
+            Expiry::At(at_ms) => self.touch_until(key, Some(at_ms)).await,
+        };
+        match touched {
+            true => "TOUCHED\r\n".to_string(),
+            false => NOT_FOUND.to_string(),
+        }
+    }
+
+    async fn touch_until(&self, key: &str, expires_at: Option<u64>) -> bool {
+        let _order = self.op_log.lock_keys(&[key]).await;
+        match self.cache.touch(key, expires_at) {
+            Some(op) => {
+                self.op_log.append(op);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Los contadores de `STATS` con los nombres de memcached.
+    fn stats(&self) -> String {
+        let stats = self.cache.stats();
+        let lines = [
+            ("curr_items", stats.entries as u64),
+            ("total_items", stats.writes),
+            ("bytes", stats.bytes),
+            ("limit_maxbytes", stats.max_bytes),
+            ("get_hits", stats.hits),
+            ("get_misses", stats.misses),
+            ("evictions", stats.evictions),
+        ];
+
+        let mut reply = format!("STAT version {}\r\n", env!("CARGO_PKG_VERSION"));
+        for (name, value) in lines {
+            reply.push_str(&format!("STAT {name} {value}\r\n"));
+        }
+        reply.push_str("END\r\n");
+        reply
+    }
+
+    /// Borra la clave y, si estaba, anota el `DEL` en el op-log.
+    async fn remove(&self, key: &str) -> bool {
+        let _order = self.op_log.lock_keys(&[key]).await;
+        let removed = exec_del(self.cache.as_ref(), key.to_string()).await;
+        if removed != Response::Integer(1) {
+            return false;
+        }
+        self.op_log.append(Op::Del {
+            key: key.to_string(),
+        });
+        true
+    }
+
+    /// Una réplica en modo estricto no acepta escrituras, tampoco por acá.
+    fn refuse_writes(&self) -> Option<String> {
+        (!self.role.accepts_writes())
+            .then(|| "SERVER_ERROR réplica en modo estricto: no acepta escrituras\r\n".to_string())
+    }
+
+    fn expiry(&self, exptime: i64) -> Expiry {
+        Expiry::from_exptime(exptime, self.clock.now_millis().as_millis_u64())
+    }
+}
+
+const ERROR: &str = "ERROR\r\n";
+const STORED: &str = "STORED\r\n";
+const NOT_FOUND: &str = "NOT_FOUND\r\n";
+
+fn bad_format() -> String {
+    "CLIENT_ERROR formato de comando inválido\r\n".to_string()
+}
+
+/// Sin espacios ni caracteres de control y de hasta `MAX_KEY_LEN` bytes.
+fn check_key(key: &str) -> Result<(), String> {
+    if key.len() > MAX_KEY_LEN || key.chars().any(char::is_control) {
+        return Err("CLIENT_ERROR clave inválida\r\n".to_string());
+    }
+    Ok(())
+}
@@ -1 +1,6 @@
 pub mod cache_service;
+pub mod expiry_reporter;
+pub mod heartbeat;
+pub mod memcached_service;
+pub mod pressure_reporter;
+pub mod replication_service;
@@ -0,0 +1,48 @@
+use std::{sync::Arc, time::Duration};
+
+use app_net::{CachePressure, EventData, Socket, event::CACHE_PRESSURE};
+use tokio::time::{self, MissedTickBehavior};
+use tracing::trace;
+
+use crate::{
+    core::services::CacheStats, infrastructure::adapters::services::cache_service::InMemCache,
+};
+
+/// Reporte de `current` respecto de `previous` (los contadores del cache son acumulados).
+fn pressure_between(previous: &CacheStats, current: &CacheStats, every: Duration) -> CachePressure {
+    CachePressure {
+        evictions: current.evictions.saturating_sub(previous.evictions),
+        expirations: current.expirations.saturating_sub(previous.expirations),
+        invalidations: current.invalidations.saturating_sub(previous.invalidations),
+        entries: current.entries as u64,
+        capacity: current.capacity as u64,
+        interval_ms: every.as_millis() as u64,
+        bytes: current.bytes,
+        max_bytes: current.max_bytes,
+    }
+}
+
+/// Manda un `EVT CACHE-PRESSURE` por `socket` cada `every` hasta que la conexión se cierre.
+/// El primero sale al completar el primer intervalo.
+pub async fn report_cache_pressure(cache: Arc<InMemCache>, socket: Arc<Socket>, every: Duration) {
+    let mut interval = time::interval(every);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    interval.tick().await;
+
+    let mut previous = cache.stats();
+    loop {
+        interval.tick().await;
+
+        let current = cache.stats();
+        let report = pressure_between(&previous, &current, every);
+        previous = current;
+
+        trace!(target: "conn", %report, "cache pressure");
+        if socket
+            .send_evt(&EventData::new(CACHE_PRESSURE, report.to_string()))
+            .is_err()
+        {
+            break;
+        }
+    }
+}
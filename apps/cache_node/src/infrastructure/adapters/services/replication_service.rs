@@ -0,0 +1,397 @@
+//! Replicación nodo a nodo. El primario acepta réplicas en su listener de replicación
+//! (`serve_replicas`). Cada una se identifica con `SYNC <node_id> [<epoch> <seq>]`, donde
+//! `epoch`/`seq` es lo último que aplicó de este primario. Si el op-log todavía tiene lo que
+//! sigue, se retoma desde ahí; si no, recibe un SYNC completo (las entradas vigentes). En
+//! ambos casos el primario marca el punto de partida con `REQ <seq> SYNCED "<epoch>"
+//! "full"|"delta"` y después manda el op-log en orden. Si una réplica se atrasa más que lo
+//! que guarda el log, recibe otro SYNC completo. La réplica (`NodeReplication`) aplica las
+//! operaciones directamente en su cache, sin pasar por su propio op-log; al recibir un
+//! `SYNCED ... "full"` borra las claves que el snapshot no trajo.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use app_core::retry::{Backoff, RetryError, RetryPolicy, retry_with_backoff_until};
+use app_net::{
+    Acceptor, BoxedStream, Connector, ParsedMsg, RequestDataInput, encode_args, encode_token,
+    parse_line, tokenize,
+};
+use parking_lot::Mutex;
+use tokio::io::{AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use crate::{
+    core::{
+        domain::services::{CacheService, ReplicationService},
+        services::{Op, OpLog, op_log::Truncated},
+    },
+    infrastructure::adapters::services::cache_service::InMemCache,
+};
+
+/// Operaciones por escritura al socket de una réplica.
+const STREAM_BATCH: usize = 256;
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Acepta réplicas en `acceptor` hasta que se cancele `cancel`.
+pub async fn serve_replicas(
+    mut acceptor: Box<dyn Acceptor>,
+    cache: Arc<InMemCache>,
+    op_log: Arc<OpLog>,
+    cancel: CancellationToken,
+) {
+    loop {
+        let (stream, peer) = tokio::select! {
+            _ = cancel.cancelled() => break,
+            accepted = acceptor.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!(target: "repl", "accept error: {e}");
+                    continue;
+                }
+            },
+        };
+
+        let cache = cache.clone();
+        let op_log = op_log.clone();
+        let cancel = cancel.child_token();
+        tokio::spawn(async move {
+            if let Err(e) = stream_to_replica(stream, &peer.addr, cache, op_log, cancel).await {
+                debug!(target: "repl", %peer, "stream de replicación cortado: {e}");
+            }
+        });
+    }
+}
+
+async fn stream_to_replica(
+    stream: BoxedStream,
+    peer: &str,
+    cache: Arc<InMemCache>,
+    op_log: Arc<OpLog>,
+    cancel: CancellationToken,
+) -> std::io::Result<()> {
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut reader = BufReader::new(reader);
+
+    let mut line = String::new();
+    let (replica_id, position) =
+        match tokio::time::timeout(HANDSHAKE_TIMEOUT, reader.read_line(&mut line)).await {
+            Ok(Ok(n)) if n > 0 => match parse_handshake(&line) {
+                Some(handshake) => handshake,
+                None => return Ok(()),
+            },
+            _ => return Ok(()),
+        };
+
+    let mut changes = op_log.subscribe();
+    let resume = position.and_then(|(epoch, seq)| op_log.resume_point(&epoch, seq));
+    info!(target: "repl", %peer, replica_id, delta = resume.is_some(), "réplica conectada");
+
+    let mut next = match resume {
+        Some(next) => {
+            let marker = synced_line(&op_log, next - 1, "delta");
+            writer.write_all(marker.as_bytes()).await?;
+            next
+        }
+        None => full_sync(&mut writer, &cache, &op_log).await?,
+    };
+
+    loop {
+        match op_log.read_from(next, STREAM_BATCH) {
+            Ok(ops) if !ops.is_empty() => {
+                let mut out = String::new();
+                for (seq, op) in &ops {
+                    out.push_str(&op.to_line(*seq));
+                    next = seq + 1;
+                }
+                writer.write_all(out.as_bytes()).await?;
+                continue;
+            }
+            Ok(_) => {}
+            Err(Truncated { oldest }) => {
+                warn!(target: "repl", replica_id, next, oldest, "réplica atrasada; SYNC completo");
+                next = full_sync(&mut writer, &cache, &op_log).await?;
+                continue;
+            }
+        }
+
+        line.clear();
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            changed = changes.changed() => if changed.is_err() { break },
+            // la réplica no manda nada después del SYNC: solo se lee para notar el cierre
+            read = reader.read_line(&mut line) => if read? == 0 { break },
+        }
+    }
+
+    info!(target: "repl", %peer, replica_id, "réplica desconectada");
+    Ok(())
+}
+
+/// `SYNC <node_id> [<epoch> <seq>]` -> id de la réplica y hasta dónde aplicó.
+fn parse_handshake(line: &str) -> Option<(String, Option<(String, u64)>)> {
+    let mut parts = tokenize(line.trim());
+    if parts.next()? != "SYNC" {
+        return None;
+    }
+    let replica_id = parts.next()?.into_owned();
+    let position = match (parts.next(), parts.next().and_then(|s| s.parse().ok())) {
+        (Some(epoch), Some(seq)) => Some((epoch.into_owned(), seq)),
+        _ => None,
+    };
+    Some((replica_id, position))
+}
+
+fn synced_line(op_log: &OpLog, seq: u64, mode: &str) -> String {
+    RequestDataInput::new("SYNCED", &encode_args([op_log.epoch(), mode]))
+        .from_id(seq.to_string())
+        .to_string()
+}
+
+/// Manda todas las entradas vigentes (con secuencia 0: no mueven el offset de la réplica)
+/// y el marcador con la secuencia actual del log; devuelve la siguiente a enviar. Lo que
+/// se escriba mientras se arma el snapshot puede llegar dos veces; reaplicarlo no cambia
+/// el resultado.
+async fn full_sync<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    cache: &InMemCache,
+    op_log: &OpLog,
+) -> std::io::Result<u64> {
+    let head = op_log.head();
+    let mut snapshot = cache.snapshot_iter();
+
+    loop {
+        let out: String = snapshot
+            .by_ref()
+            .take(STREAM_BATCH)
+            .map(|op| op.to_line(0))
+            .collect();
+        if out.is_empty() {
+            break;
+        }
+        writer.write_all(out.as_bytes()).await?;
+    }
+    writer
+        .write_all(synced_line(op_log, head, "full").as_bytes())
+        .await?;
+
+    Ok(head + 1)
+}
+
+/// Hasta dónde aplicó la réplica del log de su primario.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReplicationPosition {
+    pub epoch: Option<String>,
+    pub seq: u64,
+    /// SYNC completos recibidos (el resto de las reconexiones retomaron por offset).
+    pub full_syncs: u64,
+}
+
+/// Lado réplica: sigue al primario que indique el master (`REPLICATE-FROM`).
+pub struct NodeReplication {
+    node_id: Arc<str>,
+    cache: Arc<InMemCache>,
+    connector: Arc<dyn Connector>,
+    /// Se cancela al apagar el nodo.
+    shutdown: CancellationToken,
+    current: Mutex<Option<(String, CancellationToken)>>,
+    position: Arc<Mutex<ReplicationPosition>>,
+}
+
+impl NodeReplication {
+    pub fn new(
+        node_id: &str,
+        cache: Arc<InMemCache>,
+        connector: Arc<dyn Connector>,
+        shutdown: CancellationToken,
+    ) -> Self {
+        Self {
+            node_id: Arc::from(node_id),
+            cache,
+            connector,
+            shutdown,
+            current: Mutex::new(None),
+            position: Arc::default(),
+        }
+    }
+
+    /// Secuencia del primario de la última operación aplicada.
+    pub fn applied_seq(&self) -> u64 {
+        self.position.lock().seq
+    }
+
+    pub fn position(&self) -> ReplicationPosition {
+        self.position.lock().clone()
+    }
+}
+
+impl ReplicationService for NodeReplication {
+    fn follow(&self, primary_addr: Option<&str>) {
+        let mut current = self.current.lock();
+
+        if current.as_ref().map(|(addr, _)| addr.as_str()) == primary_addr {
+            return;
+        }
+        if let Some((addr, token)) = current.take() {
+            info!(target: "repl", primary = addr, "se deja de replicar");
+            token.cancel();
+        }
+
+        let Some(addr) = primary_addr else {
+            return;
+        };
+
+        let token = self.shutdown.child_token();
+        *current = Some((addr.to_string(), token.clone()));
+
+        tokio::spawn(follow_loop(
+            addr.to_string(),
+            self.node_id.clone(),
+            self.cache.clone(),
+            self.connector.clone(),
+            self.position.clone(),
+            token,
+        ));
+    }
+
+    fn source(&self) -> Option<String> {
+        self.current.lock().as_ref().map(|(addr, _)| addr.clone())
+    }
+}
+
+async fn follow_loop(
+    addr: String,
+    node_id: Arc<str>,
+    cache: Arc<InMemCache>,
+    connector: Arc<dyn Connector>,
+    position: Arc<Mutex<ReplicationPosition>>,
+    cancel: CancellationToken,
+) {
+    let policy = RetryPolicy::default();
+    // entre streams: un primario que corta antes de sincronizar se reintenta cada vez más tarde
+    let mut backoff = Backoff::new(policy.clone());
+
+    loop {
+        let stream =
+            match retry_with_backoff_until(&policy, &cancel, |_| connector.connect(&addr)).await {
+                Ok(stream) => stream,
+                Err(RetryError::Cancelled { .. }) => return,
+                Err(e) => {
+                    warn!(target: "repl", primary = addr, "no se pudo conectar: {e}");
+                    continue;
+                }
+            };
+        info!(target: "repl", primary = addr, "replicando");
+
+        let mut synced = false;
+        let res = tokio::select! {
+            _ = cancel.cancelled() => return,
+            res = apply_stream(stream, &node_id, &cache, &position, &mut synced) => res,
+        };
+        match res {
+            Ok(()) => info!(target: "repl", primary = addr, "el primario cerró el stream"),
+            Err(e) => warn!(target: "repl", primary = addr, "stream de replicación: {e}"),
+        }
+
+        if synced {
+            backoff.reset();
+        }
+        tokio::select! {
+            _ = cancel.cancelled() => return,
+            _ = tokio::time::sleep(backoff.next_delay()) => {}
+        }
+    }
+}
+
+/// Aplica el stream del primario hasta que se corte; `synced` queda en `true` desde el
+/// primer `SYNCED`.
+async fn apply_stream(
+    stream: BoxedStream,
+    node_id: &str,
+    cache: &InMemCache,
+    position: &Mutex<ReplicationPosition>,
+    synced: &mut bool,
+) -> std::io::Result<()> {
+    let (reader, mut writer) = tokio::io::split(stream);
+    let handshake = {
+        let position = position.lock();
+        match &position.epoch {
+            Some(epoch) => format!("SYNC {node_id} {} {}\n", encode_token(epoch), position.seq),
+            None => format!("SYNC {node_id}\n"),
+        }
+    };
+    writer.write_all(handshake.as_bytes()).await?;
+
+    let mut reader = BufReader::new(reader);
+    let mut line = String::new();
+    // claves del SYNC completo en curso: las demás ya no están en el primario
+    let mut synced_keys = HashSet::new();
+
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 {
+            return Ok(());
+        }
+
+        let Ok(ParsedMsg::Req { data }) = parse_line(&line) else {
+            debug!(target: "repl", line = line.trim(), "línea inesperada en el stream");
+            continue;
+        };
+
+        let seq = data.id.parse::<u64>().unwrap_or(0);
+
+        if data.action == "SYNCED" {
+            let mut args = tokenize(&data.payload);
+            let epoch = args.next().map(|e| e.into_owned());
+            let full = args.next().is_some_and(|mode| mode == "full");
+
+            if full {
+                let removed = cache.retain_only(&synced_keys);
+                debug!(target: "repl", removed, "claves que el primario ya no tiene");
+            }
+            synced_keys = HashSet::new();
+            *synced = true;
+
+            let mut position = position.lock();
+            position.epoch = epoch;
+            position.seq = seq;
+            position.full_syncs += u64::from(full);
+            debug!(target: "repl", seq, full, "sincronizado con el primario");
+            continue;
+        }
+
+        match Op::parse(data.action, &data.payload) {
+            Some(Op::Put {
+                key,
+                value,
+                expires_at,
+                tags,
+            }) => {
+                if seq == 0 {
+                    synced_keys.insert(key.clone());
+                }
+                // con las mismas cuotas que el primario, lo que él aceptó acá también entra
+                cache.put(key, value, expires_at, &tags).await;
+            }
+            Some(Op::Del { key }) => {
+                cache.remove(&key).await;
+            }
+            Some(Op::InvalidateTag { tag }) => {
+                cache.invalidate_tag(&tag).await;
+            }
+            Some(Op::DelPrefix { prefix }) => {
+                cache.remove_prefix(&prefix).await;
+            }
+            Some(Op::Rename { from, to }) => {
+                let _ = cache.rename(&from, &to).await;
+            }
+            None => continue,
+        }
+
+        // las entradas del SYNC completo llegan con 0
+        if seq > 0 {
+            position.lock().seq = seq;
+        }
+    }
+}
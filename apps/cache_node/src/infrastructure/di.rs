@@ -1,21 +1,126 @@
 use std::sync::Arc;
 
+use app_core::{
+    clock::{AppClock, Clock},
+    id::new_sortable_id,
+    supervisor::{ShutdownStage, Supervisor},
+};
+use app_net::{Connector, MonitorHub, TcpConnector};
+
 use crate::{
-    core::services::request_controller_service::RequestControllerService,
-    infrastructure::adapters::services::cache_service::InMemCache,
+    core::{
+        commands::{CommandDeps, SlowLogCommand, TopKeysCommand, register_builtins},
+        domain::models::RoleState,
+        services::{
+            CommandRegistry, IdempotencyCache, OpLog, SlowLog, SlowLogConfig, TopKeys,
+            WriteBatcher, request_controller_service::RequestControllerService,
+        },
+    },
+    infrastructure::adapters::services::{
+        cache_service::{CacheConfig, InMemCache},
+        replication_service::NodeReplication,
+    },
 };
 
 pub struct CacheNodeModule {
-    pub request_controller_service: Arc<RequestControllerService<InMemCache>>,
+    pub request_controller_service: Arc<RequestControllerService>,
+    pub role: Arc<RoleState>,
+    pub cache: Arc<InMemCache>,
+    pub op_log: Arc<OpLog>,
+    pub replication: Arc<NodeReplication>,
+    /// Suscripciones a `MONITOR` de las conexiones a masters.
+    pub monitor: Arc<MonitorHub>,
+    pub slow_log: Arc<SlowLog>,
+    /// Vacío si `CacheConfig::top_keys` no tiene capacidad.
+    pub top_keys: Arc<TopKeys>,
+    /// Con `CacheConfig::write_batching`, por donde pasan los `PUT` sin condición.
+    pub write_batcher: Option<Arc<WriteBatcher<InMemCache>>>,
+    /// El del cache; también fecha los `HEARTBEAT` a los masters.
+    pub clock: Arc<dyn Clock>,
 }
 
 impl CacheNodeModule {
-    pub fn init_dependencies() -> Self {
-        let cache = Arc::new(InMemCache::new());
-        let request_controller_service = Arc::new(RequestControllerService::new(cache));
+    pub fn init_dependencies(supervisor: &Supervisor) -> Self {
+        Self::init_with_clock(supervisor, Arc::new(AppClock::new()))
+    }
+
+    pub fn init_with_clock(supervisor: &Supervisor, clock: Arc<dyn Clock>) -> Self {
+        Self::init_with(
+            supervisor,
+            clock,
+            Arc::new(RoleState::default()),
+            &new_sortable_id(),
+            Arc::new(TcpConnector),
+            SlowLogConfig::default(),
+            CacheConfig::default(),
+        )
+    }
+
+    /// `node_id` y `connector` los usa la réplica para conectarse a su primario; el reloj
+    /// también fecha las entradas del `SLOWLOG`. `cache` elige qué saca el cache al
+    /// llenarse y las cuotas de cada namespace.
+    pub fn init_with(
+        supervisor: &Supervisor,
+        clock: Arc<dyn Clock>,
+        role: Arc<RoleState>,
+        node_id: &str,
+        connector: Arc<dyn Connector>,
+        slow_log: SlowLogConfig,
+        cache: CacheConfig,
+    ) -> Self {
+        let slow_log = Arc::new(SlowLog::new(slow_log, clock.clone()));
+        let write_batching = cache.write_batching;
+        let top_keys = Arc::new(TopKeys::new(cache.top_keys));
+        let idempotency = Arc::new(IdempotencyCache::new(cache.idempotency, clock.clone()));
+        let cache = Arc::new(InMemCache::with_config(supervisor, clock.clone(), cache));
+        let write_batcher = write_batching.map(|config| {
+            let batcher = Arc::new(WriteBatcher::new(cache.clone(), config));
+            let run = batcher.clone();
+            supervisor.spawn("write-batcher", ShutdownStage::Background, |token| {
+                run.run(token)
+            });
+            batcher
+        });
+        let op_log = Arc::new(OpLog::default());
+        let replication = Arc::new(NodeReplication::new(
+            node_id,
+            cache.clone(),
+            connector,
+            supervisor.token(),
+        ));
+
+        let mut commands = CommandRegistry::new();
+        register_builtins(
+            &mut commands,
+            CommandDeps {
+                cache: cache.clone(),
+                role: role.clone(),
+                op_log: op_log.clone(),
+                replication: Some(replication.clone()),
+                clock: clock.clone(),
+                write_batcher: write_batcher.clone(),
+                idempotency: Some(idempotency),
+            },
+        );
+        commands.register(SlowLogCommand::new(slow_log.clone()));
+        commands.register(TopKeysCommand::new(top_keys.clone()));
+        let request_controller_service = Arc::new(
+            RequestControllerService::new(commands, role.clone())
+                .with_slow_log(slow_log.clone())
+                .with_top_keys(top_keys.clone()),
+        );
 
         Self {
             request_controller_service,
+            role,
+            cache,
+            op_log,
+            replication,
+            monitor: Arc::new(MonitorHub::new()),
+            slow_log,
+            top_keys,
+            write_batcher,
+            clock,
         }
     }
 }
@@ -0,0 +1,65 @@
+//! Grupos (shards) en los que está este proceso. Cada pertenencia es un miembro con su
+//! propio cache, rol y conexiones a los masters, que se anuncia con su propio id (ver
+//! `app_net::event::group`); al registrarlo, el master le dice en qué grupo quedó con
+//! `GROUP-ASSIGNED`.
+
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+
+use crate::infrastructure::di::CacheNodeModule;
+
+pub struct GroupMember {
+    pub member_id: Arc<str>,
+    pub module: Arc<CacheNodeModule>,
+}
+
+pub struct NodeGroups {
+    members: Vec<GroupMember>,
+    /// Grupo de cada miembro, en el orden de `members`; `None` hasta que el master lo
+    /// asigna.
+    assigned: RwLock<Vec<Option<Arc<str>>>>,
+}
+
+impl NodeGroups {
+    pub fn new(members: Vec<GroupMember>) -> Self {
+        let assigned = RwLock::new(vec![None; members.len()]);
+        Self { members, assigned }
+    }
+
+    pub fn members(&self) -> &[GroupMember] {
+        &self.members
+    }
+
+    pub fn member(&self, member_id: &str) -> Option<&GroupMember> {
+        self.members.iter().find(|m| &*m.member_id == member_id)
+    }
+
+    /// Miembros que el master puso en `group_id`. Suele ser uno: el master solo pone dos
+    /// miembros del mismo proceso en un grupo si no tiene otro lugar para la réplica.
+    pub fn group(&self, group_id: &str) -> Vec<&GroupMember> {
+        let assigned = self.assigned.read();
+        self.members
+            .iter()
+            .zip(assigned.iter())
+            .filter(|(_, group)| group.as_deref() == Some(group_id))
+            .map(|(member, _)| member)
+            .collect()
+    }
+
+    pub fn group_of(&self, member_id: &str) -> Option<Arc<str>> {
+        let idx = self.position(member_id)?;
+        self.assigned.read()[idx].clone()
+    }
+
+    /// Pasa `member_id` a `group_id`; con varios masters vale la última asignación.
+    /// Devuelve el grupo anterior.
+    pub fn assign(&self, member_id: &str, group_id: &str) -> Option<Arc<str>> {
+        let idx = self.position(member_id)?;
+        self.assigned.write()[idx].replace(Arc::from(group_id))
+    }
+
+    fn position(&self, member_id: &str) -> Option<usize> {
+        self.members.iter().position(|m| &*m.member_id == member_id)
+    }
+}
@@ -1,2 +1,3 @@
 pub mod adapters;
 pub mod di;
+pub mod groups;
@@ -0,0 +1,5 @@
+pub mod app_common;
+pub mod core;
+pub mod infrastructure;
+pub mod server;
+pub mod tests;
@@ -1,230 +1,430 @@
 use std::env;
+use std::io;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
-use app_core::utils::generate_short_id;
-use app_net::request::data::RequestDataOwned;
-use app_net::{
-    ParsedMsg, RequestDataInput, ResponseData, Socket, parse_line, request::RequestData,
+use app_core::{
+    id::short_form,
+    supervisor::{ShutdownStage, Supervisor},
 };
-use bytes::Bytes;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::net::TcpStream;
-use tokio::sync::mpsc;
-use tokio::task::JoinSet;
-use tracing::{error, info, trace};
-use tracing_subscriber::layer::SubscriberExt;
-use tracing_subscriber::util::SubscriberInitExt;
-
-use crate::core::domain::models::{AppError, Response};
-use crate::core::services::ActionParserService;
-use crate::infrastructure::di::CacheNodeModule;
-
-pub mod app_common;
-pub mod core;
-pub mod infrastructure;
-pub mod tests;
-
-// ---------- helpers ----------
-
-async fn handle_request(app_module: Arc<CacheNodeModule>, action: &str, payload: &str) -> String {
-    let cmd = ActionParserService::parse(action, payload);
-    let res: Response = app_module.request_controller_service.handle(cmd).await;
-    res.to_wire()
-}
+use app_net::{Acceptor, ClusterTls, Connector, QUIC_SCHEME, TcpConnector};
+use tracing::{info, warn};
 
-async fn handle_request_async(
-    app_module: Arc<CacheNodeModule>,
-    socket: Arc<Socket>,
-    data: RequestData<'_>,
-) {
-    let data = RequestDataOwned::from(data);
-    let app_module_clone = app_module.clone();
-    tokio::spawn(async move {
-        let reply = handle_request(app_module_clone, &data.action, &data.payload).await;
-        let response = ResponseData::new(data.id, 200, reply);
-        let _ = socket.send_res(response);
-    });
-}
+use cache_node::{
+    core::domain::models::{AppError, NodeRole},
+    core::services::{
+        EvictionPolicy, ExpiryStrategy, IdempotencyConfig, NamespaceQuotas, QuotaMode,
+        RequestWorkers, SlowLogConfig, TopKeysConfig, WriteBatching,
+    },
+    server::{self, NodeOptions, ReplicationListener, RequestLimits},
+};
+
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+const DEFAULT_COMPACTION_SECS: u64 = 300;
 
 // ---------- main ----------
 #[tokio::main]
 async fn main() -> Result<(), AppError> {
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::fmt::layer())
-        .init();
-
     dotenvy::dotenv().ok();
     let _ = dotenvy::from_filename(concat!(env!("CARGO_MANIFEST_DIR"), "/.env"));
     let _ = dotenvy::from_filename(".env");
 
+    let log = app_logging::init_logging();
+
     let role = env::var("ROLE").unwrap_or_else(|_| "MASTER".to_string());
-    let short_id = generate_short_id(8);
-    let node_identity = format!("{role} {short_id}");
-    info!("Node Identity: {node_identity}");
+    // STRICT_WRITES=true: como réplica, el nodo rechaza PUT
+    let strict_writes = env::var("STRICT_WRITES").is_ok_and(|v| v.trim() == "true");
 
-    let app_module = Arc::new(CacheNodeModule::init_dependencies());
+    // EXTRA_GROUPS: un grupo más por cada rol de la lista (p. ej. `REPLICA` para ser
+    // primario de un shard y réplica de otro en la misma máquina)
+    let extra_groups = parse_extra_groups();
 
     let addrs = parse_master_ips();
     info!("Master IPs: {:?}", addrs);
 
-    // una tarea por servidor
-    let mut set = JoinSet::new();
-    for s in parse_master_ips() {
-        let app = app_module.clone();
-        let ident = node_identity.clone();
-        let addr_arc: Arc<str> = Arc::<str>::from(s); // de String -> Arc<str>
-        set.spawn(run_connection_loop(app, ident, addr_arc));
+    let supervisor = Supervisor::new();
+
+    // SIGHUP vuelve a leer los .env y aplica su RUST_LOG
+    let env_files = vec![
+        PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/.env")),
+        PathBuf::from(".env"),
+    ];
+    supervisor.spawn("log-reload", ShutdownStage::Background, |token| {
+        log.reload_on_sighup(env_files, token)
+    });
+
+    // PRESSURE_REPORT_SECS: cada cuánto avisar al master evicciones y ocupación del cache
+    let pressure_report = env::var("PRESSURE_REPORT_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .map(Duration::from_secs);
+
+    // HEARTBEAT_MS: cada cuánto latirle a cada master (1000 por defecto, 0 no late)
+    let heartbeat = Some(
+        env::var("HEARTBEAT_MS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .unwrap_or(1000),
+    )
+    .filter(|ms| *ms > 0)
+    .map(Duration::from_millis);
+
+    // MAX_INFLIGHT_PER_CONN / MAX_INFLIGHT / MAX_PAYLOAD_BYTES: topes de requests en curso
+    // y del payload de cada uno (ver `RequestLimits`); REQUEST_WORKERS / BULK_WORKERS:
+    // cuántos se atienden a la vez y cuántos de ellos pueden ser `SNAPSHOT`, `EXPORT-RANGE`..
+    let env_limit = |var: &str| {
+        env::var(var)
+            .ok()
+            .and_then(|v| v.trim().parse::<usize>().ok())
+    };
+    let defaults = RequestLimits::default();
+    let limits = RequestLimits {
+        per_connection: inflight_limit("MAX_INFLIGHT_PER_CONN", defaults.per_connection)?,
+        global: inflight_limit("MAX_INFLIGHT", defaults.global)?,
+        max_payload: env_limit("MAX_PAYLOAD_BYTES")
+            .filter(|bytes| *bytes > 0)
+            .unwrap_or(defaults.max_payload),
+        workers: RequestWorkers {
+            workers: env_limit("REQUEST_WORKERS")
+                .filter(|workers| *workers > 0)
+                .unwrap_or(defaults.workers.workers),
+            bulk: env_limit("BULK_WORKERS")
+                .filter(|workers| *workers > 0)
+                .unwrap_or(defaults.workers.bulk),
+        },
+    };
+
+    // SLOWLOG_THRESHOLD_MS / SLOWLOG_MAX_LEN: qué comando cuenta como lento y cuántos guardar
+    let slow_defaults = SlowLogConfig::default();
+    let slow_log = SlowLogConfig {
+        threshold: env_limit("SLOWLOG_THRESHOLD_MS")
+            .map(|ms| Duration::from_millis(ms as u64))
+            .unwrap_or(slow_defaults.threshold),
+        max_len: env_limit("SLOWLOG_MAX_LEN").unwrap_or(slow_defaults.max_len),
+    };
+
+    // EVICTION_POLICY: lru (por defecto), slru, tinylfu o sampled (EVICTION_SAMPLES claves)
+    let mut eviction = match env::var("EVICTION_POLICY") {
+        Ok(raw) => raw.parse::<EvictionPolicy>().unwrap_or_else(|e| {
+            warn!("{e}; se usa lru");
+            EvictionPolicy::Lru
+        }),
+        Err(_) => EvictionPolicy::default(),
+    };
+    if let EvictionPolicy::Sampled { samples } = &mut eviction
+        && let Some(n) = env_limit("EVICTION_SAMPLES").filter(|n| *n > 0)
+    {
+        *samples = n;
     }
 
-    // Mantén vivo el proceso: si alguna tarea termina, la reportamos y seguimos.
-    loop {
-        if let Some(res) = set.join_next().await {
-            match res {
-                Ok(Ok(())) => info!("Conexión terminó (Ok)"),
-                Ok(Err(e)) => error!("Conexión terminó con error: {e:?}"),
-                Err(join_err) => error!("Conexión paniqueó: {join_err:?}"),
-            }
+    // EXPIRY_STRATEGY: lazy, active o hybrid (por defecto); en hybrid el reaper revisa a lo
+    // sumo EXPIRY_MAX_KEYS_PER_TICK claves y EXPIRY_BUDGET_MS por tick
+    let mut expiry = match env::var("EXPIRY_STRATEGY") {
+        Ok(raw) => raw.parse::<ExpiryStrategy>().unwrap_or_else(|e| {
+            warn!("{e}; se usa hybrid");
+            ExpiryStrategy::default()
+        }),
+        Err(_) => ExpiryStrategy::default(),
+    };
+    if let ExpiryStrategy::Hybrid {
+        max_keys_per_tick,
+        budget,
+    } = &mut expiry
+    {
+        *max_keys_per_tick = env_limit("EXPIRY_MAX_KEYS_PER_TICK").filter(|n| *n > 0);
+        *budget = env_limit("EXPIRY_BUDGET_MS")
+            .filter(|ms| *ms > 0)
+            .map(|ms| Duration::from_millis(ms as u64));
+    }
+
+    // STALE_GRACE_MS: cuánto se guardan las entradas vencidas para los GET con stale=
+    let stale_grace = env_limit("STALE_GRACE_MS")
+        .map(|ms| Duration::from_millis(ms as u64))
+        .unwrap_or_default();
+
+    // COMPACTION_INTERVAL_SECS: cada cuánto soltar la memoria que el cache reservó en un
+    // pico (300 por defecto; 0 no compacta)
+    let compaction = env::var("COMPACTION_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(DEFAULT_COMPACTION_SECS);
+    let compaction = (compaction > 0).then(|| Duration::from_secs(compaction));
+
+    // WRITE_BATCH_MAX_OPS: agrupar hasta tantos PUT por lote (sin definir o 0, cada uno por
+    // su lado); WRITE_BATCH_WINDOW_US: cuánto espera el primero a que se llene (200)
+    let write_batching = env_limit("WRITE_BATCH_MAX_OPS")
+        .filter(|n| *n > 0)
+        .map(|max_ops| WriteBatching {
+            max_ops,
+            window: env_limit("WRITE_BATCH_WINDOW_US")
+                .map(|us| Duration::from_micros(us as u64))
+                .unwrap_or(WriteBatching::default().window),
+        });
+
+    // IDEMPOTENCY_WINDOW_SECS: cuánto recordar los idem= de los PUT y DEL (30; 0 no los
+    // recuerda); IDEMPOTENCY_MAX_KEYS: cuántos a la vez
+    let idempotency_defaults = IdempotencyConfig::default();
+    let idempotency = IdempotencyConfig {
+        window: env_limit("IDEMPOTENCY_WINDOW_SECS")
+            .map(|secs| Duration::from_secs(secs as u64))
+            .unwrap_or(idempotency_defaults.window),
+        max_len: env_limit("IDEMPOTENCY_MAX_KEYS")
+            .filter(|n| *n > 0)
+            .unwrap_or(idempotency_defaults.max_len),
+    };
+
+    // TOPKEYS_CAPACITY: cuántas claves contar para TOPKEYS (0 por defecto: no se cuentan);
+    // TOPKEYS_SAMPLE: contar uno de cada tantos accesos (16)
+    let top_keys_defaults = TopKeysConfig::default();
+    let top_keys = TopKeysConfig {
+        capacity: env_limit("TOPKEYS_CAPACITY").unwrap_or(top_keys_defaults.capacity),
+        sample: env_limit("TOPKEYS_SAMPLE")
+            .filter(|n| *n > 0)
+            .map(|n| n as u32)
+            .unwrap_or(top_keys_defaults.sample),
+    };
+
+    // NAMESPACE_QUOTAS: `<namespace>=<entradas>[/<bytes>],...`; NAMESPACE_QUOTA_MODE: reject
+    // (por defecto) o evict
+    let mut namespace_quotas = match env::var("NAMESPACE_QUOTAS") {
+        Ok(raw) => raw.parse::<NamespaceQuotas>().unwrap_or_else(|e| {
+            warn!("{e}; sin cuotas por namespace");
+            NamespaceQuotas::default()
+        }),
+        Err(_) => NamespaceQuotas::default(),
+    };
+    if let Ok(raw) = env::var("NAMESPACE_QUOTA_MODE") {
+        namespace_quotas.mode = raw.parse::<QuotaMode>().unwrap_or_else(|e| {
+            warn!("{e}; se usa reject");
+            QuotaMode::Reject
+        });
+    }
+
+    // MAX_MEMORY_BYTES: tope de memoria que el nodo anuncia; el master deja de mandarle PUT
+    // cerca del tope
+    let max_memory_bytes = env::var("MAX_MEMORY_BYTES")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|n| *n > 0);
+
+    // COMPRESS_MIN_BYTES: las respuestas al master de al menos esto se mandan comprimidas
+    // si el master las acepta; 0 o sin definir, no se comprime
+    let compress_min_bytes = env::var("COMPRESS_MIN_BYTES")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|n| *n > 0);
+
+    // TLS_CERT / TLS_KEY / TLS_CA: TLS mutuo con el master (MASTER_IPS tiene que apuntar a su
+    // CLUSTER_PORT, o a su CLUSTER_QUIC_PORT como `quic://host:puerto`). El id del nodo es la
+    // identidad del certificado
+    let tls = ClusterTls::from_env().map_err(tls_error)?;
+    let (node_id, connector) = cluster_tls(tls.as_ref(), &addrs)?;
+
+    let options = NodeOptions {
+        node_id,
+        connector,
+        strict_writes,
+        pressure_report,
+        heartbeat,
+        limits,
+        slow_log,
+        eviction,
+        expiry,
+        stale_grace,
+        compaction,
+        write_batching,
+        idempotency,
+        top_keys,
+        namespace_quotas,
+        max_memory_bytes,
+        compress_min_bytes,
+        replication: replication_listener(tls.as_ref()).await?,
+        memcached: memcached_listener().await?,
+        extra_groups,
+        ..NodeOptions::default()
+    };
+    let node = server::start_with(&supervisor, &role, addrs, options);
+    info!(
+        "Node Identity: {role} {} ({})",
+        short_form(&node.node_id),
+        node.node_id
+    );
+    for member in &node.groups.members()[1..] {
+        info!(
+            "Grupo extra: {} {}",
+            member.module.role.get(),
+            member.member_id
+        );
+    }
+
+    let _ = tokio::signal::ctrl_c().await;
+    info!("Apagando...");
+
+    let report = supervisor.shutdown(SHUTDOWN_GRACE).await;
+    info!(
+        completed = report.completed.len(),
+        aborted = report.aborted.len(),
+        panics = report.panics.len(),
+        "Apagado completo"
+    );
+
+    Ok(())
+}
+
+/// Sin TLS, TCP y un id generado. Con TLS, las direcciones `quic://` van por QUIC (con la
+/// feature `quic`) y las demás por TCP. `TLS_SERVER_NAME` es el nombre a verificar en el certificado del master
+/// si no es el host de `MASTER_IPS`.
+fn cluster_tls(
+    tls: Option<&ClusterTls>,
+    addrs: &[String],
+) -> Result<(Option<String>, Arc<dyn Connector>), AppError> {
+    let Some(tls) = tls else {
+        if addrs.iter().any(|addr| addr.starts_with(QUIC_SCHEME)) {
+            return Err(AppError::SocketError(
+                "las direcciones quic:// necesitan TLS_CERT, TLS_KEY y TLS_CA".into(),
+            ));
         }
+        return Ok((None, Arc::new(TcpConnector)));
+    };
+
+    let node_id = tls.identity().ok_or_else(|| {
+        AppError::SocketError("TLS_CERT no tiene CN ni nombre DNS para el id del nodo".into())
+    })?;
+    let server_name = env::var("TLS_SERVER_NAME").ok();
+    let mut tcp = tls.connector(TcpConnector).map_err(tls_error)?;
+    if let Some(name) = &server_name {
+        tcp = tcp.with_server_name(name.trim()).map_err(tls_error)?;
     }
+    #[cfg(feature = "quic")]
+    let connector = {
+        let mut connector = tls.quic_connector(tcp).map_err(tls_error)?;
+        if let Some(name) = &server_name {
+            connector = connector.with_server_name(name.trim()).map_err(tls_error)?;
+        }
+        connector
+    };
+    #[cfg(not(feature = "quic"))]
+    let connector = {
+        if addrs.iter().any(|addr| addr.starts_with(QUIC_SCHEME)) {
+            return Err(AppError::Config(
+                "las direcciones quic:// necesitan el nodo compilado con la feature quic".into(),
+            ));
+        }
+        tcp
+    };
+    info!("TLS mutuo con el master como {node_id}");
+    Ok((Some(node_id), Arc::new(connector)))
+}
+
+fn tls_error(e: io::Error) -> AppError {
+    AppError::SocketError(format!("TLS: {e}"))
+}
+
+/// `REPL_ADDR`: dónde escuchar a las réplicas de este nodo; `quic://ip:puerto` escucha
+/// QUIC (necesita TLS, y las réplicas lo alcanzan por QUIC). `REPL_ADVERTISE_ADDR` es la
+/// dirección que se anuncia al master, si difiere de la local (NAT, contenedores).
+async fn replication_listener(
+    tls: Option<&ClusterTls>,
+) -> Result<Option<ReplicationListener>, AppError> {
+    let Ok(addr) = env::var("REPL_ADDR") else {
+        return Ok(None);
+    };
+    let bind_error = |e| AppError::SocketError(format!("REPL_ADDR {addr}: {e}"));
+
+    let (acceptor, local_addr): (Box<dyn Acceptor>, String) =
+        match addr.trim().strip_prefix(QUIC_SCHEME) {
+            Some(quic_addr) => quic_replication_acceptor(tls, quic_addr, bind_error)?,
+            None => {
+                let listener = tokio::net::TcpListener::bind(addr.trim())
+                    .await
+                    .map_err(bind_error)?;
+                let local = listener.local_addr().map_err(bind_error)?;
+                (Box::new(listener), local.to_string())
+            }
+        };
+    let advertised_addr = env::var("REPL_ADVERTISE_ADDR").unwrap_or(local_addr);
+    info!("Replicación en {advertised_addr}");
+
+    Ok(Some(ReplicationListener {
+        acceptor,
+        advertised_addr,
+    }))
+}
+
+#[cfg(feature = "quic")]
+fn quic_replication_acceptor(
+    tls: Option<&ClusterTls>,
+    addr: &str,
+    bind_error: impl Fn(io::Error) -> AppError,
+) -> Result<(Box<dyn Acceptor>, String), AppError> {
+    let tls = tls.ok_or_else(|| {
+        AppError::SocketError("REPL_ADDR quic:// necesita TLS_CERT, TLS_KEY y TLS_CA".into())
+    })?;
+    let addr = addr
+        .parse()
+        .map_err(|e| bind_error(io::Error::new(io::ErrorKind::InvalidInput, e)))?;
+    let acceptor = tls.quic_acceptor(addr).map_err(&bind_error)?;
+    let local = acceptor.local_addr().map_err(bind_error)?;
+    Ok((Box::new(acceptor), format!("{QUIC_SCHEME}{local}")))
+}
+
+#[cfg(not(feature = "quic"))]
+fn quic_replication_acceptor(
+    _tls: Option<&ClusterTls>,
+    _addr: &str,
+    _bind_error: impl Fn(io::Error) -> AppError,
+) -> Result<(Box<dyn Acceptor>, String), AppError> {
+    Err(AppError::Config(
+        "REPL_ADDR quic:// necesita el nodo compilado con la feature quic".into(),
+    ))
+}
+
+/// `MEMCACHED_ADDR`: dónde atender el protocolo de texto de memcached (p. ej.
+/// `0.0.0.0:11211`). Sin la variable no se abre el puerto.
+async fn memcached_listener() -> Result<Option<Box<dyn Acceptor>>, AppError> {
+    let Ok(addr) = env::var("MEMCACHED_ADDR") else {
+        return Ok(None);
+    };
+
+    let listener = tokio::net::TcpListener::bind(addr.trim())
+        .await
+        .map_err(|e| AppError::SocketError(format!("MEMCACHED_ADDR {addr}: {e}")))?;
+    info!("memcached en {addr}");
+    Ok(Some(Box::new(listener)))
+}
+
+fn parse_extra_groups() -> Vec<NodeRole> {
+    let raw = env::var("EXTRA_GROUPS").unwrap_or_default();
+    raw.split([',', ' '])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .filter_map(|s| {
+            s.parse::<NodeRole>()
+                .inspect_err(|e| warn!("EXTRA_GROUPS: {e}; se ignora"))
+                .ok()
+        })
+        .collect()
+}
+
+/// Un tope de cero rechazaría todos los requests: se prefiere no arrancar.
+fn inflight_limit(var: &str, default: usize) -> Result<usize, AppError> {
+    let Ok(raw) = env::var(var) else {
+        return Ok(default);
+    };
+    raw.trim()
+        .parse::<usize>()
+        .ok()
+        .filter(|limit| *limit > 0)
+        .ok_or_else(|| AppError::Config(format!("{var}={raw}: se espera un entero mayor que 0")))
 }
 
 fn parse_master_ips() -> Vec<String> {
     let raw = env::var("MASTER_IPS").unwrap_or_else(|_| "".to_string());
-    raw.split(|c| c == ',' || c == ' ')
+    raw.split([',', ' '])
         .map(str::trim)
         .filter(|s| !s.is_empty())
         .map(|s| s.to_string())
         .collect()
 }
-
-// Lanza y mantiene una conexión (con reconexión) a un addr específico
-async fn run_connection_loop(
-    app_module: Arc<CacheNodeModule>,
-    node_identity: String,
-    addr: Arc<str>,
-) -> Result<(), AppError> {
-    let mut backoff = Duration::from_millis(500);
-    let max_backoff = Duration::from_secs(10);
-
-    loop {
-        // ——— CLON LOCAL PARA ESTA ITERACIÓN ———
-        let addr_iter = addr.clone();
-
-        info!(target: "conn", "Conectando a {}...", &*addr_iter);
-
-        match TcpStream::connect(&*addr_iter).await {
-            Ok(stream) => {
-                info!(target: "conn", "Conectado a {}", &*addr_iter);
-                let (reader, mut writer) = stream.into_split();
-
-                let (tx, mut rx) = mpsc::unbounded_channel::<Bytes>();
-                let connection_socket = Arc::new(Socket::new(
-                    node_identity.clone(),
-                    tx,
-                    Duration::from_secs(10),
-                ));
-
-                // writer_task
-                let writer_id = connection_socket.id.clone();
-                let writer_task = tokio::spawn(async move {
-                    while let Some(bytes) = rx.recv().await {
-                        if let Err(e) = writer.write_all(&bytes).await {
-                            error!(target:"conn", "[{writer_id}] write error: {e}");
-                            break;
-                        }
-                    }
-                });
-
-                // Identificación
-                connection_socket
-                    .send_raw(Bytes::from(format!("{}\n", node_identity)))
-                    .map_err(|e| {
-                        AppError::SocketError(format!("Failed on identification: {}", e))
-                    })?;
-
-                // PING (usa otro clon)
-                {
-                    let req_socket = connection_socket.clone();
-                    let addr_ping = addr_iter.clone();
-                    tokio::spawn(async move {
-                        trace!(
-                            "PING({}): {:?}",
-                            &*addr_ping,
-                            req_socket.request(RequestDataInput::new("PING", "")).await
-                        );
-                    });
-                }
-
-                // reader_task (usa otro clon)
-                let reader_socket = connection_socket.clone();
-                let app_module_clone = app_module.clone();
-                let addr_reader = addr_iter.clone();
-                let reader_task = tokio::spawn(async move {
-                    let mut br = BufReader::new(reader);
-                    let mut line = String::new();
-
-                    loop {
-                        line.clear();
-                        let n = br
-                            .read_line(&mut line)
-                            .await
-                            .map_err(|e| AppError::SocketReadingError(e.to_string()))?;
-
-                        if n == 0 {
-                            info!(target:"conn",
-                                  "[{}] servidor cerró la conexión ({})",
-                                  reader_socket.id, &*addr_reader);
-                            break;
-                        }
-
-                        let current_line = parse_line(&line).map_err(|e| {
-                            AppError::SocketReadingError(format!("Failed Reading Line: {:?}", e))
-                        })?;
-
-                        match current_line {
-                            ParsedMsg::Req { data } => {
-                                handle_request_async(
-                                    app_module_clone.clone(),
-                                    reader_socket.clone(),
-                                    data,
-                                )
-                                .await;
-                            }
-                            ParsedMsg::Res { id, raw_response } => {
-                                reader_socket.handle_response(id, raw_response.to_string());
-                            }
-                            ParsedMsg::Other(msg) => {
-                                info!(target:"srv", "[{}] {}", &*addr_reader, msg);
-                            }
-                        }
-                    }
-
-                    Ok::<(), AppError>(())
-                });
-
-                // Espera fin del reader; corta writer; backoff
-                let res = reader_task.await;
-                writer_task.abort();
-
-                match res {
-                    Ok(Ok(())) => info!(target:"conn", "Reader finalizó para {}", &*addr_iter),
-                    Ok(Err(e)) => error!(target:"conn", "Reader error en {}: {:?}", &*addr_iter, e),
-                    Err(e) => error!(target:"conn", "Reader panic en {}: {:?}", &*addr_iter, e),
-                }
-
-                info!(target:"conn", "Reintentando {} en {:?}...", &*addr_iter, backoff);
-                tokio::time::sleep(backoff).await;
-                backoff = (backoff * 2).min(max_backoff);
-            }
-            Err(e) => {
-                error!(target:"conn",
-                    "No se pudo conectar a {}: {}. Reintentando en {:?}...",
-                    &*addr_iter, e, backoff);
-                tokio::time::sleep(backoff).await;
-                backoff = (backoff * 2).min(max_backoff);
-            }
-        }
-        // aquí termina la vida de `addr_iter`; en la siguiente vuelta clonamos `addr` de nuevo
-    }
-}
@@ -0,0 +1,654 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+use std::time::Duration;
+
+use app_core::{
+    clock::{AppClock, Clock},
+    error::ErrorKind,
+    id::new_sortable_id,
+    retry::{Backoff, RetryError, RetryPolicy, retry_with_backoff_until},
+    supervisor::{ShutdownStage, Supervisor},
+};
+use app_net::request::data::RequestDataOwned;
+use app_net::{
+    Acceptor, COMPRESS, Compression, Connector, DEFAULT_MAX_PAYLOAD, FRAME_OVERHEAD, FrameReader,
+    FrameTooLarge, MonitorEntry, MonitorOptions, ParsedMsg, RequestDataInput, Socket, TcpConnector,
+    event::{GROUP_ASSIGNED, HEARTBEAT, NODE_ID_CONFLICT, NODE_REFUSED, group},
+    monitor::MONITOR,
+    parse_frame,
+    request::RequestData,
+    tokenize,
+};
+use bytes::Bytes;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore, mpsc};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, trace, warn};
+
+use crate::core::{
+    domain::models::{AppError, NodeRole, Response, RoleState},
+    services::{
+        EvictionPolicy, ExpiryStrategy, IdempotencyConfig, NamespaceQuotas, RequestPriority,
+        RequestQueue, RequestWorkers, SlowLogConfig, TopKeysConfig, WriteBatching,
+    },
+};
+use crate::infrastructure::{
+    adapters::services::{
+        cache_service::CacheConfig,
+        expiry_reporter::report_expired_keys,
+        heartbeat::{MasterEcho, send_heartbeats},
+        memcached_service::{Memcached, serve_memcached},
+        pressure_reporter::report_cache_pressure,
+        replication_service::serve_replicas,
+    },
+    di::CacheNodeModule,
+    groups::{GroupMember, NodeGroups},
+};
+
+/// Nodo levantado con `start`. `module` es el de su primer grupo, el de `role`.
+pub struct NodeHandle {
+    pub node_id: String,
+    pub module: Arc<CacheNodeModule>,
+    /// Todos los grupos del proceso, con el que el master le asignó a cada uno.
+    pub groups: Arc<NodeGroups>,
+}
+
+/// Piezas intercambiables del nodo; por defecto TCP y reloj del sistema.
+pub struct NodeOptions {
+    /// ID fijo (simulaciones, o el del certificado con TLS mutuo). Si es `None` se genera
+    /// uno ordenable.
+    pub node_id: Option<String>,
+    pub connector: Arc<dyn Connector>,
+    pub clock: Arc<dyn Clock>,
+    /// Las réplicas rechazan `PUT` (ver `RoleState`).
+    pub strict_writes: bool,
+    /// Listener para las réplicas de este nodo. Sin él, el nodo no puede ser primario de
+    /// la replicación nodo a nodo (las réplicas solo reciben el fan-out del master).
+    pub replication: Option<ReplicationListener>,
+    /// Cada cuánto avisar al master la presión sobre el cache (`EVT CACHE-PRESSURE`).
+    /// Sin intervalo no se avisa.
+    pub pressure_report: Option<Duration>,
+    /// Cada cuánto mandarle un `EVT HEARTBEAT` a cada master, para que detecte al nodo
+    /// trabado aunque la conexión siga abierta. Sin intervalo no se late.
+    pub heartbeat: Option<Duration>,
+    pub limits: RequestLimits,
+    /// Umbral y largo del `SLOWLOG`.
+    pub slow_log: SlowLogConfig,
+    pub eviction: EvictionPolicy,
+    /// Quién saca las entradas vencidas: las lecturas, el reaper o los dos.
+    pub expiry: ExpiryStrategy,
+    /// Cuánto se guardan las entradas vencidas para los `GET ... stale=`; cero es sacarlas
+    /// al vencer.
+    pub stale_grace: Duration,
+    /// Cada cuánto el cache suelta la memoria que reservó en un pico (ver
+    /// `CacheConfig::compaction`).
+    pub compaction: Option<Duration>,
+    /// Agrupar los `PUT` que llegan casi juntos (ver `CacheConfig::write_batching`).
+    pub write_batching: Option<WriteBatching>,
+    /// Cuánto se recuerdan los `idem=` de las escrituras (ver `CacheConfig::idempotency`).
+    pub idempotency: IdempotencyConfig,
+    /// Las claves que se cuentan para `TOPKEYS` (ver `CacheConfig::top_keys`).
+    pub top_keys: TopKeysConfig,
+    /// Cuotas de entradas y bytes por namespace, y qué hacer al pasarlas.
+    pub namespace_quotas: NamespaceQuotas,
+    /// Tope de memoria que se anuncia al master (ver `CacheConfig::max_bytes`).
+    pub max_memory_bytes: Option<u64>,
+    /// Las respuestas al master de al menos estos bytes de payload van comprimidas, si el
+    /// master avisó que las entiende (`EVT COMPRESS`). `None` no comprime.
+    pub compress_min_bytes: Option<usize>,
+    /// Listener para clientes del protocolo de texto de memcached (ver
+    /// `memcached_service`). Sin él no se atiende memcached.
+    pub memcached: Option<Box<dyn Acceptor>>,
+    /// Un grupo más por cada rol, además del de `role`: cada uno con su cache (armado con
+    /// las mismas opciones), su rol y sus conexiones a los masters, anunciado como
+    /// `<node_id>+<n>`. La replicación nodo a nodo y memcached quedan en el primero.
+    pub extra_groups: Vec<NodeRole>,
+}
+
+/// Requests en curso que acepta el nodo; pasado el tope responde `503` sin ejecutarlos.
+/// Un payload de más de `max_payload` bytes se contesta con `413`, también sin ejecutarlo.
+#[derive(Debug, Clone, Copy)]
+pub struct RequestLimits {
+    /// Por cada conexión a un master.
+    pub per_connection: usize,
+    /// Sumando todas las conexiones.
+    pub global: usize,
+    pub max_payload: usize,
+    /// Quiénes los atienden, por prioridad (ver `RequestQueue`).
+    pub workers: RequestWorkers,
+}
+
+impl Default for RequestLimits {
+    fn default() -> Self {
+        Self {
+            per_connection: 1024,
+            global: 4096,
+            max_payload: DEFAULT_MAX_PAYLOAD,
+            workers: RequestWorkers::default(),
+        }
+    }
+}
+
+/// Lo que cada conexión a un master comparte con las demás o recibe de `NodeOptions`.
+#[derive(Clone)]
+struct ConnectionConfig {
+    /// El del grupo de la conexión.
+    node_id: Arc<str>,
+    groups: Arc<NodeGroups>,
+    pressure_report: Option<Duration>,
+    heartbeat: Option<Duration>,
+    inflight_per_connection: usize,
+    inflight_global: Arc<Semaphore>,
+    max_payload: usize,
+    compress_min_bytes: Option<usize>,
+    /// La de todo el proceso, como `inflight_global`.
+    requests: Arc<RequestQueue>,
+}
+
+/// Listener de replicación y la dirección con la que las réplicas lo alcanzan; el nodo la
+/// anuncia al master al identificarse (`<ROL> <id> <dirección>`).
+pub struct ReplicationListener {
+    pub acceptor: Box<dyn Acceptor>,
+    pub advertised_addr: String,
+}
+
+impl Default for NodeOptions {
+    fn default() -> Self {
+        Self {
+            node_id: None,
+            connector: Arc::new(TcpConnector),
+            clock: Arc::new(AppClock::new()),
+            strict_writes: false,
+            replication: None,
+            pressure_report: None,
+            heartbeat: None,
+            limits: RequestLimits::default(),
+            slow_log: SlowLogConfig::default(),
+            eviction: EvictionPolicy::default(),
+            expiry: ExpiryStrategy::default(),
+            stale_grace: Duration::ZERO,
+            compaction: None,
+            write_batching: None,
+            idempotency: IdempotencyConfig::default(),
+            top_keys: TopKeysConfig::default(),
+            namespace_quotas: NamespaceQuotas::default(),
+            max_memory_bytes: None,
+            compress_min_bytes: None,
+            memcached: None,
+            extra_groups: Vec::new(),
+        }
+    }
+}
+
+/// Arma las dependencias y abre una conexión (con reconexión) a cada master.
+/// `role` es `MASTER` o `REPLICA` y puede cambiar después con `SET-ROLE`; al reconectar el
+/// nodo se anuncia con el rol vigente. Todas las tareas quedan bajo `supervisor`.
+pub fn start(supervisor: &Supervisor, role: &str, masters: Vec<String>) -> NodeHandle {
+    start_with(supervisor, role, masters, NodeOptions::default())
+}
+
+pub fn start_with(
+    supervisor: &Supervisor,
+    role: &str,
+    masters: Vec<String>,
+    options: NodeOptions,
+) -> NodeHandle {
+    let node_id = options.node_id.unwrap_or_else(new_sortable_id);
+    let role = role.parse::<NodeRole>().unwrap_or_else(|e| {
+        warn!("{e}; se usa MASTER");
+        NodeRole::Master
+    });
+    let cache_config = CacheConfig {
+        eviction: options.eviction,
+        namespaces: options.namespace_quotas,
+        max_bytes: options.max_memory_bytes,
+        expiry: options.expiry,
+        stale_grace: options.stale_grace,
+        compaction: options.compaction,
+        write_batching: options.write_batching,
+        idempotency: options.idempotency,
+        top_keys: options.top_keys,
+    };
+    let roles = std::iter::once(role).chain(options.extra_groups);
+    let members: Vec<_> = roles
+        .enumerate()
+        .map(|(idx, role)| {
+            let member_id = group::member_id(&node_id, idx);
+            let role_state = Arc::new(RoleState::new(role, options.strict_writes));
+            let module = Arc::new(CacheNodeModule::init_with(
+                supervisor,
+                options.clock.clone(),
+                role_state,
+                &member_id,
+                options.connector.clone(),
+                options.slow_log,
+                cache_config.clone(),
+            ));
+            GroupMember {
+                member_id: Arc::from(member_id),
+                module,
+            }
+        })
+        .collect();
+    let groups = Arc::new(NodeGroups::new(members));
+    let app_module = groups.members()[0].module.clone();
+
+    // lo que sigue al rol en la línea de identificación
+    let mut announced = node_id.clone();
+    if let Some(listener) = options.replication {
+        announced = format!("{node_id} {}", listener.advertised_addr);
+        let cache = app_module.cache.clone();
+        let op_log = app_module.op_log.clone();
+        supervisor.spawn("repl-accept", ShutdownStage::Ingress, |token| {
+            serve_replicas(listener.acceptor, cache, op_log, token)
+        });
+    }
+
+    if let Some(acceptor) = options.memcached {
+        let memcached = Memcached {
+            cache: app_module.cache.clone(),
+            op_log: app_module.op_log.clone(),
+            role: app_module.role.clone(),
+            clock: options.clock.clone(),
+        };
+        supervisor.spawn("memcached-accept", ShutdownStage::Ingress, |token| {
+            serve_memcached(acceptor, memcached, token)
+        });
+    }
+
+    let inflight_global = Arc::new(Semaphore::new(options.limits.global));
+    let requests = Arc::new(RequestQueue::new(options.limits.workers));
+    for idx in 0..requests.config().workers {
+        let requests = requests.clone();
+        supervisor.spawn(
+            format!("request-worker {idx}"),
+            ShutdownStage::Connections,
+            |token| async move { requests.run_worker(token).await },
+        );
+    }
+    for (idx, member) in groups.members().iter().enumerate() {
+        let announced: Arc<str> = match idx {
+            0 => Arc::from(announced.as_str()),
+            _ => member.member_id.clone(),
+        };
+        let config = ConnectionConfig {
+            node_id: member.member_id.clone(),
+            groups: groups.clone(),
+            pressure_report: options.pressure_report,
+            heartbeat: options.heartbeat,
+            inflight_per_connection: options.limits.per_connection,
+            max_payload: options.limits.max_payload,
+            inflight_global: inflight_global.clone(),
+            compress_min_bytes: options.compress_min_bytes,
+            requests: requests.clone(),
+        };
+
+        // una tarea por servidor
+        for s in &masters {
+            let app = member.module.clone();
+            let announced = announced.clone();
+            let connector = options.connector.clone();
+            let config = config.clone();
+            let addr_arc: Arc<str> = Arc::from(s.as_str());
+            let name = match idx {
+                0 => format!("conn {addr_arc}"),
+                _ => format!("conn {addr_arc} {}", member.member_id),
+            };
+            supervisor.spawn(name, ShutdownStage::Connections, |token| async move {
+                match run_connection_loop(app, connector, announced, addr_arc, config, token).await
+                {
+                    Ok(()) => info!("Conexión terminó (Ok)"),
+                    Err(e) => error!("Conexión terminó con error: {e:?}"),
+                }
+            });
+        }
+    }
+
+    NodeHandle {
+        node_id,
+        module: app_module,
+        groups,
+    }
+}
+
+// ---------- helpers ----------
+
+async fn handle_request(app_module: Arc<CacheNodeModule>, action: &str, payload: &str) -> Response {
+    app_module
+        .request_controller_service
+        .handle(action, payload)
+        .await
+}
+
+async fn handle_request_async(
+    app_module: Arc<CacheNodeModule>,
+    socket: Arc<Socket>,
+    inflight: &InflightPermits,
+    requests: &RequestQueue,
+    max_payload: usize,
+    frame: &Bytes,
+    data: RequestData<'_>,
+) {
+    if data.payload.len() > max_payload {
+        let too_large = Response::error(
+            ErrorKind::PayloadTooLarge,
+            format!(
+                "payload de {} bytes; el máximo es {max_payload}",
+                data.payload.len()
+            ),
+        );
+        let _ = socket.send_res(too_large.into_response(data.id));
+        return;
+    }
+    // sin lugar se contesta acá mismo, sin encolarlo
+    let Some(permits) = inflight.try_acquire() else {
+        debug!(target: "conn", req_id = %data.id, action = data.action, "nodo ocupado");
+        let busy = Response::error(ErrorKind::Unavailable, "nodo ocupado, reintentar");
+        let _ = socket.send_res(busy.into_response(data.id));
+        return;
+    };
+
+    let priority = RequestPriority::of(data.action);
+    let data = RequestDataOwned::from_frame(frame, data);
+    requests.push(priority, async move {
+        let reply = handle_request(app_module, data.action(), data.payload()).await;
+        let response = reply.into_response(data.id);
+        let _ = socket.send_res(response);
+        drop(permits);
+    });
+}
+
+/// Copia el request a las suscripciones de `MONITOR`. El `MONITOR` mismo se atiende acá,
+/// porque suscribe la conexión por la que llega y los comandos no la ven; devuelve `true`
+/// en ese caso.
+fn monitor_request(
+    app_module: &CacheNodeModule,
+    socket: &Arc<Socket>,
+    node_id: &str,
+    peer: &str,
+    data: &RequestData<'_>,
+) -> bool {
+    if data.action != MONITOR {
+        app_module.monitor.record(&MonitorEntry::new(
+            node_id,
+            peer,
+            data.action,
+            &data.payload,
+        ));
+        return false;
+    }
+
+    let reply = match MonitorOptions::parse(tokenize(&data.payload)) {
+        Ok(options) => {
+            app_module.monitor.subscribe(socket.clone(), options);
+            Response::OkEmpty
+        }
+        Err(e) => Response::from_error(&e),
+    };
+    let _ = socket.send_res(reply.into_response(data.id.clone()));
+    true
+}
+
+/// Semáforos de requests en curso de una conexión: el propio y el global del nodo.
+struct InflightPermits {
+    connection: Arc<Semaphore>,
+    global: Arc<Semaphore>,
+}
+
+impl InflightPermits {
+    fn try_acquire(&self) -> Option<(OwnedSemaphorePermit, OwnedSemaphorePermit)> {
+        let connection = self.connection.clone().try_acquire_owned().ok()?;
+        let global = self.global.clone().try_acquire_owned().ok()?;
+        Some((connection, global))
+    }
+}
+
+/// Parte del backoff de reconexión que se sortea (ver `RetryPolicy::jitter`).
+const RECONNECT_JITTER: f64 = 0.5;
+
+// Lanza y mantiene una conexión (con reconexión) a un addr específico
+async fn run_connection_loop(
+    app_module: Arc<CacheNodeModule>,
+    connector: Arc<dyn Connector>,
+    announced: Arc<str>,
+    addr: Arc<str>,
+    config: ConnectionConfig,
+    cancel: CancellationToken,
+) -> Result<(), AppError> {
+    // con jitter amplio: si el master reinicia, los nodos no vuelven todos en el mismo tick
+    let policy = RetryPolicy::default().with_jitter(RECONNECT_JITTER);
+    // entre sesiones: una conexión que el master corta sin contestar espera cada vez más
+    let mut backoff = Backoff::new(policy.clone());
+
+    loop {
+        // ——— CLON LOCAL PARA ESTA ITERACIÓN ———
+        let addr_iter = addr.clone();
+
+        let stream = match retry_with_backoff_until(&policy, &cancel, |attempt| {
+            let addr = addr_iter.clone();
+            let connector = connector.clone();
+            async move {
+                info!(target: "conn", "Conectando a {} (intento {})...", &*addr, attempt);
+                connector.connect(&addr).await.inspect_err(|e| {
+                    error!(target:"conn", "No se pudo conectar a {}: {}", &*addr, e);
+                })
+            }
+        })
+        .await
+        {
+            Ok(stream) => stream,
+            Err(RetryError::Cancelled { .. }) => return Ok(()),
+            Err(e) => return Err(AppError::SocketError(e.to_string())),
+        };
+
+        info!(target: "conn", "Conectado a {}", &*addr_iter);
+        // el rol se lee en cada conexión: tras un SET-ROLE el master lo ve al reconectar
+        let node_identity = format!("{} {announced}", app_module.role.get());
+        let (reader, mut writer) = tokio::io::split(stream);
+
+        let (tx, mut rx) = mpsc::unbounded_channel::<Bytes>();
+        let connection_socket = Arc::new(
+            Socket::new(node_identity.clone(), tx, Duration::from_secs(10))
+                .with_compression(config.compress_min_bytes),
+        );
+
+        // writer_task
+        let writer_id = connection_socket.id.clone();
+        let writer_task = tokio::spawn(async move {
+            while let Some(bytes) = rx.recv().await {
+                if let Err(e) = writer.write_all(&bytes).await {
+                    error!(target:"conn", "[{writer_id}] write error: {e}");
+                    break;
+                }
+            }
+        });
+
+        // Identificación
+        connection_socket.send_raw(Bytes::from(format!("{}\n", node_identity)))?;
+
+        // PING (usa otro clon): si el master contesta, la conexión cuenta como lograda
+        let answered = Arc::new(AtomicBool::new(false));
+        {
+            let req_socket = connection_socket.clone();
+            let addr_ping = addr_iter.clone();
+            let answered = answered.clone();
+            tokio::spawn(async move {
+                // el request va fuera del macro: con `trace` apagado no se evaluaría
+                let res = req_socket.request(RequestDataInput::new("PING", "")).await;
+                trace!(target: "conn", addr = &*addr_ping, ok = res.is_ok(), "PING");
+                if res.is_ok() {
+                    answered.store(true, Ordering::Relaxed);
+                }
+            });
+        }
+
+        // se cortan junto con la conexión (ver abajo)
+        let reporter = config.pressure_report.map(|every| {
+            tokio::spawn(report_cache_pressure(
+                app_module.cache.clone(),
+                connection_socket.clone(),
+                every,
+            ))
+        });
+        let echo = Arc::new(MasterEcho::new());
+        let heartbeats = config.heartbeat.map(|every| {
+            tokio::spawn(send_heartbeats(
+                connection_socket.clone(),
+                every,
+                app_module.clock.clone(),
+                echo.clone(),
+            ))
+        });
+        let expiries = tokio::spawn(report_expired_keys(
+            app_module.cache.clone(),
+            connection_socket.clone(),
+        ));
+        let background: Vec<_> = reporter
+            .into_iter()
+            .chain(heartbeats)
+            .chain([expiries])
+            .collect();
+
+        // reader_task (usa otro clon)
+        let reader_socket = connection_socket.clone();
+        let app_module_clone = app_module.clone();
+        let addr_reader = addr_iter.clone();
+        let node_id = config.node_id.clone();
+        let groups = config.groups.clone();
+        // el cupo por conexión arranca de cero en cada reconexión
+        let inflight = InflightPermits {
+            connection: Arc::new(Semaphore::new(config.inflight_per_connection)),
+            global: config.inflight_global.clone(),
+        };
+        let max_payload = config.max_payload;
+        let requests = config.requests.clone();
+        let reader_task = tokio::spawn(async move {
+            let mut frames = FrameReader::new(reader).with_max_frame(max_payload + FRAME_OVERHEAD);
+
+            loop {
+                let frame = match frames.next_frame().await {
+                    Err(e) => {
+                        // se contesta y se sigue: la línea ya se descartó entera
+                        if let Some(req_id) =
+                            FrameTooLarge::from_io(&e).and_then(FrameTooLarge::req_id)
+                        {
+                            let too_large =
+                                Response::error(ErrorKind::PayloadTooLarge, e.to_string());
+                            let _ = reader_socket.send_res(too_large.into_response(req_id));
+                            continue;
+                        }
+                        return Err(AppError::SocketReadingError(e.to_string()));
+                    }
+                    Ok(frame) => frame,
+                };
+
+                let Some(frame) = frame else {
+                    info!(target:"conn",
+                          "[{}] servidor cerró la conexión ({})",
+                          reader_socket.id, &*addr_reader);
+                    break;
+                };
+
+                let current_line = parse_frame(&frame)?;
+
+                match current_line {
+                    ParsedMsg::Req { data } => {
+                        if monitor_request(
+                            &app_module_clone,
+                            &reader_socket,
+                            &node_id,
+                            &addr_reader,
+                            &data,
+                        ) {
+                            continue;
+                        }
+                        handle_request_async(
+                            app_module_clone.clone(),
+                            reader_socket.clone(),
+                            &inflight,
+                            &requests,
+                            max_payload,
+                            &frame,
+                            data,
+                        )
+                        .await;
+                    }
+                    ParsedMsg::Res { id, raw_response } => {
+                        reader_socket.handle_response(id, raw_response.to_string());
+                    }
+                    ParsedMsg::Evt { data } if data.name == NODE_ID_CONFLICT => {
+                        // el master corta la conexión; se reintenta como en cualquier corte
+                        error!(target:"conn",
+                               "[{}] {} rechazó el id {}: otro nodo conectado lo usa",
+                               reader_socket.id, &*addr_reader, data.payload);
+                    }
+                    ParsedMsg::Evt { data } if data.name == GROUP_ASSIGNED => {
+                        let previous = groups.assign(&node_id, &data.payload);
+                        if previous.as_deref() != Some(&*data.payload) {
+                            info!(target:"conn", member_id = &*node_id, group = %data.payload,
+                                  "asignado al grupo");
+                        }
+                    }
+                    ParsedMsg::Evt { data } if data.name == HEARTBEAT => {
+                        let now_ms = app_module_clone.clock.now_millis().as_millis_u64();
+                        echo.record(&data.payload, now_ms);
+                    }
+                    ParsedMsg::Evt { data } if data.name == COMPRESS => {
+                        match data.payload.parse::<Compression>() {
+                            Ok(compression) => reader_socket.accept_compression(compression),
+                            Err(e) => warn!(target:"conn", "{e}"),
+                        }
+                    }
+                    ParsedMsg::Evt { data } if data.name == NODE_REFUSED => {
+                        error!(target:"conn",
+                               "[{}] {} no acepta este nodo: {}",
+                               reader_socket.id, &*addr_reader, data.payload);
+                    }
+                    ParsedMsg::Evt { data } => {
+                        trace!(target:"srv", name = data.name, "EVT ignorado");
+                    }
+                    ParsedMsg::Other(msg) => {
+                        info!(target:"srv", "[{}] {}", &*addr_reader, msg);
+                    }
+                }
+            }
+
+            Ok::<(), AppError>(())
+        });
+
+        // Espera fin del reader (o apagado); corta writer; backoff
+        let reader_abort = reader_task.abort_handle();
+        let res = tokio::select! {
+            res = reader_task => res,
+            _ = cancel.cancelled() => {
+                reader_abort.abort();
+                writer_task.abort();
+                background.iter().for_each(JoinHandle::abort);
+                info!(target:"conn", "Cerrando conexión a {}", &*addr_iter);
+                return Ok(());
+            }
+        };
+        writer_task.abort();
+        background.iter().for_each(JoinHandle::abort);
+
+        match res {
+            Ok(Ok(())) => info!(target:"conn", "Reader finalizó para {}", &*addr_iter),
+            Ok(Err(e)) => error!(target:"conn", "Reader error en {}: {:?}", &*addr_iter, e),
+            Err(e) => error!(target:"conn", "Reader panic en {}: {:?}", &*addr_iter, e),
+        }
+
+        if answered.load(Ordering::Relaxed) {
+            backoff.reset();
+        }
+        let delay = backoff.next_delay();
+        info!(target:"conn", "Reintentando {} en {:?}...", &*addr_iter, delay);
+        tokio::select! {
+            _ = cancel.cancelled() => return Ok(()),
+            _ = tokio::time::sleep(delay) => {}
+        }
+        // aquí termina la vida de `addr_iter`; en la siguiente vuelta clonamos `addr` de nuevo
+    }
+}
@@ -1,8 +1,14 @@
 #[cfg(test)]
 mod tests {
-    use app_core::clock::Clock;
+    use std::{sync::Arc, time::Duration};
 
-    use crate::core::services::Cache;
+    use app_core::clock::{AppClock, SimulatedClock};
+
+    use crate::core::services::{
+        BatchPut, Cache, EvictionPolicy, ExpiryStrategy, NamespaceAccounting, NamespaceQuota,
+        NamespaceQuotas, NamespaceStats, QuotaExceeded, QuotaMode, RenameOutcome, TxConflict,
+        TxOutcome, TxStep,
+    };
 
     #[test]
     fn test_put_and_len() {
@@ -28,12 +34,37 @@ mod tests {
         cache.put("key1", "value2", Some(exp));
         assert_eq!(cache.len(), 1);
 
-        let entry = cache.map.get("key1").unwrap();
+        let entry = cache.meta(&"key1").unwrap();
         assert_eq!(entry.value.as_ref(), &"value2");
         assert_eq!(entry.version, 2);
         assert!(entry.expires_at.is_some());
     }
 
+    #[test]
+    fn introspection_reports_keys_capacity_and_weighed_bytes() {
+        let cache = namespaced("", QuotaMode::Reject);
+        assert!(cache.is_empty());
+        assert_eq!(cache.capacity(), 100);
+
+        cache.put("a:1".into(), "xy".into(), None);
+        cache.put("b".into(), "z".into(), None);
+        assert!(!cache.is_empty());
+        assert_eq!(cache.memory_used(), 5 + 2);
+
+        let keys = cache.iter_keys();
+        // las claves ya se copiaron: se puede escribir mientras se recorren
+        cache.invalidate(&"b".to_string());
+        let mut keys: Vec<String> = keys.collect();
+        keys.sort();
+        assert_eq!(keys, ["a:1", "b"]);
+        assert_eq!(cache.memory_used(), 5);
+
+        // sin `NamespaceAccounting` no se pesa nada
+        let plain = Cache::<&str, &str>::new_with_capacity(7, 8, 1);
+        plain.put("k", "v", None);
+        assert_eq!((plain.capacity(), plain.memory_used()), (7, 0));
+    }
+
     #[test]
     fn get_returns_none_when_expired_in_past_and_does_not_remove() {
         let cache = Cache::<&str, &str>::new();
@@ -56,24 +87,35 @@ mod tests {
         assert!(cache.get(&"k3").is_none());
     }
 
+    fn simulated_cache(
+        wheel_size: usize,
+        tick_ms: u64,
+    ) -> (Arc<Cache<&'static str, &'static str>>, Arc<SimulatedClock>) {
+        let clock = Arc::new(SimulatedClock::new(1_000_000));
+        let cache = Cache::new_with_clock(128, wheel_size, tick_ms, clock.clone());
+        (cache, clock)
+    }
+
     #[test]
     fn wheel_expires_after_advancing_to_now() {
-        let cache = Cache::new_with_capacity(128, 16, 10);
+        let (cache, clock) = simulated_cache(16, 10);
 
         let exp = cache.clock.now_millis().as_millis_u64() + 30;
         cache.put("kx", "vx", Some(exp));
         assert!(cache.get(&"kx").is_some());
 
-        std::thread::sleep(std::time::Duration::from_millis(35));
+        // La rueda drena un slot cuando su tick completo ya pasó (granularidad = tick_ms)
+        clock.advance(Duration::from_millis(40));
         cache.advance_wheel_to_now();
 
-        assert!(cache.get(&"kx").is_none());
+        // La rueda lo borró sin necesidad de un get (expiración activa)
         assert!(!cache.contains_key(&"kx"));
+        assert!(cache.get(&"kx").is_none());
     }
 
     #[test]
     fn wheel_does_not_expire_if_ttl_extended_before_tick() {
-        let cache = Cache::new_with_capacity(128, 16, 10);
+        let (cache, clock) = simulated_cache(16, 10);
 
         let exp1 = cache.clock.now_millis().as_millis_u64() + 20;
         cache.put("kext", "v", Some(exp1));
@@ -82,12 +124,974 @@ mod tests {
         let exp2 = cache.clock.now_millis().as_millis_u64() + 200;
         cache.put("kext", "v", Some(exp2));
 
-        std::thread::sleep(std::time::Duration::from_millis(50));
+        clock.advance(Duration::from_millis(50));
         cache.advance_wheel_to_now();
         assert!(cache.get(&"kext").is_some());
 
-        std::thread::sleep(std::time::Duration::from_millis(170));
+        clock.advance(Duration::from_millis(170));
         cache.advance_wheel_to_now();
+        assert!(!cache.contains_key(&"kext"));
         assert!(cache.get(&"kext").is_none());
     }
+
+    #[test]
+    fn lazy_expiry_uses_injected_clock() {
+        let (cache, clock) = simulated_cache(16, 10);
+
+        let exp = cache.clock.now_millis().as_millis_u64() + 1_000;
+        cache.put("klazy", "v", Some(exp));
+
+        clock.advance(Duration::from_millis(999));
+        assert!(cache.get(&"klazy").is_some());
+
+        // Sin avanzar la rueda: el get detecta la expiración
+        clock.advance(Duration::from_millis(1));
+        assert!(cache.get(&"klazy").is_none());
+    }
+
+    #[test]
+    fn wheel_handles_ttl_longer_than_one_revolution() {
+        // 16 slots * 10ms = 160ms por vuelta; el TTL da más de una vuelta
+        let (cache, clock) = simulated_cache(16, 10);
+
+        let exp = cache.clock.now_millis().as_millis_u64() + 500;
+        cache.put("klong", "v", Some(exp));
+
+        clock.advance(Duration::from_millis(200));
+        cache.advance_wheel_to_now();
+        assert!(cache.contains_key(&"klong"));
+
+        clock.advance(Duration::from_millis(310));
+        cache.advance_wheel_to_now();
+        assert!(!cache.contains_key(&"klong"));
+    }
+
+    fn with_expiry(
+        expiry: ExpiryStrategy,
+    ) -> (Arc<Cache<&'static str, &'static str>>, Arc<SimulatedClock>) {
+        let clock = Arc::new(SimulatedClock::new(1_000_000));
+        let cache = Cache::new_with_namespaces(
+            128,
+            16,
+            10,
+            clock.clone(),
+            EvictionPolicy::Lru,
+            None,
+            expiry,
+        );
+        (cache, clock)
+    }
+
+    #[test]
+    fn lazy_expiry_leaves_expired_entries_until_they_are_read() {
+        let (cache, clock) = with_expiry(ExpiryStrategy::Lazy);
+        cache.put("a", "1", Some(1_000_020));
+
+        clock.advance(Duration::from_millis(50));
+        cache.advance_wheel_to_now();
+        assert!(cache.contains_key(&"a"));
+
+        assert!(cache.get(&"a").is_none());
+        assert!(!cache.contains_key(&"a"));
+        assert_eq!(cache.stats().expirations, 1);
+    }
+
+    #[test]
+    fn active_expiry_serves_expired_entries_until_the_reaper_runs() {
+        let (cache, clock) = with_expiry(ExpiryStrategy::Active);
+        cache.put("a", "1", Some(1_000_020));
+
+        clock.advance(Duration::from_millis(25));
+        assert_eq!(cache.get(&"a").as_deref(), Some(&"1"));
+        assert_eq!(cache.peek(&"a").as_deref(), Some(&"1"));
+
+        clock.advance(Duration::from_millis(25));
+        cache.advance_wheel_to_now();
+        assert!(!cache.contains_key(&"a"));
+        assert!(cache.get(&"a").is_none());
+    }
+
+    #[test]
+    fn expired_keys_reach_subscribers_whether_read_or_reaped() {
+        let (cache, clock) = with_expiry(ExpiryStrategy::Hybrid {
+            max_keys_per_tick: None,
+            budget: None,
+        });
+        // sin suscriptores no se junta nada
+        cache.put("antes", "0", Some(1_000_010));
+        clock.advance(Duration::from_millis(20));
+        assert!(cache.get(&"antes").is_none());
+
+        let mut expired = cache.subscribe_expired(8);
+        cache.put("leida", "1", Some(1_000_030));
+        cache.put("barrida", "2", Some(1_000_030));
+        clock.advance(Duration::from_millis(20));
+        assert!(cache.get(&"leida").is_none());
+        cache.advance_wheel_to_now();
+
+        assert_eq!(expired.try_recv().unwrap(), "leida");
+        assert_eq!(expired.try_recv().unwrap(), "barrida");
+        assert!(expired.try_recv().is_err());
+        assert_eq!(cache.stats().expirations, 3);
+    }
+
+    #[test]
+    fn hybrid_expiry_carries_keys_past_the_tick_limit_to_the_next_tick() {
+        let (cache, clock) = with_expiry(ExpiryStrategy::Hybrid {
+            max_keys_per_tick: Some(2),
+            budget: None,
+        });
+        for key in ["a", "b", "c", "d", "e"] {
+            cache.put(key, "1", Some(1_000_020));
+        }
+
+        clock.advance(Duration::from_millis(50));
+        cache.advance_wheel_to_now();
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.stats().expiry_backlog, 3);
+
+        cache.advance_wheel_to_now();
+        cache.advance_wheel_to_now();
+        assert_eq!(cache.len(), 0);
+        assert_eq!(cache.stats().expiry_backlog, 0);
+        assert_eq!(cache.stats().expirations, 5);
+    }
+
+    #[test]
+    fn the_reaper_reports_how_late_it_removed_entries_and_the_ticks_it_missed() {
+        let (cache, clock) = with_expiry(ExpiryStrategy::default());
+        cache.put("a", "1", Some(1_000_020));
+        cache.put("b", "1", Some(1_000_020));
+        cache.put("c", "1", Some(1_000_035));
+
+        clock.advance(Duration::from_millis(10));
+        cache.advance_wheel_to_now();
+        assert_eq!(cache.stats().expiry_missed_ticks, 0);
+
+        // el reaper corre 40 ms después: se saltea 3 ticks
+        clock.advance(Duration::from_millis(40));
+        cache.advance_wheel_to_now();
+        let stats = cache.stats();
+        assert_eq!(cache.len(), 0);
+        assert_eq!(stats.expiry_missed_ticks, 3);
+        assert_eq!(stats.expiry_tick_keys, 3);
+        assert_eq!(stats.expiry_reaped, 3);
+        assert_eq!(stats.expiry_drift_ms, 30 + 30 + 15);
+        assert_eq!(stats.expiry_drift_max_ms, 30);
+
+        // un tick sin nada que sacar
+        clock.advance(Duration::from_millis(10));
+        cache.advance_wheel_to_now();
+        let stats = cache.stats();
+        assert_eq!((stats.expiry_tick_keys, stats.expiry_drift_max_ms), (0, 0));
+        assert_eq!(stats.expiry_reaped, 3);
+    }
+
+    #[test]
+    fn stale_reads_see_expired_entries_only_within_the_grace() {
+        let (cache, clock) = with_expiry(ExpiryStrategy::default());
+        cache.set_stale_grace(Duration::from_millis(100));
+        cache.put("a", "1", Some(1_000_020));
+
+        // vigente: no está vencida ni se elige a nadie
+        assert_eq!(
+            cache.get_stale(&"a", 50),
+            Some((Arc::new("1"), false, None))
+        );
+
+        clock.advance(Duration::from_millis(50));
+        cache.advance_wheel_to_now();
+        // vencida hace 30ms: las lecturas normales no la ven, pero sigue guardada
+        assert!(cache.get(&"a").is_none());
+        assert!(cache.contains_key(&"a"));
+        assert!(cache.get_stale(&"a", 20).is_none());
+        assert_eq!(
+            cache.get_stale(&"a", 50),
+            Some((Arc::new("1"), true, Some(30)))
+        );
+        // solo el primero refresca
+        assert!(!cache.get_stale(&"a", 50).unwrap().1);
+
+        clock.advance(Duration::from_millis(80));
+        cache.advance_wheel_to_now();
+        assert!(!cache.contains_key(&"a"));
+        assert!(cache.get_stale(&"a", 1_000).is_none());
+        assert_eq!(cache.stats().expirations, 1);
+    }
+
+    #[test]
+    fn meta_does_not_count_as_an_access() {
+        let clock = Arc::new(SimulatedClock::new(1_000_000));
+        let cache = Cache::new_with_clock(2, 16, 10, clock.clone());
+
+        cache.put("a", "1", Some(1_000_500));
+        cache.put("b", "2", None);
+        clock.advance(Duration::from_millis(100));
+        assert!(cache.get(&"a").is_some());
+        clock.advance(Duration::from_millis(100));
+        assert!(cache.get(&"b").is_some());
+
+        clock.advance(Duration::from_millis(100));
+        let meta = cache.meta(&"a").unwrap();
+        assert_eq!(meta.version, 1);
+        assert_eq!(meta.expires_at.unwrap().as_millis_u64(), 1_000_500);
+        assert_eq!(meta.last_access.as_millis_u64(), 1_000_100);
+
+        // `a` sigue siendo la menos usada: la saca el próximo alta
+        cache.put("c", "3", None);
+        assert!(!cache.contains_key(&"a"));
+        assert!(cache.contains_key(&"b"));
+    }
+
+    #[test]
+    fn peek_reads_without_touching_recency_or_expired_entries() {
+        let clock = Arc::new(SimulatedClock::new(1_000_000));
+        let cache = Cache::new_with_clock(2, 16, 10, clock.clone());
+
+        cache.put("a", "1", Some(1_000_500));
+        cache.put("b", "2", None);
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(cache.peek(&"a").as_deref(), Some(&"1"));
+        assert_eq!(
+            cache.meta(&"a").unwrap().last_access.as_millis_u64(),
+            1_000_000
+        );
+
+        clock.advance(Duration::from_millis(500));
+        assert!(cache.peek(&"a").is_none());
+        assert!(cache.contains_key(&"a"));
+        assert_eq!(cache.stats().expirations, 0);
+
+        // `get` sí la borra; con `b` nunca leída, el alta siguiente la desaloja
+        assert!(cache.get(&"a").is_none());
+        cache.put("c", "3", None);
+        assert_eq!(cache.peek(&"b").as_deref(), Some(&"2"));
+        cache.put("d", "4", None);
+        assert!(!cache.contains_key(&"b"));
+    }
+
+    #[test]
+    fn snapshot_iter_skips_expired_and_removed_keys_while_the_cache_stays_writable() {
+        let clock = Arc::new(SimulatedClock::new(1_000_000));
+        let cache = Cache::new_with_clock(16, 16, 10, clock.clone());
+
+        cache.put("a", "1", None);
+        cache.put("b", "2", Some(1_000_500));
+        cache.put("c", "3", None);
+        cache.put("d", "4", Some(1_000_100));
+        clock.advance(Duration::from_millis(200));
+
+        // con el recorrido abierto se puede escribir: lo que sale ya no aparece, lo que
+        // cambia aparece con el valor nuevo y lo que entra después no aparece
+        let iter = cache.snapshot_iter();
+        cache.invalidate(&"c");
+        cache.put("a", "nuevo", None);
+        cache.put("e", "5", None);
+
+        let mut seen: Vec<_> = iter.map(|e| (e.key, *e.value)).collect();
+        seen.sort();
+        assert_eq!(seen, vec![("a", "nuevo"), ("b", "2")]);
+        assert_eq!(cache.len(), 4);
+    }
+
+    #[test]
+    fn stats_count_evictions_and_expirations_apart() {
+        let clock = Arc::new(SimulatedClock::new(1_000_000));
+        let cache = Cache::new_with_clock(2, 16, 10, clock.clone());
+
+        cache.put("a", "1", Some(1_000_050));
+        cache.put("b", "2", Some(1_000_050));
+        cache.put("c", "3", None);
+        clock.advance(Duration::from_millis(60));
+        // `b` vence por el get, `a` ya la había sacado el LRU
+        assert!(cache.get(&"b").is_none());
+        assert!(cache.get(&"a").is_none());
+
+        let stats = cache.stats();
+        assert_eq!((stats.entries, stats.capacity), (1, 2));
+        assert_eq!((stats.evictions, stats.expirations), (1, 1));
+
+        cache.put("d", "4", Some(1_000_070));
+        clock.advance(Duration::from_millis(30));
+        cache.advance_wheel_to_now();
+        assert_eq!(cache.stats().expirations, 2);
+    }
+
+    #[test]
+    fn stats_count_invalidations_apart_from_expirations() {
+        let clock = Arc::new(SimulatedClock::new(1_000_000));
+        let cache = Cache::new_with_clock(8, 16, 10, clock.clone());
+
+        cache.put_tagged("a", "1", None, &["t".to_string()]);
+        cache.put_tagged("b", "2", None, &["t".to_string()]);
+        cache.put("c", "3", None);
+        cache.put("d", "4", Some(1_000_010));
+        cache.put("e", "5", None);
+
+        assert_eq!(cache.invalidate_tag("t").len(), 2);
+        assert!(cache.invalidate(&"c"));
+        assert!(!cache.invalidate(&"c"));
+        let outcomes = cache
+            .transact(&[], vec![TxStep::Del("e"), TxStep::Del("e")])
+            .unwrap();
+        assert_eq!(
+            outcomes,
+            [TxOutcome::Removed(true), TxOutcome::Removed(false)]
+        );
+        clock.advance(Duration::from_millis(20));
+        assert!(cache.get(&"d").is_none());
+
+        let stats = cache.stats();
+        assert_eq!(
+            (stats.invalidations, stats.expirations, stats.evictions),
+            (4, 1, 0)
+        );
+    }
+
+    #[tokio::test]
+    async fn reaper_stops_when_cancelled() {
+        let (cache, _clock) = simulated_cache(8, 1);
+        let cancel = tokio_util::sync::CancellationToken::new();
+        let reaper = tokio::spawn(cache.clone().reaper(cancel.clone()));
+
+        cancel.cancel();
+        tokio::time::timeout(Duration::from_secs(1), reaper)
+            .await
+            .expect("reaper ignoró la cancelación")
+            .unwrap();
+    }
+
+    #[test]
+    fn transact_applies_the_steps_in_order_when_the_watches_match() {
+        let cache = Cache::<&str, &str>::new();
+        cache.put("a", "1", None);
+
+        let out = cache
+            .transact(
+                &[("a", 1), ("b", 0)],
+                vec![
+                    TxStep::Put {
+                        key: "a",
+                        value: "2",
+                        expires_at: None,
+                    },
+                    TxStep::Get("a"),
+                    TxStep::Version("a"),
+                    TxStep::Del("b"),
+                ],
+            )
+            .unwrap();
+
+        assert!(matches!(out[0], TxOutcome::Stored));
+        assert!(matches!(&out[1], TxOutcome::Value(Some(v)) if **v == "2"));
+        assert!(matches!(out[2], TxOutcome::Version(2)));
+        assert!(matches!(out[3], TxOutcome::Removed(false)));
+    }
+
+    #[test]
+    fn transact_applies_nothing_when_a_watch_is_stale() {
+        let cache = Cache::<&str, &str>::new();
+        cache.put("a", "1", None);
+        cache.put("a", "2", None);
+
+        let err = cache
+            .transact(
+                &[("a", 1)],
+                vec![
+                    TxStep::Del("a"),
+                    TxStep::Put {
+                        key: "b",
+                        value: "x",
+                        expires_at: None,
+                    },
+                ],
+            )
+            .unwrap_err();
+
+        assert_eq!(
+            err,
+            TxConflict {
+                key: "a",
+                version: 2
+            }
+        );
+        assert_eq!(cache.get(&"a").as_deref(), Some(&"2"));
+        assert!(cache.get(&"b").is_none());
+    }
+
+    #[test]
+    fn invalidate_where_removes_the_matching_keys_in_batches() {
+        let cache = Cache::<String, u32>::new_with_clock(4096, 16, 10, Arc::new(AppClock::new()));
+        for i in 0..3000 {
+            let group = if i % 3 == 0 { "a" } else { "b" };
+            cache.put(format!("{group}:{i}"), i, None);
+        }
+
+        assert_eq!(cache.count_where(|k| k.starts_with("a:")), 1000);
+        let removed = cache.invalidate_where(|k| k.starts_with("a:"));
+        assert_eq!(removed.len(), 1000);
+        assert!(removed.iter().all(|k| k.starts_with("a:")));
+        assert_eq!(cache.len(), 2000);
+        assert_eq!(cache.count_where(|k| k.starts_with("a:")), 0);
+        assert_eq!(cache.stats().invalidations, 1000);
+    }
+
+    #[test]
+    fn rename_moves_value_expiry_and_tags_without_overwriting() {
+        let clock = Arc::new(SimulatedClock::new(1_000_000));
+        let cache = Cache::new_with_clock(8, 16, 10, clock.clone());
+        cache.put_tagged("a", "1", Some(1_000_050), &["t".to_string()]);
+        cache.put("c", "3", None);
+
+        assert_eq!(cache.rename(&"a", "c"), Ok(RenameOutcome::TargetExists));
+        assert_eq!(cache.rename(&"a", "a"), Ok(RenameOutcome::TargetExists));
+        assert_eq!(cache.rename(&"x", "y"), Ok(RenameOutcome::Missing));
+        assert_eq!(cache.rename(&"a", "b"), Ok(RenameOutcome::Moved));
+
+        assert!(cache.get(&"a").is_none());
+        let moved = cache.meta(&"b").unwrap();
+        assert_eq!(moved.value.as_ref(), &"1");
+        assert_eq!(moved.version, 1);
+        assert_eq!(moved.expires_at.map(|t| t.as_millis_u64()), Some(1_000_050));
+        assert_eq!(cache.tags(&"b"), ["t"]);
+        assert_eq!(cache.len(), 2);
+
+        // el vencimiento viaja con la entrada
+        clock.advance(Duration::from_millis(60));
+        cache.advance_wheel_to_now();
+        assert!(cache.get(&"b").is_none());
+        assert_eq!(cache.rename(&"b", "d"), Ok(RenameOutcome::Missing));
+    }
+
+    #[test]
+    fn put_if_checks_the_live_entry_before_writing() {
+        let (cache, clock) = simulated_cache(8, 1);
+        let exp = 1_000_010;
+        assert_eq!(
+            cache.put_if("a", "1", Some(exp), &[], |current| current.is_none()),
+            Ok(true)
+        );
+        assert_eq!(
+            cache.put_if("a", "2", None, &[], |current| current.is_none()),
+            Ok(false)
+        );
+        assert_eq!(
+            cache.put_if("a", "2", None, &[], |current| current == Some((&"1", 1))),
+            Ok(true)
+        );
+
+        // una entrada vencida cuenta como ausente
+        cache.put("b", "1", Some(exp));
+        clock.advance(Duration::from_millis(20));
+        assert_eq!(
+            cache.put_if("b", "2", None, &[], |current| current.is_none()),
+            Ok(true)
+        );
+        assert_eq!(cache.meta(&"b").unwrap().version, 1);
+    }
+
+    #[test]
+    fn invalidate_tag_removes_only_the_keys_still_tagged() {
+        let cache = Cache::<&str, &str>::new_with_capacity(3, 8, 1);
+        let user = ["user:42".to_string()];
+        let both = ["user:42".to_string(), "session".to_string()];
+
+        cache.put_tagged("a", "1", None, &user);
+        cache.put_tagged("b", "1", None, &both);
+        cache.put_tagged("c", "1", None, &user);
+        // reescrita sin tags: ya no es del tag
+        cache.put("c", "2", None);
+        // desaloja `a`
+        cache.put("d", "1", None);
+        assert_eq!(cache.tags(&"b"), both);
+
+        assert_eq!(cache.invalidate_tag("user:42"), vec!["b"]);
+        assert!(cache.get(&"b").is_none());
+        assert_eq!(cache.get(&"c").as_deref(), Some(&"2"));
+        assert!(cache.invalidate_tag("session").is_empty());
+        assert!(cache.tags(&"b").is_empty());
+    }
+
+    #[test]
+    fn get_for_refresh_picks_one_reader_per_write_near_expiry() {
+        let (cache, clock) = simulated_cache(8, 1);
+        cache.put("a", "1", Some(1_001_000));
+        cache.put("sin-ttl", "1", None);
+
+        // fuera de la ventana no elige aunque el azar dé lo máximo
+        assert_eq!(
+            cache.get_for_refresh(&"a", 500, 0.99),
+            Some((Arc::new("1"), false))
+        );
+
+        clock.advance(Duration::from_millis(800));
+        // a 200ms del vencimiento con ventana de 500ms la chance es 0.6
+        assert!(!cache.get_for_refresh(&"a", 500, 0.3).unwrap().1);
+        assert!(cache.get_for_refresh(&"a", 500, 0.5).unwrap().1);
+        assert!(!cache.get_for_refresh(&"a", 500, 0.99).unwrap().1);
+        assert!(!cache.get_for_refresh(&"sin-ttl", 500, 0.99).unwrap().1);
+
+        // la reescritura vuelve a habilitar la elección
+        cache.put("a", "2", Some(1_001_100));
+        assert_eq!(
+            cache.get_for_refresh(&"a", 500, 0.9),
+            Some((Arc::new("2"), true))
+        );
+    }
+
+    fn cache_with(policy: EvictionPolicy, capacity: usize) -> Arc<Cache<u32, u32>> {
+        Cache::new_with_policy(capacity, 64, 1000, Arc::new(AppClock::new()), policy)
+    }
+
+    #[test]
+    fn resize_evicts_down_to_the_new_capacity_and_keeps_it() {
+        for policy in [
+            EvictionPolicy::Lru,
+            EvictionPolicy::Slru,
+            EvictionPolicy::TinyLfu,
+            EvictionPolicy::Sampled { samples: 5 },
+        ] {
+            let cache = cache_with(policy, 100);
+            for key in 0..100 {
+                cache.put(key, key, None);
+            }
+
+            assert_eq!(cache.resize(40), 60, "{policy:?}");
+            assert_eq!(cache.len(), 40, "{policy:?}");
+            assert_eq!(cache.capacity(), 40);
+            assert_eq!(cache.stats().evictions, 60);
+
+            // la capacidad nueva se respeta en los PUT siguientes
+            for key in 100..200 {
+                cache.put(key, key, None);
+            }
+            assert_eq!(cache.len(), 40, "{policy:?}");
+
+            // crecer no desaloja y deja lugar
+            assert_eq!(cache.resize(150), 0);
+            for key in 200..300 {
+                cache.put(key, key, None);
+            }
+            assert_eq!(cache.len(), 140, "{policy:?}");
+        }
+    }
+
+    #[test]
+    fn compact_returns_the_room_left_by_a_drained_peak() {
+        for policy in [
+            EvictionPolicy::Lru,
+            EvictionPolicy::Slru,
+            EvictionPolicy::TinyLfu,
+            EvictionPolicy::Sampled { samples: 5 },
+        ] {
+            let cache = cache_with(policy, 10_000);
+            let far = cache.clock.now_millis().as_millis_u64() + 3_600_000;
+            for key in 0..10_000 {
+                cache.put(key, key, Some(far));
+            }
+            // a la medida: no hay nada que soltar
+            assert_eq!(cache.compact(), 0, "{policy:?}");
+
+            for key in 10..10_000 {
+                cache.invalidate(&key);
+            }
+            assert!(cache.compact() > 0, "{policy:?}");
+            assert_eq!(cache.compact(), 0, "{policy:?}");
+
+            let stats = cache.stats();
+            assert_eq!(stats.compactions, 1);
+            assert!(stats.compacted_bytes > 0);
+            // sigue funcionando igual
+            assert_eq!(cache.len(), 10);
+            assert_eq!(cache.get(&5).as_deref(), Some(&5));
+            cache.put(20_000, 1, None);
+            assert!(cache.invalidate(&3));
+        }
+    }
+
+    #[test]
+    fn tinylfu_keeps_frequent_keys_among_one_hit_wonders() {
+        for (policy, survivors) in [(EvictionPolicy::Lru, 0), (EvictionPolicy::TinyLfu, 50)] {
+            let cache = cache_with(policy, 100);
+            let mut once = 1_000..;
+            for _ in 0..20 {
+                for key in 0..50 {
+                    if cache.get(&key).is_none() {
+                        cache.put(key, key, None);
+                    }
+                }
+                // claves que se escriben una vez y no se vuelven a leer
+                for key in once.by_ref().take(100) {
+                    cache.put(key, key, None);
+                }
+            }
+
+            let kept = (0..50).filter(|key| cache.contains_key(key)).count();
+            assert_eq!(kept, survivors, "{policy:?}");
+            assert_eq!(cache.len(), 100);
+        }
+    }
+
+    #[test]
+    fn tinylfu_hits_more_than_lru_on_a_zipfian_workload() {
+        let mut cumulative = Vec::new();
+        let mut total = 0.0;
+        for rank in 1..=10_000 {
+            total += 1.0 / f64::from(rank).powf(0.9);
+            cumulative.push(total);
+        }
+        let mut rng = fastrand::Rng::with_seed(7);
+        let accesses: Vec<u32> = (0..50_000)
+            .map(|_| cumulative.partition_point(|c| *c < rng.f64() * total) as u32)
+            .collect();
+
+        let hits = |policy| {
+            let cache = cache_with(policy, 200);
+            accesses
+                .iter()
+                .filter(|key| {
+                    let hit = cache.get(key).is_some();
+                    if !hit {
+                        cache.put(**key, **key, None);
+                    }
+                    hit
+                })
+                .count()
+        };
+        let (lru, lfu) = (hits(EvictionPolicy::Lru), hits(EvictionPolicy::TinyLfu));
+        assert!(lfu > lru + lru / 20, "lru {lru} tinylfu {lfu}");
+    }
+
+    #[test]
+    fn slru_keeps_keys_read_twice_among_one_hit_wonders() {
+        for (policy, survivors) in [(EvictionPolicy::Lru, 0), (EvictionPolicy::Slru, 50)] {
+            let cache = cache_with(policy, 100);
+            let read_hot = || {
+                for key in 0..50 {
+                    if cache.get(&key).is_none() {
+                        cache.put(key, key, None);
+                    }
+                }
+            };
+            // el segmento protegido es para las que ya se leyeron antes de los recorridos
+            read_hot();
+
+            let mut once = 1_000..;
+            for _ in 0..20 {
+                read_hot();
+                // claves que se escriben una vez y no se vuelven a leer
+                for key in once.by_ref().take(100) {
+                    cache.put(key, key, None);
+                }
+            }
+
+            let kept = (0..50).filter(|key| cache.contains_key(key)).count();
+            assert_eq!(kept, survivors, "{policy:?}");
+            assert_eq!(cache.len(), 100);
+        }
+    }
+
+    #[test]
+    fn slru_hits_more_than_lru_on_a_zipfian_workload() {
+        let mut cumulative = Vec::new();
+        let mut total = 0.0;
+        for rank in 1..=10_000 {
+            total += 1.0 / f64::from(rank).powf(0.9);
+            cumulative.push(total);
+        }
+        let mut rng = fastrand::Rng::with_seed(7);
+        let accesses: Vec<u32> = (0..50_000)
+            .map(|_| cumulative.partition_point(|c| *c < rng.f64() * total) as u32)
+            .collect();
+
+        let hits = |policy| {
+            let cache = cache_with(policy, 200);
+            accesses
+                .iter()
+                .filter(|key| {
+                    let hit = cache.get(key).is_some();
+                    if !hit {
+                        cache.put(**key, **key, None);
+                    }
+                    hit
+                })
+                .count()
+        };
+        let (lru, slru) = (hits(EvictionPolicy::Lru), hits(EvictionPolicy::Slru));
+        assert!(slru > lru + lru / 20, "lru {lru} slru {slru}");
+    }
+
+    #[test]
+    fn buffered_reads_reach_the_lru_before_the_next_eviction() {
+        for policy in [EvictionPolicy::Lru, EvictionPolicy::Slru] {
+            let cache = cache_with(policy, 3);
+            for key in 1..=3 {
+                cache.put(key, key, None);
+            }
+            assert!(cache.get(&1).is_some());
+
+            cache.put(4, 4, None);
+            assert!(cache.contains_key(&1), "{policy:?}");
+            assert!(!cache.contains_key(&2), "{policy:?}");
+        }
+    }
+
+    #[test]
+    fn concurrent_reads_and_writes_keep_the_lru_in_sync() {
+        let cache = cache_with(EvictionPolicy::Lru, 100);
+
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                let cache = &cache;
+                scope.spawn(move || {
+                    for i in 0..5_000u32 {
+                        cache.get(&(i % 400));
+                    }
+                });
+            }
+            for key in 0..1_000u32 {
+                cache.put(key % 400, key, None);
+            }
+        });
+
+        assert_eq!(cache.len(), 100);
+        let removed = (0..400u32).filter(|key| cache.invalidate(key)).count();
+        assert_eq!(removed, 100);
+
+        // sin claves viejas en el LRU: llenarlo de nuevo no desaloja nada
+        let evictions = cache.stats().evictions;
+        for key in 1_000..1_100u32 {
+            cache.put(key, key, None);
+            assert!(cache.get(&key).is_some());
+        }
+        assert_eq!(cache.stats().evictions, evictions);
+        assert_eq!(cache.len(), 100);
+    }
+
+    #[test]
+    fn sampled_eviction_prefers_entries_not_accessed_lately() {
+        let clock = Arc::new(SimulatedClock::new(1_000_000));
+        let policy = EvictionPolicy::Sampled { samples: 5 };
+        let cache = Cache::new_with_policy(100, 64, 1000, clock.clone(), policy);
+
+        for key in 0..100u32 {
+            cache.put(key, key, None);
+            clock.advance(Duration::from_millis(1));
+        }
+        for key in 100..300u32 {
+            for hot in 0..5 {
+                assert!(cache.get(&hot).is_some(), "{hot}");
+            }
+            cache.put(key, key, None);
+            clock.advance(Duration::from_millis(1));
+        }
+
+        assert_eq!(cache.len(), 100);
+        assert_eq!(cache.stats().evictions, 200);
+        assert!(cache.contains_key(&299));
+    }
+
+    #[test]
+    fn sampled_eviction_keeps_the_capacity_with_concurrent_puts() {
+        let policy = EvictionPolicy::Sampled { samples: 5 };
+        let cache = cache_with(policy, 100);
+        let tags = ["t".to_string()];
+
+        std::thread::scope(|scope| {
+            for thread in 0..8u32 {
+                let cache = &cache;
+                let tags = &tags;
+                scope.spawn(move || {
+                    for i in 0..1_000 {
+                        cache.put_tagged(thread * 1_000 + i, i, None, tags);
+                    }
+                });
+            }
+        });
+
+        assert_eq!(cache.len(), 100);
+        assert_eq!(cache.stats().evictions, 7_900);
+        // el índice de tags quedó igual que el map
+        assert_eq!(cache.invalidate_tag("t").len(), 100);
+        assert_eq!(cache.len(), 0);
+    }
+
+    #[test]
+    fn eviction_policy_parses_from_env() {
+        assert_eq!("lru".parse(), Ok(EvictionPolicy::Lru));
+        assert_eq!("SLRU".parse(), Ok(EvictionPolicy::Slru));
+        assert_eq!(" TinyLFU ".parse(), Ok(EvictionPolicy::TinyLfu));
+        assert_eq!(
+            "sampled".parse(),
+            Ok(EvictionPolicy::Sampled {
+                samples: EvictionPolicy::DEFAULT_SAMPLES
+            })
+        );
+        assert!("lfu".parse::<EvictionPolicy>().is_err());
+    }
+
+    fn namespaced(quotas: &str, mode: QuotaMode) -> Arc<Cache<String, String>> {
+        let mut quotas: NamespaceQuotas = quotas.parse().unwrap();
+        quotas.mode = mode;
+        let namespaces = NamespaceAccounting::new(
+            quotas,
+            |k: &String| k.split_once(':').map(|(ns, _)| ns),
+            |k: &String, v: &String| (k.len() + v.len()) as u64,
+        );
+        Cache::new_with_namespaces(
+            100,
+            64,
+            1000,
+            Arc::new(AppClock::new()),
+            EvictionPolicy::Lru,
+            Some(namespaces),
+            ExpiryStrategy::default(),
+        )
+    }
+
+    fn stats_of(cache: &Cache<String, String>, namespace: &str) -> NamespaceStats {
+        cache
+            .namespace_stats()
+            .into_iter()
+            .find(|(ns, _)| ns == namespace)
+            .map(|(_, stats)| stats)
+            .unwrap()
+    }
+
+    #[test]
+    fn namespace_quota_rejects_writes_past_the_limit() {
+        let cache = namespaced("a=2", QuotaMode::Reject);
+
+        assert!(cache.put("a:1".into(), "x".into(), None));
+        assert!(cache.put("a:2".into(), "x".into(), None));
+        assert!(!cache.put("a:3".into(), "x".into(), None));
+        // reemplazar no suma entradas, y los demás namespaces no tienen tope
+        assert!(cache.put("a:1".into(), "y".into(), None));
+        assert!(cache.put("b:1".into(), "x".into(), None));
+
+        assert!(cache.get(&"a:3".to_string()).is_none());
+        let a = stats_of(&cache, "a");
+        assert_eq!((a.entries, a.bytes, a.rejected), (2, 8, 1));
+        assert_eq!(stats_of(&cache, "b").entries, 1);
+
+        cache.invalidate(&"a:1".to_string());
+        assert!(cache.put("a:3".into(), "x".into(), None));
+        assert_eq!(stats_of(&cache, "a").entries, 2);
+    }
+
+    fn batch_put(key: &str, value: &str) -> BatchPut<String, String> {
+        BatchPut {
+            key: key.into(),
+            value: value.into(),
+            expires_at: None,
+            tags: vec![],
+        }
+    }
+
+    #[test]
+    fn put_batch_applies_in_order_with_a_result_for_each_put() {
+        let cache = namespaced("a=1", QuotaMode::Reject);
+
+        let stored = cache.put_batch(vec![
+            batch_put("a:1", "x"),
+            batch_put("a:2", "x"),
+            batch_put("b:1", "x"),
+            batch_put("b:1", "y"),
+        ]);
+
+        assert_eq!(stored, vec![true, false, true, true]);
+        assert!(cache.get(&"a:2".to_string()).is_none());
+        // el último de la misma clave gana, como con `put` seguidos
+        let (value, version) = cache.get_versioned(&"b:1".to_string()).unwrap();
+        assert_eq!((value.as_str(), version), ("y", 2));
+        assert_eq!(cache.stats().writes, 3);
+    }
+
+    #[test]
+    fn put_batch_keeps_the_capacity_with_every_policy() {
+        for policy in ["lru", "sampled"] {
+            let cache = cache_with(policy.parse().unwrap(), 8);
+            let puts = (0..32u32).map(|key| BatchPut {
+                key,
+                value: key,
+                expires_at: Some(u64::MAX),
+                tags: vec![],
+            });
+
+            assert!(
+                cache
+                    .put_batch(puts.collect())
+                    .into_iter()
+                    .all(|stored| stored)
+            );
+            assert_eq!(cache.len(), 8, "{policy}");
+            assert_eq!(cache.stats().evictions, 24, "{policy}");
+        }
+    }
+
+    #[test]
+    fn namespace_byte_quota_keeps_the_old_value_on_reject() {
+        let cache = namespaced("a=/10", QuotaMode::Reject);
+
+        assert!(cache.put("a:1".into(), "1234567".into(), None));
+        assert_eq!(
+            cache.put_if("a:1".into(), "12345678".into(), None, &[], |_| true),
+            Err(QuotaExceeded {
+                namespace: "a".into()
+            })
+        );
+
+        assert_eq!(cache.get(&"a:1".to_string()).unwrap().as_str(), "1234567");
+        assert_eq!(stats_of(&cache, "a").bytes, 10);
+    }
+
+    #[test]
+    fn namespace_quota_in_evict_mode_drops_keys_of_the_same_namespace() {
+        let cache = namespaced("a=1", QuotaMode::Evict);
+        cache.put("b:1".into(), "x".into(), None);
+
+        assert!(cache.put("a:1".into(), "x".into(), None));
+        assert!(cache.put("a:2".into(), "x".into(), None));
+
+        assert!(cache.get(&"a:1".to_string()).is_none());
+        assert!(cache.get(&"a:2".to_string()).is_some());
+        assert!(cache.get(&"b:1".to_string()).is_some());
+        let a = stats_of(&cache, "a");
+        assert_eq!((a.entries, a.evictions, a.rejected), (1, 1, 0));
+        assert_eq!(cache.stats().evictions, 1);
+    }
+
+    #[test]
+    fn stats_count_reads_writes_and_bytes_of_every_entry() {
+        let cache = namespaced("", QuotaMode::Reject);
+        cache.put("a:1".into(), "x".into(), None);
+        cache.put("k".into(), "vv".into(), None);
+        assert!(cache.get(&"a:1".to_string()).is_some());
+        assert!(cache.get(&"nope".to_string()).is_none());
+        cache.put("k".into(), "v".into(), None);
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.writes), (1, 1, 3));
+        assert_eq!(stats.bytes, 6);
+
+        cache.invalidate(&"a:1".to_string());
+        assert_eq!(cache.stats().bytes, 2);
+    }
+
+    #[test]
+    fn namespace_quotas_parse_from_env() {
+        let quotas: NamespaceQuotas = "tenant-a=1000/1048576, tenant-b=500".parse().unwrap();
+        assert_eq!(
+            quotas.quotas["tenant-a"],
+            NamespaceQuota {
+                max_entries: Some(1000),
+                max_bytes: Some(1_048_576)
+            }
+        );
+        assert_eq!(quotas.quotas["tenant-b"].max_bytes, None);
+        assert_eq!(quotas.mode, QuotaMode::Reject);
+        assert_eq!("EVICT".parse(), Ok(QuotaMode::Evict));
+        assert!("a=lots".parse::<NamespaceQuotas>().is_err());
+        assert!("tenant-a".parse::<NamespaceQuotas>().is_err());
+    }
 }
@@ -0,0 +1,178 @@
+//! Modelo reducido de `Cache` para explorar con loom los entrelazados entre el map
+//! (un shard de DashMap), el mutex del LRU y las evicciones. El wheel no participa
+//! del invariante y queda afuera.
+//!
+//! `cargo test -p cache_node --features loom --release cache_loom`
+
+#[cfg(all(test, feature = "loom"))]
+mod tests {
+    use std::collections::HashMap;
+
+    use loom::sync::{Arc, Mutex};
+    use loom::thread;
+
+    use crate::core::services::cache::lru::LruState;
+
+    const KEYS: [u8; 3] = [0, 1, 2];
+
+    /// `ordered = true` replica `Cache` actual (altas/bajas del map bajo el lock del
+    /// LRU); `false`, el orden anterior (map primero, LRU después).
+    struct Model {
+        capacity: usize,
+        ordered: bool,
+        map: Mutex<HashMap<u8, u32>>,
+        lru: Mutex<LruState<u8>>,
+    }
+
+    impl Model {
+        fn new(capacity: usize, ordered: bool) -> Arc<Self> {
+            Arc::new(Self {
+                capacity,
+                ordered,
+                map: Mutex::new(HashMap::new()),
+                lru: Mutex::new(LruState::new(capacity)),
+            })
+        }
+
+        fn evict(&self, lru: &mut LruState<u8>, keep: u8) -> Option<u8> {
+            if !lru.over_capacity() {
+                return None;
+            }
+            lru.pop_back().filter(|k| *k != keep)
+        }
+
+        fn put(&self, key: u8, value: u32) {
+            if self.ordered {
+                let mut lru = self.lru.lock().unwrap();
+                self.map.lock().unwrap().insert(key, value);
+                lru.touch(key);
+                if let Some(e) = self.evict(&mut lru, key) {
+                    self.map.lock().unwrap().remove(&e);
+                }
+            } else {
+                self.map.lock().unwrap().insert(key, value);
+                let evicted = {
+                    let mut lru = self.lru.lock().unwrap();
+                    lru.touch(key);
+                    self.evict(&mut lru, key)
+                };
+                if let Some(e) = evicted {
+                    self.map.lock().unwrap().remove(&e);
+                }
+            }
+        }
+
+        fn get(&self, key: u8) -> Option<u32> {
+            let value = *self.map.lock().unwrap().get(&key)?;
+            let mut lru = self.lru.lock().unwrap();
+            if self.ordered && !self.map.lock().unwrap().contains_key(&key) {
+                return Some(value);
+            }
+            lru.touch(key);
+            if let Some(e) = self.evict(&mut lru, key) {
+                self.map.lock().unwrap().remove(&e);
+            }
+            Some(value)
+        }
+
+        fn invalidate(&self, key: u8) {
+            if self.ordered {
+                let mut lru = self.lru.lock().unwrap();
+                self.map.lock().unwrap().remove(&key);
+                lru.remove(&key);
+            } else {
+                self.map.lock().unwrap().remove(&key);
+                self.lru.lock().unwrap().remove(&key);
+            }
+        }
+
+        /// Invariante: el map y el LRU tienen exactamente las mismas claves.
+        fn check(&self) {
+            let map = self.map.lock().unwrap();
+            let lru = self.lru.lock().unwrap();
+            for k in KEYS {
+                assert_eq!(
+                    map.contains_key(&k),
+                    lru.contains(&k),
+                    "clave {k} desincronizada"
+                );
+            }
+            assert!(map.len() <= self.capacity);
+        }
+    }
+
+    fn run<A, B>(model: Arc<Model>, a: A, b: B)
+    where
+        A: Fn(&Model) + Send + Sync + 'static,
+        B: Fn(&Model) + Send + Sync + 'static,
+    {
+        let m = model.clone();
+        let t = thread::spawn(move || a(&m));
+        b(&model);
+        t.join().unwrap();
+        model.check();
+    }
+
+    #[test]
+    fn concurrent_puts_with_eviction_stay_in_sync() {
+        loom::model(|| {
+            let model = Model::new(1, true);
+            model.put(0, 0);
+            run(model, |m| m.put(1, 1), |m| m.put(0, 2));
+        });
+    }
+
+    #[test]
+    fn put_vs_invalidate_same_key_stay_in_sync() {
+        loom::model(|| {
+            let model = Model::new(2, true);
+            model.put(0, 0);
+            run(model, |m| m.invalidate(0), |m| m.put(0, 1));
+        });
+    }
+
+    #[test]
+    fn get_does_not_resurrect_invalidated_keys() {
+        loom::model(|| {
+            let model = Model::new(2, true);
+            model.put(0, 0);
+            run(
+                model,
+                |m| m.invalidate(0),
+                |m| {
+                    let _ = m.get(0);
+                },
+            );
+        });
+    }
+
+    #[test]
+    fn get_and_put_evicting_each_other_stay_in_sync() {
+        loom::model(|| {
+            let model = Model::new(2, true);
+            model.put(0, 0);
+            model.put(1, 1);
+            run(
+                model,
+                |m| {
+                    let _ = m.get(0);
+                },
+                |m| m.put(2, 2),
+            );
+        });
+    }
+
+    #[test]
+    fn previous_ordering_is_caught_by_the_model() {
+        // Con el map fuera del lock del LRU, loom encuentra un entrelazado que rompe
+        // el invariante. Si esto deja de fallar, el modelo dejó de ser útil.
+        let result = std::panic::catch_unwind(|| {
+            loom::model(|| {
+                let model = Model::new(2, false);
+                model.put(0, 0);
+                run(model, |m| m.invalidate(0), |m| m.put(0, 1));
+            });
+        });
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use app_core::error::ErrorKind;
+    use async_trait::async_trait;
+
+    use crate::{
+        core::{
+            commands::PingCommand,
+            domain::{
+                models::{NodeRole, Response, RoleState},
+                services::CommandHandler,
+            },
+            services::{CommandRegistry, request_controller_service::RequestControllerService},
+        },
+        tests::test_mocks::{cache_service_mock::MockCache, controller::controller_for},
+    };
+
+    /// Comando de prueba que no es de base.
+    struct Upper;
+
+    #[async_trait]
+    impl CommandHandler for Upper {
+        fn action(&self) -> &'static str {
+            "UPPER"
+        }
+
+        fn is_write(&self) -> bool {
+            true
+        }
+
+        async fn handle(&self, payload: &str) -> Response {
+            Response::Value(payload.to_uppercase())
+        }
+    }
+
+    #[test]
+    fn builtins_are_registered() {
+        let (controller, _) =
+            controller_for(Arc::new(MockCache::new()), Arc::new(RoleState::default()));
+
+        assert_eq!(
+            controller.commands().actions(),
+            vec![
+                "CONFIG",
+                "DEL",
+                "DEL-PREFIX",
+                "EXPORT-RANGE",
+                "GET",
+                "INVALIDATE-TAG",
+                "LOG-FILTER",
+                "META",
+                "MULTI",
+                "PEEK",
+                "PING",
+                "PUT",
+                "RENAME",
+                "REPLICATE-FROM",
+                "SET-ROLE",
+                "SNAPSHOT",
+                "STATS"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn registered_commands_are_dispatched_and_unknown_ones_echoed() {
+        let mut commands = CommandRegistry::new();
+        commands.register(PingCommand).register(Upper);
+        let role = Arc::new(RoleState::default());
+        let controller = RequestControllerService::new(commands, role.clone());
+
+        assert_eq!(controller.handle("UPPER", "abc").await.to_wire(), "ABC");
+        assert_eq!(controller.handle("PING", "").await.to_wire(), "pong");
+        assert_eq!(controller.handle("NOPE", "").await.to_wire(), "echo:NOPE");
+
+        // las escrituras se cortan antes del handler en una réplica estricta
+        let strict = Arc::new(RoleState::new(NodeRole::Replica, true));
+        let mut commands = CommandRegistry::new();
+        commands.register(Upper);
+        let controller = RequestControllerService::new(commands, strict);
+        assert!(matches!(
+            controller.handle("UPPER", "abc").await,
+            Response::Error {
+                code: ErrorKind::Conflict,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "registrado dos veces")]
+    fn registering_an_action_twice_panics() {
+        CommandRegistry::new().register(Upper).register(Upper);
+    }
+}
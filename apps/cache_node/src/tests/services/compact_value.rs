@@ -0,0 +1,27 @@
+#[cfg(test)]
+mod tests {
+    use std::mem::size_of;
+
+    use crate::core::services::{CompactValue, INLINE_CAPACITY};
+
+    #[test]
+    fn short_values_live_inline_and_long_ones_in_an_exact_allocation() {
+        assert_eq!(size_of::<CompactValue>(), size_of::<String>());
+
+        for len in [0, 1, INLINE_CAPACITY] {
+            let text = "é".repeat(len / 2) + &"x".repeat(len % 2);
+            let value = CompactValue::from(text.clone());
+            assert!(value.is_inline(), "{len}");
+            assert_eq!((value.as_str(), value.len()), (text.as_str(), len));
+        }
+
+        let mut text = String::with_capacity(1024);
+        text.push_str(&"y".repeat(INLINE_CAPACITY + 1));
+        let value = CompactValue::from(text.clone());
+        assert!(!value.is_inline());
+        assert_eq!(&*value, text);
+        assert_eq!(value.to_string(), text);
+        assert_eq!(CompactValue::from(text.as_str()), value);
+        assert_eq!(format!("{:?}", CompactValue::from("a b")), "\"a b\"");
+    }
+}
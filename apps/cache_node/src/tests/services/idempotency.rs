@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicU64, Ordering},
+        },
+        time::Duration,
+    };
+
+    use app_core::clock::SimulatedClock;
+
+    use crate::{
+        core::{
+            commands::{DelCommand, PutCommand},
+            domain::{
+                models::Response,
+                services::{CacheService, CommandHandler},
+            },
+            services::{IdempotencyCache, IdempotencyConfig, Op, OpLog},
+        },
+        tests::test_mocks::cache_service_mock::MockCache,
+    };
+
+    fn idempotency(clock: Arc<SimulatedClock>, max_len: usize) -> IdempotencyCache {
+        let config = IdempotencyConfig {
+            window: Duration::from_secs(30),
+            max_len,
+        };
+        IdempotencyCache::new(config, clock)
+    }
+
+    /// Cuenta cuántas veces se ejecutó y responde ese número.
+    async fn count(runs: &AtomicU64) -> Response {
+        Response::Integer(runs.fetch_add(1, Ordering::SeqCst) as i64 + 1)
+    }
+
+    #[tokio::test]
+    async fn a_repeated_token_gets_the_first_response_until_the_window_ends() {
+        let clock = Arc::new(SimulatedClock::new(0));
+        let idempotency = idempotency(clock.clone(), 16);
+        let runs = AtomicU64::new(0);
+
+        assert_eq!(
+            idempotency.run(Some("a"), count(&runs)).await,
+            Response::Integer(1)
+        );
+        assert_eq!(
+            idempotency.run(Some("a"), count(&runs)).await,
+            Response::Integer(1)
+        );
+        assert_eq!(
+            idempotency.run(Some("b"), count(&runs)).await,
+            Response::Integer(2)
+        );
+        assert_eq!(
+            idempotency.run(None, count(&runs)).await,
+            Response::Integer(3)
+        );
+
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(
+            idempotency.run(Some("a"), count(&runs)).await,
+            Response::Integer(4)
+        );
+        assert_eq!(idempotency.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn concurrent_duplicates_run_once_and_the_oldest_tokens_make_room() {
+        let clock = Arc::new(SimulatedClock::new(0));
+        let idempotency = idempotency(clock, 2);
+        let runs = AtomicU64::new(0);
+
+        let slow = async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            count(&runs).await
+        };
+        let (first, second) = tokio::join!(
+            idempotency.run(Some("a"), slow),
+            idempotency.run(Some("a"), count(&runs)),
+        );
+        assert_eq!(
+            (first, second),
+            (Response::Integer(1), Response::Integer(1))
+        );
+
+        idempotency.run(Some("b"), count(&runs)).await;
+        idempotency.run(Some("c"), count(&runs)).await;
+        assert_eq!(idempotency.len(), 2);
+        // "a" ya no estaba: se vuelve a ejecutar
+        assert_eq!(
+            idempotency.run(Some("a"), count(&runs)).await,
+            Response::Integer(4)
+        );
+    }
+
+    #[tokio::test]
+    async fn retried_writes_are_applied_once() {
+        let cache = Arc::new(MockCache::new());
+        let op_log = Arc::new(OpLog::default());
+        let idempotency = Some(Arc::new(idempotency(Arc::new(SimulatedClock::new(0)), 16)));
+        let put =
+            PutCommand::new(cache.clone(), op_log.clone()).with_idempotency(idempotency.clone());
+        let del = DelCommand::new(cache.clone(), op_log.clone()).with_idempotency(idempotency);
+
+        assert_eq!(put.handle("k v1 idem=p1").await, Response::OkEmpty);
+        assert_eq!(put.handle("k v2 idem=p2").await, Response::OkEmpty);
+        // el reintento del primero no pisa lo que se escribió después
+        assert_eq!(put.handle("k v1 idem=p1").await, Response::OkEmpty);
+        assert_eq!(cache.get("k").await.as_deref(), Some("v2"));
+
+        assert_eq!(del.handle("k idem=d1").await, Response::Integer(1));
+        assert_eq!(del.handle("k idem=d1").await, Response::Integer(1));
+        assert!(matches!(
+            del.handle("k idem=").await,
+            Response::Error { .. }
+        ));
+
+        let ops: Vec<Op> = op_log
+            .read_from(1, 10)
+            .unwrap()
+            .into_iter()
+            .map(|(_, op)| (*op).clone())
+            .collect();
+        assert_eq!(ops.len(), 3);
+        assert_eq!(ops[2], Op::Del { key: "k".into() });
+    }
+}
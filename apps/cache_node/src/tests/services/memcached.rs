@@ -0,0 +1,243 @@
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use app_core::{clock::SimulatedClock, supervisor::Supervisor};
+    use app_net::{BoxedStream, Connector, MemoryNetwork};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use crate::{
+        core::{
+            commands::PutCommand,
+            domain::{
+                models::{NodeRole, RoleState},
+                services::{CacheService, CommandHandler},
+            },
+            services::{Op, OpLog},
+        },
+        infrastructure::adapters::services::{
+            cache_service::{CacheConfig, InMemCache},
+            memcached_service::{Memcached, serve_memcached},
+        },
+    };
+
+    const NOW_MS: u64 = 1_700_000_000_000;
+
+    struct Fixture {
+        client: BoxedStream,
+        cache: Arc<InMemCache>,
+        op_log: Arc<OpLog>,
+        _supervisor: Supervisor,
+    }
+
+    async fn start(role: RoleState) -> Fixture {
+        let supervisor = Supervisor::new();
+        let clock = Arc::new(SimulatedClock::new(NOW_MS));
+        let cache = Arc::new(InMemCache::with_config(
+            &supervisor,
+            clock.clone(),
+            CacheConfig::default(),
+        ));
+        let op_log = Arc::new(OpLog::default());
+
+        let net = MemoryNetwork::new();
+        let listener = net.bind("memcached").unwrap();
+        let memcached = Memcached {
+            cache: cache.clone(),
+            op_log: op_log.clone(),
+            role: Arc::new(role),
+            clock,
+        };
+        tokio::spawn(serve_memcached(
+            Box::new(listener),
+            memcached,
+            supervisor.token(),
+        ));
+
+        Fixture {
+            client: net.connect("memcached").await.unwrap(),
+            cache,
+            op_log,
+            _supervisor: supervisor,
+        }
+    }
+
+    /// Manda `request` y lee hasta juntar `expected` bytes.
+    async fn call(client: &mut BoxedStream, request: &str, expected: &str) {
+        client.write_all(request.as_bytes()).await.unwrap();
+        let mut reply = vec![0; expected.len()];
+        client.read_exact(&mut reply).await.unwrap();
+        assert_eq!(String::from_utf8_lossy(&reply), expected, "{request:?}");
+    }
+
+    #[tokio::test]
+    async fn set_get_delete_and_touch_go_through_the_cache_and_the_op_log() {
+        let Fixture {
+            mut client,
+            cache,
+            op_log,
+            ..
+        } = start(RoleState::default()).await;
+
+        call(&mut client, "set a 0 0 5\r\nuno\r\n\r\n", "STORED\r\n").await;
+        call(&mut client, "set b 0 60 3 noreply\r\ndos\r\n", "").await;
+        call(
+            &mut client,
+            "get a b c\r\n",
+            "VALUE a 0 5\r\nuno\r\n\r\nVALUE b 0 3\r\ndos\r\nEND\r\n",
+        )
+        .await;
+        assert_eq!(cache.get("a").await.as_deref(), Some("uno\r\n"));
+        assert_eq!(
+            cache.meta("b").await.unwrap().expires_at,
+            Some(NOW_MS + 60_000)
+        );
+
+        call(&mut client, "touch a 10\r\n", "TOUCHED\r\n").await;
+        assert_eq!(
+            cache.meta("a").await.unwrap().expires_at,
+            Some(NOW_MS + 10_000)
+        );
+        call(&mut client, "touch c 10\r\n", "NOT_FOUND\r\n").await;
+
+        call(&mut client, "delete b\r\n", "DELETED\r\n").await;
+        call(&mut client, "delete b\r\n", "NOT_FOUND\r\n").await;
+        // exptime negativo: vence en el acto
+        call(&mut client, "set a 0 -1 1\r\nx\r\n", "STORED\r\n").await;
+        call(&mut client, "get a\r\n", "END\r\n").await;
+
+        let ops: Vec<Op> = op_log
+            .read_from(1, 10)
+            .unwrap()
+            .into_iter()
+            .map(|(_, op)| (*op).clone())
+            .collect();
+        assert_eq!(
+            ops,
+            [
+                Op::Put {
+                    key: "a".into(),
+                    value: "uno\r\n".into(),
+                    expires_at: None,
+                    tags: vec![],
+                },
+                Op::Put {
+                    key: "b".into(),
+                    value: "dos".into(),
+                    expires_at: Some(NOW_MS + 60_000),
+                    tags: vec![],
+                },
+                Op::Put {
+                    key: "a".into(),
+                    value: "uno\r\n".into(),
+                    expires_at: Some(NOW_MS + 10_000),
+                    tags: vec![],
+                },
+                Op::Del { key: "b".into() },
+                Op::Del { key: "a".into() },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn malformed_and_unsupported_commands_get_protocol_errors() {
+        let Fixture { mut client, .. } = start(RoleState::default()).await;
+
+        call(&mut client, "incr a 1\r\n", "ERROR\r\n").await;
+        call(
+            &mut client,
+            "set a 0 0\r\n",
+            "CLIENT_ERROR formato de comando inválido\r\n",
+        )
+        .await;
+        // el bloque de un set rechazado se consume igual
+        call(
+            &mut client,
+            "set a 3 0 1\r\nx\r\n",
+            "CLIENT_ERROR solo se aceptan flags 0\r\n",
+        )
+        .await;
+        call(
+            &mut client,
+            "set a 0 0 1 noreply\r\nxy\r\n",
+            "CLIENT_ERROR bloque de datos mal terminado\r\n",
+        )
+        .await;
+        call(&mut client, "set a 0 0 0\r\n\r\n", "NOT_STORED\r\n").await;
+        call(&mut client, "stats\r\n", "STAT version ").await;
+    }
+
+    #[tokio::test]
+    async fn strict_replicas_refuse_memcached_writes() {
+        let Fixture {
+            mut client, cache, ..
+        } = start(RoleState::new(NodeRole::Replica, true)).await;
+        cache.put("a".into(), "uno".into(), None, &[]).await;
+
+        call(
+            &mut client,
+            "delete a\r\n",
+            "SERVER_ERROR réplica en modo estricto: no acepta escrituras\r\n",
+        )
+        .await;
+        call(&mut client, "get a\r\n", "VALUE a 0 3\r\nuno\r\nEND\r\n").await;
+    }
+
+    #[tokio::test]
+    async fn memcached_and_protocol_writes_on_a_key_are_logged_in_the_order_they_apply() {
+        let Fixture {
+            mut client,
+            cache,
+            op_log,
+            ..
+        } = start(RoleState::default()).await;
+        let put = Arc::new(PutCommand::new(cache.clone(), op_log.clone()));
+
+        let guard = op_log.lock_keys(&["k"]).await;
+        let set = tokio::spawn(async move {
+            call(&mut client, "set k 0 0 3\r\nmem\r\n", "STORED\r\n").await;
+            client
+        });
+        let req = tokio::spawn({
+            let put = put.clone();
+            async move { put.handle("k req").await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!set.is_finished());
+        assert!(!req.is_finished());
+        assert_eq!(op_log.head(), 0);
+        assert_eq!(cache.get("k").await, None);
+
+        drop(guard);
+        let mut client = set.await.unwrap();
+        req.await.unwrap();
+
+        // el último en el op-log es el que quedó en el cache
+        let last = |op_log: &OpLog| match op_log.read_from(1, 10).unwrap().last() {
+            Some((_, op)) => match &**op {
+                Op::Put { value, .. } => Some(value.clone()),
+                _ => None,
+            },
+            None => None,
+        };
+        assert_eq!(op_log.head(), 2);
+        assert_eq!(
+            last(&op_log),
+            cache.get("k").await.as_deref().map(str::to_string)
+        );
+
+        // `touch` y `delete` también esperan la guarda de la clave
+        let guard = op_log.lock_keys(&["k"]).await;
+        let touch = tokio::spawn(async move {
+            call(&mut client, "touch k 10\r\n", "TOUCHED\r\n").await;
+            call(&mut client, "delete k\r\n", "DELETED\r\n").await;
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!touch.is_finished());
+        assert_eq!(op_log.head(), 2);
+        drop(guard);
+        touch.await.unwrap();
+        assert_eq!(op_log.head(), 4);
+        assert_eq!(cache.get("k").await, None);
+    }
+}
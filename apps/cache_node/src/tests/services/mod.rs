@@ -1 +1,11 @@
 pub mod cache;
+pub mod cache_loom;
+pub mod command_registry;
+pub mod compact_value;
+pub mod idempotency;
+pub mod memcached;
+pub mod op_log;
+pub mod request_queue;
+pub mod slow_log;
+pub mod top_keys;
+pub mod write_batcher;
@@ -0,0 +1,162 @@
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use app_net::{ParsedMsg, parse_line};
+
+    use crate::{
+        core::{
+            commands::PutCommand,
+            domain::services::CommandHandler,
+            services::{Op, OpLog, op_log::Truncated},
+        },
+        tests::test_mocks::cache_service_mock::MockCache,
+    };
+
+    fn put(key: &str) -> Op {
+        Op::Put {
+            key: key.into(),
+            value: "v".into(),
+            expires_at: None,
+            tags: vec![],
+        }
+    }
+
+    #[test]
+    fn sequences_start_at_one_and_reads_resume_from_any_offset() {
+        let log = OpLog::new(8);
+        assert_eq!(log.head(), 0);
+        assert!(log.read_from(1, 10).unwrap().is_empty());
+
+        for k in ["a", "b", "c"] {
+            log.append(put(k));
+        }
+
+        let seqs: Vec<u64> = log
+            .read_from(2, 10)
+            .unwrap()
+            .iter()
+            .map(|(s, _)| *s)
+            .collect();
+        assert_eq!(seqs, vec![2, 3]);
+        assert_eq!(log.read_from(1, 1).unwrap().len(), 1);
+        assert!(log.read_from(4, 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn dropped_offsets_report_truncation() {
+        let log = OpLog::new(2);
+        for k in ["a", "b", "c", "d"] {
+            log.append(put(k));
+        }
+
+        assert_eq!(log.read_from(2, 10), Err(Truncated { oldest: 3 }));
+        assert_eq!(log.read_from(3, 10).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn replicas_resume_only_within_the_same_epoch_and_retained_range() {
+        let log = OpLog::new(2);
+        for k in ["a", "b", "c"] {
+            log.append(put(k));
+        }
+        let epoch = log.epoch().to_string();
+
+        assert_eq!(log.resume_point(&epoch, 3), Some(4));
+        assert_eq!(log.resume_point(&epoch, 1), Some(2));
+        // la 2 ya se descartó
+        assert_eq!(log.resume_point(&epoch, 0), None);
+        // offsets de otro log (p. ej. el primario se reinició)
+        assert_eq!(log.resume_point("otra", 3), None);
+        assert_eq!(log.resume_point(&epoch, 9), None);
+        assert_ne!(OpLog::new(2).epoch(), epoch);
+    }
+
+    #[test]
+    fn ops_round_trip_through_their_request_line() {
+        let ops = [
+            Op::Put {
+                key: "k 1".into(),
+                value: "say \"hi\"".into(),
+                expires_at: Some(1_500),
+                tags: vec![],
+            },
+            Op::Del { key: "k 1".into() },
+            Op::Put {
+                key: "k".into(),
+                value: "v".into(),
+                expires_at: None,
+                tags: vec!["user:42".into(), "session".into()],
+            },
+            Op::InvalidateTag {
+                tag: "user:42".into(),
+            },
+            Op::DelPrefix {
+                prefix: "user:".into(),
+            },
+            Op::Rename {
+                from: "k 1".into(),
+                to: "k 2".into(),
+            },
+        ];
+
+        for op in ops {
+            let line = op.to_line(7);
+            let Ok(ParsedMsg::Req { data }) = parse_line(&line) else {
+                panic!("no es un REQ: {line}");
+            };
+            assert_eq!(data.id, "7");
+
+            assert_eq!(Op::parse(data.action, &data.payload), Some(op));
+        }
+    }
+
+    #[test]
+    fn only_well_formed_writes_parse_as_ops() {
+        assert_eq!(Op::parse("GET", "k"), None);
+        assert_eq!(Op::parse("PUT", "k"), None);
+        assert_eq!(Op::parse("PUT", "k v 10d"), None);
+        assert_eq!(Op::parse("PUT", "k v 2s"), None);
+        assert_eq!(
+            Op::parse("PUT", "k v 2000ms"),
+            Some(Op::Put {
+                key: "k".into(),
+                value: "v".into(),
+                expires_at: Some(2_000),
+                tags: vec![],
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn a_write_waits_for_the_guard_of_its_key_before_touching_cache_or_log() {
+        let cache = Arc::new(MockCache::new());
+        let op_log = Arc::new(OpLog::default());
+        let put = Arc::new(PutCommand::new(cache.clone(), op_log.clone()));
+
+        let guard = op_log.lock_keys(&["k"]).await;
+        let blocked = tokio::spawn({
+            let put = put.clone();
+            async move { put.handle("k v").await }
+        });
+        // otra clave no espera
+        put.handle("otra v").await;
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!blocked.is_finished());
+        assert_eq!(op_log.head(), 1);
+        assert!(!cache.store.lock().contains_key("k"));
+
+        drop(guard);
+        blocked.await.unwrap();
+        assert_eq!(op_log.head(), 2);
+
+        // `lock_all` espera a que terminen las escrituras en curso y frena las nuevas
+        let all = op_log.lock_all().await;
+        let blocked = tokio::spawn(async move { put.handle("otra w").await });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!blocked.is_finished());
+        drop(all);
+        blocked.await.unwrap();
+        assert_eq!(op_log.head(), 3);
+    }
+}
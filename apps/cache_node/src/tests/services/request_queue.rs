@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use parking_lot::Mutex;
+    use tokio::{sync::oneshot, task::JoinHandle};
+    use tokio_util::sync::CancellationToken;
+
+    use crate::core::services::{RequestPriority, RequestQueue, RequestWorkers};
+
+    fn queue(workers: usize, bulk: usize) -> Arc<RequestQueue> {
+        Arc::new(RequestQueue::new(RequestWorkers { workers, bulk }))
+    }
+
+    fn spawn_workers(queue: &Arc<RequestQueue>, token: &CancellationToken) -> Vec<JoinHandle<()>> {
+        (0..queue.config().workers)
+            .map(|_| {
+                let (queue, token) = (queue.clone(), token.clone());
+                tokio::spawn(async move { queue.run_worker(token).await })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn actions_fall_in_their_priority() {
+        assert_eq!(RequestPriority::of("PING"), RequestPriority::Control);
+        assert_eq!(RequestPriority::of("STATS"), RequestPriority::Control);
+        assert_eq!(RequestPriority::of("GET"), RequestPriority::Read);
+        assert_eq!(RequestPriority::of("META"), RequestPriority::Read);
+        assert_eq!(RequestPriority::of("PUT"), RequestPriority::Write);
+        assert_eq!(RequestPriority::of("RENAME"), RequestPriority::Write);
+        assert_eq!(RequestPriority::of("EXPORT-RANGE"), RequestPriority::Bulk);
+        assert_eq!(RequestPriority::of("SNAPSHOT"), RequestPriority::Bulk);
+    }
+
+    #[tokio::test]
+    async fn queued_requests_are_served_by_priority_then_by_arrival() {
+        let queue = queue(1, 1);
+        let served = Arc::new(Mutex::new(Vec::new()));
+        for (priority, name) in [
+            (RequestPriority::Bulk, "export"),
+            (RequestPriority::Write, "put"),
+            (RequestPriority::Read, "get 1"),
+            (RequestPriority::Control, "ping"),
+            (RequestPriority::Read, "get 2"),
+        ] {
+            let served = served.clone();
+            queue.push(priority, async move { served.lock().push(name) });
+        }
+        assert_eq!(queue.queued(RequestPriority::Read), 2);
+
+        let token = CancellationToken::new();
+        let workers = spawn_workers(&queue, &token);
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while queue.served(RequestPriority::Bulk) == 0 {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(
+            *served.lock(),
+            vec!["ping", "get 1", "get 2", "put", "export"]
+        );
+        token.cancel();
+        for worker in workers {
+            worker.await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn bulk_requests_never_take_every_worker() {
+        let queue = queue(2, 1);
+        let token = CancellationToken::new();
+        let _workers = spawn_workers(&queue, &token);
+
+        // un EXPORT-RANGE trabado ocupa su único lugar; el segundo espera
+        let (release, blocked) = oneshot::channel::<()>();
+        queue.push(RequestPriority::Bulk, async move {
+            let _ = blocked.await;
+        });
+        let (second_tx, second_done) = oneshot::channel();
+        queue.push(RequestPriority::Bulk, async move {
+            let _ = second_tx.send(());
+        });
+
+        let (get_tx, get_done) = oneshot::channel();
+        queue.push(RequestPriority::Read, async move {
+            let _ = get_tx.send(());
+        });
+        tokio::time::timeout(Duration::from_secs(5), get_done)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(queue.queued(RequestPriority::Bulk), 1);
+
+        release.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), second_done)
+            .await
+            .unwrap()
+            .unwrap();
+        token.cancel();
+    }
+
+    #[tokio::test]
+    async fn a_request_that_panics_does_not_take_its_worker_down() {
+        let queue = queue(1, 1);
+        let token = CancellationToken::new();
+        let _workers = spawn_workers(&queue, &token);
+
+        queue.push(RequestPriority::Write, async { panic!("comando roto") });
+        let (done_tx, done) = oneshot::channel();
+        queue.push(RequestPriority::Read, async move {
+            let _ = done_tx.send(());
+        });
+
+        tokio::time::timeout(Duration::from_secs(5), done)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(queue.served(RequestPriority::Write), 1);
+        token.cancel();
+    }
+}
@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use app_core::clock::SimulatedClock;
+    use async_trait::async_trait;
+
+    use crate::core::{
+        commands::SlowLogCommand,
+        domain::{models::Response, services::CommandHandler},
+        services::{
+            CommandRegistry, SlowLog, SlowLogConfig,
+            request_controller_service::RequestControllerService,
+        },
+    };
+
+    /// Tarda lo que diga el payload (ms) antes de responder.
+    struct Sleep;
+
+    #[async_trait]
+    impl CommandHandler for Sleep {
+        fn action(&self) -> &'static str {
+            "SLEEP"
+        }
+
+        async fn handle(&self, payload: &str) -> Response {
+            let ms = payload.split(' ').next().unwrap().parse().unwrap();
+            tokio::time::sleep(Duration::from_millis(ms)).await;
+            Response::OkEmpty
+        }
+    }
+
+    fn slow_log(clock: Arc<SimulatedClock>, max_len: usize) -> Arc<SlowLog> {
+        let config = SlowLogConfig {
+            threshold: Duration::from_millis(20),
+            max_len,
+        };
+        Arc::new(SlowLog::new(config, clock))
+    }
+
+    #[test]
+    fn keeps_only_the_newest_slow_entries_with_their_clock_time() {
+        let clock = Arc::new(SimulatedClock::new(1_000));
+        let log = slow_log(clock.clone(), 2);
+
+        assert!(!log.observe("GET", "k", Duration::from_millis(5)));
+        for (n, key) in ["a", "b", "c"].into_iter().enumerate() {
+            clock.advance(Duration::from_millis(100));
+            assert!(log.observe(
+                "PUT",
+                &format!("{key} valor"),
+                Duration::from_millis(20 + n as u64)
+            ));
+        }
+
+        let entries = log.latest(10);
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[0].id, entries[0].at), (2, 1_300));
+        assert_eq!(entries[1].args, "b \"<5 bytes>\"");
+        assert_eq!(
+            entries[0].to_wire(),
+            "id=2 at=1300 duration_us=22000 action=PUT args=c \"<5 bytes>\""
+        );
+        assert_eq!(log.latest(1), entries[..1]);
+    }
+
+    #[tokio::test]
+    async fn the_controller_records_slow_commands_and_slowlog_reads_them() {
+        let clock = Arc::new(SimulatedClock::new(0));
+        let log = slow_log(clock, 8);
+        let mut commands = CommandRegistry::new();
+        commands.register(Sleep);
+        let controller =
+            RequestControllerService::new(commands, Default::default()).with_slow_log(log.clone());
+
+        controller.handle("SLEEP", "0").await;
+        controller.handle("SLEEP", "30 secreto").await;
+        assert_eq!(log.len(), 1);
+
+        let slowlog = SlowLogCommand::new(log.clone());
+        let Response::Values(entries) = slowlog.handle("").await else {
+            panic!("SLOWLOG GET sin valores");
+        };
+        assert_eq!(entries.len(), 1);
+        assert!(
+            entries[0].contains("action=SLEEP args=30 \"<7 bytes>\""),
+            "{}",
+            entries[0]
+        );
+
+        assert_eq!(slowlog.handle("LEN").await, Response::Integer(1));
+        assert_eq!(slowlog.handle("RESET").await, Response::OkEmpty);
+        assert_eq!(slowlog.handle("GET 5").await, Response::Values(vec![]));
+        assert!(matches!(
+            slowlog.handle("GET x").await,
+            Response::Error { .. }
+        ));
+        assert!(matches!(
+            slowlog.handle("FLUSH").await,
+            Response::Error { .. }
+        ));
+    }
+}
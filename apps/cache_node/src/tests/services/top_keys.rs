@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        core::{
+            commands::TopKeysCommand,
+            domain::{
+                models::{Response, RoleState},
+                services::CommandHandler,
+            },
+            services::{TopKeys, TopKeysConfig},
+        },
+        tests::test_mocks::{cache_service_mock::MockCache, controller::controller_for},
+    };
+
+    fn top_keys(capacity: usize) -> Arc<TopKeys> {
+        Arc::new(TopKeys::new(TopKeysConfig {
+            capacity,
+            sample: 1,
+        }))
+    }
+
+    #[test]
+    fn a_new_key_on_a_full_table_replaces_the_least_counted_and_inherits_its_count() {
+        let top = top_keys(2);
+        for _ in 0..5 {
+            top.observe(false, "caliente");
+        }
+        top.observe(true, "\"tibia 1\" valor");
+        top.observe(false, "tibia-2");
+
+        let keys = top.top(10);
+        assert_eq!(keys.len(), 2);
+        assert_eq!((keys[0].key.as_str(), keys[0].count), ("caliente", 5));
+        assert_eq!(
+            keys[1].to_wire(),
+            "count=2 reads=1 writes=0 error=1 key=tibia-2"
+        );
+        assert_eq!(top.top(1), keys[..1]);
+
+        assert!(!top_keys(0).observe(false, "k"));
+        assert!(!top.observe(false, ""));
+    }
+
+    #[test]
+    fn sampled_counts_are_scaled_back_to_all_accesses() {
+        let top = TopKeys::new(TopKeysConfig {
+            capacity: 4,
+            sample: 4,
+        });
+        let counted = (0..4_000).filter(|_| top.observe(false, "k")).count() as u64;
+
+        let keys = top.top(1);
+        assert_eq!(keys[0].count, counted * 4);
+        assert!((2_000..6_000).contains(&keys[0].count), "{:?}", keys[0]);
+    }
+
+    #[tokio::test]
+    async fn the_controller_counts_only_the_keys_of_data_commands() {
+        let top = top_keys(8);
+        let (controller, _) =
+            controller_for(Arc::new(MockCache::new()), Arc::new(RoleState::default()));
+        let controller = controller.with_top_keys(top.clone());
+
+        controller.handle("PUT", "a 1").await;
+        controller.handle("GET", "a").await;
+        controller.handle("GET", "a").await;
+        controller.handle("DEL", "b").await;
+        controller.handle("PEEK", "c").await;
+        controller.handle("PING", "").await;
+
+        let topkeys = TopKeysCommand::new(top.clone());
+        assert_eq!(
+            topkeys.handle("GET").await,
+            Response::Values(vec![
+                "count=3 reads=2 writes=1 error=0 key=a".to_string(),
+                "count=1 reads=0 writes=1 error=0 key=b".to_string(),
+            ])
+        );
+        assert_eq!(topkeys.handle("LEN").await, Response::Integer(2));
+        assert_eq!(topkeys.handle("RESET").await, Response::OkEmpty);
+        assert_eq!(topkeys.handle("GET 5").await, Response::Values(vec![]));
+        assert!(matches!(
+            topkeys.handle("GET x").await,
+            Response::Error { .. }
+        ));
+    }
+}
@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use app_core::{clock::AppClock, supervisor::Supervisor};
+    use tokio::task::JoinHandle;
+    use tokio_util::sync::CancellationToken;
+
+    use crate::{
+        core::{
+            domain::services::CacheService,
+            services::{WriteBatcher, WriteBatching},
+        },
+        infrastructure::adapters::services::cache_service::{CacheConfig, InMemCache},
+    };
+
+    fn cache(supervisor: &Supervisor, quotas: &str) -> Arc<InMemCache> {
+        Arc::new(InMemCache::with_config(
+            supervisor,
+            Arc::new(AppClock::new()),
+            CacheConfig {
+                namespaces: quotas.parse().unwrap(),
+                ..CacheConfig::default()
+            },
+        ))
+    }
+
+    fn batcher(cache: &Arc<InMemCache>, max_ops: usize) -> Arc<WriteBatcher<InMemCache>> {
+        Arc::new(WriteBatcher::new(
+            cache.clone(),
+            WriteBatching {
+                window: Duration::ZERO,
+                max_ops,
+            },
+        ))
+    }
+
+    /// Deja encolados los `PUT` de `keys` antes de que arranque `run`.
+    async fn queue(
+        batcher: &Arc<WriteBatcher<InMemCache>>,
+        keys: &[&str],
+    ) -> Vec<JoinHandle<bool>> {
+        let puts: Vec<_> = keys
+            .iter()
+            .map(|key| {
+                let (batcher, key) = (batcher.clone(), key.to_string());
+                tokio::spawn(async move { batcher.put(key, "v".into(), None, &[]).await })
+            })
+            .collect();
+        tokio::task::yield_now().await;
+        puts
+    }
+
+    async fn results(puts: Vec<JoinHandle<bool>>) -> Vec<bool> {
+        let mut stored = Vec::new();
+        for put in puts {
+            stored.push(put.await.unwrap());
+        }
+        stored
+    }
+
+    #[tokio::test]
+    async fn queued_puts_share_a_batch_and_each_gets_its_own_result() {
+        let supervisor = Supervisor::new();
+        let cache = cache(&supervisor, "a=1");
+        let batcher = batcher(&cache, 64);
+        let puts = queue(&batcher, &["a:1", "a:2", "b:1", "b:2"]).await;
+
+        tokio::spawn(batcher.clone().run(supervisor.token()));
+
+        assert_eq!(results(puts).await, vec![true, false, true, true]);
+        assert_eq!(batcher.counts(), (1, 4));
+        assert_eq!(cache.get("b:2").await.as_deref(), Some("v"));
+        assert!(cache.get("a:2").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn a_batch_never_takes_more_than_max_ops() {
+        let supervisor = Supervisor::new();
+        let cache = cache(&supervisor, "");
+        let batcher = batcher(&cache, 4);
+        let keys: Vec<String> = (0..10).map(|i| format!("k{i}")).collect();
+        let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+        let puts = queue(&batcher, &keys).await;
+
+        tokio::spawn(batcher.clone().run(supervisor.token()));
+
+        assert!(results(puts).await.into_iter().all(|stored| stored));
+        assert_eq!(batcher.counts(), (3, 10));
+        assert_eq!(cache.stats().entries, 10);
+    }
+
+    #[tokio::test]
+    async fn puts_are_written_alone_once_the_batcher_stopped() {
+        let supervisor = Supervisor::new();
+        let cache = cache(&supervisor, "");
+        let batcher = batcher(&cache, 64);
+        let cancel = CancellationToken::new();
+        let run = tokio::spawn(batcher.clone().run(cancel.clone()));
+
+        cancel.cancel();
+        run.await.unwrap();
+
+        assert!(batcher.put("k".into(), "v".into(), None, &[]).await);
+        assert_eq!(cache.get("k").await.as_deref(), Some("v"));
+        assert_eq!(batcher.counts(), (0, 0));
+    }
+}
@@ -1,29 +1,217 @@
 use std::{collections::HashMap, sync::Arc};
 
+use app_net::{PutCondition, TxCommand};
 use async_trait::async_trait;
 use parking_lot::Mutex;
 
-use crate::core::domain::services::CacheService;
+use crate::core::{
+    domain::{models::KeyMeta, services::CacheService},
+    services::{
+        CacheStats, NamespaceStats, Op, QuotaExceeded, RenameOutcome, TxConflict, TxOutcome,
+    },
+};
 
 pub struct MockCache {
     pub store: Arc<Mutex<HashMap<String, String>>>,
+    /// Lo último de `resize` y `set_max_bytes`, que `stats` devuelve.
+    pub limits: Mutex<(usize, u64)>,
 }
 
 impl MockCache {
     pub fn new() -> Self {
         Self {
             store: Arc::new(Mutex::new(HashMap::new())),
+            limits: Mutex::new((0, 0)),
         }
     }
 }
 
+impl Default for MockCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[async_trait]
 impl CacheService for MockCache {
-    async fn put(&self, key: String, value: String, _ttl: Option<u64>) {
+    async fn put(&self, key: String, value: String, _ttl: Option<u64>, _tags: &[String]) -> bool {
         self.store.lock().insert(key, value);
+        true
+    }
+
+    /// Igual que en `transact`, toda clave existente tiene versión 1.
+    async fn put_if(
+        &self,
+        key: String,
+        value: String,
+        _ttl: Option<u64>,
+        _tags: &[String],
+        condition: &PutCondition,
+    ) -> Result<bool, QuotaExceeded> {
+        let mut store = self.store.lock();
+        if !condition.holds(store.get(&key).map(|v| (v.as_str(), 1))) {
+            return Ok(false);
+        }
+        store.insert(key, value);
+        Ok(true)
     }
 
-    async fn get(&self, key: &String) -> Option<String> {
+    async fn get(&self, key: &str) -> Option<String> {
         self.store.lock().get(key).cloned()
     }
+
+    /// Como en `put_if`, toda clave existente tiene versión 1.
+    async fn get_versioned(&self, key: &str) -> Option<(String, u64)> {
+        self.get(key).await.map(|value| (value, 1))
+    }
+
+    async fn peek(&self, key: &str) -> Option<String> {
+        self.get(key).await
+    }
+
+    /// Sin TTL: nunca elige a nadie para refrescar.
+    async fn get_for_refresh(&self, key: &str, _window_ms: u64) -> Option<(String, bool)> {
+        self.get(key).await.map(|value| (value, false))
+    }
+
+    /// Sin TTL: nada vence ni se refresca.
+    async fn get_stale(
+        &self,
+        key: &str,
+        _max_stale_ms: u64,
+    ) -> Option<(String, bool, Option<u64>)> {
+        self.get(key).await.map(|value| (value, false, None))
+    }
+
+    async fn remove(&self, key: &str) -> bool {
+        self.store.lock().remove(key).is_some()
+    }
+
+    /// Sin índice de tags: nunca borra nada.
+    async fn invalidate_tag(&self, _tag: &str) -> Vec<String> {
+        Vec::new()
+    }
+
+    async fn remove_prefix(&self, prefix: &str) -> usize {
+        let mut store = self.store.lock();
+        let before = store.len();
+        store.retain(|key, _| !key.starts_with(prefix));
+        before - store.len()
+    }
+
+    async fn count_prefix(&self, prefix: &str) -> usize {
+        let store = self.store.lock();
+        store.keys().filter(|key| key.starts_with(prefix)).count()
+    }
+
+    async fn rename(&self, from: &str, to: &str) -> Result<RenameOutcome, QuotaExceeded> {
+        let mut store = self.store.lock();
+        if !store.contains_key(from) {
+            return Ok(RenameOutcome::Missing);
+        }
+        if from == to || store.contains_key(to) {
+            return Ok(RenameOutcome::TargetExists);
+        }
+        let value = store.remove(from).expect("existe");
+        store.insert(to.to_string(), value);
+        Ok(RenameOutcome::Moved)
+    }
+
+    async fn meta(&self, key: &str) -> Option<KeyMeta> {
+        let store = self.store.lock();
+        let value = store.get(key)?;
+        Some(KeyMeta {
+            version: 1,
+            expires_at: None,
+            size_bytes: key.len() + value.len(),
+            last_access: 0,
+            expired: false,
+            stale: false,
+        })
+    }
+
+    /// Con el lock tomado todo el tiempo; toda clave existente tiene versión 1.
+    async fn transact(
+        &self,
+        commands: &[TxCommand],
+    ) -> Result<Vec<TxOutcome<String>>, TxConflict<String>> {
+        let mut store = self.store.lock();
+        let version =
+            |store: &HashMap<String, String>, key: &str| u64::from(store.contains_key(key));
+
+        for command in commands {
+            if let TxCommand::Watch {
+                key,
+                version: expected,
+            } = command
+                && version(&store, key) != *expected
+            {
+                return Err(TxConflict {
+                    key: key.clone(),
+                    version: version(&store, key),
+                });
+            }
+        }
+
+        let mut outcomes = Vec::new();
+        for command in commands {
+            outcomes.push(match command {
+                TxCommand::Watch { .. } => continue,
+                TxCommand::Get { key } => TxOutcome::Value(store.get(key).cloned().map(Arc::new)),
+                TxCommand::Version { key } => TxOutcome::Version(version(&store, key)),
+                TxCommand::Put { key, value, .. } => {
+                    store.insert(key.clone(), value.clone());
+                    TxOutcome::Stored
+                }
+                TxCommand::Del { key } => TxOutcome::Removed(store.remove(key).is_some()),
+            });
+        }
+        Ok(outcomes)
+    }
+
+    /// Sin contadores: las entradas y lo que se fijó con `resize` y `set_max_bytes`.
+    fn stats(&self) -> CacheStats {
+        let (capacity, max_bytes) = *self.limits.lock();
+        CacheStats {
+            entries: self.store.lock().len(),
+            capacity,
+            max_bytes,
+            ..CacheStats::default()
+        }
+    }
+
+    /// No desaloja: devuelve cuántas entradas sobran.
+    fn resize(&self, capacity: usize) -> usize {
+        self.limits.lock().0 = capacity;
+        self.store.lock().len().saturating_sub(capacity)
+    }
+
+    fn set_max_bytes(&self, max_bytes: Option<u64>) {
+        self.limits.lock().1 = max_bytes.unwrap_or(0);
+    }
+
+    /// No cuenta por namespace.
+    fn namespace_stats(&self) -> Vec<(String, NamespaceStats)> {
+        Vec::new()
+    }
+
+    /// Sin vencimiento ni tags, ordenadas por clave.
+    fn snapshot(&self) -> Vec<Op> {
+        let mut entries: Vec<_> = self
+            .store
+            .lock()
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        entries.sort();
+        entries
+            .into_iter()
+            .map(|(key, value)| Op::Put {
+                key,
+                value,
+                expires_at: None,
+                tags: Vec::new(),
+            })
+            .collect()
+    }
 }
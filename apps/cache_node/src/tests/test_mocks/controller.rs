@@ -0,0 +1,34 @@
+use std::sync::Arc;
+
+use app_core::clock::AppClock;
+
+use crate::{
+    core::{
+        commands::{CommandDeps, register_builtins},
+        domain::models::RoleState,
+        services::{CommandRegistry, OpLog, request_controller_service::RequestControllerService},
+    },
+    tests::test_mocks::cache_service_mock::MockCache,
+};
+
+/// Controller con los comandos de base sobre `cache` (sin replicación) y su op-log.
+pub fn controller_for(
+    cache: Arc<MockCache>,
+    role: Arc<RoleState>,
+) -> (RequestControllerService, Arc<OpLog>) {
+    let op_log = Arc::new(OpLog::default());
+    let mut commands = CommandRegistry::new();
+    register_builtins(
+        &mut commands,
+        CommandDeps {
+            cache,
+            role: role.clone(),
+            op_log: op_log.clone(),
+            replication: None,
+            clock: Arc::new(AppClock::new()),
+            write_batcher: None,
+            idempotency: None,
+        },
+    );
+    (RequestControllerService::new(commands, role), op_log)
+}
@@ -1 +1,2 @@
 pub mod cache_service_mock;
+pub mod controller;
@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        core::{domain::models::Response, usecases::exec_config},
+        tests::test_mocks::cache_service_mock::MockCache,
+    };
+
+    #[tokio::test]
+    async fn get_reads_every_setting_or_just_one() {
+        let cache = MockCache::new();
+
+        assert_eq!(
+            exec_config(&cache, "GET", None, None).await.to_wire(),
+            "capacity=0 max-memory=0"
+        );
+        assert_eq!(
+            exec_config(&cache, "GET", Some("capacity"), None)
+                .await
+                .to_wire(),
+            "0"
+        );
+        assert!(matches!(
+            exec_config(&cache, "GET", Some("ttl"), None).await,
+            Response::Error { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn set_resizes_and_changes_the_memory_cap() {
+        let cache = MockCache::new();
+        for i in 0..5 {
+            cache.store.lock().insert(format!("k{i}"), "v".into());
+        }
+
+        assert_eq!(
+            exec_config(&cache, "SET", Some("capacity"), Some("3"))
+                .await
+                .to_wire(),
+            "capacity=3 evicted=2"
+        );
+        assert_eq!(
+            exec_config(&cache, "SET", Some("max-memory"), Some("4096"))
+                .await
+                .to_wire(),
+            "max-memory=4096"
+        );
+        assert_eq!(*cache.limits.lock(), (3, 4096));
+
+        // 0 saca el tope
+        exec_config(&cache, "SET", Some("max-memory"), Some("0")).await;
+        assert_eq!(cache.limits.lock().1, 0);
+    }
+
+    #[tokio::test]
+    async fn set_rejects_invalid_values_without_changing_anything() {
+        let cache = MockCache::new();
+
+        for (key, value) in [("capacity", "0"), ("capacity", "x"), ("max-memory", "-1")] {
+            assert!(matches!(
+                exec_config(&cache, "SET", Some(key), Some(value)).await,
+                Response::Error { .. }
+            ));
+        }
+        assert!(matches!(
+            exec_config(&cache, "SET", Some("capacity"), None).await,
+            Response::Error { .. }
+        ));
+        assert_eq!(*cache.limits.lock(), (0, 0));
+    }
+}
@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use app_net::DelPrefix;
+
+    use crate::{
+        core::{
+            domain::{
+                models::{Response, RoleState},
+                services::CacheService,
+            },
+            services::Op,
+            usecases::exec_del_prefix,
+        },
+        tests::test_mocks::{cache_service_mock::MockCache, controller::controller_for},
+    };
+
+    #[tokio::test]
+    async fn a_dry_run_counts_what_a_real_one_removes() {
+        let cache = MockCache::new();
+        for key in ["user:1", "user:2", "users", "session:1"] {
+            cache.put(key.into(), "v".into(), None, &[]).await;
+        }
+        let dry = DelPrefix::parse("user: dry-run").unwrap();
+        let real = DelPrefix::parse("user:").unwrap();
+
+        assert_eq!(exec_del_prefix(&cache, &dry).await, Response::Integer(2));
+        assert_eq!(cache.get("user:1").await.as_deref(), Some("v"));
+        assert_eq!(exec_del_prefix(&cache, &real).await, Response::Integer(2));
+        assert!(cache.get("user:1").await.is_none());
+        assert_eq!(cache.get("users").await.as_deref(), Some("v"));
+        assert_eq!(exec_del_prefix(&cache, &dry).await, Response::Integer(0));
+    }
+
+    #[tokio::test]
+    async fn only_real_deletions_that_removed_keys_are_replicated() {
+        let (controller, op_log) =
+            controller_for(Arc::new(MockCache::new()), Arc::new(RoleState::default()));
+
+        controller.handle("PUT", "user:1 v").await;
+        controller.handle("DEL-PREFIX", "user: dry-run").await;
+        controller.handle("DEL-PREFIX", "nadie:").await;
+        let res = controller.handle("DEL-PREFIX", "\"\"").await;
+        assert!(matches!(res, Response::Error { .. }));
+        assert_eq!(
+            controller.handle("DEL-PREFIX", "user:").await,
+            Response::Integer(1)
+        );
+
+        let ops: Vec<Op> = op_log
+            .read_from(2, 10)
+            .unwrap()
+            .into_iter()
+            .map(|(_, op)| (*op).clone())
+            .collect();
+        assert_eq!(
+            ops,
+            vec![Op::DelPrefix {
+                prefix: "user:".into()
+            }]
+        );
+    }
+}
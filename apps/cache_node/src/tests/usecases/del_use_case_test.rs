@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        core::{
+            domain::{
+                models::{Response, RoleState},
+                services::CacheService,
+            },
+            services::Op,
+            usecases::exec_del,
+        },
+        tests::test_mocks::{cache_service_mock::MockCache, controller::controller_for},
+    };
+
+    #[tokio::test]
+    async fn exec_del_reports_whether_the_key_existed() {
+        let cache = MockCache::new();
+        cache.put("k".into(), "v".into(), None, &[]).await;
+
+        assert_eq!(exec_del(&cache, "k".into()).await.to_wire(), "1");
+        assert_eq!(exec_del(&cache, "k".into()).await.to_wire(), "0");
+        assert!(matches!(exec_del(&cache, "".into()).await, Response::Empty));
+    }
+
+    #[tokio::test]
+    async fn applied_writes_are_appended_to_the_op_log() {
+        let (controller, op_log) =
+            controller_for(Arc::new(MockCache::new()), Arc::new(RoleState::default()));
+
+        controller.handle("PUT", "k v 10").await;
+        // ni las escrituras rechazadas ni los DEL de claves inexistentes se registran
+        controller.handle("PUT", "\"\" v").await;
+        controller.handle("PUT", "k v 10d").await;
+        controller.handle("DEL", "x").await;
+        controller.handle("DEL", "k").await;
+
+        let ops: Vec<Op> = op_log
+            .read_from(1, 10)
+            .unwrap()
+            .into_iter()
+            .map(|(_, op)| (*op).clone())
+            .collect();
+        assert_eq!(
+            ops,
+            vec![
+                Op::Put {
+                    key: "k".into(),
+                    value: "v".into(),
+                    expires_at: Some(10),
+                    tags: vec![],
+                },
+                Op::Del { key: "k".into() },
+            ]
+        );
+    }
+}
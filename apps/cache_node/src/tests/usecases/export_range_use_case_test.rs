@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use app_core::error::ErrorKind;
+    use app_net::{HashRange, SnapshotHeader, key_hash, tokenize};
+
+    use crate::{
+        core::{
+            domain::{
+                models::{Response, RoleState},
+                services::CacheService,
+            },
+            services::OpLog,
+            usecases::exec_export_range,
+        },
+        infrastructure::adapters::services::cache_service::InMemCache,
+        tests::test_mocks::{cache_service_mock::MockCache, controller::controller_for},
+    };
+
+    fn keys(lines: &[String]) -> Vec<String> {
+        let mut keys: Vec<String> = lines
+            .iter()
+            .map(|line| tokenize(line).next().unwrap().into_owned())
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    #[tokio::test]
+    async fn only_the_keys_hashed_into_the_range_are_exported() {
+        let cache = InMemCache::new();
+        let op_log = OpLog::new(16);
+        let all: Vec<String> = (0..50).map(|i| format!("k{i}")).collect();
+        for key in &all {
+            cache.put(key.clone(), "v".into(), None, &[]).await;
+        }
+
+        // el tramo que va de la clave con hash más bajo a la del medio
+        let mut hashes: Vec<u64> = all.iter().map(|key| key_hash(key)).collect();
+        hashes.sort();
+        let range = HashRange {
+            start: hashes[0],
+            end: hashes[25],
+        };
+        let mut inside: Vec<String> = all
+            .iter()
+            .filter(|key| range.contains(key_hash(key)))
+            .cloned()
+            .collect();
+        inside.sort();
+        assert_eq!(inside.len(), 25);
+
+        let Response::Values(lines) = exec_export_range(&cache, &op_log, range).await else {
+            panic!("EXPORT-RANGE responde con valores");
+        };
+        let header: SnapshotHeader = lines[0].parse().unwrap();
+        assert_eq!(header.entries, 25);
+        assert_eq!(keys(&lines[1..]), inside);
+
+        // el resto del anillo se lleva lo que falta
+        let rest = HashRange {
+            start: range.end,
+            end: range.start,
+        };
+        let Response::Values(lines) = exec_export_range(&cache, &op_log, rest).await else {
+            panic!("EXPORT-RANGE responde con valores");
+        };
+        assert_eq!(lines.len() - 1 + inside.len(), all.len());
+    }
+
+    #[tokio::test]
+    async fn a_bad_range_is_a_bad_request() {
+        let (controller, _) =
+            controller_for(Arc::new(MockCache::new()), Arc::new(RoleState::default()));
+
+        assert!(matches!(
+            controller.handle("EXPORT-RANGE", "\"zz\"").await,
+            Response::Error {
+                code: ErrorKind::BadRequest,
+                ..
+            }
+        ));
+    }
+}
@@ -3,7 +3,7 @@ mod tests {
     use crate::{
         core::{
             domain::{models::Response, services::CacheService},
-            usecases::exec_get,
+            usecases::{exec_get, exec_get_if_not_version, exec_get_refresh, exec_get_stale},
         },
         tests::test_mocks::cache_service_mock::MockCache,
     };
@@ -23,11 +23,11 @@ mod tests {
     #[tokio::test]
     async fn exec_get_returns_okvalue_when_found() {
         let cache = MockCache::new();
-        cache.put("k".into(), "v".into(), None).await;
+        cache.put("k".into(), "v".into(), None, &[]).await;
 
         let resp = exec_get(&cache, "k".to_string()).await;
         match resp {
-            Response::OkValue(v) => assert_eq!(v, "v"),
+            Response::Value(v) => assert_eq!(v, "v"),
             _ => panic!("Expected OkValue"),
         }
     }
@@ -41,4 +41,56 @@ mod tests {
             _ => panic!("Expected OkEmpty"),
         }
     }
+
+    #[tokio::test]
+    async fn exec_get_refresh_returns_the_value_and_whether_to_refresh() {
+        let cache = MockCache::new();
+        cache.put("k".into(), "v".into(), None, &[]).await;
+
+        match exec_get_refresh(&cache, "k".to_string(), 1_000).await {
+            Response::Values(values) => assert_eq!(values, ["v", "0"]),
+            _ => panic!("Expected Values"),
+        }
+        assert!(matches!(
+            exec_get_refresh(&cache, "missing".to_string(), 1_000).await,
+            Response::OkEmpty
+        ));
+    }
+
+    #[tokio::test]
+    async fn exec_get_stale_returns_the_value_whether_to_refresh_and_how_stale_it_is() {
+        let cache = MockCache::new();
+        cache.put("k".into(), "v".into(), None, &[]).await;
+
+        match exec_get_stale(&cache, "k".to_string(), 1_000).await {
+            Response::Values(values) => assert_eq!(values, ["v", "0", "0"]),
+            _ => panic!("Expected Values"),
+        }
+        assert!(matches!(
+            exec_get_stale(&cache, "missing".to_string(), 1_000).await,
+            Response::OkEmpty
+        ));
+    }
+
+    #[tokio::test]
+    async fn exec_get_if_not_version_skips_the_value_only_when_the_version_matches() {
+        let cache = MockCache::new();
+        cache.put("k".into(), "v".into(), None, &[]).await;
+
+        assert_eq!(
+            exec_get_if_not_version(&cache, "k".to_string(), 1).await,
+            Response::NotModified { version: 1 }
+        );
+        assert_eq!(
+            exec_get_if_not_version(&cache, "k".to_string(), 0).await,
+            Response::Versioned {
+                value: "v".into(),
+                version: 1
+            }
+        );
+        assert_eq!(
+            exec_get_if_not_version(&cache, "missing".to_string(), 1).await,
+            Response::OkEmpty
+        );
+    }
 }
@@ -0,0 +1,27 @@
+#[cfg(test)]
+mod tests {
+    use app_logging::LogControl;
+
+    use crate::core::{domain::models::Response, usecases::log_filter_use_case::apply_log_filter};
+
+    #[test]
+    fn empty_filter_reads_the_current_one() {
+        let (_layer, control) = LogControl::layer("warn").unwrap();
+        assert_eq!(apply_log_filter(&control, "").to_wire(), "warn");
+    }
+
+    #[test]
+    fn sets_valid_filters_and_rejects_invalid_ones() {
+        let (_layer, control) = LogControl::layer("info").unwrap();
+
+        assert_eq!(
+            apply_log_filter(&control, "debug").to_wire(),
+            "debug".to_string()
+        );
+        assert!(matches!(
+            apply_log_filter(&control, "x=nivel"),
+            Response::Error { .. }
+        ));
+        assert_eq!(control.current(), "debug");
+    }
+}
@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use app_core::{clock::SimulatedClock, supervisor::Supervisor};
+
+    use crate::{
+        core::{
+            domain::{
+                models::{KeyMeta, Response, RoleState},
+                services::CacheService,
+            },
+            usecases::exec_meta,
+        },
+        infrastructure::adapters::services::cache_service::{CacheConfig, InMemCache},
+        tests::test_mocks::{cache_service_mock::MockCache, controller::controller_for},
+    };
+
+    #[tokio::test]
+    async fn exec_meta_describes_the_entry() {
+        let cache = MockCache::new();
+        cache.put("k".into(), "valor".into(), None, &[]).await;
+
+        assert_eq!(
+            exec_meta(&cache, "k".into()).await.to_wire(),
+            "version=1 expires_at=- size=6 last_access=0 expired=false stale=false"
+        );
+        assert!(matches!(
+            exec_meta(&cache, "x".into()).await,
+            Response::Empty
+        ));
+        assert!(matches!(
+            exec_meta(&cache, "".into()).await,
+            Response::Empty
+        ));
+    }
+
+    #[test]
+    fn key_meta_shows_the_expiration() {
+        let meta = KeyMeta {
+            version: 3,
+            expires_at: Some(1_500),
+            size_bytes: 10,
+            last_access: 1_200,
+            expired: true,
+            stale: false,
+        };
+        assert_eq!(
+            meta.to_string(),
+            "version=3 expires_at=1500 size=10 last_access=1200 expired=true stale=false"
+        );
+    }
+
+    #[tokio::test]
+    async fn meta_tells_fresh_entries_from_stale_ones() {
+        let supervisor = Supervisor::new();
+        let clock = Arc::new(SimulatedClock::new(1_000_000));
+        let cache = InMemCache::with_config(
+            &supervisor,
+            clock.clone(),
+            CacheConfig {
+                stale_grace: Duration::from_millis(100),
+                ..CacheConfig::default()
+            },
+        );
+        cache
+            .put("k".into(), "v".into(), Some(1_000_020), &[])
+            .await;
+
+        let meta = cache.meta("k").await.unwrap();
+        assert!(!meta.expired && !meta.stale);
+
+        // vencida hace 30ms, dentro de la gracia: los GET con stale= la siguen viendo
+        clock.advance(Duration::from_millis(50));
+        let meta = cache.meta("k").await.unwrap();
+        assert!(meta.expired && meta.stale);
+        assert_eq!(
+            cache
+                .get_stale("k", 50)
+                .await
+                .map(|(_, _, stale_for)| stale_for),
+            Some(Some(30))
+        );
+    }
+
+    #[tokio::test]
+    async fn meta_is_routed_by_the_controller() {
+        let (controller, _) =
+            controller_for(Arc::new(MockCache::new()), Arc::new(RoleState::default()));
+        controller.handle("PUT", "k v").await;
+
+        assert!(
+            controller
+                .handle("META", "\"k\"")
+                .await
+                .to_wire()
+                .starts_with("version=1 ")
+        );
+    }
+}
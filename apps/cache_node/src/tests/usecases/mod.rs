@@ -1,3 +1,14 @@
+mod config_use_case_test;
+mod del_prefix_use_case_test;
+mod del_use_case_test;
+mod export_range_use_case_test;
 mod get_use_case_test;
+mod log_filter_use_case_test;
+mod meta_use_case_test;
+mod peek_use_case_test;
 mod ping_use_case_test;
 mod put_use_case_test;
+mod rename_use_case_test;
+mod set_role_use_case_test;
+mod snapshot_use_case_test;
+mod stats_use_case_test;
@@ -0,0 +1,34 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        core::{
+            domain::{models::Response, services::CacheService},
+            usecases::exec_peek,
+        },
+        tests::test_mocks::cache_service_mock::MockCache,
+    };
+
+    #[tokio::test]
+    async fn exec_peek_returns_empty_when_key_is_empty() {
+        let cache = MockCache::new();
+        assert!(matches!(
+            exec_peek(&cache, "".to_string()).await,
+            Response::Empty
+        ));
+    }
+
+    #[tokio::test]
+    async fn exec_peek_returns_the_value_or_okempty() {
+        let cache = MockCache::new();
+        cache.put("k".into(), "v".into(), None, &[]).await;
+
+        match exec_peek(&cache, "k".to_string()).await {
+            Response::Value(v) => assert_eq!(v, "v"),
+            _ => panic!("Expected Value"),
+        }
+        assert!(matches!(
+            exec_peek(&cache, "missing".to_string()).await,
+            Response::OkEmpty
+        ));
+    }
+}
@@ -1,14 +1,23 @@
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
+    use app_core::{clock::SimulatedClock, error::ErrorKind};
+
     use crate::{
-        core::{domain::models::Response, usecases::exec_put},
+        core::{
+            commands::PutCommand,
+            domain::{models::Response, services::CommandHandler},
+            services::{Op, OpLog, WriteBatcher, WriteBatching},
+            usecases::exec_put,
+        },
         tests::test_mocks::cache_service_mock::MockCache,
     };
 
     #[tokio::test]
     async fn exec_put_returns_empty_when_key_is_empty() {
         let cache = MockCache::new();
-        let resp = exec_put(&cache, "".into(), "value".into(), None).await;
+        let resp = exec_put(&cache, "".into(), "value".into(), None, &[]).await;
 
         match resp {
             Response::Empty => {}
@@ -22,7 +31,7 @@ mod tests {
     #[tokio::test]
     async fn exec_put_returns_empty_when_value_is_empty() {
         let cache = MockCache::new();
-        let resp = exec_put(&cache, "key".into(), "".into(), None).await;
+        let resp = exec_put(&cache, "key".into(), "".into(), None, &[]).await;
 
         match resp {
             Response::Empty => {}
@@ -35,7 +44,7 @@ mod tests {
     #[tokio::test]
     async fn exec_put_stores_value_and_returns_okempty() {
         let cache = MockCache::new();
-        let resp = exec_put(&cache, "key".into(), "value".into(), None).await;
+        let resp = exec_put(&cache, "key".into(), "value".into(), None, &[]).await;
 
         match resp {
             Response::OkEmpty => {}
@@ -45,4 +54,136 @@ mod tests {
         let stored = cache.store.lock();
         assert_eq!(stored.get("key"), Some(&"value".to_string()));
     }
+
+    #[tokio::test]
+    async fn exec_put_rejects_a_zero_expiration() {
+        let cache = MockCache::new();
+        let resp = exec_put(&cache, "key".into(), "value".into(), Some(0), &[]).await;
+
+        assert!(matches!(resp, Response::Error { .. }));
+        assert!(cache.store.lock().is_empty());
+    }
+
+    #[tokio::test]
+    async fn put_expiration_is_an_instant_in_millis_and_rejects_other_units() {
+        let op_log = Arc::new(OpLog::default());
+        let put = PutCommand::new(Arc::new(MockCache::new()), op_log.clone());
+
+        for payload in ["k v 1500", "k v 1500ms", "k v 2000", "k v"] {
+            assert!(matches!(put.handle(payload).await, Response::OkEmpty));
+        }
+        // antes se ignoraba y la clave quedaba sin expiración; un instante no lleva unidad
+        for payload in ["k v 10d", "k v 2s"] {
+            assert!(
+                matches!(put.handle(payload).await, Response::Error { .. }),
+                "{payload}"
+            );
+        }
+
+        let expirations: Vec<Option<u64>> = op_log
+            .read_from(1, 10)
+            .unwrap()
+            .into_iter()
+            .map(|(_, op)| match &*op {
+                Op::Put { expires_at, .. } => *expires_at,
+                Op::Del { .. }
+                | Op::InvalidateTag { .. }
+                | Op::DelPrefix { .. }
+                | Op::Rename { .. } => unreachable!(),
+            })
+            .collect();
+        assert_eq!(
+            expirations,
+            vec![Some(1_500), Some(1_500), Some(2_000), None]
+        );
+    }
+
+    #[tokio::test]
+    async fn a_relative_expiration_counts_from_the_node_clock() {
+        let op_log = Arc::new(OpLog::default());
+        let put = PutCommand::new(Arc::new(MockCache::new()), op_log.clone())
+            .with_clock(Arc::new(SimulatedClock::new(50_000)));
+
+        assert!(matches!(
+            put.handle("k v ttl=1500ms").await,
+            Response::OkEmpty
+        ));
+        assert!(matches!(
+            put.handle("k v ttl=nunca").await,
+            Response::Error { .. }
+        ));
+
+        // las réplicas reciben el instante ya resuelto
+        let (_, op) = op_log.read_from(1, 10).unwrap().remove(0);
+        assert!(matches!(
+            &*op,
+            Op::Put {
+                expires_at: Some(51_500),
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn conditional_put_writes_only_when_the_condition_holds() {
+        let cache = Arc::new(MockCache::new());
+        let op_log = Arc::new(OpLog::default());
+        let put = PutCommand::new(cache.clone(), op_log.clone());
+
+        assert!(matches!(
+            put.handle("k v IF absent").await,
+            Response::OkEmpty
+        ));
+        for payload in ["k w IF absent", "k w 2000 IF value==x", "k w IF version=2"] {
+            assert!(
+                matches!(
+                    put.handle(payload).await,
+                    Response::Error {
+                        code: ErrorKind::PreconditionFailed,
+                        ..
+                    }
+                ),
+                "{payload}"
+            );
+        }
+        assert!(matches!(
+            put.handle("k w IF value==v").await,
+            Response::OkEmpty
+        ));
+        assert!(matches!(
+            put.handle("k x IF nunca").await,
+            Response::Error {
+                code: ErrorKind::BadRequest,
+                ..
+            }
+        ));
+
+        // solo las escrituras aplicadas llegan a las réplicas
+        assert_eq!(op_log.head(), 2);
+        assert_eq!(cache.store.lock().get("k").map(String::as_str), Some("w"));
+    }
+
+    #[tokio::test]
+    async fn batched_puts_are_written_and_logged_like_the_others() {
+        let cache = Arc::new(MockCache::new());
+        let op_log = Arc::new(OpLog::default());
+        let batcher = Arc::new(WriteBatcher::new(cache.clone(), WriteBatching::default()));
+        let cancel = tokio_util::sync::CancellationToken::new();
+        tokio::spawn(batcher.clone().run(cancel.clone()));
+        let put =
+            PutCommand::new(cache.clone(), op_log.clone()).with_batcher(Some(batcher.clone()));
+
+        assert!(matches!(put.handle("k v").await, Response::OkEmpty));
+        assert!(matches!(put.handle("\"\" v").await, Response::Empty));
+        // las condicionales no esperan al lote
+        assert!(matches!(
+            put.handle("k w IF value==v").await,
+            Response::OkEmpty
+        ));
+
+        assert_eq!(batcher.counts(), (1, 1));
+        assert_eq!(op_log.head(), 2);
+        assert_eq!(cache.store.lock().get("k").map(String::as_str), Some("w"));
+        cancel.cancel();
+    }
 }
@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use app_core::error::ErrorKind;
+
+    use crate::{
+        core::{
+            domain::{
+                models::{Response, RoleState},
+                services::CacheService,
+            },
+            services::Op,
+            usecases::exec_rename,
+        },
+        tests::test_mocks::{cache_service_mock::MockCache, controller::controller_for},
+    };
+
+    #[tokio::test]
+    async fn exec_rename_answers_with_a_code_per_outcome() {
+        let cache = MockCache::new();
+        cache.put("a".into(), "1".into(), None, &[]).await;
+        cache.put("b".into(), "2".into(), None, &[]).await;
+
+        let conflict = exec_rename(&cache, "a", "b").await;
+        assert!(matches!(
+            conflict,
+            Response::Error {
+                code: ErrorKind::Conflict,
+                ..
+            }
+        ));
+        assert_eq!(cache.get("b").await.as_deref(), Some("2"));
+
+        assert_eq!(exec_rename(&cache, "a", "c").await, Response::OkEmpty);
+        assert_eq!(cache.get("c").await.as_deref(), Some("1"));
+        assert!(cache.get("a").await.is_none());
+
+        let missing = exec_rename(&cache, "a", "d").await;
+        assert!(matches!(
+            missing,
+            Response::Error {
+                code: ErrorKind::NotFound,
+                ..
+            }
+        ));
+        let bad = exec_rename(&cache, "c", "").await;
+        assert!(matches!(
+            bad,
+            Response::Error {
+                code: ErrorKind::BadRequest,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn only_applied_renames_are_replicated() {
+        let (controller, op_log) =
+            controller_for(Arc::new(MockCache::new()), Arc::new(RoleState::default()));
+
+        controller.handle("PUT", "a 1").await;
+        controller.handle("PUT", "b 2").await;
+        controller.handle("RENAME", "a b").await;
+        controller.handle("RENAME", "x y").await;
+        controller.handle("RENAME", "a c").await;
+
+        let ops: Vec<Op> = op_log
+            .read_from(3, 10)
+            .unwrap()
+            .into_iter()
+            .map(|(_, op)| (*op).clone())
+            .collect();
+        assert_eq!(
+            ops,
+            vec![Op::Rename {
+                from: "a".into(),
+                to: "c".into(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        core::{
+            commands::SetRoleCommand,
+            domain::{
+                models::{NodeRole, Response, RoleState},
+                services::CommandHandler,
+            },
+            usecases::exec_set_role,
+        },
+        tests::test_mocks::{cache_service_mock::MockCache, controller::controller_for},
+    };
+
+    #[tokio::test]
+    async fn set_role_command_reads_its_argument() {
+        let state = Arc::new(RoleState::default());
+        let command = SetRoleCommand::new(state.clone());
+
+        assert_eq!(command.handle("\"REPLICA\"").await.to_wire(), "REPLICA");
+        assert_eq!(state.get(), NodeRole::Replica);
+    }
+
+    #[tokio::test]
+    async fn changes_role_and_acknowledges_it() {
+        let state = RoleState::new(NodeRole::Replica, false);
+
+        assert_eq!(exec_set_role(&state, "".into()).await.to_wire(), "REPLICA");
+        assert_eq!(
+            exec_set_role(&state, "master".into()).await.to_wire(),
+            "MASTER"
+        );
+        assert_eq!(state.get(), NodeRole::Master);
+
+        assert!(matches!(
+            exec_set_role(&state, "LIDER".into()).await,
+            Response::Error { .. }
+        ));
+        assert_eq!(state.get(), NodeRole::Master);
+    }
+
+    #[tokio::test]
+    async fn strict_replicas_reject_writes_until_promoted() {
+        let cache = Arc::new(MockCache::new());
+        let role = Arc::new(RoleState::new(NodeRole::Replica, true));
+        let (controller, _) = controller_for(cache.clone(), role);
+        let put = || controller.handle("PUT", "k v");
+
+        assert!(matches!(put().await, Response::Error { .. }));
+        assert!(cache.store.lock().is_empty());
+
+        controller.handle("SET-ROLE", "MASTER").await;
+        assert!(matches!(put().await, Response::OkEmpty));
+        assert_eq!(cache.store.lock().len(), 1);
+    }
+}
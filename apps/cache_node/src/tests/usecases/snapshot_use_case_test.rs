@@ -0,0 +1,43 @@
+#[cfg(test)]
+mod tests {
+    use app_net::{SnapshotHeader, tokenize};
+
+    use crate::{
+        core::{
+            domain::{models::Response, services::CacheService},
+            services::{Op, OpLog},
+            usecases::exec_snapshot,
+        },
+        infrastructure::adapters::services::cache_service::InMemCache,
+    };
+
+    #[tokio::test]
+    async fn snapshot_lists_live_entries_after_the_log_position() {
+        let cache = InMemCache::new();
+        let op_log = OpLog::new(16);
+        let far = u64::MAX / 2;
+        cache
+            .put("a".into(), "uno dos".into(), Some(far), &["t".into()])
+            .await;
+        cache.put("b".into(), "2".into(), None, &[]).await;
+        op_log.append(Op::Del { key: "x".into() });
+
+        let Response::Values(lines) = exec_snapshot(&cache, &op_log).await else {
+            panic!("SNAPSHOT responde con valores");
+        };
+
+        let header: SnapshotHeader = lines[0].parse().unwrap();
+        assert_eq!(header.epoch, op_log.epoch());
+        assert_eq!((header.seq, header.entries), (1, 2));
+
+        let mut entries: Vec<Vec<String>> = lines[1..]
+            .iter()
+            .map(|line| tokenize(line).map(|t| t.into_owned()).collect())
+            .collect();
+        entries.sort();
+        assert_eq!(entries[0][..2], ["a", "uno dos"]);
+        assert_eq!(entries[0][2], format!("{far}ms"));
+        assert_eq!(entries[0][3], "tags=t");
+        assert_eq!(entries[1], ["b", "2"]);
+    }
+}
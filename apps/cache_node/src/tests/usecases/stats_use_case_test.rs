@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use app_core::{clock::AppClock, error::ErrorKind, supervisor::Supervisor};
+
+    use crate::{
+        core::{
+            domain::models::Response,
+            services::NamespaceQuotas,
+            usecases::{exec_put, exec_stats},
+        },
+        infrastructure::adapters::services::cache_service::{CacheConfig, InMemCache},
+        tests::test_mocks::cache_service_mock::MockCache,
+    };
+
+    fn with_quotas(supervisor: &Supervisor, quotas: &str) -> InMemCache {
+        let config = CacheConfig {
+            namespaces: quotas.parse::<NamespaceQuotas>().unwrap(),
+            ..CacheConfig::default()
+        };
+        InMemCache::with_config(supervisor, Arc::new(AppClock::new()), config)
+    }
+
+    #[tokio::test]
+    async fn exec_stats_starts_with_the_cache_totals() {
+        let cache = MockCache::new();
+        cache.store.lock().insert("a:1".into(), "x".into());
+
+        let resp = exec_stats(&cache, None).await;
+
+        assert_eq!(
+            resp,
+            Response::Values(vec![
+                "entries=1 capacity=0 bytes=0 max_bytes=0 hits=0 misses=0 writes=0 evictions=0 expirations=0 invalidations=0 compactions=0 compacted_bytes=0 expiry_backlog=0 expiry_tick_keys=0 expiry_missed_ticks=0 expiry_reaped=0 expiry_drift_ms=0 expiry_drift_max_ms=0"
+                    .to_string()
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn exec_put_past_the_namespace_quota_is_rejected_and_counted() {
+        let supervisor = Supervisor::new();
+        let cache = with_quotas(&supervisor, "a=1");
+
+        let ok = exec_put(&cache, "a:1".into(), "x".into(), None, &[]).await;
+        let full = exec_put(&cache, "a:2".into(), "x".into(), None, &[]).await;
+        exec_put(&cache, "b:1".into(), "yy".into(), None, &[]).await;
+
+        assert_eq!(ok, Response::OkEmpty);
+        assert!(matches!(
+            full,
+            Response::Error {
+                code: ErrorKind::QuotaExceeded,
+                ..
+            }
+        ));
+        let Response::Values(lines) = exec_stats(&cache, None).await else {
+            panic!("STATS responde con una línea por namespace");
+        };
+        assert_eq!(
+            lines[1..],
+            [
+                "a entries=1 bytes=4 max_entries=1 max_bytes=- evictions=0 rejected=1",
+                "b entries=1 bytes=5 max_entries=- max_bytes=- evictions=0 rejected=0"
+            ]
+        );
+        assert_eq!(
+            exec_stats(&cache, Some("b".into())).await,
+            Response::Values(vec![lines[0].clone(), lines[2].clone()])
+        );
+    }
+}
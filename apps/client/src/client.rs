@@ -1,32 +1,88 @@
 use std::{
     env,
+    future::Future,
     sync::{
-        Arc,
+        Arc, Weak,
         atomic::{AtomicUsize, Ordering},
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use bytes::Bytes;
-use tokio::{
-    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
-    net::TcpStream,
-    sync::mpsc,
-    task::JoinHandle,
+use tokio::{io::AsyncWriteExt, net::TcpStream, sync::mpsc, task::JoinHandle};
+
+use app_core::error::ErrorKind;
+use app_core::{
+    id::new_sortable_id,
+    namespace::{is_valid_namespace, namespaced_key},
+    retry::{RetryPolicy, retry_with_backoff},
+};
+use app_net::{
+    COMPRESS, ClusterMap, Compression, EventData, FrameReader, IF_NOT_VERSION, ParsedMsg,
+    PutCondition, RequestDataInput, ResponseData, Socket, TopologyEvent, encode_args,
+    encode_idempotency, encode_read_node, encode_refresh, encode_stale, encode_sticky,
+    encode_token,
+    event::{CLUSTER_MAP, TOPOLOGY},
+    format_duration, parse_frame,
+    tx::IF,
+};
+use tracing::{error, info, warn};
+
+use crate::{
+    errors::AppError,
+    metrics::{ClientMetrics, ErrorBudget, ErrorBudgetConfig, OperationMetrics, Readiness},
+    sticky::StickyReads,
+    topology::ClusterTopology,
 };
 
-use app_core::utils::generate_short_id;
-use app_net::{ParsedMsg, RequestDataInput, ResponseData, Socket, parse_line};
-use tracing::error;
+/// Actions that mutate data and therefore count against the write error budget.
+const WRITE_ACTIONS: &[&str] = &["PUT", "MULTI", "INVALIDATE-TAG", "RENAME", "DEL-PREFIX"];
 
-use crate::errors::AppError;
+/// Read-then-store rounds `get_or_set` tries before giving up on a key that keeps
+/// appearing and vanishing under it.
+const GET_OR_SET_ATTEMPTS: usize = 3;
+
+/// Full cluster-map refresh period unless `CACHE_TOPOLOGY_REFRESH_SECS` says otherwise.
+const DEFAULT_TOPOLOGY_REFRESH: Duration = Duration::from_secs(30);
+
+/// A read from `CacheClient::get_stale`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaleRead {
+    pub value: String,
+    /// This caller was picked to refresh the entry: the first one to read it expired
+    /// after each write.
+    pub refresh: bool,
+    /// How long ago the value expired; `None` while it's still live.
+    pub stale_for: Option<Duration>,
+}
 
 #[derive(Clone, Debug)]
 pub struct CacheClientConfig {
     pub node_ips: Vec<String>,
     pub connect_timeout: Duration,
     pub request_timeout: Duration,
-    pub retry_backoff: Duration,
+    /// Backoff between connection attempts. `max_attempts` is always one pass over `node_ips`.
+    pub retry: RetryPolicy,
+    pub error_budget: ErrorBudgetConfig,
+    /// Default namespace prepended to every key (`{namespace}:{key}`), if any.
+    pub namespace: Option<String>,
+    /// Up to this fraction (0..=1) is randomly added to every PUT TTL, so keys written
+    /// together don't all expire at the same instant.
+    pub ttl_jitter: f64,
+    /// How often the cluster map is re-read in full and the other masters are probed for
+    /// a more complete view. Pushed `TOPOLOGY` events keep it current in between; `None`
+    /// disables the map altogether.
+    pub topology_refresh: Option<Duration>,
+    /// Tag every PUT with a fresh `idem=` token, so a retry or failover that re-sends it
+    /// after a lost response isn't applied twice by the node.
+    pub idempotency_keys: bool,
+    /// Read-your-writes: for this long after a PUT, GETs of the key go to the node that
+    /// acknowledged it instead of whichever node of the shard answers first. `None` (the
+    /// default) reads as usual.
+    pub sticky_reads: Option<Duration>,
+    /// Tell the master this client reads compressed responses (`EVT COMPRESS`), so large
+    /// values come compressed if the master is configured to compress them.
+    pub accept_compression: bool,
 }
 
 impl CacheClientConfig {
@@ -44,22 +100,98 @@ impl CacheClientConfig {
             return Err(AppError::ConnectionError("CACHE_IPS is empty".into()));
         }
 
+        let mut error_budget = ErrorBudgetConfig::default();
+        if let Some(rate) = env::var("WRITE_ERROR_BUDGET")
+            .ok()
+            .and_then(|s| s.parse::<f64>().ok())
+        {
+            error_budget.max_write_error_rate = rate.clamp(0.0, 1.0);
+        }
+        if let Some(secs) = env::var("WRITE_ERROR_BUDGET_WINDOW_SECS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+        {
+            error_budget.window = Duration::from_secs(secs);
+        }
+
+        let namespace = env::var("CACHE_NAMESPACE")
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty());
+
+        if let Some(ns) = &namespace
+            && !is_valid_namespace(ns)
+        {
+            return Err(AppError::BadRequest(format!(
+                "invalid CACHE_NAMESPACE: {ns}"
+            )));
+        }
+
+        let ttl_jitter = env::var("CACHE_TTL_JITTER")
+            .ok()
+            .and_then(|s| s.parse::<f64>().ok())
+            .map_or(0.0, |j| j.clamp(0.0, 1.0));
+
+        let topology_refresh = match env::var("CACHE_TOPOLOGY_REFRESH_SECS") {
+            Ok(secs) => {
+                let secs = secs.trim().parse::<u64>().map_err(|_| {
+                    AppError::BadRequest(format!("invalid CACHE_TOPOLOGY_REFRESH_SECS: {secs}"))
+                })?;
+                (secs > 0).then(|| Duration::from_secs(secs))
+            }
+            Err(_) => Some(DEFAULT_TOPOLOGY_REFRESH),
+        };
+
+        let idempotency_keys =
+            env::var("CACHE_IDEMPOTENCY_KEYS").is_ok_and(|v| matches!(v.trim(), "1" | "true"));
+
+        let sticky_reads = match env::var("CACHE_STICKY_READS_MS") {
+            Ok(ms) => {
+                let ms = ms.trim().parse::<u64>().map_err(|_| {
+                    AppError::BadRequest(format!("invalid CACHE_STICKY_READS_MS: {ms}"))
+                })?;
+                (ms > 0).then(|| Duration::from_millis(ms))
+            }
+            Err(_) => None,
+        };
+
+        let accept_compression =
+            env::var("CACHE_COMPRESSION").map_or(true, |v| !matches!(v.trim(), "0" | "false"));
+
         Ok(Self {
             node_ips,
             connect_timeout: Duration::from_secs(5),
             request_timeout: Duration::from_secs(10),
-            retry_backoff: Duration::from_millis(300),
+            retry: default_retry_policy(),
+            error_budget,
+            namespace,
+            ttl_jitter,
+            topology_refresh,
+            idempotency_keys,
+            sticky_reads,
+            accept_compression,
         })
     }
 }
 
+fn default_retry_policy() -> RetryPolicy {
+    RetryPolicy::new(Duration::from_millis(300), Duration::from_secs(3)).with_jitter(0.2)
+}
+
 impl Default for CacheClientConfig {
     fn default() -> Self {
         Self {
             node_ips: vec!["127.0.0.1:5555".to_string()],
             connect_timeout: Duration::from_secs(5),
             request_timeout: Duration::from_secs(10),
-            retry_backoff: Duration::from_millis(300),
+            retry: default_retry_policy(),
+            error_budget: ErrorBudgetConfig::default(),
+            namespace: None,
+            ttl_jitter: 0.0,
+            topology_refresh: Some(DEFAULT_TOPOLOGY_REFRESH),
+            idempotency_keys: false,
+            sticky_reads: None,
+            accept_compression: true,
         }
     }
 }
@@ -75,12 +207,38 @@ pub struct CacheClient {
     /// IO tasks associated with the current connection (writer and reader).
     io_writer: parking_lot::Mutex<Option<JoinHandle<()>>>,
     io_reader: parking_lot::Mutex<Option<JoinHandle<Result<(), AppError>>>>,
+    /// Latency histograms per (action, target master).
+    metrics: ClientMetrics,
+    /// Write error budget driving the read-only degradation.
+    error_budget: ErrorBudget,
+    /// Cluster map of the connected master; empty while `topology_refresh` is `None`.
+    topology: Arc<ClusterTopology>,
+    /// Keys recently written, with the node to read them from; with `sticky_reads` only.
+    sticky: Option<StickyReads>,
+}
+
+/// An open connection to one master, before it's installed as the current one.
+struct Connection {
+    socket: Arc<Socket>,
+    writer: JoinHandle<()>,
+    reader: JoinHandle<Result<(), AppError>>,
+    /// Topology generation its events belong to; `None` if they're not followed.
+    generation: Option<u64>,
+}
+
+impl Connection {
+    fn close(self) {
+        self.writer.abort();
+        self.reader.abort();
+    }
 }
 
 impl CacheClient {
     /// Build a client and eagerly connect to the first available master.
     pub async fn connect_with(cfg: CacheClientConfig) -> Result<Arc<Self>, AppError> {
-        let node_id = Arc::<str>::from(generate_short_id(8));
+        let node_id = Arc::<str>::from(new_sortable_id());
+        let error_budget = ErrorBudget::new(cfg.error_budget.clone());
+        let sticky = cfg.sticky_reads.map(StickyReads::new);
         let client = Arc::new(Self {
             cfg,
             node_id,
@@ -88,9 +246,23 @@ impl CacheClient {
             socket: parking_lot::RwLock::new(None),
             io_writer: parking_lot::Mutex::new(None),
             io_reader: parking_lot::Mutex::new(None),
+            metrics: ClientMetrics::new(),
+            error_budget,
+            topology: Arc::new(ClusterTopology::new()),
+            sticky,
         });
 
         client.ensure_connected().await?;
+
+        if let Some(every) = client.cfg.topology_refresh {
+            if let Err(e) = client.refresh_topology().await {
+                warn!(
+                    ?e,
+                    "could not fetch the cluster map; retrying in the background"
+                );
+            }
+            tokio::spawn(refresh_topology_loop(Arc::downgrade(&client), every));
+        }
         Ok(client)
     }
 
@@ -103,7 +275,37 @@ impl CacheClient {
     }
 
     /// Send a raw request; auto-reconnects once if the first attempt fails.
+    ///
+    /// Writes are rejected locally with `AppError::ReadOnly` while the write error budget
+    /// is exhausted.
     pub async fn request_raw(&self, action: &str, payload: &str) -> Result<ResponseData, AppError> {
+        let is_write = WRITE_ACTIONS.contains(&action);
+
+        if is_write && self.readiness() == Readiness::ReadOnly {
+            return Err(AppError::ReadOnly(format!(
+                "write error rate {:.2} over budget",
+                self.error_budget.write_error_rate()
+            )));
+        }
+
+        let result = self.request_with_failover(action, payload).await;
+
+        if is_write {
+            // a `PUT ... IF` whose condition didn't hold was still served by the cluster
+            self.error_budget.record_write(matches!(
+                &result,
+                Ok(r) if r.is_success() || r.error_kind() == Some(ErrorKind::PreconditionFailed)
+            ));
+        }
+
+        result
+    }
+
+    async fn request_with_failover(
+        &self,
+        action: &str,
+        payload: &str,
+    ) -> Result<ResponseData, AppError> {
         self.ensure_connected().await?;
         match self.do_request(action, payload).await {
             Ok(s) => Ok(s),
@@ -116,34 +318,490 @@ impl CacheClient {
         }
     }
 
+    /// Snapshot of the latency histograms recorded so far, per (action, target master).
+    pub fn metrics(&self) -> Vec<OperationMetrics> {
+        self.metrics.snapshot()
+    }
+
+    /// The cluster as seen by the connected master: shards with their nodes, as of the
+    /// last pushed `TOPOLOGY` event. `None` until the first map arrives, or with
+    /// `topology_refresh` disabled.
+    pub fn cluster_map(&self) -> Option<ClusterMap> {
+        self.topology.map()
+    }
+
+    /// Subscribe to `TOPOLOGY` on the connected master (again is harmless) and replace
+    /// the cluster map with its `CLUSTER-MAP`.
+    pub async fn refresh_topology(&self) -> Result<ClusterMap, AppError> {
+        let res = self
+            .request_with_failover("SUBSCRIBE", &encode_token(TOPOLOGY))
+            .await?;
+        if !res.is_success() {
+            return Err(AppError::BadRequest(format!("SUBSCRIBE: {}", res.payload)));
+        }
+
+        let res = self.request_with_failover(CLUSTER_MAP, "").await?;
+        if !res.is_success() {
+            return Err(AppError::BadRequest(format!(
+                "{CLUSTER_MAP}: {}",
+                res.payload
+            )));
+        }
+        let map: ClusterMap = res
+            .payload
+            .parse()
+            .map_err(|e: app_net::SocketError| AppError::BadRequest(e.to_string()))?;
+
+        self.topology.install(map.clone());
+        Ok(map)
+    }
+
+    /// Ask every other master for its map and move to the one that sees the most nodes,
+    /// if it sees more than the current one: a master that just restarted, or that lost
+    /// some nodes, knows less of the cluster than its peers. Returns whether it moved.
+    pub async fn prefer_best_master(&self) -> Result<bool, AppError> {
+        let total = self.cfg.node_ips.len();
+        if total < 2 {
+            return Ok(false);
+        }
+
+        let current = self.current_idx.load(Ordering::Relaxed);
+        let mut best = (current, self.topology.map().map_or(0, |m| m.node_count()));
+        for idx in (0..total).filter(|idx| *idx != current) {
+            match self.probe_map(idx).await {
+                Ok(map) if map.node_count() > best.1 => best = (idx, map.node_count()),
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::debug!(?e, addr = %self.cfg.node_ips[idx], "master probe failed")
+                }
+            }
+        }
+        if best.0 == current {
+            return Ok(false);
+        }
+
+        let conn = self.open(best.0).await?;
+        let previous = self.replace_connection(best.0, conn);
+        info!(
+            addr = %self.cfg.node_ips[best.0],
+            nodes = best.1,
+            "moved to the master with the most complete cluster map"
+        );
+        // requests still in flight on the old connection get to finish
+        if let Some(previous) = previous {
+            let grace = self.cfg.request_timeout;
+            tokio::spawn(async move {
+                tokio::time::sleep(grace).await;
+                previous.close();
+            });
+        }
+        self.refresh_topology().await?;
+        Ok(true)
+    }
+
+    /// `CLUSTER-MAP` from master `idx` over a throwaway connection.
+    async fn probe_map(&self, idx: usize) -> Result<ClusterMap, AppError> {
+        let conn = self.connect(idx, None).await?;
+        let res = conn
+            .socket
+            .request(RequestDataInput::new(CLUSTER_MAP, ""))
+            .await;
+        conn.close();
+
+        let res = res?;
+        if !res.is_success() {
+            return Err(AppError::BadRequest(format!(
+                "{CLUSTER_MAP}: {}",
+                res.payload
+            )));
+        }
+        res.payload
+            .parse()
+            .map_err(|e: app_net::SocketError| AppError::BadRequest(e.to_string()))
+    }
+
+    /// Current write error rate over the error-budget window.
+    pub fn write_error_rate(&self) -> f64 {
+        self.error_budget.write_error_rate()
+    }
+
+    /// `ReadOnly` when the write error rate exceeds the configured budget.
+    pub fn readiness(&self) -> Readiness {
+        self.error_budget.readiness()
+    }
+
     /// High-level convenience: GET (returns raw string). Use `get_opt` for `Option` handling.
+    /// The key is scoped to the configured default namespace, if any.
     pub async fn get(&self, key: &str) -> Result<ResponseData, AppError> {
-        self.request_raw("GET", key).await
+        let key = self.scoped_key(None, key)?;
+        self.request_raw("GET", &self.get_payload(&key)).await
+    }
+
+    /// GET scoped to an explicit namespace, overriding the configured default.
+    pub async fn get_in(&self, namespace: &str, key: &str) -> Result<ResponseData, AppError> {
+        let key = self.scoped_key(Some(namespace), key)?;
+        self.request_raw("GET", &self.get_payload(&key)).await
+    }
+
+    /// Conditional GET in `namespace` (or the configured default). If the key is still at
+    /// `version` the cluster answers `304` without the value (`is_not_modified`);
+    /// otherwise the value comes back as usual. Either way `ResponseData::version` carries
+    /// the key's current version for the next call. Pass `0` on a first read: no stored
+    /// key is at version 0.
+    pub async fn get_if_not_version(
+        &self,
+        namespace: Option<&str>,
+        key: &str,
+        version: u64,
+    ) -> Result<ResponseData, AppError> {
+        let key = self.scoped_key(namespace, key)?;
+        let version = version.to_string();
+        let payload = encode_args([key.as_str(), IF_NOT_VERSION, &version]);
+        self.request_raw("GET", &payload).await
     }
 
     /// GET but mapped to Option: treats "EMPTY" (or empty line) as None.
     pub async fn get_opt(&self, key: &str) -> Result<Option<String>, AppError> {
         let raw = self.get(key).await?;
         let trimmed = raw.payload.trim();
-        if trimmed.is_empty() || trimmed.eq_ignore_ascii_case("EMPTY") {
+        if trimmed.is_empty() || raw.is_empty_value() {
             Ok(None)
         } else {
             Ok(Some(trimmed.to_string()))
         }
     }
 
-    /// High-level convenience: PUT ("key value" or with ttl if provided).
+    /// PEEK: like `get`, but the read doesn't count as an access (hot-key tracking and
+    /// eviction order are left as they were). Meant for monitoring and debugging tools.
+    pub async fn peek(&self, key: &str) -> Result<ResponseData, AppError> {
+        let key = self.scoped_key(None, key)?;
+        self.request_raw("PEEK", &encode_token(&key)).await
+    }
+
+    /// High-level convenience: PUT ("key value", plus the TTL if provided). The TTL goes
+    /// over the wire in milliseconds with an explicit unit (`"30000ms"`).
+    /// The key is scoped to the configured default namespace, if any.
     pub async fn put(
         &self,
         key: &str,
         value: &str,
-        ttl_secs: Option<u64>,
+        ttl: Option<Duration>,
+    ) -> Result<ResponseData, AppError> {
+        let key = self.scoped_key(None, key)?;
+        self.put_raw(&key, value, ttl).await
+    }
+
+    /// PUT scoped to an explicit namespace, overriding the configured default.
+    pub async fn put_in(
+        &self,
+        namespace: &str,
+        key: &str,
+        value: &str,
+        ttl: Option<Duration>,
+    ) -> Result<ResponseData, AppError> {
+        let key = self.scoped_key(Some(namespace), key)?;
+        self.put_raw(&key, value, ttl).await
+    }
+
+    /// Conditional PUT in `namespace` (or the configured default): the write only
+    /// happens if `condition` holds against the current entry, checked by the owning node
+    /// (`PutCondition::Version` for compare-and-set on a version from
+    /// `get_if_not_version`). A condition that doesn't hold comes back as `412`.
+    pub async fn put_if(
+        &self,
+        namespace: Option<&str>,
+        key: &str,
+        value: &str,
+        ttl: Option<Duration>,
+        condition: &PutCondition,
+    ) -> Result<ResponseData, AppError> {
+        let key = self.scoped_key(namespace, key)?;
+        self.put_if_raw(&key, value, ttl, condition).await
+    }
+
+    /// RENAME: moves `from` to `to`, both in `namespace` (or the configured default),
+    /// keeping the value and its expiry. Comes back as `404` when `from` doesn't exist and
+    /// `409` when `to` already does; nothing is overwritten.
+    pub async fn rename(
+        &self,
+        namespace: Option<&str>,
+        from: &str,
+        to: &str,
+    ) -> Result<ResponseData, AppError> {
+        let from = self.scoped_key(namespace, from)?;
+        let to = self.scoped_key(namespace, to)?;
+        self.request_raw("RENAME", &encode_args([from.as_str(), to.as_str()]))
+            .await
+    }
+
+    /// Read-through GET with early refresh ("stale-while-refresh"). Within `window` of the
+    /// entry's expiry the cluster may pick this caller to refresh it, with a chance that
+    /// grows towards expiry and at most once per write on each node: the current value is
+    /// returned right away and `loader` runs in the background to PUT a fresh one with
+    /// `ttl`. On a miss `loader` runs inline and its value is stored before returning.
+    pub async fn get_or_refresh<F, Fut>(
+        self: &Arc<Self>,
+        key: &str,
+        ttl: Duration,
+        window: Duration,
+        loader: F,
+    ) -> Result<String, AppError>
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<String, AppError>> + Send + 'static,
+    {
+        let key = self.scoped_key(None, key)?;
+        let window_ms = u64::try_from(window.as_millis()).unwrap_or(u64::MAX);
+        let payload = encode_args([key.as_str(), &encode_refresh(window_ms)]);
+
+        let response = self.request_raw("GET", &payload).await?;
+        if !response.is_success() {
+            return Err(AppError::remote("GET", &response));
+        }
+
+        if let [value, refresh] = response.values().as_slice() {
+            if refresh == "1" {
+                self.spawn_refresh(key, ttl, loader);
+            }
+            return Ok(value.clone());
+        }
+
+        let value = loader().await?;
+        self.put_checked(&key, &value, ttl).await?;
+        Ok(value)
+    }
+
+    /// GET in `namespace` (or the configured default) that also accepts a value that
+    /// expired less than `max_stale` ago, while the nodes still keep it (their
+    /// `STALE_GRACE_MS`). `None` when there is neither a live nor a recent enough value.
+    pub async fn get_stale(
+        &self,
+        namespace: Option<&str>,
+        key: &str,
+        max_stale: Duration,
+    ) -> Result<Option<StaleRead>, AppError> {
+        let key = self.scoped_key(namespace, key)?;
+        // the wire refuses `stale=0`; a millisecond is as good as fresh-only
+        let max_stale_ms = u64::try_from(max_stale.as_millis())
+            .unwrap_or(u64::MAX)
+            .max(1);
+        let payload = encode_args([key.as_str(), &encode_stale(max_stale_ms)]);
+
+        let response = self.request_raw("GET", &payload).await?;
+        if !response.is_success() {
+            return Err(AppError::remote("GET", &response));
+        }
+        match response.values().as_slice() {
+            [value, refresh, stale_ms] => {
+                let stale_ms: u64 = stale_ms.parse().map_err(|_| {
+                    AppError::SocketError(format!("bad stale GET reply: {}", response.payload))
+                })?;
+                Ok(Some(StaleRead {
+                    value: value.clone(),
+                    refresh: refresh == "1",
+                    stale_for: (stale_ms > 0).then(|| Duration::from_millis(stale_ms)),
+                }))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Read-through GET that rides out origin latency ("stale-while-revalidate"): a value
+    /// that expired less than `max_stale` ago is returned right away, and the one caller
+    /// picked to refresh it runs `loader` in the background to PUT a fresh one with `ttl`.
+    /// On a miss `loader` runs inline and its value is stored before returning.
+    pub async fn get_stale_while_revalidate<F, Fut>(
+        self: &Arc<Self>,
+        key: &str,
+        ttl: Duration,
+        max_stale: Duration,
+        loader: F,
+    ) -> Result<String, AppError>
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<String, AppError>> + Send + 'static,
+    {
+        let read = self.get_stale(None, key, max_stale).await?;
+        let key = self.scoped_key(None, key)?;
+        if let Some(read) = read {
+            if read.refresh {
+                self.spawn_refresh(key, ttl, loader);
+            }
+            return Ok(read.value);
+        }
+
+        let value = loader().await?;
+        self.put_checked(&key, &value, ttl).await?;
+        Ok(value)
+    }
+
+    /// Runs `loader` in the background and PUTs what it returns under the physical `key`.
+    fn spawn_refresh<F, Fut>(self: &Arc<Self>, key: String, ttl: Duration, loader: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<String, AppError>> + Send + 'static,
+    {
+        let client = self.clone();
+        tokio::spawn(async move {
+            let stored = match loader().await {
+                Ok(value) => client.put_checked(&key, &value, ttl).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = stored {
+                error!("background refresh of {key} failed: {e}");
+            }
+        });
+    }
+
+    /// Cache-aside in one call: the value stored under `key` in `namespace` (or the
+    /// configured default) or, when the key is absent, `default` stored with `ttl`. The
+    /// store is a `PUT ... IF absent` (SETNX), so of several callers racing on a missing
+    /// key exactly one writes and the others get what it wrote. The flag is `true` when
+    /// this call stored `default`.
+    pub async fn get_or_set(
+        &self,
+        namespace: Option<&str>,
+        key: &str,
+        default: &str,
+        ttl: Option<Duration>,
+    ) -> Result<(String, bool), AppError> {
+        let key = self.scoped_key(namespace, key)?;
+        let mut lost = None;
+
+        for _ in 0..GET_OR_SET_ATTEMPTS {
+            let response = self.request_raw("GET", &self.get_payload(&key)).await?;
+            if !response.is_success() {
+                return Err(AppError::remote("GET", &response));
+            }
+            if !response.payload.is_empty() && !response.is_empty_value() {
+                return Ok((response.payload, false));
+            }
+
+            let response = self
+                .put_if_raw(&key, default, ttl, &PutCondition::Absent)
+                .await?;
+            if response.is_success() {
+                return Ok((default.to_string(), true));
+            }
+            if response.error_kind() != Some(ErrorKind::PreconditionFailed) {
+                return Err(AppError::remote("PUT", &response));
+            }
+            // someone else stored it first: read theirs
+            lost = Some(response);
+        }
+
+        let response = lost.expect("GET_OR_SET_ATTEMPTS is not zero");
+        Err(AppError::remote("PUT", &response))
+    }
+
+    /// Physical key for `key` under `namespace` (or the configured default namespace).
+    pub fn scoped_key(&self, namespace: Option<&str>, key: &str) -> Result<String, AppError> {
+        match namespace.or(self.cfg.namespace.as_deref()) {
+            Some(ns) if !is_valid_namespace(ns) => {
+                Err(AppError::BadRequest(format!("invalid namespace: {ns}")))
+            }
+            Some(ns) => Ok(namespaced_key(ns, key)),
+            None => Ok(key.to_string()),
+        }
+    }
+
+    async fn put_raw(
+        &self,
+        key: &str,
+        value: &str,
+        ttl: Option<Duration>,
     ) -> Result<ResponseData, AppError> {
-        let payload = match ttl_secs {
-            Some(ttl) => format!("{} \"{}\" {}", key, value, ttl),
-            None => format!("{} \"{}\"", key, value),
+        let ttl = ttl.map(|ttl| format_duration(self.jittered(ttl)));
+        let token = self.idempotency_token();
+        let payload = encode_args(
+            [key, value]
+                .into_iter()
+                .chain(ttl.as_deref())
+                .chain(token.as_deref())
+                .chain(self.sticky_option()),
+        );
+        let response = self.request_raw("PUT", &payload).await?;
+        Ok(self.pin_write(key, response))
+    }
+
+    /// `put_raw` that only writes if `condition` holds; `412` otherwise.
+    async fn put_if_raw(
+        &self,
+        key: &str,
+        value: &str,
+        ttl: Option<Duration>,
+        condition: &PutCondition,
+    ) -> Result<ResponseData, AppError> {
+        let ttl = ttl.map(|ttl| format_duration(self.jittered(ttl)));
+        let condition = condition.to_string();
+        let token = self.idempotency_token();
+        let payload = encode_args(
+            [key, value]
+                .into_iter()
+                .chain(ttl.as_deref())
+                .chain(token.as_deref())
+                .chain(self.sticky_option())
+                .chain([IF, condition.as_str()]),
+        );
+        let response = self.request_raw("PUT", &payload).await?;
+        Ok(self.pin_write(key, response))
+    }
+
+    /// `put_raw` with a TTL, turning a non-2xx response into an error. `key` is already
+    /// scoped (see `scoped_key`).
+    pub(crate) async fn put_checked(
+        &self,
+        key: &str,
+        value: &str,
+        ttl: Duration,
+    ) -> Result<(), AppError> {
+        let response = self.put_raw(key, value, Some(ttl)).await?;
+        if !response.is_success() {
+            return Err(AppError::remote("PUT", &response));
+        }
+        Ok(())
+    }
+
+    /// A new `idem=` argument for a PUT, with `idempotency_keys` on.
+    fn idempotency_token(&self) -> Option<String> {
+        self.cfg
+            .idempotency_keys
+            .then(|| encode_idempotency(&new_sortable_id()))
+    }
+
+    /// `sticky=1` on every PUT with `sticky_reads` on, so the reply names the node.
+    fn sticky_option(&self) -> Option<&'static str> {
+        self.sticky.as_ref().map(|_| encode_sticky())
+    }
+
+    /// Pin `key` to the node a sticky PUT's reply names, and hand back the plain `OK`.
+    fn pin_write(&self, key: &str, mut response: ResponseData) -> ResponseData {
+        let Some(sticky) = &self.sticky else {
+            return response;
         };
-        self.request_raw("PUT", &payload).await
+        if response.is_success()
+            && let [ok, node_id] = response.values().as_slice()
+        {
+            sticky.pin(key, node_id);
+            response.payload = ok.clone();
+        }
+        response
+    }
+
+    /// GET payload for `key`: pinned to a node while a write of it is recent.
+    fn get_payload(&self, key: &str) -> String {
+        match self.sticky.as_ref().and_then(|sticky| sticky.node_for(key)) {
+            Some(node_id) => encode_args([key, &encode_read_node(&node_id)]),
+            None => encode_token(key).into_owned(),
+        }
+    }
+
+    /// `ttl` plus up to `ttl_jitter` of itself, at random; never shorter.
+    fn jittered(&self, ttl: Duration) -> Duration {
+        if self.cfg.ttl_jitter <= 0.0 {
+            return ttl;
+        }
+        ttl.mul_f64(1.0 + self.cfg.ttl_jitter * fastrand::f64())
     }
 
     // --- Internals ---
@@ -155,15 +813,20 @@ impl CacheClient {
             .as_ref()
             .cloned()
             .ok_or_else(|| AppError::ConnectionError("no active connection".into()))?;
-        let res = sock
-            .request(RequestDataInput::new(action, payload))
-            .await
-            .map_err(|e| {
-                AppError::SocketError(format!("request failed: {} {} => {}", action, payload, e))
-            })?;
-        // `res` is likely some ResponseData type. Convert to String in a way your API supports.
-        // If `ResponseData` already implements `ToString`, this works; otherwise adjust accordingly.
-        Ok(res)
+        let target = self.current_target();
+        let start = Instant::now();
+        let res = sock.request(RequestDataInput::new(action, payload)).await;
+
+        let success = matches!(&res, Ok(r) if r.is_success());
+        self.metrics
+            .record(action, &target, start.elapsed(), success);
+
+        res.map_err(|e| AppError::request(action, payload, e))
+    }
+
+    fn current_target(&self) -> String {
+        let idx = self.current_idx.load(Ordering::Relaxed);
+        self.cfg.node_ips.get(idx).cloned().unwrap_or_default()
     }
 
     async fn try_connect_any(&self) -> Result<(), AppError> {
@@ -173,23 +836,42 @@ impl CacheClient {
             ));
         }
 
-        let start = self.current_idx.load(Ordering::Relaxed) % self.cfg.node_ips.len();
+        let total = self.cfg.node_ips.len();
+        let start = self.current_idx.load(Ordering::Relaxed) % total;
+        let policy = self.cfg.retry.clone().with_max_attempts(total as u32);
+
         // Try from `start`, wrap once.
-        for attempt in 0..self.cfg.node_ips.len() {
-            let idx = (start + attempt) % self.cfg.node_ips.len();
-            match self.open_and_handshake(idx).await {
-                Ok(()) => return Ok(()),
-                Err(e) => {
+        retry_with_backoff(&policy, |attempt| {
+            let idx = (start + attempt as usize - 1) % total;
+            async move {
+                self.open_and_handshake(idx).await.inspect_err(|e| {
                     tracing::warn!(?e, addr = %self.cfg.node_ips[idx], "connect attempt failed; trying next");
-                    tokio::time::sleep(self.cfg.retry_backoff).await;
-                }
+                })
             }
+        })
+        .await
+        .map_err(|_| AppError::ConnectionError("all masters unreachable".into()))
+    }
+
+    async fn open_and_handshake(&self, idx: usize) -> Result<(), AppError> {
+        let conn = self.open(idx).await?;
+        // Swap current connection (and abort old one if present)
+        if let Some(previous) = self.replace_connection(idx, conn) {
+            previous.close();
         }
+        Ok(())
+    }
 
-        Err(AppError::ConnectionError("all masters unreachable".into()))
+    /// Connection to master `idx` that will feed the cluster map once installed.
+    async fn open(&self, idx: usize) -> Result<Connection, AppError> {
+        let generation = self
+            .cfg
+            .topology_refresh
+            .map(|_| self.topology.new_generation());
+        self.connect(idx, generation).await
     }
 
-    async fn open_and_handshake(&self, idx: usize) -> Result<(), AppError> {
+    async fn connect(&self, idx: usize, generation: Option<u64>) -> Result<Connection, AppError> {
         let addr = self.cfg.node_ips[idx].clone();
         let stream = tokio::time::timeout(self.cfg.connect_timeout, TcpStream::connect(&addr))
             .await
@@ -219,64 +901,79 @@ impl CacheClient {
         });
 
         // Identify ourselves once connected
-        socket
-            .send_raw(Bytes::from(format!("{}\n", self.node_id)))
-            .map_err(|e| AppError::SocketError(format!("Failed on identification: {}", e)))?;
+        socket.send_raw(Bytes::from(format!("{}\n", self.node_id)))?;
+        if self.cfg.accept_compression {
+            socket.send_evt(&EventData::new(COMPRESS, Compression::Deflate.as_str()))?;
+        }
 
         // Reader task: route server lines into `socket.handle_response`
         let reader_socket = socket.clone();
+        let topology = generation.map(|g| (g, self.topology.clone()));
         let reader_task = tokio::spawn(async move {
-            let mut br = BufReader::new(reader);
-            let mut line = String::new();
-            loop {
-                line.clear();
-                let n = br
-                    .read_line(&mut line)
-                    .await
-                    .map_err(|e| AppError::SocketError(e.to_string()))?;
-                if n == 0 {
-                    break;
-                }
-                let current_line = parse_line(&line)
-                    .map_err(|e| AppError::SocketError(format!("Failed Reading Line: {:?}", e)))?;
+            let mut frames = FrameReader::new(reader);
+            while let Some(frame) = frames
+                .next_frame()
+                .await
+                .map_err(|e| AppError::SocketError(e.to_string()))?
+            {
+                let current_line = parse_frame(&frame)?;
                 match current_line {
                     ParsedMsg::Res { id, raw_response } => {
                         reader_socket.handle_response(id, raw_response.to_string())
                     }
                     ParsedMsg::Req { data } => {
-                        // Client-side we don't expect server-initiated REQ, but print for visibility.
-                        tracing::info!(?data, "server -> client REQ");
+                        // Client-side we don't expect server-initiated REQ; log it for visibility.
+                        tracing::debug!(req_id = %data.id, action = data.action, "server -> client REQ");
                     }
-                    ParsedMsg::Other(msg) => tracing::info!(%msg, "server line"),
+                    ParsedMsg::Evt { data } if data.name == TOPOLOGY => {
+                        let Some((generation, topology)) = &topology else {
+                            continue;
+                        };
+                        match data.payload.parse::<TopologyEvent>() {
+                            Ok(event) => topology.on_event(*generation, event),
+                            Err(e) => warn!(%e, "unreadable TOPOLOGY event"),
+                        }
+                    }
+                    ParsedMsg::Evt { data } => {
+                        tracing::debug!(name = data.name, "server -> client EVT");
+                    }
+                    ParsedMsg::Other(msg) => tracing::debug!(%msg, "server line"),
                 }
             }
             Ok::<(), AppError>(())
         });
 
-        // Swap current connection (and abort old one if present)
-        self.replace_connection(idx, socket, writer_task, reader_task);
-        Ok(())
+        Ok(Connection {
+            socket,
+            writer: writer_task,
+            reader: reader_task,
+            generation,
+        })
     }
 
-    fn replace_connection(
-        &self,
-        idx: usize,
-        sock: Arc<Socket>,
-        writer: JoinHandle<()>,
-        reader: JoinHandle<Result<(), AppError>>,
-    ) {
-        // Abort previous tasks (if any)
-        if let Some(h) = self.io_writer.lock().take() {
-            h.abort();
+    /// Install `conn` as the current connection and hand back the previous one, if any.
+    fn replace_connection(&self, idx: usize, conn: Connection) -> Option<Connection> {
+        let previous_socket = self.socket.write().replace(conn.socket);
+        let previous_writer = self.io_writer.lock().replace(conn.writer);
+        let previous_reader = self.io_reader.lock().replace(conn.reader);
+        self.current_idx.store(idx, Ordering::Relaxed);
+        if let Some(generation) = conn.generation {
+            self.topology.activate(generation);
         }
-        if let Some(h) = self.io_reader.lock().take() {
-            h.abort();
+
+        match (previous_socket, previous_writer, previous_reader) {
+            (Some(socket), Some(writer), Some(reader)) => Some(Connection {
+                socket,
+                writer,
+                reader,
+                generation: None,
+            }),
+            (_, writer, reader) => {
+                writer.inspect(JoinHandle::abort);
+                reader.inspect(JoinHandle::abort);
+                None
+            }
         }
-        // Install new
-        *self.socket.write() = Some(sock);
-        *self.io_writer.lock() = Some(writer);
-        *self.io_reader.lock() = Some(reader);
-        self.current_idx.store(idx, Ordering::Relaxed);
     }
 
     /// Break the current connection (forces next request to reconnect/failover).
@@ -290,3 +987,31 @@ impl CacheClient {
         *self.socket.write() = None;
     }
 }
+
+/// Re-read the cluster map whenever a connection changes or an epoch goes missing, and
+/// every `every` in full, probing the other masters first. Ends with the client.
+async fn refresh_topology_loop(client: Weak<CacheClient>, every: Duration) {
+    let Some(topology) = client.upgrade().map(|c| c.topology.clone()) else {
+        return;
+    };
+    loop {
+        let periodic = tokio::select! {
+            _ = topology.refresh_requested() => false,
+            _ = tokio::time::sleep(every) => true,
+        };
+        let Some(client) = client.upgrade() else {
+            return;
+        };
+
+        if periodic {
+            match client.prefer_best_master().await {
+                Ok(true) => continue,
+                Ok(false) => {}
+                Err(e) => warn!(?e, "could not compare the masters' cluster maps"),
+            }
+        }
+        if let Err(e) = client.refresh_topology().await {
+            warn!(?e, "cluster map refresh failed");
+        }
+    }
+}
@@ -1,3 +1,6 @@
+use app_core::error::{ErrorKind, HasErrorKind};
+use app_logging::LogFilterError;
+use app_net::{ResponseData, SocketError, redact_args};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -10,4 +13,97 @@ pub enum AppError {
 
     #[error("Connection error: {0}")]
     ConnectionError(String),
+
+    #[error("Bad request: {0}")]
+    BadRequest(String),
+
+    #[error("Read-only: {0}")]
+    ReadOnly(String),
+
+    #[error("Network error: {0}")]
+    Net(#[from] SocketError),
+
+    /// A request that never got a response; `args` is its payload with the values
+    /// redacted (see `app_net::redact_args`).
+    #[error("{action} {args}: {source}")]
+    Request {
+        action: String,
+        args: String,
+        #[source]
+        source: SocketError,
+    },
+
+    #[error("Log filter: {0}")]
+    LogFilter(#[from] LogFilterError),
+
+    /// A read-through `Loader` failed; `kind` is the one of its original error.
+    #[error("origin load failed: {message}")]
+    Origin { kind: ErrorKind, message: String },
+
+    /// Non-2xx response from the cluster; `code` is the wire code sent by the master.
+    #[error("{action} failed ({code}): {message}")]
+    Remote {
+        action: String,
+        code: u16,
+        message: String,
+    },
+}
+
+impl AppError {
+    pub fn request(action: &str, payload: &str, source: SocketError) -> Self {
+        AppError::Request {
+            action: action.to_string(),
+            args: redact_args(payload),
+            source,
+        }
+    }
+
+    pub fn remote(action: &str, response: &ResponseData) -> Self {
+        AppError::Remote {
+            action: action.to_string(),
+            code: response.code,
+            message: response.payload.clone(),
+        }
+    }
+}
+
+impl HasErrorKind for AppError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            AppError::Io(_) | AppError::SocketError(_) | AppError::ConnectionError(_) => {
+                ErrorKind::Connection
+            }
+            AppError::BadRequest(_) => ErrorKind::BadRequest,
+            AppError::ReadOnly(_) => ErrorKind::Unavailable,
+            AppError::Net(e) | AppError::Request { source: e, .. } => e.kind(),
+            AppError::LogFilter(e) => e.kind(),
+            AppError::Origin { kind, .. } => *kind,
+            AppError::Remote { code, .. } => ErrorKind::from_wire_code(*code),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use super::*;
+
+    #[test]
+    fn a_failed_request_keeps_its_action_and_key_but_not_its_value() {
+        let timeout = SocketError::Timeout {
+            socket_id: "s1".into(),
+            req_id: "r1".into(),
+        };
+        let err = AppError::request("PUT", "user:1 \"secreto\"", timeout);
+
+        let message = err.to_string();
+        assert!(
+            message.starts_with("PUT user:1 \"<7 bytes>\": "),
+            "{message}"
+        );
+        assert!(!message.contains("secreto"), "{message}");
+        assert_eq!(err.kind(), ErrorKind::Timeout);
+        assert!(err.source().is_some());
+    }
 }
@@ -1,26 +1,46 @@
-use std::{sync::Arc, time::Instant};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
+use app_core::error::{ErrorKind, HasErrorKind};
+use app_net::PutCondition;
 use axum::{
     Json,
     extract::{Path, State},
-    http::StatusCode,
+    http::{HeaderMap, HeaderValue, StatusCode, header},
     response::{IntoResponse, Response},
 };
 use serde::{Deserialize, Serialize};
 use tracing::error;
 
-use crate::{client::CacheClient, errors::AppError};
+use crate::{
+    client::CacheClient,
+    errors::AppError,
+    loader::ReadThrough,
+    metrics::{OperationMetrics, Readiness},
+};
+
+/// Set on the stale reply whose caller should refresh the value.
+const X_CACHE_REFRESH: &str = "x-cache-refresh";
 
 #[derive(Clone)]
 pub struct AppState {
     pub client: Arc<CacheClient>,
+    /// With a loader, a GET that misses fetches the value from the origin and stores it
+    /// (see `ReadThrough`); without one it answers `"value": null`.
+    pub read_through: Option<Arc<ReadThrough>>,
 }
 
+/// Unknown fields are refused so that a body still sending the old unitless `ttl` gets a
+/// 422 instead of an entry that never expires.
 #[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct PutBody {
     value: String,
+    /// Relative TTL in milliseconds.
     #[serde(default)]
-    ttl: Option<u64>,
+    ttl_ms: Option<u64>,
 }
 
 #[derive(Serialize)]
@@ -32,32 +52,106 @@ pub struct PingResponse {
 #[derive(Serialize)]
 pub struct PutResponse {
     key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    namespace: Option<String>,
     elapsed_ms: u128,
 }
 
 #[derive(Serialize)]
 pub struct GetResponse {
     key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    namespace: Option<String>,
     value: Option<String>,
+    /// How long ago the value expired, on a `max-stale` GET that got a stale one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stale_ms: Option<u128>,
+    elapsed_ms: u128,
+}
+
+#[derive(Serialize)]
+pub struct GetOrSetResponse {
+    key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    namespace: Option<String>,
+    value: String,
+    /// `true` when the key was absent and `value` is the default this request stored.
+    stored: bool,
     elapsed_ms: u128,
 }
 
+#[derive(Serialize)]
+pub struct ReadyResponse {
+    readiness: Readiness,
+    write_error_rate: f64,
+}
+
+#[derive(Serialize)]
+pub struct MetricsResponse {
+    readiness: Readiness,
+    write_error_rate: f64,
+    operations: Vec<OperationMetrics>,
+}
+
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         error!("AppError: {self:?}");
-        (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()).into_response()
+        let status =
+            StatusCode::from_u16(self.wire_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        (status, self.to_string()).into_response()
     }
 }
 
+pub async fn ready(State(state): State<AppState>) -> impl IntoResponse {
+    let readiness = state.client.readiness();
+    let status = match readiness {
+        Readiness::Ready => StatusCode::OK,
+        Readiness::ReadOnly => StatusCode::SERVICE_UNAVAILABLE,
+    };
+
+    (
+        status,
+        Json(ReadyResponse {
+            readiness,
+            write_error_rate: state.client.write_error_rate(),
+        }),
+    )
+}
+
+pub async fn client_metrics(State(state): State<AppState>) -> impl IntoResponse {
+    Json(MetricsResponse {
+        readiness: state.client.readiness(),
+        write_error_rate: state.client.write_error_rate(),
+        operations: state.client.metrics(),
+    })
+}
+
+/// Body of `GET`/`PUT /log-filter`.
+#[derive(Serialize, Deserialize)]
+pub struct LogFilterBody {
+    /// Directives in `RUST_LOG` syntax, e.g. `info,cache_client=debug`.
+    pub filter: String,
+}
+
+pub async fn get_log_filter() -> Result<Json<LogFilterBody>, AppError> {
+    let filter = app_logging::global()?.current();
+    Ok(Json(LogFilterBody { filter }))
+}
+
+/// Swaps the tracing filter in place; an invalid filter is rejected and the old one stays.
+pub async fn put_log_filter(
+    Json(body): Json<LogFilterBody>,
+) -> Result<Json<LogFilterBody>, AppError> {
+    let filter = app_logging::global()?.apply(&body.filter)?;
+    Ok(Json(LogFilterBody { filter }))
+}
+
 pub async fn ping(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
     let start = Instant::now();
     let response = state.client.request_raw("PING", "").await?;
 
     if !response.is_success() {
-        return Err(AppError::ConnectionError(format!(
-            "PING failed: {}",
-            response.payload
-        )));
+        return Err(AppError::remote("PING", &response));
     }
 
     let elapsed_ms = start.elapsed().as_millis();
@@ -74,43 +168,316 @@ pub async fn ping(State(state): State<AppState>) -> Result<impl IntoResponse, Ap
 pub async fn put_kv(
     State(state): State<AppState>,
     Path(key): Path<String>,
+    headers: HeaderMap,
     Json(body): Json<PutBody>,
+) -> Result<impl IntoResponse, AppError> {
+    put_scoped(state, None, key, put_condition(&headers)?, body).await
+}
+
+pub async fn put_ns_kv(
+    State(state): State<AppState>,
+    Path((namespace, key)): Path<(String, String)>,
+    headers: HeaderMap,
+    Json(body): Json<PutBody>,
+) -> Result<impl IntoResponse, AppError> {
+    let condition = put_condition(&headers)?;
+    put_scoped(state, Some(namespace), key, condition, body).await
+}
+
+pub async fn get_kv(
+    State(state): State<AppState>,
+    Path(key): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    get_scoped(state, None, key, &headers).await
+}
+
+pub async fn get_ns_kv(
+    State(state): State<AppState>,
+    Path((namespace, key)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    get_scoped(state, Some(namespace), key, &headers).await
+}
+
+/// Body: the same `PutBody` as a PUT, with `value` as the default to store.
+pub async fn get_or_set_kv(
+    State(state): State<AppState>,
+    Path(key): Path<String>,
+    Json(body): Json<PutBody>,
+) -> Result<impl IntoResponse, AppError> {
+    get_or_set_scoped(state, None, key, body).await
+}
+
+pub async fn get_or_set_ns_kv(
+    State(state): State<AppState>,
+    Path((namespace, key)): Path<(String, String)>,
+    Json(body): Json<PutBody>,
+) -> Result<impl IntoResponse, AppError> {
+    get_or_set_scoped(state, Some(namespace), key, body).await
+}
+
+/// With a `condition` (see `put_condition`) the PUT answers `412` when it doesn't hold.
+async fn put_scoped(
+    state: AppState,
+    namespace: Option<String>,
+    key: String,
+    condition: Option<PutCondition>,
+    body: PutBody,
 ) -> Result<impl IntoResponse, AppError> {
     let start = Instant::now();
-    let response = state.client.put(&key, &body.value, body.ttl).await?;
+    let ttl = body.ttl_ms.map(Duration::from_millis);
+    let response = match (condition, &namespace) {
+        (Some(condition), ns) => {
+            state
+                .client
+                .put_if(ns.as_deref(), &key, &body.value, ttl, &condition)
+                .await?
+        }
+        (None, Some(ns)) => state.client.put_in(ns, &key, &body.value, ttl).await?,
+        (None, None) => state.client.put(&key, &body.value, ttl).await?,
+    };
     let elapsed_ms = start.elapsed().as_millis();
 
     if !response.is_success() {
-        return Err(AppError::ConnectionError(format!(
-            "PUT failed: {}",
-            response.payload
-        )));
+        return Err(AppError::remote("PUT", &response));
     }
 
-    Ok((StatusCode::OK, Json(PutResponse { key, elapsed_ms })))
+    Ok((
+        StatusCode::OK,
+        Json(PutResponse {
+            key,
+            namespace,
+            elapsed_ms,
+        }),
+    ))
 }
 
-pub async fn get_kv(
-    State(state): State<AppState>,
-    Path(key): Path<String>,
+/// `200` with the stored value, or `201` when the key was absent and the default went in.
+async fn get_or_set_scoped(
+    state: AppState,
+    namespace: Option<String>,
+    key: String,
+    body: PutBody,
 ) -> Result<impl IntoResponse, AppError> {
     let start = Instant::now();
-    let response = state.client.get(&key).await?;
+    let ttl = body.ttl_ms.map(Duration::from_millis);
+    let (value, stored) = state
+        .client
+        .get_or_set(namespace.as_deref(), &key, &body.value, ttl)
+        .await?;
     let elapsed_ms = start.elapsed().as_millis();
 
+    let status = if stored {
+        StatusCode::CREATED
+    } else {
+        StatusCode::OK
+    };
+    Ok((
+        status,
+        Json(GetOrSetResponse {
+            key,
+            namespace,
+            value,
+            stored,
+            elapsed_ms,
+        }),
+    ))
+}
+
+/// Every GET is a conditional one so the reply can carry the key's version as an `ETag`;
+/// an `If-None-Match` with that tag gets a bodyless `304` while the key is unchanged. A
+/// `Cache-Control: max-stale` one goes to `get_stale_scoped` instead.
+async fn get_scoped(
+    state: AppState,
+    namespace: Option<String>,
+    key: String,
+    headers: &HeaderMap,
+) -> Result<Response, AppError> {
+    if let Some(max_stale) = max_stale(headers) {
+        return get_stale_scoped(state, namespace, key, max_stale).await;
+    }
+    let start = Instant::now();
+    let response = state
+        .client
+        .get_if_not_version(namespace.as_deref(), &key, if_none_match(headers))
+        .await?;
+    let elapsed_ms = start.elapsed().as_millis();
+
+    let mut etag = HeaderMap::new();
+    if let Some(version) = response.version {
+        let tag = HeaderValue::from_str(&format!("\"{version}\""))
+            .expect("a quoted number is a valid header value");
+        etag.insert(header::ETAG, tag);
+    }
+    if response.is_not_modified() {
+        return Ok((StatusCode::NOT_MODIFIED, etag).into_response());
+    }
     if !response.is_success() {
-        return Err(AppError::ConnectionError(format!(
-            "GET failed: {}",
-            response.payload
-        )));
+        return Err(AppError::remote("GET", &response));
+    }
+
+    // a miss goes to the origin, if there is one; what it returns has no version yet
+    if let Some(read_through) = &state.read_through
+        && (response.payload.is_empty() || response.is_empty_value())
+    {
+        let value = read_through.load(namespace.as_deref(), &key).await?;
+        let elapsed_ms = start.elapsed().as_millis();
+        return Ok((
+            StatusCode::OK,
+            Json(GetResponse {
+                key,
+                namespace,
+                value,
+                stale_ms: None,
+                elapsed_ms,
+            }),
+        )
+            .into_response());
     }
 
     Ok((
         StatusCode::OK,
+        etag,
         Json(GetResponse {
             key,
+            namespace,
             value: Some(response.payload),
+            stale_ms: None,
             elapsed_ms,
         }),
-    ))
+    )
+        .into_response())
+}
+
+/// The condition a PUT's headers ask for: `If-Match: "<version>"` (an `ETag` from a GET)
+/// is a compare-and-set on that version and `If-None-Match: *` a create-only write. Only
+/// strong tags can match (RFC 9110), so a weak or foreign `If-Match` fails right away
+/// with `412`.
+fn put_condition(headers: &HeaderMap) -> Result<Option<PutCondition>, AppError> {
+    let header = |name| {
+        headers
+            .get(name)
+            .map(|v| v.to_str().map(str::trim).unwrap_or_default())
+    };
+    match (header(header::IF_MATCH), header(header::IF_NONE_MATCH)) {
+        (None, None) => Ok(None),
+        (Some(_), Some(_)) => Err(AppError::BadRequest(
+            "If-Match and If-None-Match can't be combined".into(),
+        )),
+        (None, Some("*")) => Ok(Some(PutCondition::Absent)),
+        (None, Some(_)) => Err(AppError::BadRequest(
+            "If-None-Match on a PUT only takes *".into(),
+        )),
+        (Some("*"), None) => Err(AppError::BadRequest("If-Match: * is not supported".into())),
+        (Some(tag), None) => tag
+            .strip_prefix('"')
+            .and_then(|t| t.strip_suffix('"'))
+            .and_then(|t| t.parse().ok())
+            .map(|version| Some(PutCondition::Version(version)))
+            .ok_or_else(|| AppError::Remote {
+                action: "PUT".into(),
+                code: ErrorKind::PreconditionFailed.wire_code(),
+                message: format!("If-Match {tag} doesn't match"),
+            }),
+    }
+}
+
+/// GET that also takes a value that expired less than `max_stale` ago. A stale reply
+/// says so with `Warning: 110` and `stale_ms`, and `X-Cache-Refresh: 1` tells the one
+/// caller picked to revalidate it that it should write a fresh value.
+async fn get_stale_scoped(
+    state: AppState,
+    namespace: Option<String>,
+    key: String,
+    max_stale: Duration,
+) -> Result<Response, AppError> {
+    let start = Instant::now();
+    let read = state
+        .client
+        .get_stale(namespace.as_deref(), &key, max_stale)
+        .await?;
+    let elapsed_ms = start.elapsed().as_millis();
+
+    let mut stale = HeaderMap::new();
+    if let Some(read) = &read {
+        if read.stale_for.is_some() {
+            stale.insert(
+                header::WARNING,
+                HeaderValue::from_static("110 - \"Response is Stale\""),
+            );
+        }
+        if read.refresh {
+            stale.insert(X_CACHE_REFRESH, HeaderValue::from_static("1"));
+        }
+    }
+
+    Ok((
+        StatusCode::OK,
+        stale,
+        Json(GetResponse {
+            key,
+            namespace,
+            stale_ms: read
+                .as_ref()
+                .and_then(|read| read.stale_for)
+                .map(|d| d.as_millis()),
+            value: read.map(|read| read.value),
+            elapsed_ms,
+        }),
+    )
+        .into_response())
+}
+
+/// The bound of a `Cache-Control: max-stale[=<seconds>]`, if non-zero; without seconds any value the
+/// nodes still keep will do.
+fn max_stale(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get_all(header::CACHE_CONTROL)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(str::trim)
+        .find_map(|directive| {
+            let rest = directive.strip_prefix("max-stale")?;
+            match rest.strip_prefix('=') {
+                Some(secs) => secs.trim().parse().ok().map(Duration::from_secs),
+                None if rest.is_empty() => Some(Duration::MAX),
+                None => None,
+            }
+        })
+        .filter(|max| !max.is_zero())
+}
+
+/// The version in an `If-None-Match: "<version>"` (weak tags too); `0`, which never
+/// matches a stored key, when there is none or it isn't one of ours.
+fn if_none_match(headers: &HeaderMap) -> u64 {
+    headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|tag| {
+            let tag = tag.trim();
+            let tag = tag.strip_prefix("W/").unwrap_or(tag);
+            tag.strip_prefix('"')?.strip_suffix('"')?.parse().ok()
+        })
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn put_bodies_with_the_old_ttl_field_are_refused() {
+        let body: PutBody = serde_json::from_str(r#"{"value": "v", "ttl_ms": 1500}"#).unwrap();
+        assert_eq!(body.ttl_ms, Some(1500));
+
+        let old = serde_json::from_str::<PutBody>(r#"{"value": "v", "ttl": 1500}"#);
+        assert!(
+            old.err()
+                .unwrap()
+                .to_string()
+                .contains("unknown field `ttl`")
+        );
+    }
 }
@@ -0,0 +1,341 @@
+use std::{collections::HashMap, future::Future, pin::Pin, sync::Arc, time::Duration};
+
+use app_core::error::{ErrorKind, HasErrorKind};
+use parking_lot::Mutex;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    sync::OnceCell,
+};
+
+use crate::{client::CacheClient, errors::AppError};
+
+/// What a `Loader` hands back: the origin's value, or `None` when it doesn't have one.
+pub type LoadFuture = Pin<Box<dyn Future<Output = Result<Option<String>, AppError>> + Send>>;
+
+/// Fetches a value from the origin (database, upstream service...) on a cache miss.
+/// It gets the physical key, namespace included (`{namespace}:{key}`).
+pub trait Loader: Send + Sync {
+    fn load(&self, key: &str) -> LoadFuture;
+}
+
+impl<F, Fut> Loader for F
+where
+    F: Fn(String) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<Option<String>, AppError>> + Send + 'static,
+{
+    fn load(&self, key: &str) -> LoadFuture {
+        Box::pin(self(key.to_string()))
+    }
+}
+
+/// A `Loader` that asks an HTTP origin: `GET {base}/{key}`, with the key percent-encoded.
+/// A 200 is the value (its body), a 404 is "not there", anything else an error. Plain
+/// `http://` only, one HTTP/1.0 request per load, so the body is never chunked.
+#[derive(Clone)]
+pub struct HttpOrigin {
+    authority: String,
+    base_path: String,
+    timeout: Duration,
+}
+
+impl HttpOrigin {
+    /// `url` is `http://host[:port][/path]`.
+    pub fn parse(url: &str, timeout: Duration) -> Result<Self, AppError> {
+        let invalid = || AppError::BadRequest(format!("invalid origin url: {url}"));
+        let rest = url.trim().strip_prefix("http://").ok_or_else(invalid)?;
+        let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+        if authority.is_empty() {
+            return Err(invalid());
+        }
+        let authority = if authority.contains(':') {
+            authority.to_string()
+        } else {
+            format!("{authority}:80")
+        };
+        Ok(Self {
+            authority,
+            base_path: path.trim_end_matches('/').to_string(),
+            timeout,
+        })
+    }
+
+    async fn fetch(&self, key: &str) -> Result<Option<String>, AppError> {
+        let mut stream = TcpStream::connect(&self.authority).await?;
+        let request = format!(
+            "GET {}/{} HTTP/1.0\r\nHost: {}\r\n\r\n",
+            self.base_path,
+            percent_encode(key),
+            self.authority
+        );
+        stream.write_all(request.as_bytes()).await?;
+        let mut raw = Vec::new();
+        stream.read_to_end(&mut raw).await?;
+
+        let bad = || AppError::ConnectionError(format!("bad response from {}", self.authority));
+        let split = raw
+            .windows(4)
+            .position(|w| w == b"\r\n\r\n")
+            .ok_or_else(bad)?;
+        let status: u16 = std::str::from_utf8(&raw[..split])
+            .ok()
+            .and_then(|head| head.split_whitespace().nth(1)?.parse().ok())
+            .ok_or_else(bad)?;
+        match status {
+            200 => String::from_utf8(raw[split + 4..].to_vec())
+                .map(Some)
+                .map_err(|_| bad()),
+            404 => Ok(None),
+            _ => Err(AppError::ConnectionError(format!(
+                "origin {} answered {status}",
+                self.authority
+            ))),
+        }
+    }
+}
+
+impl Loader for HttpOrigin {
+    fn load(&self, key: &str) -> LoadFuture {
+        let origin = self.clone();
+        let key = key.to_string();
+        Box::pin(async move {
+            tokio::time::timeout(origin.timeout, origin.fetch(&key))
+                .await
+                .map_err(|_| {
+                    AppError::ConnectionError(format!("origin {} timed out", origin.authority))
+                })?
+        })
+    }
+}
+
+/// Everything but RFC 3986's unreserved characters, as `%XX`.
+fn percent_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~') {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
+}
+
+/// How a shared load ended, for every caller waiting on it.
+type Outcome<T> = Result<T, (ErrorKind, String)>;
+
+/// One run of a load per key at a time: callers arriving while it's in flight wait for
+/// it and get the same outcome, errors included, instead of starting their own.
+pub struct SingleFlight<T> {
+    flights: Mutex<HashMap<String, Arc<OnceCell<Outcome<T>>>>>,
+}
+
+impl<T: Clone> SingleFlight<T> {
+    pub fn new() -> Self {
+        Self {
+            flights: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Runs `load` unless a load of `key` is already in flight, and waits for whichever
+    /// runs. A failure reaches every waiter as `AppError::Origin` with the original kind.
+    pub async fn run<F, Fut>(&self, key: &str, load: F) -> Result<T, AppError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, AppError>>,
+    {
+        let flight = self
+            .flights
+            .lock()
+            .entry(key.to_string())
+            .or_default()
+            .clone();
+        let outcome = flight
+            .get_or_init(|| async { load().await.map_err(|e| (e.kind(), e.to_string())) })
+            .await
+            .clone();
+
+        // the next miss after this one starts a new load
+        let mut flights = self.flights.lock();
+        if flights
+            .get(key)
+            .is_some_and(|current| Arc::ptr_eq(current, &flight))
+        {
+            flights.remove(key);
+        }
+        drop(flights);
+
+        outcome.map_err(|(kind, message)| AppError::Origin { kind, message })
+    }
+
+    /// Keys with a load in flight.
+    pub fn len(&self) -> usize {
+        self.flights.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Clone> Default for SingleFlight<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Read-through on top of a `CacheClient`: a GET that misses asks `loader` for the value,
+/// stores it with `ttl` and returns it. Concurrent misses of the same key share one call
+/// to the origin.
+pub struct ReadThrough {
+    client: Arc<CacheClient>,
+    loader: Arc<dyn Loader>,
+    ttl: Duration,
+    flights: SingleFlight<Option<String>>,
+}
+
+impl ReadThrough {
+    pub fn new(client: Arc<CacheClient>, loader: Arc<dyn Loader>, ttl: Duration) -> Self {
+        Self {
+            client,
+            loader,
+            ttl,
+            flights: SingleFlight::new(),
+        }
+    }
+
+    /// The value of `key` in `namespace` (or the configured default), loaded from the
+    /// origin on a miss. `None` when neither the cache nor the origin have it.
+    pub async fn get(
+        &self,
+        namespace: Option<&str>,
+        key: &str,
+    ) -> Result<Option<String>, AppError> {
+        let value = match namespace {
+            Some(ns) => self.client.get_in(ns, key).await?,
+            None => self.client.get(key).await?,
+        };
+        if !value.is_success() {
+            return Err(AppError::remote("GET", &value));
+        }
+        if !value.payload.is_empty() && !value.is_empty_value() {
+            return Ok(Some(value.payload));
+        }
+        self.load(namespace, key).await
+    }
+
+    /// The miss half of `get`, for a caller that already read the key and found nothing:
+    /// asks the origin (once for all the concurrent callers) and stores what it returns.
+    pub async fn load(
+        &self,
+        namespace: Option<&str>,
+        key: &str,
+    ) -> Result<Option<String>, AppError> {
+        let key = self.client.scoped_key(namespace, key)?;
+        self.flights
+            .run(&key, || async {
+                let Some(value) = self.loader.load(&key).await? else {
+                    return Ok(None);
+                };
+                self.client.put_checked(&key, &value, self.ttl).await?;
+                Ok(Some(value))
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn concurrent_misses_of_a_key_share_one_load() {
+        let flights = Arc::new(SingleFlight::new());
+        let calls = Arc::new(AtomicUsize::new(0));
+        let (release, gate) = tokio::sync::watch::channel(false);
+
+        let waiters: Vec<_> = (0..8)
+            .map(|_| {
+                let (flights, calls, mut gate) = (flights.clone(), calls.clone(), gate.clone());
+                tokio::spawn(async move {
+                    flights
+                        .run("k", || async move {
+                            calls.fetch_add(1, Ordering::SeqCst);
+                            gate.wait_for(|open| *open).await.unwrap();
+                            Ok("origin".to_string())
+                        })
+                        .await
+                })
+            })
+            .collect();
+        // the eight are waiting on the first one by then
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        release.send(true).unwrap();
+
+        for waiter in waiters {
+            assert_eq!(waiter.await.unwrap().unwrap(), "origin");
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(flights.is_empty());
+
+        // done: the next miss loads again
+        let again = flights.run("k", || async { Ok("fresh".to_string()) }).await;
+        assert_eq!(again.unwrap(), "fresh");
+    }
+
+    #[tokio::test]
+    async fn a_failed_load_keeps_its_kind_and_the_next_miss_tries_again() {
+        let flights: SingleFlight<String> = SingleFlight::new();
+        let err = flights
+            .run("k", || async {
+                Err(AppError::BadRequest("no such row".into()))
+            })
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::BadRequest);
+        assert!(err.to_string().contains("no such row"), "{err}");
+
+        let again = flights.run("k", || async { Ok("back".to_string()) }).await;
+        assert_eq!(again.unwrap(), "back");
+    }
+
+    #[tokio::test]
+    async fn the_http_origin_reads_values_and_misses_from_the_status() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let mut paths = Vec::new();
+            for response in [
+                "HTTP/1.0 200 OK\r\n\r\nfrom the db",
+                "HTTP/1.0 404 Not Found\r\n\r\n",
+                "HTTP/1.0 503 Service Unavailable\r\n\r\n",
+            ] {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0; 1024];
+                let n = stream.read(&mut buf).await.unwrap();
+                let head = String::from_utf8_lossy(&buf[..n]).into_owned();
+                paths.push(head.split_whitespace().nth(1).unwrap().to_string());
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+            paths
+        });
+
+        let origin =
+            HttpOrigin::parse(&format!("http://{addr}/rows/"), Duration::from_secs(5)).unwrap();
+        assert_eq!(
+            origin.load("app:user 1").await.unwrap().as_deref(),
+            Some("from the db")
+        );
+        assert_eq!(origin.load("gone").await.unwrap(), None);
+        assert!(origin.load("down").await.is_err());
+        assert_eq!(
+            server.await.unwrap(),
+            ["/rows/app%3Auser%201", "/rows/gone", "/rows/down"]
+        );
+
+        assert!(HttpOrigin::parse("https://db", Duration::from_secs(1)).is_err());
+    }
+}
@@ -1,23 +1,33 @@
-use std::net::SocketAddr;
+use std::{net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
 
+use app_net::{DEFAULT_MAX_PAYLOAD, FRAME_OVERHEAD};
 use axum::{
     Router,
-    routing::{get, put},
+    extract::DefaultBodyLimit,
+    routing::{get, post, put},
 };
 use dotenvy::{dotenv, from_filename};
 use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
 use tracing::info;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use crate::{
     client::{CacheClient, CacheClientConfig},
     errors::AppError,
-    http::{AppState, get_kv, ping, put_kv},
+    http::{
+        AppState, client_metrics, get_kv, get_log_filter, get_ns_kv, get_or_set_kv,
+        get_or_set_ns_kv, ping, put_kv, put_log_filter, put_ns_kv, ready,
+    },
+    loader::{HttpOrigin, ReadThrough},
 };
 
 pub mod client;
 pub mod errors;
 pub mod http;
+pub mod loader;
+pub mod metrics;
+pub mod sticky;
+pub mod topology;
 
 fn load_env_for_workspace() {
     let _ = from_filename(concat!(env!("CARGO_MANIFEST_DIR"), "/.env"));
@@ -30,17 +40,65 @@ async fn main() -> Result<(), AppError> {
 
     load_env_for_workspace();
 
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    let log = app_logging::init_logging();
+
+    // SIGHUP re-reads the .env files and applies their RUST_LOG
+    let log_reload = CancellationToken::new();
+    tokio::spawn(log.reload_on_sighup(
+        vec![
+            PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/.env")),
+            PathBuf::from(".env"),
+        ],
+        log_reload.clone(),
+    ));
 
     let cfg = CacheClientConfig::from_env()?;
     let client = CacheClient::connect_with(cfg).await?;
 
+    // MAX_PAYLOAD_BYTES: bodies past the cluster's payload limit get a 413 here, without
+    // reaching the master (which would answer 413 as well)
+    let max_payload = std::env::var("MAX_PAYLOAD_BYTES")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|bytes| *bytes > 0)
+        .unwrap_or(DEFAULT_MAX_PAYLOAD);
+
+    // READ_THROUGH_URL: a GET that misses asks this HTTP origin for the key and stores
+    // what it returns for READ_THROUGH_TTL_SECS. Unset means misses answer `null`.
+    let read_through = match std::env::var("READ_THROUGH_URL") {
+        Ok(url) => {
+            let ttl = std::env::var("READ_THROUGH_TTL_SECS")
+                .ok()
+                .and_then(|s| s.parse::<u64>().ok())
+                .filter(|secs| *secs > 0)
+                .unwrap_or(300);
+            let origin = HttpOrigin::parse(&url, Duration::from_secs(5))?;
+            info!("Read-through from {url} (ttl {ttl}s)");
+            Some(Arc::new(ReadThrough::new(
+                client.clone(),
+                Arc::new(origin),
+                Duration::from_secs(ttl),
+            )))
+        }
+        Err(_) => None,
+    };
+
     let app = Router::new()
         .route("/ping", get(ping))
+        .route("/ready", get(ready))
+        .route("/metrics", get(client_metrics))
         .route("/kv/{key}", put(put_kv).get(get_kv))
-        .with_state(AppState { client });
+        .route("/ns/{namespace}/kv/{key}", put(put_ns_kv).get(get_ns_kv))
+        .route("/kv/{key}/get-or-set", post(get_or_set_kv))
+        .route(
+            "/ns/{namespace}/kv/{key}/get-or-set",
+            post(get_or_set_ns_kv),
+        )
+        .layer(DefaultBodyLimit::max(max_payload + FRAME_OVERHEAD))
+        .with_state(AppState {
+            client: client.clone(),
+            read_through,
+        });
 
     let port: u16 = std::env::var("PORT")
         .ok()
@@ -49,10 +107,44 @@ async fn main() -> Result<(), AppError> {
 
     let addr = SocketAddr::from(([0, 0, 0, 0], port));
 
+    let shutdown = CancellationToken::new();
+
+    // ADMIN_PORT: operator endpoints live on their own listener, bound to loopback and
+    // never mounted on the public gateway. Unset means no admin listener at all.
+    if let Some(admin_port) = std::env::var("ADMIN_PORT")
+        .ok()
+        .and_then(|s| s.parse::<u16>().ok())
+    {
+        let admin_addr = SocketAddr::from(([127, 0, 0, 1], admin_port));
+        let admin = Router::new().route("/log-filter", get(get_log_filter).put(put_log_filter));
+        let admin_listener = TcpListener::bind(admin_addr).await?;
+        info!("Admin server listening on http://{admin_addr}");
+
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move {
+            if let Err(e) = axum::serve(admin_listener, admin)
+                .with_graceful_shutdown(shutdown.cancelled_owned())
+                .await
+            {
+                tracing::error!("Admin server stopped: {e}");
+            }
+        });
+    }
+
     info!("HTTP server listening on http://{addr}");
 
     let listener = TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+            info!("Shutting down...");
+        })
+        .await?;
+    shutdown.cancel();
+
+    // In-flight HTTP requests are drained; now tear down the master connection.
+    client.break_connection();
+    log_reload.cancel();
 
     Ok(())
 }
@@ -0,0 +1,221 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+
+pub use app_core::metrics::{LATENCY_BUCKETS_MS, LatencyHistogram};
+
+#[derive(Clone, Debug, Serialize)]
+pub struct BucketSnapshot {
+    pub le_ms: u64,
+    /// Cumulative count of observations `<= le_ms`.
+    pub count: u64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct OperationMetrics {
+    pub action: String,
+    pub target: String,
+    pub count: u64,
+    pub errors: u64,
+    pub sum_ms: f64,
+    pub p50_ms: Option<u64>,
+    pub p95_ms: Option<u64>,
+    pub p99_ms: Option<u64>,
+    pub buckets: Vec<BucketSnapshot>,
+}
+
+type OperationKey = (Arc<str>, Arc<str>);
+
+/// Per-operation histograms keyed by (action, target master address).
+#[derive(Default)]
+pub struct ClientMetrics {
+    operations: RwLock<HashMap<OperationKey, Arc<LatencyHistogram>>>,
+}
+
+impl ClientMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, action: &str, target: &str, elapsed: Duration, success: bool) {
+        self.histogram(action, target).record(elapsed, success);
+    }
+
+    pub fn histogram(&self, action: &str, target: &str) -> Arc<LatencyHistogram> {
+        let key: OperationKey = (Arc::from(action), Arc::from(target));
+
+        if let Some(h) = self.operations.read().get(&key) {
+            return h.clone();
+        }
+
+        self.operations.write().entry(key).or_default().clone()
+    }
+
+    pub fn snapshot(&self) -> Vec<OperationMetrics> {
+        let mut out: Vec<OperationMetrics> = self
+            .operations
+            .read()
+            .iter()
+            .map(|((action, target), h)| OperationMetrics {
+                action: action.to_string(),
+                target: target.to_string(),
+                count: h.count(),
+                errors: h.errors(),
+                sum_ms: h.sum_micros() as f64 / 1_000.0,
+                p50_ms: h.quantile_ms(0.50),
+                p95_ms: h.quantile_ms(0.95),
+                p99_ms: h.quantile_ms(0.99),
+                buckets: h
+                    .cumulative_counts()
+                    .into_iter()
+                    .map(|(le_ms, count)| BucketSnapshot { le_ms, count })
+                    .collect(),
+            })
+            .collect();
+
+        out.sort_by(|a, b| (&a.action, &a.target).cmp(&(&b.action, &b.target)));
+        out
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Readiness {
+    Ready,
+    /// Write error rate is over budget: writes are rejected locally, reads keep flowing.
+    ReadOnly,
+}
+
+#[derive(Clone, Debug)]
+pub struct ErrorBudgetConfig {
+    /// Maximum tolerated write error rate in `[0, 1]` before degrading to read-only.
+    pub max_write_error_rate: f64,
+    /// Sliding window the error rate is computed over.
+    pub window: Duration,
+    /// Minimum number of writes in the window before the budget is enforced.
+    pub min_samples: usize,
+}
+
+impl Default for ErrorBudgetConfig {
+    fn default() -> Self {
+        Self {
+            max_write_error_rate: 0.5,
+            window: Duration::from_secs(30),
+            min_samples: 20,
+        }
+    }
+}
+
+/// Sliding-window write error budget. Once the error rate exceeds the budget the client
+/// reports `ReadOnly` until enough failed writes age out of the window.
+pub struct ErrorBudget {
+    cfg: ErrorBudgetConfig,
+    samples: Mutex<VecDeque<(Instant, bool)>>,
+}
+
+impl ErrorBudget {
+    pub fn new(cfg: ErrorBudgetConfig) -> Self {
+        Self {
+            cfg,
+            samples: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn record_write(&self, success: bool) {
+        self.record_write_at(Instant::now(), success);
+    }
+
+    pub fn write_error_rate(&self) -> f64 {
+        self.error_rate_at(Instant::now()).0
+    }
+
+    pub fn readiness(&self) -> Readiness {
+        self.readiness_at(Instant::now())
+    }
+
+    fn record_write_at(&self, now: Instant, success: bool) {
+        let mut samples = self.samples.lock();
+        Self::prune(&mut samples, now, self.cfg.window);
+        samples.push_back((now, success));
+    }
+
+    fn error_rate_at(&self, now: Instant) -> (f64, usize) {
+        let mut samples = self.samples.lock();
+        Self::prune(&mut samples, now, self.cfg.window);
+
+        if samples.is_empty() {
+            return (0.0, 0);
+        }
+
+        let failed = samples.iter().filter(|(_, ok)| !ok).count();
+        (failed as f64 / samples.len() as f64, samples.len())
+    }
+
+    fn readiness_at(&self, now: Instant) -> Readiness {
+        let (rate, samples) = self.error_rate_at(now);
+
+        if samples >= self.cfg.min_samples && rate > self.cfg.max_write_error_rate {
+            Readiness::ReadOnly
+        } else {
+            Readiness::Ready
+        }
+    }
+
+    fn prune(samples: &mut VecDeque<(Instant, bool)>, now: Instant, window: Duration) {
+        while let Some((at, _)) = samples.front() {
+            if now.duration_since(*at) > window {
+                samples.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metrics_are_keyed_by_action_and_target() {
+        let m = ClientMetrics::new();
+        m.record("GET", "a:1", Duration::from_millis(3), true);
+        m.record("GET", "a:1", Duration::from_millis(3), true);
+        m.record("GET", "b:1", Duration::from_millis(3), true);
+        m.record("PUT", "a:1", Duration::from_millis(3), false);
+
+        let snap = m.snapshot();
+        assert_eq!(snap.len(), 3);
+        assert_eq!((snap[0].action.as_str(), snap[0].count), ("GET", 2));
+        assert_eq!(snap[2].errors, 1);
+    }
+
+    #[test]
+    fn error_budget_degrades_and_recovers() {
+        let budget = ErrorBudget::new(ErrorBudgetConfig {
+            max_write_error_rate: 0.5,
+            window: Duration::from_secs(10),
+            min_samples: 4,
+        });
+        let t0 = Instant::now();
+
+        for _ in 0..3 {
+            budget.record_write_at(t0, false);
+        }
+        // Below the minimum sample count
+        assert_eq!(budget.readiness_at(t0), Readiness::Ready);
+
+        budget.record_write_at(t0, true);
+        assert_eq!(budget.readiness_at(t0), Readiness::ReadOnly);
+
+        // Samples age out of the window
+        let later = t0 + Duration::from_secs(11);
+        assert_eq!(budget.readiness_at(later), Readiness::Ready);
+        assert_eq!(budget.error_rate_at(later), (0.0, 0));
+    }
+}
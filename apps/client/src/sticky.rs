@@ -0,0 +1,92 @@
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+/// Pins kept before expired ones are swept on the next write.
+const SWEEP_AT: usize = 4096;
+
+/// Read-your-writes pins: after a PUT, the node that acknowledged it serves this client's
+/// GETs of that key for `window`, while the write may still be on its way to the rest of
+/// the shard.
+pub struct StickyReads {
+    window: Duration,
+    pins: Mutex<HashMap<String, (Arc<str>, Instant)>>,
+}
+
+impl StickyReads {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pins: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// `node_id` acknowledged a write of `key` just now.
+    pub fn pin(&self, key: &str, node_id: &str) {
+        self.pin_at(key, node_id, Instant::now());
+    }
+
+    /// The node to read `key` from, if a write of it is still within the window.
+    pub fn node_for(&self, key: &str) -> Option<Arc<str>> {
+        self.node_for_at(key, Instant::now())
+    }
+
+    pub fn len(&self) -> usize {
+        self.pins.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn pin_at(&self, key: &str, node_id: &str, now: Instant) {
+        let mut pins = self.pins.lock();
+        if pins.len() >= SWEEP_AT {
+            pins.retain(|_, (_, until)| *until > now);
+        }
+        pins.insert(key.to_string(), (Arc::from(node_id), now + self.window));
+    }
+
+    fn node_for_at(&self, key: &str, now: Instant) -> Option<Arc<str>> {
+        let mut pins = self.pins.lock();
+        match pins.get(key) {
+            Some((node_id, until)) if *until > now => Some(node_id.clone()),
+            Some(_) => {
+                pins.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_pin_lasts_for_the_window_and_the_last_write_wins() {
+        let sticky = StickyReads::new(Duration::from_secs(2));
+        let start = Instant::now();
+
+        sticky.pin_at("k", "n1", start);
+        sticky.pin_at("k", "n2", start + Duration::from_secs(1));
+        assert_eq!(
+            sticky
+                .node_for_at("k", start + Duration::from_secs(2))
+                .as_deref(),
+            Some("n2")
+        );
+        assert_eq!(sticky.node_for_at("other", start), None);
+
+        assert_eq!(
+            sticky.node_for_at("k", start + Duration::from_secs(3)),
+            None
+        );
+        assert!(sticky.is_empty());
+    }
+}
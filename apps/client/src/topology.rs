@@ -0,0 +1,168 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use app_net::{ClusterMap, MapUpdate, TopologyEvent};
+use parking_lot::Mutex;
+use tokio::sync::Notify;
+use tracing::debug;
+
+/// `TOPOLOGY` events kept while no map from the current master has arrived yet.
+const MAX_EARLY_EVENTS: usize = 1024;
+
+/// The client's view of the cluster: the last `CLUSTER-MAP` from the connected master,
+/// kept current with the `EVT TOPOLOGY` it pushes after `SUBSCRIBE "TOPOLOGY"`.
+///
+/// Epochs are numbered per master, so every new connection starts a new generation:
+/// events from an older connection are ignored, and the ones arriving before the new
+/// master's map are held and replayed on top of it.
+#[derive(Default)]
+pub struct ClusterTopology {
+    /// Last generation handed to a connection.
+    issued: AtomicU64,
+    /// Generation of the installed connection; events from any other are dropped.
+    generation: AtomicU64,
+    state: Mutex<State>,
+    refresh: Notify,
+}
+
+#[derive(Default)]
+struct State {
+    map: Option<ClusterMap>,
+    /// `map` came from the current generation's master.
+    current: bool,
+    early: Vec<TopologyEvent>,
+}
+
+impl ClusterTopology {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Last known map, possibly from the previous master while a new one is fetched.
+    pub fn map(&self) -> Option<ClusterMap> {
+        self.state.lock().map.clone()
+    }
+
+    /// Generation for a connection being opened; its events count once it's `activate`d.
+    pub fn new_generation(&self) -> u64 {
+        self.issued.fetch_add(1, Ordering::AcqRel) + 1
+    }
+
+    /// The connection with `generation` became the current one: its master numbers
+    /// epochs on its own, so ask for its map.
+    pub fn activate(&self, generation: u64) {
+        let mut state = self.state.lock();
+        state.current = false;
+        state.early.clear();
+        self.generation.store(generation, Ordering::Release);
+        drop(state);
+        self.request_refresh();
+    }
+
+    /// Install a map fetched from the current master and replay the events that raced
+    /// ahead of it.
+    pub fn install(&self, mut map: ClusterMap) {
+        let mut state = self.state.lock();
+        let mut gap = false;
+        for event in state.early.drain(..) {
+            gap |= map.apply(&event) == MapUpdate::Gap;
+        }
+        state.map = Some(map);
+        state.current = true;
+        drop(state);
+
+        if gap {
+            self.request_refresh();
+        }
+    }
+
+    /// Apply a pushed event; a gap in the epochs asks for a full refresh.
+    pub fn on_event(&self, generation: u64, event: TopologyEvent) {
+        if generation != self.generation.load(Ordering::Acquire) {
+            return;
+        }
+
+        let mut state = self.state.lock();
+        if !state.current {
+            if state.early.len() >= MAX_EARLY_EVENTS {
+                // the map on its way is the fallback; a gap shows up after installing it
+                state.early.clear();
+            }
+            state.early.push(event);
+            return;
+        }
+
+        let Some(map) = state.map.as_mut() else {
+            return;
+        };
+        if map.apply(&event) == MapUpdate::Gap {
+            debug!(
+                map_epoch = map.epoch,
+                event_epoch = event.epoch,
+                "topology gap"
+            );
+            drop(state);
+            self.request_refresh();
+        }
+    }
+
+    pub fn request_refresh(&self) {
+        self.refresh.notify_one();
+    }
+
+    /// Resolves once someone asked for a refresh (a stored request counts).
+    pub async fn refresh_requested(&self) {
+        self.refresh.notified().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use app_net::TopologyChange;
+
+    use super::*;
+
+    fn joined(epoch: u64, node_id: &str) -> TopologyEvent {
+        TopologyEvent {
+            epoch,
+            change: TopologyChange::NodeJoined {
+                node_id: node_id.into(),
+                shard_id: "a".into(),
+            },
+        }
+    }
+
+    #[test]
+    fn events_racing_ahead_of_the_map_are_replayed_on_it() {
+        let topology = ClusterTopology::new();
+        let generation = topology.new_generation();
+        topology.activate(generation);
+
+        topology.on_event(generation, joined(3, "c"));
+        assert!(topology.map().is_none());
+
+        topology.install(ClusterMap::new(2).with_shard("a", ["a", "b"]));
+        let map = topology.map().unwrap();
+        assert_eq!((map.epoch, map.node_count()), (3, 3));
+
+        topology.on_event(generation, joined(4, "d"));
+        assert_eq!(topology.map().unwrap().epoch, 4);
+    }
+
+    #[test]
+    fn events_from_an_older_connection_are_ignored() {
+        let topology = ClusterTopology::new();
+        let old = topology.new_generation();
+        topology.activate(old);
+        topology.install(ClusterMap::new(5).with_shard("a", ["a"]));
+
+        let new = topology.new_generation();
+        topology.activate(new);
+        topology.install(ClusterMap::new(1).with_shard("a", ["a"]));
+        topology.on_event(old, joined(2, "x"));
+        topology.on_event(new, joined(2, "y"));
+
+        let map = topology.map().unwrap();
+        assert_eq!(map.shard_of("x"), None);
+        assert_eq!(map.shard_of("y"), Some("a"));
+    }
+}
@@ -0,0 +1,460 @@
+//! Clusters armados por forma (masters, shards, réplicas por shard) en vez de nodo por
+//! nodo, con las configuraciones de cada instancia generadas y manijas para tirar abajo y
+//! volver a levantar masters y nodos sueltos. Todo corre dentro del runtime del test,
+//! sobre puertos efímeros, como `TestCluster`.
+//!
+//! ```ignore
+//! let mut cluster = ClusterBuilder::new()
+//!     .masters(1)
+//!     .shards(2)
+//!     .replicas_per_shard(2)
+//!     .start()
+//!     .await;
+//! cluster.kill_node(0).await;
+//! cluster.restart_node(0).await;
+//! cluster.shutdown().await;
+//! ```
+
+use std::{net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
+
+use app_core::{
+    id::new_sortable_id,
+    supervisor::{ShutdownReport, Supervisor},
+};
+use cache_master::{
+    infrastructure::{di::config::MasterConfig, peers::PeerConfig},
+    server::MasterHandle,
+};
+use cache_node::server::{NodeHandle, NodeOptions, ReplicationListener, RequestLimits};
+use tokio::net::TcpListener;
+
+use crate::{DEFAULT_TIMEOUT, NodeRole, SHUTDOWN_GRACE, TestClient};
+
+/// Cuánto se espera a que los nodos se reconecten a un master que volvió: el backoff de
+/// reconexión de los nodos llega a 10 s.
+pub const REJOIN_TIMEOUT: Duration = Duration::from_secs(20);
+
+#[derive(Clone)]
+pub struct ClusterBuilder {
+    masters: usize,
+    shards: usize,
+    replicas_per_shard: usize,
+    config: MasterConfig,
+    heartbeat: Option<Duration>,
+    request_limits: RequestLimits,
+}
+
+impl Default for ClusterBuilder {
+    fn default() -> Self {
+        Self {
+            masters: 1,
+            shards: 1,
+            replicas_per_shard: 0,
+            config: MasterConfig::default(),
+            heartbeat: None,
+            request_limits: RequestLimits::default(),
+        }
+    }
+}
+
+impl ClusterBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Masters del cluster; con más de uno se conocen entre sí por `PEER_MASTERS` y los
+    /// nodos se conectan a todos.
+    pub fn masters(mut self, masters: usize) -> Self {
+        self.masters = masters.max(1);
+        self
+    }
+
+    pub fn shards(mut self, shards: usize) -> Self {
+        self.shards = shards;
+        self
+    }
+
+    pub fn replicas_per_shard(mut self, replicas: usize) -> Self {
+        self.replicas_per_shard = replicas;
+        self
+    }
+
+    /// Base de la configuración de cada master; los pares se completan al arrancar.
+    pub fn config(mut self, config: MasterConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Cada cuánto le laten los nodos a los masters.
+    pub fn heartbeat(mut self, every: Duration) -> Self {
+        self.heartbeat = Some(every);
+        self
+    }
+
+    pub fn request_limits(mut self, limits: RequestLimits) -> Self {
+        self.request_limits = limits;
+        self
+    }
+
+    /// Levanta los masters, después los primarios de cada shard y por último las
+    /// réplicas, que el master reparte entre los shards de menos réplicas. Vuelve cuando
+    /// todos los masters ven a todos los nodos.
+    pub async fn start(self) -> Cluster {
+        let mut listeners = Vec::with_capacity(self.masters);
+        for _ in 0..self.masters {
+            listeners.push(TcpListener::bind("127.0.0.1:0").await.unwrap());
+        }
+        let addrs: Vec<SocketAddr> = listeners.iter().map(|l| l.local_addr().unwrap()).collect();
+
+        let masters: Vec<MasterInstance> = listeners
+            .into_iter()
+            .enumerate()
+            .map(|(idx, listener)| {
+                let config = self.master_config(idx, &addrs);
+                let running = RunningMaster::start(listener, &config);
+                MasterInstance {
+                    id: config.peers.master_id.clone(),
+                    addr: addrs[idx],
+                    config,
+                    running: Some(running),
+                }
+            })
+            .collect();
+
+        let raft_dirs = RaftDirs(
+            masters
+                .iter()
+                .filter_map(|m| m.config.peers.raft_dir.clone())
+                .collect(),
+        );
+        let mut cluster = Cluster {
+            masters,
+            raft_dirs,
+            nodes: Vec::new(),
+            heartbeat: self.heartbeat,
+            request_limits: self.request_limits,
+        };
+        if self.masters > 1 {
+            cluster
+                .eventually(DEFAULT_TIMEOUT, "los masters no eligieron líder", |c| {
+                    c.running_masters()
+                        .filter(|m| m.module.peers.is_leader())
+                        .count()
+                        == 1
+                })
+                .await;
+        }
+        for _ in 0..self.shards {
+            cluster.add_node(NodeRole::Master).await;
+        }
+        for _ in 0..self.shards * self.replicas_per_shard {
+            cluster.add_node(NodeRole::Replica).await;
+        }
+        cluster
+    }
+
+    fn master_config(&self, idx: usize, addrs: &[SocketAddr]) -> MasterConfig {
+        let mut config = self.config.clone();
+        if addrs.len() > 1 {
+            config.peers = PeerConfig {
+                master_id: format!("master-{idx}"),
+                peers: addrs
+                    .iter()
+                    .filter(|addr| **addr != addrs[idx])
+                    .map(SocketAddr::to_string)
+                    .collect(),
+                heartbeat_interval: Duration::from_millis(20),
+                election_timeout: Duration::from_millis(150),
+                // como en producción, un master que reinicia vuelve con su estado de Raft
+                raft_dir: Some(
+                    std::env::temp_dir().join(format!("harness-raft-{}", fastrand::u64(..))),
+                ),
+                ..config.peers
+            };
+        }
+        config
+    }
+}
+
+struct RunningMaster {
+    supervisor: Arc<Supervisor>,
+    handle: MasterHandle,
+}
+
+impl RunningMaster {
+    fn start(listener: TcpListener, config: &MasterConfig) -> Self {
+        let supervisor = Supervisor::new_shared();
+        let handle = cache_master::server::start_with_config(listener, &supervisor, config);
+        Self { supervisor, handle }
+    }
+}
+
+/// Un master del cluster, corriendo o caído. Conserva su dirección y su configuración
+/// para volver a levantarlo igual.
+pub struct MasterInstance {
+    pub id: String,
+    pub addr: SocketAddr,
+    config: MasterConfig,
+    running: Option<RunningMaster>,
+}
+
+impl MasterInstance {
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    /// `None` mientras está caído.
+    pub fn handle(&self) -> Option<&MasterHandle> {
+        self.running.as_ref().map(|running| &running.handle)
+    }
+
+    /// Si el master tiene a `node_id` en su anillo.
+    fn sees(&self, node_id: &str) -> bool {
+        self.handle().is_some_and(|handle| {
+            handle
+                .module
+                .tcp_network_service
+                .shard_tree()
+                .iter()
+                .any(|(_, members)| members.iter().any(|m| &**m == node_id))
+        })
+    }
+}
+
+/// Un `cache_node` del cluster, corriendo o caído. Vuelve con el mismo id y rol.
+pub struct NodeInstance {
+    pub node_id: String,
+    pub role: NodeRole,
+    running: Option<(Supervisor, NodeHandle)>,
+}
+
+impl NodeInstance {
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    /// `None` mientras está caído.
+    pub fn handle(&self) -> Option<&NodeHandle> {
+        self.running.as_ref().map(|(_, handle)| handle)
+    }
+}
+
+/// Los `RAFT_DIR` de los masters, que se borran con el cluster.
+struct RaftDirs(Vec<PathBuf>);
+
+impl Drop for RaftDirs {
+    fn drop(&mut self) {
+        for dir in &self.0 {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+}
+
+pub struct Cluster {
+    masters: Vec<MasterInstance>,
+    raft_dirs: RaftDirs,
+    nodes: Vec<NodeInstance>,
+    heartbeat: Option<Duration>,
+    request_limits: RequestLimits,
+}
+
+impl Cluster {
+    pub fn masters(&self) -> &[MasterInstance] {
+        &self.masters
+    }
+
+    /// Los nodos en el orden en que se agregaron: primero los primarios de cada shard y
+    /// después las réplicas.
+    pub fn nodes(&self) -> &[NodeInstance] {
+        &self.nodes
+    }
+
+    /// Manija del master `idx`. Entra en pánico si está caído.
+    pub fn master(&self, idx: usize) -> &MasterHandle {
+        self.masters[idx]
+            .handle()
+            .unwrap_or_else(|| panic!("el master {idx} está caído"))
+    }
+
+    fn running_masters(&self) -> impl Iterator<Item = &MasterHandle> {
+        self.masters.iter().filter_map(MasterInstance::handle)
+    }
+
+    fn master_addrs(&self) -> Vec<String> {
+        self.masters.iter().map(|m| m.addr.to_string()).collect()
+    }
+
+    /// El shard de `node_id` según el primer master que esté corriendo.
+    pub fn shard_of(&self, node_id: &str) -> Option<String> {
+        let handle = self.running_masters().next()?;
+        handle
+            .module
+            .tcp_network_service
+            .shard_tree()
+            .into_iter()
+            .find(|(_, members)| members.iter().any(|m| &**m == node_id))
+            .map(|(shard, _)| shard.to_string())
+    }
+
+    /// Cliente conectado al primer master que esté corriendo.
+    pub async fn client(&self) -> TestClient {
+        let idx = self
+            .masters
+            .iter()
+            .position(MasterInstance::is_running)
+            .expect("no hay ningún master corriendo");
+        self.client_of(idx).await
+    }
+
+    pub async fn client_of(&self, master: usize) -> TestClient {
+        TestClient::connect(self.masters[master].addr)
+            .await
+            .expect("conexión del cliente de test")
+    }
+
+    /// Lanza un nodo nuevo conectado a todos los masters y espera a que los que corren lo
+    /// tengan en su anillo. Devuelve su índice.
+    pub async fn add_node(&mut self, role: NodeRole) -> usize {
+        self.nodes.push(NodeInstance {
+            node_id: new_sortable_id(),
+            role,
+            running: None,
+        });
+        let idx = self.nodes.len() - 1;
+        self.launch_node(idx).await;
+        idx
+    }
+
+    /// Apaga el nodo `idx` (como si se cayera) y espera a que los masters lo saquen.
+    pub async fn kill_node(&mut self, idx: usize) -> ShutdownReport {
+        let (supervisor, _) = self.nodes[idx]
+            .running
+            .take()
+            .unwrap_or_else(|| panic!("el nodo {idx} ya está caído"));
+        let report = supervisor.shutdown(SHUTDOWN_GRACE).await;
+
+        let node_id = self.nodes[idx].node_id.clone();
+        self.eventually(DEFAULT_TIMEOUT, "los masters no sacaron el nodo", |c| {
+            c.running_masters().all(|handle| {
+                !handle
+                    .app_state
+                    .network_state
+                    .nodes_registry
+                    .contains_key(node_id.as_str())
+            })
+        })
+        .await;
+        report
+    }
+
+    /// Vuelve a levantar el nodo `idx` caído, con su id y su rol pero el cache vacío
+    /// (una réplica se pone al día por replicación).
+    pub async fn restart_node(&mut self, idx: usize) {
+        assert!(
+            !self.nodes[idx].is_running(),
+            "el nodo {idx} sigue corriendo"
+        );
+        self.launch_node(idx).await;
+    }
+
+    async fn launch_node(&mut self, idx: usize) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let replication = ReplicationListener {
+            advertised_addr: listener.local_addr().unwrap().to_string(),
+            acceptor: Box::new(listener),
+        };
+        let node = &self.nodes[idx];
+        let options = NodeOptions {
+            node_id: Some(node.node_id.clone()),
+            replication: Some(replication),
+            heartbeat: self.heartbeat,
+            limits: self.request_limits,
+            ..NodeOptions::default()
+        };
+        let supervisor = Supervisor::new();
+        let handle = cache_node::server::start_with(
+            &supervisor,
+            node.role.as_wire(),
+            self.master_addrs(),
+            options,
+        );
+        self.nodes[idx].running = Some((supervisor, handle));
+
+        let node_id = self.nodes[idx].node_id.clone();
+        self.eventually(REJOIN_TIMEOUT, "el nodo no se unió al cluster", |c| {
+            c.masters
+                .iter()
+                .filter(|m| m.is_running())
+                .all(|m| m.sees(&node_id))
+        })
+        .await;
+    }
+
+    /// Apaga el master `idx`; los nodos siguen conectados a los demás.
+    pub async fn kill_master(&mut self, idx: usize) -> ShutdownReport {
+        let running = self.masters[idx]
+            .running
+            .take()
+            .unwrap_or_else(|| panic!("el master {idx} ya está caído"));
+        running.supervisor.shutdown(SHUTDOWN_GRACE).await
+    }
+
+    /// Vuelve a levantar el master `idx` en la misma dirección y con la misma
+    /// configuración, y espera a que se le reconecten todos los nodos que corren.
+    pub async fn restart_master(&mut self, idx: usize) {
+        let master = &self.masters[idx];
+        assert!(!master.is_running(), "el master {idx} sigue corriendo");
+        let listener = rebind(master.addr).await;
+        self.masters[idx].running = Some(RunningMaster::start(listener, &master.config));
+
+        self.eventually(REJOIN_TIMEOUT, "los nodos no volvieron al master", |c| {
+            c.nodes
+                .iter()
+                .filter(|n| n.is_running())
+                .all(|n| c.masters[idx].sees(&n.node_id))
+        })
+        .await;
+    }
+
+    /// Hace polling de `cond` hasta que sea cierta; falla el test con `what` si vence el
+    /// timeout.
+    pub async fn eventually<F>(&self, timeout: Duration, what: &str, cond: F)
+    where
+        F: Fn(&Self) -> bool,
+    {
+        let deadline = tokio::time::Instant::now() + timeout;
+        while !cond(self) {
+            assert!(tokio::time::Instant::now() < deadline, "{what}");
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    }
+
+    /// Apaga nodos y masters en orden y devuelve los reportes (nodos primero).
+    pub async fn shutdown(self) -> Vec<ShutdownReport> {
+        let mut reports = Vec::new();
+        for (supervisor, _) in self.nodes.into_iter().filter_map(|n| n.running) {
+            reports.push(supervisor.shutdown(SHUTDOWN_GRACE).await);
+        }
+        for running in self.masters.into_iter().filter_map(|m| m.running) {
+            reports.push(running.supervisor.shutdown(SHUTDOWN_GRACE).await);
+        }
+        // con los masters apagados nadie vuelve a escribir el estado de Raft
+        drop(self.raft_dirs);
+        reports
+    }
+}
+
+/// El puerto recién liberado puede tardar un momento en aceptar otro bind.
+async fn rebind(addr: SocketAddr) -> TcpListener {
+    let deadline = tokio::time::Instant::now() + DEFAULT_TIMEOUT;
+    loop {
+        match TcpListener::bind(addr).await {
+            Ok(listener) => return listener,
+            Err(e) if tokio::time::Instant::now() < deadline => {
+                tracing::debug!(%addr, error = %e, "el puerto del master sigue ocupado");
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+            Err(e) => panic!("no se pudo volver a abrir {addr}: {e}"),
+        }
+    }
+}
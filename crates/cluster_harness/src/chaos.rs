@@ -0,0 +1,121 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use app_net::chaos::FaultInjector;
+use bytes::Bytes;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+    task::JoinHandle,
+};
+
+/// Proxy TCP por líneas entre nodos y master. Cada sentido pasa por su propio
+/// `FaultInjector`, así se puede, por ejemplo, perder solo las respuestas.
+pub struct ChaosProxy {
+    addr: SocketAddr,
+    /// Tráfico nodo → master.
+    pub to_master: Arc<FaultInjector>,
+    /// Tráfico master → nodo.
+    pub to_node: Arc<FaultInjector>,
+    accept_task: JoinHandle<()>,
+}
+
+impl ChaosProxy {
+    /// Escucha en un puerto efímero y reenvía cada conexión a `target`.
+    pub async fn start(target: SocketAddr) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind del proxy");
+        let addr = listener.local_addr().unwrap();
+
+        let to_master = FaultInjector::passthrough();
+        let to_node = FaultInjector::passthrough();
+
+        let (up, down) = (to_master.clone(), to_node.clone());
+        let accept_task = tokio::spawn(async move {
+            while let Ok((inbound, _)) = listener.accept().await {
+                tokio::spawn(pipe(inbound, target, up.clone(), down.clone()));
+            }
+        });
+
+        Self {
+            addr,
+            to_master,
+            to_node,
+            accept_task,
+        }
+    }
+
+    /// Dirección a la que deben conectarse los nodos.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Corta todas las conexiones vivas; las siguientes pasan normalmente.
+    pub fn disconnect_all(&self) {
+        self.to_master.disconnect_all();
+        self.to_node.disconnect_all();
+    }
+}
+
+impl Drop for ChaosProxy {
+    fn drop(&mut self) {
+        self.accept_task.abort();
+    }
+}
+
+async fn pipe(
+    inbound: TcpStream,
+    target: SocketAddr,
+    up: Arc<FaultInjector>,
+    down: Arc<FaultInjector>,
+) {
+    let Ok(outbound) = TcpStream::connect(target).await else {
+        return;
+    };
+    let (in_r, in_w) = inbound.into_split();
+    let (out_r, out_w) = outbound.into_split();
+    let (kill_up, kill_down) = (up.disconnected(), down.disconnected());
+
+    // al salir se sueltan las cuatro mitades y ambos extremos ven EOF
+    tokio::select! {
+        _ = relay(in_r, out_w, &up) => {}
+        _ = relay(out_r, in_w, &down) => {}
+        _ = kill_up.cancelled() => {}
+        _ = kill_down.cancelled() => {}
+    }
+}
+
+async fn relay<R, W>(reader: R, mut writer: W, injector: &Arc<FaultInjector>)
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let (tx, mut rx) = mpsc::unbounded_channel::<Bytes>();
+    let faulty = injector.wrap_sender(tx);
+
+    let read = async move {
+        let mut br = BufReader::new(reader);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match br.read_line(&mut line).await {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {}
+            }
+            if faulty.send(Bytes::from(line.clone())).is_err() {
+                break;
+            }
+        }
+    };
+
+    let write = async move {
+        while let Some(bytes) = rx.recv().await {
+            if writer.write_all(&bytes).await.is_err() {
+                break;
+            }
+        }
+    };
+
+    tokio::join!(read, write);
+}
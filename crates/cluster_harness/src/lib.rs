@@ -0,0 +1,615 @@
+//! Arnés para tests end-to-end: levanta un `cache_master` y N `cache_node` dentro del
+//! mismo runtime de tokio sobre puertos efímeros, y expone un cliente de protocolo.
+//! Con `sim::Simulation` lo mismo corre en memoria y con tiempo virtual, y con
+//! `ClusterBuilder` se arma un cluster de varios masters y shards con réplicas, del que se
+//! pueden tirar abajo y volver a levantar masters y nodos sueltos.
+//!
+//! ```ignore
+//! let cluster = TestCluster::start(3).await;
+//! let client = cluster.client().await;
+//! client.put("k", "v", None).await.unwrap();
+//! assert_eq!(client.get("k").await.unwrap().payload, "v");
+//! cluster.shutdown().await;
+//! ```
+
+mod builder;
+mod chaos;
+mod pki;
+pub mod sim;
+
+pub use app_net::chaos::{FaultConfig, FaultInjector, FaultStats};
+pub use builder::{Cluster, ClusterBuilder, MasterInstance, NodeInstance, REJOIN_TIMEOUT};
+pub use chaos::ChaosProxy;
+pub use pki::TestCa;
+
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+use app_core::{
+    clock::{AppClock, Clock},
+    events::Envelope,
+    id::new_sortable_id,
+    supervisor::{ShutdownReport, Supervisor},
+};
+use app_net::{
+    BoxedStream, COMPRESS, ClusterTls, Compression, Connector, EventData, MemoryNetwork, ParsedMsg,
+    QUIC_SCHEME, RequestDataInput, ResponseData, Socket, TcpConnector, encode_args, encode_token,
+    format_millis, parse_line, types::SocketResult,
+};
+use bytes::Bytes;
+use cache_master::{
+    core::domain::models::DomainEvent,
+    infrastructure::{di::config::MasterConfig, hot_keys::HotKeyConfig},
+    server::MasterHandle,
+};
+use cache_node::{
+    core::services::{SlowLogConfig, TopKeysConfig, WriteBatching},
+    server::{NodeHandle, NodeOptions, ReplicationListener, RequestLimits},
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::broadcast,
+    sync::{Mutex, mpsc},
+    task::JoinHandle,
+};
+
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeRole {
+    Master,
+    Replica,
+}
+
+impl NodeRole {
+    fn as_wire(&self) -> &'static str {
+        match self {
+            NodeRole::Master => "MASTER",
+            NodeRole::Replica => "REPLICA",
+        }
+    }
+}
+
+/// Un `cache_node` corriendo dentro del test.
+pub struct TestNode {
+    pub role: NodeRole,
+    pub handle: NodeHandle,
+    supervisor: Supervisor,
+}
+
+impl TestNode {
+    pub fn node_id(&self) -> &str {
+        &self.handle.node_id
+    }
+}
+
+pub struct TestCluster {
+    master_addr: String,
+    /// Solo en clusters TCP.
+    tcp_addr: Option<SocketAddr>,
+    connector: Arc<dyn Connector>,
+    clock: Arc<dyn Clock>,
+    /// En memoria los nodos usan IDs fijos (`node-N`) para que el hashing sea reproducible.
+    fixed_ids: bool,
+    /// `NodeOptions::pressure_report` de los nodos que se agreguen.
+    pressure_report: Option<Duration>,
+    /// `NodeOptions::heartbeat` de los nodos que se agreguen.
+    heartbeat: Option<Duration>,
+    /// `NodeOptions::limits` de los nodos que se agreguen.
+    request_limits: RequestLimits,
+    /// `NodeOptions::slow_log` de los nodos que se agreguen.
+    slow_log: SlowLogConfig,
+    /// `NodeOptions::top_keys` de los nodos que se agreguen.
+    top_keys: TopKeysConfig,
+    /// `NodeOptions::max_memory_bytes` de los nodos que se agreguen.
+    max_memory_bytes: Option<u64>,
+    /// `NodeOptions::compress_min_bytes` de los nodos que se agreguen.
+    compress_min_bytes: Option<usize>,
+    /// `NodeOptions::stale_grace` de los nodos que se agreguen.
+    stale_grace: Duration,
+    /// `NodeOptions::write_batching` de los nodos que se agreguen.
+    write_batching: Option<WriteBatching>,
+    /// `NodeOptions::extra_groups` de los nodos que se agreguen.
+    extra_groups: Vec<NodeRole>,
+    spawned: usize,
+    master_supervisor: Arc<Supervisor>,
+    pub master: MasterHandle,
+    nodes: Vec<TestNode>,
+    events: broadcast::Receiver<Envelope<DomainEvent>>,
+}
+
+impl TestCluster {
+    /// Master + `masters` nodos con rol `MASTER` (un shard cada uno).
+    pub async fn start(masters: usize) -> Self {
+        Self::start_with(masters, 0).await
+    }
+
+    /// Master + `masters` shards + `replicas` réplicas (repartidas por el master).
+    /// Vuelve recién cuando todos los nodos figuran en la topología.
+    pub async fn start_with(masters: usize, replicas: usize) -> Self {
+        Self::start_with_config(masters, replicas, &MasterConfig::default()).await
+    }
+
+    /// Igual que `start_with` con la configuración del master que se quiera probar.
+    pub async fn start_with_config(masters: usize, replicas: usize, config: &MasterConfig) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind de puerto efímero");
+        let tcp_addr = listener.local_addr().unwrap();
+
+        let master_supervisor = Supervisor::new_shared();
+        let master = cache_master::server::start_with_config(listener, &master_supervisor, config);
+
+        let mut cluster = Self::assemble(
+            master_supervisor,
+            master,
+            tcp_addr.to_string(),
+            Arc::new(TcpConnector),
+            Arc::new(AppClock::new()),
+        );
+        cluster.tcp_addr = Some(tcp_addr);
+        cluster.populate(masters, replicas).await;
+        cluster
+    }
+
+    /// Igual que `start_with` pero sobre `net` y con `clock` compartido por master y
+    /// nodos. Pensado para `sim::Simulation`.
+    pub async fn start_in_memory(
+        net: Arc<MemoryNetwork>,
+        clock: Arc<dyn Clock>,
+        masters: usize,
+        replicas: usize,
+    ) -> Self {
+        let listener = net.bind("master").expect("dirección del master en uso");
+        let master_addr = listener.addr().to_string();
+
+        let master_supervisor = Supervisor::new_shared();
+        let master =
+            cache_master::server::start_with_clock(listener, &master_supervisor, clock.clone());
+
+        let mut cluster = Self::assemble(master_supervisor, master, master_addr, net, clock);
+        cluster.fixed_ids = true;
+        cluster.populate(masters, replicas).await;
+        cluster
+    }
+
+    fn assemble(
+        master_supervisor: Arc<Supervisor>,
+        master: MasterHandle,
+        master_addr: String,
+        connector: Arc<dyn Connector>,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        let events = master.module.event_bus.subscribe();
+        Self {
+            master_addr,
+            tcp_addr: None,
+            connector,
+            clock,
+            fixed_ids: false,
+            pressure_report: None,
+            heartbeat: None,
+            request_limits: RequestLimits::default(),
+            slow_log: SlowLogConfig::default(),
+            top_keys: TopKeysConfig::default(),
+            max_memory_bytes: None,
+            compress_min_bytes: None,
+            stale_grace: Duration::ZERO,
+            write_batching: None,
+            extra_groups: Vec::new(),
+            spawned: 0,
+            master_supervisor,
+            master,
+            nodes: Vec::new(),
+            events,
+        }
+    }
+
+    async fn populate(&mut self, masters: usize, replicas: usize) {
+        for _ in 0..masters {
+            self.add_node(NodeRole::Master).await;
+        }
+        for _ in 0..replicas {
+            self.add_node(NodeRole::Replica).await;
+        }
+    }
+
+    /// Dirección TCP del master. Entra en pánico en clusters en memoria.
+    pub fn master_addr(&self) -> SocketAddr {
+        self.tcp_addr
+            .expect("el cluster corre en memoria, no tiene dirección TCP")
+    }
+
+    pub fn nodes(&self) -> &[TestNode] {
+        &self.nodes
+    }
+
+    /// Los nodos que se agreguen desde ahora reportan `CACHE-PRESSURE` cada `every`.
+    pub fn set_pressure_report(&mut self, every: Option<Duration>) {
+        self.pressure_report = every;
+    }
+
+    /// Los nodos que se agreguen desde ahora le laten al master cada `every`.
+    pub fn set_heartbeat(&mut self, every: Option<Duration>) {
+        self.heartbeat = every;
+    }
+
+    /// Tope de memoria que anuncian los nodos que se agreguen desde ahora.
+    pub fn set_max_memory_bytes(&mut self, max: Option<u64>) {
+        self.max_memory_bytes = max;
+    }
+
+    /// Desde cuántos bytes comprimen sus respuestas los nodos que se agreguen desde ahora.
+    pub fn set_compress_min_bytes(&mut self, min: Option<usize>) {
+        self.compress_min_bytes = min;
+    }
+
+    /// Cuánto guardan las entradas vencidas los nodos que se agreguen desde ahora.
+    pub fn set_stale_grace(&mut self, grace: Duration) {
+        self.stale_grace = grace;
+    }
+
+    /// Los nodos que se agreguen desde ahora aplican sus `PUT` en lotes.
+    pub fn set_write_batching(&mut self, batching: Option<WriteBatching>) {
+        self.write_batching = batching;
+    }
+
+    /// Grupos que suman los nodos que se agreguen desde ahora, además del de su rol.
+    pub fn set_extra_groups(&mut self, roles: Vec<NodeRole>) {
+        self.extra_groups = roles;
+    }
+
+    /// Umbral y largo del `SLOWLOG` de los nodos que se agreguen desde ahora.
+    pub fn set_slow_log(&mut self, config: SlowLogConfig) {
+        self.slow_log = config;
+    }
+
+    /// Las claves que cuentan para `TOPKEYS` los nodos que se agreguen desde ahora.
+    pub fn set_top_keys(&mut self, config: TopKeysConfig) {
+        self.top_keys = config;
+    }
+
+    /// Topes de requests en curso de los nodos que se agreguen desde ahora.
+    pub fn set_request_limits(&mut self, limits: RequestLimits) {
+        self.request_limits = limits;
+    }
+
+    /// Lanza un nodo nuevo y espera a que el master lo incorpore.
+    pub async fn add_node(&mut self, role: NodeRole) -> &TestNode {
+        self.spawn_node(role, self.master_addr.clone()).await
+    }
+
+    /// Como `add_node`, pero el nodo se conecta a `addr` (p. ej. un `ChaosProxy`).
+    pub async fn add_node_via(&mut self, role: NodeRole, addr: SocketAddr) -> &TestNode {
+        self.spawn_node(role, addr.to_string()).await
+    }
+
+    /// Abre el puerto de cluster del master (ver `server::start_cluster`) con el
+    /// certificado `tls`.
+    pub async fn listen_tls(&self, tls: &ClusterTls) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        cache_master::server::start_cluster(
+            tls.acceptor(listener).unwrap(),
+            &self.master,
+            &self.master_supervisor,
+        );
+        addr
+    }
+
+    /// Como `add_node_via`, con TLS mutuo: el nodo presenta `tls` y su id es el del
+    /// certificado.
+    pub async fn add_node_tls(
+        &mut self,
+        role: NodeRole,
+        addr: SocketAddr,
+        tls: &ClusterTls,
+    ) -> &TestNode {
+        let connector = Arc::new(tls.connector(TcpConnector).unwrap());
+        self.spawn_node_with(role, addr.to_string(), tls.identity(), connector)
+            .await
+    }
+
+    /// Como `listen_tls`, sobre QUIC; devuelve la dirección `quic://` para los nodos.
+    pub fn listen_quic(&self, tls: &ClusterTls) -> String {
+        let acceptor = tls
+            .quic_acceptor(SocketAddr::from(([127, 0, 0, 1], 0)))
+            .unwrap();
+        let addr = acceptor.local_addr().unwrap();
+        cache_master::server::start_cluster(acceptor, &self.master, &self.master_supervisor);
+        format!("{QUIC_SCHEME}{addr}")
+    }
+
+    /// Como `add_node_tls`, con un conector que también sabe ir por QUIC (`addr` con
+    /// `quic://`).
+    pub async fn add_node_quic(
+        &mut self,
+        role: NodeRole,
+        addr: &str,
+        tls: &ClusterTls,
+    ) -> &TestNode {
+        let tcp = tls.connector(TcpConnector).unwrap();
+        let connector = Arc::new(tls.quic_connector(tcp).unwrap());
+        self.spawn_node_with(role, addr.to_string(), tls.identity(), connector)
+            .await
+    }
+
+    async fn spawn_node(&mut self, role: NodeRole, addr: String) -> &TestNode {
+        let node_id = self.fixed_ids.then(|| format!("node-{}", self.spawned + 1));
+        self.spawn_node_with(role, addr, node_id, self.connector.clone())
+            .await
+    }
+
+    async fn spawn_node_with(
+        &mut self,
+        role: NodeRole,
+        addr: String,
+        node_id: Option<String>,
+        connector: Arc<dyn Connector>,
+    ) -> &TestNode {
+        self.spawned += 1;
+
+        // en TCP cada nodo escucha a sus réplicas (replicación nodo a nodo)
+        let replication = match self.tcp_addr {
+            Some(_) => {
+                let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                Some(ReplicationListener {
+                    advertised_addr: listener.local_addr().unwrap().to_string(),
+                    acceptor: Box::new(listener),
+                })
+            }
+            None => None,
+        };
+        let options = NodeOptions {
+            node_id,
+            connector,
+            clock: self.clock.clone(),
+            strict_writes: false,
+            replication,
+            pressure_report: self.pressure_report,
+            heartbeat: self.heartbeat,
+            limits: self.request_limits,
+            slow_log: self.slow_log,
+            eviction: Default::default(),
+            expiry: Default::default(),
+            stale_grace: self.stale_grace,
+            compaction: None,
+            write_batching: self.write_batching,
+            idempotency: Default::default(),
+            top_keys: self.top_keys,
+            namespace_quotas: Default::default(),
+            max_memory_bytes: self.max_memory_bytes,
+            compress_min_bytes: self.compress_min_bytes,
+            memcached: None,
+            extra_groups: self
+                .extra_groups
+                .iter()
+                .map(|role| role.as_wire().parse().unwrap())
+                .collect(),
+        };
+
+        let supervisor = Supervisor::new();
+        let handle =
+            cache_node::server::start_with(&supervisor, role.as_wire(), vec![addr], options);
+
+        // los grupos de un mismo nodo se unen en cualquier orden
+        let pending: Vec<String> = handle
+            .groups
+            .members()
+            .iter()
+            .map(|m| m.member_id.to_string())
+            .collect();
+        let pending = std::sync::Mutex::new(pending);
+        self.wait_for_event(DEFAULT_TIMEOUT, |e| {
+            let mut pending = pending.lock().unwrap();
+            if let DomainEvent::NodeJoined { node_id, .. } = e {
+                pending.retain(|id| id != node_id);
+            }
+            pending.is_empty()
+        })
+        .await
+        .unwrap_or_else(|| panic!("el nodo {} no se unió al cluster", handle.node_id));
+
+        self.nodes.push(TestNode {
+            role,
+            handle,
+            supervisor,
+        });
+        self.nodes.last().unwrap()
+    }
+
+    /// Apaga el nodo `idx` (como si se cayera) y espera a que el master lo saque.
+    pub async fn stop_node(&mut self, idx: usize) -> ShutdownReport {
+        let node = self.nodes.remove(idx);
+        let node_id = node.handle.node_id.clone();
+        let report = node.supervisor.shutdown(SHUTDOWN_GRACE).await;
+
+        self.wait_until(DEFAULT_TIMEOUT, || {
+            !self
+                .master
+                .app_state
+                .network_state
+                .nodes_registry
+                .contains_key(node_id.as_str())
+        })
+        .await;
+
+        report
+    }
+
+    /// Espera un evento de dominio que cumpla `pred`. `None` si vence el timeout.
+    pub async fn wait_for_event<F>(&mut self, timeout: Duration, pred: F) -> Option<DomainEvent>
+    where
+        F: Fn(&DomainEvent) -> bool,
+    {
+        let events = &mut self.events;
+        tokio::time::timeout(timeout, async move {
+            loop {
+                match events.recv().await {
+                    Ok(envelope) if pred(&envelope.event) => return Some(envelope.event),
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        })
+        .await
+        .ok()
+        .flatten()
+    }
+
+    /// Hace polling de `cond` hasta que sea cierta; falla el test si vence el timeout.
+    pub async fn wait_until<F>(&self, timeout: Duration, cond: F)
+    where
+        F: Fn() -> bool,
+    {
+        let deadline = tokio::time::Instant::now() + timeout;
+        while !cond() {
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "condición no alcanzada en {timeout:?}"
+            );
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    }
+
+    /// Arranca en el master la copia de claves calientes (ver `server::start_hot_keys`).
+    pub fn start_hot_keys(&self, config: HotKeyConfig) {
+        cache_master::server::start_hot_keys(&self.master, &self.master_supervisor, config);
+    }
+
+    /// Proxy con inyección de fallas delante del master.
+    pub async fn chaos_proxy(&self) -> ChaosProxy {
+        ChaosProxy::start(self.master_addr()).await
+    }
+
+    pub async fn client(&self) -> TestClient {
+        let stream = self
+            .connector
+            .connect(&self.master_addr)
+            .await
+            .expect("conexión del cliente de test");
+        TestClient::over(stream)
+    }
+
+    /// Apaga nodos y master en orden y devuelve los reportes (nodos primero).
+    pub async fn shutdown(self) -> Vec<ShutdownReport> {
+        let mut reports = Vec::with_capacity(self.nodes.len() + 1);
+        for node in self.nodes {
+            reports.push(node.supervisor.shutdown(SHUTDOWN_GRACE).await);
+        }
+        reports.push(self.master_supervisor.shutdown(SHUTDOWN_GRACE).await);
+        reports
+    }
+}
+
+/// Cliente mínimo del protocolo de línea, conectado al master como `Client`.
+pub struct TestClient {
+    pub id: String,
+    socket: Arc<Socket>,
+    /// `EVT` recibidos (nombre y payload), en orden.
+    events: Mutex<mpsc::UnboundedReceiver<(String, String)>>,
+    tasks: [JoinHandle<()>; 2],
+}
+
+impl TestClient {
+    pub async fn connect(addr: SocketAddr) -> std::io::Result<Self> {
+        let stream = TcpStream::connect(addr).await?;
+        Ok(Self::over(Box::new(stream)))
+    }
+
+    /// Cliente sobre una conexión ya abierta (TCP o en memoria).
+    pub fn over(stream: BoxedStream) -> Self {
+        let (reader, mut writer) = tokio::io::split(stream);
+
+        let id = new_sortable_id();
+        let (tx, mut rx) = mpsc::unbounded_channel::<Bytes>();
+        let socket = Arc::new(Socket::new(id.clone(), tx, DEFAULT_TIMEOUT));
+
+        let writer_task = tokio::spawn(async move {
+            while let Some(bytes) = rx.recv().await {
+                if writer.write_all(&bytes).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let reader_socket = socket.clone();
+        let (events_tx, events) = mpsc::unbounded_channel();
+        let reader_task = tokio::spawn(async move {
+            let mut br = BufReader::new(reader);
+            let mut line = String::new();
+            loop {
+                line.clear();
+                match br.read_line(&mut line).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(_) => {}
+                }
+                match parse_line(&line) {
+                    Ok(ParsedMsg::Res { id, raw_response }) => {
+                        reader_socket.handle_response(id, raw_response.to_string());
+                    }
+                    Ok(ParsedMsg::Evt { data }) => {
+                        let _ = events_tx.send((data.name.to_string(), data.payload.into_owned()));
+                    }
+                    _ => {}
+                }
+            }
+        });
+
+        // el writer acaba de arrancar: el canal no puede estar cerrado
+        let _ = socket.send_raw(Bytes::from(format!("{id}\n")));
+
+        Self {
+            id,
+            socket,
+            events: Mutex::new(events),
+            tasks: [writer_task, reader_task],
+        }
+    }
+
+    pub async fn request(&self, action: &str, payload: &str) -> SocketResult<ResponseData> {
+        self.socket
+            .request(RequestDataInput::new(action, payload))
+            .await
+    }
+
+    /// `ttl_ms` es relativo, igual que en el cliente real.
+    pub async fn put(
+        &self,
+        key: &str,
+        value: &str,
+        ttl_ms: Option<u64>,
+    ) -> SocketResult<ResponseData> {
+        let ttl = ttl_ms.map(format_millis);
+        let payload = encode_args([key, value].into_iter().chain(ttl.as_deref()));
+        self.request("PUT", &payload).await
+    }
+
+    pub async fn get(&self, key: &str) -> SocketResult<ResponseData> {
+        self.request("GET", &encode_token(key)).await
+    }
+
+    /// Le avisa al master que entiende las respuestas comprimidas (`EVT COMPRESS`).
+    pub fn accept_compression(&self) -> SocketResult<()> {
+        self.socket
+            .send_evt(&EventData::new(COMPRESS, Compression::Deflate.as_str()))
+    }
+
+    /// Próximo `EVT` (nombre y payload); `None` si no llega ninguno en `timeout`.
+    pub async fn next_event(&self, timeout: Duration) -> Option<(String, String)> {
+        let mut events = self.events.lock().await;
+        tokio::time::timeout(timeout, events.recv())
+            .await
+            .ok()
+            .flatten()
+    }
+}
+
+impl Drop for TestClient {
+    fn drop(&mut self) {
+        for t in &self.tasks {
+            t.abort();
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Simulación determinista: master, nodos y clientes sobre una `MemoryNetwork`, en un
+//! runtime de un solo hilo con el tiempo de tokio pausado. Los `sleep`, timeouts,
+//! reintentos y el reaper avanzan en tiempo virtual en cuanto el runtime queda ocioso,
+//! y los TTLs se miden con un `RuntimeClock`, así que expiraciones y timeouts no esperan
+//! de verdad.
+//!
+//! La semilla alimenta `fastrand` (jitter de reintentos, `FaultInjector`) y los IDs de
+//! nodo son fijos, por lo que la misma semilla reproduce la misma ejecución. Lo único que
+//! no se siembra es el orden aleatorio de ramas de `tokio::select!` (requiere
+//! `tokio_unstable`); ramas listas a la vez pueden resolverse distinto.
+//!
+//! ```ignore
+//! Simulation::from_env().run(|world| async move {
+//!     let cluster = world.cluster(2).await;
+//!     let client = cluster.client().await;
+//!     client.put("k", "v", Some(1_000)).await.unwrap();
+//!     world.sleep(Duration::from_secs(2)).await;
+//!     assert_eq!(client.get("k").await.unwrap().payload, "");
+//! });
+//! ```
+
+use std::{
+    future::Future,
+    panic::{AssertUnwindSafe, catch_unwind, resume_unwind},
+    sync::Arc,
+    time::Duration,
+};
+
+use app_core::clock::{Clock, RuntimeClock};
+use app_net::MemoryNetwork;
+use tokio::time::Instant;
+
+use crate::TestCluster;
+
+/// Variable de entorno para reproducir una corrida fallida.
+pub const SEED_ENV: &str = "SIM_SEED";
+
+/// Instante virtual de arranque de todos los relojes (2024-01-01T00:00:00Z).
+pub const SIM_EPOCH_MS: u64 = 1_704_067_200_000;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Simulation {
+    seed: u64,
+}
+
+impl Simulation {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    /// Usa `SIM_SEED` si está definida; si no, una semilla al azar.
+    pub fn from_env() -> Self {
+        let seed = std::env::var(SEED_ENV)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_else(|| fastrand::u64(..));
+        Self::new(seed)
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Corre `scenario` hasta el final. Si entra en pánico se informa la semilla.
+    pub fn run<F, Fut, T>(&self, scenario: F) -> T
+    where
+        F: FnOnce(SimWorld) -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let seed = self.seed;
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            fastrand::seed(seed);
+
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .start_paused(true)
+                .build()
+                .expect("runtime de simulación");
+
+            rt.block_on(async move {
+                let world = SimWorld {
+                    seed,
+                    net: MemoryNetwork::new(),
+                    clock: Arc::new(RuntimeClock::starting_at(SIM_EPOCH_MS)),
+                    started: Instant::now(),
+                };
+                scenario(world).await
+            })
+        }));
+
+        result.unwrap_or_else(|panic| {
+            eprintln!("simulación falló; reproducir con {SEED_ENV}={seed}");
+            resume_unwind(panic)
+        })
+    }
+}
+
+/// Todo lo que comparte una corrida: red, reloj y semilla.
+pub struct SimWorld {
+    pub seed: u64,
+    pub net: Arc<MemoryNetwork>,
+    pub clock: Arc<RuntimeClock>,
+    started: Instant,
+}
+
+impl SimWorld {
+    /// Master + `masters` shards sobre la red de la simulación.
+    pub async fn cluster(&self, masters: usize) -> TestCluster {
+        self.cluster_with(masters, 0).await
+    }
+
+    pub async fn cluster_with(&self, masters: usize, replicas: usize) -> TestCluster {
+        TestCluster::start_in_memory(self.net.clone(), self.clock.clone(), masters, replicas).await
+    }
+
+    /// Avanza el tiempo virtual (instantáneo en tiempo real).
+    pub async fn sleep(&self, by: Duration) {
+        tokio::time::sleep(by).await;
+    }
+
+    /// Tiempo virtual transcurrido desde el arranque de la simulación.
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Milisegundos Unix virtuales, los mismos que ven master y nodos.
+    pub fn now_ms(&self) -> u64 {
+        self.clock.now_millis().as_millis_u64()
+    }
+}
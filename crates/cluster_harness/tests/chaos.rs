@@ -0,0 +1,187 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use cache_master::{
+    core::domain::models::DomainEvent,
+    infrastructure::{di::config::MasterConfig, failure_detector::FailureDetectorConfig},
+};
+use cache_node::server::RequestLimits;
+use cluster_harness::{DEFAULT_TIMEOUT, FaultConfig, NodeRole, TestCluster};
+use tokio::task::JoinSet;
+
+#[tokio::test]
+async fn node_reconnects_after_forced_disconnect() {
+    let mut cluster = TestCluster::start(0).await;
+    let proxy = cluster.chaos_proxy().await;
+    let node_id = cluster
+        .add_node_via(NodeRole::Master, proxy.addr())
+        .await
+        .node_id()
+        .to_string();
+
+    let client = cluster.client().await;
+    assert_eq!(client.put("k", "v", None).await.unwrap().code, 200);
+
+    proxy.disconnect_all();
+
+    let removed = cluster
+        .wait_for_event(
+            DEFAULT_TIMEOUT,
+            |e| matches!(e, DomainEvent::NodeRemoved { node_id: id } if *id == node_id),
+        )
+        .await;
+    assert!(removed.is_some(), "el master no detectó la desconexión");
+
+    let rejoined = cluster
+        .wait_for_event(
+            DEFAULT_TIMEOUT,
+            |e| matches!(e, DomainEvent::NodeJoined { node_id: id, .. } if *id == node_id),
+        )
+        .await;
+    assert!(rejoined.is_some(), "el nodo no volvió a conectarse");
+
+    // la data en memoria del nodo sobrevive a la reconexión
+    assert_eq!(client.get("k").await.unwrap().payload, "v");
+    assert_eq!(proxy.to_node.stats().disconnects, 1);
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn latency_slows_requests_without_breaking_them() {
+    let mut cluster = TestCluster::start(0).await;
+    let proxy = cluster.chaos_proxy().await;
+    cluster.add_node_via(NodeRole::Master, proxy.addr()).await;
+
+    proxy.to_node.set_config(
+        FaultConfig::new().with_latency(Duration::from_millis(40), Duration::from_millis(10)),
+    );
+
+    let client = cluster.client().await;
+    let start = Instant::now();
+    assert_eq!(client.put("slow", "v", None).await.unwrap().code, 200);
+    assert!(start.elapsed() >= Duration::from_millis(40));
+    assert_eq!(client.get("slow").await.unwrap().payload, "v");
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn reordered_responses_still_match_their_requests() {
+    let mut cluster = TestCluster::start(0).await;
+    let proxy = cluster.chaos_proxy().await;
+    cluster.add_node_via(NodeRole::Master, proxy.addr()).await;
+
+    let client = cluster.client().await;
+    for i in 0..10 {
+        let res = client.put(&format!("k{i}"), &format!("v{i}"), None).await;
+        assert_eq!(res.unwrap().code, 200);
+    }
+
+    proxy
+        .to_master
+        .set_config(FaultConfig::new().with_reorder_rate(0.5));
+
+    let client = Arc::new(client);
+    let mut gets = JoinSet::new();
+    for i in 0..10 {
+        let client = client.clone();
+        gets.spawn(async move { (i, client.get(&format!("k{i}")).await.unwrap()) });
+    }
+    while let Some(joined) = gets.join_next().await {
+        let (i, res) = joined.unwrap();
+        assert_eq!(res.payload, format!("v{i}"));
+    }
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn lost_replies_surface_as_timeouts() {
+    let mut cluster = TestCluster::start(0).await;
+    let proxy = cluster.chaos_proxy().await;
+    cluster.add_node_via(NodeRole::Master, proxy.addr()).await;
+
+    proxy
+        .to_master
+        .set_config(FaultConfig::new().with_drop_rate(1.0));
+
+    let client = cluster.client().await;
+    let res = client.put("lost", "v", None).await.unwrap();
+    assert_eq!(res.code, 504, "{}", res.payload);
+    assert!(proxy.to_master.stats().dropped >= 1);
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn a_node_at_its_request_cap_answers_busy_instead_of_queueing() {
+    let mut cluster = TestCluster::start(0).await;
+    // sin cupo: todo request al nodo se rechaza
+    cluster.set_request_limits(RequestLimits {
+        per_connection: 0,
+        global: 16,
+        ..RequestLimits::default()
+    });
+    cluster.add_node(NodeRole::Master).await;
+
+    let client = cluster.client().await;
+    let res = client.put("k", "v", None).await.unwrap();
+    assert_eq!(res.code, 503, "{}", res.payload);
+    assert!(res.payload.contains("nodo ocupado"), "{}", res.payload);
+
+    let res = client.get("k").await.unwrap();
+    assert_eq!(res.code, 503, "{}", res.payload);
+    assert_eq!(cluster.master.module.metrics.shed_total(), 2);
+    // nada llegó a ejecutarse
+    assert_eq!(cluster.nodes()[0].handle.module.cache.stats().entries, 0);
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn a_node_that_stops_beating_is_cut_even_with_its_connection_open() {
+    let config = MasterConfig {
+        failure_detector: FailureDetectorConfig {
+            acceptable_pause: Duration::from_millis(100),
+            first_heartbeat_estimate: Duration::from_millis(50),
+            min_std_deviation: Duration::from_millis(20),
+            check_interval: Duration::from_millis(20),
+            ..FailureDetectorConfig::default()
+        },
+        ..MasterConfig::default()
+    };
+    let mut cluster = TestCluster::start_with_config(0, 0, &config).await;
+    cluster.set_heartbeat(Some(Duration::from_millis(50)));
+    let proxy = cluster.chaos_proxy().await;
+    let node_id = cluster
+        .add_node_via(NodeRole::Master, proxy.addr())
+        .await
+        .node_id()
+        .to_string();
+
+    // latiendo, un poco de latencia no alcanza para sospechar
+    proxy.to_master.set_config(
+        FaultConfig::new().with_latency(Duration::from_millis(20), Duration::from_millis(30)),
+    );
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let detector = cluster.master.module.failure_detector.clone();
+    assert!(detector.phi(&node_id).is_some_and(|phi| phi < 8.0));
+
+    // los latidos se pierden pero la conexión sigue abierta
+    proxy
+        .to_master
+        .set_config(FaultConfig::new().with_drop_rate(1.0));
+    let removed = cluster
+        .wait_for_event(
+            DEFAULT_TIMEOUT,
+            |e| matches!(e, DomainEvent::NodeRemoved { node_id: id } if *id == node_id),
+        )
+        .await;
+    assert!(removed.is_some(), "el master no sospechó del nodo");
+    assert_eq!(proxy.to_node.stats().disconnects, 0);
+
+    cluster.shutdown().await;
+}
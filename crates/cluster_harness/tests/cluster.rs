@@ -0,0 +1,2074 @@
+use std::{sync::Arc, time::Duration};
+
+use app_core::supervisor::Supervisor;
+
+use app_net::{
+    ClusterMap, Connector, MapUpdate, MonitorEntry, TcpConnector, TopologyChange, TopologyEvent,
+    monitor::MONITOR,
+};
+use cache_master::{
+    core::domain::models::DomainEvent,
+    infrastructure::{
+        adapters::{
+            controllers::router::RouterConfig,
+            services::{BackupTarget, NodeRule},
+        },
+        dashboard::{Dashboard, NodeRole as DashboardRole},
+        di::config::MasterConfig,
+        hot_keys::HotKeyConfig,
+        peers::PeerConfig,
+    },
+    server::MasterHandle,
+};
+use cache_node::{
+    core::{
+        domain::services::CacheService,
+        services::{Op, SlowLogConfig, TopKeysConfig, WriteBatching},
+    },
+    server::RequestLimits,
+};
+use cluster_harness::{DEFAULT_TIMEOUT, NodeRole, TestCa, TestClient, TestCluster};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+};
+
+#[tokio::test]
+async fn put_then_get_round_trips_through_the_cluster() {
+    let cluster = TestCluster::start(3).await;
+    let client = cluster.client().await;
+
+    for i in 0..20 {
+        let res = client
+            .put(&format!("key-{i}"), &format!("value-{i}"), None)
+            .await
+            .unwrap();
+        assert_eq!(res.code, 200, "PUT key-{i}: {}", res.payload);
+    }
+
+    for i in 0..20 {
+        let res = client.get(&format!("key-{i}")).await.unwrap();
+        assert_eq!(res.code, 200);
+        assert_eq!(res.payload, format!("value-{i}"));
+    }
+
+    let reports = cluster.shutdown().await;
+    assert!(reports.iter().all(|r| r.panics.is_empty()));
+}
+
+#[tokio::test]
+async fn missing_key_reads_as_empty() {
+    let cluster = TestCluster::start(1).await;
+    let client = cluster.client().await;
+
+    // hoy el nodo responde un miss como `200` con payload vacío
+    let res = client.get("nope").await.unwrap();
+    assert_eq!((res.code, res.payload.as_str()), (200, ""));
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn values_with_spaces_and_quotes_survive_both_hops() {
+    let cluster = TestCluster::start(2).await;
+    let client = cluster.client().await;
+
+    let value = "say \"hi\" \\ to\tthe world";
+    assert_eq!(
+        client.put("quoted key", value, None).await.unwrap().code,
+        200
+    );
+
+    let res = client.get("quoted key").await.unwrap();
+    assert_eq!(res.code, 200);
+    assert_eq!(res.payload, value);
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn ttl_expires_entries() {
+    let cluster = TestCluster::start(1).await;
+    let client = cluster.client().await;
+
+    assert_eq!(client.put("short", "v", Some(50)).await.unwrap().code, 200);
+    assert_eq!(client.get("short").await.unwrap().payload, "v");
+
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    assert_eq!(client.get("short").await.unwrap().payload, "");
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn heartbeats_measure_the_node_clock_and_relative_ttls_expire_on_it() {
+    let config = MasterConfig {
+        relative_ttl: true,
+        ..MasterConfig::default()
+    };
+    let mut cluster = TestCluster::start_with_config(0, 0, &config).await;
+    cluster.set_heartbeat(Some(Duration::from_millis(20)));
+    let node_id = cluster
+        .add_node(NodeRole::Master)
+        .await
+        .node_id()
+        .to_string();
+
+    let skews = cluster.master.module.clock_skews.clone();
+    cluster
+        .wait_until(DEFAULT_TIMEOUT, || skews.estimate(&node_id).is_some())
+        .await;
+    // master y nodo leen el mismo reloj del sistema
+    assert!(skews.estimate(&node_id).unwrap().offset_ms.abs() < 50);
+
+    let client = cluster.client().await;
+    let stats = client
+        .request("STATS", &format!("\"{node_id}\""))
+        .await
+        .unwrap();
+    assert!(stats.values()[0].contains(" clock_skew_ms="), "{stats:?}");
+
+    assert_eq!(client.put("short", "v", Some(50)).await.unwrap().code, 200);
+    assert_eq!(client.get("short").await.unwrap().payload, "v");
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    assert_eq!(client.get("short").await.unwrap().payload, "");
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn ttl_accepts_an_explicit_unit_and_rejects_garbage() {
+    let cluster = TestCluster::start(1).await;
+    let client = cluster.client().await;
+
+    let res = client
+        .request("PUT", "\"unit\" \"v\" \"1s\"")
+        .await
+        .unwrap();
+    assert_eq!(res.code, 200);
+    assert_eq!(client.get("unit").await.unwrap().payload, "v");
+
+    // antes un TTL ilegible dejaba la clave sin expiración
+    let res = client
+        .request("PUT", "\"bad\" \"v\" \"10d\"")
+        .await
+        .unwrap();
+    assert_eq!(res.code, 400);
+    let res = client.request("PUT", "\"zero\" \"v\" \"0\"").await.unwrap();
+    assert_eq!(res.code, 400);
+    assert_eq!(client.get("bad").await.unwrap().payload, "");
+
+    tokio::time::sleep(Duration::from_millis(1_100)).await;
+    assert_eq!(client.get("unit").await.unwrap().payload, "");
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn writes_keep_working_after_a_shard_goes_down() {
+    let mut cluster = TestCluster::start(2).await;
+    let client = cluster.client().await;
+
+    cluster.stop_node(0).await;
+
+    for i in 0..10 {
+        let key = format!("after-{i}");
+        assert_eq!(client.put(&key, "v", None).await.unwrap().code, 200);
+        assert_eq!(client.get(&key).await.unwrap().payload, "v");
+    }
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn replica_joins_an_existing_shard() {
+    let mut cluster = TestCluster::start(1).await;
+    let replica = cluster.add_node(NodeRole::Replica).await;
+    let replica_id = replica.node_id().to_string();
+
+    assert!(
+        cluster
+            .master
+            .app_state
+            .network_state
+            .nodes_registry
+            .contains_key(replica_id.as_str())
+    );
+    assert_eq!(cluster.nodes().len(), 2);
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn dashboard_shows_shards_lag_and_hot_keys() {
+    let mut cluster = TestCluster::start(1).await;
+    let replica_id = cluster
+        .add_node(NodeRole::Replica)
+        .await
+        .node_id()
+        .to_string();
+
+    let client = cluster.client().await;
+    for i in 0..5 {
+        client.put(&format!("k{i}"), "v", None).await.unwrap();
+    }
+    for _ in 0..3 {
+        client.get("k0").await.unwrap();
+    }
+
+    // el lag de la réplica se registra cuando llega su confirmación, en segundo plano; cada
+    // nodo además manda un PING al master al conectarse
+    let module = cluster.master.module.clone();
+    cluster
+        .wait_until(DEFAULT_TIMEOUT, || {
+            module
+                .metrics
+                .replica_lag_histogram(&replica_id)
+                .is_some_and(|h| h.count() == 5)
+                && module.metrics.requests_total("PING", 200) == 2
+        })
+        .await;
+
+    let view = Dashboard::collect(&module);
+    assert_eq!(view.registered_nodes, 2);
+    assert_eq!(view.shards.len(), 1);
+
+    let nodes = &view.shards[0].nodes;
+    assert_eq!(nodes.len(), 2);
+    let replica = nodes.iter().find(|n| n.id == replica_id).unwrap();
+    assert_eq!(replica.role, DashboardRole::Replica);
+    assert!(replica.replica_lag_p99_ms.is_some());
+    assert!(nodes.iter().any(|n| n.role == DashboardRole::Master));
+
+    assert_eq!(view.hot_keys[0].key, "k0");
+    assert_eq!(view.hot_keys[0].hits, 4);
+    assert_eq!(view.throughput.requests, 5 + 3 + 2);
+    assert_eq!(view.throughput.per_second.len(), 60);
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn peek_reads_without_counting_as_a_hot_read() {
+    let cluster = TestCluster::start(2).await;
+    let client = cluster.client().await;
+    client.put("k", "valor", None).await.unwrap();
+
+    for _ in 0..3 {
+        let res = client.request("PEEK", "k").await.unwrap();
+        assert_eq!((res.code, res.payload.as_str()), (200, "valor"));
+    }
+    let res = client.request("PEEK", "nope").await.unwrap();
+    assert_eq!((res.code, res.payload.as_str()), (200, ""));
+
+    // solo cuenta el PUT
+    let view = Dashboard::collect(&cluster.master.module);
+    assert_eq!(view.hot_keys.len(), 1);
+    assert_eq!(view.hot_keys[0].hits, 1);
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn cluster_stats_merge_the_stats_of_every_shard() {
+    let cluster = TestCluster::start(2).await;
+    let client = cluster.client().await;
+    for key in ["k1", "k2", "k3"] {
+        client.put(key, "valor", None).await.unwrap();
+    }
+
+    let res = client.request("CLUSTER-STATS", "refresh").await.unwrap();
+    assert_eq!(res.code, 200);
+    let lines = res.values();
+    assert!(
+        lines[0].starts_with("keys=3 bytes=21 ") && lines[0].contains(" shards=2 "),
+        "{lines:?}"
+    );
+    assert_eq!(lines.len(), 3);
+    assert!(lines[1..].iter().all(|l| l.contains(" capacity=1024 ")));
+
+    let metrics = cluster.master.module.render_metrics();
+    assert!(metrics.contains("cache_master_cluster_keys 3\n"));
+    assert_eq!(metrics.matches("cache_master_shard_keys{shard=").count(), 2);
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn puts_are_refused_while_the_shard_is_over_its_memory_watermark() {
+    let mut cluster = TestCluster::start(0).await;
+    // 10 claves de 7 bytes lo llenan
+    cluster.set_max_memory_bytes(Some(70));
+    cluster.add_node(NodeRole::Master).await;
+    let client = cluster.client().await;
+    for i in 0..10 {
+        assert_eq!(
+            client
+                .put(&format!("k{i}"), "valor", None)
+                .await
+                .unwrap()
+                .code,
+            200
+        );
+    }
+
+    client.request("CLUSTER-STATS", "refresh").await.unwrap();
+    let res = client.put("otra", "valor", None).await.unwrap();
+    assert_eq!(res.code, 503, "{}", res.payload);
+    assert_eq!(client.get("k0").await.unwrap().payload, "valor");
+
+    // 49 de 70: bajo la marca baja
+    let res = client
+        .request("MULTI", "\"DEL k0\" \"DEL k1\" \"DEL k2\"")
+        .await
+        .unwrap();
+    assert_eq!(res.code, 200, "{}", res.payload);
+    client.request("CLUSTER-STATS", "refresh").await.unwrap();
+    assert_eq!(client.put("otra", "valor", None).await.unwrap().code, 200);
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn node_config_resizes_a_node_and_moves_its_memory_cap_live() {
+    let cluster = TestCluster::start(1).await;
+    let node_id = cluster.nodes()[0].node_id().to_string();
+    let client = cluster.client().await;
+    for i in 0..10 {
+        client.put(&format!("k{i}"), "valor", None).await.unwrap();
+    }
+
+    let res = client
+        .request("NODE-CONFIG", "\"*\" \"SET\" \"capacity\" \"4\"")
+        .await
+        .unwrap();
+    assert_eq!(
+        (res.code, res.payload),
+        (200, format!("{node_id}=capacity=4 evicted=6"))
+    );
+
+    // con un tope que ya está pasado, el master frena los PUT sin esperar otro STATS
+    let res = client
+        .request(
+            "NODE-CONFIG",
+            &format!("\"{node_id}\" \"SET\" \"max-memory\" \"20\""),
+        )
+        .await
+        .unwrap();
+    assert_eq!((res.code, res.payload.as_str()), (200, "max-memory=20"));
+    assert_eq!(client.put("otra", "valor", None).await.unwrap().code, 503);
+
+    let res = client
+        .request("NODE-CONFIG", &format!("\"{node_id}\""))
+        .await
+        .unwrap();
+    assert_eq!(
+        (res.code, res.payload.as_str()),
+        (200, "capacity=4 max-memory=20")
+    );
+
+    let res = client
+        .request(
+            "NODE-CONFIG",
+            &format!("\"{node_id}\" \"SET\" \"capacity\" \"0\""),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.code, 400, "{}", res.payload);
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn read_only_rejects_writes_and_keeps_serving_reads() {
+    let cluster = TestCluster::start(1).await;
+    let client = cluster.client().await;
+    client.put("k", "valor", None).await.unwrap();
+
+    let res = client.request("READ-ONLY", "on").await.unwrap();
+    assert_eq!((res.code, res.payload.as_str()), (200, "cluster=on nodes="));
+    assert_eq!(client.put("k", "otro", None).await.unwrap().code, 423);
+    let res = client.request("MULTI", "\"DEL k\"").await.unwrap();
+    assert_eq!(res.code, 423, "{}", res.payload);
+    assert_eq!(
+        client.request("INVALIDATE-TAG", "t").await.unwrap().code,
+        423
+    );
+    assert_eq!(client.get("k").await.unwrap().payload, "valor");
+
+    client.request("READ-ONLY", "off").await.unwrap();
+    assert_eq!(client.put("k", "otro", None).await.unwrap().code, 200);
+
+    // un nodo en solo lectura frena a su shard
+    let node_id = cluster.nodes()[0].node_id().to_string();
+    let res = client
+        .request("READ-ONLY", &format!("on \"{node_id}\""))
+        .await
+        .unwrap();
+    assert_eq!(res.payload, format!("cluster=off nodes={node_id}"));
+    assert_eq!(client.put("k", "tercero", None).await.unwrap().code, 423);
+    assert_eq!(client.get("k").await.unwrap().payload, "otro");
+
+    let res = client.request("READ-ONLY", "on desconocido").await.unwrap();
+    assert_ne!(res.code, 200);
+    let res = client.request("READ-ONLY", "quizas").await.unwrap();
+    assert_eq!(res.code, 400);
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn backup_and_restore_bring_the_cluster_back() {
+    let dir = std::env::temp_dir().join(format!("cluster-backups-{}", fastrand::u64(..)));
+    let config = MasterConfig {
+        backup_target: Some(BackupTarget::Dir(dir.clone())),
+        ..MasterConfig::default()
+    };
+    let cluster = TestCluster::start_with_config(2, 0, &config).await;
+    let client = cluster.client().await;
+
+    for i in 0..10 {
+        client.put(&format!("k{i}"), "valor", None).await.unwrap();
+    }
+    let res = client
+        .request("PUT", "\"etiquetada\" \"v\" \"tags=t\"")
+        .await
+        .unwrap();
+    assert_eq!(res.code, 200, "{}", res.payload);
+    client.put("efimera", "v", Some(150)).await.unwrap();
+
+    let res = client.request("BACKUP", "").await.unwrap();
+    assert_eq!(res.code, 200, "{}", res.payload);
+    let manifest = res.values();
+    assert!(
+        manifest[0].contains("entries=12 shards=2 complete=true"),
+        "{manifest:?}"
+    );
+    let id = manifest[0]
+        .split_whitespace()
+        .find_map(|f| f.strip_prefix("id="))
+        .unwrap()
+        .to_string();
+    let listed = client.request("BACKUP", "list").await.unwrap().values();
+    assert_eq!(listed, [id.as_str()]);
+
+    // se pierde todo y vence la clave con TTL
+    for node in cluster.nodes() {
+        let cache = &node.handle.module.cache;
+        for op in cache.snapshot() {
+            if let Op::Put { key, .. } = op {
+                cache.remove(&key).await;
+            }
+        }
+    }
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(client.get("k0").await.unwrap().payload, "");
+
+    let res = client
+        .request("RESTORE", &format!("\"{id}\""))
+        .await
+        .unwrap();
+    assert_eq!(res.code, 200, "{}", res.payload);
+    assert_eq!(
+        res.values(),
+        [format!("id={id} restored=11 expired=1 failed=0")]
+    );
+    for i in 0..10 {
+        assert_eq!(client.get(&format!("k{i}")).await.unwrap().payload, "valor");
+    }
+    assert_eq!(client.get("efimera").await.unwrap().payload, "");
+    // los tags vuelven con la entrada
+    client.request("INVALIDATE-TAG", "t").await.unwrap();
+    assert_eq!(client.get("etiquetada").await.unwrap().payload, "");
+
+    assert_eq!(
+        client
+            .request("RESTORE", "\"no-existe\"")
+            .await
+            .unwrap()
+            .code,
+        404
+    );
+
+    cluster.shutdown().await;
+    let _ = tokio::fs::remove_dir_all(&dir).await;
+}
+
+#[tokio::test]
+async fn import_loads_ndjson_exports_and_redis_dumps() {
+    let dir = std::env::temp_dir().join(format!("cluster-imports-{}", fastrand::u64(..)));
+    std::fs::create_dir_all(&dir).unwrap();
+    let ndjson = [
+        r#"{"key": "a", "value": "uno"}"#,
+        r#"{"key": "b", "value": "dos", "ttl_ms": 60000, "tags": ["t"]}"#,
+        r#"{"key": "c", "value": "tres", "expires_at": 1}"#,
+        "no es json",
+    ];
+    std::fs::write(dir.join("export.ndjson"), ndjson.join("\n")).unwrap();
+    // REDIS0011, base 0, "r" => "rdb" y fin
+    let mut rdb = b"REDIS0011\xFE\x00\x00\x01r\x03rdb\xFF".to_vec();
+    rdb.extend_from_slice(&[0; 8]);
+    std::fs::write(dir.join("dump.rdb"), rdb).unwrap();
+
+    let config = MasterConfig {
+        import_dir: Some(dir.clone()),
+        ..MasterConfig::default()
+    };
+    let cluster = TestCluster::start_with_config(2, 0, &config).await;
+    let client = cluster.client().await;
+
+    let res = client.request("IMPORT", "export.ndjson").await.unwrap();
+    assert_eq!(res.code, 200, "{}", res.payload);
+    let report = res.values();
+    assert_eq!(
+        report[0],
+        "file=export.ndjson format=ndjson imported=2 expired=1 skipped=0 failed=1"
+    );
+    assert!(report[1].starts_with("error=línea 4:"), "{report:?}");
+    assert_eq!(client.get("a").await.unwrap().payload, "uno");
+    assert_eq!(client.get("b").await.unwrap().payload, "dos");
+    assert_eq!(client.get("c").await.unwrap().payload, "");
+    client.request("INVALIDATE-TAG", "t").await.unwrap();
+    assert_eq!(client.get("b").await.unwrap().payload, "");
+
+    let res = client.request("IMPORT", "dump.rdb").await.unwrap();
+    assert_eq!(
+        res.values(),
+        ["file=dump.rdb format=rdb imported=1 expired=0 skipped=0 failed=0"]
+    );
+    assert_eq!(client.get("r").await.unwrap().payload, "rdb");
+
+    for bad in ["../dump.rdb", "no-existe"] {
+        let res = client.request("IMPORT", bad).await.unwrap();
+        assert_ne!(res.code, 200, "{bad}");
+    }
+
+    cluster.shutdown().await;
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn backup_without_a_target_is_refused() {
+    let cluster = TestCluster::start(1).await;
+    let client = cluster.client().await;
+
+    assert_eq!(client.request("BACKUP", "").await.unwrap().code, 400);
+    assert_eq!(client.request("RESTORE", "").await.unwrap().code, 400);
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn set_role_is_forwarded_to_the_node() {
+    let mut cluster = TestCluster::start(1).await;
+    let replica = cluster.add_node(NodeRole::Replica).await;
+    let replica_id = replica.node_id().to_string();
+    let role = replica.handle.module.role.clone();
+
+    let client = cluster.client().await;
+    let res = client
+        .request("SET-ROLE", &format!("\"{replica_id}\" \"MASTER\""))
+        .await
+        .unwrap();
+    assert_eq!((res.code, res.payload.as_str()), (200, "MASTER"));
+    assert_eq!(
+        role.get(),
+        cache_node::core::domain::models::NodeRole::Master
+    );
+
+    let res = client
+        .request("SET-ROLE", &format!("\"{replica_id}\" \"LIDER\""))
+        .await
+        .unwrap();
+    assert_eq!(res.code, 400);
+
+    let res = client
+        .request("SET-ROLE", "\"no-existe\" \"MASTER\"")
+        .await
+        .unwrap();
+    assert_ne!(res.code, 200);
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn a_node_with_an_extra_group_leads_one_shard_and_replicates_another() {
+    let mut cluster = TestCluster::start(1).await;
+    let first_id = cluster.nodes()[0].node_id().to_string();
+    cluster.set_extra_groups(vec![NodeRole::Replica]);
+    let node = cluster.add_node(NodeRole::Master).await;
+    let node_id = node.node_id().to_string();
+    let groups = node.handle.groups.clone();
+    let replica_id = format!("{node_id}+1");
+
+    // la réplica no va al shard que el mismo proceso ya sirve, aunque tenga menos réplicas
+    cluster
+        .wait_until(DEFAULT_TIMEOUT, || {
+            groups.group_of(&node_id).is_some() && groups.group_of(&replica_id).is_some()
+        })
+        .await;
+    assert_eq!(groups.group_of(&node_id).as_deref(), Some(node_id.as_str()));
+    let replicas = groups.group(&first_id);
+    assert_eq!(replicas.len(), 1);
+    assert_eq!(&*replicas[0].member_id, replica_id.as_str());
+    assert_eq!(
+        replicas[0].module.role.get(),
+        cache_node::core::domain::models::NodeRole::Replica
+    );
+
+    // cada grupo tiene su cache: la réplica copia el shard del otro nodo y nada del propio
+    let client = cluster.client().await;
+    for i in 0..20 {
+        client.put(&format!("k{i}"), "valor", None).await.unwrap();
+    }
+    let first = cluster.nodes()[0].handle.module.cache.clone();
+    let own = groups.members()[0].module.cache.clone();
+    let replica = replicas[0].module.cache.clone();
+    cluster
+        .wait_until(DEFAULT_TIMEOUT, || {
+            replica.stats().entries == first.stats().entries
+        })
+        .await;
+    assert_eq!(first.stats().entries + own.stats().entries, 20);
+    assert!(first.stats().entries > 0 && own.stats().entries > 0);
+
+    cluster.shutdown().await;
+}
+
+/// Próximo `EVT TOPOLOGY`, ya interpretado.
+async fn next_topology(client: &TestClient) -> TopologyEvent {
+    let (name, payload) = client
+        .next_event(DEFAULT_TIMEOUT)
+        .await
+        .expect("no llegó el EVT TOPOLOGY");
+    assert_eq!(name, "TOPOLOGY");
+    payload.parse().unwrap()
+}
+
+#[tokio::test]
+async fn subscribed_clients_hear_about_nodes_and_shards_coming_and_going() {
+    let mut cluster = TestCluster::start(1).await;
+    let first_id = cluster.nodes()[0].node_id().to_string();
+    let feed = cluster.master.module.topology_feed.clone();
+    cluster
+        .wait_until(DEFAULT_TIMEOUT, || feed.epoch() == 1)
+        .await;
+
+    let client = cluster.client().await;
+    let res = client.request("SUBSCRIBE", "\"TOPOLOGY\"").await.unwrap();
+    assert_eq!((res.code, res.payload.as_str()), (200, "1"));
+    let res = client.request("SUBSCRIBE", "\"KEYS\"").await.unwrap();
+    assert_eq!(res.code, 400, "{}", res.payload);
+
+    let replica_id = cluster
+        .add_node(NodeRole::Replica)
+        .await
+        .node_id()
+        .to_string();
+    let event = next_topology(&client).await;
+    assert_eq!(event.epoch, 2);
+    assert_eq!(
+        event.change,
+        TopologyChange::NodeJoined {
+            node_id: replica_id.clone(),
+            shard_id: first_id.clone(),
+        }
+    );
+    assert!(!event.change.moves_ownership());
+
+    let second_id = cluster
+        .add_node(NodeRole::Master)
+        .await
+        .node_id()
+        .to_string();
+    let event = next_topology(&client).await;
+    assert_eq!(
+        event.change,
+        TopologyChange::ShardAdded {
+            shard_id: second_id.clone()
+        }
+    );
+    assert!(event.change.moves_ownership());
+
+    cluster.stop_node(1).await;
+    assert_eq!(
+        next_topology(&client).await.change,
+        TopologyChange::NodeLeft {
+            node_id: replica_id
+        }
+    );
+
+    // el último nodo de un shard se lleva el shard
+    cluster.stop_node(1).await;
+    assert_eq!(
+        next_topology(&client).await.change,
+        TopologyChange::NodeLeft {
+            node_id: second_id.clone()
+        }
+    );
+    let event = next_topology(&client).await;
+    assert_eq!(
+        (event.epoch, event.change),
+        (
+            6,
+            TopologyChange::ShardRemoved {
+                shard_id: second_id
+            }
+        )
+    );
+
+    let res = client.request("UNSUBSCRIBE", "\"TOPOLOGY\"").await.unwrap();
+    assert_eq!(res.code, 200);
+    cluster.add_node(NodeRole::Master).await;
+    assert!(
+        client
+            .next_event(Duration::from_millis(100))
+            .await
+            .is_none()
+    );
+    assert_eq!(feed.subscribers(), 0);
+
+    cluster.shutdown().await;
+}
+
+async fn cluster_map(client: &TestClient) -> ClusterMap {
+    let res = client.request("CLUSTER-MAP", "").await.unwrap();
+    assert_eq!(res.code, 200, "{}", res.payload);
+    res.payload.parse().unwrap()
+}
+
+#[tokio::test]
+async fn a_cluster_map_kept_with_topology_events_matches_a_fresh_one() {
+    let mut cluster = TestCluster::start(2).await;
+    let feed = cluster.master.module.topology_feed.clone();
+    cluster
+        .wait_until(DEFAULT_TIMEOUT, || feed.epoch() == 2)
+        .await;
+
+    let client = cluster.client().await;
+    client.request("SUBSCRIBE", "\"TOPOLOGY\"").await.unwrap();
+    let mut map = cluster_map(&client).await;
+    assert_eq!((map.epoch, map.shard_count(), map.node_count()), (2, 2, 2));
+
+    let replica_id = cluster
+        .add_node(NodeRole::Replica)
+        .await
+        .node_id()
+        .to_string();
+    assert_eq!(map.apply(&next_topology(&client).await), MapUpdate::Applied);
+    assert!(map.shard_of(&replica_id).is_some());
+
+    // si la réplica no quedó en su shard, el primario se lleva el shard al irse
+    let first_id = cluster.nodes()[0].node_id().to_string();
+    let last_epoch = if map.shard_of(&replica_id) == Some(first_id.as_str()) {
+        4
+    } else {
+        5
+    };
+    cluster.stop_node(0).await;
+    while map.epoch < last_epoch {
+        assert_eq!(map.apply(&next_topology(&client).await), MapUpdate::Applied);
+    }
+    assert_eq!(map, cluster_map(&client).await);
+
+    cluster.shutdown().await;
+}
+
+/// Payload del próximo `EVT MONITOR` con la acción `action`.
+async fn next_monitored(client: &TestClient, action: &str) -> String {
+    loop {
+        let (name, payload) = client
+            .next_event(DEFAULT_TIMEOUT)
+            .await
+            .unwrap_or_else(|| panic!("no llegó ningún {action}"));
+        assert_eq!(name, MONITOR);
+        if MonitorEntry::parse(&payload).unwrap().action == action {
+            return payload;
+        }
+    }
+}
+
+#[tokio::test]
+async fn monitor_streams_the_commands_of_the_master_and_of_a_node() {
+    let cluster = TestCluster::start(1).await;
+    let node_id = cluster.nodes()[0].node_id().to_string();
+
+    let master_watcher = cluster.client().await;
+    let res = master_watcher.request(MONITOR, "secs=30").await.unwrap();
+    assert_eq!((res.code, res.payload.as_str()), (200, "OK"));
+    let node_watcher = cluster.client().await;
+    let res = node_watcher
+        .request(MONITOR, &format!("\"{node_id}\" redact"))
+        .await
+        .unwrap();
+    assert_eq!((res.code, res.payload.as_str()), (200, "OK"));
+
+    let client = cluster.client().await;
+    client.put("k", "secreto", None).await.unwrap();
+
+    let seen = next_monitored(&master_watcher, "PUT").await;
+    let entry = MonitorEntry::parse(&seen).unwrap();
+    assert_eq!(
+        (&*entry.source, &*entry.peer),
+        ("master", client.id.as_str())
+    );
+    assert_eq!(entry.payload, "k secreto");
+
+    // el nodo recibe el PUT del master; con `redact` el valor no sale de la clave
+    let seen = next_monitored(&node_watcher, "PUT").await;
+    let entry = MonitorEntry::parse(&seen).unwrap();
+    assert_eq!(entry.source, node_id);
+    assert!(entry.payload.starts_with("k "), "{seen}");
+    assert!(!seen.contains("secreto"), "{seen}");
+
+    let res = client.request(MONITOR, "sample=3").await.unwrap();
+    assert_eq!(res.code, 400);
+    let res = client.request(MONITOR, "\"no-existe\"").await.unwrap();
+    assert_ne!(res.code, 200);
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn slowlog_on_the_master_reads_the_slow_commands_of_a_node() {
+    let mut cluster = TestCluster::start(0).await;
+    // umbral 0: todo comando cuenta como lento
+    cluster.set_slow_log(SlowLogConfig {
+        threshold: Duration::ZERO,
+        max_len: 4,
+    });
+    let node_id = cluster
+        .add_node(NodeRole::Master)
+        .await
+        .node_id()
+        .to_string();
+
+    let client = cluster.client().await;
+    client.put("k", "secreto", None).await.unwrap();
+
+    let res = client
+        .request("SLOWLOG", &format!("\"{node_id}\" GET 10"))
+        .await
+        .unwrap();
+    assert_eq!(res.code, 200);
+    let put = res
+        .values()
+        .into_iter()
+        .find(|entry| entry.contains("action=PUT"))
+        .unwrap_or_else(|| panic!("sin PUT: {}", res.payload));
+    assert!(put.ends_with("args=k \"<7 bytes>\""), "{put}");
+
+    let res = client
+        .request("SLOWLOG", &format!("\"{node_id}\" LEN"))
+        .await
+        .unwrap();
+    assert!(res.integer().unwrap() >= 1);
+    let res = client.request("SLOWLOG", "\"no-existe\"").await.unwrap();
+    assert_ne!(res.code, 200);
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn topkeys_on_the_master_reads_the_keys_a_node_serves_most() {
+    let mut cluster = TestCluster::start(0).await;
+    cluster.set_top_keys(TopKeysConfig {
+        capacity: 4,
+        sample: 1,
+    });
+    let node_id = cluster
+        .add_node(NodeRole::Master)
+        .await
+        .node_id()
+        .to_string();
+
+    let client = cluster.client().await;
+    client.put("caliente", "v", None).await.unwrap();
+    client.put("fria", "v", None).await.unwrap();
+    for _ in 0..3 {
+        client.get("caliente").await.unwrap();
+    }
+
+    let res = client
+        .request("TOPKEYS", &format!("\"{node_id}\" GET 1"))
+        .await
+        .unwrap();
+    assert_eq!(res.code, 200);
+    assert_eq!(
+        res.values(),
+        vec!["count=4 reads=3 writes=1 error=0 key=caliente"]
+    );
+
+    let res = client
+        .request("TOPKEYS", &format!("\"{node_id}\" LEN"))
+        .await
+        .unwrap();
+    assert_eq!(res.integer().unwrap(), 2);
+    let res = client.request("TOPKEYS", "\"no-existe\"").await.unwrap();
+    assert_ne!(res.code, 200);
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn multi_applies_a_watched_read_modify_write_on_the_shard() {
+    let mut cluster = TestCluster::start(1).await;
+    let replica_cache = cluster
+        .add_node(NodeRole::Replica)
+        .await
+        .handle
+        .module
+        .cache
+        .clone();
+    let client = cluster.client().await;
+    client.put("saldo", "10", None).await.unwrap();
+
+    let res = client
+        .request("MULTI", "\"VERSION saldo\" \"GET saldo\"")
+        .await
+        .unwrap();
+    assert_eq!(res.code, 200);
+    let read = res.values();
+    assert_eq!(read[1], "10");
+
+    let write = format!("\"WATCH saldo {}\" \"PUT saldo 15\" \"GET saldo\"", read[0]);
+    let res = client.request("MULTI", &write).await.unwrap();
+    assert_eq!(res.values(), vec!["OK", "15"]);
+
+    // la versión ya cambió: no se aplica nada
+    let res = client
+        .request("MULTI", &format!("{write} \"DEL otra\""))
+        .await
+        .unwrap();
+    assert_eq!(res.code, 409, "{}", res.payload);
+    assert_eq!(client.get("saldo").await.unwrap().payload, "15");
+
+    // la réplica recibe las escrituras en segundo plano
+    let replicated = tokio::time::timeout(DEFAULT_TIMEOUT, async {
+        while replica_cache.get("saldo").await.as_deref() != Some("15") {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await;
+    assert!(replicated.is_ok());
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn conditional_gets_skip_the_value_while_the_version_holds() {
+    let mut cluster = TestCluster::start(1).await;
+    cluster.add_node(NodeRole::Replica).await;
+    let client = cluster.client().await;
+    client.put("grande", "v1", None).await.unwrap();
+
+    let res = client
+        .request("GET", "grande IF-NOT-VERSION 0")
+        .await
+        .unwrap();
+    assert_eq!(res.payload, "v1");
+    let version = res.version.expect("el GET condicional trae la versión");
+
+    let unchanged = format!("grande IF-NOT-VERSION {version}");
+    let res = client.request("GET", &unchanged).await.unwrap();
+    assert!(res.is_not_modified(), "{} {}", res.code, res.payload);
+    assert_eq!((res.payload.as_str(), res.version), ("", Some(version)));
+
+    client.put("grande", "v2", None).await.unwrap();
+    let res = client.request("GET", &unchanged).await.unwrap();
+    assert_eq!(res.payload, "v2");
+    assert!(res.version > Some(version));
+
+    // sin la condición el GET sigue como siempre
+    let res = client.request("GET", "grande").await.unwrap();
+    assert_eq!((res.payload.as_str(), res.version), ("v2", None));
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn conditional_puts_fail_with_412_when_the_entry_changed() {
+    let cluster = TestCluster::start(1).await;
+    let client = cluster.client().await;
+
+    let put = |payload: &'static str| client.request("PUT", payload);
+    assert_eq!(put("k v1 IF absent").await.unwrap().code, 200);
+    let res = put("k otro IF absent").await.unwrap();
+    assert_eq!(res.code, 412, "{}", res.payload);
+
+    assert_eq!(put("k v2 30s IF version=1").await.unwrap().code, 200);
+    assert_eq!(put("k v3 IF version=1").await.unwrap().code, 412);
+    assert_eq!(put("k v3 IF \"value==v2\"").await.unwrap().code, 200);
+    assert_eq!(client.get("k").await.unwrap().payload, "v3");
+
+    assert_eq!(put("k v4 IF casi").await.unwrap().code, 400);
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn invalidate_tag_removes_the_tagged_keys_of_every_shard() {
+    let cluster = TestCluster::start(3).await;
+    let client = cluster.client().await;
+
+    for i in 0..8 {
+        let res = client
+            .request("PUT", &format!("k{i} v tags=grp,user:{i}"))
+            .await
+            .unwrap();
+        assert_eq!(res.code, 200, "{}", res.payload);
+    }
+    client.put("suelta", "v", None).await.unwrap();
+    // reescribir sin tags los saca del índice
+    client.put("k7", "otro", None).await.unwrap();
+
+    let res = client.request("INVALIDATE-TAG", "grp").await.unwrap();
+    assert_eq!((res.code, res.payload.as_str()), (200, "7"));
+    for i in 0..7 {
+        let res = client.get(&format!("k{i}")).await.unwrap();
+        assert_eq!((res.code, res.payload.as_str()), (200, ""), "k{i}");
+    }
+    assert_eq!(client.get("k7").await.unwrap().payload, "otro");
+    assert_eq!(client.get("suelta").await.unwrap().payload, "v");
+
+    let res = client.request("INVALIDATE-TAG", "grp").await.unwrap();
+    assert_eq!(res.payload, "0");
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn del_prefix_counts_with_a_dry_run_and_clears_the_group_in_every_shard() {
+    let mut cluster = TestCluster::start(3).await;
+    let replica_cache = cluster
+        .add_node(NodeRole::Replica)
+        .await
+        .handle
+        .module
+        .cache
+        .clone();
+    let client = cluster.client().await;
+
+    for i in 0..12 {
+        client.put(&format!("tmp:{i}"), "v", None).await.unwrap();
+    }
+    client.put("tmpx", "v", None).await.unwrap();
+
+    let res = client.request("DEL-PREFIX", "tmp: dry-run").await.unwrap();
+    assert_eq!((res.code, res.payload.as_str()), (200, "12"));
+    assert_eq!(client.get("tmp:0").await.unwrap().payload, "v");
+
+    let res = client.request("DEL-PREFIX", "tmp:").await.unwrap();
+    assert_eq!((res.code, res.payload.as_str()), (200, "12"));
+    for i in 0..12 {
+        let res = client.get(&format!("tmp:{i}")).await.unwrap();
+        assert_eq!(res.payload, "", "tmp:{i}");
+    }
+    assert_eq!(client.get("tmpx").await.unwrap().payload, "v");
+    assert_eq!(
+        client.request("DEL-PREFIX", "tmp:").await.unwrap().payload,
+        "0"
+    );
+    assert_eq!(
+        client.request("DEL-PREFIX", "\"\"").await.unwrap().code,
+        400
+    );
+
+    // la réplica borra lo mismo
+    let cleared = tokio::time::timeout(DEFAULT_TIMEOUT, async {
+        while replica_cache.count_prefix("tmp:").await > 0 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await;
+    assert!(cleared.is_ok());
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn a_get_near_expiry_picks_a_single_refresher_per_write() {
+    let cluster = TestCluster::start(1).await;
+    let client = cluster.client().await;
+
+    client.put("k", "v", Some(1_000)).await.unwrap();
+    // con una ventana de una hora, a un segundo del vencimiento la chance es casi 1
+    let mut picked = 0;
+    for _ in 0..20 {
+        let res = client.request("GET", "k refresh=1h").await.unwrap();
+        match res.values().as_slice() {
+            [value, refresh] if value == "v" => picked += usize::from(refresh == "1"),
+            other => panic!("respuesta inesperada {other:?}"),
+        }
+    }
+    assert_eq!(picked, 1);
+
+    // fuera de la ventana nadie refresca; la reescritura vuelve a habilitarlo
+    client.put("k", "v2", Some(60_000)).await.unwrap();
+    let res = client.request("GET", "k refresh=1s").await.unwrap();
+    assert_eq!(res.values(), ["v2", "0"]);
+
+    assert_eq!(client.get("k").await.unwrap().payload, "v2");
+    let res = client.request("GET", "falta refresh=1s").await.unwrap();
+    assert_eq!((res.code, res.payload.as_str()), (200, ""));
+    assert_eq!(
+        client.request("GET", "k refresh=0").await.unwrap().code,
+        400
+    );
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn concurrent_puts_are_applied_in_batches_when_the_node_batches_writes() {
+    let mut cluster = TestCluster::start(0).await;
+    cluster.set_write_batching(Some(WriteBatching {
+        window: Duration::from_millis(2),
+        max_ops: 16,
+    }));
+    cluster.add_node(NodeRole::Master).await;
+    let client = Arc::new(cluster.client().await);
+
+    let puts: Vec<_> = (0..40)
+        .map(|i| {
+            let client = client.clone();
+            tokio::spawn(async move { client.put(&format!("k{i}"), "v", None).await })
+        })
+        .collect();
+    for put in puts {
+        assert_eq!(put.await.unwrap().unwrap().code, 200);
+    }
+
+    for i in 0..40 {
+        assert_eq!(client.get(&format!("k{i}")).await.unwrap().payload, "v");
+    }
+    let batcher = cluster.nodes()[0]
+        .handle
+        .module
+        .write_batcher
+        .clone()
+        .unwrap();
+    let (batches, batched) = batcher.counts();
+    assert_eq!(batched, 40);
+    assert!(batches < 40, "{batches} lotes para 40 PUT");
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn stale_reads_serve_expired_values_within_the_grace_and_pick_one_refresher() {
+    let mut cluster = TestCluster::start(0).await;
+    cluster.set_stale_grace(Duration::from_secs(60));
+    cluster.add_node(NodeRole::Master).await;
+    let client = cluster.client().await;
+
+    client.put("k", "v", Some(50)).await.unwrap();
+    let res = client.request("GET", "k stale=1h").await.unwrap();
+    assert_eq!(res.values(), ["v", "0", "0"]);
+
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    // vencida: la lectura normal no la ve, la tolerante sí y solo uno refresca
+    assert_eq!(client.get("k").await.unwrap().payload, "");
+    let mut picked = 0;
+    for _ in 0..5 {
+        let res = client.request("GET", "k stale=1h").await.unwrap();
+        match res.values().as_slice() {
+            [value, refresh, stale_ms] if value == "v" => {
+                assert!(stale_ms.parse::<u64>().unwrap() >= 100);
+                picked += usize::from(refresh == "1");
+            }
+            other => panic!("respuesta inesperada {other:?}"),
+        }
+    }
+    assert_eq!(picked, 1);
+
+    let res = client.request("GET", "k stale=10ms").await.unwrap();
+    assert_eq!((res.code, res.payload.as_str()), (200, ""));
+    assert_eq!(
+        client
+            .request("GET", "k refresh=1s stale=1s")
+            .await
+            .unwrap()
+            .code,
+        400
+    );
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn hot_keys_are_read_from_copies_until_they_are_written() {
+    let cluster = TestCluster::start(3).await;
+    cluster.start_hot_keys(HotKeyConfig {
+        min_reads: 20,
+        factor: 2.0,
+        interval: Duration::from_millis(20),
+        ..HotKeyConfig::default()
+    });
+    let client = cluster.client().await;
+
+    client.put("viral", "v1", None).await.unwrap();
+    client.put("tibia", "x", None).await.unwrap();
+    client.get("tibia").await.unwrap();
+    for _ in 0..30 {
+        client.get("viral").await.unwrap();
+    }
+
+    let network = cluster.master.module.tcp_network_service.clone();
+    cluster
+        .wait_until(DEFAULT_TIMEOUT, || network.hot_key_copies().len() == 1)
+        .await;
+    // el dueño y dos shards más
+    let mut holders = 0;
+    for node in cluster.nodes() {
+        holders += usize::from(node.handle.module.cache.get("viral").await.is_some());
+    }
+    assert_eq!(holders, 3);
+
+    for _ in 0..6 {
+        assert_eq!(client.get("viral").await.unwrap().payload, "v1");
+    }
+
+    // la escritura deja de leer las copias antes de aplicarse
+    client.put("viral", "v2", None).await.unwrap();
+    for _ in 0..6 {
+        assert_eq!(client.get("viral").await.unwrap().payload, "v2");
+    }
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn multi_rejects_keys_of_different_shards() {
+    let cluster = TestCluster::start(3).await;
+    let client = cluster.client().await;
+
+    let mut rejected = None;
+    for i in 1..64 {
+        let res = client
+            .request("MULTI", &format!("\"PUT k0 v\" \"PUT k{i} v\""))
+            .await
+            .unwrap();
+        if res.code != 200 {
+            rejected = Some((i, res));
+            break;
+        }
+    }
+    let (i, res) = rejected.expect("todas las claves cayeron en el mismo shard");
+    assert_eq!(res.code, 400, "{}", res.payload);
+    // el lote se rechaza entero
+    let res = client.get(&format!("k{i}")).await.unwrap();
+    assert_eq!((res.code, res.payload.as_str()), (200, ""));
+
+    let res = client.request("MULTI", "\"INCR k0\"").await.unwrap();
+    assert_eq!(res.code, 400);
+
+    cluster.shutdown().await;
+}
+
+/// Shard dueño de `key` según `EXPLAIN`.
+async fn owner_of(client: &TestClient, key: &str) -> String {
+    let res = client.request("EXPLAIN", key).await.unwrap();
+    res.payload
+        .split(' ')
+        .find_map(|field| field.strip_prefix("owner="))
+        .expect("EXPLAIN sin owner")
+        .to_string()
+}
+
+#[tokio::test]
+async fn rename_moves_keys_within_and_across_shards_without_overwriting() {
+    let cluster = TestCluster::start(2).await;
+    let client = cluster.client().await;
+
+    let home = owner_of(&client, "origen").await;
+    let mut same = None;
+    let mut other = None;
+    for i in 0..64 {
+        let key = format!("destino{i}");
+        if owner_of(&client, &key).await == home {
+            same.get_or_insert(key);
+        } else {
+            other.get_or_insert(key);
+        }
+    }
+    let (same, other) = (same.unwrap(), other.unwrap());
+
+    client.put("origen", "v", Some(60_000)).await.unwrap();
+    client.put("ocupada", "x", None).await.unwrap();
+    let res = client.request("RENAME", "origen ocupada").await.unwrap();
+    assert_eq!(res.code, 409, "{}", res.payload);
+    assert_eq!(client.get("ocupada").await.unwrap().payload, "x");
+
+    // mismo shard: lo mueve el nodo, con su vencimiento
+    let res = client
+        .request("RENAME", &format!("origen {same}"))
+        .await
+        .unwrap();
+    assert_eq!(res.code, 200, "{}", res.payload);
+    assert_eq!(client.get("origen").await.unwrap().payload, "");
+    assert_eq!(client.get(&same).await.unwrap().payload, "v");
+    let meta = client.request("META", &same).await.unwrap();
+    assert!(!meta.payload.contains("expires_at=-"), "{}", meta.payload);
+
+    // a otro shard: copia y borrado a través del master
+    let res = client
+        .request("RENAME", &format!("{same} {other}"))
+        .await
+        .unwrap();
+    assert_eq!(res.code, 200, "{}", res.payload);
+    assert_eq!(client.get(&same).await.unwrap().payload, "");
+    assert_eq!(client.get(&other).await.unwrap().payload, "v");
+
+    let res = client
+        .request("RENAME", &format!("{same} {other}"))
+        .await
+        .unwrap();
+    assert_eq!(res.code, 404, "{}", res.payload);
+    let res = client
+        .request("RENAME", &format!("ocupada {other}"))
+        .await
+        .unwrap();
+    assert_eq!(res.code, 409, "{}", res.payload);
+    assert_eq!(client.get("ocupada").await.unwrap().payload, "x");
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn a_node_evicting_with_a_full_cache_reports_its_shard_as_undersized() {
+    let mut cluster = TestCluster::start(0).await;
+    cluster.set_pressure_report(Some(Duration::from_millis(50)));
+    cluster.add_node(NodeRole::Master).await;
+
+    // directo al cache del nodo: por el master serían miles de round-trips
+    let node = &cluster.nodes()[0];
+    let node_id = node.node_id().to_string();
+    let cache = node.handle.module.cache.clone();
+    let capacity = cache.stats().capacity;
+    // sigue escribiendo para que algún intervalo reportado tenga evicciones
+    let writer = tokio::spawn(async move {
+        for i in 0.. {
+            cache.put(format!("key-{i}"), "v".into(), None, &[]).await;
+            if i >= capacity {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        }
+    });
+
+    let event = cluster
+        .wait_for_event(DEFAULT_TIMEOUT, |e| {
+            matches!(e, DomainEvent::ShardUndersized { .. })
+        })
+        .await
+        .expect("el master no marcó el shard");
+    let DomainEvent::ShardUndersized {
+        node_id: reported,
+        shard_id,
+        fill_percent,
+        ..
+    } = event
+    else {
+        unreachable!()
+    };
+    assert_eq!(
+        (reported.as_str(), shard_id.as_str()),
+        (node_id.as_str(), node_id.as_str())
+    );
+    assert_eq!(fill_percent, 100);
+
+    let pressure = cluster
+        .master
+        .module
+        .metrics
+        .cache_pressure(&node_id)
+        .unwrap();
+    writer.abort();
+    assert_eq!(pressure.capacity as usize, capacity);
+    assert!(cluster.master.module.render_metrics().contains(&format!(
+        "cache_master_node_cache_fill_ratio{{node=\"{node_id}\"}} 1\n"
+    )));
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn keys_that_expire_on_a_node_are_published_by_the_master() {
+    // sin reporte de presión: los vencimientos van por su cuenta
+    let mut cluster = TestCluster::start(0).await;
+    cluster.add_node(NodeRole::Master).await;
+    let node_id = cluster.nodes()[0].node_id().to_string();
+    let client = cluster.client().await;
+
+    assert_eq!(client.put("corta", "v", Some(50)).await.unwrap().code, 200);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(client.get("corta").await.unwrap().payload, "");
+
+    let event = cluster
+        .wait_for_event(DEFAULT_TIMEOUT, |e| {
+            matches!(e, DomainEvent::KeyExpired { .. })
+        })
+        .await
+        .expect("el master no publicó el vencimiento");
+    let DomainEvent::KeyExpired { key, node_id: from } = event else {
+        unreachable!()
+    };
+    assert_eq!((key.as_str(), from.as_str()), ("corta", node_id.as_str()));
+
+    cluster.shutdown().await;
+}
+
+/// Conexión cruda al master que se identifica como `identity` y no contesta nada.
+async fn raw_node(cluster: &TestCluster, identity: &str) -> BufReader<TcpStream> {
+    let mut stream = TcpStream::connect(cluster.master_addr()).await.unwrap();
+    stream
+        .write_all(format!("{identity}\n").as_bytes())
+        .await
+        .unwrap();
+    BufReader::new(stream)
+}
+
+#[tokio::test]
+async fn a_node_claiming_the_id_of_a_live_node_is_refused() {
+    let cluster = TestCluster::start(1).await;
+    let node_id = cluster.nodes()[0].node_id().to_string();
+    let client = cluster.client().await;
+    client.put("k", "v", None).await.unwrap();
+
+    let mut impostor = raw_node(&cluster, &format!("MASTER {node_id}")).await;
+    let mut line = String::new();
+    impostor.read_line(&mut line).await.unwrap();
+    assert_eq!(line, format!("EVT NODE-ID-CONFLICT \"{node_id}\"\n"));
+    // y cierra la conexión
+    line.clear();
+    assert_eq!(impostor.read_line(&mut line).await.unwrap(), 0);
+
+    // el nodo de verdad sigue atendiendo su shard
+    assert_eq!(client.get("k").await.unwrap().payload, "v");
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn a_node_reconnecting_over_a_dead_connection_takes_its_id_back() {
+    let mut cluster = TestCluster::start(1).await;
+
+    // la primera conexión no contesta el PING: el master la da por muerta
+    let stale = raw_node(&cluster, "MASTER ghost").await;
+    cluster
+        .wait_for_event(
+            DEFAULT_TIMEOUT,
+            |e| matches!(e, DomainEvent::NodeJoined { node_id, .. } if node_id == "ghost"),
+        )
+        .await
+        .expect("la primera conexión no se registró");
+
+    let _fresh = raw_node(&cluster, "MASTER ghost").await;
+    cluster
+        .wait_for_event(
+            DEFAULT_TIMEOUT,
+            |e| matches!(e, DomainEvent::NodeRemoved { node_id } if node_id == "ghost"),
+        )
+        .await
+        .expect("no se sacó la conexión muerta");
+    cluster
+        .wait_for_event(
+            DEFAULT_TIMEOUT,
+            |e| matches!(e, DomainEvent::NodeJoined { node_id, .. } if node_id == "ghost"),
+        )
+        .await
+        .expect("la conexión nueva no se registró");
+
+    // al cerrarse la vieja, el id sigue siendo de la nueva
+    drop(stale);
+    let removed = cluster
+        .wait_for_event(
+            Duration::from_millis(500),
+            |e| matches!(e, DomainEvent::NodeRemoved { node_id } if node_id == "ghost"),
+        )
+        .await;
+    assert!(removed.is_none(), "la conexión vieja sacó a la nueva");
+    let registry = &cluster.master.app_state.network_state.nodes_registry;
+    assert!(registry.contains_key("ghost"));
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn nodes_matching_node_deny_are_refused() {
+    let config = MasterConfig {
+        node_deny: vec!["intruso-*".parse::<NodeRule>().unwrap()],
+        ..MasterConfig::default()
+    };
+    let cluster = TestCluster::start_with_config(1, 0, &config).await;
+
+    let mut intruder = raw_node(&cluster, "MASTER intruso-1").await;
+    let mut line = String::new();
+    intruder.read_line(&mut line).await.unwrap();
+    assert_eq!(line, "EVT NODE-REFUSED \"denegado por intruso-*\"\n");
+    line.clear();
+    assert_eq!(intruder.read_line(&mut line).await.unwrap(), 0);
+
+    let registry = &cluster.master.app_state.network_state.nodes_registry;
+    assert!(!registry.contains_key("intruso-1"));
+    assert_eq!(registry.len(), 1);
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn ban_disconnects_a_node_and_refuses_it_until_unban() {
+    let mut cluster = TestCluster::start(1).await;
+    let client = cluster.client().await;
+
+    let mut rogue = raw_node(&cluster, "MASTER rogue").await;
+    cluster
+        .wait_for_event(
+            DEFAULT_TIMEOUT,
+            |e| matches!(e, DomainEvent::NodeJoined { node_id, .. } if node_id == "rogue"),
+        )
+        .await
+        .expect("el nodo no se registró");
+
+    let res = client.request("BAN", "rogue").await.unwrap();
+    assert_eq!(
+        (res.code, res.payload.as_str()),
+        (200, "bans=rogue disconnected=rogue")
+    );
+    cluster
+        .wait_for_event(
+            DEFAULT_TIMEOUT,
+            |e| matches!(e, DomainEvent::NodeRemoved { node_id } if node_id == "rogue"),
+        )
+        .await
+        .expect("el BAN no sacó al nodo");
+    let mut line = String::new();
+    while rogue.read_line(&mut line).await.unwrap() > 0 {
+        line.clear();
+    }
+
+    let mut again = raw_node(&cluster, "MASTER rogue").await;
+    again.read_line(&mut line).await.unwrap();
+    assert_eq!(line, "EVT NODE-REFUSED \"denegado por rogue\"\n");
+
+    let res = client.request("UNBAN", "rogue").await.unwrap();
+    assert_eq!((res.code, res.payload.as_str()), (200, "bans="));
+    assert_eq!(client.request("UNBAN", "rogue").await.unwrap().code, 404);
+    let _back = raw_node(&cluster, "MASTER rogue").await;
+    cluster
+        .wait_for_event(
+            DEFAULT_TIMEOUT,
+            |e| matches!(e, DomainEvent::NodeJoined { node_id, .. } if node_id == "rogue"),
+        )
+        .await
+        .expect("tras el UNBAN el nodo no volvió");
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn with_node_certs_nodes_register_only_over_mutual_tls() {
+    let config = MasterConfig {
+        node_certs: true,
+        ..MasterConfig::default()
+    };
+    let mut cluster = TestCluster::start_with_config(0, 0, &config).await;
+    let ca = TestCa::new();
+    let cluster_addr = cluster.listen_tls(&ca.issue("master")).await;
+
+    let node = cluster
+        .add_node_tls(NodeRole::Master, cluster_addr, &ca.issue("node-a"))
+        .await;
+    assert_eq!(node.node_id(), "node-a");
+    let client = cluster.client().await;
+    client.put("k", "v", None).await.unwrap();
+    assert_eq!(client.get("k").await.unwrap().payload, "v");
+
+    // sin certificado, por el puerto de los clientes
+    let mut plain = raw_node(&cluster, "MASTER node-b").await;
+    let mut line = String::new();
+    plain.read_line(&mut line).await.unwrap();
+    assert_eq!(
+        line,
+        "EVT NODE-REFUSED \"hace falta un certificado de cliente\"\n"
+    );
+
+    // con certificado, pero anunciando el id de otro
+    let mut impostor = ca
+        .issue("node-c")
+        .connector(TcpConnector)
+        .unwrap()
+        .connect(&cluster_addr.to_string())
+        .await
+        .unwrap();
+    impostor.write_all(b"MASTER node-a\n").await.unwrap();
+    let mut impostor = BufReader::new(impostor);
+    line.clear();
+    impostor.read_line(&mut line).await.unwrap();
+    assert_eq!(line, "EVT NODE-REFUSED \"el certificado es de node-c\"\n");
+    assert_eq!(client.get("k").await.unwrap().payload, "v");
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn nodes_register_over_quic_with_the_identity_of_their_certificate() {
+    let config = MasterConfig {
+        node_certs: true,
+        ..MasterConfig::default()
+    };
+    let mut cluster = TestCluster::start_with_config(0, 0, &config).await;
+    let ca = TestCa::new();
+    let quic_addr = cluster.listen_quic(&ca.issue("master"));
+
+    let node = cluster
+        .add_node_quic(NodeRole::Master, &quic_addr, &ca.issue("node-a"))
+        .await;
+    assert_eq!(node.node_id(), "node-a");
+    cluster
+        .add_node_quic(NodeRole::Replica, &quic_addr, &ca.issue("node-b"))
+        .await;
+
+    let client = cluster.client().await;
+    client.put("k", "v", None).await.unwrap();
+    assert_eq!(client.get("k").await.unwrap().payload, "v");
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn a_value_past_the_node_payload_limit_is_stored_in_chunks_across_the_ring() {
+    let config = MasterConfig {
+        router: RouterConfig {
+            max_payload: 64 * 1024,
+            ..RouterConfig::default()
+        },
+        chunk_size: Some(1024),
+        ..MasterConfig::default()
+    };
+    let mut cluster = TestCluster::start_with_config(0, 0, &config).await;
+    cluster.set_request_limits(RequestLimits {
+        max_payload: 4 * 1024,
+        ..RequestLimits::default()
+    });
+    for _ in 0..3 {
+        cluster.add_node(NodeRole::Master).await;
+    }
+    let client = cluster.client().await;
+
+    let big: String = (0..20_000)
+        .map(|i| char::from(b'a' + (i % 26) as u8))
+        .collect();
+    let res = client.put("big", &big, None).await.unwrap();
+    assert_eq!(res.code, 200, "{}", res.payload);
+    assert_eq!(client.get("big").await.unwrap().payload, big);
+
+    // la clave guarda el manifiesto y los pedazos se reparten por los shards
+    let holding = cluster
+        .nodes()
+        .iter()
+        .filter(|node| node.handle.module.cache.stats().entries > 0)
+        .count();
+    assert!(holding > 1, "los pedazos quedaron en un solo shard");
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn overwriting_or_deleting_a_chunked_value_leaves_no_chunks_behind() {
+    let config = MasterConfig {
+        router: RouterConfig {
+            max_payload: 64 * 1024,
+            ..RouterConfig::default()
+        },
+        chunk_size: Some(1024),
+        ..MasterConfig::default()
+    };
+    let mut cluster = TestCluster::start_with_config(0, 0, &config).await;
+    for _ in 0..3 {
+        cluster.add_node(NodeRole::Master).await;
+    }
+    let client = cluster.client().await;
+    let entries = |cluster: &TestCluster| -> usize {
+        cluster
+            .nodes()
+            .iter()
+            .map(|node| node.handle.module.cache.stats().entries)
+            .sum()
+    };
+
+    let value = |c: u8| -> String { std::iter::repeat_n(char::from(c), 10_000).collect() };
+    for c in [b'a', b'b'] {
+        let res = client.put("big", &value(c), None).await.unwrap();
+        assert_eq!(res.code, 200, "{}", res.payload);
+    }
+    // el manifiesto y los 10 pedazos del valor nuevo
+    assert_eq!(entries(&cluster), 11);
+    assert_eq!(client.get("big").await.unwrap().payload, value(b'b'));
+
+    let res = client.request("MULTI", "\"DEL big\"").await.unwrap();
+    assert_eq!(res.code, 200, "{}", res.payload);
+    assert_eq!(entries(&cluster), 0);
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn every_key_lands_on_as_many_shards_as_the_replication_factor() {
+    let config = MasterConfig {
+        replication_factor: 2,
+        ..MasterConfig::default()
+    };
+    let cluster = TestCluster::start_with_config(3, 0, &config).await;
+    let client = cluster.client().await;
+
+    for i in 0..10 {
+        client.put(&format!("k{i}"), "valor", None).await.unwrap();
+    }
+    let mut copies = std::collections::HashMap::<String, usize>::new();
+    for node in cluster.nodes() {
+        for op in node.handle.module.cache.snapshot() {
+            if let Op::Put { key, .. } = op {
+                *copies.entry(key).or_default() += 1;
+            }
+        }
+    }
+    assert_eq!(copies.len(), 10);
+    assert!(copies.values().all(|n| *n == 2), "{copies:?}");
+
+    // un shard pierde todo: lo que tenía sigue en el siguiente del anillo
+    let cache = &cluster.nodes()[0].handle.module.cache;
+    for op in cache.snapshot() {
+        if let Op::Put { key, .. } = op {
+            cache.remove(&key).await;
+        }
+    }
+    for i in 0..10 {
+        assert_eq!(client.get(&format!("k{i}")).await.unwrap().payload, "valor");
+    }
+
+    cluster.shutdown().await;
+}
+
+/// `count` masters que se conocen entre sí por `PEER_MASTERS`.
+async fn peer_masters(count: usize) -> Vec<(Arc<Supervisor>, MasterHandle, String)> {
+    let mut listeners = Vec::new();
+    for _ in 0..count {
+        listeners.push(TcpListener::bind("127.0.0.1:0").await.unwrap());
+    }
+    let addrs: Vec<String> = listeners
+        .iter()
+        .map(|l| l.local_addr().unwrap().to_string())
+        .collect();
+
+    listeners
+        .into_iter()
+        .enumerate()
+        .map(|(idx, listener)| {
+            let config = MasterConfig {
+                peers: PeerConfig {
+                    master_id: format!("master-{idx}"),
+                    peers: addrs
+                        .iter()
+                        .filter(|a| **a != addrs[idx])
+                        .cloned()
+                        .collect(),
+                    heartbeat_interval: Duration::from_millis(20),
+                    election_timeout: Duration::from_millis(150),
+                    ..PeerConfig::default()
+                },
+                ..MasterConfig::default()
+            };
+            let supervisor = Supervisor::new_shared();
+            let handle = cache_master::server::start_with_config(listener, &supervisor, &config);
+            (supervisor, handle, addrs[idx].clone())
+        })
+        .collect()
+}
+
+async fn eventually(what: &str, cond: impl Fn() -> bool) {
+    let deadline = tokio::time::Instant::now() + DEFAULT_TIMEOUT;
+    while !cond() {
+        assert!(tokio::time::Instant::now() < deadline, "{what}");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+}
+
+#[tokio::test]
+async fn peer_masters_agree_on_where_every_replica_goes() {
+    let masters = peer_masters(3).await;
+    for (_, handle, _) in &masters {
+        let peers = handle.module.peers.clone();
+        eventually("los masters no se conectaron entre sí", || {
+            peers.reachable_peers() == 2
+        })
+        .await;
+    }
+    let addrs: Vec<String> = masters.iter().map(|(_, _, addr)| addr.clone()).collect();
+
+    // las réplicas necesitan shards a los que sumarse
+    let nodes = Supervisor::new();
+    for (roles, expected) in [
+        (["MASTER"; 2].as_slice(), 2),
+        (["REPLICA"; 3].as_slice(), 5),
+    ] {
+        for role in roles {
+            cache_node::server::start(&nodes, role, addrs.clone());
+        }
+        for (_, handle, _) in &masters {
+            let network = handle.module.tcp_network_service.clone();
+            eventually("no se unieron todos los nodos", || {
+                network
+                    .shard_tree()
+                    .iter()
+                    .map(|(_, n)| n.len())
+                    .sum::<usize>()
+                    == expected
+            })
+            .await;
+        }
+    }
+
+    let trees: Vec<_> = masters
+        .iter()
+        .map(|(_, handle, _)| handle.module.tcp_network_service.shard_tree())
+        .collect();
+    assert_eq!(trees[0].len(), 2);
+    assert!(trees.iter().all(|tree| *tree == trees[0]), "{trees:?}");
+
+    nodes.shutdown(Duration::from_secs(2)).await;
+    for (supervisor, _, _) in masters {
+        supervisor.shutdown(Duration::from_secs(2)).await;
+    }
+}
+
+#[tokio::test]
+async fn a_master_without_a_majority_refuses_new_nodes() {
+    let mut masters = peer_masters(3).await;
+    let (_, lonely, addr) = masters.remove(0);
+    let peers = lonely.module.peers.clone();
+    eventually("los masters no se conectaron entre sí", || {
+        peers.reachable_peers() == 2
+    })
+    .await;
+
+    for (supervisor, _, _) in masters {
+        supervisor.shutdown(Duration::from_secs(2)).await;
+    }
+    eventually("el master sigue viendo a los otros", || !peers.has_quorum()).await;
+    let committed = peers.commit_index();
+
+    let nodes = Supervisor::new();
+    cache_node::server::start(&nodes, "MASTER", vec![addr]);
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert!(lonely.module.tcp_network_service.shard_tree().is_empty());
+    assert_eq!(peers.commit_index(), committed);
+    assert!(peers.ledger().is_empty());
+
+    nodes.shutdown(Duration::from_secs(2)).await;
+}
+
+#[tokio::test]
+async fn the_topology_outlives_the_leader_master() {
+    let mut masters = peer_masters(3).await;
+    eventually("los masters no eligieron líder", || {
+        masters
+            .iter()
+            .filter(|(_, handle, _)| handle.module.peers.is_leader())
+            .count()
+            == 1
+    })
+    .await;
+    let addrs: Vec<String> = masters.iter().map(|(_, _, addr)| addr.clone()).collect();
+
+    let nodes = Supervisor::new();
+    for (roles, expected) in [
+        (["MASTER"; 2].as_slice(), 2),
+        (["REPLICA"; 2].as_slice(), 4),
+    ] {
+        for role in roles {
+            cache_node::server::start(&nodes, role, addrs.clone());
+        }
+        for (_, handle, _) in &masters {
+            let peers = handle.module.peers.clone();
+            eventually("no se acordaron todas las altas", || {
+                peers.ledger().len() == expected
+            })
+            .await;
+        }
+    }
+
+    let leader = masters
+        .iter()
+        .position(|(_, handle, _)| handle.module.peers.is_leader())
+        .unwrap();
+    let agreed = masters[leader].1.module.peers.ledger();
+    let (supervisor, old_leader, _) = masters.remove(leader);
+    let old_term = old_leader.module.peers.term();
+    supervisor.shutdown(Duration::from_secs(2)).await;
+
+    eventually("los que quedan no eligieron otro líder", || {
+        masters
+            .iter()
+            .any(|(_, handle, _)| handle.module.peers.is_leader())
+    })
+    .await;
+    for (_, handle, _) in &masters {
+        assert!(handle.module.peers.term() > old_term);
+    }
+
+    // lo acordado sigue, y una réplica nueva se acuerda con el líder nuevo
+    cache_node::server::start(&nodes, "REPLICA", addrs.clone());
+    for (_, handle, _) in &masters {
+        let peers = handle.module.peers.clone();
+        eventually("la réplica nueva no se acordó", || {
+            peers.ledger().len() == 5
+        })
+        .await;
+        let ledger = peers.ledger();
+        for (node_id, shard_id) in agreed.assignments() {
+            assert_eq!(ledger.shard_of(node_id), Some(shard_id), "{ledger}");
+        }
+    }
+
+    nodes.shutdown(Duration::from_secs(2)).await;
+    for (supervisor, _, _) in masters {
+        supervisor.shutdown(Duration::from_secs(2)).await;
+    }
+}
+
+#[tokio::test]
+async fn a_payload_over_the_limit_gets_413_and_the_connection_stays_usable() {
+    let config = MasterConfig {
+        router: RouterConfig {
+            max_payload: 1024,
+            ..RouterConfig::default()
+        },
+        ..MasterConfig::default()
+    };
+    let mut cluster = TestCluster::start_with_config(0, 0, &config).await;
+    // el nodo acepta menos que el master
+    cluster.set_request_limits(RequestLimits {
+        max_payload: 256,
+        ..RequestLimits::default()
+    });
+    cluster.add_node(NodeRole::Master).await;
+    let client = cluster.client().await;
+
+    // la línea pasa el máximo de framing: se descarta sin juntarla
+    let res = client
+        .put("grande", &"x".repeat(64 * 1024), None)
+        .await
+        .unwrap();
+    assert_eq!(res.code, 413, "{}", res.payload);
+    // la línea entra pero el payload no
+    let res = client
+        .put("mediana", &"x".repeat(1500), None)
+        .await
+        .unwrap();
+    assert_eq!(res.code, 413, "{}", res.payload);
+    // el master la acepta y el nodo no
+    let res = client.put("chica", &"x".repeat(500), None).await.unwrap();
+    assert_eq!(res.code, 413, "{}", res.payload);
+
+    let res = client.put("k", "v", None).await.unwrap();
+    assert_eq!(res.code, 200, "{}", res.payload);
+    assert_eq!(client.get("k").await.unwrap().payload, "v");
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn stats_without_a_node_lists_each_connection_with_its_counters() {
+    let cluster = TestCluster::start(1).await;
+    let busy = cluster.client().await;
+    let idle = cluster.client().await;
+
+    for i in 0..10 {
+        assert_eq!(
+            busy.put(&format!("k{i}"), "v", None).await.unwrap().code,
+            200
+        );
+    }
+    assert_eq!(busy.request("NOPE", "").await.unwrap().code, 400);
+
+    let stats = idle.request("STATS", "").await.unwrap();
+    assert_eq!(stats.code, 200, "{}", stats.payload);
+    let lines = stats.values();
+    let line_of = |id: &str| {
+        lines
+            .iter()
+            .position(|line| line.starts_with(&format!("{id} ")))
+            .unwrap_or_else(|| panic!("{id} no está en {lines:?}"))
+    };
+    // de la que más bytes mandó a la que menos
+    let (busy_at, idle_at) = (line_of(&busy.id), line_of(&idle.id));
+    assert!(busy_at < idle_at, "{lines:?}");
+    let busy_line = &lines[busy_at];
+    assert!(busy_line.contains(" type=client "), "{busy_line}");
+    assert!(busy_line.contains(" requests=11 errors=1 "), "{busy_line}");
+    assert!(!busy_line.contains(" last_activity_ms=0"), "{busy_line}");
+    assert!(
+        lines.iter().any(|line| line.contains(" type=master ")),
+        "{lines:?}"
+    );
+
+    // el de un nodo trae los de su conexión
+    let node_id = lines
+        .iter()
+        .find(|line| line.contains(" type=master "))
+        .and_then(|line| line.split(' ').next())
+        .unwrap()
+        .to_string();
+    let stats = idle
+        .request("STATS", &format!("\"{node_id}\""))
+        .await
+        .unwrap();
+    assert!(stats.values()[0].contains(" bytes_in="), "{stats:?}");
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn large_responses_travel_compressed_to_whoever_accepts_them() {
+    let config = MasterConfig {
+        compress_min_bytes: Some(1024),
+        ..MasterConfig::default()
+    };
+    let mut cluster = TestCluster::start_with_config(0, 0, &config).await;
+    cluster.set_compress_min_bytes(Some(1024));
+    cluster.add_node(NodeRole::Master).await;
+
+    let value = "documento en cache ".repeat(4000);
+    let compressed = cluster.client().await;
+    compressed.accept_compression().unwrap();
+    let plain = cluster.client().await;
+    assert_eq!(compressed.put("doc", &value, None).await.unwrap().code, 200);
+
+    assert_eq!(compressed.get("doc").await.unwrap().payload, value);
+    assert_eq!(plain.get("doc").await.unwrap().payload, value);
+
+    // lo que salió hacia cada cliente y lo que mandó el nodo, según los contadores
+    let stats = plain.request("STATS", "").await.unwrap();
+    let bytes = |id: &str, field: &str| -> usize {
+        let line = stats
+            .values()
+            .into_iter()
+            .find(|line| line.starts_with(&format!("{id} ")))
+            .unwrap_or_else(|| panic!("{id} no está en {stats:?}"));
+        line.split(' ')
+            .find_map(|kv| kv.strip_prefix(&format!("{field}=")))
+            .unwrap()
+            .parse()
+            .unwrap()
+    };
+    let node_id = cluster.nodes()[0].node_id().to_string();
+    assert!(bytes(&node_id, "bytes_in") < value.len() / 4, "{stats:?}");
+    assert!(
+        bytes(&compressed.id, "bytes_out") < value.len() / 4,
+        "{stats:?}"
+    );
+    assert!(bytes(&plain.id, "bytes_out") > value.len(), "{stats:?}");
+
+    cluster.shutdown().await;
+}
@@ -0,0 +1,189 @@
+//! Escenarios de punta a punta sobre clusters armados con `ClusterBuilder`: caídas de
+//! primarios y de masters, nodos que vuelven, shards que se suman y TTLs que tienen que
+//! seguir valiendo después de cada cosa.
+
+use std::{collections::BTreeMap, time::Duration};
+
+use cache_master::infrastructure::raft::TopologyLedger;
+use cluster_harness::{ClusterBuilder, DEFAULT_TIMEOUT, NodeRole, TestClient};
+
+async fn put_all(client: &TestClient, keys: impl IntoIterator<Item = String>) {
+    for key in keys {
+        let res = client.put(&key, &format!("v-{key}"), None).await.unwrap();
+        assert_eq!(res.code, 200, "{key}: {}", res.payload);
+    }
+}
+
+async fn assert_all(client: &TestClient, keys: impl IntoIterator<Item = String>) {
+    for key in keys {
+        let res = client.get(&key).await.unwrap();
+        assert_eq!((res.code, res.payload), (200, format!("v-{key}")), "{key}");
+    }
+}
+
+fn keys(prefix: &str, n: usize) -> Vec<String> {
+    (0..n).map(|i| format!("{prefix}-{i}")).collect()
+}
+
+#[tokio::test]
+async fn the_replica_keeps_serving_the_shard_after_its_primary_dies() {
+    let mut cluster = ClusterBuilder::new()
+        .shards(1)
+        .replicas_per_shard(1)
+        .start()
+        .await;
+    let client = cluster.client().await;
+    put_all(&client, keys("before", 20)).await;
+
+    cluster.kill_node(0).await;
+
+    assert_all(&client, keys("before", 20)).await;
+    put_all(&client, keys("after", 20)).await;
+    assert_all(&client, keys("after", 20)).await;
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn a_restarted_replica_catches_up_before_taking_over() {
+    let mut cluster = ClusterBuilder::new()
+        .shards(1)
+        .replicas_per_shard(1)
+        .start()
+        .await;
+    let client = cluster.client().await;
+    put_all(&client, keys("before", 20)).await;
+
+    cluster.kill_node(1).await;
+    // lo que se escribe mientras la réplica está caída le llega con el SYNC
+    put_all(&client, keys("while-down", 20)).await;
+    cluster.restart_node(1).await;
+    assert_eq!(
+        cluster.shard_of(&cluster.nodes()[1].node_id),
+        Some(cluster.nodes()[0].node_id.clone())
+    );
+    let replica = cluster.nodes()[1].handle().unwrap().module.cache.clone();
+    cluster
+        .eventually(DEFAULT_TIMEOUT, "la réplica no se puso al día", |_| {
+            replica.stats().entries == 40
+        })
+        .await;
+
+    cluster.kill_node(0).await;
+    assert_all(&client, keys("before", 20)).await;
+    assert_all(&client, keys("while-down", 20)).await;
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn a_new_shard_takes_over_part_of_the_keys() {
+    let mut cluster = ClusterBuilder::new().shards(2).start().await;
+    let client = cluster.client().await;
+    put_all(&client, keys("k", 300)).await;
+
+    let added = cluster.add_node(NodeRole::Master).await;
+
+    // las claves no se mudan: las que pasaron al shard nuevo se leen vacías
+    let mut moved = 0;
+    for key in keys("k", 300) {
+        let res = client.get(&key).await.unwrap();
+        assert_eq!(res.code, 200, "{key}: {}", res.payload);
+        match res.payload.as_str() {
+            "" => moved += 1,
+            value => assert_eq!(value, format!("v-{key}")),
+        }
+    }
+    assert!((30..=180).contains(&moved), "se movieron {moved} de 300");
+
+    put_all(&client, keys("k", 300)).await;
+    assert_all(&client, keys("k", 300)).await;
+    let cache = &cluster.nodes()[added].handle().unwrap().module.cache;
+    assert_eq!(cache.stats().entries, moved);
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn ttls_keep_running_on_the_replica_that_takes_over() {
+    let mut cluster = ClusterBuilder::new()
+        .shards(1)
+        .replicas_per_shard(1)
+        .start()
+        .await;
+    let client = cluster.client().await;
+    assert_eq!(client.put("short", "v", Some(400)).await.unwrap().code, 200);
+    assert_eq!(client.put("long", "v", None).await.unwrap().code, 200);
+
+    cluster.kill_node(0).await;
+    assert_eq!(client.get("short").await.unwrap().payload, "v");
+
+    tokio::time::sleep(Duration::from_millis(600)).await;
+    assert_eq!(client.get("short").await.unwrap().payload, "");
+    assert_eq!(client.get("long").await.unwrap().payload, "v");
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn the_cluster_survives_a_master_going_down_and_coming_back() {
+    let mut cluster = ClusterBuilder::new()
+        .masters(3)
+        .shards(2)
+        .replicas_per_shard(1)
+        .start()
+        .await;
+    put_all(&cluster.client_of(0).await, keys("k", 30)).await;
+    let shards = |ledger: TopologyLedger| -> BTreeMap<String, String> {
+        ledger
+            .assignments()
+            .map(|(node, shard)| (node.to_string(), shard.to_string()))
+            .collect()
+    };
+    let before = shards(cluster.master(1).module.peers.ledger());
+
+    cluster.kill_master(0).await;
+    let other = cluster.client_of(1).await;
+    assert_all(&other, keys("k", 30)).await;
+    put_all(&other, keys("while-down", 10)).await;
+
+    cluster.restart_master(0).await;
+    let restarted = cluster.master(0).module.peers.clone();
+    cluster
+        .eventually(DEFAULT_TIMEOUT, "el master no volvió al acuerdo", |_| {
+            restarted.ledger().len() == 4
+        })
+        .await;
+    // las réplicas que vuelven antes que su primario esperan: nadie cambia de shard
+    assert_eq!(shards(restarted.ledger()), before);
+    let client = cluster.client_of(0).await;
+    assert_all(&client, keys("k", 30)).await;
+    assert_all(&client, keys("while-down", 10)).await;
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn a_put_repeated_with_the_same_token_is_not_applied_again() {
+    let mut cluster = ClusterBuilder::new()
+        .shards(1)
+        .replicas_per_shard(1)
+        .start()
+        .await;
+    let client = cluster.client().await;
+
+    // el reintento de un PUT cuya respuesta se perdió, después de otra escritura
+    for payload in ["k v1 idem=t1", "k v2", "k v1 idem=t1"] {
+        let res = client.request("PUT", payload).await.unwrap();
+        assert_eq!(res.code, 200, "{payload}: {}", res.payload);
+    }
+    let res = client.get("k").await.unwrap();
+    assert_eq!((res.code, res.payload.as_str()), (200, "v2"));
+
+    // tampoco en la réplica, que recibió el mismo token
+    cluster.kill_node(0).await;
+    let res = client.get("k").await.unwrap();
+    assert_eq!((res.code, res.payload.as_str()), (200, "v2"));
+
+    cluster.shutdown().await;
+}
@@ -0,0 +1,66 @@
+use std::sync::Once;
+
+use cluster_harness::TestCluster;
+
+// El subscriber global se instala una vez por binario de test; arranca callado para no
+// ensuciar la salida (escribe directo a stdout, sin captura del test runner).
+fn init() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| app_logging::init_logging().set_filter("error").unwrap());
+}
+
+#[tokio::test]
+async fn log_filter_reaches_master_and_every_node() {
+    init();
+    let cluster = TestCluster::start(2).await;
+    let client = cluster.client().await;
+
+    let res = client
+        .request("LOG-FILTER", "\"error,app_net=warn\" \"*\"")
+        .await
+        .unwrap();
+    assert_eq!(res.code, 200, "{}", res.payload);
+
+    let applied: Vec<&str> = res.payload.split("; ").collect();
+    assert_eq!(applied.len(), 3, "{}", res.payload);
+    assert!(applied.iter().all(|a| a.ends_with("=error,app_net=warn")));
+    assert_eq!(
+        app_logging::global().unwrap().current(),
+        "error,app_net=warn"
+    );
+
+    // solo lectura sobre un nodo puntual
+    let node_id = cluster.nodes()[0].node_id().to_string();
+    let res = client
+        .request("LOG-FILTER", &format!("\"\" \"{node_id}\""))
+        .await
+        .unwrap();
+    assert_eq!(
+        (res.code, res.payload.as_str()),
+        (200, "error,app_net=warn")
+    );
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn invalid_filters_and_unknown_nodes_are_rejected() {
+    init();
+    let cluster = TestCluster::start(1).await;
+    let client = cluster.client().await;
+    let node_id = cluster.nodes()[0].node_id().to_string();
+
+    let res = client
+        .request("LOG-FILTER", &format!("\"app_net=fuerte\" \"{node_id}\""))
+        .await
+        .unwrap();
+    assert_eq!(res.code, 400, "{}", res.payload);
+
+    let res = client
+        .request("LOG-FILTER", "\"debug\" \"no-existe\"")
+        .await
+        .unwrap();
+    assert_eq!(res.code, 502, "{}", res.payload);
+
+    cluster.shutdown().await;
+}
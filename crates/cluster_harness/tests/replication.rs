@@ -0,0 +1,206 @@
+use std::sync::Arc;
+
+use app_net::{encode_args, encode_read_node, encode_token};
+use cache_node::{
+    core::{
+        domain::services::{CacheService, ReplicationService},
+        services::{Op, op_log::DEFAULT_OP_LOG_CAPACITY},
+    },
+    infrastructure::di::CacheNodeModule,
+};
+use cluster_harness::{DEFAULT_TIMEOUT, NodeRole, TestCluster};
+
+/// Escribe directo en el nodo, sin pasar por el fan-out del master.
+async fn write_on_node(cluster: &TestCluster, idx: usize, (action, payload): (&str, String)) {
+    cluster.nodes()[idx]
+        .handle
+        .module
+        .request_controller_service
+        .handle(action, &payload)
+        .await;
+}
+
+fn value_of(node: &CacheNodeModule, key: &str) -> Option<String> {
+    node.cache.snapshot().into_iter().find_map(|op| match op {
+        Op::Put { key: k, value, .. } if k == key => Some(value),
+        _ => None,
+    })
+}
+
+fn put(key: &str, value: &str) -> (&'static str, String) {
+    ("PUT", encode_args([key, value]))
+}
+
+#[tokio::test]
+async fn replicas_converge_with_writes_the_master_never_sent() {
+    let mut cluster = TestCluster::start(1).await;
+    write_on_node(&cluster, 0, put("antes", "1")).await;
+
+    cluster.add_node(NodeRole::Replica).await;
+    let replica: Arc<CacheNodeModule> = cluster.nodes()[1].handle.module.clone();
+    cluster
+        .wait_until(DEFAULT_TIMEOUT, || replica.replication.source().is_some())
+        .await;
+
+    // SYNC completo al conectarse y después el op-log
+    write_on_node(&cluster, 0, put("despues", "2")).await;
+    cluster
+        .wait_until(DEFAULT_TIMEOUT, || {
+            value_of(&replica, "antes").as_deref() == Some("1")
+                && value_of(&replica, "despues").as_deref() == Some("2")
+        })
+        .await;
+
+    // los DEL también se replican
+    write_on_node(&cluster, 0, ("DEL", encode_token("antes").into_owned())).await;
+    cluster
+        .wait_until(DEFAULT_TIMEOUT, || value_of(&replica, "antes").is_none())
+        .await;
+    assert!(replica.replication.applied_seq() >= 3);
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn reconnecting_replicas_catch_up_from_their_offset() {
+    let mut cluster = TestCluster::start(1).await;
+    cluster.add_node(NodeRole::Replica).await;
+    let replica: Arc<CacheNodeModule> = cluster.nodes()[1].handle.module.clone();
+    cluster
+        .wait_until(DEFAULT_TIMEOUT, || {
+            replica.replication.position().epoch.is_some()
+        })
+        .await;
+    let primary_addr = replica.replication.source().unwrap();
+
+    write_on_node(&cluster, 0, put("k1", "1")).await;
+    cluster
+        .wait_until(DEFAULT_TIMEOUT, || replica.replication.applied_seq() == 1)
+        .await;
+
+    // corte corto: lo escrito mientras tanto llega por offset, sin SYNC completo
+    replica.replication.follow(None);
+    write_on_node(&cluster, 0, put("k2", "2")).await;
+    write_on_node(&cluster, 0, put("k1", "3")).await;
+    replica.replication.follow(Some(&primary_addr));
+
+    cluster
+        .wait_until(DEFAULT_TIMEOUT, || replica.replication.applied_seq() == 3)
+        .await;
+    assert_eq!(value_of(&replica, "k1").as_deref(), Some("3"));
+    assert_eq!(value_of(&replica, "k2").as_deref(), Some("2"));
+    assert_eq!(replica.replication.position().full_syncs, 1);
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn a_full_resync_drops_keys_deleted_during_the_gap() {
+    let mut cluster = TestCluster::start(1).await;
+    cluster.add_node(NodeRole::Replica).await;
+    let replica: Arc<CacheNodeModule> = cluster.nodes()[1].handle.module.clone();
+    cluster
+        .wait_until(DEFAULT_TIMEOUT, || {
+            replica.replication.position().epoch.is_some()
+        })
+        .await;
+    let primary_addr = replica.replication.source().unwrap();
+
+    write_on_node(&cluster, 0, put("borrada", "1")).await;
+    write_on_node(&cluster, 0, put("queda", "1")).await;
+    cluster
+        .wait_until(DEFAULT_TIMEOUT, || replica.replication.applied_seq() == 2)
+        .await;
+
+    // el DEL se pierde del op-log antes de que la réplica vuelva: le toca un SYNC completo
+    replica.replication.follow(None);
+    write_on_node(&cluster, 0, ("DEL", encode_token("borrada").into_owned())).await;
+    for _ in 0..DEFAULT_OP_LOG_CAPACITY {
+        write_on_node(&cluster, 0, put("relleno", "x")).await;
+    }
+    replica.replication.follow(Some(&primary_addr));
+
+    cluster
+        .wait_until(DEFAULT_TIMEOUT, || {
+            replica.replication.position().full_syncs == 2
+        })
+        .await;
+    assert_eq!(value_of(&replica, "borrada"), None);
+    assert_eq!(value_of(&replica, "queda").as_deref(), Some("1"));
+    assert_eq!(value_of(&replica, "relleno").as_deref(), Some("x"));
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn meta_shows_the_entry_on_every_node_of_the_shard() {
+    let mut cluster = TestCluster::start(1).await;
+    cluster.add_node(NodeRole::Replica).await;
+    let replica: Arc<CacheNodeModule> = cluster.nodes()[1].handle.module.clone();
+    cluster
+        .wait_until(DEFAULT_TIMEOUT, || {
+            replica.replication.position().epoch.is_some()
+        })
+        .await;
+
+    write_on_node(&cluster, 0, put("k", "valor")).await;
+    cluster
+        .wait_until(DEFAULT_TIMEOUT, || replica.replication.applied_seq() == 1)
+        .await;
+
+    let primary_id = cluster.nodes()[0].node_id().to_string();
+    let replica_id = cluster.nodes()[1].node_id().to_string();
+    let client = cluster.client().await;
+    let res = client.request("META", "\"k\"").await.unwrap();
+    assert_eq!(res.code, 200);
+
+    // primero el primario; la misma versión en los dos
+    let nodes: Vec<&str> = res.payload.split("; ").collect();
+    assert_eq!(nodes.len(), 2, "{}", res.payload);
+    assert!(nodes[0].starts_with(&format!("{primary_id}=version=1 expires_at=- size=6 ")));
+    assert!(nodes[1].starts_with(&format!("{replica_id}=version=1 ")));
+
+    let res = client.request("META", "\"otra\"").await.unwrap();
+    assert_eq!(
+        res.payload,
+        format!("{primary_id}=EMPTY; {replica_id}=EMPTY")
+    );
+
+    cluster.shutdown().await;
+}
+
+#[tokio::test]
+async fn sticky_puts_name_the_node_that_pinned_gets_read_from() {
+    let mut cluster = TestCluster::start(1).await;
+    cluster.add_node(NodeRole::Replica).await;
+    let client = cluster.client().await;
+
+    let res = client.request("PUT", "k v sticky=1").await.unwrap();
+    assert_eq!(res.code, 200, "{}", res.payload);
+    let values = res.values();
+    let ids: Vec<&str> = cluster.nodes().iter().map(|n| n.node_id()).collect();
+    assert_eq!(values[0], "OK");
+    assert!(ids.contains(&values[1].as_str()), "{values:?}");
+
+    // una clave que cada nodo tiene distinta: cada GET fijado ve la de su nodo
+    for (node, value) in cluster
+        .nodes()
+        .iter()
+        .zip(["del primario", "de la réplica"])
+    {
+        node.handle
+            .module
+            .cache
+            .put("d".into(), value.into(), None, &[])
+            .await;
+    }
+    for (id, value) in ids.iter().zip(["del primario", "de la réplica"]) {
+        let res = client
+            .request("GET", &encode_args(["d", &encode_read_node(id)]))
+            .await
+            .unwrap();
+        assert_eq!((res.code, res.payload.as_str()), (200, value));
+    }
+
+    cluster.shutdown().await;
+}
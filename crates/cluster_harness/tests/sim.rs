@@ -0,0 +1,118 @@
+use std::time::{Duration, Instant};
+
+use app_net::Connector;
+use cache_master::core::domain::models::DomainEvent;
+use cluster_harness::{DEFAULT_TIMEOUT, sim::Simulation};
+use tokio::io::AsyncWriteExt;
+
+// Todo lo que sigue corre en tiempo virtual: ninguno de estos tests debería tardar de
+// verdad más que unos milisegundos.
+const REAL_TIME_BUDGET: Duration = Duration::from_secs(2);
+
+#[test]
+fn ttl_expires_in_virtual_time() {
+    let real = Instant::now();
+
+    Simulation::from_env().run(|world| async move {
+        let cluster = world.cluster(2).await;
+        let client = cluster.client().await;
+
+        let ttl_ms = 30_000;
+        assert_eq!(client.put("k", "v", Some(ttl_ms)).await.unwrap().code, 200);
+
+        world.sleep(Duration::from_secs(10)).await;
+        assert_eq!(client.get("k").await.unwrap().payload, "v");
+
+        world.sleep(Duration::from_secs(25)).await;
+        assert_eq!(client.get("k").await.unwrap().payload, "");
+        assert!(world.elapsed() >= Duration::from_secs(35));
+
+        cluster.shutdown().await;
+    });
+
+    assert!(real.elapsed() < REAL_TIME_BUDGET);
+}
+
+#[test]
+fn silent_node_surfaces_as_timeout() {
+    let real = Instant::now();
+
+    Simulation::from_env().run(|world| async move {
+        let mut cluster = world.cluster(0).await;
+
+        // nodo falso: se anuncia y nunca responde
+        let mut ghost = world.net.connect("master").await.unwrap();
+        ghost.write_all(b"MASTER ghost\n").await.unwrap();
+        let joined = cluster
+            .wait_for_event(
+                DEFAULT_TIMEOUT,
+                |e| matches!(e, DomainEvent::NodeJoined { node_id, .. } if node_id == "ghost"),
+            )
+            .await;
+        assert!(joined.is_some());
+
+        let client = cluster.client().await;
+        let before = world.elapsed();
+        let res = client.put("k", "v", None).await.unwrap();
+
+        assert_eq!(res.code, 504, "{}", res.payload);
+        assert!(world.elapsed() - before >= Duration::from_secs(2));
+
+        cluster.shutdown().await;
+    });
+
+    assert!(real.elapsed() < REAL_TIME_BUDGET);
+}
+
+#[test]
+fn writes_survive_losing_a_shard() {
+    Simulation::from_env().run(|world| async move {
+        let mut cluster = world.cluster(3).await;
+        let client = cluster.client().await;
+
+        cluster.stop_node(1).await;
+
+        for i in 0..30 {
+            let key = format!("k{i}");
+            assert_eq!(client.put(&key, "v", None).await.unwrap().code, 200);
+            assert_eq!(client.get(&key).await.unwrap().payload, "v");
+        }
+
+        cluster.shutdown().await;
+    });
+}
+
+#[test]
+fn same_seed_replays_the_same_run() {
+    fn scenario(seed: u64) -> Vec<(u64, String, String)> {
+        Simulation::new(seed).run(|world| async move {
+            let cluster = world.cluster(3).await;
+            let client = cluster.client().await;
+            let mut trace = Vec::new();
+
+            for i in 0..20 {
+                let key = format!("k{i}");
+                let ttl = fastrand::u64(1_000..20_000);
+                client.put(&key, "v", Some(ttl)).await.unwrap();
+                world
+                    .sleep(Duration::from_millis(fastrand::u64(0..2_000)))
+                    .await;
+            }
+
+            for i in 0..20 {
+                let key = format!("k{i}");
+                let payload = client.get(&key).await.unwrap().payload;
+                trace.push((world.now_ms(), key, payload));
+            }
+
+            cluster.shutdown().await;
+            trace
+        })
+    }
+
+    let first = scenario(42);
+    assert_eq!(first, scenario(42));
+    // con 20 TTLs al azar entre 1s y 20s algunas claves vencieron y otras no
+    assert!(first.iter().any(|(_, _, v)| v.is_empty()));
+    assert!(first.iter().any(|(_, _, v)| v == "v"));
+}
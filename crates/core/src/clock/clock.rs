@@ -14,6 +14,12 @@ impl AppClock {
     }
 }
 
+impl Default for AppClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Clock for AppClock {
     #[inline]
     fn now_millis(&self) -> AppTime {
@@ -1,6 +1,13 @@
+// `clock.rs` tiene el `Clock` que da nombre al módulo y se reexporta abajo: de afuera
+// se usa `clock::AppClock`, nunca `clock::clock`
+#[allow(clippy::module_inception)]
 pub mod clock;
+pub mod runtime;
+pub mod simulated;
 mod test;
 pub mod time;
 
 pub use self::clock::{AppClock, Clock};
+pub use self::runtime::RuntimeClock;
+pub use self::simulated::SimulatedClock;
 pub use self::time::AppTime;
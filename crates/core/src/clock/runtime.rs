@@ -0,0 +1,44 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tokio::time::Instant;
+
+use crate::clock::{AppTime, Clock};
+
+/// Reloj que sigue al reloj de tokio. Con el tiempo pausado (`start_paused`) avanza
+/// solo con el runtime, lo que permite simular TTLs y timeouts sin esperar de verdad.
+#[derive(Debug, Clone)]
+pub struct RuntimeClock {
+    base_ms: u64,
+    origin: Instant,
+}
+
+impl RuntimeClock {
+    /// Arranca en la hora real actual. Requiere estar dentro de un runtime de tokio.
+    pub fn new() -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Self::starting_at(now.as_millis() as u64)
+    }
+
+    /// Arranca en un instante fijo, para que las simulaciones sean reproducibles.
+    pub fn starting_at(base_ms: u64) -> Self {
+        Self {
+            base_ms,
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl Default for RuntimeClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for RuntimeClock {
+    #[inline]
+    fn now_millis(&self) -> AppTime {
+        AppTime::new(self.base_ms + self.origin.elapsed().as_millis() as u64)
+    }
+}
@@ -0,0 +1,39 @@
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use crate::clock::{AppTime, Clock};
+
+/// Reloj virtual para tests: el tiempo solo avanza cuando se llama a `advance`/`set`.
+#[derive(Debug, Default)]
+pub struct SimulatedClock {
+    now_ms: AtomicU64,
+}
+
+impl SimulatedClock {
+    pub fn new(start_ms: u64) -> Self {
+        Self {
+            now_ms: AtomicU64::new(start_ms),
+        }
+    }
+
+    /// Avanza el reloj y devuelve el nuevo instante.
+    pub fn advance(&self, by: Duration) -> AppTime {
+        let delta = by.as_millis() as u64;
+        let now = self.now_ms.fetch_add(delta, Ordering::SeqCst) + delta;
+        AppTime::new(now)
+    }
+
+    /// Fija el reloj en un instante absoluto (no valida que sea monótono).
+    pub fn set(&self, ms: u64) {
+        self.now_ms.store(ms, Ordering::SeqCst);
+    }
+}
+
+impl Clock for SimulatedClock {
+    #[inline]
+    fn now_millis(&self) -> AppTime {
+        AppTime::new(self.now_ms.load(Ordering::SeqCst))
+    }
+}
@@ -2,6 +2,7 @@
 mod tests {
     use crate::clock::clock::{AppClock, Clock};
     use crate::clock::time::AppTime;
+    use crate::clock::{RuntimeClock, SimulatedClock};
 
     use std::sync::atomic::{AtomicU64, Ordering};
     use std::thread;
@@ -90,4 +91,51 @@ mod tests {
         assert!(t1.is_before(&t2));
         assert!(AppTime::new(2_000).is_before_or_eq(&t2));
     }
+
+    #[test]
+    fn simulated_clock_only_moves_when_advanced() {
+        let clock = SimulatedClock::new(5_000);
+        assert_eq!(clock.now_millis().as_millis_u64(), 5_000);
+
+        thread::sleep(Duration::from_millis(2));
+        assert_eq!(clock.now_millis().as_millis_u64(), 5_000);
+
+        let t = clock.advance(Duration::from_millis(250));
+        assert_eq!(t.as_millis_u64(), 5_250);
+        assert_eq!(clock.now_millis().as_millis_u64(), 5_250);
+
+        clock.set(10);
+        assert_eq!(clock.now_millis().as_millis_u64(), 10);
+    }
+
+    #[test]
+    fn simulated_clock_is_usable_as_dyn_clock() {
+        let sim = std::sync::Arc::new(SimulatedClock::new(0));
+        let clock: std::sync::Arc<dyn Clock> = sim.clone();
+
+        sim.advance(Duration::from_secs(1));
+        assert_eq!(clock.now_millis().as_millis_u64(), 1_000);
+    }
+
+    #[tokio::test]
+    async fn runtime_clock_follows_tokio_time() {
+        let clock = RuntimeClock::starting_at(1_000);
+        assert!(clock.now_millis().as_millis_u64() < 1_005);
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(clock.now_millis().as_millis_u64() >= 1_010);
+    }
+
+    #[test]
+    fn utc_basic_format() {
+        assert_eq!(AppTime::new(0).to_utc_basic(), "19700101T000000Z");
+        assert_eq!(
+            AppTime::new(1_369_353_600_000).to_utc_basic(),
+            "20130524T000000Z"
+        );
+        assert_eq!(
+            AppTime::new(1_709_251_199_999).to_utc_basic(),
+            "20240229T235959Z"
+        );
+    }
 }
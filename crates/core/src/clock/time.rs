@@ -20,6 +20,34 @@ impl AppTime {
     pub fn as_millis_u64(&self) -> u64 {
         self.date
     }
+
+    /// Fecha y hora UTC como `20240131T235959Z` (ISO 8601 básico, sin milisegundos): ordena
+    /// igual que el instante.
+    pub fn to_utc_basic(&self) -> String {
+        let secs = self.date / 1000;
+        let (year, month, day) = civil_from_days(secs / 86_400);
+        let rest = secs % 86_400;
+        format!(
+            "{year:04}{month:02}{day:02}T{:02}{:02}{:02}Z",
+            rest / 3600,
+            rest % 3600 / 60,
+            rest % 60
+        )
+    }
+}
+
+/// Año, mes y día de los días desde 1970-01-01 (algoritmo de Howard Hinnant).
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    (year, month, day)
 }
 
 impl PartialEq for AppTime {
@@ -0,0 +1,132 @@
+use std::fmt;
+
+/// Categoría de error compartida por todos los crates. Cada `AppError`/`SocketError`
+/// se clasifica en una de estas, y la categoría define el código que viaja en `RES`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    BadRequest,
+    Unauthorized,
+    NotFound,
+    Conflict,
+    /// La condición de una escritura condicional no se cumplió.
+    PreconditionFailed,
+    /// El request o su payload pasan el tamaño máximo aceptado.
+    PayloadTooLarge,
+    RateLimited,
+    /// La escritura no entra en la cuota de su namespace.
+    QuotaExceeded,
+    /// El cluster o el shard está en solo lectura (mantenimiento).
+    ReadOnly,
+    Connection,
+    Unavailable,
+    Timeout,
+    Internal,
+}
+
+impl ErrorKind {
+    /// Código (estilo HTTP) que se envía en la línea `RES`.
+    pub fn wire_code(&self) -> u16 {
+        match self {
+            ErrorKind::BadRequest => 400,
+            ErrorKind::Unauthorized => 401,
+            ErrorKind::NotFound => 404,
+            ErrorKind::Conflict => 409,
+            ErrorKind::PreconditionFailed => 412,
+            ErrorKind::PayloadTooLarge => 413,
+            ErrorKind::ReadOnly => 423,
+            ErrorKind::RateLimited => 429,
+            ErrorKind::Internal => 500,
+            ErrorKind::Connection => 502,
+            ErrorKind::Unavailable => 503,
+            ErrorKind::Timeout => 504,
+            ErrorKind::QuotaExceeded => 507,
+        }
+    }
+
+    /// Inversa de `wire_code`. Códigos desconocidos caen al rango más cercano.
+    pub fn from_wire_code(code: u16) -> Self {
+        match code {
+            401 => ErrorKind::Unauthorized,
+            404 => ErrorKind::NotFound,
+            409 => ErrorKind::Conflict,
+            412 => ErrorKind::PreconditionFailed,
+            413 => ErrorKind::PayloadTooLarge,
+            423 => ErrorKind::ReadOnly,
+            429 => ErrorKind::RateLimited,
+            502 => ErrorKind::Connection,
+            503 => ErrorKind::Unavailable,
+            504 => ErrorKind::Timeout,
+            507 => ErrorKind::QuotaExceeded,
+            400..=499 => ErrorKind::BadRequest,
+            _ => ErrorKind::Internal,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorKind::BadRequest => "bad_request",
+            ErrorKind::Unauthorized => "unauthorized",
+            ErrorKind::NotFound => "not_found",
+            ErrorKind::Conflict => "conflict",
+            ErrorKind::PreconditionFailed => "precondition_failed",
+            ErrorKind::PayloadTooLarge => "payload_too_large",
+            ErrorKind::RateLimited => "rate_limited",
+            ErrorKind::QuotaExceeded => "quota_exceeded",
+            ErrorKind::ReadOnly => "read_only",
+            ErrorKind::Connection => "connection",
+            ErrorKind::Unavailable => "unavailable",
+            ErrorKind::Timeout => "timeout",
+            ErrorKind::Internal => "internal",
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Implementado por los errores de cada crate para exponer su categoría común.
+pub trait HasErrorKind {
+    fn kind(&self) -> ErrorKind;
+
+    fn wire_code(&self) -> u16 {
+        self.kind().wire_code()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ErrorKind;
+
+    const ALL: [ErrorKind; 13] = [
+        ErrorKind::BadRequest,
+        ErrorKind::Unauthorized,
+        ErrorKind::NotFound,
+        ErrorKind::Conflict,
+        ErrorKind::PreconditionFailed,
+        ErrorKind::PayloadTooLarge,
+        ErrorKind::RateLimited,
+        ErrorKind::QuotaExceeded,
+        ErrorKind::ReadOnly,
+        ErrorKind::Connection,
+        ErrorKind::Unavailable,
+        ErrorKind::Timeout,
+        ErrorKind::Internal,
+    ];
+
+    #[test]
+    fn wire_code_round_trips() {
+        for kind in ALL {
+            assert_eq!(ErrorKind::from_wire_code(kind.wire_code()), kind);
+        }
+    }
+
+    #[test]
+    fn unknown_codes_fall_back_by_range() {
+        assert_eq!(ErrorKind::from_wire_code(422), ErrorKind::BadRequest);
+        assert_eq!(ErrorKind::from_wire_code(599), ErrorKind::Internal);
+        assert_eq!(ErrorKind::from_wire_code(200), ErrorKind::Internal);
+    }
+}
@@ -0,0 +1,166 @@
+use std::{future::Future, sync::Arc};
+
+use async_trait::async_trait;
+use tokio::{
+    sync::broadcast::{self, error::RecvError},
+    task::JoinHandle,
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::id::new_sortable_id;
+
+/// Evento publicado junto con su ID (ordenable por tiempo de publicación).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Envelope<E> {
+    pub id: String,
+    pub event: E,
+}
+
+impl<E> Envelope<E> {
+    pub fn new(event: E) -> Self {
+        Self {
+            id: new_sortable_id(),
+            event,
+        }
+    }
+}
+
+/// Suscriptor asíncrono de eventos de tipo `E`.
+#[async_trait]
+pub trait EventSubscriber<E>: Send + Sync
+where
+    E: Send + Sync + 'static,
+{
+    async fn handle(&self, envelope: &Envelope<E>);
+}
+
+/// Bus publish/subscribe tipado. Publicar nunca bloquea: si un suscriptor se atrasa
+/// más que `capacity` eventos, pierde los más viejos (semántica de `broadcast`).
+pub struct EventBus<E> {
+    tx: broadcast::Sender<Envelope<E>>,
+}
+
+impl<E> EventBus<E>
+where
+    E: Clone + Send + Sync + 'static,
+{
+    pub fn new(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(capacity.max(1));
+        Self { tx }
+    }
+
+    pub fn new_shared(capacity: usize) -> Arc<Self> {
+        Arc::new(Self::new(capacity))
+    }
+
+    /// Publica un evento y devuelve cuántos suscriptores lo recibirán.
+    pub fn publish(&self, event: E) -> usize {
+        self.tx.send(Envelope::new(event)).unwrap_or(0)
+    }
+
+    /// Receptor crudo, para quien quiera manejar el loop por su cuenta.
+    pub fn subscribe(&self) -> broadcast::Receiver<Envelope<E>> {
+        self.tx.subscribe()
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.tx.receiver_count()
+    }
+
+    /// Lanza una tarea que entrega cada evento al suscriptor hasta que el bus se cierre.
+    pub fn spawn_subscriber<S>(&self, subscriber: Arc<S>) -> JoinHandle<()>
+    where
+        S: EventSubscriber<E> + ?Sized + 'static,
+    {
+        tokio::spawn(self.subscriber_loop(subscriber, CancellationToken::new()))
+    }
+
+    /// Loop de entrega para correr bajo un supervisor: termina al cerrarse el bus
+    /// o al cancelarse `cancel`.
+    pub fn subscriber_loop<S>(
+        &self,
+        subscriber: Arc<S>,
+        cancel: CancellationToken,
+    ) -> impl Future<Output = ()> + Send + 'static
+    where
+        S: EventSubscriber<E> + ?Sized + 'static,
+    {
+        let mut rx = self.subscribe();
+
+        async move {
+            loop {
+                let received = tokio::select! {
+                    _ = cancel.cancelled() => break,
+                    r = rx.recv() => r,
+                };
+                match received {
+                    Ok(envelope) => subscriber.handle(&envelope).await,
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Mutex, time::Duration};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder {
+        seen: Mutex<Vec<u32>>,
+    }
+
+    #[async_trait]
+    impl EventSubscriber<u32> for Recorder {
+        async fn handle(&self, envelope: &Envelope<u32>) {
+            self.seen.lock().unwrap().push(envelope.event);
+        }
+    }
+
+    #[tokio::test]
+    async fn publish_without_subscribers_is_noop() {
+        let bus = EventBus::<u32>::new(8);
+        assert_eq!(bus.publish(1), 0);
+    }
+
+    #[tokio::test]
+    async fn raw_subscribers_receive_in_order() {
+        let bus = EventBus::<u32>::new(8);
+        let mut a = bus.subscribe();
+        let mut b = bus.subscribe();
+
+        assert_eq!(bus.publish(1), 2);
+        bus.publish(2);
+
+        let first = a.recv().await.unwrap();
+        let second = a.recv().await.unwrap();
+        assert_eq!((first.event, second.event), (1, 2));
+        assert!(first.id < second.id);
+        assert_eq!(b.recv().await.unwrap(), first);
+    }
+
+    #[tokio::test]
+    async fn spawned_subscriber_handles_events() {
+        let bus = EventBus::<u32>::new(8);
+        let recorder = Arc::new(Recorder::default());
+        let handle = bus.spawn_subscriber(recorder.clone());
+
+        bus.publish(7);
+        bus.publish(8);
+
+        for _ in 0..50 {
+            if recorder.seen.lock().unwrap().len() == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        assert_eq!(*recorder.seen.lock().unwrap(), vec![7, 8]);
+
+        drop(bus);
+        handle.await.unwrap();
+    }
+}
@@ -0,0 +1,190 @@
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use uuid::Uuid;
+
+/// Largo de la forma corta usada solo para logs/pantalla.
+pub const SHORT_ID_LEN: usize = 8;
+
+/// ID único y ordenable por tiempo (UUIDv7, 32 hex sin guiones). Dentro del mismo
+/// proceso los IDs salen estrictamente crecientes.
+pub fn new_sortable_id() -> String {
+    Uuid::now_v7().simple().to_string()
+}
+
+/// Forma corta de un ID para mostrar. Toma el final (la parte aleatoria en un UUIDv7),
+/// nunca usar como identificador.
+pub fn short_form(id: &str) -> &str {
+    let start = id.len().saturating_sub(SHORT_ID_LEN);
+    id.get(start..).unwrap_or(id)
+}
+
+// Snowflake de 64 bits: | 41 bits ms desde EPOCH | 10 bits nodo | 12 bits secuencia |
+const EPOCH_MS: u64 = 1_704_067_200_000; // 2024-01-01T00:00:00Z
+const NODE_BITS: u32 = 10;
+const SEQ_BITS: u32 = 12;
+const NODE_MASK: u64 = (1 << NODE_BITS) - 1;
+const SEQ_MASK: u64 = (1 << SEQ_BITS) - 1;
+
+/// Generador snowflake: IDs `u64` compactos, ordenables y sin colisiones entre
+/// generadores con distinto `node`. Si se agota la secuencia de un milisegundo,
+/// "toma prestado" el siguiente en lugar de bloquear.
+#[derive(Debug)]
+pub struct SnowflakeGenerator {
+    node: u64,
+    // (ms << SEQ_BITS) | seq del último ID emitido
+    last: AtomicU64,
+}
+
+impl SnowflakeGenerator {
+    pub fn new(node: u16) -> Self {
+        Self {
+            node: node as u64 & NODE_MASK,
+            last: AtomicU64::new(0),
+        }
+    }
+
+    /// Deriva los bits de nodo a partir de un identificador arbitrario (FNV-1a). Son 10
+    /// bits: dos ids distintos pueden caer en el mismo nodo y entonces sus IDs pueden
+    /// repetirse. Donde eso importa hay que repartir los nodos con `new`.
+    pub fn for_node_id(node_id: &str) -> Self {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for b in node_id.as_bytes() {
+            hash ^= *b as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        Self::new((hash & NODE_MASK) as u16)
+    }
+
+    pub fn node(&self) -> u16 {
+        self.node as u16
+    }
+
+    pub fn next_id(&self) -> u64 {
+        self.next_id_at(now_ms())
+    }
+
+    fn next_id_at(&self, now_ms: u64) -> u64 {
+        let now = now_ms.saturating_sub(EPOCH_MS) << SEQ_BITS;
+
+        let mut prev = self.last.load(Ordering::Relaxed);
+        let next = loop {
+            let candidate = if now > prev { now } else { prev + 1 };
+            match self.last.compare_exchange_weak(
+                prev,
+                candidate,
+                Ordering::AcqRel,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break candidate,
+                Err(actual) => prev = actual,
+            }
+        };
+
+        let ms = next >> SEQ_BITS;
+        let seq = next & SEQ_MASK;
+        (ms << (NODE_BITS + SEQ_BITS)) | (self.node << SEQ_BITS) | seq
+    }
+
+    /// Milisegundos Unix embebidos en un ID.
+    pub fn timestamp_ms(id: u64) -> u64 {
+        (id >> (NODE_BITS + SEQ_BITS)) + EPOCH_MS
+    }
+
+    pub fn node_of(id: u64) -> u16 {
+        ((id >> SEQ_BITS) & NODE_MASK) as u16
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use super::*;
+
+    #[test]
+    fn sortable_ids_are_monotonic_and_unique() {
+        let ids: Vec<String> = (0..1000).map(|_| new_sortable_id()).collect();
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+        assert!(ids.iter().all(|id| id.len() == 32));
+    }
+
+    #[test]
+    fn short_form_takes_the_random_tail() {
+        let id = new_sortable_id();
+        assert_eq!(short_form(&id), &id[24..]);
+        assert_eq!(short_form("abc"), "abc");
+    }
+
+    #[test]
+    fn snowflake_is_monotonic_within_and_across_milliseconds() {
+        let g = SnowflakeGenerator::new(7);
+        let base = EPOCH_MS + 1_000;
+
+        let a = g.next_id_at(base);
+        let b = g.next_id_at(base);
+        let c = g.next_id_at(base + 1);
+        // reloj que retrocede: sigue creciendo
+        let d = g.next_id_at(base - 500);
+
+        assert!(a < b && b < c && c < d);
+        assert_eq!(SnowflakeGenerator::timestamp_ms(a), base);
+        assert_eq!(SnowflakeGenerator::node_of(d), 7);
+    }
+
+    #[test]
+    fn snowflake_sequence_overflow_borrows_next_millisecond() {
+        let g = SnowflakeGenerator::new(1);
+        let base = EPOCH_MS + 5_000;
+
+        let ids: Vec<u64> = (0..=SEQ_MASK + 1).map(|_| g.next_id_at(base)).collect();
+        let unique: HashSet<_> = ids.iter().collect();
+
+        assert_eq!(unique.len(), ids.len());
+        assert_eq!(
+            SnowflakeGenerator::timestamp_ms(*ids.last().unwrap()),
+            base + 1
+        );
+    }
+
+    #[test]
+    fn generators_with_distinct_node_bits_never_collide() {
+        // los 1024 nodos posibles, todos en el mismo milisegundo y con la misma secuencia
+        let ids: Vec<u64> = (0..=NODE_MASK as u16)
+            .flat_map(|node| {
+                let g = SnowflakeGenerator::new(node);
+                [g.next_id_at(EPOCH_MS), g.next_id_at(EPOCH_MS)]
+            })
+            .collect();
+        let unique: HashSet<_> = ids.iter().collect();
+        assert_eq!(unique.len(), ids.len());
+    }
+
+    #[test]
+    fn node_ids_that_hash_to_the_same_bits_share_their_ids() {
+        let a = SnowflakeGenerator::for_node_id("node-a");
+        assert_eq!(SnowflakeGenerator::for_node_id("node-a").node(), a.node());
+
+        // con 1025 nombres dos caen por fuerza en el mismo nodo
+        let mut seen = HashMap::new();
+        let (first, second) = (0..=NODE_MASK + 1)
+            .map(|i| format!("node-{i}"))
+            .find_map(|name| {
+                let node = SnowflakeGenerator::for_node_id(&name).node();
+                seen.insert(node, name.clone()).map(|first| (first, name))
+            })
+            .unwrap();
+        let g1 = SnowflakeGenerator::for_node_id(&first);
+        let g2 = SnowflakeGenerator::for_node_id(&second);
+        assert_eq!(g1.next_id_at(EPOCH_MS), g2.next_id_at(EPOCH_MS));
+    }
+}
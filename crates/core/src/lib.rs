@@ -1,7 +1,17 @@
 pub mod clock;
+pub mod error;
+pub mod events;
+pub mod id;
+pub mod memoize;
+pub mod metrics;
+pub mod namespace;
+pub mod pipeline;
+pub mod retry;
+pub mod supervisor;
 pub mod use_case;
 pub mod utils;
 
+pub use crate::pipeline::UseCaseExt;
 pub use crate::use_case::UseCase;
 pub use crate::use_case::UseCaseValidatable;
 
@@ -0,0 +1,215 @@
+use std::{collections::HashMap, hash::Hash, marker::PhantomData, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use parking_lot::Mutex;
+
+use crate::{
+    clock::{AppClock, Clock},
+    use_case::{UseCase, UseCaseValidatable},
+};
+
+/// Cuántas salidas guarda como máximo un `Memoized` si no se le dice otra cosa.
+pub const DEFAULT_MAX_ENTRIES: usize = 10_000;
+
+/// Qué entradas de un caso de uso se pueden contestar con una salida anterior, y con cuál:
+/// dos entradas con la misma clave tienen que dar la misma salida. `None` no se memoiza
+/// (p. ej. una lectura que además reserva algo para quien pregunta).
+pub trait MemoKey {
+    type Key: Eq + Hash + Send + Sync + 'static;
+
+    fn memo_key(&self) -> Option<Self::Key>;
+}
+
+/// Contesta con la última salida de `inner` para la misma clave mientras no pasó `window`
+/// desde que se obtuvo. Pensado para casos de uso de solo lectura que reciben ráfagas de
+/// la misma entrada: los errores no se guardan, y con `window` en cero no guarda nada.
+/// Guarda hasta `max_entries` salidas; lleno, descarta las vencidas y, si no alcanza, deja
+/// de guardar hasta que venzan.
+pub struct Memoized<A, In, Out>
+where
+    In: MemoKey,
+{
+    inner: A,
+    window_ms: u64,
+    max_entries: usize,
+    clock: Arc<dyn Clock>,
+    /// Clave -> (vence en ms, salida).
+    entries: Mutex<HashMap<In::Key, (u64, Out)>>,
+    _in: PhantomData<fn(In)>,
+}
+
+impl<A, In, Out> Memoized<A, In, Out>
+where
+    In: MemoKey,
+{
+    pub fn new(inner: A, window: Duration) -> Self {
+        Self {
+            inner,
+            window_ms: window.as_millis() as u64,
+            max_entries: DEFAULT_MAX_ENTRIES,
+            clock: Arc::new(AppClock::new()),
+            entries: Mutex::new(HashMap::new()),
+            _in: PhantomData,
+        }
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    pub fn inner(&self) -> &A {
+        &self.inner
+    }
+
+    /// Salidas guardadas, vencidas incluidas.
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[async_trait]
+impl<A, In, Out, Err> UseCase<In, Out, Err> for Memoized<A, In, Out>
+where
+    A: UseCase<In, Out, Err>,
+    In: MemoKey + Send + 'static,
+    Out: Clone + Send + Sync + 'static,
+    Err: Send + 'static,
+{
+    async fn execute(&self, input: In) -> Result<Out, Err> {
+        let key = match input.memo_key() {
+            Some(key) if self.window_ms > 0 => key,
+            _ => return self.inner.execute(input).await,
+        };
+
+        let now = self.clock.now_millis().as_millis_u64();
+        if let Some((expires, out)) = self.entries.lock().get(&key)
+            && now < *expires
+        {
+            return Ok(out.clone());
+        }
+
+        let out = self.inner.execute(input).await?;
+
+        // la ventana corre desde que se obtuvo la salida, no desde que se pidió
+        let now = self.clock.now_millis().as_millis_u64();
+        let mut entries = self.entries.lock();
+        if entries.len() >= self.max_entries && !entries.contains_key(&key) {
+            entries.retain(|_, (expires, _)| now < *expires);
+        }
+        if entries.len() < self.max_entries || entries.contains_key(&key) {
+            entries.insert(key, (now + self.window_ms, out.clone()));
+        }
+        Ok(out)
+    }
+}
+
+/// La validación es la de `inner` y corre siempre, aunque la salida ya esté guardada.
+#[async_trait]
+impl<A, In, Out, Err> UseCaseValidatable<In, Out, Err> for Memoized<A, In, Out>
+where
+    A: UseCaseValidatable<In, Out, Err>,
+    In: MemoKey + Send + Sync + 'static,
+    Out: Clone + Send + Sync + 'static,
+    Err: Send + 'static,
+{
+    async fn validate(&self, input: &In) -> Result<(), Err> {
+        self.inner.validate(input).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::{UseCaseExt, clock::SimulatedClock, pipeline::from_fn};
+
+    /// Los pares se memoizan por su valor; los impares no se memoizan.
+    impl MemoKey for u32 {
+        type Key = u32;
+
+        fn memo_key(&self) -> Option<u32> {
+            self.is_multiple_of(2).then_some(*self)
+        }
+    }
+
+    fn counted(calls: &Arc<AtomicUsize>) -> impl UseCase<u32, usize, String> + use<> {
+        let calls = calls.clone();
+        from_fn(move |x: u32| {
+            let n = calls.fetch_add(1, Ordering::SeqCst) + 1;
+            async move {
+                if x == 0 {
+                    Err("cero".to_string())
+                } else {
+                    Ok(n)
+                }
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn repeats_within_the_window_reuse_the_output() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let clock = Arc::new(SimulatedClock::new(1_000));
+        let memo = counted(&calls)
+            .memoize(Duration::from_millis(100))
+            .with_clock(clock.clone());
+
+        assert_eq!(memo.execute(2).await.unwrap(), 1);
+        assert_eq!(memo.execute(2).await.unwrap(), 1);
+        assert_eq!(memo.execute(4).await.unwrap(), 2);
+
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(memo.execute(2).await.unwrap(), 3);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn errors_and_inputs_without_key_are_not_stored() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let memo = counted(&calls).memoize(Duration::from_secs(60));
+
+        assert!(memo.execute(0).await.is_err());
+        assert!(memo.execute(0).await.is_err());
+        assert_eq!(memo.execute(3).await.unwrap(), 3);
+        assert_eq!(memo.execute(3).await.unwrap(), 4);
+        assert!(memo.is_empty());
+
+        // sin ventana no se guarda nada
+        let memo = counted(&calls).memoize(Duration::ZERO);
+        memo.execute(2).await.unwrap();
+        memo.execute(2).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 6);
+    }
+
+    #[tokio::test]
+    async fn a_full_memo_drops_expired_outputs_before_refusing_new_ones() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let clock = Arc::new(SimulatedClock::new(0));
+        let memo = counted(&calls)
+            .memoize(Duration::from_millis(10))
+            .with_clock(clock.clone())
+            .with_max_entries(2);
+
+        memo.execute(2).await.unwrap();
+        memo.execute(4).await.unwrap();
+        // lleno y sin vencidas: 6 no se guarda
+        memo.execute(6).await.unwrap();
+        assert_eq!(memo.len(), 2);
+        assert_eq!(memo.execute(6).await.unwrap(), 4);
+
+        clock.advance(Duration::from_millis(10));
+        memo.execute(8).await.unwrap();
+        assert_eq!(memo.len(), 1);
+    }
+}
@@ -0,0 +1,261 @@
+use std::{
+    fmt::Write,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+/// Límites superiores (inclusive, en ms) de los buckets de latencia, estilo prometheus.
+pub const LATENCY_BUCKETS_MS: [u64; 12] = [1, 2, 5, 10, 25, 50, 100, 250, 500, 1_000, 2_500, 5_000];
+
+/// Histograma de latencia de buckets fijos. Sin locks: todo son contadores atómicos.
+#[derive(Default)]
+pub struct LatencyHistogram {
+    /// Un slot por bucket más uno final para `+Inf`.
+    buckets: [AtomicU64; LATENCY_BUCKETS_MS.len() + 1],
+    count: AtomicU64,
+    errors: AtomicU64,
+    sum_micros: AtomicU64,
+}
+
+impl LatencyHistogram {
+    pub fn record(&self, elapsed: Duration, success: bool) {
+        let ms = elapsed.as_millis() as u64;
+        let idx = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|le| ms <= *le)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+
+        self.buckets[idx].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+
+        if !success {
+            self.errors.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Suma las muestras de `other` a este histograma.
+    pub fn merge_from(&self, other: &LatencyHistogram) {
+        for (mine, theirs) in self.buckets.iter().zip(&other.buckets) {
+            mine.fetch_add(theirs.load(Ordering::Relaxed), Ordering::Relaxed);
+        }
+        self.count.fetch_add(other.count(), Ordering::Relaxed);
+        self.errors.fetch_add(other.errors(), Ordering::Relaxed);
+        self.sum_micros
+            .fetch_add(other.sum_micros(), Ordering::Relaxed);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    pub fn errors(&self) -> u64 {
+        self.errors.load(Ordering::Relaxed)
+    }
+
+    pub fn sum_micros(&self) -> u64 {
+        self.sum_micros.load(Ordering::Relaxed)
+    }
+
+    /// Límite superior (ms) del bucket que contiene el cuantil pedido. `None` si no hay
+    /// muestras o si el cuantil cae en `+Inf`.
+    pub fn quantile_ms(&self, q: f64) -> Option<u64> {
+        let total = self.count();
+        if total == 0 {
+            return None;
+        }
+
+        let target = ((total as f64) * q.clamp(0.0, 1.0)).ceil().max(1.0) as u64;
+        let mut acc = 0u64;
+
+        for (i, le) in LATENCY_BUCKETS_MS.iter().enumerate() {
+            acc += self.buckets[i].load(Ordering::Relaxed);
+            if acc >= target {
+                return Some(*le);
+            }
+        }
+
+        None
+    }
+
+    /// `(le_ms, acumulado <= le_ms)` por bucket, sin `+Inf` (que es `count()`).
+    pub fn cumulative_counts(&self) -> Vec<(u64, u64)> {
+        let mut acc = 0u64;
+        LATENCY_BUCKETS_MS
+            .iter()
+            .enumerate()
+            .map(|(i, le)| {
+                acc += self.buckets[i].load(Ordering::Relaxed);
+                (*le, acc)
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricKind {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+impl MetricKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MetricKind::Counter => "counter",
+            MetricKind::Gauge => "gauge",
+            MetricKind::Histogram => "histogram",
+        }
+    }
+}
+
+/// Escribe métricas en el formato de texto de Prometheus (0.0.4).
+///
+/// Se declara cada familia con `family` y después se agregan sus muestras.
+#[derive(Default)]
+pub struct PrometheusEncoder {
+    out: String,
+}
+
+impl PrometheusEncoder {
+    pub const CONTENT_TYPE: &'static str = "text/plain; version=0.0.4; charset=utf-8";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn family(&mut self, name: &str, kind: MetricKind, help: &str) -> &mut Self {
+        let _ = writeln!(self.out, "# HELP {name} {help}");
+        let _ = writeln!(self.out, "# TYPE {name} {}", kind.as_str());
+        self
+    }
+
+    pub fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) -> &mut Self {
+        self.out.push_str(name);
+        self.write_labels(labels, None);
+        let _ = writeln!(self.out, " {value}");
+        self
+    }
+
+    /// Muestras `_bucket`, `_sum` y `_count` de un histograma, en segundos.
+    pub fn histogram(
+        &mut self,
+        name: &str,
+        labels: &[(&str, &str)],
+        histogram: &LatencyHistogram,
+    ) -> &mut Self {
+        for (le_ms, count) in histogram.cumulative_counts() {
+            let le = (le_ms as f64 / 1_000.0).to_string();
+            let _ = write!(self.out, "{name}_bucket");
+            self.write_labels(labels, Some(&le));
+            let _ = writeln!(self.out, " {count}");
+        }
+
+        let total = histogram.count();
+        let _ = write!(self.out, "{name}_bucket");
+        self.write_labels(labels, Some("+Inf"));
+        let _ = writeln!(self.out, " {total}");
+
+        let _ = write!(self.out, "{name}_sum");
+        self.write_labels(labels, None);
+        let _ = writeln!(self.out, " {}", histogram.sum_micros() as f64 / 1_000_000.0);
+
+        let _ = write!(self.out, "{name}_count");
+        self.write_labels(labels, None);
+        let _ = writeln!(self.out, " {total}");
+        self
+    }
+
+    pub fn finish(self) -> String {
+        self.out
+    }
+
+    fn write_labels(&mut self, labels: &[(&str, &str)], le: Option<&str>) {
+        if labels.is_empty() && le.is_none() {
+            return;
+        }
+        self.out.push('{');
+        let extra = le.map(|le| ("le", le));
+        for (i, (k, v)) in labels.iter().copied().chain(extra).enumerate() {
+            if i > 0 {
+                self.out.push(',');
+            }
+            let _ = write!(self.out, "{k}=\"");
+            for c in v.chars() {
+                match c {
+                    '\\' => self.out.push_str("\\\\"),
+                    '"' => self.out.push_str("\\\""),
+                    '\n' => self.out.push_str("\\n"),
+                    c => self.out.push(c),
+                }
+            }
+            self.out.push('"');
+        }
+        self.out.push('}');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_places_samples_in_buckets() {
+        let h = LatencyHistogram::default();
+        h.record(Duration::from_millis(1), true);
+        h.record(Duration::from_millis(7), true);
+        h.record(Duration::from_millis(40), false);
+
+        assert_eq!(h.count(), 3);
+        assert_eq!(h.errors(), 1);
+        assert_eq!(h.quantile_ms(0.0), Some(1));
+        assert_eq!(h.quantile_ms(0.5), Some(10));
+        assert_eq!(h.quantile_ms(1.0), Some(50));
+    }
+
+    #[test]
+    fn histogram_overflow_goes_to_inf_bucket() {
+        let h = LatencyHistogram::default();
+        h.record(Duration::from_secs(60), true);
+        assert_eq!(h.quantile_ms(0.5), None);
+        assert_eq!(h.cumulative_counts().last().unwrap().1, 0);
+    }
+
+    #[test]
+    fn merged_histograms_add_up() {
+        let a = LatencyHistogram::default();
+        let b = LatencyHistogram::default();
+        a.record(Duration::from_millis(1), true);
+        b.record(Duration::from_millis(300), false);
+
+        a.merge_from(&b);
+        assert_eq!((a.count(), a.errors()), (2, 1));
+        assert_eq!(a.quantile_ms(1.0), Some(500));
+        assert_eq!(b.count(), 1);
+    }
+
+    #[test]
+    fn encoder_writes_prometheus_text() {
+        let h = LatencyHistogram::default();
+        h.record(Duration::from_millis(3), true);
+        h.record(Duration::from_secs(9), false);
+
+        let mut enc = PrometheusEncoder::new();
+        enc.family("reqs_total", MetricKind::Counter, "Requests.")
+            .sample("reqs_total", &[("action", "GET"), ("code", "200")], 4.0)
+            .family("up", MetricKind::Gauge, "Up.")
+            .sample("up", &[], 1.0)
+            .family("lat_seconds", MetricKind::Histogram, "Latency.")
+            .histogram("lat_seconds", &[("node", "a\"b")], &h);
+        let text = enc.finish();
+
+        assert!(text.contains("# TYPE reqs_total counter\n"));
+        assert!(text.contains("reqs_total{action=\"GET\",code=\"200\"} 4\n"));
+        assert!(text.contains("up 1\n"));
+        assert!(text.contains("lat_seconds_bucket{node=\"a\\\"b\",le=\"0.005\"} 1\n"));
+        assert!(text.contains("lat_seconds_bucket{node=\"a\\\"b\",le=\"+Inf\"} 2\n"));
+        assert!(text.contains("lat_seconds_count{node=\"a\\\"b\"} 2\n"));
+        assert!(text.contains("lat_seconds_sum{node=\"a\\\"b\"} 9.003\n"));
+    }
+}
@@ -0,0 +1,53 @@
+/// Separador entre namespace y clave: `{namespace}:{key}`.
+pub const NAMESPACE_SEPARATOR: char = ':';
+
+/// Un namespace válido no puede estar vacío ni contener el separador, espacios o comillas,
+/// para que la clave resultante siga siendo un único token del protocolo.
+pub fn is_valid_namespace(namespace: &str) -> bool {
+    !namespace.is_empty()
+        && !namespace
+            .chars()
+            .any(|c| c == NAMESPACE_SEPARATOR || c == '"' || c.is_whitespace())
+}
+
+/// Construye la clave física a partir de namespace y clave lógica.
+pub fn namespaced_key(namespace: &str, key: &str) -> String {
+    let mut out = String::with_capacity(namespace.len() + 1 + key.len());
+    out.push_str(namespace);
+    out.push(NAMESPACE_SEPARATOR);
+    out.push_str(key);
+    out
+}
+
+/// Separa una clave física en `(namespace, clave)` si tiene prefijo de namespace.
+pub fn split_namespace(key: &str) -> Option<(&str, &str)> {
+    key.split_once(NAMESPACE_SEPARATOR)
+        .filter(|(ns, _)| !ns.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn namespaced_key_round_trips() {
+        let k = namespaced_key("app1", "user:42");
+        assert_eq!(k, "app1:user:42");
+        assert_eq!(split_namespace(&k), Some(("app1", "user:42")));
+    }
+
+    #[test]
+    fn split_namespace_ignores_plain_keys() {
+        assert_eq!(split_namespace("plain"), None);
+        assert_eq!(split_namespace(":leading"), None);
+    }
+
+    #[test]
+    fn namespace_validation() {
+        assert!(is_valid_namespace("tenant-a"));
+        assert!(!is_valid_namespace(""));
+        assert!(!is_valid_namespace("a:b"));
+        assert!(!is_valid_namespace("a b"));
+        assert!(!is_valid_namespace("a\"b"));
+    }
+}
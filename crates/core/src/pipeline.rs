@@ -0,0 +1,233 @@
+use std::{future::Future, marker::PhantomData, time::Duration};
+
+use async_trait::async_trait;
+
+use crate::{
+    memoize::{MemoKey, Memoized},
+    use_case::UseCase,
+};
+
+// Combinadores para componer casos de uso de forma declarativa:
+//
+//   assign.map_output(|o| Ok(RebalanceInput::from(o)))
+//         .then(rebalance)
+//         .then(notify)
+//
+// Cada combinador es a su vez un `UseCase`, así que el flujo completo se puede
+// inyectar y testear como una unidad.
+
+/// Ejecuta `first` y alimenta su salida como entrada de `second`.
+pub struct Then<A, B, Mid> {
+    first: A,
+    second: B,
+    _mid: PhantomData<fn(Mid) -> Mid>,
+}
+
+/// Transforma (de forma falible) la salida de un caso de uso.
+pub struct MapOutput<A, F, Out> {
+    inner: A,
+    f: F,
+    _out: PhantomData<fn(Out) -> Out>,
+}
+
+/// Adapta (de forma falible) la entrada antes de llegar al caso de uso.
+pub struct MapInput<A, F, In> {
+    inner: A,
+    f: F,
+    _in: PhantomData<fn(In) -> In>,
+}
+
+/// Caso de uso a partir de una función async, útil para pasos pequeños y tests.
+pub struct FnUseCase<F> {
+    f: F,
+}
+
+pub fn from_fn<F>(f: F) -> FnUseCase<F> {
+    FnUseCase { f }
+}
+
+#[async_trait]
+impl<In, Mid, Out, Err, A, B> UseCase<In, Out, Err> for Then<A, B, Mid>
+where
+    In: Send + 'static,
+    Mid: Send + 'static,
+    Out: Send + 'static,
+    Err: Send + 'static,
+    A: UseCase<In, Mid, Err>,
+    B: UseCase<Mid, Out, Err>,
+{
+    async fn execute(&self, input: In) -> Result<Out, Err> {
+        let mid = self.first.execute(input).await?;
+        self.second.execute(mid).await
+    }
+}
+
+#[async_trait]
+impl<In, Out, Out2, Err, A, F> UseCase<In, Out2, Err> for MapOutput<A, F, Out>
+where
+    In: Send + 'static,
+    Out: Send + 'static,
+    Out2: Send + 'static,
+    Err: Send + 'static,
+    A: UseCase<In, Out, Err>,
+    F: Fn(Out) -> Result<Out2, Err> + Send + Sync,
+{
+    async fn execute(&self, input: In) -> Result<Out2, Err> {
+        let out = self.inner.execute(input).await?;
+        (self.f)(out)
+    }
+}
+
+#[async_trait]
+impl<In, In2, Out, Err, A, F> UseCase<In2, Out, Err> for MapInput<A, F, In>
+where
+    In: Send + 'static,
+    In2: Send + 'static,
+    Out: Send + 'static,
+    Err: Send + 'static,
+    A: UseCase<In, Out, Err>,
+    F: Fn(In2) -> Result<In, Err> + Send + Sync,
+{
+    async fn execute(&self, input: In2) -> Result<Out, Err> {
+        let input = (self.f)(input)?;
+        self.inner.execute(input).await
+    }
+}
+
+#[async_trait]
+impl<In, Out, Err, F, Fut> UseCase<In, Out, Err> for FnUseCase<F>
+where
+    In: Send + 'static,
+    Out: Send + 'static,
+    Err: Send + 'static,
+    F: Fn(In) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<Out, Err>> + Send,
+{
+    async fn execute(&self, input: In) -> Result<Out, Err> {
+        (self.f)(input).await
+    }
+}
+
+/// Métodos de composición disponibles para cualquier `UseCase`.
+pub trait UseCaseExt<In, Out, Err>: UseCase<In, Out, Err> + Sized
+where
+    In: Send + 'static,
+    Out: Send + 'static,
+    Err: Send + 'static,
+{
+    fn then<B, Next>(self, next: B) -> Then<Self, B, Out>
+    where
+        B: UseCase<Out, Next, Err>,
+        Next: Send + 'static,
+    {
+        Then {
+            first: self,
+            second: next,
+            _mid: PhantomData,
+        }
+    }
+
+    fn map_output<F, Out2>(self, f: F) -> MapOutput<Self, F, Out>
+    where
+        F: Fn(Out) -> Result<Out2, Err> + Send + Sync,
+        Out2: Send + 'static,
+    {
+        MapOutput {
+            inner: self,
+            f,
+            _out: PhantomData,
+        }
+    }
+
+    fn map_input<F, In2>(self, f: F) -> MapInput<Self, F, In>
+    where
+        F: Fn(In2) -> Result<In, Err> + Send + Sync,
+        In2: Send + 'static,
+    {
+        MapInput {
+            inner: self,
+            f,
+            _in: PhantomData,
+        }
+    }
+
+    /// Ver `Memoized`.
+    fn memoize(self, window: Duration) -> Memoized<Self, In, Out>
+    where
+        In: MemoKey,
+        Out: Clone + Sync,
+    {
+        Memoized::new(self, window)
+    }
+}
+
+impl<T, In, Out, Err> UseCaseExt<In, Out, Err> for T
+where
+    T: UseCase<In, Out, Err>,
+    In: Send + 'static,
+    Out: Send + 'static,
+    Err: Send + 'static,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn then_feeds_output_into_next() {
+        let double = from_fn(|x: u32| async move { Ok::<_, String>(x * 2) });
+        let to_text = from_fn(|x: u32| async move { Ok::<_, String>(format!("n={x}")) });
+
+        let pipeline = double.then(to_text);
+        assert_eq!(pipeline.execute(21).await.unwrap(), "n=42");
+    }
+
+    #[tokio::test]
+    async fn errors_short_circuit_the_pipeline() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let calls_clone = calls.clone();
+
+        let fail = from_fn(|_: u32| async move { Err::<u32, _>("boom".to_string()) });
+        let never = from_fn(move |x: u32| {
+            calls_clone.fetch_add(1, Ordering::SeqCst);
+            async move { Ok::<_, String>(x) }
+        });
+
+        let err = fail.then(never).execute(1).await.unwrap_err();
+        assert_eq!(err, "boom");
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn map_output_and_map_input_are_fallible() {
+        let identity = from_fn(|x: u32| async move { Ok::<_, String>(x) });
+
+        let pipeline = identity
+            .map_input(|s: &'static str| s.parse::<u32>().map_err(|e| e.to_string()))
+            .map_output(|x| {
+                if x > 10 {
+                    Ok(x)
+                } else {
+                    Err("too small".to_string())
+                }
+            });
+
+        assert_eq!(pipeline.execute("42").await.unwrap(), 42);
+        assert_eq!(pipeline.execute("3").await.unwrap_err(), "too small");
+        assert!(pipeline.execute("x").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn arc_use_cases_compose() {
+        let shared = Arc::new(from_fn(|x: u32| async move { Ok::<_, String>(x + 1) }));
+
+        let pipeline = shared.clone().then(shared);
+        assert_eq!(pipeline.execute(1).await.unwrap(), 3);
+    }
+}
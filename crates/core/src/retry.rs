@@ -0,0 +1,290 @@
+use std::{future::Future, time::Duration};
+
+use thiserror::Error;
+use tokio_util::sync::CancellationToken;
+
+/// Política de reintentos con backoff exponencial.
+///
+/// El delay del intento `n` (1 = primer reintento) es `initial_delay * multiplier^(n-1)`,
+/// acotado por `max_delay`, y luego se le resta hasta un `jitter` (fracción 0..=1) al azar
+/// para que muchos clientes no reintenten todos al mismo tiempo.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub multiplier: u32,
+    /// `None` = reintentar para siempre (hasta cancelación).
+    pub max_attempts: Option<u32>,
+    pub jitter: f64,
+}
+
+impl RetryPolicy {
+    pub fn new(initial_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            initial_delay,
+            max_delay,
+            multiplier: 2,
+            max_attempts: None,
+            jitter: 0.0,
+        }
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = Some(max_attempts.max(1));
+        self
+    }
+
+    pub fn with_multiplier(mut self, multiplier: u32) -> Self {
+        self.multiplier = multiplier.max(1);
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Delay sin jitter antes del reintento `retry` (1-based).
+    pub fn base_delay(&self, retry: u32) -> Duration {
+        let factor = self
+            .multiplier
+            .checked_pow(retry.saturating_sub(1))
+            .unwrap_or(u32::MAX);
+        self.initial_delay
+            .checked_mul(factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay)
+    }
+
+    /// Delay con jitter aplicado.
+    pub fn delay(&self, retry: u32) -> Duration {
+        let base = self.base_delay(retry);
+        if self.jitter <= 0.0 {
+            return base;
+        }
+        base.mul_f64(1.0 - self.jitter * fastrand::f64())
+    }
+
+    fn can_retry(&self, attempts: u32) -> bool {
+        self.max_attempts.is_none_or(|max| attempts < max)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(500), Duration::from_secs(10)).with_jitter(0.2)
+    }
+}
+
+/// Backoff de un ciclo que se repite sin fin (p. ej. reconectar tras perder una conexión):
+/// cada `next_delay` sin un `reset` de por medio espera más, hasta `max_delay`.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    policy: RetryPolicy,
+    retries: u32,
+}
+
+impl Backoff {
+    pub fn new(policy: RetryPolicy) -> Self {
+        Self { policy, retries: 0 }
+    }
+
+    /// Delay (con jitter) antes del próximo intento.
+    pub fn next_delay(&mut self) -> Duration {
+        self.retries = self.retries.saturating_add(1);
+        self.policy.delay(self.retries)
+    }
+
+    /// Vuelve al delay inicial; se llama cuando un intento salió bien.
+    pub fn reset(&mut self) {
+        self.retries = 0;
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum RetryError<E> {
+    #[error("reintentos agotados tras {attempts} intentos: {last}")]
+    Exhausted { attempts: u32, last: E },
+
+    #[error("reintentos cancelados tras {attempts} intentos")]
+    Cancelled { attempts: u32, last: Option<E> },
+}
+
+impl<E> RetryError<E> {
+    /// Último error de la operación, si llegó a ejecutarse.
+    pub fn into_last(self) -> Option<E> {
+        match self {
+            RetryError::Exhausted { last, .. } => Some(last),
+            RetryError::Cancelled { last, .. } => last,
+        }
+    }
+}
+
+/// Ejecuta `op` hasta que tenga éxito o se agoten los intentos de `policy`.
+/// `op` recibe el número de intento (1-based).
+pub async fn retry_with_backoff<T, E, F, Fut>(
+    policy: &RetryPolicy,
+    op: F,
+) -> Result<T, RetryError<E>>
+where
+    F: FnMut(u32) -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    retry_with_backoff_until(policy, &CancellationToken::new(), op).await
+}
+
+/// Igual que `retry_with_backoff`, pero se corta apenas se cancele `cancel`
+/// (tanto durante la operación como durante la espera).
+pub async fn retry_with_backoff_until<T, E, F, Fut>(
+    policy: &RetryPolicy,
+    cancel: &CancellationToken,
+    mut op: F,
+) -> Result<T, RetryError<E>>
+where
+    F: FnMut(u32) -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut attempts = 0u32;
+    let mut last = None;
+
+    loop {
+        if cancel.is_cancelled() {
+            return Err(RetryError::Cancelled { attempts, last });
+        }
+
+        attempts += 1;
+        let result = tokio::select! {
+            biased;
+            _ = cancel.cancelled() => return Err(RetryError::Cancelled { attempts, last }),
+            r = op(attempts) => r,
+        };
+
+        let err = match result {
+            Ok(v) => return Ok(v),
+            Err(e) => e,
+        };
+
+        if !policy.can_retry(attempts) {
+            return Err(RetryError::Exhausted {
+                attempts,
+                last: err,
+            });
+        }
+        last = Some(err);
+
+        tokio::select! {
+            biased;
+            _ = cancel.cancelled() => return Err(RetryError::Cancelled { attempts, last }),
+            _ = tokio::time::sleep(policy.delay(attempts)) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicU32, Ordering},
+    };
+
+    use super::*;
+
+    fn fast_policy() -> RetryPolicy {
+        RetryPolicy::new(Duration::from_millis(1), Duration::from_millis(4))
+    }
+
+    #[test]
+    fn base_delay_grows_and_is_capped() {
+        let p = RetryPolicy::new(Duration::from_millis(100), Duration::from_millis(500));
+        assert_eq!(p.base_delay(1), Duration::from_millis(100));
+        assert_eq!(p.base_delay(2), Duration::from_millis(200));
+        assert_eq!(p.base_delay(3), Duration::from_millis(400));
+        assert_eq!(p.base_delay(4), Duration::from_millis(500));
+        assert_eq!(p.base_delay(100), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn jitter_only_shortens_the_delay() {
+        let p =
+            RetryPolicy::new(Duration::from_millis(100), Duration::from_secs(1)).with_jitter(0.5);
+        for _ in 0..100 {
+            let d = p.delay(2);
+            assert!(d <= Duration::from_millis(200));
+            assert!(d >= Duration::from_millis(100));
+        }
+    }
+
+    #[test]
+    fn backoff_grows_until_reset() {
+        let mut backoff = Backoff::new(RetryPolicy::new(
+            Duration::from_millis(100),
+            Duration::from_millis(300),
+        ));
+        let delays: Vec<_> = (0..3).map(|_| backoff.next_delay().as_millis()).collect();
+        assert_eq!(delays, [100, 200, 300]);
+
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn retries_until_success() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let c = calls.clone();
+
+        let out = retry_with_backoff(&fast_policy(), |attempt| {
+            c.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if attempt < 3 {
+                    Err("nope")
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(out, Ok(3));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn stops_after_max_attempts() {
+        let out: Result<(), _> =
+            retry_with_backoff(&fast_policy().with_max_attempts(2), |attempt| async move {
+                Err(format!("fallo {attempt}"))
+            })
+            .await;
+
+        assert_eq!(
+            out,
+            Err(RetryError::Exhausted {
+                attempts: 2,
+                last: "fallo 2".to_string()
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn cancellation_interrupts_the_wait() {
+        let cancel = CancellationToken::new();
+        let policy = RetryPolicy::new(Duration::from_secs(60), Duration::from_secs(60));
+
+        let canceller = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            canceller.cancel();
+        });
+
+        let out: Result<(), _> =
+            retry_with_backoff_until(&policy, &cancel, |_| async { Err("down") }).await;
+
+        assert_eq!(
+            out,
+            Err(RetryError::Cancelled {
+                attempts: 1,
+                last: Some("down")
+            })
+        );
+    }
+}
@@ -0,0 +1,335 @@
+use std::{any::Any, future::Future, sync::Arc, time::Duration};
+
+use parking_lot::Mutex;
+use tokio::task::{AbortHandle, JoinHandle};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, warn};
+
+/// Orden de apagado: primero se corta lo que recibe trabajo nuevo, al final lo que
+/// mantiene el estado (reaper, suscriptores).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ShutdownStage {
+    Ingress,
+    Connections,
+    Background,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskPanic {
+    pub task: Arc<str>,
+    pub message: String,
+}
+
+/// Resultado de `Supervisor::shutdown`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// Tareas que terminaron por su cuenta dentro del tiempo de gracia.
+    pub completed: Vec<Arc<str>>,
+    /// Tareas abortadas por no respetar la cancelación a tiempo.
+    pub aborted: Vec<Arc<str>>,
+    /// Todos los pánicos observados durante la vida del supervisor.
+    pub panics: Vec<TaskPanic>,
+}
+
+impl ShutdownReport {
+    pub fn is_clean(&self) -> bool {
+        self.aborted.is_empty() && self.panics.is_empty()
+    }
+}
+
+struct Tracked {
+    name: Arc<str>,
+    stage: ShutdownStage,
+    token: CancellationToken,
+    abort: AbortHandle,
+    watcher: JoinHandle<()>,
+}
+
+/// Tareas registradas. `closed` queda en `true` cuando `shutdown` ya esperó a todas: desde
+/// ahí no se aceptan más.
+#[derive(Default)]
+struct Registry {
+    tasks: Vec<Tracked>,
+    closed: bool,
+}
+
+/// Registro de tareas de fondo con cancelación ordenada por etapas y reporte de pánicos.
+///
+/// Cada tarea recibe su propio `CancellationToken`; al apagar se cancelan las etapas en
+/// orden y se espera a cada una antes de pasar a la siguiente.
+pub struct Supervisor {
+    root: CancellationToken,
+    registry: Mutex<Registry>,
+    panics: Arc<Mutex<Vec<TaskPanic>>>,
+}
+
+impl Supervisor {
+    pub fn new() -> Self {
+        Self {
+            root: CancellationToken::new(),
+            registry: Mutex::new(Registry::default()),
+            panics: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn new_shared() -> Arc<Self> {
+        Arc::new(Self::new())
+    }
+
+    /// Token que se cancela cuando empieza el apagado (para código no supervisado).
+    pub fn token(&self) -> CancellationToken {
+        self.root.child_token()
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.root.is_cancelled()
+    }
+
+    /// Lanza una tarea supervisada. `task` recibe el token que debe observar. Una vez
+    /// terminado `shutdown` no se lanza nada.
+    pub fn spawn<F, Fut>(&self, name: impl Into<Arc<str>>, stage: ShutdownStage, task: F)
+    where
+        F: FnOnce(CancellationToken) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let name = name.into();
+        if self.registry.lock().closed {
+            warn!(target: "supervisor", task = %name, "supervisor already shut down, not spawning");
+            return;
+        }
+        // no es hijo de `root`: cada etapa se cancela por separado
+        let token = CancellationToken::new();
+        if self.is_shutting_down() {
+            token.cancel();
+        }
+
+        let inner = tokio::spawn(task(token.clone()));
+        let abort = inner.abort_handle();
+
+        let panics = self.panics.clone();
+        let task_name = name.clone();
+        let watcher = tokio::spawn(async move {
+            if let Err(e) = inner.await
+                && e.is_panic()
+            {
+                let message = panic_message(e.into_panic());
+                error!(target: "supervisor", task = %task_name, %message, "task panicked");
+                panics.lock().push(TaskPanic {
+                    task: task_name,
+                    message,
+                });
+            }
+        });
+
+        let mut registry = self.registry.lock();
+        // `shutdown` terminó mientras se lanzaba: ya nadie la esperaría
+        if registry.closed {
+            warn!(target: "supervisor", task = %name, "supervisor already shut down, aborting");
+            abort.abort();
+            return;
+        }
+        registry.tasks.retain(|t| !t.watcher.is_finished());
+        registry.tasks.push(Tracked {
+            name,
+            stage,
+            token,
+            abort,
+            watcher,
+        });
+    }
+
+    /// Tareas vivas en este momento.
+    pub fn active(&self) -> Vec<Arc<str>> {
+        self.registry
+            .lock()
+            .tasks
+            .iter()
+            .filter(|t| !t.watcher.is_finished())
+            .map(|t| t.name.clone())
+            .collect()
+    }
+
+    pub fn panics(&self) -> Vec<TaskPanic> {
+        self.panics.lock().clone()
+    }
+
+    /// Cancela etapa por etapa; cada etapa tiene `grace` para terminar antes de abortarse.
+    /// Las tareas lanzadas mientras tanto (una conexión aceptada antes de cortar el accept,
+    /// lo que lanza una tarea al cerrar) nacen canceladas y se esperan en su etapa; el
+    /// registro se cierra recién cuando no queda ninguna.
+    pub async fn shutdown(&self, grace: Duration) -> ShutdownReport {
+        self.root.cancel();
+
+        let mut tasks = Vec::new();
+        let mut report = ShutdownReport::default();
+
+        loop {
+            {
+                let mut registry = self.registry.lock();
+                tasks.append(&mut registry.tasks);
+                if tasks.is_empty() {
+                    registry.closed = true;
+                    break;
+                }
+            }
+            tasks.sort_by_key(|t| t.stage);
+
+            let stage = tasks[0].stage;
+            let split = tasks
+                .iter()
+                .position(|t| t.stage != stage)
+                .unwrap_or(tasks.len());
+            let batch: Vec<Tracked> = tasks.drain(..split).collect();
+
+            for t in &batch {
+                t.token.cancel();
+            }
+
+            let deadline = tokio::time::Instant::now() + grace;
+            for mut t in batch {
+                match tokio::time::timeout_at(deadline, &mut t.watcher).await {
+                    Ok(_) => report.completed.push(t.name),
+                    Err(_) => {
+                        warn!(target: "supervisor", task = %t.name, ?stage, "task ignored cancellation, aborting");
+                        t.abort.abort();
+                        let _ = t.watcher.await;
+                        report.aborted.push(t.name);
+                    }
+                }
+            }
+        }
+
+        report.panics = self.panics();
+        report
+    }
+}
+
+impl Default for Supervisor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "panic sin mensaje".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn stages_are_cancelled_in_order() {
+        let sup = Supervisor::new();
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        for (name, stage) in [
+            ("reaper", ShutdownStage::Background),
+            ("accept", ShutdownStage::Ingress),
+            ("conn", ShutdownStage::Connections),
+        ] {
+            let order = order.clone();
+            sup.spawn(name, stage, move |token| async move {
+                token.cancelled().await;
+                order.lock().push(name);
+            });
+        }
+
+        let report = sup.shutdown(Duration::from_secs(1)).await;
+
+        assert_eq!(*order.lock(), vec!["accept", "conn", "reaper"]);
+        assert_eq!(report.completed.len(), 3);
+        assert!(report.is_clean());
+    }
+
+    #[tokio::test]
+    async fn uncooperative_tasks_are_aborted_after_grace() {
+        let sup = Supervisor::new();
+        sup.spawn("stubborn", ShutdownStage::Connections, |_token| async {
+            std::future::pending::<()>().await;
+        });
+
+        let report = sup.shutdown(Duration::from_millis(10)).await;
+
+        assert_eq!(report.aborted, vec![Arc::<str>::from("stubborn")]);
+        assert!(report.completed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn panics_are_reported() {
+        let sup = Supervisor::new();
+        sup.spawn("boom", ShutdownStage::Background, |_token| async {
+            panic!("kaboom");
+        });
+
+        for _ in 0..50 {
+            if !sup.panics().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+
+        let report = sup.shutdown(Duration::from_millis(10)).await;
+        assert_eq!(report.panics.len(), 1);
+        assert_eq!(report.panics[0].message, "kaboom");
+        assert!(!report.is_clean());
+    }
+
+    #[tokio::test]
+    async fn tasks_spawned_during_shutdown_are_awaited_too() {
+        let sup = Arc::new(Supervisor::new());
+        let closed = Arc::new(Mutex::new(false));
+
+        // al cortarse, la tarea de ingreso lanza otra (una conexión recién aceptada)
+        let (spawner, flag) = (sup.clone(), closed.clone());
+        sup.spawn("accept", ShutdownStage::Ingress, move |token| async move {
+            token.cancelled().await;
+            spawner.spawn(
+                "late",
+                ShutdownStage::Connections,
+                move |token| async move {
+                    token.cancelled().await;
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    *flag.lock() = true;
+                },
+            );
+        });
+
+        let report = sup.shutdown(Duration::from_secs(1)).await;
+        assert!(*closed.lock());
+        assert_eq!(
+            report.completed,
+            vec![Arc::<str>::from("accept"), Arc::from("late")]
+        );
+
+        // con el registro cerrado no se lanza nada
+        let ran = Arc::new(Mutex::new(false));
+        let flag = ran.clone();
+        sup.spawn(
+            "after",
+            ShutdownStage::Background,
+            move |_token| async move {
+                *flag.lock() = true;
+            },
+        );
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert!(!*ran.lock());
+        assert!(sup.active().is_empty());
+    }
+
+    #[tokio::test]
+    async fn finished_tasks_are_pruned() {
+        let sup = Supervisor::new();
+        sup.spawn("quick", ShutdownStage::Background, |_token| async {});
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        assert!(sup.active().is_empty());
+        assert!(!sup.token().is_cancelled());
+    }
+}
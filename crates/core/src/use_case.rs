@@ -11,6 +11,20 @@ where
     async fn execute(&self, input: In) -> Result<Out, Err>;
 }
 
+// Permite componer casos de uso compartidos (p. ej. los `Arc<...>` del módulo DI)
+#[async_trait]
+impl<T, In, Out, Err> UseCase<In, Out, Err> for std::sync::Arc<T>
+where
+    T: UseCase<In, Out, Err> + ?Sized,
+    In: Send + 'static,
+    Out: Send + 'static,
+    Err: Send + 'static,
+{
+    async fn execute(&self, input: In) -> Result<Out, Err> {
+        (**self).execute(input).await
+    }
+}
+
 #[async_trait]
 pub trait UseCaseValidatable<In, Out, Err>: UseCase<In, Out, Err> + Send + Sync
 where
@@ -0,0 +1,230 @@
+//! Filtro de logs ajustable en caliente. Los binarios instalan el subscriber con
+//! `init_logging` y después cambian el filtro (`RUST_LOG`, p. ej. `info,app_net=trace`) con
+//! `LogControl::set_filter`, o releyendo el entorno con SIGHUP, sin reiniciar el proceso.
+
+use std::{
+    path::PathBuf,
+    sync::{Arc, OnceLock},
+};
+
+use parking_lot::RwLock;
+use thiserror::Error;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+use tracing_subscriber::{
+    EnvFilter, Registry, layer::SubscriberExt, reload, util::SubscriberInitExt,
+};
+
+use app_core::error::{ErrorKind, HasErrorKind};
+
+/// Variable de entorno con el filtro inicial (misma sintaxis que `EnvFilter`).
+pub const LOG_FILTER_ENV: &str = "RUST_LOG";
+
+/// Filtro cuando `RUST_LOG` no está definida.
+pub const DEFAULT_LOG_FILTER: &str = "info";
+
+static GLOBAL: OnceLock<LogControl> = OnceLock::new();
+
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum LogFilterError {
+    #[error("filtro inválido '{filter}': {reason}")]
+    Invalid { filter: String, reason: String },
+
+    #[error("no se pudo aplicar el filtro: {0}")]
+    Reload(String),
+
+    #[error("logging no inicializado")]
+    NotInitialized,
+}
+
+impl HasErrorKind for LogFilterError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            LogFilterError::Invalid { .. } => ErrorKind::BadRequest,
+            LogFilterError::Reload(_) => ErrorKind::Internal,
+            LogFilterError::NotInitialized => ErrorKind::Unavailable,
+        }
+    }
+}
+
+pub type FilterLayer = reload::Layer<EnvFilter, Registry>;
+
+/// Handle para leer y reemplazar el filtro activo.
+#[derive(Clone)]
+pub struct LogControl {
+    handle: reload::Handle<EnvFilter, Registry>,
+    current: Arc<RwLock<String>>,
+}
+
+impl LogControl {
+    /// Capa de filtro recargable y su handle. `init_logging` la instala como global; los
+    /// tests pueden montarla en un subscriber local.
+    pub fn layer(initial: &str) -> Result<(FilterLayer, Self), LogFilterError> {
+        let (layer, handle) = reload::Layer::new(parse(initial)?);
+        let control = Self {
+            handle,
+            current: Arc::new(RwLock::new(initial.to_string())),
+        };
+        Ok((layer, control))
+    }
+
+    pub fn current(&self) -> String {
+        self.current.read().clone()
+    }
+
+    /// Reemplaza el filtro. Si `directives` no parsea, el filtro activo no cambia.
+    pub fn set_filter(&self, directives: &str) -> Result<(), LogFilterError> {
+        let filter = parse(directives)?;
+        let mut current = self.current.write();
+
+        self.handle
+            .reload(filter)
+            .map_err(|e| LogFilterError::Reload(e.to_string()))?;
+        *current = directives.trim().to_string();
+        Ok(())
+    }
+
+    /// Para acciones de administración: sin directivas devuelve el filtro activo; con
+    /// directivas lo reemplaza. En ambos casos devuelve el filtro que queda activo.
+    pub fn apply(&self, directives: &str) -> Result<String, LogFilterError> {
+        if !directives.trim().is_empty() {
+            self.set_filter(directives)?;
+        }
+        Ok(self.current())
+    }
+
+    /// Vuelve a cargar `env_files` (pisando variables ya definidas) y aplica `RUST_LOG`,
+    /// o el filtro por defecto si ya no está. Devuelve el filtro aplicado.
+    pub fn reload_from_env(&self, env_files: &[PathBuf]) -> Result<String, LogFilterError> {
+        for file in env_files {
+            let _ = dotenvy::from_path_override(file);
+        }
+
+        let directives = filter_from_env();
+        self.set_filter(&directives)?;
+        Ok(directives)
+    }
+
+    /// Recarga el filtro desde el entorno con cada SIGHUP hasta que `token` se cancele.
+    pub async fn reload_on_sighup(self, env_files: Vec<PathBuf>, token: CancellationToken) {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{SignalKind, signal};
+
+            let mut hangup = match signal(SignalKind::hangup()) {
+                Ok(s) => s,
+                Err(e) => {
+                    warn!("no se pudo escuchar SIGHUP: {e}");
+                    return token.cancelled().await;
+                }
+            };
+
+            loop {
+                tokio::select! {
+                    _ = token.cancelled() => break,
+                    _ = hangup.recv() => match self.reload_from_env(&env_files) {
+                        Ok(filter) => info!(%filter, "filtro de logs recargado"),
+                        Err(e) => warn!("SIGHUP: {e}"),
+                    },
+                }
+            }
+        }
+
+        #[cfg(not(unix))]
+        {
+            let _ = env_files;
+            token.cancelled().await;
+        }
+    }
+}
+
+/// Instala el subscriber global (fmt + filtro recargable) con el filtro de `RUST_LOG`.
+/// Un `RUST_LOG` inválido se avisa y se usa el filtro por defecto. Llamadas siguientes
+/// devuelven el handle ya instalado.
+pub fn init_logging() -> LogControl {
+    if let Some(control) = GLOBAL.get() {
+        return control.clone();
+    }
+
+    let requested = filter_from_env();
+    let (layer, control, rejected) = match LogControl::layer(&requested) {
+        Ok((layer, control)) => (layer, control, None),
+        Err(e) => {
+            let (layer, control) =
+                LogControl::layer(DEFAULT_LOG_FILTER).expect("filtro por defecto válido");
+            (layer, control, Some(e))
+        }
+    };
+
+    tracing_subscriber::registry()
+        .with(layer)
+        .with(tracing_subscriber::fmt::layer())
+        .init();
+
+    if let Some(e) = rejected {
+        warn!("{LOG_FILTER_ENV}: {e}; usando '{DEFAULT_LOG_FILTER}'");
+    }
+
+    let _ = GLOBAL.set(control.clone());
+    control
+}
+
+/// Handle instalado por `init_logging`, si el proceso lo llamó.
+pub fn global() -> Result<&'static LogControl, LogFilterError> {
+    GLOBAL.get().ok_or(LogFilterError::NotInitialized)
+}
+
+fn filter_from_env() -> String {
+    std::env::var(LOG_FILTER_ENV)
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_LOG_FILTER.to_string())
+}
+
+fn parse(directives: &str) -> Result<EnvFilter, LogFilterError> {
+    EnvFilter::builder()
+        .parse(directives.trim())
+        .map_err(|e| LogFilterError::Invalid {
+            filter: directives.trim().to_string(),
+            reason: e.to_string(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing::Level;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+
+    fn enabled(control_layer: FilterLayer, check: impl FnOnce() -> Vec<bool>) -> Vec<bool> {
+        let subscriber = tracing_subscriber::registry().with(control_layer);
+        tracing::subscriber::with_default(subscriber, check)
+    }
+
+    #[test]
+    fn filter_changes_apply_without_reinstalling() {
+        let (layer, control) = LogControl::layer("info").unwrap();
+
+        let seen = enabled(layer, || {
+            let before = tracing::enabled!(target: "conn", Level::TRACE);
+            control.set_filter("info,conn=trace").unwrap();
+            let after = tracing::enabled!(target: "conn", Level::TRACE);
+            let other = tracing::enabled!(target: "otro", Level::DEBUG);
+            vec![before, after, other]
+        });
+
+        assert_eq!(seen, vec![false, true, false]);
+        assert_eq!(control.current(), "info,conn=trace");
+    }
+
+    #[test]
+    fn invalid_filters_keep_the_previous_one() {
+        let (_layer, control) = LogControl::layer("warn").unwrap();
+
+        let err = control.set_filter("conn=ruidoso").unwrap_err();
+        assert!(matches!(err, LogFilterError::Invalid { .. }));
+        assert_eq!(err.kind(), ErrorKind::BadRequest);
+        assert_eq!(control.current(), "warn");
+    }
+}
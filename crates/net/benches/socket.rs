@@ -0,0 +1,76 @@
+//! Costo del round-trip de `Socket::request` según el nivel de logs.
+//!
+//! `verbose` escribe todos los eventos (hasta `trace`) a un sink, como hacía la salida por
+//! `println!`; `info` es el filtro por defecto de los binarios y deja fuera los eventos del
+//! hot path. `cargo bench -p app_net --bench socket`
+
+use std::{io, sync::Arc, time::Duration};
+
+use app_net::{ParsedMsg, RequestDataInput, Socket, parse_line};
+use bytes::Bytes;
+use tokio::{runtime::Runtime, sync::mpsc};
+use tracing_subscriber::{EnvFilter, layer::SubscriberExt};
+
+fn main() {
+    divan::main();
+}
+
+/// Socket cuyo "peer" es una tarea que contesta `RES <id> 200 "ok"` a cada REQ.
+fn echo_socket(rt: &Runtime) -> Arc<Socket> {
+    let (tx, mut rx) = mpsc::unbounded_channel::<Bytes>();
+    let socket = Arc::new(Socket::new("bench".into(), tx, Duration::from_secs(5)));
+    let responder = socket.clone();
+
+    rt.spawn(async move {
+        while let Some(bytes) = rx.recv().await {
+            let line = String::from_utf8_lossy(&bytes);
+            if let Ok(ParsedMsg::Req { data }) = parse_line(&line) {
+                let id = data.id.to_string();
+                let res = format!("RES {id} 200 \"ok\"");
+                responder.handle_response(id, res);
+            }
+        }
+    });
+
+    socket
+}
+
+fn round_trips(bencher: divan::Bencher, filter: Option<&str>) {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let socket = echo_socket(&rt);
+
+    let run = || {
+        bencher.bench_local(|| {
+            rt.block_on(socket.request(RequestDataInput::new("GET", "clave")))
+                .unwrap()
+        })
+    };
+
+    match filter {
+        Some(filter) => {
+            let subscriber = tracing_subscriber::registry()
+                .with(EnvFilter::new(filter))
+                .with(tracing_subscriber::fmt::layer().with_writer(io::sink));
+            tracing::subscriber::with_default(subscriber, run);
+        }
+        None => run(),
+    }
+}
+
+#[divan::bench]
+fn no_subscriber(bencher: divan::Bencher) {
+    round_trips(bencher, None);
+}
+
+#[divan::bench]
+fn info(bencher: divan::Bencher) {
+    round_trips(bencher, Some("info"));
+}
+
+#[divan::bench]
+fn verbose(bencher: divan::Bencher) {
+    round_trips(bencher, Some("trace"));
+}
@@ -0,0 +1,299 @@
+//! Inyección de fallas para tests (feature `chaos`).
+//!
+//! `FaultInjector::wrap_sender` se interpone en el canal de salida de un `Socket` (o de
+//! cualquier writer basado en `mpsc<Bytes>`) y aplica, mensaje por mensaje, pérdidas,
+//! latencia y reordenamiento. `disconnect_all` corta todos los canales envueltos de golpe.
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use bytes::Bytes;
+use parking_lot::{Mutex, RwLock};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+/// Tiempo máximo que un mensaje retenido para reordenar espera a su sucesor.
+const REORDER_WINDOW: Duration = Duration::from_millis(20);
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FaultConfig {
+    /// Probabilidad (0..=1) de descartar cada mensaje.
+    pub drop_rate: f64,
+    /// Latencia fija agregada a cada mensaje.
+    pub latency: Duration,
+    /// Latencia extra al azar, entre 0 y `jitter`.
+    pub jitter: Duration,
+    /// Probabilidad (0..=1) de retener un mensaje y mandarlo después del siguiente.
+    pub reorder_rate: f64,
+}
+
+impl FaultConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_drop_rate(mut self, drop_rate: f64) -> Self {
+        self.drop_rate = drop_rate.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_latency(mut self, latency: Duration, jitter: Duration) -> Self {
+        self.latency = latency;
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn with_reorder_rate(mut self, reorder_rate: f64) -> Self {
+        self.reorder_rate = reorder_rate.clamp(0.0, 1.0);
+        self
+    }
+
+    fn delay(&self) -> Duration {
+        if self.jitter.is_zero() {
+            return self.latency;
+        }
+        self.latency + self.jitter.mul_f64(fastrand::f64())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FaultStats {
+    pub forwarded: u64,
+    pub dropped: u64,
+    pub reordered: u64,
+    pub disconnects: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    forwarded: AtomicU64,
+    dropped: AtomicU64,
+    reordered: AtomicU64,
+    disconnects: AtomicU64,
+}
+
+/// Configuración de fallas compartida por todos los canales que envuelve. Se puede
+/// cambiar en caliente con `set_config`.
+pub struct FaultInjector {
+    config: RwLock<FaultConfig>,
+    kill: Mutex<CancellationToken>,
+    counters: Counters,
+}
+
+impl FaultInjector {
+    pub fn new(config: FaultConfig) -> Arc<Self> {
+        Arc::new(Self {
+            config: RwLock::new(config),
+            kill: Mutex::new(CancellationToken::new()),
+            counters: Counters::default(),
+        })
+    }
+
+    /// Sin fallas hasta que se configure alguna.
+    pub fn passthrough() -> Arc<Self> {
+        Self::new(FaultConfig::default())
+    }
+
+    pub fn config(&self) -> FaultConfig {
+        self.config.read().clone()
+    }
+
+    pub fn set_config(&self, config: FaultConfig) {
+        *self.config.write() = config;
+    }
+
+    /// Corta todos los canales envueltos hasta ahora. Los que se envuelvan después
+    /// funcionan normalmente.
+    pub fn disconnect_all(&self) {
+        let old = std::mem::take(&mut *self.kill.lock());
+        old.cancel();
+        self.counters.disconnects.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Token que se cancela en el próximo `disconnect_all`.
+    pub fn disconnected(&self) -> CancellationToken {
+        self.kill.lock().clone()
+    }
+
+    pub fn stats(&self) -> FaultStats {
+        FaultStats {
+            forwarded: self.counters.forwarded.load(Ordering::Relaxed),
+            dropped: self.counters.dropped.load(Ordering::Relaxed),
+            reordered: self.counters.reordered.load(Ordering::Relaxed),
+            disconnects: self.counters.disconnects.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Devuelve un sender que reenvía a `downstream` aplicando las fallas configuradas.
+    /// Al desconectar, el relay suelta `downstream` y el writer ve el canal cerrado.
+    pub fn wrap_sender(
+        self: &Arc<Self>,
+        downstream: mpsc::UnboundedSender<Bytes>,
+    ) -> mpsc::UnboundedSender<Bytes> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let kill = self.disconnected();
+        tokio::spawn(self.clone().relay(rx, downstream, kill));
+        tx
+    }
+
+    async fn relay(
+        self: Arc<Self>,
+        mut rx: mpsc::UnboundedReceiver<Bytes>,
+        downstream: mpsc::UnboundedSender<Bytes>,
+        kill: CancellationToken,
+    ) {
+        let mut held: Option<Bytes> = None;
+
+        loop {
+            let msg = tokio::select! {
+                biased;
+                _ = kill.cancelled() => return,
+                _ = tokio::time::sleep(REORDER_WINDOW), if held.is_some() => {
+                    // nadie vino a adelantarlo: sale en orden
+                    self.forward(&downstream, held.take().unwrap());
+                    continue;
+                }
+                msg = rx.recv() => msg,
+            };
+
+            let Some(msg) = msg else {
+                if let Some(h) = held.take() {
+                    self.forward(&downstream, h);
+                }
+                return;
+            };
+
+            let config = self.config();
+
+            if config.drop_rate > 0.0 && fastrand::f64() < config.drop_rate {
+                self.counters.dropped.fetch_add(1, Ordering::Relaxed);
+                continue;
+            }
+
+            if held.is_none() && config.reorder_rate > 0.0 && fastrand::f64() < config.reorder_rate
+            {
+                held = Some(msg);
+                continue;
+            }
+
+            let delay = config.delay();
+            if !delay.is_zero() {
+                tokio::select! {
+                    _ = kill.cancelled() => return,
+                    _ = tokio::time::sleep(delay) => {}
+                }
+            }
+
+            self.forward(&downstream, msg);
+            if let Some(h) = held.take() {
+                self.counters.reordered.fetch_add(1, Ordering::Relaxed);
+                self.forward(&downstream, h);
+            }
+        }
+    }
+
+    fn forward(&self, downstream: &mpsc::UnboundedSender<Bytes>, msg: Bytes) {
+        if downstream.send(msg).is_ok() {
+            self.counters.forwarded.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wrapped(
+        config: FaultConfig,
+    ) -> (
+        Arc<FaultInjector>,
+        mpsc::UnboundedSender<Bytes>,
+        mpsc::UnboundedReceiver<Bytes>,
+    ) {
+        let injector = FaultInjector::new(config);
+        let (tx, rx) = mpsc::unbounded_channel();
+        let faulty = injector.wrap_sender(tx);
+        (injector, faulty, rx)
+    }
+
+    #[tokio::test]
+    async fn passthrough_keeps_order() {
+        let (injector, tx, mut rx) = wrapped(FaultConfig::new());
+        for i in 0..5 {
+            tx.send(Bytes::from(i.to_string())).unwrap();
+        }
+        drop(tx);
+
+        let mut out = Vec::new();
+        while let Some(b) = rx.recv().await {
+            out.push(b);
+        }
+        assert_eq!(out, ["0", "1", "2", "3", "4"].map(Bytes::from));
+        assert_eq!(injector.stats().forwarded, 5);
+    }
+
+    #[tokio::test]
+    async fn drop_rate_one_drops_everything() {
+        let (injector, tx, mut rx) = wrapped(FaultConfig::new().with_drop_rate(1.0));
+        for _ in 0..10 {
+            tx.send(Bytes::from_static(b"x")).unwrap();
+        }
+        drop(tx);
+
+        assert!(rx.recv().await.is_none());
+        assert_eq!(injector.stats().dropped, 10);
+    }
+
+    #[tokio::test]
+    async fn reorder_swaps_consecutive_messages() {
+        let (injector, tx, mut rx) = wrapped(FaultConfig::new().with_reorder_rate(1.0));
+        tx.send(Bytes::from_static(b"a")).unwrap();
+        tx.send(Bytes::from_static(b"b")).unwrap();
+
+        assert_eq!(rx.recv().await.unwrap(), "b");
+        assert_eq!(rx.recv().await.unwrap(), "a");
+        assert_eq!(injector.stats().reordered, 1);
+    }
+
+    #[tokio::test]
+    async fn held_message_is_released_without_a_successor() {
+        let (_injector, tx, mut rx) = wrapped(FaultConfig::new().with_reorder_rate(1.0));
+        tx.send(Bytes::from_static(b"lonely")).unwrap();
+
+        let got = tokio::time::timeout(Duration::from_secs(1), rx.recv()).await;
+        assert_eq!(got.unwrap().unwrap(), "lonely");
+    }
+
+    #[tokio::test]
+    async fn latency_delays_delivery() {
+        let (_injector, tx, mut rx) =
+            wrapped(FaultConfig::new().with_latency(Duration::from_millis(30), Duration::ZERO));
+        let start = tokio::time::Instant::now();
+        tx.send(Bytes::from_static(b"slow")).unwrap();
+
+        rx.recv().await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(30));
+    }
+
+    #[tokio::test]
+    async fn disconnect_closes_wrapped_channels_only() {
+        let injector = FaultInjector::passthrough();
+        let (down_a, mut rx_a) = mpsc::unbounded_channel();
+        let tx_a = injector.wrap_sender(down_a);
+
+        injector.disconnect_all();
+        assert!(rx_a.recv().await.is_none());
+        drop(tx_a);
+
+        let (down_b, mut rx_b) = mpsc::unbounded_channel();
+        let tx_b = injector.wrap_sender(down_b);
+        tx_b.send(Bytes::from_static(b"new")).unwrap();
+        assert_eq!(rx_b.recv().await.unwrap(), "new");
+        assert_eq!(injector.stats().disconnects, 1);
+    }
+}
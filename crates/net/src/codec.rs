@@ -0,0 +1,333 @@
+use std::borrow::Cow;
+use std::fmt::{self, Write};
+
+// Reglas del protocolo de línea:
+//   - Los tokens se separan por espacios.
+//   - Un token entre comillas puede contener espacios; dentro de él `\"`, `\\`, `\n`,
+//     `\r` y `\t` se escapan. Fuera de comillas no hay escapes.
+//   - El payload de `REQ`/`RES` es el resto de la línea, siempre emitido entre comillas.
+//
+// `tokenize`/`split_message` devuelven slices prestados salvo que el token tenga escapes.
+
+/// Itera los tokens de una línea.
+pub struct Tokenizer<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Tokenizer<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self { input, pos: 0 }
+    }
+
+    fn skip_spaces(&mut self) {
+        let bytes = self.input.as_bytes();
+        while self.pos < bytes.len() && bytes[self.pos] == b' ' {
+            self.pos += 1;
+        }
+    }
+
+    /// Lee un token entre comillas empezando en `self.pos` (que apunta a `"`).
+    /// Sin comilla de cierre, el token llega hasta el final de la línea.
+    fn read_quoted(&mut self) -> Cow<'a, str> {
+        let bytes = self.input.as_bytes();
+        let start = self.pos + 1;
+        let mut i = start;
+        let mut owned: Option<String> = None;
+        let mut chunk_start = start;
+
+        while i < bytes.len() {
+            match bytes[i] {
+                b'"' => {
+                    self.pos = i + 1;
+                    return finish(self.input, owned, chunk_start, start, i);
+                }
+                b'\\' if i + 1 < bytes.len() => {
+                    let buf = owned.get_or_insert_with(String::new);
+                    buf.push_str(&self.input[chunk_start..i]);
+                    match bytes[i + 1] {
+                        b'n' => buf.push('\n'),
+                        b'r' => buf.push('\r'),
+                        b't' => buf.push('\t'),
+                        // `\"`, `\\` y cualquier otro ASCII escapado se toman literal
+                        b if b.is_ascii() => buf.push(b as char),
+                        _ => {
+                            // escape de un char multibyte: se deja el char tal cual
+                            chunk_start = i + 1;
+                            i += 1;
+                            continue;
+                        }
+                    }
+                    i += 2;
+                    chunk_start = i;
+                }
+                _ => i += 1,
+            }
+        }
+
+        self.pos = bytes.len();
+        finish(self.input, owned, chunk_start, start, bytes.len())
+    }
+
+    fn read_bare(&mut self) -> Cow<'a, str> {
+        let bytes = self.input.as_bytes();
+        let start = self.pos;
+        while self.pos < bytes.len() && bytes[self.pos] != b' ' {
+            self.pos += 1;
+        }
+        Cow::Borrowed(&self.input[start..self.pos])
+    }
+
+    /// Consume el resto de la línea como un único token (payload). Si empieza con
+    /// comillas se decodifica; si no, se devuelve tal cual. `None` si no queda nada.
+    pub fn rest(&mut self) -> Option<Cow<'a, str>> {
+        self.skip_spaces();
+        let bytes = self.input.as_bytes();
+        let mut end = bytes.len();
+        while end > self.pos && (bytes[end - 1] == b'\r' || bytes[end - 1] == b'\n') {
+            end -= 1;
+        }
+        if self.pos >= end {
+            self.pos = bytes.len();
+            return None;
+        }
+
+        let raw = &self.input[self.pos..end];
+        self.pos = bytes.len();
+
+        if raw.starts_with('"') {
+            let mut inner = Tokenizer::new(raw);
+            let token = inner.read_quoted();
+            if raw[inner.pos..].trim().is_empty() {
+                return Some(token);
+            }
+            // compat: payload con comillas sin escapar (`"k "v" 1"`), se quitan solo las exteriores
+            if raw.len() >= 2 && raw.ends_with('"') {
+                return Some(Cow::Borrowed(&raw[1..raw.len() - 1]));
+            }
+        }
+
+        Some(Cow::Borrowed(raw))
+    }
+}
+
+fn finish<'a>(
+    input: &'a str,
+    owned: Option<String>,
+    chunk_start: usize,
+    start: usize,
+    end: usize,
+) -> Cow<'a, str> {
+    match owned {
+        Some(mut buf) => {
+            buf.push_str(&input[chunk_start..end]);
+            Cow::Owned(buf)
+        }
+        None => Cow::Borrowed(&input[start..end]),
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Cow<'a, str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.skip_spaces();
+        if self.pos >= self.input.len() {
+            return None;
+        }
+
+        if self.input.as_bytes()[self.pos] == b'"' {
+            Some(self.read_quoted())
+        } else {
+            Some(self.read_bare())
+        }
+    }
+}
+
+/// Todos los tokens de una línea (argumentos de un payload).
+pub fn tokenize(input: &str) -> Tokenizer<'_> {
+    Tokenizer::new(input)
+}
+
+/// Línea de protocolo: 3 tokens de cabecera (`REQ|RES`, id, acción|código) y el resto
+/// como payload.
+pub fn split_message(input: &str) -> Vec<Cow<'_, str>> {
+    let mut tokens = Tokenizer::new(input);
+    let mut parts: Vec<Cow<'_, str>> = Vec::with_capacity(4);
+
+    while parts.len() < 3 {
+        match tokens.next() {
+            Some(t) => parts.push(t),
+            None => return parts,
+        }
+    }
+
+    if let Some(payload) = tokens.rest() {
+        parts.push(payload);
+    }
+
+    parts
+}
+
+fn needs_escape(c: char) -> bool {
+    matches!(c, '"' | '\\' | '\n' | '\r' | '\t')
+}
+
+/// Formatea un string entre comillas escapando lo necesario. No aloca.
+pub struct Quoted<'a>(pub &'a str);
+
+impl fmt::Display for Quoted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_char('"')?;
+        let mut chunk_start = 0;
+        for (i, c) in self.0.char_indices() {
+            if !needs_escape(c) {
+                continue;
+            }
+            f.write_str(&self.0[chunk_start..i])?;
+            f.write_str(match c {
+                '"' => "\\\"",
+                '\\' => "\\\\",
+                '\n' => "\\n",
+                '\r' => "\\r",
+                _ => "\\t",
+            })?;
+            chunk_start = i + c.len_utf8();
+        }
+        f.write_str(&self.0[chunk_start..])?;
+        f.write_char('"')
+    }
+}
+
+/// Un token tal cual si no requiere comillas, o entre comillas y escapado si sí.
+pub fn encode_token(token: &str) -> Cow<'_, str> {
+    let bare = !token.is_empty()
+        && !token.starts_with('"')
+        && !token.contains(' ')
+        && !token.chars().any(needs_escape);
+
+    if bare {
+        Cow::Borrowed(token)
+    } else {
+        Cow::Owned(Quoted(token).to_string())
+    }
+}
+
+/// Une argumentos en un payload que `tokenize` vuelve a separar igual.
+pub fn encode_args<'a, I>(args: I) -> String
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut out = String::new();
+    for (i, arg) in args.into_iter().enumerate() {
+        if i > 0 {
+            out.push(' ');
+        }
+        out.push_str(&encode_token(arg));
+    }
+    out
+}
+
+/// Deja el primer argumento de `payload` (la clave) y reemplaza cada uno de los demás por
+/// su largo, p. ej. `k "<1048576 bytes>"`, para mostrar un comando sin sus valores.
+pub fn redact_args(payload: &str) -> String {
+    let mut args = tokenize(payload);
+    let mut kept: Vec<String> = args.next().map(Cow::into_owned).into_iter().collect();
+    kept.extend(args.map(|rest| format!("<{} bytes>", rest.len())));
+    encode_args(kept.iter().map(String::as_str))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn toks(s: &str) -> Vec<String> {
+        tokenize(s).map(|t| t.into_owned()).collect()
+    }
+
+    #[test]
+    fn tokenize_bare_and_quoted() {
+        assert_eq!(toks("a  b \"c d\" e"), vec!["a", "b", "c d", "e"]);
+        assert_eq!(toks(""), Vec::<String>::new());
+        assert_eq!(toks("\"\""), vec![""]);
+    }
+
+    #[test]
+    fn quoted_tokens_without_escapes_are_borrowed() {
+        let mut t = tokenize("\"plain value\" x");
+        assert!(matches!(t.next(), Some(Cow::Borrowed("plain value"))));
+        assert!(matches!(t.next(), Some(Cow::Borrowed("x"))));
+    }
+
+    #[test]
+    fn escapes_are_decoded() {
+        assert_eq!(
+            toks(r#""say \"hi\"" "a\\b" "l1\nl2""#),
+            vec!["say \"hi\"", "a\\b", "l1\nl2"]
+        );
+    }
+
+    #[test]
+    fn unterminated_quote_runs_to_end() {
+        assert_eq!(toks("k \"open value"), vec!["k", "open value"]);
+    }
+
+    #[test]
+    fn split_message_keeps_payload_whole() {
+        let parts = split_message("REQ 1 PUT \"k \\\"v w\\\" 10\"\r\n");
+        assert_eq!(parts, vec!["REQ", "1", "PUT", "k \"v w\" 10"]);
+
+        let parts = split_message("REQ 1 GET key");
+        assert_eq!(parts, vec!["REQ", "1", "GET", "key"]);
+
+        assert_eq!(split_message("RES 1 200").len(), 3);
+    }
+
+    #[test]
+    fn split_message_accepts_legacy_unescaped_payload() {
+        let parts = split_message(r#"REQ 1 PUT "k "v" 10""#);
+        assert_eq!(parts[3], r#"k "v" 10"#);
+    }
+
+    #[test]
+    fn encode_args_round_trips() {
+        let cases: &[&[&str]] = &[
+            &["key", "value"],
+            &["key", "with space", "10"],
+            &["k", "quote\"inside", "back\\slash", "new\nline", ""],
+            &["\"starts", "ñandú ☃"],
+        ];
+
+        for args in cases {
+            let encoded = encode_args(args.iter().copied());
+            assert!(!encoded.contains('\n'));
+            assert_eq!(&toks(&encoded), args, "encoded: {encoded}");
+        }
+    }
+
+    #[test]
+    fn nested_payload_round_trips_through_a_line() {
+        let payload = encode_args(["k", "v \"x\" \\", "5"]);
+        let line = format!("REQ 9 PUT {}", Quoted(&payload));
+
+        let parts = split_message(&line);
+        assert_eq!(parts[3], payload);
+        assert_eq!(toks(&parts[3]), vec!["k", "v \"x\" \\", "5"]);
+    }
+
+    #[test]
+    fn request_and_response_display_round_trip() {
+        use crate::{request::RequestData, response::ResponseData};
+
+        for payload in ["", "plain", "k \"v\" 10", "a\\b\nc", "  padded  "] {
+            let line = RequestData::new("7".into(), "PUT", payload).to_string();
+            let parsed = RequestData::parse(line.trim_end()).unwrap();
+            assert_eq!((parsed.id.as_str(), parsed.action), ("7", "PUT"));
+            assert_eq!(parsed.payload, payload);
+
+            let line = ResponseData::new("7".into(), 200, payload.to_string()).to_string();
+            let parsed: ResponseData = line.parse().unwrap();
+            assert_eq!((parsed.code, parsed.payload.as_str()), (200, payload));
+        }
+    }
+}
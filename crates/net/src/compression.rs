@@ -0,0 +1,92 @@
+//! Compresión de respuestas. Quien recibe las respuestas avisa con `EVT COMPRESS
+//! "<algoritmo>"` que las entiende comprimidas; desde ahí el otro extremo manda las que
+//! pasan su umbral (ver `Socket::with_compression`) con la marca pegada al código,
+//! `RES <id> <código>[:<versión>]+<algoritmo> "<payload comprimido en base64>"`, y
+//! `ResponseData` las descomprime al leerlas. Sin el aviso nada viaja comprimido, así que
+//! un extremo que no lo conoce sigue funcionando igual.
+
+use std::{fmt, str::FromStr};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use miniz_oxide::{deflate::compress_to_vec, inflate::decompress_to_vec_with_limit};
+
+use crate::error::SocketError;
+
+pub const COMPRESS: &str = "COMPRESS";
+
+/// Lo más que se acepta descomprimir de una respuesta, contra un payload que se infla
+/// sin medida.
+pub const MAX_INFLATED_BYTES: usize = 512 * 1024 * 1024;
+
+/// Nivel de deflate: de los rápidos, que la compresión va en el camino de cada respuesta.
+const DEFLATE_LEVEL: u8 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Deflate,
+}
+
+impl Compression {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Deflate => "deflate",
+        }
+    }
+
+    /// El payload comprimido y en base64, listo para ir entre comillas.
+    pub fn encode(&self, payload: &str) -> String {
+        match self {
+            Self::Deflate => STANDARD.encode(compress_to_vec(payload.as_bytes(), DEFLATE_LEVEL)),
+        }
+    }
+
+    pub fn decode(&self, encoded: &str) -> Result<String, SocketError> {
+        let bad = |why: String| SocketError::BadMessage(format!("{}: {why}", self.as_str()));
+        let compressed = STANDARD.decode(encoded).map_err(|e| bad(e.to_string()))?;
+        let inflated = match self {
+            Self::Deflate => decompress_to_vec_with_limit(&compressed, MAX_INFLATED_BYTES)
+                .map_err(|e| bad(e.to_string()))?,
+        };
+        String::from_utf8(inflated).map_err(|e| bad(e.to_string()))
+    }
+}
+
+impl FromStr for Compression {
+    type Err = SocketError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "deflate" => Ok(Self::Deflate),
+            other => Err(SocketError::BadMessage(format!(
+                "compresión desconocida: {other}"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deflate_round_trips_and_refuses_garbage() {
+        let payload = "\"doc\" ".repeat(1000);
+        let encoded = Compression::Deflate.encode(&payload);
+        assert!(encoded.len() < payload.len() / 10, "{}", encoded.len());
+        assert_eq!(Compression::Deflate.decode(&encoded).unwrap(), payload);
+
+        assert!(Compression::Deflate.decode("no es base64!").is_err());
+        assert!(
+            Compression::Deflate
+                .decode(&STANDARD.encode("crudo"))
+                .is_err()
+        );
+        assert!("zstd".parse::<Compression>().is_err());
+    }
+}
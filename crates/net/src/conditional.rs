@@ -0,0 +1,57 @@
+//! `GET` condicional: `GET <clave> IF-NOT-VERSION <versión>`. Si la versión de la clave
+//! (la que lee `VERSION`, ver `tx`) es esa, el nodo responde `304` sin el valor; si no,
+//! el valor como siempre. En los dos casos el `RES` trae la versión junto al código
+//! (`RES <id> 200:<versión> "<valor>"`, ver `ResponseData`), así quien pregunta la tiene
+//! para la próxima vez. Con `IF-NOT-VERSION 0` nunca hay `304` (una clave que existe
+//! tiene versión 1 o más): sirve para la primera lectura.
+//!
+//! Solo los `GET` con `IF-NOT-VERSION` reciben la versión, así un par que no la conoce
+//! nunca ve un `RES` con ese formato.
+
+use std::borrow::Cow;
+
+use crate::error::SocketError;
+
+pub const IF_NOT_VERSION: &str = "IF-NOT-VERSION";
+/// Código de la respuesta "no cambió".
+pub const NOT_MODIFIED: u16 = 304;
+
+/// Saca el `IF-NOT-VERSION <versión>` de los argumentos de un `GET` (después de la
+/// clave), si lo hay.
+pub fn take_if_not_version(args: &mut Vec<Cow<'_, str>>) -> Result<Option<u64>, SocketError> {
+    if args.len() < 2 || !args[1].eq_ignore_ascii_case(IF_NOT_VERSION) {
+        return Ok(None);
+    }
+    let version = args
+        .get(2)
+        .and_then(|v| v.parse::<u64>().ok())
+        .ok_or_else(|| SocketError::BadRequest(format!("{IF_NOT_VERSION}: falta la versión")))?;
+    args.drain(1..3);
+    Ok(Some(version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::tokenize;
+
+    #[test]
+    fn the_version_follows_the_key() {
+        let mut args: Vec<_> = tokenize("k IF-NOT-VERSION 7").collect();
+        assert_eq!(take_if_not_version(&mut args).unwrap(), Some(7));
+        assert_eq!(args, ["k"]);
+
+        let mut args: Vec<_> = tokenize("k if-not-version 0 refresh=1s").collect();
+        assert_eq!(take_if_not_version(&mut args).unwrap(), Some(0));
+        assert_eq!(args, ["k", "refresh=1s"]);
+
+        // una clave que se llama así no es la condición
+        let mut args: Vec<_> = tokenize("IF-NOT-VERSION").collect();
+        assert_eq!(take_if_not_version(&mut args).unwrap(), None);
+
+        for bad in ["k IF-NOT-VERSION", "k IF-NOT-VERSION siete"] {
+            let mut args: Vec<_> = tokenize(bad).collect();
+            assert!(take_if_not_version(&mut args).is_err(), "{bad}");
+        }
+    }
+}
@@ -1,6 +1,7 @@
+use app_core::error::{ErrorKind, HasErrorKind};
 use thiserror::Error;
 
-#[derive(Debug, Error)]
+#[derive(Debug, Error, Clone)]
 pub enum SocketError {
     #[error("Canal de escritura cerrado para socket {0}")]
     WriteChannelClosed(String),
@@ -17,9 +18,26 @@ pub enum SocketError {
     #[error("BadRequest: {0}")]
     BadRequest(String),
 
+    #[error("Payload demasiado grande: {0}")]
+    PayloadTooLarge(String),
+
     #[error("Error de conexión: {0}")]
     ConnectionError(String),
 
     #[error("Error interno: {0}")]
     Internal(String),
 }
+
+impl HasErrorKind for SocketError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            SocketError::BadMessage(_) | SocketError::BadRequest(_) => ErrorKind::BadRequest,
+            SocketError::PayloadTooLarge(_) => ErrorKind::PayloadTooLarge,
+            SocketError::Timeout { .. } => ErrorKind::Timeout,
+            SocketError::WriteChannelClosed(_)
+            | SocketError::ResponseChannelClosed { .. }
+            | SocketError::ConnectionError(_) => ErrorKind::Connection,
+            SocketError::Internal(_) => ErrorKind::Internal,
+        }
+    }
+}
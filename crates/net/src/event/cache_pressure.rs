@@ -0,0 +1,133 @@
+use std::{fmt, str::FromStr};
+
+use crate::{codec::tokenize, error::SocketError, stats::memory_ratio};
+
+/// Nombre del `EVT` con el que un nodo reporta la presión sobre su cache.
+pub const CACHE_PRESSURE: &str = "CACHE-PRESSURE";
+
+/// Resumen del último intervalo de un nodo: evicciones, expiraciones e invalidaciones del
+/// intervalo y ocupación al cerrarlo. Viaja como `evictions=.. expirations=.. entries=..
+/// capacity=.. interval_ms=.. bytes=.. max_bytes=.. invalidations=..`; las claves
+/// desconocidas se ignoran.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CachePressure {
+    pub evictions: u64,
+    pub expirations: u64,
+    pub entries: u64,
+    pub capacity: u64,
+    pub interval_ms: u64,
+    /// Clave más valor de todas las entradas.
+    pub bytes: u64,
+    /// Memoria que el nodo se permite (`MAX_MEMORY_BYTES`); 0 es sin tope.
+    pub max_bytes: u64,
+    /// Borradas a pedido en el intervalo; con `evictions` y `expirations` dice de qué
+    /// salen las claves.
+    pub invalidations: u64,
+}
+
+impl CachePressure {
+    /// Ocupación del cache, de 0 a 1.
+    pub fn fill_ratio(&self) -> f64 {
+        if self.capacity == 0 {
+            return 0.0;
+        }
+        self.entries as f64 / self.capacity as f64
+    }
+
+    /// `bytes` sobre `max_bytes`; `None` si el nodo no tiene tope.
+    pub fn memory_ratio(&self) -> Option<f64> {
+        memory_ratio(self.bytes, self.max_bytes)
+    }
+
+    pub fn evictions_per_sec(&self) -> f64 {
+        if self.interval_ms == 0 {
+            return 0.0;
+        }
+        self.evictions as f64 * 1000.0 / self.interval_ms as f64
+    }
+}
+
+impl fmt::Display for CachePressure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "evictions={} expirations={} entries={} capacity={} interval_ms={} bytes={} \
+             max_bytes={} invalidations={}",
+            self.evictions,
+            self.expirations,
+            self.entries,
+            self.capacity,
+            self.interval_ms,
+            self.bytes,
+            self.max_bytes,
+            self.invalidations
+        )
+    }
+}
+
+impl FromStr for CachePressure {
+    type Err = SocketError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut out = Self::default();
+
+        for token in tokenize(s) {
+            let bad = || SocketError::BadMessage(format!("{CACHE_PRESSURE}: {token}"));
+            let (field, value) = token.split_once('=').ok_or_else(bad)?;
+            let slot = match field {
+                "evictions" => &mut out.evictions,
+                "expirations" => &mut out.expirations,
+                "entries" => &mut out.entries,
+                "capacity" => &mut out.capacity,
+                "interval_ms" => &mut out.interval_ms,
+                "bytes" => &mut out.bytes,
+                "max_bytes" => &mut out.max_bytes,
+                "invalidations" => &mut out.invalidations,
+                _ => continue,
+            };
+            *slot = value.parse().map_err(|_| bad())?;
+        }
+
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ParsedMsg, event::EventData, parse_line};
+
+    #[test]
+    fn reports_round_trip_through_an_evt_line() {
+        let report = CachePressure {
+            evictions: 30,
+            expirations: 2,
+            entries: 1000,
+            capacity: 1024,
+            interval_ms: 10_000,
+            bytes: 900,
+            max_bytes: 1000,
+            invalidations: 4,
+        };
+        let line = EventData::new(CACHE_PRESSURE, report.to_string()).to_string();
+
+        let Ok(ParsedMsg::Evt { data }) = parse_line(&line) else {
+            panic!("no es un EVT: {line}");
+        };
+        assert_eq!(data.name, CACHE_PRESSURE);
+        assert_eq!(data.payload.parse::<CachePressure>().unwrap(), report);
+        assert_eq!(report.evictions_per_sec(), 3.0);
+        assert_eq!(report.memory_ratio(), Some(0.9));
+    }
+
+    #[test]
+    fn unknown_fields_are_skipped_and_garbage_rejected() {
+        let report: CachePressure = "entries=5 capacity=10 futuro=1".parse().unwrap();
+        assert_eq!(report.fill_ratio(), 0.5);
+        assert_eq!(report.evictions_per_sec(), 0.0);
+        assert_eq!(report.memory_ratio(), None);
+
+        assert!("entries=cinco".parse::<CachePressure>().is_err());
+        assert!("entries".parse::<CachePressure>().is_err());
+    }
+}
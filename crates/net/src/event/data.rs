@@ -0,0 +1,46 @@
+use crate::{
+    codec::{Quoted, Tokenizer},
+    error::SocketError,
+};
+use std::borrow::Cow;
+use std::fmt;
+
+/// Notificación sin respuesta: `EVT <nombre> "<payload>"`. No lleva id porque nadie la
+/// espera; quien la recibe y no la conoce la ignora.
+#[derive(Debug)]
+pub struct EventData<'a> {
+    pub name: &'a str,
+    pub payload: Cow<'a, str>,
+}
+
+impl<'a> EventData<'a> {
+    #[inline]
+    pub fn new(name: &'a str, payload: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            name,
+            payload: payload.into(),
+        }
+    }
+
+    pub fn parse(s: &'a str) -> Result<Self, SocketError> {
+        let mut tokens = Tokenizer::new(s);
+
+        let (Some(_), Some(name)) = (tokens.next(), tokens.next()) else {
+            return Err(SocketError::BadMessage(s.to_string()));
+        };
+
+        // los nombres son siempre tokens simples, como las acciones
+        let name = match name {
+            Cow::Borrowed(n) if !n.is_empty() => n,
+            _ => return Err(SocketError::BadMessage(s.to_string())),
+        };
+
+        Ok(Self::new(name, tokens.rest().unwrap_or_default()))
+    }
+}
+
+impl fmt::Display for EventData<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "EVT {} {}", self.name, Quoted(&self.payload))
+    }
+}
@@ -0,0 +1,40 @@
+//! Un proceso de nodo puede estar en varios grupos (shards) a la vez. Cada pertenencia se
+//! identifica ante el master como un nodo aparte: la primera con el id del proceso y las
+//! demás con `<id del proceso>+<n>`. Así el master sabe qué miembros comparten máquina.
+
+/// Nombre del `EVT` con el que el master le dice a un miembro en qué grupo quedó; el
+/// payload es el id del grupo (el del primario del shard).
+pub const GROUP_ASSIGNED: &str = "GROUP-ASSIGNED";
+
+/// Separa el id del proceso del número de pertenencia.
+pub const MEMBER_SEPARATOR: char = '+';
+
+/// Id con el que se anuncia la pertenencia `index` del proceso `process_id`; la 0 usa el
+/// id del proceso tal cual.
+pub fn member_id(process_id: &str, index: usize) -> String {
+    if index == 0 {
+        return process_id.to_string();
+    }
+    format!("{process_id}{MEMBER_SEPARATOR}{index}")
+}
+
+/// Proceso al que pertenece `node_id`.
+pub fn process_id(node_id: &str) -> &str {
+    node_id
+        .split_once(MEMBER_SEPARATOR)
+        .map_or(node_id, |(process, _)| process)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn members_share_the_id_of_their_process() {
+        assert_eq!(member_id("nodo-a", 0), "nodo-a");
+        assert_eq!(member_id("nodo-a", 2), "nodo-a+2");
+
+        assert_eq!(process_id("nodo-a+2"), "nodo-a");
+        assert_eq!(process_id("nodo-a"), "nodo-a");
+    }
+}
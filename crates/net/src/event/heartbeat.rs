@@ -0,0 +1,143 @@
+use std::{fmt, str::FromStr};
+
+use crate::{codec::tokenize, error::SocketError};
+
+/// `EVT` que cada nodo le manda periódicamente a cada master (ver `Heartbeat`). El master
+/// estima con ellos si el nodo sigue vivo aunque la conexión no se haya cortado, y contesta
+/// cada uno con otro `HEARTBEAT` cuyo payload es su reloj en ms: así mide la diferencia de
+/// reloj con el nodo como NTP.
+pub const HEARTBEAT: &str = "HEARTBEAT";
+
+/// Payload de `HEARTBEAT` del nodo: `<n> <enviado> [<master> <recibido>]`. `n` sube de a
+/// uno, `enviado` es el reloj del nodo al mandarlo y los dos últimos repiten el último
+/// `HEARTBEAT` del master (su reloj) junto con el del nodo al recibirlo. Un nodo viejo manda
+/// solo `<n>`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Heartbeat {
+    pub seq: u64,
+    pub sent_ms: Option<u64>,
+    pub echo: Option<ClockEcho>,
+}
+
+/// El último `HEARTBEAT` del master visto por el nodo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockEcho {
+    pub master_ms: u64,
+    pub received_ms: u64,
+}
+
+/// Diferencia de reloj de un nodo con el master: `offset_ms` es lo que el del nodo va
+/// adelantado (negativo si atrasa), con un error de hasta la mitad de `rtt_ms`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockSample {
+    pub offset_ms: i64,
+    pub rtt_ms: u64,
+}
+
+impl Heartbeat {
+    /// La diferencia de reloj que da este latido recibido en `received_ms` (reloj del
+    /// master); `None` si no trae el eco de un `HEARTBEAT` del master. El tiempo que el
+    /// nodo tuvo el eco antes de mandarlo no cuenta en el `rtt_ms`.
+    pub fn clock_sample(&self, received_ms: u64) -> Option<ClockSample> {
+        let (sent, echo) = (self.sent_ms?, self.echo?);
+        let (t0, t1, t2, t3) = (
+            echo.master_ms as i64,
+            echo.received_ms as i64,
+            sent as i64,
+            received_ms as i64,
+        );
+        Some(ClockSample {
+            offset_ms: ((t1 - t0) + (t2 - t3)) / 2,
+            rtt_ms: ((t3 - t0) - (t2 - t1)).max(0) as u64,
+        })
+    }
+}
+
+impl fmt::Display for Heartbeat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.seq)?;
+        if let Some(sent) = self.sent_ms {
+            write!(f, " {sent}")?;
+            if let Some(echo) = self.echo {
+                write!(f, " {} {}", echo.master_ms, echo.received_ms)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Heartbeat {
+    type Err = SocketError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = || SocketError::BadMessage(format!("{HEARTBEAT}: {s}"));
+        let numbers = tokenize(s)
+            .map(|token| token.parse::<u64>().map_err(|_| bad()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match numbers[..] {
+            [seq] => Ok(Self {
+                seq,
+                ..Self::default()
+            }),
+            [seq, sent] => Ok(Self {
+                seq,
+                sent_ms: Some(sent),
+                echo: None,
+            }),
+            [seq, sent, master_ms, received_ms] => Ok(Self {
+                seq,
+                sent_ms: Some(sent),
+                echo: Some(ClockEcho {
+                    master_ms,
+                    received_ms,
+                }),
+            }),
+            _ => Err(bad()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heartbeats_round_trip_and_old_ones_still_parse() {
+        let beat = Heartbeat {
+            seq: 7,
+            sent_ms: Some(1_000),
+            echo: Some(ClockEcho {
+                master_ms: 500,
+                received_ms: 510,
+            }),
+        };
+        assert_eq!(beat.to_string().parse::<Heartbeat>().unwrap(), beat);
+
+        let old: Heartbeat = "3".parse().unwrap();
+        assert_eq!((old.seq, old.clock_sample(10)), (3, None));
+        assert!("3 x".parse::<Heartbeat>().is_err());
+        assert!("1 2 3".parse::<Heartbeat>().is_err());
+    }
+
+    #[test]
+    fn the_offset_discounts_the_round_trip() {
+        // el nodo va 200 ms adelantado y cada tramo tarda 5 ms
+        let beat = Heartbeat {
+            seq: 2,
+            sent_ms: Some(10_205 + 900),
+            echo: Some(ClockEcho {
+                master_ms: 10_000,
+                received_ms: 10_205,
+            }),
+        };
+        let sample = beat.clock_sample(10_910).unwrap();
+        assert_eq!(
+            sample,
+            ClockSample {
+                offset_ms: 200,
+                rtt_ms: 10
+            }
+        );
+    }
+}
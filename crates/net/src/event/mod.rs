@@ -0,0 +1,23 @@
+pub mod cache_pressure;
+pub mod data;
+pub mod group;
+pub mod heartbeat;
+pub mod topology;
+
+pub use cache_pressure::{CACHE_PRESSURE, CachePressure};
+pub use data::EventData;
+pub use group::GROUP_ASSIGNED;
+pub use heartbeat::{ClockEcho, ClockSample, HEARTBEAT, Heartbeat};
+pub use topology::{CLUSTER_MAP, ClusterMap, MapUpdate, TOPOLOGY, TopologyChange, TopologyEvent};
+
+/// `EVT` con el que un nodo le avisa al master las claves que vencieron desde el anterior;
+/// el payload son las claves con `encode_args`.
+pub const KEYS_EXPIRED: &str = "KEYS-EXPIRED";
+
+/// `EVT` con el que el master rechaza a un nodo que se identifica con el id de otro que
+/// sigue conectado; el payload es el id. Después cierra la conexión.
+pub const NODE_ID_CONFLICT: &str = "NODE-ID-CONFLICT";
+
+/// `EVT` con el que el master rechaza a un nodo que no pasa `NODE_ALLOW`/`NODE_DENY` o que
+/// tiene un `BAN`; el payload es el motivo. Después cierra la conexión.
+pub const NODE_REFUSED: &str = "NODE-REFUSED";
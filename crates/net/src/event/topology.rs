@@ -0,0 +1,319 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    str::FromStr,
+};
+
+use crate::{
+    codec::{encode_token, tokenize},
+    error::SocketError,
+};
+
+/// Nombre del `EVT` con el que el master avisa un cambio de topología a los clientes
+/// suscritos con `SUBSCRIBE "TOPOLOGY"`.
+pub const TOPOLOGY: &str = "TOPOLOGY";
+
+/// Acción con la que se le pide al master su `ClusterMap`.
+pub const CLUSTER_MAP: &str = "CLUSTER-MAP";
+
+/// Qué cambió. Los shards se nombran por el id de su primario, como en el anillo.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TopologyChange {
+    /// Un shard nuevo: pasa a ser dueño de parte de las claves.
+    ShardAdded { shard_id: String },
+    /// Un nodo entró como réplica de `shard_id`.
+    NodeJoined { node_id: String, shard_id: String },
+    /// Un nodo se fue; si era lo último de su shard llega además `ShardRemoved`.
+    NodeLeft { node_id: String },
+    /// El shard salió del anillo y sus claves pasan a otros.
+    ShardRemoved { shard_id: String },
+}
+
+impl TopologyChange {
+    /// Si cambia qué shard es dueño de cada clave.
+    pub fn moves_ownership(&self) -> bool {
+        matches!(self, Self::ShardAdded { .. } | Self::ShardRemoved { .. })
+    }
+}
+
+/// Un cambio con su número de orden: viaja como `<epoch> <tipo> "<id>" ["<shard>"]`. El
+/// `epoch` sube de a uno, así que un salto dice que se perdió algún aviso y conviene
+/// releer la topología entera.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TopologyEvent {
+    pub epoch: u64,
+    pub change: TopologyChange,
+}
+
+impl fmt::Display for TopologyEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ", self.epoch)?;
+        match &self.change {
+            TopologyChange::ShardAdded { shard_id } => {
+                write!(f, "shard-added {}", encode_token(shard_id))
+            }
+            TopologyChange::NodeJoined { node_id, shard_id } => write!(
+                f,
+                "node-joined {} {}",
+                encode_token(node_id),
+                encode_token(shard_id)
+            ),
+            TopologyChange::NodeLeft { node_id } => {
+                write!(f, "node-left {}", encode_token(node_id))
+            }
+            TopologyChange::ShardRemoved { shard_id } => {
+                write!(f, "shard-removed {}", encode_token(shard_id))
+            }
+        }
+    }
+}
+
+impl FromStr for TopologyEvent {
+    type Err = SocketError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = || SocketError::BadMessage(format!("{TOPOLOGY} inválido: {s}"));
+        let mut parts = tokenize(s);
+        let epoch = parts
+            .next()
+            .and_then(|e| e.parse::<u64>().ok())
+            .ok_or_else(bad)?;
+        let kind = parts.next().ok_or_else(bad)?;
+        let mut id = || parts.next().map(|id| id.into_owned()).ok_or_else(bad);
+
+        let change = match &*kind {
+            "shard-added" => TopologyChange::ShardAdded { shard_id: id()? },
+            "node-joined" => TopologyChange::NodeJoined {
+                node_id: id()?,
+                shard_id: id()?,
+            },
+            "node-left" => TopologyChange::NodeLeft { node_id: id()? },
+            "shard-removed" => TopologyChange::ShardRemoved { shard_id: id()? },
+            _ => return Err(bad()),
+        };
+        Ok(Self { epoch, change })
+    }
+}
+
+/// Qué pasó al aplicar un `TopologyEvent` a un `ClusterMap`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapUpdate {
+    /// Era el siguiente: el mapa quedó en su `epoch`.
+    Applied,
+    /// El mapa ya lo tenía en cuenta; no cambió nada.
+    Stale,
+    /// Faltan avisos entre el mapa y el evento: no se aplicó y hay que releer el mapa.
+    Gap,
+}
+
+/// Los shards con sus nodos tal como los ve un master en el `epoch` de su último aviso de
+/// topología. Viaja como `<epoch>` seguido, por cada shard, de `"<shard>" <n>` y sus `n`
+/// nodos; el primario (mismo id que el shard) está entre ellos mientras siga conectado.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClusterMap {
+    pub epoch: u64,
+    shards: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl ClusterMap {
+    pub fn new(epoch: u64) -> Self {
+        Self {
+            epoch,
+            shards: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_shard<I, S>(mut self, shard_id: impl Into<String>, nodes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.shards
+            .entry(shard_id.into())
+            .or_default()
+            .extend(nodes.into_iter().map(Into::into));
+        self
+    }
+
+    /// Shards ordenados por id, cada uno con sus nodos.
+    pub fn shards(&self) -> impl Iterator<Item = (&str, impl Iterator<Item = &str>)> {
+        self.shards
+            .iter()
+            .map(|(id, nodes)| (id.as_str(), nodes.iter().map(String::as_str)))
+    }
+
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.shards.values().map(BTreeSet::len).sum()
+    }
+
+    pub fn shard_of(&self, node_id: &str) -> Option<&str> {
+        self.shards
+            .iter()
+            .find(|(_, nodes)| nodes.contains(node_id))
+            .map(|(id, _)| id.as_str())
+    }
+
+    /// Aplica `event` si es el que sigue a `epoch`. Los cambios son idempotentes, así que
+    /// un mapa leído justo mientras cambiaba la topología converge igual al aplicarle los
+    /// avisos siguientes.
+    pub fn apply(&mut self, event: &TopologyEvent) -> MapUpdate {
+        if event.epoch <= self.epoch {
+            return MapUpdate::Stale;
+        }
+        if event.epoch > self.epoch + 1 {
+            return MapUpdate::Gap;
+        }
+
+        match &event.change {
+            TopologyChange::ShardAdded { shard_id } => {
+                self.shards
+                    .entry(shard_id.clone())
+                    .or_default()
+                    .insert(shard_id.clone());
+            }
+            TopologyChange::NodeJoined { node_id, shard_id } => {
+                self.shards
+                    .entry(shard_id.clone())
+                    .or_default()
+                    .insert(node_id.clone());
+            }
+            TopologyChange::NodeLeft { node_id } => {
+                for nodes in self.shards.values_mut() {
+                    nodes.remove(node_id);
+                }
+            }
+            TopologyChange::ShardRemoved { shard_id } => {
+                self.shards.remove(shard_id);
+            }
+        }
+        self.epoch = event.epoch;
+        MapUpdate::Applied
+    }
+}
+
+impl fmt::Display for ClusterMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.epoch)?;
+        for (shard_id, nodes) in &self.shards {
+            write!(f, " {} {}", encode_token(shard_id), nodes.len())?;
+            for node_id in nodes {
+                write!(f, " {}", encode_token(node_id))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ClusterMap {
+    type Err = SocketError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = || SocketError::BadMessage(format!("{CLUSTER_MAP} inválido: {s}"));
+        let mut parts = tokenize(s);
+        let epoch = parts
+            .next()
+            .and_then(|e| e.parse::<u64>().ok())
+            .ok_or_else(bad)?;
+
+        let mut map = ClusterMap::new(epoch);
+        while let Some(shard_id) = parts.next() {
+            let count = parts
+                .next()
+                .and_then(|n| n.parse::<usize>().ok())
+                .ok_or_else(bad)?;
+            let nodes = map.shards.entry(shard_id.into_owned()).or_default();
+            for _ in 0..count {
+                nodes.insert(parts.next().ok_or_else(bad)?.into_owned());
+            }
+        }
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_the_wire() {
+        for change in [
+            TopologyChange::ShardAdded {
+                shard_id: "a".into(),
+            },
+            TopologyChange::NodeJoined {
+                node_id: "b+1".into(),
+                shard_id: "a".into(),
+            },
+            TopologyChange::NodeLeft {
+                node_id: "b".into(),
+            },
+            TopologyChange::ShardRemoved {
+                shard_id: "a".into(),
+            },
+        ] {
+            let event = TopologyEvent { epoch: 7, change };
+            assert_eq!(event.to_string().parse::<TopologyEvent>().unwrap(), event);
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_kinds_and_missing_ids() {
+        assert!("1 shard-moved \"a\"".parse::<TopologyEvent>().is_err());
+        assert!("1 node-joined \"a\"".parse::<TopologyEvent>().is_err());
+        assert!("x shard-added \"a\"".parse::<TopologyEvent>().is_err());
+    }
+
+    fn event(epoch: u64, change: TopologyChange) -> TopologyEvent {
+        TopologyEvent { epoch, change }
+    }
+
+    #[test]
+    fn cluster_map_round_trips_through_the_wire() {
+        let map = ClusterMap::new(4)
+            .with_shard("a", ["a", "b+1"])
+            .with_shard("c c", ["c c"])
+            .with_shard("d", Vec::<String>::new());
+        assert_eq!(map.to_string().parse::<ClusterMap>().unwrap(), map);
+        assert_eq!("0".parse::<ClusterMap>().unwrap(), ClusterMap::new(0));
+        assert!("1 \"a\" 2 \"a\"".parse::<ClusterMap>().is_err());
+    }
+
+    #[test]
+    fn cluster_map_applies_events_in_order_and_reports_gaps() {
+        let mut map = ClusterMap::new(1).with_shard("a", ["a"]);
+        let joined = event(
+            2,
+            TopologyChange::NodeJoined {
+                node_id: "b".into(),
+                shard_id: "a".into(),
+            },
+        );
+        assert_eq!(map.apply(&joined), MapUpdate::Applied);
+        assert_eq!(map.apply(&joined), MapUpdate::Stale);
+        assert_eq!(map.shard_of("b"), Some("a"));
+
+        let removed = event(
+            4,
+            TopologyChange::ShardRemoved {
+                shard_id: "a".into(),
+            },
+        );
+        assert_eq!(map.apply(&removed), MapUpdate::Gap);
+        assert_eq!(map.epoch, 2);
+
+        let left = event(
+            3,
+            TopologyChange::NodeLeft {
+                node_id: "a".into(),
+            },
+        );
+        assert_eq!(map.apply(&left), MapUpdate::Applied);
+        assert_eq!((map.shard_count(), map.node_count()), (1, 1));
+        assert_eq!(map.apply(&removed), MapUpdate::Applied);
+        assert_eq!((map.shard_count(), map.node_count()), (0, 0));
+    }
+}
@@ -0,0 +1,265 @@
+//! Lectura de líneas del protocolo sin una reserva por línea. `FrameReader` lee a un
+//! `BytesMut` que se reusa y corta cada línea como un `Bytes` que comparte su memoria;
+//! `parse_frame` la interpreta como `parse_line`, y `RequestDataOwned::from_frame` se
+//! queda con vistas de ella en vez de copiar la acción y el payload.
+//!
+//! Con `with_max_frame`, una línea más larga que el máximo no se junta en memoria: se
+//! descarta hasta su `\n` y `next_frame` devuelve un `FrameTooLarge`, después del cual el
+//! lector sigue con la línea siguiente.
+
+use std::{fmt, io};
+
+use bytes::{Bytes, BytesMut};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::{error::SocketError, message::ParsedMsg, parse_line, types::ReqId};
+
+/// Lugar libre que se asegura antes de cada lectura. El buffer se reusa cuando ya no
+/// quedan líneas vivas de la memoria anterior; si quedan, se reserva otra.
+const READ_CHUNK: usize = 8 * 1024;
+
+/// Payload máximo de un request si no se configura otro (el mismo tope que memcached).
+pub const DEFAULT_MAX_PAYLOAD: usize = 1024 * 1024;
+
+/// Lo que una línea `REQ` suma al payload: el id, la acción y los separadores.
+pub const FRAME_OVERHEAD: usize = 1024;
+
+/// Bytes del principio de una línea demasiado larga que se guardan en `FrameTooLarge`:
+/// alcanzan para `REQ <id> <acción>`.
+const HEAD_LEN: usize = 128;
+
+/// Una línea pasó el máximo de `FrameReader::with_max_frame`. Viaja dentro del
+/// `io::Error` (de tipo `InvalidData`) que devuelve `next_frame`; ver `from_io`.
+#[derive(Debug, Clone)]
+pub struct FrameTooLarge {
+    /// El principio de la línea.
+    pub head: Bytes,
+    /// Largo de la línea completa, sin el `\n`.
+    pub len: usize,
+    pub max: usize,
+}
+
+impl FrameTooLarge {
+    /// El `FrameTooLarge` de un error de `next_frame`, si lo es.
+    pub fn from_io(e: &io::Error) -> Option<&Self> {
+        e.get_ref()?.downcast_ref()
+    }
+
+    /// El id de la línea si es un `REQ`, para contestarle.
+    pub fn req_id(&self) -> Option<ReqId> {
+        let mut words = self.head.split(|b| *b == b' ');
+        if words.next()? != b"REQ" {
+            return None;
+        }
+        let id = std::str::from_utf8(words.next()?).ok()?;
+        // sin la acción detrás, el id pudo quedar cortado por `HEAD_LEN`
+        words.next()?;
+        (!id.is_empty()).then(|| id.to_string())
+    }
+}
+
+impl fmt::Display for FrameTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "línea de {} bytes; el máximo es {}", self.len, self.max)
+    }
+}
+
+impl std::error::Error for FrameTooLarge {}
+
+impl From<FrameTooLarge> for SocketError {
+    fn from(e: FrameTooLarge) -> Self {
+        SocketError::PayloadTooLarge(e.to_string())
+    }
+}
+
+/// Una línea que se está descartando por larga.
+struct Oversized {
+    head: Bytes,
+    len: usize,
+}
+
+pub struct FrameReader<R> {
+    reader: R,
+    buf: BytesMut,
+    /// Hasta dónde del buffer ya se buscó el `\n`.
+    scanned: usize,
+    max_frame: Option<usize>,
+    oversized: Option<Oversized>,
+}
+
+impl<R: AsyncRead + Unpin> FrameReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: BytesMut::with_capacity(READ_CHUNK),
+            scanned: 0,
+            max_frame: None,
+            oversized: None,
+        }
+    }
+
+    /// Líneas de hasta `max` bytes; sin máximo, cualquier largo.
+    pub fn with_max_frame(mut self, max: usize) -> Self {
+        self.max_frame = Some(max);
+        self
+    }
+
+    /// Como `with_max_frame`, para un lector que ya está leyendo (p. ej. cuando recién la
+    /// primera línea dice quién está del otro lado).
+    pub fn set_max_frame(&mut self, max: Option<usize>) {
+        self.max_frame = max;
+    }
+
+    /// La próxima línea, sin el `\n`, o `None` si la conexión se cerró. Una última línea
+    /// sin `\n` también se devuelve. A diferencia de `read_line`, se puede cancelar (p. ej.
+    /// en un `select!`) sin perder lo leído: queda para la próxima llamada.
+    pub async fn next_frame(&mut self) -> io::Result<Option<Bytes>> {
+        loop {
+            if let Some(pos) = self.buf[self.scanned..].iter().position(|b| *b == b'\n') {
+                let end = self.scanned + pos;
+                let mut frame = self.buf.split_to(end + 1);
+                frame.truncate(end);
+                self.scanned = 0;
+                if let Some(oversized) = self.oversized.take() {
+                    return Err(self.too_large(oversized.head, oversized.len + end));
+                }
+                if self.max_frame.is_some_and(|max| end > max) {
+                    let head = frame.freeze().slice(..HEAD_LEN.min(end));
+                    return Err(self.too_large(head, end));
+                }
+                return Ok(Some(frame.freeze()));
+            }
+            self.scanned = self.buf.len();
+
+            if let Some(max) = self.max_frame
+                && (self.oversized.is_some() || self.buf.len() > max)
+            {
+                // lo leído de la línea larga no se guarda: solo su principio y su largo
+                let dropped = self.buf.split();
+                self.scanned = 0;
+                let oversized = self.oversized.get_or_insert_with(|| Oversized {
+                    head: dropped
+                        .clone()
+                        .freeze()
+                        .slice(..HEAD_LEN.min(dropped.len())),
+                    len: 0,
+                });
+                oversized.len += dropped.len();
+            }
+
+            if self.buf.capacity() - self.buf.len() < READ_CHUNK / 4 {
+                self.buf.reserve(READ_CHUNK);
+            }
+            if self.reader.read_buf(&mut self.buf).await? == 0 {
+                self.scanned = 0;
+                if let Some(oversized) = self.oversized.take() {
+                    let len = oversized.len + self.buf.len();
+                    self.buf.clear();
+                    return Err(self.too_large(oversized.head, len));
+                }
+                if self.buf.is_empty() {
+                    return Ok(None);
+                }
+                return Ok(Some(self.buf.split().freeze()));
+            }
+        }
+    }
+
+    fn too_large(&self, head: Bytes, len: usize) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            FrameTooLarge {
+                head,
+                len,
+                max: self.max_frame.unwrap_or(usize::MAX),
+            },
+        )
+    }
+}
+
+/// `parse_line` sobre una línea de `FrameReader`; que no sea UTF-8 es un mensaje inválido.
+pub fn parse_frame(frame: &Bytes) -> Result<ParsedMsg<'_>, SocketError> {
+    let line = std::str::from_utf8(frame)
+        .map_err(|_| SocketError::BadMessage(String::from_utf8_lossy(frame).into_owned()))?;
+    parse_line(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::AsyncWriteExt;
+
+    use super::*;
+    use crate::request::{RequestData, data::RequestDataOwned};
+
+    #[tokio::test]
+    async fn frames_split_on_newlines_across_reads_and_keep_a_trailing_line() {
+        // un buffer de 4 bytes parte las líneas en varias lecturas
+        let (mut tx, rx) = tokio::io::duplex(4);
+        tokio::spawn(async move {
+            tx.write_all(b"REQ 1 GET \"a b\"\n\nRES 1 200 \"x\"\nsin fin")
+                .await
+                .unwrap();
+        });
+
+        let mut frames = FrameReader::new(rx);
+        let mut lines = Vec::new();
+        while let Some(frame) = frames.next_frame().await.unwrap() {
+            lines.push(frame);
+        }
+        assert_eq!(
+            lines,
+            ["REQ 1 GET \"a b\"", "", "RES 1 200 \"x\"", "sin fin"]
+        );
+
+        let Ok(ParsedMsg::Req { data }) = parse_frame(&lines[0]) else {
+            panic!("se esperaba un REQ");
+        };
+        let owned = RequestDataOwned::from_frame(&lines[0], data);
+        assert_eq!((owned.action(), owned.payload()), ("GET", "a b"));
+        assert!(parse_frame(&Bytes::from_static(b"REQ 1 \xff")).is_err());
+    }
+
+    #[test]
+    fn owned_requests_keep_multibyte_and_escaped_payloads() {
+        for (line, payload) in [
+            ("REQ 2 PUT año \"ñandú €\"", "año \"ñandú €\""),
+            ("REQ 3 PUT \"a\\\"ñ\"", "a\"ñ"),
+        ] {
+            let frame = Bytes::copy_from_slice(line.as_bytes());
+            let Ok(ParsedMsg::Req { data }) = parse_frame(&frame) else {
+                panic!("se esperaba un REQ: {line}");
+            };
+            let copied = RequestDataOwned::from(RequestData::new(
+                data.id.clone(),
+                data.action,
+                data.payload.clone(),
+            ));
+            let owned = RequestDataOwned::from_frame(&frame, data);
+            assert_eq!((owned.action(), owned.payload()), ("PUT", payload));
+            assert_eq!((copied.action(), copied.payload()), ("PUT", payload));
+        }
+    }
+
+    #[tokio::test]
+    async fn a_line_over_the_maximum_is_skipped_and_reading_goes_on() {
+        let (mut tx, rx) = tokio::io::duplex(64);
+        let big = "x".repeat(40_000);
+        tokio::spawn(async move {
+            let lines = format!("REQ 7 PUT \"k\" \"{big}\"\nREQ 8 GET \"k\"\n{big}");
+            tx.write_all(lines.as_bytes()).await.unwrap();
+        });
+
+        let mut frames = FrameReader::new(rx).with_max_frame(1024);
+        let e = frames.next_frame().await.unwrap_err();
+        let too_large = FrameTooLarge::from_io(&e).unwrap();
+        assert_eq!(too_large.req_id().as_deref(), Some("7"));
+        assert_eq!((too_large.len, too_large.max), (40_016, 1024));
+
+        let next = frames.next_frame().await.unwrap().unwrap();
+        assert_eq!(next, "REQ 8 GET \"k\"");
+        // la última, sin `\n` ni forma de `REQ`
+        let e = frames.next_frame().await.unwrap_err();
+        assert_eq!(FrameTooLarge::from_io(&e).unwrap().req_id(), None);
+        assert!(frames.next_frame().await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,88 @@
+//! Token de idempotencia de una escritura: un argumento `idem=<token>` entre los opcionales
+//! de un `PUT` (después del valor) o de un `DEL` (después de la clave). Lo genera el
+//! cliente y viaja igual en cada reintento; el nodo recuerda la respuesta de cada token
+//! durante un rato y a un repetido le contesta lo mismo sin volver a aplicarlo.
+
+use std::borrow::Cow;
+
+use crate::error::SocketError;
+
+const PREFIX: &str = "idem=";
+const MAX_LEN: usize = 64;
+
+/// Saca el `idem=<token>` de los argumentos de una escritura, si lo hay. Los `positional`
+/// primeros (clave y valor) no se miran: un valor puede empezar con `idem=`. El token no
+/// puede estar vacío ni pasar de 64 bytes.
+pub fn take_idempotency(
+    args: &mut Vec<Cow<'_, str>>,
+    positional: usize,
+) -> Result<Option<String>, SocketError> {
+    let Some(pos) = args
+        .iter()
+        .skip(positional)
+        .position(|arg| arg.starts_with(PREFIX))
+        .map(|pos| pos + positional)
+    else {
+        return Ok(None);
+    };
+
+    let arg = args.remove(pos);
+    let token = &arg[PREFIX.len()..];
+    if token.is_empty() || token.len() > MAX_LEN {
+        return Err(SocketError::BadRequest(format!("{PREFIX} inválido")));
+    }
+    if args
+        .iter()
+        .skip(positional)
+        .any(|arg| arg.starts_with(PREFIX))
+    {
+        return Err(SocketError::BadRequest(format!("{PREFIX} repetido")));
+    }
+    Ok(Some(token.to_string()))
+}
+
+/// El argumento `idem=...` que lee `take_idempotency`.
+pub fn encode_idempotency(token: &str) -> String {
+    format!("{PREFIX}{token}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::tokenize;
+
+    fn args(payload: &str) -> Vec<Cow<'_, str>> {
+        tokenize(payload).collect()
+    }
+
+    #[test]
+    fn the_token_is_taken_from_the_optional_arguments() {
+        let mut put = args("k v 30s idem=abc tags=a");
+        assert_eq!(
+            take_idempotency(&mut put, 2).unwrap().as_deref(),
+            Some("abc")
+        );
+        assert_eq!(put, ["k", "v", "30s", "tags=a"]);
+
+        let payload = format!("k {}", encode_idempotency("x1"));
+        let mut del = args(&payload);
+        assert_eq!(
+            take_idempotency(&mut del, 1).unwrap().as_deref(),
+            Some("x1")
+        );
+        assert_eq!(del, ["k"]);
+
+        // un valor que empieza con idem= es un valor
+        let mut put = args("k idem=valor");
+        assert_eq!(take_idempotency(&mut put, 2).unwrap(), None);
+        assert_eq!(put.len(), 2);
+    }
+
+    #[test]
+    fn empty_long_or_repeated_tokens_are_refused() {
+        assert!(take_idempotency(&mut args("k idem="), 1).is_err());
+        let long = format!("k idem={}", "x".repeat(65));
+        assert!(take_idempotency(&mut args(&long), 1).is_err());
+        assert!(take_idempotency(&mut args("k idem=a idem=b"), 1).is_err());
+    }
+}
@@ -1,14 +1,62 @@
+#[cfg(feature = "chaos")]
+pub mod chaos;
+pub mod codec;
+pub mod compression;
+pub mod conditional;
 pub mod error;
+pub mod event;
+pub mod frame;
+pub mod idempotency;
 pub mod message;
+pub mod monitor;
+pub mod prefix;
+#[cfg(feature = "quic")]
+pub mod quic;
+pub mod refresh;
 pub mod request;
 pub mod response;
+pub mod ring;
+pub mod snapshot;
 pub mod socket;
+pub mod stats;
+pub mod sticky;
+pub mod tags;
+pub mod timeout;
+pub mod tls;
+pub mod transport;
+pub mod ttl;
+pub mod tx;
 pub mod types;
 pub mod utils;
 
+pub use codec::{Quoted, encode_args, encode_token, redact_args, split_message, tokenize};
+pub use compression::{COMPRESS, Compression};
+pub use conditional::{IF_NOT_VERSION, take_if_not_version};
 pub use error::SocketError;
+pub use event::{CachePressure, ClusterMap, EventData, MapUpdate, TopologyChange, TopologyEvent};
+pub use frame::{DEFAULT_MAX_PAYLOAD, FRAME_OVERHEAD, FrameReader, FrameTooLarge, parse_frame};
+pub use idempotency::{encode_idempotency, take_idempotency};
 pub use message::ParsedMsg;
 pub use message::parse_line;
+pub use monitor::{MonitorEntry, MonitorHub, MonitorOptions};
+pub use prefix::{DEL_PREFIX, DelPrefix};
+#[cfg(feature = "quic")]
+pub use quic::{QuicAcceptor, QuicConnector};
+pub use refresh::{encode_refresh, encode_stale, take_refresh, take_stale};
 pub use request::RequestDataInput;
-pub use response::ResponseData;
+pub use response::{ResponseBody, ResponseData};
+pub use ring::{EXPORT_RANGE, HashRange, key_hash};
+pub use snapshot::SnapshotHeader;
 pub use socket::Socket;
+pub use stats::NodeStats;
+pub use sticky::{encode_read_node, encode_sticky, take_read_node, take_sticky};
+pub use tags::{encode_tags, take_tags};
+pub use timeout::{ActionTimeouts, TimeoutClass};
+pub use tls::{ClusterTls, TlsAcceptor, TlsConnector};
+pub use transport::{
+    Acceptor, BoxedStream, Connector, MemoryNetwork, Peer, QUIC_SCHEME, TcpConnector,
+};
+pub use ttl::{
+    format_duration, format_millis, format_relative, parse_expiry, parse_instant, parse_millis,
+};
+pub use tx::{PutCondition, TxCommand, encode_multi, parse_multi};
@@ -1,4 +1,5 @@
 use crate::error::SocketError;
+use crate::event::EventData;
 use crate::request::RequestData;
 use crate::types::ReqId;
 use crate::utils::split_once_space;
@@ -6,6 +7,7 @@ use crate::utils::split_once_space;
 pub enum ParsedMsg<'a> {
     Req { data: RequestData<'a> },
     Res { id: String, raw_response: &'a str },
+    Evt { data: EventData<'a> },
     Other(&'a str), // Línea cualquiera (compat/log)
 }
 
@@ -32,5 +34,11 @@ pub fn parse_line(line: &str) -> Result<ParsedMsg<'_>, SocketError> {
         });
     }
 
+    if msg.starts_with("EVT ") {
+        return Ok(ParsedMsg::Evt {
+            data: EventData::parse(msg)?,
+        });
+    }
+
     Ok(ParsedMsg::Other(msg))
 }
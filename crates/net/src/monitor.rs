@@ -0,0 +1,352 @@
+//! `MONITOR`: copia en vivo de los comandos que procesa un proceso, enviada como
+//! `EVT MONITOR "<origen> <peer> <acción> <payload>"` a las conexiones suscriptas.
+
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    sync::{
+        Arc, Mutex, MutexGuard, PoisonError,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use tokio::time::Instant;
+
+use crate::{
+    codec::{encode_args, redact_args, tokenize},
+    error::SocketError,
+    event::EventData,
+    socket::Socket,
+};
+
+/// Nombre de la acción y del `EVT` que lleva cada comando observado.
+pub const MONITOR: &str = "MONITOR";
+
+const DEFAULT_SECS: u64 = 60;
+const MAX_SECS: u64 = 3600;
+
+/// Opciones de una suscripción: `secs=<n>` (duración, 60 por defecto y hasta 3600),
+/// `sample=<r>` (fracción de comandos entre 0 y 1; se toma uno de cada `1/r`) y `redact`
+/// (solo se conserva el primer argumento, la clave; el resto se reemplaza por su largo).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MonitorOptions {
+    pub duration: Duration,
+    pub sample_every: u64,
+    pub redact: bool,
+}
+
+impl Default for MonitorOptions {
+    fn default() -> Self {
+        Self {
+            duration: Duration::from_secs(DEFAULT_SECS),
+            sample_every: 1,
+            redact: false,
+        }
+    }
+}
+
+impl MonitorOptions {
+    pub fn parse<I>(tokens: I) -> Result<Self, SocketError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let mut out = Self::default();
+
+        for token in tokens {
+            let token = token.as_ref();
+            let bad = || SocketError::BadRequest(format!("{MONITOR}: opción inválida {token}"));
+            match token.split_once('=') {
+                None if token == "redact" => out.redact = true,
+                Some(("secs", secs)) => {
+                    let secs: u64 = secs.parse().map_err(|_| bad())?;
+                    if secs == 0 || secs > MAX_SECS {
+                        return Err(bad());
+                    }
+                    out.duration = Duration::from_secs(secs);
+                }
+                Some(("sample", rate)) => {
+                    let rate: f64 = rate.parse().map_err(|_| bad())?;
+                    if !(rate > 0.0 && rate <= 1.0) {
+                        return Err(bad());
+                    }
+                    out.sample_every = (1.0 / rate).round().max(1.0) as u64;
+                }
+                _ => return Err(bad()),
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// Forma de argumentos que `parse` vuelve a leer igual.
+    pub fn to_args(&self) -> String {
+        let mut args = vec![format!("secs={}", self.duration.as_secs())];
+        if self.sample_every > 1 {
+            args.push(format!("sample={}", 1.0 / self.sample_every as f64));
+        }
+        if self.redact {
+            args.push("redact".to_string());
+        }
+        encode_args(args.iter().map(String::as_str))
+    }
+}
+
+/// Un comando observado.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonitorEntry<'a> {
+    /// Quién lo procesó (`master` o el id del nodo).
+    pub source: Cow<'a, str>,
+    /// De qué conexión llegó.
+    pub peer: Cow<'a, str>,
+    pub action: Cow<'a, str>,
+    pub payload: Cow<'a, str>,
+}
+
+impl<'a> MonitorEntry<'a> {
+    pub fn new(source: &'a str, peer: &'a str, action: &'a str, payload: &'a str) -> Self {
+        Self {
+            source: source.into(),
+            peer: peer.into(),
+            action: action.into(),
+            payload: payload.into(),
+        }
+    }
+
+    pub fn to_payload(&self) -> String {
+        encode_args([&*self.source, &self.peer, &self.action, &self.payload])
+    }
+
+    pub fn parse(payload: &'a str) -> Result<Self, SocketError> {
+        let mut parts = tokenize(payload);
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(source), Some(peer), Some(action)) => Ok(Self {
+                source,
+                peer,
+                action,
+                payload: parts.next().unwrap_or_default(),
+            }),
+            _ => Err(SocketError::BadMessage(format!("{MONITOR}: {payload}"))),
+        }
+    }
+
+    fn redacted(&self) -> MonitorEntry<'_> {
+        MonitorEntry {
+            source: Cow::Borrowed(&self.source),
+            peer: Cow::Borrowed(&self.peer),
+            action: Cow::Borrowed(&self.action),
+            payload: redact_args(&self.payload).into(),
+        }
+    }
+}
+
+struct Subscription {
+    socket: Arc<Socket>,
+    options: MonitorOptions,
+    until: Instant,
+    seen: u64,
+}
+
+/// Suscripciones activas de un proceso, una por conexión: suscribirse de nuevo desde la
+/// misma conexión reemplaza las opciones. Cada una se descarta al vencer o cuando su
+/// conexión se cierra.
+#[derive(Default)]
+pub struct MonitorHub {
+    subscriptions: Mutex<HashMap<String, Subscription>>,
+    /// Atajo para no tomar el lock en cada comando cuando nadie mira; solo se escribe con
+    /// el lock tomado.
+    active: AtomicBool,
+}
+
+impl MonitorHub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribe(&self, socket: Arc<Socket>, options: MonitorOptions) {
+        let mut subs = self.lock();
+        subs.insert(
+            socket.id.clone(),
+            Subscription {
+                socket,
+                options,
+                until: Instant::now() + options.duration,
+                seen: 0,
+            },
+        );
+        self.active.store(true, Ordering::Relaxed);
+    }
+
+    /// Suelta la suscripción de la conexión `socket_id` (p. ej. al cerrarse).
+    pub fn unsubscribe(&self, socket_id: &str) {
+        let mut subs = self.lock();
+        subs.remove(socket_id);
+        self.active.store(!subs.is_empty(), Ordering::Relaxed);
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    /// Hasta cuándo hay alguien mirando, `None` si nadie.
+    pub fn watched_until(&self) -> Option<Instant> {
+        self.lock().values().map(|s| s.until).max()
+    }
+
+    /// Reparte `entry` entre las suscripciones, cada una con su muestreo y redacción.
+    pub fn record(&self, entry: &MonitorEntry<'_>) {
+        if !self.is_active() {
+            return;
+        }
+
+        let now = Instant::now();
+        let mut redacted: Option<String> = None;
+        let mut plain: Option<String> = None;
+
+        let mut subs = self.lock();
+        subs.retain(|_, sub| {
+            if sub.until <= now {
+                return false;
+            }
+            sub.seen += 1;
+            if (sub.seen - 1) % sub.options.sample_every != 0 {
+                return true;
+            }
+
+            let payload = if sub.options.redact {
+                redacted.get_or_insert_with(|| entry.redacted().to_payload())
+            } else {
+                plain.get_or_insert_with(|| entry.to_payload())
+            };
+            sub.socket
+                .send_evt(&EventData::new(MONITOR, payload.as_str()))
+                .is_ok()
+        });
+        self.active.store(!subs.is_empty(), Ordering::Relaxed);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Subscription>> {
+        // nada de lo que corre con el lock puede dejar el map a medias
+        self.subscriptions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::{ParsedMsg, parse_line};
+
+    fn subscriber(id: &str) -> (Arc<Socket>, mpsc::UnboundedReceiver<Bytes>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (
+            Arc::new(Socket::new(id.into(), tx, Duration::from_secs(1))),
+            rx,
+        )
+    }
+
+    fn entry(n: usize) -> MonitorEntry<'static> {
+        MonitorEntry {
+            payload: format!("k{n} \"valor secreto\" 30s").into(),
+            ..MonitorEntry::new("master", "client-1", "PUT", "")
+        }
+    }
+
+    /// Payloads de los `EVT MONITOR` recibidos.
+    fn received(rx: &mut mpsc::UnboundedReceiver<Bytes>) -> Vec<String> {
+        let mut out = Vec::new();
+        while let Ok(bytes) = rx.try_recv() {
+            let line = String::from_utf8(bytes.to_vec()).unwrap();
+            let Ok(ParsedMsg::Evt { data }) = parse_line(&line) else {
+                panic!("no es un EVT: {line}");
+            };
+            assert_eq!(data.name, MONITOR);
+            out.push(data.payload.into_owned());
+        }
+        out
+    }
+
+    #[test]
+    fn options_parse_and_round_trip() {
+        let opts = MonitorOptions::parse(["secs=5", "sample=0.25", "redact"]).unwrap();
+        assert_eq!(opts.duration, Duration::from_secs(5));
+        assert_eq!((opts.sample_every, opts.redact), (4, true));
+        assert_eq!(
+            MonitorOptions::parse(tokenize(&opts.to_args())).unwrap(),
+            opts
+        );
+
+        for bad in [
+            "secs=0",
+            "secs=9999",
+            "sample=0",
+            "sample=2",
+            "verbose",
+            "x=1",
+        ] {
+            assert!(MonitorOptions::parse([bad]).is_err(), "{bad}");
+        }
+    }
+
+    #[tokio::test]
+    async fn each_subscription_gets_its_own_sampling_and_redaction() {
+        let hub = MonitorHub::new();
+        assert!(!hub.is_active());
+
+        let (all, mut all_rx) = subscriber("a");
+        let (sampled, mut sampled_rx) = subscriber("b");
+        hub.subscribe(all, MonitorOptions::default());
+        hub.subscribe(
+            sampled,
+            MonitorOptions {
+                sample_every: 2,
+                redact: true,
+                ..Default::default()
+            },
+        );
+
+        for n in 0..4 {
+            hub.record(&entry(n));
+        }
+
+        let all: Vec<String> = (0..4).map(|n| entry(n).to_payload()).collect();
+        assert_eq!(received(&mut all_rx), all);
+
+        let sampled = received(&mut sampled_rx);
+        assert_eq!(sampled.len(), 2);
+        let second = MonitorEntry::parse(&sampled[1]).unwrap();
+        assert_eq!((&*second.source, &*second.action), ("master", "PUT"));
+        assert_eq!(second.payload, "k2 \"<13 bytes>\" \"<3 bytes>\"");
+    }
+
+    #[tokio::test]
+    async fn closed_or_expired_subscriptions_are_dropped() {
+        let hub = MonitorHub::new();
+
+        let (closed, closed_rx) = subscriber("a");
+        let (expiring, mut expiring_rx) = subscriber("b");
+        hub.subscribe(closed, MonitorOptions::default());
+        hub.subscribe(
+            expiring,
+            MonitorOptions {
+                duration: Duration::from_millis(50),
+                ..Default::default()
+            },
+        );
+        drop(closed_rx);
+
+        hub.record(&entry(0));
+        assert_eq!(received(&mut expiring_rx).len(), 1);
+        assert!(hub.is_active());
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        hub.record(&entry(1));
+        assert!(received(&mut expiring_rx).is_empty());
+        assert!(!hub.is_active());
+    }
+}
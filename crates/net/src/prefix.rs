@@ -0,0 +1,71 @@
+//! `DEL-PREFIX "<prefijo>" ["dry-run"]`: borra las claves que empiezan con el prefijo y
+//! responde cuántas eran; con `dry-run` solo las cuenta. El master lo manda a todos los
+//! shards y suma.
+
+use std::fmt;
+
+use crate::{
+    codec::{encode_args, tokenize},
+    error::SocketError,
+};
+
+pub const DEL_PREFIX: &str = "DEL-PREFIX";
+pub const DRY_RUN: &str = "dry-run";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DelPrefix {
+    /// Nunca vacío: un prefijo vacío borraría todo.
+    pub prefix: String,
+    pub dry_run: bool,
+}
+
+impl DelPrefix {
+    pub fn parse(payload: &str) -> Result<Self, SocketError> {
+        let bad = |why: &str| SocketError::BadRequest(format!("{DEL_PREFIX}: {why}"));
+
+        let mut args = tokenize(payload);
+        let prefix = args.next().unwrap_or_default().into_owned();
+        if prefix.is_empty() {
+            return Err(bad("falta el prefijo"));
+        }
+        let dry_run = match args.next() {
+            None => false,
+            Some(arg) if arg.eq_ignore_ascii_case(DRY_RUN) => true,
+            Some(arg) => return Err(bad(&format!("argumento inválido {arg}"))),
+        };
+        if args.next().is_some() {
+            return Err(bad("sobran argumentos"));
+        }
+        Ok(Self { prefix, dry_run })
+    }
+}
+
+impl fmt::Display for DelPrefix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let dry_run = self.dry_run.then_some(DRY_RUN);
+        let args = std::iter::once(self.prefix.as_str()).chain(dry_run);
+        f.write_str(&encode_args(args))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn del_prefix_round_trips_and_refuses_an_empty_prefix() {
+        let dry = DelPrefix {
+            prefix: "user 42:".into(),
+            dry_run: true,
+        };
+        assert_eq!(DelPrefix::parse(&dry.to_string()).unwrap(), dry);
+        let real = DelPrefix::parse("session:").unwrap();
+        assert!(!real.dry_run);
+        assert_eq!(DelPrefix::parse(&real.to_string()).unwrap(), real);
+
+        assert!(DelPrefix::parse("").is_err());
+        assert!(DelPrefix::parse("\"\" dry-run").is_err());
+        assert!(DelPrefix::parse("a now").is_err());
+        assert!(DelPrefix::parse("a dry-run extra").is_err());
+    }
+}
@@ -0,0 +1,345 @@
+//! QUIC (quinn) para los enlaces del cluster que cruzan datacenters. Usa el mismo TLS mutuo
+//! que `tls` (QUIC no anda sin TLS) y entrega cada stream bidireccional como una conexión
+//! más de `Connector`/`Acceptor`, así que el resto del código no se entera del cambio.
+//!
+//! Se elige por dirección: `quic://host:puerto` va por QUIC y cualquier otra por el
+//! `Connector` de siempre. Todas las conexiones a una misma dirección son streams de una
+//! sola conexión QUIC: un stream trabado por una pérdida no frena a los demás, y una
+//! conexión nueva no paga otro handshake.
+
+use std::{
+    collections::HashMap,
+    io,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use quinn::{
+    Connection, Endpoint, Incoming, TransportConfig,
+    congestion::BbrConfig,
+    crypto::rustls::{QuicClientConfig, QuicServerConfig},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    sync::mpsc,
+    task::JoinSet,
+};
+use tokio_rustls::rustls::pki_types::CertificateDer;
+use tracing::warn;
+
+use crate::{
+    tls::{ClusterTls, HANDSHAKE_TIMEOUT, cert_identity, host, invalid, server_name},
+    transport::{Acceptor, BoxedStream, Connector, Peer, QUIC_SCHEME},
+};
+
+const ALPN: &[u8] = b"cache-cluster/1";
+
+/// Primer byte de cada stream. Un stream de QUIC no le llega al otro lado hasta que tiene
+/// datos, y en algunos enlaces el que abre la conexión espera a que le hablen primero.
+const STREAM_OPEN: u8 = 1;
+
+/// Para que un enlace sin tráfico no se cierre por inactividad ni lo olvide un NAT.
+const KEEP_ALIVE: Duration = Duration::from_secs(5);
+
+impl ClusterTls {
+    /// Escucha QUIC en `addr` (UDP) y exige a cada conexión un certificado de cliente
+    /// firmado por la CA, como `acceptor`.
+    pub fn quic_acceptor(&self, addr: SocketAddr) -> io::Result<QuicAcceptor> {
+        let mut tls = self.server_config()?;
+        tls.alpn_protocols = vec![ALPN.to_vec()];
+        let crypto = QuicServerConfig::try_from(tls).map_err(|e| invalid(e.to_string()))?;
+        let mut config = quinn::ServerConfig::with_crypto(Arc::new(crypto));
+        config.transport_config(transport());
+
+        let (streams_tx, streams) = mpsc::unbounded_channel();
+        Ok(QuicAcceptor {
+            endpoint: Endpoint::server(config, addr)?,
+            connections: JoinSet::new(),
+            streams_tx,
+            streams,
+        })
+    }
+
+    /// Las direcciones `quic://` van por QUIC con el certificado propio; las demás, por
+    /// `inner`.
+    pub fn quic_connector<C: Connector>(&self, inner: C) -> io::Result<QuicConnector<C>> {
+        let mut tls = self.client_config()?;
+        tls.alpn_protocols = vec![ALPN.to_vec()];
+        let crypto = QuicClientConfig::try_from(tls).map_err(|e| invalid(e.to_string()))?;
+        let mut config = quinn::ClientConfig::new(Arc::new(crypto));
+        config.transport_config(transport());
+
+        Ok(QuicConnector {
+            inner,
+            config,
+            server_name: None,
+            endpoints: Mutex::new([None, None]),
+            connections: Mutex::new(HashMap::new()),
+        })
+    }
+}
+
+/// BBR en vez de Cubic: no toma cada paquete perdido como congestión, que en un enlace
+/// largo con pérdidas deja la ventana en el piso.
+fn transport() -> Arc<TransportConfig> {
+    let mut transport = TransportConfig::default();
+    transport
+        .keep_alive_interval(Some(KEEP_ALIVE))
+        .congestion_controller_factory(Arc::new(BbrConfig::default()));
+    Arc::new(transport)
+}
+
+/// Entrega cada stream de las conexiones QUIC que completaron el handshake, con la
+/// identidad del certificado del cliente en `Peer::identity`. `Peer::addr` es
+/// `ip:puerto/<stream>`.
+pub struct QuicAcceptor {
+    endpoint: Endpoint,
+    /// Una tarea por conexión: handshake y después sus streams.
+    connections: JoinSet<()>,
+    streams_tx: mpsc::UnboundedSender<(BoxedStream, Peer)>,
+    streams: mpsc::UnboundedReceiver<(BoxedStream, Peer)>,
+}
+
+impl QuicAcceptor {
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.endpoint.local_addr()
+    }
+}
+
+#[async_trait]
+impl Acceptor for QuicAcceptor {
+    async fn accept(&mut self) -> io::Result<(BoxedStream, Peer)> {
+        loop {
+            tokio::select! {
+                Some(stream) = self.streams.recv() => return Ok(stream),
+                incoming = self.endpoint.accept() => {
+                    let incoming = incoming.ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::NotConnected, "endpoint QUIC cerrado")
+                    })?;
+                    self.connections.spawn(serve(incoming, self.streams_tx.clone()));
+                }
+                Some(_) = self.connections.join_next() => {}
+            }
+        }
+    }
+}
+
+async fn serve(incoming: Incoming, streams: mpsc::UnboundedSender<(BoxedStream, Peer)>) {
+    let remote = incoming.remote_address();
+    let connection = match tokio::time::timeout(HANDSHAKE_TIMEOUT, incoming).await {
+        Ok(Ok(connection)) => connection,
+        Ok(Err(e)) => return warn!(target: "quic", %remote, "handshake fallido: {e}"),
+        Err(_) => return warn!(target: "quic", %remote, "handshake fallido: timeout"),
+    };
+    let identity = connection
+        .peer_identity()
+        .and_then(|certs| certs.downcast::<Vec<CertificateDer<'static>>>().ok())
+        .and_then(|certs| certs.first().and_then(cert_identity));
+
+    // hasta que el cliente cierre la conexión
+    while let Ok((send, mut recv)) = connection.accept_bi().await {
+        match tokio::time::timeout(HANDSHAKE_TIMEOUT, recv.read_u8()).await {
+            Ok(Ok(STREAM_OPEN)) => {}
+            _ => {
+                warn!(target: "quic", %remote, "stream sin apertura");
+                continue;
+            }
+        }
+        let peer = Peer {
+            addr: format!("{remote}/{}", send.id().index()),
+            identity: identity.clone(),
+        };
+        let stream: BoxedStream = Box::new(tokio::io::join(recv, send));
+        if streams.send((stream, peer)).is_err() {
+            break;
+        }
+    }
+}
+
+/// Abre un stream por `connect` sobre la conexión QUIC de esa dirección, que se arma la
+/// primera vez y se vuelve a armar si se cayó. El nombre que se verifica en el
+/// certificado del servidor es el host de la dirección, salvo que se fije otro con
+/// `with_server_name`.
+pub struct QuicConnector<C> {
+    inner: C,
+    config: quinn::ClientConfig,
+    server_name: Option<String>,
+    /// IPv4 e IPv6, cada uno se abre al conectar a la primera dirección de su familia.
+    endpoints: Mutex<[Option<Endpoint>; 2]>,
+    connections: Mutex<HashMap<String, Connection>>,
+}
+
+impl<C> QuicConnector<C> {
+    pub fn with_server_name(mut self, name: &str) -> io::Result<Self> {
+        server_name(name)?;
+        self.server_name = Some(name.to_string());
+        Ok(self)
+    }
+
+    async fn connection(&self, addr: &str) -> io::Result<Connection> {
+        if let Some(connection) = self.connections.lock().get(addr)
+            && connection.close_reason().is_none()
+        {
+            return Ok(connection.clone());
+        }
+
+        let remote = tokio::net::lookup_host(addr)
+            .await?
+            .next()
+            .ok_or_else(|| invalid(format!("{addr}: no resuelve")))?;
+        let name = self.server_name.as_deref().unwrap_or_else(|| host(addr));
+        let connecting = self
+            .endpoint(remote)?
+            .connect_with(self.config.clone(), remote, name)
+            .map_err(|e| invalid(format!("{addr}: {e}")))?;
+        let connection = tokio::time::timeout(HANDSHAKE_TIMEOUT, connecting)
+            .await
+            .map_err(|_| io::Error::from(io::ErrorKind::TimedOut))??;
+
+        self.connections
+            .lock()
+            .insert(addr.to_string(), connection.clone());
+        Ok(connection)
+    }
+
+    fn endpoint(&self, remote: SocketAddr) -> io::Result<Endpoint> {
+        let mut endpoints = self.endpoints.lock();
+        let (slot, local) = match remote {
+            SocketAddr::V4(_) => (
+                &mut endpoints[0],
+                SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+            ),
+            SocketAddr::V6(_) => (
+                &mut endpoints[1],
+                SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)),
+            ),
+        };
+        if let Some(endpoint) = slot {
+            return Ok(endpoint.clone());
+        }
+        Ok(slot.insert(Endpoint::client(local)?).clone())
+    }
+}
+
+#[async_trait]
+impl<C: Connector> Connector for QuicConnector<C> {
+    async fn connect(&self, addr: &str) -> io::Result<BoxedStream> {
+        let Some(addr) = addr.strip_prefix(QUIC_SCHEME) else {
+            return self.inner.connect(addr).await;
+        };
+        let connection = self.connection(addr).await?;
+        let (mut send, recv) = connection.open_bi().await.inspect_err(|_| {
+            self.connections.lock().remove(addr);
+        })?;
+        send.write_u8(STREAM_OPEN).await?;
+        Ok(Box::new(tokio::io::join(recv, send)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    use super::*;
+    use crate::{tls::tests::Ca, transport::MemoryNetwork};
+
+    fn listen(tls: &ClusterTls) -> (QuicAcceptor, String) {
+        let acceptor = tls
+            .quic_acceptor(SocketAddr::from((Ipv4Addr::LOCALHOST, 0)))
+            .unwrap();
+        let port = acceptor.local_addr().unwrap().port();
+        (acceptor, format!("{QUIC_SCHEME}localhost:{port}"))
+    }
+
+    async fn read_line(stream: BoxedStream) -> String {
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).await.unwrap();
+        line
+    }
+
+    #[tokio::test]
+    async fn links_to_one_address_are_streams_of_one_connection() {
+        let ca = Ca::new();
+        let (mut acceptor, addr) = listen(&ca.issue(Some("master"), &["localhost"]));
+        let connector = ca
+            .issue(Some("node-a"), &[])
+            .quic_connector(MemoryNetwork::new())
+            .unwrap();
+
+        let (first, server_first) = tokio::join!(connector.connect(&addr), acceptor.accept());
+        let (mut first, (server_first, peer_first)) = (first.unwrap(), server_first.unwrap());
+        // ya no hace falta otro handshake
+        let mut second = connector.connect(&addr).await.unwrap();
+        let (server_second, peer_second) = acceptor.accept().await.unwrap();
+        assert_eq!(peer_first.identity.as_deref(), Some("node-a"));
+        assert_eq!(peer_second.identity.as_deref(), Some("node-a"));
+        let remote = |peer: &Peer| peer.addr.split_once('/').unwrap().0.to_string();
+        assert_eq!(remote(&peer_first), remote(&peer_second));
+        assert_ne!(peer_first.addr, peer_second.addr);
+
+        second.write_all(b"dos\n").await.unwrap();
+        first.write_all(b"uno\n").await.unwrap();
+        assert_eq!(read_line(server_first).await, "uno\n");
+        assert_eq!(read_line(server_second).await, "dos\n");
+    }
+
+    #[tokio::test]
+    async fn the_server_can_speak_first() {
+        let ca = Ca::new();
+        let (mut acceptor, addr) = listen(&ca.issue(Some("master"), &["localhost"]));
+        let connector = ca
+            .issue(Some("node-a"), &[])
+            .quic_connector(MemoryNetwork::new())
+            .unwrap();
+
+        let (client, server) = tokio::join!(connector.connect(&addr), acceptor.accept());
+        let (mut server, _) = server.unwrap();
+        server.write_all(b"hola\n").await.unwrap();
+        assert_eq!(read_line(client.unwrap()).await, "hola\n");
+    }
+
+    #[tokio::test]
+    async fn certificates_from_another_ca_are_refused_on_both_sides() {
+        let (ca, other) = (Ca::new(), Ca::new());
+        let (mut acceptor, addr) = listen(&ca.issue(Some("master"), &["localhost"]));
+        let accepting = tokio::spawn(async move { acceptor.accept().await.map(|(_, p)| p) });
+
+        // cliente de otra CA: el master lo rechaza
+        let impostor = other
+            .issue(Some("node-a"), &[])
+            .quic_connector(MemoryNetwork::new())
+            .unwrap();
+        let _ = impostor.connect(&addr).await;
+        // master que no es de la CA del nodo: el nodo no sigue
+        let (mut rogue, rogue_addr) = listen(&other.issue(Some("master"), &["localhost"]));
+        tokio::spawn(async move { rogue.accept().await.map(|_| ()) });
+        let node = ca
+            .issue(Some("node-b"), &[])
+            .quic_connector(MemoryNetwork::new())
+            .unwrap();
+        assert!(node.connect(&rogue_addr).await.is_err());
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!accepting.is_finished());
+        accepting.abort();
+    }
+
+    #[tokio::test]
+    async fn other_addresses_go_through_the_inner_connector() {
+        let ca = Ca::new();
+        let net = MemoryNetwork::new();
+        let mut listener = net.bind("master").unwrap();
+        let connector = ca
+            .issue(Some("node-a"), &[])
+            .quic_connector(net.clone())
+            .unwrap();
+
+        let mut client = connector.connect("master").await.unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+        client.write_all(b"tcp\n").await.unwrap();
+        assert_eq!(read_line(server).await, "tcp\n");
+    }
+}
@@ -0,0 +1,114 @@
+//! Refresco anticipado de un `GET`: `GET <clave> refresh=<ventana>` (la ventana como un
+//! TTL, ver `ttl`). Si la entrada vence dentro de la ventana, el nodo puede elegir a quien
+//! pregunta para que la vuelva a escribir antes de que venza, y los demás siguen leyendo
+//! el valor vigente. La chance crece a medida que se acerca el vencimiento y el nodo elige
+//! a uno solo por escritura de la clave.
+//!
+//! La respuesta trae el valor y `1` si quien pregunta quedó elegido (`0` si no), o vacía
+//! si la clave no existe.
+//!
+//! `GET <clave> stale=<máximo>` es el otro lado: además de una entrada vigente devuelve
+//! una vencida hace menos de `máximo`, si el nodo todavía la guarda (`STALE_GRACE_MS`).
+//! Responde el valor, `1`/`0` como arriba (al primero que la lee vencida después de cada
+//! escritura le toca refrescarla) y hace cuántos ms venció, `0` si está vigente.
+
+use std::borrow::Cow;
+
+use crate::{error::SocketError, ttl::parse_millis};
+
+const PREFIX: &str = "refresh=";
+const STALE_PREFIX: &str = "stale=";
+
+/// Saca el `refresh=<ventana>` de los argumentos de un `GET` (después de la clave), si lo
+/// hay. La ventana no puede ser 0.
+pub fn take_refresh(args: &mut Vec<Cow<'_, str>>) -> Result<Option<u64>, SocketError> {
+    if args.len() < 2 || !args[1].starts_with(PREFIX) {
+        return Ok(None);
+    }
+    let window = parse_millis(&args.remove(1)[PREFIX.len()..])?;
+    if window == 0 {
+        return Err(SocketError::BadRequest("refresh=0".into()));
+    }
+    Ok(Some(window))
+}
+
+/// El argumento `refresh=...` que lee `take_refresh`.
+pub fn encode_refresh(window_ms: u64) -> String {
+    format!("{PREFIX}{window_ms}ms")
+}
+
+/// Saca el `stale=<máximo>` de los argumentos de un `GET` (después de la clave), si lo
+/// hay. El máximo no puede ser 0.
+pub fn take_stale(args: &mut Vec<Cow<'_, str>>) -> Result<Option<u64>, SocketError> {
+    if args.len() < 2 || !args[1].starts_with(STALE_PREFIX) {
+        return Ok(None);
+    }
+    let max_stale = parse_millis(&args.remove(1)[STALE_PREFIX.len()..])?;
+    if max_stale == 0 {
+        return Err(SocketError::BadRequest("stale=0".into()));
+    }
+    Ok(Some(max_stale))
+}
+
+/// El argumento `stale=...` que lee `take_stale`.
+pub fn encode_stale(max_stale_ms: u64) -> String {
+    format!("{STALE_PREFIX}{max_stale_ms}ms")
+}
+
+/// Si una lectura a `remaining_ms` del vencimiento debería refrescar, con `draw` al azar
+/// en `[0, 1)`: nunca fuera de la ventana y con probabilidad `1 - remaining / window`
+/// dentro de ella.
+pub fn should_refresh(remaining_ms: u64, window_ms: u64, draw: f64) -> bool {
+    remaining_ms < window_ms && draw >= remaining_ms as f64 / window_ms as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::tokenize;
+
+    #[test]
+    fn the_window_follows_the_key() {
+        let mut args: Vec<_> = tokenize("k refresh=5s").collect();
+        assert_eq!(take_refresh(&mut args).unwrap(), Some(5_000));
+        assert_eq!(args, ["k"]);
+
+        let payload = format!("k {}", encode_refresh(250));
+        let mut args: Vec<_> = tokenize(&payload).collect();
+        assert_eq!(take_refresh(&mut args).unwrap(), Some(250));
+
+        let mut args: Vec<_> = tokenize("k").collect();
+        assert_eq!(take_refresh(&mut args).unwrap(), None);
+
+        for bad in ["k refresh=0", "k refresh=pronto"] {
+            let mut args: Vec<_> = tokenize(bad).collect();
+            assert!(take_refresh(&mut args).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn the_stale_limit_follows_the_key() {
+        let payload = format!("k {}", encode_stale(30_000));
+        let mut args: Vec<_> = tokenize(&payload).collect();
+        assert_eq!(take_stale(&mut args).unwrap(), Some(30_000));
+        assert_eq!(args, ["k"]);
+
+        let mut args: Vec<_> = tokenize("k refresh=1s").collect();
+        assert_eq!(take_stale(&mut args).unwrap(), None);
+
+        for bad in ["k stale=0", "k stale=mucho"] {
+            let mut args: Vec<_> = tokenize(bad).collect();
+            assert!(take_stale(&mut args).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn the_chance_grows_towards_expiry() {
+        assert!(!should_refresh(1_000, 1_000, 0.99));
+        assert!(!should_refresh(5_000, 1_000, 0.99));
+
+        assert!(should_refresh(0, 1_000, 0.0));
+        assert!(should_refresh(250, 1_000, 0.3));
+        assert!(!should_refresh(750, 1_000, 0.3));
+    }
+}
@@ -1,43 +1,49 @@
-use app_core::utils::split_message;
-
-use crate::{error::SocketError, types::ReqId};
+use crate::{
+    codec::{Quoted, split_message},
+    error::SocketError,
+    types::ReqId,
+};
+use bytes::Bytes;
+use std::borrow::Cow;
 use std::convert::TryFrom;
-use std::sync::Arc;
+use std::fmt;
+use utf8::Utf8Bytes;
 
 #[derive(Debug)]
 pub struct RequestData<'a> {
     pub id: ReqId,
     pub action: &'a str,
-    pub payload: &'a str,
+    /// Prestado de la línea salvo que el payload traiga escapes.
+    pub payload: Cow<'a, str>,
 }
 
 impl<'a> RequestData<'a> {
     #[inline]
-    pub fn new(id: ReqId, action: &'a str, payload: &'a str) -> Self {
+    pub fn new(id: ReqId, action: &'a str, payload: impl Into<Cow<'a, str>>) -> Self {
         Self {
             id,
             action,
-            payload,
+            payload: payload.into(),
         }
     }
 
     pub fn parse(s: &'a str) -> Result<Self, SocketError> {
-        let parts = split_message(s);
+        let mut parts = split_message(s).into_iter();
 
-        if parts.len() < 3 {
+        let (Some(_), Some(id), Some(action)) = (parts.next(), parts.next(), parts.next()) else {
             return Err(SocketError::BadMessage(s.to_string()));
-        }
+        };
 
-        let (id, action, payload) = (parts[1], parts[2], parts.get(3).copied());
-
-        if action.is_empty() || id.is_empty() {
-            return Err(SocketError::BadRequest(s.to_string()));
-        }
+        // las acciones son siempre tokens simples, nunca llevan escapes
+        let action = match action {
+            Cow::Borrowed(a) if !a.is_empty() && !id.is_empty() => a,
+            _ => return Err(SocketError::BadRequest(s.to_string())),
+        };
 
         Ok(Self::new(
-            id.to_string(),
+            id.into_owned(),
             action,
-            payload.unwrap_or_default(),
+            parts.next().unwrap_or_default(),
         ))
     }
 }
@@ -50,9 +56,15 @@ impl<'a> TryFrom<&'a str> for RequestData<'a> {
     }
 }
 
-impl<'a> ToString for RequestData<'a> {
-    fn to_string(&self) -> String {
-        format!("REQ {} {} \"{}\"\n", self.id, self.action, self.payload)
+impl<'a> fmt::Display for RequestData<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "REQ {} {} {}",
+            self.id,
+            self.action,
+            Quoted(&self.payload)
+        )
     }
 }
 
@@ -73,19 +85,82 @@ impl<'a> RequestDataInput<'a> {
     }
 }
 
+/// Un `RequestData` que se puede pasar a otra tarea. Armado con `from_frame`, la acción y
+/// el payload son vistas de la línea leída (ver `frame`) y no se copian; solo un payload
+/// con escapes trae su propia memoria.
 #[derive(Clone)]
 pub struct RequestDataOwned {
     pub id: ReqId,
-    pub action: Arc<str>,
-    pub payload: Arc<str>,
+    action: Utf8Bytes,
+    payload: Utf8Bytes,
+}
+
+impl RequestDataOwned {
+    /// `data` tiene que salir de `parse_frame(frame)`: lo prestado se toma de `frame`.
+    pub fn from_frame(frame: &Bytes, data: RequestData<'_>) -> Self {
+        let payload = match data.payload {
+            Cow::Borrowed(payload) => Utf8Bytes::slice_of(frame, payload),
+            Cow::Owned(payload) => Utf8Bytes::from(payload),
+        };
+        Self {
+            id: data.id,
+            action: Utf8Bytes::slice_of(frame, data.action),
+            payload,
+        }
+    }
+
+    pub fn action(&self) -> &str {
+        self.action.as_str()
+    }
+
+    pub fn payload(&self) -> &str {
+        self.payload.as_str()
+    }
+}
+
+impl Utf8Bytes {
+    /// Vista de `part` dentro de `frame`, sin copiar. `part` tiene que ser parte de `frame`.
+    fn slice_of(frame: &Bytes, part: &str) -> Self {
+        Self::new(frame.slice_ref(part.as_bytes())).unwrap_or_default()
+    }
+}
+
+impl From<String> for Utf8Bytes {
+    fn from(s: String) -> Self {
+        Self::new(Bytes::from(s)).unwrap_or_default()
+    }
 }
 
 impl<'a> From<RequestData<'a>> for RequestDataOwned {
+    /// Copia la acción y el payload; para una línea de `FrameReader`, `from_frame`.
     fn from(d: RequestData<'a>) -> Self {
         Self {
             id: d.id,
-            action: Arc::<str>::from(d.action),
-            payload: Arc::<str>::from(d.payload),
+            action: Utf8Bytes::from(d.action.to_string()),
+            payload: Utf8Bytes::from(d.payload.into_owned()),
+        }
+    }
+}
+
+mod utf8 {
+    use bytes::Bytes;
+
+    /// `Bytes` que son UTF-8 válido. El campo es privado a este módulo: solo se arma con
+    /// `new`, que valida una vez, o vacío con `default`. Leerlos como texto ya no valida.
+    #[derive(Clone, Default)]
+    pub(super) struct Utf8Bytes(Bytes);
+
+    impl Utf8Bytes {
+        /// `None` si `bytes` no son UTF-8.
+        pub(super) fn new(bytes: Bytes) -> Option<Self> {
+            std::str::from_utf8(&bytes).ok()?;
+            Some(Self(bytes))
+        }
+
+        pub(super) fn as_str(&self) -> &str {
+            // SAFETY: los bytes son los que validó `new` (o ninguno, con `default`), y `Bytes`
+            // no deja modificarlos.
+            unsafe { std::str::from_utf8_unchecked(&self.0) }
         }
     }
 }
@@ -0,0 +1,145 @@
+use app_core::error::{ErrorKind, HasErrorKind};
+
+use crate::{codec::encode_args, conditional::NOT_MODIFIED, response::ResponseData, types::ReqId};
+
+/// Payload de "no hay valor" (clave inexistente o argumentos vacíos).
+pub const EMPTY_PAYLOAD: &str = "EMPTY";
+/// Prefijo del payload de una respuesta de error.
+pub const ERROR_PREFIX: &str = "ERROR: ";
+
+/// Respuesta tipada de un `RES`. Es la única codificación de código + payload: quien
+/// responde la arma con `into_response` y quien la recibe la lee con los helpers de
+/// `ResponseData` (`values`, `integer`, `error_message`, ...).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResponseBody {
+    /// 200 con payload vacío.
+    Ok,
+    /// 200 `EMPTY`.
+    Empty,
+    /// 200 con el valor tal cual.
+    Value(String),
+    /// 200 con los valores como argumentos (`encode_args`).
+    Values(Vec<String>),
+    /// 200 con el número en decimal.
+    Integer(i64),
+    /// 200 con el valor y su versión (ver `conditional`).
+    Versioned { value: String, version: u64 },
+    /// 304 sin payload: la clave sigue en `version`.
+    NotModified { version: u64 },
+    /// El código de `kind` con `ERROR: <msg>`.
+    Error { kind: ErrorKind, msg: String },
+}
+
+impl ResponseBody {
+    pub fn error(kind: ErrorKind, msg: impl Into<String>) -> Self {
+        Self::Error {
+            kind,
+            msg: msg.into(),
+        }
+    }
+
+    /// Error categorizado según su `ErrorKind`.
+    pub fn from_error<E: HasErrorKind + ToString>(e: &E) -> Self {
+        Self::error(e.kind(), e.to_string())
+    }
+
+    pub fn code(&self) -> u16 {
+        match self {
+            Self::Error { kind, .. } => kind.wire_code(),
+            Self::NotModified { .. } => NOT_MODIFIED,
+            _ => 200,
+        }
+    }
+
+    pub fn payload(&self) -> String {
+        match self {
+            Self::Ok | Self::NotModified { .. } => String::new(),
+            Self::Empty => EMPTY_PAYLOAD.to_string(),
+            Self::Value(v) | Self::Versioned { value: v, .. } => v.clone(),
+            Self::Values(values) => encode_args(values.iter().map(String::as_str)),
+            Self::Integer(n) => n.to_string(),
+            Self::Error { msg, .. } => format!("{ERROR_PREFIX}{msg}"),
+        }
+    }
+
+    /// La versión que viaja con el código, si la hay.
+    pub fn version(&self) -> Option<u64> {
+        match self {
+            Self::Versioned { version, .. } | Self::NotModified { version } => Some(*version),
+            _ => None,
+        }
+    }
+
+    pub fn into_response(self, req_id: ReqId) -> ResponseData {
+        let mut res = ResponseData::new(req_id, self.code(), self.payload());
+        res.version = self.version();
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compression::Compression;
+
+    fn round_trip(body: ResponseBody) -> ResponseData {
+        let line = body.into_response("7".into()).to_string();
+        line.trim_end().parse().unwrap()
+    }
+
+    #[test]
+    fn every_variant_round_trips_through_a_res_line() {
+        let res = round_trip(ResponseBody::Ok);
+        assert_eq!((res.code, res.payload.as_str()), (200, ""));
+        assert!(round_trip(ResponseBody::Empty).is_empty_value());
+        assert_eq!(round_trip(ResponseBody::Value("a b".into())).payload, "a b");
+
+        let values = vec!["uno".to_string(), "dos tres".into(), String::new()];
+        assert_eq!(
+            round_trip(ResponseBody::Values(values.clone())).values(),
+            values
+        );
+        assert_eq!(round_trip(ResponseBody::Integer(-3)).integer().unwrap(), -3);
+
+        let res = round_trip(ResponseBody::Versioned {
+            value: "a b".into(),
+            version: 7,
+        });
+        assert_eq!((res.payload.as_str(), res.version), ("a b", Some(7)));
+        let res = round_trip(ResponseBody::NotModified { version: 7 });
+        assert!(res.is_not_modified() && !res.is_success());
+        assert_eq!((res.error_kind(), res.version), (None, Some(7)));
+
+        let res = round_trip(ResponseBody::error(ErrorKind::Conflict, "no"));
+        assert_eq!(res.code, 409);
+        assert_eq!(res.error_kind(), Some(ErrorKind::Conflict));
+        assert_eq!(res.error_message(), Some("no"));
+    }
+
+    #[test]
+    fn a_compressed_line_reads_back_the_same_response() {
+        let value = "documento ".repeat(200);
+        let res = ResponseBody::Versioned {
+            value: value.clone(),
+            version: 3,
+        }
+        .into_response("7".into());
+        let line = res.to_compressed(Compression::Deflate).unwrap();
+        assert!(line.len() < value.len() / 4, "{line}");
+
+        let read: ResponseData = line.trim_end().parse().unwrap();
+        assert_eq!((read.code, read.version), (200, Some(3)));
+        assert_eq!(read.payload, value);
+        // uno corto no gana nada comprimido
+        let short = ResponseBody::Value("v".into()).into_response("8".into());
+        assert!(short.to_compressed(Compression::Deflate).is_none());
+    }
+
+    #[test]
+    fn plain_values_are_not_errors_nor_integers() {
+        let res = round_trip(ResponseBody::Value("ERROR: es un valor".into()));
+        assert!(res.is_success());
+        assert_eq!(res.error_message(), None);
+        assert!(res.integer().is_err());
+    }
+}
@@ -1,6 +1,14 @@
-use app_core::utils::split_message;
+use app_core::error::ErrorKind;
 
-use crate::{error::SocketError, types::ReqId};
+use crate::{
+    codec::{Quoted, split_message, tokenize},
+    compression::Compression,
+    conditional::NOT_MODIFIED,
+    error::SocketError,
+    response::body::{EMPTY_PAYLOAD, ERROR_PREFIX},
+    types::ReqId,
+};
+use std::fmt;
 use std::str::FromStr;
 
 #[derive(Debug)]
@@ -8,6 +16,9 @@ pub struct ResponseData {
     pub req_id: ReqId,
     pub code: u16,
     pub payload: String,
+    /// Versión de la clave de un `GET` condicional; viaja pegada al código como
+    /// `<código>:<versión>` (ver `conditional`).
+    pub version: Option<u64>,
 }
 
 impl ResponseData {
@@ -17,9 +28,15 @@ impl ResponseData {
             req_id,
             code,
             payload,
+            version: None,
         }
     }
 
+    pub fn with_version(mut self, version: u64) -> Self {
+        self.version = Some(version);
+        self
+    }
+
     fn parse(s: &str) -> Result<Self, SocketError> {
         let parts = split_message(s);
 
@@ -27,20 +44,89 @@ impl ResponseData {
             return Err(SocketError::BadMessage(s.to_string()));
         }
 
-        let code: u16 = parts[2]
-            .parse()
-            .map_err(|_| SocketError::BadRequest(format!("code {} not valid", parts[2])))?;
+        let bad_code = || SocketError::BadRequest(format!("code {} not valid", parts[2]));
+        // `+<algoritmo>`: el payload viaja comprimido (ver `compression`)
+        let (code, compression) = match parts[2].split_once('+') {
+            Some((code, algorithm)) => (code, Some(algorithm.parse::<Compression>()?)),
+            None => (parts[2].as_ref(), None),
+        };
+        let (code, version) = match code.split_once(':') {
+            Some((code, version)) => (code, Some(version.parse().map_err(|_| bad_code())?)),
+            None => (code, None),
+        };
+        let code: u16 = code.parse().map_err(|_| bad_code())?;
+        let payload = match compression {
+            Some(compression) => compression.decode(&parts[3])?,
+            None => parts[3].to_string(),
+        };
 
-        Ok(Self::new(
-            parts[1].to_string(),
-            code,
-            parts.get(3).copied().unwrap_or_default().to_string(),
-        ))
+        let mut res = Self::new(parts[1].to_string(), code, payload);
+        res.version = version;
+        Ok(res)
     }
 
     pub fn is_success(&self) -> bool {
         self.code >= 200 && self.code < 300
     }
+
+    /// `304` de un `GET` condicional: la clave no cambió.
+    pub fn is_not_modified(&self) -> bool {
+        self.code == NOT_MODIFIED
+    }
+
+    fn is_error(&self) -> bool {
+        !self.is_success() && !self.is_not_modified()
+    }
+
+    /// Categoría del error remoto, `None` si la respuesta fue exitosa (o un `304`).
+    pub fn error_kind(&self) -> Option<ErrorKind> {
+        self.is_error()
+            .then(|| ErrorKind::from_wire_code(self.code))
+    }
+
+    /// Mensaje de un `ResponseBody::Error`, `None` si la respuesta fue exitosa (o un `304`).
+    pub fn error_message(&self) -> Option<&str> {
+        if !self.is_error() {
+            return None;
+        }
+        Some(
+            self.payload
+                .strip_prefix(ERROR_PREFIX)
+                .unwrap_or(&self.payload),
+        )
+    }
+
+    /// `ResponseBody::Empty`.
+    pub fn is_empty_value(&self) -> bool {
+        self.is_success() && self.payload == EMPTY_PAYLOAD
+    }
+
+    /// Valores de un `ResponseBody::Values`.
+    pub fn values(&self) -> Vec<String> {
+        tokenize(&self.payload).map(|v| v.into_owned()).collect()
+    }
+
+    /// Número de un `ResponseBody::Integer`.
+    pub fn integer(&self) -> Result<i64, SocketError> {
+        self.payload
+            .parse()
+            .map_err(|_| SocketError::BadMessage(format!("no es un entero: {}", self.payload)))
+    }
+
+    /// La línea con el payload comprimido; `None` si así no sale más corta.
+    pub fn to_compressed(&self, compression: Compression) -> Option<String> {
+        let encoded = compression.encode(&self.payload);
+        if encoded.len() >= self.payload.len() {
+            return None;
+        }
+        let version = self.version.map(|v| format!(":{v}")).unwrap_or_default();
+        Some(format!(
+            "RES {} {}{version}+{compression} {}\n",
+            self.req_id,
+            self.code,
+            Quoted(&encoded)
+        ))
+    }
 }
 
 impl FromStr for ResponseData {
@@ -51,8 +137,12 @@ impl FromStr for ResponseData {
     }
 }
 
-impl ToString for ResponseData {
-    fn to_string(&self) -> String {
-        format!("RES {} {} \"{}\"\n", self.req_id, self.code, self.payload)
+impl fmt::Display for ResponseData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RES {} {}", self.req_id, self.code)?;
+        if let Some(version) = self.version {
+            write!(f, ":{version}")?;
+        }
+        writeln!(f, " {}", Quoted(&self.payload))
     }
 }
@@ -1,3 +1,5 @@
+pub mod body;
 pub mod data;
 
+pub use body::ResponseBody;
 pub use data::ResponseData;
@@ -0,0 +1,111 @@
+use std::{
+    fmt,
+    hash::{DefaultHasher, Hash, Hasher},
+    str::FromStr,
+};
+
+use crate::{codec::tokenize, error::SocketError};
+
+/// Acción con la que se le piden a un nodo las entradas de un tramo del anillo (la usa el
+/// rebalanceo del master). Responde como `SNAPSHOT`: un `SnapshotHeader` y el payload de un
+/// `PUT` por entrada.
+pub const EXPORT_RANGE: &str = "EXPORT-RANGE";
+
+/// Posición de una clave en el anillo del master. El nodo la necesita igual para filtrar
+/// un `EXPORT-RANGE`, así que los dos usan esta.
+//TODO change to twox-hash for better performance
+pub fn key_hash(key: &str) -> u64 {
+    let mut h = DefaultHasher::new();
+    key.hash(&mut h);
+    h.finish()
+}
+
+/// Tramo `(start, end]` del anillo: lo que le toca al vnode en `end` cuando el anterior
+/// está en `start`. Si `start >= end` da la vuelta por cero; con `start == end` es el
+/// anillo entero (un solo vnode).
+///
+/// Viaja como `"<start>" "<end>"`, en hexa de 16 dígitos como los hash del master (al leer
+/// acepta menos dígitos y el prefijo `0x`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HashRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl HashRange {
+    pub fn contains(&self, hash: u64) -> bool {
+        if self.start < self.end {
+            self.start < hash && hash <= self.end
+        } else {
+            hash > self.start || hash <= self.end
+        }
+    }
+}
+
+impl fmt::Display for HashRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{:016x}\" \"{:016x}\"", self.start, self.end)
+    }
+}
+
+impl FromStr for HashRange {
+    type Err = SocketError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = || SocketError::BadMessage(format!("{EXPORT_RANGE}: {s}"));
+        let mut tokens = tokenize(s);
+        let mut position = || {
+            let token = tokens.next().ok_or_else(bad)?;
+            u64::from_str_radix(token.trim_start_matches("0x"), 16).map_err(|_| bad())
+        };
+        Ok(Self {
+            start: position()?,
+            end: position()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges_are_open_at_the_start_and_wrap_around_zero() {
+        let range = HashRange { start: 10, end: 20 };
+        assert!(!range.contains(10));
+        assert!(range.contains(11) && range.contains(20));
+        assert!(!range.contains(21));
+
+        let wrapping = HashRange {
+            start: u64::MAX - 5,
+            end: 5,
+        };
+        assert!(wrapping.contains(u64::MAX) && wrapping.contains(0) && wrapping.contains(5));
+        assert!(!wrapping.contains(6) && !wrapping.contains(u64::MAX - 5));
+
+        let whole = HashRange { start: 7, end: 7 };
+        assert!(whole.contains(0) && whole.contains(7) && whole.contains(u64::MAX));
+    }
+
+    #[test]
+    fn ranges_round_trip_in_hex() {
+        let range = HashRange {
+            start: 0xab,
+            end: u64::MAX,
+        };
+        assert_eq!(
+            range.to_string(),
+            "\"00000000000000ab\" \"ffffffffffffffff\""
+        );
+        assert_eq!(range.to_string().parse::<HashRange>().unwrap(), range);
+        assert_eq!(
+            "0x10 \"ff\"".parse::<HashRange>().unwrap(),
+            HashRange {
+                start: 16,
+                end: 255
+            }
+        );
+        assert!("10".parse::<HashRange>().is_err());
+        assert!("\"zz\" \"10\"".parse::<HashRange>().is_err());
+    }
+}
@@ -0,0 +1,69 @@
+use std::{fmt, str::FromStr};
+
+use crate::{codec::tokenize, error::SocketError};
+
+/// Acción con la que se le pide a un nodo una foto de su cache (la usan los backups del master).
+pub const SNAPSHOT: &str = "SNAPSHOT";
+
+/// Primera línea del `SNAPSHOT` de un nodo; después va el payload de un `PUT` por entrada
+/// (`"<key>" "<value>" [<expires_at>] [tags=..]`, con el vencimiento absoluto en ms).
+///
+/// Viaja como `epoch=.. seq=.. entries=..`: `seq` es la última escritura del log de
+/// replicación de esa `epoch` cuando se armó la foto. Lo que se escribió mientras tanto
+/// puede estar o no.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SnapshotHeader {
+    pub epoch: String,
+    pub seq: u64,
+    pub entries: u64,
+}
+
+impl fmt::Display for SnapshotHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "epoch={} seq={} entries={}",
+            self.epoch, self.seq, self.entries
+        )
+    }
+}
+
+impl FromStr for SnapshotHeader {
+    type Err = SocketError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut out = Self::default();
+
+        for token in tokenize(s) {
+            let bad = || SocketError::BadMessage(format!("{SNAPSHOT}: {token}"));
+            let (field, value) = token.split_once('=').ok_or_else(bad)?;
+            match field {
+                "epoch" => out.epoch = value.to_string(),
+                "seq" => out.seq = value.parse().map_err(|_| bad())?,
+                "entries" => out.entries = value.parse().map_err(|_| bad())?,
+                _ => continue,
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_round_trips() {
+        let header = SnapshotHeader {
+            epoch: "01J0000".into(),
+            seq: 42,
+            entries: 3,
+        };
+        assert_eq!(
+            header.to_string().parse::<SnapshotHeader>().unwrap(),
+            header
+        );
+        assert!("seq=x".parse::<SnapshotHeader>().is_err());
+    }
+}
@@ -1,27 +1,37 @@
+use crate::compression::Compression;
 use crate::error::SocketError;
 use std::fmt;
 use std::str::FromStr;
-use std::sync::Arc;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
 use std::time::Duration;
 
+use crate::event::EventData;
 use crate::request::RequestDataInput;
 use crate::response::ResponseData;
+use crate::timeout::ActionTimeouts;
 use crate::types::ReqId;
 use crate::types::SocketResult;
+use app_core::id::SnowflakeGenerator;
 use bytes::Bytes;
 use dashmap::DashMap;
-use tokio::sync::{mpsc, oneshot};
+use tokio::sync::{mpsc, oneshot, watch};
 use tokio::time::timeout;
-use tracing::{error, trace};
+use tracing::{debug, trace, warn};
 
 #[derive(Clone)]
 pub struct Socket {
     pub id: String,
     tx: mpsc::UnboundedSender<Bytes>,
     pending: Arc<DashMap<Arc<ReqId>, oneshot::Sender<String>>>,
-    counter: Arc<AtomicU64>,
-    max_duration: Duration,
+    ids: Arc<SnowflakeGenerator>,
+    /// Cuánto se espera cada respuesta; se lee en cada request.
+    max_duration: watch::Receiver<Duration>,
+    /// Sin clases, todas las acciones esperan `max_duration`.
+    timeouts: Option<Arc<ActionTimeouts>>,
+    /// Desde cuántos bytes de payload se comprimen las respuestas; `None` no comprime.
+    compress_min: Option<usize>,
+    /// Lo que el otro extremo avisó que entiende (ver `compression`).
+    compression: Arc<OnceLock<Compression>>,
 }
 
 impl fmt::Debug for Socket {
@@ -33,15 +43,58 @@ impl fmt::Debug for Socket {
 impl Socket {
     pub fn new(id: String, tx: mpsc::UnboundedSender<Bytes>, max_duration: Duration) -> Self {
         Self {
+            ids: Arc::new(SnowflakeGenerator::for_node_id(&id)),
             id,
             tx,
             pending: Arc::new(DashMap::new()),
-            counter: Arc::new(AtomicU64::new(1)),
-            max_duration,
+            max_duration: watch::channel(max_duration).1,
+            timeouts: None,
+            compress_min: None,
+            compression: Arc::new(OnceLock::new()),
         }
     }
 
+    /// Toma el timeout de los requests de `max_duration`, así se puede cambiar con el
+    /// socket abierto; los requests en vuelo siguen con el que tenían.
+    pub fn with_max_duration(mut self, max_duration: watch::Receiver<Duration>) -> Self {
+        self.max_duration = max_duration;
+        self
+    }
+
+    /// Las acciones lentas esperan lo de su clase en vez de `max_duration` (ver
+    /// `ActionTimeouts`).
+    pub fn with_action_timeouts(mut self, timeouts: Arc<ActionTimeouts>) -> Self {
+        self.timeouts = Some(timeouts);
+        self
+    }
+
+    /// Comprime las respuestas de al menos `min_bytes` de payload, una vez que el otro
+    /// extremo avise que las entiende (`accept_compression`).
+    pub fn with_compression(mut self, min_bytes: Option<usize>) -> Self {
+        self.compress_min = min_bytes;
+        self
+    }
+
+    /// El otro extremo mandó `EVT COMPRESS`; vale para toda la conexión.
+    pub fn accept_compression(&self, compression: Compression) {
+        let _ = self.compression.set(compression);
+    }
+
     pub async fn request(&self, input: RequestDataInput<'_>) -> SocketResult<ResponseData> {
+        let max_duration = *self.max_duration.borrow();
+        let max_duration = match &self.timeouts {
+            Some(timeouts) => timeouts.timeout_for(input.action, max_duration),
+            None => max_duration,
+        };
+        self.request_with_timeout(input, max_duration).await
+    }
+
+    /// Como `request`, esperando la respuesta `max_duration`.
+    pub async fn request_with_timeout(
+        &self,
+        input: RequestDataInput<'_>,
+        max_duration: Duration,
+    ) -> SocketResult<ResponseData> {
         let req_id = self.get_new_id();
         let request_data = Arc::new(input.from_id(req_id));
 
@@ -49,7 +102,12 @@ impl Socket {
 
         let line: String = request_data.to_string();
 
-        trace!("Request: {:?}", request_data);
+        trace!(
+            socket_id = %self.id,
+            req_id = %request_data.id,
+            action = request_data.action,
+            "request"
+        );
 
         //TODO Find a better way to handle clone
         self.pending.insert(request_data.id.clone().into(), tx_resp);
@@ -58,11 +116,19 @@ impl Socket {
             .send(Bytes::from(line))
             .map_err(|_| SocketError::WriteChannelClosed(self.id.clone()))?;
 
-        let resp: String = timeout(self.max_duration, rx_resp)
+        let resp: String = timeout(max_duration, rx_resp)
             .await
-            .map_err(|_| SocketError::Timeout {
-                socket_id: self.id.clone(),
-                req_id: request_data.id.clone(),
+            .map_err(|_| {
+                debug!(
+                    socket_id = %self.id,
+                    req_id = %request_data.id,
+                    action = request_data.action,
+                    "request timeout"
+                );
+                SocketError::Timeout {
+                    socket_id: self.id.clone(),
+                    req_id: request_data.id.clone(),
+                }
             })?
             .map_err(|_| SocketError::ResponseChannelClosed {
                 socket_id: self.id.clone(),
@@ -71,35 +137,51 @@ impl Socket {
 
         let response_data = ResponseData::from_str(resp.as_str())?;
 
-        trace!("Response: {:?}", response_data);
+        trace!(
+            socket_id = %self.id,
+            req_id = %response_data.req_id,
+            code = response_data.code,
+            "response"
+        );
 
         Ok(response_data)
     }
 
     //Para Manejar una respuesta asincrona, lo llamamos desde la tarea lectora
     pub fn handle_response(&self, req_id: ReqId, payload: String) {
-        //trace!("Response {req_id} payload={payload}");
-
         if let Some((_, tx)) = self.pending.remove(&req_id) {
             let _ = tx.send(payload);
         } else {
-            // Log útil para ver si llega un RES que nadie espera
-            error!(
-                "[{}] RES desconocido id={}, payload={}",
-                self.id, req_id, payload
+            // un RES que nadie espera (p. ej. llegó después del timeout)
+            warn!(
+                socket_id = %self.id,
+                req_id = %req_id,
+                payload_len = payload.len(),
+                "RES desconocido"
             );
         }
     }
 
     // Para Responder a una Request
     pub fn send_res(&self, response: ResponseData) -> SocketResult<()> {
-        let line = response.to_string();
+        let compressed = match (self.compress_min, self.compression.get()) {
+            (Some(min), Some(compression)) if response.payload.len() >= min => {
+                response.to_compressed(*compression)
+            }
+            _ => None,
+        };
+        let line = compressed.unwrap_or_else(|| response.to_string());
 
         self.tx
             .send(Bytes::from(line))
             .map_err(|_| SocketError::WriteChannelClosed(self.id.clone()))
     }
 
+    /// Manda una notificación `EVT`; no espera respuesta.
+    pub fn send_evt(&self, event: &EventData<'_>) -> SocketResult<()> {
+        self.send_raw(Bytes::from(event.to_string()))
+    }
+
     pub fn send_raw(&self, bytes: bytes::Bytes) -> SocketResult<()> {
         self.tx
             .send(bytes)
@@ -107,6 +189,6 @@ impl Socket {
     }
 
     pub fn get_new_id(&self) -> ReqId {
-        self.counter.fetch_add(1, Ordering::Relaxed).to_string()
+        self.ids.next_id().to_string()
     }
 }
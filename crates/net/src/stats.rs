@@ -0,0 +1,225 @@
+use std::{fmt, str::FromStr};
+
+use crate::{codec::tokenize, error::SocketError};
+
+/// Acción con la que se le piden sus contadores a un nodo.
+pub const STATS: &str = "STATS";
+
+/// Primera línea del `STATS` de un nodo: ocupación de su cache y contadores acumulados
+/// desde que arrancó. Viaja como `entries=.. capacity=.. bytes=.. max_bytes=.. hits=..
+/// misses=.. writes=.. evictions=.. expirations=.. invalidations=.. compactions=..
+/// compacted_bytes=.. expiry_backlog=.. expiry_tick_keys=.. expiry_missed_ticks=..
+/// expiry_reaped=.. expiry_drift_ms=.. expiry_drift_max_ms=..`; las claves desconocidas se
+/// ignoran.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NodeStats {
+    pub entries: u64,
+    pub capacity: u64,
+    /// Clave más valor de todas las entradas.
+    pub bytes: u64,
+    /// Memoria que el nodo se permite; 0 es sin tope.
+    pub max_bytes: u64,
+    pub hits: u64,
+    pub misses: u64,
+    pub writes: u64,
+    /// Sacadas por capacidad o por la cuota de su namespace.
+    pub evictions: u64,
+    /// Sacadas por vencer su TTL.
+    pub expirations: u64,
+    /// Borradas a pedido (`DEL`, `INVALIDATE-TAG`...).
+    pub invalidations: u64,
+    /// Veces que el nodo achicó tablas que le quedaron grandes después de un pico.
+    pub compactions: u64,
+    /// Memoria que soltaron esas compactaciones.
+    pub compacted_bytes: u64,
+    /// Claves vencidas que el reaper dejó para su próximo tick.
+    pub expiry_backlog: u64,
+    /// Claves que revisó el último tick del reaper.
+    pub expiry_tick_keys: u64,
+    /// Ticks que el reaper salteó por correr tarde.
+    pub expiry_missed_ticks: u64,
+    /// Sacadas por el reaper (el resto de `expirations` las sacó una lectura).
+    pub expiry_reaped: u64,
+    /// Suma de cuánto después de vencer sacó el reaper cada una, en ms.
+    pub expiry_drift_ms: u64,
+    /// El mayor de esos retrasos en el último tick.
+    pub expiry_drift_max_ms: u64,
+}
+
+impl NodeStats {
+    /// Lecturas más escrituras.
+    pub fn ops(&self) -> u64 {
+        self.hits + self.misses + self.writes
+    }
+
+    /// `bytes` sobre `max_bytes`; `None` si el nodo no tiene tope.
+    pub fn memory_ratio(&self) -> Option<f64> {
+        memory_ratio(self.bytes, self.max_bytes)
+    }
+
+    /// Aciertos sobre lecturas, de 0 a 1; 0 sin lecturas.
+    pub fn hit_ratio(&self) -> f64 {
+        let reads = self.hits + self.misses;
+        if reads == 0 {
+            return 0.0;
+        }
+        self.hits as f64 / reads as f64
+    }
+
+    /// Cuánto después de vencer sacó el reaper cada entrada, en promedio; `None` si no
+    /// sacó ninguna.
+    pub fn expiry_drift_avg_ms(&self) -> Option<f64> {
+        (self.expiry_reaped > 0).then(|| self.expiry_drift_ms as f64 / self.expiry_reaped as f64)
+    }
+
+    /// Lo que cambió desde `earlier` (un `STATS` anterior del mismo nodo). Si el nodo
+    /// reinició y algún contador bajó, cuenta desde cero.
+    pub fn since(&self, earlier: &NodeStats) -> NodeStats {
+        let restarted = self.ops() < earlier.ops();
+        let delta = |now: u64, before: u64| {
+            if restarted {
+                now
+            } else {
+                now - before.min(now)
+            }
+        };
+        NodeStats {
+            entries: self.entries,
+            capacity: self.capacity,
+            bytes: self.bytes,
+            max_bytes: self.max_bytes,
+            hits: delta(self.hits, earlier.hits),
+            misses: delta(self.misses, earlier.misses),
+            writes: delta(self.writes, earlier.writes),
+            evictions: delta(self.evictions, earlier.evictions),
+            expirations: delta(self.expirations, earlier.expirations),
+            invalidations: delta(self.invalidations, earlier.invalidations),
+            compactions: delta(self.compactions, earlier.compactions),
+            compacted_bytes: delta(self.compacted_bytes, earlier.compacted_bytes),
+            expiry_backlog: self.expiry_backlog,
+            expiry_tick_keys: self.expiry_tick_keys,
+            expiry_missed_ticks: delta(self.expiry_missed_ticks, earlier.expiry_missed_ticks),
+            expiry_reaped: delta(self.expiry_reaped, earlier.expiry_reaped),
+            expiry_drift_ms: delta(self.expiry_drift_ms, earlier.expiry_drift_ms),
+            expiry_drift_max_ms: self.expiry_drift_max_ms,
+        }
+    }
+}
+
+pub(crate) fn memory_ratio(bytes: u64, max_bytes: u64) -> Option<f64> {
+    (max_bytes > 0).then(|| bytes as f64 / max_bytes as f64)
+}
+
+impl fmt::Display for NodeStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "entries={} capacity={} bytes={} max_bytes={} hits={} misses={} writes={} \
+             evictions={} expirations={} invalidations={} compactions={} compacted_bytes={} \
+             expiry_backlog={} expiry_tick_keys={} expiry_missed_ticks={} expiry_reaped={} \
+             expiry_drift_ms={} expiry_drift_max_ms={}",
+            self.entries,
+            self.capacity,
+            self.bytes,
+            self.max_bytes,
+            self.hits,
+            self.misses,
+            self.writes,
+            self.evictions,
+            self.expirations,
+            self.invalidations,
+            self.compactions,
+            self.compacted_bytes,
+            self.expiry_backlog,
+            self.expiry_tick_keys,
+            self.expiry_missed_ticks,
+            self.expiry_reaped,
+            self.expiry_drift_ms,
+            self.expiry_drift_max_ms
+        )
+    }
+}
+
+impl FromStr for NodeStats {
+    type Err = SocketError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut out = Self::default();
+
+        for token in tokenize(s) {
+            let bad = || SocketError::BadMessage(format!("{STATS}: {token}"));
+            let (field, value) = token.split_once('=').ok_or_else(bad)?;
+            let slot = match field {
+                "entries" => &mut out.entries,
+                "capacity" => &mut out.capacity,
+                "bytes" => &mut out.bytes,
+                "max_bytes" => &mut out.max_bytes,
+                "hits" => &mut out.hits,
+                "misses" => &mut out.misses,
+                "writes" => &mut out.writes,
+                "evictions" => &mut out.evictions,
+                "expirations" => &mut out.expirations,
+                "invalidations" => &mut out.invalidations,
+                "compactions" => &mut out.compactions,
+                "compacted_bytes" => &mut out.compacted_bytes,
+                "expiry_backlog" => &mut out.expiry_backlog,
+                "expiry_tick_keys" => &mut out.expiry_tick_keys,
+                "expiry_missed_ticks" => &mut out.expiry_missed_ticks,
+                "expiry_reaped" => &mut out.expiry_reaped,
+                "expiry_drift_ms" => &mut out.expiry_drift_ms,
+                "expiry_drift_max_ms" => &mut out.expiry_drift_max_ms,
+                _ => continue,
+            };
+            *slot = value.parse().map_err(|_| bad())?;
+        }
+
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_round_trip_and_skip_unknown_fields() {
+        let stats = NodeStats {
+            entries: 10,
+            capacity: 1024,
+            bytes: 640,
+            max_bytes: 0,
+            hits: 3,
+            misses: 1,
+            writes: 10,
+            evictions: 0,
+            expirations: 2,
+            invalidations: 1,
+            compactions: 1,
+            compacted_bytes: 4096,
+            expiry_backlog: 0,
+            expiry_tick_keys: 2,
+            expiry_missed_ticks: 0,
+            expiry_reaped: 2,
+            expiry_drift_ms: 30,
+            expiry_drift_max_ms: 20,
+        };
+        assert_eq!(stats.to_string().parse::<NodeStats>().unwrap(), stats);
+        assert_eq!(stats.hit_ratio(), 0.75);
+        assert_eq!(stats.expiry_drift_avg_ms(), Some(15.0));
+
+        let stats: NodeStats = "entries=5 futuro=1".parse().unwrap();
+        assert_eq!((stats.entries, stats.hit_ratio()), (5, 0.0));
+        assert!("hits=muchos".parse::<NodeStats>().is_err());
+    }
+
+    #[test]
+    fn since_counts_from_zero_after_a_restart() {
+        let earlier: NodeStats = "hits=10 misses=5 writes=5".parse().unwrap();
+        let now: NodeStats = "entries=3 hits=14 misses=5 writes=9".parse().unwrap();
+        let delta = now.since(&earlier);
+        assert_eq!((delta.entries, delta.ops()), (3, 8));
+
+        let restarted: NodeStats = "hits=1".parse().unwrap();
+        assert_eq!(restarted.since(&earlier).ops(), 1);
+    }
+}
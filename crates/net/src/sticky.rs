@@ -0,0 +1,83 @@
+//! Leer lo que uno mismo escribió mientras la escritura sigue llegando a las réplicas. Un
+//! `PUT ... sticky=1` hace que el master responda `"OK" "<nodo>"`: el nodo que confirmó la
+//! escritura. Después `GET <clave> node=<nodo>` lee de ese nodo si sigue en el shard de la
+//! clave (si no, o si no contesta, como cualquier `GET`).
+
+use std::borrow::Cow;
+
+use crate::error::SocketError;
+
+const STICKY_PREFIX: &str = "sticky=";
+const NODE_PREFIX: &str = "node=";
+
+/// Saca el `sticky=<0|1>` de los argumentos de un `PUT`, después de los `positional`
+/// primeros (clave y valor).
+pub fn take_sticky(args: &mut Vec<Cow<'_, str>>, positional: usize) -> Result<bool, SocketError> {
+    let Some(pos) = args
+        .iter()
+        .skip(positional)
+        .position(|arg| arg.starts_with(STICKY_PREFIX))
+        .map(|pos| pos + positional)
+    else {
+        return Ok(false);
+    };
+    match &args.remove(pos)[STICKY_PREFIX.len()..] {
+        "1" => Ok(true),
+        "0" => Ok(false),
+        other => Err(SocketError::BadRequest(format!("{STICKY_PREFIX}{other}"))),
+    }
+}
+
+/// El argumento que lee `take_sticky`.
+pub fn encode_sticky() -> &'static str {
+    "sticky=1"
+}
+
+/// Saca el `node=<id>` de los argumentos de un `GET` (después de la clave), si lo hay.
+pub fn take_read_node(args: &mut Vec<Cow<'_, str>>) -> Result<Option<String>, SocketError> {
+    let Some(pos) = args
+        .iter()
+        .skip(1)
+        .position(|arg| arg.starts_with(NODE_PREFIX))
+        .map(|pos| pos + 1)
+    else {
+        return Ok(None);
+    };
+    let arg = args.remove(pos);
+    let node = &arg[NODE_PREFIX.len()..];
+    if node.is_empty() {
+        return Err(SocketError::BadRequest(format!("{NODE_PREFIX} vacío")));
+    }
+    Ok(Some(node.to_string()))
+}
+
+/// El argumento `node=...` que lee `take_read_node`.
+pub fn encode_read_node(node_id: &str) -> String {
+    format!("{NODE_PREFIX}{node_id}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::tokenize;
+
+    #[test]
+    fn sticky_puts_and_pinned_gets_take_their_argument() {
+        let mut put: Vec<_> = tokenize("k v 30s sticky=1 tags=a").collect();
+        assert!(take_sticky(&mut put, 2).unwrap());
+        assert_eq!(put, ["k", "v", "30s", "tags=a"]);
+        let mut put: Vec<_> = tokenize("k sticky=1").collect();
+        assert!(!take_sticky(&mut put, 2).unwrap(), "es el valor");
+        assert!(take_sticky(&mut tokenize("k v sticky=si").collect(), 2).is_err());
+
+        let payload = format!("k {}", encode_read_node("n-1"));
+        let mut get: Vec<_> = tokenize(&payload).collect();
+        assert_eq!(take_read_node(&mut get).unwrap().as_deref(), Some("n-1"));
+        assert_eq!(get, ["k"]);
+        assert_eq!(
+            take_read_node(&mut tokenize("node=").collect()).unwrap(),
+            None
+        );
+        assert!(take_read_node(&mut tokenize("k node=").collect()).is_err());
+    }
+}
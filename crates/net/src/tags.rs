@@ -0,0 +1,91 @@
+//! Tags de un `PUT`: un argumento `tags=<tag>[,<tag>...]` después del valor, en cualquier
+//! posición entre los opcionales. `INVALIDATE-TAG "<tag>"` borra todas las claves que se
+//! escribieron con ese tag.
+
+use std::borrow::Cow;
+
+use crate::error::SocketError;
+
+pub const INVALIDATE_TAG: &str = "INVALIDATE-TAG";
+
+const PREFIX: &str = "tags=";
+const MAX_TAGS: usize = 32;
+
+/// `a,b,c` sin repetidos y en el orden dado. Hasta 32 y ninguno vacío.
+pub fn parse_tags(list: &str) -> Result<Vec<String>, SocketError> {
+    let bad = || SocketError::BadRequest(format!("tags inválidos: {list}"));
+
+    let mut tags: Vec<String> = Vec::new();
+    for tag in list.split(',') {
+        if tag.is_empty() {
+            return Err(bad());
+        }
+        if !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    if tags.len() > MAX_TAGS {
+        return Err(bad());
+    }
+    Ok(tags)
+}
+
+/// Saca el argumento `tags=...` de los argumentos de un `PUT` (después de la clave y el
+/// valor), si lo hay; sin él la clave queda sin tags.
+pub fn take_tags(args: &mut Vec<Cow<'_, str>>) -> Result<Vec<String>, SocketError> {
+    let Some(pos) = args
+        .iter()
+        .skip(2)
+        .position(|arg| arg.starts_with(PREFIX))
+        .map(|pos| pos + 2)
+    else {
+        return Ok(Vec::new());
+    };
+
+    let tags = parse_tags(&args.remove(pos)[PREFIX.len()..])?;
+    if args.iter().skip(2).any(|arg| arg.starts_with(PREFIX)) {
+        return Err(SocketError::BadRequest("tags repetido".into()));
+    }
+    Ok(tags)
+}
+
+/// El argumento `tags=...` que lee `take_tags`; `None` sin tags.
+pub fn encode_tags(tags: &[String]) -> Option<String> {
+    (!tags.is_empty()).then(|| format!("{PREFIX}{}", tags.join(",")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::tokenize;
+
+    #[test]
+    fn tags_come_out_of_any_optional_position() {
+        for payload in ["k v tags=a,b,a 30s", "k v 30s tags=a,b"] {
+            let mut args: Vec<_> = tokenize(payload).collect();
+            assert_eq!(take_tags(&mut args).unwrap(), ["a", "b"]);
+            assert_eq!(args, ["k", "v", "30s"]);
+        }
+
+        // el valor puede empezar igual sin que se lo tome como tags
+        let mut args: Vec<_> = tokenize("k tags=x").collect();
+        assert!(take_tags(&mut args).unwrap().is_empty());
+        assert_eq!(args.len(), 2);
+
+        let tags = vec!["user:42".to_string(), "session".to_string()];
+        let encoded = encode_tags(&tags).unwrap();
+        let mut args: Vec<_> = ["k", "v", encoded.as_str()].map(Cow::from).into();
+        assert_eq!(take_tags(&mut args).unwrap(), tags);
+        assert_eq!(encode_tags(&[]), None);
+    }
+
+    #[test]
+    fn malformed_tags_are_rejected() {
+        for payload in ["k v tags=", "k v tags=a,,b", "k v tags=a tags=b"] {
+            let mut args: Vec<_> = tokenize(payload).collect();
+            assert!(take_tags(&mut args).is_err(), "{payload}");
+        }
+        let many: Vec<String> = (0..=MAX_TAGS).map(|i| i.to_string()).collect();
+        assert!(parse_tags(&many.join(",")).is_err());
+    }
+}
@@ -0,0 +1,156 @@
+//! Clases de timeout por acción. Un `Socket` espera cada respuesta `max_duration`, que
+//! alcanza para un `GET` pero no para un `SNAPSHOT` de un cache grande; en vez de subir el
+//! timeout de todo, cada acción cae en una clase y las clases lentas tienen el suyo (ver
+//! `Socket::with_action_timeouts`).
+
+use std::{collections::HashMap, fmt, str::FromStr, time::Duration};
+
+use crate::{
+    error::SocketError, prefix::DEL_PREFIX, ring::EXPORT_RANGE, snapshot::SNAPSHOT, stats::STATS,
+    tags::INVALIDATE_TAG,
+};
+
+pub const DEFAULT_ADMIN_TIMEOUT: Duration = Duration::from_secs(10);
+pub const DEFAULT_BULK_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimeoutClass {
+    /// Lecturas y escrituras de claves: el `max_duration` del socket.
+    Data,
+    /// Consultas y ajustes de un nodo (`STATS`, `SLOWLOG`, `CONFIG`..).
+    Admin,
+    /// Lo que recorre todo el cache (`SNAPSHOT`, `EXPORT-RANGE`, `DEL-PREFIX`..).
+    Bulk,
+}
+
+impl TimeoutClass {
+    /// La clase de una acción si no se configura otra.
+    pub fn of(action: &str) -> Self {
+        match action {
+            SNAPSHOT | EXPORT_RANGE | DEL_PREFIX | INVALIDATE_TAG => Self::Bulk,
+            STATS | "SLOWLOG" | "TOPKEYS" | "CONFIG" | "LOG-FILTER" | "SET-ROLE"
+            | "REPLICATE-FROM" => Self::Admin,
+            _ => Self::Data,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Data => "data",
+            Self::Admin => "admin",
+            Self::Bulk => "bulk",
+        }
+    }
+}
+
+impl FromStr for TimeoutClass {
+    type Err = SocketError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [Self::Data, Self::Admin, Self::Bulk]
+            .into_iter()
+            .find(|class| class.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| SocketError::BadRequest(format!("clase de timeout desconocida: {s}")))
+    }
+}
+
+impl fmt::Display for TimeoutClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Cuánto esperar cada clase y qué acciones cambian de la suya. `Data` no tiene tiempo
+/// propio, y el de las otras nunca queda por debajo del `max_duration` del socket: subir
+/// el timeout general no acorta las operaciones lentas.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActionTimeouts {
+    classes: HashMap<TimeoutClass, Duration>,
+    actions: HashMap<String, TimeoutClass>,
+}
+
+impl Default for ActionTimeouts {
+    fn default() -> Self {
+        Self {
+            classes: HashMap::from([
+                (TimeoutClass::Admin, DEFAULT_ADMIN_TIMEOUT),
+                (TimeoutClass::Bulk, DEFAULT_BULK_TIMEOUT),
+            ]),
+            actions: HashMap::new(),
+        }
+    }
+}
+
+impl ActionTimeouts {
+    /// `Data` sigue sin tiempo propio.
+    pub fn with_class(mut self, class: TimeoutClass, timeout: Duration) -> Self {
+        if class != TimeoutClass::Data {
+            self.classes.insert(class, timeout);
+        }
+        self
+    }
+
+    pub fn with_action(mut self, action: &str, class: TimeoutClass) -> Self {
+        self.actions.insert(action.to_ascii_uppercase(), class);
+        self
+    }
+
+    /// `"<ACCIÓN>=<clase>,.."`, como en `ACTION_TIMEOUT_CLASSES`.
+    pub fn with_actions(mut self, spec: &str) -> Result<Self, SocketError> {
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let Some((action, class)) = entry.split_once('=') else {
+                return Err(SocketError::BadRequest(format!(
+                    "se esperaba <acción>=<clase>: {entry}"
+                )));
+            };
+            self = self.with_action(action.trim(), class.parse()?);
+        }
+        Ok(self)
+    }
+
+    pub fn class_of(&self, action: &str) -> TimeoutClass {
+        self.actions
+            .get(action)
+            .copied()
+            .unwrap_or_else(|| TimeoutClass::of(action))
+    }
+
+    /// Cuánto esperar la respuesta de `action` con `default` como timeout del socket.
+    pub fn timeout_for(&self, action: &str, default: Duration) -> Duration {
+        self.classes
+            .get(&self.class_of(action))
+            .map_or(default, |timeout| (*timeout).max(default))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slow_actions_get_their_class_timeout_but_never_less_than_the_default() {
+        let timeouts = ActionTimeouts::default()
+            .with_class(TimeoutClass::Admin, Duration::from_secs(5))
+            .with_actions("META=bulk, stats=data")
+            .unwrap();
+        let default = Duration::from_secs(2);
+
+        assert_eq!(timeouts.timeout_for("GET", default), default);
+        assert_eq!(
+            timeouts.timeout_for(SNAPSHOT, default),
+            DEFAULT_BULK_TIMEOUT
+        );
+        assert_eq!(
+            timeouts.timeout_for("SLOWLOG", default),
+            Duration::from_secs(5)
+        );
+        assert_eq!(timeouts.timeout_for("META", default), DEFAULT_BULK_TIMEOUT);
+        assert_eq!(timeouts.timeout_for(STATS, default), default);
+        // con un timeout general más largo que el de la clase, manda el general
+        let long = Duration::from_secs(30);
+        assert_eq!(timeouts.timeout_for("SLOWLOG", long), long);
+
+        assert!(ActionTimeouts::default().with_actions("META").is_err());
+        assert!(ActionTimeouts::default().with_actions("META=slow").is_err());
+    }
+}
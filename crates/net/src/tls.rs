@@ -0,0 +1,381 @@
+//! TLS mutuo entre master y nodos: cada lado presenta un certificado firmado por la CA del
+//! cluster y verifica el del otro. El master toma la identidad del nodo de su certificado
+//! (ver `Peer::identity`), así un nodo no puede hacerse pasar por otro.
+
+use std::{
+    env, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use tokio::task::JoinSet;
+use tokio_rustls::rustls::{
+    self, ClientConfig, RootCertStore, ServerConfig,
+    crypto::{CryptoProvider, ring},
+    pki_types::{CertificateDer, PrivateKeyDer, ServerName, pem::PemObject},
+    server::WebPkiClientVerifier,
+};
+use tracing::warn;
+use x509_parser::{extensions::GeneralName, parse_x509_certificate};
+
+use crate::transport::{Acceptor, BoxedStream, Connector, Peer};
+
+/// Cuánto puede tardar un handshake antes de cortar la conexión.
+pub(crate) const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Certificado propio (con su cadena), su clave y la CA con la que se verifica al otro lado.
+pub struct ClusterTls {
+    certs: Vec<CertificateDer<'static>>,
+    key: PrivateKeyDer<'static>,
+    roots: Arc<RootCertStore>,
+}
+
+impl ClusterTls {
+    /// Todo en PEM; `ca` puede traer más de un certificado.
+    pub fn from_pem(cert: &[u8], key: &[u8], ca: &[u8]) -> io::Result<Self> {
+        let certs = CertificateDer::pem_slice_iter(cert)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| invalid(format!("certificado: {e}")))?;
+        if certs.is_empty() {
+            return Err(invalid("el certificado está vacío".into()));
+        }
+        let key = PrivateKeyDer::from_pem_slice(key).map_err(|e| invalid(format!("clave: {e}")))?;
+
+        let mut roots = RootCertStore::empty();
+        for ca in CertificateDer::pem_slice_iter(ca) {
+            let ca = ca.map_err(|e| invalid(format!("CA: {e}")))?;
+            roots.add(ca).map_err(|e| invalid(format!("CA: {e}")))?;
+        }
+        if roots.is_empty() {
+            return Err(invalid("la CA está vacía".into()));
+        }
+
+        Ok(Self {
+            certs,
+            key,
+            roots: Arc::new(roots),
+        })
+    }
+
+    pub fn from_files(cert: &Path, key: &Path, ca: &Path) -> io::Result<Self> {
+        let read = |path: &Path| {
+            std::fs::read(path)
+                .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", path.display())))
+        };
+        Self::from_pem(&read(cert)?, &read(key)?, &read(ca)?)
+    }
+
+    /// `TLS_CERT`, `TLS_KEY` y `TLS_CA`: rutas a los PEM. `None` si no hay ninguna; si
+    /// falta alguna, error.
+    pub fn from_env() -> io::Result<Option<Self>> {
+        let var = |name: &str| env::var(name).ok().filter(|v| !v.trim().is_empty());
+        match (var("TLS_CERT"), var("TLS_KEY"), var("TLS_CA")) {
+            (None, None, None) => Ok(None),
+            (Some(cert), Some(key), Some(ca)) => Self::from_files(
+                &PathBuf::from(cert),
+                &PathBuf::from(key),
+                &PathBuf::from(ca),
+            )
+            .map(Some),
+            _ => Err(invalid("TLS_CERT, TLS_KEY y TLS_CA van juntas".into())),
+        }
+    }
+
+    /// La identidad del certificado propio (ver `cert_identity`).
+    pub fn identity(&self) -> Option<String> {
+        cert_identity(&self.certs[0])
+    }
+
+    /// Lado servidor: exige a cada conexión un certificado de cliente firmado por la CA.
+    pub fn acceptor<A: Acceptor>(&self, inner: A) -> io::Result<TlsAcceptor<A>> {
+        Ok(TlsAcceptor {
+            inner,
+            tls: tokio_rustls::TlsAcceptor::from(Arc::new(self.server_config()?)),
+            handshakes: JoinSet::new(),
+        })
+    }
+
+    /// Lado cliente: presenta el certificado propio y verifica el del servidor contra la CA.
+    pub fn connector<C: Connector>(&self, inner: C) -> io::Result<TlsConnector<C>> {
+        Ok(TlsConnector {
+            inner,
+            tls: tokio_rustls::TlsConnector::from(Arc::new(self.client_config()?)),
+            server_name: None,
+        })
+    }
+
+    pub(crate) fn server_config(&self) -> io::Result<ServerConfig> {
+        let provider = provider();
+        let verifier =
+            WebPkiClientVerifier::builder_with_provider(self.roots.clone(), provider.clone())
+                .build()
+                .map_err(|e| invalid(e.to_string()))?;
+        ServerConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .map_err(tls_error)?
+            .with_client_cert_verifier(verifier)
+            .with_single_cert(self.certs.clone(), self.key.clone_key())
+            .map_err(tls_error)
+    }
+
+    pub(crate) fn client_config(&self) -> io::Result<ClientConfig> {
+        ClientConfig::builder_with_provider(provider())
+            .with_safe_default_protocol_versions()
+            .map_err(tls_error)?
+            .with_root_certificates(self.roots.clone())
+            .with_client_auth_cert(self.certs.clone(), self.key.clone_key())
+            .map_err(tls_error)
+    }
+}
+
+/// Envuelve un `Acceptor` y entrega solo las conexiones que completaron el handshake, con
+/// la identidad del certificado del cliente en `Peer::identity`. Los handshakes corren
+/// aparte: uno lento no frena a los demás.
+pub struct TlsAcceptor<A> {
+    inner: A,
+    tls: tokio_rustls::TlsAcceptor,
+    handshakes: JoinSet<(io::Result<BoxedStream>, Peer)>,
+}
+
+#[async_trait]
+impl<A: Acceptor> Acceptor for TlsAcceptor<A> {
+    async fn accept(&mut self) -> io::Result<(BoxedStream, Peer)> {
+        loop {
+            tokio::select! {
+                accepted = self.inner.accept() => {
+                    let (stream, peer) = accepted?;
+                    self.handshakes.spawn(handshake(self.tls.clone(), stream, peer));
+                }
+                Some(done) = self.handshakes.join_next() => {
+                    let Ok((stream, peer)) = done else { continue };
+                    match stream {
+                        Ok(stream) => return Ok((stream, peer)),
+                        Err(e) => warn!(target: "tls", %peer, "handshake fallido: {e}"),
+                    }
+                }
+            }
+        }
+    }
+}
+
+async fn handshake(
+    tls: tokio_rustls::TlsAcceptor,
+    stream: BoxedStream,
+    mut peer: Peer,
+) -> (io::Result<BoxedStream>, Peer) {
+    let stream = match tokio::time::timeout(HANDSHAKE_TIMEOUT, tls.accept(stream)).await {
+        Ok(Ok(stream)) => {
+            peer.identity = stream
+                .get_ref()
+                .1
+                .peer_certificates()
+                .and_then(|certs| certs.first())
+                .and_then(cert_identity);
+            Ok(Box::new(stream) as BoxedStream)
+        }
+        Ok(Err(e)) => Err(e),
+        Err(_) => Err(io::Error::from(io::ErrorKind::TimedOut)),
+    };
+    (stream, peer)
+}
+
+/// Envuelve un `Connector` con TLS. El nombre que se verifica en el certificado del
+/// servidor es el host de la dirección, salvo que se fije otro con `with_server_name`.
+pub struct TlsConnector<C> {
+    inner: C,
+    tls: tokio_rustls::TlsConnector,
+    server_name: Option<ServerName<'static>>,
+}
+
+impl<C> TlsConnector<C> {
+    pub fn with_server_name(mut self, name: &str) -> io::Result<Self> {
+        self.server_name = Some(server_name(name)?);
+        Ok(self)
+    }
+}
+
+#[async_trait]
+impl<C: Connector> Connector for TlsConnector<C> {
+    async fn connect(&self, addr: &str) -> io::Result<BoxedStream> {
+        let name = match &self.server_name {
+            Some(name) => name.clone(),
+            None => server_name(host(addr))?,
+        };
+        let stream = self.inner.connect(addr).await?;
+        let stream = tokio::time::timeout(HANDSHAKE_TIMEOUT, self.tls.connect(name, stream))
+            .await
+            .map_err(|_| io::Error::from(io::ErrorKind::TimedOut))??;
+        Ok(Box::new(stream))
+    }
+}
+
+/// Identidad de un certificado: su CN o, si no tiene, su primer nombre DNS.
+pub fn cert_identity(cert: &CertificateDer<'_>) -> Option<String> {
+    let (_, cert) = parse_x509_certificate(cert).ok()?;
+    if let Some(cn) = cert
+        .subject()
+        .iter_common_name()
+        .find_map(|cn| cn.as_str().ok())
+    {
+        return Some(cn.to_string());
+    }
+    cert.subject_alternative_name()
+        .ok()
+        .flatten()?
+        .value
+        .general_names
+        .iter()
+        .find_map(|name| match name {
+            GeneralName::DNSName(dns) => Some(dns.to_string()),
+            _ => None,
+        })
+}
+
+fn provider() -> Arc<CryptoProvider> {
+    Arc::new(ring::default_provider())
+}
+
+/// `host:puerto` o `[v6]:puerto` → host.
+pub(crate) fn host(addr: &str) -> &str {
+    let host = match addr.rsplit_once(':') {
+        Some((host, port)) if port.parse::<u16>().is_ok() => host,
+        _ => addr,
+    };
+    host.trim_start_matches('[').trim_end_matches(']')
+}
+
+pub(crate) fn server_name(name: &str) -> io::Result<ServerName<'static>> {
+    ServerName::try_from(name.to_string()).map_err(|e| invalid(format!("{name}: {e}")))
+}
+
+pub(crate) fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+fn tls_error(e: rustls::Error) -> io::Error {
+    invalid(e.to_string())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use rcgen::{BasicConstraints, CertificateParams, CertifiedIssuer, DnType, IsCa, KeyPair};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    use super::*;
+    use crate::transport::MemoryNetwork;
+
+    pub(crate) struct Ca(CertifiedIssuer<'static, KeyPair>);
+
+    impl Ca {
+        pub(crate) fn new() -> Self {
+            let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+            params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+            params
+                .distinguished_name
+                .push(DnType::CommonName, "cluster-ca");
+            Self(CertifiedIssuer::self_signed(params, KeyPair::generate().unwrap()).unwrap())
+        }
+
+        /// `cn` como CN y `dns` como nombres alternativos.
+        pub(crate) fn issue(&self, cn: Option<&str>, dns: &[&str]) -> ClusterTls {
+            let key = KeyPair::generate().unwrap();
+            let dns: Vec<String> = dns.iter().map(|d| d.to_string()).collect();
+            let mut params = CertificateParams::new(dns).unwrap();
+            params.distinguished_name = Default::default();
+            if let Some(cn) = cn {
+                params.distinguished_name.push(DnType::CommonName, cn);
+            }
+            let cert = params.signed_by(&key, &self.0).unwrap();
+            ClusterTls::from_pem(
+                cert.pem().as_bytes(),
+                key.serialize_pem().as_bytes(),
+                self.0.pem().as_bytes(),
+            )
+            .unwrap()
+        }
+    }
+
+    #[tokio::test]
+    async fn both_sides_verify_and_the_server_sees_the_client_identity() {
+        let ca = Ca::new();
+        let net = MemoryNetwork::new();
+        let mut acceptor = ca
+            .issue(Some("master"), &["master"])
+            .acceptor(net.bind("master").unwrap())
+            .unwrap();
+        let connector = ca
+            .issue(Some("node-a"), &[])
+            .connector(net.clone())
+            .unwrap();
+
+        let (client, server) = tokio::join!(connector.connect("master"), acceptor.accept());
+        let (server, peer) = server.unwrap();
+        assert_eq!(peer.identity.as_deref(), Some("node-a"));
+
+        let mut client = client.unwrap();
+        client.write_all(b"hola\n").await.unwrap();
+        client.flush().await.unwrap();
+        let mut line = String::new();
+        BufReader::new(server).read_line(&mut line).await.unwrap();
+        assert_eq!(line, "hola\n");
+    }
+
+    #[tokio::test]
+    async fn certificates_from_another_ca_are_refused_on_both_sides() {
+        let (ca, other) = (Ca::new(), Ca::new());
+        let net = MemoryNetwork::new();
+        let mut acceptor = ca
+            .issue(Some("master"), &["master"])
+            .acceptor(net.bind("master").unwrap())
+            .unwrap();
+        let accepting = tokio::spawn(async move { acceptor.accept().await.map(|(_, p)| p) });
+
+        // cliente de otra CA: el master lo rechaza
+        let impostor = other
+            .issue(Some("node-a"), &[])
+            .connector(net.clone())
+            .unwrap();
+        let _ = impostor.connect("master").await;
+        // master que no es de la CA del nodo: el nodo no sigue
+        let rogue_net = MemoryNetwork::new();
+        let mut rogue = other
+            .issue(Some("master"), &["master"])
+            .acceptor(rogue_net.bind("master").unwrap())
+            .unwrap();
+        tokio::spawn(async move { rogue.accept().await.map(|_| ()) });
+        let node = ca.issue(Some("node-b"), &[]).connector(rogue_net).unwrap();
+        assert!(node.connect("master").await.is_err());
+
+        // el master no entregó ninguna conexión
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!accepting.is_finished());
+        accepting.abort();
+    }
+
+    #[test]
+    fn identity_is_the_cn_or_else_the_first_dns_name() {
+        let ca = Ca::new();
+        assert_eq!(
+            ca.issue(Some("node-a"), &["a.cluster"])
+                .identity()
+                .as_deref(),
+            Some("node-a")
+        );
+        assert_eq!(
+            ca.issue(None, &["b.cluster", "c.cluster"])
+                .identity()
+                .as_deref(),
+            Some("b.cluster")
+        );
+    }
+
+    #[test]
+    fn server_names_come_from_the_host_of_the_address() {
+        assert_eq!(host("master:7000"), "master");
+        assert_eq!(host("[::1]:7000"), "::1");
+        assert_eq!(host("master"), "master");
+        assert!(server_name(host("127.0.0.1:7000")).is_ok());
+    }
+}
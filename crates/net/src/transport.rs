@@ -0,0 +1,224 @@
+//! Abstracción del transporte para que master y nodos puedan correr sobre TCP o sobre
+//! una red en memoria (simulaciones y tests deterministas).
+
+use std::{
+    fmt, io,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use async_trait::async_trait;
+use dashmap::DashMap;
+use tokio::{
+    io::{AsyncRead, AsyncWrite, DuplexStream},
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+};
+
+/// Buffer de cada sentido de un enlace en memoria.
+const MEMORY_LINK_BUFFER: usize = 64 * 1024;
+
+/// Prefijo de las direcciones que van por QUIC. Está siempre, aunque falte la feature
+/// `quic`, para poder rechazar esas direcciones en vez de intentarlas por TCP.
+pub const QUIC_SCHEME: &str = "quic://";
+
+pub trait AsyncStream: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> AsyncStream for T {}
+
+pub type BoxedStream = Box<dyn AsyncStream>;
+
+/// Lado cliente: abre conexiones hacia una dirección.
+#[async_trait]
+pub trait Connector: Send + Sync {
+    async fn connect(&self, addr: &str) -> io::Result<BoxedStream>;
+}
+
+#[async_trait]
+impl<C: Connector + ?Sized> Connector for Arc<C> {
+    async fn connect(&self, addr: &str) -> io::Result<BoxedStream> {
+        (**self).connect(addr).await
+    }
+}
+
+/// Lado servidor: entrega conexiones entrantes junto con quién está del otro lado.
+#[async_trait]
+pub trait Acceptor: Send + 'static {
+    async fn accept(&mut self) -> io::Result<(BoxedStream, Peer)>;
+}
+
+/// El otro extremo de una conexión entrante.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Peer {
+    /// Etiqueta para logs: `ip:puerto` en TCP, `mem-<n>` en memoria.
+    pub addr: String,
+    /// Identidad del certificado de cliente que presentó (ver `tls::TlsAcceptor`); `None`
+    /// sin TLS mutuo.
+    pub identity: Option<String>,
+}
+
+impl Peer {
+    pub fn new(addr: impl Into<String>) -> Self {
+        Self {
+            addr: addr.into(),
+            identity: None,
+        }
+    }
+}
+
+impl fmt::Display for Peer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.addr)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TcpConnector;
+
+#[async_trait]
+impl Connector for TcpConnector {
+    async fn connect(&self, addr: &str) -> io::Result<BoxedStream> {
+        let stream = TcpStream::connect(addr).await?;
+        Ok(Box::new(stream))
+    }
+}
+
+#[async_trait]
+impl Acceptor for TcpListener {
+    async fn accept(&mut self) -> io::Result<(BoxedStream, Peer)> {
+        let (stream, addr) = TcpListener::accept(self).await?;
+        Ok((Box::new(stream), Peer::new(addr.to_string())))
+    }
+}
+
+type Incoming = mpsc::UnboundedSender<(DuplexStream, String)>;
+
+/// Red en memoria: direcciones arbitrarias (`"master"`, `"node-1"`...) sobre
+/// `tokio::io::duplex`. No toca sockets ni el reloj real.
+#[derive(Default)]
+pub struct MemoryNetwork {
+    listeners: Arc<DashMap<String, Incoming>>,
+    next_peer: AtomicU64,
+}
+
+impl MemoryNetwork {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    pub fn bind(&self, addr: impl Into<String>) -> io::Result<MemoryListener> {
+        let addr = addr.into();
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        match self.listeners.entry(addr.clone()) {
+            dashmap::Entry::Occupied(_) => Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("{addr} ya está en uso"),
+            )),
+            dashmap::Entry::Vacant(v) => {
+                v.insert(tx);
+                Ok(MemoryListener {
+                    addr,
+                    rx,
+                    listeners: self.listeners.clone(),
+                })
+            }
+        }
+    }
+
+    pub fn is_bound(&self, addr: &str) -> bool {
+        self.listeners.contains_key(addr)
+    }
+}
+
+#[async_trait]
+impl Connector for MemoryNetwork {
+    async fn connect(&self, addr: &str) -> io::Result<BoxedStream> {
+        let refused = || io::Error::new(io::ErrorKind::ConnectionRefused, addr.to_string());
+
+        let incoming = self.listeners.get(addr).ok_or_else(refused)?.clone();
+        let peer = format!("mem-{}", self.next_peer.fetch_add(1, Ordering::Relaxed));
+        let (client, server) = tokio::io::duplex(MEMORY_LINK_BUFFER);
+
+        incoming.send((server, peer)).map_err(|_| refused())?;
+        Ok(Box::new(client))
+    }
+}
+
+/// Listener de una `MemoryNetwork`. Al soltarlo la dirección queda libre.
+pub struct MemoryListener {
+    addr: String,
+    rx: mpsc::UnboundedReceiver<(DuplexStream, String)>,
+    listeners: Arc<DashMap<String, Incoming>>,
+}
+
+impl MemoryListener {
+    pub fn addr(&self) -> &str {
+        &self.addr
+    }
+}
+
+#[async_trait]
+impl Acceptor for MemoryListener {
+    async fn accept(&mut self) -> io::Result<(BoxedStream, Peer)> {
+        match self.rx.recv().await {
+            Some((stream, peer)) => Ok((Box::new(stream), Peer::new(peer))),
+            None => Err(io::Error::from(io::ErrorKind::NotConnected)),
+        }
+    }
+}
+
+impl Drop for MemoryListener {
+    fn drop(&mut self) {
+        self.listeners.remove(&self.addr);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn memory_connections_carry_bytes_both_ways() {
+        let net = MemoryNetwork::new();
+        let mut listener = net.bind("master").unwrap();
+
+        let mut client = net.connect("master").await.unwrap();
+        let (server, peer) = listener.accept().await.unwrap();
+        assert!(peer.addr.starts_with("mem-"));
+        assert_eq!(peer.identity, None);
+
+        client.write_all(b"hola\n").await.unwrap();
+        let mut server = BufReader::new(server);
+        let mut line = String::new();
+        server.read_line(&mut line).await.unwrap();
+        assert_eq!(line, "hola\n");
+
+        server.get_mut().write_all(b"chau\n").await.unwrap();
+        let mut client = BufReader::new(client);
+        line.clear();
+        client.read_line(&mut line).await.unwrap();
+        assert_eq!(line, "chau\n");
+    }
+
+    #[tokio::test]
+    async fn unbound_and_released_addresses_refuse_connections() {
+        let net = MemoryNetwork::new();
+        let err = net.connect("nadie").await.err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::ConnectionRefused);
+
+        let listener = net.bind("x").unwrap();
+        assert_eq!(
+            net.bind("x").err().unwrap().kind(),
+            io::ErrorKind::AddrInUse
+        );
+        drop(listener);
+
+        assert!(!net.is_bound("x"));
+        assert!(net.connect("x").await.is_err());
+    }
+}
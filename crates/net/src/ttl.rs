@@ -0,0 +1,123 @@
+//! Tiempos en el protocolo (TTL de un `PUT` del cliente, `expires_at` hacia los nodos):
+//! entero sin signo con sufijo de unidad opcional. Sin sufijo son milisegundos; además se
+//! aceptan `ms`, `s`, `m` y `h`. Al emitir siempre se usa `ms` explícito. Con
+//! `RELATIVE_TTL` el master manda en lugar del `expires_at` lo que le falta a la clave
+//! (`ttl=<n>ms`) y cada nodo lo suma a su propio reloj.
+
+use std::time::Duration;
+
+use crate::error::SocketError;
+
+/// Milisegundos de un token como `1500`, `1500ms`, `30s`, `5m` o `1h`.
+pub fn parse_millis(token: &str) -> Result<u64, SocketError> {
+    let invalid = || SocketError::BadRequest(format!("tiempo inválido: {token:?}"));
+
+    let digits_end = token
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(token.len());
+    let (digits, unit) = token.split_at(digits_end);
+
+    let factor: u64 = match unit {
+        "" | "ms" => 1,
+        "s" => 1_000,
+        "m" => 60_000,
+        "h" => 3_600_000,
+        _ => return Err(invalid()),
+    };
+
+    digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(factor))
+        .ok_or_else(invalid)
+}
+
+/// Un instante en ms Unix, `1700000000000` o `1700000000000ms`. Las otras unidades de
+/// `parse_millis` no significan nada en un instante y se rechazan.
+pub fn parse_instant(token: &str) -> Result<u64, SocketError> {
+    token
+        .strip_suffix("ms")
+        .unwrap_or(token)
+        .parse::<u64>()
+        .ok()
+        .filter(|_| token.starts_with(|c: char| c.is_ascii_digit()))
+        .ok_or_else(|| SocketError::BadRequest(format!("instante inválido: {token:?}")))
+}
+
+/// `<n>ms`, que `parse_millis` vuelve a leer igual.
+pub fn format_millis(ms: u64) -> String {
+    format!("{ms}ms")
+}
+
+/// Prefijo del vencimiento relativo en el `PUT` hacia los nodos.
+pub const RELATIVE_PREFIX: &str = "ttl=";
+
+/// `ttl=<n>ms`: vence `ms` después de que lo reciba el nodo.
+pub fn format_relative(ms: u64) -> String {
+    format!("{RELATIVE_PREFIX}{}", format_millis(ms))
+}
+
+/// El `expires_at` absoluto de un token de `PUT` hacia un nodo: el instante tal cual, o
+/// con `ttl=` lo que falta sumado a `now_ms`.
+pub fn parse_expiry(token: &str, now_ms: u64) -> Result<u64, SocketError> {
+    match token.strip_prefix(RELATIVE_PREFIX) {
+        Some(ttl) => Ok(now_ms.saturating_add(parse_millis(ttl)?)),
+        None => parse_instant(token),
+    }
+}
+
+/// `format_millis` de una duración (redondeada hacia abajo al milisegundo).
+pub fn format_duration(d: Duration) -> String {
+    format_millis(u64::try_from(d.as_millis()).unwrap_or(u64::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn units_are_converted_to_millis() {
+        assert_eq!(parse_millis("1500").unwrap(), 1_500);
+        assert_eq!(parse_millis("1500ms").unwrap(), 1_500);
+        assert_eq!(parse_millis("30s").unwrap(), 30_000);
+        assert_eq!(parse_millis("5m").unwrap(), 300_000);
+        assert_eq!(parse_millis("1h").unwrap(), 3_600_000);
+    }
+
+    #[test]
+    fn malformed_or_overflowing_values_are_rejected() {
+        for bad in [
+            "",
+            "s",
+            "-5",
+            "1.5s",
+            "10 s",
+            "10d",
+            "abc",
+            "18446744073709551615s",
+        ] {
+            assert!(parse_millis(bad).is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn a_relative_expiry_counts_from_the_local_clock() {
+        assert_eq!(
+            parse_expiry(&format_relative(1_500), 10_000).unwrap(),
+            11_500
+        );
+        assert_eq!(parse_expiry("2000ms", 10_000).unwrap(), 2_000);
+        assert_eq!(parse_expiry("2000", 10_000).unwrap(), 2_000);
+        assert!(parse_expiry("ttl=", 0).is_err());
+        // un instante no lleva unidades
+        for bad in ["30s", "5m", "1h", "ms", "+5", "ttl=5d"] {
+            assert!(parse_expiry(bad, 10_000).is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn formatted_values_round_trip() {
+        assert_eq!(format_duration(Duration::from_secs(2)), "2000ms");
+        assert_eq!(parse_millis(&format_millis(42)).unwrap(), 42);
+    }
+}
@@ -0,0 +1,299 @@
+//! `MULTI`: varios comandos sobre claves de un mismo shard que el nodo aplica como una
+//! unidad. Cada comando va como un argumento del payload:
+//! `MULTI "WATCH k 3" "GET k" "PUT k v2 30s"`.
+//!
+//! `WATCH <clave> <versión>` es un chequeo optimista: si la versión actual de la clave (0
+//! si no existe) es otra, no se aplica nada y se responde `409`. La versión se lee con
+//! `VERSION <clave>` (en un `MULTI` anterior) y crece con cada escritura de la clave.
+//!
+//! Para una sola clave alcanza con un `PUT` condicional: `PUT k v [ttl] IF <condición>`
+//! (ver `PutCondition`), que si no se cumple responde `412`.
+
+use std::{borrow::Cow, fmt};
+
+use crate::{
+    codec::{encode_args, tokenize},
+    error::SocketError,
+    ttl::{format_millis, parse_millis},
+};
+
+pub const MULTI: &str = "MULTI";
+/// Separa los argumentos de un `PUT` de su condición.
+pub const IF: &str = "IF";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxCommand {
+    Watch {
+        key: String,
+        version: u64,
+    },
+    Get {
+        key: String,
+    },
+    Version {
+        key: String,
+    },
+    /// `ttl` es relativo en lo que manda el cliente y el `expires_at` absoluto en lo que
+    /// manda el master al nodo, igual que en `PUT`.
+    Put {
+        key: String,
+        value: String,
+        ttl: Option<u64>,
+    },
+    Del {
+        key: String,
+    },
+}
+
+impl TxCommand {
+    pub fn parse(line: &str) -> Result<Self, SocketError> {
+        let bad = |why: &str| SocketError::BadRequest(format!("{MULTI}: {why}: {line}"));
+        let mut args = tokenize(line);
+        let action = args.next().unwrap_or_default().to_ascii_uppercase();
+        let key = args
+            .next()
+            .map(|k| k.into_owned())
+            .filter(|k| !k.is_empty())
+            .ok_or_else(|| bad("falta la clave"))?;
+
+        let cmd = match action.as_str() {
+            "WATCH" => {
+                let version = args.next().ok_or_else(|| bad("falta la versión"))?;
+                Self::Watch {
+                    key,
+                    version: version.parse().map_err(|_| bad("versión inválida"))?,
+                }
+            }
+            "GET" => Self::Get { key },
+            "VERSION" => Self::Version { key },
+            "PUT" => {
+                let value = args
+                    .next()
+                    .map(|v| v.into_owned())
+                    .filter(|v| !v.is_empty())
+                    .ok_or_else(|| bad("falta el valor"))?;
+                let ttl = args.next().map(|t| parse_millis(&t)).transpose()?;
+                if ttl == Some(0) {
+                    return Err(bad("TTL 0"));
+                }
+                Self::Put { key, value, ttl }
+            }
+            "DEL" => Self::Del { key },
+            _ => return Err(bad("comando desconocido")),
+        };
+
+        match args.next() {
+            Some(extra) => Err(bad(&format!("argumento de más {extra}"))),
+            None => Ok(cmd),
+        }
+    }
+
+    pub fn key(&self) -> &str {
+        match self {
+            Self::Watch { key, .. }
+            | Self::Get { key }
+            | Self::Version { key }
+            | Self::Put { key, .. }
+            | Self::Del { key } => key,
+        }
+    }
+
+    pub fn is_write(&self) -> bool {
+        matches!(self, Self::Put { .. } | Self::Del { .. })
+    }
+
+    /// Los `WATCH` solo condicionan; no tienen resultado propio.
+    pub fn is_watch(&self) -> bool {
+        matches!(self, Self::Watch { .. })
+    }
+}
+
+impl fmt::Display for TxCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let line = match self {
+            Self::Watch { key, version } => encode_args(["WATCH", key, &version.to_string()]),
+            Self::Get { key } => encode_args(["GET", key]),
+            Self::Version { key } => encode_args(["VERSION", key]),
+            Self::Put { key, value, ttl } => {
+                let ttl = ttl.map(format_millis);
+                encode_args(["PUT", key, value].into_iter().chain(ttl.as_deref()))
+            }
+            Self::Del { key } => encode_args(["DEL", key]),
+        };
+        f.write_str(&line)
+    }
+}
+
+/// Los comandos de un payload de `MULTI`; al menos uno.
+pub fn parse_multi(payload: &str) -> Result<Vec<TxCommand>, SocketError> {
+    let commands = tokenize(payload)
+        .map(|line| TxCommand::parse(&line))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if commands.is_empty() {
+        return Err(SocketError::BadRequest(format!("{MULTI} sin comandos")));
+    }
+    Ok(commands)
+}
+
+/// Payload de `MULTI` que `parse_multi` vuelve a leer igual.
+pub fn encode_multi(commands: &[TxCommand]) -> String {
+    let lines: Vec<String> = commands.iter().map(TxCommand::to_string).collect();
+    encode_args(lines.iter().map(String::as_str))
+}
+
+/// Condición de un `PUT ... IF <condición>`, que el nodo evalúa contra la entrada vigente
+/// con la escritura ya tomada: `version=<n>` (0 = no existe), `absent` o
+/// `value==<valor>` (el valor puede ir entre comillas).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PutCondition {
+    Version(u64),
+    Absent,
+    Value(String),
+}
+
+impl PutCondition {
+    pub fn parse(condition: &str) -> Result<Self, SocketError> {
+        let bad = || SocketError::BadRequest(format!("{IF}: condición inválida {condition}"));
+
+        if condition.eq_ignore_ascii_case("absent") {
+            return Ok(Self::Absent);
+        }
+        if let Some(value) = condition.strip_prefix("value==") {
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+            return Ok(Self::Value(value.to_string()));
+        }
+        match condition.strip_prefix("version=") {
+            Some(version) => version.parse().map(Self::Version).map_err(|_| bad()),
+            None => Err(bad()),
+        }
+    }
+
+    /// `current` es el valor y la versión de la entrada, `None` si no existe o venció.
+    pub fn holds(&self, current: Option<(&str, u64)>) -> bool {
+        match self {
+            Self::Version(expected) => current.map_or(0, |(_, version)| version) == *expected,
+            Self::Absent => current.is_none(),
+            Self::Value(expected) => current.is_some_and(|(value, _)| value == expected),
+        }
+    }
+
+    /// Saca un `IF <condición>` del final de los argumentos de un `PUT`, si lo hay. Antes
+    /// tiene que quedar al menos la clave y el valor, así que un valor `IF` no se confunde.
+    pub fn take_from(args: &mut Vec<Cow<'_, str>>) -> Result<Option<Self>, SocketError> {
+        let n = args.len();
+        if n < 4 || !args[n - 2].eq_ignore_ascii_case(IF) {
+            return Ok(None);
+        }
+        let condition = Self::parse(&args[n - 1])?;
+        args.truncate(n - 2);
+        Ok(Some(condition))
+    }
+}
+
+impl fmt::Display for PutCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Version(version) => write!(f, "version={version}"),
+            Self::Absent => f.write_str("absent"),
+            Self::Value(value) => write!(f, "value=={value}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_round_trip_through_the_multi_payload() {
+        let commands = vec![
+            TxCommand::Watch {
+                key: "k 1".into(),
+                version: 3,
+            },
+            TxCommand::Get { key: "k 1".into() },
+            TxCommand::Version { key: "k".into() },
+            TxCommand::Put {
+                key: "k 1".into(),
+                value: "say \"hi\"".into(),
+                ttl: Some(30_000),
+            },
+            TxCommand::Del { key: "k".into() },
+        ];
+
+        assert_eq!(parse_multi(&encode_multi(&commands)).unwrap(), commands);
+        assert_eq!(
+            parse_multi("\"put k v 2s\"").unwrap(),
+            vec![TxCommand::Put {
+                key: "k".into(),
+                value: "v".into(),
+                ttl: Some(2_000),
+            }]
+        );
+    }
+
+    #[test]
+    fn malformed_commands_reject_the_whole_batch() {
+        for bad in [
+            "",
+            "\"GET\"",
+            "\"WATCH k\"",
+            "\"WATCH k x\"",
+            "\"PUT k\"",
+            "\"PUT k v 0\"",
+            "\"PUT k v 10d\"",
+            "\"GET k extra\"",
+            "\"INCR k\"",
+            "\"GET k\" \"SET k v\"",
+        ] {
+            assert!(parse_multi(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn put_conditions_parse_from_the_tail_of_the_arguments() {
+        let mut args: Vec<_> = tokenize("k v 30s IF \"value==\\\"a b\\\"\"").collect();
+        assert_eq!(
+            PutCondition::take_from(&mut args).unwrap(),
+            Some(PutCondition::Value("a b".into()))
+        );
+        assert_eq!(args, ["k", "v", "30s"]);
+
+        let mut args: Vec<_> = tokenize("k IF if version=3").collect();
+        assert_eq!(
+            PutCondition::take_from(&mut args).unwrap(),
+            Some(PutCondition::Version(3))
+        );
+        assert_eq!(args, ["k", "IF"]);
+
+        let mut args: Vec<_> = tokenize("k v IF").collect();
+        assert_eq!(PutCondition::take_from(&mut args).unwrap(), None);
+        for bad in ["k v IF version=x", "k v IF present"] {
+            let mut args: Vec<_> = tokenize(bad).collect();
+            assert!(PutCondition::take_from(&mut args).is_err(), "{bad}");
+        }
+
+        let round = PutCondition::Value("x".into());
+        assert_eq!(PutCondition::parse(&round.to_string()).unwrap(), round);
+    }
+
+    #[test]
+    fn put_conditions_compare_against_the_current_entry() {
+        let absent = PutCondition::Absent;
+        assert!(absent.holds(None));
+        assert!(!absent.holds(Some(("v", 1))));
+
+        assert!(PutCondition::Version(0).holds(None));
+        assert!(PutCondition::Version(2).holds(Some(("v", 2))));
+        assert!(!PutCondition::Version(2).holds(Some(("v", 3))));
+
+        let value = PutCondition::Value("v".into());
+        assert!(value.holds(Some(("v", 7))));
+        assert!(!value.holds(Some(("w", 7))));
+        assert!(!value.holds(None));
+    }
+}